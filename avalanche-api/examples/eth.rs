@@ -0,0 +1,36 @@
+use std::env::args;
+
+use log::info;
+use tokio::runtime::Runtime;
+
+use avalanche_api::eth;
+
+/// cargo run --example eth -- [HTTP RPC ENDPOINT] C
+/// cargo run --example eth -- [HTTP RPC ENDPOINT] [SUBNET-EVM BLOCKCHAIN ID]
+fn main() {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, "info"),
+    );
+
+    let rt = Runtime::new().unwrap();
+
+    let url = args().nth(1).expect("no url given");
+    let chain = args().nth(2).unwrap_or_else(|| String::from("C"));
+    let path = eth::rpc_path(&chain);
+
+    let resp = rt
+        .block_on(eth::chain_id(&url, &path))
+        .expect("failed to get chain ID");
+    info!("chain ID: {}", resp.result);
+
+    let resp = rt
+        .block_on(eth::block_number(&url, &path))
+        .expect("failed to get block number");
+    info!("block number: {}", resp.result);
+
+    let resp = rt
+        .block_on(eth::fee_history(&url, &path, 10, &[25.0, 75.0]))
+        .expect("failed to get fee history");
+    info!("fee history: {:?}", resp.result);
+}
@@ -43,6 +43,11 @@ fn main() {
         resp.result.unwrap().blockchain_id
     );
 
+    let aliaser = rt
+        .block_on(info::get_aliaser(&url, &info::PRIMARY_CHAIN_ALIASES))
+        .expect("failed get_aliaser");
+    info!("aliaser: {:?}", aliaser);
+
     let resp = rt
         .block_on(info::get_blockchain_id(&url, "C"))
         .expect("failed get_blockchain_id");
@@ -65,10 +70,15 @@ fn main() {
     let resp = rt.block_on(info::get_vms(&url)).expect("failed get_vms");
     info!("get_vms response: {:?}", resp);
 
-    let resp = rt
-        .block_on(info::get_bootstrapped(&url))
-        .expect("failed get_bootstrapped");
-    info!("get_bootstrapped response: {:?}", resp);
+    for chain_alias in ["X", "P", "C"] {
+        let resp = rt
+            .block_on(info::is_bootstrapped(&url, chain_alias))
+            .expect("failed is_bootstrapped");
+        info!("is_bootstrapped for {} response: {:?}", chain_alias, resp);
+    }
+
+    let resp = rt.block_on(info::peers(&url)).expect("failed peers");
+    info!("peers response: {:?}", resp);
 
     let resp = rt
         .block_on(info::get_tx_fee(&url))
@@ -38,4 +38,14 @@ fn main() {
         .block_on(p::get_current_validators(&url))
         .expect("failed to get current validators");
     info!("get_current_validators response: {:?}", resp);
+
+    let resp = rt
+        .block_on(p::get_pending_validators(&url))
+        .expect("failed to get pending validators");
+    info!("get_pending_validators response: {:?}", resp);
+
+    let resp = rt
+        .block_on(p::sample_validators(&url, 1))
+        .expect("failed to sample validators");
+    info!("sample_validators response: {:?}", resp);
 }
@@ -26,4 +26,14 @@ fn main() {
         .block_on(x::get_asset_description(&url, "AVAX"))
         .expect("failed get_asset_description");
     info!("get_asset_description response: {:?}", resp);
+
+    let resp = rt
+        .block_on(x::get_all_balances(&url, &xaddr))
+        .expect("failed get_all_balances");
+    info!("get_all_balances response: {:?}", resp);
+
+    let resp = rt
+        .block_on(x::get_utxos(&url, &xaddr))
+        .expect("failed get_utxos");
+    info!("get_utxos response: {:?}", resp);
 }
@@ -0,0 +1,146 @@
+use std::{collections::HashMap, io, string::String};
+
+use log::info;
+use serde::de::DeserializeOwned;
+
+use avalanche_types::{
+    api::{admin, jsonrpc},
+    errors,
+};
+use utils::http;
+
+const ADMIN_PATH: &str = "/ext/admin";
+
+/// Assigns the alias to the API endpoint (e.g., "/ext/bc/X").
+/// Requires "--api-admin-enabled", only reachable from the node itself.
+/// ref. https://docs.avax.network/build/avalanchego-apis/admin#adminalias
+pub async fn alias(url: &str, endpoint: &str, alias: &str) -> io::Result<admin::EmptyResponse> {
+    info!("aliasing {} to '{}' via {}", endpoint, alias, url);
+    let mut params = HashMap::new();
+    params.insert(String::from("endpoint"), endpoint.to_string());
+    params.insert(String::from("alias"), alias.to_string());
+    let resp: admin::EmptyResponse = post(url, "admin.alias", params).await?;
+    check_error("admin.alias", &resp.error)?;
+    Ok(resp)
+}
+
+/// Assigns the alias to the blockchain ID.
+/// ref. https://docs.avax.network/build/avalanchego-apis/admin#adminaliaschain
+pub async fn alias_chain(url: &str, chain: &str, alias: &str) -> io::Result<admin::EmptyResponse> {
+    info!("aliasing chain {} to '{}' via {}", chain, alias, url);
+    let mut params = HashMap::new();
+    params.insert(String::from("chain"), chain.to_string());
+    params.insert(String::from("alias"), alias.to_string());
+    let resp: admin::EmptyResponse = post(url, "admin.aliasChain", params).await?;
+    check_error("admin.aliasChain", &resp.error)?;
+    Ok(resp)
+}
+
+/// Starts the CPU profile, written to "cpu.profile"
+/// in "--profile-dir" by "stop_cpu_profiler".
+/// ref. https://docs.avax.network/build/avalanchego-apis/admin#adminstartcpuprofiler
+pub async fn start_cpu_profiler(url: &str) -> io::Result<admin::EmptyResponse> {
+    info!("starting CPU profiler via {}", url);
+    let resp: admin::EmptyResponse = post(url, "admin.startCPUProfiler", HashMap::new()).await?;
+    check_error("admin.startCPUProfiler", &resp.error)?;
+    Ok(resp)
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/admin#adminstopcpuprofiler
+pub async fn stop_cpu_profiler(url: &str) -> io::Result<admin::EmptyResponse> {
+    info!("stopping CPU profiler via {}", url);
+    let resp: admin::EmptyResponse = post(url, "admin.stopCPUProfiler", HashMap::new()).await?;
+    check_error("admin.stopCPUProfiler", &resp.error)?;
+    Ok(resp)
+}
+
+/// Writes "mem.profile" to "--profile-dir".
+/// ref. https://docs.avax.network/build/avalanchego-apis/admin#adminmemoryprofile
+pub async fn memory_profile(url: &str) -> io::Result<admin::EmptyResponse> {
+    info!("writing memory profile via {}", url);
+    let resp: admin::EmptyResponse = post(url, "admin.memoryProfile", HashMap::new()).await?;
+    check_error("admin.memoryProfile", &resp.error)?;
+    Ok(resp)
+}
+
+/// Writes "lock.profile" to "--profile-dir".
+/// ref. https://docs.avax.network/build/avalanchego-apis/admin#adminlockprofile
+pub async fn lock_profile(url: &str) -> io::Result<admin::EmptyResponse> {
+    info!("writing lock profile via {}", url);
+    let resp: admin::EmptyResponse = post(url, "admin.lockProfile", HashMap::new()).await?;
+    check_error("admin.lockProfile", &resp.error)?;
+    Ok(resp)
+}
+
+/// Sets the log and display levels of the logger,
+/// or of all loggers if "logger_name" is "None".
+/// The levels revert to the flags on the next restart.
+/// ref. https://docs.avax.network/build/avalanchego-apis/admin#adminsetloggerlevel
+pub async fn set_logger_level(
+    url: &str,
+    logger_name: Option<&str>,
+    log_level: Option<&str>,
+    display_level: Option<&str>,
+) -> io::Result<admin::EmptyResponse> {
+    info!(
+        "setting logger {:?} level to {:?} (display {:?}) via {}",
+        logger_name, log_level, display_level, url
+    );
+    let mut params = HashMap::new();
+    for (k, v) in [
+        ("loggerName", logger_name),
+        ("logLevel", log_level),
+        ("displayLevel", display_level),
+    ] {
+        if let Some(v) = v {
+            params.insert(k.to_string(), v.to_string());
+        }
+    }
+    let resp: admin::EmptyResponse = post(url, "admin.setLoggerLevel", params).await?;
+    check_error("admin.setLoggerLevel", &resp.error)?;
+    Ok(resp)
+}
+
+/// Returns the levels of the logger, or of all loggers if "logger_name" is "None".
+/// ref. https://docs.avax.network/build/avalanchego-apis/admin#admingetloggerlevel
+pub async fn get_logger_level(
+    url: &str,
+    logger_name: Option<&str>,
+) -> io::Result<admin::GetLoggerLevelResponse> {
+    info!("getting logger {:?} level via {}", logger_name, url);
+    let mut params = HashMap::new();
+    if let Some(v) = logger_name {
+        params.insert(String::from("loggerName"), v.to_string());
+    }
+    let resp: admin::GetLoggerLevelResponse = post(url, "admin.getLoggerLevel", params).await?;
+    check_error("admin.getLoggerLevel", &resp.error)?;
+    Ok(resp)
+}
+
+/// The admin API methods either change the node state (e.g., aliases)
+/// or write the profile files, so never retried on the timeouts.
+async fn post<T: DeserializeOwned>(
+    url: &str,
+    method: &str,
+    params: HashMap<String, String>,
+) -> io::Result<T> {
+    let mut data = jsonrpc::Data::default();
+    data.method = String::from(method);
+    data.params = Some(params);
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, ADMIN_PATH, &d, http::Idempotency::NonIdempotent).await?;
+    serde_json::from_slice(&rb).map_err(|e| {
+        errors::Error::Decode {
+            message: format!("{} response {}", method, e),
+        }
+        .into()
+    })
+}
+
+fn check_error(method: &str, error: &Option<jsonrpc::ResponseError>) -> io::Result<()> {
+    match error {
+        Some(e) => Err(e.to_io_error(method)),
+        None => Ok(()),
+    }
+}
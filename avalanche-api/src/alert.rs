@@ -0,0 +1,374 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, Error, ErrorKind},
+    str::FromStr,
+};
+
+use aws_sdk_cloudwatch::model::{Dimension, MetricDatum, StandardUnit};
+use aws_smithy_types::DateTime as SmithyDateTime;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use utils::prometheus;
+
+/// CloudWatch metric name of the alert state, 1 if firing and 0 otherwise,
+/// with the "rule" and "severity" dimensions.
+pub const CW_METRIC_NAME: &str = "alert_firing";
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Default for Severity {
+    fn default() -> Self {
+        Severity::Warning
+    }
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// User-defined threshold rule on the node metrics.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Rule {
+    pub name: String,
+    /// Compares the metric against the threshold
+    /// (e.g., "avalanche_network_peers < 5", or
+    /// `avalanche_db_get_count{db="C"} >= 1e6` to match the labels).
+    pub expr: String,
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+impl Rule {
+    pub fn validate(&self) -> io::Result<()> {
+        if self.name.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "empty rule name"));
+        }
+        Expr::from_str(&self.expr).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid rule '{}' ({})", self.name, e),
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Returns the alerts for the samples in the scrape that satisfy the expression.
+    /// The metrics not found in the scrape never fire.
+    pub fn evaluate(
+        &self,
+        scrape: &prometheus::Scrape,
+        ts: DateTime<Utc>,
+    ) -> io::Result<Vec<Alert>> {
+        let expr = Expr::from_str(&self.expr)?;
+        let mut alerts = Vec::new();
+        for m in scrape.metrics.iter() {
+            let value = match m.value {
+                prometheus::Value::Counter(v)
+                | prometheus::Value::Gauge(v)
+                | prometheus::Value::Untyped(v) => v,
+                // no single value to compare
+                prometheus::Value::Histogram(_) | prometheus::Value::Summary(_) => continue,
+            };
+            if m.metric != expr.metric || !expr.matches_labels(m.labels.as_ref()) {
+                continue;
+            }
+            if expr.op.compare(value, expr.threshold) {
+                alerts.push(Alert {
+                    rule: self.name.clone(),
+                    severity: self.severity,
+                    expr: self.expr.clone(),
+                    metric: m.metric.clone(),
+                    labels: m.labels.as_ref().map(|l| (**l).clone()).unwrap_or_default(),
+                    value,
+                    ts,
+                });
+            }
+        }
+        Ok(alerts)
+    }
+}
+
+/// Evaluates all rules against the same scrape.
+pub fn evaluate(rules: &[Rule], scrape: &prometheus::Scrape) -> io::Result<Vec<Alert>> {
+    let ts = Utc::now();
+    let mut alerts = Vec::new();
+    for rule in rules.iter() {
+        alerts.extend(rule.evaluate(scrape, ts)?);
+    }
+    Ok(alerts)
+}
+
+/// Returns the alert state of every rule, so that the CloudWatch alarms
+/// on the metric also resolve once the rule stops firing.
+pub fn to_cw_metric_data(rules: &[Rule], alerts: &[Alert], ts: DateTime<Utc>) -> Vec<MetricDatum> {
+    let ts = SmithyDateTime::from_nanos(ts.timestamp_nanos() as i128)
+        .expect("failed to convert DateTime");
+    rules
+        .iter()
+        .map(|rule| {
+            let firing = alerts.iter().any(|a| a.rule == rule.name);
+            MetricDatum::builder()
+                .metric_name(CW_METRIC_NAME)
+                .dimensions(Dimension::builder().name("rule").value(&rule.name).build())
+                .dimensions(
+                    Dimension::builder()
+                        .name("severity")
+                        .value(rule.severity.as_str())
+                        .build(),
+                )
+                .value(if firing { 1.0 } else { 0.0 })
+                .unit(StandardUnit::Count)
+                .timestamp(ts)
+                .build()
+        })
+        .collect()
+}
+
+/// Sample that satisfies the rule expression.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Alert {
+    pub rule: String,
+    pub severity: Severity,
+    pub expr: String,
+    pub metric: String,
+    pub labels: HashMap<String, String>,
+    pub value: f64,
+    pub ts: DateTime<Utc>,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {}: {} = {} ({})",
+            self.severity, self.rule, self.metric, self.value, self.expr
+        )?;
+        if !self.labels.is_empty() {
+            let mut labels: Vec<String> = self
+                .labels
+                .iter()
+                .map(|(k, v)| format!("{}=\"{}\"", k, v))
+                .collect();
+            labels.sort();
+            write!(f, " {{{}}}", labels.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    /// Longer operators first, so that "<=" is not parsed as "<".
+    const ALL: [(&'static str, Op); 6] = [
+        ("<=", Op::Le),
+        (">=", Op::Ge),
+        ("==", Op::Eq),
+        ("!=", Op::Ne),
+        ("<", Op::Lt),
+        (">", Op::Gt),
+    ];
+
+    pub fn compare(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Op::Lt => value < threshold,
+            Op::Le => value <= threshold,
+            Op::Gt => value > threshold,
+            Op::Ge => value >= threshold,
+            Op::Eq => (value - threshold).abs() < f64::EPSILON,
+            Op::Ne => (value - threshold).abs() >= f64::EPSILON,
+        }
+    }
+}
+
+/// Parsed rule expression of "[METRIC]{[LABEL]="[VALUE]",...} [OP] [THRESHOLD]".
+#[derive(Debug, PartialEq, Clone)]
+pub struct Expr {
+    pub metric: String,
+    pub labels: HashMap<String, String>,
+    pub op: Op,
+    pub threshold: f64,
+}
+
+impl Expr {
+    fn matches_labels(&self, labels: Option<&prometheus::Labels>) -> bool {
+        self.labels
+            .iter()
+            .all(|(k, v)| labels.and_then(|l| l.get(k)) == Some(v.as_str()))
+    }
+}
+
+impl FromStr for Expr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        // the label values may contain the operator characters
+        let (selector, rest) = match s.find('{') {
+            Some(_) => {
+                let end = s.find('}').ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("unclosed labels in '{}'", s),
+                    )
+                })?;
+                s.split_at(end + 1)
+            }
+            None => {
+                let end = s.find(|c| "<>=!".contains(c)).ok_or_else(|| {
+                    Error::new(ErrorKind::InvalidInput, format!("no operator in '{}'", s))
+                })?;
+                s.split_at(end)
+            }
+        };
+
+        let (metric, labels) = match selector.trim().split_once('{') {
+            Some((metric, labels)) => {
+                let mut m = HashMap::new();
+                for kv in labels.trim_end_matches('}').split(',') {
+                    if kv.trim().is_empty() {
+                        continue;
+                    }
+                    let (k, v) = kv.split_once('=').ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("invalid label '{}' in '{}'", kv, s),
+                        )
+                    })?;
+                    m.insert(k.trim().to_string(), v.trim().trim_matches('"').to_string());
+                }
+                (metric.trim(), m)
+            }
+            None => (selector.trim(), HashMap::new()),
+        };
+        if metric.is_empty()
+            || !metric
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid metric name '{}'", metric),
+            ));
+        }
+
+        let rest = rest.trim();
+        let (op_str, op) = Op::ALL
+            .iter()
+            .find(|(p, _)| rest.starts_with(p))
+            .ok_or_else(|| {
+                Error::new(ErrorKind::InvalidInput, format!("no operator in '{}'", s))
+            })?;
+        let threshold = rest[op_str.len()..].trim();
+        let threshold = threshold.parse::<f64>().map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid threshold '{}' ({})", threshold, e),
+            )
+        })?;
+
+        Ok(Self {
+            metric: metric.to_string(),
+            labels,
+            op: *op,
+            threshold,
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-api --lib -- alert::test_rules --exact --show-output
+#[test]
+fn test_rules() {
+    let expr = Expr::from_str("avalanche_network_peers < 5").unwrap();
+    assert_eq!(expr.metric, "avalanche_network_peers");
+    assert_eq!(expr.op, Op::Lt);
+    assert_eq!(expr.threshold, 5.0);
+
+    let expr = Expr::from_str(r#"avalanche_db_get_count{db="C"}>=1e6"#).unwrap();
+    assert_eq!(expr.labels.get("db"), Some(&String::from("C")));
+    assert_eq!(expr.op, Op::Ge);
+    assert_eq!(expr.threshold, 1_000_000.0);
+
+    assert!(Expr::from_str("avalanche_network_peers").is_err());
+    assert!(Expr::from_str("avalanche_network_peers < five").is_err());
+    assert!(Expr::from_str(r#"x{db="C" > 1"#).is_err());
+    assert!(Expr::from_str(" < 1").is_err());
+
+    let scrape = prometheus::Scrape::from_bytes(
+        br#"# TYPE avalanche_network_peers gauge
+avalanche_network_peers 3
+# TYPE avalanche_db_get_count counter
+avalanche_db_get_count{db="C"} 2000000
+avalanche_db_get_count{db="X"} 10
+"#,
+    )
+    .unwrap();
+    let rules = vec![
+        Rule {
+            name: String::from("low-peers"),
+            expr: String::from("avalanche_network_peers < 5"),
+            severity: Severity::Critical,
+        },
+        Rule {
+            name: String::from("db-reads"),
+            expr: String::from(r#"avalanche_db_get_count{db="C"} >= 1e6"#),
+            severity: Severity::Info,
+        },
+        Rule {
+            name: String::from("db-reads-x"),
+            expr: String::from(r#"avalanche_db_get_count{db="X"} >= 1e6"#),
+            severity: Severity::Info,
+        },
+        Rule {
+            name: String::from("missing"),
+            expr: String::from("avalanche_not_found > 0"),
+            severity: Severity::Warning,
+        },
+    ];
+    for r in rules.iter() {
+        r.validate().unwrap();
+    }
+
+    let alerts = evaluate(&rules, &scrape).unwrap();
+    assert_eq!(alerts.len(), 2);
+    assert_eq!(alerts[0].rule, "low-peers");
+    assert_eq!(alerts[0].value, 3.0);
+    assert_eq!(alerts[1].labels.get("db"), Some(&String::from("C")));
+    log::info!("{}", alerts[1]);
+
+    let data = to_cw_metric_data(&rules, &alerts, Utc::now());
+    assert_eq!(data.len(), 4);
+    assert_eq!(data[0].value(), Some(1.0));
+    assert_eq!(data[2].value(), Some(0.0));
+}
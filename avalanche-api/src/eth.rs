@@ -0,0 +1,173 @@
+use std::{io, string::String};
+
+use log::info;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use avalanche_types::{
+    api::{eth, jsonrpc},
+    errors,
+};
+use utils::http;
+
+/// C-chain JSON-RPC path.
+pub const C_CHAIN_RPC_PATH: &str = "/ext/bc/C/rpc";
+
+/// Returns the JSON-RPC path of the EVM chain by its blockchain ID or alias
+/// (e.g., subnet-evm "/ext/bc/[BLOCKCHAIN_ID]/rpc").
+pub fn rpc_path(chain: &str) -> String {
+    format!("/ext/bc/{}/rpc", chain)
+}
+
+/// e.g., "eth_chainId" on "http://[ADDR]:9650" and "/ext/bc/C/rpc" path.
+/// ref. https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_chainid
+pub async fn chain_id(url: &str, path: &str) -> io::Result<eth::ChainIdResponse> {
+    info!("getting chain ID via {}{}", url, path);
+    let resp: eth::ChainIdResponse = post(url, path, "eth_chainId", Vec::new()).await?;
+    check_error("eth_chainId", &resp.error)?;
+    Ok(resp)
+}
+
+/// ref. https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_blocknumber
+pub async fn block_number(url: &str, path: &str) -> io::Result<eth::BlockNumberResponse> {
+    info!("getting block number via {}{}", url, path);
+    let resp: eth::BlockNumberResponse = post(url, path, "eth_blockNumber", Vec::new()).await?;
+    check_error("eth_blockNumber", &resp.error)?;
+    Ok(resp)
+}
+
+/// Returns the balance at the latest block.
+/// ref. https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_getbalance
+pub async fn get_balance(
+    url: &str,
+    path: &str,
+    eth_addr: &str,
+) -> io::Result<eth::GetBalanceResponse> {
+    info!("getting balances for {} via {}{}", eth_addr, url, path);
+    let resp: eth::GetBalanceResponse = post(
+        url,
+        path,
+        "eth_getBalance",
+        vec![json!(eth_addr), json!("latest")],
+    )
+    .await?;
+    check_error("eth_getBalance", &resp.error)?;
+    Ok(resp)
+}
+
+/// Returns the nonce of the next transaction from the address,
+/// at the block "tag" (e.g., "latest", "pending").
+/// ref. https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_gettransactioncount
+pub async fn get_transaction_count(
+    url: &str,
+    path: &str,
+    eth_addr: &str,
+    tag: &str,
+) -> io::Result<eth::GetTransactionCountResponse> {
+    info!(
+        "getting transaction count for {} via {}{}",
+        eth_addr, url, path
+    );
+    let resp: eth::GetTransactionCountResponse = post(
+        url,
+        path,
+        "eth_getTransactionCount",
+        vec![json!(eth_addr), json!(tag)],
+    )
+    .await?;
+    check_error("eth_getTransactionCount", &resp.error)?;
+    Ok(resp)
+}
+
+/// Issues the "0x"-prefixed hex-encoded signed transaction,
+/// and returns its transaction hash.
+/// ref. https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_sendrawtransaction
+pub async fn send_raw_transaction(
+    url: &str,
+    path: &str,
+    signed_tx: &str,
+) -> io::Result<eth::SendRawTransactionResponse> {
+    info!("sending raw transaction via {}{}", url, path);
+    let resp: eth::SendRawTransactionResponse =
+        post(url, path, "eth_sendRawTransaction", vec![json!(signed_tx)]).await?;
+    check_error("eth_sendRawTransaction", &resp.error)?;
+    Ok(resp)
+}
+
+/// The result is "None" if the transaction is not yet accepted.
+/// ref. https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_gettransactionreceipt
+pub async fn get_transaction_receipt(
+    url: &str,
+    path: &str,
+    tx_hash: &str,
+) -> io::Result<eth::GetTransactionReceiptResponse> {
+    info!(
+        "getting transaction receipt for {} via {}{}",
+        tx_hash, url, path
+    );
+    let resp: eth::GetTransactionReceiptResponse =
+        post(url, path, "eth_getTransactionReceipt", vec![json!(tx_hash)]).await?;
+    check_error("eth_getTransactionReceipt", &resp.error)?;
+    Ok(resp)
+}
+
+/// Returns the fee history of the last "block_count" blocks,
+/// with the priority fees at each of the "reward_percentiles" (e.g., [25.0, 75.0]).
+/// ref. https://docs.avax.network/apis/avalanchego/apis/c-chain#eth_feehistory
+pub async fn fee_history(
+    url: &str,
+    path: &str,
+    block_count: u64,
+    reward_percentiles: &[f64],
+) -> io::Result<eth::FeeHistoryResponse> {
+    info!(
+        "getting fee history of {} blocks via {}{}",
+        block_count, url, path
+    );
+    let resp: eth::FeeHistoryResponse = post(
+        url,
+        path,
+        "eth_feeHistory",
+        vec![
+            json!(format!("{:#x}", block_count)),
+            json!("latest"),
+            json!(reward_percentiles),
+        ],
+    )
+    .await?;
+    check_error("eth_feeHistory", &resp.error)?;
+    Ok(resp)
+}
+
+async fn post<T: DeserializeOwned>(
+    url: &str,
+    path: &str,
+    method: &str,
+    params: Vec<serde_json::Value>,
+) -> io::Result<T> {
+    let mut data = jsonrpc::DataWithParamsValues::default();
+    data.method = String::from(method);
+    data.params = Some(params);
+
+    // only the transaction submission must not be sent twice on the timeout
+    let idempotency = if method == "eth_sendRawTransaction" {
+        http::Idempotency::NonIdempotent
+    } else {
+        http::Idempotency::Idempotent
+    };
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, path, &d, idempotency).await?;
+    serde_json::from_slice(&rb).map_err(|e| {
+        errors::Error::Decode {
+            message: format!("{} response {}", method, e),
+        }
+        .into()
+    })
+}
+
+fn check_error(method: &str, error: &Option<jsonrpc::ResponseError>) -> io::Result<()> {
+    match error {
+        Some(e) => Err(e.to_io_error(method)),
+        None => Ok(()),
+    }
+}
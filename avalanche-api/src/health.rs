@@ -2,11 +2,13 @@ use std::{
     io::{self, Error, ErrorKind},
     string::String,
     sync::Arc,
+    time::{Duration, Instant},
 };
 
-use log::info;
+use log::{info, warn};
+use tokio::time::{sleep, timeout};
 
-use avalanche_types::api::health;
+use avalanche_types::{api::health, errors};
 use utils::http;
 
 /// "If a single piece of data must be accessible from more than one task
@@ -27,10 +29,10 @@ pub async fn check(url: Arc<String>, liveness: bool) -> io::Result<health::Respo
     let resp: health::Response = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     Ok(resp)
@@ -42,3 +44,62 @@ pub async fn spawn_check(u: &str, liveness: bool) -> io::Result<health::Response
         .await
         .expect("failed spawn await")
 }
+
+/// Fetches the full health report from "ext/health".
+/// ref. https://docs.avax.network/build/avalanchego-apis/health#healthhealth
+pub async fn health(url: &str) -> io::Result<health::Response> {
+    check(Arc::new(url.to_string()), false).await
+}
+
+/// Fetches the liveness report from "ext/health/liveness".
+/// ref. https://docs.avax.network/build/avalanchego-apis/health
+pub async fn liveness(url: &str) -> io::Result<health::Response> {
+    check(Arc::new(url.to_string()), true).await
+}
+
+/// Polls "ext/health" every "interval" until the node reports healthy,
+/// or fails once "timeout_dur" elapses with the last failing checks.
+pub async fn poll_ready(
+    url: &str,
+    timeout_dur: Duration,
+    interval: Duration,
+) -> io::Result<health::Response> {
+    info!(
+        "polling health for {} (timeout {:?}, interval {:?})",
+        url, timeout_dur, interval
+    );
+
+    let start = Instant::now();
+    let mut last_failure = String::from("no health check attempted");
+    loop {
+        let elapsed = start.elapsed();
+        if elapsed >= timeout_dur {
+            return Err(Error::new(
+                ErrorKind::TimedOut,
+                format!("{} not healthy after {:?} ({})", url, elapsed, last_failure),
+            ));
+        }
+
+        // a single check must not outlive the deadline
+        let remaining = timeout_dur - elapsed;
+        match timeout(remaining, health(url)).await {
+            Ok(Ok(res)) => {
+                if res.healthy.unwrap_or(false) {
+                    info!("{} is healthy after {:?}", url, start.elapsed());
+                    return Ok(res);
+                }
+                last_failure = format!("failed checks {:?}", res.failed_checks());
+            }
+            Ok(Err(e)) => {
+                last_failure = format!("failed health check {}", e);
+            }
+            Err(_) => {
+                last_failure = String::from("health check timed out");
+            }
+        }
+        warn!("{} not yet healthy ({})", url, last_failure);
+
+        let remaining = timeout_dur.saturating_sub(start.elapsed());
+        sleep(interval.min(remaining)).await;
+    }
+}
@@ -0,0 +1,96 @@
+use std::{collections::HashMap, io, string::String};
+
+use log::info;
+use serde::de::DeserializeOwned;
+
+use avalanche_types::{
+    api::{index, jsonrpc},
+    errors, ids,
+};
+use utils::http;
+
+/// e.g., "index.getLastAccepted" on "http://[ADDR]:9650" and "/ext/index/X/tx" path.
+/// ref. https://docs.avax.network/build/avalanchego-apis/index-api#indexgetlastaccepted
+pub async fn get_last_accepted(
+    url: &str,
+    index: &index::Index,
+) -> io::Result<index::GetLastAcceptedResponse> {
+    info!("getting last accepted via {}{}", url, index.path());
+    let resp: index::GetLastAcceptedResponse =
+        post(url, index, "index.getLastAccepted", HashMap::new()).await?;
+    check_error("index.getLastAccepted", &resp.error)?;
+    Ok(resp)
+}
+
+/// Returns the containers from "start_index" in the order of acceptance,
+/// at most "index::MAX_FETCHED_BY_RANGE".
+/// ref. https://docs.avax.network/build/avalanchego-apis/index-api#indexgetcontainerrange
+pub async fn get_container_range(
+    url: &str,
+    index: &index::Index,
+    start_index: u64,
+    num_to_fetch: u64,
+) -> io::Result<index::GetContainerRangeResponse> {
+    info!(
+        "getting {} container(s) from {} via {}{}",
+        num_to_fetch,
+        start_index,
+        url,
+        index.path()
+    );
+    let mut params = HashMap::new();
+    params.insert(String::from("startIndex"), start_index.to_string());
+    params.insert(String::from("numToFetch"), num_to_fetch.to_string());
+    let resp: index::GetContainerRangeResponse =
+        post(url, index, "index.getContainerRange", params).await?;
+    check_error("index.getContainerRange", &resp.error)?;
+    Ok(resp)
+}
+
+/// Returns the order of acceptance of the container.
+/// ref. https://docs.avax.network/build/avalanchego-apis/index-api#indexgetindex
+pub async fn get_index(
+    url: &str,
+    index: &index::Index,
+    container_id: &ids::Id,
+) -> io::Result<index::GetIndexResponse> {
+    info!(
+        "getting index of {} via {}{}",
+        container_id,
+        url,
+        index.path()
+    );
+    let mut params = HashMap::new();
+    params.insert(String::from("id"), container_id.to_string());
+    let resp: index::GetIndexResponse = post(url, index, "index.getIndex", params).await?;
+    check_error("index.getIndex", &resp.error)?;
+    Ok(resp)
+}
+
+async fn post<T: DeserializeOwned>(
+    url: &str,
+    index: &index::Index,
+    method: &str,
+    mut params: HashMap<String, String>,
+) -> io::Result<T> {
+    let mut data = jsonrpc::Data::default();
+    data.method = String::from(method);
+    params.insert(String::from("encoding"), String::from("hex"));
+    data.params = Some(params);
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, index.path(), &d, http::Idempotency::Idempotent).await?;
+    serde_json::from_slice(&rb).map_err(|e| {
+        errors::Error::Decode {
+            message: format!("{} response {}", method, e),
+        }
+        .into()
+    })
+}
+
+fn check_error(method: &str, error: &Option<jsonrpc::ResponseError>) -> io::Result<()> {
+    match error {
+        Some(e) => Err(e.to_io_error(method)),
+        None => Ok(()),
+    }
+}
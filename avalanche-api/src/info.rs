@@ -6,9 +6,15 @@ use std::{
 
 use log::info;
 
-use avalanche_types::api::{info, jsonrpc};
+use avalanche_types::{
+    api::{info, jsonrpc},
+    errors, ids,
+};
 use utils::http;
 
+/// Aliases of the primary network chains.
+pub const PRIMARY_CHAIN_ALIASES: [&str; 3] = ["X", "P", "C"];
+
 /// e.g., "info.getNetworkName".
 /// ref. https://docs.avax.network/build/avalanchego-apis/info/#infogetnetworkname
 pub async fn get_network_name(url: &str) -> io::Result<info::GetNetworkNameResponse> {
@@ -18,14 +24,14 @@ pub async fn get_network_name(url: &str) -> io::Result<info::GetNetworkNameRespo
     data.method = String::from("info.getNetworkName");
 
     let d = data.encode_json()?;
-    let rb = http::post_non_tls(url, "ext/info", &d).await?;
+    let rb = http::post_json(url, "ext/info", &d, http::Idempotency::Idempotent).await?;
     let resp: info::GetNetworkNameResponse = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     Ok(resp)
@@ -40,14 +46,14 @@ pub async fn get_network_id(url: &str) -> io::Result<info::GetNetworkIdResponse>
     data.method = String::from("info.getNetworkID");
 
     let d = data.encode_json()?;
-    let rb = http::post_non_tls(url, "ext/info", &d).await?;
+    let rb = http::post_json(url, "ext/info", &d, http::Idempotency::Idempotent).await?;
     let resp: info::RawGetNetworkIdResponse = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     let converted = resp.convert()?;
@@ -70,20 +76,41 @@ pub async fn get_blockchain_id(
     data.params = Some(params);
 
     let d = data.encode_json()?;
-    let rb = http::post_non_tls(url, "ext/info", &d).await?;
+    let rb = http::post_json(url, "ext/info", &d, http::Idempotency::Idempotent).await?;
     let resp: info::RawGetBlockchainIdResponse = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     let converted = resp.convert()?;
     Ok(converted)
 }
 
+/// Builds the aliaser with the blockchain ID of each chain alias
+/// (e.g., "X", "P", "C", or the custom chain aliases of the node),
+/// in the order of the aliases so that the first one is the primary alias.
+pub async fn get_aliaser(url: &str, chain_aliases: &[&str]) -> io::Result<ids::Aliaser> {
+    let mut aliaser = ids::Aliaser::new();
+    for alias in chain_aliases.iter() {
+        let blockchain_id = get_blockchain_id(url, alias)
+            .await?
+            .result
+            .map(|r| r.blockchain_id)
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!("no blockchain ID for alias '{}' from {}", alias, url),
+                )
+            })?;
+        aliaser.alias(blockchain_id, alias)?;
+    }
+    Ok(aliaser)
+}
+
 /// e.g., "info.getNodeID".
 /// ref. https://docs.avax.network/build/avalanchego-apis/info/#infogetnodeid
 pub async fn get_node_id(url: &str) -> io::Result<info::GetNodeIdResponse> {
@@ -93,14 +120,14 @@ pub async fn get_node_id(url: &str) -> io::Result<info::GetNodeIdResponse> {
     data.method = String::from("info.getNodeID");
 
     let d = data.encode_json()?;
-    let rb = http::post_non_tls(url, "ext/info", &d).await?;
+    let rb = http::post_json(url, "ext/info", &d, http::Idempotency::Idempotent).await?;
     let resp: info::RawGetNodeIdResponse = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     let converted = resp.convert()?;
@@ -117,14 +144,14 @@ pub async fn get_node_version(url: &str) -> io::Result<info::GetNodeVersionRespo
     data.method = String::from("info.getNodeVersion");
 
     let d = data.encode_json()?;
-    let rb = http::post_non_tls(url, "ext/info", &d).await?;
+    let rb = http::post_json(url, "ext/info", &d, http::Idempotency::Idempotent).await?;
     let resp: info::GetNodeVersionResponse = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     Ok(resp)
@@ -139,14 +166,14 @@ pub async fn get_vms(url: &str) -> io::Result<info::GetVmsResponse> {
     data.method = String::from("info.getVMs");
 
     let d = data.encode_json()?;
-    let rb = http::post_non_tls(url, "ext/info", &d).await?;
+    let rb = http::post_json(url, "ext/info", &d, http::Idempotency::Idempotent).await?;
     let resp: info::GetVmsResponse = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     Ok(resp)
@@ -154,26 +181,56 @@ pub async fn get_vms(url: &str) -> io::Result<info::GetVmsResponse> {
 
 /// e.g., "info.isBootstrapped".
 /// ref. https://docs.avax.network/build/avalanchego-apis/info/#infoisbootstrapped
-pub async fn get_bootstrapped(url: &str) -> io::Result<info::GetBootstrappedResponse> {
-    info!("getting bootstrapped for {}", url);
+pub async fn is_bootstrapped(
+    url: &str,
+    chain_alias: &str,
+) -> io::Result<info::GetBootstrappedResponse> {
+    info!("checking bootstrapped for {} and {}", url, chain_alias);
 
-    let mut data = jsonrpc::DataWithParamsArray::default();
+    let mut data = jsonrpc::Data::default();
     data.method = String::from("info.isBootstrapped");
 
+    let mut params = HashMap::new();
+    params.insert(String::from("chain"), String::from(chain_alias));
+    data.params = Some(params);
+
     let d = data.encode_json()?;
-    let rb = http::post_non_tls(url, "ext/info", &d).await?;
+    let rb = http::post_json(url, "ext/info", &d, http::Idempotency::Idempotent).await?;
     let resp: info::GetBootstrappedResponse = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     Ok(resp)
 }
 
+/// e.g., "info.peers".
+/// ref. https://docs.avax.network/build/avalanchego-apis/info/#infopeers
+pub async fn peers(url: &str) -> io::Result<info::PeersResponse> {
+    info!("getting peers for {}", url);
+
+    let mut data = jsonrpc::DataWithParamsArray::default();
+    data.method = String::from("info.peers");
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, "ext/info", &d, http::Idempotency::Idempotent).await?;
+    let resp: info::RawPeersResponse = match serde_json::from_slice(&rb) {
+        Ok(p) => p,
+        Err(e) => {
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
+        }
+    };
+    let converted = resp.convert()?;
+    Ok(converted)
+}
+
 /// e.g., "info.getTxFee".
 /// ref. https://docs.avax.network/build/avalanchego-apis/info/#infogettxfee
 pub async fn get_tx_fee(url: &str) -> io::Result<info::GetTxFeeResponse> {
@@ -183,14 +240,14 @@ pub async fn get_tx_fee(url: &str) -> io::Result<info::GetTxFeeResponse> {
     data.method = String::from("info.getTxFee");
 
     let d = data.encode_json()?;
-    let rb = http::post_non_tls(url, "ext/info", &d).await?;
+    let rb = http::post_json(url, "ext/info", &d, http::Idempotency::Idempotent).await?;
     let resp: info::RawGetTxFeeResponse = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     let converted = resp.convert()?;
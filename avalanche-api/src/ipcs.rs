@@ -0,0 +1,210 @@
+use std::{
+    collections::HashMap,
+    io::{self, Error, ErrorKind},
+    string::String,
+};
+
+use log::info;
+use serde::de::DeserializeOwned;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    net::UnixStream,
+};
+
+use avalanche_types::{
+    api::{admin, ipcs, jsonrpc},
+    errors, ids,
+};
+use utils::http;
+
+const IPCS_PATH: &str = "/ext/ipcs";
+
+/// Publishes the accepted containers of the blockchain to the Unix domain
+/// sockets on the node, which "Consumer" reads. Requires "--api-ipcs-enabled",
+/// so only for the test harnesses on the node.
+/// ref. https://docs.avax.network/build/avalanchego-apis/ipc#ipcspublishblockchain
+pub async fn publish_blockchain(
+    url: &str,
+    blockchain_id: &str,
+) -> io::Result<ipcs::PublishBlockchainResponse> {
+    info!("publishing blockchain {} via {}", blockchain_id, url);
+    let mut params = HashMap::new();
+    params.insert(String::from("blockchainID"), blockchain_id.to_string());
+    let resp: ipcs::PublishBlockchainResponse = post(url, "ipcs.publishBlockchain", params).await?;
+    check_error("ipcs.publishBlockchain", &resp.error)?;
+    Ok(resp)
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/ipc#ipcsunpublishblockchain
+pub async fn unpublish_blockchain(
+    url: &str,
+    blockchain_id: &str,
+) -> io::Result<admin::EmptyResponse> {
+    info!("unpublishing blockchain {} via {}", blockchain_id, url);
+    let mut params = HashMap::new();
+    params.insert(String::from("blockchainID"), blockchain_id.to_string());
+    let resp: admin::EmptyResponse = post(url, "ipcs.unpublishBlockchain", params).await?;
+    check_error("ipcs.unpublishBlockchain", &resp.error)?;
+    Ok(resp)
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/ipc#ipcsgetpublishedblockchains
+pub async fn get_published_blockchains(
+    url: &str,
+) -> io::Result<ipcs::GetPublishedBlockchainsResponse> {
+    info!("getting published blockchains via {}", url);
+    let resp: ipcs::GetPublishedBlockchainsResponse =
+        post(url, "ipcs.getPublishedBlockchains", HashMap::new()).await?;
+    check_error("ipcs.getPublishedBlockchains", &resp.error)?;
+    Ok(resp)
+}
+
+/// Reads the events published to one of the sockets of "publish_blockchain".
+/// Only the events accepted after the connect are read.
+pub struct Consumer {
+    kind: ipcs::EventKind,
+    chain_id: ids::Id,
+    stream: UnixStream,
+}
+
+impl Consumer {
+    /// Connects to the socket (e.g., "PublishBlockchainResult.decisions_url").
+    pub async fn connect(
+        socket_path: &str,
+        kind: ipcs::EventKind,
+        chain_id: ids::Id,
+    ) -> io::Result<Self> {
+        info!(
+            "connecting to {:?} events of {} at {}",
+            kind, chain_id, socket_path
+        );
+        let stream = UnixStream::connect(socket_path).await?;
+        Ok(Self {
+            kind,
+            chain_id,
+            stream,
+        })
+    }
+
+    /// Returns the next event, blocking until the node accepts one,
+    /// or "None" once the node closes the socket (e.g., unpublished).
+    pub async fn next_event(&mut self) -> io::Result<Option<ipcs::Event>> {
+        let bytes = match read_event(&mut self.stream).await? {
+            Some(b) => b,
+            None => return Ok(None),
+        };
+        Ok(Some(ipcs::Event::new(self.kind, self.chain_id, bytes)))
+    }
+
+    /// Reads the events until the container is accepted,
+    /// so the caller asserts on the acceptance instead of polling the status.
+    pub async fn wait_for(&mut self, container_id: &ids::Id) -> io::Result<ipcs::Event> {
+        loop {
+            match self.next_event().await? {
+                Some(event) if event.container_id == *container_id => return Ok(event),
+                Some(_) => continue,
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::UnexpectedEof,
+                        format!("socket closed before {} is accepted", container_id),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Reads the message prefixed with its 8-byte big-endian length.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/ipcs/socket#Socket.Send
+async fn read_event<S>(stream: &mut S) -> io::Result<Option<Vec<u8>>>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut len_bytes = [0u8; 8];
+    match stream.read_exact(&mut len_bytes).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u64::from_be_bytes(len_bytes);
+    if len > ipcs::MAX_EVENT_SIZE {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid event length {}", len),
+        ));
+    }
+
+    let mut b = vec![0u8; len as usize];
+    stream.read_exact(&mut b).await?;
+    Ok(Some(b))
+}
+
+async fn post<T: DeserializeOwned>(
+    url: &str,
+    method: &str,
+    params: HashMap<String, String>,
+) -> io::Result<T> {
+    let mut data = jsonrpc::Data::default();
+    data.method = String::from(method);
+    data.params = Some(params);
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, IPCS_PATH, &d, http::Idempotency::Idempotent).await?;
+    serde_json::from_slice(&rb).map_err(|e| {
+        errors::Error::Decode {
+            message: format!("{} response {}", method, e),
+        }
+        .into()
+    })
+}
+
+fn check_error(method: &str, error: &Option<jsonrpc::ResponseError>) -> io::Result<()> {
+    match error {
+        Some(e) => Err(e.to_io_error(method)),
+        None => Ok(()),
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-api --lib -- ipcs::test_consumer --exact --show-output
+#[tokio::test]
+async fn test_consumer() {
+    use tokio::io::AsyncWriteExt;
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let socket_path = tmp_dir.path().join("1-decisions");
+    let listener = tokio::net::UnixListener::bind(&socket_path).unwrap();
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        for msg in [vec![1u8, 2, 3], vec![4u8; 100]] {
+            stream
+                .write_all(&(msg.len() as u64).to_be_bytes())
+                .await
+                .unwrap();
+            stream.write_all(&msg).await.unwrap();
+        }
+    });
+
+    let chain_id = ids::Id::empty();
+    let mut consumer = Consumer::connect(
+        socket_path.as_os_str().to_str().unwrap(),
+        ipcs::EventKind::Decision,
+        chain_id,
+    )
+    .await
+    .unwrap();
+    let event = consumer.next_event().await.unwrap().unwrap();
+    assert_eq!(event.kind, ipcs::EventKind::Decision);
+    assert_eq!(event.bytes, vec![1, 2, 3]);
+
+    let expected = ipcs::Event::new(ipcs::EventKind::Decision, chain_id, vec![4u8; 100]);
+    let event = consumer.wait_for(&expected.container_id).await.unwrap();
+    assert_eq!(event, expected);
+
+    server.await.unwrap();
+    assert!(consumer.next_event().await.unwrap().is_none());
+
+    let mut invalid: &[u8] = &(ipcs::MAX_EVENT_SIZE + 1).to_be_bytes();
+    assert!(read_event(&mut invalid).await.is_err());
+}
@@ -0,0 +1,178 @@
+use std::{collections::HashMap, io, string::String};
+
+use log::info;
+use serde::de::DeserializeOwned;
+
+use avalanche_types::{
+    api::{jsonrpc, keystore},
+    errors,
+};
+use utils::http;
+
+const KEYSTORE_PATH: &str = "/ext/keystore";
+
+/// e.g., "keystore.createUser" on "http://[ADDR]:9650" and "/ext/keystore" path.
+/// Requires "--api-keystore-enabled".
+/// ref. https://docs.avax.network/build/avalanchego-apis/keystore#keystorecreateuser
+pub async fn create_user(
+    url: &str,
+    username: &str,
+    password: &str,
+) -> io::Result<keystore::SuccessResponse> {
+    info!("creating keystore user '{}' via {}", username, url);
+    let resp: keystore::SuccessResponse = post(
+        url,
+        KEYSTORE_PATH,
+        "keystore.createUser",
+        user_params(username, password),
+        http::Idempotency::NonIdempotent,
+    )
+    .await?;
+    check_error("keystore.createUser", &resp.error)?;
+    Ok(resp)
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/keystore#keystorelistusers
+pub async fn list_users(url: &str) -> io::Result<keystore::ListUsersResponse> {
+    info!("listing keystore users via {}", url);
+    let resp: keystore::ListUsersResponse = post(
+        url,
+        KEYSTORE_PATH,
+        "keystore.listUsers",
+        HashMap::new(),
+        http::Idempotency::Idempotent,
+    )
+    .await?;
+    check_error("keystore.listUsers", &resp.error)?;
+    Ok(resp)
+}
+
+/// Returns the user database encrypted with the user password, "hex" encoded.
+/// ref. https://docs.avax.network/build/avalanchego-apis/keystore#keystoreexportuser
+pub async fn export_user(
+    url: &str,
+    username: &str,
+    password: &str,
+) -> io::Result<keystore::ExportUserResponse> {
+    info!("exporting keystore user '{}' via {}", username, url);
+    let mut params = user_params(username, password);
+    params.insert(String::from("encoding"), String::from("hex"));
+    let resp: keystore::ExportUserResponse = post(
+        url,
+        KEYSTORE_PATH,
+        "keystore.exportUser",
+        params,
+        http::Idempotency::Idempotent,
+    )
+    .await?;
+    check_error("keystore.exportUser", &resp.error)?;
+    Ok(resp)
+}
+
+/// Imports the user from "export_user" with the same password.
+/// ref. https://docs.avax.network/build/avalanchego-apis/keystore#keystoreimportuser
+pub async fn import_user(
+    url: &str,
+    username: &str,
+    password: &str,
+    export: &keystore::ExportUserResult,
+) -> io::Result<keystore::SuccessResponse> {
+    info!("importing keystore user '{}' via {}", username, url);
+    let mut params = user_params(username, password);
+    params.insert(String::from("user"), export.user.clone());
+    params.insert(String::from("encoding"), export.encoding.clone());
+    let resp: keystore::SuccessResponse = post(
+        url,
+        KEYSTORE_PATH,
+        "keystore.importUser",
+        params,
+        http::Idempotency::NonIdempotent,
+    )
+    .await?;
+    check_error("keystore.importUser", &resp.error)?;
+    Ok(resp)
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/keystore#keystoredeleteuser
+pub async fn delete_user(
+    url: &str,
+    username: &str,
+    password: &str,
+) -> io::Result<keystore::SuccessResponse> {
+    info!("deleting keystore user '{}' via {}", username, url);
+    let resp: keystore::SuccessResponse = post(
+        url,
+        KEYSTORE_PATH,
+        "keystore.deleteUser",
+        user_params(username, password),
+        http::Idempotency::NonIdempotent,
+    )
+    .await?;
+    check_error("keystore.deleteUser", &resp.error)?;
+    Ok(resp)
+}
+
+/// Imports the private key (with the prefix "PrivateKey-") to the user
+/// on the chain (e.g., "avm.importKey"), so that the user spends the funds.
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmimportkey
+pub async fn import_key(
+    url: &str,
+    chain: &keystore::Chain,
+    username: &str,
+    password: &str,
+    private_key: &str,
+) -> io::Result<keystore::ImportKeyResponse> {
+    info!(
+        "importing key to keystore user '{}' via {}{}",
+        username,
+        url,
+        chain.path()
+    );
+    let mut params = user_params(username, password);
+    params.insert(String::from("privateKey"), private_key.to_string());
+    let resp: keystore::ImportKeyResponse = post(
+        url,
+        chain.path(),
+        chain.import_key_method(),
+        params,
+        http::Idempotency::Idempotent,
+    )
+    .await?;
+    check_error(chain.import_key_method(), &resp.error)?;
+    Ok(resp)
+}
+
+fn user_params(username: &str, password: &str) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    params.insert(String::from("username"), username.to_string());
+    params.insert(String::from("password"), password.to_string());
+    params
+}
+
+async fn post<T: DeserializeOwned>(
+    url: &str,
+    path: &str,
+    method: &str,
+    params: HashMap<String, String>,
+    idempotency: http::Idempotency,
+) -> io::Result<T> {
+    let mut data = jsonrpc::Data::default();
+    data.method = String::from(method);
+    data.params = Some(params);
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, path, &d, idempotency).await?;
+    serde_json::from_slice(&rb).map_err(|e| {
+        errors::Error::Decode {
+            message: format!("{} response {}", method, e),
+        }
+        .into()
+    })
+}
+
+fn check_error(method: &str, error: &Option<jsonrpc::ResponseError>) -> io::Result<()> {
+    match error {
+        Some(e) => Err(e.to_io_error(method)),
+        None => Ok(()),
+    }
+}
@@ -1,6 +1,14 @@
+pub mod admin;
+pub mod alert;
 pub mod c;
+pub mod eth;
 pub mod health;
+pub mod index;
 pub mod info;
+pub mod ipcs;
+pub mod keystore;
+pub mod load;
 pub mod metrics;
+pub mod network;
 pub mod p;
 pub mod x;
@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+/// Upper bounds of the latency buckets in milliseconds,
+/// with the last bucket holding everything above.
+const BUCKETS_MS: [u64; 14] = [
+    10, 25, 50, 100, 250, 500, 1_000, 2_000, 5_000, 10_000, 20_000, 30_000, 60_000, 120_000,
+];
+
+/// Fixed-bucket latency histogram, so that recording stays constant-time
+/// and the memory does not grow with the length of the soak test.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    counts: [u64; BUCKETS_MS.len() + 1],
+    count: u64,
+    sum: Duration,
+    max: Duration,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Histogram {
+    pub fn default() -> Self {
+        Self {
+            counts: [0; BUCKETS_MS.len() + 1],
+            count: 0,
+            sum: Duration::ZERO,
+            max: Duration::ZERO,
+        }
+    }
+
+    pub fn record(&mut self, d: Duration) {
+        let ms = d.as_millis();
+        let idx = BUCKETS_MS
+            .iter()
+            .position(|b| ms <= u128::from(*b))
+            .unwrap_or(BUCKETS_MS.len());
+        self.counts[idx] += 1;
+        self.count += 1;
+        self.sum += d;
+        self.max = self.max.max(d);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        self.sum / self.count as u32
+    }
+
+    /// Returns the upper bound of the bucket holding the "p"-th percentile
+    /// (e.g., 99.0), or the maximum if it falls in the last bucket.
+    pub fn percentile(&self, p: f64) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        let rank = ((p / 100.0) * self.count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (idx, n) in self.counts.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return match BUCKETS_MS.get(idx) {
+                    Some(ms) => Duration::from_millis(*ms).min(self.max),
+                    None => self.max,
+                };
+            }
+        }
+        self.max
+    }
+
+    pub fn merge(&mut self, other: &Histogram) {
+        for (a, b) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *a += b;
+        }
+        self.count += other.count;
+        self.sum += other.sum;
+        self.max = self.max.max(other.max);
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-api --lib -- load::histogram::test_histogram --exact --show-output
+#[test]
+fn test_histogram() {
+    let mut h = Histogram::default();
+    assert_eq!(h.percentile(50.0), Duration::ZERO);
+
+    for ms in [5, 40, 40, 80, 300, 900, 1_500, 4_000, 9_000, 200_000] {
+        h.record(Duration::from_millis(ms));
+    }
+    assert_eq!(h.count(), 10);
+    assert_eq!(h.max(), Duration::from_millis(200_000));
+    assert_eq!(h.percentile(10.0), Duration::from_millis(10));
+    assert_eq!(h.percentile(50.0), Duration::from_millis(500));
+    assert_eq!(h.percentile(90.0), Duration::from_millis(10_000));
+    assert_eq!(h.percentile(100.0), Duration::from_millis(200_000));
+
+    let mut merged = Histogram::default();
+    merged.merge(&h);
+    merged.merge(&h);
+    assert_eq!(merged.count(), 20);
+    assert_eq!(merged.mean(), h.mean());
+}
@@ -0,0 +1,364 @@
+use std::{
+    collections::HashMap,
+    io::{self, Error, ErrorKind},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use ethereum_types::{Address, U256};
+use log::debug;
+use tokio::time::sleep;
+
+use crate::{eth as api_eth, info as api_info, p as api_p, x as api_x};
+use avalanche_types::{
+    avax, avm, evm, formatting, ids, platformvm::txs::SubnetBuilder, secp256k1fx, soft_key, units,
+};
+
+use super::{Chain, Issuer, Prepared, Wallet};
+
+/// Interval to poll the status of the issued transaction.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Amount sent back to the issuing key on the X-chain (in nAVAX) and C-chain (in wei),
+/// so that each transaction only burns the fee.
+const SELF_TRANSFER_AMOUNT: u64 = 1;
+
+const EVM_GAS_LIMIT: u64 = 21_000;
+/// Above the C-chain maximum base fee, so the transactions are never underpriced.
+const EVM_MAX_FEE_PER_GAS: u64 = 225_000_000_000;
+const EVM_MAX_PRIORITY_FEE_PER_GAS: u64 = 1_000_000_000;
+
+/// Network parameters shared by the issuers of all keys.
+#[derive(Debug, Clone)]
+pub struct Network {
+    pub http_rpc: String,
+    pub network_id: u32,
+    pub avax_asset_id: ids::Id,
+    pub x_chain_id: ids::Id,
+    /// Aliases of the primary chains, to resolve the chain arguments.
+    pub aliaser: ids::Aliaser,
+    pub evm_chain_id: u64,
+    pub tx_fee: u64,
+    pub creation_tx_fee: u64,
+}
+
+impl Network {
+    /// Fetches the network parameters from the node.
+    pub async fn fetch(http_rpc: &str) -> io::Result<Self> {
+        let network_id = api_info::get_network_id(http_rpc)
+            .await?
+            .result
+            .map(|r| r.network_id)
+            .ok_or_else(|| Error::new(ErrorKind::Other, "info.getNetworkID returned no result"))?;
+        let fees = api_info::get_tx_fee(http_rpc)
+            .await?
+            .result
+            .unwrap_or_default();
+        let avax_asset_id = api_x::get_asset_description(http_rpc, "AVAX")
+            .await?
+            .result
+            .map(|r| r.asset_id)
+            .ok_or_else(|| Error::new(ErrorKind::Other, "AVAX asset description not found"))?;
+        let aliaser = api_info::get_aliaser(http_rpc, &api_info::PRIMARY_CHAIN_ALIASES).await?;
+        let x_chain_id = aliaser.lookup("X")?;
+        let evm_chain_id = api_eth::chain_id(http_rpc, api_eth::C_CHAIN_RPC_PATH)
+            .await?
+            .result;
+        let evm_chain_id = u64::try_from(&evm_chain_id).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("C-chain ID {} overflows u64 ({})", evm_chain_id, e),
+            )
+        })?;
+
+        Ok(Self {
+            http_rpc: http_rpc.to_string(),
+            network_id,
+            avax_asset_id,
+            x_chain_id,
+            aliaser,
+            evm_chain_id,
+            tx_fee: fees.tx_fee,
+            creation_tx_fee: fees.creation_tx_fee,
+        })
+    }
+
+    /// Returns the issuers of the key for each chain.
+    pub fn wallet(&self, key: &soft_key::Key, chains: &[Chain]) -> io::Result<Wallet> {
+        let mut wallet: Wallet = HashMap::new();
+        for chain in chains.iter() {
+            let issuer: Arc<dyn Issuer> = match chain {
+                Chain::X => Arc::new(XIssuer::new(self, key)?),
+                Chain::P => Arc::new(PIssuer::new(self, key)?),
+                Chain::C => Arc::new(CIssuer::new(self, key)?),
+            };
+            wallet.insert(*chain, issuer);
+        }
+        Ok(wallet)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("unexpected None duration_since")
+        .as_secs()
+}
+
+/// Sends AVAX back to the same key on the X-chain.
+pub struct XIssuer {
+    http_rpc: String,
+    x_address: String,
+    key: soft_key::Key,
+    builder: avm::txs::base::Builder,
+}
+
+impl XIssuer {
+    pub fn new(network: &Network, key: &soft_key::Key) -> io::Result<Self> {
+        Ok(Self {
+            http_rpc: network.http_rpc.clone(),
+            x_address: key.address("X", network.network_id)?,
+            key: key.clone(),
+            builder: avm::txs::base::Builder {
+                network_id: network.network_id,
+                blockchain_id: network.x_chain_id,
+                avax_asset_id: network.avax_asset_id,
+                tx_fee: units::Avax::from_navax(network.tx_fee),
+                keychain: soft_key::Keychain::new(vec![key.clone()]),
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl Issuer for XIssuer {
+    async fn prepare(&self) -> io::Result<Prepared> {
+        let utxos = match api_x::get_utxos(&self.http_rpc, &self.x_address)
+            .await?
+            .result
+        {
+            Some(r) => r.decode_utxos()?,
+            None => Vec::new(),
+        };
+        let out = avax::TransferableOutput {
+            asset_id: self.builder.avax_asset_id,
+            transfer_output: Some(secp256k1fx::TransferOutput::new(
+                SELF_TRANSFER_AMOUNT,
+                secp256k1fx::OutputOwners::new(0, 1, &[self.key.short_address]),
+            )),
+            ..avax::TransferableOutput::default()
+        };
+        let (mut tx, signers) =
+            self.builder
+                .new_base_tx(&utxos, vec![out], &self.key.short_address, unix_now())?;
+        let (signed_bytes, tx_id) = tx.sign(&signers)?;
+        Ok(Prepared {
+            id: tx_id.to_string(),
+            encoded: formatting::encode_cb58_with_checksum(&signed_bytes),
+        })
+    }
+
+    async fn issue(&self, tx: Prepared) -> io::Result<()> {
+        api_x::issue_tx(&self.http_rpc, &tx.encoded, "cb58").await?;
+        debug!("issued X-chain tx {}", tx.id);
+
+        loop {
+            sleep(POLL_INTERVAL).await;
+            let status = api_x::get_tx_status(&self.http_rpc, &tx.id)
+                .await?
+                .result
+                .map(|r| r.status)
+                .unwrap_or_default();
+            match status.as_str() {
+                "Accepted" => return Ok(()),
+                "Rejected" => {
+                    return Err(Error::new(
+                        ErrorKind::Other,
+                        format!("X-chain tx {} rejected", tx.id),
+                    ))
+                }
+                _ => continue,
+            }
+        }
+    }
+}
+
+/// Exports AVAX from the P-chain back to the same key on the X-chain,
+/// since the P-chain has no plain transfer tx.
+/// Each export only burns the (non-creation) tx fee and creates no P-chain state,
+/// and the exported UTXOs can later be imported on the X-chain.
+pub struct PIssuer {
+    http_rpc: String,
+    p_address: String,
+    x_chain_id: ids::Id,
+    key: soft_key::Key,
+    builder: SubnetBuilder,
+}
+
+impl PIssuer {
+    pub fn new(network: &Network, key: &soft_key::Key) -> io::Result<Self> {
+        Ok(Self {
+            http_rpc: network.http_rpc.clone(),
+            p_address: key.address("P", network.network_id)?,
+            x_chain_id: network.x_chain_id,
+            key: key.clone(),
+            builder: SubnetBuilder {
+                network_id: network.network_id,
+                avax_asset_id: network.avax_asset_id,
+                tx_fee: units::Avax::from_navax(network.tx_fee),
+                creation_tx_fee: units::Avax::from_navax(network.creation_tx_fee),
+                keychain: soft_key::Keychain::new(vec![key.clone()]),
+                co_signers: Vec::new(),
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl Issuer for PIssuer {
+    async fn prepare(&self) -> io::Result<Prepared> {
+        let utxos = match api_p::get_utxos(&self.http_rpc, &self.p_address)
+            .await?
+            .result
+        {
+            Some(r) => r.decode_utxos()?,
+            None => Vec::new(),
+        };
+        let out = avax::TransferableOutput {
+            asset_id: self.builder.avax_asset_id,
+            transfer_output: Some(secp256k1fx::TransferOutput::new(
+                SELF_TRANSFER_AMOUNT,
+                secp256k1fx::OutputOwners::new(0, 1, &[self.key.short_address]),
+            )),
+            ..avax::TransferableOutput::default()
+        };
+        let (mut tx, signers) = self.builder.new_export_tx(
+            &utxos,
+            self.x_chain_id,
+            vec![out],
+            &self.key.short_address,
+            unix_now(),
+        )?;
+        let (signed_bytes, tx_id) = tx.sign(&signers)?;
+        Ok(Prepared {
+            id: tx_id.to_string(),
+            encoded: formatting::encode_cb58_with_checksum(&signed_bytes),
+        })
+    }
+
+    async fn issue(&self, tx: Prepared) -> io::Result<()> {
+        api_p::issue_tx(&self.http_rpc, &tx.encoded, "cb58").await?;
+        debug!("issued P-chain tx {}", tx.id);
+
+        loop {
+            sleep(POLL_INTERVAL).await;
+            let status = api_p::get_tx_status(&self.http_rpc, &tx.id)
+                .await?
+                .result
+                .unwrap_or_default();
+            if status.is_committed() {
+                return Ok(());
+            }
+            if status.is_dropped() {
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    format!(
+                        "P-chain tx {} dropped ({})",
+                        tx.id,
+                        status.reason.unwrap_or_default()
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+/// Sends the dynamic fee transactions back to the same key on the C-chain.
+pub struct CIssuer {
+    http_rpc: String,
+    chain_id: u64,
+    eth_address: String,
+    to: Address,
+    key: soft_key::Key,
+}
+
+impl CIssuer {
+    pub fn new(network: &Network, key: &soft_key::Key) -> io::Result<Self> {
+        let to = key.eth_address.parse::<Address>().map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid eth address {} ({})", key.eth_address, e),
+            )
+        })?;
+        Ok(Self {
+            http_rpc: network.http_rpc.clone(),
+            chain_id: network.evm_chain_id,
+            eth_address: key.eth_address.clone(),
+            to,
+            key: key.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl Issuer for CIssuer {
+    async fn prepare(&self) -> io::Result<Prepared> {
+        let nonce = api_eth::get_transaction_count(
+            &self.http_rpc,
+            api_eth::C_CHAIN_RPC_PATH,
+            &self.eth_address,
+            "pending",
+        )
+        .await?
+        .result;
+        let nonce = u64::try_from(&nonce).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("nonce {} overflows u64 ({})", nonce, e),
+            )
+        })?;
+
+        let tx = evm::tx::Tx::DynamicFee(evm::tx::DynamicFeeTx {
+            chain_id: self.chain_id,
+            nonce,
+            max_priority_fee_per_gas: U256::from(EVM_MAX_PRIORITY_FEE_PER_GAS),
+            max_fee_per_gas: U256::from(EVM_MAX_FEE_PER_GAS),
+            gas_limit: EVM_GAS_LIMIT,
+            to: Some(self.to),
+            value: U256::from(SELF_TRANSFER_AMOUNT),
+            data: Vec::new(),
+        });
+        let signed = tx.sign(&self.key).await?;
+        Ok(Prepared {
+            id: format!("{:?}", signed.hash),
+            encoded: signed.to_hex(),
+        })
+    }
+
+    async fn issue(&self, tx: Prepared) -> io::Result<()> {
+        let path = api_eth::C_CHAIN_RPC_PATH;
+        let tx_hash = api_eth::send_raw_transaction(&self.http_rpc, path, &tx.encoded)
+            .await?
+            .result
+            .unwrap_or(tx.id);
+        debug!("issued C-chain tx {}", tx_hash);
+
+        loop {
+            sleep(POLL_INTERVAL).await;
+            let receipt = api_eth::get_transaction_receipt(&self.http_rpc, path, &tx_hash)
+                .await?
+                .result;
+            match receipt {
+                Some(r) if r.is_success() => return Ok(()),
+                Some(_) => {
+                    return Err(Error::new(
+                        ErrorKind::Other,
+                        format!("C-chain tx {} reverted", tx_hash),
+                    ))
+                }
+                None => continue,
+            }
+        }
+    }
+}
@@ -0,0 +1,668 @@
+pub mod histogram;
+pub mod issuer;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    io::{self, Error, ErrorKind},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::{sync::mpsc, time::sleep};
+
+use avalanche_types::{errors, ids};
+
+use self::histogram::Histogram;
+
+/// Interval of the scheduler to release the due transactions.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Chain to drive the load against.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum Chain {
+    X,
+    P,
+    C,
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Chain::X => write!(f, "X"),
+            Chain::P => write!(f, "P"),
+            Chain::C => write!(f, "C"),
+        }
+    }
+}
+
+impl FromStr for Chain {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "X" => Ok(Chain::X),
+            "P" => Ok(Chain::P),
+            "C" => Ok(Chain::C),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("unknown chain '{}' (expected X, P, or C)", s),
+            )),
+        }
+    }
+}
+
+impl Chain {
+    /// Resolves the chain alias (e.g., "X", "avm") or the blockchain ID
+    /// to the primary chain, using the aliases registered by the node.
+    /// The primary aliases are still accepted in any case (e.g., "x").
+    pub fn resolve(s: &str, aliaser: &ids::Aliaser) -> io::Result<Self> {
+        let blockchain_id = match aliaser.resolve(s) {
+            Ok(id) => id,
+            Err(e) => return Chain::from_str(s).map_err(|_| e),
+        };
+        let primary = aliaser.primary_alias(&blockchain_id).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("'{}' ({}) is not a primary chain", s.trim(), blockchain_id),
+            )
+        })?;
+        Chain::from_str(primary)
+    }
+}
+
+/// How the issue rate approaches the target TPS.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub enum Ramp {
+    /// Issues at the target TPS from the start.
+    Constant,
+    /// Increases linearly from zero to the target TPS over the duration.
+    Linear(Duration),
+    /// Increases by "target / steps" at every interval.
+    Step { steps: u32, interval: Duration },
+}
+
+impl Ramp {
+    /// Returns the TPS to issue at "elapsed" since the start.
+    pub fn tps(&self, target: f64, elapsed: Duration) -> f64 {
+        match self {
+            Ramp::Constant => target,
+            Ramp::Linear(over) => {
+                if over.is_zero() || elapsed >= *over {
+                    return target;
+                }
+                target * elapsed.as_secs_f64() / over.as_secs_f64()
+            }
+            Ramp::Step { steps, interval } => {
+                if *steps == 0 || interval.is_zero() {
+                    return target;
+                }
+                let step = (elapsed.as_secs_f64() / interval.as_secs_f64()) as u32 + 1;
+                target * f64::from(step.min(*steps)) / f64::from(*steps)
+            }
+        }
+    }
+}
+
+/// Parses "constant", "linear:[SECONDS]", or "step:[STEPS]:[SECONDS]".
+impl FromStr for Ramp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid ramp '{}' ({})", s, reason),
+            )
+        };
+        let fields: Vec<&str> = s.trim().split(':').collect();
+        match fields.as_slice() {
+            ["constant"] => Ok(Ramp::Constant),
+            ["linear", secs] => {
+                let secs = secs.parse::<u64>().map_err(|e| invalid(&e.to_string()))?;
+                Ok(Ramp::Linear(Duration::from_secs(secs)))
+            }
+            ["step", steps, secs] => {
+                let steps = steps.parse::<u32>().map_err(|e| invalid(&e.to_string()))?;
+                let secs = secs.parse::<u64>().map_err(|e| invalid(&e.to_string()))?;
+                Ok(Ramp::Step {
+                    steps,
+                    interval: Duration::from_secs(secs),
+                })
+            }
+            _ => Err(invalid(
+                "expected 'constant', 'linear:[SECONDS]', or 'step:[STEPS]:[SECONDS]'",
+            )),
+        }
+    }
+}
+
+/// Weighted share of the transactions per chain.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Mix {
+    weights: Vec<(Chain, u32)>,
+}
+
+impl Mix {
+    pub fn new(weights: Vec<(Chain, u32)>) -> io::Result<Self> {
+        let weights: Vec<(Chain, u32)> = weights.into_iter().filter(|(_, w)| *w > 0).collect();
+        if weights.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "mix must have at least one chain with non-zero weight",
+            ));
+        }
+        Ok(Self { weights })
+    }
+
+    /// Returns the chains with non-zero weights.
+    pub fn chains(&self) -> Vec<Chain> {
+        self.weights.iter().map(|(c, _)| *c).collect()
+    }
+
+    /// Returns the chain of the "n"-th transaction, so that every window
+    /// of the total weight follows the mix exactly.
+    pub fn pick(&self, n: u64) -> Chain {
+        let total: u64 = self.weights.iter().map(|(_, w)| u64::from(*w)).sum();
+        let mut slot = n % total;
+        for (chain, w) in self.weights.iter() {
+            if slot < u64::from(*w) {
+                return *chain;
+            }
+            slot -= u64::from(*w);
+        }
+        unreachable!("slot out of the total weight")
+    }
+}
+
+impl Mix {
+    /// Parses the comma-separated weights keyed by the chain alias or the
+    /// blockchain ID (e.g., "X=1,C=2" or "2oYMBNV4eNHyqk2fjjV5nVQLDbtmNJzq5s3qs3Lo6ftnC6FByM=1").
+    pub fn resolve(s: &str, aliaser: &ids::Aliaser) -> io::Result<Self> {
+        Self::parse(s, |chain| Chain::resolve(chain, aliaser))
+    }
+
+    fn parse(s: &str, chain_of: impl Fn(&str) -> io::Result<Chain>) -> io::Result<Self> {
+        let mut weights = Vec::new();
+        for field in s.split(',').filter(|f| !f.trim().is_empty()) {
+            let (chain, weight) = field.split_once('=').ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid mix '{}' (expected '[CHAIN]=[WEIGHT]')", field),
+                )
+            })?;
+            let weight = weight.trim().parse::<u32>().map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid mix weight '{}' ({})", weight, e),
+                )
+            })?;
+            weights.push((chain_of(chain)?, weight));
+        }
+        Mix::new(weights)
+    }
+}
+
+/// Parses the comma-separated weights (e.g., "x=1,p=0,c=2").
+impl FromStr for Mix {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, Chain::from_str)
+    }
+}
+
+/// Load to drive against the network.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Config {
+    /// Transactions per second to issue once fully ramped up.
+    pub target_tps: f64,
+    pub duration: Duration,
+    pub ramp: Ramp,
+    pub mix: Mix,
+    /// Maximum wait for each transaction to be accepted.
+    pub confirm_timeout: Duration,
+}
+
+impl Config {
+    pub fn validate(&self) -> io::Result<()> {
+        if !self.target_tps.is_finite() || self.target_tps <= 0.0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("target TPS must be positive, got {}", self.target_tps),
+            ));
+        }
+        if self.duration.is_zero() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "load duration must be non-zero",
+            ));
+        }
+        if self.confirm_timeout.is_zero() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "confirm timeout must be non-zero",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Signed transaction ready to issue.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Prepared {
+    /// Transaction ID (or hash on the C-chain) to poll the status with.
+    pub id: String,
+    /// Encoded signed transaction as the issue API takes it (e.g., CB58, hex).
+    pub encoded: String,
+}
+
+/// Issues one transaction on a chain from one funded key.
+#[async_trait]
+pub trait Issuer: Send + Sync {
+    /// Builds and signs the next transaction from the current state of the key
+    /// (e.g., UTXOs, nonce).
+    async fn prepare(&self) -> io::Result<Prepared>;
+
+    /// Issues the prepared transaction and waits until it is accepted,
+    /// so the next one from the same key spends the updated state.
+    async fn issue(&self, tx: Prepared) -> io::Result<()>;
+}
+
+/// Issuers of one funded key, one per chain in the mix.
+/// Each wallet has at most one transaction in flight.
+pub type Wallet = HashMap<Chain, Arc<dyn Issuer>>;
+
+/// Class of the failed transactions.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum Failure {
+    /// Not accepted within the deadline, or the API call timed out.
+    Timeout,
+    /// Failed to reach the node.
+    Connection,
+    /// The key ran out of funds.
+    InsufficientFunds,
+    /// Spent the state already spent by another transaction (e.g., nonce, UTXO).
+    Conflict,
+    /// Rejected, dropped, or reverted after issuance.
+    Rejected,
+    /// The API returned the error.
+    Api,
+    Other,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Classifies the failure by the error kind, the error class of "errors::Error",
+/// and the message of the API clients and the tx builders.
+pub fn classify(e: &Error) -> Failure {
+    if e.kind() == ErrorKind::TimedOut {
+        return Failure::Timeout;
+    }
+    let class = errors::Error::from_io(e);
+    let msg = e.to_string().to_lowercase();
+    if msg.contains("timed out") || msg.contains("deadline") {
+        Failure::Timeout
+    } else if msg.contains("insufficient funds") {
+        Failure::InsufficientFunds
+    } else if msg.contains("nonce too low")
+        || msg.contains("already known")
+        || msg.contains("missing utxo")
+        || msg.contains("conflict")
+    {
+        Failure::Conflict
+    } else if msg.contains("dropped") || msg.contains("rejected") || msg.contains("reverted") {
+        Failure::Rejected
+    } else if matches!(class, Some(errors::Error::API { .. })) {
+        Failure::Api
+    } else if matches!(
+        e.kind(),
+        ErrorKind::ConnectionRefused | ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted
+    ) || msg.contains("connect")
+        || msg.contains("failed to fetch response")
+    {
+        Failure::Connection
+    } else {
+        Failure::Other
+    }
+}
+
+/// Results of the transactions on one chain.
+#[derive(Debug, Clone, Default)]
+pub struct ChainReport {
+    pub issued: u64,
+    pub accepted: u64,
+    pub failures: BTreeMap<Failure, u64>,
+    /// Latency from issuance to acceptance of the accepted transactions.
+    pub latency: Histogram,
+}
+
+/// Results of the load run.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub elapsed: Duration,
+    /// Due transactions not issued because every wallet was busy,
+    /// meaning the key pool cannot sustain the target TPS.
+    pub skipped: u64,
+    pub chains: BTreeMap<Chain, ChainReport>,
+}
+
+impl Report {
+    pub fn accepted(&self) -> u64 {
+        self.chains.values().map(|c| c.accepted).sum()
+    }
+
+    /// Returns the accepted transactions per second over the run.
+    pub fn accepted_tps(&self) -> f64 {
+        if self.elapsed.is_zero() {
+            return 0.0;
+        }
+        self.accepted() as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "elapsed {:?}, accepted {} ({:.2} TPS), skipped {}",
+            self.elapsed,
+            self.accepted(),
+            self.accepted_tps(),
+            self.skipped
+        )?;
+        for (chain, r) in self.chains.iter() {
+            writeln!(
+                f,
+                "{}-chain: issued {}, accepted {}, p50 {:?}, p90 {:?}, p99 {:?}, max {:?}",
+                chain,
+                r.issued,
+                r.accepted,
+                r.latency.percentile(50.0),
+                r.latency.percentile(90.0),
+                r.latency.percentile(99.0),
+                r.latency.max(),
+            )?;
+            for (failure, n) in r.failures.iter() {
+                writeln!(f, "  {}: {}", failure, n)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Drives the load with the wallets until the configured duration elapses,
+/// then waits for the in-flight transactions.
+/// Every wallet must have the issuers for all chains in the mix.
+pub async fn run(config: &Config, wallets: Vec<Wallet>) -> io::Result<Report> {
+    config.validate()?;
+    if wallets.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "no wallet to drive the load",
+        ));
+    }
+    for chain in config.mix.chains() {
+        if wallets.iter().any(|w| !w.contains_key(&chain)) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("not every wallet has the {}-chain issuer", chain),
+            ));
+        }
+    }
+    info!(
+        "driving {} TPS ({:?}) for {:?} with {} wallets",
+        config.target_tps,
+        config.ramp,
+        config.duration,
+        wallets.len()
+    );
+
+    let wallets: Vec<Arc<Wallet>> = wallets.into_iter().map(Arc::new).collect();
+    let (idle_tx, mut idle_rx) = mpsc::unbounded_channel::<usize>();
+    for i in 0..wallets.len() {
+        idle_tx.send(i).expect("idle receiver dropped");
+    }
+    let report = Arc::new(Mutex::new(Report::default()));
+
+    let started = Instant::now();
+    let mut due: f64 = 0.0;
+    let mut n: u64 = 0;
+    while started.elapsed() < config.duration {
+        sleep(TICK).await;
+        due += config.ramp.tps(config.target_tps, started.elapsed()) * TICK.as_secs_f64();
+
+        while due >= 1.0 {
+            due -= 1.0;
+            let idx = match idle_rx.try_recv() {
+                Ok(idx) => idx,
+                Err(_) => {
+                    report.lock().unwrap().skipped += 1;
+                    continue;
+                }
+            };
+            let chain = config.mix.pick(n);
+            n += 1;
+
+            let issuer = wallets[idx].get(&chain).cloned().unwrap();
+            let (report, idle_tx) = (report.clone(), idle_tx.clone());
+            let confirm_timeout = config.confirm_timeout;
+            tokio::spawn(async move {
+                // the latency only counts from the issue to the acceptance,
+                // not the UTXO (or nonce) fetch and signing before it
+                let res = async {
+                    let tx = within(confirm_timeout, "not prepared", issuer.prepare()).await?;
+                    let issued = Instant::now();
+                    within(confirm_timeout, "not accepted", issuer.issue(tx)).await?;
+                    Ok::<Duration, Error>(issued.elapsed())
+                }
+                .await;
+                {
+                    let mut report = report.lock().unwrap();
+                    let r = report.chains.entry(chain).or_default();
+                    r.issued += 1;
+                    match res {
+                        Ok(latency) => {
+                            r.accepted += 1;
+                            r.latency.record(latency);
+                        }
+                        Err(e) => {
+                            let failure = classify(&e);
+                            warn!("{}-chain tx failed ({}): {}", chain, failure, e);
+                            *r.failures.entry(failure).or_default() += 1;
+                        }
+                    }
+                }
+                let _ = idle_tx.send(idx);
+            });
+        }
+    }
+
+    // every wallet returns to the idle queue once its transaction completes
+    info!("waiting for the in-flight transactions");
+    for _ in 0..wallets.len() {
+        idle_rx
+            .recv()
+            .await
+            .ok_or_else(|| Error::new(ErrorKind::Other, "idle wallet queue closed"))?;
+    }
+
+    let mut report = report.lock().unwrap().clone();
+    report.elapsed = started.elapsed();
+    Ok(report)
+}
+
+/// Fails with "ErrorKind::TimedOut" if "fut" does not complete within "timeout".
+async fn within<T>(
+    timeout: Duration,
+    what: &str,
+    fut: impl std::future::Future<Output = io::Result<T>>,
+) -> io::Result<T> {
+    match tokio::time::timeout(timeout, fut).await {
+        Ok(res) => res,
+        Err(_) => Err(Error::new(
+            ErrorKind::TimedOut,
+            format!("{} within {:?}", what, timeout),
+        )),
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-api --lib -- load::test_config --exact --show-output
+#[test]
+fn test_config() {
+    let mix = Mix::from_str("x=1, p=0, c=2").unwrap();
+    assert_eq!(mix.chains(), vec![Chain::X, Chain::C]);
+    let picked: Vec<Chain> = (0..6).map(|n| mix.pick(n)).collect();
+    assert_eq!(
+        picked,
+        vec![Chain::X, Chain::C, Chain::C, Chain::X, Chain::C, Chain::C]
+    );
+    assert!(Mix::from_str("x=0").is_err());
+    assert!(Mix::from_str("y=1").is_err());
+
+    let x_chain_id =
+        ids::Id::from_str("2oYMBNV4eNHyqk2fjjV5nVQLDbtmNJzq5s3qs3Lo6ftnC6FByM").unwrap();
+    let c_chain_id =
+        ids::Id::from_str("2q9e4r6Mu3U68nU1fYjgbR6JvwrRx36CohpAX5UQxse55x1Q5").unwrap();
+    let mut aliaser = ids::Aliaser::new();
+    aliaser.alias(x_chain_id, "X").unwrap();
+    aliaser.alias(x_chain_id, "avm").unwrap();
+    aliaser.alias(c_chain_id, "C").unwrap();
+    let mix = Mix::resolve(
+        "avm=1, 2q9e4r6Mu3U68nU1fYjgbR6JvwrRx36CohpAX5UQxse55x1Q5=2",
+        &aliaser,
+    )
+    .unwrap();
+    assert_eq!(mix.chains(), vec![Chain::X, Chain::C]);
+    assert_eq!(
+        Mix::resolve("x=1,c=1", &aliaser).unwrap().chains(),
+        vec![Chain::X, Chain::C]
+    );
+    assert!(Mix::resolve("y=1", &aliaser).is_err());
+    // registered, but not a primary chain
+    aliaser.alias(ids::Id::empty(), "subnet-evm").unwrap();
+    assert!(Mix::resolve("subnet-evm=1", &aliaser).is_err());
+
+    let target = 100.0;
+    assert_eq!(
+        Ramp::from_str("constant")
+            .unwrap()
+            .tps(target, Duration::ZERO),
+        target
+    );
+    let linear = Ramp::from_str("linear:10").unwrap();
+    assert_eq!(linear.tps(target, Duration::from_secs(5)), 50.0);
+    assert_eq!(linear.tps(target, Duration::from_secs(20)), target);
+    let step = Ramp::from_str("step:4:10").unwrap();
+    assert_eq!(step.tps(target, Duration::ZERO), 25.0);
+    assert_eq!(step.tps(target, Duration::from_secs(25)), 75.0);
+    assert_eq!(step.tps(target, Duration::from_secs(100)), target);
+    assert!(Ramp::from_str("linear").is_err());
+
+    assert_eq!(
+        classify(&Error::new(ErrorKind::TimedOut, "deadline has elapsed")),
+        Failure::Timeout
+    );
+    assert_eq!(
+        classify(
+            &avalanche_types::api::jsonrpc::ResponseError {
+                code: -32000,
+                message: String::from("nonce too low"),
+            }
+            .to_io_error("eth_sendRawTransaction")
+        ),
+        Failure::Conflict
+    );
+    assert_eq!(
+        classify(&Error::new(
+            ErrorKind::InvalidInput,
+            "insufficient funds for asset (needed 2, available 1)"
+        )),
+        Failure::InsufficientFunds
+    );
+    assert_eq!(
+        classify(
+            &avalanche_types::api::jsonrpc::ResponseError {
+                code: -32000,
+                message: String::from("invalid tx"),
+            }
+            .to_io_error("avm.issueTx")
+        ),
+        Failure::Api
+    );
+    assert_eq!(
+        classify(&Error::new(
+            ErrorKind::Other,
+            "avm.issueTx failed (code -32000, invalid tx)"
+        )),
+        Failure::Other
+    );
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-api --lib -- load::test_run --exact --show-output
+#[test]
+fn test_run() {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    struct Counter(AtomicU64);
+
+    #[async_trait]
+    impl Issuer for Counter {
+        async fn prepare(&self) -> io::Result<Prepared> {
+            // every third transaction runs out of funds
+            let n = self.0.fetch_add(1, Ordering::SeqCst);
+            if n % 3 == 2 {
+                return Err(Error::new(ErrorKind::Other, "insufficient funds"));
+            }
+            Ok(Prepared {
+                id: n.to_string(),
+                encoded: String::new(),
+            })
+        }
+
+        async fn issue(&self, _tx: Prepared) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let counter: Arc<dyn Issuer> = Arc::new(Counter(AtomicU64::new(0)));
+    let wallets: Vec<Wallet> = (0..4)
+        .map(|_| HashMap::from([(Chain::X, counter.clone())]))
+        .collect();
+    let config = Config {
+        target_tps: 50.0,
+        duration: Duration::from_secs(1),
+        ramp: Ramp::Constant,
+        mix: Mix::from_str("x=1").unwrap(),
+        confirm_timeout: Duration::from_secs(5),
+    };
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let report = rt.block_on(run(&config, wallets.clone())).unwrap();
+    let x = report.chains.get(&Chain::X).unwrap();
+    assert!(x.issued > 0);
+    assert_eq!(
+        x.issued,
+        x.accepted + x.failures[&Failure::InsufficientFunds]
+    );
+    assert_eq!(x.latency.count(), x.accepted);
+
+    let no_timeout = Config {
+        confirm_timeout: Duration::ZERO,
+        ..config.clone()
+    };
+    assert!(no_timeout.validate().is_err());
+
+    let config = Config {
+        mix: Mix::from_str("c=1").unwrap(),
+        ..config
+    };
+    assert!(rt.block_on(run(&config, wallets)).is_err());
+}
@@ -3,7 +3,7 @@ use std::{io, sync::Arc};
 use avalanche_types::metrics::avalanchego::RawMetrics;
 use aws_sdk_cloudwatch::model::{Dimension, MetricDatum, StandardUnit};
 use aws_smithy_types::DateTime as SmithyDateTime;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use log::info;
 
 use utils::{http, prometheus};
@@ -1579,14 +1579,22 @@ pub fn to_cw_metric_data(cur: &RawMetrics, prev: Option<RawMetrics>) -> Vec<Metr
 /// ref. https://tokio.rs/tokio/tutorial/spawning
 pub async fn get(url: Arc<String>) -> io::Result<RawMetrics> {
     let ts = Utc::now();
+    let s = scrape(url.as_str()).await?;
+    Ok(to_raw_metrics(ts, &s))
+}
 
-    let joined = http::join_uri(url.as_str(), "ext/metrics")?;
+/// Fetches and parses all samples of the node "/ext/metrics" endpoint.
+pub async fn scrape(url: &str) -> io::Result<prometheus::Scrape> {
+    let joined = http::join_uri(url, "ext/metrics")?;
     info!("checking for {:?}", joined);
 
-    let rb = http::get_non_tls(url.as_str(), "ext/metrics").await?;
-    let s = prometheus::Scrape::from_bytes(&rb)?;
+    let rb = http::get_non_tls(url, "ext/metrics").await?;
+    prometheus::Scrape::from_bytes(&rb)
+}
 
-    Ok(RawMetrics {
+/// Picks the metrics published to CloudWatch from the scrape.
+pub fn to_raw_metrics(ts: DateTime<Utc>, s: &prometheus::Scrape) -> RawMetrics {
+    RawMetrics {
         ts,
 
         // Network metrics.
@@ -2405,7 +2413,29 @@ pub async fn get(url: Arc<String>) -> io::Result<RawMetrics> {
             .value
             .to_f64(),
         ),
-    })
+    }
+}
+
+/// Returns the number of blocks fetched while bootstrapping the chain
+/// (e.g., "avalanche_P_bs_fetched"), or "None" if not reported.
+pub fn bootstrap_fetched(s: &prometheus::Scrape, chain_alias: &str) -> Option<f64> {
+    let name = format!("avalanche_{}_bs_fetched", chain_alias);
+    s.metrics
+        .iter()
+        .find(|m| m.metric == name)
+        .map(|m| m.value.to_f64())
+}
+
+#[test]
+fn test_bootstrap_fetched() {
+    let s = prometheus::Scrape::from_bytes(
+        b"# TYPE avalanche_P_bs_fetched counter
+avalanche_P_bs_fetched 1234
+",
+    )
+    .unwrap();
+    assert_eq!(bootstrap_fetched(&s, "P"), Some(1234.0));
+    assert_eq!(bootstrap_fetched(&s, "C"), None);
 }
 
 pub async fn spawn_get(u: &str) -> io::Result<RawMetrics> {
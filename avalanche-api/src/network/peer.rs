@@ -0,0 +1,523 @@
+use std::{
+    fs,
+    io::{self, BufReader, Error, ErrorKind},
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use log::{debug, info};
+use ring::signature::{self, UnparsedPublicKey, VerificationAlgorithm};
+use rustls::{
+    internal::msgs::handshake::DigitallySignedStruct, Certificate, ClientConfig,
+    HandshakeSignatureValid, PrivateKey, RootCertStore, ServerCertVerified, ServerCertVerifier,
+    Session, SignatureScheme, TLSError,
+};
+use rustls_pemfile::{certs, read_one, Item};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+    time::timeout,
+};
+use tokio_rustls::TlsConnector;
+
+use avalanche_types::{cert, ids, message};
+
+/// Version advertised to the peer, which must be compatible with
+/// the peer's minimum compatible version.
+/// ref. "avalanchego/version.CurrentApp"
+pub const DEFAULT_VERSION_STR: &str = "avalanche/1.7.10";
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Remote node information from the handshake.
+#[derive(Debug, Clone)]
+pub struct PeerInfo {
+    /// Node ID derived from the peer's staking certificate.
+    pub node_id: ids::NodeId,
+    /// e.g., "avalanche/1.7.10"
+    pub version: String,
+    /// IP the peer advertises for itself.
+    pub ip: SocketAddr,
+    /// Unix timestamp of the peer's clock in seconds.
+    pub peer_time: u64,
+    pub tracked_subnets: Vec<ids::Id>,
+    /// Uptime percentage from the "Pong" reply, which is this client's
+    /// uptime as observed by the peer, thus mostly useful as a liveness check.
+    pub observed_uptime: u8,
+    /// Round-trip time of the "Ping" and "Pong".
+    pub latency: Duration,
+    /// Peers gossiped by the peer during the handshake, if any.
+    pub peers: Vec<message::ClaimedIpPort>,
+}
+
+/// Dials the staking port of avalanchego nodes with the client TLS
+/// certificate, as the nodes authenticate each other with the staking
+/// certificates rather than a certificate authority.
+/// ref. "avalanchego/network.network.Dispatch"
+/// ref. "avalanchego/network/peer.Start"
+pub struct Connector {
+    tls_config: Arc<ClientConfig>,
+    key_pem: Vec<u8>,
+    pub network_id: u32,
+    /// IP advertised to the peer, signed with the staking key.
+    pub ip: SocketAddr,
+    pub version_str: String,
+    pub timeout: Duration,
+}
+
+impl Connector {
+    /// Loads the PEM-encoded staking certificate and key (e.g., "cert::generate").
+    pub fn new(cert_path: &str, key_path: &str, network_id: u32) -> io::Result<Self> {
+        info!(
+            "loading staking cert {} and key {} for peer connections",
+            cert_path, key_path
+        );
+        let cert_pem = fs::read(cert_path)?;
+        let key_pem = fs::read(key_path)?;
+
+        let cert_chain: Vec<Certificate> = certs(&mut BufReader::new(&cert_pem[..]))?
+            .into_iter()
+            .map(Certificate)
+            .collect();
+        if cert_chain.is_empty() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("found no cert in {}", cert_path),
+            ));
+        }
+        let key_der = match read_one(&mut BufReader::new(&key_pem[..]))? {
+            Some(Item::PKCS8Key(der)) | Some(Item::RSAKey(der)) | Some(Item::ECKey(der)) => der,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("found no key in {}", key_path),
+                ));
+            }
+        };
+
+        let mut tls_config = ClientConfig::new();
+        tls_config
+            .dangerous()
+            .set_certificate_verifier(Arc::new(StakingCertVerifier));
+        tls_config
+            .set_single_client_cert(cert_chain, PrivateKey(key_der))
+            .map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid staking cert and key ({})", e),
+                )
+            })?;
+
+        Ok(Self {
+            tls_config: Arc::new(tls_config),
+            key_pem,
+            network_id,
+            ip: SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+            version_str: String::from(DEFAULT_VERSION_STR),
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Connects to the peer's staking port, exchanges the version messages,
+    /// and pings the peer, within the timeout.
+    pub async fn handshake(&self, addr: SocketAddr) -> io::Result<PeerInfo> {
+        timeout(self.timeout, self.handshake_inner(addr))
+            .await
+            .map_err(|_| {
+                Error::new(
+                    ErrorKind::TimedOut,
+                    format!("handshake with {} timed out after {:?}", addr, self.timeout),
+                )
+            })?
+    }
+
+    async fn handshake_inner(&self, addr: SocketAddr) -> io::Result<PeerInfo> {
+        info!("connecting to peer {}", addr);
+        let tcp = TcpStream::connect(addr).await?;
+
+        // the server name is never checked by "StakingCertVerifier"
+        let server_name = webpki::DNSNameRef::try_from_ascii_str("avalanchego")
+            .map_err(|e| Error::new(ErrorKind::Other, format!("invalid server name ({})", e)))?;
+        let connector = TlsConnector::from(self.tls_config.clone());
+        let mut stream = connector.connect(server_name, tcp).await?;
+
+        let node_id = {
+            let (_, session) = stream.get_ref();
+            let peer_certs = session.get_peer_certificates().unwrap_or_default();
+            let leaf = peer_certs.first().ok_or_else(|| {
+                Error::new(ErrorKind::InvalidData, "peer presented no certificate")
+            })?;
+            ids::NodeId::from_cert_raw(&leaf.0)?
+        };
+        info!("connected to peer {} with node ID {}", addr, node_id);
+
+        let now = unix_now();
+        let sig = cert::sign_with_key_pem(&self.key_pem, &message::signed_ip_bytes(&self.ip, now))?;
+        let version = message::Message::Version(message::Version {
+            network_id: self.network_id,
+            node_id: 0,
+            my_time: now,
+            ip: self.ip,
+            version_str: self.version_str.clone(),
+            version_time: now,
+            sig,
+            tracked_subnets: Vec::new(),
+        });
+        write_message(&mut stream, &version).await?;
+
+        let mut peers: Vec<message::ClaimedIpPort> = Vec::new();
+        let peer_version = loop {
+            match read_message(&mut stream).await? {
+                Some(message::Message::Version(v)) => break v,
+                Some(message::Message::PeerList(v)) => peers.extend(v.peers),
+                _ => {}
+            }
+        };
+        if peer_version.network_id != self.network_id {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "peer network ID {} != {}",
+                    peer_version.network_id, self.network_id
+                ),
+            ));
+        }
+
+        let started = SystemTime::now();
+        write_message(&mut stream, &message::Message::Ping).await?;
+        let pong = loop {
+            match read_message(&mut stream).await? {
+                Some(message::Message::Pong(v)) => break v,
+                Some(message::Message::PeerList(v)) => peers.extend(v.peers),
+                _ => {}
+            }
+        };
+        let latency = started.elapsed().unwrap_or_default();
+
+        Ok(PeerInfo {
+            node_id,
+            version: peer_version.version_str,
+            ip: peer_version.ip,
+            peer_time: peer_version.my_time,
+            tracked_subnets: peer_version.tracked_subnets,
+            observed_uptime: pong.uptime,
+            latency,
+            peers,
+        })
+    }
+}
+
+/// Accepts any server certificate, as the staking certificates are
+/// self-signed and the peer is identified by the node ID derived from it.
+/// The handshake signatures are still verified against the certificate key.
+/// ref. "avalanchego/network.tlsConfig" with "InsecureSkipVerify"
+struct StakingCertVerifier;
+
+impl ServerCertVerifier for StakingCertVerifier {
+    fn verify_server_cert(
+        &self,
+        _roots: &RootCertStore,
+        _presented_certs: &[Certificate],
+        _dns_name: webpki::DNSNameRef<'_>,
+        _ocsp_response: &[u8],
+    ) -> Result<ServerCertVerified, TLSError> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TLSError> {
+        verify_handshake_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TLSError> {
+        verify_handshake_signature(message, cert, dss)
+    }
+}
+
+/// Verifies the handshake signature with the public key of the certificate.
+/// The default verifier uses "webpki", which rejects the certificates
+/// without X509 extensions (e.g., "cert::generate_pem").
+fn verify_handshake_signature(
+    message: &[u8],
+    cert: &Certificate,
+    dss: &DigitallySignedStruct,
+) -> Result<HandshakeSignatureValid, TLSError> {
+    let alg: &dyn VerificationAlgorithm = match dss.scheme {
+        SignatureScheme::ECDSA_NISTP256_SHA256 => &signature::ECDSA_P256_SHA256_ASN1,
+        SignatureScheme::ECDSA_NISTP384_SHA384 => &signature::ECDSA_P384_SHA384_ASN1,
+        SignatureScheme::ED25519 => &signature::ED25519,
+        SignatureScheme::RSA_PKCS1_SHA256 => &signature::RSA_PKCS1_2048_8192_SHA256,
+        SignatureScheme::RSA_PKCS1_SHA384 => &signature::RSA_PKCS1_2048_8192_SHA384,
+        SignatureScheme::RSA_PKCS1_SHA512 => &signature::RSA_PKCS1_2048_8192_SHA512,
+        SignatureScheme::RSA_PSS_SHA256 => &signature::RSA_PSS_2048_8192_SHA256,
+        SignatureScheme::RSA_PSS_SHA384 => &signature::RSA_PSS_2048_8192_SHA384,
+        SignatureScheme::RSA_PSS_SHA512 => &signature::RSA_PSS_2048_8192_SHA512,
+        scheme => {
+            return Err(TLSError::General(format!(
+                "unsupported signature scheme {:?}",
+                scheme
+            )));
+        }
+    };
+    let public_key = spki_public_key(&cert.0)
+        .ok_or_else(|| TLSError::General(String::from("invalid certificate public key")))?;
+    UnparsedPublicKey::new(alg, public_key)
+        .verify(message, &dss.sig.0)
+        .map_err(|_| TLSError::General(String::from("invalid handshake signature")))?;
+    Ok(HandshakeSignatureValid::assertion())
+}
+
+/// Returns the key bytes of "subjectPublicKey" in the DER-encoded certificate,
+/// as expected by "ring" (e.g., the EC point or the "RSAPublicKey").
+/// ref. https://datatracker.ietf.org/doc/html/rfc5280#section-4.1
+fn spki_public_key(cert_der: &[u8]) -> Option<&[u8]> {
+    const TAG_SEQUENCE: u8 = 0x30;
+    const TAG_INTEGER: u8 = 0x02;
+    const TAG_BIT_STRING: u8 = 0x03;
+    const TAG_VERSION: u8 = 0xa0;
+
+    let (cert, _) = read_der(cert_der, TAG_SEQUENCE)?;
+    let (tbs, _) = read_der(cert, TAG_SEQUENCE)?;
+
+    // optional "[0] EXPLICIT Version"
+    let tbs = match read_der(tbs, TAG_VERSION) {
+        Some((_, rest)) => rest,
+        None => tbs,
+    };
+    let (_, rest) = read_der(tbs, TAG_INTEGER)?; // serial number
+    let (_, rest) = read_der(rest, TAG_SEQUENCE)?; // signature algorithm
+    let (_, rest) = read_der(rest, TAG_SEQUENCE)?; // issuer
+    let (_, rest) = read_der(rest, TAG_SEQUENCE)?; // validity
+    let (_, rest) = read_der(rest, TAG_SEQUENCE)?; // subject
+    let (spki, _) = read_der(rest, TAG_SEQUENCE)?;
+
+    let (_, rest) = read_der(spki, TAG_SEQUENCE)?; // algorithm
+    let (bits, _) = read_der(rest, TAG_BIT_STRING)?;
+
+    // first byte is the number of unused bits
+    match bits.split_first() {
+        Some((0, key)) => Some(key),
+        _ => None,
+    }
+}
+
+/// Reads the DER value of the expected tag, returning the value and the rest.
+fn read_der(b: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+    if b.len() < 2 || b[0] != tag {
+        return None;
+    }
+    let (len, header_len): (usize, usize) = match b[1] {
+        n if n < 0x80 => (n as usize, 2),
+        0x81 => (*b.get(2)? as usize, 3),
+        0x82 => (u16::from_be_bytes([*b.get(2)?, *b.get(3)?]) as usize, 4),
+        0x83 => (
+            u32::from_be_bytes([0, *b.get(2)?, *b.get(3)?, *b.get(4)?]) as usize,
+            5,
+        ),
+        _ => return None,
+    };
+    let end = header_len.checked_add(len)?;
+    if end > b.len() {
+        return None;
+    }
+    Some((&b[header_len..end], &b[end..]))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Writes the message prefixed with its "u32" length.
+/// ref. "avalanchego/network/peer.Peer.writeMessages"
+async fn write_message<S>(stream: &mut S, msg: &message::Message) -> io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    let b = msg.pack()?;
+    stream.write_all(&(b.len() as u32).to_be_bytes()).await?;
+    stream.write_all(&b).await?;
+    stream.flush().await
+}
+
+/// Reads the length-prefixed message, returning "None" for the ops
+/// that are not supported by "message::Message" (e.g., "Put").
+/// ref. "avalanchego/network/peer.Peer.readMessages"
+async fn read_message<S>(stream: &mut S) -> io::Result<Option<message::Message>>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut len_bytes = [0u8; 4];
+    stream.read_exact(&mut len_bytes).await?;
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    if len == 0 || len > message::DEFAULT_MAX_MESSAGE_SIZE {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid message length {}", len),
+        ));
+    }
+
+    let mut b = vec![0u8; len];
+    stream.read_exact(&mut b).await?;
+    match message::Message::unpack(&b) {
+        Ok(msg) => Ok(Some(msg)),
+        Err(e) if e.kind() == ErrorKind::Unsupported => {
+            debug!("skipping message op {} ({} bytes)", b[0], len);
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Serves the handshake as an avalanchego node would, to test the connector
+/// without a running node.
+#[cfg(test)]
+async fn serve_handshake(
+    listener: tokio::net::TcpListener,
+    staking_cert: avalanche_types::cert::StakingCert,
+) -> ids::NodeId {
+    use rustls::{ClientCertVerified, ClientCertVerifier, DistinguishedNames, ServerConfig};
+    use tokio_rustls::TlsAcceptor;
+
+    struct AnyClientCert;
+    impl ClientCertVerifier for AnyClientCert {
+        fn client_auth_root_subjects(
+            &self,
+            _sni: Option<&webpki::DNSName>,
+        ) -> Option<DistinguishedNames> {
+            Some(DistinguishedNames::new())
+        }
+        fn verify_client_cert(
+            &self,
+            _presented_certs: &[Certificate],
+            _sni: Option<&webpki::DNSName>,
+        ) -> Result<ClientCertVerified, TLSError> {
+            Ok(ClientCertVerified::assertion())
+        }
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &Certificate,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, TLSError> {
+            verify_handshake_signature(message, cert, dss)
+        }
+    }
+
+    let cert_chain = certs(&mut BufReader::new(staking_cert.cert_pem.as_bytes()))
+        .unwrap()
+        .into_iter()
+        .map(Certificate)
+        .collect();
+    let key_der = match read_one(&mut BufReader::new(staking_cert.key_pem.as_bytes())).unwrap() {
+        Some(Item::PKCS8Key(der)) => der,
+        _ => panic!("unexpected key PEM"),
+    };
+    let mut tls_config = ServerConfig::new(Arc::new(AnyClientCert));
+    tls_config
+        .set_single_cert(cert_chain, PrivateKey(key_der))
+        .unwrap();
+
+    let (tcp, _) = listener.accept().await.unwrap();
+    let mut stream = TlsAcceptor::from(Arc::new(tls_config))
+        .accept(tcp)
+        .await
+        .unwrap();
+    let client_node_id = {
+        let (_, session) = stream.get_ref();
+        let peer_certs = session.get_peer_certificates().unwrap();
+        ids::NodeId::from_cert_raw(&peer_certs[0].0).unwrap()
+    };
+
+    let client_version = match read_message(&mut stream).await.unwrap() {
+        Some(message::Message::Version(v)) => v,
+        v => panic!("unexpected message {:?}", v),
+    };
+    let version = message::Message::Version(message::Version {
+        network_id: client_version.network_id,
+        node_id: 0,
+        my_time: unix_now(),
+        ip: "127.0.0.1:9651".parse().unwrap(),
+        version_str: String::from("avalanche/1.7.10"),
+        version_time: unix_now(),
+        sig: vec![1, 2, 3],
+        tracked_subnets: Vec::new(),
+    });
+    write_message(&mut stream, &version).await.unwrap();
+
+    // gossips the compressed peer list before replying to the ping,
+    // and unsupported ops (e.g., "Put") are skipped by the client
+    let peer_list = message::Message::PeerList(message::PeerList {
+        peers: vec![message::ClaimedIpPort {
+            cert: vec![1, 2, 3],
+            ip: "10.0.0.1:9651".parse().unwrap(),
+            timestamp: unix_now(),
+            signature: vec![4, 5, 6],
+        }],
+    });
+    let b = peer_list.pack_with_compression(true).unwrap();
+    stream
+        .write_all(&(b.len() as u32).to_be_bytes())
+        .await
+        .unwrap();
+    stream.write_all(&b).await.unwrap();
+    stream.write_all(&[0, 0, 0, 1, 13]).await.unwrap();
+
+    assert_eq!(
+        read_message(&mut stream).await.unwrap(),
+        Some(message::Message::Ping)
+    );
+    let pong = message::Message::Pong(message::Pong { uptime: 0 });
+    write_message(&mut stream, &pong).await.unwrap();
+
+    client_node_id
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-api --lib -- network::peer::test_handshake --exact --show-output
+#[tokio::test]
+async fn test_handshake() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let server_cert = cert::generate_pem().unwrap();
+    let server_node_id = server_cert.node_id;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(serve_handshake(listener, server_cert));
+
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let key_path = tmp_dir.path().join("staking.key");
+    let cert_path = tmp_dir.path().join("staking.crt");
+    let client_node_id = cert::generate(
+        key_path.as_os_str().to_str().unwrap(),
+        cert_path.as_os_str().to_str().unwrap(),
+    )
+    .unwrap();
+
+    let connector = Connector::new(
+        cert_path.as_os_str().to_str().unwrap(),
+        key_path.as_os_str().to_str().unwrap(),
+        1000,
+    )
+    .unwrap();
+    let peer = connector.handshake(addr).await.unwrap();
+    assert_eq!(peer.node_id, server_node_id);
+    assert_eq!(peer.version, "avalanche/1.7.10");
+    assert_eq!(peer.ip.to_string(), "127.0.0.1:9651");
+    assert_eq!(peer.observed_uptime, 0);
+    assert_eq!(peer.peers.len(), 1);
+    assert_eq!(peer.peers[0].ip.to_string(), "10.0.0.1:9651");
+
+    assert_eq!(server.await.unwrap(), client_node_id);
+}
@@ -1,12 +1,11 @@
-use std::{
-    collections::HashMap,
-    io::{self, Error, ErrorKind},
-    string::String,
-};
+use std::{collections::HashMap, io, string::String};
 
 use log::info;
 
-use avalanche_types::api::{jsonrpc, platformvm};
+use avalanche_types::{
+    api::{jsonrpc, platformvm},
+    errors,
+};
 use utils::http;
 
 /// e.g., "platform.getHeight" on "http://[ADDR]:9650" and "/ext/bc/P" path.
@@ -22,14 +21,14 @@ pub async fn get_height(url: &str) -> io::Result<platformvm::GetHeightResponse>
     data.params = Some(params);
 
     let d = data.encode_json()?;
-    let rb = http::post_non_tls(url, "/ext/bc/P", &d).await?;
+    let rb = http::post_json(url, "/ext/bc/P", &d, http::Idempotency::Idempotent).await?;
     let resp: platformvm::RawGetHeightResponse = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     let converted = resp.convert();
@@ -50,14 +49,14 @@ pub async fn get_balance(url: &str, paddr: &str) -> io::Result<platformvm::GetBa
     data.params = Some(params);
 
     let d = data.encode_json()?;
-    let rb = http::post_non_tls(url, "/ext/bc/P", &d).await?;
+    let rb = http::post_json(url, "/ext/bc/P", &d, http::Idempotency::Idempotent).await?;
     let resp: platformvm::RawGetBalanceResponse = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     let converted = resp.convert()?;
@@ -81,21 +80,21 @@ pub async fn get_utxos(url: &str, paddr: &str) -> io::Result<platformvm::GetUtxo
     data.params = Some(params);
 
     let d = data.encode_json()?;
-    let rb = http::post_non_tls(url, "/ext/bc/P", &d).await?;
+    let rb = http::post_json(url, "/ext/bc/P", &d, http::Idempotency::Idempotent).await?;
     let resp: platformvm::RawGetUtxosResponse = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     let converted = resp.convert()?;
     Ok(converted)
 }
 
-/// e.g., "platform.getBalance" on "http://[ADDR]:9650" and "/ext/bc/P" path.
+/// e.g., "platform.getCurrentValidators" on "http://[ADDR]:9650" and "/ext/bc/P" path.
 /// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgetcurrentvalidators
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm#APIPrimaryValidator
 pub async fn get_current_validators(
@@ -111,14 +110,195 @@ pub async fn get_current_validators(
     data.params = Some(params);
 
     let d = data.encode_json()?;
-    let rb = http::post_non_tls(url, "/ext/bc/P", &d).await?;
+    let rb = http::post_json(url, "/ext/bc/P", &d, http::Idempotency::Idempotent).await?;
     let resp: platformvm::RawGetCurrentValidatorsResponse = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
+        }
+    };
+    let converted = resp.convert()?;
+    Ok(converted)
+}
+
+/// e.g., "platform.getPendingValidators" on "http://[ADDR]:9650" and "/ext/bc/P" path.
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgetpendingvalidators
+pub async fn get_pending_validators(
+    url: &str,
+) -> io::Result<platformvm::GetPendingValidatorsResponse> {
+    let joined = http::join_uri(url, "/ext/bc/P")?;
+    info!("getting pending validators via {:?}", joined);
+
+    let mut data = jsonrpc::Data::default();
+    data.method = String::from("platform.getPendingValidators");
+
+    let params = HashMap::new();
+    data.params = Some(params);
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, "/ext/bc/P", &d, http::Idempotency::Idempotent).await?;
+    let resp: platformvm::RawGetPendingValidatorsResponse = match serde_json::from_slice(&rb) {
+        Ok(p) => p,
+        Err(e) => {
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
+        }
+    };
+    let converted = resp.convert()?;
+    Ok(converted)
+}
+
+/// e.g., "platform.sampleValidators" on "http://[ADDR]:9650" and "/ext/bc/P" path.
+/// Samples "size" validators from the primary network.
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformsamplevalidators
+pub async fn sample_validators(
+    url: &str,
+    size: u16,
+) -> io::Result<platformvm::SampleValidatorsResponse> {
+    let joined = http::join_uri(url, "/ext/bc/P")?;
+    info!("sampling {} validators via {:?}", size, joined);
+
+    let mut data = jsonrpc::Data::default();
+    data.method = String::from("platform.sampleValidators");
+
+    let mut params = HashMap::new();
+    params.insert(String::from("size"), format!("{}", size));
+    data.params = Some(params);
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, "/ext/bc/P", &d, http::Idempotency::Idempotent).await?;
+    let resp: platformvm::RawSampleValidatorsResponse = match serde_json::from_slice(&rb) {
+        Ok(p) => p,
+        Err(e) => {
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
+        }
+    };
+    let converted = resp.convert()?;
+    Ok(converted)
+}
+
+/// e.g., "platform.issueTx" on "http://[ADDR]:9650" and "/ext/bc/P" path.
+/// "tx" is the signed transaction bytes in the "encoding" (e.g., "cb58", "hex").
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformissuetx
+pub async fn issue_tx(
+    url: &str,
+    tx: &str,
+    encoding: &str,
+) -> io::Result<platformvm::IssueTxResponse> {
+    let joined = http::join_uri(url, "/ext/bc/P")?;
+    info!("issuing tx with {} encoding via {:?}", encoding, joined);
+
+    let mut data = jsonrpc::Data::default();
+    data.method = String::from("platform.issueTx");
+
+    let mut params = HashMap::new();
+    params.insert(String::from("tx"), tx.to_string());
+    params.insert(String::from("encoding"), encoding.to_string());
+    data.params = Some(params);
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, "/ext/bc/P", &d, http::Idempotency::NonIdempotent).await?;
+    let resp: platformvm::IssueTxResponse = match serde_json::from_slice(&rb) {
+        Ok(p) => p,
+        Err(e) => {
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
+        }
+    };
+    Ok(resp)
+}
+
+/// e.g., "platform.getTxStatus" on "http://[ADDR]:9650" and "/ext/bc/P" path.
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgettxstatus
+pub async fn get_tx_status(url: &str, tx_id: &str) -> io::Result<platformvm::GetTxStatusResponse> {
+    let joined = http::join_uri(url, "/ext/bc/P")?;
+    info!("getting tx status for {} via {:?}", tx_id, joined);
+
+    let mut data = jsonrpc::Data::default();
+    data.method = String::from("platform.getTxStatus");
+
+    let mut params = HashMap::new();
+    params.insert(String::from("txID"), tx_id.to_string());
+    data.params = Some(params);
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, "/ext/bc/P", &d, http::Idempotency::Idempotent).await?;
+    let resp: platformvm::GetTxStatusResponse = match serde_json::from_slice(&rb) {
+        Ok(p) => p,
+        Err(e) => {
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
+        }
+    };
+    Ok(resp)
+}
+
+/// e.g., "platform.getTx" on "http://[ADDR]:9650" and "/ext/bc/P" path.
+/// Fetches the signed tx bytes in "hex" encoding.
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgettx
+pub async fn get_tx(url: &str, tx_id: &str) -> io::Result<platformvm::GetTxResponse> {
+    let joined = http::join_uri(url, "/ext/bc/P")?;
+    info!("getting tx {} via {:?}", tx_id, joined);
+
+    let mut data = jsonrpc::Data::default();
+    data.method = String::from("platform.getTx");
+
+    let mut params = HashMap::new();
+    params.insert(String::from("txID"), tx_id.to_string());
+    params.insert(String::from("encoding"), String::from("hex"));
+    data.params = Some(params);
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, "/ext/bc/P", &d, http::Idempotency::Idempotent).await?;
+    let resp: platformvm::GetTxResponse = match serde_json::from_slice(&rb) {
+        Ok(p) => p,
+        Err(e) => {
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
+        }
+    };
+    Ok(resp)
+}
+
+/// e.g., "platform.getRewardUTXOs" on "http://[ADDR]:9650" and "/ext/bc/P" path.
+/// Returns the UTXOs paid to the staker of the add validator or delegator tx
+/// after its staking period, empty if the reward was not paid.
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgetrewardutxos
+pub async fn get_reward_utxos(url: &str, tx_id: &str) -> io::Result<platformvm::GetUtxosResponse> {
+    let joined = http::join_uri(url, "/ext/bc/P")?;
+    info!("getting reward UTXOs for {} via {:?}", tx_id, joined);
+
+    let mut data = jsonrpc::Data::default();
+    data.method = String::from("platform.getRewardUTXOs");
+
+    let mut params = HashMap::new();
+    params.insert(String::from("txID"), tx_id.to_string());
+    params.insert(String::from("encoding"), String::from("hex")); // don't use "cb58"
+    data.params = Some(params);
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, "/ext/bc/P", &d, http::Idempotency::Idempotent).await?;
+    let resp: platformvm::RawGetUtxosResponse = match serde_json::from_slice(&rb) {
+        Ok(p) => p,
+        Err(e) => {
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     let converted = resp.convert()?;
@@ -126,9 +306,6 @@ pub async fn get_current_validators(
 }
 
 // ref. https://github.com/ava-labs/avalanchego/blob/v1.7.9/wallet/chain/p/builder.go
+// subnet txs are built with "avalanche_types::platformvm::txs::SubnetBuilder"
 
-// TODO: create subnet tx
-// TODO: check p-chain tx
-// TODO: add subnet validator tx
-// TODO: create blockchain tx with genesis
 // TODO: add wallet
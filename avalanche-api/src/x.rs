@@ -1,12 +1,11 @@
-use std::{
-    collections::HashMap,
-    io::{self, Error, ErrorKind},
-    string::String,
-};
+use std::{collections::HashMap, io, string::String};
 
 use log::info;
 
-use avalanche_types::api::{avm, jsonrpc};
+use avalanche_types::{
+    api::{avm, jsonrpc, platformvm},
+    errors,
+};
 use utils::http;
 
 /// e.g., "avm.getBalance" on "http://[ADDR]:9650" and "/ext/bc/X" path.
@@ -24,14 +23,14 @@ pub async fn get_balance(url: &str, xaddr: &str) -> io::Result<avm::GetBalanceRe
     data.params = Some(params);
 
     let d = data.encode_json()?;
-    let rb = http::post_non_tls(url, "/ext/bc/X", &d).await?;
+    let rb = http::post_json(url, "/ext/bc/X", &d, http::Idempotency::Idempotent).await?;
     let resp: avm::RawGetBalanceResponse = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     let parsed = resp.convert()?;
@@ -54,16 +53,132 @@ pub async fn get_asset_description(
     data.params = Some(params);
 
     let d = data.encode_json()?;
-    let rb = http::post_non_tls(url, "ext/bc/X", &d).await?;
+    let rb = http::post_json(url, "ext/bc/X", &d, http::Idempotency::Idempotent).await?;
     let resp: avm::RawGetAssetDescriptionResponse = match serde_json::from_slice(&rb) {
         Ok(p) => p,
         Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to decode {}", e),
-            ));
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
+        }
+    };
+    let converted = resp.convert()?;
+    Ok(converted)
+}
+
+/// e.g., "avm.getAllBalances" on "http://[ADDR]:9650" and "/ext/bc/X" path.
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmgetallbalances
+pub async fn get_all_balances(url: &str, xaddr: &str) -> io::Result<avm::GetAllBalancesResponse> {
+    let joined = http::join_uri(url, "/ext/bc/X")?;
+    info!("getting all balances for {} via {:?}", xaddr, joined);
+
+    let mut data = jsonrpc::Data::default();
+    data.method = String::from("avm.getAllBalances");
+
+    let mut params = HashMap::new();
+    params.insert(String::from("address"), xaddr.to_string());
+    data.params = Some(params);
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, "/ext/bc/X", &d, http::Idempotency::Idempotent).await?;
+    let resp: avm::RawGetAllBalancesResponse = match serde_json::from_slice(&rb) {
+        Ok(p) => p,
+        Err(e) => {
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
+        }
+    };
+    let parsed = resp.convert()?;
+    Ok(parsed)
+}
+
+/// e.g., "avm.getUTXOs" on "http://[ADDR]:9650" and "/ext/bc/X" path.
+/// The request and reply share the same format as "platform.getUTXOs".
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmgetutxos
+pub async fn get_utxos(url: &str, xaddr: &str) -> io::Result<platformvm::GetUtxosResponse> {
+    let joined = http::join_uri(url, "/ext/bc/X")?;
+    info!("getting UTXOs for {} via {:?}", xaddr, joined);
+
+    let mut data = platformvm::DataForGetUtxos::default();
+    data.method = String::from("avm.getUTXOs");
+
+    let params = platformvm::GetUtxosRequest {
+        addresses: vec![xaddr.to_string()],
+        limit: 100,
+        encoding: String::from("hex"), // don't use "cb58"
+    };
+    data.params = Some(params);
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, "/ext/bc/X", &d, http::Idempotency::Idempotent).await?;
+    let resp: platformvm::RawGetUtxosResponse = match serde_json::from_slice(&rb) {
+        Ok(p) => p,
+        Err(e) => {
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
         }
     };
     let converted = resp.convert()?;
     Ok(converted)
 }
+
+/// e.g., "avm.getTxStatus" on "http://[ADDR]:9650" and "/ext/bc/X" path.
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmgettxstatus
+pub async fn get_tx_status(url: &str, tx_id: &str) -> io::Result<avm::GetTxStatusResponse> {
+    let joined = http::join_uri(url, "/ext/bc/X")?;
+    info!("getting tx status for {} via {:?}", tx_id, joined);
+
+    let mut data = jsonrpc::Data::default();
+    data.method = String::from("avm.getTxStatus");
+
+    let mut params = HashMap::new();
+    params.insert(String::from("txID"), tx_id.to_string());
+    data.params = Some(params);
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, "/ext/bc/X", &d, http::Idempotency::Idempotent).await?;
+    let resp: avm::GetTxStatusResponse = match serde_json::from_slice(&rb) {
+        Ok(p) => p,
+        Err(e) => {
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
+        }
+    };
+    Ok(resp)
+}
+
+/// e.g., "avm.issueTx" on "http://[ADDR]:9650" and "/ext/bc/X" path.
+/// "tx" is the signed transaction bytes in the "encoding" (e.g., "cb58", "hex").
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmissuetx
+pub async fn issue_tx(url: &str, tx: &str, encoding: &str) -> io::Result<avm::IssueTxResponse> {
+    let joined = http::join_uri(url, "/ext/bc/X")?;
+    info!("issuing tx with {} encoding via {:?}", encoding, joined);
+
+    let mut data = jsonrpc::Data::default();
+    data.method = String::from("avm.issueTx");
+
+    let mut params = HashMap::new();
+    params.insert(String::from("tx"), tx.to_string());
+    params.insert(String::from("encoding"), encoding.to_string());
+    data.params = Some(params);
+
+    let d = data.encode_json()?;
+    let rb = http::post_json(url, "/ext/bc/X", &d, http::Idempotency::NonIdempotent).await?;
+    let resp: avm::IssueTxResponse = match serde_json::from_slice(&rb) {
+        Ok(p) => p,
+        Err(e) => {
+            return Err(errors::Error::Decode {
+                message: e.to_string(),
+            }
+            .into());
+        }
+    };
+    Ok(resp)
+}
@@ -0,0 +1,246 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Error, ErrorKind},
+};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use avalanche_types::node;
+
+use crate::{host_port, Node, StorageNamespace};
+
+/// Defines the Elastic IPs and DNS records of the anchor nodes.
+/// "apply" allocates one Elastic IP per anchor node, and "avalanched"
+/// on the anchor node associates the unclaimed one (e.g., left by the
+/// terminated instance) before the node starts, so the replacement
+/// node keeps the same public IP and DNS name.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct AnchorEndpoints {
+    /// Set to true to allocate the Elastic IPs.
+    #[serde(default)]
+    pub elastic_ips: bool,
+    /// Route53 hosted zone to publish "anchor-<n>.<domain>" A records in.
+    /// If "None", no DNS record is published.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route53_hosted_zone_id: Option<String>,
+    /// Domain of the hosted zone (e.g., "aops.example.com").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    #[serde(default = "default_dns_ttl_seconds")]
+    pub dns_ttl_seconds: i64,
+}
+
+fn default_dns_ttl_seconds() -> i64 {
+    60
+}
+
+impl Default for AnchorEndpoints {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl AnchorEndpoints {
+    pub fn default() -> Self {
+        Self {
+            elastic_ips: true,
+            route53_hosted_zone_id: None,
+            domain: None,
+            dns_ttl_seconds: default_dns_ttl_seconds(),
+        }
+    }
+
+    /// Returns the tags that identify the Elastic IPs of the anchor nodes.
+    pub fn tags(id: &str) -> [(&str, &str); 2] {
+        [("ID", id), ("NODE_KIND", "anchor")]
+    }
+
+    /// Returns the DNS name of the anchor node with the index,
+    /// "None" if no domain is set.
+    pub fn dns_name(&self, index: u32) -> Option<String> {
+        self.domain
+            .as_ref()
+            .map(|domain| format!("anchor-{}.{}", index, domain.trim_end_matches('.')))
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        if self.route53_hosted_zone_id.is_some() != self.domain.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'route53_hosted_zone_id' and 'domain' must be set together",
+            ));
+        }
+        // the record would point to the instance public IP,
+        // which changes with the replacement
+        if self.route53_hosted_zone_id.is_some() && !self.elastic_ips {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'route53_hosted_zone_id' requires 'elastic_ips'",
+            ));
+        }
+        if self.dns_ttl_seconds <= 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("'dns_ttl_seconds' {} must be >0", self.dns_ttl_seconds),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_anchor_endpoints() {
+    let anchor_endpoints: AnchorEndpoints = serde_yaml::from_str(
+        "
+elastic_ips: true
+route53_hosted_zone_id: Z0123456789
+domain: aops.example.com.
+",
+    )
+    .unwrap();
+    assert_eq!(anchor_endpoints.dns_ttl_seconds, 60);
+    assert_eq!(
+        anchor_endpoints.dns_name(2).unwrap(),
+        "anchor-2.aops.example.com"
+    );
+    anchor_endpoints.validate().unwrap();
+
+    let mut invalid = anchor_endpoints.clone();
+    invalid.domain = None;
+    assert!(invalid.validate().is_err());
+    let mut invalid = anchor_endpoints;
+    invalid.elastic_ips = false;
+    assert!(invalid.validate().is_err());
+
+    assert!(AnchorEndpoints::default().dns_name(1).is_none());
+    assert!(AnchorEndpoints::default().validate().is_ok());
+}
+
+/// Heartbeat interval of the node registration.
+pub const REGISTRATION_INTERVAL_SECONDS: u64 = 300;
+/// Registrations not renewed within this period are from the
+/// terminated nodes (e.g., replaced by the ASG).
+pub const REGISTRATION_TTL_SECONDS: i64 = 3 * REGISTRATION_INTERVAL_SECONDS as i64;
+
+/// Represents the node self-registration in "StorageNamespace::DiscoverRegisteredNode",
+/// so that the other nodes discover the bootstrap nodes without spec updates.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Registration {
+    pub node: Node,
+    pub network_id: u32,
+    /// Hex-encoded SHA256 digest of the staking certificate.
+    /// Changes when the node ID changes (e.g., cert regenerated).
+    pub staking_cert_fingerprint: String,
+}
+
+impl Registration {
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize Registration to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+}
+
+/// Returns the anchor nodes from the registered node storage paths
+/// and their last modified unix timestamps, skipping the registrations
+/// older than the TTL. If a node ID is registered more than once
+/// (e.g., the same cert on a replaced instance), the latest one is kept.
+/// Sorted by node ID, so that the bootstrap flags are stable across polls.
+pub fn live_anchor_nodes(
+    registered: &[(String, i64)],
+    now_unix: i64,
+    ttl_seconds: i64,
+) -> io::Result<Vec<Node>> {
+    let mut latest: BTreeMap<String, (i64, Node)> = BTreeMap::new();
+    for (storage_path, last_modified) in registered.iter() {
+        if now_unix - last_modified > ttl_seconds {
+            info!("skipping stale registration '{}'", storage_path);
+            continue;
+        }
+        let node = StorageNamespace::parse_node_from_path(storage_path)?;
+        if node.kind != node::Kind::Anchor.as_str() {
+            continue;
+        }
+        match latest.get(&node.node_id) {
+            Some((ts, _)) if ts >= last_modified => {}
+            _ => {
+                latest.insert(node.node_id.clone(), (*last_modified, node));
+            }
+        }
+    }
+    Ok(latest.into_values().map(|(_, node)| node).collect())
+}
+
+/// Returns the avalanchego "--bootstrap-ips" and "--bootstrap-ids" values,
+/// assuming all nodes in the network use the same staking port.
+pub fn bootstrap_flags(nodes: &[Node], staking_port: u32) -> (String, String) {
+    let ips: Vec<String> = nodes
+        .iter()
+        .map(|n| host_port(&n.public_ip, staking_port))
+        .collect();
+    let ids: Vec<String> = nodes.iter().map(|n| n.node_id.clone()).collect();
+    (ips.join(","), ids.join(","))
+}
+
+#[test]
+fn test_live_anchor_nodes() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let anchor = |machine_id: &str, node_id: &str, ip: &str| {
+        Node::new(node::Kind::Anchor, machine_id, node_id, ip, "http", 9650)
+    };
+    let a1 = anchor("i-1", "NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg", "1.2.3.4");
+    let a2 = anchor("i-2", "NodeID-MFrZFVCXPv5iCn6M9K6XduxGTYp891xXZ", "1.2.3.5");
+    let a2_replaced = anchor("i-3", "NodeID-MFrZFVCXPv5iCn6M9K6XduxGTYp891xXZ", "1.2.3.6");
+    let stale = anchor("i-4", "NodeID-NFBbbJ4qCmNaCzeW7sxErhvWqvEQMnYcN", "1.2.3.7");
+    let non_anchor = Node::new(
+        node::Kind::NonAnchor,
+        "i-5",
+        "NodeID-GWPcbFJZFfZreETSoWjPimr846mXEKCtu",
+        "1.2.3.8",
+        "http",
+        9650,
+    );
+
+    let id = String::from("test");
+    let path = |n: &Node| StorageNamespace::DiscoverRegisteredNode(id.clone(), n.clone()).encode();
+    let now = 10_000;
+    let registered = vec![
+        (path(&a2), now - 100),
+        (path(&a1), now - 10),
+        (path(&a2_replaced), now - 50),
+        (path(&stale), now - REGISTRATION_TTL_SECONDS - 1),
+        (path(&non_anchor), now),
+    ];
+    let nodes = live_anchor_nodes(&registered, now, REGISTRATION_TTL_SECONDS).unwrap();
+    assert_eq!(nodes, vec![a1.clone(), a2_replaced.clone()]);
+
+    let (ips, ids) = bootstrap_flags(&nodes, 9651);
+    assert_eq!(ips, "1.2.3.4:9651,1.2.3.6:9651");
+    assert_eq!(
+        ids,
+        "NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg,NodeID-MFrZFVCXPv5iCn6M9K6XduxGTYp891xXZ"
+    );
+
+    let registration = Registration {
+        node: a1,
+        network_id: 1337,
+        staking_cert_fingerprint: "ab".repeat(32),
+    };
+    let encoded = registration.encode_yaml().unwrap();
+    assert_eq!(
+        Registration::decode_yaml(encoded.as_bytes()).unwrap(),
+        registration
+    );
+}
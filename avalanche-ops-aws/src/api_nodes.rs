@@ -0,0 +1,228 @@
+use std::{
+    collections::BTreeSet,
+    io::{self, Error, ErrorKind},
+};
+
+use serde::{Deserialize, Serialize};
+
+use avalanche_types::api::keystore as api_keystore;
+use avalanchego::config as avalanchego_config;
+use coreth::config as coreth_config;
+
+use crate::{MAX_MACHINE_API_NODES, MIN_MACHINE_API_NODES};
+
+/// Defines the non-validator nodes that serve the public RPC.
+/// The API nodes never stake, and "avalanched" on the API node
+/// skips the validator registration.
+/// With "load_balancer", only the API nodes are registered to its target group.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ApiNodes {
+    #[serde(default = "default_api_nodes")]
+    pub nodes: u32,
+    /// If empty, "machine.instance_types" is used.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instance_types: Vec<String>,
+    /// Enables "/ext/index" for the accepted X/P/C-chain containers.
+    #[serde(default = "default_api_nodes_index_enabled")]
+    pub index_enabled: bool,
+    /// Set to false to keep the historical EVM state (archive node),
+    /// which "eth_call" and "eth_getBalance" on the old blocks require.
+    #[serde(default = "default_api_nodes_pruning_enabled")]
+    pub pruning_enabled: bool,
+    /// Allows the EVM queries against the blocks not yet finalized.
+    #[serde(default)]
+    pub allow_unfinalized_queries: bool,
+    /// Keystore users that "avalanched" creates on each API node once healthy.
+    /// If non-empty, the keystore API is enabled on the API nodes,
+    /// so only set for the test networks.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keystore_users: Vec<KeystoreUser>,
+}
+
+fn default_api_nodes() -> u32 {
+    2
+}
+
+fn default_api_nodes_index_enabled() -> bool {
+    true
+}
+
+fn default_api_nodes_pruning_enabled() -> bool {
+    true
+}
+
+impl Default for ApiNodes {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl ApiNodes {
+    pub fn default() -> Self {
+        Self {
+            nodes: default_api_nodes(),
+            instance_types: Vec::new(),
+            index_enabled: default_api_nodes_index_enabled(),
+            pruning_enabled: default_api_nodes_pruning_enabled(),
+            allow_unfinalized_queries: false,
+            keystore_users: Vec::new(),
+        }
+    }
+
+    /// Overrides the node configuration for the RPC serving,
+    /// applied by "avalanched" on the API node before the node starts.
+    pub fn apply_rpc_config(
+        &self,
+        avalanchego_config: &mut avalanchego_config::Config,
+        coreth_config: &mut coreth_config::Config,
+    ) {
+        avalanchego_config.index_enabled = Some(self.index_enabled);
+        // the node may have run without the index (e.g., restored from the snapshot)
+        avalanchego_config.index_allow_incomplete = Some(self.index_enabled);
+        // publicly reachable, so never expose the node management APIs
+        avalanchego_config.api_admin_enabled = Some(false);
+        avalanchego_config.api_keystore_enabled = Some(!self.keystore_users.is_empty());
+
+        coreth_config.pruning_enabled = Some(self.pruning_enabled);
+        coreth_config.allow_unfinalized_queries = Some(self.allow_unfinalized_queries);
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        if self.nodes < MIN_MACHINE_API_NODES {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'machine.api_nodes.nodes' {} <minimum {}",
+                    self.nodes, MIN_MACHINE_API_NODES
+                ),
+            ));
+        }
+        if self.nodes > MAX_MACHINE_API_NODES {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'machine.api_nodes.nodes' {} >maximum {}",
+                    self.nodes, MAX_MACHINE_API_NODES
+                ),
+            ));
+        }
+        // "InstanceTypesCount" in the ASG template
+        if self.instance_types.len() > 4 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'machine.api_nodes.instance_types' has {} types (at most 4)",
+                    self.instance_types.len()
+                ),
+            ));
+        }
+        let mut usernames = BTreeSet::new();
+        for user in self.keystore_users.iter() {
+            user.validate()?;
+            if !usernames.insert(user.username.as_str()) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "duplicate 'machine.api_nodes.keystore_users' username '{}'",
+                        user.username
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_api_nodes() {
+    let api_nodes: ApiNodes = serde_yaml::from_str(
+        "
+nodes: 3
+instance_types:
+  - r6a.4xlarge
+pruning_enabled: false
+",
+    )
+    .unwrap();
+    assert_eq!(api_nodes.nodes, 3);
+    assert!(api_nodes.index_enabled);
+    assert!(!api_nodes.allow_unfinalized_queries);
+    api_nodes.validate().unwrap();
+
+    let mut avalanchego_config = avalanchego_config::Config::default();
+    let mut coreth_config = coreth_config::Config::default();
+    api_nodes.apply_rpc_config(&mut avalanchego_config, &mut coreth_config);
+    assert_eq!(avalanchego_config.index_enabled, Some(true));
+    assert_eq!(avalanchego_config.api_admin_enabled, Some(false));
+    assert_eq!(avalanchego_config.api_keystore_enabled, Some(false));
+    assert_eq!(coreth_config.pruning_enabled, Some(false));
+
+    let mut invalid = api_nodes.clone();
+    invalid.nodes = 0;
+    assert!(invalid.validate().is_err());
+    let mut invalid = api_nodes.clone();
+    invalid.instance_types = vec![String::from("c6a.2xlarge"); 5];
+    assert!(invalid.validate().is_err());
+
+    assert!(ApiNodes::default().validate().is_ok());
+
+    let mut api_nodes = api_nodes;
+    api_nodes.keystore_users = serde_yaml::from_str(
+        "
+- username: test-user
+  password: aaFUjfEJPbr3x!Zq
+  import_seed_private_keys: true
+",
+    )
+    .unwrap();
+    api_nodes.validate().unwrap();
+    api_nodes.apply_rpc_config(&mut avalanchego_config, &mut coreth_config);
+    assert_eq!(avalanchego_config.api_keystore_enabled, Some(true));
+
+    let mut invalid = api_nodes.clone();
+    invalid
+        .keystore_users
+        .push(invalid.keystore_users[0].clone());
+    assert!(invalid.validate().is_err());
+    let mut invalid = api_nodes;
+    invalid.keystore_users[0].password = String::new();
+    assert!(invalid.validate().is_err());
+}
+
+/// Keystore user on the API nodes (e.g., the test user of the load tests).
+/// The password is redacted from the spec in the remote storage,
+/// and shipped envelope-encrypted with "SeedPrivateKeys".
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct KeystoreUser {
+    pub username: String,
+    /// Rejected by "keystore.createUser" if not strong enough.
+    pub password: String,
+    /// Imports the generated seed private keys to the user
+    /// on the X, P, and C-chain, so the user spends the pre-funded balances.
+    #[serde(default)]
+    pub import_seed_private_keys: bool,
+}
+
+impl KeystoreUser {
+    pub fn validate(&self) -> io::Result<()> {
+        if self.username.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "empty keystore user 'username'",
+            ));
+        }
+        if self.password.is_empty() || self.password.len() > api_keystore::MAX_PASSWORD_LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "keystore user '{}' password must be 1-{} characters",
+                    self.username,
+                    api_keystore::MAX_PASSWORD_LEN
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
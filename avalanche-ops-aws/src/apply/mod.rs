@@ -1,7 +1,7 @@
 use std::{
     env,
     fs::{self, File},
-    io::{self, stdout, Error, ErrorKind},
+    io::{self, Error, ErrorKind, Write},
     os::unix::fs::PermissionsExt,
     path::Path,
     sync::{
@@ -14,6 +14,7 @@ use std::{
 
 use aws_sdk_cloudformation::model::{Capability, OnFailure, Parameter, StackStatus, Tag};
 use aws_sdk_s3::model::Object;
+use aws_types::SdkConfig as AwsSdkConfig;
 use clap::{Arg, Command};
 use crossterm::{
     execute,
@@ -25,9 +26,10 @@ use rust_embed::RustEmbed;
 use tokio::runtime::Runtime;
 
 use avalanche_api::health as api_health;
-use avalanche_types::api::health as api_health_types;
-use aws::{self, cloudformation, ec2, envelope, kms, s3, sts};
-use utils::{compress, home_dir, random};
+use avalanche_ops_aws::{cost, notify, output, provider};
+use avalanche_types::{api::health as api_health_types, node};
+use aws::{self, cloudformation, cloudwatch, dynamodb, ec2, envelope, kms, pricing, s3, sts};
+use utils::{compress, home_dir, http, random};
 
 pub const NAME: &str = "apply";
 
@@ -64,17 +66,46 @@ pub fn command() -> Command<'static> {
                 .takes_value(false)
                 .allow_invalid_utf8(false),
         )
+        .arg(
+            Arg::new("PLAN")
+                .long("plan")
+                .short('p')
+                .help(
+                    "Prints the resources and the spec changes to apply, without mutating anything",
+                )
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("PLAN_OUTPUT_PATH")
+                .long("plan-output-path")
+                .help("Writes the plan in JSON to the file (only with '--plan')")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
 }
 
 // 50-minute
 const MAX_WAIT_SECONDS: u64 = 50 * 60;
 
-pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::Result<()> {
-    #[derive(RustEmbed)]
-    #[folder = "cfn-templates/"]
-    #[prefix = "cfn-templates/"]
-    struct Asset;
-
+/// IP range of the primary and the regional VPCs.
+const VPC_CIDR: &str = "10.0.0.0/16";
+
+#[derive(RustEmbed)]
+#[folder = "cfn-templates/"]
+#[prefix = "cfn-templates/"]
+struct Asset;
+
+pub fn execute(
+    log_level: &str,
+    spec_file_path: &str,
+    skip_prompt: bool,
+    plan: bool,
+    plan_output_path: Option<&str>,
+    output: output::Format,
+) -> io::Result<()> {
     // ref. https://github.com/env-logger-rs/env_logger/issues/47
     env_logger::init_from_env(
         env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
@@ -82,6 +113,7 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
 
     let mut spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
     spec.validate()?;
+    http::set_default_tls(spec.api_tls.clone())?;
 
     let rt = Runtime::new().unwrap();
 
@@ -134,6 +166,14 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         aws_resources.cloudformation_asg_non_anchor_nodes =
             Some(avalanche_ops_aws::StackName::AsgNonBeaconNodes(spec.id.clone()).encode());
     }
+    if spec.machine.api_nodes.is_some() && aws_resources.cloudformation_asg_api_nodes.is_none() {
+        aws_resources.cloudformation_asg_api_nodes =
+            Some(avalanche_ops_aws::StackName::AsgApiNodes(spec.id.clone()).encode());
+    }
+    if spec.load_balancer.is_some() && aws_resources.cloudformation_load_balancer.is_none() {
+        aws_resources.cloudformation_load_balancer =
+            Some(avalanche_ops_aws::StackName::LoadBalancer(spec.id.clone()).encode());
+    }
     if aws_resources
         .cloudwatch_avalanche_metrics_namespace
         .is_none()
@@ -141,17 +181,61 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         aws_resources.cloudwatch_avalanche_metrics_namespace =
             Some(format!("{}-avalanche", spec.id));
     }
+    if let Some(regional_resources) = aws_resources.regional_resources.as_mut() {
+        for r in regional_resources.iter_mut() {
+            if r.s3_bucket.is_none() {
+                r.s3_bucket = Some(format!("{}-{}", aws_resources.s3_bucket, r.region));
+            }
+            if r.ec2_key_name.is_none() {
+                r.ec2_key_name = aws_resources.ec2_key_name.clone();
+            }
+            if r.cloudformation_vpc.is_none() {
+                r.cloudformation_vpc =
+                    Some(avalanche_ops_aws::StackName::Vpc(spec.id.clone()).encode());
+            }
+            if r.cloudformation_asg_non_anchor_nodes.is_none() {
+                r.cloudformation_asg_non_anchor_nodes =
+                    Some(avalanche_ops_aws::StackName::AsgNonBeaconNodes(spec.id.clone()).encode());
+            }
+        }
+    }
     spec.aws_resources = Some(aws_resources.clone());
+
+    if plan {
+        // nothing is mutated, including the local spec file
+        let planned = compute_plan(&rt, &spec, &aws_resources)?;
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Blue),
+            Print(format!("\n{}\n\n", planned)),
+            ResetColor
+        )?;
+        let encoded = planned.encode_json()?;
+        writeln!(output.console(), "{}", encoded)?;
+        if let Some(p) = plan_output_path {
+            fs::write(p, encoded)?;
+            info!("saved plan to '{}'", p);
+        }
+        return output.print(&planned);
+    }
     spec.sync(spec_file_path)?;
 
     execute!(
-        stdout(),
+        output.console(),
         SetForegroundColor(Color::Blue),
         Print(format!("\nLoaded Spec: '{}'\n", spec_file_path)),
         ResetColor
     )?;
     let spec_contents = spec.encode_yaml()?;
-    println!("{}\n", spec_contents);
+    writeln!(output.console(), "{}\n", spec_contents)?;
+
+    let estimate = estimate_cost(&rt, &shared_config, &spec);
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Blue),
+        Print(format!("{}\n\n", estimate)),
+        ResetColor
+    )?;
 
     if !skip_prompt {
         let options = &[
@@ -176,13 +260,82 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
     let kms_manager = kms::Manager::new(&shared_config);
     let ec2_manager = ec2::Manager::new(&shared_config);
     let cloudformation_manager = cloudformation::Manager::new(&shared_config);
+    let cw_manager = cloudwatch::Manager::new(&shared_config);
+    let provider = provider::AwsProvider::new(&shared_config, &aws_resources);
 
     let term = Arc::new(AtomicBool::new(false));
     signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&term))
         .expect("failed to register os signal");
 
+    // released when "apply" returns
+    let _state_lock = lock_state(&rt, &shared_config, &spec, NAME, output)?;
+    if spec.state_backend.is_some() {
+        spec.state_serial = Some(spec.state_serial.unwrap_or(0) + 1);
+        spec.sync(spec_file_path)?;
+    }
+
+    // existing stacks from the previous "apply" may have been modified out of band
+    // (e.g., security group edited in the console), so report before mutating them
+    let existing_stack_names: Vec<String> = [
+        aws_resources.cloudformation_ec2_instance_role.clone(),
+        aws_resources.cloudformation_vpc.clone(),
+        aws_resources.cloudformation_asg_anchor_nodes.clone(),
+        aws_resources.cloudformation_asg_non_anchor_nodes.clone(),
+        aws_resources.cloudformation_asg_api_nodes.clone(),
+        aws_resources.cloudformation_load_balancer.clone(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let mut drifted = false;
+    for stack_name in existing_stack_names.iter() {
+        let exists = rt
+            .block_on(cloudformation_manager.describe_stack(stack_name))
+            .unwrap()
+            .is_some();
+        if !exists {
+            continue;
+        }
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Green),
+            Print(format!(
+                "\n\n\nSTEP: detect drift of stack '{}'\n",
+                stack_name
+            )),
+            ResetColor
+        )?;
+        let drift = rt
+            .block_on(cloudformation_manager.detect_drift(stack_name, Duration::from_secs(300)))
+            .unwrap();
+        if drift.is_drifted() {
+            drifted = true;
+            execute!(
+                output.console(),
+                SetForegroundColor(Color::Yellow),
+                Print(format!("{}\n", drift)),
+                ResetColor
+            )?;
+        }
+    }
+    if drifted && !skip_prompt {
+        let options = &[
+            "No, I want to review the drifted resources first!",
+            "Yes, let's apply anyway!",
+        ];
+        let selected = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Stacks have drifted from their templates, continue?")
+            .items(&options[..])
+            .default(0)
+            .interact()
+            .unwrap();
+        if selected == 0 {
+            return Ok(());
+        }
+    }
+
     execute!(
-        stdout(),
+        output.console(),
         SetForegroundColor(Color::Green),
         Print("\n\n\nSTEP: create S3 buckets\n"),
         ResetColor
@@ -196,87 +349,19 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
 
     thread::sleep(Duration::from_secs(2));
     execute!(
-        stdout(),
+        output.console(),
         SetForegroundColor(Color::Green),
         Print("\n\n\nSTEP: upload artifacts to S3 bucket\n"),
         ResetColor
     )?;
 
-    // don't compress since we need to download this in user data
-    // while instance bootstrapping
-    rt.block_on(s3_manager.put_object(
-        Arc::new(spec.install_artifacts.avalanched_bin.clone()),
-        Arc::new(aws_resources.s3_bucket.clone()),
-        Arc::new(avalanche_ops_aws::StorageNamespace::AvalanchedBin(spec.id.clone()).encode()),
-    ))
-    .expect("failed put_object install_artifacts.avalanched_bin");
-
-    // compress as these will be decompressed by "avalanched"
-    let tmp_avalanche_bin_compressed_path =
-        random::tmp_path(15, Some(compress::Encoder::Zstd(3).ext())).unwrap();
-    compress::pack_file(
-        &spec.install_artifacts.avalanchego_bin,
-        &tmp_avalanche_bin_compressed_path,
-        compress::Encoder::Zstd(3),
-    )
-    .expect("failed pack_file install_artifacts.avalanched_bin");
-    rt.block_on(s3_manager.put_object(
-        Arc::new(tmp_avalanche_bin_compressed_path.clone()),
-        Arc::new(aws_resources.s3_bucket.clone()),
-        Arc::new(
-            avalanche_ops_aws::StorageNamespace::AvalancheBinCompressed(spec.id.clone()).encode(),
-        ),
-    ))
-    .expect("failed put_object compressed avalanchego_bin");
-    fs::remove_file(tmp_avalanche_bin_compressed_path)?;
-    if spec.install_artifacts.plugins_dir.is_some() {
-        let plugins_dir = spec.install_artifacts.plugins_dir.clone().unwrap();
-        for entry in fs::read_dir(plugins_dir.as_str()).unwrap() {
-            let entry = entry.unwrap();
-            let entry_path = entry.path();
-
-            let file_path = entry_path.to_str().unwrap();
-            let file_name = entry.file_name();
-            let file_name = file_name.as_os_str().to_str().unwrap();
-
-            let tmp_plugin_compressed_path =
-                random::tmp_path(15, Some(compress::Encoder::Zstd(3).ext())).unwrap();
-            compress::pack_file(
-                file_path,
-                &tmp_plugin_compressed_path,
-                compress::Encoder::Zstd(3),
-            )
-            .unwrap();
-
-            info!(
-                "uploading {} (compressed from {}) from plugins directory {}",
-                tmp_plugin_compressed_path, file_path, plugins_dir,
-            );
-            rt.block_on(s3_manager.put_object(
-                Arc::new(tmp_plugin_compressed_path.clone()),
-                Arc::new(aws_resources.s3_bucket.clone()),
-                Arc::new(format!(
-                    "{}/{}{}",
-                    &avalanche_ops_aws::StorageNamespace::PluginsDir(spec.id.clone()).encode(),
-                    file_name,
-                    compress::Encoder::Zstd(3).ext()
-                )),
-            ))
-            .expect("failed put_object tmp_plugin_compressed_path");
-            fs::remove_file(tmp_plugin_compressed_path)?;
-        }
-    }
-    rt.block_on(s3_manager.put_object(
-        Arc::new(spec_file_path.to_string()),
-        Arc::new(aws_resources.s3_bucket.clone()),
-        Arc::new(avalanche_ops_aws::StorageNamespace::ConfigFile(spec.id.clone()).encode()),
-    ))
-    .unwrap();
+    put_install_artifacts(&rt, &s3_manager, &spec, &aws_resources.s3_bucket)?;
+    put_spec(&rt, &provider, &spec)?;
 
     if aws_resources.kms_cmk_id.is_none() && aws_resources.kms_cmk_arn.is_none() {
         thread::sleep(Duration::from_secs(2));
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Green),
             Print("\n\n\nSTEP: create KMS key\n"),
             ResetColor
@@ -290,18 +375,52 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         spec.aws_resources = Some(aws_resources.clone());
         spec.sync(spec_file_path)?;
 
-        rt.block_on(s3_manager.put_object(
-            Arc::new(spec_file_path.to_string()),
-            Arc::new(aws_resources.s3_bucket.clone()),
-            Arc::new(avalanche_ops_aws::StorageNamespace::ConfigFile(spec.id.clone()).encode()),
-        ))
+        put_spec(&rt, &provider, &spec)?;
+    }
+    let envelope =
+        envelope::Envelope::new(Some(kms_manager.clone()), aws_resources.kms_cmk_id.clone());
+
+    if let Some(seed_private_keys) = spec.seed_private_keys() {
+        info!("uploading envelope-encrypted seed private keys");
+        let encrypted = rt
+            .block_on(envelope.seal_aes_256(&seed_private_keys.encode()?))
+            .unwrap();
+        let tmp_encrypted_path = random::tmp_path(15, Some(".encrypted")).unwrap();
+        fs::write(&tmp_encrypted_path, encrypted)?;
+        rt.block_on(
+            s3_manager.put_object(
+                Arc::new(tmp_encrypted_path.clone()),
+                Arc::new(aws_resources.s3_bucket.clone()),
+                Arc::new(
+                    avalanche_ops_aws::StorageNamespace::SeedPrivateKeysEncrypted(spec.id.clone())
+                        .encode(),
+                ),
+            ),
+        )
         .unwrap();
+        fs::remove_file(&tmp_encrypted_path)?;
+    }
+
+    if spec.clone_source.is_some() {
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Green),
+            Print("\n\n\nSTEP: copy the snapshot of the source network\n"),
+            ResetColor
+        )?;
+        crate::clone::copy_source(
+            &rt,
+            &s3_manager,
+            &kms_manager,
+            &envelope,
+            &spec,
+            &aws_resources.s3_bucket,
+        )?;
     }
-    let envelope = envelope::Envelope::new(Some(kms_manager), aws_resources.kms_cmk_id.clone());
 
     if aws_resources.ec2_key_path.is_none() {
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Green),
             Print("\n\n\nSTEP: create EC2 key pair\n"),
             ResetColor
@@ -347,12 +466,7 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         spec.aws_resources = Some(aws_resources.clone());
         spec.sync(spec_file_path)?;
 
-        rt.block_on(s3_manager.put_object(
-            Arc::new(spec_file_path.to_string()),
-            Arc::new(aws_resources.s3_bucket.clone()),
-            Arc::new(avalanche_ops_aws::StorageNamespace::ConfigFile(spec.id.clone()).encode()),
-        ))
-        .unwrap();
+        put_spec(&rt, &provider, &spec)?;
     }
 
     if aws_resources
@@ -360,7 +474,7 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         .is_none()
     {
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Green),
             Print("\n\n\nSTEP: create EC2 instance role\n"),
             ResetColor
@@ -386,6 +500,29 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
             );
             role_params.push(param);
         }
+        if let Some(hosted_zone_id) = spec
+            .machine
+            .anchor_endpoints
+            .as_ref()
+            .and_then(|v| v.route53_hosted_zone_id.as_ref())
+        {
+            role_params.push(build_param("Route53HostedZoneId", hosted_zone_id));
+        }
+        // the nodes only read and write the lock items, and the certificate
+        // rotation may be requested any time after the apply
+        let lock_table = spec.node_lock_table();
+        let dynamodb_manager = dynamodb::Manager::new(&shared_config);
+        rt.block_on(dynamodb_manager.create_lock_table(&lock_table))
+            .unwrap();
+        role_params.push(build_param("DynamoDbLockTableName", &lock_table));
+        let sns_topic_arns = spec
+            .notifications
+            .as_ref()
+            .map(|v| v.sns_topic_arns())
+            .unwrap_or_default();
+        if !sns_topic_arns.is_empty() {
+            role_params.push(build_param("SnsTopicArns", &sns_topic_arns.join(",")));
+        }
         rt.block_on(cloudformation_manager.create_stack(
             ec2_instance_role_stack_name.as_str(),
             Some(vec![Capability::CapabilityNamedIam]),
@@ -408,23 +545,38 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
             ))
             .unwrap();
 
+        let mut instance_role_arn = None;
         for o in stack.outputs.unwrap() {
             let k = o.output_key.unwrap();
             let v = o.output_value.unwrap();
             info!("stack output key=[{}], value=[{}]", k, v,);
             if k.eq("InstanceProfileArn") {
-                aws_resources.cloudformation_ec2_instance_profile_arn = Some(v)
+                aws_resources.cloudformation_ec2_instance_profile_arn = Some(v);
+                continue;
+            }
+            if k.eq("InstanceRoleArn") {
+                instance_role_arn = Some(v);
             }
         }
-        spec.aws_resources = Some(aws_resources.clone());
-        spec.sync(spec_file_path)?;
 
-        rt.block_on(s3_manager.put_object(
-            Arc::new(spec_file_path.to_string()),
-            Arc::new(aws_resources.s3_bucket.clone()),
-            Arc::new(avalanche_ops_aws::StorageNamespace::ConfigFile(spec.id.clone()).encode()),
+        // only the node instances and the operator can decrypt the envelope
+        let identity = aws_resources.identity.clone().unwrap();
+        let instance_role_arn = instance_role_arn.expect("unexpected None InstanceRoleArn");
+        let operator_arn = identity.principal_arn();
+        let policy = kms::restricted_key_policy(
+            &kms::account_root_arn(&identity.role_arn, &identity.account_id),
+            &[instance_role_arn.as_str(), operator_arn.as_str()],
+        );
+        rt.block_on(kms_manager.put_key_policy(
+            &aws_resources.kms_cmk_id.clone().unwrap(),
+            &policy,
+            true,
         ))
         .unwrap();
+        spec.aws_resources = Some(aws_resources.clone());
+        spec.sync(spec_file_path)?;
+
+        put_spec(&rt, &provider, &spec)?;
     }
 
     if aws_resources.cloudformation_vpc_id.is_none()
@@ -432,7 +584,7 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         && aws_resources.cloudformation_vpc_public_subnet_ids.is_none()
     {
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Green),
             Print("\n\n\nSTEP: create VPC\n"),
             ResetColor
@@ -440,78 +592,105 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
 
         let vpc_yaml = Asset::get("cfn-templates/vpc.yaml").unwrap();
         let vpc_tmpl = std::str::from_utf8(vpc_yaml.data.as_ref()).unwrap();
-        let vpc_stack_name = aws_resources.cloudformation_vpc.clone().unwrap();
-        let vpc_params = Vec::from([
-            build_param("Id", &spec.id),
-            build_param("VpcCidr", "10.0.0.0/16"),
-            build_param("PublicSubnetCidr1", "10.0.64.0/19"),
-            build_param("PublicSubnetCidr2", "10.0.128.0/19"),
-            build_param("PublicSubnetCidr3", "10.0.192.0/19"),
-            build_param("IngressIpv4Range", "0.0.0.0/0"),
-            build_param(
-                "StakingPort",
-                format!("{}", spec.avalanchego_config.staking_port).as_str(),
-            ),
-            build_param(
-                "HttpPort",
-                format!("{}", spec.avalanchego_config.http_port).as_str(),
-            ),
-        ]);
-        rt.block_on(cloudformation_manager.create_stack(
-            vpc_stack_name.as_str(),
-            None,
-            OnFailure::Delete,
+        let (vpc_id, security_group_id, public_subnet_ids) = create_vpc(
+            &rt,
+            &cloudformation_manager,
             vpc_tmpl,
-            Some(Vec::from([
-                Tag::builder().key("KIND").value("avalanche-ops").build(),
-            ])),
-            Some(vpc_params),
-        ))
-        .expect("failed create_stack for VPC");
+            &aws_resources.cloudformation_vpc.clone().unwrap(),
+            &spec,
+        )?;
+        aws_resources.cloudformation_vpc_id = Some(vpc_id);
+        aws_resources.cloudformation_vpc_security_group_id = Some(security_group_id);
+        aws_resources.cloudformation_vpc_public_subnet_ids = Some(public_subnet_ids);
+        spec.aws_resources = Some(aws_resources.clone());
+        spec.sync(spec_file_path)?;
 
-        thread::sleep(Duration::from_secs(10));
-        let stack = rt
-            .block_on(cloudformation_manager.poll_stack(
-                vpc_stack_name.as_str(),
-                StackStatus::CreateComplete,
-                Duration::from_secs(300),
-                Duration::from_secs(30),
-            ))
-            .expect("failed poll_stack for VPC");
+        put_spec(&rt, &provider, &spec)?;
+    }
 
-        for o in stack.outputs.unwrap() {
-            let k = o.output_key.unwrap();
-            let v = o.output_value.unwrap();
-            info!("stack output key=[{}], value=[{}]", k, v,);
-            if k.eq("VpcId") {
-                aws_resources.cloudformation_vpc_id = Some(v);
-                continue;
-            }
-            if k.eq("SecurityGroupId") {
-                aws_resources.cloudformation_vpc_security_group_id = Some(v);
+    if let Some(mut regional_resources) = aws_resources.regional_resources.clone() {
+        let vpc_yaml = Asset::get("cfn-templates/vpc.yaml").unwrap();
+        let vpc_tmpl = std::str::from_utf8(vpc_yaml.data.as_ref()).unwrap();
+        for r in regional_resources.iter_mut() {
+            if r.cloudformation_vpc_id.is_some() {
                 continue;
             }
-            if k.eq("PublicSubnetIds") {
-                let splits: Vec<&str> = v.split(',').collect();
-                let mut pub_subnets: Vec<String> = vec![];
-                for s in splits {
-                    info!("public subnet {}", s);
-                    pub_subnets.push(String::from(s));
-                }
-                aws_resources.cloudformation_vpc_public_subnet_ids = Some(pub_subnets);
+            execute!(
+                output.console(),
+                SetForegroundColor(Color::Green),
+                Print(format!(
+                    "\n\n\nSTEP: create S3 bucket, EC2 key pair, and VPC in region {}\n",
+                    r.region
+                )),
+                ResetColor
+            )?;
+            let regional_config = rt
+                .block_on(aws::load_config(Some(r.region.clone())))
+                .expect("failed to aws::load_config");
+
+            // install artifacts are downloaded within the region,
+            // the rest remains in the primary bucket
+            let regional_s3_manager = s3::Manager::new(&regional_config);
+            let regional_s3_bucket = r.s3_bucket.clone().unwrap();
+            rt.block_on(regional_s3_manager.create_bucket(&regional_s3_bucket))
+                .unwrap();
+            put_install_artifacts(&rt, &regional_s3_manager, &spec, &regional_s3_bucket)?;
+
+            if r.ec2_key_path.is_none() {
+                let ec2_key_path = get_regional_ec2_key_path(spec_file_path, &r.region);
+                rt.block_on(ec2::Manager::new(&regional_config).create_key_pair(
+                    r.ec2_key_name.clone().unwrap().as_str(),
+                    ec2_key_path.as_str(),
+                ))
+                .unwrap();
+                r.ec2_key_path = Some(ec2_key_path);
             }
-        }
-        spec.aws_resources = Some(aws_resources.clone());
-        spec.sync(spec_file_path)?;
 
-        rt.block_on(s3_manager.put_object(
-            Arc::new(spec_file_path.to_string()),
-            Arc::new(aws_resources.s3_bucket.clone()),
-            Arc::new(avalanche_ops_aws::StorageNamespace::ConfigFile(spec.id.clone()).encode()),
-        ))
-        .unwrap();
+            let (vpc_id, security_group_id, public_subnet_ids) = create_vpc(
+                &rt,
+                &cloudformation::Manager::new(&regional_config),
+                vpc_tmpl,
+                &r.cloudformation_vpc.clone().unwrap(),
+                &spec,
+            )?;
+            r.cloudformation_vpc_id = Some(vpc_id);
+            r.cloudformation_vpc_security_group_id = Some(security_group_id);
+            r.cloudformation_vpc_public_subnet_ids = Some(public_subnet_ids);
+
+            update_regional_resources(&mut aws_resources, r);
+            spec.aws_resources = Some(aws_resources.clone());
+            spec.sync(spec_file_path)?;
+
+            put_spec(&rt, &provider, &spec)?;
+        }
     }
 
+    // create before the nodes, so that the retention is set
+    // regardless of the log shipper (CloudWatch agent or "avalanched")
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Green),
+        Print(
+            "
+
+
+STEP: create CloudWatch log group
+"
+        ),
+        ResetColor
+    )?;
+    rt.block_on(cw_manager.create_log_group(&spec.id))
+        .expect("failed create_log_group");
+    rt.block_on(
+        cw_manager.put_retention_policy(
+            &spec.id,
+            aws_resources
+                .instance_logs_retention_in_days
+                .unwrap_or(cloudwatch::logs::DEFAULT_RETENTION_IN_DAYS),
+        ),
+    )
+    .expect("failed put_retention_policy");
+
     let mut asg_parameters = Vec::from([
         build_param("Id", &spec.id),
         build_param(
@@ -554,11 +733,24 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
             "NlbHttpPort",
             format!("{}", spec.avalanchego_config.http_port).as_str(),
         ),
+        build_param(
+            "InstallCloudWatchAgent",
+            &aws_resources
+                .install_cloudwatch_agent
+                .unwrap_or(true)
+                .to_string(),
+        ),
+        build_param(
+            "DualStack",
+            &aws_resources.dual_stack.unwrap_or(false).to_string(),
+        ),
     ]);
 
     // mainnet/* requires higher volume size
-    // TODO: make this configurable
-    if spec.avalanchego_config.is_mainnet() {
+    if let Some(data_volume) = &spec.machine.data_volume {
+        asg_parameters.extend(data_volume.volume.to_parameters());
+        asg_parameters.push(build_param("DataVolumeManaged", "true"));
+    } else if spec.avalanchego_config.is_mainnet() {
         let param = build_param("VolumeSize", "800");
         asg_parameters.push(param);
     } else if !spec.avalanchego_config.is_custom_network() {
@@ -566,6 +758,15 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         asg_parameters.push(param);
     }
 
+    if let Some(termination_policy) = &spec.termination_policy {
+        asg_parameters.push(build_param(
+            "LifecycleHookTimeoutSeconds",
+            &termination_policy
+                .lifecycle_hook_timeout_seconds
+                .to_string(),
+        ));
+    }
+
     asg_parameters.push(build_param("Arch", &spec.machine.arch));
     if !spec.machine.instance_types.is_empty() {
         let instance_types = spec.machine.instance_types.clone();
@@ -576,40 +777,197 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         ));
     }
 
-    // TODO: support bootstrap from existing DB for anchor nodes
-    let mut current_nodes: Vec<avalanche_ops_aws::Node> = Vec::new();
-    if spec.machine.anchor_nodes.unwrap_or(0) > 0
-        && aws_resources
-            .cloudformation_asg_anchor_nodes_logical_id
-            .is_none()
-    {
-        execute!(
-            stdout(),
-            SetForegroundColor(Color::Green),
-            Print("\n\n\nSTEP: create ASG for anchor nodes\n"),
-            ResetColor
-        )?;
+    if let Some(anchor_endpoints) = &spec.machine.anchor_endpoints {
+        let anchor_nodes = spec.machine.anchor_nodes.unwrap_or(0);
+        let allocated = aws_resources
+            .anchor_eips
+            .as_ref()
+            .map_or(0, |v| v.len() as u32);
+        if anchor_endpoints.elastic_ips && allocated < anchor_nodes {
+            execute!(
+                output.console(),
+                SetForegroundColor(Color::Green),
+                Print("\n\n\nSTEP: allocate Elastic IPs for anchor nodes\n"),
+                ResetColor
+            )?;
 
-        // TODO: support other platforms
-        let cloudformation_asg_anchor_nodes_yaml =
-            Asset::get("cfn-templates/asg_amd64_ubuntu.yaml").unwrap();
-        let cloudformation_asg_anchor_nodes_tmpl =
-            std::str::from_utf8(cloudformation_asg_anchor_nodes_yaml.data.as_ref()).unwrap();
-        let cloudformation_asg_anchor_nodes_stack_name = aws_resources
-            .cloudformation_asg_anchor_nodes
-            .clone()
-            .unwrap();
+            // list first, so the re-run after the failed apply
+            // does not leak the Elastic IPs allocated before
+            let tags = avalanche_ops_aws::AnchorEndpoints::tags(&spec.id);
+            let mut eips = rt.block_on(ec2_manager.list_eips(&tags)).unwrap();
+            for index in 1..=anchor_nodes {
+                if eips.iter().any(|e| e.anchor_index == Some(index)) {
+                    continue;
+                }
+                let anchor_index = index.to_string();
+                let mut eip_tags = tags.to_vec();
+                eip_tags.push((ec2::eip::ANCHOR_INDEX_TAG, anchor_index.as_str()));
+                eips.push(rt.block_on(ec2_manager.allocate_eip(&eip_tags)).unwrap());
+            }
+            eips.sort_by_key(|e| e.anchor_index);
+            for eip in eips.iter() {
+                info!(
+                    "anchor node {:?} Elastic IP {} ({})",
+                    eip.anchor_index, eip.public_ip, eip.allocation_id
+                );
+            }
 
-        let desired_capacity = spec.machine.anchor_nodes.unwrap();
+            aws_resources.anchor_eips = Some(eips);
+            spec.aws_resources = Some(aws_resources.clone());
+            spec.sync(spec_file_path)?;
 
-        // must deep-copy as shared with other node kind
+            put_spec(&rt, &provider, &spec)?;
+        }
+    }
+
+    // the non-anchor nodes register to the target group on launch
+    if let Some(load_balancer) = &spec.load_balancer {
+        let load_balancer_stack_name = aws_resources.cloudformation_load_balancer.clone().unwrap();
+        let exists = rt
+            .block_on(cloudformation_manager.describe_stack(&load_balancer_stack_name))
+            .unwrap()
+            .is_some();
+        if !exists {
+            execute!(
+                output.console(),
+                SetForegroundColor(Color::Green),
+                Print(format!(
+                    "\n\n\nSTEP: create {} for RPC endpoints\n",
+                    load_balancer.kind.as_str().to_uppercase()
+                )),
+                ResetColor
+            )?;
+
+            let load_balancer_yaml = Asset::get("cfn-templates/load_balancer.yaml").unwrap();
+            let load_balancer_tmpl = std::str::from_utf8(load_balancer_yaml.data.as_ref()).unwrap();
+
+            let mut load_balancer_params = Vec::from([
+                build_param("Id", &spec.id),
+                build_param("Kind", load_balancer.kind.as_str()),
+                build_param("Internal", &load_balancer.internal.to_string()),
+                build_param(
+                    "PublicSubnetIds",
+                    &aws_resources
+                        .cloudformation_vpc_public_subnet_ids
+                        .clone()
+                        .unwrap()
+                        .join(","),
+                ),
+                build_param(
+                    "VpcId",
+                    &aws_resources.cloudformation_vpc_id.clone().unwrap(),
+                ),
+                build_param(
+                    "HttpPort",
+                    format!("{}", spec.avalanchego_config.http_port).as_str(),
+                ),
+                build_param("HealthCheckPath", &load_balancer.health_check_path),
+            ]);
+            if let Some(arn) = &load_balancer.acm_certificate_arn {
+                load_balancer_params.push(build_param("AcmCertificateArn", arn));
+            }
+            if let Some(ingress_ipv4_range) = &load_balancer.ingress_ipv4_range {
+                load_balancer_params.push(build_param("IngressIpv4Range", ingress_ipv4_range));
+            }
+
+            rt.block_on(cloudformation_manager.create_stack(
+                load_balancer_stack_name.as_str(),
+                None,
+                OnFailure::Delete,
+                load_balancer_tmpl,
+                Some(Vec::from([
+                    Tag::builder().key("KIND").value("avalanche-ops").build(),
+                ])),
+                Some(load_balancer_params),
+            ))
+            .unwrap();
+
+            thread::sleep(Duration::from_secs(30));
+            let stack = rt
+                .block_on(cloudformation_manager.poll_stack(
+                    load_balancer_stack_name.as_str(),
+                    StackStatus::CreateComplete,
+                    Duration::from_secs(600),
+                    Duration::from_secs(30),
+                ))
+                .unwrap();
+
+            // same outputs as the NLB created with the nodes,
+            // so the non-anchor nodes register to this target group
+            for o in stack.outputs.unwrap() {
+                let k = o.output_key.unwrap();
+                let v = o.output_value.unwrap();
+                info!("stack output key=[{}], value=[{}]", k, v,);
+                if k.eq("LoadBalancerArn") {
+                    aws_resources.cloudformation_asg_nlb_arn = Some(v);
+                    continue;
+                }
+                if k.eq("TargetGroupArn") {
+                    aws_resources.cloudformation_asg_nlb_target_group_arn = Some(v);
+                    continue;
+                }
+                if k.eq("DnsName") {
+                    aws_resources.cloudformation_asg_nlb_dns_name = Some(v);
+                    continue;
+                }
+            }
+            spec.aws_resources = Some(aws_resources.clone());
+            spec.sync(spec_file_path)?;
+
+            put_spec(&rt, &provider, &spec)?;
+        }
+    }
+
+    // open the ports before the nodes launch, with the node IPs known so far
+    // (e.g., Elastic IPs of the anchor nodes)
+    if spec.ingress_policy.is_some() {
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Green),
+            Print("\n\n\nSTEP: reconcile security group ingress rules\n"),
+            ResetColor
+        )?;
+        let node_public_ips = node_public_ips(&rt, &spec, &[])?;
+        reconcile_ingress(&rt, &spec, &aws_resources, &node_public_ips, output)?;
+    }
+
+    // TODO: support bootstrap from existing DB for anchor nodes
+    let mut current_nodes: Vec<avalanche_ops_aws::Node> = Vec::new();
+    if spec.machine.anchor_nodes.unwrap_or(0) > 0
+        && aws_resources
+            .cloudformation_asg_anchor_nodes_logical_id
+            .is_none()
+    {
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Green),
+            Print("\n\n\nSTEP: create ASG for anchor nodes\n"),
+            ResetColor
+        )?;
+
+        // TODO: support other platforms
+        let cloudformation_asg_anchor_nodes_yaml =
+            Asset::get("cfn-templates/asg_amd64_ubuntu.yaml").unwrap();
+        let cloudformation_asg_anchor_nodes_tmpl =
+            std::str::from_utf8(cloudformation_asg_anchor_nodes_yaml.data.as_ref()).unwrap();
+        let cloudformation_asg_anchor_nodes_stack_name = aws_resources
+            .cloudformation_asg_anchor_nodes
+            .clone()
+            .unwrap();
+
+        let desired_capacity = spec.machine.anchor_nodes.unwrap();
+
+        // must deep-copy as shared with other node kind
         let mut asg_anchor_params = asg_parameters.clone();
         asg_anchor_params.push(build_param("NodeKind", "anchor"));
         asg_anchor_params.push(build_param(
             "AsgDesiredCapacity",
             format!("{}", desired_capacity).as_str(),
         ));
-        if aws_resources.nlb_acm_certificate_arn.is_some() {
+        if spec.load_balancer.is_some() {
+            // the anchor nodes do not serve the public RPC
+            asg_anchor_params.push(build_param("NlbEnabled", "false"));
+        } else if aws_resources.nlb_acm_certificate_arn.is_some() {
             asg_anchor_params.push(build_param(
                 "NlbAcmCertificateArn",
                 &aws_resources.nlb_acm_certificate_arn.clone().unwrap(),
@@ -709,11 +1067,12 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         let ec2_key_path = aws_resources.ec2_key_path.clone().unwrap();
         let f = File::open(&ec2_key_path).unwrap();
         f.set_permissions(PermissionsExt::from_mode(0o444)).unwrap();
-        println!("\nchmod 400 {}", ec2_key_path);
+        writeln!(output.console(), "\nchmod 400 {}", ec2_key_path)?;
         for d in droplets {
             // ssh -o "StrictHostKeyChecking no" -i [ec2_key_path] [user name]@[public IPv4/DNS name]
             // aws ssm start-session --region [region] --target [instance ID]
-            println!(
+            writeln!(
+                output.console(),
                 "# instance '{}' ({}, {})\nssh -o \"StrictHostKeyChecking no\" -i {} ubuntu@{}\naws ssm start-session --region {} --target {}",
                 d.instance_id,
                 d.instance_state_name,
@@ -722,9 +1081,9 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
                 d.public_ipv4,
                 aws_resources.region,
                 d.instance_id,
-            );
+            )?;
         }
-        println!();
+        writeln!(output.console())?;
 
         // wait for anchor nodes to generate certs and node ID and post to remote storage
         // TODO: set timeouts
@@ -755,10 +1114,10 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
 
             if term.load(Ordering::Relaxed) {
                 warn!("received signal {}", signal_hook::consts::SIGINT);
-                println!();
-                println!("# run the following to delete resources");
+                writeln!(output.console())?;
+                writeln!(output.console(), "# run the following to delete resources")?;
                 execute!(
-                        stdout(),
+                        output.console(),
                         SetForegroundColor(Color::Green),
                         Print(format!(
                             "{} delete \\\n--delete-cloudwatch-log-group \\\n--delete-s3-objects \\\n--spec-file-path {}\n",
@@ -780,12 +1139,7 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         spec.aws_resources = Some(aws_resources.clone());
         spec.sync(spec_file_path)?;
 
-        rt.block_on(s3_manager.put_object(
-            Arc::new(spec_file_path.to_string()),
-            Arc::new(aws_resources.s3_bucket.clone()),
-            Arc::new(avalanche_ops_aws::StorageNamespace::ConfigFile(spec.id.clone()).encode()),
-        ))
-        .unwrap();
+        put_spec(&rt, &provider, &spec)?;
 
         info!("waiting for anchor nodes bootstrap and ready (to be safe)");
         thread::sleep(Duration::from_secs(15));
@@ -796,7 +1150,7 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         .is_none()
     {
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Green),
             Print("\n\n\nSTEP: create ASG for non-anchor nodes\n"),
             ResetColor
@@ -834,6 +1188,9 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
                     &aws_resources.nlb_acm_certificate_arn.clone().unwrap(),
                 ));
             };
+        } else if spec.load_balancer.is_some() && spec.machine.api_nodes.is_some() {
+            // the API nodes serve the public RPC
+            asg_non_anchor_params.push(build_param("NlbEnabled", "false"));
         } else {
             // already created for anchor nodes
             asg_non_anchor_params.push(build_param(
@@ -844,6 +1201,10 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
                     .unwrap(),
             ));
         }
+        // anchor nodes are always on-demand
+        if let Some(purchase_options) = &spec.machine.purchase_options {
+            asg_non_anchor_params.extend(purchase_options.to_parameters());
+        }
 
         rt.block_on(cloudformation_manager.create_stack(
             cloudformation_asg_non_anchor_nodes_stack_name.as_str(),
@@ -940,14 +1301,27 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
             thread::sleep(Duration::from_secs(30));
             droplets = rt.block_on(ec2_manager.list_asg(&asg_name)).unwrap();
         }
+        if spec.machine.purchase_options.is_some() {
+            let fleet = aws::ec2::fleet::Fleet {
+                asg_name: asg_name.clone(),
+                instances: droplets.clone(),
+            };
+            info!(
+                "non-anchor nodes ASG '{}' has {} spot and {} on-demand instance(s)",
+                fleet.asg_name,
+                fleet.spot_instances(),
+                fleet.on_demand_instances()
+            );
+        }
         let ec2_key_path = aws_resources.ec2_key_path.clone().unwrap();
         let f = File::open(&ec2_key_path).unwrap();
         f.set_permissions(PermissionsExt::from_mode(0o444)).unwrap();
-        println!("\nchmod 400 {}", ec2_key_path);
+        writeln!(output.console(), "\nchmod 400 {}", ec2_key_path)?;
         for d in droplets {
             // ssh -o "StrictHostKeyChecking no" -i [ec2_key_path] [user name]@[public IPv4/DNS name]
             // aws ssm start-session --region [region] --target [instance ID]
-            println!(
+            writeln!(
+                output.console(),
                 "# instance '{}' ({}, {})\nssh -o \"StrictHostKeyChecking no\" -i {} ubuntu@{}\naws ssm start-session --region {} --target {}",
                 d.instance_id,
                 d.instance_state_name,
@@ -956,9 +1330,9 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
                 d.public_ipv4,
                 aws_resources.region,
                 d.instance_id,
-            );
+            )?;
         }
-        println!();
+        writeln!(output.console())?;
 
         let require_db_download = aws_resources.db_backup_s3_bucket.is_some();
         let s3_dir = {
@@ -992,10 +1366,10 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
 
             if term.load(Ordering::Relaxed) {
                 warn!("received signal {}", signal_hook::consts::SIGINT);
-                println!();
-                println!("# run the following to delete resources");
+                writeln!(output.console())?;
+                writeln!(output.console(), "# run the following to delete resources")?;
                 execute!(
-                        stdout(),
+                        output.console(),
                         SetForegroundColor(Color::Green),
                         Print(format!(
                             "{} delete \\\n--delete-cloudwatch-log-group \\\n--delete-s3-objects \\\n--spec-file-path {}\n",
@@ -1017,12 +1391,7 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         spec.aws_resources = Some(aws_resources.clone());
         spec.sync(spec_file_path)?;
 
-        rt.block_on(s3_manager.put_object(
-            Arc::new(spec_file_path.to_string()),
-            Arc::new(aws_resources.s3_bucket.clone()),
-            Arc::new(avalanche_ops_aws::StorageNamespace::ConfigFile(spec.id.clone()).encode()),
-        ))
-        .expect("failed put_object ConfigFile");
+        put_spec(&rt, &provider, &spec)?;
 
         // TODO: if downloading mainnet db, it will take a while
         // TODO: better handle this
@@ -1030,7 +1399,7 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
             spec.current_nodes = Some(current_nodes.clone());
             spec.aws_resources = Some(aws_resources);
             spec.sync(spec_file_path)?;
-            println!();
+            writeln!(output.console())?;
             warn!(
                 "non-anchor nodes are downloading db backups, can take awhile, check back later..."
             );
@@ -1040,21 +1409,396 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         info!("waiting for non-anchor nodes bootstrap and ready (to be safe)");
         thread::sleep(Duration::from_secs(20));
     }
+    if let Some(api_nodes) = &spec.machine.api_nodes {
+        if aws_resources
+            .cloudformation_asg_api_nodes_logical_id
+            .is_none()
+        {
+            execute!(
+                output.console(),
+                SetForegroundColor(Color::Green),
+                Print("\n\n\nSTEP: create ASG for API nodes\n"),
+                ResetColor
+            )?;
+
+            let cloudformation_asg_api_nodes_yaml =
+                Asset::get("cfn-templates/asg_amd64_ubuntu.yaml").unwrap();
+            let cloudformation_asg_api_nodes_tmpl =
+                std::str::from_utf8(cloudformation_asg_api_nodes_yaml.data.as_ref()).unwrap();
+            let cloudformation_asg_api_nodes_stack_name =
+                aws_resources.cloudformation_asg_api_nodes.clone().unwrap();
+
+            // must deep-copy as shared with other node kind
+            let mut asg_api_params = asg_parameters.clone();
+            if !api_nodes.instance_types.is_empty() {
+                asg_api_params.retain(|p| {
+                    !matches!(
+                        p.parameter_key(),
+                        Some("InstanceTypes") | Some("InstanceTypesCount")
+                    )
+                });
+                asg_api_params.push(build_param(
+                    "InstanceTypes",
+                    &api_nodes.instance_types.join(","),
+                ));
+                asg_api_params.push(build_param(
+                    "InstanceTypesCount",
+                    format!("{}", api_nodes.instance_types.len()).as_str(),
+                ));
+            }
+            asg_api_params.push(build_param("NodeKind", "api"));
+            asg_api_params.push(build_param(
+                "AsgDesiredCapacity",
+                format!("{}", api_nodes.nodes).as_str(),
+            ));
+            // the target group is always created by now, with the load balancer
+            // or with the non-anchor nodes
+            asg_api_params.push(build_param(
+                "NlbTargetGroupArn",
+                &aws_resources
+                    .cloudformation_asg_nlb_target_group_arn
+                    .clone()
+                    .unwrap(),
+            ));
+            if let Some(purchase_options) = &spec.machine.purchase_options {
+                asg_api_params.extend(purchase_options.to_parameters());
+            }
+
+            rt.block_on(cloudformation_manager.create_stack(
+                cloudformation_asg_api_nodes_stack_name.as_str(),
+                None,
+                OnFailure::Delete,
+                cloudformation_asg_api_nodes_tmpl,
+                Some(Vec::from([
+                    Tag::builder().key("KIND").value("avalanche-ops").build(),
+                ])),
+                Some(asg_api_params),
+            ))
+            .unwrap();
+
+            let mut wait_secs = 300 + 60 * api_nodes.nodes as u64;
+            if wait_secs > MAX_WAIT_SECONDS {
+                wait_secs = MAX_WAIT_SECONDS;
+            }
+            thread::sleep(Duration::from_secs(30));
+            let stack = rt
+                .block_on(cloudformation_manager.poll_stack(
+                    cloudformation_asg_api_nodes_stack_name.as_str(),
+                    StackStatus::CreateComplete,
+                    Duration::from_secs(wait_secs),
+                    Duration::from_secs(30),
+                ))
+                .unwrap();
+
+            for o in stack.outputs.unwrap() {
+                let k = o.output_key.unwrap();
+                let v = o.output_value.unwrap();
+                info!("stack output key=[{}], value=[{}]", k, v,);
+                if k.eq("AsgLogicalId") {
+                    aws_resources.cloudformation_asg_api_nodes_logical_id = Some(v);
+                    continue;
+                }
+            }
+            if aws_resources
+                .cloudformation_asg_api_nodes_logical_id
+                .is_none()
+            {
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    "aws_resources.cloudformation_asg_api_nodes_logical_id not found",
+                ));
+            }
+            spec.aws_resources = Some(aws_resources.clone());
+            spec.sync(spec_file_path)?;
+
+            let s3_dir = if aws_resources.db_backup_s3_bucket.is_some() {
+                avalanche_ops_aws::StorageNamespace::DiscoverProvisioningApiNodesDir(
+                    spec.id.clone(),
+                )
+            } else {
+                avalanche_ops_aws::StorageNamespace::DiscoverReadyApiNodesDir(spec.id.clone())
+            };
+            let mut objects: Vec<Object>;
+            loop {
+                thread::sleep(Duration::from_secs(30));
+                objects = rt
+                    .block_on(s3_manager.list_objects(
+                        Arc::new(aws_resources.s3_bucket.clone()),
+                        Some(Arc::new(s3::append_slash(&s3_dir.encode()))),
+                    ))
+                    .unwrap();
+                info!(
+                    "{} API nodes are ready (expecting {} nodes)",
+                    objects.len(),
+                    api_nodes.nodes
+                );
+                if objects.len() as u32 >= api_nodes.nodes {
+                    break;
+                }
+                if term.load(Ordering::Relaxed) {
+                    warn!("received signal {}", signal_hook::consts::SIGINT);
+                    return Err(Error::new(
+                        ErrorKind::Interrupted,
+                        "interrupted while waiting for API nodes",
+                    ));
+                }
+            }
+
+            current_nodes.retain(|n| n.kind != node::Kind::Api.as_str());
+            for obj in objects.iter() {
+                let s3_key = obj.key().unwrap();
+                let api_node =
+                    avalanche_ops_aws::StorageNamespace::parse_node_from_path(s3_key).unwrap();
+                current_nodes.push(api_node);
+            }
+            spec.current_nodes = Some(current_nodes.clone());
+            spec.sync(spec_file_path)?;
+            put_spec(&rt, &provider, &spec)?;
+        }
+    }
+    if let Some(mut regional_resources) = aws_resources.regional_resources.clone() {
+        let cloudformation_asg_yaml = Asset::get("cfn-templates/asg_amd64_ubuntu.yaml").unwrap();
+        let cloudformation_asg_tmpl =
+            std::str::from_utf8(cloudformation_asg_yaml.data.as_ref()).unwrap();
+        let mut created = false;
+        for r in regional_resources.iter_mut() {
+            if r.cloudformation_asg_non_anchor_nodes_logical_id.is_some() {
+                continue;
+            }
+            execute!(
+                output.console(),
+                SetForegroundColor(Color::Green),
+                Print(format!(
+                    "\n\n\nSTEP: create ASG for non-anchor nodes in region {}\n",
+                    r.region
+                )),
+                ResetColor
+            )?;
+            let regional_config = rt
+                .block_on(aws::load_config(Some(r.region.clone())))
+                .expect("failed to aws::load_config");
+            let regional_cloudformation_manager = cloudformation::Manager::new(&regional_config);
+            let regional_ec2_manager = ec2::Manager::new(&regional_config);
+            let stack_name = r.cloudformation_asg_non_anchor_nodes.clone().unwrap();
+
+            // the nodes share the primary bucket, KMS key, and CloudWatch resources,
+            // and are not registered to the NLB in the primary region
+            let mut overrides = vec![
+                build_param("NodeKind", "non-anchor"),
+                build_param(
+                    "AsgDesiredCapacity",
+                    format!("{}", r.non_anchor_nodes).as_str(),
+                ),
+                build_param("HomeRegion", &aws_resources.region),
+                build_param("InstallS3BucketName", &r.s3_bucket.clone().unwrap()),
+                build_param("Ec2KeyPairName", &r.ec2_key_name.clone().unwrap()),
+                build_param(
+                    "PublicSubnetIds",
+                    &r.cloudformation_vpc_public_subnet_ids
+                        .clone()
+                        .unwrap()
+                        .join(","),
+                ),
+                build_param(
+                    "SecurityGroupId",
+                    &r.cloudformation_vpc_security_group_id.clone().unwrap(),
+                ),
+                build_param("NlbVpcId", &r.cloudformation_vpc_id.clone().unwrap()),
+                build_param("NlbEnabled", "false"),
+            ];
+            if let Some(purchase_options) = &spec.machine.purchase_options {
+                overrides.extend(purchase_options.to_parameters());
+            }
+            rt.block_on(regional_cloudformation_manager.create_stack(
+                stack_name.as_str(),
+                None,
+                OnFailure::Delete,
+                cloudformation_asg_tmpl,
+                Some(Vec::from([
+                    Tag::builder().key("KIND").value("avalanche-ops").build(),
+                ])),
+                Some(override_params(&asg_parameters, overrides)),
+            ))
+            .unwrap();
+
+            let mut wait_secs = 300 + 60 * r.non_anchor_nodes as u64;
+            if wait_secs > MAX_WAIT_SECONDS {
+                wait_secs = MAX_WAIT_SECONDS;
+            }
+            thread::sleep(Duration::from_secs(30));
+            let stack = rt
+                .block_on(regional_cloudformation_manager.poll_stack(
+                    stack_name.as_str(),
+                    StackStatus::CreateComplete,
+                    Duration::from_secs(wait_secs),
+                    Duration::from_secs(30),
+                ))
+                .unwrap();
+            for o in stack.outputs.unwrap() {
+                let k = o.output_key.unwrap();
+                let v = o.output_value.unwrap();
+                info!("stack output key=[{}], value=[{}]", k, v,);
+                if k.eq("AsgLogicalId") {
+                    r.cloudformation_asg_non_anchor_nodes_logical_id = Some(v);
+                }
+            }
+            let asg_name = match &r.cloudformation_asg_non_anchor_nodes_logical_id {
+                Some(v) => v.clone(),
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::Other,
+                        format!(
+                            "cloudformation_asg_non_anchor_nodes_logical_id not found in region {}",
+                            r.region
+                        ),
+                    ));
+                }
+            };
+            update_regional_resources(&mut aws_resources, r);
+            spec.aws_resources = Some(aws_resources.clone());
+            spec.sync(spec_file_path)?;
+            created = true;
+
+            let droplets = rt
+                .block_on(regional_ec2_manager.list_asg(&asg_name))
+                .unwrap();
+            let ec2_key_path = r.ec2_key_path.clone().unwrap();
+            let f = File::open(&ec2_key_path).unwrap();
+            f.set_permissions(PermissionsExt::from_mode(0o444)).unwrap();
+            writeln!(output.console(), "\nchmod 400 {}", ec2_key_path)?;
+            for d in droplets {
+                writeln!(
+                    output.console(),
+                    "# instance '{}' ({}, {})\nssh -o \"StrictHostKeyChecking no\" -i {} ubuntu@{}\naws ssm start-session --region {} --target {}",
+                    d.instance_id,
+                    d.instance_state_name,
+                    d.availability_zone,
+                    ec2_key_path,
+                    d.public_ipv4,
+                    r.region,
+                    d.instance_id,
+                )?;
+            }
+            writeln!(output.console())?;
+        }
+
+        if created {
+            // regional nodes publish to the same primary bucket
+            let target_nodes = spec.machine.non_anchor_nodes
+                + regional_resources
+                    .iter()
+                    .map(|r| r.non_anchor_nodes)
+                    .sum::<u32>();
+            let s3_dir = avalanche_ops_aws::StorageNamespace::DiscoverReadyNonAnchorNodesDir(
+                spec.id.clone(),
+            );
+            let mut objects: Vec<Object>;
+            loop {
+                thread::sleep(Duration::from_secs(30));
+                objects = rt
+                    .block_on(s3_manager.list_objects(
+                        Arc::new(aws_resources.s3_bucket.clone()),
+                        Some(Arc::new(s3::append_slash(&s3_dir.encode()))),
+                    ))
+                    .unwrap();
+                info!(
+                    "{} non-anchor nodes are ready across {} regions (expecting {} nodes)",
+                    objects.len(),
+                    regional_resources.len() + 1,
+                    target_nodes
+                );
+                if objects.len() as u32 >= target_nodes {
+                    break;
+                }
+                if term.load(Ordering::Relaxed) {
+                    warn!("received signal {}", signal_hook::consts::SIGINT);
+                    return Err(Error::new(
+                        ErrorKind::Interrupted,
+                        "interrupted while waiting for regional non-anchor nodes",
+                    ));
+                }
+            }
+
+            current_nodes.retain(|n| n.kind != node::Kind::NonAnchor.as_str());
+            for obj in objects.iter() {
+                let s3_key = obj.key().unwrap();
+                let non_anchor_node =
+                    avalanche_ops_aws::StorageNamespace::parse_node_from_path(s3_key).unwrap();
+                current_nodes.push(non_anchor_node);
+            }
+            spec.current_nodes = Some(current_nodes.clone());
+            spec.sync(spec_file_path)?;
+            put_spec(&rt, &provider, &spec)?;
+        }
+    }
+
+    // with the restricted ingress range, the nodes cannot reach each other
+    // (across the regions or not) via the public IPs unless allowed
+    // NOTE: replaced instances need another "apply" to be allowed
+    let ingress_ipv4_range = aws_resources
+        .ingress_ipv4_range
+        .clone()
+        .unwrap_or_else(|| aws::DEFAULT_INGRESS_IPV4_RANGE.to_string());
+    if spec.ingress_policy.is_some() {
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Green),
+            Print("\n\n\nSTEP: reconcile security group ingress rules with all nodes\n"),
+            ResetColor
+        )?;
+        let node_public_ips = node_public_ips(&rt, &spec, &current_nodes)?;
+        reconcile_ingress(&rt, &spec, &aws_resources, &node_public_ips, output)?;
+    } else if ingress_ipv4_range != aws::DEFAULT_INGRESS_IPV4_RANGE && !current_nodes.is_empty() {
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Green),
+            Print("\n\n\nSTEP: allow staking port traffic between nodes\n"),
+            ResetColor
+        )?;
+        let cidr_ips: Vec<String> = current_nodes
+            .iter()
+            .map(|n| avalanche_ops_aws::host_cidr(&n.public_ip))
+            .collect();
+        let staking_port = spec.avalanchego_config.staking_port;
+        rt.block_on(
+            ec2_manager.authorize_ingress_tcp(
+                &aws_resources
+                    .cloudformation_vpc_security_group_id
+                    .clone()
+                    .unwrap(),
+                staking_port,
+                &cidr_ips,
+            ),
+        )
+        .unwrap();
+        for r in aws_resources.regional_resources.clone().unwrap_or_default() {
+            let regional_config = rt
+                .block_on(aws::load_config(Some(r.region.clone())))
+                .expect("failed to aws::load_config");
+            rt.block_on(ec2::Manager::new(&regional_config).authorize_ingress_tcp(
+                &r.cloudformation_vpc_security_group_id.clone().unwrap(),
+                staking_port,
+                &cidr_ips,
+            ))
+            .unwrap();
+        }
+    }
+
     spec.current_nodes = Some(current_nodes.clone());
     spec.sync(spec_file_path)?;
 
     execute!(
-        stdout(),
+        output.console(),
         SetForegroundColor(Color::Green),
         Print("\n\n\nSTEP: listing all nodes based on S3 keys...\n"),
         ResetColor
     )?;
     for node in current_nodes.iter() {
-        println!("{}", node.encode_yaml().unwrap());
+        writeln!(output.console(), "{}", node.encode_yaml().unwrap())?;
     }
 
     execute!(
-        stdout(),
+        output.console(),
         SetForegroundColor(Color::Green),
         Print("\n\n\nSTEP: nodes are ready -- check the following endpoints!\n"),
         ResetColor
@@ -1062,7 +1806,7 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
     let dns_name = aws_resources.cloudformation_asg_nlb_dns_name.unwrap();
     let http_port = spec.avalanchego_config.http_port;
 
-    let nlb_https_enabled = aws_resources.nlb_acm_certificate_arn.is_some();
+    let nlb_https_enabled = spec.load_balancer_https_enabled();
     let https_enabled = spec.avalanchego_config.http_tls_enabled.is_some()
         && spec.avalanchego_config.http_tls_enabled.unwrap();
 
@@ -1095,16 +1839,30 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
     dns_endpoints.websocket = Some(format!("ws://{}:{}/ext/bc/C/rpc", dns_name, port_for_dns));
     spec.endpoints = Some(dns_endpoints.clone());
     spec.sync(spec_file_path)?;
-    rt.block_on(s3_manager.put_object(
-        Arc::new(spec_file_path.to_string()),
-        Arc::new(aws_resources.s3_bucket.clone()),
-        Arc::new(avalanche_ops_aws::StorageNamespace::ConfigFile(spec.id.clone()).encode()),
-    ))
-    .expect("failed put_object ConfigFile");
-    println!();
+    put_spec(&rt, &provider, &spec)?;
+    writeln!(output.console())?;
 
-    println!("{}", dns_endpoints.encode_yaml().unwrap());
-    println!();
+    writeln!(output.console(), "{}", dns_endpoints.encode_yaml().unwrap())?;
+    writeln!(output.console())?;
+
+    if let (Some(anchor_endpoints), Some(eips)) =
+        (&spec.machine.anchor_endpoints, &aws_resources.anchor_eips)
+    {
+        for eip in eips.iter() {
+            let index = eip.anchor_index.unwrap_or_default();
+            match anchor_endpoints.dns_name(index) {
+                Some(name) => writeln!(
+                    output.console(),
+                    "anchor node {}: {} ({})",
+                    index,
+                    eip.public_ip,
+                    name
+                )?,
+                None => writeln!(output.console(), "anchor node {}: {}", index, eip.public_ip)?,
+            }
+        }
+        writeln!(output.console())?;
+    }
 
     let mut success = false;
     for _ in 0..10_u8 {
@@ -1186,20 +1944,39 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
             );
             return Err(Error::new(ErrorKind::Other, "health/liveness check failed"));
         }
-        println!("{}/ext/metrics", node.http_endpoint);
-        println!("{}/ext/health", node.http_endpoint);
-        println!("{}/ext/health/liveness", node.http_endpoint);
+        writeln!(output.console(), "{}/ext/metrics", node.http_endpoint)?;
+        writeln!(output.console(), "{}/ext/health", node.http_endpoint)?;
+        writeln!(
+            output.console(),
+            "{}/ext/health/liveness",
+            node.http_endpoint
+        )?;
         uris.push(node.http_endpoint.clone());
     }
-    println!("\nURIs: {}", uris.join(","));
+    writeln!(output.console(), "\nURIs: {}", uris.join(","))?;
 
-    println!();
+    writeln!(output.console())?;
     info!("apply all success!");
+    let notifier = notify::Notifier::new(
+        &spec.id,
+        spec.notifications.clone().unwrap_or_default(),
+        &shared_config,
+    );
+    rt.block_on(notifier.notify(
+        notify::Event::StackCreated,
+        None,
+        &format!(
+            "{} nodes of network ID {} are healthy at {}",
+            current_nodes.len(),
+            spec.avalanchego_config.network_id,
+            http_rpc
+        ),
+    ));
 
-    println!();
-    println!("# run the following to check balances");
+    writeln!(output.console())?;
+    writeln!(output.console(), "# run the following to check balances")?;
     execute!(
-        stdout(),
+        output.console(),
         SetForegroundColor(Color::Green),
         Print(format!(
             "{} check-balances \\\n--spec-file-path {}\n",
@@ -1209,10 +1986,10 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         ResetColor
     )?;
 
-    println!();
-    println!("# run the following to get all node IDs");
+    writeln!(output.console())?;
+    writeln!(output.console(), "# run the following to get all node IDs")?;
     execute!(
-        stdout(),
+        output.console(),
         SetForegroundColor(Color::Green),
         Print(format!(
             "{} read-spec \\\n--spec-file-path {} \\\n--node-ids\n",
@@ -1222,10 +1999,10 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         ResetColor
     )?;
 
-    println!();
-    println!("# run the following to delete resources");
+    writeln!(output.console())?;
+    writeln!(output.console(), "# run the following to delete resources")?;
     execute!(
-        stdout(),
+        output.console(),
         SetForegroundColor(Color::Green),
         Print(format!(
             "{} delete \\\n--delete-cloudwatch-log-group \\\n--delete-s3-objects \\\n--spec-file-path {}\n",
@@ -1240,16 +2017,20 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
             home_dir::named(&spec.id, Some(".subnet-evm.genesis.json"));
         let subnet_evm_genesis = spec
             .subnet_evm_genesis
+            .as_ref()
             .expect("unexpected None subnet_evm_genesis");
-        println!();
+        writeln!(output.console())?;
         subnet_evm_genesis
             .sync(&subnet_evm_genesis_file_path)
             .expect("failed subnet_evm_genesis.sync");
 
-        println!();
-        println!("# [optional] run the following to create subnet-evm resources");
+        writeln!(output.console())?;
+        writeln!(
+            output.console(),
+            "# [optional] run the following to create subnet-evm resources"
+        )?;
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Magenta),
             Print(format!("cat {} | grep private_key_hex:\n", spec_file_path)),
             ResetColor
@@ -1257,9 +2038,10 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
 
         let keys = spec
             .generated_seed_private_keys
+            .as_ref()
             .expect("unexpected None generated_seed_private_keys");
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Cyan),
             Print(format!(
                 "cat <<EOF > /tmp/test.key\n{}\nEOF\ncat /tmp/test.key\n",
@@ -1269,32 +2051,38 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         )?;
 
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Magenta),
             Print(format!("cat {} | grep http_rpc:\n", spec_file_path)),
             ResetColor
         )?;
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Magenta),
             Print(format!("cat {}\n", subnet_evm_genesis_file_path)),
             ResetColor
         )?;
 
-        let endpoints = spec.endpoints.expect("unexpected None spec.endpoints");
+        let endpoints = spec
+            .endpoints
+            .clone()
+            .expect("unexpected None spec.endpoints");
         let http_rpc = endpoints
             .http_rpc
             .expect("unexpected None endpoints.http_rpc");
-        let nodes = spec.current_nodes.expect("unexpected None current_nodes");
+        let nodes = spec
+            .current_nodes
+            .clone()
+            .expect("unexpected None current_nodes");
         let mut all_node_ids: Vec<String> = Vec::new();
         for node in nodes.iter() {
             all_node_ids.push(node.clone().node_id);
         }
 
         for node_id in all_node_ids.iter() {
-            println!();
+            writeln!(output.console())?;
             execute!(
-                stdout(),
+                output.console(),
                 SetForegroundColor(Color::Green),
                 Print(format!(
                     "subnet-cli add validator \\\n--enable-prompt \\\n--private-key-path=/tmp/test.key \\\n--public-uri={} \\\n--stake-amount=2000000000000 \\\n--validate-reward-fee-percent=2 \\\n--node-ids=\"{}\"\n",
@@ -1304,9 +2092,9 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
             )?;
         }
 
-        println!();
+        writeln!(output.console())?;
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Green),
             Print(format!(
                 "subnet-cli wizard \\\n--enable-prompt \\\n--private-key-path=/tmp/test.key \\\n--public-uri={} \\\n--vm-genesis-path={} \\\n--vm-id=srEXiWaHuhNyGwPUi444Tu47ZEDwxTWrbQiuD7FmgSAQ6X7Dy \\\n--chain-name=subnetevm \\\n--node-ids=\"{}\"\n",
@@ -1316,9 +2104,875 @@ pub fn execute(log_level: &str, spec_file_path: &str, skip_prompt: bool) -> io::
         )?;
     }
 
+    output.print(&output::Cluster::from_spec(&spec))
+}
+
+/// Uploads the install artifacts to the bucket,
+/// to be downloaded by the nodes while bootstrapping.
+fn put_install_artifacts(
+    rt: &Runtime,
+    s3_manager: &s3::Manager,
+    spec: &avalanche_ops_aws::Spec,
+    s3_bucket: &str,
+) -> io::Result<()> {
+    // don't compress since we need to download this in user data
+    // while instance bootstrapping
+    rt.block_on(s3_manager.put_object(
+        Arc::new(spec.install_artifacts.avalanched_bin.clone()),
+        Arc::new(s3_bucket.to_string()),
+        Arc::new(avalanche_ops_aws::StorageNamespace::AvalanchedBin(spec.id.clone()).encode()),
+    ))
+    .expect("failed put_object install_artifacts.avalanched_bin");
+
+    // compress as these will be decompressed by "avalanched"
+    let tmp_avalanche_bin_compressed_path =
+        random::tmp_path(15, Some(compress::Encoder::Zstd(3).ext())).unwrap();
+    compress::pack_file(
+        &spec.install_artifacts.avalanchego_bin,
+        &tmp_avalanche_bin_compressed_path,
+        compress::Encoder::Zstd(3),
+    )
+    .expect("failed pack_file install_artifacts.avalanched_bin");
+    rt.block_on(s3_manager.put_object(
+        Arc::new(tmp_avalanche_bin_compressed_path.clone()),
+        Arc::new(s3_bucket.to_string()),
+        Arc::new(
+            avalanche_ops_aws::StorageNamespace::AvalancheBinCompressed(spec.id.clone()).encode(),
+        ),
+    ))
+    .expect("failed put_object compressed avalanchego_bin");
+    fs::remove_file(tmp_avalanche_bin_compressed_path)?;
+    if spec.install_artifacts.plugins_dir.is_some() {
+        let plugins_dir = spec.install_artifacts.plugins_dir.clone().unwrap();
+        for entry in fs::read_dir(plugins_dir.as_str()).unwrap() {
+            let entry = entry.unwrap();
+            let entry_path = entry.path();
+
+            let file_path = entry_path.to_str().unwrap();
+            let file_name = entry.file_name();
+            let file_name = file_name.as_os_str().to_str().unwrap();
+
+            let tmp_plugin_compressed_path =
+                random::tmp_path(15, Some(compress::Encoder::Zstd(3).ext())).unwrap();
+            compress::pack_file(
+                file_path,
+                &tmp_plugin_compressed_path,
+                compress::Encoder::Zstd(3),
+            )
+            .unwrap();
+
+            info!(
+                "uploading {} (compressed from {}) from plugins directory {}",
+                tmp_plugin_compressed_path, file_path, plugins_dir,
+            );
+            rt.block_on(s3_manager.put_object(
+                Arc::new(tmp_plugin_compressed_path.clone()),
+                Arc::new(s3_bucket.to_string()),
+                Arc::new(format!(
+                    "{}/{}{}",
+                    &avalanche_ops_aws::StorageNamespace::PluginsDir(spec.id.clone()).encode(),
+                    file_name,
+                    compress::Encoder::Zstd(3).ext()
+                )),
+            ))
+            .expect("failed put_object tmp_plugin_compressed_path");
+            fs::remove_file(tmp_plugin_compressed_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the public IPs of the nodes that "ingress_policy" allows
+/// to the staking port: the running instances of the node ASGs in all regions
+/// (e.g., the replaced nodes that "avalanched" allowed on boot), the nodes
+/// launched by this "apply", and the nodes in the spec.
+fn node_public_ips(
+    rt: &Runtime,
+    spec: &avalanche_ops_aws::Spec,
+    current_nodes: &[avalanche_ops_aws::Node],
+) -> io::Result<Vec<String>> {
+    let mut ips: Vec<String> = current_nodes
+        .iter()
+        .chain(spec.current_nodes.iter().flatten())
+        .map(|n| n.public_ip.clone())
+        .collect();
+    if let Some(aws_resources) = &spec.aws_resources {
+        if let Some(eips) = &aws_resources.anchor_eips {
+            ips.extend(eips.iter().map(|e| e.public_ip.clone()));
+        }
+        for (region, asg_name) in aws_resources.node_asg_names() {
+            let shared_config = rt.block_on(aws::load_config(Some(region)))?;
+            let ec2_manager = ec2::Manager::new(&shared_config);
+            for d in rt.block_on(ec2_manager.list_asg(&asg_name))? {
+                if (d.instance_state_name == "pending" || d.instance_state_name == "running")
+                    && !d.public_ipv4.is_empty()
+                {
+                    ips.push(d.public_ipv4);
+                }
+            }
+        }
+    }
+    ips.retain(|ip| !ip.is_empty());
+    ips.sort();
+    ips.dedup();
+    Ok(ips)
+}
+
+/// Diffs the security group against "ingress_policy".
+fn diff_ingress(
+    rt: &Runtime,
+    ec2_manager: &ec2::Manager,
+    security_group_id: &str,
+    spec: &avalanche_ops_aws::Spec,
+    node_public_ips: &[String],
+) -> io::Result<ec2::sg::IngressDiff> {
+    let ingress_policy = spec.ingress_policy.clone().unwrap_or_default();
+    let (http_port, staking_port) = (
+        spec.avalanchego_config.http_port,
+        spec.avalanchego_config.staking_port,
+    );
+    let desired = ingress_policy.ingress_rules(http_port, staking_port, VPC_CIDR, node_public_ips);
+    let current = rt.block_on(ec2_manager.list_ingress_tcp(security_group_id))?;
+    Ok(ec2::sg::diff_ingress(
+        &current,
+        &desired,
+        &avalanche_ops_aws::IngressPolicy::managed_ports(http_port, staking_port),
+    ))
+}
+
+/// Converges the security groups of all regions to "ingress_policy".
+fn reconcile_ingress(
+    rt: &Runtime,
+    spec: &avalanche_ops_aws::Spec,
+    aws_resources: &aws::Resources,
+    node_public_ips: &[String],
+    output: output::Format,
+) -> io::Result<()> {
+    for (region, security_group_id) in aws_resources.security_groups() {
+        let shared_config = rt.block_on(aws::load_config(Some(region.clone())))?;
+        let ec2_manager = ec2::Manager::new(&shared_config);
+
+        let diff = diff_ingress(rt, &ec2_manager, &security_group_id, spec, node_public_ips)?;
+        if diff.is_empty() {
+            info!("'{}' in {} already up-to-date", security_group_id, region);
+            continue;
+        }
+        writeln!(output.console(), "# '{}' in {}", security_group_id, region)?;
+        for r in diff.authorize.iter() {
+            writeln!(output.console(), "+ {}", r)?;
+        }
+        for r in diff.revoke.iter() {
+            writeln!(output.console(), "- {}", r)?;
+        }
+        rt.block_on(ec2_manager.apply_ingress_diff(&security_group_id, &diff))?;
+    }
     Ok(())
 }
 
+/// Creates the VPC stack, and returns the VPC ID, the security group ID,
+/// and the public subnet IDs.
+fn create_vpc(
+    rt: &Runtime,
+    cloudformation_manager: &cloudformation::Manager,
+    vpc_tmpl: &str,
+    vpc_stack_name: &str,
+    spec: &avalanche_ops_aws::Spec,
+) -> io::Result<(String, String, Vec<String>)> {
+    // "ingress_policy" rules are managed outside of the stack, so "apply" can diff them
+    let aws_resources = spec.aws_resources.clone().unwrap();
+    let (ingress_ipv4_range, ingress_ipv6_range) = if spec.ingress_policy.is_some() {
+        (String::new(), String::new())
+    } else {
+        (
+            aws_resources
+                .ingress_ipv4_range
+                .clone()
+                .unwrap_or_else(|| aws::DEFAULT_INGRESS_IPV4_RANGE.to_string()),
+            aws_resources
+                .ingress_ipv6_range
+                .clone()
+                .unwrap_or_else(|| aws::DEFAULT_INGRESS_IPV6_RANGE.to_string()),
+        )
+    };
+    let vpc_params = Vec::from([
+        build_param("Id", &spec.id),
+        build_param("VpcCidr", VPC_CIDR),
+        build_param("PublicSubnetCidr1", "10.0.64.0/19"),
+        build_param("PublicSubnetCidr2", "10.0.128.0/19"),
+        build_param("PublicSubnetCidr3", "10.0.192.0/19"),
+        build_param("IngressIpv4Range", &ingress_ipv4_range),
+        build_param(
+            "DualStack",
+            &aws_resources.dual_stack.unwrap_or(false).to_string(),
+        ),
+        build_param("IngressIpv6Range", &ingress_ipv6_range),
+        build_param(
+            "StakingPort",
+            format!("{}", spec.avalanchego_config.staking_port).as_str(),
+        ),
+        build_param(
+            "HttpPort",
+            format!("{}", spec.avalanchego_config.http_port).as_str(),
+        ),
+    ]);
+    rt.block_on(cloudformation_manager.create_stack(
+        vpc_stack_name,
+        None,
+        OnFailure::Delete,
+        vpc_tmpl,
+        Some(Vec::from([
+            Tag::builder().key("KIND").value("avalanche-ops").build(),
+        ])),
+        Some(vpc_params),
+    ))
+    .expect("failed create_stack for VPC");
+
+    thread::sleep(Duration::from_secs(10));
+    let stack = rt
+        .block_on(cloudformation_manager.poll_stack(
+            vpc_stack_name,
+            StackStatus::CreateComplete,
+            Duration::from_secs(300),
+            Duration::from_secs(30),
+        ))
+        .expect("failed poll_stack for VPC");
+
+    let (mut vpc_id, mut security_group_id, mut public_subnet_ids) = (None, None, None);
+    for o in stack.outputs.unwrap() {
+        let k = o.output_key.unwrap();
+        let v = o.output_value.unwrap();
+        info!("stack output key=[{}], value=[{}]", k, v,);
+        if k.eq("VpcId") {
+            vpc_id = Some(v);
+            continue;
+        }
+        if k.eq("SecurityGroupId") {
+            security_group_id = Some(v);
+            continue;
+        }
+        if k.eq("PublicSubnetIds") {
+            let splits: Vec<&str> = v.split(',').collect();
+            let mut pub_subnets: Vec<String> = vec![];
+            for s in splits {
+                info!("public subnet {}", s);
+                pub_subnets.push(String::from(s));
+            }
+            public_subnet_ids = Some(pub_subnets);
+        }
+    }
+    match (vpc_id, security_group_id, public_subnet_ids) {
+        (Some(vpc_id), Some(security_group_id), Some(public_subnet_ids)) => {
+            Ok((vpc_id, security_group_id, public_subnet_ids))
+        }
+        _ => Err(Error::new(
+            ErrorKind::Other,
+            format!("VPC stack '{}' outputs not found", vpc_stack_name),
+        )),
+    }
+}
+
+/// Uploads the spec without the seed private keys to the remote storage,
+/// since the keys are shipped envelope-encrypted.
+/// Computes what "apply" would create or update, without mutating anything.
+/// The existing stacks are kept as is by "apply", so their change sets
+/// with the current templates are only reported.
+fn compute_plan(
+    rt: &Runtime,
+    spec: &avalanche_ops_aws::Spec,
+    aws_resources: &aws::Resources,
+) -> io::Result<avalanche_ops_aws::Plan> {
+    let shared_config = rt.block_on(aws::load_config(Some(aws_resources.region.clone())))?;
+    let s3_manager = s3::Manager::new(&shared_config);
+    let cloudformation_manager = cloudformation::Manager::new(&shared_config);
+    let region = aws_resources.region.as_str();
+
+    let role_yaml = Asset::get("cfn-templates/ec2_instance_role.yaml").unwrap();
+    let role_tmpl = std::str::from_utf8(role_yaml.data.as_ref()).unwrap();
+    let vpc_yaml = Asset::get("cfn-templates/vpc.yaml").unwrap();
+    let vpc_tmpl = std::str::from_utf8(vpc_yaml.data.as_ref()).unwrap();
+    let asg_yaml = Asset::get("cfn-templates/asg_amd64_ubuntu.yaml").unwrap();
+    let asg_tmpl = std::str::from_utf8(asg_yaml.data.as_ref()).unwrap();
+
+    let mut resources = Vec::new();
+    let mut s3_buckets = vec![aws_resources.s3_bucket.clone()];
+    if let Some(db_backup_s3_bucket) = &aws_resources.db_backup_s3_bucket {
+        s3_buckets.push(db_backup_s3_bucket.clone());
+    }
+    let mut s3_bucket_exists = false;
+    for (i, s3_bucket) in s3_buckets.iter().enumerate() {
+        let exists = rt.block_on(s3_manager.bucket_exists(s3_bucket))?;
+        if i == 0 {
+            s3_bucket_exists = exists;
+        }
+        resources.push(planned_resource(
+            "s3-bucket",
+            s3_bucket,
+            region,
+            !exists,
+            Vec::new(),
+        ));
+    }
+
+    // the nodes read the spec in the bucket, so its changes are the node config changes
+    let spec_s3_key = avalanche_ops_aws::StorageNamespace::ConfigFile(spec.id.clone()).encode();
+    let deployed_spec = if s3_bucket_exists {
+        fetch_spec(rt, &s3_manager, &aws_resources.s3_bucket, &spec_s3_key)?
+    } else {
+        None
+    };
+    let spec_changes = match &deployed_spec {
+        Some(deployed) => spec.diff(deployed)?,
+        None => Vec::new(),
+    };
+    resources.push(avalanche_ops_aws::PlannedResource {
+        kind: String::from("s3-object"),
+        name: format!("{}/{}", aws_resources.s3_bucket, spec_s3_key),
+        region: region.to_string(),
+        action: if deployed_spec.is_none() {
+            avalanche_ops_aws::PlanAction::Create
+        } else if spec_changes.is_empty() {
+            avalanche_ops_aws::PlanAction::Keep
+        } else {
+            avalanche_ops_aws::PlanAction::Update
+        },
+        details: Vec::new(),
+    });
+
+    resources.push(planned_resource(
+        "kms-key",
+        &aws_resources
+            .kms_cmk_id
+            .clone()
+            .unwrap_or_else(|| format!("{}-cmk", spec.id)),
+        region,
+        aws_resources.kms_cmk_id.is_none() && aws_resources.kms_cmk_arn.is_none(),
+        Vec::new(),
+    ));
+    resources.push(planned_resource(
+        "ec2-key-pair",
+        &aws_resources.ec2_key_name.clone().unwrap(),
+        region,
+        aws_resources.ec2_key_path.is_none(),
+        Vec::new(),
+    ));
+
+    let role_stack_name = aws_resources
+        .cloudformation_ec2_instance_role
+        .clone()
+        .unwrap();
+    resources.push(
+        if aws_resources
+            .cloudformation_ec2_instance_profile_arn
+            .is_none()
+        {
+            planned_resource(
+                "cloudformation-stack",
+                &role_stack_name,
+                region,
+                true,
+                Vec::new(),
+            )
+        } else {
+            preview_stack(
+                rt,
+                &cloudformation_manager,
+                &role_stack_name,
+                region,
+                Some(vec![Capability::CapabilityNamedIam]),
+                role_tmpl,
+                None,
+            )?
+        },
+    );
+
+    let vpc_stack_name = aws_resources.cloudformation_vpc.clone().unwrap();
+    resources.push(if aws_resources.cloudformation_vpc_id.is_none() {
+        planned_resource(
+            "cloudformation-stack",
+            &vpc_stack_name,
+            region,
+            true,
+            Vec::new(),
+        )
+    } else {
+        preview_stack(
+            rt,
+            &cloudformation_manager,
+            &vpc_stack_name,
+            region,
+            None,
+            vpc_tmpl,
+            None,
+        )?
+    });
+    if let Some(security_group_id) = &aws_resources.cloudformation_vpc_security_group_id {
+        if spec.ingress_policy.is_some() {
+            resources.push(plan_ingress(
+                rt,
+                &ec2::Manager::new(&shared_config),
+                security_group_id,
+                region,
+                spec,
+            )?);
+        }
+    }
+
+    if let Some(load_balancer_stack_name) = &aws_resources.cloudformation_load_balancer {
+        let load_balancer_yaml = Asset::get("cfn-templates/load_balancer.yaml").unwrap();
+        let load_balancer_tmpl = std::str::from_utf8(load_balancer_yaml.data.as_ref()).unwrap();
+        let exists = rt
+            .block_on(cloudformation_manager.describe_stack(load_balancer_stack_name))?
+            .is_some();
+        resources.push(if !exists {
+            planned_resource(
+                "cloudformation-stack",
+                load_balancer_stack_name,
+                region,
+                true,
+                Vec::new(),
+            )
+        } else {
+            preview_stack(
+                rt,
+                &cloudformation_manager,
+                load_balancer_stack_name,
+                region,
+                None,
+                load_balancer_tmpl,
+                None,
+            )?
+        });
+    }
+
+    // apply creates the log group if missing, and always sets the retention
+    resources.push(avalanche_ops_aws::PlannedResource {
+        kind: String::from("cloudwatch-log-group"),
+        name: spec.id.clone(),
+        region: region.to_string(),
+        action: avalanche_ops_aws::PlanAction::Update,
+        details: vec![format!(
+            "retention {} day(s)",
+            aws_resources
+                .instance_logs_retention_in_days
+                .unwrap_or(cloudwatch::logs::DEFAULT_RETENTION_IN_DAYS)
+        )],
+    });
+
+    let mut asg_stacks = Vec::new();
+    if spec.machine.anchor_nodes.unwrap_or(0) > 0 {
+        asg_stacks.push((
+            aws_resources
+                .cloudformation_asg_anchor_nodes
+                .clone()
+                .unwrap(),
+            aws_resources
+                .cloudformation_asg_anchor_nodes_logical_id
+                .is_none(),
+            spec.machine.anchor_nodes.unwrap(),
+        ));
+    }
+    asg_stacks.push((
+        aws_resources
+            .cloudformation_asg_non_anchor_nodes
+            .clone()
+            .unwrap(),
+        aws_resources
+            .cloudformation_asg_non_anchor_nodes_logical_id
+            .is_none(),
+        spec.machine.non_anchor_nodes,
+    ));
+    if let Some(api_nodes) = &spec.machine.api_nodes {
+        asg_stacks.push((
+            aws_resources.cloudformation_asg_api_nodes.clone().unwrap(),
+            aws_resources
+                .cloudformation_asg_api_nodes_logical_id
+                .is_none(),
+            api_nodes.nodes,
+        ));
+    }
+    for (stack_name, create, desired_capacity) in asg_stacks.iter() {
+        let details = vec![format!("{} node(s)", desired_capacity)];
+        resources.push(if *create {
+            planned_resource("cloudformation-stack", stack_name, region, true, details)
+        } else {
+            preview_stack(
+                rt,
+                &cloudformation_manager,
+                stack_name,
+                region,
+                None,
+                asg_tmpl,
+                Some(vec![build_param(
+                    "AsgDesiredCapacity",
+                    format!("{}", desired_capacity).as_str(),
+                )]),
+            )?
+        });
+    }
+
+    for r in aws_resources
+        .regional_resources
+        .clone()
+        .unwrap_or_default()
+        .iter()
+    {
+        let regional_config = rt.block_on(aws::load_config(Some(r.region.clone())))?;
+        let regional_cloudformation_manager = cloudformation::Manager::new(&regional_config);
+
+        let regional_s3_bucket = r.s3_bucket.clone().unwrap();
+        let exists =
+            rt.block_on(s3::Manager::new(&regional_config).bucket_exists(&regional_s3_bucket))?;
+        resources.push(planned_resource(
+            "s3-bucket",
+            &regional_s3_bucket,
+            &r.region,
+            !exists,
+            Vec::new(),
+        ));
+        resources.push(planned_resource(
+            "ec2-key-pair",
+            &r.ec2_key_name.clone().unwrap(),
+            &r.region,
+            r.ec2_key_path.is_none(),
+            Vec::new(),
+        ));
+
+        let vpc_stack_name = r.cloudformation_vpc.clone().unwrap();
+        resources.push(if r.cloudformation_vpc_id.is_none() {
+            planned_resource(
+                "cloudformation-stack",
+                &vpc_stack_name,
+                &r.region,
+                true,
+                Vec::new(),
+            )
+        } else {
+            preview_stack(
+                rt,
+                &regional_cloudformation_manager,
+                &vpc_stack_name,
+                &r.region,
+                None,
+                vpc_tmpl,
+                None,
+            )?
+        });
+        if let Some(security_group_id) = &r.cloudformation_vpc_security_group_id {
+            if spec.ingress_policy.is_some() {
+                resources.push(plan_ingress(
+                    rt,
+                    &ec2::Manager::new(&regional_config),
+                    security_group_id,
+                    &r.region,
+                    spec,
+                )?);
+            }
+        }
+
+        let asg_stack_name = r.cloudformation_asg_non_anchor_nodes.clone().unwrap();
+        resources.push(
+            if r.cloudformation_asg_non_anchor_nodes_logical_id.is_none() {
+                planned_resource(
+                    "cloudformation-stack",
+                    &asg_stack_name,
+                    &r.region,
+                    true,
+                    vec![format!("{} node(s)", r.non_anchor_nodes)],
+                )
+            } else {
+                preview_stack(
+                    rt,
+                    &regional_cloudformation_manager,
+                    &asg_stack_name,
+                    &r.region,
+                    None,
+                    asg_tmpl,
+                    Some(vec![build_param(
+                        "AsgDesiredCapacity",
+                        format!("{}", r.non_anchor_nodes).as_str(),
+                    )]),
+                )?
+            },
+        );
+    }
+
+    Ok(avalanche_ops_aws::Plan {
+        id: spec.id.clone(),
+        resources,
+        spec_changes,
+        cost: Some(estimate_cost(rt, &shared_config, spec)),
+    })
+}
+
+/// Estimates the monthly cost of the spec, with the offline prices
+/// for anything the price list API does not return.
+fn estimate_cost(
+    rt: &Runtime,
+    shared_config: &AwsSdkConfig,
+    spec: &avalanche_ops_aws::Spec,
+) -> cost::Estimate {
+    let region = spec
+        .aws_resources
+        .as_ref()
+        .map_or_else(String::new, |r| r.region.clone());
+    let pricing_manager = pricing::Manager::new(shared_config);
+    let prices = rt.block_on(cost::PriceTable::fetch(&pricing_manager, &region, spec));
+    cost::estimate(spec, &prices, cost::install_artifacts_gb(spec))
+}
+
+fn planned_resource(
+    kind: &str,
+    name: &str,
+    region: &str,
+    create: bool,
+    details: Vec<String>,
+) -> avalanche_ops_aws::PlannedResource {
+    avalanche_ops_aws::PlannedResource {
+        kind: kind.to_string(),
+        name: name.to_string(),
+        region: region.to_string(),
+        action: if create {
+            avalanche_ops_aws::PlanAction::Create
+        } else {
+            avalanche_ops_aws::PlanAction::Keep
+        },
+        details,
+    }
+}
+
+/// Reports the ingress rules that "apply" would add and remove.
+fn plan_ingress(
+    rt: &Runtime,
+    ec2_manager: &ec2::Manager,
+    security_group_id: &str,
+    region: &str,
+    spec: &avalanche_ops_aws::Spec,
+) -> io::Result<avalanche_ops_aws::PlannedResource> {
+    let diff = diff_ingress(
+        rt,
+        ec2_manager,
+        security_group_id,
+        spec,
+        &node_public_ips(rt, spec, &[])?,
+    )?;
+    let mut details: Vec<String> = diff.authorize.iter().map(|r| format!("+ {}", r)).collect();
+    details.extend(diff.revoke.iter().map(|r| format!("- {}", r)));
+    Ok(avalanche_ops_aws::PlannedResource {
+        kind: String::from("security-group-ingress"),
+        name: security_group_id.to_string(),
+        region: region.to_string(),
+        action: if diff.is_empty() {
+            avalanche_ops_aws::PlanAction::Keep
+        } else {
+            avalanche_ops_aws::PlanAction::Update
+        },
+        details,
+    })
+}
+
+/// Previews the existing stack with the current template.
+/// "apply" does not update the existing stacks, so the stack is kept
+/// and the changes are reported in the details.
+fn preview_stack(
+    rt: &Runtime,
+    cloudformation_manager: &cloudformation::Manager,
+    stack_name: &str,
+    region: &str,
+    capabilities: Option<Vec<Capability>>,
+    tmpl: &str,
+    overrides: Option<Vec<Parameter>>,
+) -> io::Result<avalanche_ops_aws::PlannedResource> {
+    let exists = rt
+        .block_on(cloudformation_manager.describe_stack(stack_name))?
+        .is_some();
+    if !exists {
+        return Ok(planned_resource(
+            "cloudformation-stack",
+            stack_name,
+            region,
+            false,
+            vec![String::from(
+                "not found, but 'apply' skips it based on the spec outputs",
+            )],
+        ));
+    }
+
+    let details = match rt.block_on(cloudformation_manager.preview_stack_update(
+        stack_name,
+        capabilities,
+        tmpl,
+        overrides,
+        Duration::from_secs(300),
+    )) {
+        Ok(change_set) => change_set
+            .changes
+            .iter()
+            .map(|c| format!("{} (not executed by 'apply')", c))
+            .collect(),
+        Err(e) => {
+            warn!("failed to preview stack '{}' ({})", stack_name, e);
+            vec![format!("failed to preview ({})", e.message())]
+        }
+    };
+    Ok(planned_resource(
+        "cloudformation-stack",
+        stack_name,
+        region,
+        false,
+        details,
+    ))
+}
+
+/// Returns the spec in the remote storage, or "None" if not uploaded yet.
+fn fetch_spec(
+    rt: &Runtime,
+    s3_manager: &s3::Manager,
+    s3_bucket: &str,
+    s3_key: &str,
+) -> io::Result<Option<avalanche_ops_aws::Spec>> {
+    let objects = rt.block_on(s3_manager.list_objects(
+        Arc::new(s3_bucket.to_string()),
+        Some(Arc::new(s3_key.to_string())),
+    ))?;
+    if !objects.iter().any(|o| o.key() == Some(s3_key)) {
+        return Ok(None);
+    }
+
+    let tmp_spec_file_path = random::tmp_path(15, Some(".yaml"))?;
+    rt.block_on(s3_manager.get_object(
+        Arc::new(s3_bucket.to_string()),
+        Arc::new(s3_key.to_string()),
+        Arc::new(tmp_spec_file_path.clone()),
+    ))?;
+    let deployed = avalanche_ops_aws::Spec::load(&tmp_spec_file_path)?;
+    fs::remove_file(&tmp_spec_file_path)?;
+    Ok(Some(deployed))
+}
+
+/// Holds the state lock until dropped, so that the lock is also released
+/// on the early returns and the panics (e.g., stack creation failures).
+/// The lock expires after the lease, if the process is killed.
+pub(crate) struct StateLock<'a> {
+    rt: &'a Runtime,
+    dynamodb_manager: dynamodb::Manager,
+    lock_table: String,
+    lock: dynamodb::Lock,
+}
+
+impl Drop for StateLock<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.rt.block_on(
+            self.dynamodb_manager
+                .release_lock(&self.lock_table, &self.lock),
+        ) {
+            warn!(
+                "failed to release lock '{}' ({})",
+                self.lock.lock_id,
+                e.message()
+            );
+        }
+    }
+}
+
+/// Acquires the state lock if "state_backend" is set, then rejects the spec
+/// older than the one in the remote storage. Returns "None" without the lock.
+pub(crate) fn lock_state<'a>(
+    rt: &'a Runtime,
+    shared_config: &AwsSdkConfig,
+    spec: &avalanche_ops_aws::Spec,
+    operation: &str,
+    output: output::Format,
+) -> io::Result<Option<StateLock<'a>>> {
+    let state_backend = match &spec.state_backend {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Green),
+        Print(format!(
+            "\n\n\nSTEP: acquire state lock '{}' in '{}'\n",
+            spec.id, state_backend.lock_table
+        )),
+        ResetColor
+    )?;
+
+    let dynamodb_manager = dynamodb::Manager::new(shared_config);
+    rt.block_on(dynamodb_manager.create_lock_table(&state_backend.lock_table))?;
+
+    let identity = spec
+        .aws_resources
+        .as_ref()
+        .and_then(|r| r.identity.clone())
+        .map_or_else(String::new, |v| v.role_arn);
+    let lock = dynamodb::Lock::new(
+        &spec.id,
+        &format!("{}/{}", identity, random::string(10)),
+        operation,
+        Duration::from_secs(state_backend.lock_lease_seconds),
+    );
+    rt.block_on(dynamodb_manager.acquire_lock(&state_backend.lock_table, &lock))?;
+    let state_lock = StateLock {
+        rt,
+        dynamodb_manager,
+        lock_table: state_backend.lock_table.clone(),
+        lock,
+    };
+
+    // only compared once locked, so no other operator writes in between
+    let aws_resources = spec
+        .aws_resources
+        .clone()
+        .expect("unexpected None aws_resources");
+    let s3_manager = s3::Manager::new(shared_config);
+    if rt.block_on(s3_manager.bucket_exists(&aws_resources.s3_bucket))? {
+        let s3_key = avalanche_ops_aws::StorageNamespace::ConfigFile(spec.id.clone()).encode();
+        if let Some(remote) = fetch_spec(rt, &s3_manager, &aws_resources.s3_bucket, &s3_key)? {
+            avalanche_ops_aws::check_state_serial(spec.state_serial, remote.state_serial)?;
+        }
+    }
+    Ok(Some(state_lock))
+}
+
+pub(crate) fn put_spec(
+    rt: &Runtime,
+    provider: &dyn provider::Provider,
+    spec: &avalanche_ops_aws::Spec,
+) -> io::Result<()> {
+    let contents = spec.redacted().encode_yaml()?;
+    rt.block_on(provider.put_object(
+        &avalanche_ops_aws::StorageNamespace::ConfigFile(spec.id.clone()).encode(),
+        contents.as_bytes(),
+    ))
+}
+
+/// Replaces the parameters of the same keys with the overrides.
+fn override_params(params: &[Parameter], overrides: Vec<Parameter>) -> Vec<Parameter> {
+    let mut merged: Vec<Parameter> = params
+        .iter()
+        .filter(|p| {
+            !overrides
+                .iter()
+                .any(|o| o.parameter_key() == p.parameter_key())
+        })
+        .cloned()
+        .collect();
+    merged.extend(overrides);
+    merged
+}
+
+/// Records the updated regional resources, so that the progress is
+/// persisted region by region.
+fn update_regional_resources(aws_resources: &mut aws::Resources, updated: &aws::RegionalResources) {
+    if let Some(regional_resources) = aws_resources.regional_resources.as_mut() {
+        for r in regional_resources.iter_mut() {
+            if r.region == updated.region {
+                *r = updated.clone();
+            }
+        }
+    }
+}
+
 fn build_param(k: &str, v: &str) -> Parameter {
     Parameter::builder()
         .parameter_key(k)
@@ -1339,3 +2993,17 @@ fn get_ec2_key_path(spec_file_path: &str) -> String {
             .unwrap(),
     )
 }
+
+fn get_regional_ec2_key_path(spec_file_path: &str, region: &str) -> String {
+    let path = Path::new(spec_file_path);
+    let parent_dir = path.parent().unwrap();
+    let name = path.file_stem().unwrap();
+    let new_name = format!("{}-ec2-access.{}.key", name.to_str().unwrap(), region);
+    String::from(
+        parent_dir
+            .join(Path::new(new_name.as_str()))
+            .as_path()
+            .to_str()
+            .unwrap(),
+    )
+}
@@ -0,0 +1,502 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Error, ErrorKind},
+};
+
+use serde::{Deserialize, Serialize};
+
+use avalanche_types::node;
+use avalanchego::config as avalanchego_config;
+use aws::ec2::ebs;
+use coreth::config as coreth_config;
+
+use crate::{upgrade, Machine, Spec};
+
+/// Defines the database volume that outlives the instance.
+/// "avalanched" attaches the volume left by the terminated instance
+/// of the same node kind, or creates a new one before the node starts.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct DataVolume {
+    #[serde(default)]
+    pub volume: ebs::VolumeSpec,
+    /// Interval between the data volume snapshots.
+    /// Zero disables the scheduled snapshots.
+    #[serde(default)]
+    pub snapshot_interval_seconds: u64,
+    /// Number of the most recent snapshots to keep per node kind.
+    #[serde(default = "default_snapshot_retention")]
+    pub snapshot_retention: usize,
+    /// Creates the new volume from the latest snapshot of the same node kind,
+    /// so the replacement node catches up from the snapshot
+    /// rather than bootstrapping from genesis.
+    #[serde(default)]
+    pub restore_from_snapshot: bool,
+}
+
+fn default_snapshot_retention() -> usize {
+    3
+}
+
+impl Default for DataVolume {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl DataVolume {
+    pub fn default() -> Self {
+        Self {
+            volume: ebs::VolumeSpec::default(),
+            snapshot_interval_seconds: 0,
+            snapshot_retention: default_snapshot_retention(),
+            restore_from_snapshot: false,
+        }
+    }
+
+    /// Returns the tags that identify the volumes and snapshots
+    /// shared by the nodes of the same kind.
+    pub fn tags<'a>(id: &'a str, node_kind: &'a str) -> [(&'a str, &'a str); 2] {
+        [("ID", id), ("NODE_KIND", node_kind)]
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        self.volume.validate()?;
+        if self.snapshot_interval_seconds > 0 && self.snapshot_interval_seconds < 3600 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'snapshot_interval_seconds' {} too short (at least 3600)",
+                    self.snapshot_interval_seconds
+                ),
+            ));
+        }
+        if self.snapshot_interval_seconds > 0 && self.snapshot_retention == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'snapshot_retention' must be >0 with scheduled snapshots",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_data_volume() {
+    let data_volume: DataVolume = serde_yaml::from_str(
+        "
+volume:
+  size_in_gb: 800
+snapshot_interval_seconds: 86400
+restore_from_snapshot: true
+",
+    )
+    .unwrap();
+    assert_eq!(data_volume.volume.volume_type, "gp3");
+    assert_eq!(data_volume.volume.size_in_gb, 800);
+    assert_eq!(data_volume.snapshot_retention, 3);
+    data_volume.validate().unwrap();
+
+    let mut invalid = data_volume.clone();
+    invalid.snapshot_interval_seconds = 60;
+    assert!(invalid.validate().is_err());
+    let mut invalid = data_volume;
+    invalid.snapshot_retention = 0;
+    assert!(invalid.validate().is_err());
+
+    assert!(DataVolume::default().validate().is_ok());
+}
+
+/// Represents the point-in-time copy of the network by "avalanche-ops-aws snapshot",
+/// in "StorageNamespace::NetworkSnapshot": the data volume snapshot of every node,
+/// and the configs that the databases were written with.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct NetworkSnapshot {
+    pub name: String,
+    /// Spec ID of the snapshotted network.
+    pub id: String,
+    /// EBS snapshots are regional, so the clone must be in the same region.
+    pub region: String,
+    /// Unix timestamp of the snapshot.
+    pub created_at: u64,
+    pub avalanchego_config: avalanchego_config::Config,
+    pub coreth_config: coreth_config::Config,
+    pub nodes: Vec<SnapshotNode>,
+}
+
+/// Node of "NetworkSnapshot", with the snapshot of its data volume.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SnapshotNode {
+    pub kind: String,
+    pub machine_id: String,
+    pub node_id: String,
+    pub snapshot_id: String,
+}
+
+impl NetworkSnapshot {
+    /// Returns the tags of the EBS snapshots. Unlike "DataVolume::tags",
+    /// the scheduled snapshot pruning never matches these.
+    pub fn tags<'a>(id: &'a str, name: &'a str) -> [(&'a str, &'a str); 2] {
+        [("SNAPSHOT_OF", id), ("SNAPSHOT_NAME", name)]
+    }
+
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize NetworkSnapshot to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+
+    /// Returns the number of the nodes of the kind.
+    pub fn count(&self, kind: node::Kind) -> u32 {
+        self.nodes
+            .iter()
+            .filter(|n| n.kind == kind.as_str())
+            .count() as u32
+    }
+
+    /// Returns the spec of the new network with one node per snapshot node,
+    /// which "apply" creates with its own AWS resources. The network ID and
+    /// the genesis are kept, since the chain IDs of the databases derive from
+    /// them, so the clone is isolated by its own anchor nodes instead.
+    pub fn clone_spec(&self, source: &Spec, id: &str) -> io::Result<Spec> {
+        let source_resources = source
+            .aws_resources
+            .clone()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "unexpected None aws_resources"))?;
+        if source_resources.region != self.region {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "snapshot '{}' in region {} != spec region {}",
+                    self.name, self.region, source_resources.region
+                ),
+            ));
+        }
+
+        let mut spec = source.clone();
+        spec.id = id.to_string();
+        spec.aws_resources = Some(aws::Resources {
+            region: source_resources.region.clone(),
+            s3_bucket: format!("avalanche-ops-{}", id.to_lowercase()),
+            instance_system_logs: source_resources.instance_system_logs,
+            instance_system_metrics: source_resources.instance_system_metrics,
+            instance_logs_retention_in_days: source_resources.instance_logs_retention_in_days,
+            install_cloudwatch_agent: source_resources.install_cloudwatch_agent,
+            ingress_ipv4_range: source_resources.ingress_ipv4_range.clone(),
+            dual_stack: source_resources.dual_stack,
+            ingress_ipv6_range: source_resources.ingress_ipv6_range.clone(),
+            advertise_ipv6: source_resources.advertise_ipv6,
+            nlb_acm_certificate_arn: source_resources.nlb_acm_certificate_arn.clone(),
+            cloudwatch_telemetry_interval_seconds: source_resources
+                .cloudwatch_telemetry_interval_seconds,
+            ..aws::Resources::default()
+        });
+        spec.avalanchego_config = self.avalanchego_config.clone();
+        spec.coreth_config = self.coreth_config.clone();
+
+        spec.machine.anchor_nodes = match self.count(node::Kind::Anchor) {
+            0 => None,
+            n => Some(n),
+        };
+        spec.machine.non_anchor_nodes = self.count(node::Kind::NonAnchor);
+        let api_nodes = self.count(node::Kind::Api);
+        spec.machine.api_nodes = match (spec.machine.api_nodes.take(), api_nodes) {
+            (_, 0) => None,
+            (Some(mut v), n) => {
+                v.nodes = n;
+                Some(v)
+            }
+            (None, _) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "snapshot has api nodes without 'machine.api_nodes'",
+                ))
+            }
+        };
+        // the DNS records and the Elastic IPs belong to the source network
+        spec.machine.anchor_endpoints = None;
+        if let Some(data_volume) = spec.machine.data_volume.as_mut() {
+            data_volume.restore_from_snapshot = false;
+        }
+
+        spec.clone_source = Some(CloneSource {
+            id: self.id.clone(),
+            snapshot_name: self.name.clone(),
+            s3_bucket: source_resources.s3_bucket.clone(),
+            kms_cmk_id: source_resources.kms_cmk_id.clone().unwrap_or_default(),
+        });
+        spec.state_serial = None;
+        spec.current_nodes = None;
+        spec.endpoints = None;
+        Ok(spec)
+    }
+}
+
+/// Source network of the spec created by "avalanche-ops-aws clone".
+/// "apply" copies the snapshot and the staking certificates of the source
+/// nodes, and each new node restores one source node of the same kind.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct CloneSource {
+    /// Spec ID of the source network.
+    pub id: String,
+    pub snapshot_name: String,
+    /// S3 bucket of the source network.
+    pub s3_bucket: String,
+    /// KMS key of the source network, to decrypt the staking keys in "apply".
+    pub kms_cmk_id: String,
+}
+
+impl CloneSource {
+    pub fn validate(&self, machine: &Machine) -> io::Result<()> {
+        if self.id.is_empty() || self.snapshot_name.is_empty() || self.s3_bucket.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'clone_source' requires 'id', 'snapshot_name', and 's3_bucket'",
+            ));
+        }
+        if self.kms_cmk_id.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "empty 'clone_source.kms_cmk_id'",
+            ));
+        }
+        if machine.data_volume.is_none() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'clone_source' requires 'machine.data_volume'",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Claim of the new node on "SnapshotNode" in "StorageNamespace::CloneClaim".
+/// The winning claim is copied to "StorageNamespace::CloneAssignment".
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct CloneClaim {
+    pub machine_id: String,
+    pub source: SnapshotNode,
+    /// Unix timestamp of the claim.
+    pub claimed_at: u64,
+}
+
+impl CloneClaim {
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize CloneClaim to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+}
+
+/// Returns the machine ID that wins the claims on the same source node:
+/// the earliest claim, ties broken by the machine ID, same as "config_update_slot".
+pub fn clone_claim_winner(claims: &[CloneClaim]) -> Option<&str> {
+    claims
+        .iter()
+        .min_by(|a, b| {
+            (a.claimed_at, a.machine_id.as_str()).cmp(&(b.claimed_at, b.machine_id.as_str()))
+        })
+        .map(|c| c.machine_id.as_str())
+}
+
+#[test]
+fn test_network_snapshot() {
+    let snapshot_node = |kind: &str, machine_id: &str| SnapshotNode {
+        kind: kind.to_string(),
+        machine_id: machine_id.to_string(),
+        node_id: format!("NodeID-{}", machine_id),
+        snapshot_id: format!("snap-{}", machine_id),
+    };
+    let mut avalanchego_config = avalanchego_config::Config::default();
+    avalanchego_config.network_id = 1337;
+    let snapshot = NetworkSnapshot {
+        name: String::from("before-upgrade"),
+        id: String::from("aops-custom-202210-abc"),
+        region: String::from("us-west-2"),
+        created_at: 1650000000,
+        avalanchego_config,
+        coreth_config: coreth_config::Config::default(),
+        nodes: vec![
+            snapshot_node("anchor", "i-a"),
+            snapshot_node("non-anchor", "i-b"),
+            snapshot_node("non-anchor", "i-c"),
+        ],
+    };
+    let decoded = NetworkSnapshot::decode_yaml(snapshot.encode_yaml().unwrap().as_bytes()).unwrap();
+    assert_eq!(decoded, snapshot);
+    assert_eq!(snapshot.count(node::Kind::Anchor), 1);
+    assert_eq!(snapshot.count(node::Kind::NonAnchor), 2);
+    assert_eq!(snapshot.count(node::Kind::Api), 0);
+
+    let claim = |machine_id: &str, claimed_at: u64| CloneClaim {
+        machine_id: machine_id.to_string(),
+        source: snapshot.nodes[1].clone(),
+        claimed_at,
+    };
+    let claims = vec![claim("i-z", 100), claim("i-y", 100), claim("i-x", 120)];
+    let decoded = CloneClaim::decode_yaml(claims[0].encode_yaml().unwrap().as_bytes()).unwrap();
+    assert_eq!(decoded, claims[0]);
+    assert_eq!(clone_claim_winner(&claims), Some("i-y"));
+    assert_eq!(clone_claim_winner(&[]), None);
+
+    let source = CloneSource {
+        id: snapshot.id.clone(),
+        snapshot_name: snapshot.name.clone(),
+        s3_bucket: String::from("avalanche-ops-202210-abc"),
+        kms_cmk_id: String::from("key-id"),
+    };
+    let mut machine: Machine = serde_yaml::from_str(
+        "
+non_anchor_nodes: 2
+arch: amd64
+instance_types: [c6a.large]
+data_volume: {}
+",
+    )
+    .unwrap();
+    source.validate(&machine).unwrap();
+    machine.data_volume = None;
+    assert!(source.validate(&machine).is_err());
+}
+
+/// Describes the database backup uploaded by "avalanched backup create",
+/// stored next to the archive with the ".manifest.yaml" suffix,
+/// so that the restore can check the network before downloading the archive.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct BackupManifest {
+    pub network_id: u32,
+    pub node_id: String,
+    pub avalanchego_version: String,
+    /// Maps the chain alias (e.g., "P", "C") to its last accepted height
+    /// at the time of the backup.
+    #[serde(default)]
+    pub chain_heights: BTreeMap<String, u64>,
+    /// S3 key of the archive.
+    pub archive_s3_key: String,
+    /// "compress::DirEncoder" ID of the archive (e.g., "tar-zstd3").
+    pub compression: String,
+    /// Size of the compressed archive.
+    pub size_bytes: u64,
+    /// Hex-encoded SHA256 digest of the compressed archive.
+    pub sha256: String,
+    /// Unix timestamp of the backup.
+    pub created_at: u64,
+}
+
+impl BackupManifest {
+    /// Returns the S3 key of the manifest for the archive.
+    pub fn s3_key(archive_s3_key: &str) -> String {
+        format!("{}.manifest.yaml", archive_s3_key)
+    }
+
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize BackupManifest to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+
+    /// Errors if the downloaded archive is truncated or corrupted.
+    pub fn verify_archive(&self, archive_path: &str) -> io::Result<()> {
+        let size = fs::metadata(archive_path)?.len();
+        if size != self.size_bytes {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "downloaded backup size {} does not match the manifest size {}",
+                    size, self.size_bytes
+                ),
+            ));
+        }
+        upgrade::verify_sha256_file(archive_path, &self.sha256)
+    }
+
+    /// Errors if the backup was taken from a different network,
+    /// since avalanchego cannot start with the other network's database.
+    pub fn verify_network_id(&self, network_id: u32) -> io::Result<()> {
+        if self.network_id != network_id {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "backup '{}' is for network ID {} (expected {})",
+                    self.archive_s3_key, self.network_id, network_id
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_backup_manifest() {
+    let mut chain_heights = BTreeMap::new();
+    chain_heights.insert(String::from("P"), 1234);
+    chain_heights.insert(String::from("C"), 5678);
+    let manifest = BackupManifest {
+        network_id: 5,
+        node_id: String::from("NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg"),
+        avalanchego_version: String::from("avalanche/1.7.10"),
+        chain_heights,
+        archive_s3_key: String::from("test/backups/backup.tar.zstd"),
+        compression: String::from("tar-zstd3"),
+        size_bytes: 10 * 1024 * 1024 * 1024,
+        sha256: String::from("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+        created_at: 1650000000,
+    };
+    assert_eq!(
+        BackupManifest::s3_key(&manifest.archive_s3_key),
+        "test/backups/backup.tar.zstd.manifest.yaml"
+    );
+
+    let decoded = BackupManifest::decode_yaml(manifest.encode_yaml().unwrap().as_bytes()).unwrap();
+    assert_eq!(decoded, manifest);
+    assert_eq!(decoded.chain_heights.get("C"), Some(&5678));
+
+    manifest.verify_network_id(5).unwrap();
+    assert!(manifest.verify_network_id(1).is_err());
+
+    // SHA256 of the empty archive
+    let archive = tempfile::NamedTempFile::new().unwrap();
+    let archive_path = archive.path().to_str().unwrap();
+    assert!(manifest.verify_archive(archive_path).is_err());
+    let empty = BackupManifest {
+        size_bytes: 0,
+        ..manifest.clone()
+    };
+    empty.verify_archive(archive_path).unwrap();
+    fs::write(archive_path, b"x").unwrap();
+    let corrupted = BackupManifest {
+        size_bytes: 1,
+        ..manifest
+    };
+    assert!(corrupted.verify_archive(archive_path).is_err());
+}
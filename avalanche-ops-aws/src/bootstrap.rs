@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+/// Instance tag of the bootstrap progress (0 ~ 100), updated by avalanched.
+pub const BOOTSTRAP_PROGRESS_TAG: &str = "BOOTSTRAP_PROGRESS";
+
+/// Returns the public API endpoint to fetch the expected chain heights,
+/// used when no other node in the fleet is ready. "None" for custom networks.
+pub fn public_api_endpoint(network_id: u32) -> Option<&'static str> {
+    match network_id {
+        1 => Some("https://api.avax.network"),
+        5 => Some("https://api.avax-test.network"),
+        _ => None,
+    }
+}
+
+/// Represents the bootstrap progress of the chain.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ChainBootstrapProgress {
+    /// e.g., "X", "P", "C".
+    pub chain: String,
+    pub bootstrapped: bool,
+    /// Blocks fetched while bootstrapping (e.g., "avalanche_P_bs_fetched").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fetched: Option<f64>,
+    /// Highest height of the chain among the ready peers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<u64>,
+}
+
+impl ChainBootstrapProgress {
+    /// Returns the progress (0 ~ 100), or "None" if unknown.
+    /// Capped at 99 until bootstrapped, since the fetched blocks are executed
+    /// afterwards. The node restored from a backup fetches fewer blocks than
+    /// the height, so its progress jumps to 100 once bootstrapped.
+    pub fn percent(&self) -> Option<f64> {
+        if self.bootstrapped {
+            return Some(100.0);
+        }
+        match (self.fetched, self.expected) {
+            (Some(fetched), Some(expected)) if expected > 0 => {
+                Some((fetched * 100.0 / expected as f64).clamp(0.0, 99.0))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the average progress of the chains (0 ~ 100),
+/// counting the chains with unknown progress as zero.
+pub fn bootstrap_progress_percent(chains: &[ChainBootstrapProgress]) -> f64 {
+    if chains.is_empty() {
+        return 0.0;
+    }
+    let sum: f64 = chains.iter().map(|c| c.percent().unwrap_or(0.0)).sum();
+    sum / chains.len() as f64
+}
+
+#[test]
+fn test_bootstrap_progress() {
+    let chain = |chain: &str, bootstrapped: bool, fetched: Option<f64>, expected: Option<u64>| {
+        ChainBootstrapProgress {
+            chain: chain.to_string(),
+            bootstrapped,
+            fetched,
+            expected,
+        }
+    };
+
+    assert_eq!(chain("X", true, None, None).percent(), Some(100.0));
+    assert_eq!(chain("X", false, None, None).percent(), None);
+    assert_eq!(
+        chain("P", false, Some(250.0), Some(1000)).percent(),
+        Some(25.0)
+    );
+    assert_eq!(chain("P", false, Some(250.0), Some(0)).percent(), None);
+    assert_eq!(
+        chain("C", false, Some(1200.0), Some(1000)).percent(),
+        Some(99.0)
+    );
+
+    let chains = vec![
+        chain("X", true, None, None),
+        chain("P", false, Some(500.0), Some(1000)),
+        chain("C", false, Some(10.0), None),
+    ];
+    assert_eq!(bootstrap_progress_percent(&chains), 50.0);
+    assert_eq!(bootstrap_progress_percent(&[]), 0.0);
+
+    assert_eq!(public_api_endpoint(1), Some("https://api.avax.network"));
+    assert_eq!(public_api_endpoint(1000000), None);
+}
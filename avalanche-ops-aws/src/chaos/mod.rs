@@ -0,0 +1,675 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Error, ErrorKind, Write},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use clap::{Arg, Command};
+use crossterm::{
+    execute,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+};
+use dialoguer::{theme::ColorfulTheme, Select};
+use log::{info, warn};
+use serde::{Serialize, Serializer};
+use tokio::runtime::Runtime;
+
+use avalanche_api::health as api_health;
+use avalanche_ops_aws::output;
+use aws::{self, ec2, s3, ssm};
+use utils::random;
+
+pub const NAME: &str = "chaos";
+
+/// Interval between the recovery health checks.
+const POLL_INTERVAL_SECONDS: u64 = 10;
+
+/// Time to wait for the SSM commands on the nodes.
+const COMMAND_TIMEOUT_SECONDS: u64 = 120;
+
+/// Time that "avalanched" keeps the disk throttle after the scenario duration,
+/// in case this command exits before reverting it.
+const THROTTLE_GRACE_SECONDS: u64 = 300;
+
+pub fn command() -> Command<'static> {
+    Command::new(NAME)
+        .about("Runs the fault injection scenarios of 'chaos_policy' against the custom network, and verifies that the network recovers from each")
+        .arg(
+            Arg::new("LOG_LEVEL")
+                .long("log-level")
+                .short('l')
+                .help("Sets the log level")
+                .required(false)
+                .takes_value(true)
+                .possible_value("debug")
+                .possible_value("info")
+                .allow_invalid_utf8(false)
+                .default_value("info"),
+        )
+        .arg(
+            Arg::new("SPEC_FILE_PATH")
+                .long("spec-file-path")
+                .short('s')
+                .help("The spec file to load")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("SCENARIOS")
+                .long("scenarios")
+                .help("Only runs the scenarios of the names (comma-separated, runs all if empty)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("SEED")
+                .long("seed")
+                .help("Sets the seed to pick the target nodes, to reproduce the previous run")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("RECOVER_ONLY")
+                .long("recover-only")
+                .help("Only reverts the faults left by the interrupted run")
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("SKIP_PROMPT")
+                .long("skip-prompt")
+                .help("Skips prompt mode")
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        )
+}
+
+/// Result of each scenario.
+#[derive(Serialize)]
+struct Outcome {
+    name: String,
+    fault: &'static str,
+    targets: Vec<String>,
+    /// Healthy nodes at the end of the fault.
+    healthy_during_fault: usize,
+    /// Time for all nodes to become healthy after the fault is reverted.
+    #[serde(rename = "recovered_in_seconds", serialize_with = "serialize_seconds")]
+    recovered_in: Option<Duration>,
+    error: Option<String>,
+}
+
+fn serialize_seconds<S: Serializer>(d: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+    d.map(|d| d.as_secs_f64()).serialize(s)
+}
+
+/// AWS clients and the resources that the faults need.
+struct Injector {
+    rt: Runtime,
+    ec2_manager: ec2::Manager,
+    ssm_manager: ssm::Manager,
+    s3_manager: s3::Manager,
+    s3_bucket: String,
+    id: String,
+    vpc_id: String,
+    vpc_security_group_id: String,
+    staking_port: u32,
+}
+
+/// Runs the scenarios in order: injects each fault to the random non-anchor
+/// nodes, reverts it after the duration, and waits for all nodes to become
+/// healthy before the next scenario. Any fault left by the interrupted run
+/// is reverted first. Fails if any scenario does not recover.
+pub fn execute(
+    log_level: &str,
+    spec_file_path: &str,
+    scenarios: Option<&str>,
+    seed: Option<&str>,
+    recover_only: bool,
+    skip_prompt: bool,
+    output: output::Format,
+) -> io::Result<()> {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    );
+
+    let spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
+    spec.validate()?;
+    let policy = spec
+        .chaos_policy
+        .clone()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "no 'chaos_policy' in the spec"))?;
+    let current_nodes = spec.current_nodes.clone().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            "no 'current_nodes' in the spec (run 'apply' first)",
+        )
+    })?;
+    let selected: Vec<&str> = scenarios
+        .unwrap_or("")
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    for name in selected.iter() {
+        if !policy.scenarios.iter().any(|s| s.name == *name) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("unknown chaos scenario '{}'", name),
+            ));
+        }
+    }
+    let scenarios: Vec<avalanche_ops_aws::ChaosScenario> = policy
+        .scenarios
+        .iter()
+        .filter(|s| selected.is_empty() || selected.contains(&s.name.as_str()))
+        .cloned()
+        .collect();
+    let seed = match seed {
+        Some(v) => v.parse::<u64>().map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid --seed '{}' ({})", v, e),
+            )
+        })?,
+        None => unix_now(),
+    };
+
+    let aws_resources = spec
+        .aws_resources
+        .clone()
+        .expect("unexpected None aws_resources");
+    let (vpc_id, vpc_security_group_id) = match (
+        &aws_resources.cloudformation_vpc_id,
+        &aws_resources.cloudformation_vpc_security_group_id,
+    ) {
+        (Some(vpc_id), Some(sg_id)) => (vpc_id.clone(), sg_id.clone()),
+        _ => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "no VPC in 'aws_resources' (run 'apply' first)",
+            ))
+        }
+    };
+
+    if !recover_only {
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Blue),
+            Print(format!(
+                "\nChaos scenarios against {} node(s) of network ID {} (seed {}):\n",
+                current_nodes.len(),
+                spec.avalanchego_config.network_id,
+                seed
+            )),
+            ResetColor
+        )?;
+        writeln!(
+            output.console(),
+            "{}\n",
+            serde_yaml::to_string(&scenarios).unwrap()
+        )?;
+
+        if !skip_prompt {
+            let options = &[
+                "No, I am not ready to inject the faults!",
+                "Yes, let's inject the faults!",
+            ];
+            let selected = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Select your 'chaos' option")
+                .items(&options[..])
+                .default(0)
+                .interact()
+                .unwrap();
+            if selected == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    let rt = Runtime::new().unwrap();
+    let shared_config = rt
+        .block_on(aws::load_config(Some(aws_resources.region.clone())))
+        .expect("failed to aws::load_config");
+    let injector = Injector {
+        ec2_manager: ec2::Manager::new(&shared_config),
+        ssm_manager: ssm::Manager::new(&shared_config),
+        s3_manager: s3::Manager::new(&shared_config),
+        rt,
+        s3_bucket: aws_resources.s3_bucket.clone(),
+        id: spec.id.clone(),
+        vpc_id,
+        vpc_security_group_id,
+        staking_port: spec.avalanchego_config.staking_port,
+    };
+
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Green),
+        Print("\n\n\nSTEP: reverting the faults left by the previous run\n"),
+        ResetColor
+    )?;
+    injector.recover(&current_nodes)?;
+    if recover_only {
+        return Ok(());
+    }
+
+    let mut outcomes = Vec::new();
+    for (i, scenario) in scenarios.iter().enumerate() {
+        if scenario.start_after_seconds > 0 {
+            info!(
+                "waiting {} seconds before '{}'",
+                scenario.start_after_seconds, scenario.name
+            );
+            thread::sleep(Duration::from_secs(scenario.start_after_seconds));
+        }
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Green),
+            Print(format!(
+                "\n\n\nSTEP: running chaos scenario '{}' ({})\n",
+                scenario.name,
+                scenario.fault.as_str()
+            )),
+            ResetColor
+        )?;
+        let targets = avalanche_ops_aws::chaos_targets(
+            &current_nodes,
+            scenario.fault.target_count(current_nodes.len()),
+            seed.wrapping_add(i as u64),
+        );
+        outcomes.push(injector.run(
+            scenario,
+            &current_nodes,
+            &targets,
+            policy.recovery_timeout_seconds,
+        ));
+    }
+
+    writeln!(output.console())?;
+    let mut failed = 0;
+    for o in outcomes.iter() {
+        let (color, result) = match (&o.error, o.recovered_in) {
+            (None, Some(took)) => (Color::Green, format!("recovered in {:?}", took)),
+            (Some(e), _) => {
+                failed += 1;
+                (Color::Red, format!("failed ({})", e))
+            }
+            (None, None) => {
+                failed += 1;
+                (Color::Red, String::from("did not recover"))
+            }
+        };
+        execute!(
+            output.console(),
+            SetForegroundColor(color),
+            Print(format!(
+                "{} ({} on {}): {}/{} healthy during the fault, {}\n",
+                o.name,
+                o.fault,
+                o.targets.join(", "),
+                o.healthy_during_fault,
+                current_nodes.len(),
+                result
+            )),
+            ResetColor
+        )?;
+    }
+    writeln!(output.console())?;
+    output.print(&outcomes)?;
+
+    if failed > 0 {
+        return Err(Error::new(
+            ErrorKind::Other,
+            format!("{}/{} chaos scenario(s) failed", failed, outcomes.len()),
+        ));
+    }
+    info!("all {} chaos scenario(s) recovered", outcomes.len());
+    Ok(())
+}
+
+impl Injector {
+    fn run(
+        &self,
+        scenario: &avalanche_ops_aws::ChaosScenario,
+        nodes: &[avalanche_ops_aws::Node],
+        targets: &[avalanche_ops_aws::Node],
+        recovery_timeout_seconds: u64,
+    ) -> Outcome {
+        let mut outcome = Outcome {
+            name: scenario.name.clone(),
+            fault: scenario.fault.as_str(),
+            targets: targets.iter().map(|n| n.machine_id.clone()).collect(),
+            healthy_during_fault: 0,
+            recovered_in: None,
+            error: None,
+        };
+        if targets.is_empty() {
+            outcome.error = Some(String::from("no non-anchor node to inject"));
+            return outcome;
+        }
+        info!("injecting '{}' to {:?}", scenario.name, outcome.targets);
+
+        // reverts the partially injected fault as well
+        let injected = self.inject(&scenario.fault, targets, scenario.duration_seconds);
+        if injected.is_ok() {
+            info!(
+                "keeping '{}' for {} seconds",
+                scenario.name, scenario.duration_seconds
+            );
+            thread::sleep(Duration::from_secs(scenario.duration_seconds));
+            outcome.healthy_during_fault = self.count_healthy(nodes);
+        }
+        let reverted = self.revert(&scenario.fault, nodes, targets);
+        if let Err(e) = injected.and(reverted) {
+            warn!("chaos scenario '{}' failed ({})", scenario.name, e);
+            outcome.error = Some(e.to_string());
+            return outcome;
+        }
+
+        outcome.recovered_in =
+            self.wait_for_recovery(nodes, Duration::from_secs(recovery_timeout_seconds));
+        outcome
+    }
+
+    fn inject(
+        &self,
+        fault: &avalanche_ops_aws::ChaosFault,
+        targets: &[avalanche_ops_aws::Node],
+        duration_seconds: u64,
+    ) -> io::Result<()> {
+        match fault {
+            // stop right after the kill, so that systemd does not restart it
+            avalanche_ops_aws::ChaosFault::KillNodes { .. } => self.run_commands(
+                targets,
+                &[
+                    String::from("sudo systemctl kill --signal=SIGKILL avalanche.service"),
+                    String::from("sudo systemctl stop avalanche.service"),
+                ],
+                "kill",
+            ),
+
+            avalanche_ops_aws::ChaosFault::Partition { .. } => {
+                let sg_id = self.rt.block_on(self.ec2_manager.create_security_group(
+                    &self.vpc_id,
+                    &self.partition_group_name(),
+                    "avalanche-ops chaos partition",
+                ))?;
+                self.rt
+                    .block_on(self.ec2_manager.revoke_default_egress(&sg_id))?;
+
+                // the nodes talk over the public IPs, so self-referencing rules do not match
+                let members: Vec<String> = targets
+                    .iter()
+                    .map(|n| avalanche_ops_aws::host_cidr(&n.public_ip))
+                    .collect();
+                let mut authorize: Vec<ec2::sg::IngressRule> = self
+                    .rt
+                    .block_on(
+                        self.ec2_manager
+                            .list_ingress_tcp(&self.vpc_security_group_id),
+                    )?
+                    .into_iter()
+                    .filter(|r| r.port != self.staking_port)
+                    .collect();
+                authorize.extend(
+                    members
+                        .iter()
+                        .map(|cidr_ip| ec2::sg::IngressRule::new(self.staking_port, cidr_ip)),
+                );
+                self.rt.block_on(self.ec2_manager.apply_ingress_diff(
+                    &sg_id,
+                    &ec2::sg::IngressDiff {
+                        authorize,
+                        revoke: Vec::new(),
+                    },
+                ))?;
+                self.rt.block_on(self.ec2_manager.authorize_egress_tcp(
+                    &sg_id,
+                    self.staking_port,
+                    &members,
+                ))?;
+                // "avalanched" and SSM still need the AWS APIs
+                self.rt.block_on(self.ec2_manager.authorize_egress_tcp(
+                    &sg_id,
+                    443,
+                    &[String::from("0.0.0.0/0")],
+                ))?;
+
+                for node in targets.iter() {
+                    self.rt.block_on(
+                        self.ec2_manager
+                            .modify_instance_security_groups(&node.machine_id, &[sg_id.clone()]),
+                    )?;
+                }
+                // the established peer connections outlive the security group swap
+                self.run_commands(
+                    targets,
+                    &[String::from("sudo systemctl restart avalanche.service")],
+                    "partition",
+                )
+            }
+
+            avalanche_ops_aws::ChaosFault::ThrottleDisk {
+                read_bytes_per_second,
+                write_bytes_per_second,
+                ..
+            } => {
+                let now = unix_now();
+                let throttle = avalanche_ops_aws::ChaosDiskThrottle {
+                    version: now,
+                    machine_ids: targets.iter().map(|n| n.machine_id.clone()).collect(),
+                    read_bytes_per_second: *read_bytes_per_second,
+                    write_bytes_per_second: *write_bytes_per_second,
+                    expires_at: now + duration_seconds + THROTTLE_GRACE_SECONDS,
+                };
+                let tmp_path = random::tmp_path(15, Some(".yaml"))?;
+                fs::write(&tmp_path, throttle.encode_yaml()?)?;
+                self.rt.block_on(self.s3_manager.put_object(
+                    Arc::new(tmp_path.clone()),
+                    Arc::new(self.s3_bucket.clone()),
+                    Arc::new(self.throttle_s3_key()),
+                ))?;
+                fs::remove_file(&tmp_path)?;
+                info!(
+                    "uploaded disk throttle version {} (applied by 'avalanched' within a minute)",
+                    throttle.version
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn revert(
+        &self,
+        fault: &avalanche_ops_aws::ChaosFault,
+        nodes: &[avalanche_ops_aws::Node],
+        targets: &[avalanche_ops_aws::Node],
+    ) -> io::Result<()> {
+        info!("reverting '{}'", fault.as_str());
+        match fault {
+            avalanche_ops_aws::ChaosFault::KillNodes { .. } => self.run_commands(
+                targets,
+                &[String::from("sudo systemctl start avalanche.service")],
+                "revert kill",
+            ),
+            avalanche_ops_aws::ChaosFault::Partition { .. } => self.revert_partition(nodes),
+            avalanche_ops_aws::ChaosFault::ThrottleDisk { .. } => {
+                self.rt.block_on(self.s3_manager.delete_objects(
+                    Arc::new(self.s3_bucket.clone()),
+                    Some(Arc::new(self.throttle_s3_key())),
+                ))?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Reverts every kind of fault on all nodes.
+    /// Starting the running service is a no-op.
+    fn recover(&self, nodes: &[avalanche_ops_aws::Node]) -> io::Result<()> {
+        self.revert_partition(nodes)?;
+        self.rt.block_on(self.s3_manager.delete_objects(
+            Arc::new(self.s3_bucket.clone()),
+            Some(Arc::new(self.throttle_s3_key())),
+        ))?;
+        self.run_commands(
+            nodes,
+            &[String::from("sudo systemctl start avalanche.service")],
+            "recover",
+        )
+    }
+
+    /// Moves the nodes in the partition security group back to
+    /// the VPC security group, and deletes the partition group.
+    fn revert_partition(&self, nodes: &[avalanche_ops_aws::Node]) -> io::Result<()> {
+        let sg_id = match self.rt.block_on(
+            self.ec2_manager
+                .find_security_group(&self.vpc_id, &self.partition_group_name()),
+        )? {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+
+        let machine_ids: Vec<String> = nodes.iter().map(|n| n.machine_id.clone()).collect();
+        let droplets = self
+            .rt
+            .block_on(self.ec2_manager.list_instances(&machine_ids))?;
+        for droplet in droplets
+            .iter()
+            .filter(|d| d.security_group_ids.contains(&sg_id))
+        {
+            self.rt
+                .block_on(self.ec2_manager.modify_instance_security_groups(
+                    &droplet.instance_id,
+                    &[self.vpc_security_group_id.clone()],
+                ))?;
+        }
+
+        // the network interfaces release the group with a delay
+        let mut last_error = None;
+        for _ in 0..10_u8 {
+            match self
+                .rt
+                .block_on(self.ec2_manager.delete_security_group(&sg_id))
+            {
+                Ok(_) => return Ok(()),
+                Err(e) => {
+                    warn!(
+                        "failed to delete '{}' ({}), retrying...",
+                        sg_id,
+                        e.message()
+                    );
+                    last_error = Some(e);
+                }
+            }
+            thread::sleep(Duration::from_secs(POLL_INTERVAL_SECONDS));
+        }
+        Err(last_error.expect("unexpected None error").into())
+    }
+
+    fn run_commands(
+        &self,
+        targets: &[avalanche_ops_aws::Node],
+        commands: &[String],
+        action: &str,
+    ) -> io::Result<()> {
+        let comment = format!("avalanche-ops {} chaos {}", self.id, action);
+        let target_ids: Vec<String> = targets.iter().map(|n| n.machine_id.clone()).collect();
+        let mut failed: BTreeMap<String, String> = BTreeMap::new();
+        for ids in target_ids.chunks(ssm::MAX_INSTANCE_IDS_PER_COMMAND) {
+            let command_id = self.rt.block_on(self.ssm_manager.send_shell_commands(
+                ids,
+                commands,
+                &comment,
+                COMMAND_TIMEOUT_SECONDS as i32,
+            ))?;
+            for inv in self.rt.block_on(self.ssm_manager.poll_command(
+                &command_id,
+                ids,
+                Duration::from_secs(COMMAND_TIMEOUT_SECONDS),
+                Duration::from_secs(5),
+            ))? {
+                if !inv.is_success() {
+                    failed.insert(inv.instance_id.clone(), inv.status.clone());
+                }
+            }
+        }
+        if !failed.is_empty() {
+            return Err(Error::new(
+                ErrorKind::Other,
+                format!("chaos {} failed on {:?}", action, failed),
+            ));
+        }
+        Ok(())
+    }
+
+    fn count_healthy(&self, nodes: &[avalanche_ops_aws::Node]) -> usize {
+        nodes
+            .iter()
+            .filter(|n| {
+                match self
+                    .rt
+                    .block_on(api_health::check(Arc::new(n.http_endpoint.clone()), false))
+                {
+                    Ok(res) => res.healthy == Some(true),
+                    Err(_) => false,
+                }
+            })
+            .count()
+    }
+
+    /// Returns the time it took for all nodes to become healthy,
+    /// or "None" if any node is still unhealthy after the timeout.
+    fn wait_for_recovery(
+        &self,
+        nodes: &[avalanche_ops_aws::Node],
+        timeout: Duration,
+    ) -> Option<Duration> {
+        let started = Instant::now();
+        loop {
+            let healthy = self.count_healthy(nodes);
+            if healthy == nodes.len() {
+                info!(
+                    "all {} node(s) healthy after {:?}",
+                    healthy,
+                    started.elapsed()
+                );
+                return Some(started.elapsed());
+            }
+            if started.elapsed() >= timeout {
+                warn!(
+                    "{}/{} node(s) healthy after {:?}",
+                    healthy,
+                    nodes.len(),
+                    timeout
+                );
+                return None;
+            }
+            info!("{}/{} node(s) healthy, waiting...", healthy, nodes.len());
+            thread::sleep(Duration::from_secs(POLL_INTERVAL_SECONDS));
+        }
+    }
+
+    fn partition_group_name(&self) -> String {
+        format!("{}-chaos-partition", self.id)
+    }
+
+    fn throttle_s3_key(&self) -> String {
+        avalanche_ops_aws::StorageNamespace::EventsChaosDiskThrottle(self.id.clone()).encode()
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("unexpected None duration_since")
+        .as_secs()
+}
@@ -0,0 +1,317 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    io::{self, Error, ErrorKind},
+};
+
+use serde::{Deserialize, Serialize};
+
+use avalanche_types::node;
+
+use crate::Node;
+
+/// Defines the fault injection scenarios that "avalanche-ops-aws chaos"
+/// runs in order against the custom network. Each fault is reverted after
+/// its duration, and the network must recover before the next scenario.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ChaosPolicy {
+    pub scenarios: Vec<ChaosScenario>,
+    /// Time for all nodes to become healthy after the fault is reverted,
+    /// otherwise the scenario fails.
+    #[serde(default = "default_chaos_recovery_timeout_seconds")]
+    pub recovery_timeout_seconds: u64,
+}
+
+fn default_chaos_recovery_timeout_seconds() -> u64 {
+    900
+}
+
+impl ChaosPolicy {
+    pub fn validate(&self) -> io::Result<()> {
+        if self.scenarios.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'chaos_policy.scenarios' is empty",
+            ));
+        }
+        let mut names = HashSet::new();
+        for scenario in self.scenarios.iter() {
+            if !names.insert(scenario.name.as_str()) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("duplicate chaos scenario '{}'", scenario.name),
+                ));
+            }
+            scenario.validate()?;
+        }
+        if self.recovery_timeout_seconds < 60 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'recovery_timeout_seconds' {} too short (at least 60)",
+                    self.recovery_timeout_seconds
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ChaosScenario {
+    /// Unique name of the scenario in the reports.
+    pub name: String,
+    /// Time to wait before injecting the fault,
+    /// after the previous scenario recovered.
+    #[serde(default)]
+    pub start_after_seconds: u64,
+    /// Time to keep the fault before reverting it.
+    pub duration_seconds: u64,
+    pub fault: ChaosFault,
+}
+
+impl ChaosScenario {
+    pub fn validate(&self) -> io::Result<()> {
+        if self.name.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "empty chaos scenario name",
+            ));
+        }
+        if self.duration_seconds == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("chaos scenario '{}' has zero duration", self.name),
+            ));
+        }
+        self.fault.validate().map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("chaos scenario '{}': {}", self.name, e),
+            )
+        })
+    }
+}
+
+/// Fault to inject. The targets are picked at random
+/// from the non-anchor nodes, see "chaos_targets".
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChaosFault {
+    /// SIGKILLs avalanchego (no graceful shutdown) on the nodes,
+    /// and keeps the service stopped until the fault is reverted.
+    KillNodes { count: u32 },
+    /// Isolates the percent of all nodes from the rest of the network,
+    /// with the security group that only allows the staking port
+    /// between the isolated nodes.
+    Partition { percent: u32 },
+    /// Caps the I/O bandwidth of avalanchego on the nodes,
+    /// via the systemd cgroup properties that "avalanched" sets.
+    /// Zero leaves the direction unlimited.
+    ThrottleDisk {
+        count: u32,
+        #[serde(default)]
+        read_bytes_per_second: u64,
+        #[serde(default)]
+        write_bytes_per_second: u64,
+    },
+}
+
+impl ChaosFault {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChaosFault::KillNodes { .. } => "kill_nodes",
+            ChaosFault::Partition { .. } => "partition",
+            ChaosFault::ThrottleDisk { .. } => "throttle_disk",
+        }
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        match self {
+            ChaosFault::KillNodes { count } | ChaosFault::ThrottleDisk { count, .. }
+                if *count == 0 =>
+            {
+                Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'count' must be at least 1",
+                ))
+            }
+            // the isolated side must stay the minority
+            ChaosFault::Partition { percent } if *percent == 0 || *percent >= 50 => {
+                Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("'percent' {} must be in [1, 50)", percent),
+                ))
+            }
+            ChaosFault::ThrottleDisk {
+                read_bytes_per_second: 0,
+                write_bytes_per_second: 0,
+                ..
+            } => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "no 'read_bytes_per_second' nor 'write_bytes_per_second' to throttle",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the number of the nodes to inject the fault to,
+    /// out of all "total_nodes".
+    pub fn target_count(&self, total_nodes: usize) -> usize {
+        match self {
+            ChaosFault::KillNodes { count } | ChaosFault::ThrottleDisk { count, .. } => {
+                *count as usize
+            }
+            ChaosFault::Partition { percent } => (total_nodes * (*percent as usize) + 99) / 100,
+        }
+    }
+}
+
+/// Picks up to "count" non-anchor nodes at random, with the seed
+/// so that the same run can be reproduced. Anchor nodes are never
+/// picked, since the other nodes need them to bootstrap on recovery.
+pub fn chaos_targets(nodes: &[Node], count: usize, seed: u64) -> Vec<Node> {
+    use std::hash::{Hash, Hasher};
+
+    let mut candidates: Vec<(u64, &Node)> = nodes
+        .iter()
+        .filter(|n| n.kind == node::Kind::NonAnchor.as_str())
+        .map(|n| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            (seed, n.machine_id.as_str()).hash(&mut hasher);
+            (hasher.finish(), n)
+        })
+        .collect();
+    candidates.sort_by(|a, b| (a.0, &a.1.machine_id).cmp(&(b.0, &b.1.machine_id)));
+    candidates
+        .into_iter()
+        .take(count)
+        .map(|(_, n)| n.clone())
+        .collect()
+}
+
+/// Represents the disk throttle in "StorageNamespace::EventsChaosDiskThrottle",
+/// which "avalanched" of each listed machine applies until it expires.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ChaosDiskThrottle {
+    /// Unix timestamp of the injection.
+    pub version: u64,
+    pub machine_ids: BTreeSet<String>,
+    #[serde(default)]
+    pub read_bytes_per_second: u64,
+    #[serde(default)]
+    pub write_bytes_per_second: u64,
+    /// Unix timestamp after which "avalanched" reverts the throttle,
+    /// even if "avalanche-ops-aws chaos" exited before reverting it.
+    pub expires_at: u64,
+}
+
+impl ChaosDiskThrottle {
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize ChaosDiskThrottle to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+
+    /// Returns true if the machine should be throttled at "now".
+    pub fn applies_to(&self, machine_id: &str, now: u64) -> bool {
+        now < self.expires_at && self.machine_ids.contains(machine_id)
+    }
+}
+
+#[test]
+fn test_chaos() {
+    let policy: ChaosPolicy = serde_yaml::from_str(
+        "
+scenarios:
+- name: kill-one
+  duration_seconds: 120
+  fault:
+    kind: kill_nodes
+    count: 1
+- name: split
+  start_after_seconds: 60
+  duration_seconds: 300
+  fault:
+    kind: partition
+    percent: 30
+- name: slow-disk
+  duration_seconds: 600
+  fault:
+    kind: throttle_disk
+    count: 2
+    write_bytes_per_second: 1048576
+",
+    )
+    .unwrap();
+    assert_eq!(policy.recovery_timeout_seconds, 900);
+    assert_eq!(policy.scenarios.len(), 3);
+    assert_eq!(
+        policy.scenarios[2].fault,
+        ChaosFault::ThrottleDisk {
+            count: 2,
+            read_bytes_per_second: 0,
+            write_bytes_per_second: 1048576,
+        }
+    );
+    policy.validate().unwrap();
+
+    let mut invalid = policy.clone();
+    invalid.scenarios[1].name = String::from("kill-one");
+    assert!(invalid.validate().is_err());
+    for fault in [
+        ChaosFault::KillNodes { count: 0 },
+        ChaosFault::Partition { percent: 50 },
+        ChaosFault::ThrottleDisk {
+            count: 1,
+            read_bytes_per_second: 0,
+            write_bytes_per_second: 0,
+        },
+    ] {
+        assert!(fault.validate().is_err());
+    }
+
+    // 30% of 10 nodes, rounded up
+    assert_eq!(policy.scenarios[1].fault.target_count(10), 3);
+    assert_eq!(policy.scenarios[1].fault.target_count(7), 3);
+    assert_eq!(policy.scenarios[0].fault.target_count(7), 1);
+
+    let nodes: Vec<Node> = (0..6)
+        .map(|i| Node {
+            kind: String::from(if i < 2 { "anchor" } else { "non-anchor" }),
+            machine_id: format!("i-{}", i),
+            node_id: format!("NodeID-{}", i),
+            public_ip: format!("10.0.0.{}", i),
+            http_endpoint: format!("http://10.0.0.{}:9650", i),
+        })
+        .collect();
+    let targets = chaos_targets(&nodes, 3, 1);
+    assert_eq!(targets.len(), 3);
+    assert!(targets.iter().all(|n| n.kind == "non-anchor"));
+    assert_eq!(targets, chaos_targets(&nodes, 3, 1));
+    assert_eq!(chaos_targets(&nodes, 10, 2).len(), 4);
+
+    let throttle = ChaosDiskThrottle {
+        version: 1650000000,
+        machine_ids: BTreeSet::from([String::from("i-2")]),
+        read_bytes_per_second: 0,
+        write_bytes_per_second: 1048576,
+        expires_at: 1650000600,
+    };
+    let decoded =
+        ChaosDiskThrottle::decode_yaml(throttle.encode_yaml().unwrap().as_bytes()).unwrap();
+    assert_eq!(decoded, throttle);
+    assert!(throttle.applies_to("i-2", 1650000001));
+    assert!(!throttle.applies_to("i-3", 1650000001));
+    assert!(!throttle.applies_to("i-2", 1650000600));
+}
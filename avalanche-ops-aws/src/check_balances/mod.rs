@@ -1,9 +1,12 @@
-use std::io;
+use std::io::{self, Write};
 
 use clap::{Arg, Command};
+use serde::Serialize;
 use tokio::runtime::Runtime;
 
 use avalanche_api::{c, p, x};
+use avalanche_ops_aws::output;
+use utils::http;
 
 pub const NAME: &str = "check-balances";
 
@@ -67,13 +70,25 @@ curl --location --request POST [HTTP_RPC_ENDPOINT]/ext/bc/C/rpc \
 }'
 */
 
-pub fn execute(log_level: &str, spec_file_path: &str) -> io::Result<()> {
+/// Balances of a generated key, in nano-AVAX (wei for the C-chain).
+#[derive(Debug, Serialize)]
+struct Balances {
+    x_address: String,
+    x_balance: u64,
+    p_address: String,
+    p_balance: u64,
+    c_address: String,
+    c_balance: String,
+}
+
+pub fn execute(log_level: &str, spec_file_path: &str, output: output::Format) -> io::Result<()> {
     // ref. https://github.com/env-logger-rs/env_logger/issues/47
     env_logger::init_from_env(
         env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
     );
 
     let spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
+    http::set_default_tls(spec.api_tls.clone())?;
     let eps = spec.endpoints.expect("unexpected None endpoints");
     let http_rpc = eps.http_rpc.expect("unexpected None http_rpc");
 
@@ -87,8 +102,9 @@ pub fn execute(log_level: &str, spec_file_path: &str) -> io::Result<()> {
         keys.push(k.clone());
     }
 
-    println!();
+    writeln!(output.console())?;
     let rt = Runtime::new().unwrap();
+    let mut balances: Vec<Balances> = Vec::new();
     for k in keys.iter() {
         let (xaddr, paddr, caddr) = {
             (
@@ -117,11 +133,20 @@ pub fn execute(log_level: &str, spec_file_path: &str) -> io::Result<()> {
 
             (x, p, c)
         };
-        println!("{}: {}", xaddr, xb);
-        println!("{}: {}", paddr, pb);
-        println!("{}: {}", caddr, cb);
-        println!();
+        writeln!(output.console(), "{}: {}", xaddr, xb)?;
+        writeln!(output.console(), "{}: {}", paddr, pb)?;
+        writeln!(output.console(), "{}: {}", caddr, cb)?;
+        writeln!(output.console())?;
+
+        balances.push(Balances {
+            x_address: xaddr,
+            x_balance: xb,
+            p_address: paddr,
+            p_balance: pb,
+            c_address: caddr,
+            c_balance: cb.to_string(),
+        });
     }
 
-    Ok(())
+    output.print(&balances)
 }
@@ -0,0 +1,271 @@
+use std::{
+    io::{self, Error, ErrorKind},
+    path::Path,
+};
+
+use clap::{Arg, Command};
+use crossterm::{
+    execute,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+};
+use log::info;
+use serde::Serialize;
+use tokio::runtime::Runtime;
+
+use avalanche_ops_aws::output;
+use aws::{self, envelope, kms, s3};
+use utils::id;
+
+pub const NAME: &str = "clone";
+
+pub fn command() -> Command<'static> {
+    Command::new(NAME)
+        .about("Writes the spec of the new network restored from the snapshot by 'snapshot' (run 'apply' to create it)")
+        .arg(
+            Arg::new("LOG_LEVEL")
+                .long("log-level")
+                .short('l')
+                .help("Sets the log level")
+                .required(false)
+                .takes_value(true)
+                .possible_value("debug")
+                .possible_value("info")
+                .allow_invalid_utf8(false)
+                .default_value("info"),
+        )
+        .arg(
+            Arg::new("SPEC_FILE_PATH")
+                .long("spec-file-path")
+                .short('s')
+                .help("The spec file of the source network")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("SNAPSHOT_NAME")
+                .long("snapshot-name")
+                .help("Sets the snapshot of the source network to restore from")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("CLONE_SPEC_FILE_PATH")
+                .long("clone-spec-file-path")
+                .help("The spec file to write for the new network")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("ID")
+                .long("id")
+                .help("Sets the ID of the new network (generated if empty)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+}
+
+/// Describes the written clone spec, for the structured output.
+#[derive(Debug, Serialize)]
+struct Cloned {
+    id: String,
+    spec_file_path: String,
+    source_id: String,
+    snapshot_name: String,
+    nodes: usize,
+}
+
+pub fn execute(
+    log_level: &str,
+    spec_file_path: &str,
+    snapshot_name: &str,
+    clone_spec_file_path: &str,
+    id: Option<&str>,
+    output: output::Format,
+) -> io::Result<()> {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    );
+
+    if Path::new(clone_spec_file_path).exists() {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("clone spec file '{}' already exists", clone_spec_file_path),
+        ));
+    }
+
+    let spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
+    let aws_resources = spec
+        .aws_resources
+        .clone()
+        .expect("unexpected None aws_resources");
+
+    let rt = Runtime::new().unwrap();
+    let shared_config = rt
+        .block_on(aws::load_config(Some(aws_resources.region.clone())))
+        .expect("failed to aws::load_config");
+    let s3_manager = s3::Manager::new(&shared_config);
+
+    let snapshot_key = avalanche_ops_aws::StorageNamespace::NetworkSnapshot(
+        spec.id.clone(),
+        snapshot_name.to_string(),
+    )
+    .encode();
+    let snapshot =
+        crate::subnet::get_object(&rt, &s3_manager, &aws_resources.s3_bucket, &snapshot_key)?
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!(
+                        "snapshot '{}' not found (run 'snapshot' first)",
+                        snapshot_name
+                    ),
+                )
+            })?;
+    let snapshot = avalanche_ops_aws::NetworkSnapshot::decode_yaml(&snapshot)?;
+
+    let id = match id {
+        Some(v) if !v.is_empty() => v.to_string(),
+        _ => id::with_time("aops-clone"),
+    };
+    let clone_spec = snapshot.clone_spec(&spec, &id)?;
+    clone_spec.validate()?;
+    clone_spec.sync(clone_spec_file_path)?;
+
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Blue),
+        Print(format!(
+            "\nwrote the spec '{}' of {} node(s) restored from snapshot '{}' of '{}'\n",
+            id,
+            snapshot.nodes.len(),
+            snapshot_name,
+            spec.id
+        )),
+        ResetColor
+    )?;
+    let exec_path = std::env::current_exe().expect("unexpected None current_exe");
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Green),
+        Print(format!(
+            "{} apply \\\n--spec-file-path {}\n",
+            exec_path.display(),
+            clone_spec_file_path
+        )),
+        ResetColor
+    )?;
+
+    output.print(&Cloned {
+        id,
+        spec_file_path: clone_spec_file_path.to_string(),
+        source_id: spec.id.clone(),
+        snapshot_name: snapshot_name.to_string(),
+        nodes: snapshot.nodes.len(),
+    })
+}
+
+/// Copies the snapshot and the genesis of the source network to the bucket
+/// of the new network, for "avalanched" to restore from. On custom networks,
+/// the staking keys of the source nodes are re-encrypted with the KMS key
+/// of the new network, so the clone keeps the validator set of the genesis.
+/// Called by "apply" once the bucket and the KMS key exist.
+pub(crate) fn copy_source(
+    rt: &Runtime,
+    s3_manager: &s3::Manager,
+    kms_manager: &kms::Manager,
+    target_envelope: &envelope::Envelope,
+    spec: &avalanche_ops_aws::Spec,
+    s3_bucket: &str,
+) -> io::Result<()> {
+    let source = match &spec.clone_source {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+
+    let snapshot_key = avalanche_ops_aws::StorageNamespace::NetworkSnapshot(
+        source.id.clone(),
+        source.snapshot_name.clone(),
+    )
+    .encode();
+    let snapshot_bytes =
+        crate::subnet::get_object(rt, s3_manager, &source.s3_bucket, &snapshot_key)?.ok_or_else(
+            || Error::new(ErrorKind::NotFound, format!("'{}' not found", snapshot_key)),
+        )?;
+    let snapshot = avalanche_ops_aws::NetworkSnapshot::decode_yaml(&snapshot_bytes)?;
+    crate::snapshot::put_bytes(
+        rt,
+        s3_manager,
+        s3_bucket,
+        &avalanche_ops_aws::StorageNamespace::CloneSnapshot(spec.id.clone()).encode(),
+        &snapshot_bytes,
+    )?;
+
+    if !spec.avalanchego_config.is_custom_network() {
+        // the node IDs must be unique on the public networks,
+        // so the clone gets new staking certificates
+        return Ok(());
+    }
+
+    info!("copying the genesis file of snapshot '{}'", snapshot.name);
+    let genesis_key = avalanche_ops_aws::StorageNamespace::NetworkSnapshotGenesisFile(
+        source.id.clone(),
+        source.snapshot_name.clone(),
+    )
+    .encode();
+    let genesis = crate::subnet::get_object(rt, s3_manager, &source.s3_bucket, &genesis_key)?
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("'{}' not found", genesis_key)))?;
+    crate::snapshot::put_bytes(
+        rt,
+        s3_manager,
+        s3_bucket,
+        &avalanche_ops_aws::StorageNamespace::GenesisFile(spec.id.clone()).encode(),
+        &genesis,
+    )?;
+
+    let source_envelope =
+        envelope::Envelope::new(Some(kms_manager.clone()), Some(source.kms_cmk_id.clone()));
+    let source_pki_dir = avalanche_ops_aws::StorageNamespace::PkiKeyDir(source.id.clone()).encode();
+    let target_pki_dir =
+        avalanche_ops_aws::StorageNamespace::ClonePkiKeyDir(spec.id.clone()).encode();
+    for node in snapshot.nodes.iter() {
+        info!(
+            "copying the staking certificate of '{}' ({})",
+            node.machine_id, node.node_id
+        );
+        let cert_key = format!("{}/{}.crt", source_pki_dir, node.machine_id);
+        let cert = crate::subnet::get_object(rt, s3_manager, &source.s3_bucket, &cert_key)?
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("'{}' not found", cert_key)))?;
+        crate::snapshot::put_bytes(
+            rt,
+            s3_manager,
+            s3_bucket,
+            &format!("{}/{}.crt", target_pki_dir, node.machine_id),
+            &cert,
+        )?;
+
+        let key_key = format!(
+            "{}/{}.key.zstd.seal_aes_256.encrypted",
+            source_pki_dir, node.machine_id
+        );
+        let sealed = crate::subnet::get_object(rt, s3_manager, &source.s3_bucket, &key_key)?
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("'{}' not found", key_key)))?;
+        let unsealed = rt.block_on(source_envelope.unseal_aes_256(&sealed))?;
+        let resealed = rt.block_on(target_envelope.seal_aes_256(&unsealed))?;
+        crate::snapshot::put_bytes(
+            rt,
+            s3_manager,
+            s3_bucket,
+            &format!(
+                "{}/{}.key.zstd.seal_aes_256.encrypted",
+                target_pki_dir, node.machine_id
+            ),
+            &resealed,
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,384 @@
+use std::{
+    io::{self, Error, ErrorKind},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use avalanche_types::ids;
+use avalanchego::config as avalanchego_config;
+
+use crate::{upgrade::default_health_check_timeout_seconds, FLEET_COMMAND_LOG_LEVELS};
+
+/// Defines how "avalanched" rolls out the config updates,
+/// restarting at most "max_unavailable" nodes at a time.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ConfigUpdatePolicy {
+    /// Maximum number of nodes restarting at the same time.
+    #[serde(default = "default_max_unavailable")]
+    pub max_unavailable: u32,
+    /// Time to wait for the restarted node to become healthy
+    /// before restoring the previous config.
+    #[serde(default = "default_health_check_timeout_seconds")]
+    pub health_check_timeout_seconds: u64,
+}
+
+fn default_max_unavailable() -> u32 {
+    1
+}
+
+impl Default for ConfigUpdatePolicy {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl ConfigUpdatePolicy {
+    pub fn default() -> Self {
+        Self {
+            max_unavailable: default_max_unavailable(),
+            health_check_timeout_seconds: default_health_check_timeout_seconds(),
+        }
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        if self.max_unavailable == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'max_unavailable' must be at least 1",
+            ));
+        }
+        if self.health_check_timeout_seconds < 60 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'health_check_timeout_seconds' {} too short (at least 60)",
+                    self.health_check_timeout_seconds
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Subset of the avalanchego config that can be updated without
+/// recreating the nodes. "None" fields are left unchanged.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct ConfigDelta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_admin_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_keystore_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_metrics_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_health_enabled: Option<bool>,
+    /// Replaces the tracked subnet IDs (empty to track none).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_subnets: Option<Vec<String>>,
+}
+
+impl ConfigDelta {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        if self.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "empty config delta"));
+        }
+        if let Some(log_level) = &self.log_level {
+            if !FLEET_COMMAND_LOG_LEVELS.contains(&log_level.as_str()) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "invalid log level '{}' (expected one of {:?})",
+                        log_level, FLEET_COMMAND_LOG_LEVELS
+                    ),
+                ));
+            }
+        }
+        if let Some(subnets) = &self.track_subnets {
+            for subnet_id in subnets.iter() {
+                ids::Id::from_str(subnet_id).map_err(|e| {
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("invalid subnet ID '{}' ({})", subnet_id, e),
+                    )
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the delta to the config, and returns true if anything changed.
+    /// The subnets are written to "whitelisted-subnets" if already set
+    /// (older avalanchego releases), otherwise to "track-subnets".
+    pub fn apply(&self, config: &mut avalanchego_config::Config) -> bool {
+        let mut changed = false;
+        fn set<T: PartialEq + Clone>(dst: &mut Option<T>, src: &Option<T>, changed: &mut bool) {
+            if let Some(v) = src {
+                if dst.as_ref() != Some(v) {
+                    *dst = Some(v.clone());
+                    *changed = true;
+                }
+            }
+        }
+        set(&mut config.log_level, &self.log_level, &mut changed);
+        set(&mut config.index_enabled, &self.index_enabled, &mut changed);
+        set(
+            &mut config.api_admin_enabled,
+            &self.api_admin_enabled,
+            &mut changed,
+        );
+        set(
+            &mut config.api_keystore_enabled,
+            &self.api_keystore_enabled,
+            &mut changed,
+        );
+        set(
+            &mut config.api_metrics_enabled,
+            &self.api_metrics_enabled,
+            &mut changed,
+        );
+        set(
+            &mut config.api_health_enabled,
+            &self.api_health_enabled,
+            &mut changed,
+        );
+        if let Some(subnets) = &self.track_subnets {
+            let tracked = if config.whitelisted_subnets.is_some() {
+                &mut config.whitelisted_subnets
+            } else {
+                &mut config.track_subnets
+            };
+            let joined = if subnets.is_empty() {
+                None
+            } else {
+                Some(subnets.join(","))
+            };
+            if *tracked != joined {
+                *tracked = joined;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// Represents the config update in "StorageNamespace::EventsConfigUpdate",
+/// which "avalanched" applies once per version with the rolling restarts.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ConfigUpdateEvent {
+    /// Increases on every new event (unix timestamp of the request),
+    /// so that the failed nodes retry only when the event is posted again.
+    pub version: u64,
+    pub delta: ConfigDelta,
+    /// Copied from "Spec.config_update_policy" at the request,
+    /// so that the running nodes need not reload the spec.
+    pub policy: ConfigUpdatePolicy,
+}
+
+impl ConfigUpdateEvent {
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize ConfigUpdateEvent to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+
+    /// Returns true if the node status is the final result of this event.
+    pub fn is_applied(&self, status: &ConfigUpdateStatus) -> bool {
+        self.version == status.version
+            && matches!(
+                status.state,
+                ConfigUpdateState::Updated | ConfigUpdateState::Failed
+            )
+    }
+}
+
+/// Result of applying "ConfigUpdateEvent" on each node.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigUpdateState {
+    /// Waiting for the other nodes to finish restarting.
+    Waiting,
+    /// Claimed a restart slot, see "config_update_slot".
+    Restarting,
+    Updated,
+    /// Restored the previous config (e.g., the node did not become healthy).
+    Failed,
+}
+
+impl ConfigUpdateState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfigUpdateState::Waiting => "waiting",
+            ConfigUpdateState::Restarting => "restarting",
+            ConfigUpdateState::Updated => "updated",
+            ConfigUpdateState::Failed => "failed",
+        }
+    }
+}
+
+/// Represents the "ConfigUpdateEvent" result of the node
+/// in "StorageNamespace::EventsConfigUpdateStatus".
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ConfigUpdateStatus {
+    pub machine_id: String,
+    pub node_id: String,
+    pub version: u64,
+    pub state: ConfigUpdateState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Unix timestamp of the result.
+    pub updated_at: u64,
+}
+
+impl ConfigUpdateStatus {
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize ConfigUpdateStatus to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+}
+
+/// Returns true if the machine holds one of the "max_unavailable" restart slots
+/// of the event version. Every node first records its "Restarting" claim, then
+/// lists all claims: the earliest claims (ties broken by the machine ID) win,
+/// so all nodes agree without a lock. The claims not refreshed within
+/// "stale_seconds" (e.g., the node crashed while restarting) are ignored.
+pub fn config_update_slot(
+    statuses: &[ConfigUpdateStatus],
+    version: u64,
+    machine_id: &str,
+    max_unavailable: u32,
+    now: u64,
+    stale_seconds: u64,
+) -> bool {
+    let mut claims: Vec<&ConfigUpdateStatus> = statuses
+        .iter()
+        .filter(|s| {
+            s.version == version
+                && s.state == ConfigUpdateState::Restarting
+                && now.saturating_sub(s.updated_at) <= stale_seconds
+        })
+        .collect();
+    claims.sort_by(|a, b| {
+        (a.updated_at, a.machine_id.as_str()).cmp(&(b.updated_at, b.machine_id.as_str()))
+    });
+    claims
+        .iter()
+        .take(max_unavailable as usize)
+        .any(|s| s.machine_id == machine_id)
+}
+
+#[test]
+fn test_config_update() {
+    let policy: ConfigUpdatePolicy = serde_yaml::from_str("max_unavailable: 2").unwrap();
+    assert_eq!(policy.max_unavailable, 2);
+    assert_eq!(policy.health_check_timeout_seconds, 600);
+    policy.validate().unwrap();
+    let mut invalid = policy.clone();
+    invalid.max_unavailable = 0;
+    assert!(invalid.validate().is_err());
+
+    assert!(ConfigDelta::default().validate().is_err());
+    let delta = ConfigDelta {
+        log_level: Some(String::from("DEBUG")),
+        api_admin_enabled: Some(true),
+        track_subnets: Some(vec![String::from(
+            "hW8Ma7dLMA7o4xmJf3AXBbo17bXzE7xnThUd3ypM4VAWo1sNJ",
+        )]),
+        ..Default::default()
+    };
+    delta.validate().unwrap();
+    let mut invalid = delta.clone();
+    invalid.log_level = Some(String::from("LOUD"));
+    assert!(invalid.validate().is_err());
+
+    let mut config = avalanchego_config::Config::default();
+    config.api_admin_enabled = Some(false);
+    assert!(delta.apply(&mut config));
+    assert_eq!(config.log_level, Some(String::from("DEBUG")));
+    assert_eq!(config.api_admin_enabled, Some(true));
+    assert_eq!(
+        config.track_subnets,
+        Some(String::from(
+            "hW8Ma7dLMA7o4xmJf3AXBbo17bXzE7xnThUd3ypM4VAWo1sNJ"
+        ))
+    );
+    assert!(!delta.apply(&mut config));
+
+    config.whitelisted_subnets = Some(String::new());
+    config.track_subnets = None;
+    assert!(delta.apply(&mut config));
+    assert!(config.whitelisted_subnets.is_some());
+    assert!(config.track_subnets.is_none());
+
+    let event = ConfigUpdateEvent {
+        version: 1650000000,
+        delta,
+        policy,
+    };
+    let decoded = ConfigUpdateEvent::decode_yaml(event.encode_yaml().unwrap().as_bytes()).unwrap();
+    assert_eq!(decoded, event);
+
+    let status = |machine_id: &str, state: ConfigUpdateState, updated_at: u64| ConfigUpdateStatus {
+        machine_id: machine_id.to_string(),
+        node_id: String::from("NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg"),
+        version: event.version,
+        state,
+        message: None,
+        updated_at,
+    };
+    let mut statuses = vec![
+        status("i-c", ConfigUpdateState::Restarting, 100),
+        status("i-b", ConfigUpdateState::Restarting, 100),
+        status("i-a", ConfigUpdateState::Restarting, 110),
+        status("i-d", ConfigUpdateState::Updated, 50),
+        status("i-e", ConfigUpdateState::Restarting, 10),
+    ];
+    let decoded =
+        ConfigUpdateStatus::decode_yaml(statuses[0].encode_yaml().unwrap().as_bytes()).unwrap();
+    assert_eq!(decoded, statuses[0]);
+    assert!(!event.is_applied(&statuses[0]));
+    assert!(event.is_applied(&statuses[3]));
+
+    // "i-e" is stale, "i-b" and "i-c" claimed first
+    let slot = |statuses: &[ConfigUpdateStatus], version: u64, machine_id: &str| {
+        config_update_slot(statuses, version, machine_id, 2, 120, 60)
+    };
+    assert!(slot(&statuses, event.version, "i-b"));
+    assert!(slot(&statuses, event.version, "i-c"));
+    assert!(!slot(&statuses, event.version, "i-a"));
+    assert!(!slot(&statuses, event.version, "i-d"));
+    assert!(!slot(&statuses, event.version, "i-e"));
+
+    statuses[1].state = ConfigUpdateState::Updated;
+    assert!(slot(&statuses, event.version, "i-a"));
+    assert!(!slot(&statuses, event.version + 1, "i-a"));
+}
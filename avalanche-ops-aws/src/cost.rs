@@ -0,0 +1,478 @@
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use aws::{
+    ec2::{ebs, fleet},
+    pricing,
+};
+
+use crate::Spec;
+
+/// Hours in an average month, as in the AWS pricing calculator.
+pub const HOURS_PER_MONTH: f64 = 730.0;
+
+/// Default of "InstanceTypes" in the ASG template.
+pub const DEFAULT_INSTANCE_TYPE: &str = "c6a.2xlarge";
+
+/// Spot prices vary by the zone and the hour, and the price list API does not
+/// serve them, so the spot instances are estimated at this ratio of on-demand.
+pub const SPOT_PRICE_RATIO: f64 = 0.35;
+
+/// Prices in USD used for the estimate.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct PriceTable {
+    /// "pricing-api" if any price is fetched, "offline" otherwise.
+    pub source: String,
+    /// On-demand hourly price of the Linux instances.
+    pub instance_hourly: BTreeMap<String, f64>,
+    /// Monthly price per GB of the EBS volume types.
+    pub ebs_gb_month: BTreeMap<String, f64>,
+    /// Monthly price per provisioned IOPS (io1/io2, and gp3 beyond 3,000).
+    pub ebs_iops_month: BTreeMap<String, f64>,
+    /// Monthly price per MiB/s of gp3 throughput beyond 125 MiB/s.
+    pub gp3_throughput_month: f64,
+    pub snapshot_gb_month: f64,
+    pub s3_gb_month: f64,
+    /// Hourly price of the load balancer, without the capacity units.
+    pub load_balancer_hourly: f64,
+}
+
+impl Default for PriceTable {
+    fn default() -> Self {
+        Self::offline()
+    }
+}
+
+impl PriceTable {
+    /// Returns the fallback prices of "us-east-1" (2022), used when
+    /// the price list API is not reachable or does not list the item.
+    pub fn offline() -> Self {
+        let instance_hourly = [
+            ("c5.large", 0.085),
+            ("c5.xlarge", 0.17),
+            ("c5.2xlarge", 0.34),
+            ("c5.4xlarge", 0.68),
+            ("c6a.large", 0.0765),
+            ("c6a.xlarge", 0.153),
+            ("c6a.2xlarge", 0.306),
+            ("c6a.4xlarge", 0.612),
+            ("m5.large", 0.096),
+            ("m5.xlarge", 0.192),
+            ("m5.2xlarge", 0.384),
+            ("m5.4xlarge", 0.768),
+            ("m6a.large", 0.0864),
+            ("m6a.xlarge", 0.1728),
+            ("m6a.2xlarge", 0.3456),
+            ("m6a.4xlarge", 0.6912),
+            ("r5.large", 0.126),
+            ("r5.xlarge", 0.252),
+            ("r5.2xlarge", 0.504),
+            ("r5.4xlarge", 1.008),
+            ("r6a.large", 0.1134),
+            ("r6a.xlarge", 0.2268),
+            ("r6a.2xlarge", 0.4536),
+            ("r6a.4xlarge", 0.9072),
+            ("t3.large", 0.0832),
+            ("t3.xlarge", 0.1664),
+            ("t3.2xlarge", 0.3328),
+        ];
+        let ebs_gb_month = [
+            ("gp2", 0.10),
+            ("gp3", 0.08),
+            ("io1", 0.125),
+            ("io2", 0.125),
+            ("st1", 0.045),
+            ("sc1", 0.015),
+        ];
+        let ebs_iops_month = [("gp3", 0.005), ("io1", 0.065), ("io2", 0.065)];
+        Self {
+            source: String::from("offline"),
+            instance_hourly: instance_hourly
+                .iter()
+                .map(|(k, v)| (k.to_string(), *v))
+                .collect(),
+            ebs_gb_month: ebs_gb_month
+                .iter()
+                .map(|(k, v)| (k.to_string(), *v))
+                .collect(),
+            ebs_iops_month: ebs_iops_month
+                .iter()
+                .map(|(k, v)| (k.to_string(), *v))
+                .collect(),
+            gp3_throughput_month: 0.04,
+            snapshot_gb_month: 0.05,
+            s3_gb_month: 0.023,
+            load_balancer_hourly: 0.0225,
+        }
+    }
+
+    /// Overrides the offline prices with the price list API for the instance
+    /// and the volume types of the spec. Each failed lookup keeps the offline price.
+    pub async fn fetch(pricing_manager: &pricing::Manager, region: &str, spec: &Spec) -> Self {
+        let mut table = Self::offline();
+        let mut fetched = false;
+        for instance_type in instance_types(spec) {
+            match pricing_manager
+                .fetch_instance_hourly_usd(region, &instance_type)
+                .await
+            {
+                Ok(Some(v)) => {
+                    table.instance_hourly.insert(instance_type, v);
+                    fetched = true;
+                }
+                Ok(None) => warn!("no price listed for '{}' in '{}'", instance_type, region),
+                Err(e) => warn!("failed to fetch price ({}), using offline", e.message()),
+            }
+        }
+        let volume_type = volume_spec(spec).volume_type;
+        match pricing_manager
+            .fetch_ebs_gb_month_usd(region, &volume_type)
+            .await
+        {
+            Ok(Some(v)) => {
+                table.ebs_gb_month.insert(volume_type, v);
+                fetched = true;
+            }
+            Ok(None) => warn!("no price listed for '{}' in '{}'", volume_type, region),
+            Err(e) => warn!("failed to fetch price ({}), using offline", e.message()),
+        }
+        if fetched {
+            table.source = String::from("pricing-api");
+        }
+        table
+    }
+}
+
+/// Represents each line of the estimate.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct LineItem {
+    pub name: String,
+    pub quantity: f64,
+    /// e.g., "instance-hour", "GB-month".
+    pub unit: String,
+    /// "None" if no price is known (e.g., instance type not in the offline table).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_usd: Option<f64>,
+    pub monthly_usd: f64,
+}
+
+impl LineItem {
+    fn new(name: String, quantity: f64, unit: &str, unit_usd: Option<f64>) -> Self {
+        Self {
+            name,
+            quantity,
+            unit: unit.to_string(),
+            unit_usd,
+            monthly_usd: quantity * unit_usd.unwrap_or(0.0),
+        }
+    }
+}
+
+/// Represents the monthly cost estimate of the spec.
+/// Excludes the data transfer, the load balancer capacity units,
+/// and the CloudWatch usage, which depend on the traffic.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Estimate {
+    pub region: String,
+    pub source: String,
+    pub items: Vec<LineItem>,
+    pub monthly_usd: f64,
+}
+
+impl Estimate {
+    /// Returns the items without the known price.
+    pub fn unpriced(&self) -> Vec<&LineItem> {
+        self.items.iter().filter(|i| i.unit_usd.is_none()).collect()
+    }
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "estimated monthly cost in '{}' ({} prices): ${:.2}",
+            self.region, self.source, self.monthly_usd
+        )?;
+        for i in self.items.iter() {
+            let unit_usd = match i.unit_usd {
+                Some(v) => format!("${}", v),
+                None => String::from("unknown price"),
+            };
+            write!(
+                f,
+                "\n  {:<48} {:>10.1} {:<14} x {:<14} = ${:>10.2}",
+                i.name, i.quantity, i.unit, unit_usd, i.monthly_usd
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the on-demand and spot instances of the node group.
+pub fn split_purchase(nodes: u32, purchase_options: Option<&fleet::PurchaseOptions>) -> (u32, u32) {
+    let opts = match purchase_options {
+        Some(v) => v,
+        None => return (nodes, 0),
+    };
+    let base = nodes.min(opts.on_demand_base_capacity);
+    let above = nodes - base;
+    // the ASG rounds the on-demand instances up
+    let on_demand_above =
+        (f64::from(above * opts.on_demand_percentage_above_base_capacity) / 100.0).ceil() as u32;
+    let on_demand = base + on_demand_above.min(above);
+    (on_demand, nodes - on_demand)
+}
+
+/// Returns the instance type of each node group, which the ASG launches
+/// in the priority order.
+fn instance_types(spec: &Spec) -> Vec<String> {
+    let primary = spec
+        .machine
+        .instance_types
+        .first()
+        .cloned()
+        .unwrap_or_else(|| String::from(DEFAULT_INSTANCE_TYPE));
+    let mut types = vec![primary.clone()];
+    if let Some(api_nodes) = &spec.machine.api_nodes {
+        let t = api_nodes.instance_types.first().cloned().unwrap_or(primary);
+        if !types.contains(&t) {
+            types.push(t);
+        }
+    }
+    types
+}
+
+/// Returns the volume of each node, as set by "apply".
+fn volume_spec(spec: &Spec) -> ebs::VolumeSpec {
+    if let Some(data_volume) = &spec.machine.data_volume {
+        return data_volume.volume.clone();
+    }
+    let mut volume = ebs::VolumeSpec::default();
+    if spec.avalanchego_config.is_mainnet() {
+        volume.size_in_gb = 800;
+    }
+    volume
+}
+
+/// Returns the size of the install artifacts to upload, in GB.
+/// The missing files are skipped.
+pub fn install_artifacts_gb(spec: &Spec) -> f64 {
+    let size = |p: &str| fs::metadata(p).map_or(0, |m| m.len());
+    let mut bytes = size(&spec.install_artifacts.avalanched_bin)
+        + size(&spec.install_artifacts.avalanchego_bin);
+    if let Some(plugins_dir) = &spec.install_artifacts.plugins_dir {
+        if let Ok(entries) = fs::read_dir(Path::new(plugins_dir)) {
+            for entry in entries.flatten() {
+                bytes += entry.metadata().map_or(0, |m| m.len());
+            }
+        }
+    }
+    bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+}
+
+/// Estimates the monthly cost of the spec with the prices.
+/// The nodes in the other regions are priced as the primary region.
+pub fn estimate(spec: &Spec, prices: &PriceTable, s3_gb: f64) -> Estimate {
+    let region = spec
+        .aws_resources
+        .as_ref()
+        .map_or_else(String::new, |r| r.region.clone());
+    let types = instance_types(spec);
+    let purchase_options = spec.machine.purchase_options.as_ref();
+
+    // (node kind, instance type, nodes, purchase options)
+    let mut groups: Vec<(String, String, u32, Option<&fleet::PurchaseOptions>)> = Vec::new();
+    if spec.avalanchego_config.is_custom_network() {
+        // always on-demand
+        groups.push((
+            String::from("anchor"),
+            types[0].clone(),
+            spec.machine.anchor_nodes.unwrap_or(0),
+            None,
+        ));
+    }
+    groups.push((
+        String::from("non-anchor"),
+        types[0].clone(),
+        spec.machine.non_anchor_nodes,
+        purchase_options,
+    ));
+    if let Some(api_nodes) = &spec.machine.api_nodes {
+        groups.push((
+            String::from("api"),
+            types.last().unwrap().clone(),
+            api_nodes.nodes,
+            purchase_options,
+        ));
+    }
+    if let Some(regional_resources) = spec
+        .aws_resources
+        .as_ref()
+        .and_then(|r| r.regional_resources.as_ref())
+    {
+        for r in regional_resources.iter() {
+            groups.push((
+                format!("non-anchor in '{}'", r.region),
+                types[0].clone(),
+                r.non_anchor_nodes,
+                purchase_options,
+            ));
+        }
+    }
+
+    let mut items = Vec::new();
+    let mut total_nodes = 0;
+    for (kind, instance_type, nodes, opts) in groups.iter() {
+        if *nodes == 0 {
+            continue;
+        }
+        total_nodes += nodes;
+        let on_demand_hourly = prices.instance_hourly.get(instance_type).copied();
+        let (on_demand, spot) = split_purchase(*nodes, *opts);
+        if on_demand > 0 {
+            items.push(LineItem::new(
+                format!("{} {} on-demand x{}", kind, instance_type, on_demand),
+                on_demand as f64 * HOURS_PER_MONTH,
+                "instance-hour",
+                on_demand_hourly,
+            ));
+        }
+        if spot > 0 {
+            items.push(LineItem::new(
+                format!("{} {} spot x{}", kind, instance_type, spot),
+                spot as f64 * HOURS_PER_MONTH,
+                "instance-hour",
+                on_demand_hourly.map(|v| v * SPOT_PRICE_RATIO),
+            ));
+        }
+    }
+
+    let volume = volume_spec(spec);
+    items.push(LineItem::new(
+        format!("EBS {} volume x{}", volume.volume_type, total_nodes),
+        (total_nodes * volume.size_in_gb) as f64,
+        "GB-month",
+        prices.ebs_gb_month.get(&volume.volume_type).copied(),
+    ));
+    let provisioned_iops = match volume.volume_type.as_str() {
+        "gp3" => volume.iops.saturating_sub(3000),
+        "io1" | "io2" => volume.iops,
+        _ => 0,
+    };
+    if provisioned_iops > 0 {
+        items.push(LineItem::new(
+            format!("EBS {} provisioned IOPS", volume.volume_type),
+            (total_nodes * provisioned_iops) as f64,
+            "IOPS-month",
+            prices.ebs_iops_month.get(&volume.volume_type).copied(),
+        ));
+    }
+    if volume.volume_type == "gp3" && volume.throughput > 125 {
+        items.push(LineItem::new(
+            String::from("EBS gp3 provisioned throughput"),
+            (total_nodes * (volume.throughput - 125)) as f64,
+            "MiBps-month",
+            Some(prices.gp3_throughput_month),
+        ));
+    }
+    if let Some(data_volume) = &spec.machine.data_volume {
+        if data_volume.snapshot_interval_seconds > 0 {
+            // the snapshots are incremental, so this is the upper bound
+            let node_kinds = groups.iter().filter(|g| g.2 > 0).count();
+            items.push(LineItem::new(
+                format!(
+                    "EBS snapshots (retention {}, upper bound)",
+                    data_volume.snapshot_retention
+                ),
+                (node_kinds * data_volume.snapshot_retention) as f64 * volume.size_in_gb as f64,
+                "GB-month",
+                Some(prices.snapshot_gb_month),
+            ));
+        }
+    }
+
+    items.push(LineItem::new(
+        String::from("S3 install artifacts"),
+        s3_gb,
+        "GB-month",
+        Some(prices.s3_gb_month),
+    ));
+
+    // the VPC only has the public subnets, so no NAT gateway
+    if let Some(load_balancer) = &spec.load_balancer {
+        items.push(LineItem::new(
+            format!(
+                "{} (without capacity units)",
+                load_balancer.kind.as_str().to_uppercase()
+            ),
+            HOURS_PER_MONTH,
+            "LB-hour",
+            Some(prices.load_balancer_hourly),
+        ));
+    }
+
+    for i in items.iter().filter(|i| i.unit_usd.is_none()) {
+        warn!("no price known for '{}', excluded from the total", i.name);
+    }
+    let monthly_usd = items.iter().map(|i| i.monthly_usd).sum();
+    Estimate {
+        region,
+        source: prices.source.clone(),
+        items,
+        monthly_usd,
+    }
+}
+
+#[test]
+fn test_split_purchase() {
+    assert_eq!(split_purchase(5, None), (5, 0));
+
+    let mut opts = fleet::PurchaseOptions::default();
+    assert_eq!(split_purchase(5, Some(&opts)), (5, 0));
+
+    opts.on_demand_base_capacity = 1;
+    opts.on_demand_percentage_above_base_capacity = 25;
+    // 1 + ceil(4 * 25%)
+    assert_eq!(split_purchase(5, Some(&opts)), (2, 3));
+    assert_eq!(split_purchase(1, Some(&opts)), (1, 0));
+    assert_eq!(split_purchase(0, Some(&opts)), (0, 0));
+
+    opts.on_demand_percentage_above_base_capacity = 0;
+    assert_eq!(split_purchase(5, Some(&opts)), (1, 4));
+}
+
+#[test]
+fn test_line_item() {
+    let prices = PriceTable::offline();
+    let item = LineItem::new(
+        String::from("non-anchor c6a.2xlarge on-demand x2"),
+        2.0 * HOURS_PER_MONTH,
+        "instance-hour",
+        prices.instance_hourly.get("c6a.2xlarge").copied(),
+    );
+    assert!((item.monthly_usd - 446.76).abs() < 0.01);
+
+    let unknown = LineItem::new(
+        String::from("non-anchor x9.large on-demand x1"),
+        HOURS_PER_MONTH,
+        "instance-hour",
+        prices.instance_hourly.get("x9.large").copied(),
+    );
+    assert_eq!(unknown.monthly_usd, 0.0);
+
+    let estimate = Estimate {
+        region: String::from("us-west-2"),
+        source: prices.source.clone(),
+        monthly_usd: item.monthly_usd,
+        items: vec![item, unknown],
+    };
+    assert_eq!(estimate.unpriced().len(), 1);
+    assert!(estimate
+        .to_string()
+        .starts_with("estimated monthly cost in 'us-west-2' (offline prices): $446.76"));
+}
@@ -1,11 +1,22 @@
-use std::io::{self, stdout};
+use std::{
+    io::{self, Error, ErrorKind, Write},
+    str::FromStr,
+};
 
 use clap::{Arg, Command};
 use crossterm::{
     execute,
     style::{Color, Print, ResetColor, SetForegroundColor},
 };
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
+use serde::Serialize;
 
+use avalanche_ops_aws::{
+    output, DefaultSpecOption, DEFAULT_MACHINE_ANCHOR_NODES, DEFAULT_MACHINE_NON_ANCHOR_NODES,
+    MAX_MACHINE_ANCHOR_NODES, MAX_MACHINE_NON_ANCHOR_NODES, MIN_MACHINE_ANCHOR_NODES,
+    MIN_MACHINE_NON_ANCHOR_NODES,
+};
+use avalanche_types::{constants, ids};
 use avalanchego::config as avalanchego_config;
 use utils::home_dir;
 
@@ -26,6 +37,15 @@ pub fn command() -> Command<'static> {
                 .allow_invalid_utf8(false)
                 .default_value("info"),
         )
+        .arg(
+            Arg::new("INTERACTIVE")
+                .long("interactive")
+                .short('i')
+                .help("Prompts for the network, regions, nodes, instance types, keys, and subnets (other flags are the defaults)")
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        )
         .arg(
             Arg::new("REGION")
                 .long("region")
@@ -36,6 +56,14 @@ pub fn command() -> Command<'static> {
                 .allow_invalid_utf8(false)
                 .default_value("us-west-2"),
         )
+        .arg(
+            Arg::new("REGIONAL_NON_ANCHOR_NODES")
+                .long("regional-non-anchor-nodes")
+                .help("Sets the non-anchor nodes in the other regions (e.g., us-east-1=2,eu-west-1=1)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
         .arg(
             Arg::new("DB_BACKUP_S3_REGION") 
                 .long("db-backup-s3-region")
@@ -101,6 +129,38 @@ pub fn command() -> Command<'static> {
                 .allow_invalid_utf8(false)
                 .default_value("custom"),
         )
+        .arg(
+            Arg::new("NETWORK_ID")
+                .long("network-id")
+                .help("Sets the network ID of the custom network (defaults to 1000000)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("ANCHOR_NODES")
+                .long("anchor-nodes")
+                .help("Sets the number of anchor nodes (only for custom networks)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("NON_ANCHOR_NODES")
+                .long("non-anchor-nodes")
+                .help("Sets the number of non-anchor nodes in the primary region")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("INSTANCE_TYPES")
+                .long("instance-types")
+                .help("Sets the comma-separated EC2 instance types (e.g., c6a.2xlarge,m6a.2xlarge)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
         .arg(
             Arg::new("KEYS_TO_GENERATE") 
                 .long("keys-to-generate")
@@ -250,12 +310,36 @@ pub fn command() -> Command<'static> {
         )
 }
 
-pub fn execute(opt: avalanche_ops_aws::DefaultSpecOption) -> io::Result<()> {
+/// Describes the written spec, for the structured output.
+#[derive(Debug, Serialize)]
+struct Written {
+    id: String,
+    network_id: u32,
+    spec_file_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subnet_evm_genesis_file_path: Option<String>,
+}
+
+pub fn execute(
+    opt: DefaultSpecOption,
+    interactive: bool,
+    output: output::Format,
+) -> io::Result<()> {
     // ref. https://github.com/env-logger-rs/env_logger/issues/47
     env_logger::init_from_env(
         env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, opt.clone().log_level),
     );
 
+    let opt = if interactive {
+        match wizard(opt, output)? {
+            Some(v) => v,
+            None => return Ok(()),
+        }
+    } else {
+        opt
+    };
+    validate_option(&opt)?;
+
     let spec = avalanche_ops_aws::Spec::default_aws(opt.clone());
     spec.validate()?;
 
@@ -269,25 +353,25 @@ pub fn execute(opt: avalanche_ops_aws::DefaultSpecOption) -> io::Result<()> {
     spec.sync(&spec_file_path)?;
 
     execute!(
-        stdout(),
+        output.console(),
         SetForegroundColor(Color::Blue),
         Print(format!("\nSaved spec: '{}'\n", spec_file_path)),
         ResetColor
     )?;
     let spec_contents = spec.encode_yaml().expect("failed spec.encode_yaml");
-    println!("{}", spec_contents);
+    writeln!(output.console(), "{}", spec_contents)?;
 
-    println!();
-    println!("# run the following to create resources");
+    writeln!(output.console())?;
+    writeln!(output.console(), "# run the following to create resources")?;
     execute!(
-        stdout(),
+        output.console(),
         SetForegroundColor(Color::Magenta),
         Print(format!("cat {}\n", spec_file_path)),
         ResetColor
     )?;
     let exec_path = std::env::current_exe().expect("unexpected None current_exe");
     execute!(
-        stdout(),
+        output.console(),
         SetForegroundColor(Color::Green),
         Print(format!(
             "{} apply \\\n--spec-file-path {}\n",
@@ -296,10 +380,10 @@ pub fn execute(opt: avalanche_ops_aws::DefaultSpecOption) -> io::Result<()> {
         )),
         ResetColor
     )?;
-    println!();
-    println!("# run the following to delete resources");
+    writeln!(output.console())?;
+    writeln!(output.console(), "# run the following to delete resources")?;
     execute!(
-        stdout(),
+        output.console(),
         SetForegroundColor(Color::Green),
         Print(format!(
                     "{} delete \\\n--delete-cloudwatch-log-group \\\n--delete-s3-objects \\\n--spec-file-path {}\n",
@@ -309,21 +393,31 @@ pub fn execute(opt: avalanche_ops_aws::DefaultSpecOption) -> io::Result<()> {
         ResetColor
     )?;
 
+    let mut written = Written {
+        id: spec.id.clone(),
+        network_id: spec.avalanchego_config.network_id,
+        spec_file_path: spec_file_path.clone(),
+        subnet_evm_genesis_file_path: None,
+    };
     if spec.subnet_evm_genesis.is_some() {
         let subnet_evm_genesis_file_path =
             home_dir::named(&spec.id, Some(".subnet-evm.genesis.json"));
+        written.subnet_evm_genesis_file_path = Some(subnet_evm_genesis_file_path.clone());
         let subnet_evm_genesis = spec
             .subnet_evm_genesis
             .expect("unexpected None subnet_evm_genesis");
-        println!();
+        writeln!(output.console())?;
         subnet_evm_genesis
             .sync(&subnet_evm_genesis_file_path)
             .expect("failed subnet_evm_genesis.sync");
 
-        println!();
-        println!("# [optional] after 'apply', run the following to create subnet-evm resources");
+        writeln!(output.console())?;
+        writeln!(
+            output.console(),
+            "# [optional] after 'apply', run the following to create subnet-evm resources"
+        )?;
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Magenta),
             Print(format!("cat {} | grep private_key_hex:\n", spec_file_path)),
             ResetColor
@@ -332,7 +426,7 @@ pub fn execute(opt: avalanche_ops_aws::DefaultSpecOption) -> io::Result<()> {
             .generated_seed_private_keys
             .expect("unexpected None generated_seed_private_keys");
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Cyan),
             Print(format!(
                 "cat <<EOF > /tmp/test.key\n{}\nEOF\ncat /tmp/test.key\n",
@@ -341,23 +435,23 @@ pub fn execute(opt: avalanche_ops_aws::DefaultSpecOption) -> io::Result<()> {
             ResetColor
         )?;
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Magenta),
             Print(format!("cat {}\n", subnet_evm_genesis_file_path)),
             ResetColor
         )?;
 
-        println!();
+        writeln!(output.console())?;
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Green),
             Print("subnet-cli add validator \\\n--enable-prompt \\\n--private-key-path=/tmp/test.key \\\n--public-uri=... \\\n--stake-amount=2000000000000 \\\n--validate-reward-fee-percent=2 \\\n--node-ids=\"...\"\n"),
             ResetColor
         )?;
 
-        println!();
+        writeln!(output.console())?;
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Green),
             Print(format!(
                 "subnet-cli wizard \\\n--enable-prompt \\\n--private-key-path=/tmp/test.key \\\n--public-uri=... \\\n--vm-genesis-path={} \\\n--vm-id=srEXiWaHuhNyGwPUi444Tu47ZEDwxTWrbQiuD7FmgSAQ6X7Dy \\\n--chain-name=subnetevm \\\n--node-ids=\"...\"\n",
@@ -367,5 +461,332 @@ pub fn execute(opt: avalanche_ops_aws::DefaultSpecOption) -> io::Result<()> {
         )?;
     }
 
+    output.print(&written)
+}
+
+/// Walks the operator through the spec options, validating each answer.
+/// The flag values are the defaults of the prompts.
+/// Returns None if the operator does not confirm the options.
+fn wizard(
+    mut opt: DefaultSpecOption,
+    output: output::Format,
+) -> io::Result<Option<DefaultSpecOption>> {
+    let theme = ColorfulTheme::default();
+
+    let networks = ["custom", "fuji", "mainnet"];
+    let selected = Select::with_theme(&theme)
+        .with_prompt("Select the network")
+        .items(&networks[..])
+        .default(
+            networks
+                .iter()
+                .position(|v| *v == opt.network_name)
+                .unwrap_or(0),
+        )
+        .interact()?;
+    opt.network_name = networks[selected].to_string();
+    let custom = opt.network_name == "custom";
+    if custom {
+        let network_id: u32 = Input::with_theme(&theme)
+            .with_prompt("Network ID of the custom network")
+            .default(
+                opt.network_id
+                    .unwrap_or(constants::DEFAULT_CUSTOM_NETWORK_ID),
+            )
+            .validate_with(|v: &u32| validate_custom_network_id(*v))
+            .interact_text()?;
+        opt.network_id = Some(network_id);
+    } else {
+        opt.network_id = None;
+    }
+
+    opt.region = Input::with_theme(&theme)
+        .with_prompt("AWS region of the anchor and non-anchor nodes")
+        .default(opt.region.clone())
+        .validate_with(|v: &String| avalanche_ops_aws::validate_region(v))
+        .interact_text()?;
+
+    if custom {
+        let anchor_nodes: u32 = Input::with_theme(&theme)
+            .with_prompt(format!(
+                "Anchor nodes, the initial validators ({}-{})",
+                MIN_MACHINE_ANCHOR_NODES, MAX_MACHINE_ANCHOR_NODES
+            ))
+            .default(opt.anchor_nodes.unwrap_or(DEFAULT_MACHINE_ANCHOR_NODES))
+            .validate_with(|v: &u32| {
+                validate_nodes(
+                    "anchor nodes",
+                    *v,
+                    MIN_MACHINE_ANCHOR_NODES,
+                    MAX_MACHINE_ANCHOR_NODES,
+                )
+            })
+            .interact_text()?;
+        opt.anchor_nodes = Some(anchor_nodes);
+    } else {
+        opt.anchor_nodes = None;
+    }
+    let non_anchor_nodes: u32 = Input::with_theme(&theme)
+        .with_prompt(format!(
+            "Non-anchor nodes in {} ({}-{})",
+            opt.region, MIN_MACHINE_NON_ANCHOR_NODES, MAX_MACHINE_NON_ANCHOR_NODES
+        ))
+        .default(
+            opt.non_anchor_nodes
+                .unwrap_or(DEFAULT_MACHINE_NON_ANCHOR_NODES),
+        )
+        .validate_with(|v: &u32| {
+            validate_nodes(
+                "non-anchor nodes",
+                *v,
+                MIN_MACHINE_NON_ANCHOR_NODES,
+                MAX_MACHINE_NON_ANCHOR_NODES,
+            )
+        })
+        .interact_text()?;
+    opt.non_anchor_nodes = Some(non_anchor_nodes);
+
+    // the regional non-anchor nodes from the flags are kept
+    opt.regional_non_anchor_nodes
+        .retain(|(region, _)| *region != opt.region);
+    loop {
+        let regions: Vec<String> = opt
+            .regional_non_anchor_nodes
+            .iter()
+            .map(|(region, nodes)| format!("{}={}", region, nodes))
+            .collect();
+        let more = Confirm::with_theme(&theme)
+            .with_prompt(format!(
+                "Add non-anchor nodes in another region (current: [{}])?",
+                regions.join(",")
+            ))
+            .default(false)
+            .interact()?;
+        if !more {
+            break;
+        }
+
+        let mut taken = vec![opt.region.clone()];
+        taken.extend(opt.regional_non_anchor_nodes.iter().map(|(r, _)| r.clone()));
+        let region: String = Input::with_theme(&theme)
+            .with_prompt("AWS region")
+            .validate_with(|v: &String| -> io::Result<()> {
+                avalanche_ops_aws::validate_region(v)?;
+                if taken.contains(v) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("region '{}' already has nodes", v),
+                    ));
+                }
+                Ok(())
+            })
+            .interact_text()?;
+        let nodes: u32 = Input::with_theme(&theme)
+            .with_prompt(format!(
+                "Non-anchor nodes in {} ({}-{})",
+                region, MIN_MACHINE_NON_ANCHOR_NODES, MAX_MACHINE_NON_ANCHOR_NODES
+            ))
+            .default(DEFAULT_MACHINE_NON_ANCHOR_NODES)
+            .validate_with(|v: &u32| {
+                validate_nodes(
+                    "non-anchor nodes",
+                    *v,
+                    MIN_MACHINE_NON_ANCHOR_NODES,
+                    MAX_MACHINE_NON_ANCHOR_NODES,
+                )
+            })
+            .interact_text()?;
+        opt.regional_non_anchor_nodes.push((region, nodes));
+    }
+
+    let instance_types = if opt.instance_types.is_empty() {
+        avalanche_ops_aws::DEFAULT_EC2_INSTANCE_TYPES_AMD64.join(",")
+    } else {
+        opt.instance_types.join(",")
+    };
+    let instance_types: String = Input::with_theme(&theme)
+        .with_prompt("EC2 instance types (comma-separated, in the order of preference)")
+        .default(instance_types)
+        .validate_with(|v: &String| parse_instance_types(v).map(|_| ()))
+        .interact_text()?;
+    opt.instance_types = parse_instance_types(&instance_types)?;
+
+    opt.enable_subnet_evm = Confirm::with_theme(&theme)
+        .with_prompt("Enable subnet-evm (pre-funds the generated keys in its genesis)?")
+        .default(opt.enable_subnet_evm)
+        .interact()?;
+    let enable_subnet_evm = opt.enable_subnet_evm;
+    opt.keys_to_generate = Input::with_theme(&theme)
+        .with_prompt(if custom {
+            "Keys to generate and pre-fund in the genesis"
+        } else {
+            "Keys to generate"
+        })
+        .default(opt.keys_to_generate)
+        .validate_with(|v: &usize| validate_keys_to_generate(*v, enable_subnet_evm))
+        .interact_text()?;
+
+    opt.avalanchego_whitelisted_subnets = Input::with_theme(&theme)
+        .with_prompt("Whitelisted subnet IDs (comma-separated, empty for none)")
+        .default(opt.avalanchego_whitelisted_subnets.clone())
+        .allow_empty(true)
+        .validate_with(|v: &String| validate_subnet_ids(v))
+        .interact_text()?;
+
+    opt.spec_file_path = Input::with_theme(&theme)
+        .with_prompt("Spec file to create (empty for the default path)")
+        .default(opt.spec_file_path.clone())
+        .allow_empty(true)
+        .interact_text()?;
+
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Blue),
+        Print("\nSelected options:\n"),
+        ResetColor
+    )?;
+    writeln!(output.console(), "{}", serde_yaml::to_string(&opt).unwrap())?;
+
+    let options = &[
+        "No, I do not want to write the spec!",
+        "Yes, let's write the spec!",
+    ];
+    let selected = Select::with_theme(&theme)
+        .with_prompt("Select your 'default-spec' option")
+        .items(&options[..])
+        .default(1)
+        .interact()?;
+    if selected == 0 {
+        return Ok(None);
+    }
+    Ok(Some(opt))
+}
+
+/// Validates the flags (or the wizard answers) that "Spec::validate"
+/// cannot check once the spec is generated.
+fn validate_option(opt: &DefaultSpecOption) -> io::Result<()> {
+    let custom = !constants::NETWORK_NAME_TO_NETWORK_ID.contains_key(opt.network_name.as_str());
+    if let Some(network_id) = opt.network_id {
+        if !custom {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "--network-id is only for custom networks (got network name '{}')",
+                    opt.network_name
+                ),
+            ));
+        }
+        validate_custom_network_id(network_id)?;
+    }
+    if opt.anchor_nodes.is_some() && !custom {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "--anchor-nodes is only for custom networks (got network name '{}')",
+                opt.network_name
+            ),
+        ));
+    }
+    validate_keys_to_generate(opt.keys_to_generate, opt.enable_subnet_evm)?;
+    validate_subnet_ids(&opt.avalanchego_whitelisted_subnets)
+}
+
+fn validate_custom_network_id(network_id: u32) -> io::Result<()> {
+    if network_id == 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "network ID cannot be 0",
+        ));
+    }
+    if let Some(name) = constants::NETWORK_ID_TO_NETWORK_NAME.get(&network_id) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "network ID {} is reserved for '{}' (not a custom network)",
+                network_id, name
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_nodes(name: &str, nodes: u32, min: u32, max: u32) -> io::Result<()> {
+    if nodes < min || nodes > max {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} {} must be within [{}, {}]", name, nodes, min, max),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_keys_to_generate(keys: usize, enable_subnet_evm: bool) -> io::Result<()> {
+    // the first key is the one with the locked P-chain balance,
+    // and subnet-evm needs another one to create the subnet
+    let min = if enable_subnet_evm { 2 } else { 1 };
+    if keys < min {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("keys to generate {} must be >={}", keys, min),
+        ));
+    }
     Ok(())
 }
+
+fn validate_subnet_ids(subnet_ids: &str) -> io::Result<()> {
+    for subnet_id in subnet_ids
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+    {
+        ids::Id::from_str(subnet_id).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid subnet ID '{}' ({})", subnet_id, e),
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Parses the comma-separated EC2 instance types (e.g., "c6a.2xlarge,m6a.2xlarge").
+pub fn parse_instance_types(s: &str) -> io::Result<Vec<String>> {
+    let mut instance_types = Vec::new();
+    for instance_type in s.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+        avalanche_ops_aws::validate_instance_type(instance_type)?;
+        instance_types.push(instance_type.to_string());
+    }
+    if instance_types.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "instance types cannot be empty",
+        ));
+    }
+    Ok(instance_types)
+}
+
+/// Parses the comma-separated region and non-anchor nodes pairs
+/// (e.g., "us-east-1=2,eu-west-1=1").
+pub fn parse_regional_non_anchor_nodes(s: &str) -> io::Result<Vec<(String, u32)>> {
+    let mut regional_non_anchor_nodes = Vec::new();
+    for pair in s.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+        let (region, nodes) = pair.split_once('=').ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("'{}' is not in the form of '[region]=[nodes]'", pair),
+            )
+        })?;
+        avalanche_ops_aws::validate_region(region)?;
+        let nodes = nodes.parse::<u32>().map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "invalid non-anchor nodes '{}' for {} ({})",
+                    nodes, region, e
+                ),
+            )
+        })?;
+        regional_non_anchor_nodes.push((region.to_string(), nodes));
+    }
+    Ok(regional_non_anchor_nodes)
+}
@@ -1,6 +1,6 @@
 use std::{
     fs,
-    io::{self, stdout, Error, ErrorKind},
+    io::{self, Error, ErrorKind, Write},
     path::Path,
     sync::Arc,
     thread,
@@ -15,9 +15,14 @@ use crossterm::{
 };
 use dialoguer::{theme::ColorfulTheme, Select};
 use log::info;
+use serde::Serialize;
 use tokio::runtime::Runtime;
 
-use aws::{self, cloudformation, cloudwatch, ec2, kms, s3, sts};
+use avalanche_ops_aws::{
+    output,
+    provider::{self, Provider},
+};
+use aws::{self, cloudformation, cloudwatch, ec2, kms, route53, s3, sts};
 use utils::compress;
 
 pub const NAME: &str = "delete";
@@ -84,6 +89,17 @@ pub fn command() -> Command<'static> {
 // 50-minute
 const MAX_WAIT_SECONDS: u64 = 50 * 60;
 
+/// Describes the deleted resources, for the structured output.
+#[derive(Debug, Serialize)]
+struct Deleted {
+    id: String,
+    cloudwatch_log_group: bool,
+    s3_objects: bool,
+    s3_bucket: bool,
+    /// True if "retention_policy" kept any resources.
+    retained: bool,
+}
+
 pub fn execute(
     log_level: &str,
     spec_file_path: &str,
@@ -91,6 +107,7 @@ pub fn execute(
     delete_s3_objects: bool,
     delete_s3_bucket: bool,
     skip_prompt: bool,
+    output: output::Format,
 ) -> io::Result<()> {
     // ref. https://github.com/env-logger-rs/env_logger/issues/47
     env_logger::init_from_env(
@@ -100,6 +117,14 @@ pub fn execute(
     let spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
     let aws_resources = spec.aws_resources.clone().unwrap();
 
+    let retention_policy = spec.retention_policy.clone().unwrap_or_default();
+    retention_policy.check_delete_flags(
+        delete_cloudwatch_log_group,
+        delete_s3_objects,
+        delete_s3_bucket,
+    )?;
+    let retained_spec = spec.retained();
+
     let rt = Runtime::new().unwrap();
     let shared_config = rt
         .block_on(aws::load_config(Some(aws_resources.region.clone())))
@@ -128,13 +153,13 @@ pub fn execute(
     }
 
     execute!(
-        stdout(),
+        output.console(),
         SetForegroundColor(Color::Blue),
         Print(format!("\nLoaded configuration: '{}'\n", spec_file_path)),
         ResetColor
     )?;
     let spec_contents = spec.encode_yaml().unwrap();
-    println!("{}\n", spec_contents);
+    writeln!(output.console(), "{}\n", spec_contents)?;
 
     if !skip_prompt {
         let options = &[
@@ -152,8 +177,17 @@ pub fn execute(
         }
     }
 
+    // released when "delete" returns
+    let _state_lock = crate::apply::lock_state(&rt, &shared_config, &spec, NAME, output)?;
+
     info!("deleting resources...");
     let s3_manager = s3::Manager::new(&shared_config);
+    let provider = provider::AwsProvider::new(
+        &shared_config,
+        spec.aws_resources
+            .as_ref()
+            .expect("unexpected None aws_resources"),
+    );
     let kms_manager = kms::Manager::new(&shared_config);
     let ec2_manager = ec2::Manager::new(&shared_config);
     let cloudformation_manager = cloudformation::Manager::new(&shared_config);
@@ -161,10 +195,12 @@ pub fn execute(
 
     // delete this first since EC2 key delete does not depend on ASG/VPC
     // (mainly to speed up delete operation)
-    if aws_resources.ec2_key_name.is_some() && aws_resources.ec2_key_path.is_some() {
+    if retention_policy.ec2_key_pair {
+        info!("retaining EC2 key pair");
+    } else if aws_resources.ec2_key_name.is_some() && aws_resources.ec2_key_path.is_some() {
         thread::sleep(Duration::from_secs(2));
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Red),
             Print("\n\n\nSTEP: delete EC2 key pair\n"),
             ResetColor
@@ -189,10 +225,12 @@ pub fn execute(
 
     // delete this first since KMS key delete does not depend on ASG/VPC
     // (mainly to speed up delete operation)
-    if aws_resources.kms_cmk_id.is_some() && aws_resources.kms_cmk_arn.is_some() {
+    if retention_policy.kms_key {
+        info!("retaining KMS key {:?}", aws_resources.kms_cmk_id);
+    } else if aws_resources.kms_cmk_id.is_some() && aws_resources.kms_cmk_arn.is_some() {
         thread::sleep(Duration::from_secs(2));
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Red),
             Print("\n\n\nSTEP: delete KMS key\n"),
             ResetColor
@@ -210,7 +248,7 @@ pub fn execute(
     {
         thread::sleep(Duration::from_secs(2));
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Red),
             Print("\n\n\nSTEP: trigger delete EC2 instance role\n"),
             ResetColor
@@ -224,13 +262,30 @@ pub fn execute(
             .unwrap();
     }
 
+    if aws_resources
+        .cloudformation_asg_api_nodes_logical_id
+        .is_some()
+    {
+        thread::sleep(Duration::from_secs(2));
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Red),
+            Print("\n\n\nSTEP: triggering delete ASG for API nodes\n"),
+            ResetColor
+        )?;
+
+        let asg_api_nodes_stack_name = aws_resources.cloudformation_asg_api_nodes.clone().unwrap();
+        rt.block_on(cloudformation_manager.delete_stack(asg_api_nodes_stack_name.as_str()))
+            .unwrap();
+    }
+
     if aws_resources
         .cloudformation_asg_non_anchor_nodes_logical_id
         .is_some()
     {
         thread::sleep(Duration::from_secs(2));
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Red),
             Print("\n\n\nSTEP: triggering delete ASG for non-anchor nodes\n"),
             ResetColor
@@ -251,7 +306,7 @@ pub fn execute(
     {
         thread::sleep(Duration::from_secs(2));
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Red),
             Print("\n\n\nSTEP: triggering delete ASG for anchor nodes\n"),
             ResetColor
@@ -271,7 +326,7 @@ pub fn execute(
     {
         thread::sleep(Duration::from_secs(2));
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Red),
             Print("\n\n\nSTEP: confirming delete ASG for non-anchor nodes\n"),
             ResetColor
@@ -294,6 +349,35 @@ pub fn execute(
         .unwrap();
     }
 
+    if let Some(api_nodes) = &spec.machine.api_nodes {
+        if let Some(asg_api_nodes_stack_name) = &aws_resources.cloudformation_asg_api_nodes {
+            if aws_resources
+                .cloudformation_asg_api_nodes_logical_id
+                .is_some()
+            {
+                thread::sleep(Duration::from_secs(2));
+                execute!(
+                    output.console(),
+                    SetForegroundColor(Color::Red),
+                    Print("\n\n\nSTEP: confirming delete ASG for API nodes\n"),
+                    ResetColor
+                )?;
+
+                let mut wait_secs = 300 + 60 * api_nodes.nodes as u64;
+                if wait_secs > MAX_WAIT_SECONDS {
+                    wait_secs = MAX_WAIT_SECONDS;
+                }
+                rt.block_on(cloudformation_manager.poll_stack(
+                    asg_api_nodes_stack_name.as_str(),
+                    StackStatus::DeleteComplete,
+                    Duration::from_secs(wait_secs),
+                    Duration::from_secs(30),
+                ))
+                .unwrap();
+            }
+        }
+    }
+
     if spec.machine.anchor_nodes.unwrap_or(0) > 0
         && aws_resources
             .cloudformation_asg_anchor_nodes_logical_id
@@ -301,7 +385,7 @@ pub fn execute(
     {
         thread::sleep(Duration::from_secs(2));
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Red),
             Print("\n\n\nSTEP: confirming delete ASG for anchor nodes\n"),
             ResetColor
@@ -323,6 +407,160 @@ pub fn execute(
         .unwrap();
     }
 
+    // the target group is in use until the ASGs are deleted
+    if let Some(load_balancer_stack_name) = &aws_resources.cloudformation_load_balancer {
+        thread::sleep(Duration::from_secs(2));
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Red),
+            Print("\n\n\nSTEP: delete load balancer\n"),
+            ResetColor
+        )?;
+
+        rt.block_on(cloudformation_manager.delete_stack(load_balancer_stack_name.as_str()))
+            .unwrap();
+        rt.block_on(cloudformation_manager.poll_stack(
+            load_balancer_stack_name.as_str(),
+            StackStatus::DeleteComplete,
+            Duration::from_secs(600),
+            Duration::from_secs(30),
+        ))
+        .unwrap();
+    }
+
+    // nodes in the other regions than the primary one
+    for r in aws_resources.regional_resources.clone().unwrap_or_default() {
+        thread::sleep(Duration::from_secs(2));
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Red),
+            Print(format!(
+                "\n\n\nSTEP: delete resources in region {}\n",
+                r.region
+            )),
+            ResetColor
+        )?;
+        let regional_config = rt
+            .block_on(aws::load_config(Some(r.region.clone())))
+            .unwrap();
+        let regional_ec2_manager = ec2::Manager::new(&regional_config);
+        let regional_cloudformation_manager = cloudformation::Manager::new(&regional_config);
+        let regional_s3_manager = s3::Manager::new(&regional_config);
+
+        if retention_policy.ec2_key_pair {
+            info!("retaining EC2 key pair in region {}", r.region);
+        } else if let (Some(ec2_key_name), Some(ec2_key_path)) = (&r.ec2_key_name, &r.ec2_key_path)
+        {
+            if Path::new(ec2_key_path.as_str()).exists() {
+                fs::remove_file(ec2_key_path.as_str()).unwrap();
+            }
+            rt.block_on(regional_ec2_manager.delete_key_pair(ec2_key_name))
+                .unwrap();
+        }
+
+        if r.cloudformation_asg_non_anchor_nodes_logical_id.is_some() {
+            let stack_name = r.cloudformation_asg_non_anchor_nodes.clone().unwrap();
+            rt.block_on(regional_cloudformation_manager.delete_stack(stack_name.as_str()))
+                .unwrap();
+            let mut wait_secs = 300 + 60 * r.non_anchor_nodes as u64;
+            if wait_secs > MAX_WAIT_SECONDS {
+                wait_secs = MAX_WAIT_SECONDS;
+            }
+            rt.block_on(regional_cloudformation_manager.poll_stack(
+                stack_name.as_str(),
+                StackStatus::DeleteComplete,
+                Duration::from_secs(wait_secs),
+                Duration::from_secs(30),
+            ))
+            .unwrap();
+        }
+
+        if spec.machine.data_volume.is_some() {
+            delete_data_volumes(&rt, &regional_ec2_manager, &spec.id, &retention_policy);
+        }
+
+        if r.cloudformation_vpc_id.is_some() {
+            let vpc_stack_name = r.cloudformation_vpc.clone().unwrap();
+            rt.block_on(regional_cloudformation_manager.delete_stack(vpc_stack_name.as_str()))
+                .unwrap();
+            thread::sleep(Duration::from_secs(10));
+            rt.block_on(regional_cloudformation_manager.poll_stack(
+                vpc_stack_name.as_str(),
+                StackStatus::DeleteComplete,
+                Duration::from_secs(500),
+                Duration::from_secs(30),
+            ))
+            .unwrap();
+        }
+
+        // only holds the install artifacts
+        if let Some(regional_s3_bucket) = &r.s3_bucket {
+            if delete_s3_objects {
+                rt.block_on(regional_s3_manager.delete_objects(
+                    Arc::new(regional_s3_bucket.clone()),
+                    Some(Arc::new(spec.id.clone())),
+                ))
+                .unwrap();
+            }
+            if delete_s3_bucket {
+                rt.block_on(regional_s3_manager.delete_bucket(regional_s3_bucket))
+                    .unwrap();
+            }
+        }
+    }
+
+    // data volumes are detached once the instances are terminated
+    if spec.machine.data_volume.is_some() {
+        thread::sleep(Duration::from_secs(2));
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Red),
+            Print("\n\n\nSTEP: delete data volumes and snapshots\n"),
+            ResetColor
+        )?;
+
+        delete_data_volumes(&rt, &ec2_manager, &spec.id, &retention_policy);
+    }
+
+    // Elastic IPs are disassociated once the anchor instances are terminated
+    if let Some(anchor_endpoints) = &spec.machine.anchor_endpoints {
+        if retention_policy.elastic_ips {
+            info!("retaining Elastic IPs and DNS records of anchor nodes");
+        } else if anchor_endpoints.elastic_ips {
+            thread::sleep(Duration::from_secs(2));
+            execute!(
+                output.console(),
+                SetForegroundColor(Color::Red),
+                Print("\n\n\nSTEP: delete Elastic IPs and DNS records of anchor nodes\n"),
+                ResetColor
+            )?;
+
+            let route53_manager = route53::Manager::new(&shared_config);
+            let eips = rt
+                .block_on(
+                    ec2_manager.list_eips(&avalanche_ops_aws::AnchorEndpoints::tags(&spec.id)),
+                )
+                .unwrap();
+            for eip in eips.iter() {
+                if let (Some(hosted_zone_id), Some(name)) = (
+                    &anchor_endpoints.route53_hosted_zone_id,
+                    anchor_endpoints.dns_name(eip.anchor_index.unwrap_or_default()),
+                ) {
+                    rt.block_on(route53_manager.delete_a_record(
+                        hosted_zone_id,
+                        &name,
+                        &eip.public_ip,
+                        anchor_endpoints.dns_ttl_seconds,
+                    ))
+                    .unwrap();
+                }
+                rt.block_on(ec2_manager.release_eip(&eip.allocation_id))
+                    .unwrap();
+            }
+            info!("released {} Elastic IP(s)", eips.len());
+        }
+    }
+
     // VPC delete must run after associated EC2 instances are terminated due to dependencies
     if aws_resources.cloudformation_vpc_id.is_some()
         && aws_resources.cloudformation_vpc_security_group_id.is_some()
@@ -330,7 +568,7 @@ pub fn execute(
     {
         thread::sleep(Duration::from_secs(2));
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Red),
             Print("\n\n\nSTEP: delete VPC\n"),
             ResetColor
@@ -355,7 +593,7 @@ pub fn execute(
     {
         thread::sleep(Duration::from_secs(2));
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Red),
             Print("\n\n\nSTEP: confirming delete EC2 instance role\n"),
             ResetColor
@@ -375,7 +613,7 @@ pub fn execute(
         // deletes the one auto-created by nodes
         thread::sleep(Duration::from_secs(2));
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Red),
             Print("\n\n\nSTEP: cloudwatch log groups\n"),
             ResetColor
@@ -386,23 +624,19 @@ pub fn execute(
     if delete_s3_objects {
         thread::sleep(Duration::from_secs(1));
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Red),
             Print("\n\n\nSTEP: delete S3 objects\n"),
             ResetColor
         )?;
         thread::sleep(Duration::from_secs(5));
-        rt.block_on(s3_manager.delete_objects(
-            Arc::new(aws_resources.s3_bucket.clone()),
-            Some(Arc::new(spec.id)),
-        ))
-        .unwrap();
+        rt.block_on(provider.delete_objects(&spec.id)).unwrap();
     }
 
     if delete_s3_bucket {
         thread::sleep(Duration::from_secs(1));
         execute!(
-            stdout(),
+            output.console(),
             SetForegroundColor(Color::Red),
             Print("\n\n\nSTEP: delete S3 bucket\n"),
             ResetColor
@@ -428,7 +662,59 @@ pub fn execute(
         }
     }
 
-    println!();
+    let deleted = Deleted {
+        id: spec.id.clone(),
+        cloudwatch_log_group: delete_cloudwatch_log_group,
+        s3_objects: delete_s3_objects,
+        s3_bucket: delete_s3_bucket,
+        retained: retention_policy.retains_any(),
+    };
+    if retention_policy.retains_any() {
+        // only keeps the outputs of the retained resources,
+        // so the next "apply" recreates the rest
+        retained_spec.sync(spec_file_path)?;
+        writeln!(output.console())?;
+        info!(
+            "delete success, retained {:?} (run 'apply' with '{}' to recreate)",
+            retention_policy, spec_file_path
+        );
+        return output.print(&deleted);
+    }
+
+    writeln!(output.console())?;
     info!("delete all success!");
-    Ok(())
+    output.print(&deleted)
+}
+
+/// Deletes the detached data volumes and their snapshots,
+/// unless retained by the policy.
+fn delete_data_volumes(
+    rt: &Runtime,
+    ec2_manager: &ec2::Manager,
+    id: &str,
+    retention_policy: &avalanche_ops_aws::RetentionPolicy,
+) {
+    let tags = [("ID", id)];
+    if retention_policy.ebs_volumes {
+        info!("retaining data volumes");
+    } else {
+        let volumes = rt
+            .block_on(ec2_manager.list_available_volumes(None, &tags))
+            .unwrap();
+        for v in volumes.iter() {
+            rt.block_on(ec2_manager.delete_volume(&v.volume_id))
+                .unwrap();
+        }
+        info!("deleted {} data volume(s)", volumes.len());
+    }
+    if retention_policy.ebs_snapshots {
+        info!("retaining data volume snapshots");
+    } else {
+        let snapshots = rt.block_on(ec2_manager.list_snapshots(&tags)).unwrap();
+        for s in snapshots.iter() {
+            rt.block_on(ec2_manager.delete_snapshot(&s.snapshot_id))
+                .unwrap();
+        }
+        info!("deleted {} snapshot(s)", snapshots.len());
+    }
 }
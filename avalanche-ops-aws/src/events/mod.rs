@@ -1,4 +1,7 @@
+pub mod rolling_upgrade;
+pub mod rotate_cert;
 pub mod update_artifacts;
+pub mod update_config;
 
 use clap::Command;
 
@@ -8,4 +11,7 @@ pub fn command() -> Command<'static> {
     Command::new(NAME)
         .about("Events to trigger to the network")
         .subcommand(update_artifacts::subcommand())
+        .subcommand(rolling_upgrade::subcommand())
+        .subcommand(rotate_cert::subcommand())
+        .subcommand(update_config::subcommand())
 }
@@ -0,0 +1,545 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Error, ErrorKind, Write},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use clap::{Arg, Command};
+use crossterm::{
+    execute,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+};
+use dialoguer::{theme::ColorfulTheme, Select};
+use log::{info, warn};
+use serde::Serialize;
+use tokio::runtime::Runtime;
+
+use avalanche_api::p as api_p;
+use avalanche_ops_aws::{output, provider};
+
+use super::update_artifacts;
+
+pub const NAME: &str = "rolling-upgrade";
+
+/// Interval between the upgrade status and height polls.
+const POLL_INTERVAL_SECONDS: u64 = 30;
+
+pub fn subcommand() -> Command<'static> {
+    Command::new(NAME)
+        .about("Uploads new artifacts and upgrades the nodes in waves, gated on the node health and the P-chain height")
+        .arg(
+            Arg::new("LOG_LEVEL")
+                .long("log-level")
+                .short('l')
+                .help("Sets the log level")
+                .required(false)
+                .takes_value(true)
+                .possible_value("debug")
+                .possible_value("info")
+                .allow_invalid_utf8(false)
+                .default_value("info"),
+        )
+        .arg(
+            Arg::new("SPEC_FILE_PATH")
+                .long("spec-file-path")
+                .short('s')
+                .help("The spec file to load")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("INSTALL_ARTIFACTS_AVALANCHE_BIN")
+                .long("install-artifacts-avalanche-bin")
+                .help("Sets the Avalanche node binary path in the local machine to be shared with remote machines")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("INSTALL_ARTIFACTS_PLUGINS_DIR")
+                .long("install-artifacts-plugins-dir")
+                .help("Sets 'plugins' directory in the local machine to be shared with remote machines")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("SIGNING_KEY_PATH")
+                .long("signing-key-path")
+                .help("Sets the PKCS#8 DER-encoded ed25519 key file to sign the upgrade manifest (required if 'upgrade_policy.signing_public_key' is set)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("WAVE_BY")
+                .long("wave-by")
+                .help("Sets how to split the nodes into the waves (anchor nodes are always upgraded last)")
+                .required(false)
+                .takes_value(true)
+                .possible_value("availability-zone")
+                .possible_value("percent")
+                .allow_invalid_utf8(false)
+                .default_value("percent"),
+        )
+        .arg(
+            Arg::new("WAVE_PERCENT")
+                .long("wave-percent")
+                .help("Sets the percentage of the nodes per wave for '--wave-by percent'")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("25"),
+        )
+        .arg(
+            Arg::new("MAX_FAILURES")
+                .long("max-failures")
+                .help("Sets the number of the failed node upgrades to tolerate before aborting")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("WAVE_TIMEOUT_SECONDS")
+                .long("wave-timeout-seconds")
+                .help("Sets the time to wait for the nodes of each wave to report healthy")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("1800"),
+        )
+        .arg(
+            Arg::new("HEIGHT_TIMEOUT_SECONDS")
+                .long("height-timeout-seconds")
+                .help("Sets the time for each upgraded node to see the P-chain height advance after each wave (0 to skip, as the idle networks produce no blocks)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("SKIP_PROMPT")
+                .long("skip-prompt")
+                .help("Skips prompt mode")
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        )
+}
+
+/// Describes the completed rollout, for the structured output.
+#[derive(Debug, Serialize)]
+struct Upgraded {
+    id: String,
+    version: u64,
+    waves: Vec<Vec<String>>,
+    failed: usize,
+}
+
+/// Uploads the artifacts and the manifest gated by "UpgradeRollout", then
+/// lets one wave of the nodes upgrade at a time. Each wave must report
+/// "succeeded" and, with "height_timeout_seconds", each upgraded node must
+/// see the P-chain height advance before the next wave.
+/// Once the failed nodes exceed "max_failures", or the height stalls,
+/// the rollout is aborted and the upgraded nodes restore the previous binaries.
+#[allow(clippy::too_many_arguments)]
+pub fn execute(
+    log_level: &str,
+    spec_file_path: &str,
+    install_artifacts_avalanche_bin: &str,
+    install_artifacts_plugins_dir: &str,
+    signing_key_path: &str,
+    wave_by: &str,
+    wave_percent: &str,
+    max_failures: &str,
+    wave_timeout_seconds: &str,
+    height_timeout_seconds: &str,
+    skip_prompt: bool,
+    output: output::Format,
+) -> io::Result<()> {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    );
+
+    let parse = |name: &str, v: &str| {
+        v.parse::<u64>().map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid --{} '{}' ({})", name, v, e),
+            )
+        })
+    };
+    let wave_percent = parse("wave-percent", wave_percent)?;
+    if wave_percent == 0 || wave_percent > 100 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("--wave-percent {} out of range (1 ~ 100)", wave_percent),
+        ));
+    }
+    let max_failures = parse("max-failures", max_failures)? as usize;
+    let wave_timeout = Duration::from_secs(parse("wave-timeout-seconds", wave_timeout_seconds)?);
+    let height_timeout =
+        Duration::from_secs(parse("height-timeout-seconds", height_timeout_seconds)?);
+
+    let spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
+    spec.validate()?;
+    let signing_public_key = spec
+        .upgrade_policy
+        .clone()
+        .unwrap_or_default()
+        .signing_public_key;
+    if signing_public_key.is_some() && signing_key_path.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "'upgrade_policy.signing_public_key' requires '--signing-key-path'",
+        ));
+    }
+    let current_nodes = spec.current_nodes.clone().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            "no 'current_nodes' in the spec (run 'apply' first)",
+        )
+    })?;
+
+    let rt = Runtime::new().unwrap();
+    let provider = rt.block_on(provider::new_provider(&spec))?;
+
+    let (wave_by, zones) = if wave_by == "availability-zone" {
+        let machine_ids: Vec<String> = current_nodes.iter().map(|n| n.machine_id.clone()).collect();
+        let zones: BTreeMap<String, String> = rt
+            .block_on(provider.list_machines(&machine_ids))?
+            .into_iter()
+            .map(|m| (m.machine_id, m.zone))
+            .collect();
+        (avalanche_ops_aws::UpgradeWaveBy::AvailabilityZone, zones)
+    } else {
+        (
+            avalanche_ops_aws::UpgradeWaveBy::Percent(wave_percent as u32),
+            BTreeMap::new(),
+        )
+    };
+    let waves = avalanche_ops_aws::upgrade_waves(&current_nodes, &zones, &wave_by);
+
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Blue),
+        Print(format!(
+            "\nRolling upgrade of {} node(s) in {} wave(s) (max failures {}):\n",
+            current_nodes.len(),
+            waves.len(),
+            max_failures
+        )),
+        ResetColor
+    )?;
+    for (i, wave) in waves.iter().enumerate() {
+        writeln!(output.console(), "wave {}: {}", i + 1, wave.join(", "))?;
+    }
+    writeln!(output.console())?;
+
+    if !skip_prompt {
+        let options = &[
+            "No, I am not ready to upgrade the nodes!",
+            "Yes, let's upgrade the nodes!",
+        ];
+        let selected = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select your 'rolling-upgrade' option")
+            .items(&options[..])
+            .default(0)
+            .interact()
+            .unwrap();
+        if selected == 0 {
+            return Ok(());
+        }
+    }
+
+    let manifest = update_artifacts::upload_artifacts(
+        &rt,
+        provider.as_ref(),
+        &spec.id,
+        install_artifacts_avalanche_bin,
+        install_artifacts_plugins_dir,
+        signing_key_path,
+        signing_public_key.as_deref(),
+    )?;
+
+    // gate the manifest before uploading it, so no node starts on its own
+    let mut rollout = avalanche_ops_aws::UpgradeRollout {
+        version: manifest.version,
+        state: avalanche_ops_aws::UpgradeRolloutState::InProgress,
+        machine_ids: Default::default(),
+        updated_at: unix_now(),
+    };
+    put_rollout(&rt, provider.as_ref(), &spec.id, &rollout)?;
+    update_artifacts::put_manifest(&rt, provider.as_ref(), &spec.id, &manifest)?;
+
+    let nodes: BTreeMap<String, avalanche_ops_aws::Node> = current_nodes
+        .iter()
+        .map(|n| (n.machine_id.clone(), n.clone()))
+        .collect();
+    let mut failed = 0;
+    for (i, wave) in waves.iter().enumerate() {
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Green),
+            Print(format!(
+                "\n\n\nSTEP: upgrading wave {}/{} ({} node(s))\n",
+                i + 1,
+                waves.len(),
+                wave.len()
+            )),
+            ResetColor
+        )?;
+        let wave_nodes: Vec<&avalanche_ops_aws::Node> =
+            wave.iter().filter_map(|id| nodes.get(id)).collect();
+        let endpoints: Vec<String> = current_nodes
+            .iter()
+            .map(|n| n.http_endpoint.clone())
+            .collect();
+        let height_before = rt.block_on(fetch_max_p_height(&endpoints));
+
+        rollout.machine_ids.extend(wave.iter().cloned());
+        rollout.updated_at = unix_now();
+        put_rollout(&rt, provider.as_ref(), &spec.id, &rollout)?;
+
+        let results = wait_for_wave(
+            &rt,
+            provider.as_ref(),
+            &spec.id,
+            manifest.version,
+            &wave_nodes,
+            &[],
+            wave_timeout,
+        )?;
+        let upgraded: Vec<String> = wave_nodes
+            .iter()
+            .filter(|n| {
+                results.get(&n.machine_id) == Some(&avalanche_ops_aws::UpgradeState::Succeeded)
+            })
+            .map(|n| n.http_endpoint.clone())
+            .collect();
+        failed += wave_nodes.len() - upgraded.len();
+        let mut abort_reason = None;
+        if failed > max_failures {
+            abort_reason = Some(format!(
+                "{} failed node upgrade(s) exceed --max-failures {}",
+                failed, max_failures
+            ));
+        } else if !height_timeout.is_zero() {
+            match height_before {
+                Some(_) if upgraded.is_empty() => {
+                    abort_reason =
+                        Some(String::from("no upgraded node to check the P-chain height"));
+                }
+                Some(before) => {
+                    let stalled = wait_for_height(&rt, &upgraded, before, height_timeout);
+                    if !stalled.is_empty() {
+                        abort_reason = Some(format!(
+                            "P-chain height did not advance from {} within {:?} on {:?}",
+                            before, height_timeout, stalled
+                        ));
+                    }
+                }
+                None => warn!(
+                    "no P-chain height before wave {}, skipping the height check",
+                    i + 1
+                ),
+            }
+        }
+
+        if let Some(reason) = abort_reason {
+            warn!("aborting the rolling upgrade ({})", reason);
+            rollout.state = avalanche_ops_aws::UpgradeRolloutState::Aborted;
+            rollout.updated_at = unix_now();
+            put_rollout(&rt, provider.as_ref(), &spec.id, &rollout)?;
+
+            // only the nodes that passed the health check kept the previous binaries
+            let rolling_back: Vec<&avalanche_ops_aws::Node> = rollout
+                .machine_ids
+                .iter()
+                .filter_map(|id| nodes.get(id))
+                .collect();
+            wait_for_wave(
+                &rt,
+                provider.as_ref(),
+                &spec.id,
+                manifest.version,
+                &rolling_back,
+                &[avalanche_ops_aws::UpgradeState::Succeeded],
+                wave_timeout,
+            )?;
+            return Err(Error::new(
+                ErrorKind::Other,
+                format!("rolling upgrade aborted ({})", reason),
+            ));
+        }
+        info!("wave {}/{} upgraded", i + 1, waves.len());
+    }
+
+    rollout.state = avalanche_ops_aws::UpgradeRolloutState::Completed;
+    rollout.updated_at = unix_now();
+    put_rollout(&rt, provider.as_ref(), &spec.id, &rollout)?;
+
+    writeln!(output.console())?;
+    info!(
+        "rolling-upgrade all success! ({} failed node upgrade(s))",
+        failed
+    );
+    writeln!(output.console())?;
+
+    output.print(&Upgraded {
+        id: spec.id.clone(),
+        version: manifest.version,
+        waves,
+        failed,
+    })
+}
+
+fn put_rollout(
+    rt: &Runtime,
+    provider: &dyn provider::Provider,
+    id: &str,
+    rollout: &avalanche_ops_aws::UpgradeRollout,
+) -> io::Result<()> {
+    let s3_key =
+        avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsRollout(id.to_string()).encode();
+    rt.block_on(provider.put_object(&s3_key, rollout.encode_yaml()?.as_bytes()))?;
+    info!(
+        "uploaded rollout version {} ({:?}, {} node(s))",
+        rollout.version,
+        rollout.state,
+        rollout.machine_ids.len()
+    );
+    Ok(())
+}
+
+/// Polls the upgrade status of each node until every node reports the
+/// manifest version in a state other than "pending", or the timeout elapses.
+/// Returns the reported state of each machine ID, and the nodes without
+/// one timed out.
+#[allow(clippy::too_many_arguments)]
+fn wait_for_wave(
+    rt: &Runtime,
+    provider: &dyn provider::Provider,
+    id: &str,
+    version: u64,
+    nodes: &[&avalanche_ops_aws::Node],
+    pending: &[avalanche_ops_aws::UpgradeState],
+    timeout: Duration,
+) -> io::Result<BTreeMap<String, avalanche_ops_aws::UpgradeState>> {
+    let started = Instant::now();
+    let mut results = BTreeMap::new();
+    loop {
+        for node in nodes.iter() {
+            let s3_key = avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsStatus(
+                id.to_string(),
+                node.node_id.clone(),
+            )
+            .encode();
+            let status = match rt.block_on(provider.get_object(&s3_key))? {
+                Some(d) => avalanche_ops_aws::UpgradeStatus::decode_yaml(&d)?,
+                None => continue,
+            };
+            if status.version != version || pending.contains(&status.state) {
+                continue;
+            }
+            if results.get(&node.machine_id) != Some(&status.state) {
+                if status.state == avalanche_ops_aws::UpgradeState::Succeeded {
+                    info!("{} ({}) succeeded", node.machine_id, node.node_id);
+                } else {
+                    warn!(
+                        "{} ({}) {} ({})",
+                        node.machine_id,
+                        node.node_id,
+                        status.state.as_str(),
+                        status.message.clone().unwrap_or_default()
+                    );
+                }
+                results.insert(node.machine_id.clone(), status.state);
+            }
+        }
+        if results.len() == nodes.len() {
+            return Ok(results);
+        }
+        if started.elapsed() >= timeout {
+            warn!(
+                "{} node(s) did not report within {:?}",
+                nodes.len() - results.len(),
+                timeout
+            );
+            return Ok(results);
+        }
+        info!(
+            "{}/{} node(s) reported, waiting...",
+            results.len(),
+            nodes.len()
+        );
+        thread::sleep(Duration::from_secs(POLL_INTERVAL_SECONDS));
+    }
+}
+
+/// Returns the P-chain height of the node, or "None" if not reachable.
+async fn fetch_p_height(endpoint: &str) -> Option<u64> {
+    match api_p::get_height(endpoint).await {
+        Ok(resp) => resp.result.and_then(|r| r.height),
+        Err(e) => {
+            warn!("failed get_height from {} ({})", endpoint, e);
+            None
+        }
+    }
+}
+
+/// Returns the highest P-chain height among the endpoints,
+/// or "None" if none is reachable.
+async fn fetch_max_p_height(endpoints: &[String]) -> Option<u64> {
+    let mut height: Option<u64> = None;
+    for ep in endpoints.iter() {
+        if let Some(h) = fetch_p_height(ep).await {
+            height = Some(height.map_or(h, |v| v.max(h)));
+        }
+    }
+    height
+}
+
+/// Waits for each upgraded node to see the P-chain height above "before",
+/// so that one stalled node is not hidden by the others.
+/// Returns the endpoints that did not advance within the timeout.
+fn wait_for_height(
+    rt: &Runtime,
+    endpoints: &[String],
+    before: u64,
+    timeout: Duration,
+) -> Vec<String> {
+    let started = Instant::now();
+    let mut stalled = endpoints.to_vec();
+    loop {
+        stalled.retain(|ep| match rt.block_on(fetch_p_height(ep)) {
+            Some(h) if h > before => {
+                info!(
+                    "{} advanced the P-chain height from {} to {}",
+                    ep, before, h
+                );
+                false
+            }
+            Some(h) => {
+                info!("{} at P-chain height {}, not advanced yet", ep, h);
+                true
+            }
+            None => true,
+        });
+        if stalled.is_empty() || started.elapsed() >= timeout {
+            return stalled;
+        }
+        thread::sleep(Duration::from_secs(POLL_INTERVAL_SECONDS));
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("unexpected None duration_since")
+        .as_secs()
+}
@@ -0,0 +1,211 @@
+use std::{
+    io::{self, Error, ErrorKind, Write},
+    time::SystemTime,
+};
+
+use clap::{Arg, Command};
+use crossterm::{
+    execute,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+};
+use dialoguer::{theme::ColorfulTheme, Select};
+use log::{info, warn};
+use serde::Serialize;
+use tokio::runtime::Runtime;
+
+use avalanche_ops_aws::{output, provider};
+
+pub const NAME: &str = "rotate-cert";
+
+pub fn subcommand() -> Command<'static> {
+    Command::new(NAME)
+        .about("Triggers the staking certificate rotation of all nodes, and syncs the rotated node IDs to the spec file")
+        .arg(
+            Arg::new("LOG_LEVEL")
+                .long("log-level")
+                .short('l')
+                .help("Sets the log level")
+                .required(false)
+                .takes_value(true)
+                .possible_value("debug")
+                .possible_value("info")
+                .allow_invalid_utf8(false)
+                .default_value("info"),
+        )
+        .arg(
+            Arg::new("SPEC_FILE_PATH")
+                .long("spec-file-path")
+                .short('s')
+                .help("The spec file to load and update")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("REREGISTER_VALIDATOR")
+                .long("reregister-validator")
+                .help("Registers the new node IDs as validators with 'validator_registration' of the spec")
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("SYNC_ONLY")
+                .long("sync-only")
+                .help("Only syncs the node IDs from the rotation results without triggering a new rotation (e.g., after the scheduled rotations)")
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("SKIP_PROMPT")
+                .long("skip-prompt")
+                .help("Skips prompt mode")
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        )
+}
+
+/// Uploads the rotation event that every "avalanched" applies once its
+/// validation ends, then updates "current_nodes" of the spec with the node IDs
+/// rotated so far. The rotation may be scheduled for weeks, so this does not
+/// wait, and re-running with "--sync-only" picks up the later results.
+/// Describes the rotated node IDs, for the structured output.
+#[derive(Debug, Serialize)]
+struct Rotated {
+    id: String,
+    rotated: usize,
+    nodes: Vec<avalanche_ops_aws::Node>,
+}
+
+pub fn execute(
+    log_level: &str,
+    spec_file_path: &str,
+    reregister_validator: bool,
+    sync_only: bool,
+    skip_prompt: bool,
+    output: output::Format,
+) -> io::Result<()> {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    );
+
+    let mut spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
+    spec.validate()?;
+    if reregister_validator && spec.validator_registration.is_none() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "'--reregister-validator' requires 'validator_registration' in the spec",
+        ));
+    }
+    let current_nodes = spec.current_nodes.clone().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            "no 'current_nodes' in the spec (run 'apply' first)",
+        )
+    })?;
+
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Blue),
+        Print(format!("\nLoaded Spec: '{}'\n", spec_file_path)),
+        ResetColor
+    )?;
+    let spec_contents = spec.encode_yaml()?;
+    writeln!(output.console(), "{}\n", spec_contents)?;
+
+    if !sync_only && !skip_prompt {
+        let options = &[
+            "No, I am not ready to rotate the staking certificates!",
+            "Yes, let's rotate the staking certificates!",
+        ];
+        let selected = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select your 'rotate-cert' option")
+            .items(&options[..])
+            .default(0)
+            .interact()
+            .unwrap();
+        if selected == 0 {
+            return Ok(());
+        }
+    }
+
+    let rt = Runtime::new().unwrap();
+    let provider = rt.block_on(provider::new_provider(&spec))?;
+
+    if !sync_only {
+        let event = avalanche_ops_aws::RotateCertEvent {
+            version: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("unexpected None duration_since")
+                .as_secs(),
+            reregister_validator,
+        };
+        rt.block_on(provider.put_object(
+            &avalanche_ops_aws::StorageNamespace::EventsRotateCert(spec.id.clone()).encode(),
+            event.encode_yaml()?.as_bytes(),
+        ))?;
+        info!(
+            "uploaded rotate-cert event version {} for {} node(s)",
+            event.version,
+            current_nodes.len()
+        );
+    }
+
+    let mut nodes = current_nodes;
+    let mut rotated = 0;
+    for node in nodes.iter_mut() {
+        let s3_key = avalanche_ops_aws::StorageNamespace::EventsRotateCertStatus(
+            spec.id.clone(),
+            node.machine_id.clone(),
+        )
+        .encode();
+        let status = match rt.block_on(provider.get_object(&s3_key))? {
+            Some(d) => avalanche_ops_aws::RotateCertStatus::decode_yaml(&d)?,
+            None => continue,
+        };
+        match (&status.state, &status.new_node_id) {
+            (avalanche_ops_aws::RotateCertState::Rotated, Some(new_node_id))
+                if status.old_node_id == node.node_id =>
+            {
+                info!(
+                    "{} rotated node ID {} to {}",
+                    node.machine_id, node.node_id, new_node_id
+                );
+                node.node_id = new_node_id.clone();
+                rotated += 1;
+            }
+            (avalanche_ops_aws::RotateCertState::Scheduled, _) => info!(
+                "{} ({}) rotates after {}",
+                node.machine_id,
+                node.node_id,
+                status.scheduled_after.unwrap_or_default()
+            ),
+            (avalanche_ops_aws::RotateCertState::Failed, _) => warn!(
+                "{} ({}) failed to rotate ({})",
+                node.machine_id,
+                node.node_id,
+                status.message.unwrap_or_default()
+            ),
+            _ => {}
+        }
+    }
+    if rotated > 0 {
+        spec.current_nodes = Some(nodes.clone());
+        spec.sync(spec_file_path)?;
+        crate::apply::put_spec(&rt, provider.as_ref(), &spec)?;
+        info!("synced {} rotated node ID(s) to the spec", rotated);
+    }
+
+    writeln!(output.console())?;
+    info!("rotate-cert all success!");
+    writeln!(output.console())?;
+
+    output.print(&Rotated {
+        id: spec.id.clone(),
+        rotated,
+        nodes,
+    })
+}
@@ -1,7 +1,8 @@
 use std::{
+    collections::BTreeMap,
     fs,
-    io::{self, stdout},
-    sync::Arc,
+    io::{self, Error, ErrorKind, Write},
+    time::SystemTime,
 };
 
 use clap::{Arg, Command};
@@ -13,8 +14,7 @@ use dialoguer::{theme::ColorfulTheme, Select};
 use log::info;
 use tokio::runtime::Runtime;
 
-use avalanche_ops_aws;
-use aws::{self, s3};
+use avalanche_ops_aws::{self, output, provider};
 use utils::{compress, random};
 
 pub const NAME: &str = "update-artifacts";
@@ -59,6 +59,14 @@ pub fn subcommand() -> Command<'static> {
                 .takes_value(true)
                 .allow_invalid_utf8(false),
         )
+        .arg(
+            Arg::new("SIGNING_KEY_PATH")
+                .long("signing-key-path")
+                .help("Sets the PKCS#8 DER-encoded ed25519 key file to sign the upgrade manifest (required if 'upgrade_policy.signing_public_key' is set)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
         .arg(
             Arg::new("SKIP_PROMPT")
                 .long("skip-prompt")
@@ -75,7 +83,9 @@ pub fn execute(
     spec_file_path: &str,
     install_artifacts_avalanche_bin: &str,
     install_artifacts_plugins_dir: &str,
+    signing_key_path: &str,
     skip_prompt: bool,
+    output: output::Format,
 ) -> io::Result<()> {
     // ref. https://github.com/env-logger-rs/env_logger/issues/47
     env_logger::init_from_env(
@@ -84,15 +94,26 @@ pub fn execute(
 
     let spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
     spec.validate()?;
+    let signing_public_key = spec
+        .upgrade_policy
+        .clone()
+        .unwrap_or_default()
+        .signing_public_key;
+    if signing_public_key.is_some() && signing_key_path.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "'upgrade_policy.signing_public_key' requires '--signing-key-path'",
+        ));
+    }
 
     execute!(
-        stdout(),
+        output.console(),
         SetForegroundColor(Color::Blue),
         Print(format!("\nLoaded Spec: '{}'\n", spec_file_path)),
         ResetColor
     )?;
     let spec_contents = spec.encode_yaml()?;
-    println!("{}\n", spec_contents);
+    writeln!(output.console(), "{}\n", spec_contents)?;
 
     if !skip_prompt {
         let options = &[
@@ -111,11 +132,50 @@ pub fn execute(
     }
 
     let rt = Runtime::new().unwrap();
-    let aws_resources = spec.aws_resources.expect("unexpected None aws_resources");
-    let shared_config = rt
-        .block_on(aws::load_config(Some(aws_resources.region.clone())))
-        .expect("failed to aws::load_config");
-    let s3_manager = s3::Manager::new(&shared_config);
+    let provider = rt.block_on(provider::new_provider(&spec))?;
+
+    let manifest = upload_artifacts(
+        &rt,
+        provider.as_ref(),
+        &spec.id,
+        install_artifacts_avalanche_bin,
+        install_artifacts_plugins_dir,
+        signing_key_path,
+        signing_public_key.as_deref(),
+    )?;
+
+    // upload the manifest last, since "avalanched" starts the upgrade once it changes
+    put_manifest(&rt, provider.as_ref(), &spec.id, &manifest)?;
+
+    writeln!(output.console())?;
+    info!("update-artifacts all success!");
+    writeln!(output.console())?;
+
+    output.print(&manifest)
+}
+
+/// Uploads the compressed binaries to "EventsUpdateArtifactsInstallDir",
+/// and returns the (optionally signed) manifest to upload last.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn upload_artifacts(
+    rt: &Runtime,
+    provider: &dyn provider::Provider,
+    id: &str,
+    install_artifacts_avalanche_bin: &str,
+    install_artifacts_plugins_dir: &str,
+    signing_key_path: &str,
+    signing_public_key: Option<&str>,
+) -> io::Result<avalanche_ops_aws::UpgradeManifest> {
+    let version = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("unexpected None duration_since")
+        .as_secs();
+    let mut manifest = avalanche_ops_aws::UpgradeManifest {
+        version,
+        avalanche_bin_sha256: avalanche_ops_aws::sha256_file_hex(install_artifacts_avalanche_bin)?,
+        plugins_sha256: BTreeMap::new(),
+        signature: None,
+    };
 
     // compress as these will be decompressed by "avalanched"
     let tmp_avalanche_bin_compressed_path =
@@ -126,12 +186,11 @@ pub fn execute(
         compress::Encoder::Zstd(3),
     )
     .expect("failed pack_file install_artifacts_avalanche_bin");
-    rt.block_on(s3_manager.put_object(
-        Arc::new(tmp_avalanche_bin_compressed_path.clone()),
-        Arc::new(aws_resources.s3_bucket.clone()),
-        Arc::new(avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsInstallDirAvalancheBinCompressed(spec.id.clone()).encode()),
+    rt.block_on(provider.put_file(
+        &tmp_avalanche_bin_compressed_path,
+        &avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsInstallDirAvalancheBinCompressed(id.to_string()).encode(),
     ))
-    .expect("failed put_object compressed install_artifacts_avalanche_bin");
+    .expect("failed put_file compressed install_artifacts_avalanche_bin");
     fs::remove_file(tmp_avalanche_bin_compressed_path)?;
     if !install_artifacts_plugins_dir.is_empty() {
         for entry in fs::read_dir(&install_artifacts_plugins_dir).unwrap() {
@@ -141,6 +200,10 @@ pub fn execute(
             let file_path = entry_path.to_str().unwrap();
             let file_name = entry.file_name();
             let file_name = file_name.as_os_str().to_str().unwrap();
+            manifest.plugins_sha256.insert(
+                file_name.to_string(),
+                avalanche_ops_aws::sha256_file_hex(file_path)?,
+            );
 
             let tmp_plugin_compressed_path =
                 random::tmp_path(15, Some(compress::Encoder::Zstd(3).ext())).unwrap();
@@ -156,31 +219,52 @@ pub fn execute(
                 tmp_plugin_compressed_path, file_path, install_artifacts_plugins_dir,
             );
             rt.block_on(
-                s3_manager.put_object(
-                    Arc::new(tmp_plugin_compressed_path.clone()),
-                        Arc::new(aws_resources.s3_bucket.clone()),
-                    Arc::new(format!(
+                provider.put_file(
+                    &tmp_plugin_compressed_path,
+                    &format!(
                         "{}/{}{}",
-                        &avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsInstallDirPluginsDir(spec.id.clone()).encode(),
+                        &avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsInstallDirPluginsDir(id.to_string()).encode(),
                         file_name,
                         compress::Encoder::Zstd(3).ext()
-                    )),
+                    ),
                 ),
             )
-            .expect("failed put_object tmp_plugin_compressed_path");
+            .expect("failed put_file tmp_plugin_compressed_path");
             fs::remove_file(tmp_plugin_compressed_path)?;
         }
     }
-    rt.block_on(s3_manager.put_object(
-        Arc::new(spec_file_path.to_string()),
-        Arc::new(aws_resources.s3_bucket),
-        Arc::new(avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsEvent(spec.id).encode()),
-    ))
-    .expect("failed put_object EventsUpdateArtifactsEvent");
+    manifest.validate()?;
+    if !signing_key_path.is_empty() {
+        let pkcs8_der = fs::read(signing_key_path)?;
+        let public_key = manifest.sign(&pkcs8_der)?;
+        if let Some(expected) = signing_public_key {
+            if !expected.eq_ignore_ascii_case(&public_key) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "signing key public key {} does not match 'upgrade_policy.signing_public_key' {}",
+                        public_key, expected
+                    ),
+                ));
+            }
+        }
+        info!("signed upgrade manifest with public key {}", public_key);
+    }
 
-    println!();
-    info!("update-artifacts all success!");
-    println!();
+    Ok(manifest)
+}
 
+/// Uploads the manifest, which triggers the upgrade in "avalanched".
+pub(super) fn put_manifest(
+    rt: &Runtime,
+    provider: &dyn provider::Provider,
+    id: &str,
+    manifest: &avalanche_ops_aws::UpgradeManifest,
+) -> io::Result<()> {
+    let s3_key =
+        avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsManifest(id.to_string()).encode();
+    rt.block_on(provider.put_object(&s3_key, manifest.encode_yaml()?.as_bytes()))
+        .expect("failed put_object EventsUpdateArtifactsManifest");
+    info!("uploaded upgrade manifest version {}", manifest.version);
     Ok(())
 }
@@ -0,0 +1,288 @@
+use std::{
+    io::{self, Error, ErrorKind, Write},
+    time::SystemTime,
+};
+
+use clap::{Arg, Command};
+use crossterm::{
+    execute,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+};
+use dialoguer::{theme::ColorfulTheme, Select};
+use log::{info, warn};
+use serde::Serialize;
+use tokio::runtime::Runtime;
+
+use avalanche_ops_aws::{output, provider};
+
+pub const NAME: &str = "update-config";
+
+/// Flags of the boolean avalanchego config fields that "ConfigDelta" updates.
+const BOOL_FLAGS: [(&str, &str); 5] = [
+    ("INDEX_ENABLED", "index-enabled"),
+    ("API_ADMIN_ENABLED", "api-admin-enabled"),
+    ("API_KEYSTORE_ENABLED", "api-keystore-enabled"),
+    ("API_METRICS_ENABLED", "api-metrics-enabled"),
+    ("API_HEALTH_ENABLED", "api-health-enabled"),
+];
+
+pub fn subcommand() -> Command<'static> {
+    let mut cmd = Command::new(NAME)
+        .about("Updates the avalanchego config of all nodes with the rolling restarts, without recreating the nodes")
+        .arg(
+            Arg::new("LOG_LEVEL")
+                .long("log-level")
+                .short('l')
+                .help("Sets the log level")
+                .required(false)
+                .takes_value(true)
+                .possible_value("debug")
+                .possible_value("info")
+                .allow_invalid_utf8(false)
+                .default_value("info"),
+        )
+        .arg(
+            Arg::new("SPEC_FILE_PATH")
+                .long("spec-file-path")
+                .short('s')
+                .help("The spec file to load and update")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("AVALANCHEGO_LOG_LEVEL")
+                .long("avalanchego-log-level")
+                .help("Sets the avalanchego log level (e.g., DEBUG)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("TRACK_SUBNETS")
+                .long("track-subnets")
+                .help("Replaces the tracked subnet IDs (comma-separated, empty to track none)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("STATUS_ONLY")
+                .long("status-only")
+                .help("Only prints the update results of the nodes without triggering a new update")
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("SKIP_PROMPT")
+                .long("skip-prompt")
+                .help("Skips prompt mode")
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        );
+    for (name, long) in BOOL_FLAGS {
+        cmd = cmd.arg(
+            Arg::new(name)
+                .long(long)
+                .help("Sets the avalanchego flag of the same name")
+                .required(false)
+                .takes_value(true)
+                .possible_value("true")
+                .possible_value("false")
+                .allow_invalid_utf8(false),
+        );
+    }
+    cmd
+}
+
+/// Builds the config delta from the flags of the subcommand.
+pub fn parse_delta(matches: &clap::ArgMatches) -> avalanche_ops_aws::ConfigDelta {
+    let flag = |name: &str| matches.value_of(name).map(|v| v == "true");
+    avalanche_ops_aws::ConfigDelta {
+        log_level: matches
+            .value_of("AVALANCHEGO_LOG_LEVEL")
+            .map(|v| v.to_uppercase()),
+        index_enabled: flag(BOOL_FLAGS[0].0),
+        api_admin_enabled: flag(BOOL_FLAGS[1].0),
+        api_keystore_enabled: flag(BOOL_FLAGS[2].0),
+        api_metrics_enabled: flag(BOOL_FLAGS[3].0),
+        api_health_enabled: flag(BOOL_FLAGS[4].0),
+        track_subnets: matches.value_of("TRACK_SUBNETS").map(|v| {
+            v.split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect()
+        }),
+    }
+}
+
+/// Describes the rollout progress of "--status-only", for the structured output.
+#[derive(Debug, Serialize)]
+struct Progress {
+    id: String,
+    version: u64,
+    updated: usize,
+    nodes: usize,
+}
+
+/// Applies the delta to "avalanchego_config" of the spec for the new nodes,
+/// and uploads the config update event that every "avalanched" applies with
+/// at most "config_update_policy.max_unavailable" nodes restarting at a time.
+/// The rollout may take a while in a large fleet, so this does not wait,
+/// and re-running with "--status-only" prints the later results.
+pub fn execute(
+    log_level: &str,
+    spec_file_path: &str,
+    delta: avalanche_ops_aws::ConfigDelta,
+    status_only: bool,
+    skip_prompt: bool,
+    output: output::Format,
+) -> io::Result<()> {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    );
+
+    let mut spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
+    spec.validate()?;
+    if !status_only {
+        delta.validate()?;
+    }
+    let current_nodes = spec.current_nodes.clone().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            "no 'current_nodes' in the spec (run 'apply' first)",
+        )
+    })?;
+    let policy = spec.config_update_policy.clone().unwrap_or_default();
+
+    if !status_only {
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Blue),
+            Print(format!(
+                "\nConfig update for {} node(s) (max unavailable {}):\n",
+                current_nodes.len(),
+                policy.max_unavailable
+            )),
+            ResetColor
+        )?;
+        writeln!(
+            output.console(),
+            "{}\n",
+            serde_yaml::to_string(&delta).unwrap()
+        )?;
+
+        if !skip_prompt {
+            let options = &[
+                "No, I am not ready to update the config!",
+                "Yes, let's update the config!",
+            ];
+            let selected = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt("Select your 'update-config' option")
+                .items(&options[..])
+                .default(0)
+                .interact()
+                .unwrap();
+            if selected == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    let rt = Runtime::new().unwrap();
+    let provider = rt.block_on(provider::new_provider(&spec))?;
+
+    if !status_only {
+        // the nodes launched after this (e.g., ASG replacements) boot with the delta
+        if delta.apply(&mut spec.avalanchego_config) {
+            spec.avalanchego_config.validate()?;
+            spec.sync(spec_file_path)?;
+            crate::apply::put_spec(&rt, provider.as_ref(), &spec)?;
+            info!("synced the config delta to the spec");
+        }
+
+        let event = avalanche_ops_aws::ConfigUpdateEvent {
+            version: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("unexpected None duration_since")
+                .as_secs(),
+            delta,
+            policy,
+        };
+        rt.block_on(provider.put_object(
+            &avalanche_ops_aws::StorageNamespace::EventsConfigUpdate(spec.id.clone()).encode(),
+            event.encode_yaml()?.as_bytes(),
+        ))?;
+        info!(
+            "uploaded config-update event version {} for {} node(s)",
+            event.version,
+            current_nodes.len()
+        );
+        return output.print(&event);
+    }
+
+    let event_s3_key =
+        avalanche_ops_aws::StorageNamespace::EventsConfigUpdate(spec.id.clone()).encode();
+    let event = match rt.block_on(provider.get_object(&event_s3_key))? {
+        Some(d) => avalanche_ops_aws::ConfigUpdateEvent::decode_yaml(&d)?,
+        None => {
+            info!("no config-update event found");
+            return Ok(());
+        }
+    };
+
+    let mut updated = 0;
+    for node in current_nodes.iter() {
+        let s3_key = avalanche_ops_aws::StorageNamespace::EventsConfigUpdateStatus(
+            spec.id.clone(),
+            node.machine_id.clone(),
+        )
+        .encode();
+        let status = match rt.block_on(provider.get_object(&s3_key))? {
+            Some(d) => Some(avalanche_ops_aws::ConfigUpdateStatus::decode_yaml(&d)?),
+            None => None,
+        };
+        let status = match status {
+            Some(v) if v.version == event.version => v,
+            _ => {
+                info!("{} ({}) not started", node.machine_id, node.node_id);
+                continue;
+            }
+        };
+        match status.state {
+            avalanche_ops_aws::ConfigUpdateState::Updated => updated += 1,
+            avalanche_ops_aws::ConfigUpdateState::Failed => warn!(
+                "{} ({}) failed ({})",
+                node.machine_id,
+                node.node_id,
+                status.message.unwrap_or_default()
+            ),
+            _ => info!(
+                "{} ({}) {}",
+                node.machine_id,
+                node.node_id,
+                status.state.as_str()
+            ),
+        }
+    }
+
+    writeln!(output.console())?;
+    info!(
+        "update-config version {} updated {}/{} node(s)",
+        event.version,
+        updated,
+        current_nodes.len()
+    );
+    writeln!(output.console())?;
+
+    output.print(&Progress {
+        id: spec.id.clone(),
+        version: event.version,
+        updated,
+        nodes: current_nodes.len(),
+    })
+}
@@ -0,0 +1,161 @@
+use std::io::{self, Error, ErrorKind};
+
+use avalanche_types::api::admin as api_admin;
+use avalanchego::config as avalanchego_config;
+
+use crate::{Spec, StorageNamespace};
+
+/// Valid log levels of "admin.setLoggerLevel".
+pub const FLEET_COMMAND_LOG_LEVELS: [&str; 8] = api_admin::LOG_LEVELS;
+
+/// Predefined command that "run-command" fans out to the nodes
+/// via SSM Run Command, so that no SSH access is required.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FleetCommand {
+    /// Restarts the avalanchego systemd service.
+    RestartNode,
+    /// Sets the log level via the admin API.
+    /// Reverts to "avalanchego_config.log_level" on the next restart.
+    SetLogLevel(String),
+    /// Writes the memory and lock profiles via the admin API,
+    /// and uploads the profile directory to "ProfilesDir".
+    ProfileDump,
+    /// Prints "NodeStatus" from the local status endpoint of "avalanched".
+    Status,
+}
+
+impl FleetCommand {
+    pub fn new(name: &str, log_level: Option<&str>) -> io::Result<Self> {
+        match name {
+            "restart-node" => Ok(FleetCommand::RestartNode),
+            "set-log-level" => {
+                let log_level = log_level.unwrap_or("").to_uppercase();
+                if !FLEET_COMMAND_LOG_LEVELS.contains(&log_level.as_str()) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "invalid log level '{}' (expected one of {:?})",
+                            log_level, FLEET_COMMAND_LOG_LEVELS
+                        ),
+                    ));
+                }
+                Ok(FleetCommand::SetLogLevel(log_level))
+            }
+            "profile-dump" => Ok(FleetCommand::ProfileDump),
+            "status" => Ok(FleetCommand::Status),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("unknown fleet command '{}'", name),
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            FleetCommand::RestartNode => "restart-node",
+            FleetCommand::SetLogLevel(_) => "set-log-level",
+            FleetCommand::ProfileDump => "profile-dump",
+            FleetCommand::Status => "status",
+        }
+    }
+
+    /// Returns the shell commands to run on each node.
+    pub fn shell_commands(&self, spec: &Spec) -> io::Result<Vec<String>> {
+        if matches!(
+            self,
+            FleetCommand::SetLogLevel(_) | FleetCommand::ProfileDump
+        ) && !spec.avalanchego_config.api_admin_enabled.unwrap_or(false)
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'{}' requires 'avalanchego_config.api_admin_enabled'",
+                    self.as_str()
+                ),
+            ));
+        }
+
+        // the admin API is only reachable from the node itself
+        let (scheme, curl) = if spec.avalanchego_config.http_tls_enabled.unwrap_or(false) {
+            ("https", "curl -sfk")
+        } else {
+            ("http", "curl -sf")
+        };
+        let admin = |method: &str, params: &str| {
+            format!(
+                "{} -X POST -H 'content-type:application/json;' --data '{{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"{}\",\"params\":{}}}' {}://127.0.0.1:{}/ext/admin",
+                curl, method, params, scheme, spec.avalanchego_config.http_port
+            )
+        };
+
+        match self {
+            FleetCommand::RestartNode => Ok(vec![
+                String::from("sudo systemctl restart avalanche.service"),
+                String::from("sudo systemctl is-active avalanche.service"),
+            ]),
+            FleetCommand::Status => {
+                let policy = spec.status_api_policy.clone().unwrap_or_default();
+                if policy.port == 0 {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "'status' requires 'status_api_policy.port' >0",
+                    ));
+                }
+                Ok(vec![format!(
+                    "curl -sf http://127.0.0.1:{}/status",
+                    policy.port
+                )])
+            }
+            FleetCommand::SetLogLevel(log_level) => Ok(vec![admin(
+                "admin.setLoggerLevel",
+                &format!(
+                    "{{\"logLevel\":\"{}\",\"displayLevel\":\"{}\"}}",
+                    log_level, log_level
+                ),
+            )]),
+            FleetCommand::ProfileDump => {
+                let profile_dir = spec
+                    .avalanchego_config
+                    .profile_dir
+                    .clone()
+                    .unwrap_or_else(|| String::from(avalanchego_config::DEFAULT_PROFILE_DIR));
+                let aws_resources = spec.aws_resources.clone().ok_or_else(|| {
+                    Error::new(ErrorKind::InvalidInput, "unexpected None aws_resources")
+                })?;
+                Ok(vec![
+                    admin("admin.memoryProfile", "{}"),
+                    admin("admin.lockProfile", "{}"),
+                    String::from("TOKEN=$(curl -sf -X PUT http://169.254.169.254/latest/api/token -H 'X-aws-ec2-metadata-token-ttl-seconds: 60')"),
+                    String::from("INSTANCE_ID=$(curl -sf -H \"X-aws-ec2-metadata-token: $TOKEN\" http://169.254.169.254/latest/meta-data/instance-id)"),
+                    format!("tar -czf /tmp/avalanche-profile.tar.gz -C {} .", profile_dir),
+                    format!(
+                        "aws s3 cp --region {} /tmp/avalanche-profile.tar.gz s3://{}/{}/$INSTANCE_ID.tar.gz",
+                        aws_resources.region,
+                        aws_resources.s3_bucket,
+                        StorageNamespace::ProfilesDir(spec.id.clone()).encode()
+                    ),
+                    String::from("rm -f /tmp/avalanche-profile.tar.gz"),
+                ])
+            }
+        }
+    }
+}
+
+#[test]
+fn test_fleet_command() {
+    assert_eq!(
+        FleetCommand::new("restart-node", None).unwrap(),
+        FleetCommand::RestartNode
+    );
+    assert_eq!(
+        FleetCommand::new("set-log-level", Some("debug")).unwrap(),
+        FleetCommand::SetLogLevel(String::from("DEBUG"))
+    );
+    assert!(FleetCommand::new("set-log-level", None).is_err());
+    assert!(FleetCommand::new("set-log-level", Some("LOUD")).is_err());
+    assert!(FleetCommand::new("reboot", None).is_err());
+
+    for name in ["restart-node", "profile-dump", "status"] {
+        assert_eq!(FleetCommand::new(name, None).unwrap().as_str(), name);
+    }
+}
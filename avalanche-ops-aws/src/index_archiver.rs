@@ -0,0 +1,194 @@
+use std::{
+    collections::BTreeSet,
+    io::{self, Error, ErrorKind},
+};
+
+use serde::{Deserialize, Serialize};
+
+use avalanche_types::api::index as api_index;
+use avalanchego::config as avalanchego_config;
+
+/// Archives the accepted containers of the local node from the index API
+/// to "StorageNamespace::IndexArchiveDir", as the zstd-compressed NDJSON
+/// files (one "index::Container" per line) for the offline analysis.
+/// Each node archives its own acceptance order and timestamps.
+/// Requires "avalanchego_config.index_enabled".
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct IndexArchiver {
+    #[serde(default = "default_archived_indexes")]
+    pub indexes: Vec<api_index::Index>,
+    /// Maximum number of the containers in each file.
+    #[serde(default = "default_containers_per_file")]
+    pub containers_per_file: u64,
+    /// Interval to archive the newly accepted containers.
+    #[serde(default = "default_archive_interval_seconds")]
+    pub interval_seconds: u64,
+}
+
+fn default_archived_indexes() -> Vec<api_index::Index> {
+    vec![
+        api_index::Index::XTx,
+        api_index::Index::XVtx,
+        api_index::Index::PBlock,
+        api_index::Index::CBlock,
+    ]
+}
+
+fn default_containers_per_file() -> u64 {
+    4096
+}
+
+fn default_archive_interval_seconds() -> u64 {
+    60
+}
+
+impl Default for IndexArchiver {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl IndexArchiver {
+    pub fn default() -> Self {
+        Self {
+            indexes: default_archived_indexes(),
+            containers_per_file: default_containers_per_file(),
+            interval_seconds: default_archive_interval_seconds(),
+        }
+    }
+
+    pub fn validate(&self, avalanchego_config: &avalanchego_config::Config) -> io::Result<()> {
+        if !avalanchego_config.index_enabled.unwrap_or(false) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'index_archiver' requires 'avalanchego_config.index_enabled'",
+            ));
+        }
+        if self.indexes.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'index_archiver.indexes' is empty",
+            ));
+        }
+        let unique: BTreeSet<&api_index::Index> = self.indexes.iter().collect();
+        if unique.len() != self.indexes.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("duplicate 'index_archiver.indexes' {:?}", self.indexes),
+            ));
+        }
+        if self.containers_per_file == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'index_archiver.containers_per_file' must be >0",
+            ));
+        }
+        if self.interval_seconds < 10 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'index_archiver.interval_seconds' {} must be >=10",
+                    self.interval_seconds
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the file name of the archived containers,
+/// sorted by the index of the first container.
+pub fn index_archive_file_name(first: u64, last: u64) -> String {
+    format!("{:020}-{:020}.ndjson.zstd", first, last)
+}
+
+/// Returns the index of the last container in the archived file,
+/// "None" if the key is not an archived file.
+pub fn parse_index_archive_last(key: &str) -> Option<u64> {
+    let file_name = key.rsplit('/').next()?;
+    let (_, last) = file_name.strip_suffix(".ndjson.zstd")?.split_once('-')?;
+    last.parse::<u64>().ok()
+}
+
+/// Encodes the containers as NDJSON, one container per line.
+pub fn encode_ndjson(containers: &[api_index::Container]) -> io::Result<Vec<u8>> {
+    let mut d = Vec::new();
+    for c in containers.iter() {
+        serde_json::to_writer(&mut d, c).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize Container to JSON {}", e),
+            )
+        })?;
+        d.push(b'\n');
+    }
+    Ok(d)
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-ops-aws --lib -- index_archiver::test_index_archiver --exact --show-output
+#[test]
+fn test_index_archiver() {
+    use avalanche_types::ids;
+
+    let mut avalanchego_config = avalanchego_config::Config::default();
+    assert!(IndexArchiver::default()
+        .validate(&avalanchego_config)
+        .is_err());
+    avalanchego_config.index_enabled = Some(true);
+    IndexArchiver::default()
+        .validate(&avalanchego_config)
+        .unwrap();
+
+    let archiver: IndexArchiver = serde_yaml::from_str(
+        "
+indexes:
+  - p-block
+  - c-block
+",
+    )
+    .unwrap();
+    archiver.validate(&avalanchego_config).unwrap();
+    assert_eq!(
+        archiver.indexes,
+        vec![api_index::Index::PBlock, api_index::Index::CBlock]
+    );
+    assert_eq!(archiver.containers_per_file, 4096);
+
+    let mut invalid = archiver.clone();
+    invalid.indexes.push(api_index::Index::PBlock);
+    assert!(invalid.validate(&avalanchego_config).is_err());
+    let mut invalid = archiver;
+    invalid.containers_per_file = 0;
+    assert!(invalid.validate(&avalanchego_config).is_err());
+
+    let name = index_archive_file_name(4096, 8191);
+    assert_eq!(
+        name,
+        "00000000000000004096-00000000000000008191.ndjson.zstd"
+    );
+    assert_eq!(
+        parse_index_archive_last(&format!("aops/index-archive/i-0/p-block/{}", name)),
+        Some(8191)
+    );
+    assert_eq!(
+        parse_index_archive_last("aops/index-archive/i-0/p-block/"),
+        None
+    );
+    assert_eq!(
+        parse_index_archive_last("00000000000000004096.ndjson"),
+        None
+    );
+
+    let container = api_index::Container {
+        id: ids::Id::empty(),
+        bytes: String::from("0x"),
+        timestamp: String::from("2021-04-02T15:34:00.262979-07:00"),
+        encoding: String::from("hex"),
+        index: 7,
+    };
+    let d = encode_ndjson(&[container.clone(), container]).unwrap();
+    let lines: Vec<&str> = std::str::from_utf8(&d).unwrap().lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("\"index\":7"));
+}
@@ -0,0 +1,158 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use aws::ec2::sg;
+
+use crate::{host_cidr, validate_ipv4_cidr};
+
+/// Port of the SSH ingress.
+pub const SSH_PORT: u32 = 22;
+
+/// Defines the ingress rules of the node security groups.
+/// "apply" diffs the rules against the security groups on every run,
+/// and revokes the rules on the SSH, HTTP, and staking ports
+/// that are not listed here (e.g., added by hand).
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct IngressPolicy {
+    /// IPv4 ranges allowed to the staking port.
+    /// If "None", the staking port is open to the world.
+    /// Set to the peer ranges for the private network.
+    /// The public IPs of the nodes are always allowed: "avalanched" allows
+    /// its own on boot (e.g., the replaced node with a new public IP),
+    /// and "apply" keeps the rules of the running instances.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staking_ipv4_ranges: Option<Vec<String>>,
+    /// Operator IPv4 ranges allowed to the HTTP port.
+    /// The VPC range is always allowed, for the NLB health checks
+    /// and the internal load balancers.
+    /// "apply" checks the node health from the local machine,
+    /// so its range must be listed for the check to pass.
+    #[serde(default)]
+    pub http_ipv4_ranges: Vec<String>,
+    /// IPv4 ranges allowed to SSH.
+    /// If empty, SSH is disabled ("aws ssm start-session" still works).
+    #[serde(default)]
+    pub ssh_ipv4_ranges: Vec<String>,
+}
+
+impl Default for IngressPolicy {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl IngressPolicy {
+    pub fn default() -> Self {
+        Self {
+            staking_ipv4_ranges: None,
+            http_ipv4_ranges: Vec::new(),
+            ssh_ipv4_ranges: Vec::new(),
+        }
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        for cidr in self
+            .staking_ipv4_ranges
+            .iter()
+            .flatten()
+            .chain(self.http_ipv4_ranges.iter())
+            .chain(self.ssh_ipv4_ranges.iter())
+        {
+            validate_ipv4_cidr(cidr)?;
+        }
+        if matches!(&self.staking_ipv4_ranges, Some(v) if v.is_empty()) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "empty 'staking_ipv4_ranges' (omit to open the staking port)",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the ports that the policy manages.
+    pub fn managed_ports(http_port: u32, staking_port: u32) -> [u32; 3] {
+        [SSH_PORT, http_port, staking_port]
+    }
+
+    /// Returns the sorted ingress rules for the security group.
+    pub fn ingress_rules(
+        &self,
+        http_port: u32,
+        staking_port: u32,
+        vpc_cidr: &str,
+        node_public_ips: &[String],
+    ) -> Vec<sg::IngressRule> {
+        let mut rules = std::collections::BTreeSet::new();
+        for cidr in self.ssh_ipv4_ranges.iter() {
+            rules.insert(sg::IngressRule::new(SSH_PORT, cidr));
+        }
+
+        rules.insert(sg::IngressRule::new(http_port, vpc_cidr));
+        for cidr in self.http_ipv4_ranges.iter() {
+            rules.insert(sg::IngressRule::new(http_port, cidr));
+        }
+
+        match &self.staking_ipv4_ranges {
+            Some(ranges) => {
+                for cidr in ranges.iter() {
+                    rules.insert(sg::IngressRule::new(staking_port, cidr));
+                }
+                for ip in node_public_ips.iter() {
+                    rules.insert(sg::IngressRule::new(staking_port, &host_cidr(ip)));
+                }
+            }
+            None => {
+                rules.insert(sg::IngressRule::new(
+                    staking_port,
+                    aws::DEFAULT_INGRESS_IPV4_RANGE,
+                ));
+            }
+        }
+        rules.into_iter().collect()
+    }
+}
+
+#[test]
+fn test_ingress_policy() {
+    let policy = IngressPolicy::default();
+    policy.validate().unwrap();
+    assert_eq!(
+        policy.ingress_rules(9650, 9651, "10.0.0.0/16", &[String::from("1.2.3.4")]),
+        vec![
+            sg::IngressRule::new(9650, "10.0.0.0/16"),
+            sg::IngressRule::new(9651, "0.0.0.0/0"),
+        ]
+    );
+
+    let policy: IngressPolicy = serde_yaml::from_str(
+        "
+staking_ipv4_ranges:
+  - 10.1.0.0/16
+http_ipv4_ranges:
+  - 203.0.113.0/24
+ssh_ipv4_ranges:
+  - 203.0.113.7/32
+",
+    )
+    .unwrap();
+    policy.validate().unwrap();
+    assert_eq!(
+        policy.ingress_rules(9650, 9651, "10.0.0.0/16", &[String::from("1.2.3.4")]),
+        vec![
+            sg::IngressRule::new(22, "203.0.113.7/32"),
+            sg::IngressRule::new(9650, "10.0.0.0/16"),
+            sg::IngressRule::new(9650, "203.0.113.0/24"),
+            sg::IngressRule::new(9651, "1.2.3.4/32"),
+            sg::IngressRule::new(9651, "10.1.0.0/16"),
+        ]
+    );
+
+    let mut invalid = policy.clone();
+    invalid.ssh_ipv4_ranges = vec![String::from("203.0.113.7")];
+    assert!(invalid.validate().is_err());
+    let mut invalid = policy;
+    invalid.staking_ipv4_ranges = Some(Vec::new());
+    assert!(invalid.validate().is_err());
+}
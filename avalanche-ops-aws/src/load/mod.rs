@@ -0,0 +1,240 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Error, ErrorKind},
+    str::FromStr,
+    time::Duration,
+};
+
+use clap::{Arg, Command};
+use crossterm::{
+    execute,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+};
+use log::info;
+use serde::Serialize;
+use tokio::runtime::Runtime;
+
+use avalanche_api::load;
+use avalanche_ops_aws::output;
+use avalanche_types::soft_key;
+use utils::http;
+
+pub const NAME: &str = "load";
+
+pub fn command() -> Command<'static> {
+    Command::new(NAME)
+        .about("Drives sustained transaction load against the network with the generated seed keys")
+        .arg(
+            Arg::new("LOG_LEVEL")
+                .long("log-level")
+                .short('l')
+                .help("Sets the log level")
+                .required(false)
+                .takes_value(true)
+                .possible_value("debug")
+                .possible_value("info")
+                .allow_invalid_utf8(false)
+                .default_value("info"),
+        )
+        .arg(
+            Arg::new("SPEC_FILE_PATH")
+                .long("spec-file-path")
+                .short('s')
+                .help("The spec file to load")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("TARGET_TPS")
+                .long("target-tps")
+                .help("Sets the transactions per second to issue once fully ramped up")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("DURATION_SECONDS")
+                .long("duration-seconds")
+                .help("Sets the duration of the load in seconds")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("300"),
+        )
+        .arg(
+            Arg::new("RAMP")
+                .long("ramp")
+                .help("Sets the ramp profile ('constant', 'linear:[SECONDS]', or 'step:[STEPS]:[SECONDS]')")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("constant"),
+        )
+        .arg(
+            Arg::new("MIX")
+                .long("mix")
+                .help("Sets the weighted share of the transactions per chain alias or blockchain ID (e.g., 'X=1,P=0,C=2')")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("X=1,C=1"),
+        )
+        .arg(
+            Arg::new("CONFIRM_TIMEOUT_SECONDS")
+                .long("confirm-timeout-seconds")
+                .help("Sets the maximum wait for each transaction to be accepted")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("60"),
+        )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute(
+    log_level: &str,
+    spec_file_path: &str,
+    target_tps: &str,
+    duration_seconds: &str,
+    ramp: &str,
+    mix: &str,
+    confirm_timeout_seconds: &str,
+    output: output::Format,
+) -> io::Result<()> {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    );
+
+    let spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
+    http::set_default_tls(spec.api_tls.clone())?;
+    let http_rpc = spec
+        .endpoints
+        .clone()
+        .and_then(|eps| eps.http_rpc)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "no 'endpoints.http_rpc' in the spec (run 'apply' first)",
+            )
+        })?;
+    let key_infos = spec.generated_seed_private_keys.unwrap_or_default();
+    if key_infos.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "no 'generated_seed_private_keys' in the spec to fund the load",
+        ));
+    }
+
+    // fetch the network first to resolve the chain aliases of the mix
+    let rt = Runtime::new().unwrap();
+    let network = rt.block_on(load::issuer::Network::fetch(&http_rpc))?;
+    info!("fetched network parameters {:?}", network);
+
+    let config = load::Config {
+        target_tps: parse_arg("target-tps", target_tps)?,
+        duration: Duration::from_secs(parse_arg("duration-seconds", duration_seconds)?),
+        ramp: load::Ramp::from_str(ramp)?,
+        mix: load::Mix::resolve(mix, &network.aliaser)?,
+        confirm_timeout: Duration::from_secs(parse_arg(
+            "confirm-timeout-seconds",
+            confirm_timeout_seconds,
+        )?),
+    };
+    config.validate()?;
+
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Blue),
+        Print(format!(
+            "\nDriving load against '{}' with {} keys: {:?}\n",
+            http_rpc,
+            key_infos.len(),
+            config
+        )),
+        ResetColor
+    )?;
+
+    let chains = config.mix.chains();
+    let mut wallets = Vec::new();
+    for k in key_infos.iter() {
+        let key = soft_key::Key::from_private_key(&k.private_key)?;
+        wallets.push(network.wallet(&key, &chains)?);
+    }
+    let report = rt.block_on(load::run(&config, wallets))?;
+
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Green),
+        Print(format!("\nLoad report:\n{}\n", report)),
+        ResetColor
+    )?;
+
+    output.print(&LoadReport::new(&report))
+}
+
+/// "load::Report" for the structured output, with the latencies in milliseconds.
+#[derive(Debug, Serialize)]
+struct LoadReport {
+    elapsed_seconds: f64,
+    accepted: u64,
+    accepted_tps: f64,
+    skipped: u64,
+    chains: BTreeMap<String, ChainReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChainReport {
+    issued: u64,
+    accepted: u64,
+    failures: BTreeMap<String, u64>,
+    latency_p50_ms: u64,
+    latency_p90_ms: u64,
+    latency_p99_ms: u64,
+    latency_max_ms: u64,
+}
+
+impl LoadReport {
+    fn new(report: &load::Report) -> Self {
+        let mut chains = BTreeMap::new();
+        for (chain, r) in report.chains.iter() {
+            chains.insert(
+                chain.to_string(),
+                ChainReport {
+                    issued: r.issued,
+                    accepted: r.accepted,
+                    failures: r
+                        .failures
+                        .iter()
+                        .map(|(failure, n)| (failure.to_string(), *n))
+                        .collect(),
+                    latency_p50_ms: r.latency.percentile(50.0).as_millis() as u64,
+                    latency_p90_ms: r.latency.percentile(90.0).as_millis() as u64,
+                    latency_p99_ms: r.latency.percentile(99.0).as_millis() as u64,
+                    latency_max_ms: r.latency.max().as_millis() as u64,
+                },
+            );
+        }
+        Self {
+            elapsed_seconds: report.elapsed.as_secs_f64(),
+            accepted: report.accepted(),
+            accepted_tps: report.accepted_tps(),
+            skipped: report.skipped,
+            chains,
+        }
+    }
+}
+
+fn parse_arg<T: FromStr>(name: &str, v: &str) -> io::Result<T>
+where
+    T::Err: std::fmt::Display,
+{
+    v.parse::<T>().map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid --{} '{}' ({})", name, v, e),
+        )
+    })
+}
@@ -0,0 +1,137 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::validate_ipv4_cidr;
+
+/// Defines the load balancer that "apply" creates in its own stack
+/// before the nodes, so the non-anchor nodes register to its target group.
+/// The anchor nodes are kept out of the public RPC traffic.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct LoadBalancer {
+    #[serde(default)]
+    pub kind: LoadBalancerKind,
+    /// Set to true for the load balancer only reachable within the VPC.
+    #[serde(default)]
+    pub internal: bool,
+    /// ACM certificate to terminate TLS on port 443.
+    /// If "None", only the HTTP port is served.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acm_certificate_arn: Option<String>,
+    /// Path that the target group health checks with HTTP GET,
+    /// so the nodes that are not healthy (e.g., bootstrapping)
+    /// are out of the rotation.
+    #[serde(default = "default_health_check_path")]
+    pub health_check_path: String,
+    /// IPv4 range allowed to the listeners of the ALB.
+    /// Defaults to "0.0.0.0/0" if None.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ingress_ipv4_range: Option<String>,
+}
+
+fn default_health_check_path() -> String {
+    String::from("/ext/health")
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadBalancerKind {
+    /// Network load balancer, TLS listener with the ACM certificate.
+    Nlb,
+    /// Application load balancer, HTTPS listener with the ACM certificate.
+    Alb,
+}
+
+impl Default for LoadBalancerKind {
+    fn default() -> Self {
+        LoadBalancerKind::Nlb
+    }
+}
+
+impl LoadBalancerKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            LoadBalancerKind::Nlb => "nlb",
+            LoadBalancerKind::Alb => "alb",
+        }
+    }
+}
+
+impl Default for LoadBalancer {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl LoadBalancer {
+    pub fn default() -> Self {
+        Self {
+            kind: LoadBalancerKind::default(),
+            internal: false,
+            acm_certificate_arn: None,
+            health_check_path: default_health_check_path(),
+            ingress_ipv4_range: None,
+        }
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        if !self.health_check_path.starts_with('/') {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'health_check_path' {} must start with '/'",
+                    self.health_check_path
+                ),
+            ));
+        }
+        if let Some(arn) = &self.acm_certificate_arn {
+            if !arn.starts_with("arn:") {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid 'acm_certificate_arn' {}", arn),
+                ));
+            }
+        }
+        if let Some(ingress_ipv4_range) = &self.ingress_ipv4_range {
+            validate_ipv4_cidr(ingress_ipv4_range)?;
+            // the NLB has no security group of its own,
+            // the node security group filters the clients
+            if self.kind == LoadBalancerKind::Nlb {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'ingress_ipv4_range' is only supported for 'alb'",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_load_balancer() {
+    let load_balancer: LoadBalancer = serde_yaml::from_str(
+        "
+kind: alb
+acm_certificate_arn: arn:aws:acm:us-west-2:123456789012:certificate/abc
+ingress_ipv4_range: 203.0.113.0/24
+",
+    )
+    .unwrap();
+    assert_eq!(load_balancer.kind, LoadBalancerKind::Alb);
+    assert_eq!(load_balancer.health_check_path, "/ext/health");
+    assert!(!load_balancer.internal);
+    load_balancer.validate().unwrap();
+
+    let mut invalid = load_balancer.clone();
+    invalid.kind = LoadBalancerKind::Nlb;
+    assert!(invalid.validate().is_err());
+    let mut invalid = load_balancer.clone();
+    invalid.health_check_path = String::from("ext/health");
+    assert!(invalid.validate().is_err());
+    let mut invalid = load_balancer;
+    invalid.acm_certificate_arn = Some(String::from("abc"));
+    assert!(invalid.validate().is_err());
+
+    assert!(LoadBalancer::default().validate().is_ok());
+}
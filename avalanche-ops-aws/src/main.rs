@@ -1,26 +1,65 @@
-use clap::Command;
+use std::str::FromStr;
+
+use avalanche_ops_aws::output;
+use clap::{Arg, ArgMatches, Command};
 
 mod apply;
+mod chaos;
 mod check_balances;
+mod clone;
 mod default_spec;
 mod delete;
 mod events;
+mod load;
 mod read_spec;
+mod run_command;
+mod simulate_consensus;
+mod snapshot;
+mod staking_rewards;
+mod subnet;
 
 const NAME: &str = "avalanche-ops-aws";
 
+/// Parses the global "--output" flag, which clap propagates to every subcommand.
+fn output_format(matches: &ArgMatches) -> output::Format {
+    output::Format::from_str(matches.value_of("OUTPUT").unwrap_or("table"))
+        .expect("invalid --output")
+}
+
 /// Should be able to run with idempotency
 /// (e.g., multiple restarts should not recreate the same CloudFormation stacks)
 fn main() {
     let matches = Command::new(NAME)
         .about("Avalanche node operations on AWS")
+        .arg(
+            Arg::new("OUTPUT")
+                .long("output")
+                .short('o')
+                .help("Sets the output format of the results (json and yaml print one document to stdout, and the other messages to stderr)")
+                .required(false)
+                .takes_value(true)
+                .possible_value("table")
+                .possible_value("json")
+                .possible_value("yaml")
+                .allow_invalid_utf8(false)
+                .default_value("table")
+                .global(true),
+        )
         .subcommands(vec![
             default_spec::command(),
             read_spec::command(),
             check_balances::command(),
+            staking_rewards::command(),
+            simulate_consensus::command(),
             events::command(),
             apply::command(),
             delete::command(),
+            subnet::command(),
+            load::command(),
+            run_command::command(),
+            chaos::command(),
+            snapshot::command(),
+            clone::command(),
         ])
         .get_matches();
 
@@ -28,6 +67,16 @@ fn main() {
         Some((default_spec::NAME, sub_matches)) => {
             let keys_to_generate = sub_matches.value_of("KEYS_TO_GENERATE").unwrap_or("");
             let keys_to_generate = keys_to_generate.parse::<usize>().unwrap();
+            let instance_types = match sub_matches.value_of("INSTANCE_TYPES") {
+                Some(v) => default_spec::parse_instance_types(v).expect("invalid --instance-types"),
+                None => Vec::new(),
+            };
+            let regional_non_anchor_nodes = default_spec::parse_regional_non_anchor_nodes(
+                sub_matches
+                    .value_of("REGIONAL_NON_ANCHOR_NODES")
+                    .unwrap_or(""),
+            )
+            .expect("invalid --regional-non-anchor-nodes");
             let opt = avalanche_ops_aws::DefaultSpecOption {
                 log_level: sub_matches
                     .value_of("LOG_LEVEL")
@@ -37,9 +86,21 @@ fn main() {
                     .value_of("NETWORK_NAME")
                     .unwrap_or("")
                     .to_string(),
+                network_id: sub_matches
+                    .value_of("NETWORK_ID")
+                    .map(|v| v.parse::<u32>().expect("invalid --network-id")),
                 keys_to_generate,
 
                 region: sub_matches.value_of("REGION").unwrap().to_string(),
+                regional_non_anchor_nodes,
+
+                anchor_nodes: sub_matches
+                    .value_of("ANCHOR_NODES")
+                    .map(|v| v.parse::<u32>().expect("invalid --anchor-nodes")),
+                non_anchor_nodes: sub_matches
+                    .value_of("NON_ANCHOR_NODES")
+                    .map(|v| v.parse::<u32>().expect("invalid --non-anchor-nodes")),
+                instance_types,
 
                 db_backup_s3_region: sub_matches
                     .value_of("DB_BACKUP_S3_REGION")
@@ -122,7 +183,12 @@ fn main() {
                     .unwrap_or("")
                     .to_string(),
             };
-            default_spec::execute(opt).expect("failed to execute 'default-spec'");
+            default_spec::execute(
+                opt,
+                sub_matches.is_present("INTERACTIVE"),
+                output_format(sub_matches),
+            )
+            .expect("failed to execute 'default-spec'");
         }
 
         Some((read_spec::NAME, sub_matches)) => {
@@ -133,6 +199,7 @@ fn main() {
                 sub_matches.is_present("NLB_ENDPOINT"),
                 sub_matches.is_present("HTTP_ENDPOINTS"),
                 sub_matches.is_present("NODE_IDS"),
+                output_format(sub_matches),
             )
             .expect("failed to execute 'read-spec'");
         }
@@ -141,10 +208,40 @@ fn main() {
             check_balances::execute(
                 sub_matches.value_of("LOG_LEVEL").unwrap_or("info"),
                 sub_matches.value_of("SPEC_FILE_PATH").unwrap(),
+                output_format(sub_matches),
             )
             .expect("failed to execute 'check-balances'");
         }
 
+        Some((staking_rewards::NAME, sub_matches)) => {
+            staking_rewards::execute(
+                sub_matches.value_of("LOG_LEVEL").unwrap_or("info"),
+                sub_matches.value_of("SPEC_FILE_PATH").unwrap(),
+                sub_matches.value_of("NODE_ID").unwrap(),
+                sub_matches.value_of("START_TIME"),
+                sub_matches.value_of("END_TIME"),
+                output_format(sub_matches),
+            )
+            .expect("failed to execute 'staking-rewards'");
+        }
+
+        Some((simulate_consensus::NAME, sub_matches)) => {
+            simulate_consensus::execute(
+                sub_matches.value_of("LOG_LEVEL").unwrap_or("info"),
+                sub_matches.value_of("SPEC_FILE_PATH").unwrap(),
+                sub_matches.value_of("NODES"),
+                sub_matches.value_of("BYZANTINE_FRACTION").unwrap(),
+                sub_matches.value_of("INITIAL_SPLIT").unwrap(),
+                sub_matches.value_of("MIN_LATENCY_MS").unwrap(),
+                sub_matches.value_of("MAX_LATENCY_MS").unwrap(),
+                sub_matches.value_of("MAX_ROUNDS").unwrap(),
+                sub_matches.value_of("RUNS").unwrap(),
+                sub_matches.value_of("SEED").unwrap(),
+                output_format(sub_matches),
+            )
+            .expect("failed to execute 'simulate-consensus'");
+        }
+
         Some((events::NAME, sub_matches)) => match sub_matches.subcommand() {
             Some((events::update_artifacts::NAME, sub_sub_matches)) => {
                 events::update_artifacts::execute(
@@ -156,10 +253,59 @@ fn main() {
                     sub_sub_matches
                         .value_of("INSTALL_ARTIFACTS_PLUGINS_DIR")
                         .unwrap_or(""),
+                    sub_sub_matches.value_of("SIGNING_KEY_PATH").unwrap_or(""),
                     sub_sub_matches.is_present("SKIP_PROMPT"),
+                    output_format(sub_sub_matches),
                 )
                 .expect("failed to execute 'events update-artifacts'");
             }
+            Some((events::rolling_upgrade::NAME, sub_sub_matches)) => {
+                events::rolling_upgrade::execute(
+                    sub_sub_matches.value_of("LOG_LEVEL").unwrap_or("info"),
+                    sub_sub_matches.value_of("SPEC_FILE_PATH").unwrap(),
+                    sub_sub_matches
+                        .value_of("INSTALL_ARTIFACTS_AVALANCHE_BIN")
+                        .unwrap(),
+                    sub_sub_matches
+                        .value_of("INSTALL_ARTIFACTS_PLUGINS_DIR")
+                        .unwrap_or(""),
+                    sub_sub_matches.value_of("SIGNING_KEY_PATH").unwrap_or(""),
+                    sub_sub_matches.value_of("WAVE_BY").unwrap_or("percent"),
+                    sub_sub_matches.value_of("WAVE_PERCENT").unwrap_or("25"),
+                    sub_sub_matches.value_of("MAX_FAILURES").unwrap_or("0"),
+                    sub_sub_matches
+                        .value_of("WAVE_TIMEOUT_SECONDS")
+                        .unwrap_or("1800"),
+                    sub_sub_matches
+                        .value_of("HEIGHT_TIMEOUT_SECONDS")
+                        .unwrap_or("600"),
+                    sub_sub_matches.is_present("SKIP_PROMPT"),
+                    output_format(sub_sub_matches),
+                )
+                .expect("failed to execute 'events rolling-upgrade'");
+            }
+            Some((events::rotate_cert::NAME, sub_sub_matches)) => {
+                events::rotate_cert::execute(
+                    sub_sub_matches.value_of("LOG_LEVEL").unwrap_or("info"),
+                    sub_sub_matches.value_of("SPEC_FILE_PATH").unwrap(),
+                    sub_sub_matches.is_present("REREGISTER_VALIDATOR"),
+                    sub_sub_matches.is_present("SYNC_ONLY"),
+                    sub_sub_matches.is_present("SKIP_PROMPT"),
+                    output_format(sub_sub_matches),
+                )
+                .expect("failed to execute 'events rotate-cert'");
+            }
+            Some((events::update_config::NAME, sub_sub_matches)) => {
+                events::update_config::execute(
+                    sub_sub_matches.value_of("LOG_LEVEL").unwrap_or("info"),
+                    sub_sub_matches.value_of("SPEC_FILE_PATH").unwrap(),
+                    events::update_config::parse_delta(sub_sub_matches),
+                    sub_sub_matches.is_present("STATUS_ONLY"),
+                    sub_sub_matches.is_present("SKIP_PROMPT"),
+                    output_format(sub_sub_matches),
+                )
+                .expect("failed to execute 'events update-config'");
+            }
             _ => unreachable!("unknown sub-subcommand"),
         },
 
@@ -168,6 +314,9 @@ fn main() {
                 sub_matches.value_of("LOG_LEVEL").unwrap_or("info"),
                 sub_matches.value_of("SPEC_FILE_PATH").unwrap(),
                 sub_matches.is_present("SKIP_PROMPT"),
+                sub_matches.is_present("PLAN"),
+                sub_matches.value_of("PLAN_OUTPUT_PATH"),
+                output_format(sub_matches),
             )
             .expect("failed to execute 'apply'");
         }
@@ -180,10 +329,90 @@ fn main() {
                 sub_matches.is_present("DELETE_S3_OBJECTS"),
                 sub_matches.is_present("DELETE_S3_BUCKET"),
                 sub_matches.is_present("SKIP_PROMPT"),
+                output_format(sub_matches),
             )
             .expect("failed to execute 'delete'");
         }
 
+        Some((subnet::NAME, sub_matches)) => {
+            subnet::execute(
+                sub_matches.value_of("LOG_LEVEL").unwrap_or("info"),
+                sub_matches.value_of("SPEC_FILE_PATH").unwrap(),
+                sub_matches.is_present("SKIP_PROMPT"),
+                sub_matches
+                    .value_of("LEDGER_ADDRESS_INDEX")
+                    .map(|v| v.parse::<u32>().expect("invalid --ledger-address-index")),
+                output_format(sub_matches),
+            )
+            .expect("failed to execute 'subnet'");
+        }
+
+        Some((load::NAME, sub_matches)) => {
+            load::execute(
+                sub_matches.value_of("LOG_LEVEL").unwrap_or("info"),
+                sub_matches.value_of("SPEC_FILE_PATH").unwrap(),
+                sub_matches.value_of("TARGET_TPS").unwrap(),
+                sub_matches.value_of("DURATION_SECONDS").unwrap(),
+                sub_matches.value_of("RAMP").unwrap(),
+                sub_matches.value_of("MIX").unwrap(),
+                sub_matches.value_of("CONFIRM_TIMEOUT_SECONDS").unwrap(),
+                output_format(sub_matches),
+            )
+            .expect("failed to execute 'load'");
+        }
+
+        Some((run_command::NAME, sub_matches)) => {
+            run_command::execute(
+                sub_matches.value_of("LOG_LEVEL").unwrap_or("info"),
+                sub_matches.value_of("SPEC_FILE_PATH").unwrap(),
+                sub_matches.value_of("COMMAND").unwrap(),
+                sub_matches.value_of("AVALANCHEGO_LOG_LEVEL"),
+                sub_matches.value_of("NODE_KIND"),
+                sub_matches.value_of("INSTANCE_IDS"),
+                sub_matches.value_of("TIMEOUT_SECONDS").unwrap(),
+                sub_matches.is_present("SKIP_PROMPT"),
+                output_format(sub_matches),
+            )
+            .expect("failed to execute 'run-command'");
+        }
+
+        Some((chaos::NAME, sub_matches)) => {
+            chaos::execute(
+                sub_matches.value_of("LOG_LEVEL").unwrap_or("info"),
+                sub_matches.value_of("SPEC_FILE_PATH").unwrap(),
+                sub_matches.value_of("SCENARIOS"),
+                sub_matches.value_of("SEED"),
+                sub_matches.is_present("RECOVER_ONLY"),
+                sub_matches.is_present("SKIP_PROMPT"),
+                output_format(sub_matches),
+            )
+            .expect("failed to execute 'chaos'");
+        }
+
+        Some((snapshot::NAME, sub_matches)) => {
+            snapshot::execute(
+                sub_matches.value_of("LOG_LEVEL").unwrap_or("info"),
+                sub_matches.value_of("SPEC_FILE_PATH").unwrap(),
+                sub_matches.value_of("NAME").unwrap(),
+                sub_matches.value_of("TIMEOUT_SECONDS").unwrap(),
+                sub_matches.is_present("SKIP_PROMPT"),
+                output_format(sub_matches),
+            )
+            .expect("failed to execute 'snapshot'");
+        }
+
+        Some((clone::NAME, sub_matches)) => {
+            clone::execute(
+                sub_matches.value_of("LOG_LEVEL").unwrap_or("info"),
+                sub_matches.value_of("SPEC_FILE_PATH").unwrap(),
+                sub_matches.value_of("SNAPSHOT_NAME").unwrap(),
+                sub_matches.value_of("CLONE_SPEC_FILE_PATH").unwrap(),
+                sub_matches.value_of("ID"),
+                output_format(sub_matches),
+            )
+            .expect("failed to execute 'clone'");
+        }
+
         _ => unreachable!("unknown subcommand"),
     }
 }
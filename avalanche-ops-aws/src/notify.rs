@@ -0,0 +1,360 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, Error, ErrorKind},
+    time::Duration,
+};
+
+use aws_types::SdkConfig as AwsSdkConfig;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use avalanche_api::alert::Severity;
+use aws::sns;
+use utils::http;
+
+/// Timeout of each webhook request, so that a slow sink
+/// does not stall the orchestrator or "avalanched".
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Lifecycle events to notify.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+    StackCreated,
+    NodeBootstrapped,
+    ValidatorAdded,
+    UpgradeFailed,
+    NodeTerminating,
+}
+
+impl Event {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Event::StackCreated => "stack_created",
+            Event::NodeBootstrapped => "node_bootstrapped",
+            Event::ValidatorAdded => "validator_added",
+            Event::UpgradeFailed => "upgrade_failed",
+            Event::NodeTerminating => "node_terminating",
+        }
+    }
+
+    /// Severity of the event unless overridden in "NotificationPolicy.severities".
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            Event::StackCreated | Event::NodeBootstrapped | Event::ValidatorAdded => Severity::Info,
+            Event::UpgradeFailed => Severity::Critical,
+            Event::NodeTerminating => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Destination of the notifications.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SinkTarget {
+    /// Publishes to the SNS topic in the home region,
+    /// which is allowed "sns:Publish" on the instance role.
+    Sns { topic_arn: String },
+    /// Posts the "text" payload to the Slack incoming webhook.
+    Slack { webhook_url: String },
+    /// Posts the JSON-encoded "Notification" to the URL.
+    Http { url: String },
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Sink {
+    #[serde(flatten)]
+    pub target: SinkTarget,
+    /// Only the events of the equal or higher severity are sent to the sink.
+    #[serde(default = "default_min_severity")]
+    pub min_severity: Severity,
+}
+
+fn default_min_severity() -> Severity {
+    Severity::Info
+}
+
+impl Sink {
+    pub fn validate(&self) -> io::Result<()> {
+        match &self.target {
+            SinkTarget::Sns { topic_arn } => {
+                if !topic_arn.starts_with("arn:") {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("invalid SNS topic ARN '{}'", topic_arn),
+                    ));
+                }
+            }
+            SinkTarget::Slack { webhook_url: url } | SinkTarget::Http { url } => {
+                if !url.starts_with("https://") && !url.starts_with("http://") {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("invalid notification URL '{}'", url),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Defines where "avalanche-ops-aws" and "avalanched" send the lifecycle events.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct NotificationPolicy {
+    #[serde(default)]
+    pub sinks: Vec<Sink>,
+    /// Overrides "Event::default_severity" per event
+    /// (e.g., "node_bootstrapped: warning" to route it to the paging sinks).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub severities: BTreeMap<Event, Severity>,
+}
+
+impl NotificationPolicy {
+    pub fn validate(&self) -> io::Result<()> {
+        for sink in self.sinks.iter() {
+            sink.validate()?;
+        }
+        Ok(())
+    }
+
+    pub fn severity(&self, event: Event) -> Severity {
+        self.severities
+            .get(&event)
+            .copied()
+            .unwrap_or_else(|| event.default_severity())
+    }
+
+    /// Returns the SNS topics to allow "sns:Publish" on the instance role.
+    pub fn sns_topic_arns(&self) -> Vec<String> {
+        self.sinks
+            .iter()
+            .filter_map(|s| match &s.target {
+                SinkTarget::Sns { topic_arn } => Some(topic_arn.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the Slack webhook URLs in the order of the sinks,
+    /// which embed the secret token of the channel.
+    pub fn slack_webhook_urls(&self) -> Vec<String> {
+        self.sinks
+            .iter()
+            .filter_map(|s| match &s.target {
+                SinkTarget::Slack { webhook_url } => Some(webhook_url.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Clears the Slack webhook URLs, keeping the sinks and their routing.
+    pub fn redact_slack_webhook_urls(&mut self) {
+        for sink in self.sinks.iter_mut() {
+            if let SinkTarget::Slack { webhook_url } = &mut sink.target {
+                webhook_url.clear();
+            }
+        }
+    }
+
+    /// Restores the Slack webhook URLs returned by "slack_webhook_urls".
+    pub fn restore_slack_webhook_urls(&mut self, urls: Vec<String>) {
+        let mut urls = urls.into_iter();
+        for sink in self.sinks.iter_mut() {
+            if let SinkTarget::Slack { webhook_url } = &mut sink.target {
+                if let Some(url) = urls.next() {
+                    *webhook_url = url;
+                }
+            }
+        }
+    }
+
+    /// Returns the sinks that receive the event.
+    pub fn route(&self, event: Event) -> Vec<&Sink> {
+        let severity = self.severity(event);
+        self.sinks
+            .iter()
+            .filter(|s| severity >= s.min_severity)
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Notification {
+    /// Spec ID of the cluster.
+    pub id: String,
+    pub event: Event,
+    pub severity: Severity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    pub message: String,
+    pub ts: DateTime<Utc>,
+}
+
+impl Notification {
+    pub fn subject(&self) -> String {
+        format!("[{}] {}: {}", self.severity, self.id, self.event)
+    }
+}
+
+impl fmt::Display for Notification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.subject())?;
+        if let Some(node_id) = &self.node_id {
+            write!(f, " ({})", node_id)?;
+        }
+        write!(f, "\n{}", self.message)
+    }
+}
+
+/// Sends the lifecycle events to the sinks routed by the policy.
+/// Failed deliveries are only logged, so that a broken sink never fails the operation.
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    pub id: String,
+    pub policy: NotificationPolicy,
+    sns_manager: Option<sns::Manager>,
+}
+
+impl Notifier {
+    pub fn new(id: &str, policy: NotificationPolicy, shared_config: &AwsSdkConfig) -> Self {
+        let sns_manager = if policy
+            .sinks
+            .iter()
+            .any(|s| matches!(s.target, SinkTarget::Sns { .. }))
+        {
+            Some(sns::Manager::new(shared_config))
+        } else {
+            None
+        };
+        Self {
+            id: id.to_string(),
+            policy,
+            sns_manager,
+        }
+    }
+
+    /// Returns the number of the sinks that accepted the notification.
+    pub async fn notify(&self, event: Event, node_id: Option<&str>, message: &str) -> usize {
+        let sinks = self.policy.route(event);
+        if sinks.is_empty() {
+            return 0;
+        }
+        let n = Notification {
+            id: self.id.clone(),
+            event,
+            severity: self.policy.severity(event),
+            node_id: node_id.map(String::from),
+            message: message.to_string(),
+            ts: Utc::now(),
+        };
+        info!("notifying {} to {} sink(s)", n.subject(), sinks.len());
+
+        let mut sent = 0;
+        for sink in sinks.iter() {
+            match self.send(&sink.target, &n).await {
+                Ok(_) => sent += 1,
+                Err(e) => warn!("failed to notify {} ({})", n.subject(), e),
+            }
+        }
+        sent
+    }
+
+    async fn send(&self, target: &SinkTarget, n: &Notification) -> io::Result<()> {
+        match target {
+            SinkTarget::Sns { topic_arn } => {
+                let sns_manager = self
+                    .sns_manager
+                    .as_ref()
+                    .expect("unexpected None sns_manager");
+                sns_manager
+                    .publish(topic_arn, &n.subject(), &n.to_string())
+                    .await?;
+            }
+            SinkTarget::Slack { webhook_url } => {
+                let payload = serde_json::json!({ "text": n.to_string() });
+                post_json(webhook_url, &payload.to_string()).await?;
+            }
+            SinkTarget::Http { url } => {
+                let payload = serde_json::to_string(n).map_err(|e| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("failed to encode notification {}", e),
+                    )
+                })?;
+                post_json(url, &payload).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn post_json(url: &str, payload: &str) -> io::Result<()> {
+    let req = http::create_json_post(url, "", payload)?;
+    http::read_bytes(req, WEBHOOK_TIMEOUT, url.starts_with("https"), true).await?;
+    Ok(())
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-ops-aws --lib -- notify::test_policy --exact --show-output
+#[test]
+fn test_policy() {
+    let policy: NotificationPolicy = serde_yaml::from_str(
+        r#"
+sinks:
+- kind: sns
+  topic_arn: arn:aws:sns:us-west-2:123456789012:avalanche-ops
+  min_severity: critical
+- kind: slack
+  webhook_url: https://hooks.slack.com/services/T000/B000/XXX
+- kind: http
+  url: http://localhost:8080/events
+  min_severity: warning
+severities:
+  node_bootstrapped: warning
+"#,
+    )
+    .unwrap();
+    policy.validate().unwrap();
+    assert_eq!(policy.sinks.len(), 3);
+    assert_eq!(policy.sinks[1].min_severity, Severity::Info);
+
+    assert_eq!(policy.severity(Event::StackCreated), Severity::Info);
+    assert_eq!(policy.severity(Event::NodeBootstrapped), Severity::Warning);
+    assert_eq!(policy.route(Event::StackCreated).len(), 1);
+    assert_eq!(policy.route(Event::NodeBootstrapped).len(), 2);
+    assert_eq!(policy.route(Event::UpgradeFailed).len(), 3);
+
+    assert_eq!(
+        policy.sns_topic_arns(),
+        vec![String::from(
+            "arn:aws:sns:us-west-2:123456789012:avalanche-ops"
+        )]
+    );
+    let mut redacted = policy.clone();
+    redacted.redact_slack_webhook_urls();
+    assert!(redacted.slack_webhook_urls()[0].is_empty());
+    assert!(redacted.validate().is_err());
+    redacted.restore_slack_webhook_urls(policy.slack_webhook_urls());
+    assert_eq!(redacted, policy);
+
+    let invalid = NotificationPolicy {
+        sinks: vec![Sink {
+            target: SinkTarget::Http {
+                url: String::from("localhost:8080"),
+            },
+            min_severity: Severity::Info,
+        }],
+        ..NotificationPolicy::default()
+    };
+    assert!(invalid.validate().is_err());
+}
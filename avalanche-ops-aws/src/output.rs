@@ -0,0 +1,253 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, stderr, stdout, Error, ErrorKind, Write},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Endpoints, Node, Spec};
+
+/// Output formats of the "--output" flag.
+pub const FORMATS: [&str; 3] = ["table", "json", "yaml"];
+
+/// Format of the command results.
+/// With "json" or "yaml", the results are printed to stdout as one document
+/// at the end of the command, and the human-readable messages (e.g., progress,
+/// hints) are written to stderr so that scripts can parse stdout as is.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    /// The human-readable messages on stdout.
+    Table,
+    Json,
+    Yaml,
+}
+
+impl Format {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Format::Table => "table",
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+        }
+    }
+
+    /// Returns true if the results are printed as a JSON or YAML document.
+    pub fn is_structured(&self) -> bool {
+        *self != Format::Table
+    }
+
+    /// Returns the writer for the human-readable messages,
+    /// which is stderr with the structured output.
+    pub fn console(&self) -> Box<dyn Write> {
+        if self.is_structured() {
+            Box::new(stderr())
+        } else {
+            Box::new(stdout())
+        }
+    }
+
+    /// Encodes the result in JSON or YAML.
+    /// Fails for "table", whose results are the human-readable messages.
+    pub fn encode<T: Serialize>(&self, result: &T) -> io::Result<String> {
+        match self {
+            Format::Json => serde_json::to_string_pretty(result).map_err(|e| {
+                Error::new(
+                    ErrorKind::Other,
+                    format!("failed to serialize to JSON {}", e),
+                )
+            }),
+            Format::Yaml => serde_yaml::to_string(result).map_err(|e| {
+                Error::new(
+                    ErrorKind::Other,
+                    format!("failed to serialize to YAML {}", e),
+                )
+            }),
+            Format::Table => Err(Error::new(
+                ErrorKind::Unsupported,
+                "'table' output has no encoded document",
+            )),
+        }
+    }
+
+    /// Prints the result to stdout if the output is structured.
+    /// No-op for "table".
+    pub fn print<T: Serialize>(&self, result: &T) -> io::Result<()> {
+        if !self.is_structured() {
+            return Ok(());
+        }
+        let encoded = self.encode(result)?;
+        let mut out = stdout();
+        writeln!(out, "{}", encoded.trim_end())?;
+        out.flush()
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "yaml" => Ok(Format::Yaml),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("unknown output '{}' (must be one of {:?})", s, FORMATS),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Describes the deployed cluster in the spec,
+/// the structured result of "apply" and "read-spec".
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Cluster {
+    pub id: String,
+    pub network_id: u32,
+    pub region: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoints: Option<Endpoints>,
+    pub nodes: Vec<Node>,
+    /// CloudFormation stack IDs and outputs (e.g., VPC ID, NLB DNS name),
+    /// prefixed with the region for the other regions than the primary one.
+    pub stack_outputs: BTreeMap<String, String>,
+}
+
+impl Cluster {
+    pub fn from_spec(spec: &Spec) -> Self {
+        let (region, stack_outputs) = match &spec.aws_resources {
+            Some(aws_resources) => (aws_resources.region.clone(), stack_outputs(aws_resources)),
+            None => (String::new(), BTreeMap::new()),
+        };
+        Self {
+            id: spec.id.clone(),
+            network_id: spec.avalanchego_config.network_id,
+            region,
+            endpoints: spec.endpoints.clone(),
+            nodes: spec.current_nodes.clone().unwrap_or_default(),
+            stack_outputs,
+        }
+    }
+}
+
+/// Collects the CloudFormation stack IDs and outputs that are set.
+pub fn stack_outputs(aws_resources: &aws::Resources) -> BTreeMap<String, String> {
+    let mut outputs = BTreeMap::new();
+    let mut insert = |k: &str, v: &Option<String>| {
+        if let Some(v) = v {
+            outputs.insert(k.to_string(), v.clone());
+        }
+    };
+    insert("s3_bucket", &Some(aws_resources.s3_bucket.clone()));
+    insert(
+        "ec2_instance_role",
+        &aws_resources.cloudformation_ec2_instance_role,
+    );
+    insert("vpc", &aws_resources.cloudformation_vpc);
+    insert("vpc_id", &aws_resources.cloudformation_vpc_id);
+    insert(
+        "vpc_security_group_id",
+        &aws_resources.cloudformation_vpc_security_group_id,
+    );
+    insert(
+        "vpc_public_subnet_ids",
+        &aws_resources
+            .cloudformation_vpc_public_subnet_ids
+            .as_ref()
+            .map(|v| v.join(",")),
+    );
+    insert(
+        "asg_anchor_nodes",
+        &aws_resources.cloudformation_asg_anchor_nodes,
+    );
+    insert(
+        "asg_non_anchor_nodes",
+        &aws_resources.cloudformation_asg_non_anchor_nodes,
+    );
+    insert("asg_api_nodes", &aws_resources.cloudformation_asg_api_nodes);
+    insert("load_balancer", &aws_resources.cloudformation_load_balancer);
+    insert("nlb_arn", &aws_resources.cloudformation_asg_nlb_arn);
+    insert(
+        "nlb_target_group_arn",
+        &aws_resources.cloudformation_asg_nlb_target_group_arn,
+    );
+    insert(
+        "nlb_dns_name",
+        &aws_resources.cloudformation_asg_nlb_dns_name,
+    );
+
+    for r in aws_resources.regional_resources.iter().flatten() {
+        let mut insert = |k: &str, v: &Option<String>| {
+            if let Some(v) = v {
+                outputs.insert(format!("{}/{}", r.region, k), v.clone());
+            }
+        };
+        insert("s3_bucket", &r.s3_bucket);
+        insert("vpc", &r.cloudformation_vpc);
+        insert("vpc_id", &r.cloudformation_vpc_id);
+        insert(
+            "vpc_security_group_id",
+            &r.cloudformation_vpc_security_group_id,
+        );
+        insert(
+            "vpc_public_subnet_ids",
+            &r.cloudformation_vpc_public_subnet_ids
+                .as_ref()
+                .map(|v| v.join(",")),
+        );
+        insert(
+            "asg_non_anchor_nodes",
+            &r.cloudformation_asg_non_anchor_nodes,
+        );
+    }
+    outputs
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-ops-aws --lib -- output::test_format --exact --show-output
+#[test]
+fn test_format() {
+    for s in FORMATS.iter() {
+        let format = Format::from_str(s).unwrap();
+        assert_eq!(format.to_string(), *s);
+    }
+    assert!(Format::from_str("csv").is_err());
+    assert!(!Format::Table.is_structured());
+    assert!(Format::Json.is_structured());
+
+    let mut aws_resources = aws::Resources {
+        region: String::from("us-west-2"),
+        s3_bucket: String::from("test-bucket"),
+        cloudformation_vpc_id: Some(String::from("vpc-123")),
+        cloudformation_vpc_public_subnet_ids: Some(vec![
+            String::from("subnet-1"),
+            String::from("subnet-2"),
+        ]),
+        ..aws::Resources::default()
+    };
+    let mut regional = aws::RegionalResources::new("eu-west-1", 2);
+    regional.cloudformation_vpc_id = Some(String::from("vpc-456"));
+    aws_resources.regional_resources = Some(vec![regional]);
+
+    let outputs = stack_outputs(&aws_resources);
+    assert_eq!(outputs.len(), 4);
+    assert_eq!(outputs["vpc_id"], "vpc-123");
+    assert_eq!(outputs["vpc_public_subnet_ids"], "subnet-1,subnet-2");
+    assert_eq!(outputs["eu-west-1/vpc_id"], "vpc-456");
+
+    let encoded = Format::Json.encode(&outputs).unwrap();
+    let decoded: BTreeMap<String, String> = serde_json::from_str(&encoded).unwrap();
+    assert_eq!(decoded, outputs);
+    let encoded = Format::Yaml.encode(&outputs).unwrap();
+    let decoded: BTreeMap<String, String> = serde_yaml::from_str(&encoded).unwrap();
+    assert_eq!(decoded, outputs);
+    assert!(Format::Table.encode(&outputs).is_err());
+}
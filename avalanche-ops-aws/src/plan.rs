@@ -0,0 +1,250 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cost;
+
+/// Recursively compares the JSON objects, and records the changed leaves
+/// with the dot-separated paths. Arrays are compared as a whole.
+pub(crate) fn diff_values(
+    path: &str,
+    before: Option<&serde_json::Value>,
+    after: Option<&serde_json::Value>,
+    changes: &mut Vec<SpecChange>,
+) {
+    if before == after {
+        return;
+    }
+    if let (Some(serde_json::Value::Object(b)), Some(serde_json::Value::Object(a))) =
+        (before, after)
+    {
+        let keys: std::collections::BTreeSet<&String> = b.keys().chain(a.keys()).collect();
+        for k in keys {
+            let p = if path.is_empty() {
+                k.clone()
+            } else {
+                format!("{}.{}", path, k)
+            };
+            diff_values(&p, b.get(k), a.get(k), changes);
+        }
+        return;
+    }
+    changes.push(SpecChange {
+        path: path.to_string(),
+        before: before.cloned(),
+        after: after.cloned(),
+    });
+}
+
+/// Represents the changed spec field between the deployed and the local spec.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SpecChange {
+    /// Dot-separated path of the field (e.g., "avalanchego_config.snow_sample_size").
+    pub path: String,
+    /// "None" if the field is newly added.
+    pub before: Option<serde_json::Value>,
+    /// "None" if the field is removed.
+    pub after: Option<serde_json::Value>,
+}
+
+/// Represents what "apply" would do to each resource.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanAction {
+    Create,
+    Update,
+    /// Exists and "apply" leaves it as is.
+    Keep,
+}
+
+impl PlanAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlanAction::Create => "create",
+            PlanAction::Update => "update",
+            PlanAction::Keep => "keep",
+        }
+    }
+}
+
+/// Represents the AWS resource that "apply" would create or update.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct PlannedResource {
+    /// e.g., "s3-bucket", "kms-key", "cloudformation-stack".
+    pub kind: String,
+    pub name: String,
+    pub region: String,
+    pub action: PlanAction,
+    /// Human-readable details (e.g., change set of the existing stack).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+}
+
+/// Represents the result of "apply --plan", computed without mutating anything.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Plan {
+    pub id: String,
+    pub resources: Vec<PlannedResource>,
+    /// Changes from the spec in the remote storage that the nodes read,
+    /// empty if nothing has been uploaded yet.
+    pub spec_changes: Vec<SpecChange>,
+    /// Monthly cost estimate of the spec, if computed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<cost::Estimate>,
+}
+
+impl Plan {
+    pub fn count(&self, action: PlanAction) -> usize {
+        self.resources.iter().filter(|r| r.action == action).count()
+    }
+
+    pub fn encode_json(&self) -> io::Result<String> {
+        serde_json::to_string_pretty(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize Plan to JSON {}", e),
+            )
+        })
+    }
+}
+
+impl std::fmt::Display for Plan {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "plan for '{}': {} to create, {} to update, {} to keep",
+            self.id,
+            self.count(PlanAction::Create),
+            self.count(PlanAction::Update),
+            self.count(PlanAction::Keep)
+        )?;
+        for r in self.resources.iter() {
+            let sign = match r.action {
+                PlanAction::Create => '+',
+                PlanAction::Update => '~',
+                PlanAction::Keep => '=',
+            };
+            write!(
+                f,
+                "\n  {} {} {} '{}' ({})",
+                sign,
+                r.action.as_str(),
+                r.kind,
+                r.name,
+                r.region
+            )?;
+            for d in r.details.iter() {
+                write!(f, "\n      {}", d)?;
+            }
+        }
+        if !self.spec_changes.is_empty() {
+            write!(f, "\nspec changes:")?;
+        }
+        for c in self.spec_changes.iter() {
+            let show = |v: &Option<serde_json::Value>| match v {
+                Some(v) => v.to_string(),
+                None => String::from("(none)"),
+            };
+            write!(
+                f,
+                "\n  ~ {}: {} -> {}",
+                c.path,
+                show(&c.before),
+                show(&c.after)
+            )?;
+        }
+        if let Some(cost) = &self.cost {
+            write!(f, "\n{}", cost)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_plan() {
+    use serde_json::json;
+
+    let before = json!({
+        "id": "test",
+        "machine": {"anchor_nodes": 3, "non_anchor_nodes": 2},
+        "avalanchego_config": {"log_level": "INFO", "whitelisted_subnets": ["a"]},
+        "removed": true,
+    });
+    let after = json!({
+        "id": "test",
+        "machine": {"anchor_nodes": 3, "non_anchor_nodes": 5},
+        "avalanchego_config": {"log_level": "INFO", "whitelisted_subnets": ["a", "b"]},
+        "added": 1,
+    });
+    let mut spec_changes = Vec::new();
+    diff_values("", Some(&before), Some(&after), &mut spec_changes);
+    assert_eq!(
+        spec_changes,
+        vec![
+            SpecChange {
+                path: String::from("added"),
+                before: None,
+                after: Some(json!(1)),
+            },
+            SpecChange {
+                path: String::from("avalanchego_config.whitelisted_subnets"),
+                before: Some(json!(["a"])),
+                after: Some(json!(["a", "b"])),
+            },
+            SpecChange {
+                path: String::from("machine.non_anchor_nodes"),
+                before: Some(json!(2)),
+                after: Some(json!(5)),
+            },
+            SpecChange {
+                path: String::from("removed"),
+                before: Some(json!(true)),
+                after: None,
+            },
+        ]
+    );
+
+    let mut unchanged = Vec::new();
+    diff_values("", Some(&before), Some(&before), &mut unchanged);
+    assert!(unchanged.is_empty());
+
+    let plan = Plan {
+        id: String::from("test"),
+        resources: vec![
+            PlannedResource {
+                kind: String::from("kms-key"),
+                name: String::from("test-cmk"),
+                region: String::from("us-west-2"),
+                action: PlanAction::Create,
+                details: Vec::new(),
+            },
+            PlannedResource {
+                kind: String::from("cloudformation-stack"),
+                name: String::from("test-vpc"),
+                region: String::from("us-west-2"),
+                action: PlanAction::Keep,
+                details: vec![String::from("Modify VPC (AWS::EC2::VPC)")],
+            },
+        ],
+        spec_changes: spec_changes[2..3].to_vec(),
+        cost: None,
+    };
+    assert_eq!(plan.count(PlanAction::Create), 1);
+    assert_eq!(plan.count(PlanAction::Update), 0);
+    assert_eq!(
+        plan.to_string(),
+        "plan for 'test': 1 to create, 0 to update, 1 to keep
+  + create kms-key 'test-cmk' (us-west-2)
+  = keep cloudformation-stack 'test-vpc' (us-west-2)
+      Modify VPC (AWS::EC2::VPC)
+spec changes:
+  ~ machine.non_anchor_nodes: 2 -> 5"
+    );
+
+    let encoded = plan.encode_json().unwrap();
+    let decoded: Plan = serde_json::from_str(&encoded).unwrap();
+    assert_eq!(plan, decoded);
+}
@@ -0,0 +1,300 @@
+use std::{
+    fs,
+    io::{self, Error, ErrorKind},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use aws_types::SdkConfig as AwsSdkConfig;
+use serde::{Deserialize, Serialize};
+
+use avalanche_types::node;
+use aws::{autoscaling, cloudwatch, ec2, envelope, kms, s3};
+use utils::random;
+
+use crate::Spec;
+
+/// Machine of the fleet, as reported by the provider.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Machine {
+    pub machine_id: String,
+    /// "None" if the provider has no node kind for the machine.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Failure domain of the machine (e.g., availability zone),
+    /// which "events rolling-upgrade" waves by.
+    pub zone: String,
+    pub public_ip: String,
+    /// Provider-specific state (e.g., "running").
+    pub state: String,
+}
+
+/// Metric datum that the provider publishes (e.g., the rollout progress).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metric {
+    pub name: String,
+    pub value: f64,
+    pub dimensions: Vec<(String, String)>,
+}
+
+/// Cloud layer under "apply", "delete", and the "events" flows, so that
+/// other backends (e.g., bare-metal machines over SSH) run the same flows.
+/// The storage keys are "StorageNamespace" encoded, shared with "avalanched".
+#[async_trait]
+pub trait Provider: Send + Sync {
+    /// Name of the backend (e.g., "aws").
+    fn name(&self) -> &str;
+
+    /// Uploads the local file to the network storage.
+    async fn put_file(&self, file_path: &str, key: &str) -> io::Result<()>;
+
+    /// Uploads the bytes to the network storage.
+    async fn put_object(&self, key: &str, d: &[u8]) -> io::Result<()> {
+        let tmp_path = random::tmp_path(15, None)?;
+        fs::write(&tmp_path, d)?;
+        let ret = self.put_file(&tmp_path, key).await;
+        fs::remove_file(&tmp_path)?;
+        ret
+    }
+
+    /// Returns "None" if the key does not exist.
+    async fn get_object(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+
+    /// Lists the keys under the prefix.
+    async fn list_objects(&self, prefix: &str) -> io::Result<Vec<String>>;
+
+    /// Deletes all keys under the prefix.
+    async fn delete_objects(&self, prefix: &str) -> io::Result<()>;
+
+    /// Encrypts the secret (e.g., staking key) with the key of the network.
+    async fn seal(&self, d: &[u8]) -> io::Result<Vec<u8>>;
+
+    /// Decrypts the secret from "seal".
+    async fn unseal(&self, d: &[u8]) -> io::Result<Vec<u8>>;
+
+    async fn put_metrics(&self, namespace: &str, metrics: &[Metric]) -> io::Result<()>;
+
+    /// Lists the machines by their IDs, skipping the unknown ones.
+    async fn list_machines(&self, machine_ids: &[String]) -> io::Result<Vec<Machine>>;
+
+    /// Lists the machines of the node kind.
+    async fn list_fleet(&self, kind: &node::Kind) -> io::Result<Vec<Machine>>;
+
+    /// Launches or terminates the machines of the node kind
+    /// to the desired count.
+    async fn provision_fleet(&self, kind: &node::Kind, desired: u32) -> io::Result<()>;
+}
+
+/// Returns the provider of the spec.
+pub async fn new_provider(spec: &Spec) -> io::Result<Box<dyn Provider>> {
+    match &spec.aws_resources {
+        Some(resources) => {
+            let shared_config = aws::load_config(Some(resources.region.clone())).await?;
+            Ok(Box::new(AwsProvider::new(&shared_config, resources)))
+        }
+        None => Err(Error::new(
+            ErrorKind::Unsupported,
+            format!("no provider resources in spec '{}'", spec.id),
+        )),
+    }
+}
+
+/// Runs the flows on S3, KMS, CloudWatch, and the EC2 Auto Scaling Groups
+/// created by "apply".
+pub struct AwsProvider {
+    s3_manager: s3::Manager,
+    s3_bucket: String,
+    envelope: envelope::Envelope,
+    cw_manager: cloudwatch::Manager,
+    ec2_manager: ec2::Manager,
+    autoscaling_manager: autoscaling::Manager,
+    resources: aws::Resources,
+}
+
+impl AwsProvider {
+    pub fn new(shared_config: &AwsSdkConfig, resources: &aws::Resources) -> Self {
+        Self {
+            s3_manager: s3::Manager::new(shared_config),
+            s3_bucket: resources.s3_bucket.clone(),
+            envelope: envelope::Envelope::new(
+                Some(kms::Manager::new(shared_config)),
+                resources.kms_cmk_id.clone(),
+            ),
+            cw_manager: cloudwatch::Manager::new(shared_config),
+            ec2_manager: ec2::Manager::new(shared_config),
+            autoscaling_manager: autoscaling::Manager::new(shared_config),
+            resources: resources.clone(),
+        }
+    }
+
+    fn asg_name(&self, kind: &node::Kind) -> io::Result<String> {
+        asg_name(&self.resources, kind).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("no ASG for {} nodes (run 'apply' first)", kind.as_str()),
+            )
+        })
+    }
+}
+
+/// Returns the Auto Scaling Group of the node kind in the home region,
+/// "None" if "apply" has not created it.
+pub fn asg_name(resources: &aws::Resources, kind: &node::Kind) -> Option<String> {
+    match kind {
+        node::Kind::Anchor => resources.cloudformation_asg_anchor_nodes_logical_id.clone(),
+        node::Kind::NonAnchor => resources
+            .cloudformation_asg_non_anchor_nodes_logical_id
+            .clone(),
+        node::Kind::Api => resources.cloudformation_asg_api_nodes_logical_id.clone(),
+    }
+}
+
+fn to_machine(d: ec2::Droplet) -> Machine {
+    Machine {
+        machine_id: d.instance_id,
+        kind: d.node_kind,
+        zone: d.availability_zone,
+        public_ip: d.public_ipv4,
+        state: d.instance_state_name,
+    }
+}
+
+#[async_trait]
+impl Provider for AwsProvider {
+    fn name(&self) -> &str {
+        "aws"
+    }
+
+    async fn put_file(&self, file_path: &str, key: &str) -> io::Result<()> {
+        self.s3_manager
+            .put_object(
+                Arc::new(file_path.to_string()),
+                Arc::new(self.s3_bucket.clone()),
+                Arc::new(key.to_string()),
+            )
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn get_object(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        let objects = self
+            .s3_manager
+            .list_objects(
+                Arc::new(self.s3_bucket.clone()),
+                Some(Arc::new(key.to_string())),
+            )
+            .await?;
+        if !objects.iter().any(|o| o.key() == Some(key)) {
+            return Ok(None);
+        }
+
+        let tmp_path = random::tmp_path(15, None)?;
+        self.s3_manager
+            .get_object(
+                Arc::new(self.s3_bucket.clone()),
+                Arc::new(key.to_string()),
+                Arc::new(tmp_path.clone()),
+            )
+            .await?;
+        let d = fs::read(&tmp_path)?;
+        fs::remove_file(&tmp_path)?;
+        Ok(Some(d))
+    }
+
+    async fn list_objects(&self, prefix: &str) -> io::Result<Vec<String>> {
+        let objects = self
+            .s3_manager
+            .list_objects(
+                Arc::new(self.s3_bucket.clone()),
+                Some(Arc::new(prefix.to_string())),
+            )
+            .await?;
+        Ok(objects
+            .iter()
+            .filter_map(|o| o.key().map(String::from))
+            .collect())
+    }
+
+    async fn delete_objects(&self, prefix: &str) -> io::Result<()> {
+        // empty prefix empties the whole bucket
+        if prefix.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "empty prefix to delete",
+            ));
+        }
+        self.s3_manager
+            .delete_objects(
+                Arc::new(self.s3_bucket.clone()),
+                Some(Arc::new(prefix.to_string())),
+            )
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn seal(&self, d: &[u8]) -> io::Result<Vec<u8>> {
+        self.envelope.seal_aes_256(d).await.map_err(Error::from)
+    }
+
+    async fn unseal(&self, d: &[u8]) -> io::Result<Vec<u8>> {
+        self.envelope.unseal_aes_256(d).await.map_err(Error::from)
+    }
+
+    async fn put_metrics(&self, namespace: &str, metrics: &[Metric]) -> io::Result<()> {
+        let mut data = Vec::with_capacity(metrics.len());
+        for m in metrics.iter() {
+            let dimensions: Vec<(&str, &str)> = m
+                .dimensions
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str()))
+                .collect();
+            data.extend(cloudwatch::with_dimensions(
+                vec![cloudwatch::new_datum(&m.name, m.value)],
+                &dimensions,
+            ));
+        }
+        self.cw_manager
+            .put_metric_data(Arc::new(namespace.to_string()), Arc::new(data))
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn list_machines(&self, machine_ids: &[String]) -> io::Result<Vec<Machine>> {
+        let droplets = self.ec2_manager.list_instances(machine_ids).await?;
+        Ok(droplets.into_iter().map(to_machine).collect())
+    }
+
+    async fn list_fleet(&self, kind: &node::Kind) -> io::Result<Vec<Machine>> {
+        let asg_name = self.asg_name(kind)?;
+        let droplets = self.ec2_manager.list_asg(&asg_name).await?;
+        Ok(droplets.into_iter().map(to_machine).collect())
+    }
+
+    async fn provision_fleet(&self, kind: &node::Kind, desired: u32) -> io::Result<()> {
+        let asg_name = self.asg_name(kind)?;
+        self.autoscaling_manager
+            .set_desired_capacity(&asg_name, desired)
+            .await
+            .map_err(Error::from)
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-ops-aws --lib -- provider::test_asg_name --exact --show-output
+#[test]
+fn test_asg_name() {
+    let resources = aws::Resources {
+        cloudformation_asg_anchor_nodes_logical_id: Some(String::from("anchor-asg")),
+        cloudformation_asg_non_anchor_nodes_logical_id: Some(String::from("non-anchor-asg")),
+        ..aws::Resources::default()
+    };
+    assert_eq!(
+        asg_name(&resources, &node::Kind::Anchor),
+        Some(String::from("anchor-asg"))
+    );
+    assert_eq!(
+        asg_name(&resources, &node::Kind::NonAnchor),
+        Some(String::from("non-anchor-asg"))
+    );
+    assert_eq!(asg_name(&resources, &node::Kind::Api), None);
+}
@@ -0,0 +1,249 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    io::{self, Error, ErrorKind},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Source of the IP address that the node advertises to its peers.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum PublicIpSource {
+    /// Public IPv4 from the EC2 instance metadata (IMDSv2).
+    Imds,
+    /// Reflexive address from the STUN servers in "stun_servers".
+    Stun,
+    /// Address returned by the HTTPS echo services in "echo_urls".
+    Echo,
+    /// Public source address of the default route,
+    /// for the hosts with the public address on the network interface.
+    Interface,
+}
+
+impl PublicIpSource {
+    pub fn as_str(&self) -> &str {
+        match self {
+            PublicIpSource::Imds => "imds",
+            PublicIpSource::Stun => "stun",
+            PublicIpSource::Echo => "echo",
+            PublicIpSource::Interface => "interface",
+        }
+    }
+}
+
+/// Defines how "avalanched" discovers the IP address that the node
+/// advertises ("public-ip" in the avalanchego config), so the nodes
+/// without the EC2 public IPv4 (e.g., behind the NAT, bare-metal) can join.
+/// The anchor nodes with "anchor_endpoints.elastic_ips" always advertise
+/// their Elastic IPs.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct PublicIpPolicy {
+    /// Addresses pinned by the machine ID, used as-is before "sources".
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overrides: BTreeMap<String, String>,
+    /// Sources tried in order, the first public address wins.
+    #[serde(default = "default_public_ip_sources")]
+    pub sources: Vec<PublicIpSource>,
+    /// STUN servers in "host:port".
+    #[serde(default = "default_stun_servers")]
+    pub stun_servers: Vec<String>,
+    /// HTTPS services that return the caller address in the body.
+    #[serde(default = "default_echo_urls")]
+    pub echo_urls: Vec<String>,
+    /// Set to true to resolve the address from the next source as well,
+    /// and fail if the two disagree (e.g., the instance metadata
+    /// reports the address that is not routed to the host).
+    #[serde(default)]
+    pub cross_check: bool,
+    /// Interval to resolve the address again. On change, "avalanched"
+    /// rewrites the avalanchego config, restarts the node, and
+    /// re-registers it for discovery. Zero disables the re-detection.
+    #[serde(default = "default_redetect_interval_seconds")]
+    pub redetect_interval_seconds: u64,
+}
+
+fn default_public_ip_sources() -> Vec<PublicIpSource> {
+    vec![
+        PublicIpSource::Imds,
+        PublicIpSource::Stun,
+        PublicIpSource::Echo,
+        PublicIpSource::Interface,
+    ]
+}
+
+fn default_stun_servers() -> Vec<String> {
+    vec![
+        String::from("stun.l.google.com:19302"),
+        String::from("stun.cloudflare.com:3478"),
+    ]
+}
+
+fn default_echo_urls() -> Vec<String> {
+    vec![
+        String::from("https://checkip.amazonaws.com"),
+        String::from("https://api.ipify.org"),
+    ]
+}
+
+fn default_redetect_interval_seconds() -> u64 {
+    300
+}
+
+impl Default for PublicIpPolicy {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl PublicIpPolicy {
+    pub fn default() -> Self {
+        Self {
+            overrides: BTreeMap::new(),
+            sources: default_public_ip_sources(),
+            stun_servers: default_stun_servers(),
+            echo_urls: default_echo_urls(),
+            cross_check: false,
+            redetect_interval_seconds: default_redetect_interval_seconds(),
+        }
+    }
+
+    /// Used when "Spec.public_ip_policy" is "None",
+    /// advertising the public IPv4 of the EC2 instance.
+    pub fn imds_only() -> Self {
+        Self {
+            sources: vec![PublicIpSource::Imds],
+            redetect_interval_seconds: 0,
+            ..Self::default()
+        }
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        for (machine_id, ip) in self.overrides.iter() {
+            if ip.parse::<std::net::IpAddr>().is_err() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid override IP '{}' for '{}'", ip, machine_id),
+                ));
+            }
+        }
+        if self.sources.is_empty() && self.overrides.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'public_ip_policy.sources' is empty",
+            ));
+        }
+        let mut seen = HashSet::new();
+        for source in self.sources.iter() {
+            if !seen.insert(source.as_str()) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("duplicate public IP source '{}'", source.as_str()),
+                ));
+            }
+        }
+        if seen.contains(PublicIpSource::Stun.as_str()) {
+            if self.stun_servers.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "source 'stun' requires 'stun_servers'",
+                ));
+            }
+            for server in self.stun_servers.iter() {
+                let port = server.rsplit_once(':').map(|(_, p)| p.parse::<u16>());
+                if !matches!(port, Some(Ok(_))) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("invalid STUN server '{}' (expected 'host:port')", server),
+                    ));
+                }
+            }
+        }
+        if seen.contains(PublicIpSource::Echo.as_str()) {
+            if self.echo_urls.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "source 'echo' requires 'echo_urls'",
+                ));
+            }
+            // the plain HTTP response could be rewritten on the way
+            if let Some(url) = self.echo_urls.iter().find(|u| !u.starts_with("https://")) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("echo URL '{}' must be 'https'", url),
+                ));
+            }
+        }
+        if self.cross_check && self.sources.len() < 2 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'cross_check' requires at least 2 sources",
+            ));
+        }
+        if self.redetect_interval_seconds > 0 && self.redetect_interval_seconds < 60 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'redetect_interval_seconds' {} too short (at least 60, or 0 to disable)",
+                    self.redetect_interval_seconds
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_public_ip_policy() {
+    let policy = PublicIpPolicy::default();
+    policy.validate().unwrap();
+    PublicIpPolicy::imds_only().validate().unwrap();
+
+    let policy: PublicIpPolicy = serde_yaml::from_str(
+        "
+overrides:
+  metal-1: 203.0.113.7
+sources:
+  - stun
+  - interface
+cross_check: true
+",
+    )
+    .unwrap();
+    policy.validate().unwrap();
+    assert_eq!(
+        policy.sources,
+        vec![PublicIpSource::Stun, PublicIpSource::Interface]
+    );
+    assert_eq!(policy.stun_servers, default_stun_servers());
+    assert_eq!(policy.redetect_interval_seconds, 300);
+
+    let mut invalid = policy.clone();
+    invalid
+        .overrides
+        .insert(String::from("metal-2"), String::from("metal-2"));
+    assert!(invalid.validate().is_err());
+    let mut invalid = policy.clone();
+    invalid.sources.push(PublicIpSource::Stun);
+    assert!(invalid.validate().is_err());
+    let mut invalid = policy.clone();
+    invalid.stun_servers = vec![String::from("stun.l.google.com")];
+    assert!(invalid.validate().is_err());
+    let mut invalid = policy.clone();
+    invalid.sources = vec![PublicIpSource::Stun];
+    assert!(invalid.validate().is_err());
+    let mut invalid = policy.clone();
+    invalid.redetect_interval_seconds = 10;
+    assert!(invalid.validate().is_err());
+    let invalid = PublicIpPolicy {
+        echo_urls: vec![String::from("http://checkip.amazonaws.com")],
+        ..PublicIpPolicy::default()
+    };
+    assert!(invalid.validate().is_err());
+
+    // the overrides alone pin every node
+    let mut pinned = policy;
+    pinned.sources.clear();
+    pinned.cross_check = false;
+    pinned.validate().unwrap();
+}
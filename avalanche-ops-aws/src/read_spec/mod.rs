@@ -1,6 +1,9 @@
-use std::io;
+use std::io::{self, Write};
 
 use clap::{Arg, Command};
+use serde::Serialize;
+
+use avalanche_ops_aws::output;
 
 pub const NAME: &str = "read-spec";
 
@@ -61,6 +64,21 @@ pub fn command() -> Command<'static> {
         )
 }
 
+/// Selected fields of the spec, for the structured output.
+#[derive(Debug, Default, Serialize)]
+struct Fields {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance_ids: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    public_ips: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nlb_endpoint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_endpoints: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    node_ids: Option<Vec<String>>,
+}
+
 pub fn execute(
     spec_file_path: &str,
     instance_ids: bool,
@@ -68,31 +86,38 @@ pub fn execute(
     nlb_endpoint: bool,
     http_endpoints: bool,
     node_ids: bool,
+    output: output::Format,
 ) -> io::Result<()> {
     let spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
+    if !(instance_ids || public_ips || nlb_endpoint || http_endpoints || node_ids) {
+        // nothing selected, so describe the whole cluster
+        return output.print(&output::Cluster::from_spec(&spec));
+    }
+
     let current_nodes = spec
         .current_nodes
+        .clone()
         .expect("unexpected None current_nodes in spec file");
-
+    let mut fields = Fields::default();
     if instance_ids {
         let mut rs = Vec::new();
         for node in current_nodes.iter() {
             rs.push(node.machine_id.clone());
         }
-        println!("{}", rs.join(","));
+        writeln!(output.console(), "{}", rs.join(","))?;
+        fields.instance_ids = Some(rs);
     };
-
     if public_ips {
         let mut rs = Vec::new();
         for node in current_nodes.iter() {
             rs.push(node.public_ip.clone());
         }
-        println!("{}", rs.join(","));
+        writeln!(output.console(), "{}", rs.join(","))?;
+        fields.public_ips = Some(rs);
     };
-
     if nlb_endpoint {
+        let nlb_https_enabled = spec.load_balancer_https_enabled();
         let aws_resources = spec.aws_resources.expect("unexpected None aws_resources");
-        let nlb_https_enabled = aws_resources.nlb_acm_certificate_arn.is_some();
         let dns_name = aws_resources.cloudformation_asg_nlb_dns_name.unwrap();
         let (scheme_for_dns, port_for_dns) = {
             if nlb_https_enabled {
@@ -101,27 +126,29 @@ pub fn execute(
                 ("http", spec.avalanchego_config.http_port)
             }
         };
-        println!(
+        let endpoint = format!(
             "{}://{}:{}/ext/metrics",
             scheme_for_dns, dns_name, port_for_dns
         );
+        writeln!(output.console(), "{}", endpoint)?;
+        fields.nlb_endpoint = Some(endpoint);
     };
-
     if http_endpoints {
         let mut rs = Vec::new();
         for node in current_nodes.iter() {
             rs.push(node.http_endpoint.clone());
         }
-        println!("{}", rs.join(","));
+        writeln!(output.console(), "{}", rs.join(","))?;
+        fields.http_endpoints = Some(rs);
     };
-
     if node_ids {
         let mut rs = Vec::new();
         for node in current_nodes.iter() {
             rs.push(node.node_id.clone());
         }
-        println!("{}", rs.join(","));
+        writeln!(output.console(), "{}", rs.join(","))?;
+        fields.node_ids = Some(rs);
     };
 
-    Ok(())
+    output.print(&fields)
 }
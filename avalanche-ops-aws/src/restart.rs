@@ -0,0 +1,165 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+/// Defines how the avalanchego process is restarted on failures.
+/// systemd restarts the process after "restart_delay_seconds",
+/// and gives up once it fails more than "max_restarts" times within
+/// "restart_interval_seconds" (crash loop). "avalanched" then restarts
+/// the service again with the exponential backoff, up to "max_crash_loops" times.
+/// ref. https://www.freedesktop.org/software/systemd/man/systemd.unit.html#StartLimitIntervalSec=interval
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct RestartPolicy {
+    /// systemd "RestartSec".
+    #[serde(default = "default_restart_delay_seconds")]
+    pub restart_delay_seconds: u64,
+    /// systemd "StartLimitBurst".
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+    /// systemd "StartLimitIntervalSec".
+    #[serde(default = "default_restart_interval_seconds")]
+    pub restart_interval_seconds: u64,
+
+    /// Initial backoff before restarting the service out of a crash loop,
+    /// doubled on every consecutive crash loop.
+    #[serde(default = "default_initial_backoff_seconds")]
+    pub initial_backoff_seconds: u64,
+    #[serde(default = "default_max_backoff_seconds")]
+    pub max_backoff_seconds: u64,
+    /// Zero to leave the service failed on the first crash loop.
+    #[serde(default = "default_max_crash_loops")]
+    pub max_crash_loops: u32,
+}
+
+fn default_restart_delay_seconds() -> u64 {
+    5
+}
+
+fn default_max_restarts() -> u32 {
+    5
+}
+
+fn default_restart_interval_seconds() -> u64 {
+    300
+}
+
+fn default_initial_backoff_seconds() -> u64 {
+    60
+}
+
+fn default_max_backoff_seconds() -> u64 {
+    1800
+}
+
+fn default_max_crash_loops() -> u32 {
+    10
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl RestartPolicy {
+    pub fn default() -> Self {
+        Self {
+            restart_delay_seconds: default_restart_delay_seconds(),
+            max_restarts: default_max_restarts(),
+            restart_interval_seconds: default_restart_interval_seconds(),
+            initial_backoff_seconds: default_initial_backoff_seconds(),
+            max_backoff_seconds: default_max_backoff_seconds(),
+            max_crash_loops: default_max_crash_loops(),
+        }
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        if self.restart_delay_seconds == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'restart_delay_seconds' must be >0",
+            ));
+        }
+        if self.max_restarts == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'max_restarts' must be >0",
+            ));
+        }
+        // otherwise, systemd never hits the start limit
+        if self.restart_interval_seconds <= self.restart_delay_seconds * self.max_restarts as u64 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'restart_interval_seconds' {} must be >'restart_delay_seconds' x 'max_restarts' ({})",
+                    self.restart_interval_seconds,
+                    self.restart_delay_seconds * self.max_restarts as u64
+                ),
+            ));
+        }
+        if self.initial_backoff_seconds == 0
+            || self.initial_backoff_seconds > self.max_backoff_seconds
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'initial_backoff_seconds' {} must be in (0, 'max_backoff_seconds' {}]",
+                    self.initial_backoff_seconds, self.max_backoff_seconds
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the backoff before restarting out of the n-th consecutive
+    /// crash loop (starting from 1), or "None" if the crash loops exceed the limit.
+    pub fn backoff_seconds(&self, crash_loops: u32) -> Option<u64> {
+        if crash_loops == 0 || crash_loops > self.max_crash_loops {
+            return None;
+        }
+        let backoff = 2_u64
+            .checked_pow(crash_loops - 1)
+            .and_then(|m| m.checked_mul(self.initial_backoff_seconds))
+            .unwrap_or(self.max_backoff_seconds);
+        Some(backoff.min(self.max_backoff_seconds))
+    }
+}
+
+#[test]
+fn test_restart_policy() {
+    let policy = RestartPolicy::default();
+    policy.validate().unwrap();
+    assert_eq!(policy.backoff_seconds(0), None);
+    assert_eq!(policy.backoff_seconds(1), Some(60));
+    assert_eq!(policy.backoff_seconds(2), Some(120));
+    assert_eq!(policy.backoff_seconds(5), Some(960));
+    assert_eq!(policy.backoff_seconds(6), Some(1800));
+    assert_eq!(policy.backoff_seconds(10), Some(1800));
+    assert_eq!(policy.backoff_seconds(11), None);
+
+    let policy: RestartPolicy = serde_yaml::from_str(
+        "
+max_restarts: 3
+max_crash_loops: 0
+",
+    )
+    .unwrap();
+    assert_eq!(policy.restart_delay_seconds, 5);
+    policy.validate().unwrap();
+    assert_eq!(policy.backoff_seconds(1), None);
+
+    let mut huge = RestartPolicy::default();
+    huge.max_crash_loops = 100;
+    assert_eq!(huge.backoff_seconds(100), Some(1800));
+
+    let mut invalid = RestartPolicy::default();
+    invalid.restart_interval_seconds = 25;
+    assert!(invalid.validate().is_err());
+    let mut invalid = RestartPolicy::default();
+    invalid.max_restarts = 0;
+    assert!(invalid.validate().is_err());
+    let mut invalid = RestartPolicy::default();
+    invalid.initial_backoff_seconds = 3600;
+    assert!(invalid.validate().is_err());
+}
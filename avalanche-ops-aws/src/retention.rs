@@ -0,0 +1,143 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Spec;
+
+/// Defines the resources that "delete" keeps while removing the compute
+/// (ASGs, VPC, and instance role), so that "apply" with the same spec
+/// recreates the network from the retained state. For instance, the new
+/// nodes attach the retained data volumes or restore from the snapshots,
+/// and the retained KMS key still decrypts the envelope-encrypted objects.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct RetentionPolicy {
+    /// Keeps the S3 buckets and objects (e.g., spec, encrypted keys, install artifacts),
+    /// so "--delete-s3-objects" and "--delete-s3-bucket" are rejected.
+    #[serde(default)]
+    pub s3: bool,
+    /// Keeps the detached data volumes.
+    #[serde(default)]
+    pub ebs_volumes: bool,
+    /// Keeps the data volume snapshots.
+    #[serde(default)]
+    pub ebs_snapshots: bool,
+    /// Keeps the KMS key rather than scheduling its deletion.
+    #[serde(default)]
+    pub kms_key: bool,
+    /// Keeps the EC2 key pairs and the local key files.
+    #[serde(default)]
+    pub ec2_key_pair: bool,
+    /// Keeps the CloudWatch log group,
+    /// so "--delete-cloudwatch-log-group" is rejected.
+    #[serde(default)]
+    pub cloudwatch_log_group: bool,
+    /// Keeps the Elastic IPs and the DNS records of the anchor nodes,
+    /// so the re-created network is reachable at the same endpoints.
+    #[serde(default)]
+    pub elastic_ips: bool,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl RetentionPolicy {
+    pub fn default() -> Self {
+        Self {
+            s3: false,
+            ebs_volumes: false,
+            ebs_snapshots: false,
+            kms_key: false,
+            ec2_key_pair: false,
+            cloudwatch_log_group: false,
+            elastic_ips: false,
+        }
+    }
+
+    /// Returns true if "delete" keeps any resource.
+    pub fn retains_any(&self) -> bool {
+        self.s3
+            || self.ebs_volumes
+            || self.ebs_snapshots
+            || self.kms_key
+            || self.ec2_key_pair
+            || self.cloudwatch_log_group
+            || self.elastic_ips
+    }
+
+    pub fn validate(&self, spec: &Spec) -> io::Result<()> {
+        if (self.ebs_volumes || self.ebs_snapshots) && spec.machine.data_volume.is_none() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'retention_policy' retains EBS volumes or snapshots without 'machine.data_volume'",
+            ));
+        }
+        if self.elastic_ips
+            && !spec
+                .machine
+                .anchor_endpoints
+                .as_ref()
+                .map_or(false, |v| v.elastic_ips)
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'retention_policy.elastic_ips' without 'machine.anchor_endpoints.elastic_ips'",
+            ));
+        }
+        // the retained key would be unused, since the S3 objects
+        // encrypted with the key are deleted
+        if self.kms_key && !self.s3 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'retention_policy.kms_key' requires 'retention_policy.s3'",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Rejects the "delete" flags that contradict the retention.
+    pub fn check_delete_flags(
+        &self,
+        delete_cloudwatch_log_group: bool,
+        delete_s3_objects: bool,
+        delete_s3_bucket: bool,
+    ) -> io::Result<()> {
+        if self.cloudwatch_log_group && delete_cloudwatch_log_group {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'--delete-cloudwatch-log-group' conflicts with 'retention_policy.cloudwatch_log_group'",
+            ));
+        }
+        if self.s3 && (delete_s3_objects || delete_s3_bucket) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'--delete-s3-objects' and '--delete-s3-bucket' conflict with 'retention_policy.s3'",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_retention_policy() {
+    let policy: RetentionPolicy = serde_yaml::from_str("kms_key: true\ns3: true\n").unwrap();
+    assert!(policy.s3);
+    assert!(policy.kms_key);
+    assert!(!policy.ebs_snapshots);
+    assert!(policy.retains_any());
+    assert!(!RetentionPolicy::default().retains_any());
+
+    assert!(policy.check_delete_flags(true, false, false).is_ok());
+    assert!(policy.check_delete_flags(false, true, false).is_err());
+    assert!(policy.check_delete_flags(false, false, true).is_err());
+
+    let logs = RetentionPolicy {
+        cloudwatch_log_group: true,
+        ..RetentionPolicy::default()
+    };
+    assert!(logs.check_delete_flags(true, false, false).is_err());
+    assert!(logs.check_delete_flags(false, true, true).is_ok());
+}
@@ -0,0 +1,148 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+/// Minimum time between the rotations of two nodes, on top of the time
+/// for the rotated node to become healthy, so that the fleet changes
+/// one node ID at a time and the peers reconnect in between.
+pub const ROTATE_CERT_SPACING_SECONDS: u64 = 10 * 60;
+
+/// Returns the ID of the lock that paces the certificate rotations
+/// across the fleet, held by one node at a time.
+pub fn rotate_cert_lock_id(id: &str) -> String {
+    format!("{}/rotate-cert", id)
+}
+
+/// Represents the staking certificate rotation in "StorageNamespace::EventsRotateCert",
+/// which "avalanched" applies once per version, after the node stops validating.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct RotateCertEvent {
+    /// Increases on every new event (unix timestamp of the request),
+    /// so that the failed nodes retry only when the event is posted again.
+    pub version: u64,
+    /// True to register the new node ID as a primary network validator
+    /// with "Spec.validator_registration" after the rotation.
+    pub reregister_validator: bool,
+}
+
+impl RotateCertEvent {
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize RotateCertEvent to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+
+    /// Returns true if the node status is the final result of this event.
+    /// The scheduled rotation is still to be applied.
+    pub fn is_applied(&self, status: &RotateCertStatus) -> bool {
+        self.version == status.version && status.state != RotateCertState::Scheduled
+    }
+}
+
+/// Result of applying "RotateCertEvent" on each node.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum RotateCertState {
+    /// Waiting for the current or pending validation period to end.
+    Scheduled,
+    Rotated,
+    /// Restored the previous certificate (e.g., the node did not become healthy).
+    Failed,
+}
+
+impl RotateCertState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RotateCertState::Scheduled => "scheduled",
+            RotateCertState::Rotated => "rotated",
+            RotateCertState::Failed => "failed",
+        }
+    }
+}
+
+/// Represents the "RotateCertEvent" result of the node
+/// in "StorageNamespace::EventsRotateCertStatus".
+/// Keyed by the machine ID, since the rotation changes the node ID.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct RotateCertStatus {
+    pub machine_id: String,
+    /// Node ID of the certificate before the rotation.
+    pub old_node_id: String,
+    /// Node ID of the new certificate, only set once rotated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_node_id: Option<String>,
+    pub version: u64,
+    pub state: RotateCertState,
+    /// Copied from the event, so that "avalanched" knows on restart
+    /// whether to register the new node ID.
+    pub reregister_validator: bool,
+    /// Unix timestamp of the validation end, after which the rotation is applied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled_after: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Unix timestamp of the result.
+    pub updated_at: u64,
+}
+
+impl RotateCertStatus {
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize RotateCertStatus to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+}
+
+#[test]
+fn test_rotate_cert_event() {
+    let event = RotateCertEvent {
+        version: 1650000000,
+        reregister_validator: true,
+    };
+    let decoded = RotateCertEvent::decode_yaml(event.encode_yaml().unwrap().as_bytes()).unwrap();
+    assert_eq!(decoded, event);
+
+    let mut status = RotateCertStatus {
+        machine_id: String::from("i-123123"),
+        old_node_id: String::from("NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg"),
+        new_node_id: None,
+        version: event.version,
+        state: RotateCertState::Scheduled,
+        reregister_validator: event.reregister_validator,
+        scheduled_after: Some(1650086400),
+        message: None,
+        updated_at: 1650000000,
+    };
+    let decoded = RotateCertStatus::decode_yaml(status.encode_yaml().unwrap().as_bytes()).unwrap();
+    assert_eq!(decoded, status);
+    assert!(!event.is_applied(&status));
+
+    status.state = RotateCertState::Rotated;
+    status.new_node_id = Some(String::from("NodeID-MFrZFVCXPv5iCn6M9K6XduxGTYp891xXZ"));
+    assert!(event.is_applied(&status));
+    status.version += 1;
+    assert!(!event.is_applied(&status));
+
+    assert_eq!(
+        rotate_cert_lock_id("aops-custom-abc"),
+        "aops-custom-abc/rotate-cert"
+    );
+}
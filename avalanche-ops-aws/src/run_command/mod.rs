@@ -0,0 +1,285 @@
+use std::{
+    io::{self, Error, ErrorKind, Write},
+    time::Duration,
+};
+
+use clap::{Arg, Command};
+use crossterm::{
+    execute,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+};
+use dialoguer::{theme::ColorfulTheme, Select};
+use log::info;
+use serde::Serialize;
+use tokio::runtime::Runtime;
+
+use avalanche_ops_aws::output;
+use aws::{self, ssm};
+
+pub const NAME: &str = "run-command";
+
+pub fn command() -> Command<'static> {
+    Command::new(NAME)
+        .about("Runs the predefined command on the nodes via SSM Run Command, and collects the per-node results")
+        .arg(
+            Arg::new("LOG_LEVEL")
+                .long("log-level")
+                .short('l')
+                .help("Sets the log level")
+                .required(false)
+                .takes_value(true)
+                .possible_value("debug")
+                .possible_value("info")
+                .allow_invalid_utf8(false)
+                .default_value("info"),
+        )
+        .arg(
+            Arg::new("SPEC_FILE_PATH")
+                .long("spec-file-path")
+                .short('s')
+                .help("The spec file to load")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("COMMAND")
+                .long("command")
+                .short('c')
+                .help("Sets the command to run on the nodes")
+                .required(true)
+                .takes_value(true)
+                .possible_value("restart-node")
+                .possible_value("set-log-level")
+                .possible_value("profile-dump")
+                .possible_value("status")
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("AVALANCHEGO_LOG_LEVEL")
+                .long("avalanchego-log-level")
+                .help("Sets the log level for 'set-log-level' (e.g., DEBUG)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("NODE_KIND")
+                .long("node-kind")
+                .help("Only runs on the nodes of the kind (runs on all nodes if empty)")
+                .required(false)
+                .takes_value(true)
+                .possible_value("anchor")
+                .possible_value("non-anchor")
+                .possible_value("api")
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("INSTANCE_IDS")
+                .long("instance-ids")
+                .help("Only runs on the instances in 'current_nodes' (comma-separated)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("TIMEOUT_SECONDS")
+                .long("timeout-seconds")
+                .help("Sets the time to wait for the results")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("600"),
+        )
+        .arg(
+            Arg::new("SKIP_PROMPT")
+                .long("skip-prompt")
+                .help("Skips prompt mode")
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        )
+}
+
+/// Per-node results of the command, for the structured output.
+#[derive(Debug, Serialize)]
+struct Results<'a> {
+    command: &'a str,
+    invocations: &'a [ssm::CommandInvocation],
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute(
+    log_level: &str,
+    spec_file_path: &str,
+    command_name: &str,
+    avalanchego_log_level: Option<&str>,
+    node_kind: Option<&str>,
+    instance_ids: Option<&str>,
+    timeout_seconds: &str,
+    skip_prompt: bool,
+    output: output::Format,
+) -> io::Result<()> {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    );
+
+    let spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
+    let timeout_seconds = timeout_seconds.parse::<u64>().map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid --timeout-seconds '{}' ({})", timeout_seconds, e),
+        )
+    })?;
+    let fleet_command = avalanche_ops_aws::FleetCommand::new(command_name, avalanchego_log_level)?;
+    let commands = fleet_command.shell_commands(&spec)?;
+
+    let current_nodes = spec.current_nodes.clone().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            "no 'current_nodes' in the spec (run 'apply' first)",
+        )
+    })?;
+    let selected_ids: Vec<&str> = instance_ids
+        .unwrap_or("")
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let target_ids: Vec<String> = current_nodes
+        .iter()
+        .filter(|n| node_kind.map_or(true, |k| n.kind == k))
+        .filter(|n| selected_ids.is_empty() || selected_ids.contains(&n.machine_id.as_str()))
+        .map(|n| n.machine_id.clone())
+        .collect();
+    if target_ids.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "no node in 'current_nodes' matches the selection",
+        ));
+    }
+
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Blue),
+        Print(format!(
+            "\n'{}' on {} node(s):\n",
+            fleet_command.as_str(),
+            target_ids.len()
+        )),
+        ResetColor
+    )?;
+    for c in commands.iter() {
+        writeln!(output.console(), "$ {}", c)?;
+    }
+    writeln!(output.console(), "\non {}\n", target_ids.join(", "))?;
+
+    if !skip_prompt {
+        let options = &[
+            "No, I am not ready to run the command!",
+            "Yes, let's run the command!",
+        ];
+        let selected = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select your 'run-command' option")
+            .items(&options[..])
+            .default(0)
+            .interact()
+            .unwrap();
+        if selected == 0 {
+            return Ok(());
+        }
+    }
+
+    let rt = Runtime::new().unwrap();
+    let aws_resources = spec
+        .aws_resources
+        .clone()
+        .expect("unexpected None aws_resources");
+    let shared_config = rt
+        .block_on(aws::load_config(Some(aws_resources.region.clone())))
+        .expect("failed to aws::load_config");
+    let ssm_manager = ssm::Manager::new(&shared_config);
+
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Green),
+        Print(format!(
+            "\n\n\nSTEP: running '{}'\n",
+            fleet_command.as_str()
+        )),
+        ResetColor
+    )?;
+    let comment = format!("avalanche-ops {} {}", spec.id, fleet_command.as_str());
+    let mut invocations = Vec::new();
+    for ids in target_ids.chunks(ssm::MAX_INSTANCE_IDS_PER_COMMAND) {
+        let command_id = rt.block_on(ssm_manager.send_shell_commands(
+            ids,
+            &commands,
+            &comment,
+            timeout_seconds as i32,
+        ))?;
+        invocations.extend(rt.block_on(ssm_manager.poll_command(
+            &command_id,
+            ids,
+            Duration::from_secs(timeout_seconds),
+            Duration::from_secs(5),
+        ))?);
+    }
+
+    let mut failed = 0;
+    for inv in invocations.iter() {
+        let node = current_nodes
+            .iter()
+            .find(|n| n.machine_id == inv.instance_id)
+            .expect("unexpected node not in current_nodes");
+        let color = if inv.is_success() {
+            Color::Green
+        } else {
+            failed += 1;
+            Color::Red
+        };
+        execute!(
+            output.console(),
+            SetForegroundColor(color),
+            Print(format!(
+                "\n{} ({}, {}): {} (exit code {})\n",
+                inv.instance_id, node.kind, node.node_id, inv.status, inv.response_code
+            )),
+            ResetColor
+        )?;
+        if !inv.output.is_empty() {
+            writeln!(output.console(), "{}", inv.output.trim_end())?;
+        }
+        if !inv.error.is_empty() {
+            writeln!(output.console(), "{}", inv.error.trim_end())?;
+        }
+    }
+    if fleet_command == avalanche_ops_aws::FleetCommand::ProfileDump {
+        writeln!(
+            output.console(),
+            "\naws s3 ls s3://{}/{}/\n",
+            aws_resources.s3_bucket,
+            avalanche_ops_aws::StorageNamespace::ProfilesDir(spec.id.clone()).encode()
+        )?;
+    }
+
+    info!(
+        "'{}' succeeded on {}/{} node(s)",
+        fleet_command.as_str(),
+        invocations.len() - failed,
+        invocations.len()
+    );
+    // printed before the failure, so that scripts still get the per-node results
+    output.print(&Results {
+        command: fleet_command.as_str(),
+        invocations: &invocations,
+    })?;
+    if failed > 0 {
+        return Err(Error::new(
+            ErrorKind::Other,
+            format!("'{}' failed on {} node(s)", fleet_command.as_str(), failed),
+        ));
+    }
+    Ok(())
+}
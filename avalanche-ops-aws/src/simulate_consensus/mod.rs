@@ -0,0 +1,214 @@
+use std::{
+    io::{self, Error, ErrorKind, Write},
+    str::FromStr,
+};
+
+use clap::{Arg, Command};
+use crossterm::{
+    execute,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+};
+use log::info;
+use serde::Serialize;
+
+use avalanche_ops_aws::output;
+use avalanche_types::consensus::sim;
+
+pub const NAME: &str = "simulate-consensus";
+
+pub fn command() -> Command<'static> {
+    Command::new(NAME)
+        .about("Simulates the Snowball consensus with the spec's snow parameters to evaluate the finality before deploying")
+        .arg(
+            Arg::new("LOG_LEVEL")
+                .long("log-level")
+                .short('l')
+                .help("Sets the log level")
+                .required(false)
+                .takes_value(true)
+                .possible_value("debug")
+                .possible_value("info")
+                .allow_invalid_utf8(false)
+                .default_value("info"),
+        )
+        .arg(
+            Arg::new("SPEC_FILE_PATH")
+                .long("spec-file-path")
+                .short('s')
+                .help("The spec file to load")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("NODES")
+                .long("nodes")
+                .help("Number of validators to simulate (defaults to the anchor and non-anchor nodes in the spec)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("BYZANTINE_FRACTION")
+                .long("byzantine-fraction")
+                .help("Fraction of the validators that always vote for the minority choice")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("INITIAL_SPLIT")
+                .long("initial-split")
+                .help("Fraction of the honest validators that initially prefer the conflicting choice (0 for a virtuous decision)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("0.5"),
+        )
+        .arg(
+            Arg::new("MIN_LATENCY_MS")
+                .long("min-latency-ms")
+                .help("Minimum one-poll latency in milliseconds")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("50"),
+        )
+        .arg(
+            Arg::new("MAX_LATENCY_MS")
+                .long("max-latency-ms")
+                .help("Maximum one-poll latency in milliseconds")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("200"),
+        )
+        .arg(
+            Arg::new("MAX_ROUNDS")
+                .long("max-rounds")
+                .help("Polling rounds before giving up on the finality of a run")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("1000"),
+        )
+        .arg(
+            Arg::new("RUNS")
+                .long("runs")
+                .help("Number of independent runs")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("100"),
+        )
+        .arg(
+            Arg::new("SEED")
+                .long("seed")
+                .help("Seed for the sampling and latencies, to reproduce the report")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("0"),
+        )
+}
+
+#[derive(Debug, Serialize)]
+struct Simulation<'a> {
+    network_id: u32,
+    config: &'a sim::Config,
+    report: &'a sim::Report,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute(
+    log_level: &str,
+    spec_file_path: &str,
+    nodes: Option<&str>,
+    byzantine_fraction: &str,
+    initial_split: &str,
+    min_latency_ms: &str,
+    max_latency_ms: &str,
+    max_rounds: &str,
+    runs: &str,
+    seed: &str,
+    output: output::Format,
+) -> io::Result<()> {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    );
+
+    let spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
+    let nodes = match nodes {
+        Some(v) => parse_arg("nodes", v)?,
+        None => spec.machine.anchor_nodes.unwrap_or(0) + spec.machine.non_anchor_nodes,
+    };
+
+    let cfg = sim::Config {
+        parameters: spec.consensus_parameters(),
+        nodes,
+        byzantine_fraction: parse_arg("byzantine-fraction", byzantine_fraction)?,
+        initial_split: parse_arg("initial-split", initial_split)?,
+        min_latency_ms: parse_arg("min-latency-ms", min_latency_ms)?,
+        max_latency_ms: parse_arg("max-latency-ms", max_latency_ms)?,
+        max_rounds: parse_arg("max-rounds", max_rounds)?,
+        runs: parse_arg("runs", runs)?,
+        seed: parse_arg("seed", seed)?,
+    };
+    info!(
+        "simulating {} run(s) over {} node(s) with {:?}",
+        cfg.runs, cfg.nodes, cfg.parameters
+    );
+    let report = sim::simulate(&cfg)?;
+
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Blue),
+        Print(format!(
+            "\nSimulated consensus of network ID {} (seed {}):\n",
+            spec.avalanchego_config.network_id, cfg.seed
+        )),
+        ResetColor
+    )?;
+    writeln!(
+        output.console(),
+        "{}\n",
+        serde_yaml::to_string(&cfg).unwrap()
+    )?;
+    writeln!(
+        output.console(),
+        "{}\n",
+        serde_yaml::to_string(&report).unwrap()
+    )?;
+
+    if report.safety_violations > 0 {
+        execute!(
+            output.console(),
+            SetForegroundColor(Color::Red),
+            Print(format!(
+                "\n{} run(s) finalized conflicting choices; increase the quorum or beta\n",
+                report.safety_violations
+            )),
+            ResetColor
+        )?;
+    }
+
+    output.print(&Simulation {
+        network_id: spec.avalanchego_config.network_id,
+        config: &cfg,
+        report: &report,
+    })
+}
+
+fn parse_arg<T: FromStr>(name: &str, v: &str) -> io::Result<T>
+where
+    T::Err: std::fmt::Display,
+{
+    v.parse::<T>().map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid --{} '{}' ({})", name, v, e),
+        )
+    })
+}
@@ -0,0 +1,325 @@
+use std::{
+    fs,
+    io::{self, Error, ErrorKind},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use clap::{Arg, Command};
+use crossterm::{
+    execute,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+};
+use dialoguer::{theme::ColorfulTheme, Select};
+use log::info;
+use tokio::runtime::Runtime;
+
+use avalanche_ops_aws::output;
+use aws::{self, ec2, ec2::ebs, s3};
+use utils::random;
+
+pub const NAME: &str = "snapshot";
+
+/// Interval between the snapshot state checks.
+const POLL_INTERVAL_SECONDS: u64 = 30;
+
+pub fn command() -> Command<'static> {
+    Command::new(NAME)
+        .about("Snapshots the data volumes of all nodes, to clone the network with 'clone'")
+        .arg(
+            Arg::new("LOG_LEVEL")
+                .long("log-level")
+                .short('l')
+                .help("Sets the log level")
+                .required(false)
+                .takes_value(true)
+                .possible_value("debug")
+                .possible_value("info")
+                .allow_invalid_utf8(false)
+                .default_value("info"),
+        )
+        .arg(
+            Arg::new("SPEC_FILE_PATH")
+                .long("spec-file-path")
+                .short('s')
+                .help("The spec file to load")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("NAME")
+                .long("name")
+                .help("Sets the snapshot name (unique per network)")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("TIMEOUT_SECONDS")
+                .long("timeout-seconds")
+                .help("Sets the time to wait for the snapshots to complete")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("3600"),
+        )
+        .arg(
+            Arg::new("SKIP_PROMPT")
+                .long("skip-prompt")
+                .help("Skips prompt mode")
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        )
+}
+
+/// Snapshots the data volume of every current node, and publishes the
+/// "NetworkSnapshot" once all snapshots complete. The nodes keep running,
+/// so each snapshot is crash-consistent, the same as the scheduled ones.
+pub fn execute(
+    log_level: &str,
+    spec_file_path: &str,
+    name: &str,
+    timeout_seconds: &str,
+    skip_prompt: bool,
+    output: output::Format,
+) -> io::Result<()> {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    );
+
+    let timeout_seconds = timeout_seconds.parse::<u64>().map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid --timeout-seconds '{}' ({})", timeout_seconds, e),
+        )
+    })?;
+    if name.is_empty() || name.contains('/') {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid snapshot name '{}'", name),
+        ));
+    }
+
+    let spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
+    spec.validate()?;
+    if spec.machine.data_volume.is_none() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "snapshot requires 'machine.data_volume'",
+        ));
+    }
+    let current_nodes = spec.current_nodes.clone().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            "no 'current_nodes' in the spec (run 'apply' first)",
+        )
+    })?;
+    let aws_resources = spec
+        .aws_resources
+        .clone()
+        .expect("unexpected None aws_resources");
+    if aws_resources.regional_resources.is_some() {
+        // EBS snapshots are regional, and the clone is in one region
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "snapshot does not support 'regional_resources'",
+        ));
+    }
+
+    let rt = Runtime::new().unwrap();
+    let shared_config = rt
+        .block_on(aws::load_config(Some(aws_resources.region.clone())))
+        .expect("failed to aws::load_config");
+    let ec2_manager = ec2::Manager::new(&shared_config);
+    let s3_manager = s3::Manager::new(&shared_config);
+
+    let snapshot_key =
+        avalanche_ops_aws::StorageNamespace::NetworkSnapshot(spec.id.clone(), name.to_string())
+            .encode();
+    if crate::subnet::get_object(&rt, &s3_manager, &aws_resources.s3_bucket, &snapshot_key)?
+        .is_some()
+    {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("snapshot '{}' already exists", name),
+        ));
+    }
+
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Blue),
+        Print(format!(
+            "\nSnapshotting the data volumes of {} node(s) as '{}'\n",
+            current_nodes.len(),
+            name
+        )),
+        ResetColor
+    )?;
+    if !skip_prompt {
+        let options = &[
+            "No, I am not ready to snapshot the network!",
+            "Yes, let's snapshot the network!",
+        ];
+        let selected = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select your 'snapshot' option")
+            .items(&options[..])
+            .default(0)
+            .interact()
+            .unwrap();
+        if selected == 0 {
+            return Ok(());
+        }
+    }
+
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Green),
+        Print("\n\n\nSTEP: creating the snapshots of the data volumes\n"),
+        ResetColor
+    )?;
+    let snapshot_tags = avalanche_ops_aws::NetworkSnapshot::tags(&spec.id, name);
+    let mut nodes = Vec::new();
+    for node in current_nodes.iter() {
+        let volume = rt
+            .block_on(
+                ec2_manager.find_attached_volume(&node.machine_id, ebs::DEFAULT_DATA_DEVICE_NAME),
+            )?
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!("no data volume attached to '{}'", node.machine_id),
+                )
+            })?;
+
+        let description = format!("{} snapshot '{}' of {}", spec.id, name, node.machine_id);
+        let mut tags = snapshot_tags.to_vec();
+        tags.push(("NODE_KIND", node.kind.as_str()));
+        tags.push(("MACHINE_ID", node.machine_id.as_str()));
+        let snapshot =
+            rt.block_on(ec2_manager.create_snapshot(&volume.volume_id, &description, &tags))?;
+        nodes.push(avalanche_ops_aws::SnapshotNode {
+            kind: node.kind.clone(),
+            machine_id: node.machine_id.clone(),
+            node_id: node.node_id.clone(),
+            snapshot_id: snapshot.snapshot_id,
+        });
+    }
+
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Green),
+        Print("\n\n\nSTEP: waiting for the snapshots to complete\n"),
+        ResetColor
+    )?;
+    let started = Instant::now();
+    loop {
+        let snapshots = rt.block_on(ec2_manager.list_snapshots(&snapshot_tags))?;
+        let pending: Vec<&str> = nodes
+            .iter()
+            .filter(|n| {
+                !snapshots
+                    .iter()
+                    .any(|s| s.snapshot_id == n.snapshot_id && s.is_completed())
+            })
+            .map(|n| n.snapshot_id.as_str())
+            .collect();
+        if pending.is_empty() {
+            break;
+        }
+        if started.elapsed() >= Duration::from_secs(timeout_seconds) {
+            return Err(Error::new(
+                ErrorKind::TimedOut,
+                format!(
+                    "{} snapshot(s) not completed within {} seconds ({:?})",
+                    pending.len(),
+                    timeout_seconds,
+                    pending
+                ),
+            ));
+        }
+        info!("waiting for {} snapshot(s) to complete", pending.len());
+        thread::sleep(Duration::from_secs(POLL_INTERVAL_SECONDS));
+    }
+
+    if spec.avalanchego_config.is_custom_network() {
+        // the databases of the clone only work with the same genesis
+        info!("copying the genesis file of the custom network");
+        let genesis_key =
+            avalanche_ops_aws::StorageNamespace::GenesisFile(spec.id.clone()).encode();
+        let genesis =
+            crate::subnet::get_object(&rt, &s3_manager, &aws_resources.s3_bucket, &genesis_key)?
+                .ok_or_else(|| {
+                    Error::new(ErrorKind::NotFound, format!("'{}' not found", genesis_key))
+                })?;
+        put_bytes(
+            &rt,
+            &s3_manager,
+            &aws_resources.s3_bucket,
+            &avalanche_ops_aws::StorageNamespace::NetworkSnapshotGenesisFile(
+                spec.id.clone(),
+                name.to_string(),
+            )
+            .encode(),
+            &genesis,
+        )?;
+    }
+
+    // the manifest goes last, so "clone" never sees the partial snapshot
+    let snapshot = avalanche_ops_aws::NetworkSnapshot {
+        name: name.to_string(),
+        id: spec.id.clone(),
+        region: aws_resources.region.clone(),
+        created_at: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("unexpected None duration_since")
+            .as_secs(),
+        avalanchego_config: spec.avalanchego_config.clone(),
+        coreth_config: spec.coreth_config.clone(),
+        nodes,
+    };
+    put_bytes(
+        &rt,
+        &s3_manager,
+        &aws_resources.s3_bucket,
+        &snapshot_key,
+        snapshot.encode_yaml()?.as_bytes(),
+    )?;
+
+    let exec_path = std::env::current_exe().expect("unexpected None current_exe");
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Green),
+        Print(format!(
+            "\nsnapshot '{}' of {} node(s) is ready\n\n{} clone --spec-file-path {} --snapshot-name {} --clone-spec-file-path [NEW SPEC FILE PATH]\n\n",
+            name,
+            snapshot.nodes.len(),
+            exec_path.display(),
+            spec_file_path,
+            name,
+        )),
+        ResetColor
+    )?;
+
+    output.print(&snapshot)
+}
+
+pub(crate) fn put_bytes(
+    rt: &Runtime,
+    s3_manager: &s3::Manager,
+    s3_bucket: &str,
+    s3_key: &str,
+    d: &[u8],
+) -> io::Result<()> {
+    let tmp_path = random::tmp_path(15, None)?;
+    fs::write(&tmp_path, d)?;
+    rt.block_on(s3_manager.put_object(
+        Arc::new(tmp_path.clone()),
+        Arc::new(s3_bucket.to_string()),
+        Arc::new(s3_key.to_string()),
+    ))?;
+    fs::remove_file(&tmp_path)
+}
@@ -0,0 +1,2229 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::{self, File},
+    io::{self, Error, ErrorKind, Write},
+    net::{Ipv4Addr, Ipv6Addr},
+    path::Path,
+    string::String,
+};
+
+use lazy_static::lazy_static;
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use avalanche_api::alert;
+use avalanche_types::{consensus, constants, errors, genesis as avalanchego_genesis, soft_key};
+use avalanchego::config as avalanchego_config;
+use aws::{
+    cloudwatch::{self, logs as cloudwatch_logs},
+    ec2::fleet,
+};
+use coreth::config as coreth_config;
+use subnet_evm::genesis as subnet_evm_genesis;
+use utils::{http, id, time};
+
+use crate::{
+    notify, plan::diff_values, AnchorEndpoints, ApiNodes, ChaosPolicy, CloneSource,
+    ConfigUpdatePolicy, DataVolume, IndexArchiver, IngressPolicy, KeystoreUser, LoadBalancer, Node,
+    PublicIpPolicy, RestartPolicy, RetentionPolicy, SpecChange, StateBackend, StatusApiPolicy,
+    SubnetDeployment, TerminationPolicy, UpgradePolicy, ValidatorRegistration,
+};
+
+pub const DEFAULT_KEYS_TO_GENERATE: usize = 5;
+
+/// Default machine anchor nodes size, the smallest that tolerates
+/// one anchor node outage with the majority quorum (2 of 3).
+/// only required for custom networks
+pub const DEFAULT_MACHINE_ANCHOR_NODES: u32 = 3;
+pub const MIN_MACHINE_ANCHOR_NODES: u32 = 1;
+pub const MAX_MACHINE_ANCHOR_NODES: u32 = 10; // TODO: allow higher number?
+
+/// Default machine non-anchor nodes size.
+pub const DEFAULT_MACHINE_NON_ANCHOR_NODES: u32 = 2;
+pub const MIN_MACHINE_NON_ANCHOR_NODES: u32 = 1;
+pub const MAX_MACHINE_NON_ANCHOR_NODES: u32 = 20; // TODO: allow higher number?
+
+/// Machine API nodes size, if "machine.api_nodes" is set.
+pub const MIN_MACHINE_API_NODES: u32 = 1;
+pub const MAX_MACHINE_API_NODES: u32 = 20;
+
+/// Represents the topology and consensus defaults layered on top of
+/// "avalanchego_config::Config::default_for_network", by the network ID.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct NetworkDefaults {
+    /// None for the existing networks (e.g., mainnet),
+    /// since the anchor nodes are only required for custom networks.
+    pub anchor_nodes: Option<u32>,
+    pub non_anchor_nodes: u32,
+    /// Overrides the avalanchego default if not None.
+    pub snow_sample_size: Option<u32>,
+    /// Overrides the avalanchego default if not None.
+    pub snow_quorum_size: Option<u32>,
+}
+
+impl NetworkDefaults {
+    pub fn for_network(network_id: u32) -> Self {
+        if constants::NETWORK_ID_TO_NETWORK_NAME.contains_key(&network_id) {
+            return Self {
+                anchor_nodes: None,
+                non_anchor_nodes: DEFAULT_MACHINE_NON_ANCHOR_NODES,
+                snow_sample_size: None,
+                snow_quorum_size: None,
+            };
+        }
+
+        // custom network starts with the anchor nodes as the only validators,
+        // so the consensus must be able to sample all of them
+        let anchor_nodes = DEFAULT_MACHINE_ANCHOR_NODES;
+        Self {
+            anchor_nodes: Some(anchor_nodes),
+            non_anchor_nodes: DEFAULT_MACHINE_NON_ANCHOR_NODES,
+            snow_sample_size: Some(anchor_nodes),
+            snow_quorum_size: Some(anchor_nodes / 2 + 1),
+        }
+    }
+
+    /// Overrides the anchor nodes of the custom network, along with the
+    /// snow sample and quorum sizes that depend on them.
+    /// No-op for the existing networks, which have no anchor nodes.
+    pub fn with_anchor_nodes(mut self, anchor_nodes: u32) -> Self {
+        if self.anchor_nodes.is_none() {
+            return self;
+        }
+        self.anchor_nodes = Some(anchor_nodes);
+        self.snow_sample_size = Some(anchor_nodes);
+        self.snow_quorum_size = Some(anchor_nodes / 2 + 1);
+        self
+    }
+}
+
+#[test]
+fn test_network_defaults() {
+    let defaults = NetworkDefaults::for_network(1);
+    assert!(defaults.anchor_nodes.is_none());
+    assert!(defaults.snow_sample_size.is_none());
+
+    let defaults = NetworkDefaults::for_network(constants::DEFAULT_CUSTOM_NETWORK_ID);
+    assert_eq!(defaults.anchor_nodes, Some(DEFAULT_MACHINE_ANCHOR_NODES));
+    assert_eq!(
+        defaults.snow_sample_size,
+        Some(DEFAULT_MACHINE_ANCHOR_NODES)
+    );
+    assert!(defaults.snow_quorum_size.unwrap() > DEFAULT_MACHINE_ANCHOR_NODES / 2);
+    assert!(defaults.snow_quorum_size.unwrap() <= DEFAULT_MACHINE_ANCHOR_NODES);
+
+    let defaults = defaults.with_anchor_nodes(5);
+    assert_eq!(defaults.anchor_nodes, Some(5));
+    assert_eq!(defaults.snow_sample_size, Some(5));
+    assert_eq!(defaults.snow_quorum_size, Some(3));
+
+    let defaults = NetworkDefaults::for_network(1).with_anchor_nodes(5);
+    assert!(defaults.anchor_nodes.is_none());
+    assert!(defaults.snow_sample_size.is_none());
+}
+
+/// Represents network-level configuration shared among all nodes.
+/// The node-level configuration is generated during each
+/// bootstrap process (e.g., certificates) and not defined
+/// in this cluster-level "Config".
+/// At the beginning, the user is expected to provide this configuration.
+/// "Clone" is for deep-copying.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Spec {
+    /// User-provided ID of the cluster/test.
+    /// This is NOT the avalanche node ID.
+    /// This is NOT the avalanche network ID.
+    #[serde(default)]
+    pub id: String,
+
+    /// AWS resources if run in AWS.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aws_resources: Option<aws::Resources>,
+
+    /// Defines how the underlying infrastructure is set up.
+    /// MUST BE NON-EMPTY.
+    pub machine: Machine,
+    /// Install artifacts to share with remote machines.
+    pub install_artifacts: InstallArtifacts,
+
+    /// Represents the configuration for "avalanchego".
+    /// Set as if run in remote machines.
+    /// For instance, "config-file" must be the path valid
+    /// in the remote machines.
+    /// MUST BE "kebab-case" to be compatible with "avalanchego".
+    pub avalanchego_config: avalanchego_config::Config,
+    /// If non-empty, the JSON-encoded data are saved to a file
+    /// in Path::new(&avalanchego_config.chain_config_dir).join("C").
+    pub coreth_config: coreth_config::Config,
+    /// If non-empty, the JSON-encoded data are saved to a file
+    /// and used for "--genesis" in Path::new(&avalanchego_config.genesis).
+    /// This includes "coreth_genesis::Genesis".
+    /// Names after "_template" since it has not included
+    /// initial stakers yet with to-be-created node IDs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avalanchego_genesis_template: Option<avalanchego_genesis::Genesis>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subnet_evm_genesis: Option<subnet_evm_genesis::Genesis>,
+
+    /// Generated key info with locked P-chain balance with
+    /// initial stake duration in genesis.
+    /// Only valid for custom networks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated_seed_private_key_with_locked_p_chain_balance: Option<soft_key::PrivateKeyInfo>,
+    /// Generated key infos with immediately unlocked P-chain balance.
+    /// Only pre-funded for custom networks with a custom genesis file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated_seed_private_keys: Option<Vec<soft_key::PrivateKeyInfo>>,
+
+    /// If "Some", each non-anchor node registers itself as a primary network
+    /// validator once bootstrapped, staking from the first generated seed key.
+    /// The registrations take turns on the lock in the "state_backend" table
+    /// (or the default table if "None").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator_registration: Option<ValidatorRegistration>,
+    /// Restart policy of the avalanchego systemd service supervised by "avalanched".
+    /// If "None", "RestartPolicy::default" is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<RestartPolicy>,
+    /// Defines how "avalanched" handles the spot interruption and
+    /// the ASG termination of its instance.
+    /// If "None", the instance is terminated without draining the node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub termination_policy: Option<TerminationPolicy>,
+    /// Serializes "apply" and "delete" across operators with the lock.
+    /// If "None", no lock is taken.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_backend: Option<StateBackend>,
+    /// Incremented by each "apply" with "state_backend",
+    /// to reject the applies from the stale spec files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_serial: Option<u64>,
+    /// Defines how "avalanched" applies the artifacts
+    /// from "avalanche-ops-aws events update-artifacts".
+    /// If "None", "UpgradePolicy::default" is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upgrade_policy: Option<UpgradePolicy>,
+    /// Defines how "avalanched" rolls out the config updates
+    /// from "avalanche-ops-aws events update-config".
+    /// If "None", "ConfigUpdatePolicy::default" is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_update_policy: Option<ConfigUpdatePolicy>,
+    /// Fault injection scenarios of "avalanche-ops-aws chaos",
+    /// only for the custom networks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chaos_policy: Option<ChaosPolicy>,
+    /// Source network of the spec created by "avalanche-ops-aws clone".
+    /// If "None", the nodes start from the empty databases.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clone_source: Option<CloneSource>,
+    /// Resources that "delete" keeps for recreating the network.
+    /// If "None", everything but the S3 bucket and the log group is deleted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention_policy: Option<RetentionPolicy>,
+    /// Security group ingress rules that "apply" converges the security groups to.
+    /// If "None", the rules are created once from "aws_resources.ingress_ipv4_range".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ingress_policy: Option<IngressPolicy>,
+    /// Defines how "avalanched" discovers the IP address that the node advertises.
+    /// If "None", the public IPv4 from the EC2 instance metadata is advertised.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_ip_policy: Option<PublicIpPolicy>,
+    /// Local status endpoint of "avalanched".
+    /// If "None", "StatusApiPolicy::default" is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_api_policy: Option<StatusApiPolicy>,
+    /// Archives the accepted containers of each node to S3.
+    /// If "None", no container is archived.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_archiver: Option<IndexArchiver>,
+    /// Load balancer in front of the non-anchor nodes that serve the public RPC.
+    /// If "None", the NLB with the TCP health checks is created with the nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load_balancer: Option<LoadBalancer>,
+    /// Subnet and blockchain to deploy with "avalanche-ops-aws subnet".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subnet: Option<SubnetDeployment>,
+    /// Threshold rules that "avalanched" evaluates on every metrics scrape,
+    /// publishing the alert states to CloudWatch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert_rules: Option<Vec<alert::Rule>>,
+    /// Sinks of the lifecycle events (e.g., stack created, upgrade failed).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notifications: Option<notify::NotificationPolicy>,
+    /// Verifies the "https" API endpoints with the custom CA bundle
+    /// and the client certificate (e.g., nodes behind the authenticated proxy).
+    /// If "None", the "https" endpoints are verified with the system roots,
+    /// and only "insecure: true" skips the verification.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_tls: Option<http::TlsConfig>,
+
+    /// Current all nodes. May be stale.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_nodes: Option<Vec<Node>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoints: Option<Endpoints>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Endpoints {
+    /// Only updated after creation.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_rpc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_rpc_x: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_rpc_p: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_rpc_c: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liveness: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metamask_rpc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub websocket: Option<String>,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Endpoints {
+    pub fn default() -> Self {
+        Self {
+            http_rpc: None,
+            http_rpc_x: None,
+            http_rpc_p: None,
+            http_rpc_c: None,
+            metrics: None,
+            health: None,
+            liveness: None,
+            metamask_rpc: None,
+            websocket: None,
+        }
+    }
+
+    /// Converts to string in YAML format.
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        match serde_yaml::to_string(&self) {
+            Ok(s) => Ok(s),
+            Err(e) => {
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    format!("failed to serialize DnsEndpoints to YAML {}", e),
+                ));
+            }
+        }
+    }
+}
+
+/// Defines how the underlying infrastructure is set up.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Machine {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor_nodes: Option<u32>,
+    #[serde(default)]
+    pub non_anchor_nodes: u32,
+    #[serde(default)]
+    pub arch: String,
+    #[serde(default)]
+    pub instance_types: Vec<String>,
+    /// Spot and on-demand mix for non-anchor nodes.
+    /// Anchor nodes are always on-demand, since the spot interruption
+    /// would take down the bootstrap nodes of the network.
+    /// If "None", all nodes are on-demand.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purchase_options: Option<fleet::PurchaseOptions>,
+    /// Dedicated EBS volume for the node database.
+    /// If "None", the database volume is deleted with the instance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_volume: Option<DataVolume>,
+    /// Stable public endpoints of the anchor nodes, which survive
+    /// the instance replacement so that the bootstrap IPs of the other
+    /// nodes (and of the users) do not go stale.
+    /// If "None", the anchor nodes use the instance public IPs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor_endpoints: Option<AnchorEndpoints>,
+    /// Read-only RPC nodes, deployed in their own ASG with the "api" node kind.
+    /// If "None", the non-anchor nodes serve the RPC.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_nodes: Option<ApiNodes>,
+}
+
+/// Represents artifacts for installation, to be shared with
+/// remote machines. All paths are local to the caller's environment.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct InstallArtifacts {
+    /// "avalanched" agent binary path in the local environment.
+    /// The file is uploaded to the remote storage with the path
+    /// "install/avalanched" to be shared with remote machines.
+    /// The file is NOT compressed when uploaded.
+    #[serde(default)]
+    pub avalanched_bin: String,
+    /// AvalancheGo binary path in the local environment.
+    /// The file is "compressed" and uploaded to remote storage
+    /// to be shared with remote machines.
+    ///
+    ///  build
+    ///    ├── avalanchego (the binary from compiling the app directory)
+    ///    └── plugins
+    ///        └── evm
+    #[serde(default)]
+    pub avalanchego_bin: String,
+    /// Plugin directories in the local environment.
+    /// Files (if any) are uploaded to the remote storage to be shared
+    /// with remote machiens.
+    #[serde(default)]
+    pub plugins_dir: Option<String>,
+}
+
+/// Represents the CloudFormation stack name.
+pub enum StackName {
+    Ec2InstanceRole(String),
+    Vpc(String),
+    AsgBeaconNodes(String),
+    AsgNonBeaconNodes(String),
+    AsgApiNodes(String),
+    LoadBalancer(String),
+}
+
+impl StackName {
+    pub fn encode(&self) -> String {
+        match self {
+            StackName::Ec2InstanceRole(id) => format!("{}-ec2-instance-role", id),
+            StackName::Vpc(id) => format!("{}-vpc", id),
+            StackName::AsgBeaconNodes(id) => format!("{}-asg-anchor-nodes", id),
+            StackName::AsgNonBeaconNodes(id) => format!("{}-asg-non-anchor-nodes", id),
+            StackName::AsgApiNodes(id) => format!("{}-asg-api-nodes", id),
+            StackName::LoadBalancer(id) => format!("{}-load-balancer", id),
+        }
+    }
+}
+
+/// Defines "default-spec" option.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct DefaultSpecOption {
+    pub log_level: String,
+    pub network_name: String,
+    /// Overrides the default custom network ID, if not None.
+    /// Only valid for custom networks.
+    pub network_id: Option<u32>,
+    pub keys_to_generate: usize,
+
+    pub region: String,
+    /// Non-anchor nodes in the other regions than "region".
+    pub regional_non_anchor_nodes: Vec<(String, u32)>,
+
+    /// Overrides "NetworkDefaults" if not None.
+    pub anchor_nodes: Option<u32>,
+    pub non_anchor_nodes: Option<u32>,
+    /// Overrides "DEFAULT_EC2_INSTANCE_TYPES_AMD64" if not empty.
+    pub instance_types: Vec<String>,
+
+    pub db_backup_s3_region: String,
+    pub db_backup_s3_bucket: String,
+    pub db_backup_s3_key: String,
+
+    pub nlb_acm_certificate_arn: String,
+
+    pub install_artifacts_avalanched_bin: String,
+    pub install_artifacts_avalanche_bin: String,
+    pub install_artifacts_plugins_dir: String,
+
+    pub avalanchego_log_level: String,
+    pub avalanchego_whitelisted_subnets: String,
+    pub avalanchego_http_tls_enabled: bool,
+    pub avalanchego_state_sync_ids: String,
+    pub avalanchego_state_sync_ips: String,
+    pub avalanchego_profile_continuous_enabled: bool,
+    pub avalanchego_profile_continuous_freq: String,
+    pub avalanchego_profile_continuous_max_files: String,
+
+    pub coreth_metrics_enabled: bool,
+    pub coreth_continuous_profiler_enabled: bool,
+    pub coreth_offline_pruning_enabled: bool,
+    pub coreth_state_sync_enabled: bool,
+    pub coreth_state_sync_metrics_enabled: bool,
+
+    pub enable_subnet_evm: bool,
+
+    pub disable_instance_system_logs: bool,
+    pub disable_instance_system_metrics: bool,
+
+    pub spec_file_path: String,
+}
+
+pub const ARCH_AMD64: &str = "amd64";
+pub const ARCH_ARM64: &str = "arm64";
+
+lazy_static! {
+    /// Avalanche consensus paper used "c5.large" for testing 125 ~ 2,000 nodes
+    /// Avalanche test net ("fuji") runs "c5.2xlarge"
+    ///
+    /// https://aws.amazon.com/ec2/instance-types/c6a/
+    /// c6a.large:   2 vCPU + 4  GiB RAM
+    /// c6a.xlarge:  4 vCPU + 8  GiB RAM
+    /// c6a.2xlarge: 8 vCPU + 16 GiB RAM
+    ///
+    /// https://aws.amazon.com/ec2/instance-types/m6a/
+    /// m6a.large:   2 vCPU + 8  GiB RAM
+    /// m6a.xlarge:  4 vCPU + 16 GiB RAM
+    /// m6a.2xlarge: 8 vCPU + 32 GiB RAM
+    ///
+    /// https://aws.amazon.com/ec2/instance-types/m5/
+    /// m5.large:   2 vCPU + 8  GiB RAM
+    /// m5.xlarge:  4 vCPU + 16 GiB RAM
+    /// m5.2xlarge: 8 vCPU + 32 GiB RAM
+    ///
+    /// https://aws.amazon.com/ec2/instance-types/c5/
+    /// c5.large:   2 vCPU + 4  GiB RAM
+    /// c5.xlarge:  4 vCPU + 8  GiB RAM
+    /// c5.2xlarge: 8 vCPU + 16 GiB RAM
+    ///
+    /// https://aws.amazon.com/ec2/instance-types/r5/
+    /// r5.large:   2 vCPU + 16 GiB RAM
+    /// r5.xlarge:  4 vCPU + 32 GiB RAM
+    /// r5.2xlarge: 8 vCPU + 64 GiB RAM
+    ///
+    /// https://aws.amazon.com/ec2/instance-types/t3/
+    /// t3.large:   2 vCPU + 8 GiB RAM
+    /// t3.xlarge:  4 vCPU + 16 GiB RAM
+    /// t3.2xlarge: 8 vCPU + 32 GiB RAM
+    pub static ref DEFAULT_EC2_INSTANCE_TYPES_AMD64: Vec<String> = vec![
+        String::from("c6a.2xlarge"),
+        String::from("m6a.2xlarge"),
+        String::from("m5.2xlarge"),
+        String::from("c5.2xlarge"),
+    ];
+
+    /// Avalanche consensus paper used "c5.large" for testing 125 ~ 2,000 nodes
+    /// Avalanche test net ("fuji") runs "c5.2xlarge"
+    ///
+    /// Graviton 3 (in preview)
+    /// https://aws.amazon.com/ec2/instance-types/c7g/
+    /// c7g.large:   2 vCPU + 8  GiB RAM
+    /// c7g.xlarge:  4 vCPU + 16 GiB RAM
+    /// c7g.2xlarge: 8 vCPU + 32 GiB RAM
+    ///
+    /// Graviton 2
+    /// https://aws.amazon.com/ec2/instance-types/c6g/
+    /// c6g.large:   2 vCPU + 4  GiB RAM
+    /// c6g.xlarge:  4 vCPU + 8  GiB RAM
+    /// c6g.2xlarge: 8 vCPU + 16 GiB RAM
+    ///
+    /// Graviton 2
+    /// https://aws.amazon.com/ec2/instance-types/m6g/
+    /// m6g.large:   2 vCPU + 8  GiB RAM
+    /// m6g.xlarge:  4 vCPU + 16 GiB RAM
+    /// m6g.2xlarge: 8 vCPU + 32 GiB RAM
+    ///
+    /// Graviton 2
+    /// https://aws.amazon.com/ec2/instance-types/r6g/
+    /// r6g.large:   2 vCPU + 16 GiB RAM
+    /// r6g.xlarge:  4 vCPU + 32 GiB RAM
+    /// r6g.2xlarge: 8 vCPU + 64 GiB RAM
+    ///
+    /// Graviton 2
+    /// https://aws.amazon.com/ec2/instance-types/t4/
+    /// t4g.large:   2 vCPU + 8 GiB RAM
+    /// t4g.xlarge:  4 vCPU + 16 GiB RAM
+    /// t4g.2xlarge: 8 vCPU + 32 GiB RAM
+    pub static ref DEFAULT_EC2_INSTANCE_TYPES_ARM64: Vec<String> = vec![
+        String::from("c6g.2xlarge"),
+        String::from("m6g.2xlarge"),
+        String::from("r6g.2xlarge"),
+        String::from("t4g.2xlarge"),
+    ];
+}
+
+impl Spec {
+    /// Creates a default Status based on the network ID.
+    /// For custom networks, it generates the "keys" number of keys
+    /// and pre-funds them in the genesis file path, which is
+    /// included in "InstallArtifacts.genesis_draft_file_path".
+    pub fn default_aws(opt: DefaultSpecOption) -> Self {
+        let network_id = match constants::NETWORK_NAME_TO_NETWORK_ID.get(opt.network_name.as_str())
+        {
+            Some(v) => *v,
+            None => opt
+                .network_id
+                .unwrap_or(constants::DEFAULT_CUSTOM_NETWORK_ID),
+        };
+
+        let mut network_defaults = NetworkDefaults::for_network(network_id);
+        if let Some(anchor_nodes) = opt.anchor_nodes {
+            network_defaults = network_defaults.with_anchor_nodes(anchor_nodes);
+        }
+        if let Some(non_anchor_nodes) = opt.non_anchor_nodes {
+            network_defaults.non_anchor_nodes = non_anchor_nodes;
+        }
+        let mut avalanchego_config = avalanchego_config::Config::default_for_network(network_id);
+        avalanchego_config.log_level = Some(opt.avalanchego_log_level);
+        if network_defaults.snow_sample_size.is_some() {
+            avalanchego_config.snow_sample_size = network_defaults.snow_sample_size;
+        }
+        if network_defaults.snow_quorum_size.is_some() {
+            avalanchego_config.snow_quorum_size = network_defaults.snow_quorum_size;
+        }
+
+        // only set values if non empty
+        // otherwise, avalanchego will fail with "couldn't load node config: read .: is a directory"
+        // TODO: use different certs than staking?
+        if opt.avalanchego_http_tls_enabled {
+            avalanchego_config.http_tls_enabled = Some(true);
+            avalanchego_config.http_tls_key_file = avalanchego_config.staking_tls_key_file.clone();
+            avalanchego_config.http_tls_cert_file =
+                avalanchego_config.staking_tls_cert_file.clone();
+        }
+
+        if !opt.avalanchego_state_sync_ids.is_empty() {
+            avalanchego_config.state_sync_ids = Some(opt.avalanchego_state_sync_ids.clone());
+        };
+        if !opt.avalanchego_state_sync_ips.is_empty() {
+            avalanchego_config.state_sync_ips = Some(opt.avalanchego_state_sync_ips.clone());
+        };
+        if opt.avalanchego_profile_continuous_enabled {
+            avalanchego_config.profile_continuous_enabled = Some(true);
+        }
+        if !opt.avalanchego_profile_continuous_freq.is_empty() {
+            avalanchego_config.profile_continuous_freq =
+                Some(opt.avalanchego_profile_continuous_freq.clone());
+        };
+        if !opt.avalanchego_profile_continuous_max_files.is_empty() {
+            let profile_continuous_max_files = opt.avalanchego_profile_continuous_max_files;
+            let profile_continuous_max_files = profile_continuous_max_files.parse::<u32>().unwrap();
+            avalanchego_config.profile_continuous_max_files = Some(profile_continuous_max_files);
+        };
+        if !opt.avalanchego_whitelisted_subnets.is_empty() {
+            avalanchego_config.whitelisted_subnets = Some(opt.avalanchego_whitelisted_subnets);
+        };
+
+        let network_id = avalanchego_config.network_id;
+        let id = {
+            if !opt.spec_file_path.is_empty() {
+                let spec_file_stem = Path::new(&opt.spec_file_path).file_stem().unwrap();
+                spec_file_stem.to_str().unwrap().to_string()
+            } else {
+                match constants::NETWORK_ID_TO_NETWORK_NAME.get(&network_id) {
+                    Some(v) => id::with_time(format!("aops-{}", *v).as_str()),
+                    None => id::with_time("aops-custom"),
+                }
+            }
+        };
+        let machine = Machine {
+            anchor_nodes: network_defaults.anchor_nodes,
+            non_anchor_nodes: network_defaults.non_anchor_nodes,
+
+            // TODO: support "arm64"
+            arch: ARCH_AMD64.to_string(),
+            instance_types: if opt.instance_types.is_empty() {
+                DEFAULT_EC2_INSTANCE_TYPES_AMD64.to_vec()
+            } else {
+                opt.instance_types.clone()
+            },
+            purchase_options: None,
+            data_volume: None,
+            anchor_endpoints: None,
+            api_nodes: None,
+        };
+
+        let (avalanchego_genesis_template, generated_seed_keys) = {
+            if avalanchego_config.is_custom_network() {
+                let (g, seed_keys) =
+                    avalanchego_genesis::Genesis::new(network_id, opt.keys_to_generate)
+                        .expect("unexpected None genesis");
+                (Some(g), seed_keys)
+            } else {
+                // existing network has only 1 pre-funded key "ewoq"
+                let mut seed_keys: Vec<soft_key::PrivateKeyInfo> = Vec::new();
+                for i in 0..opt.keys_to_generate {
+                    let k = {
+                        if i < soft_key::TEST_KEYS.len() {
+                            soft_key::TEST_KEYS[i].clone()
+                        } else {
+                            soft_key::Key::generate().expect("unexpected key generate failure")
+                        }
+                    };
+                    let info = k.info(network_id).expect("unexpected to_info failure");
+                    seed_keys.push(info);
+                }
+                (None, seed_keys)
+            }
+        };
+        let generated_seed_private_key_with_locked_p_chain_balance =
+            Some(generated_seed_keys[0].clone());
+        let generated_seed_private_keys = Some(generated_seed_keys[1..].to_vec());
+
+        let subnet_evm_genesis = {
+            if opt.enable_subnet_evm {
+                let mut genesis = subnet_evm_genesis::Genesis::default();
+                genesis.alloc = Some(BTreeMap::new());
+                let mut admin_addresses: Vec<String> = Vec::new();
+                for key_info in generated_seed_keys.iter() {
+                    genesis
+                        .add_alloc(
+                            &key_info.eth_address,
+                            subnet_evm_genesis::AllocAccount::default().balance,
+                        )
+                        .expect("unexpected invalid eth_address");
+                    admin_addresses.push(key_info.eth_address.clone());
+                }
+                genesis
+                    .enable_contract_deployer_allow_list(&admin_addresses)
+                    .expect("unexpected invalid eth_address");
+
+                Some(genesis)
+            } else {
+                None
+            }
+        };
+
+        let mut aws_resources = aws::Resources {
+            region: opt.region,
+            s3_bucket: format!("avalanche-ops-{}-{}", time::get(6), id::system(10)), // [year][month][date]-[system host-based id]
+            ..aws::Resources::default()
+        };
+        if !opt.db_backup_s3_region.is_empty() {
+            aws_resources.db_backup_s3_region = Some(opt.db_backup_s3_region);
+        }
+        if !opt.db_backup_s3_bucket.is_empty() {
+            aws_resources.db_backup_s3_bucket = Some(opt.db_backup_s3_bucket);
+        }
+        if !opt.db_backup_s3_key.is_empty() {
+            aws_resources.db_backup_s3_key = Some(opt.db_backup_s3_key);
+        }
+        if !opt.nlb_acm_certificate_arn.is_empty() {
+            aws_resources.nlb_acm_certificate_arn = Some(opt.nlb_acm_certificate_arn);
+        }
+        if opt.disable_instance_system_logs {
+            aws_resources.instance_system_logs = Some(false);
+        }
+        if opt.disable_instance_system_metrics {
+            aws_resources.instance_system_metrics = Some(false);
+        }
+        if !opt.regional_non_anchor_nodes.is_empty() {
+            aws_resources.regional_resources = Some(
+                opt.regional_non_anchor_nodes
+                    .iter()
+                    .map(|(region, nodes)| aws::RegionalResources::new(region, *nodes))
+                    .collect(),
+            );
+        }
+        let aws_resources = Some(aws_resources);
+
+        let mut install_artifacts = InstallArtifacts {
+            avalanched_bin: opt.install_artifacts_avalanched_bin,
+            avalanchego_bin: opt.install_artifacts_avalanche_bin,
+            plugins_dir: None,
+        };
+        if !opt.install_artifacts_plugins_dir.is_empty() {
+            install_artifacts.plugins_dir = Some(opt.install_artifacts_plugins_dir);
+        }
+
+        let mut coreth_config = coreth_config::Config::default();
+        if opt.coreth_metrics_enabled {
+            coreth_config.metrics_enabled = Some(true);
+        }
+        if opt.coreth_continuous_profiler_enabled {
+            coreth_config.continuous_profiler_dir =
+                Some(String::from(coreth_config::DEFAULT_PROFILE_DIR));
+            coreth_config.continuous_profiler_frequency =
+                Some(coreth_config::DEFAULT_PROFILE_FREQUENCY);
+            coreth_config.continuous_profiler_max_files =
+                Some(coreth_config::DEFAULT_PROFILE_MAX_FILES);
+        }
+        if opt.coreth_offline_pruning_enabled {
+            coreth_config.offline_pruning_enabled = Some(true);
+        }
+        if opt.coreth_state_sync_enabled {
+            coreth_config.state_sync_enabled = Some(true);
+            if !opt.avalanchego_state_sync_ids.is_empty() {
+                coreth_config.state_sync_ids = Some(opt.avalanchego_state_sync_ids.clone());
+            }
+        }
+        if opt.coreth_state_sync_metrics_enabled {
+            coreth_config.state_sync_metrics_enabled = Some(true);
+        }
+
+        Self {
+            id,
+
+            aws_resources,
+            machine,
+            install_artifacts,
+
+            avalanchego_config,
+            coreth_config,
+            avalanchego_genesis_template,
+
+            subnet_evm_genesis,
+
+            generated_seed_private_key_with_locked_p_chain_balance,
+            generated_seed_private_keys,
+            validator_registration: None,
+            restart_policy: None,
+            termination_policy: None,
+            state_backend: None,
+            state_serial: None,
+            upgrade_policy: None,
+            config_update_policy: None,
+            chaos_policy: None,
+            clone_source: None,
+            retention_policy: None,
+            ingress_policy: None,
+            public_ip_policy: None,
+            status_api_policy: None,
+            index_archiver: None,
+            load_balancer: None,
+            subnet: None,
+            alert_rules: None,
+            notifications: None,
+            api_tls: None,
+
+            current_nodes: None,
+            endpoints: None,
+        }
+    }
+
+    /// Converts to string in YAML format.
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        match serde_yaml::to_string(&self) {
+            Ok(s) => Ok(s),
+            Err(e) => {
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    format!("failed to serialize Spec to YAML {}", e),
+                ));
+            }
+        }
+    }
+
+    /// Saves the current spec to disk
+    /// and overwrites the file.
+    pub fn sync(&self, file_path: &str) -> io::Result<()> {
+        info!("syncing Spec to '{}'", file_path);
+        let path = Path::new(file_path);
+        let parent_dir = path.parent().expect("unexpected None parent");
+        fs::create_dir_all(parent_dir)?;
+
+        let ret = serde_yaml::to_vec(self);
+        let d = match ret {
+            Ok(d) => d,
+            Err(e) => {
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    format!("failed to serialize Spec to YAML {}", e),
+                ));
+            }
+        };
+        let mut f = File::create(file_path)?;
+        f.write_all(&d)?;
+
+        Ok(())
+    }
+
+    pub fn load(file_path: &str) -> io::Result<Self> {
+        info!("loading Spec from {}", file_path);
+
+        if !Path::new(file_path).exists() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("file {} does not exists", file_path),
+            ));
+        }
+
+        let f = File::open(&file_path).map_err(|e| {
+            return Error::new(
+                ErrorKind::Other,
+                format!("failed to open {} ({})", file_path, e),
+            );
+        })?;
+        serde_yaml::from_reader(f).map_err(|e| {
+            return Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e));
+        })
+    }
+
+    /// Returns the seed private keys, the keystore users, and the Slack webhook URLs
+    /// to envelope-encrypt, or "None" if the spec has none of them.
+    pub fn seed_private_keys(&self) -> Option<SeedPrivateKeys> {
+        let keystore_users = self
+            .machine
+            .api_nodes
+            .as_ref()
+            .map(|v| v.keystore_users.clone())
+            .unwrap_or_default();
+        let slack_webhook_urls = self
+            .notifications
+            .as_ref()
+            .map(|v| v.slack_webhook_urls())
+            .unwrap_or_default();
+        if self
+            .generated_seed_private_key_with_locked_p_chain_balance
+            .is_none()
+            && self.generated_seed_private_keys.is_none()
+            && keystore_users.is_empty()
+            && slack_webhook_urls.is_empty()
+        {
+            return None;
+        }
+        Some(SeedPrivateKeys {
+            generated_seed_private_key_with_locked_p_chain_balance: self
+                .generated_seed_private_key_with_locked_p_chain_balance
+                .clone(),
+            generated_seed_private_keys: self.generated_seed_private_keys.clone(),
+            keystore_users,
+            slack_webhook_urls,
+        })
+    }
+
+    /// Returns the copy without the secrets of the seed private keys,
+    /// the keystore passwords, and the Slack webhook URLs,
+    /// to upload to the remote storage for remote machines.
+    /// The addresses are kept.
+    pub fn redacted(&self) -> Self {
+        let mut redacted = self.clone();
+        if let Some(k) = redacted
+            .generated_seed_private_key_with_locked_p_chain_balance
+            .as_mut()
+        {
+            redact_private_key(k);
+        }
+        if let Some(keys) = redacted.generated_seed_private_keys.as_mut() {
+            keys.iter_mut().for_each(redact_private_key);
+        }
+        if let Some(api_nodes) = redacted.machine.api_nodes.as_mut() {
+            for user in api_nodes.keystore_users.iter_mut() {
+                user.password = String::new();
+            }
+        }
+        if let Some(notifications) = redacted.notifications.as_mut() {
+            notifications.redact_slack_webhook_urls();
+        }
+        redacted
+    }
+
+    /// Returns the DynamoDB table of the locks that the nodes take turns on
+    /// (e.g., the validator registration and the staking certificate rotation).
+    pub fn node_lock_table(&self) -> String {
+        self.state_backend.clone().unwrap_or_default().lock_table
+    }
+
+    /// Returns true if the load balancer terminates TLS on port 443.
+    pub fn load_balancer_https_enabled(&self) -> bool {
+        let nlb = self
+            .aws_resources
+            .as_ref()
+            .map_or(false, |v| v.nlb_acm_certificate_arn.is_some());
+        let load_balancer = self
+            .load_balancer
+            .as_ref()
+            .map_or(false, |v| v.acm_certificate_arn.is_some());
+        nlb || load_balancer
+    }
+
+    /// Returns true if any seed private key, keystore password,
+    /// or Slack webhook URL is redacted.
+    pub fn is_redacted(&self) -> bool {
+        self.generated_seed_private_key_with_locked_p_chain_balance
+            .iter()
+            .chain(self.generated_seed_private_keys.iter().flatten())
+            .any(|k| k.private_key.is_empty())
+            || self.machine.api_nodes.as_ref().map_or(false, |v| {
+                v.keystore_users.iter().any(|u| u.password.is_empty())
+            })
+            || self
+                .notifications
+                .iter()
+                .flat_map(|v| v.slack_webhook_urls())
+                .any(|u| u.is_empty())
+    }
+
+    /// Returns the snowball parameters of the avalanchego config,
+    /// with the avalanchego defaults for the unset ones.
+    pub fn consensus_parameters(&self) -> consensus::Parameters {
+        let defaults = consensus::Parameters::default();
+        consensus::Parameters {
+            k: self
+                .avalanchego_config
+                .snow_sample_size
+                .unwrap_or(avalanchego_config::DEFAULT_SNOW_SAMPLE_SIZE),
+            alpha: self
+                .avalanchego_config
+                .snow_quorum_size
+                .unwrap_or(avalanchego_config::DEFAULT_SNOW_QUORUM_SIZE),
+            beta_virtuous: self
+                .avalanchego_config
+                .snow_virtuous_commit_threshold
+                .unwrap_or(defaults.beta_virtuous),
+            beta_rogue: self
+                .avalanchego_config
+                .snow_rogue_commit_threshold
+                .unwrap_or(defaults.beta_rogue),
+        }
+    }
+
+    /// Restores the seed private keys, the keystore users,
+    /// and the Slack webhook URLs of the redacted spec.
+    pub fn restore_seed_private_keys(&mut self, keys: SeedPrivateKeys) {
+        self.generated_seed_private_key_with_locked_p_chain_balance =
+            keys.generated_seed_private_key_with_locked_p_chain_balance;
+        self.generated_seed_private_keys = keys.generated_seed_private_keys;
+        if let Some(api_nodes) = self.machine.api_nodes.as_mut() {
+            api_nodes.keystore_users = keys.keystore_users;
+        }
+        if let Some(notifications) = self.notifications.as_mut() {
+            notifications.restore_slack_webhook_urls(keys.slack_webhook_urls);
+        }
+    }
+
+    /// Returns the field changes from the deployed spec (e.g., the one in the
+    /// remote storage that the nodes read) to this spec, with the secrets redacted.
+    /// The node status fields ("current_nodes" and "endpoints") are ignored.
+    pub fn diff(&self, deployed: &Self) -> io::Result<Vec<SpecChange>> {
+        let to_value = |spec: &Self| {
+            let mut v = serde_json::to_value(spec.redacted()).map_err(|e| {
+                Error::new(
+                    ErrorKind::Other,
+                    format!("failed to serialize Spec to JSON {}", e),
+                )
+            })?;
+            if let Some(m) = v.as_object_mut() {
+                m.remove("current_nodes");
+                m.remove("endpoints");
+            }
+            Ok::<serde_json::Value, Error>(v)
+        };
+
+        let mut changes = Vec::new();
+        diff_values(
+            "",
+            Some(&to_value(deployed)?),
+            Some(&to_value(self)?),
+            &mut changes,
+        );
+        Ok(changes)
+    }
+
+    /// Returns the spec after "delete" with the retention policy,
+    /// which only keeps the outputs of the retained resources,
+    /// so that "apply" recreates the rest.
+    pub fn retained(&self) -> Self {
+        let retention_policy = self.retention_policy.clone().unwrap_or_default();
+        let mut spec = self.clone();
+        if let Some(aws_resources) = spec.aws_resources.as_mut() {
+            if !retention_policy.kms_key {
+                aws_resources.kms_cmk_id = None;
+                aws_resources.kms_cmk_arn = None;
+            }
+            if !retention_policy.ec2_key_pair {
+                aws_resources.ec2_key_path = None;
+            }
+            aws_resources.cloudformation_ec2_instance_profile_arn = None;
+            aws_resources.cloudformation_vpc_id = None;
+            aws_resources.cloudformation_vpc_security_group_id = None;
+            aws_resources.cloudformation_vpc_public_subnet_ids = None;
+            aws_resources.cloudformation_asg_anchor_nodes_logical_id = None;
+            aws_resources.cloudformation_asg_non_anchor_nodes_logical_id = None;
+            aws_resources.cloudformation_asg_api_nodes_logical_id = None;
+            aws_resources.cloudformation_asg_nlb_arn = None;
+            aws_resources.cloudformation_asg_nlb_target_group_arn = None;
+            aws_resources.cloudformation_asg_nlb_dns_name = None;
+            for r in aws_resources.regional_resources.iter_mut().flatten() {
+                if !retention_policy.ec2_key_pair {
+                    r.ec2_key_path = None;
+                }
+                r.cloudformation_vpc_id = None;
+                r.cloudformation_vpc_security_group_id = None;
+                r.cloudformation_vpc_public_subnet_ids = None;
+                r.cloudformation_asg_non_anchor_nodes_logical_id = None;
+            }
+        }
+        spec.current_nodes = None;
+        spec.endpoints = None;
+        spec
+    }
+
+    /// Validates the spec, failing with "errors::Error::SpecValidation"
+    /// so that the callers never retry on it.
+    pub fn validate(&self) -> io::Result<()> {
+        info!("validating Spec");
+        self.validate_fields().map_err(|e| {
+            errors::Error::SpecValidation {
+                message: e.to_string(),
+            }
+            .into()
+        })
+    }
+
+    fn validate_fields(&self) -> io::Result<()> {
+        if self.id.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "'id' cannot be empty"));
+        }
+
+        // some AWS resources have tag limit of 32-character
+        if self.id.len() > 28 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("'id' length cannot be >28 (got {})", self.id.len()),
+            ));
+        }
+
+        if self.aws_resources.is_some() {
+            let aws_resources = self.aws_resources.clone().unwrap();
+            if aws_resources.region.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'machine.region' cannot be empty",
+                ));
+            }
+            validate_region(&aws_resources.region)?;
+            if aws_resources.db_backup_s3_region.is_some()
+                && aws_resources.db_backup_s3_bucket.is_none()
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "{} missing corresponding bucket",
+                        aws_resources
+                            .db_backup_s3_bucket
+                            .expect("unexpected aws_resources.db_backup_s3_bucket")
+                    ),
+                ));
+            }
+            if aws_resources.db_backup_s3_bucket.is_some()
+                && aws_resources.db_backup_s3_key.is_none()
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "{} missing corresponding key",
+                        aws_resources
+                            .db_backup_s3_bucket
+                            .expect("unexpected aws_resources.db_backup_s3_bucket")
+                    ),
+                ));
+            }
+            if aws_resources.db_backup_s3_bucket.is_some()
+                && aws_resources.db_backup_s3_region.is_none()
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "{} missing corresponding region",
+                        aws_resources
+                            .db_backup_s3_bucket
+                            .expect("unexpected aws_resources.db_backup_s3_bucket")
+                    ),
+                ));
+            }
+            if let Some(days) = aws_resources.instance_logs_retention_in_days {
+                cloudwatch_logs::validate_retention_in_days(days)?;
+            }
+            if let Some(interval_seconds) = aws_resources.cloudwatch_telemetry_interval_seconds {
+                cloudwatch::validate_telemetry_interval_seconds(interval_seconds)?;
+            }
+            if let Some(ingress_ipv4_range) = &aws_resources.ingress_ipv4_range {
+                validate_ipv4_cidr(ingress_ipv4_range)?;
+            }
+            validate_dual_stack(&aws_resources, self.machine.anchor_endpoints.as_ref())?;
+            if let Some(regional_resources) = &aws_resources.regional_resources {
+                validate_regional_resources(&aws_resources, regional_resources)?;
+            }
+        }
+
+        if self.machine.instance_types.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'machine.instance_types' cannot be empty",
+            ));
+        }
+        for instance_type in self.machine.instance_types.iter() {
+            validate_instance_type(instance_type)?;
+        }
+
+        if self.machine.non_anchor_nodes < MIN_MACHINE_NON_ANCHOR_NODES {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'machine.non_anchor_nodes' {} <minimum {}",
+                    self.machine.non_anchor_nodes, MIN_MACHINE_NON_ANCHOR_NODES
+                ),
+            ));
+        }
+        if self.machine.non_anchor_nodes > MAX_MACHINE_NON_ANCHOR_NODES {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'machine.non_anchor_nodes' {} >maximum {}",
+                    self.machine.non_anchor_nodes, MAX_MACHINE_NON_ANCHOR_NODES
+                ),
+            ));
+        }
+
+        if let Some(purchase_options) = &self.machine.purchase_options {
+            purchase_options.validate()?;
+        }
+        if let Some(data_volume) = &self.machine.data_volume {
+            data_volume.validate()?;
+        }
+        if let Some(anchor_endpoints) = &self.machine.anchor_endpoints {
+            anchor_endpoints.validate()?;
+            if self.machine.anchor_nodes.unwrap_or(0) == 0 {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'machine.anchor_endpoints' without 'machine.anchor_nodes'",
+                ));
+            }
+        }
+        if let Some(api_nodes) = &self.machine.api_nodes {
+            api_nodes.validate()?;
+        }
+        if let Some(restart_policy) = &self.restart_policy {
+            restart_policy.validate()?;
+        }
+        if let Some(termination_policy) = &self.termination_policy {
+            termination_policy.validate(&self.machine)?;
+        }
+        if let Some(state_backend) = &self.state_backend {
+            state_backend.validate()?;
+        }
+        if let Some(upgrade_policy) = &self.upgrade_policy {
+            upgrade_policy.validate()?;
+        }
+        if let Some(config_update_policy) = &self.config_update_policy {
+            config_update_policy.validate()?;
+        }
+        if let Some(chaos_policy) = &self.chaos_policy {
+            if !self.avalanchego_config.is_custom_network() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "'chaos_policy' not allowed for network ID {}",
+                        self.avalanchego_config.network_id
+                    ),
+                ));
+            }
+            chaos_policy.validate()?;
+        }
+        if let Some(clone_source) = &self.clone_source {
+            clone_source.validate(&self.machine)?;
+        }
+        if let Some(retention_policy) = &self.retention_policy {
+            retention_policy.validate(self)?;
+        }
+        if let Some(load_balancer) = &self.load_balancer {
+            load_balancer.validate()?;
+            if self
+                .aws_resources
+                .as_ref()
+                .map_or(false, |v| v.nlb_acm_certificate_arn.is_some())
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'load_balancer' conflicts with 'aws_resources.nlb_acm_certificate_arn' (use 'load_balancer.acm_certificate_arn')",
+                ));
+            }
+        }
+        if let Some(ingress_policy) = &self.ingress_policy {
+            ingress_policy.validate()?;
+            if self
+                .aws_resources
+                .as_ref()
+                .map_or(false, |v| v.ingress_ipv4_range.is_some())
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'ingress_policy' conflicts with 'aws_resources.ingress_ipv4_range'",
+                ));
+            }
+            // the policy only manages the IPv4 rules, so the stack
+            // creates no IPv6 ingress other than the VPC range
+            if self.aws_resources.as_ref().map_or(false, |v| {
+                v.ingress_ipv6_range.is_some() || v.advertise_ipv6.unwrap_or(false)
+            }) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'ingress_policy' conflicts with 'aws_resources.ingress_ipv6_range' and 'aws_resources.advertise_ipv6'",
+                ));
+            }
+        }
+        if let Some(public_ip_policy) = &self.public_ip_policy {
+            public_ip_policy.validate()?;
+            if self
+                .aws_resources
+                .as_ref()
+                .map_or(false, |v| v.advertise_ipv6.unwrap_or(false))
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'public_ip_policy' conflicts with 'aws_resources.advertise_ipv6'",
+                ));
+            }
+        }
+        if let Some(status_api_policy) = &self.status_api_policy {
+            status_api_policy.validate(&self.avalanchego_config)?;
+        }
+        if let Some(index_archiver) = &self.index_archiver {
+            index_archiver.validate(&self.avalanchego_config)?;
+        }
+        if self.machine.api_nodes.as_ref().map_or(false, |v| {
+            v.keystore_users.iter().any(|u| u.import_seed_private_keys)
+        }) && self.generated_seed_private_keys.is_none()
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'machine.api_nodes.keystore_users' import the seed private keys but no 'generated_seed_private_keys'",
+            ));
+        }
+        if let Some(registration) = &self.validator_registration {
+            registration.validate(self.avalanchego_config.network_id)?;
+            if self
+                .generated_seed_private_keys
+                .as_ref()
+                .map_or(0, |keys| keys.len())
+                == 0
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'validator_registration' requires 'generated_seed_private_keys' to fund the stake",
+                ));
+            }
+        }
+        if let Some(genesis) = &self.subnet_evm_genesis {
+            genesis.validate().map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid 'subnet_evm_genesis' ({})", e),
+                )
+            })?;
+        }
+        if let Some(notifications) = &self.notifications {
+            notifications.validate()?;
+        }
+        if let Some(api_tls) = &self.api_tls {
+            api_tls.validate()?;
+        }
+        if let Some(rules) = &self.alert_rules {
+            let mut names = HashSet::new();
+            for rule in rules.iter() {
+                rule.validate()?;
+                if !names.insert(rule.name.as_str()) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("duplicate alert rule name '{}'", rule.name),
+                    ));
+                }
+            }
+        }
+        if let Some(subnet) = &self.subnet {
+            subnet.validate()?;
+            if subnet.genesis_path.is_empty() && self.subnet_evm_genesis.is_none() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'subnet' requires 'subnet.genesis_path' or 'subnet_evm_genesis'",
+                ));
+            }
+            if self
+                .generated_seed_private_keys
+                .as_ref()
+                .map_or(0, |keys| keys.len())
+                == 0
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'subnet' requires 'generated_seed_private_keys' to own the subnet and pay the fees",
+                ));
+            }
+        }
+
+        if !Path::new(&self.install_artifacts.avalanched_bin).exists() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "avalanched_bin {} does not exist",
+                    self.install_artifacts.avalanched_bin
+                ),
+            ));
+        }
+        if !Path::new(&self.install_artifacts.avalanchego_bin).exists() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "avalanchego_bin {} does not exist",
+                    self.install_artifacts.avalanchego_bin
+                ),
+            ));
+        }
+        if self.install_artifacts.plugins_dir.is_some()
+            && !Path::new(
+                &self
+                    .install_artifacts
+                    .plugins_dir
+                    .clone()
+                    .expect("unexpected None install_artifacts.plugins_dir"),
+            )
+            .exists()
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "plugins_dir {} does not exist",
+                    self.install_artifacts
+                        .plugins_dir
+                        .clone()
+                        .expect("unexpected None install_artifacts.plugins_dir")
+                ),
+            ));
+        }
+
+        if !self.avalanchego_config.is_custom_network() {
+            if self.avalanchego_genesis_template.is_some() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "cannot specify 'avalanchego_genesis_template' for network_id {:?}",
+                        self.avalanchego_config.network_id
+                    ),
+                ));
+            }
+            if self.machine.anchor_nodes.unwrap_or(0) > 0 {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "cannot specify non-zero 'machine.anchor_nodes' for network_id {:?}",
+                        self.avalanchego_config.network_id
+                    ),
+                ));
+            }
+        } else {
+            if self.avalanchego_genesis_template.is_none() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "must specify 'avalanchego_genesis_template' for network_id {:?}",
+                        self.avalanchego_config.network_id
+                    ),
+                ));
+            }
+            if self.machine.anchor_nodes.unwrap_or(0) == 0 {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "cannot specify 0 for 'machine.anchor_nodes' for custom network",
+                ));
+            }
+            if self.machine.anchor_nodes.unwrap_or(0) < MIN_MACHINE_ANCHOR_NODES {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "'machine.anchor_nodes' {} below min {}",
+                        self.machine.anchor_nodes.unwrap_or(0),
+                        MIN_MACHINE_ANCHOR_NODES
+                    ),
+                ));
+            }
+            if self.machine.anchor_nodes.unwrap_or(0) > MAX_MACHINE_ANCHOR_NODES {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "'machine.anchor_nodes' {} exceeds limit {}",
+                        self.machine.anchor_nodes.unwrap_or(0),
+                        MAX_MACHINE_ANCHOR_NODES
+                    ),
+                ));
+            }
+
+            // anchor nodes are the only initial validators in the genesis,
+            // so consensus cannot make progress if it samples more than those
+            // ("snow-sample-size" defaults to 20 in avalanchego if unset)
+            let params = self.consensus_parameters();
+            params.verify()?;
+            let anchor_nodes = self.machine.anchor_nodes.unwrap_or(0);
+            if anchor_nodes < params.k {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "'machine.anchor_nodes' {} fewer than 'snow-sample-size' {} (initial validators cannot be sampled)",
+                        anchor_nodes, params.k
+                    ),
+                ));
+            }
+            // a poll that samples the unreachable node then fails, so with
+            // the quorum of the whole sample, one anchor node outage halts the network
+            if anchor_nodes > 1 && params.alpha >= params.k {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "'snow-quorum-size' {} of 'snow-sample-size' {} tolerates no anchor node outage (use 1 or at least 3 anchor nodes)",
+                        params.alpha, params.k
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Validates the AWS region name (e.g., "us-west-2", "us-gov-east-1").
+/// It only checks the format, so that the typo fails before any API call.
+pub fn validate_region(region: &str) -> io::Result<()> {
+    let parts: Vec<&str> = region.split('-').collect();
+    let valid = parts.len() >= 3
+        && parts[..parts.len() - 1]
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_lowercase()))
+        && parts[parts.len() - 1].parse::<u8>().is_ok();
+    if !valid {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid region '{}'", region),
+        ));
+    }
+    Ok(())
+}
+
+/// Validates the EC2 instance type (e.g., "c6a.2xlarge").
+pub fn validate_instance_type(instance_type: &str) -> io::Result<()> {
+    let valid = match instance_type.split_once('.') {
+        Some((family, size)) => {
+            !family.is_empty()
+                && !size.is_empty()
+                && family
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+                && size
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        }
+        None => false,
+    };
+    if !valid {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid instance type '{}'", instance_type),
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_validate_region_and_instance_type() {
+    validate_region("us-west-2").unwrap();
+    validate_region("ap-northeast-1").unwrap();
+    validate_region("us-gov-east-1").unwrap();
+    assert!(validate_region("").is_err());
+    assert!(validate_region("us-west").is_err());
+    assert!(validate_region("us-west-2a").is_err());
+    assert!(validate_region("US-WEST-2").is_err());
+
+    validate_instance_type("c6a.2xlarge").unwrap();
+    validate_instance_type("m5.large").unwrap();
+    validate_instance_type("u-6tb1.metal").unwrap();
+    assert!(validate_instance_type("").is_err());
+    assert!(validate_instance_type("c6a").is_err());
+    assert!(validate_instance_type("c6a.").is_err());
+    assert!(validate_instance_type("C6A.2XLARGE").is_err());
+}
+
+/// Validates the IPv4 CIDR notation (e.g., "10.0.0.0/16").
+pub(crate) fn validate_ipv4_cidr(cidr: &str) -> io::Result<()> {
+    let valid = match cidr.split_once('/') {
+        Some((ip, prefix)) => {
+            ip.parse::<Ipv4Addr>().is_ok() && matches!(prefix.parse::<u8>(), Ok(p) if p <= 32)
+        }
+        None => false,
+    };
+    if !valid {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid IPv4 CIDR '{}'", cidr),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_ipv6_cidr(cidr: &str) -> io::Result<()> {
+    let valid = match cidr.split_once('/') {
+        Some((ip, prefix)) => {
+            ip.parse::<Ipv6Addr>().is_ok() && matches!(prefix.parse::<u8>(), Ok(p) if p <= 128)
+        }
+        None => false,
+    };
+    if !valid {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid IPv6 CIDR '{}'", cidr),
+        ));
+    }
+    Ok(())
+}
+
+/// Validates the IPv6 settings, which all require "dual_stack".
+fn validate_dual_stack(
+    aws_resources: &aws::Resources,
+    anchor_endpoints: Option<&AnchorEndpoints>,
+) -> io::Result<()> {
+    let dual_stack = aws_resources.dual_stack.unwrap_or(false);
+    if let Some(ingress_ipv6_range) = &aws_resources.ingress_ipv6_range {
+        if !dual_stack {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'ingress_ipv6_range' requires 'dual_stack'",
+            ));
+        }
+        validate_ipv6_cidr(ingress_ipv6_range)?;
+    }
+    if !aws_resources.advertise_ipv6.unwrap_or(false) {
+        return Ok(());
+    }
+    if !dual_stack {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "'advertise_ipv6' requires 'dual_stack'",
+        ));
+    }
+    if matches!(anchor_endpoints, Some(v) if v.elastic_ips) {
+        // the Elastic IPs are IPv4 only
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "'advertise_ipv6' conflicts with 'machine.anchor_endpoints.elastic_ips'",
+        ));
+    }
+    let restricted = aws_resources
+        .ingress_ipv6_range
+        .as_ref()
+        .map_or(false, |v| v != aws::DEFAULT_INGRESS_IPV6_RANGE);
+    if restricted && aws_resources.regional_resources.is_some() {
+        // the stack only allows the VPC IPv6 range besides "ingress_ipv6_range",
+        // so the nodes in the other regions could not connect
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "'advertise_ipv6' with 'regional_resources' requires the open 'ingress_ipv6_range'",
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_validate_dual_stack() {
+    let mut aws_resources = aws::Resources {
+        ingress_ipv6_range: Some(String::from("2600:1f14::/56")),
+        advertise_ipv6: Some(true),
+        ..aws::Resources::default()
+    };
+    assert!(validate_dual_stack(&aws_resources, None).is_err());
+
+    aws_resources.dual_stack = Some(true);
+    validate_dual_stack(&aws_resources, None).unwrap();
+
+    let anchor_endpoints = AnchorEndpoints {
+        elastic_ips: true,
+        ..AnchorEndpoints::default()
+    };
+    assert!(validate_dual_stack(&aws_resources, Some(&anchor_endpoints)).is_err());
+
+    aws_resources.regional_resources = Some(Vec::new());
+    assert!(validate_dual_stack(&aws_resources, None).is_err());
+    aws_resources.ingress_ipv6_range = None;
+    validate_dual_stack(&aws_resources, None).unwrap();
+
+    aws_resources.ingress_ipv6_range = Some(String::from("10.0.0.0/16"));
+    assert!(validate_dual_stack(&aws_resources, None).is_err());
+}
+
+/// Validates the non-anchor nodes in the other regions than the primary one.
+fn validate_regional_resources(
+    aws_resources: &aws::Resources,
+    regional_resources: &[aws::RegionalResources],
+) -> io::Result<()> {
+    if aws_resources.db_backup_s3_bucket.is_some() && !regional_resources.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "'regional_resources' not supported with 'db_backup_s3_bucket'",
+        ));
+    }
+
+    let mut regions = vec![aws_resources.region.as_str()];
+    for r in regional_resources.iter() {
+        if r.region.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'regional_resources.region' cannot be empty",
+            ));
+        }
+        validate_region(&r.region)?;
+        if regions.contains(&r.region.as_str()) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'regional_resources.region' {} duplicates another region",
+                    r.region
+                ),
+            ));
+        }
+        regions.push(r.region.as_str());
+
+        if r.non_anchor_nodes < MIN_MACHINE_NON_ANCHOR_NODES {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'regional_resources.non_anchor_nodes' {} <minimum {} in {}",
+                    r.non_anchor_nodes, MIN_MACHINE_NON_ANCHOR_NODES, r.region
+                ),
+            ));
+        }
+        if r.non_anchor_nodes > MAX_MACHINE_NON_ANCHOR_NODES {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'regional_resources.non_anchor_nodes' {} >maximum {} in {}",
+                    r.non_anchor_nodes, MAX_MACHINE_NON_ANCHOR_NODES, r.region
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_validate_regional_resources() {
+    let aws_resources = aws::Resources {
+        region: String::from("us-west-2"),
+        ..aws::Resources::default()
+    };
+    assert!(validate_regional_resources(
+        &aws_resources,
+        &[
+            aws::RegionalResources::new("eu-west-1", 2),
+            aws::RegionalResources::new("ap-northeast-1", 3),
+        ],
+    )
+    .is_ok());
+    assert!(validate_regional_resources(
+        &aws_resources,
+        &[aws::RegionalResources::new("us-west-2", 2)],
+    )
+    .is_err());
+    assert!(validate_regional_resources(
+        &aws_resources,
+        &[
+            aws::RegionalResources::new("eu-west-1", 2),
+            aws::RegionalResources::new("eu-west-1", 2),
+        ],
+    )
+    .is_err());
+    assert!(validate_regional_resources(
+        &aws_resources,
+        &[aws::RegionalResources::new("eu-west-1", 0)],
+    )
+    .is_err());
+
+    assert!(validate_ipv4_cidr("0.0.0.0/0").is_ok());
+    assert!(validate_ipv4_cidr("10.0.0.0/16").is_ok());
+    assert!(validate_ipv4_cidr("10.0.0.0").is_err());
+    assert!(validate_ipv4_cidr("10.0.0.0/33").is_err());
+}
+
+#[test]
+fn test_spec_custom_network_topology() {
+    use utils::random;
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    assert!(f.write_all(&vec![0]).is_ok());
+    let bin_path = f.path().to_str().unwrap().to_string();
+
+    let opt = DefaultSpecOption {
+        log_level: String::from("info"),
+        network_name: String::from("custom"),
+        network_id: None,
+        keys_to_generate: 3,
+        region: String::from("us-west-2"),
+        regional_non_anchor_nodes: Vec::new(),
+        anchor_nodes: None,
+        non_anchor_nodes: None,
+        instance_types: Vec::new(),
+        db_backup_s3_region: String::new(),
+        db_backup_s3_bucket: String::new(),
+        db_backup_s3_key: String::new(),
+        nlb_acm_certificate_arn: String::new(),
+        install_artifacts_avalanched_bin: bin_path.clone(),
+        install_artifacts_avalanche_bin: bin_path,
+        install_artifacts_plugins_dir: String::new(),
+        avalanchego_log_level: String::from("INFO"),
+        avalanchego_whitelisted_subnets: String::new(),
+        avalanchego_http_tls_enabled: false,
+        avalanchego_state_sync_ids: String::new(),
+        avalanchego_state_sync_ips: String::new(),
+        avalanchego_profile_continuous_enabled: false,
+        avalanchego_profile_continuous_freq: String::new(),
+        avalanchego_profile_continuous_max_files: String::new(),
+        coreth_metrics_enabled: false,
+        coreth_continuous_profiler_enabled: false,
+        coreth_offline_pruning_enabled: false,
+        coreth_state_sync_enabled: false,
+        coreth_state_sync_metrics_enabled: false,
+        enable_subnet_evm: false,
+        disable_instance_system_logs: false,
+        disable_instance_system_metrics: false,
+        spec_file_path: String::new(),
+    };
+    let mut spec = Spec::default_aws(opt.clone());
+    spec.id = random::string(10);
+    assert_eq!(
+        spec.machine.anchor_nodes,
+        Some(DEFAULT_MACHINE_ANCHOR_NODES)
+    );
+    assert_eq!(
+        spec.avalanchego_config.snow_sample_size,
+        Some(DEFAULT_MACHINE_ANCHOR_NODES)
+    );
+    assert!(spec.validate().is_ok());
+
+    let p = random::tmp_path(10, Some(".yaml")).unwrap();
+    spec.sync(&p).unwrap();
+    let loaded = Spec::load(&p).unwrap();
+    assert_eq!(spec, loaded);
+    fs::remove_file(&p).unwrap();
+
+    // fewer anchor nodes than the consensus sample size
+    let mut invalid = spec.clone();
+    invalid.machine.anchor_nodes = Some(1);
+    let err = invalid.validate().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(matches!(
+        errors::Error::from_io(&err),
+        Some(errors::Error::SpecValidation { .. })
+    ));
+    assert!(!errors::is_retryable(&err));
+    let mut invalid = spec.clone();
+    invalid.avalanchego_config.snow_sample_size = None;
+    assert!(invalid.validate().is_err());
+
+    // quorum must be a majority of the sample
+    let mut invalid = spec.clone();
+    invalid.avalanchego_config.snow_quorum_size = Some(1);
+    assert!(invalid.validate().is_err());
+
+    // one anchor node outage must not halt the network
+    let mut invalid = spec.clone();
+    invalid.machine.anchor_nodes = Some(2);
+    invalid.avalanchego_config.snow_sample_size = Some(2);
+    invalid.avalanchego_config.snow_quorum_size = Some(2);
+    assert!(invalid.validate().is_err());
+    let mut valid = spec.clone();
+    valid.machine.anchor_nodes = Some(1);
+    valid.avalanchego_config.snow_sample_size = Some(1);
+    valid.avalanchego_config.snow_quorum_size = Some(1);
+    assert!(valid.validate().is_ok());
+    let mut invalid = spec.clone();
+    invalid.avalanchego_config.snow_virtuous_commit_threshold = Some(0);
+    assert!(invalid.validate().is_err());
+    let mut valid = spec.clone();
+    valid.machine.anchor_nodes = Some(5);
+    assert!(valid.validate().is_ok());
+
+    // custom network samples all anchor nodes with the avalanchego betas
+    let params = spec.consensus_parameters();
+    assert_eq!(params.k, DEFAULT_MACHINE_ANCHOR_NODES);
+    assert_eq!(params.alpha, DEFAULT_MACHINE_ANCHOR_NODES / 2 + 1);
+    assert_eq!(
+        params.beta_rogue,
+        consensus::Parameters::default().beta_rogue
+    );
+    params.verify().unwrap();
+
+    // overrides from the "default-spec" flags (or its interactive mode)
+    let mut spec = Spec::default_aws(DefaultSpecOption {
+        network_id: Some(2000000),
+        keys_to_generate: 1,
+        regional_non_anchor_nodes: vec![(String::from("eu-west-1"), 3)],
+        anchor_nodes: Some(5),
+        non_anchor_nodes: Some(4),
+        instance_types: vec![String::from("c6a.xlarge")],
+        ..opt
+    });
+    spec.id = random::string(10);
+    assert_eq!(spec.avalanchego_config.network_id, 2000000);
+    assert_eq!(spec.machine.anchor_nodes, Some(5));
+    assert_eq!(spec.machine.non_anchor_nodes, 4);
+    assert_eq!(spec.avalanchego_config.snow_sample_size, Some(5));
+    assert_eq!(spec.avalanchego_config.snow_quorum_size, Some(3));
+    assert_eq!(
+        spec.machine.instance_types,
+        vec![String::from("c6a.xlarge")]
+    );
+    let regional_resources = spec
+        .aws_resources
+        .as_ref()
+        .unwrap()
+        .regional_resources
+        .clone()
+        .unwrap();
+    assert_eq!(regional_resources.len(), 1);
+    assert_eq!(regional_resources[0].region, "eu-west-1");
+    assert_eq!(regional_resources[0].non_anchor_nodes, 3);
+    assert!(spec.validate().is_ok());
+
+    let mut invalid = spec.clone();
+    invalid.machine.instance_types = vec![String::from("c6a")];
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn test_spec() {
+    use std::fs;
+    use utils::random;
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    let ret = f.write_all(&vec![0]);
+    assert!(ret.is_ok());
+    let avalanched_bin = f.path().to_str().unwrap();
+
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    let ret = f.write_all(&vec![0]);
+    assert!(ret.is_ok());
+    let avalanchego_bin = f.path().to_str().unwrap();
+
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let plugin_path = tmp_dir.path().join(random::string(10));
+    let mut f = File::create(&plugin_path).unwrap();
+    let ret = f.write_all(&vec![0]);
+    assert!(ret.is_ok());
+    let plugins_dir = tmp_dir.path().as_os_str().to_str().unwrap();
+
+    // test just to see how "read_dir" works in Rust
+    for entry in fs::read_dir(plugins_dir).unwrap() {
+        let entry = entry.unwrap();
+        let path = entry.path();
+        info!("read_dir: {:?}", path);
+    }
+
+    let id = random::string(10);
+    let bucket = format!("test-{}", time::get(8));
+
+    let contents = format!(
+        r#"
+
+id: {}
+
+aws_resources:
+  region: us-west-2
+  s3_bucket: {}
+  instance_system_logs: true
+  instance_system_metrics: true
+
+machine:
+  non_anchor_nodes: 20
+  arch: amd64
+  instance_types:
+  - m5.large
+  - c5.large
+  - r5.large
+  - t3.large
+
+install_artifacts:
+  avalanched_bin: {}
+  avalanchego_bin: {}
+  plugins_dir: {}
+
+avalanchego_config:
+  config-file: /etc/avalanche.config.json
+  network-id: 1
+  db-type: leveldb
+  db-dir: /avalanche-data
+  log-dir: /var/log/avalanche
+  log-level: INFO
+  http-port: 9650
+  http-host: 0.0.0.0
+  http-tls-enabled: false
+  staking-enabled: true
+  staking-port: 9651
+  staking-tls-key-file: "/etc/pki/tls/certs/avalanched.pki.key"
+  staking-tls-cert-file: "/etc/pki/tls/certs/avalanched.pki.crt"
+  staking-signer-key-file: "/etc/pki/tls/certs/avalanched.signer.key"
+  snow-sample-size: 20
+  snow-quorum-size: 15
+  index-enabled: false
+  index-allow-incomplete: false
+  api-admin-enabled: true
+  api-info-enabled: true
+  api-keystore-enabled: true
+  api-metrics-enabled: true
+  api-health-enabled: true
+  api-ipcs-enabled: true
+  chain-config-dir: /etc/avalanche/configs/chains
+  subnet-config-dir: /etc/avalanche/configs/subnets
+  profile-dir: /var/log/avalanche-profile/avalanche
+
+coreth_config:
+  coreth-admin-api-enabled: true
+  metrics-enabled: true
+  log-level: "info"
+
+
+"#,
+        id, bucket, avalanched_bin, avalanchego_bin, plugins_dir,
+    );
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    let ret = f.write_all(contents.as_bytes());
+    assert!(ret.is_ok());
+    let config_path = f.path().to_str().unwrap();
+
+    let ret = Spec::load(config_path);
+    assert!(ret.is_ok());
+    let cfg = ret.unwrap();
+
+    let ret = cfg.sync(config_path);
+    assert!(ret.is_ok());
+
+    let mut avalanchego_config = avalanchego_config::Config::default();
+    avalanchego_config.genesis = None;
+    avalanchego_config.network_id = 1;
+
+    let orig = Spec {
+        id: id.clone(),
+
+        aws_resources: Some(aws::Resources {
+            region: String::from("us-west-2"),
+            s3_bucket: bucket.clone(),
+            ..aws::Resources::default()
+        }),
+
+        machine: Machine {
+            anchor_nodes: None,
+            non_anchor_nodes: 20,
+            arch: "amd64".to_string(),
+            instance_types: vec![
+                String::from("m5.large"),
+                String::from("c5.large"),
+                String::from("r5.large"),
+                String::from("t3.large"),
+            ],
+            purchase_options: None,
+            data_volume: None,
+            anchor_endpoints: None,
+            api_nodes: None,
+        },
+
+        install_artifacts: InstallArtifacts {
+            avalanched_bin: avalanched_bin.to_string(),
+            avalanchego_bin: avalanchego_bin.to_string(),
+            plugins_dir: Some(plugins_dir.to_string()),
+        },
+
+        avalanchego_config,
+        coreth_config: coreth_config::Config::default(),
+        avalanchego_genesis_template: None,
+
+        subnet_evm_genesis: None,
+
+        generated_seed_private_key_with_locked_p_chain_balance: None,
+        generated_seed_private_keys: None,
+        validator_registration: None,
+        restart_policy: None,
+        termination_policy: None,
+        state_backend: None,
+        state_serial: None,
+        upgrade_policy: None,
+        config_update_policy: None,
+        chaos_policy: None,
+        clone_source: None,
+        retention_policy: None,
+        ingress_policy: None,
+        public_ip_policy: None,
+        status_api_policy: None,
+        index_archiver: None,
+        load_balancer: None,
+        subnet: None,
+        alert_rules: None,
+        notifications: None,
+        api_tls: None,
+        current_nodes: None,
+        endpoints: None,
+    };
+
+    assert_eq!(cfg, orig);
+    cfg.validate().expect("unexpected validate failure");
+    orig.validate().expect("unexpected validate failure");
+
+    // seed private keys are redacted for the remote storage
+    assert!(orig.seed_private_keys().is_none());
+    let mut with_keys = orig.clone();
+    with_keys.generated_seed_private_keys = Some(vec![soft_key::TEST_KEYS[0]
+        .info(1)
+        .expect("unexpected info failure")]);
+    let keys = with_keys.seed_private_keys().unwrap();
+    assert_eq!(
+        SeedPrivateKeys::decode(&keys.encode().unwrap()).unwrap(),
+        keys
+    );
+    let mut redacted = with_keys.redacted();
+    assert!(redacted.is_redacted());
+    assert!(!with_keys.is_redacted());
+    let redacted_key = &redacted.generated_seed_private_keys.as_ref().unwrap()[0];
+    assert!(redacted_key.private_key_hex.is_empty());
+    assert_eq!(
+        redacted_key.x_address,
+        keys.generated_seed_private_keys.as_ref().unwrap()[0].x_address
+    );
+    redacted.restore_seed_private_keys(keys);
+    assert_eq!(redacted, with_keys);
+
+    // keystore passwords are shipped with the seed private keys
+    let mut with_users = orig.clone();
+    with_users.machine.api_nodes = Some(ApiNodes {
+        keystore_users: vec![KeystoreUser {
+            username: String::from("test-user"),
+            password: String::from("aaFUjfEJPbr3x!Zq"),
+            import_seed_private_keys: false,
+        }],
+        ..ApiNodes::default()
+    });
+    let keys = with_users.seed_private_keys().unwrap();
+    let mut redacted = with_users.redacted();
+    assert!(redacted.is_redacted());
+    assert!(
+        redacted.machine.api_nodes.as_ref().unwrap().keystore_users[0]
+            .password
+            .is_empty()
+    );
+    redacted.restore_seed_private_keys(keys);
+    assert_eq!(redacted, with_users);
+
+    // Slack webhook URLs embed the channel token
+    let mut with_slack = orig.clone();
+    with_slack.notifications = Some(notify::NotificationPolicy {
+        sinks: vec![notify::Sink {
+            target: notify::SinkTarget::Slack {
+                webhook_url: String::from("https://hooks.slack.com/services/T000/B000/XXX"),
+            },
+            min_severity: alert::Severity::Info,
+        }],
+        ..notify::NotificationPolicy::default()
+    });
+    let keys = with_slack.seed_private_keys().unwrap();
+    let mut redacted = with_slack.redacted();
+    assert!(redacted.is_redacted());
+    assert!(!redacted.encode_yaml().unwrap().contains("hooks.slack.com"));
+    redacted.restore_seed_private_keys(keys);
+    assert_eq!(redacted, with_slack);
+
+    // manually check to make sure the serde deserializer works
+    assert_eq!(cfg.id, id);
+
+    let aws_resources = cfg.aws_resources.clone().unwrap();
+    assert_eq!(aws_resources.region, "us-west-2");
+    assert_eq!(aws_resources.s3_bucket, bucket);
+
+    let mut invalid = cfg.clone();
+    invalid.aws_resources = Some(aws::Resources {
+        instance_logs_retention_in_days: Some(10),
+        ..aws_resources.clone()
+    });
+    assert!(invalid.validate().is_err());
+    invalid.aws_resources = Some(aws::Resources {
+        cloudwatch_telemetry_interval_seconds: Some(0),
+        ..aws_resources.clone()
+    });
+    assert!(invalid.validate().is_err());
+
+    assert_eq!(cfg.install_artifacts.avalanched_bin, avalanched_bin);
+    assert_eq!(cfg.install_artifacts.avalanchego_bin, avalanchego_bin);
+    assert_eq!(
+        cfg.install_artifacts
+            .plugins_dir
+            .unwrap_or(String::from("")),
+        plugins_dir.to_string()
+    );
+
+    assert!(cfg.machine.anchor_nodes.is_none());
+    assert_eq!(cfg.machine.non_anchor_nodes, 20);
+    let instance_types = cfg.machine.instance_types;
+    assert_eq!(instance_types[0], "m5.large");
+    assert_eq!(instance_types[1], "c5.large");
+    assert_eq!(instance_types[2], "r5.large");
+    assert_eq!(instance_types[3], "t3.large");
+
+    assert_eq!(cfg.avalanchego_config.clone().network_id, 1);
+    assert_eq!(
+        cfg.avalanchego_config
+            .clone()
+            .config_file
+            .unwrap_or("".to_string()),
+        avalanchego_config::DEFAULT_CONFIG_FILE_PATH,
+    );
+    assert_eq!(
+        cfg.avalanchego_config.clone().snow_sample_size.unwrap_or(0),
+        20
+    );
+    assert_eq!(
+        cfg.avalanchego_config.clone().snow_quorum_size.unwrap_or(0),
+        15
+    );
+    assert_eq!(
+        cfg.avalanchego_config.clone().http_port,
+        avalanchego_config::DEFAULT_HTTP_PORT,
+    );
+    assert_eq!(
+        cfg.avalanchego_config.clone().staking_port,
+        avalanchego_config::DEFAULT_STAKING_PORT,
+    );
+    assert_eq!(
+        cfg.avalanchego_config.clone().db_dir,
+        avalanchego_config::DEFAULT_DB_DIR,
+    );
+}
+
+fn redact_private_key(k: &mut soft_key::PrivateKeyInfo) {
+    k.mnemonic_phrase = None;
+    k.private_key = String::new();
+    k.private_key_hex = String::new();
+}
+
+/// Represents the pre-funded seed private keys (and the keystore users
+/// with their passwords, and the Slack webhook URLs)
+/// that are removed from the spec in the remote storage,
+/// and shipped envelope-encrypted with the KMS CMK
+/// (see "StorageNamespace::SeedPrivateKeysEncrypted").
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SeedPrivateKeys {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated_seed_private_key_with_locked_p_chain_balance: Option<soft_key::PrivateKeyInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated_seed_private_keys: Option<Vec<soft_key::PrivateKeyInfo>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keystore_users: Vec<KeystoreUser>,
+    /// In the order of the Slack sinks of "Spec.notifications".
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slack_webhook_urls: Vec<String>,
+}
+
+impl SeedPrivateKeys {
+    pub fn encode(&self) -> io::Result<Vec<u8>> {
+        serde_yaml::to_vec(self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize SeedPrivateKeys to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+}
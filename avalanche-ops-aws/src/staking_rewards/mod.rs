@@ -0,0 +1,279 @@
+use std::{
+    io::{self, Error, ErrorKind, Write},
+    str::FromStr,
+    time::SystemTime,
+};
+
+use chrono::DateTime;
+use clap::{Arg, Command};
+use log::{info, warn};
+use serde::Serialize;
+use tokio::runtime::Runtime;
+
+use avalanche_api::{index as api_index, p as api_p};
+use avalanche_ops_aws::{output, StakingReward};
+use avalanche_types::{
+    api::index,
+    ids,
+    platformvm::{self, blocks, txs},
+    units,
+};
+use utils::http;
+
+pub const NAME: &str = "staking-rewards";
+
+pub fn command() -> Command<'static> {
+    Command::new(NAME)
+        .about("Reports the staking rewards of the node by walking the P-chain blocks via the index API (requires '--index-enabled')")
+        .arg(
+            Arg::new("LOG_LEVEL")
+                .long("log-level")
+                .short('l')
+                .help("Sets the log level")
+                .required(false)
+                .takes_value(true)
+                .possible_value("debug")
+                .possible_value("info")
+                .allow_invalid_utf8(false)
+                .default_value("info"),
+        )
+        .arg(
+            Arg::new("SPEC_FILE_PATH")
+                .long("spec-file-path")
+                .short('s')
+                .help("The spec file to load")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("NODE_ID")
+                .long("node-id")
+                .short('n')
+                .help("The node ID of the validator (e.g., NodeID-...)")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("START_TIME")
+                .long("start-time")
+                .help("Reports the stakers whose staking period ended at or after this RFC 3339 time (defaults to the genesis)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("END_TIME")
+                .long("end-time")
+                .help("Reports the stakers whose staking period ended at or before this RFC 3339 time (defaults to now)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+}
+
+/// Staking rewards of the node whose staking periods ended in the range.
+#[derive(Debug, Serialize)]
+struct Report {
+    node_id: String,
+    start: u64,
+    end: u64,
+    total: units::Avax,
+    rewards: Vec<StakingReward>,
+}
+
+pub fn execute(
+    log_level: &str,
+    spec_file_path: &str,
+    node_id: &str,
+    start_time: Option<&str>,
+    end_time: Option<&str>,
+    output: output::Format,
+) -> io::Result<()> {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    );
+
+    let node_id = ids::NodeId::from_str(node_id)?;
+    let start = match start_time {
+        Some(s) => parse_unix_time(s)?,
+        None => 0,
+    };
+    let end = match end_time {
+        Some(s) => parse_unix_time(s)?,
+        None => SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?
+            .as_secs(),
+    };
+    if start > end {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("start time {} is after end time {}", start, end),
+        ));
+    }
+
+    let spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
+    http::set_default_tls(spec.api_tls.clone())?;
+    let http_rpc = spec.endpoints.and_then(|eps| eps.http_rpc).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            "no 'endpoints.http_rpc' in the spec (run 'apply' first)",
+        )
+    })?;
+
+    let rt = Runtime::new().unwrap();
+    let blks = rt.block_on(fetch_blocks(&http_rpc))?;
+    let decisions = blocks::reward_decisions(&blks);
+    info!(
+        "found {} reward decision(s) in {} P-chain block(s)",
+        decisions.len(),
+        blks.len()
+    );
+
+    let mut rewards: Vec<StakingReward> = Vec::new();
+    for decision in decisions.iter() {
+        let staker_tx_id = decision.staker_tx_id.to_string();
+        let resp = rt.block_on(api_p::get_tx(&http_rpc, &staker_tx_id))?;
+        let tx_bytes = resp
+            .result
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!("no staker tx {}", staker_tx_id),
+                )
+            })?
+            .decode_tx()?;
+
+        let (validator, delegator) = match decode_staker(&tx_bytes)? {
+            Some(v) => v,
+            None => continue,
+        };
+        if validator.node_id.as_bytes() != node_id.as_bytes() {
+            continue;
+        }
+        // filtered before fetching the reward UTXOs
+        if !(start..=end).contains(&validator.end) {
+            continue;
+        }
+
+        let mut reward = units::Avax::ZERO;
+        if decision.rewarded {
+            let resp = rt.block_on(api_p::get_reward_utxos(&http_rpc, &staker_tx_id))?;
+            for utxo in resp.result.unwrap_or_default().decode_utxos()?.iter() {
+                reward = reward
+                    .checked_add(units::Avax::from_navax(utxo.amount()))
+                    .ok_or_else(|| Error::new(ErrorKind::InvalidData, "reward overflows u64"))?;
+            }
+        }
+        rewards.push(StakingReward {
+            staker_tx_id: decision.staker_tx_id,
+            delegator,
+            start: validator.start,
+            end: validator.end,
+            weight: units::Avax::from_navax(validator.weight),
+            rewarded: decision.rewarded,
+            reward,
+        });
+    }
+
+    let (rewards, total) = avalanche_ops_aws::staking_rewards_in_range(&rewards, start, end)?;
+    writeln!(output.console())?;
+    for r in rewards.iter() {
+        writeln!(
+            output.console(),
+            "{} ({}): staked {} from {} to {}, {}",
+            r.staker_tx_id,
+            if r.delegator {
+                "delegator"
+            } else {
+                "validator"
+            },
+            r.weight,
+            r.start,
+            r.end,
+            if r.rewarded {
+                format!("rewarded {}", r.reward)
+            } else {
+                String::from("not rewarded")
+            }
+        )?;
+    }
+    writeln!(
+        output.console(),
+        "\n{}: {} earned by {} staker(s) ended between {} and {}\n",
+        node_id,
+        total,
+        rewards.len(),
+        start,
+        end
+    )?;
+
+    output.print(&Report {
+        node_id: node_id.to_string(),
+        start,
+        end,
+        total,
+        rewards,
+    })
+}
+
+/// Fetches and decodes all the accepted P-chain blocks in the order of acceptance.
+async fn fetch_blocks(http_rpc: &str) -> io::Result<Vec<blocks::Block>> {
+    let last = api_index::get_last_accepted(http_rpc, &index::Index::PBlock)
+        .await?
+        .result
+        .map_or(0, |c| c.index + 1);
+
+    let mut blks: Vec<blocks::Block> = Vec::new();
+    let mut cur = 0;
+    while cur < last {
+        let num_to_fetch = (last - cur).min(index::MAX_FETCHED_BY_RANGE);
+        let resp =
+            api_index::get_container_range(http_rpc, &index::Index::PBlock, cur, num_to_fetch)
+                .await?;
+        let fetched = resp.result.unwrap_or_default();
+        if fetched.is_empty() {
+            break;
+        }
+        cur += fetched.len() as u64;
+        for c in fetched.iter() {
+            blks.push(blocks::Block::from_bytes(&c.decode_bytes()?)?);
+        }
+    }
+    Ok(blks)
+}
+
+/// Decodes the validator of the primary network staker tx,
+/// and whether it is the delegator. Returns "None" for the other txs
+/// (e.g., the elastic subnet stakers rewarded in the subnet asset).
+fn decode_staker(tx_bytes: &[u8]) -> io::Result<Option<(platformvm::Validator, bool)>> {
+    let (tx, _) = txs::Tx::from_bytes(tx_bytes)?;
+    let staker = match tx {
+        txs::Tx::AddValidator(tx) => (tx.validator, false),
+        txs::Tx::AddDelegator(tx) => (tx.validator, true),
+        txs::Tx::AddPermissionlessValidator(tx) if tx.subnet_id == ids::Id::empty() => {
+            (tx.validator, false)
+        }
+        txs::Tx::AddPermissionlessDelegator(tx) if tx.subnet_id == ids::Id::empty() => {
+            (tx.validator, true)
+        }
+        tx => {
+            warn!("skipping staker tx {}", tx.type_name());
+            return Ok(None);
+        }
+    };
+    Ok(Some(staker))
+}
+
+fn parse_unix_time(s: &str) -> io::Result<u64> {
+    let t = DateTime::parse_from_rfc3339(s).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid RFC 3339 time '{}' ({})", s, e),
+        )
+    })?;
+    Ok(t.timestamp().max(0) as u64)
+}
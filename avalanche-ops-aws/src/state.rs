@@ -0,0 +1,108 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+/// Defines the lock that serializes "apply" and "delete" across operators,
+/// similar to the Terraform state locking. The spec in the remote storage
+/// is the state, versioned with "Spec.state_serial".
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct StateBackend {
+    /// DynamoDB table of the locks, keyed by the spec ID,
+    /// so one table is shared by all deployments.
+    /// Created on the first "apply" if not exists.
+    #[serde(default = "default_state_lock_table")]
+    pub lock_table: String,
+    /// Lease of the lock, after which other operators may take over the lock
+    /// (e.g., the lock holder crashed). MUST be longer than "apply".
+    #[serde(default = "default_state_lock_lease_seconds")]
+    pub lock_lease_seconds: u64,
+}
+
+fn default_state_lock_table() -> String {
+    String::from("avalanche-ops-state-lock")
+}
+
+fn default_state_lock_lease_seconds() -> u64 {
+    3 * 60 * 60
+}
+
+impl Default for StateBackend {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl StateBackend {
+    pub fn default() -> Self {
+        Self {
+            lock_table: default_state_lock_table(),
+            lock_lease_seconds: default_state_lock_lease_seconds(),
+        }
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        // ref. https://docs.aws.amazon.com/amazondynamodb/latest/developerguide/HowItWorks.NamingRulesDataTypes.html
+        if self.lock_table.len() < 3
+            || self.lock_table.len() > 255
+            || !self
+                .lock_table
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid 'lock_table' name '{}'", self.lock_table),
+            ));
+        }
+        if self.lock_lease_seconds < 600 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'lock_lease_seconds' {} too short (expected >= 600)",
+                    self.lock_lease_seconds
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Rejects the local spec older than the one in the remote storage,
+/// which was applied by other operator since the local spec was synced.
+pub fn check_state_serial(local: Option<u64>, remote: Option<u64>) -> io::Result<()> {
+    let (local, remote) = (local.unwrap_or(0), remote.unwrap_or(0));
+    if local < remote {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "stale spec (local 'state_serial' {} < remote {}), sync the spec from the remote storage first",
+                local, remote
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_state_backend() {
+    let backend: StateBackend = serde_yaml::from_str("lock_lease_seconds: 7200").unwrap();
+    assert_eq!(backend.lock_table, "avalanche-ops-state-lock");
+    assert_eq!(backend.lock_lease_seconds, 7200);
+    backend.validate().unwrap();
+
+    let mut invalid = backend.clone();
+    invalid.lock_table = String::from("my table");
+    assert!(invalid.validate().is_err());
+    let mut invalid = backend;
+    invalid.lock_lease_seconds = 60;
+    assert!(invalid.validate().is_err());
+
+    check_state_serial(None, None).unwrap();
+    check_state_serial(Some(3), Some(3)).unwrap();
+    check_state_serial(Some(4), Some(3)).unwrap();
+    check_state_serial(Some(3), None).unwrap();
+    let err = check_state_serial(Some(2), Some(3)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(check_state_serial(None, Some(1)).is_err());
+}
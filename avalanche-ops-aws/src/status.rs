@@ -0,0 +1,188 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Error, ErrorKind},
+};
+
+use serde::{Deserialize, Serialize};
+
+use avalanchego::config as avalanchego_config;
+
+use crate::Node;
+
+/// Defines the local status endpoint of "avalanched"
+/// ("http://127.0.0.1:[PORT]/status"), which returns "NodeStatus" in JSON.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct StatusApiPolicy {
+    /// Only bound to the loopback interface, so no ingress rule is needed.
+    /// Zero to disable the endpoint.
+    #[serde(default = "default_status_api_port")]
+    pub port: u16,
+}
+
+fn default_status_api_port() -> u16 {
+    9670
+}
+
+impl Default for StatusApiPolicy {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl StatusApiPolicy {
+    pub fn default() -> Self {
+        Self {
+            port: default_status_api_port(),
+        }
+    }
+
+    pub fn validate(&self, avalanchego_config: &avalanchego_config::Config) -> io::Result<()> {
+        let port = self.port as u32;
+        if port != 0
+            && (port == avalanchego_config.http_port || port == avalanchego_config.staking_port)
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'status_api_policy.port' {} conflicts with the avalanchego ports",
+                    self.port
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// State of the local node as seen by "avalanched".
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum BootstrapState {
+    /// "avalanched" is setting up the node (e.g., downloading the database).
+    Starting,
+    /// The node is running but not healthy yet.
+    Bootstrapping,
+    Healthy,
+    /// The node was healthy before, and fails the health check now.
+    Unhealthy,
+}
+
+impl BootstrapState {
+    pub fn as_str(&self) -> &str {
+        match self {
+            BootstrapState::Starting => "starting",
+            BootstrapState::Bootstrapping => "bootstrapping",
+            BootstrapState::Healthy => "healthy",
+            BootstrapState::Unhealthy => "unhealthy",
+        }
+    }
+}
+
+/// Warning or error that "avalanched" logged.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct StatusError {
+    /// Unix timestamp.
+    pub at: u64,
+    pub message: String,
+}
+
+/// Returned by the local status endpoint of "avalanched",
+/// for "run-command --command status" and the monitoring sidecars.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct NodeStatus {
+    pub id: String,
+    pub kind: String,
+    pub machine_id: String,
+    pub node_id: String,
+    pub bootstrap_state: BootstrapState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bootstrap_progress_percent: Option<f64>,
+    /// Unix timestamp of the last data volume snapshot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_backup_at: Option<u64>,
+    /// Installed versions by component
+    /// (e.g., "avalanched", "avalanchego", "evm").
+    #[serde(default)]
+    pub versions: BTreeMap<String, String>,
+    /// Most recent first.
+    #[serde(default)]
+    pub recent_errors: Vec<StatusError>,
+    /// Unix timestamp.
+    pub updated_at: u64,
+}
+
+impl NodeStatus {
+    pub fn new(id: &str, local_node: &Node) -> Self {
+        Self {
+            id: id.to_string(),
+            kind: local_node.kind.clone(),
+            machine_id: local_node.machine_id.clone(),
+            node_id: local_node.node_id.clone(),
+            bootstrap_state: BootstrapState::Starting,
+            bootstrap_progress_percent: None,
+            last_backup_at: None,
+            versions: BTreeMap::new(),
+            recent_errors: Vec::new(),
+            updated_at: 0,
+        }
+    }
+
+    pub fn encode_json(&self) -> io::Result<String> {
+        serde_json::to_string_pretty(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize NodeStatus to JSON {}", e),
+            )
+        })
+    }
+
+    pub fn decode_json(d: &[u8]) -> io::Result<Self> {
+        serde_json::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid JSON: {}", e)))
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-ops-aws --lib -- status::test_node_status --exact --show-output
+#[test]
+fn test_node_status() {
+    let mut avalanchego_config = avalanchego_config::Config::default();
+    StatusApiPolicy::default()
+        .validate(&avalanchego_config)
+        .unwrap();
+    avalanchego_config.http_port = 9670;
+    assert!(StatusApiPolicy::default()
+        .validate(&avalanchego_config)
+        .is_err());
+    StatusApiPolicy { port: 0 }
+        .validate(&avalanchego_config)
+        .unwrap();
+
+    let policy: StatusApiPolicy = serde_yaml::from_str("{}").unwrap();
+    assert_eq!(policy, StatusApiPolicy::default());
+
+    let mut status = NodeStatus {
+        id: String::from("aops-test"),
+        kind: String::from("non-anchor"),
+        machine_id: String::from("i-0123456789abcdef0"),
+        node_id: String::from("NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg"),
+        bootstrap_state: BootstrapState::Bootstrapping,
+        bootstrap_progress_percent: Some(42.5),
+        last_backup_at: None,
+        versions: BTreeMap::new(),
+        recent_errors: vec![StatusError {
+            at: 1650000000,
+            message: String::from("failed to put metric data"),
+        }],
+        updated_at: 1650000001,
+    };
+    status.versions.insert(
+        String::from("avalanchego"),
+        String::from("avalanche/1.7.10"),
+    );
+    let d = status.encode_json().unwrap();
+    assert!(d.contains("\"bootstrap_state\": \"bootstrapping\""));
+    assert!(!d.contains("last_backup_at"));
+    assert_eq!(NodeStatus::decode_json(d.as_bytes()).unwrap(), status);
+}
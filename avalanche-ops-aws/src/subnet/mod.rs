@@ -0,0 +1,818 @@
+use std::{
+    fs,
+    io::{self, Error, ErrorKind, Write},
+    str::FromStr,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use clap::{Arg, Command};
+use crossterm::{
+    execute,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+};
+use dialoguer::{theme::ColorfulTheme, Select};
+use log::{info, warn};
+use serde::Serialize;
+use tokio::runtime::Runtime;
+
+use avalanche_api::{info as api_info, p as api_p, x as api_x};
+use avalanche_ops_aws::{output, provider};
+use avalanche_types::{
+    avax, formatting, ids,
+    platformvm::{
+        self,
+        txs::{create_chain, SubnetBuilder},
+    },
+    secp256k1fx,
+    signer::{PartialCredentials, Signer},
+    soft_key, units, utxo,
+};
+use aws::{self, s3};
+use utils::{compress, http, random};
+
+pub const NAME: &str = "subnet";
+
+pub fn command() -> Command<'static> {
+    Command::new(NAME)
+        .about("Creates the subnet, adds its validators, installs the VM, creates the blockchain, and optionally makes the subnet elastic based on the spec file")
+        .arg(
+            Arg::new("LOG_LEVEL")
+                .long("log-level")
+                .short('l')
+                .help("Sets the log level")
+                .required(false)
+                .takes_value(true)
+                .possible_value("debug")
+                .possible_value("info")
+                .allow_invalid_utf8(false)
+                .default_value("info"),
+        )
+        .arg(
+            Arg::new("SPEC_FILE_PATH")
+                .long("spec-file-path")
+                .short('s')
+                .help("The spec file to load and update")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("SKIP_PROMPT")
+                .long("skip-prompt")
+                .short('s')
+                .help("Skips prompt mode")
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("LEDGER_ADDRESS_INDEX")
+                .long("ledger-address-index")
+                .help("Makes the Ledger address \"m/44'/9000'/0'/0/{index}\" the subnet owner, which authorizes the subnet txs on the device (requires the \"ledger\" feature)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+}
+
+/// Maximum wait for a P-chain tx to be committed.
+const TX_COMMIT_TIMEOUT_SECONDS: u64 = 2 * 60;
+/// Maximum wait for every node to restart with the VM and the subnet.
+const NODES_TRACKING_TIMEOUT_SECONDS: u64 = 30 * 60;
+/// The validation start time must be in the future when the tx is accepted.
+const VALIDATOR_START_DELAY_SECONDS: u64 = 60;
+
+/// Runs the steps in order, skipping the ones already checkpointed
+/// in "subnet.status". Each checkpoint is synced to the spec file
+/// and S3, so the command resumes from the failed step when re-run.
+/// Describes the deployed subnet, for the structured output.
+#[derive(Debug, Serialize)]
+struct Deployed {
+    id: String,
+    #[serde(flatten)]
+    status: avalanche_ops_aws::SubnetStatus,
+    rpc_endpoint: String,
+}
+
+pub fn execute(
+    log_level: &str,
+    spec_file_path: &str,
+    skip_prompt: bool,
+    ledger_address_index: Option<u32>,
+    output: output::Format,
+) -> io::Result<()> {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    );
+
+    let mut spec = avalanche_ops_aws::Spec::load(spec_file_path).expect("failed to load spec");
+    spec.validate()?;
+    http::set_default_tls(spec.api_tls.clone())?;
+    let subnet = spec
+        .subnet
+        .clone()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "no 'subnet' in the spec"))?;
+    let http_rpc = spec
+        .endpoints
+        .clone()
+        .and_then(|eps| eps.http_rpc)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "no 'endpoints.http_rpc' in the spec (run 'apply' first)",
+            )
+        })?;
+    let network_id = spec.avalanchego_config.network_id;
+
+    // validated to be non-empty
+    let funding_key = spec.generated_seed_private_keys.clone().unwrap()[0].clone();
+    let key = soft_key::Key::from_private_key(&funding_key.private_key)?;
+
+    // the seed key still pays the fees
+    let owner_signer = match ledger_address_index {
+        Some(index) => Some(open_ledger(index)?),
+        None => None,
+    };
+    let owner_address = owner_signer
+        .as_ref()
+        .map_or(key.short_address, |s| s.short_address());
+    let subnet_owner = secp256k1fx::OutputOwners::new(0, 1, &[owner_address]);
+    if let Some(recorded) = subnet
+        .status
+        .as_ref()
+        .and_then(|st| st.owner_address.clone())
+    {
+        if recorded != owner_address.to_string() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "subnet is owned by {} but the selected owner key is {}",
+                    recorded, owner_address
+                ),
+            ));
+        }
+    }
+
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Blue),
+        Print(format!("\nLoaded Spec: '{}'\n", spec_file_path)),
+        ResetColor
+    )?;
+    let spec_contents = spec.encode_yaml()?;
+    writeln!(output.console(), "{}\n", spec_contents)?;
+
+    if !skip_prompt {
+        let options = &[
+            "No, I am not ready to deploy the subnet!",
+            "Yes, let's deploy the subnet!",
+        ];
+        let selected = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select your 'subnet' option")
+            .items(&options[..])
+            .default(0)
+            .interact()
+            .unwrap();
+        if selected == 0 {
+            return Ok(());
+        }
+    }
+
+    let rt = Runtime::new().unwrap();
+    let aws_resources = spec.aws_resources.clone().unwrap();
+    let shared_config = rt
+        .block_on(aws::load_config(Some(aws_resources.region.clone())))
+        .expect("failed to aws::load_config");
+    let s3_manager = s3::Manager::new(&shared_config);
+    let provider = provider::AwsProvider::new(&shared_config, &aws_resources);
+
+    let wallet = Wallet::new(&rt, &http_rpc, network_id, key, owner_signer)?;
+    let mut status = subnet.status.clone().unwrap_or_default();
+    let checkpoint = |spec: &mut avalanche_ops_aws::Spec,
+                      status: &avalanche_ops_aws::SubnetStatus|
+     -> io::Result<()> {
+        if let Some(subnet) = spec.subnet.as_mut() {
+            subnet.status = Some(status.clone());
+        }
+        spec.sync(spec_file_path)?;
+        crate::apply::put_spec(&rt, &provider, spec)
+    };
+
+    if status.subnet_id.is_none() {
+        print_step(output, "create subnet")?;
+        let (mut tx, signers) = wallet.builder.new_create_subnet_tx(
+            &wallet.utxos(&rt)?,
+            subnet_owner.clone(),
+            &wallet.key.short_address,
+            unix_now(),
+        )?;
+        let (signed_bytes, tx_id) = tx.sign(&signers)?;
+        wallet.issue(&rt, &signed_bytes, tx_id, "CreateSubnetTx")?;
+
+        status.subnet_id = Some(tx_id.to_string());
+        status.owner_address = Some(owner_address.to_string());
+        checkpoint(&mut spec, &status)?;
+    }
+    let subnet_id = ids::Id::from_str(status.subnet_id.as_ref().unwrap())?;
+    info!("subnet ID {}", subnet_id);
+
+    let primary_validators = rt
+        .block_on(api_p::get_current_validators(&http_rpc))?
+        .result
+        .and_then(|r| r.validators)
+        .unwrap_or_default();
+    let current_nodes = spec.current_nodes.clone().unwrap_or_default();
+    let validator_node_ids: Vec<String> = if subnet.validator_node_ids.is_empty() {
+        current_nodes
+            .iter()
+            .filter(|n| {
+                primary_validators
+                    .iter()
+                    .any(|v| v.node_id.map(|id| id.to_string()).as_ref() == Some(&n.node_id))
+            })
+            .map(|n| n.node_id.clone())
+            .collect()
+    } else {
+        subnet.validator_node_ids.clone()
+    };
+    if validator_node_ids.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "no primary network validator to validate the subnet",
+        ));
+    }
+    let validator_set = validator_node_ids
+        .iter()
+        .map(|node_id| ids::NodeId::from_str(node_id))
+        .collect::<io::Result<ids::NodeIds>>()?;
+    info!(
+        "{} subnet validator(s) {}",
+        validator_set.len(),
+        validator_set.short_strings(6).join(", ")
+    );
+    for node_id in validator_node_ids.iter() {
+        if status.validator_tx_ids.contains_key(node_id) {
+            continue;
+        }
+        print_step(output, &format!("add subnet validator {}", node_id))?;
+
+        let parsed_node_id = ids::NodeId::from_str(node_id)?;
+        let primary_end = primary_validators
+            .iter()
+            .find(|v| v.node_id == Some(parsed_node_id))
+            .and_then(|v| v.end_time)
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{} is not a primary network validator", node_id),
+                )
+            })?;
+        let now = unix_now();
+        let start = now + VALIDATOR_START_DELAY_SECONDS;
+        let end = (start + subnet.validator_period_seconds).min(primary_end);
+        if end <= start {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{} stops validating the primary network at {} before the subnet validation starts",
+                    node_id, primary_end
+                ),
+            ));
+        }
+
+        let (tx, signers) = wallet.builder.new_add_subnet_validator_tx(
+            &wallet.utxos(&rt)?,
+            platformvm::Validator {
+                node_id: parsed_node_id.short_id(),
+                start,
+                end,
+                weight: subnet.validator_weight,
+            },
+            subnet_id,
+            &subnet_owner,
+            &wallet.key.short_address,
+            now,
+        )?;
+        let (signed_bytes, tx_id) = wallet.sign_subnet_tx(
+            &rt,
+            &tx.unsigned_bytes()?,
+            &signers,
+            &subnet_owner,
+            &tx.subnet_auth,
+        )?;
+        wallet.issue(&rt, &signed_bytes, tx_id, "AddSubnetValidatorTx")?;
+
+        status
+            .validator_tx_ids
+            .insert(node_id.clone(), tx_id.to_string());
+        checkpoint(&mut spec, &status)?;
+    }
+
+    let vm_plugin_sha256 = avalanche_ops_aws::sha256_file_hex(&subnet.vm_plugin_path)?;
+    if status.vm_plugin_sha256.as_deref() != Some(vm_plugin_sha256.as_str()) {
+        print_step(output, "install VM plugin and track subnet on every node")?;
+        if current_nodes.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "no 'current_nodes' in the spec (run 'apply' first)",
+            ));
+        }
+
+        // compress as these will be decompressed by "avalanched",
+        // also uploaded to each install bucket for the new nodes
+        let tmp_plugin_compressed_path =
+            random::tmp_path(15, Some(compress::Encoder::Zstd(3).ext()))?;
+        compress::pack_file(
+            &subnet.vm_plugin_path,
+            &tmp_plugin_compressed_path,
+            compress::Encoder::Zstd(3),
+        )?;
+        let plugin_s3_key = format!(
+            "{}/{}{}",
+            avalanche_ops_aws::StorageNamespace::PluginsDir(spec.id.clone()).encode(),
+            subnet.vm_id,
+            compress::Encoder::Zstd(3).ext()
+        );
+        let mut install_buckets = vec![(s3_manager.clone(), aws_resources.s3_bucket.clone())];
+        for r in aws_resources.regional_resources.iter().flatten() {
+            let regional_config = rt
+                .block_on(aws::load_config(Some(r.region.clone())))
+                .expect("failed to aws::load_config");
+            install_buckets.push((
+                s3::Manager::new(&regional_config),
+                r.s3_bucket.clone().unwrap(),
+            ));
+        }
+        for (manager, bucket) in install_buckets.iter() {
+            info!("uploading VM plugin to s3://{}/{}", bucket, plugin_s3_key);
+            rt.block_on(manager.put_object(
+                Arc::new(tmp_plugin_compressed_path.clone()),
+                Arc::new(bucket.clone()),
+                Arc::new(plugin_s3_key.clone()),
+            ))?;
+        }
+        fs::remove_file(&tmp_plugin_compressed_path)?;
+
+        // the new nodes load the config from the spec
+        spec.avalanchego_config.track_subnet(&subnet_id.to_string());
+        checkpoint(&mut spec, &status)?;
+
+        let event = post_event(
+            &rt,
+            &s3_manager,
+            &aws_resources.s3_bucket,
+            &spec.id,
+            &current_nodes,
+            avalanche_ops_aws::SubnetEvent {
+                version: unix_now(),
+                subnet_id: subnet_id.to_string(),
+                vm_id: subnet.vm_id.clone(),
+                vm_plugin_sha256: vm_plugin_sha256.clone(),
+            },
+        )?;
+        wait_for_nodes_tracking(
+            &rt,
+            &s3_manager,
+            &aws_resources.s3_bucket,
+            &spec.id,
+            &event,
+            &current_nodes,
+        )?;
+
+        status.vm_plugin_sha256 = Some(vm_plugin_sha256);
+        checkpoint(&mut spec, &status)?;
+    }
+
+    if status.blockchain_id.is_none() {
+        print_step(output, "create blockchain")?;
+        let genesis_data = if subnet.genesis_path.is_empty() {
+            spec.subnet_evm_genesis
+                .as_ref()
+                .expect("unexpected None subnet_evm_genesis")
+                .encode_json()?
+                .into_bytes()
+        } else {
+            fs::read(&subnet.genesis_path)?
+        };
+        let fx_ids = subnet
+            .fx_ids
+            .iter()
+            .map(|fx_id| create_chain::parse_fx_id(fx_id))
+            .collect::<io::Result<Vec<ids::Id>>>()?;
+        let (tx, signers) = wallet.builder.new_create_chain_tx(
+            &wallet.utxos(&rt)?,
+            subnet_id,
+            &subnet_owner,
+            &subnet.chain_name,
+            ids::Id::from_str(&subnet.vm_id)?,
+            &fx_ids,
+            genesis_data,
+            &wallet.key.short_address,
+            unix_now(),
+        )?;
+        let (signed_bytes, tx_id) = wallet.sign_subnet_tx(
+            &rt,
+            &tx.unsigned_bytes()?,
+            &signers,
+            &subnet_owner,
+            &tx.subnet_auth,
+        )?;
+        wallet.issue(&rt, &signed_bytes, tx_id, "CreateChainTx")?;
+
+        status.blockchain_id = Some(tx_id.to_string());
+        checkpoint(&mut spec, &status)?;
+    }
+
+    // last, since the subnet owner can no longer authorize the txs after
+    if let Some(elastic) = &subnet.elastic {
+        if status.transform_subnet_tx_id.is_none() {
+            print_step(output, "transform into elastic subnet")?;
+            let (tx, signers) = wallet.builder.new_transform_subnet_tx(
+                &wallet.utxos(&rt)?,
+                subnet_id,
+                &subnet_owner,
+                elastic.clone(),
+                &wallet.key.short_address,
+                unix_now(),
+            )?;
+            let (signed_bytes, tx_id) = wallet.sign_subnet_tx(
+                &rt,
+                &tx.unsigned_bytes()?,
+                &signers,
+                &subnet_owner,
+                &tx.subnet_auth,
+            )?;
+            wallet.issue(&rt, &signed_bytes, tx_id, "TransformSubnetTx")?;
+
+            status.transform_subnet_tx_id = Some(tx_id.to_string());
+            checkpoint(&mut spec, &status)?;
+        }
+        info!(
+            "elastic subnet {} staked with asset {}",
+            subnet_id, elastic.asset_id
+        );
+    }
+
+    let rpc_endpoint = format!(
+        "{}/ext/bc/{}/rpc",
+        http_rpc,
+        status.blockchain_id.as_ref().unwrap()
+    );
+    writeln!(output.console())?;
+    info!(
+        "subnet {} blockchain {} RPC endpoint: {}",
+        subnet_id,
+        status.blockchain_id.as_ref().unwrap(),
+        rpc_endpoint
+    );
+    info!("subnet all success!");
+    writeln!(output.console())?;
+
+    output.print(&Deployed {
+        id: spec.id.clone(),
+        status,
+        rpc_endpoint,
+    })
+}
+
+fn print_step(output: output::Format, msg: &str) -> io::Result<()> {
+    execute!(
+        output.console(),
+        SetForegroundColor(Color::Green),
+        Print(format!("\n\n\nSTEP: {}\n", msg)),
+        ResetColor
+    )
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("unexpected None duration_since")
+        .as_secs()
+}
+
+/// Opens the Ledger signer, and shows its address on the device
+/// for the user to check against the one logged.
+#[cfg(feature = "ledger")]
+fn open_ledger(address_index: u32) -> io::Result<Box<dyn Signer>> {
+    use avalanche_types::ledger;
+
+    let transport = ledger::HidTransport::find()?;
+    let signer = ledger::LedgerSigner::new(Box::new(transport), address_index)?;
+    info!(
+        "confirm the subnet owner address {} on the Ledger",
+        signer.short_address()
+    );
+    signer.prompt_address()?;
+    Ok(Box::new(signer))
+}
+
+#[cfg(not(feature = "ledger"))]
+fn open_ledger(_address_index: u32) -> io::Result<Box<dyn Signer>> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "'--ledger-address-index' requires the build with the \"ledger\" feature",
+    ))
+}
+
+/// Issues the P-chain txs paid by the first generated seed key,
+/// and authorized by the seed key or the Ledger subnet owner.
+struct Wallet {
+    http_rpc: String,
+    key: soft_key::Key,
+    /// Subnet owner key held outside the keychain (e.g., Ledger).
+    owner_signer: Option<Box<dyn Signer>>,
+    p_address: String,
+    builder: SubnetBuilder,
+}
+
+impl Wallet {
+    fn new(
+        rt: &Runtime,
+        http_rpc: &str,
+        network_id: u32,
+        key: soft_key::Key,
+        owner_signer: Option<Box<dyn Signer>>,
+    ) -> io::Result<Self> {
+        let fees = rt
+            .block_on(api_info::get_tx_fee(http_rpc))?
+            .result
+            .unwrap_or_default();
+        let avax_asset_id = rt
+            .block_on(api_x::get_asset_description(http_rpc, "AVAX"))?
+            .result
+            .map(|r| r.asset_id)
+            .ok_or_else(|| Error::new(ErrorKind::Other, "AVAX asset description not found"))?;
+        Ok(Self {
+            http_rpc: http_rpc.to_string(),
+            p_address: key.address("P", network_id)?,
+            builder: SubnetBuilder {
+                network_id,
+                avax_asset_id,
+                tx_fee: units::Avax::from_navax(fees.tx_fee),
+                creation_tx_fee: units::Avax::from_navax(fees.creation_tx_fee),
+                keychain: soft_key::Keychain::new(vec![key.clone()]),
+                co_signers: owner_signer.iter().map(|s| s.short_address()).collect(),
+            },
+            key,
+            owner_signer,
+        })
+    }
+
+    /// Signs the tx with the subnet authorization, with the seed key
+    /// and then the owner signer for the signatures the seed key cannot make.
+    fn sign_subnet_tx(
+        &self,
+        rt: &Runtime,
+        unsigned_bytes: &[u8],
+        signers: &[Vec<soft_key::Key>],
+        subnet_owner: &secp256k1fx::OutputOwners,
+        subnet_auth: &secp256k1fx::Input,
+    ) -> io::Result<(Vec<u8>, ids::Id)> {
+        let plan = SubnetBuilder::signing_plan(signers, subnet_owner, subnet_auth)?;
+        let mut partial = PartialCredentials::new(unsigned_bytes, plan);
+        rt.block_on(partial.sign(&self.key))?;
+        if let Some(owner) = &self.owner_signer {
+            if !partial.is_complete() {
+                info!("confirm the subnet tx on the Ledger");
+                rt.block_on(partial.sign(owner.as_ref()))?;
+            }
+        }
+        avax::pack_signed_tx(unsigned_bytes, &partial.finalize()?)
+    }
+
+    /// Fetched before each tx, since the previous tx spent the UTXOs.
+    fn utxos(&self, rt: &Runtime) -> io::Result<Vec<utxo::Utxo>> {
+        match rt
+            .block_on(api_p::get_utxos(&self.http_rpc, &self.p_address))?
+            .result
+        {
+            Some(r) => r.decode_utxos(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Issues the signed tx, and waits until it is committed.
+    fn issue(
+        &self,
+        rt: &Runtime,
+        signed_bytes: &[u8],
+        tx_id: ids::Id,
+        tx_name: &str,
+    ) -> io::Result<()> {
+        let issued = rt
+            .block_on(api_p::issue_tx(
+                &self.http_rpc,
+                &formatting::encode_cb58_with_checksum(signed_bytes),
+                "cb58",
+            ))?
+            .result
+            .map(|r| r.tx_id)
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::Other,
+                    format!("platform.issueTx returned no tx ID for {}", tx_name),
+                )
+            })?;
+        if issued != tx_id {
+            warn!(
+                "issued tx ID {} differs from the signed tx ID {}",
+                issued, tx_id
+            );
+        }
+        info!("issued {} {} from {}", tx_name, tx_id, self.p_address);
+
+        let started = Instant::now();
+        loop {
+            thread::sleep(Duration::from_secs(5));
+            let status = rt
+                .block_on(api_p::get_tx_status(&self.http_rpc, &tx_id.to_string()))?
+                .result
+                .unwrap_or_default();
+            if status.is_committed() {
+                info!("{} {} committed", tx_name, tx_id);
+                return Ok(());
+            }
+            if status.is_dropped() {
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    format!(
+                        "{} {} dropped ({})",
+                        tx_name,
+                        tx_id,
+                        status.reason.unwrap_or_default()
+                    ),
+                ));
+            }
+            if started.elapsed() >= Duration::from_secs(TX_COMMIT_TIMEOUT_SECONDS) {
+                return Err(Error::new(
+                    ErrorKind::TimedOut,
+                    format!(
+                        "{} {} not committed within {} seconds (status '{}')",
+                        tx_name, tx_id, TX_COMMIT_TIMEOUT_SECONDS, status.status
+                    ),
+                ));
+            }
+            info!(
+                "{} {} status '{}', waiting...",
+                tx_name, tx_id, status.status
+            );
+        }
+    }
+}
+
+/// Uploads the subnet event for "avalanched" to apply.
+/// The previous event of the same subnet and plugin is kept unless any node
+/// failed it, so that re-running the command only restarts the nodes to retry.
+fn post_event(
+    rt: &Runtime,
+    s3_manager: &s3::Manager,
+    s3_bucket: &str,
+    id: &str,
+    nodes: &[avalanche_ops_aws::Node],
+    event: avalanche_ops_aws::SubnetEvent,
+) -> io::Result<avalanche_ops_aws::SubnetEvent> {
+    let s3_key = avalanche_ops_aws::StorageNamespace::EventsSubnet(id.to_string()).encode();
+    if let Some(d) = get_object(rt, s3_manager, s3_bucket, &s3_key)? {
+        let prev = avalanche_ops_aws::SubnetEvent::decode_yaml(&d)?;
+        if prev.subnet_id == event.subnet_id && prev.vm_plugin_sha256 == event.vm_plugin_sha256 {
+            let statuses = fetch_statuses(rt, s3_manager, s3_bucket, id, nodes)?;
+            if !statuses
+                .iter()
+                .flatten()
+                .any(|st| prev.is_applied(st) && !st.tracking)
+            {
+                info!("reusing subnet event version {}", prev.version);
+                return Ok(prev);
+            }
+            info!("retrying failed subnet event version {}", prev.version);
+        }
+    }
+
+    let tmp_path = random::tmp_path(15, Some(".yaml"))?;
+    fs::write(&tmp_path, event.encode_yaml()?)?;
+    rt.block_on(s3_manager.put_object(
+        Arc::new(tmp_path.clone()),
+        Arc::new(s3_bucket.to_string()),
+        Arc::new(s3_key),
+    ))?;
+    fs::remove_file(&tmp_path)?;
+    info!("uploaded subnet event version {}", event.version);
+    Ok(event)
+}
+
+/// Returns the last subnet status of each node, "None" if not reported yet.
+fn fetch_statuses(
+    rt: &Runtime,
+    s3_manager: &s3::Manager,
+    s3_bucket: &str,
+    id: &str,
+    nodes: &[avalanche_ops_aws::Node],
+) -> io::Result<Vec<Option<avalanche_ops_aws::SubnetNodeStatus>>> {
+    let mut statuses = Vec::new();
+    for node in nodes.iter() {
+        let s3_key = avalanche_ops_aws::StorageNamespace::EventsSubnetStatus(
+            id.to_string(),
+            node.node_id.clone(),
+        )
+        .encode();
+        let status = match get_object(rt, s3_manager, s3_bucket, &s3_key)? {
+            Some(d) => Some(avalanche_ops_aws::SubnetNodeStatus::decode_yaml(&d)?),
+            None => None,
+        };
+        statuses.push(status);
+    }
+    Ok(statuses)
+}
+
+/// Waits until every node reports the result of the event.
+/// Errors if any node failed, then re-running the command
+/// posts a new event for the failed nodes to retry.
+fn wait_for_nodes_tracking(
+    rt: &Runtime,
+    s3_manager: &s3::Manager,
+    s3_bucket: &str,
+    id: &str,
+    event: &avalanche_ops_aws::SubnetEvent,
+    nodes: &[avalanche_ops_aws::Node],
+) -> io::Result<()> {
+    let started = Instant::now();
+    loop {
+        let statuses = fetch_statuses(rt, s3_manager, s3_bucket, id, nodes)?;
+        let mut pending = Vec::new();
+        let mut failed = Vec::new();
+        for (node, status) in nodes.iter().zip(statuses) {
+            match status {
+                Some(st) if event.is_applied(&st) => {
+                    if !st.tracking {
+                        failed.push(format!(
+                            "{} ({})",
+                            node.node_id,
+                            st.message.unwrap_or_default()
+                        ));
+                    }
+                }
+                _ => pending.push(node.node_id.clone()),
+            }
+        }
+        if !failed.is_empty() {
+            return Err(Error::new(
+                ErrorKind::Other,
+                format!(
+                    "{} node(s) failed to track the subnet, re-run to retry: {}",
+                    failed.len(),
+                    failed.join(", ")
+                ),
+            ));
+        }
+        if pending.is_empty() {
+            info!("all {} node(s) track the subnet", nodes.len());
+            return Ok(());
+        }
+        if started.elapsed() >= Duration::from_secs(NODES_TRACKING_TIMEOUT_SECONDS) {
+            return Err(Error::new(
+                ErrorKind::TimedOut,
+                format!(
+                    "{} node(s) did not track the subnet within {} seconds: {}",
+                    pending.len(),
+                    NODES_TRACKING_TIMEOUT_SECONDS,
+                    pending.join(", ")
+                ),
+            ));
+        }
+        info!(
+            "waiting for {} node(s) to track the subnet...",
+            pending.len()
+        );
+        thread::sleep(Duration::from_secs(30));
+    }
+}
+
+/// Returns "None" if the object does not exist.
+pub(crate) fn get_object(
+    rt: &Runtime,
+    s3_manager: &s3::Manager,
+    s3_bucket: &str,
+    s3_key: &str,
+) -> io::Result<Option<Vec<u8>>> {
+    let objects = rt.block_on(s3_manager.list_objects(
+        Arc::new(s3_bucket.to_string()),
+        Some(Arc::new(s3_key.to_string())),
+    ))?;
+    if !objects.iter().any(|o| o.key() == Some(s3_key)) {
+        return Ok(None);
+    }
+
+    let tmp_path = random::tmp_path(15, Some(".yaml"))?;
+    rt.block_on(s3_manager.get_object(
+        Arc::new(s3_bucket.to_string()),
+        Arc::new(s3_key.to_string()),
+        Arc::new(tmp_path.clone()),
+    ))?;
+    let d = fs::read(&tmp_path)?;
+    fs::remove_file(&tmp_path)?;
+    Ok(Some(d))
+}
@@ -0,0 +1,348 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Error, ErrorKind},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use avalanche_types::{
+    ids,
+    platformvm::txs::{create_chain, transform_subnet},
+};
+
+use crate::{validator::default_staking_period_seconds, MIN_STAKING_PERIOD_SECONDS};
+
+/// Defines the subnet that "avalanche-ops-aws subnet" deploys end-to-end:
+/// creates the subnet owned by the first generated seed key, adds the validators,
+/// installs the VM plugin on every node and tracks the subnet, then creates
+/// the blockchain, and optionally transforms it into the elastic subnet.
+/// Each step is checkpointed in "status", so re-running
+/// the command resumes from the first incomplete step.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SubnetDeployment {
+    /// Name of the blockchain (alphanumeric and spaces).
+    pub chain_name: String,
+    /// CB58-encoded VM ID, also the plugin file name on the nodes.
+    pub vm_id: String,
+    /// VM plugin binary path in the local environment.
+    pub vm_plugin_path: String,
+    /// Fxs of the VM, either the registered fx names
+    /// (e.g., "secp256k1fx" for the AVM-like VMs) or the CB58-encoded fx IDs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fx_ids: Vec<String>,
+    /// Blockchain genesis file path in the local environment, passed to the VM as is.
+    /// If empty, "subnet_evm_genesis" of the spec is used.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub genesis_path: String,
+    /// Node IDs to validate the subnet, which must be the primary network validators.
+    /// If empty, all current nodes validating the primary network validate the subnet.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub validator_node_ids: Vec<String>,
+    #[serde(default = "default_subnet_validator_weight")]
+    pub validator_weight: u64,
+    /// Capped at the end of the primary network validation.
+    #[serde(default = "default_staking_period_seconds")]
+    pub validator_period_seconds: u64,
+    /// Transforms the subnet into the elastic subnet after the blockchain is created,
+    /// staked with the asset that the first generated seed key holds on the P-chain
+    /// (at least "maximum_supply - initial_supply" for the reward pool).
+    /// The subnet owner can no longer add the validators or create the blockchains.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elastic: Option<transform_subnet::Parameters>,
+
+    /// Only updated after each step.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<SubnetStatus>,
+}
+
+fn default_subnet_validator_weight() -> u64 {
+    1_000
+}
+
+impl Default for SubnetDeployment {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl SubnetDeployment {
+    pub fn default() -> Self {
+        Self {
+            chain_name: String::new(),
+            vm_id: String::new(),
+            vm_plugin_path: String::new(),
+            fx_ids: Vec::new(),
+            genesis_path: String::new(),
+            validator_node_ids: Vec::new(),
+            validator_weight: default_subnet_validator_weight(),
+            validator_period_seconds: default_staking_period_seconds(),
+            elastic: None,
+            status: None,
+        }
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        if self.chain_name.is_empty()
+            || self.chain_name.len() > create_chain::MAX_NAME_LEN
+            || !self
+                .chain_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == ' ')
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'subnet.chain_name' '{}' must be 1-{} alphanumeric characters or spaces",
+                    self.chain_name,
+                    create_chain::MAX_NAME_LEN
+                ),
+            ));
+        }
+        ids::Id::from_str(&self.vm_id).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid 'subnet.vm_id' '{}' ({})", self.vm_id, e),
+            )
+        })?;
+        if self.vm_plugin_path.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'subnet.vm_plugin_path' must be non-empty",
+            ));
+        }
+        for fx_id in self.fx_ids.iter() {
+            create_chain::parse_fx_id(fx_id).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid 'subnet.fx_ids' ({})", e),
+                )
+            })?;
+        }
+        for node_id in self.validator_node_ids.iter() {
+            ids::NodeId::from_str(node_id).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid 'subnet.validator_node_ids' '{}' ({})", node_id, e),
+                )
+            })?;
+        }
+        if self.validator_weight == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'subnet.validator_weight' must be >0",
+            ));
+        }
+        if self.validator_period_seconds < MIN_STAKING_PERIOD_SECONDS {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'subnet.validator_period_seconds' {} <minimum {}",
+                    self.validator_period_seconds, MIN_STAKING_PERIOD_SECONDS
+                ),
+            ));
+        }
+        if let Some(elastic) = &self.elastic {
+            elastic.verify().map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid 'subnet.elastic' ({})", e),
+                )
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Checkpoints of "SubnetDeployment", in the order of the steps.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct SubnetStatus {
+    /// ID of the "CreateSubnetTx", which is the subnet ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subnet_id: Option<String>,
+    /// Short address of the subnet owner (the seed key or the Ledger address),
+    /// so that the re-run with a different owner key fails before issuing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_address: Option<String>,
+    /// Maps the node ID to its committed "AddSubnetValidatorTx" ID.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub validator_tx_ids: BTreeMap<String, String>,
+    /// Hex-encoded SHA256 digest of the VM plugin,
+    /// set once every node tracks the subnet with the plugin.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vm_plugin_sha256: Option<String>,
+    /// ID of the "CreateChainTx", which is the blockchain ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blockchain_id: Option<String>,
+    /// ID of the "TransformSubnetTx", set only with "SubnetDeployment.elastic".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform_subnet_tx_id: Option<String>,
+}
+
+/// Represents the VM plugin to install and the subnet to track
+/// in "StorageNamespace::EventsSubnet", which "avalanched" applies once per version.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SubnetEvent {
+    /// Increases on every new event (unix timestamp of the request),
+    /// so that the failed nodes retry only when the event is posted again.
+    pub version: u64,
+    pub subnet_id: String,
+    pub vm_id: String,
+    /// Hex-encoded SHA256 digest of the uncompressed VM plugin
+    /// in "StorageNamespace::PluginsDir".
+    pub vm_plugin_sha256: String,
+}
+
+impl SubnetEvent {
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize SubnetEvent to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+
+    /// Returns true if the node status is the result of this event.
+    pub fn is_applied(&self, status: &SubnetNodeStatus) -> bool {
+        self.version == status.version
+    }
+}
+
+/// Represents the "SubnetEvent" result of the node
+/// in "StorageNamespace::EventsSubnetStatus".
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SubnetNodeStatus {
+    pub node_id: String,
+    pub version: u64,
+    pub subnet_id: String,
+    /// True if the node is healthy after the restart with the plugin and the subnet.
+    pub tracking: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Unix timestamp of the result.
+    pub updated_at: u64,
+}
+
+impl SubnetNodeStatus {
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize SubnetNodeStatus to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+}
+
+#[test]
+fn test_subnet_deployment() {
+    use avalanche_types::platformvm::txs::PERCENT_DENOMINATOR;
+
+    let subnet: SubnetDeployment = serde_yaml::from_str(
+        "
+chain_name: subnetevm
+vm_id: srEXiWaHuhNyGwPUi444Tu47ZEDwxTWrbQiuD7FmgSAQ6X7Dy
+vm_plugin_path: /tmp/subnet-evm
+genesis_path: /tmp/genesis.json
+validator_node_ids:
+- NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg
+",
+    )
+    .unwrap();
+    assert_eq!(subnet.validator_weight, 1_000);
+    assert_eq!(subnet.validator_period_seconds, 14 * 24 * 60 * 60);
+    assert!(subnet.status.is_none());
+    subnet.validate().unwrap();
+
+    let mut invalid = subnet.clone();
+    invalid.chain_name = String::from("subnet-evm");
+    assert!(invalid.validate().is_err());
+    let mut invalid = subnet.clone();
+    invalid.vm_id = String::from("subnet-evm");
+    assert!(invalid.validate().is_err());
+    let mut invalid = subnet.clone();
+    invalid.validator_node_ids = vec![String::from("NodeID-invalid")];
+    assert!(invalid.validate().is_err());
+    let mut invalid = subnet.clone();
+    invalid.validator_weight = 0;
+    assert!(invalid.validate().is_err());
+    let mut with_fx = subnet.clone();
+    with_fx.fx_ids = vec![
+        String::from("secp256k1fx"),
+        String::from("qd2U4HDWUvMrVUeTcCHp6xH3Qpnn1XbU5MDdnBoiifFqvgXwT"),
+    ];
+    with_fx.validate().unwrap();
+    with_fx.fx_ids = vec![String::from("unknownfx")];
+    assert!(with_fx.validate().is_err());
+
+    let elastic: SubnetDeployment = serde_yaml::from_str(
+        "
+chain_name: subnetevm
+vm_id: srEXiWaHuhNyGwPUi444Tu47ZEDwxTWrbQiuD7FmgSAQ6X7Dy
+vm_plugin_path: /tmp/subnet-evm
+elastic:
+  asset_id: 2ebCneCbwthjQ1rYT41nhd7M76Hc6YmosMAQrTFhBq8qeqh6tt
+  initial_supply: 1000000
+  maximum_supply: 3000000
+  min_consumption_rate: 100000
+  max_consumption_rate: 120000
+  min_validator_stake: 2000
+  max_validator_stake: 3000000
+  min_stake_duration: 86400
+  max_stake_duration: 31536000
+  min_delegation_fee: 20000
+  min_delegator_stake: 25
+  max_validator_weight_factor: 5
+  uptime_requirement: 800000
+",
+    )
+    .unwrap();
+    elastic.validate().unwrap();
+    assert_eq!(elastic.elastic.as_ref().unwrap().reward_pool(), 2_000_000);
+    let mut invalid = elastic;
+    invalid.elastic.as_mut().unwrap().uptime_requirement = PERCENT_DENOMINATOR + 1;
+    assert!(invalid
+        .validate()
+        .unwrap_err()
+        .to_string()
+        .contains("subnet.elastic"));
+
+    let event = SubnetEvent {
+        version: 1650000000,
+        subnet_id: String::from("2ebCneCbwthjQ1rYT41nhd7M76Hc6YmosMAQrTFhBq8qeqh6tt"),
+        vm_id: subnet.vm_id.clone(),
+        vm_plugin_sha256: "ab".repeat(32),
+    };
+    let decoded = SubnetEvent::decode_yaml(event.encode_yaml().unwrap().as_bytes()).unwrap();
+    assert_eq!(decoded, event);
+
+    let mut status = SubnetNodeStatus {
+        node_id: String::from("NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg"),
+        version: event.version,
+        subnet_id: event.subnet_id.clone(),
+        tracking: true,
+        message: None,
+        updated_at: 1650000000,
+    };
+    let decoded = SubnetNodeStatus::decode_yaml(status.encode_yaml().unwrap().as_bytes()).unwrap();
+    assert_eq!(decoded, status);
+    assert!(event.is_applied(&status));
+    status.version += 1;
+    assert!(!event.is_applied(&status));
+}
@@ -0,0 +1,204 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Machine;
+
+/// Name of the ASG lifecycle hook that holds the terminating instance
+/// in "Terminating:Wait" until "avalanched" drains the node.
+pub const TERMINATION_LIFECYCLE_HOOK_NAME: &str = "avalanched-termination";
+
+/// Defines the termination handler of "avalanched", which watches the
+/// spot interruption notice and the ASG target lifecycle state, then stops
+/// avalanchego, deregisters the node from the discovery, takes the final
+/// data volume snapshot, and publishes "TerminationNotice" before
+/// letting the termination proceed.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct TerminationPolicy {
+    /// Interval between the instance metadata polls.
+    #[serde(default = "default_termination_poll_interval_seconds")]
+    pub poll_interval_seconds: u64,
+    /// Timeout of the ASG termination lifecycle hook, after which
+    /// the instance is terminated even if the node is not drained.
+    #[serde(default = "default_lifecycle_hook_timeout_seconds")]
+    pub lifecycle_hook_timeout_seconds: u32,
+    /// Set to true to snapshot the data volume once avalanchego is stopped,
+    /// so that the replacement node restores from the final state
+    /// with "machine.data_volume.restore_from_snapshot".
+    #[serde(default)]
+    pub final_snapshot: bool,
+}
+
+fn default_termination_poll_interval_seconds() -> u64 {
+    5
+}
+
+fn default_lifecycle_hook_timeout_seconds() -> u32 {
+    300
+}
+
+impl Default for TerminationPolicy {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl TerminationPolicy {
+    pub fn default() -> Self {
+        Self {
+            poll_interval_seconds: default_termination_poll_interval_seconds(),
+            lifecycle_hook_timeout_seconds: default_lifecycle_hook_timeout_seconds(),
+            final_snapshot: false,
+        }
+    }
+
+    pub fn validate(&self, machine: &Machine) -> io::Result<()> {
+        // the spot interruption notice comes two minutes ahead
+        if self.poll_interval_seconds == 0 || self.poll_interval_seconds > 60 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'poll_interval_seconds' {} not in [1, 60]",
+                    self.poll_interval_seconds
+                ),
+            ));
+        }
+        // ref. https://docs.aws.amazon.com/autoscaling/ec2/APIReference/API_LifecycleHookSpecification.html
+        if !(30..=7200).contains(&self.lifecycle_hook_timeout_seconds) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'lifecycle_hook_timeout_seconds' {} not in [30, 7200]",
+                    self.lifecycle_hook_timeout_seconds
+                ),
+            ));
+        }
+        if self.final_snapshot && machine.data_volume.is_none() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'final_snapshot' requires 'machine.data_volume'",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Source of the termination notice.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminationReason {
+    SpotInterruption,
+    AsgTermination,
+}
+
+impl TerminationReason {
+    pub fn as_str(&self) -> &str {
+        match self {
+            TerminationReason::SpotInterruption => "spot_interruption",
+            TerminationReason::AsgTermination => "asg_termination",
+        }
+    }
+}
+
+/// Published by the terminating node in "StorageNamespace::TerminationNotice",
+/// for the replacement node of the same kind to restore from "snapshot_id".
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct TerminationNotice {
+    pub machine_id: String,
+    pub node_id: String,
+    pub node_kind: String,
+    pub reason: TerminationReason,
+    /// Final data volume snapshot, taken after avalanchego is stopped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_id: Option<String>,
+    /// Unix timestamp of the notice.
+    pub terminated_at: u64,
+}
+
+impl TerminationNotice {
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize TerminationNotice to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+}
+
+/// Returns the snapshot of the most recent notice of the node kind,
+/// which is the most up-to-date state of the database.
+pub fn latest_final_snapshot(notices: &[TerminationNotice], node_kind: &str) -> Option<String> {
+    notices
+        .iter()
+        .filter(|n| n.node_kind == node_kind && n.snapshot_id.is_some())
+        .max_by_key(|n| n.terminated_at)
+        .and_then(|n| n.snapshot_id.clone())
+}
+
+#[test]
+fn test_termination_policy() {
+    use crate::{DataVolume, ARCH_AMD64};
+
+    let policy: TerminationPolicy = serde_yaml::from_str(
+        "
+final_snapshot: true
+",
+    )
+    .unwrap();
+    assert_eq!(policy.poll_interval_seconds, 5);
+    assert_eq!(policy.lifecycle_hook_timeout_seconds, 300);
+
+    let mut machine = Machine {
+        anchor_nodes: None,
+        non_anchor_nodes: 1,
+        arch: String::from(ARCH_AMD64),
+        instance_types: Vec::new(),
+        purchase_options: None,
+        data_volume: None,
+        anchor_endpoints: None,
+        api_nodes: None,
+    };
+    assert!(policy.validate(&machine).is_err());
+    machine.data_volume = Some(DataVolume::default());
+    policy.validate(&machine).unwrap();
+
+    let mut invalid = policy.clone();
+    invalid.lifecycle_hook_timeout_seconds = 10;
+    assert!(invalid.validate(&machine).is_err());
+    let mut invalid = policy;
+    invalid.poll_interval_seconds = 0;
+    assert!(invalid.validate(&machine).is_err());
+
+    let notice =
+        |node_kind: &str, snapshot_id: Option<&str>, terminated_at: u64| TerminationNotice {
+            machine_id: String::from("i-123123"),
+            node_id: String::from("NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg"),
+            node_kind: node_kind.to_string(),
+            reason: TerminationReason::SpotInterruption,
+            snapshot_id: snapshot_id.map(String::from),
+            terminated_at,
+        };
+    let notices = vec![
+        notice("non-anchor", Some("snap-1"), 100),
+        notice("non-anchor", Some("snap-2"), 200),
+        notice("non-anchor", None, 300),
+        notice("anchor", Some("snap-3"), 400),
+    ];
+    assert_eq!(
+        latest_final_snapshot(&notices, "non-anchor"),
+        Some(String::from("snap-2"))
+    );
+    assert_eq!(latest_final_snapshot(&notices, "api"), None);
+
+    let decoded =
+        TerminationNotice::decode_yaml(notices[0].encode_yaml().unwrap().as_bytes()).unwrap();
+    assert_eq!(decoded, notices[0]);
+}
@@ -0,0 +1,491 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, Error, ErrorKind},
+    path::{Component, Path},
+};
+
+use ring::signature::{self, Ed25519KeyPair, KeyPair};
+use serde::{Deserialize, Serialize};
+
+use crate::Node;
+
+/// Defines how "avalanched" upgrades the node binaries.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct UpgradePolicy {
+    /// Hex-encoded ed25519 public key to verify the manifest signature.
+    /// If "Some", unsigned manifests are rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_public_key: Option<String>,
+    /// Time to wait for the upgraded node to become healthy
+    /// before rolling back to the previous binaries.
+    #[serde(default = "default_health_check_timeout_seconds")]
+    pub health_check_timeout_seconds: u64,
+}
+
+pub(crate) fn default_health_check_timeout_seconds() -> u64 {
+    600
+}
+
+impl Default for UpgradePolicy {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl UpgradePolicy {
+    pub fn default() -> Self {
+        Self {
+            signing_public_key: None,
+            health_check_timeout_seconds: default_health_check_timeout_seconds(),
+        }
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        if let Some(k) = &self.signing_public_key {
+            let d = hex::decode(k).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid 'signing_public_key' hex ({})", e),
+                )
+            })?;
+            if d.len() != 32 {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "'signing_public_key' must be 32-byte ed25519 key (got {} bytes)",
+                        d.len()
+                    ),
+                ));
+            }
+        }
+        if self.health_check_timeout_seconds < 60 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'health_check_timeout_seconds' {} too short (at least 60)",
+                    self.health_check_timeout_seconds
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Represents the artifacts uploaded by "avalanche-ops-aws events update-artifacts"
+/// in "StorageNamespace::EventsUpdateArtifactsManifest".
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct UpgradeManifest {
+    /// Increases on every update (unix timestamp of the request),
+    /// so that each node applies each version at most once.
+    pub version: u64,
+    /// Hex-encoded SHA256 digest of the uncompressed avalanchego binary.
+    pub avalanche_bin_sha256: String,
+    /// Maps the plugin file name to the hex-encoded SHA256 digest
+    /// of the uncompressed plugin binary.
+    #[serde(default)]
+    pub plugins_sha256: BTreeMap<String, String>,
+    /// Hex-encoded ed25519 signature of "signing_payload".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl UpgradeManifest {
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize UpgradeManifest to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+
+    /// Rejects the plugin names that are not a single file name
+    /// (e.g., "../../usr/bin/avalanchego"), since "avalanched" installs
+    /// each plugin by its name under the plugins directory.
+    pub fn validate(&self) -> io::Result<()> {
+        for name in self.plugins_sha256.keys() {
+            let mut components = Path::new(name).components();
+            match (components.next(), components.next()) {
+                (Some(Component::Normal(v)), None) if v == name.as_str() => {}
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("invalid plugin name '{}' (must be a file name)", name),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the bytes to sign, one "name=digest" line per artifact
+    /// (plugins sorted by name), so the signature does not depend on the YAML encoding.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let mut payload = format!(
+            "version={}\navalanche={}\n",
+            self.version, self.avalanche_bin_sha256
+        );
+        for (name, digest) in self.plugins_sha256.iter() {
+            payload.push_str(&format!("plugin/{}={}\n", name, digest));
+        }
+        payload.into_bytes()
+    }
+
+    /// Signs the manifest with the PKCS#8 DER-encoded ed25519 key
+    /// (e.g., "openssl genpkey -algorithm ed25519 -outform DER"),
+    /// and returns the hex-encoded public key to set in "UpgradePolicy".
+    pub fn sign(&mut self, pkcs8_der: &[u8]) -> io::Result<String> {
+        let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(pkcs8_der).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid ed25519 PKCS#8 key ({})", e),
+            )
+        })?;
+        let sig = key_pair.sign(&self.signing_payload());
+        self.signature = Some(hex::encode(sig.as_ref()));
+        Ok(hex::encode(key_pair.public_key().as_ref()))
+    }
+
+    pub fn verify_signature(&self, public_key_hex: &str) -> io::Result<()> {
+        let sig = match &self.signature {
+            Some(s) => hex::decode(s).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid signature hex ({})", e),
+                )
+            })?,
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("manifest version {} is not signed", self.version),
+                ));
+            }
+        };
+        let public_key = hex::decode(public_key_hex).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid public key hex ({})", e),
+            )
+        })?;
+        signature::UnparsedPublicKey::new(&signature::ED25519, public_key)
+            .verify(&self.signing_payload(), &sig)
+            .map_err(|_| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("manifest version {} has invalid signature", self.version),
+                )
+            })
+    }
+}
+
+/// Returns the hex-encoded SHA256 digest of the file.
+pub fn sha256_file_hex(file_path: &str) -> io::Result<String> {
+    Ok(hex::encode(utils::hash::compute_sha256_file(file_path)?))
+}
+
+/// Errors if the file does not match the hex-encoded SHA256 digest.
+pub fn verify_sha256_file(file_path: &str, expected_hex: &str) -> io::Result<()> {
+    let actual = sha256_file_hex(file_path)?;
+    if !actual.eq_ignore_ascii_case(expected_hex) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "SHA256 mismatch for '{}' (expected {}, got {})",
+                file_path, expected_hex, actual
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Result of applying "UpgradeManifest" on each node.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeState {
+    Succeeded,
+    /// Swapped back to the previous binaries after the failed health check.
+    RolledBack,
+    /// Rejected before stopping the node (e.g., checksum mismatch).
+    Rejected,
+}
+
+impl UpgradeState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpgradeState::Succeeded => "succeeded",
+            UpgradeState::RolledBack => "rolled-back",
+            UpgradeState::Rejected => "rejected",
+        }
+    }
+}
+
+/// Represents the upgrade result of the node
+/// in "StorageNamespace::EventsUpdateArtifactsStatus".
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct UpgradeStatus {
+    pub node_id: String,
+    pub version: u64,
+    pub state: UpgradeState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Unix timestamp of the result.
+    pub updated_at: u64,
+}
+
+impl UpgradeStatus {
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize UpgradeStatus to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+}
+
+/// Gates "UpgradeManifest" of the same version to the waves of the nodes
+/// selected by "avalanche-ops-aws events rolling-upgrade",
+/// in "StorageNamespace::EventsUpdateArtifactsRollout".
+/// Without the rollout, every node applies the manifest at once.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct UpgradeRollout {
+    /// Version of the gated manifest.
+    pub version: u64,
+    pub state: UpgradeRolloutState,
+    /// Machine IDs allowed to apply the manifest so far.
+    #[serde(default)]
+    pub machine_ids: BTreeSet<String>,
+    /// Unix timestamp of the last wave.
+    pub updated_at: u64,
+}
+
+impl UpgradeRollout {
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize UpgradeRollout to YAML {}", e),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+
+    /// Returns true if the machine may apply the manifest.
+    pub fn allows(&self, machine_id: &str) -> bool {
+        match self.state {
+            UpgradeRolloutState::InProgress => self.machine_ids.contains(machine_id),
+            UpgradeRolloutState::Completed => true,
+            UpgradeRolloutState::Aborted => false,
+        }
+    }
+
+    /// Returns true if the upgraded machine must restore its previous binaries.
+    pub fn rolls_back(&self, machine_id: &str) -> bool {
+        self.state == UpgradeRolloutState::Aborted && self.machine_ids.contains(machine_id)
+    }
+}
+
+/// State of "UpgradeRollout". The upgraded nodes keep the previous binaries
+/// until the rollout completes, so that the aborted rollout rolls back
+/// the waves that passed the health checks.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeRolloutState {
+    InProgress,
+    Completed,
+    Aborted,
+}
+
+/// Defines how "events rolling-upgrade" splits the nodes into the waves.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum UpgradeWaveBy {
+    /// One availability zone at a time.
+    AvailabilityZone,
+    /// The percentage of the nodes at a time (at least one node).
+    Percent(u32),
+}
+
+/// Returns the machine IDs of each wave, the anchor nodes last, since the
+/// other nodes bootstrap from them. "zones" maps the machine ID to its
+/// availability zone, and the nodes without a zone are grouped together.
+pub fn upgrade_waves(
+    nodes: &[Node],
+    zones: &BTreeMap<String, String>,
+    by: &UpgradeWaveBy,
+) -> Vec<Vec<String>> {
+    let mut sorted: Vec<&Node> = nodes.iter().collect();
+    sorted.sort_by(|a, b| {
+        (a.kind == "anchor", &a.machine_id).cmp(&(b.kind == "anchor", &b.machine_id))
+    });
+
+    let mut waves: Vec<Vec<String>> = Vec::new();
+    for anchor in [false, true] {
+        let group: Vec<&Node> = sorted
+            .iter()
+            .filter(|n| (n.kind == "anchor") == anchor)
+            .cloned()
+            .collect();
+        match by {
+            UpgradeWaveBy::AvailabilityZone => {
+                let mut by_zone: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+                for n in group.iter() {
+                    let zone = zones.get(&n.machine_id).map_or("", |z| z.as_str());
+                    by_zone.entry(zone).or_default().push(n.machine_id.clone());
+                }
+                waves.extend(by_zone.into_values());
+            }
+            UpgradeWaveBy::Percent(percent) => {
+                let size = (nodes.len() * (*percent as usize) + 99) / 100;
+                for chunk in group.chunks(size.max(1)) {
+                    waves.push(chunk.iter().map(|n| n.machine_id.clone()).collect());
+                }
+            }
+        }
+    }
+    waves
+}
+
+#[test]
+fn test_upgrade_rollout() {
+    let mut rollout = UpgradeRollout {
+        version: 1650000000,
+        state: UpgradeRolloutState::InProgress,
+        machine_ids: BTreeSet::from([String::from("i-a")]),
+        updated_at: 1650000000,
+    };
+    let decoded = UpgradeRollout::decode_yaml(rollout.encode_yaml().unwrap().as_bytes()).unwrap();
+    assert_eq!(decoded, rollout);
+    assert!(rollout.allows("i-a"));
+    assert!(!rollout.allows("i-b"));
+    assert!(!rollout.rolls_back("i-a"));
+
+    rollout.state = UpgradeRolloutState::Aborted;
+    assert!(!rollout.allows("i-b"));
+    assert!(rollout.rolls_back("i-a"));
+    assert!(!rollout.rolls_back("i-b"));
+    rollout.state = UpgradeRolloutState::Completed;
+    assert!(rollout.allows("i-b"));
+
+    let node = |kind: &str, machine_id: &str| Node {
+        kind: kind.to_string(),
+        machine_id: machine_id.to_string(),
+        node_id: String::new(),
+        public_ip: String::new(),
+        http_endpoint: String::new(),
+    };
+    let nodes = vec![
+        node("anchor", "i-1"),
+        node("non-anchor", "i-5"),
+        node("non-anchor", "i-3"),
+        node("non-anchor", "i-4"),
+        node("api", "i-2"),
+    ];
+    let waves = upgrade_waves(&nodes, &BTreeMap::new(), &UpgradeWaveBy::Percent(40));
+    assert_eq!(
+        waves,
+        vec![
+            vec![String::from("i-2"), String::from("i-3")],
+            vec![String::from("i-4"), String::from("i-5")],
+            vec![String::from("i-1")],
+        ]
+    );
+    let waves = upgrade_waves(&nodes, &BTreeMap::new(), &UpgradeWaveBy::Percent(1));
+    assert_eq!(waves.len(), 5);
+    assert_eq!(waves[4], vec![String::from("i-1")]);
+
+    let zones = BTreeMap::from([
+        (String::from("i-1"), String::from("us-west-2a")),
+        (String::from("i-2"), String::from("us-west-2b")),
+        (String::from("i-3"), String::from("us-west-2a")),
+        (String::from("i-4"), String::from("us-west-2b")),
+    ]);
+    let waves = upgrade_waves(&nodes, &zones, &UpgradeWaveBy::AvailabilityZone);
+    assert_eq!(
+        waves,
+        vec![
+            vec![String::from("i-5")],
+            vec![String::from("i-3")],
+            vec![String::from("i-2"), String::from("i-4")],
+            vec![String::from("i-1")],
+        ]
+    );
+}
+
+#[test]
+fn test_upgrade_manifest() {
+    use ring::rand::SystemRandom;
+    use std::io::Write;
+
+    let mut plugins_sha256 = BTreeMap::new();
+    plugins_sha256.insert(String::from("subnet-evm"), "cd".repeat(32));
+    let mut manifest = UpgradeManifest {
+        version: 1650000000,
+        avalanche_bin_sha256: "ab".repeat(32),
+        plugins_sha256,
+        signature: None,
+    };
+    let decoded = UpgradeManifest::decode_yaml(manifest.encode_yaml().unwrap().as_bytes()).unwrap();
+    assert_eq!(decoded, manifest);
+    manifest.validate().unwrap();
+    for name in [
+        "../avalanchego",
+        "/usr/bin/avalanchego",
+        "plugins/evm",
+        "..",
+        ".",
+        "",
+    ] {
+        let mut invalid = manifest.clone();
+        invalid
+            .plugins_sha256
+            .insert(String::from(name), "cd".repeat(32));
+        assert!(invalid.validate().is_err(), "{}", name);
+    }
+    assert!(manifest.verify_signature(&"00".repeat(32)).is_err());
+
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+    let public_key = manifest.sign(pkcs8.as_ref()).unwrap();
+    manifest.verify_signature(&public_key).unwrap();
+
+    let mut policy = UpgradePolicy::default();
+    policy.signing_public_key = Some(public_key.clone());
+    policy.validate().unwrap();
+    policy.signing_public_key = Some(String::from("abcd"));
+    assert!(policy.validate().is_err());
+
+    // tampered digest
+    let mut tampered = manifest.clone();
+    tampered.avalanche_bin_sha256 = "ef".repeat(32);
+    assert!(tampered.verify_signature(&public_key).is_err());
+    let mut tampered = manifest;
+    tampered
+        .plugins_sha256
+        .insert(String::from("evil"), "00".repeat(32));
+    assert!(tampered.verify_signature(&public_key).is_err());
+
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(b"avalanchego").unwrap();
+    let p = f.path().to_str().unwrap();
+    let digest = sha256_file_hex(p).unwrap();
+    verify_sha256_file(p, &digest).unwrap();
+    verify_sha256_file(p, &digest.to_uppercase()).unwrap();
+    assert!(verify_sha256_file(p, &"00".repeat(32)).is_err());
+}
@@ -0,0 +1,246 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use avalanche_types::{formatting::address, ids, platformvm::txs::PERCENT_DENOMINATOR, units};
+
+/// Minimum staking period of the primary network validator.
+/// ref. "avalanchego/genesis.StakingConfig.MinStakeDuration"
+pub const MIN_STAKING_PERIOD_SECONDS: u64 = 24 * 60 * 60;
+/// Maximum staking period of the primary network validator.
+/// ref. "avalanchego/genesis.StakingConfig.MaxStakeDuration"
+pub const MAX_STAKING_PERIOD_SECONDS: u64 = 365 * 24 * 60 * 60;
+
+/// Lease of the validator registration lock, long enough to issue
+/// the "AddValidatorTx" and wait for its commit, after which
+/// the other nodes may take over the lock (e.g., the holder crashed).
+pub const REGISTRATION_LOCK_LEASE_SECONDS: u64 = 10 * 60;
+
+/// Returns the ID of the lock that serializes the validator registrations,
+/// since all nodes stake from the same seed key and would otherwise
+/// spend the same UTXOs.
+pub fn registration_lock_id(id: &str) -> String {
+    format!("{}/validator-registration", id)
+}
+
+/// Defines the "AddValidatorTx" that "avalanched" issues for the node
+/// once it is bootstrapped.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ValidatorRegistration {
+    /// Amount to stake (e.g., "2000 AVAX").
+    pub stake_amount: units::Avax,
+    #[serde(default = "default_staking_period_seconds")]
+    pub staking_period_seconds: u64,
+    /// P-chain address to receive the staking rewards (e.g., "P-custom1...").
+    /// If "None", the rewards go to the staking key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reward_address: Option<String>,
+    /// Delegation fee in the units of "PERCENT_DENOMINATOR" (e.g., 20000 for 2%).
+    #[serde(default = "default_delegation_fee_shares")]
+    pub delegation_fee_shares: u32,
+}
+
+pub(crate) fn default_staking_period_seconds() -> u64 {
+    14 * 24 * 60 * 60
+}
+
+fn default_delegation_fee_shares() -> u32 {
+    20_000
+}
+
+impl Default for ValidatorRegistration {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl ValidatorRegistration {
+    pub fn default() -> Self {
+        Self {
+            stake_amount: units::Avax::from_navax(2 * units::KILO_AVAX),
+            staking_period_seconds: default_staking_period_seconds(),
+            reward_address: None,
+            delegation_fee_shares: default_delegation_fee_shares(),
+        }
+    }
+
+    pub fn validate(&self, network_id: u32) -> io::Result<()> {
+        if self.stake_amount == units::Avax::ZERO {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'stake_amount' must be >0",
+            ));
+        }
+        if self.staking_period_seconds < MIN_STAKING_PERIOD_SECONDS
+            || self.staking_period_seconds > MAX_STAKING_PERIOD_SECONDS
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'staking_period_seconds' {} out of range [{}, {}]",
+                    self.staking_period_seconds,
+                    MIN_STAKING_PERIOD_SECONDS,
+                    MAX_STAKING_PERIOD_SECONDS
+                ),
+            ));
+        }
+        if self.delegation_fee_shares > PERCENT_DENOMINATOR {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'delegation_fee_shares' {} >{}",
+                    self.delegation_fee_shares, PERCENT_DENOMINATOR
+                ),
+            ));
+        }
+        if let Some(addr) = &self.reward_address {
+            address::decode_for(addr, address::Chain::P, network_id)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_validator_registration() {
+    use avalanche_types::constants;
+
+    let registration: ValidatorRegistration = serde_yaml::from_str(
+        "
+stake_amount: 2000 AVAX
+reward_address: P-custom18jma8ppw3nhx5r4ap8clazz0dps7rv5u9xde7p
+",
+    )
+    .unwrap();
+    assert_eq!(registration.stake_amount.as_navax(), 2 * units::KILO_AVAX);
+    assert_eq!(registration.staking_period_seconds, 14 * 24 * 60 * 60);
+    assert_eq!(registration.delegation_fee_shares, 20_000);
+    assert_eq!(
+        registration_lock_id("aops-custom-abc"),
+        "aops-custom-abc/validator-registration"
+    );
+    registration
+        .validate(constants::DEFAULT_CUSTOM_NETWORK_ID)
+        .unwrap();
+
+    // reward address for the other network
+    assert!(registration.validate(1).is_err());
+
+    let mut invalid = registration.clone();
+    invalid.stake_amount = units::Avax::ZERO;
+    assert!(invalid
+        .validate(constants::DEFAULT_CUSTOM_NETWORK_ID)
+        .is_err());
+    let mut invalid = registration.clone();
+    invalid.staking_period_seconds = 60;
+    assert!(invalid
+        .validate(constants::DEFAULT_CUSTOM_NETWORK_ID)
+        .is_err());
+    let mut invalid = registration;
+    invalid.delegation_fee_shares = PERCENT_DENOMINATOR + 1;
+    assert!(invalid
+        .validate(constants::DEFAULT_CUSTOM_NETWORK_ID)
+        .is_err());
+}
+
+/// Represents the issued "AddValidatorTx" of the node
+/// in "StorageNamespace::ValidatorRegistrationStatus".
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ValidatorRegistrationStatus {
+    pub node_id: String,
+    pub tx_id: String,
+    pub stake_amount: units::Avax,
+    /// Unix timestamps of the staking period.
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ValidatorRegistrationStatus {
+    pub fn encode_yaml(&self) -> io::Result<String> {
+        serde_yaml::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!(
+                    "failed to serialize ValidatorRegistrationStatus to YAML {}",
+                    e
+                ),
+            )
+        })
+    }
+
+    pub fn decode_yaml(d: &[u8]) -> io::Result<Self> {
+        serde_yaml::from_slice(d)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid YAML: {}", e)))
+    }
+}
+
+/// Staking reward of the validator or delegator of the node,
+/// decided by the commit or abort block when its staking period ended.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct StakingReward {
+    /// ID of the add validator or delegator tx.
+    #[serde(deserialize_with = "ids::must_deserialize_id")]
+    pub staker_tx_id: ids::Id,
+    pub delegator: bool,
+    /// Unix time in seconds.
+    pub start: u64,
+    /// Unix time in seconds, when the reward is paid.
+    pub end: u64,
+    pub weight: units::Avax,
+    /// "false" if aborted (e.g., the uptime was below the requirement),
+    /// in which case only the stake is returned.
+    pub rewarded: bool,
+    /// Total of the reward UTXOs.
+    pub reward: units::Avax,
+}
+
+/// Returns the rewards whose staking period ended in "[start, end]"
+/// (unix time in seconds), and their total.
+pub fn staking_rewards_in_range(
+    rewards: &[StakingReward],
+    start: u64,
+    end: u64,
+) -> io::Result<(Vec<StakingReward>, units::Avax)> {
+    let mut total = units::Avax::ZERO;
+    let mut in_range: Vec<StakingReward> = Vec::new();
+    for r in rewards.iter().filter(|r| (start..=end).contains(&r.end)) {
+        total = total
+            .checked_add(r.reward)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "total reward overflows u64"))?;
+        in_range.push(r.clone());
+    }
+    Ok((in_range, total))
+}
+
+#[test]
+fn test_staking_rewards_in_range() {
+    let reward = |end: u64, rewarded: bool, navax: u64| StakingReward {
+        staker_tx_id: ids::Id::from_slice(&[end as u8; 32]),
+        delegator: false,
+        start: end - 100,
+        end,
+        weight: units::Avax::from_avax(2_000).unwrap(),
+        rewarded,
+        reward: units::Avax::from_navax(navax),
+    };
+    let rewards = vec![
+        reward(1_000, true, 5),
+        reward(2_000, false, 0),
+        reward(3_000, true, 7),
+        reward(4_000, true, 11),
+    ];
+
+    let (in_range, total) = staking_rewards_in_range(&rewards, 1_000, 3_000).unwrap();
+    assert_eq!(in_range, rewards[..3].to_vec());
+    assert_eq!(total, units::Avax::from_navax(12));
+
+    let (in_range, total) = staking_rewards_in_range(&rewards, 3_001, 3_999).unwrap();
+    assert!(in_range.is_empty());
+    assert_eq!(total, units::Avax::ZERO);
+
+    let mut overflow = rewards;
+    overflow[0].reward = units::Avax::from_navax(u64::MAX);
+    assert!(staking_rewards_in_range(&overflow, 0, u64::MAX).is_err());
+}
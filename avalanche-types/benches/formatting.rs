@@ -0,0 +1,41 @@
+use bitcoin::util::base58;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use avalanche_types::formatting;
+
+/// cargo bench --package avalanche-types --bench formatting
+fn bench_formatting(c: &mut Criterion) {
+    // 32-byte ID, and the size of a large "getUTXOs" page
+    for size in [32_usize, 1024, 64 * 1024] {
+        let d: Vec<u8> = (0..size).map(|i| (i * 31 % 256) as u8).collect();
+        let cb58 = formatting::encode_cb58_with_checksum(&d);
+        let hex = formatting::encode_hex_with_checksum(&d);
+
+        let mut group = c.benchmark_group("formatting");
+        group.throughput(Throughput::Bytes(size as u64));
+        if size > 1024 {
+            // base58 is quadratic to the input size
+            group.sample_size(10);
+        }
+        group.bench_with_input(BenchmarkId::new("encode_cb58", size), &d, |b, d| {
+            b.iter(|| formatting::encode_cb58_with_checksum(black_box(d)))
+        });
+        group.bench_with_input(BenchmarkId::new("decode_cb58", size), &cb58, |b, s| {
+            b.iter(|| formatting::decode_cb58_with_checksum(black_box(s)).unwrap())
+        });
+        // baseline of the previous "bitcoin" crate implementation
+        group.bench_with_input(BenchmarkId::new("bitcoin_base58", size), &d, |b, d| {
+            b.iter(|| base58::encode_slice(black_box(d)))
+        });
+        group.bench_with_input(BenchmarkId::new("encode_hex", size), &d, |b, d| {
+            b.iter(|| formatting::encode_hex_with_checksum(black_box(d)))
+        });
+        group.bench_with_input(BenchmarkId::new("decode_hex", size), &hex, |b, s| {
+            b.iter(|| formatting::decode_hex_with_checksum(black_box(s.as_bytes())).unwrap())
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_formatting);
+criterion_main!(benches);
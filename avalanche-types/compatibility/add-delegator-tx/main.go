@@ -0,0 +1,107 @@
+package main
+
+import (
+	"encoding/hex"
+	"fmt"
+	"io/ioutil"
+	"os"
+	"strings"
+
+	"github.com/ava-labs/avalanchego/ids"
+	"github.com/ava-labs/avalanchego/utils/crypto"
+	"github.com/ava-labs/avalanchego/utils/formatting"
+	"github.com/ava-labs/avalanchego/vms/components/avax"
+	"github.com/ava-labs/avalanchego/vms/platformvm"
+	"github.com/ava-labs/avalanchego/vms/secp256k1fx"
+)
+
+var keyFactory = new(crypto.FactorySECP256K1R)
+
+// Builds and signs the same add delegator tx as
+// "examples/platformvm_add_delegator_tx.rs", and writes its hex-encoded bytes.
+//
+// go run main.go /tmp/add.delegator.tx.hex
+func main() {
+	if len(os.Args) != 2 {
+		panic(fmt.Errorf("expected 2 args, got %d", len(os.Args)))
+	}
+
+	// "artifacts/ewoq.key.json"
+	rawPk := strings.Replace("PrivateKey-ewoqjP7PxY4yr3iLTpLisriqt94hdyDFNgchSxGGztUrTXtNN", "PrivateKey-", "", 1)
+	skBytes, err := formatting.Decode(formatting.CB58, rawPk)
+	if err != nil {
+		panic(err)
+	}
+	rpk, err := keyFactory.ToPrivateKey(skBytes)
+	if err != nil {
+		panic(err)
+	}
+	key := rpk.(*crypto.PrivateKeySECP256K1R)
+
+	assetID := ids.ID{}
+	for i := range assetID {
+		assetID[i] = 0xbb
+	}
+	utxoTxID := ids.ID{}
+	for i := range utxoTxID {
+		utxoTxID[i] = 0xaa
+	}
+	nodeID := ids.ShortID{}
+	for i := range nodeID {
+		nodeID[i] = 0x11
+	}
+	owner := ids.ShortID{}
+	for i := range owner {
+		owner[i] = 0x22
+	}
+	owners := secp256k1fx.OutputOwners{
+		Locktime:  0,
+		Threshold: 1,
+		Addrs:     []ids.ShortID{owner},
+	}
+
+	utx := &platformvm.UnsignedAddDelegatorTx{
+		BaseTx: platformvm.BaseTx{BaseTx: avax.BaseTx{
+			NetworkID:    12345,
+			BlockchainID: ids.Empty,
+			Outs: []*avax.TransferableOutput{{
+				Asset: avax.Asset{ID: assetID},
+				Out: &secp256k1fx.TransferOutput{
+					Amt:          1_000_000_000,
+					OutputOwners: owners,
+				},
+			}},
+			Ins: []*avax.TransferableInput{{
+				UTXOID: avax.UTXOID{TxID: utxoTxID, OutputIndex: 1},
+				Asset:  avax.Asset{ID: assetID},
+				In: &secp256k1fx.TransferInput{
+					Amt:   2_001_000_000_000,
+					Input: secp256k1fx.Input{SigIndices: []uint32{0}},
+				},
+			}},
+		}},
+		Validator: platformvm.Validator{
+			NodeID: nodeID,
+			Start:  1_600_000_000,
+			End:    1_700_000_000,
+			Wght:   2_000_000_000_000,
+		},
+		Stake: []*avax.TransferableOutput{{
+			Asset: avax.Asset{ID: assetID},
+			Out: &secp256k1fx.TransferOutput{
+				Amt:          2_000_000_000_000,
+				OutputOwners: owners,
+			},
+		}},
+		RewardsOwner: &owners,
+	}
+	tx := &platformvm.Tx{UnsignedTx: utx}
+	if err := tx.Sign(platformvm.Codec, [][]*crypto.PrivateKeySECP256K1R{{key}}); err != nil {
+		panic(err)
+	}
+
+	if err := ioutil.WriteFile(os.Args[1], []byte(hex.EncodeToString(tx.Bytes())), 0644); err != nil {
+		panic(err)
+	}
+	fmt.Println(tx.ID())
+}
@@ -0,0 +1,79 @@
+package main
+
+import (
+	"encoding/hex"
+	"fmt"
+	"io/ioutil"
+	"math/big"
+	"os"
+	"strings"
+
+	"github.com/ethereum/go-ethereum/common"
+	"github.com/ethereum/go-ethereum/core/types"
+	eth_crypto "github.com/ethereum/go-ethereum/crypto"
+)
+
+// Private key of the EIP-155 example.
+// ref. https://eips.ethereum.org/EIPS/eip-155#example
+const privKeyHex = "4646464646464646464646464646464646464646464646464646464646464646"
+
+const chainID = 43112
+
+// Signs the EIP-1559 transactions with "go-ethereum/core/types.LondonSigner",
+// and writes the hex-encoded raw transaction and its hash of each,
+// one transaction per line, for "examples/evm_dynamic_fee_tx.rs".
+//
+// go run main.go /tmp/evm.dynamic.fee.tx.hex
+func main() {
+	if len(os.Args) != 2 {
+		panic(fmt.Errorf("expected 2 args, got %d", len(os.Args)))
+	}
+
+	key, err := eth_crypto.HexToECDSA(privKeyHex)
+	if err != nil {
+		panic(err)
+	}
+	to := common.HexToAddress("0x3535353535353535353535353535353535353535")
+	txs := []*types.DynamicFeeTx{
+		// transfer
+		{
+			ChainID:   big.NewInt(chainID),
+			Nonce:     9,
+			GasTipCap: big.NewInt(1_000_000_000),
+			GasFeeCap: big.NewInt(50_000_000_000),
+			Gas:       21000,
+			To:        &to,
+			Value:     big.NewInt(1_000_000_000_000_000_000),
+		},
+		// contract creation
+		{
+			ChainID:   big.NewInt(chainID),
+			Nonce:     10,
+			GasTipCap: big.NewInt(2_000_000_000),
+			GasFeeCap: big.NewInt(225_000_000_000),
+			Gas:       100000,
+			Value:     big.NewInt(0),
+			Data:      []byte{0x60, 0x80, 0x60, 0x40, 0x52},
+		},
+	}
+
+	signer := types.NewLondonSigner(big.NewInt(chainID))
+	lines := make([]string, 0, len(txs))
+	for _, txData := range txs {
+		tx, err := types.SignTx(types.NewTx(txData), signer, key)
+		if err != nil {
+			panic(err)
+		}
+		raw, err := tx.MarshalBinary()
+		if err != nil {
+			panic(err)
+		}
+		lines = append(lines, fmt.Sprintf("%s %s", hex.EncodeToString(raw), hex.EncodeToString(tx.Hash().Bytes())))
+	}
+
+	out := strings.Join(lines, "\n")
+	if err := ioutil.WriteFile(os.Args[1], []byte(out), 0644); err != nil {
+		panic(err)
+	}
+	fmt.Println(out)
+}
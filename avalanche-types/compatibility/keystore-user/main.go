@@ -0,0 +1,121 @@
+package main
+
+import (
+	"bytes"
+	"encoding/hex"
+	"fmt"
+	"io/ioutil"
+	"os"
+	"strings"
+
+	"github.com/ava-labs/avalanchego/api/keystore"
+	"github.com/ava-labs/avalanchego/ids"
+	"github.com/ava-labs/avalanchego/utils/crypto"
+	"github.com/ava-labs/avalanchego/utils/formatting"
+	vmkeystore "github.com/ava-labs/avalanchego/vms/components/keystore"
+)
+
+const (
+	username = "test-user"
+	password = "Hfb^jn6N#2@!eXmD"
+)
+
+var keyFactory = new(crypto.FactorySECP256K1R)
+
+// Exports the keystore user with the ewoq key in the P-chain and
+// "examples/keystore_user.rs" chain databases, or imports the user that
+// "examples/keystore_user.rs" exports and checks its keys.
+// The file has the hex-encoded "keystore.UserDB" bytes.
+//
+// go run main.go export /tmp/keystore.user.hex
+// go run main.go import /tmp/keystore.user.hex
+func main() {
+	if len(os.Args) != 3 {
+		panic(fmt.Errorf("expected 3 args, got %d", len(os.Args)))
+	}
+
+	// "artifacts/ewoq.key.json"
+	rawPk := strings.Replace("PrivateKey-ewoqjP7PxY4yr3iLTpLisriqt94hdyDFNgchSxGGztUrTXtNN", "PrivateKey-", "", 1)
+	skBytes, err := formatting.Decode(formatting.CB58, rawPk)
+	if err != nil {
+		panic(err)
+	}
+	rpk, err := keyFactory.ToPrivateKey(skBytes)
+	if err != nil {
+		panic(err)
+	}
+	key := rpk.(*crypto.PrivateKeySECP256K1R)
+
+	otherChainID := ids.ID{}
+	for i := range otherChainID {
+		otherChainID[i] = 0x01
+	}
+	chainIDs := []ids.ID{ids.Empty, otherChainID}
+
+	ks, err := keystore.CreateTestKeystore()
+	if err != nil {
+		panic(err)
+	}
+
+	switch os.Args[1] {
+	case "export":
+		if err := ks.CreateUser(username, password); err != nil {
+			panic(err)
+		}
+		for _, chainID := range chainIDs {
+			db, err := ks.GetDatabase(chainID, username, password)
+			if err != nil {
+				panic(err)
+			}
+			if err := vmkeystore.NewUserFromDB(db).PutKeys(key); err != nil {
+				panic(err)
+			}
+		}
+		b, err := ks.ExportUser(username, password)
+		if err != nil {
+			panic(err)
+		}
+		if err := ioutil.WriteFile(os.Args[2], []byte(hex.EncodeToString(b)), 0644); err != nil {
+			panic(err)
+		}
+		fmt.Println(hex.EncodeToString(b))
+
+	case "import":
+		d, err := ioutil.ReadFile(os.Args[2])
+		if err != nil {
+			panic(err)
+		}
+		b, err := hex.DecodeString(strings.TrimSpace(string(d)))
+		if err != nil {
+			panic(err)
+		}
+		if err := ks.ImportUser(username, password, b); err != nil {
+			panic(err)
+		}
+		for _, chainID := range chainIDs {
+			db, err := ks.GetDatabase(chainID, username, password)
+			if err != nil {
+				panic(err)
+			}
+			user := vmkeystore.NewUserFromDB(db)
+			addrs, err := user.GetAddresses()
+			if err != nil {
+				panic(err)
+			}
+			if len(addrs) != 1 || addrs[0] != key.PublicKey().Address() {
+				panic(fmt.Errorf("unexpected addresses %v in chain %s", addrs, chainID))
+			}
+			imported, err := user.GetKey(addrs[0])
+			if err != nil {
+				panic(err)
+			}
+			if !bytes.Equal(imported.Bytes(), key.Bytes()) {
+				panic(fmt.Errorf("unexpected key in chain %s", chainID))
+			}
+		}
+		fmt.Println("imported", key.PublicKey().Address())
+
+	default:
+		panic(fmt.Errorf("unknown mode %q", os.Args[1]))
+	}
+}
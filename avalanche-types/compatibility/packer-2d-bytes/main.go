@@ -0,0 +1,41 @@
+package main
+
+import (
+	"encoding/hex"
+	"fmt"
+	"io/ioutil"
+	"os"
+
+	"github.com/ava-labs/avalanchego/utils/wrappers"
+)
+
+// Packs the empty and non-empty 2D byte slices with avalanchego's packer,
+// and writes the hex-encoded bytes for "examples/packer_2d_bytes.rs".
+//
+// go run main.go /tmp/packer.2d.bytes.hex
+func main() {
+	if len(os.Args) != 2 {
+		panic(fmt.Errorf("expected 2 args, got %d", len(os.Args)))
+	}
+
+	p := wrappers.Packer{MaxSize: 1024}
+	p.Pack2DByteSlice([][]byte{})
+	p.Pack2DByteSlice([][]byte{{1, 2, 3}, {}, {4, 5, 6, 7}})
+	p.PackStr("avax")
+	p.PackBool(true)
+	if p.Errored() {
+		panic(p.Err)
+	}
+
+	// the non-empty 2D byte slice cut after its first slice fails to unpack
+	truncated := wrappers.Packer{Bytes: p.Bytes[4:16]}
+	truncated.Unpack2DByteSlice()
+	if !truncated.Errored() {
+		panic("expected the truncated 2D byte slice to fail")
+	}
+
+	if err := ioutil.WriteFile(os.Args[1], []byte(hex.EncodeToString(p.Bytes)), 0644); err != nil {
+		panic(err)
+	}
+	fmt.Println(hex.EncodeToString(p.Bytes))
+}
@@ -0,0 +1,74 @@
+package main
+
+import (
+	"encoding/hex"
+	"fmt"
+	"io/ioutil"
+	"os"
+
+	"github.com/ava-labs/avalanchego/ids"
+	"github.com/ava-labs/avalanchego/vms/components/avax"
+	"github.com/ava-labs/avalanchego/vms/platformvm"
+	"github.com/ava-labs/avalanchego/vms/secp256k1fx"
+)
+
+const codecVersion = 0
+
+// Marshals the UTXO of "avalanchego/vms/components/avax.TestUTXOSerialize"
+// with the P-chain codec, and writes its hex-encoded bytes for
+// "examples/utxo_serialize.rs".
+//
+// go run main.go /tmp/utxo.hex
+func main() {
+	if len(os.Args) != 2 {
+		panic(fmt.Errorf("expected 2 args, got %d", len(os.Args)))
+	}
+
+	utxo := &avax.UTXO{
+		UTXOID: avax.UTXOID{
+			TxID: ids.ID{
+				0xf9, 0x66, 0x75, 0x0f, 0x43, 0x88, 0x67, 0xc3,
+				0xc9, 0x82, 0x8d, 0xdc, 0xdb, 0xe6, 0x60, 0xe2,
+				0x1c, 0xcd, 0xbb, 0x36, 0xa9, 0x27, 0x69, 0x58,
+				0xf0, 0x11, 0xba, 0x47, 0x2f, 0x75, 0xd4, 0xe7,
+			},
+			OutputIndex: 13,
+		},
+		Asset: avax.Asset{
+			ID: ids.ID{
+				0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07,
+				0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+				0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17,
+				0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+			},
+		},
+		Out: &secp256k1fx.TransferOutput{
+			Amt: 12345,
+			OutputOwners: secp256k1fx.OutputOwners{
+				Locktime:  54321,
+				Threshold: 1,
+				Addrs: []ids.ShortID{
+					{
+						0x51, 0x02, 0x5c, 0x61, 0xfb, 0xcf, 0xc0, 0x78,
+						0xf6, 0x93, 0x34, 0xf8, 0x34, 0xbe, 0x6d, 0xd2,
+						0x6d, 0x55, 0xa9, 0x55,
+					},
+					{
+						0xc3, 0x34, 0x41, 0x28, 0xe0, 0x60, 0x12, 0x8e,
+						0xde, 0x35, 0x23, 0xa2, 0x4a, 0x46, 0x1c, 0x89,
+						0x43, 0xab, 0x08, 0x59,
+					},
+				},
+			},
+		},
+	}
+
+	b, err := platformvm.Codec.Marshal(codecVersion, utxo)
+	if err != nil {
+		panic(err)
+	}
+	if err := ioutil.WriteFile(os.Args[1], []byte(hex.EncodeToString(b)), 0644); err != nil {
+		panic(err)
+	}
+	fmt.Println(hex.EncodeToString(b))
+}
@@ -19,10 +19,12 @@ fn main() {
         fs::remove_file(&cert_path).expect("failed remove_file");
     }
 
-    cert::generate(key_path.as_str(), cert_path.as_str()).expect("failed to generate certs");
+    let node_id =
+        cert::generate(key_path.as_str(), cert_path.as_str()).expect("failed to generate certs");
     // openssl x509 -in /tmp/test.insecure.cert -text -noout
     // openssl x509 -in artifacts/staker1.insecure.crt -text -noout
 
-    let node_id = ids::NodeId::from_cert_file(cert_path.as_str()).unwrap();
+    let loaded_node_id = ids::NodeId::from_cert_file(cert_path.as_str()).unwrap();
+    assert_eq!(node_id, loaded_node_id);
     println!("Node ID: {}", node_id);
 }
@@ -0,0 +1,65 @@
+use std::{env::args, fs, str::FromStr};
+
+use ethereum_types::{Address, U256};
+use log::info;
+
+use avalanche_types::{
+    evm::tx::{DynamicFeeTx, Tx},
+    soft_key,
+};
+
+/// Signs the EIP-1559 transactions that "compatibility/evm-dynamic-fee-tx/main.go"
+/// signs with go-ethereum, and checks the raw transactions and hashes are the same.
+///
+/// cargo run --example evm_dynamic_fee_tx -- /tmp/evm.dynamic.fee.tx.hex
+fn main() {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, "info"),
+    );
+
+    let txs_path = args().nth(1).expect("no txs path given");
+    let go_txs = fs::read_to_string(&txs_path).unwrap();
+
+    // ref. https://eips.ethereum.org/EIPS/eip-155#example
+    let key = soft_key::Key::from_private_key_eth(
+        "4646464646464646464646464646464646464646464646464646464646464646",
+    )
+    .unwrap();
+    let txs = vec![
+        // transfer
+        Tx::DynamicFee(DynamicFeeTx {
+            chain_id: 43112,
+            nonce: 9,
+            max_priority_fee_per_gas: U256::from(1_000_000_000_u64),
+            max_fee_per_gas: U256::from(50_000_000_000_u64),
+            gas_limit: 21_000,
+            to: Some(Address::from_str("3535353535353535353535353535353535353535").unwrap()),
+            value: U256::from(1_000_000_000_000_000_000_u64),
+            data: Vec::new(),
+        }),
+        // contract creation
+        Tx::DynamicFee(DynamicFeeTx {
+            chain_id: 43112,
+            nonce: 10,
+            max_priority_fee_per_gas: U256::from(2_000_000_000_u64),
+            max_fee_per_gas: U256::from(225_000_000_000_u64),
+            gas_limit: 100_000,
+            to: None,
+            value: U256::zero(),
+            data: vec![0x60, 0x80, 0x60, 0x40, 0x52],
+        }),
+    ];
+
+    let go_lines: Vec<&str> = go_txs.trim().lines().collect();
+    assert_eq!(go_lines.len(), txs.len());
+    for (tx, line) in txs.iter().zip(go_lines.iter()) {
+        let (go_raw, go_hash) = line.split_once(' ').expect("expected raw and hash");
+        let signed = tokio_test::block_on(tx.sign(&key)).unwrap();
+        info!("signed {}", signed.to_hex());
+        assert_eq!(hex::encode(&signed.raw), go_raw);
+        assert_eq!(hex::encode(signed.hash), go_hash);
+    }
+
+    info!("SUCCESS");
+}
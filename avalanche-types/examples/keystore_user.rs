@@ -0,0 +1,61 @@
+use std::{env::args, fs};
+
+use log::info;
+
+use avalanche_types::{ids, keystore, soft_key};
+
+const USERNAME: &str = "test-user";
+const PASSWORD: &str = "Hfb^jn6N#2@!eXmD";
+
+/// Imports the keystore user that "compatibility/keystore-user/main.go"
+/// exports with avalanchego and checks its keys, or exports the user
+/// with the ewoq key for "compatibility/keystore-user/main.go" to import.
+/// The file has the hex-encoded "keystore.UserDB" bytes.
+///
+/// cargo run --example keystore_user -- import /tmp/keystore.user.hex
+/// cargo run --example keystore_user -- export /tmp/keystore.user.hex
+fn main() {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, "info"),
+    );
+
+    let mode = args().nth(1).expect("no mode given");
+    let user_path = args().nth(2).expect("no user path given");
+
+    // "artifacts/ewoq.key.json"
+    let key = soft_key::Key::from_private_key(
+        "PrivateKey-ewoqjP7PxY4yr3iLTpLisriqt94hdyDFNgchSxGGztUrTXtNN",
+    )
+    .unwrap();
+
+    match mode.as_str() {
+        "import" => {
+            let go_hex = fs::read_to_string(&user_path).unwrap();
+            let go_bytes = hex::decode(go_hex.trim()).unwrap();
+
+            let user_keys =
+                keystore::UserKeys::decode_user_db(USERNAME, &go_bytes, PASSWORD).unwrap();
+            let keys = user_keys.open_keys(PASSWORD).unwrap();
+            info!("imported {} key(s)", keys.len());
+            assert_eq!(keys.len(), 1);
+            assert_eq!(keys[0].private_key, key.private_key);
+            assert!(keystore::UserKeys::decode_user_db(USERNAME, &go_bytes, "wrong").is_err());
+        }
+        "export" => {
+            let mut user_keys = keystore::UserKeys::new(USERNAME);
+            user_keys.seal_keys(&[key], PASSWORD).unwrap();
+            let b = user_keys
+                .encode_user_db(
+                    PASSWORD,
+                    &[ids::Id::empty(), ids::Id::from_slice(&[0x01; 32])],
+                )
+                .unwrap();
+            fs::write(&user_path, hex::encode(&b)).unwrap();
+            info!("exported {} bytes to {}", b.len(), user_path);
+        }
+        _ => panic!("unknown mode '{}'", mode),
+    }
+
+    info!("SUCCESS");
+}
@@ -0,0 +1,44 @@
+use std::{env::args, fs};
+
+use log::info;
+
+use avalanche_types::packer;
+
+/// Unpacks the bytes that "compatibility/packer-2d-bytes/main.go" packs
+/// with avalanchego, and checks they pack back to the same bytes.
+///
+/// cargo run --example packer_2d_bytes -- /tmp/packer.2d.bytes.hex
+fn main() {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, "info"),
+    );
+
+    let packed_path = args().nth(1).expect("no packed path given");
+    let go_hex = fs::read_to_string(&packed_path).unwrap();
+    let go_bytes = hex::decode(go_hex.trim()).unwrap();
+
+    let unpacker = packer::Packer::load_bytes_for_unpack(1024, &go_bytes);
+    assert!(unpacker.unpack_2d_bytes().unwrap().is_empty());
+    let v = unpacker.unpack_2d_bytes().unwrap();
+    assert_eq!(v, vec![vec![1, 2, 3], vec![], vec![4, 5, 6, 7]]);
+    assert_eq!(unpacker.unpack_str().unwrap(), "avax");
+    assert!(unpacker.unpack_bool().unwrap());
+    assert_eq!(unpacker.remaining(), 0);
+    info!("unpacked {:?}", v);
+
+    let packer = packer::Packer::new(1024, 0);
+    packer.pack_2d_bytes(&[]);
+    packer.pack_2d_bytes(&v);
+    packer.pack_str("avax");
+    packer.pack_bool(true);
+    assert_eq!(hex::encode(packer.take_bytes()), go_hex.trim());
+
+    // the non-empty 2D byte slice cut after its first slice fails
+    // without moving the offset
+    let truncated = packer::Packer::load_bytes_for_unpack(1024, &go_bytes[4..16]);
+    assert!(truncated.unpack_2d_bytes().is_err());
+    assert_eq!(truncated.get_offset(), 0);
+
+    info!("SUCCESS");
+}
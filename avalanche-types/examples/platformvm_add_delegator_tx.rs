@@ -0,0 +1,80 @@
+use std::{env::args, fs};
+
+use log::info;
+
+use avalanche_types::{avax, ids, platformvm, secp256k1fx, soft_key};
+
+/// Builds and signs the same add delegator tx as
+/// "compatibility/add-delegator-tx/main.go", and checks the bytes
+/// that avalanchego wrote.
+///
+/// cargo run --example platformvm_add_delegator_tx -- /tmp/add.delegator.tx.hex
+fn main() {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, "info"),
+    );
+
+    let tx_path = args().nth(1).expect("no tx path given");
+    let go_hex = fs::read_to_string(&tx_path).unwrap();
+    let go_bytes = hex::decode(go_hex.trim()).unwrap();
+
+    let avax_asset_id = ids::Id::from_slice(&[0xbb; 32]);
+    let owners = secp256k1fx::OutputOwners::new(0, 1, &[ids::ShortId::from_slice(&[0x22; 20])]);
+    let mut tx = platformvm::txs::add_delegator::Tx {
+        base_tx: avax::BaseTx {
+            network_id: 12345,
+            blockchain_id: platformvm::chain_id(),
+            transferable_outputs: Some(vec![avax::TransferableOutput {
+                asset_id: avax_asset_id,
+                transfer_output: Some(secp256k1fx::TransferOutput::new(
+                    1_000_000_000,
+                    owners.clone(),
+                )),
+                ..avax::TransferableOutput::default()
+            }]),
+            transferable_inputs: Some(vec![avax::TransferableInput {
+                utxo_id: avax::UtxoId {
+                    tx_id: ids::Id::from_slice(&[0xaa; 32]),
+                    output_index: 1,
+                    ..avax::UtxoId::default()
+                },
+                asset_id: avax_asset_id,
+                transfer_input: Some(secp256k1fx::TransferInput::new(2_001_000_000_000, vec![0])),
+                ..avax::TransferableInput::default()
+            }]),
+            memo: None,
+        },
+        validator: platformvm::Validator {
+            node_id: ids::ShortId::from_slice(&[0x11; 20]),
+            start: 1_600_000_000,
+            end: 1_700_000_000,
+            weight: 2_000_000_000_000,
+        },
+        stake_transferable_outputs: Some(vec![avax::TransferableOutput {
+            asset_id: avax_asset_id,
+            transfer_output: Some(secp256k1fx::TransferOutput::new(
+                2_000_000_000_000,
+                owners.clone(),
+            )),
+            ..avax::TransferableOutput::default()
+        }]),
+        rewards_owner: owners,
+        creds: Vec::new(),
+    };
+
+    // "artifacts/ewoq.key.json"
+    let key = soft_key::Key::from_private_key(
+        "PrivateKey-ewoqjP7PxY4yr3iLTpLisriqt94hdyDFNgchSxGGztUrTXtNN",
+    )
+    .unwrap();
+    let (signed_bytes, tx_id) = tx.sign(&[vec![key]]).unwrap();
+    info!("tx ID {}", tx_id);
+    assert_eq!(hex::encode(&signed_bytes), hex::encode(&go_bytes));
+
+    let (decoded, decoded_id) = platformvm::txs::add_delegator::Tx::from_bytes(&go_bytes).unwrap();
+    assert_eq!(decoded, tx);
+    assert_eq!(decoded_id, tx_id);
+
+    info!("SUCCESS");
+}
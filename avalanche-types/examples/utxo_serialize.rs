@@ -0,0 +1,31 @@
+use std::{env::args, fs};
+
+use log::info;
+
+use avalanche_types::utxo;
+
+/// Decodes the UTXO that "compatibility/utxo-serialize/main.go" marshals
+/// with avalanchego, and checks it encodes back to the same bytes.
+///
+/// cargo run --example utxo_serialize -- /tmp/utxo.hex
+fn main() {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, "info"),
+    );
+
+    let utxo_path = args().nth(1).expect("no UTXO path given");
+    let go_hex = fs::read_to_string(&utxo_path).unwrap();
+    let go_bytes = hex::decode(go_hex.trim()).unwrap();
+
+    let decoded = utxo::Utxo::from_bytes(&go_bytes).unwrap();
+    info!("decoded {:?}", decoded);
+    assert_eq!(decoded.utxo_id.output_index, 13);
+    assert_eq!(decoded.amount(), 12345);
+    let out = decoded.transfer_output.as_ref().unwrap();
+    assert_eq!(out.output_owners.locktime, 54321);
+    assert_eq!(out.output_owners.addrs.len(), 2);
+    assert_eq!(hex::encode(decoded.to_bytes().unwrap()), go_hex.trim());
+
+    info!("SUCCESS");
+}
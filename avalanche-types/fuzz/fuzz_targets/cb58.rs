@@ -0,0 +1,30 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use avalanche_types::formatting;
+
+fuzz_target!(|data: &[u8]| {
+    let s = match std::str::from_utf8(data) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    // decoded bytes must re-encode to the same bytes
+    if let Ok(decoded) = formatting::decode_cb58_untrusted(s) {
+        assert!(decoded.len() <= formatting::MAX_CB58_DECODED_LEN);
+        let encoded = formatting::encode_cb58_with_checksum(&decoded);
+        assert_eq!(
+            formatting::decode_cb58_untrusted(&encoded).unwrap(),
+            decoded
+        );
+    }
+    if let Ok(decoded) = formatting::decode_hex_untrusted(data) {
+        assert!(decoded.len() <= formatting::MAX_HEX_DECODED_LEN);
+        let encoded = formatting::encode_hex_with_checksum(&decoded);
+        assert_eq!(
+            formatting::decode_hex_untrusted(encoded.as_bytes()).unwrap(),
+            decoded
+        );
+    }
+});
@@ -0,0 +1,38 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use avalanche_types::{codec, packer};
+
+// The first byte selects the unpack operations, the rest is the input to unpack,
+// so the fuzzer covers every unpack with the short reads and the bogus lengths.
+fuzz_target!(|data: &[u8]| {
+    let (ops, b) = match data.split_first() {
+        Some((ops, b)) => (*ops, b),
+        None => return,
+    };
+    let packer = packer::Packer::load_bytes_for_unpack(codec::DEFAULT_MAX_SIZE, b);
+
+    let mut op = ops;
+    while packer.remaining() > 0 {
+        let before = packer.get_offset();
+        let res = match op % 9 {
+            0 => packer.unpack_byte().map(|_| ()),
+            1 => packer.unpack_u16().map(|_| ()),
+            2 => packer.unpack_u32().map(|_| ()),
+            3 => packer.unpack_u64().map(|_| ()),
+            4 => packer.unpack_bytes_with_header().map(|_| ()),
+            5 => packer.unpack_str().map(|_| ()),
+            6 => packer.unpack_bool().map(|_| ()),
+            7 => packer.unpack_2d_bytes().map(|_| ()),
+            _ => packer.unpack_ip().map(|_| ()),
+        };
+        if res.is_err() {
+            break;
+        }
+        // every successful unpack consumes the input
+        assert!(packer.get_offset() > before);
+        assert!(packer.get_offset() <= b.len());
+        op = op.rotate_left(1).wrapping_add(1);
+    }
+});
@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use avalanche_types::{avm, formatting, platformvm};
+
+fuzz_target!(|data: &[u8]| {
+    // signed tx bytes as embedded in the blocks and the index API containers
+    let _ = platformvm::txs::Tx::from_bytes(data);
+    let _ = avm::txs::Tx::from_bytes(data);
+    let _ = platformvm::blocks::Block::from_bytes(data);
+
+    // encoded tx as returned by "getTx"
+    if let Ok(s) = std::str::from_utf8(data) {
+        let _ = platformvm::txs::decode_tx_untrusted(s);
+        let _ = avm::txs::decode_tx_untrusted(s);
+    }
+
+    // the checksum rarely matches the random input, so also decode the checksummed bytes
+    let encoded = formatting::encode_hex_with_checksum(data);
+    let _ = platformvm::txs::decode_tx_untrusted(&encoded);
+    let _ = avm::txs::decode_tx_untrusted(&encoded);
+});
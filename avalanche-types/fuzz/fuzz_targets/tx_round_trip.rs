@@ -0,0 +1,27 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use avalanche_types::{avax, avm, platformvm};
+
+// the "arbitrary" feature generates the canonical txs,
+// which must decode back to the same txs and IDs
+fuzz_target!(
+    |txs: (avm::txs::base::Tx, platformvm::txs::add_validator::Tx)| {
+        let (x_tx, p_tx) = txs;
+
+        let (b, tx_id) =
+            avax::pack_signed_tx(&x_tx.unsigned_bytes().unwrap(), &x_tx.creds).unwrap();
+        assert_eq!(
+            avm::txs::Tx::from_bytes(&b).unwrap(),
+            (avm::txs::Tx::Base(x_tx), tx_id)
+        );
+
+        let (b, tx_id) =
+            avax::pack_signed_tx(&p_tx.unsigned_bytes().unwrap(), &p_tx.creds).unwrap();
+        assert_eq!(
+            platformvm::txs::Tx::from_bytes(&b).unwrap(),
+            (platformvm::txs::Tx::AddValidator(p_tx), tx_id)
+        );
+    }
+);
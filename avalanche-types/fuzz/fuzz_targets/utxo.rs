@@ -0,0 +1,26 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use avalanche_types::{codec, formatting, packer, utxo};
+
+fuzz_target!(|data: &[u8]| {
+    // the decoded UTXO must re-encode to the very same bytes
+    if let Ok(utxo) = utxo::Utxo::from_bytes(data) {
+        assert_eq!(utxo.to_bytes().unwrap(), data);
+    }
+
+    if let Ok(s) = std::str::from_utf8(data) {
+        let _ = utxo::decode_utxo_untrusted(s);
+    }
+    let encoded = formatting::encode_hex_with_checksum(data);
+    let _ = utxo::decode_utxo_untrusted(&encoded);
+
+    // the transferables embedded in the txs, without the codec version
+    let packer = packer::Packer::load_bytes_for_unpack(codec::DEFAULT_MAX_SIZE, data);
+    if utxo::TransferableOutput::unpack(&packer).is_err() {
+        return;
+    }
+    let _ = utxo::TransferableInput::unpack(&packer);
+    assert!(packer.get_offset() <= data.len());
+});
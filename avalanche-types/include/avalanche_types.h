@@ -0,0 +1,114 @@
+#ifndef AVALANCHE_TYPES_H
+#define AVALANCHE_TYPES_H
+
+/* Generated by cbindgen from "src/ffi.rs" (see "scripts/build.ffi.sh"), do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Returns the error message of the last failed call on this thread,
+ * or NULL if none failed. The string is owned by the library and stays
+ * valid until the next failed call on the same thread.
+ */
+const char *avax_last_error(void);
+
+/**
+ * Releases the string returned by this library.
+ *
+ * # Safety
+ *
+ * "s" must be NULL or a string returned by this library that has not been freed.
+ */
+void avax_string_free(char *s);
+
+/**
+ * Releases the byte buffer returned by this library.
+ *
+ * # Safety
+ *
+ * "d" must be NULL or a buffer returned by this library that has not been freed,
+ * and "len" must be the length returned with it.
+ */
+void avax_bytes_free(uint8_t *d, size_t len);
+
+/**
+ * Encodes the bytes in CB58 (base58 with the 4-byte checksum).
+ *
+ * # Safety
+ *
+ * "d" must point to "len" readable bytes (or be NULL if "len" is zero).
+ */
+char *avax_cb58_encode(const uint8_t *d, size_t len);
+
+/**
+ * Decodes the CB58 string and verifies its checksum, writing the length of
+ * the returned buffer to "out_len". The input is treated as untrusted,
+ * so the string that decodes beyond the CB58 size limit is rejected.
+ *
+ * # Safety
+ *
+ * "s" must be a NUL-terminated string and "out_len" must be writable.
+ */
+uint8_t *avax_cb58_decode(const char *s, size_t *out_len);
+
+/**
+ * Derives the node ID (e.g., "NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg")
+ * from the PEM-encoded staking certificate.
+ *
+ * # Safety
+ *
+ * "pem" must point to "len" readable bytes.
+ */
+char *avax_node_id_from_cert_pem(const uint8_t *pem, size_t len);
+
+/**
+ * Derives the node ID from the DER-encoded staking certificate
+ * (i.e., "Certificate.Leaf.Raw" in avalanchego).
+ *
+ * # Safety
+ *
+ * "der" must point to "len" readable bytes.
+ */
+char *avax_node_id_from_cert_der(const uint8_t *der, size_t len);
+
+/**
+ * Derives the chain-prefixed bech32 address (e.g., "X-avax1...") of the
+ * secp256k1 public key (33-byte compressed or 65-byte uncompressed),
+ * with the hrp of the network ID.
+ *
+ * # Safety
+ *
+ * "public_key" must point to "len" readable bytes and "chain_alias"
+ * ("X", "P", or "C") must be a NUL-terminated string.
+ */
+char *avax_address_from_public_key(const uint8_t *public_key,
+                                   size_t len,
+                                   const char *chain_alias,
+                                   uint32_t network_id);
+
+/**
+ * Derives the checksummed ETH address (e.g., "0x8db9...") of the
+ * secp256k1 public key (33-byte compressed or 65-byte uncompressed).
+ *
+ * # Safety
+ *
+ * "public_key" must point to "len" readable bytes.
+ */
+char *avax_eth_address_from_public_key(const uint8_t *public_key, size_t len);
+
+/**
+ * Decodes the chain-prefixed bech32 address into its 20-byte short ID.
+ * Returns 0 on success and -1 on the invalid address.
+ *
+ * # Safety
+ *
+ * "addr" must be a NUL-terminated string and "out" must point to
+ * 20 writable bytes.
+ */
+int avax_address_to_short_id(const char *addr, uint8_t *out);
+
+#endif /* AVALANCHE_TYPES_H */
@@ -0,0 +1,19 @@
+#!/usr/bin/env bash
+set -xue
+
+if ! [[ "$0" =~ ./scripts/build.ffi.sh ]]; then
+  echo "must be run from repository root"
+  exit 255
+fi
+
+# cargo install cbindgen
+cbindgen --config cbindgen.toml --output include/avalanche_types.h
+
+# builds "libavalanche_types.{so,dylib,a}" for the Go (cgo) and Python (ctypes) tooling
+cargo rustc \
+--release \
+--lib \
+--features ffi \
+--crate-type cdylib,staticlib
+
+echo "ALL SUCCESS!"
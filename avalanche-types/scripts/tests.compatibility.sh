@@ -51,5 +51,39 @@ go run ./node-id-load/main.go /tmp/test.insecure.key /tmp/test.insecure.cert
 popd
 cargo run --example cert -- /tmp/test.insecure.key /tmp/test.insecure.cert
 
+###
+pushd ./compatibility
+go run ./add-delegator-tx/main.go /tmp/add.delegator.tx.hex
+popd
+cargo run --example platformvm_add_delegator_tx -- /tmp/add.delegator.tx.hex
+
+###
+pushd ./compatibility
+go run ./utxo-serialize/main.go /tmp/utxo.hex
+popd
+cargo run --example utxo_serialize -- /tmp/utxo.hex
+
+###
+pushd ./compatibility
+go run ./packer-2d-bytes/main.go /tmp/packer.2d.bytes.hex
+popd
+cargo run --example packer_2d_bytes -- /tmp/packer.2d.bytes.hex
+
+###
+pushd ./compatibility
+go run ./keystore-user/main.go export /tmp/keystore.user.hex
+popd
+cargo run --example keystore_user -- import /tmp/keystore.user.hex
+cargo run --example keystore_user -- export /tmp/keystore.user.hex
+pushd ./compatibility
+go run ./keystore-user/main.go import /tmp/keystore.user.hex
+popd
+
+###
+pushd ./compatibility
+go run ./evm-dynamic-fee-tx/main.go /tmp/evm.dynamic.fee.tx.hex
+popd
+cargo run --example evm_dynamic_fee_tx -- /tmp/evm.dynamic.fee.tx.hex
+
 ###
 echo "ALL SUCCESS!"
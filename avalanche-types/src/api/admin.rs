@@ -0,0 +1,101 @@
+use std::{collections::BTreeMap, string::String};
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::jsonrpc;
+
+/// Valid log levels of "admin.setLoggerLevel".
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/logging#Level
+pub const LOG_LEVELS: [&str; 8] = [
+    "OFF", "FATAL", "ERROR", "WARN", "INFO", "TRACE", "DEBUG", "VERBO",
+];
+
+/// Response of the admin API methods with the empty result
+/// (e.g., "admin.alias", "admin.startCPUProfiler", "admin.setLoggerLevel").
+/// ref. https://docs.avax.network/build/avalanchego-apis/admin#adminalias
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct EmptyResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<EmptyResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Default)]
+pub struct EmptyResult {}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/admin#admingetloggerlevel
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetLoggerLevelResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<GetLoggerLevelResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/admin#admingetloggerlevel
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetLoggerLevelResult {
+    /// Levels of each logger (e.g., "main", "C", "P").
+    #[serde(default)]
+    pub logger_levels: BTreeMap<String, LoggerLevel>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/admin#admingetloggerlevel
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LoggerLevel {
+    pub log_level: String,
+    pub display_level: String,
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::admin::test_admin --exact --show-output
+#[test]
+fn test_admin() {
+    let resp: EmptyResponse =
+        serde_json::from_str(r#"{"jsonrpc":"2.0","id":1,"result":{}}"#).unwrap();
+    assert_eq!(resp.result, Some(EmptyResult {}));
+    assert!(resp.error.is_none());
+
+    let resp: EmptyResponse = serde_json::from_str(
+        r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"alias already exists"}}"#,
+    )
+    .unwrap();
+    assert!(resp.result.is_none());
+    assert_eq!(resp.error.unwrap().code, -32000);
+
+    // ref. https://docs.avax.network/build/avalanchego-apis/admin#admingetloggerlevel
+    let resp: GetLoggerLevelResponse = serde_json::from_str(
+        "
+
+{
+    \"jsonrpc\": \"2.0\",
+    \"result\": {
+        \"loggerLevels\": {
+            \"C\": {
+                \"logLevel\": \"DEBUG\",
+                \"displayLevel\": \"INFO\"
+            }
+        }
+    },
+    \"id\": 1
+}
+
+",
+    )
+    .unwrap();
+    let levels = resp.result.unwrap().logger_levels;
+    assert_eq!(
+        levels.get("C"),
+        Some(&LoggerLevel {
+            log_level: String::from("DEBUG"),
+            display_level: String::from("INFO"),
+        })
+    );
+    assert!(LOG_LEVELS.contains(&levels["C"].log_level.as_str()));
+}
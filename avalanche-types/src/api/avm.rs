@@ -2,7 +2,7 @@ use std::{io, str::FromStr, string::String};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{avax, ids};
+use crate::{avax, errors, ids};
 
 /// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmgetbalance
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
@@ -290,3 +290,250 @@ fn test_get_asset_description_response_convert() {
     };
     assert_eq!(parsed, expected);
 }
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmgetallbalances
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetAllBalancesResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    pub result: Option<GetAllBalancesResult>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmgetallbalances
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetAllBalancesResult {
+    pub balances: Vec<Balance>,
+}
+
+impl Default for GetAllBalancesResult {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl GetAllBalancesResult {
+    pub fn default() -> Self {
+        Self {
+            balances: Vec::new(),
+        }
+    }
+}
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/avm#Balance
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Balance {
+    /// Asset ID or its alias (e.g., "AVAX").
+    pub asset: String,
+    pub balance: u64,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmgetallbalances
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct RawGetAllBalancesResponse {
+    jsonrpc: String,
+    id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<RawGetAllBalancesResult>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmgetallbalances
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct RawGetAllBalancesResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    balances: Option<Vec<RawBalance>>,
+}
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/avm#Balance
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct RawBalance {
+    asset: String,
+    balance: String,
+}
+
+impl RawGetAllBalancesResponse {
+    pub fn convert(&self) -> io::Result<GetAllBalancesResponse> {
+        let mut result = GetAllBalancesResult::default();
+        if let Some(raw) = &self.result {
+            for b in raw.balances.clone().unwrap_or_default().iter() {
+                let balance = b
+                    .balance
+                    .parse::<u64>()
+                    .map_err(|e| errors::Error::Decode {
+                        message: format!("balance {} ({})", b.balance, e),
+                    })?;
+                result.balances.push(Balance {
+                    asset: b.asset.clone(),
+                    balance,
+                });
+            }
+        }
+
+        Ok(GetAllBalancesResponse {
+            jsonrpc: self.jsonrpc.clone(),
+            id: self.id,
+            result: Some(result),
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::avm::test_get_all_balances_response_convert --exact --show-output
+#[test]
+fn test_get_all_balances_response_convert() {
+    // ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmgetallbalances
+    let resp: RawGetAllBalancesResponse = serde_json::from_str(
+        "
+
+{
+    \"jsonrpc\": \"2.0\",
+    \"result\": {
+        \"balances\": [
+            {
+                \"asset\": \"AVAX\",
+                \"balance\": \"102\"
+            },
+            {
+                \"asset\": \"2sdnziCz37Jov3QSNMXcFRGFJ1tgauaj6L7qfk7yUcRPfQMC79\",
+                \"balance\": \"10000\"
+            }
+        ]
+    },
+    \"id\": 1
+}
+
+",
+    )
+    .unwrap();
+    let parsed = resp.convert().unwrap();
+    let expected = GetAllBalancesResponse {
+        jsonrpc: "2.0".to_string(),
+        id: 1,
+        result: Some(GetAllBalancesResult {
+            balances: vec![
+                Balance {
+                    asset: String::from("AVAX"),
+                    balance: 102,
+                },
+                Balance {
+                    asset: String::from("2sdnziCz37Jov3QSNMXcFRGFJ1tgauaj6L7qfk7yUcRPfQMC79"),
+                    balance: 10000,
+                },
+            ],
+        }),
+    };
+    assert_eq!(parsed, expected);
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmgettxstatus
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetTxStatusResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    pub result: Option<GetTxStatusResult>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmgettxstatus
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/snow/choices#Status
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetTxStatusResult {
+    /// One of "Accepted", "Processing", "Rejected", or "Unknown".
+    pub status: String,
+}
+
+impl Default for GetTxStatusResult {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl GetTxStatusResult {
+    pub fn default() -> Self {
+        Self {
+            status: String::from("Unknown"),
+        }
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::avm::test_get_tx_status_response --exact --show-output
+#[test]
+fn test_get_tx_status_response() {
+    // ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmgettxstatus
+    let parsed: GetTxStatusResponse = serde_json::from_str(
+        "
+
+{
+    \"jsonrpc\": \"2.0\",
+    \"result\": {
+        \"status\": \"Accepted\"
+    },
+    \"id\": 1
+}
+
+",
+    )
+    .unwrap();
+    let expected = GetTxStatusResponse {
+        jsonrpc: "2.0".to_string(),
+        id: 1,
+        result: Some(GetTxStatusResult {
+            status: String::from("Accepted"),
+        }),
+    };
+    assert_eq!(parsed, expected);
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmissuetx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct IssueTxResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    pub result: Option<IssueTxResult>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmissuetx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct IssueTxResult {
+    #[serde(rename = "txID", deserialize_with = "ids::must_deserialize_id")]
+    pub tx_id: ids::Id,
+}
+
+impl Default for IssueTxResult {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl IssueTxResult {
+    pub fn default() -> Self {
+        Self {
+            tx_id: ids::Id::empty(),
+        }
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::avm::test_issue_tx_response --exact --show-output
+#[test]
+fn test_issue_tx_response() {
+    // ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmissuetx
+    let parsed: IssueTxResponse = serde_json::from_str(
+        "
+
+{
+    \"jsonrpc\": \"2.0\",
+    \"result\": {
+        \"txID\": \"2QouvFWUbjuySRxeX5xMbNCuAaKWfbk5FeEa2JmoF85RKLk2dD\"
+    },
+    \"id\": 1
+}
+
+",
+    )
+    .unwrap();
+    let expected = IssueTxResponse {
+        jsonrpc: "2.0".to_string(),
+        id: 1,
+        result: Some(IssueTxResult {
+            tx_id: ids::Id::from_str("2QouvFWUbjuySRxeX5xMbNCuAaKWfbk5FeEa2JmoF85RKLk2dD").unwrap(),
+        }),
+    };
+    assert_eq!(parsed, expected);
+}
@@ -1,8 +1,9 @@
-use std::string::String;
+use std::{io, string::String};
 
 use num_bigint::BigInt;
 use serde::{Deserialize, Serialize};
 
+use crate::{api::jsonrpc, ids};
 use utils::big_int;
 
 /// ref. https://docs.avax.network/build/avalanchego-apis/c-chain#eth_getassetbalance
@@ -10,8 +11,10 @@ use utils::big_int;
 pub struct GetBalanceResponse {
     pub jsonrpc: String,
     pub id: u32,
-    #[serde(with = "big_int::serde_hex_format")]
+    #[serde(default, with = "big_int::serde_hex_format")]
     pub result: BigInt,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
 }
 
 /// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::eth::test_get_balance_response --exact --show-output
@@ -34,6 +37,223 @@ fn test_get_balance_response() {
         jsonrpc: "2.0".to_string(),
         id: 1,
         result: big_int::from_hex("0x1388").unwrap(),
+        error: None,
     };
     assert_eq!(resp, expected);
 }
+
+/// ref. https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_chainid
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct ChainIdResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(default, with = "big_int::serde_hex_format")]
+    pub result: BigInt,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// ref. https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_blocknumber
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct BlockNumberResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(default, with = "big_int::serde_hex_format")]
+    pub result: BigInt,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// ref. https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_gettransactioncount
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetTransactionCountResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    /// Nonce of the next transaction from the address.
+    #[serde(default, with = "big_int::serde_hex_format")]
+    pub result: BigInt,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// ref. https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_sendrawtransaction
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct SendRawTransactionResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    /// "0x"-prefixed transaction hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// ref. https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_gettransactionreceipt
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetTransactionReceiptResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    /// "None" if the transaction is not yet accepted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<TransactionReceipt>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/coreth/core/types#Receipt
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionReceipt {
+    #[serde(with = "ids::serde_hex_format")]
+    pub transaction_hash: ids::Id,
+    #[serde(with = "ids::serde_hex_format")]
+    pub block_hash: ids::Id,
+    #[serde(with = "big_int::serde_hex_format")]
+    pub block_number: BigInt,
+    pub from: String,
+    /// "None" for the contract creation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<String>,
+    #[serde(with = "big_int::serde_hex_format")]
+    pub gas_used: BigInt,
+    #[serde(with = "big_int::serde_hex_format")]
+    pub cumulative_gas_used: BigInt,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_gas_price: Option<String>,
+    /// "0x1" if succeeded, "0x0" if reverted.
+    pub status: String,
+}
+
+impl TransactionReceipt {
+    pub fn is_success(&self) -> bool {
+        self.status == "0x1"
+    }
+}
+
+/// ref. https://docs.avax.network/apis/avalanchego/apis/c-chain#eth_feehistory
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct FeeHistoryResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<FeeHistory>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/coreth/internal/ethapi#PublicEthereumAPI.FeeHistory
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+    #[serde(with = "big_int::serde_hex_format")]
+    pub oldest_block: BigInt,
+    /// Hex-encoded base fees of each block, plus the next block.
+    pub base_fee_per_gas: Vec<String>,
+    pub gas_used_ratio: Vec<f64>,
+    /// Hex-encoded priority fees at the requested percentiles of each block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reward: Option<Vec<Vec<String>>>,
+}
+
+impl FeeHistory {
+    /// Returns the base fee of the next block, to price the new transactions.
+    pub fn next_base_fee(&self) -> io::Result<Option<BigInt>> {
+        self.base_fee_per_gas
+            .last()
+            .map(|v| big_int::from_hex(v))
+            .transpose()
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::eth::test_responses --exact --show-output
+#[test]
+fn test_responses() {
+    let resp: ChainIdResponse =
+        serde_json::from_str(r#"{"jsonrpc":"2.0","id":1,"result":"0xa868"}"#).unwrap();
+    assert_eq!(resp.result, BigInt::from(43112));
+    assert!(resp.error.is_none());
+
+    let resp: GetTransactionCountResponse =
+        serde_json::from_str(r#"{"jsonrpc":"2.0","id":1,"result":"0x1a"}"#).unwrap();
+    assert_eq!(resp.result, BigInt::from(26));
+
+    let resp: SendRawTransactionResponse = serde_json::from_str(
+        r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"nonce too low"}}"#,
+    )
+    .unwrap();
+    assert!(resp.result.is_none());
+    assert_eq!(
+        resp.error,
+        Some(jsonrpc::ResponseError {
+            code: -32000,
+            message: String::from("nonce too low"),
+        })
+    );
+
+    let resp: GetTransactionReceiptResponse =
+        serde_json::from_str(r#"{"jsonrpc":"2.0","id":1,"result":null}"#).unwrap();
+    assert!(resp.result.is_none());
+
+    let resp: GetTransactionReceiptResponse = serde_json::from_str(
+        r#"
+{
+    "jsonrpc": "2.0",
+    "id": 1,
+    "result": {
+        "blockHash": "0x5a9e2c4a7e86c7ba1c2d47cd6e5a21f6e0e5c9a0f16a8a7e5cf5f6d3e1a0c5b1",
+        "blockNumber": "0x1b4",
+        "contractAddress": null,
+        "cumulativeGasUsed": "0x5208",
+        "effectiveGasPrice": "0x5d21dba00",
+        "from": "0x8db97c7cece249c2b98bdc0226cc4c2a57bf52fc",
+        "gasUsed": "0x5208",
+        "logs": [],
+        "logsBloom": "0x00",
+        "status": "0x1",
+        "to": "0x613040a239bdfcf110969fecb41c6f92ea3515c0",
+        "transactionHash": "0x9fc76417374aa880d4449a1f7f31ec597f00b1f6f3dd2d66f4c9c6c445836d8b",
+        "transactionIndex": "0x0",
+        "type": "0x2"
+    }
+}
+"#,
+    )
+    .unwrap();
+    let receipt = resp.result.unwrap();
+    assert!(receipt.is_success());
+    assert_eq!(receipt.block_number, BigInt::from(436));
+    assert_eq!(receipt.gas_used, BigInt::from(21000));
+    assert!(receipt.contract_address.is_none());
+    assert_eq!(
+        receipt.transaction_hash.to_hex(),
+        "9fc76417374aa880d4449a1f7f31ec597f00b1f6f3dd2d66f4c9c6c445836d8b"
+    );
+    let json = serde_json::to_string(&receipt).unwrap();
+    assert!(json.contains(
+        r#""blockHash":"0x5a9e2c4a7e86c7ba1c2d47cd6e5a21f6e0e5c9a0f16a8a7e5cf5f6d3e1a0c5b1""#
+    ));
+
+    let resp: FeeHistoryResponse = serde_json::from_str(
+        r#"
+{
+    "jsonrpc": "2.0",
+    "id": 1,
+    "result": {
+        "oldestBlock": "0x1b2",
+        "baseFeePerGas": ["0x5d21dba00", "0x5d21dba00", "0x5d21dba00"],
+        "gasUsedRatio": [0.0008, 0.5],
+        "reward": [["0x0"], ["0x3b9aca00"]]
+    }
+}
+"#,
+    )
+    .unwrap();
+    let history = resp.result.unwrap();
+    assert_eq!(history.oldest_block, BigInt::from(434));
+    assert_eq!(
+        history.next_base_fee().unwrap(),
+        Some(BigInt::from(25_000_000_000_u64))
+    );
+}
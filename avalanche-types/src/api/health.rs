@@ -1,13 +1,9 @@
-use std::{
-    collections::HashMap,
-    io::{Error, ErrorKind},
-    str::FromStr,
-    string::String,
-};
+use std::{collections::HashMap, io::Error, str::FromStr, string::String};
 
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
+use crate::errors;
 use utils::rfc3339;
 
 /// Represents AvalancheGo health status.
@@ -42,14 +38,36 @@ impl FromStr for Response {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         serde_json::from_str(s).map_err(|e| {
-            Error::new(
-                ErrorKind::Other,
-                format!("failed serde_json::from_str '{}'", e),
-            )
+            errors::Error::Decode {
+                message: format!("health response ({})", e),
+            }
+            .into()
         })
     }
 }
 
+impl Response {
+    /// Returns the error of each failing check, keyed by the check name.
+    /// A check is failing if it reports an error or any contiguous failures.
+    pub fn failed_checks(&self) -> HashMap<String, String> {
+        let mut failed = HashMap::new();
+        if let Some(checks) = &self.checks {
+            for (name, rs) in checks.iter() {
+                let failures = rs.contiguous_failures.unwrap_or(0);
+                if rs.error.is_none() && failures == 0 {
+                    continue;
+                }
+                let msg = rs
+                    .error
+                    .clone()
+                    .unwrap_or_else(|| format!("{} contiguous failures", failures));
+                failed.insert(name.clone(), msg);
+            }
+        }
+        failed
+    }
+}
+
 /// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::health::test_parse --exact --show-output
 #[test]
 fn test_parse() {
@@ -129,3 +147,39 @@ fn test_parse() {
     info!("parsed: {:?}", parsed);
     assert!(parsed.healthy.unwrap());
 }
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::health::test_failed_checks --exact --show-output
+#[test]
+fn test_failed_checks() {
+    let data = "
+
+{
+    \"checks\": {
+        \"bootstrapped\": {
+            \"message\": [\"X\"],
+            \"error\": \"subnets not bootstrapped\",
+            \"timestamp\": \"2022-02-16T08:15:01.766704522Z\",
+            \"duration\": 8120,
+            \"contiguousFailures\": 3,
+            \"timeOfFirstFailure\": \"2022-02-16T08:14:31.766704522Z\"
+        },
+        \"router\": {
+            \"message\": {
+                \"longestRunningRequest\": \"0s\",
+                \"outstandingRequests\": 0
+            },
+            \"timestamp\": \"2022-02-16T08:15:01.766689781Z\",
+            \"duration\": 11210
+        }
+    },
+    \"healthy\": false
+}
+
+";
+    let parsed = Response::from_str(data).unwrap();
+    assert!(!parsed.healthy.unwrap());
+
+    let failed = parsed.failed_checks();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed["bootstrapped"], "subnets not bootstrapped");
+}
@@ -0,0 +1,204 @@
+use std::{
+    fmt,
+    io::{self, Error, ErrorKind},
+    str::FromStr,
+    string::String,
+};
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{api::jsonrpc, formatting, ids};
+
+/// Maximum "numToFetch" of "index.getContainerRange".
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/indexer#MaxFetchedByRange
+pub const MAX_FETCHED_BY_RANGE: u64 = 1024;
+
+/// Index of the accepted containers, enabled by "--index-enabled".
+/// ref. https://docs.avax.network/build/avalanchego-apis/index-api
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum Index {
+    /// X-chain transactions.
+    XTx,
+    /// X-chain vertices.
+    XVtx,
+    PBlock,
+    CBlock,
+}
+
+impl Index {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Index::XTx => "x-tx",
+            Index::XVtx => "x-vtx",
+            Index::PBlock => "p-block",
+            Index::CBlock => "c-block",
+        }
+    }
+
+    /// e.g., "/ext/index/X/tx".
+    pub fn path(&self) -> &str {
+        match self {
+            Index::XTx => "/ext/index/X/tx",
+            Index::XVtx => "/ext/index/X/vtx",
+            Index::PBlock => "/ext/index/P/block",
+            Index::CBlock => "/ext/index/C/block",
+        }
+    }
+}
+
+impl fmt::Display for Index {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Index {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "x-tx" => Ok(Index::XTx),
+            "x-vtx" => Ok(Index::XVtx),
+            "p-block" => Ok(Index::PBlock),
+            "c-block" => Ok(Index::CBlock),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("unknown index '{}'", s),
+            )),
+        }
+    }
+}
+
+/// Accepted transaction, vertex, or block, with the "hex" encoding.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/indexer#FormattedContainer
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Container {
+    #[serde(deserialize_with = "ids::must_deserialize_id")]
+    pub id: ids::Id,
+    /// "0x"-prefixed hex with the checksum.
+    pub bytes: String,
+    /// RFC 3339 time of the acceptance by the node.
+    pub timestamp: String,
+    pub encoding: String,
+    /// Order of the acceptance in the index, starting from 0.
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub index: u64,
+}
+
+impl Container {
+    pub fn decode_bytes(&self) -> io::Result<Vec<u8>> {
+        formatting::decode_hex_untrusted(self.bytes.as_bytes())
+    }
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/index-api#indexgetlastaccepted
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetLastAcceptedResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Container>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/index-api#indexgetcontainerrange
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetContainerRangeResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Vec<Container>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/index-api#indexgetindex
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetIndexResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<GetIndexResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/index-api#indexgetindex
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetIndexResult {
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub index: u64,
+}
+
+/// avalanchego "json.Uint64" is encoded as a string.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/json#Uint64
+fn deserialize_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(u64),
+    }
+    match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::String(s) => s.parse::<u64>().map_err(serde::de::Error::custom),
+        StringOrNumber::Number(n) => Ok(n),
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::index::test_index --exact --show-output
+#[test]
+fn test_index() {
+    for index in [Index::XTx, Index::XVtx, Index::PBlock, Index::CBlock] {
+        assert_eq!(Index::from_str(index.as_str()).unwrap(), index);
+    }
+    assert_eq!(Index::XVtx.path(), "/ext/index/X/vtx");
+    assert!(Index::from_str("x-block").is_err());
+
+    // ref. https://docs.avax.network/build/avalanchego-apis/index-api#indexgetlastaccepted
+    let resp: GetLastAcceptedResponse = serde_json::from_str(
+        "
+
+{
+    \"jsonrpc\": \"2.0\",
+    \"result\": {
+        \"id\": \"6fXf5hncR8LXvwtM8iezFQBpK5cubV6y1dWgpJCcNyzGB1EzY\",
+        \"bytes\": \"0x00000000000000000000000000000000000000000000000000000000000000000000007c1e9f1a\",
+        \"timestamp\": \"2021-04-02T15:34:00.262979-07:00\",
+        \"encoding\": \"hex\",
+        \"index\": \"0\"
+    },
+    \"id\": 1
+}
+
+",
+    )
+    .unwrap();
+    let container = resp.result.unwrap();
+    assert_eq!(
+        container.id.to_string(),
+        "6fXf5hncR8LXvwtM8iezFQBpK5cubV6y1dWgpJCcNyzGB1EzY"
+    );
+    assert_eq!(container.index, 0);
+    let container = Container {
+        bytes: formatting::encode_hex_with_checksum(&[1, 2, 3]),
+        ..container
+    };
+    assert_eq!(container.decode_bytes().unwrap(), vec![1, 2, 3]);
+
+    let resp: GetContainerRangeResponse = serde_json::from_str(
+        r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"no containers have been accepted"}}"#,
+    )
+    .unwrap();
+    assert!(resp.result.is_none());
+    assert_eq!(resp.error.unwrap().code, -32000);
+
+    let resp: GetIndexResponse =
+        serde_json::from_str(r#"{"jsonrpc":"2.0","id":1,"result":{"index":"17"}}"#).unwrap();
+    assert_eq!(resp.result.unwrap().index, 17);
+}
@@ -2,7 +2,7 @@ use std::{collections::HashMap, io, str::FromStr, string::String};
 
 use serde::{Deserialize, Serialize};
 
-use crate::ids;
+use crate::{errors, ids};
 
 /// ref. https://docs.avax.network/build/avalanchego-apis/info/#infogetnetworkname
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
@@ -463,6 +463,150 @@ impl GetBootstrappedResult {
     }
 }
 
+/// ref. https://docs.avax.network/build/avalanchego-apis/info/#infopeers
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct PeersResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<PeersResult>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/info/#infopeers
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct PeersResult {
+    pub num_peers: u32,
+    pub peers: Vec<Peer>,
+}
+
+impl Default for PeersResult {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl PeersResult {
+    pub fn default() -> Self {
+        Self {
+            num_peers: 0,
+            peers: Vec::new(),
+        }
+    }
+}
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/network#PeerInfo
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Peer {
+    pub ip: String,
+    #[serde(rename = "publicIP")]
+    pub public_ip: String,
+    #[serde(rename = "nodeID", deserialize_with = "ids::must_deserialize_node_id")]
+    pub node_id: ids::NodeId,
+    pub version: String,
+    #[serde(rename = "lastSent")]
+    pub last_sent: String,
+    #[serde(rename = "lastReceived")]
+    pub last_received: String,
+    #[serde(rename = "observedUptime", default)]
+    pub observed_uptime: String,
+    #[serde(default)]
+    pub benched: Vec<String>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/info/#infopeers
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct RawPeersResponse {
+    jsonrpc: String,
+    id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<RawPeersResult>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/info/#infopeers
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct RawPeersResult {
+    #[serde(rename = "numPeers")]
+    num_peers: String,
+    peers: Option<Vec<Peer>>,
+}
+
+impl RawPeersResponse {
+    pub fn convert(&self) -> io::Result<PeersResponse> {
+        let mut result = PeersResult::default();
+        if let Some(raw) = &self.result {
+            result.num_peers = {
+                if raw.num_peers.is_empty() {
+                    0_u32
+                } else {
+                    raw.num_peers
+                        .parse::<u32>()
+                        .map_err(|e| errors::Error::Decode {
+                            message: format!("numPeers {} ({})", raw.num_peers, e),
+                        })?
+                }
+            };
+            result.peers = raw.peers.clone().unwrap_or_default();
+        }
+
+        Ok(PeersResponse {
+            jsonrpc: self.jsonrpc.clone(),
+            id: self.id,
+            result: Some(result),
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::info::test_peers_response_convert --exact --show-output
+#[test]
+fn test_peers_response_convert() {
+    // ref. https://docs.avax.network/build/avalanchego-apis/info/#infopeers
+    let resp: RawPeersResponse = serde_json::from_str(
+        "
+
+{
+    \"jsonrpc\": \"2.0\",
+    \"result\": {
+        \"numPeers\": \"1\",
+        \"peers\": [
+            {
+                \"ip\": \"206.189.137.87:9651\",
+                \"publicIP\": \"206.189.137.87:9651\",
+                \"nodeID\": \"NodeID-8PYXX47kqLDe2wD4oPbvRRchcnSzMA4J4\",
+                \"version\": \"avalanche/1.7.10\",
+                \"lastSent\": \"2020-06-01T15:23:02Z\",
+                \"lastReceived\": \"2020-06-01T15:22:57Z\",
+                \"benched\": [],
+                \"observedUptime\": \"99\"
+            }
+        ]
+    },
+    \"id\": 1
+}
+
+",
+    )
+    .unwrap();
+    let parsed = resp.convert().unwrap();
+    let expected = PeersResponse {
+        jsonrpc: "2.0".to_string(),
+        id: 1,
+        result: Some(PeersResult {
+            num_peers: 1,
+            peers: vec![Peer {
+                ip: String::from("206.189.137.87:9651"),
+                public_ip: String::from("206.189.137.87:9651"),
+                node_id: ids::NodeId::from_str("NodeID-8PYXX47kqLDe2wD4oPbvRRchcnSzMA4J4").unwrap(),
+                version: String::from("avalanche/1.7.10"),
+                last_sent: String::from("2020-06-01T15:23:02Z"),
+                last_received: String::from("2020-06-01T15:22:57Z"),
+                observed_uptime: String::from("99"),
+                benched: Vec::new(),
+            }],
+        }),
+    };
+    assert_eq!(parsed, expected);
+}
+
 /// ref. https://docs.avax.network/build/avalanchego-apis/info/#infogettxfee
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub struct GetTxFeeResponse {
@@ -0,0 +1,122 @@
+use std::string::String;
+
+use serde::{Deserialize, Serialize};
+use utils::hash;
+
+use crate::{api::jsonrpc, ids};
+
+/// Maximum size of the event that the consumer reads,
+/// which guards against the corrupted length prefix.
+pub const MAX_EVENT_SIZE: u64 = 16 * 1024 * 1024;
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/ipc#ipcspublishblockchain
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct PublishBlockchainResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<PublishBlockchainResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// Unix domain sockets of the node that publish the accepted containers.
+/// ref. https://docs.avax.network/build/avalanchego-apis/ipc#ipcspublishblockchain
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct PublishBlockchainResult {
+    /// Publishes the accepted consensus containers (e.g., X-chain vertices).
+    #[serde(rename = "consensusURL")]
+    pub consensus_url: String,
+    /// Publishes the accepted decisions (e.g., X-chain transactions, blocks).
+    #[serde(rename = "decisionsURL")]
+    pub decisions_url: String,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/ipc#ipcsgetpublishedblockchains
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetPublishedBlockchainsResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<GetPublishedBlockchainsResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/ipc#ipcsgetpublishedblockchains
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetPublishedBlockchainsResult {
+    /// Blockchain IDs.
+    #[serde(default)]
+    pub chains: Vec<String>,
+}
+
+/// Socket that the event is published to.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Consensus,
+    Decision,
+}
+
+/// Accepted container published by the node.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/ipcs#EventSockets
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Event {
+    pub kind: EventKind,
+    #[serde(deserialize_with = "ids::must_deserialize_id")]
+    pub chain_id: ids::Id,
+    /// SHA256 of the bytes, same as the transaction, vertex, or block ID.
+    #[serde(deserialize_with = "ids::must_deserialize_id")]
+    pub container_id: ids::Id,
+    pub bytes: Vec<u8>,
+}
+
+impl Event {
+    pub fn new(kind: EventKind, chain_id: ids::Id, bytes: Vec<u8>) -> Self {
+        Self {
+            kind,
+            chain_id,
+            container_id: ids::Id::from_slice(&hash::compute_sha256(&bytes)),
+            bytes,
+        }
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::ipcs::test_ipcs --exact --show-output
+#[test]
+fn test_ipcs() {
+    // ref. https://docs.avax.network/build/avalanchego-apis/ipc#ipcspublishblockchain
+    let resp: PublishBlockchainResponse = serde_json::from_str(
+        "
+
+{
+    \"jsonrpc\": \"2.0\",
+    \"result\": {
+        \"consensusURL\": \"/tmp/1-11111111111111111111111111111111LpoYY-consensus\",
+        \"decisionsURL\": \"/tmp/1-11111111111111111111111111111111LpoYY-decisions\"
+    },
+    \"id\": 1
+}
+
+",
+    )
+    .unwrap();
+    let result = resp.result.unwrap();
+    assert!(result.consensus_url.ends_with("-consensus"));
+    assert!(result.decisions_url.ends_with("-decisions"));
+
+    let resp: GetPublishedBlockchainsResponse = serde_json::from_str(
+        r#"{"jsonrpc":"2.0","id":1,"result":{"chains":["11111111111111111111111111111111LpoYY"]}}"#,
+    )
+    .unwrap();
+    assert_eq!(resp.result.unwrap().chains.len(), 1);
+
+    let event = Event::new(EventKind::Decision, ids::Id::empty(), vec![1, 2, 3]);
+    assert_eq!(
+        event.container_id,
+        ids::Id::from_slice(&hash::compute_sha256(&[1, 2, 3]))
+    );
+    assert_ne!(event.container_id, ids::Id::empty());
+}
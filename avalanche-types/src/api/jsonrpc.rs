@@ -6,9 +6,14 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
+use crate::errors;
+
 pub const DEFAULT_VERSION: &str = "2.0";
 pub const DEFAULT_ID: u32 = 1;
 
+/// JSON-RPC "Internal error", which is transient (e.g., the node is still syncing).
+pub const INTERNAL_ERROR_CODE: i32 = -32603;
+
 /// ref. https://docs.avax.network/build/avalanchego-apis/issuing-api-calls
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub struct Data {
@@ -86,3 +91,62 @@ impl DataWithParamsArray {
         }
     }
 }
+
+/// Same as "DataWithParamsArray" but with the mixed-type parameters
+/// (e.g., "eth_feeHistory" takes the block count, the block tag, and the percentiles).
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct DataWithParamsValues {
+    pub jsonrpc: String,
+    pub id: u32,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Vec<serde_json::Value>>,
+}
+
+impl Default for DataWithParamsValues {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl DataWithParamsValues {
+    pub fn default() -> Self {
+        Self {
+            jsonrpc: String::from(DEFAULT_VERSION),
+            id: DEFAULT_ID,
+            method: String::new(),
+            params: None,
+        }
+    }
+
+    pub fn encode_json(&self) -> io::Result<String> {
+        serde_json::to_string(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("failed to serialize to JSON {}", e),
+            )
+        })
+    }
+}
+
+/// Error object of the failed JSON-RPC call.
+/// ref. https://www.jsonrpc.org/specification#error_object
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct ResponseError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl ResponseError {
+    /// Converts to the error with the method name for context,
+    /// which "errors::Error::from_io" recovers with the code.
+    pub fn to_io_error(&self, method: &str) -> Error {
+        errors::Error::API {
+            code: self.code,
+            message: format!("{} failed ({})", method, self.message),
+            // ref. https://www.jsonrpc.org/specification#error_object
+            is_retryable: self.code == INTERNAL_ERROR_CODE,
+        }
+        .into()
+    }
+}
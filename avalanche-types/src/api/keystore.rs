@@ -0,0 +1,181 @@
+use std::{
+    io::{self, Error, ErrorKind},
+    string::String,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{api::jsonrpc, errors};
+
+/// Maximum length of the keystore user password.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/password#MaxPassLen
+pub const MAX_PASSWORD_LEN: usize = 1024;
+
+/// Chain that a keystore user imports the private key to.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+pub enum Chain {
+    X,
+    P,
+    C,
+}
+
+impl Chain {
+    /// e.g., "/ext/bc/X".
+    pub fn path(&self) -> &str {
+        match self {
+            Chain::X => "/ext/bc/X",
+            Chain::P => "/ext/bc/P",
+            Chain::C => "/ext/bc/C/avax",
+        }
+    }
+
+    /// e.g., "avm.importKey".
+    pub fn import_key_method(&self) -> &str {
+        match self {
+            Chain::X => "avm.importKey",
+            Chain::P => "platform.importKey",
+            Chain::C => "avax.importKey",
+        }
+    }
+}
+
+/// Response of "keystore.createUser", "keystore.importUser",
+/// and "keystore.deleteUser".
+/// ref. https://docs.avax.network/build/avalanchego-apis/keystore#keystorecreateuser
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct SuccessResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<SuccessResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/keystore#keystorecreateuser
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct SuccessResult {
+    pub success: bool,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/keystore#keystorelistusers
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct ListUsersResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<ListUsersResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/keystore#keystorelistusers
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct ListUsersResult {
+    #[serde(default)]
+    pub users: Vec<String>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/keystore#keystoreexportuser
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct ExportUserResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<ExportUserResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// User database encrypted with the user password,
+/// which "keystore.importUser" takes as it is.
+/// ref. https://docs.avax.network/build/avalanchego-apis/keystore#keystoreexportuser
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct ExportUserResult {
+    /// "0x"-prefixed hex with the checksum.
+    pub user: String,
+    pub encoding: String,
+}
+
+impl ExportUserResult {
+    pub fn encode_json(&self) -> io::Result<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize ExportUserResult to JSON {}", e),
+            )
+        })
+    }
+
+    pub fn decode_json(d: &[u8]) -> io::Result<Self> {
+        serde_json::from_slice(d).map_err(|e| {
+            errors::Error::Decode {
+                message: format!("ExportUserResult JSON ({})", e),
+            }
+            .into()
+        })
+    }
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmimportkey
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct ImportKeyResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<ImportKeyResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc::ResponseError>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/x-chain#avmimportkey
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct ImportKeyResult {
+    pub address: String,
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::keystore::test_keystore --exact --show-output
+#[test]
+fn test_keystore() {
+    assert_eq!(Chain::C.path(), "/ext/bc/C/avax");
+    assert_eq!(Chain::P.import_key_method(), "platform.importKey");
+
+    let resp: SuccessResponse =
+        serde_json::from_str(r#"{"jsonrpc":"2.0","id":1,"result":{"success":true}}"#).unwrap();
+    assert!(resp.result.unwrap().success);
+
+    let resp: ListUsersResponse =
+        serde_json::from_str(r#"{"jsonrpc":"2.0","id":1,"result":{"users":["myUsername"]}}"#)
+            .unwrap();
+    assert_eq!(resp.result.unwrap().users, vec![String::from("myUsername")]);
+
+    // ref. https://docs.avax.network/build/avalanchego-apis/keystore#keystoreexportuser
+    let resp: ExportUserResponse = serde_json::from_str(
+        "
+
+{
+    \"jsonrpc\": \"2.0\",
+    \"result\": {
+        \"user\": \"7655a29df6fc2747b0874e1148b423b954a25fcdb1f170d0ec8eb196430f7001942ce55b02a83b1faf50a674b1e55bfc00000000\",
+        \"encoding\": \"hex\"
+    },
+    \"id\": 1
+}
+
+",
+    )
+    .unwrap();
+    let export = resp.result.unwrap();
+    assert_eq!(export.encoding, "hex");
+    assert_eq!(
+        ExportUserResult::decode_json(&export.encode_json().unwrap()).unwrap(),
+        export
+    );
+
+    let resp: ImportKeyResponse = serde_json::from_str(
+        r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"incorrect password for user \"myUsername\""}}"#,
+    )
+    .unwrap();
+    assert!(resp.result.is_none());
+    assert_eq!(resp.error.unwrap().code, -32000);
+}
@@ -1,6 +1,10 @@
+pub mod admin;
 pub mod avm;
 pub mod eth;
 pub mod health;
+pub mod index;
 pub mod info;
+pub mod ipcs;
 pub mod jsonrpc;
+pub mod keystore;
 pub mod platformvm;
@@ -6,7 +6,7 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
-use crate::{api::jsonrpc, avax, ids};
+use crate::{api::jsonrpc, avax, errors, formatting, ids, utxo};
 
 /// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgetheight
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
@@ -389,6 +389,27 @@ impl GetUtxosResult {
             encoding: None,
         }
     }
+
+    /// Decodes the "utxos" payloads with the "encoding" of the response.
+    /// Falls back to "hex" for "0x"-prefixed payloads if "encoding" is not set.
+    pub fn decode_utxos(&self) -> io::Result<Vec<utxo::Utxo>> {
+        let mut decoded: Vec<utxo::Utxo> = Vec::new();
+        for s in self.utxos.clone().unwrap_or_default().iter() {
+            let hex_encoded = match &self.encoding {
+                Some(enc) => enc == "hex",
+                None => s.starts_with("0x"),
+            };
+            let utxo = if hex_encoded {
+                utxo::Utxo::from_hex(s)
+            } else {
+                utxo::Utxo::from_cb58(s)
+            };
+            decoded.push(utxo.map_err(|e| errors::Error::Decode {
+                message: format!("UTXO '{}' ({})", s, e),
+            })?);
+        }
+        Ok(decoded)
+    }
 }
 
 /// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgetutxos
@@ -624,6 +645,20 @@ fn test_convert_get_utxos_non_empty() {
         }),
     };
     assert_eq!(parsed, expected);
+
+    let utxos = parsed.result.unwrap().decode_utxos().unwrap();
+    assert_eq!(utxos.len(), 1);
+    assert_eq!(utxos[0].amount(), 0x02c68af0bb140000);
+    assert_eq!(
+        utxos[0]
+            .transfer_output
+            .as_ref()
+            .unwrap()
+            .output_owners
+            .addrs[0]
+            .to_hex(),
+        "65844a05405f3662c1928142c6c2a783ef871de9"
+    );
 }
 
 /// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgetcurrentvalidators
@@ -1373,3 +1408,446 @@ fn test_convert_get_current_validators() {
     };
     assert_eq!(parsed, expected);
 }
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgetpendingvalidators
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct GetPendingValidatorsResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<GetPendingValidatorsResult>,
+}
+
+impl Default for GetPendingValidatorsResponse {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl GetPendingValidatorsResponse {
+    pub fn default() -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: 1,
+            result: None,
+        }
+    }
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgetpendingvalidators
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm#GetPendingValidatorsReply
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct GetPendingValidatorsResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validators: Option<Vec<ApiPrimaryValidator>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegators: Option<Vec<ApiPrimaryDelegator>>,
+}
+
+impl Default for GetPendingValidatorsResult {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl GetPendingValidatorsResult {
+    pub fn default() -> Self {
+        Self {
+            validators: None,
+            delegators: None,
+        }
+    }
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgetpendingvalidators
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct RawGetPendingValidatorsResponse {
+    jsonrpc: String,
+    id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<RawGetPendingValidatorsResult>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgetpendingvalidators
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct RawGetPendingValidatorsResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validators: Option<Vec<RawApiPrimaryValidator>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegators: Option<Vec<RawApiPrimaryDelegator>>,
+}
+
+impl RawGetPendingValidatorsResponse {
+    pub fn convert(&self) -> io::Result<GetPendingValidatorsResponse> {
+        if self.result.as_ref().is_none() {
+            return Ok(GetPendingValidatorsResponse::default());
+        };
+
+        let rs = self.result.as_ref().unwrap();
+        let validators = rs
+            .validators
+            .as_ref()
+            .map(|vs| vs.iter().map(|v| v.convert()).collect());
+        let delegators = rs
+            .delegators
+            .as_ref()
+            .map(|ds| ds.iter().map(|d| d.convert()).collect());
+
+        Ok(GetPendingValidatorsResponse {
+            jsonrpc: self.jsonrpc.clone(),
+            id: self.id,
+            result: Some(GetPendingValidatorsResult {
+                validators,
+                delegators,
+            }),
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::platformvm::test_convert_get_pending_validators --exact --show-output
+#[test]
+fn test_convert_get_pending_validators() {
+    // ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgetpendingvalidators
+    let resp: RawGetPendingValidatorsResponse = serde_json::from_str(
+        "
+{
+    \"jsonrpc\": \"2.0\",
+    \"result\": {
+        \"validators\": [
+            {
+                \"txID\": \"2NNkpYTGfTFLSGXJcHtVv6drwVU2cczhmjK2uhvwDyxwsjzZMm\",
+                \"startTime\": \"1600368632\",
+                \"endTime\": \"1602960455\",
+                \"stakeAmount\": \"200000000000\",
+                \"nodeID\": \"NodeID-5mb46qkSBj81k9g9e4VFjGGSbaaSLFRzD\",
+                \"delegationFee\": \"10.0000\",
+                \"connected\": false
+            }
+        ],
+        \"delegators\": []
+    },
+    \"id\": 1
+}
+
+",
+    )
+    .unwrap();
+    let parsed = resp.convert().unwrap();
+    let expected = GetPendingValidatorsResponse {
+        jsonrpc: "2.0".to_string(),
+        id: 1,
+        result: Some(GetPendingValidatorsResult {
+            validators: Some(vec![ApiPrimaryValidator {
+                tx_id: Some(
+                    ids::Id::from_str("2NNkpYTGfTFLSGXJcHtVv6drwVU2cczhmjK2uhvwDyxwsjzZMm")
+                        .unwrap(),
+                ),
+                start_time: Some(1600368632),
+                end_time: Some(1602960455),
+                weight: Some(0),
+                stake_amount: Some(200000000000),
+                node_id: Some(
+                    ids::NodeId::from_str("NodeID-5mb46qkSBj81k9g9e4VFjGGSbaaSLFRzD").unwrap(),
+                ),
+                potential_reward: Some(0),
+                delegation_fee: Some(10.0),
+                uptime: Some(0.0),
+                connected: Some(false),
+                ..ApiPrimaryValidator::default()
+            }]),
+            delegators: Some(Vec::new()),
+        }),
+    };
+    assert_eq!(parsed, expected);
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformsamplevalidators
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct SampleValidatorsResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<SampleValidatorsResult>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformsamplevalidators
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct SampleValidatorsResult {
+    pub validators: Vec<ids::NodeId>,
+}
+
+impl Default for SampleValidatorsResult {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl SampleValidatorsResult {
+    pub fn default() -> Self {
+        Self {
+            validators: Vec::new(),
+        }
+    }
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformsamplevalidators
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct RawSampleValidatorsResponse {
+    jsonrpc: String,
+    id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<RawSampleValidatorsResult>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformsamplevalidators
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct RawSampleValidatorsResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    validators: Option<Vec<String>>,
+}
+
+impl RawSampleValidatorsResponse {
+    pub fn convert(&self) -> io::Result<SampleValidatorsResponse> {
+        let mut result = SampleValidatorsResult::default();
+        if let Some(raw) = &self.result {
+            for node_id in raw.validators.clone().unwrap_or_default().iter() {
+                result.validators.push(ids::NodeId::from_str(node_id)?);
+            }
+        }
+
+        Ok(SampleValidatorsResponse {
+            jsonrpc: self.jsonrpc.clone(),
+            id: self.id,
+            result: Some(result),
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::platformvm::test_convert_sample_validators --exact --show-output
+#[test]
+fn test_convert_sample_validators() {
+    // ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformsamplevalidators
+    let resp: RawSampleValidatorsResponse = serde_json::from_str(
+        "
+{
+    \"jsonrpc\": \"2.0\",
+    \"id\": 1,
+    \"result\": {
+        \"validators\": [
+            \"NodeID-MFrZFVCXPv5iCn6M9K6XduxGTYp891xXZ\",
+            \"NodeID-NFBbbJ4qCmNaCzeW7sxErhvWqvEQMnYcN\"
+        ]
+    }
+}
+
+",
+    )
+    .unwrap();
+    let parsed = resp.convert().unwrap();
+    let expected = SampleValidatorsResponse {
+        jsonrpc: "2.0".to_string(),
+        id: 1,
+        result: Some(SampleValidatorsResult {
+            validators: vec![
+                ids::NodeId::from_str("NodeID-MFrZFVCXPv5iCn6M9K6XduxGTYp891xXZ").unwrap(),
+                ids::NodeId::from_str("NodeID-NFBbbJ4qCmNaCzeW7sxErhvWqvEQMnYcN").unwrap(),
+            ],
+        }),
+    };
+    assert_eq!(parsed, expected);
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformissuetx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct IssueTxResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    pub result: Option<IssueTxResult>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformissuetx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct IssueTxResult {
+    #[serde(rename = "txID", deserialize_with = "ids::must_deserialize_id")]
+    pub tx_id: ids::Id,
+}
+
+impl Default for IssueTxResult {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl IssueTxResult {
+    pub fn default() -> Self {
+        Self {
+            tx_id: ids::Id::empty(),
+        }
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::platformvm::test_issue_tx_response --exact --show-output
+#[test]
+fn test_issue_tx_response() {
+    // ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformissuetx
+    let parsed: IssueTxResponse = serde_json::from_str(
+        "
+
+{
+    \"jsonrpc\": \"2.0\",
+    \"result\": {
+        \"txID\": \"G3BuH6ytQ2averrLxJJugjWZHTRubzCrUZEXoheG5JMqL5ccY\"
+    },
+    \"id\": 1
+}
+
+",
+    )
+    .unwrap();
+    let expected = IssueTxResponse {
+        jsonrpc: "2.0".to_string(),
+        id: 1,
+        result: Some(IssueTxResult {
+            tx_id: ids::Id::from_str("G3BuH6ytQ2averrLxJJugjWZHTRubzCrUZEXoheG5JMqL5ccY").unwrap(),
+        }),
+    };
+    assert_eq!(parsed, expected);
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgettx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetTxResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    pub result: Option<GetTxResult>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgettx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetTxResult {
+    /// Signed tx bytes in the "encoding" with the checksum.
+    pub tx: String,
+    pub encoding: String,
+}
+
+impl Default for GetTxResult {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl GetTxResult {
+    pub fn default() -> Self {
+        Self {
+            tx: String::new(),
+            encoding: String::from("hex"),
+        }
+    }
+
+    /// Decodes the signed tx bytes (e.g., for "txs::add_validator::Tx::from_bytes").
+    pub fn decode_tx(&self) -> io::Result<Vec<u8>> {
+        if self.encoding == "hex" {
+            formatting::decode_hex_untrusted(self.tx.as_bytes())
+        } else {
+            formatting::decode_cb58_untrusted(&self.tx)
+        }
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::platformvm::test_get_tx_response --exact --show-output
+#[test]
+fn test_get_tx_response() {
+    use crate::platformvm::txs::reward_validator;
+
+    let (tx_bytes, _) = reward_validator::Tx::new(ids::Id::from_slice(&[0x11; 32]))
+        .to_bytes()
+        .unwrap();
+    let encoded = formatting::encode_hex_with_checksum(&tx_bytes);
+
+    let parsed: GetTxResponse = serde_json::from_str(&format!(
+        "{{\"jsonrpc\":\"2.0\",\"result\":{{\"tx\":\"{}\",\"encoding\":\"hex\"}},\"id\":1}}",
+        encoded
+    ))
+    .unwrap();
+    let result = parsed.result.unwrap();
+    assert_eq!(result.decode_tx().unwrap(), tx_bytes);
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgettxstatus
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetTxStatusResponse {
+    pub jsonrpc: String,
+    pub id: u32,
+    pub result: Option<GetTxStatusResult>,
+}
+
+/// ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgettxstatus
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct GetTxStatusResult {
+    /// e.g., "Committed", "Processing", "Dropped", "Unknown".
+    pub status: String,
+    /// Set if the tx was dropped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Default for GetTxStatusResult {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl GetTxStatusResult {
+    pub fn default() -> Self {
+        Self {
+            status: String::from("Unknown"),
+            reason: None,
+        }
+    }
+
+    pub fn is_committed(&self) -> bool {
+        self.status == "Committed"
+    }
+
+    pub fn is_dropped(&self) -> bool {
+        self.status == "Dropped"
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- api::platformvm::test_get_tx_status_response --exact --show-output
+#[test]
+fn test_get_tx_status_response() {
+    // ref. https://docs.avax.network/build/avalanchego-apis/p-chain/#platformgettxstatus
+    let parsed: GetTxStatusResponse = serde_json::from_str(
+        "
+
+{
+    \"jsonrpc\": \"2.0\",
+    \"result\": {
+        \"status\": \"Committed\"
+    },
+    \"id\": 1
+}
+
+",
+    )
+    .unwrap();
+    let expected = GetTxStatusResponse {
+        jsonrpc: "2.0".to_string(),
+        id: 1,
+        result: Some(GetTxStatusResult {
+            status: String::from("Committed"),
+            reason: None,
+        }),
+    };
+    assert_eq!(parsed, expected);
+    assert!(parsed.result.unwrap().is_committed());
+
+    let parsed: GetTxStatusResponse = serde_json::from_str(
+        "{\"jsonrpc\":\"2.0\",\"result\":{\"status\":\"Dropped\",\"reason\":\"insufficient balance\"},\"id\":1}",
+    )
+    .unwrap();
+    let result = parsed.result.unwrap();
+    assert!(result.is_dropped());
+    assert_eq!(result.reason, Some(String::from("insufficient balance")));
+}
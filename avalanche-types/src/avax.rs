@@ -1,7 +1,13 @@
-use std::{cmp::Ordering, io, str::FromStr};
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    io::{self, Error, ErrorKind},
+    str::FromStr,
+};
 
-use crate::ids;
+use crate::{codec, ids, packer, platformvm, secp256k1fx, soft_key, utxo};
 use serde::{Deserialize, Serialize};
+use utils::{cmp, hash, secp256k1r};
 
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/components/avax#UTXOID
 #[derive(Debug, Serialize, Deserialize, Eq, Clone)]
@@ -44,8 +50,7 @@ impl UtxoId {
 impl Ord for UtxoId {
     fn cmp(&self, other: &UtxoId) -> Ordering {
         self.tx_id
-            .d
-            .cmp(&(other.tx_id.d)) // returns when "tx_id"s are not Equal
+            .cmp(&(other.tx_id)) // returns when "tx_id"s are not Equal
             .then_with(
                 || self.output_index.cmp(&other.output_index), // if "tx_id"s are Equal, compare "output_index"
             )
@@ -68,8 +73,6 @@ impl PartialEq for UtxoId {
 /// RUST_LOG=debug cargo test --package avalanche-types --lib -- avax::test_sort_utxo_ids --exact --show-output
 #[test]
 fn test_sort_utxo_ids() {
-    use utils::cmp;
-
     let mut utxos: Vec<UtxoId> = Vec::new();
     for i in (0..10).rev() {
         utxos.push(UtxoId {
@@ -142,3 +145,678 @@ fn test_utxo_id() {
     let expected_id = ids::Id::from_slice(&expected_id);
     assert_eq!(utxo_id.id, expected_id);
 }
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/components/avax#TransferableOutput
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct TransferableOutput {
+    pub asset_id: ids::Id,
+    /// Only one of "transfer_output" and "stakeable_lock_out" must be set.
+    pub transfer_output: Option<secp256k1fx::TransferOutput>,
+    /// Only valid on the P-chain.
+    pub stakeable_lock_out: Option<platformvm::StakeableLockOut>,
+}
+
+impl Default for TransferableOutput {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl TransferableOutput {
+    pub fn default() -> Self {
+        Self {
+            asset_id: ids::Id::empty(),
+            transfer_output: None,
+            stakeable_lock_out: None,
+        }
+    }
+
+    /// Returns the amount of the underlying transfer output.
+    pub fn amount(&self) -> u64 {
+        if let Some(out) = &self.transfer_output {
+            return out.amount;
+        }
+        if let Some(lock_out) = &self.stakeable_lock_out {
+            return lock_out.transfer_output.amount;
+        }
+        0
+    }
+
+    /// Packs the output with the type ID of its "TransferableOut" interface.
+    pub fn pack(&self, packer: &packer::Packer) -> io::Result<()> {
+        packer.pack_bytes(&self.asset_id.d);
+        pack_out(packer, &self.transfer_output, &self.stakeable_lock_out)
+    }
+
+    /// Unpacks the output written by "pack".
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let asset_id = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+        let type_id = packer.unpack_u32()?;
+
+        let mut out = Self {
+            asset_id,
+            ..Self::default()
+        };
+        if type_id == secp256k1fx::TransferOutput::type_id() {
+            out.transfer_output = Some(secp256k1fx::TransferOutput::unpack(packer)?);
+        } else if type_id == platformvm::StakeableLockOut::type_id() {
+            let locktime = packer.unpack_u64()?;
+            let inner_type_id = packer.unpack_u32()?;
+            if inner_type_id != secp256k1fx::TransferOutput::type_id() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("unexpected locked output type ID {}", inner_type_id),
+                ));
+            }
+            out.stakeable_lock_out = Some(platformvm::StakeableLockOut {
+                locktime,
+                transfer_output: secp256k1fx::TransferOutput::unpack(packer)?,
+            });
+        } else {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unexpected output type ID {}", type_id),
+            ));
+        }
+        Ok(out)
+    }
+
+    fn packed_bytes(&self) -> io::Result<Vec<u8>> {
+        let packer = packer::Packer::new(codec::DEFAULT_MAX_SIZE, 128);
+        self.pack(&packer)?;
+        if let Some(e) = packer.take_error() {
+            return Err(e);
+        }
+        Ok(packer.take_bytes().to_vec())
+    }
+}
+
+/// Sorts the outputs by their packed bytes, as required by the codec.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/components/avax#SortTransferableOutputs
+pub fn sort_transferable_outputs(outs: &mut Vec<TransferableOutput>) -> io::Result<()> {
+    let mut keyed: Vec<(Vec<u8>, TransferableOutput)> = Vec::with_capacity(outs.len());
+    for out in outs.drain(..) {
+        keyed.push((out.packed_bytes()?, out));
+    }
+    keyed.sort_by(|a, b| a.0.cmp(&b.0));
+    outs.extend(keyed.into_iter().map(|(_, out)| out));
+    Ok(())
+}
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/components/avax#TransferableInput
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct TransferableInput {
+    pub utxo_id: UtxoId,
+    pub asset_id: ids::Id,
+    /// Only one of "transfer_input" and "stakeable_lock_in" must be set.
+    pub transfer_input: Option<secp256k1fx::TransferInput>,
+    /// Only valid on the P-chain.
+    pub stakeable_lock_in: Option<platformvm::StakeableLockIn>,
+}
+
+impl Default for TransferableInput {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl TransferableInput {
+    pub fn default() -> Self {
+        Self {
+            utxo_id: UtxoId::default(),
+            asset_id: ids::Id::empty(),
+            transfer_input: None,
+            stakeable_lock_in: None,
+        }
+    }
+
+    /// Returns the amount of the underlying transfer input.
+    pub fn amount(&self) -> u64 {
+        if let Some(input) = &self.transfer_input {
+            return input.amount;
+        }
+        if let Some(lock_in) = &self.stakeable_lock_in {
+            return lock_in.transfer_input.amount;
+        }
+        0
+    }
+
+    /// Packs the input with the type ID of its "TransferableIn" interface.
+    pub fn pack(&self, packer: &packer::Packer) -> io::Result<()> {
+        packer.pack_bytes(&self.utxo_id.tx_id.d);
+        packer.pack_u32(self.utxo_id.output_index);
+        packer.pack_bytes(&self.asset_id.d);
+        match (&self.transfer_input, &self.stakeable_lock_in) {
+            (Some(input), None) => {
+                packer.pack_u32(secp256k1fx::TransferInput::type_id());
+                input.pack(packer);
+            }
+            (None, Some(lock_in)) => {
+                packer.pack_u32(platformvm::StakeableLockIn::type_id());
+                packer.pack_u64(lock_in.locktime);
+                packer.pack_u32(secp256k1fx::TransferInput::type_id());
+                lock_in.transfer_input.pack(packer);
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "exactly one of transfer_input and stakeable_lock_in must be set",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Unpacks the input written by "pack".
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let tx_id = packer.unpack_bytes(ids::ID_LEN)?;
+        let output_index = packer.unpack_u32()?;
+        let asset_id = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+        let type_id = packer.unpack_u32()?;
+
+        let mut input = Self {
+            utxo_id: UtxoId::new(&tx_id, output_index, false),
+            asset_id,
+            ..Self::default()
+        };
+        if type_id == secp256k1fx::TransferInput::type_id() {
+            input.transfer_input = Some(secp256k1fx::TransferInput::unpack(packer)?);
+        } else if type_id == platformvm::StakeableLockIn::type_id() {
+            let locktime = packer.unpack_u64()?;
+            let inner_type_id = packer.unpack_u32()?;
+            if inner_type_id != secp256k1fx::TransferInput::type_id() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("unexpected locked input type ID {}", inner_type_id),
+                ));
+            }
+            input.stakeable_lock_in = Some(platformvm::StakeableLockIn {
+                locktime,
+                transfer_input: secp256k1fx::TransferInput::unpack(packer)?,
+            });
+        } else {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unexpected input type ID {}", type_id),
+            ));
+        }
+        Ok(input)
+    }
+}
+
+/// Inputs are ordered by their UTXO IDs.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/components/avax#SortTransferableInputs
+impl Ord for TransferableInput {
+    fn cmp(&self, other: &TransferableInput) -> Ordering {
+        self.utxo_id.cmp(&other.utxo_id)
+    }
+}
+
+impl PartialOrd for TransferableInput {
+    fn partial_cmp(&self, other: &TransferableInput) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/components/avax#BaseTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct BaseTx {
+    pub network_id: u32,
+    pub blockchain_id: ids::Id,
+    pub transferable_outputs: Option<Vec<TransferableOutput>>,
+    pub transferable_inputs: Option<Vec<TransferableInput>>,
+    pub memo: Option<Vec<u8>>,
+}
+
+impl Default for BaseTx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl BaseTx {
+    pub fn default() -> Self {
+        Self {
+            network_id: 0,
+            blockchain_id: ids::Id::empty(),
+            transferable_outputs: None,
+            transferable_inputs: None,
+            memo: None,
+        }
+    }
+
+    /// Packs the fields without the type ID.
+    pub fn pack(&self, packer: &packer::Packer) -> io::Result<()> {
+        packer.pack_u32(self.network_id);
+        packer.pack_bytes(&self.blockchain_id.d);
+
+        let outs = self.transferable_outputs.clone().unwrap_or_default();
+        packer.pack_u32(outs.len() as u32);
+        for out in outs.iter() {
+            out.pack(packer)?;
+        }
+
+        let ins = self.transferable_inputs.clone().unwrap_or_default();
+        packer.pack_u32(ins.len() as u32);
+        for input in ins.iter() {
+            input.pack(packer)?;
+        }
+
+        packer.pack_bytes_with_header(&self.memo.clone().unwrap_or_default());
+        Ok(())
+    }
+
+    /// Unpacks the fields written by "pack".
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let network_id = packer.unpack_u32()?;
+        let blockchain_id = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+
+        let n = packer.unpack_u32()?;
+        let mut outs: Vec<TransferableOutput> = Vec::new();
+        for _ in 0..n {
+            outs.push(TransferableOutput::unpack(packer)?);
+        }
+
+        let n = packer.unpack_u32()?;
+        let mut ins: Vec<TransferableInput> = Vec::new();
+        for _ in 0..n {
+            ins.push(TransferableInput::unpack(packer)?);
+        }
+
+        let memo = packer.unpack_bytes_with_header()?;
+        Ok(Self {
+            network_id,
+            blockchain_id,
+            transferable_outputs: if outs.is_empty() { None } else { Some(outs) },
+            transferable_inputs: if ins.is_empty() { None } else { Some(ins) },
+            memo: if memo.is_empty() { None } else { Some(memo) },
+        })
+    }
+
+    /// Checks the canonical ordering that the codec requires.
+    /// ref. "avalanchego/vms/components/avax.BaseTx.Verify"
+    pub fn verify_sorted(&self) -> io::Result<()> {
+        let outs = self.transferable_outputs.as_deref().unwrap_or_default();
+        let mut packed: Vec<Vec<u8>> = Vec::with_capacity(outs.len());
+        for out in outs.iter() {
+            packed.push(out.packed_bytes()?);
+        }
+        if !packed.windows(2).all(|w| w[0] <= w[1]) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "outputs not sorted (use \"sort_transferable_outputs\")", // ref. "errOutputsNotSorted"
+            ));
+        }
+
+        let ins = self.transferable_inputs.as_deref().unwrap_or_default();
+        if !cmp::is_sorted_and_unique(ins) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "inputs not sorted and unique by UTXO ID", // ref. "errInputsNotSortedUnique"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks that the inputs spend the UTXOs as they are, and that they cover
+    /// the outputs of each asset plus the AVAX "fee" to burn.
+    /// ref. "avalanchego/vms/components/avax.FlowChecker"
+    pub fn verify_flow(
+        &self,
+        utxos: &[utxo::Utxo],
+        avax_asset_id: &ids::Id,
+        fee: u64,
+    ) -> io::Result<()> {
+        let mut consumed: BTreeMap<ids::Id, u64> = BTreeMap::new();
+        for input in self
+            .transferable_inputs
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+        {
+            let utxo = find_utxo(utxos, input)?;
+            if input.amount() != utxo.amount() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "input amount {} does not match UTXO {}:{} amount {}",
+                        input.amount(),
+                        input.utxo_id.tx_id,
+                        input.utxo_id.output_index,
+                        utxo.amount()
+                    ),
+                ));
+            }
+            add_flow(&mut consumed, input.asset_id, input.amount())?;
+        }
+
+        let mut produced: BTreeMap<ids::Id, u64> = BTreeMap::new();
+        for out in self
+            .transferable_outputs
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+        {
+            if out.amount() == 0 {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "output has no value", // ref. "errNoValueOutput"
+                ));
+            }
+            add_flow(&mut produced, out.asset_id, out.amount())?;
+        }
+        add_flow(&mut produced, *avax_asset_id, fee)?;
+
+        for (asset_id, needed) in produced.iter() {
+            let available = consumed.get(asset_id).copied().unwrap_or(0);
+            if available < *needed {
+                let msg = if asset_id == avax_asset_id {
+                    format!(
+                        "insufficient AVAX for outputs and fee {} (needed {}, consumed {})",
+                        fee, needed, available
+                    )
+                } else {
+                    format!(
+                        "insufficient funds for asset {} (needed {}, consumed {})",
+                        asset_id, needed, available
+                    )
+                };
+                return Err(Error::new(ErrorKind::InvalidInput, msg));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that each credential has the signatures of the UTXO owners
+    /// at the signature indices of its input, in the same order as the inputs.
+    /// ref. "avalanchego/vms/secp256k1fx.Fx.VerifyCredentials"
+    pub fn verify_credentials(
+        &self,
+        unsigned_bytes: &[u8],
+        creds: &[secp256k1fx::Credential],
+        utxos: &[utxo::Utxo],
+        now: u64,
+    ) -> io::Result<()> {
+        let ins = self.transferable_inputs.as_deref().unwrap_or_default();
+        if ins.len() != creds.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} inputs but {} credentials", ins.len(), creds.len()),
+            ));
+        }
+
+        let digest = hash::compute_sha256(unsigned_bytes);
+        for (pos, (input, cred)) in ins.iter().zip(creds.iter()).enumerate() {
+            let utxo = find_utxo(utxos, input)?;
+            let owners = match (&utxo.transfer_output, &utxo.stakeable_lock_out) {
+                (Some(out), _) => &out.output_owners,
+                (None, Some(lock_out)) => &lock_out.transfer_output.output_owners,
+                (None, None) => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("UTXO of input {} has no output", pos),
+                    ))
+                }
+            };
+            let transfer_input = match (&input.transfer_input, &input.stakeable_lock_in) {
+                (Some(v), _) => v,
+                (None, Some(lock_in)) => &lock_in.transfer_input,
+                (None, None) => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("input {} has no transfer input", pos),
+                    ))
+                }
+            };
+            transfer_input.verify()?;
+
+            if owners.locktime > now {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "input {} spends UTXO locked until {} (now {})", // ref. "errTimelocked"
+                        pos, owners.locktime, now
+                    ),
+                ));
+            }
+            if transfer_input.sig_indices.len() != owners.threshold as usize {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "input {} has {} signature indices but owner threshold {}",
+                        pos,
+                        transfer_input.sig_indices.len(),
+                        owners.threshold
+                    ),
+                ));
+            }
+            if cred.signatures.len() != transfer_input.sig_indices.len() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "credential {} has {} signatures but {} signature indices", // ref. "errInputCredentialSignersMismatch"
+                        pos,
+                        cred.signatures.len(),
+                        transfer_input.sig_indices.len()
+                    ),
+                ));
+            }
+
+            let addrs = owners.addrs_at(&transfer_input.sig_indices)?;
+            for (addr, sig) in addrs.iter().zip(cred.signatures.iter()) {
+                let public_key = secp256k1r::recover_public_key(&digest, sig)?;
+                let signer = soft_key::public_key_to_short_address(&public_key)?;
+                if signer != *addr {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "credential {} signed by {} instead of owner {}", // ref. "errWrongSig"
+                            pos, signer, addr
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn find_utxo<'a>(utxos: &'a [utxo::Utxo], input: &TransferableInput) -> io::Result<&'a utxo::Utxo> {
+    let utxo = utxos
+        .iter()
+        .find(|u| u.utxo_id == input.utxo_id)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "UTXO {}:{} of the input not found",
+                    input.utxo_id.tx_id, input.utxo_id.output_index
+                ),
+            )
+        })?;
+    if utxo.asset_id != input.asset_id {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "input asset {} does not match UTXO {}:{} asset {}",
+                input.asset_id, input.utxo_id.tx_id, input.utxo_id.output_index, utxo.asset_id
+            ),
+        ));
+    }
+    Ok(utxo)
+}
+
+fn add_flow(m: &mut BTreeMap<ids::Id, u64>, asset_id: ids::Id, amount: u64) -> io::Result<()> {
+    let cur = m.entry(asset_id).or_insert(0);
+    *cur = cur
+        .checked_add(amount)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "amount overflows u64"))?;
+    Ok(())
+}
+
+/// Packs the "Out" interface value with its type ID.
+pub(crate) fn pack_out(
+    packer: &packer::Packer,
+    transfer_output: &Option<secp256k1fx::TransferOutput>,
+    stakeable_lock_out: &Option<platformvm::StakeableLockOut>,
+) -> io::Result<()> {
+    match (transfer_output, stakeable_lock_out) {
+        (Some(out), None) => {
+            packer.pack_u32(secp256k1fx::TransferOutput::type_id());
+            out.pack(packer);
+        }
+        (None, Some(lock_out)) => {
+            packer.pack_u32(platformvm::StakeableLockOut::type_id());
+            packer.pack_u64(lock_out.locktime);
+            packer.pack_u32(secp256k1fx::TransferOutput::type_id());
+            lock_out.transfer_output.pack(packer);
+        }
+        _ => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "exactly one of transfer_output and stakeable_lock_out must be set",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Signs the hash of the unsigned tx bytes with the keys of each input,
+/// returning one credential per input in the same order.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm#Tx.Sign
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/avm#Tx.SignSECP256K1Fx
+pub fn sign_credentials(
+    unsigned_bytes: &[u8],
+    signers: &[Vec<soft_key::Key>],
+) -> io::Result<Vec<secp256k1fx::Credential>> {
+    let digest = hash::compute_sha256(unsigned_bytes);
+
+    let mut creds: Vec<secp256k1fx::Credential> = Vec::with_capacity(signers.len());
+    for keys in signers.iter() {
+        let mut sigs: Vec<Vec<u8>> = Vec::with_capacity(keys.len());
+        for k in keys.iter() {
+            sigs.push(k.sign_digest(&digest)?);
+        }
+        creds.push(secp256k1fx::Credential::new(sigs));
+    }
+    Ok(creds)
+}
+
+/// Appends the credentials to the unsigned tx bytes,
+/// and returns the signed tx bytes with its ID.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm#Tx
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/avm#Tx
+pub fn pack_signed_tx(
+    unsigned_bytes: &[u8],
+    creds: &[secp256k1fx::Credential],
+) -> io::Result<(Vec<u8>, ids::Id)> {
+    let packer = packer::Packer::load_bytes_for_pack(codec::DEFAULT_MAX_SIZE, unsigned_bytes);
+    packer.pack_u32(creds.len() as u32);
+    for cred in creds.iter() {
+        cred.pack(&packer);
+    }
+    if let Some(e) = packer.take_error() {
+        return Err(e);
+    }
+
+    let signed_bytes = packer.take_bytes().to_vec();
+    let tx_id = ids::Id::from_slice(&hash::compute_sha256(&signed_bytes));
+    Ok((signed_bytes, tx_id))
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- avax::test_sort_transferable_outputs --exact --show-output
+#[test]
+fn test_sort_transferable_outputs() {
+    let mut outs: Vec<TransferableOutput> = Vec::new();
+    for amount in [30_u64, 10, 20] {
+        outs.push(TransferableOutput {
+            asset_id: ids::Id::from_slice(&[1, 2, 3]),
+            transfer_output: Some(secp256k1fx::TransferOutput {
+                amount,
+                output_owners: secp256k1fx::OutputOwners::new(
+                    0,
+                    1,
+                    &[ids::ShortId::from_slice(&[4, 5, 6])],
+                ),
+            }),
+            ..TransferableOutput::default()
+        });
+    }
+    sort_transferable_outputs(&mut outs).unwrap();
+
+    let amounts: Vec<u64> = outs.iter().map(|o| o.amount()).collect();
+    assert_eq!(amounts, vec![10, 20, 30]);
+
+    outs.push(TransferableOutput::default());
+    assert!(sort_transferable_outputs(&mut outs).is_err());
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- avax::test_transferable_round_trip --exact --show-output
+#[test]
+fn test_transferable_round_trip() {
+    let owners = secp256k1fx::OutputOwners::new(0, 1, &[ids::ShortId::from_slice(&[0x11; 20])]);
+
+    let outs = [
+        TransferableOutput {
+            asset_id: ids::Id::from_slice(&[0xbb; 32]),
+            transfer_output: Some(secp256k1fx::TransferOutput::new(1000, owners.clone())),
+            ..TransferableOutput::default()
+        },
+        TransferableOutput {
+            asset_id: ids::Id::from_slice(&[0xbb; 32]),
+            stakeable_lock_out: Some(platformvm::StakeableLockOut {
+                locktime: 12345,
+                transfer_output: secp256k1fx::TransferOutput::new(2000, owners),
+            }),
+            ..TransferableOutput::default()
+        },
+    ];
+    let ins = [
+        TransferableInput {
+            utxo_id: UtxoId::new(&[0xaa; 32], 1, false),
+            asset_id: ids::Id::from_slice(&[0xbb; 32]),
+            transfer_input: Some(secp256k1fx::TransferInput::new(1000, vec![0, 1])),
+            ..TransferableInput::default()
+        },
+        TransferableInput {
+            utxo_id: UtxoId::new(&[0xaa; 32], 2, false),
+            asset_id: ids::Id::from_slice(&[0xbb; 32]),
+            stakeable_lock_in: Some(platformvm::StakeableLockIn {
+                locktime: 12345,
+                transfer_input: secp256k1fx::TransferInput::new(2000, vec![0]),
+            }),
+            ..TransferableInput::default()
+        },
+    ];
+    let cred = secp256k1fx::Credential::new(vec![vec![0x01; 65], vec![0x02; 65]]);
+
+    let packer = packer::Packer::new(codec::DEFAULT_MAX_SIZE, 512);
+    for out in outs.iter() {
+        out.pack(&packer).unwrap();
+    }
+    for input in ins.iter() {
+        input.pack(&packer).unwrap();
+    }
+    cred.pack(&packer);
+    let b = packer.take_bytes();
+
+    let packer = packer::Packer::load_bytes_for_unpack(codec::DEFAULT_MAX_SIZE, &b);
+    for out in outs.iter() {
+        assert_eq!(&TransferableOutput::unpack(&packer).unwrap(), out);
+    }
+    for input in ins.iter() {
+        let unpacked = TransferableInput::unpack(&packer).unwrap();
+        assert_eq!(unpacked.utxo_id.id, input.utxo_id.id);
+        assert_eq!(unpacked.transfer_input, input.transfer_input);
+        assert_eq!(unpacked.stakeable_lock_in, input.stakeable_lock_in);
+    }
+    assert_eq!(secp256k1fx::Credential::unpack(&packer).unwrap(), cred);
+    assert_eq!(packer.remaining(), 0);
+
+    // unknown output type ID
+    let packer = packer::Packer::load_bytes_for_unpack(
+        codec::DEFAULT_MAX_SIZE,
+        &[&[0xbb; 32][..], &[0x00, 0x00, 0x00, 0x63]].concat(),
+    );
+    assert!(TransferableOutput::unpack(&packer).is_err());
+}
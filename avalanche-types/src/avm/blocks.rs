@@ -0,0 +1,279 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avm::txs, codec, ids, packer};
+use utils::hash;
+
+/// Codec version of the vertex with the txs.
+pub const VERTEX_VERSION: u16 = 0;
+
+/// Codec version of the stop vertex, which has no epoch nor txs
+/// and marks the end of the DAG before the X-chain linearization.
+pub const STOP_VERTEX_VERSION: u16 = 1;
+
+/// ref. "avalanchego/snow/engine/avalanche/vertex.maxNumParents"
+pub const MAX_PARENTS: u32 = 128;
+
+/// ref. "avalanchego/snow/engine/avalanche/vertex.maxTxsPerVtx"
+pub const MAX_TXS_PER_VERTEX: u32 = 128;
+
+/// Tx embedded in the vertex or the block, with its ID.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct BlockTx {
+    pub id: ids::Id,
+    pub tx: txs::Tx,
+}
+
+/// Accepted X-chain vertex, as returned by the "/ext/index/X/vtx" index.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/snow/engine/avalanche/vertex#StatelessVertex
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Vertex {
+    pub id: ids::Id,
+    /// Codec version, either "VERTEX_VERSION" or "STOP_VERTEX_VERSION".
+    pub version: u16,
+    pub chain_id: ids::Id,
+    pub height: u64,
+    /// Always zero for the stop vertex.
+    pub epoch: u32,
+    pub parent_ids: Vec<ids::Id>,
+    /// Always empty for the stop vertex.
+    pub txs: Vec<BlockTx>,
+}
+
+impl Vertex {
+    /// Decodes the codec-encoded vertex bytes (e.g., "index::Container.decode_bytes")
+    /// with all its txs.
+    pub fn from_bytes(b: &[u8]) -> io::Result<Self> {
+        codec::VERTEX_CODEC.unmarshal(b, |version, packer| {
+            let chain_id = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+            let height = packer.unpack_u64()?;
+            let epoch = if version == VERTEX_VERSION {
+                packer.unpack_u32()?
+            } else {
+                0
+            };
+
+            let n = unpack_len(packer, MAX_PARENTS, "parents")?;
+            let mut parent_ids: Vec<ids::Id> = Vec::new();
+            for _ in 0..n {
+                parent_ids.push(ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?));
+            }
+
+            let mut txs: Vec<BlockTx> = Vec::new();
+            if version == VERTEX_VERSION {
+                let n = unpack_len(packer, MAX_TXS_PER_VERTEX, "txs")?;
+                for _ in 0..n {
+                    // each tx is the signed tx bytes with its own codec version
+                    let (tx, id) = txs::Tx::from_bytes(&packer.unpack_bytes_with_header()?)?;
+                    txs.push(BlockTx { id, tx });
+                }
+            }
+
+            Ok(Self {
+                id: ids::Id::from_slice(&hash::compute_sha256(b)),
+                version,
+                chain_id,
+                height,
+                epoch,
+                parent_ids,
+                txs,
+            })
+        })
+    }
+
+    pub fn is_stop_vertex(&self) -> bool {
+        self.version == STOP_VERTEX_VERSION
+    }
+}
+
+/// Accepted X-chain block after the linearization.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/avm/blocks#StandardBlock
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct StandardBlock {
+    pub id: ids::Id,
+    pub parent_id: ids::Id,
+    pub height: u64,
+    /// Unix time in seconds.
+    pub time: u64,
+    pub merkle_root: ids::Id,
+    pub txs: Vec<BlockTx>,
+}
+
+impl StandardBlock {
+    pub fn type_name() -> String {
+        "blocks.StandardBlock".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::X_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// Decodes the codec-encoded block bytes with all its txs.
+    pub fn from_bytes(b: &[u8]) -> io::Result<Self> {
+        codec::X_CODEC.unmarshal(b, |_, packer| {
+            let type_id = packer.unpack_u32()?;
+            if type_id != Self::type_id() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "type ID {} ({}) is not an X-chain block",
+                        type_id,
+                        codec::X_CODEC
+                            .type_name(codec::VERSION, type_id)
+                            .unwrap_or("unknown")
+                    ),
+                ));
+            }
+
+            let parent_id = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+            let height = packer.unpack_u64()?;
+            let time = packer.unpack_u64()?;
+            let merkle_root = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+
+            let n = packer.unpack_u32()?;
+            let mut txs: Vec<BlockTx> = Vec::new();
+            for _ in 0..n {
+                // "*txs.Tx" without the codec version, so the tx ID is computed
+                // over the bytes of the tx as if issued alone
+                let start = packer.get_offset();
+                let tx = txs::Tx::unpack(packer)?;
+                let mut tx_bytes = b[..2].to_vec();
+                tx_bytes.extend_from_slice(&b[start..packer.get_offset()]);
+                txs.push(BlockTx {
+                    id: ids::Id::from_slice(&hash::compute_sha256(&tx_bytes)),
+                    tx,
+                });
+            }
+
+            Ok(Self {
+                id: ids::Id::from_slice(&hash::compute_sha256(b)),
+                parent_id,
+                height,
+                time,
+                merkle_root,
+                txs,
+            })
+        })
+    }
+}
+
+/// Unpacks the length of the slice limited by its "len" struct tag.
+fn unpack_len(packer: &packer::Packer, max: u32, name: &str) -> io::Result<u32> {
+    let n = packer.unpack_u32()?;
+    if n > max {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("{} {} exceeds the maximum {}", n, name, max),
+        ));
+    }
+    Ok(n)
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- avm::blocks::test_vertex --exact --show-output
+#[test]
+fn test_vertex() {
+    let base_tx = txs::base::Tx {
+        base_tx: txs::test_base_tx(),
+        creds: vec![crate::secp256k1fx::Credential::new(vec![vec![0x44; 65]])],
+    };
+    let (signed_bytes, tx_id) =
+        crate::avax::pack_signed_tx(&base_tx.unsigned_bytes().unwrap(), &base_tx.creds).unwrap();
+
+    let vertex_bytes = |version: u16, txs: &[Vec<u8>]| {
+        codec::VERTEX_CODEC
+            .marshal(version, |packer| {
+                packer.pack_bytes(&[0xcc; 32]); // chain ID
+                packer.pack_u64(7); // height
+                if version == VERTEX_VERSION {
+                    packer.pack_u32(0); // epoch
+                }
+                packer.pack_u32(2);
+                packer.pack_bytes(&[0x01; 32]);
+                packer.pack_bytes(&[0x02; 32]);
+                if version == VERTEX_VERSION {
+                    packer.pack_u32(txs.len() as u32);
+                    for tx in txs.iter() {
+                        packer.pack_bytes_with_header(tx);
+                    }
+                }
+                Ok(())
+            })
+            .unwrap()
+    };
+
+    let b = vertex_bytes(VERTEX_VERSION, &[signed_bytes.clone()]);
+    let vtx = Vertex::from_bytes(&b).unwrap();
+    assert_eq!(vtx.id, ids::Id::from_slice(&hash::compute_sha256(&b)));
+    assert_eq!(vtx.chain_id, ids::Id::from_slice(&[0xcc; 32]));
+    assert_eq!(vtx.height, 7);
+    assert_eq!(
+        vtx.parent_ids,
+        vec![
+            ids::Id::from_slice(&[0x01; 32]),
+            ids::Id::from_slice(&[0x02; 32])
+        ]
+    );
+    assert!(!vtx.is_stop_vertex());
+    assert_eq!(
+        vtx.txs,
+        vec![BlockTx {
+            id: tx_id,
+            tx: txs::Tx::Base(base_tx),
+        }]
+    );
+
+    let stop = Vertex::from_bytes(&vertex_bytes(STOP_VERTEX_VERSION, &[])).unwrap();
+    assert!(stop.is_stop_vertex());
+    assert!(stop.txs.is_empty());
+    assert_eq!(stop.parent_ids.len(), 2);
+
+    // unknown codec version
+    let mut future = b.clone();
+    future[1] = 0x02;
+    assert!(Vertex::from_bytes(&future).is_err());
+
+    // the tx bytes must be the whole signed tx
+    let mut truncated = signed_bytes;
+    truncated.pop();
+    assert!(Vertex::from_bytes(&vertex_bytes(VERTEX_VERSION, &[truncated])).is_err());
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- avm::blocks::test_standard_block --exact --show-output
+#[test]
+fn test_standard_block() {
+    let base_tx = txs::base::Tx {
+        base_tx: txs::test_base_tx(),
+        creds: vec![crate::secp256k1fx::Credential::new(vec![vec![0x44; 65]])],
+    };
+    let (signed_bytes, tx_id) =
+        crate::avax::pack_signed_tx(&base_tx.unsigned_bytes().unwrap(), &base_tx.creds).unwrap();
+
+    let b = codec::X_CODEC
+        .marshal(codec::VERSION, |packer| {
+            packer.pack_u32(StandardBlock::type_id());
+            packer.pack_bytes(&[0x01; 32]); // parent ID
+            packer.pack_u64(10); // height
+            packer.pack_u64(1_700_000_000); // time
+            packer.pack_bytes(&[0x02; 32]); // merkle root
+            packer.pack_u32(2);
+            // embedded without the codec version
+            packer.pack_bytes(&signed_bytes[2..]);
+            packer.pack_bytes(&signed_bytes[2..]);
+            Ok(())
+        })
+        .unwrap();
+    let blk = StandardBlock::from_bytes(&b).unwrap();
+    assert_eq!(blk.id, ids::Id::from_slice(&hash::compute_sha256(&b)));
+    assert_eq!(blk.parent_id, ids::Id::from_slice(&[0x01; 32]));
+    assert_eq!(blk.height, 10);
+    assert_eq!(blk.time, 1_700_000_000);
+    assert_eq!(blk.merkle_root, ids::Id::from_slice(&[0x02; 32]));
+    assert_eq!(blk.txs.len(), 2);
+    assert_eq!(blk.txs[1].id, tx_id);
+    assert_eq!(blk.txs[1].tx, txs::Tx::Base(base_tx));
+
+    // the tx is not a block
+    let err = StandardBlock::from_bytes(&signed_bytes).unwrap_err();
+    assert!(err.to_string().contains("is not an X-chain block"));
+}
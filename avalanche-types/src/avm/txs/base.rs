@@ -0,0 +1,374 @@
+use std::{
+    collections::HashMap,
+    io::{self, Error, ErrorKind},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, formatting, ids, packer, secp256k1fx, soft_key, units, utxo};
+use utils::hash;
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/avm#BaseTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+
+    /// Populated by "sign", one per input.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "avm.BaseTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::X_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// Encodes the unsigned "avm.BaseTx" with the X-chain codec,
+    /// which is the message its credentials sign.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::X_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            self.base_tx.pack(packer)?;
+            Ok(())
+        })
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        Ok(Self {
+            base_tx: avax::BaseTx::unpack(packer)?,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+
+    /// Signs the tx with the keys for each input (in the same order as the inputs),
+    /// and returns the signed tx bytes to issue with its tx ID.
+    pub fn sign(&mut self, signers: &[Vec<soft_key::Key>]) -> io::Result<(Vec<u8>, ids::Id)> {
+        let ins = self
+            .base_tx
+            .transferable_inputs
+            .as_ref()
+            .map_or(0, |ins| ins.len());
+        if ins != signers.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} inputs but {} signers", ins, signers.len()),
+            ));
+        }
+
+        let unsigned_bytes = self.unsigned_bytes()?;
+        self.creds = avax::sign_credentials(&unsigned_bytes, signers)?;
+        avax::pack_signed_tx(&unsigned_bytes, &self.creds)
+    }
+
+    /// Returns the signed tx bytes with the credentials from "sign".
+    pub fn signed_bytes(&self) -> io::Result<Vec<u8>> {
+        let ins = self
+            .base_tx
+            .transferable_inputs
+            .as_ref()
+            .map_or(0, |ins| ins.len());
+        if ins != self.creds.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "tx not signed ({} inputs but {} credentials)",
+                    ins,
+                    self.creds.len()
+                ),
+            ));
+        }
+        let (signed_bytes, _) = avax::pack_signed_tx(&self.unsigned_bytes()?, &self.creds)?;
+        Ok(signed_bytes)
+    }
+
+    /// Returns the tx ID, the SHA256 hash of the signed tx bytes,
+    /// which is the ID returned by "avm.issueTx".
+    pub fn id(&self) -> io::Result<ids::Id> {
+        let signed_bytes = self.signed_bytes()?;
+        Ok(ids::Id::from_slice(&hash::compute_sha256(&signed_bytes)))
+    }
+
+    /// Verifies the signed tx against the UTXOs it spends, as the node does
+    /// before accepting it (e.g., sorted inputs and outputs, balanced amounts,
+    /// sufficient fee, and the signatures of the UTXO owners),
+    /// so the malformed tx fails before the submission.
+    /// "now" is the Unix timestamp in seconds to check the locktimes.
+    /// ref. "avalanchego/vms/avm.txSemanticVerify"
+    pub fn verify(
+        &self,
+        utxos: &[utxo::Utxo],
+        avax_asset_id: &ids::Id,
+        tx_fee: &units::Avax,
+        now: u64,
+    ) -> io::Result<()> {
+        self.base_tx.verify_sorted()?;
+        self.base_tx
+            .verify_flow(utxos, avax_asset_id, tx_fee.as_navax())?;
+        self.base_tx
+            .verify_credentials(&self.unsigned_bytes()?, &self.creds, utxos, now)
+    }
+}
+
+/// Encodes the signed tx bytes for "avm.issueTx" with "cb58" encoding.
+pub fn encode_cb58(signed_bytes: &[u8]) -> String {
+    formatting::encode_cb58_with_checksum(signed_bytes)
+}
+
+/// Builds X-chain base txs by spending the UTXOs owned by the keychain.
+/// ref. https://github.com/ava-labs/avalanchego/blob/v1.7.9/wallet/chain/x/builder.go
+pub struct Builder {
+    pub network_id: u32,
+    pub blockchain_id: ids::Id,
+    pub avax_asset_id: ids::Id,
+    /// Burned in AVAX on every base tx.
+    pub tx_fee: units::Avax,
+    pub keychain: soft_key::Keychain,
+}
+
+impl Builder {
+    /// Selects enough UTXOs to cover the outputs and the tx fee,
+    /// and sends any remainder of each asset back to "change_to".
+    /// Returns the unsigned tx and the signers of each input to pass to "Tx::sign".
+    /// ref. "avalanchego/wallet/chain/x.builder.NewBaseTx"
+    pub fn new_base_tx(
+        &self,
+        utxos: &[utxo::Utxo],
+        outputs: Vec<avax::TransferableOutput>,
+        change_to: &ids::ShortId,
+        now: u64,
+    ) -> io::Result<(Tx, Vec<Vec<soft_key::Key>>)> {
+        let mut to_burn: HashMap<ids::Id, u64> = HashMap::new();
+        for out in outputs.iter() {
+            add_amount(&mut to_burn, out.asset_id, out.amount())?;
+        }
+        add_amount(&mut to_burn, self.avax_asset_id, self.tx_fee.as_navax())?;
+
+        let mut consumed: HashMap<ids::Id, u64> = HashMap::new();
+        let mut ins: Vec<(avax::TransferableInput, Vec<soft_key::Key>)> = Vec::new();
+        for utxo in utxos.iter() {
+            let needed = to_burn.get(&utxo.asset_id).copied().unwrap_or(0);
+            let spent = consumed.get(&utxo.asset_id).copied().unwrap_or(0);
+            if spent >= needed {
+                continue;
+            }
+
+            // only "secp256k1fx.TransferOutput" can be spent on the X-chain
+            let out = match &utxo.transfer_output {
+                Some(out) => out,
+                None => continue,
+            };
+            let (transfer_input, keys) = match self.keychain.spend(out, now) {
+                Some(v) => v,
+                None => continue,
+            };
+
+            add_amount(&mut consumed, utxo.asset_id, transfer_input.amount)?;
+            ins.push((
+                avax::TransferableInput {
+                    utxo_id: utxo.utxo_id.clone(),
+                    asset_id: utxo.asset_id,
+                    transfer_input: Some(transfer_input),
+                    ..avax::TransferableInput::default()
+                },
+                keys,
+            ));
+        }
+
+        let mut outs = outputs;
+        for (asset_id, needed) in to_burn.iter() {
+            let spent = consumed.get(asset_id).copied().unwrap_or(0);
+            if spent < *needed {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "insufficient funds for asset {} (needed {}, available {})",
+                        asset_id, needed, spent
+                    ),
+                ));
+            }
+            if spent > *needed {
+                outs.push(avax::TransferableOutput {
+                    asset_id: *asset_id,
+                    transfer_output: Some(secp256k1fx::TransferOutput::new(
+                        spent - needed,
+                        secp256k1fx::OutputOwners::new(0, 1, &[*change_to]),
+                    )),
+                    ..avax::TransferableOutput::default()
+                });
+            }
+        }
+        avax::sort_transferable_outputs(&mut outs)?;
+
+        ins.sort_by(|a, b| a.0.cmp(&b.0));
+        let (ins, signers): (Vec<avax::TransferableInput>, Vec<Vec<soft_key::Key>>) =
+            ins.into_iter().unzip();
+
+        let tx = Tx {
+            base_tx: avax::BaseTx {
+                network_id: self.network_id,
+                blockchain_id: self.blockchain_id,
+                transferable_outputs: Some(outs),
+                transferable_inputs: Some(ins),
+                memo: None,
+            },
+            creds: Vec::new(),
+        };
+        Ok((tx, signers))
+    }
+}
+
+fn add_amount(m: &mut HashMap<ids::Id, u64>, asset_id: ids::Id, amount: u64) -> io::Result<()> {
+    let cur = m.entry(asset_id).or_insert(0);
+    *cur = cur
+        .checked_add(amount)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "amount overflows u64"))?;
+    Ok(())
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- avm::txs::base::test_new_base_tx --exact --show-output
+#[test]
+fn test_new_base_tx() {
+    let key = soft_key::TEST_KEYS[0].clone();
+    let avax_asset_id = ids::Id::from_slice(&[0xbb; 32]);
+    let owner = secp256k1fx::OutputOwners::new(0, 1, &[key.short_address]);
+
+    let mut utxos: Vec<utxo::Utxo> = Vec::new();
+    for (i, amount) in [5_000_000_000_u64, 10_000_000_000, 20_000_000_000]
+        .iter()
+        .enumerate()
+    {
+        utxos.push(utxo::Utxo {
+            utxo_id: avax::UtxoId {
+                tx_id: ids::Id::from_slice(&[(3 - i) as u8; 32]),
+                output_index: 0,
+                ..avax::UtxoId::default()
+            },
+            asset_id: avax_asset_id,
+            transfer_output: Some(secp256k1fx::TransferOutput::new(*amount, owner.clone())),
+            ..utxo::Utxo::default()
+        });
+    }
+
+    let builder = Builder {
+        network_id: 12345,
+        blockchain_id: ids::Id::from_slice(&[0xcc; 32]),
+        avax_asset_id,
+        tx_fee: units::Avax::from_navax(1_000_000),
+        keychain: soft_key::Keychain::new(vec![key.clone()]),
+    };
+    let to = ids::ShortId::from_slice(&[0x22; 20]);
+    let transfer = avax::TransferableOutput {
+        asset_id: avax_asset_id,
+        transfer_output: Some(secp256k1fx::TransferOutput::new(
+            12_000_000_000,
+            secp256k1fx::OutputOwners::new(0, 1, &[to]),
+        )),
+        ..avax::TransferableOutput::default()
+    };
+
+    // only the first two UTXOs are needed for 12 AVAX + fee
+    let (mut tx, signers) = builder
+        .new_base_tx(&utxos, vec![transfer.clone()], &key.short_address, 0)
+        .unwrap();
+    let ins = tx.base_tx.transferable_inputs.clone().unwrap();
+    assert_eq!(ins.len(), 2);
+    assert_eq!(signers.len(), 2);
+    assert!(ins[0] < ins[1]);
+    assert_eq!(ins[0].utxo_id.tx_id, ids::Id::from_slice(&[0x02; 32]));
+
+    let outs = tx.base_tx.transferable_outputs.clone().unwrap();
+    let amounts: Vec<u64> = outs.iter().map(|o| o.amount()).collect();
+    assert_eq!(amounts, vec![2_999_000_000, 12_000_000_000]);
+
+    assert!(tx.id().is_err());
+    let (signed_bytes, tx_id) = tx.sign(&signers).unwrap();
+    assert_eq!(tx.creds.len(), 2);
+    assert_eq!(tx.id().unwrap(), tx_id);
+    assert_eq!(tx.signed_bytes().unwrap(), signed_bytes);
+    assert_eq!(
+        super::Tx::from_bytes(&signed_bytes).unwrap(),
+        (super::Tx::Base(tx.clone()), tx_id)
+    );
+    tx.verify(&utxos, &avax_asset_id, &builder.tx_fee, 0)
+        .unwrap();
+
+    // the fee is burned on top of the outputs
+    let err = tx
+        .verify(
+            &utxos,
+            &avax_asset_id,
+            &units::Avax::from_navax(1_000_001),
+            0,
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("insufficient AVAX"));
+
+    // unknown UTXO
+    assert_eq!(
+        tx.verify(&utxos[1..], &avax_asset_id, &builder.tx_fee, 0)
+            .unwrap_err()
+            .kind(),
+        ErrorKind::NotFound
+    );
+
+    // signed by the key that does not own the UTXO
+    let mut forged = tx.clone();
+    forged.creds = avax::sign_credentials(
+        &tx.unsigned_bytes().unwrap(),
+        &[
+            vec![soft_key::TEST_KEYS[1].clone()],
+            vec![soft_key::TEST_KEYS[1].clone()],
+        ],
+    )
+    .unwrap();
+    assert!(forged
+        .verify(&utxos, &avax_asset_id, &builder.tx_fee, 0)
+        .unwrap_err()
+        .to_string()
+        .contains("instead of owner"));
+
+    // credentials must be re-signed after any change
+    let mut changed = tx.clone();
+    changed.base_tx.memo = Some(b"memo".to_vec());
+    assert!(changed
+        .verify(&utxos, &avax_asset_id, &builder.tx_fee, 0)
+        .is_err());
+    assert_ne!(changed.id().unwrap(), tx_id);
+
+    let mut unsorted = tx.clone();
+    unsorted
+        .base_tx
+        .transferable_outputs
+        .as_mut()
+        .unwrap()
+        .reverse();
+    assert!(unsorted
+        .verify(&utxos, &avax_asset_id, &builder.tx_fee, 0)
+        .unwrap_err()
+        .to_string()
+        .contains("outputs not sorted"));
+    let encoded = encode_cb58(&signed_bytes);
+    assert_eq!(
+        formatting::decode_cb58_with_checksum(&encoded).unwrap(),
+        signed_bytes
+    );
+
+    let res = builder.new_base_tx(&utxos[..1], vec![transfer], &key.short_address, 0);
+    assert!(res.is_err());
+}
@@ -0,0 +1,153 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, packer, secp256k1fx};
+
+use super::fxs;
+
+/// Outputs of the fx in the create asset tx.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/avm/txs#InitialState
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct InitialState {
+    /// Index of the fx (e.g., 0 for secp256k1fx, 1 for nftfx, 2 for propertyfx).
+    pub fx_index: u32,
+    pub outputs: Vec<fxs::Output>,
+}
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/avm/txs#CreateAssetTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+    pub name: String,
+    pub symbol: String,
+    pub denomination: u8,
+    pub initial_states: Vec<InitialState>,
+
+    /// One per input.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            name: String::new(),
+            symbol: String::new(),
+            denomination: 0,
+            initial_states: Vec::new(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "avm.CreateAssetTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::X_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// Encodes the unsigned "avm.CreateAssetTx": the base tx, the asset
+    /// name, symbol and denomination, then the initial states per fx.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::X_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            self.base_tx.pack(packer)?;
+            packer.pack_str(&self.name);
+            packer.pack_str(&self.symbol);
+            packer.pack_byte(self.denomination);
+            packer.pack_u32(self.initial_states.len() as u32);
+            for state in self.initial_states.iter() {
+                packer.pack_u32(state.fx_index);
+                packer.pack_u32(state.outputs.len() as u32);
+                for out in state.outputs.iter() {
+                    out.pack(packer)?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let base_tx = avax::BaseTx::unpack(packer)?;
+        let name = packer.unpack_str()?;
+        let symbol = packer.unpack_str()?;
+        let denomination = packer.unpack_byte()?;
+
+        let n = packer.unpack_u32()?;
+        let mut initial_states: Vec<InitialState> = Vec::new();
+        for _ in 0..n {
+            let fx_index = packer.unpack_u32()?;
+            let m = packer.unpack_u32()?;
+            let mut outputs: Vec<fxs::Output> = Vec::new();
+            for _ in 0..m {
+                outputs.push(fxs::Output::unpack(packer)?);
+            }
+            initial_states.push(InitialState { fx_index, outputs });
+        }
+        Ok(Self {
+            base_tx,
+            name,
+            symbol,
+            denomination,
+            initial_states,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- avm::txs::create_asset::test_unsigned_bytes --exact --show-output
+#[test]
+fn test_unsigned_bytes() {
+    let base_tx = super::test_base_tx();
+    let owners = base_tx.transferable_outputs.as_ref().unwrap()[0]
+        .transfer_output
+        .clone()
+        .unwrap()
+        .output_owners;
+    let tx = Tx {
+        base_tx,
+        name: "Test".to_string(),
+        symbol: "TST".to_string(),
+        denomination: 9,
+        initial_states: vec![InitialState {
+            fx_index: 0,
+            outputs: vec![fxs::Output::SecpMint(owners)],
+        }],
+        creds: vec![secp256k1fx::Credential::new(vec![vec![0x44; 65]])],
+    };
+
+    let unsigned_bytes = tx.unsigned_bytes().unwrap();
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x01, // "avm.CreateAssetTx" type ID
+    ];
+    expected.extend(super::test_base_tx_bytes());
+    expected.extend_from_slice(&[
+        0x00, 0x04, b'T', b'e', b's', b't', // name
+        0x00, 0x03, b'T', b'S', b'T', // symbol
+        0x09, // denomination
+        0x00, 0x00, 0x00, 0x01, // number of initial states
+        0x00, 0x00, 0x00, 0x00, // fx index
+        0x00, 0x00, 0x00, 0x01, // number of outputs
+        0x00, 0x00, 0x00, 0x06, // "secp256k1fx.MintOutput" type ID
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // locktime
+        0x00, 0x00, 0x00, 0x01, // threshold
+        0x00, 0x00, 0x00, 0x01, // number of addresses
+    ]);
+    expected.extend_from_slice(&[0x22; 20]);
+    assert_eq!(unsigned_bytes, expected);
+
+    let (signed_bytes, tx_id) = avax::pack_signed_tx(&unsigned_bytes, &tx.creds).unwrap();
+    assert_eq!(
+        super::Tx::from_bytes(&signed_bytes).unwrap(),
+        (super::Tx::CreateAsset(tx), tx_id)
+    );
+}
@@ -0,0 +1,97 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer, secp256k1fx};
+
+/// Exports the outputs from the X-chain to the destination chain
+/// (e.g., the P-chain), which then imports them.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/avm/txs#ExportTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+    pub destination_chain_id: ids::Id,
+    pub exported_outputs: Vec<avax::TransferableOutput>,
+
+    /// One per input.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            destination_chain_id: ids::Id::empty(),
+            exported_outputs: Vec::new(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "avm.ExportTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::X_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// Encodes the unsigned "avm.ExportTx": the base tx, the destination
+    /// chain ID and the outputs exported to it.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::X_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            self.base_tx.pack(packer)?;
+            packer.pack_bytes(self.destination_chain_id.as_bytes());
+            packer.pack_u32(self.exported_outputs.len() as u32);
+            for out in self.exported_outputs.iter() {
+                out.pack(packer)?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        Ok(Self {
+            base_tx: avax::BaseTx::unpack(packer)?,
+            destination_chain_id: ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?),
+            exported_outputs: super::unpack_outputs(packer)?,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- avm::txs::export::test_unsigned_bytes --exact --show-output
+#[test]
+fn test_unsigned_bytes() {
+    let base_tx = super::test_base_tx();
+    let exported_outputs = base_tx.transferable_outputs.clone().unwrap();
+    let tx = Tx {
+        base_tx,
+        destination_chain_id: ids::Id::from_slice(&[0x33; 32]),
+        exported_outputs,
+        creds: vec![secp256k1fx::Credential::new(vec![vec![0x44; 65]])],
+    };
+
+    let unsigned_bytes = tx.unsigned_bytes().unwrap();
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x04, // "avm.ExportTx" type ID
+    ];
+    expected.extend(super::test_base_tx_bytes());
+    expected.extend_from_slice(&[0x33; 32]); // destination chain ID
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // number of exported outputs
+    expected.extend(super::test_transfer_output_bytes());
+    assert_eq!(unsigned_bytes, expected);
+
+    let (signed_bytes, tx_id) = avax::pack_signed_tx(&unsigned_bytes, &tx.creds).unwrap();
+    assert_eq!(
+        super::Tx::from_bytes(&signed_bytes).unwrap(),
+        (super::Tx::Export(tx), tx_id)
+    );
+}
@@ -0,0 +1,390 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{codec, packer, secp256k1fx};
+
+/// Initial state of the asset created by the create asset tx
+/// ("verify.State" interface), which can be minted or transferred later.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/secp256k1fx#MintOutput
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/nftfx#MintOutput
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/propertyfx#MintOutput
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Output {
+    SecpMint(secp256k1fx::OutputOwners),
+    SecpTransfer(secp256k1fx::TransferOutput),
+    NftMint {
+        group_id: u32,
+        owners: secp256k1fx::OutputOwners,
+    },
+    NftTransfer {
+        group_id: u32,
+        payload: Vec<u8>,
+        owners: secp256k1fx::OutputOwners,
+    },
+    PropertyMint(secp256k1fx::OutputOwners),
+    PropertyOwned(secp256k1fx::OutputOwners),
+}
+
+impl Output {
+    /// Packs the output with its type ID.
+    pub fn pack(&self, packer: &packer::Packer) -> io::Result<()> {
+        packer.pack_u32(type_id(self.type_name())?);
+        match self {
+            Output::SecpMint(owners)
+            | Output::PropertyMint(owners)
+            | Output::PropertyOwned(owners) => owners.pack(packer),
+            Output::SecpTransfer(out) => out.pack(packer),
+            Output::NftMint { group_id, owners } => {
+                packer.pack_u32(*group_id);
+                owners.pack(packer);
+            }
+            Output::NftTransfer {
+                group_id,
+                payload,
+                owners,
+            } => {
+                packer.pack_u32(*group_id);
+                packer.pack_bytes_with_header(payload);
+                owners.pack(packer);
+            }
+        }
+        Ok(())
+    }
+
+    /// Unpacks the output including its type ID.
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let type_id = packer.unpack_u32()?;
+        let out = match type_name(type_id) {
+            "secp256k1fx.MintOutput" => {
+                Output::SecpMint(secp256k1fx::OutputOwners::unpack(packer)?)
+            }
+            "secp256k1fx.TransferOutput" => {
+                Output::SecpTransfer(secp256k1fx::TransferOutput::unpack(packer)?)
+            }
+            "nftfx.MintOutput" => Output::NftMint {
+                group_id: packer.unpack_u32()?,
+                owners: secp256k1fx::OutputOwners::unpack(packer)?,
+            },
+            "nftfx.TransferOutput" => Output::NftTransfer {
+                group_id: packer.unpack_u32()?,
+                payload: packer.unpack_bytes_with_header()?,
+                owners: secp256k1fx::OutputOwners::unpack(packer)?,
+            },
+            "propertyfx.MintOutput" => {
+                Output::PropertyMint(secp256k1fx::OutputOwners::unpack(packer)?)
+            }
+            "propertyfx.OwnedOutput" => {
+                Output::PropertyOwned(secp256k1fx::OutputOwners::unpack(packer)?)
+            }
+            name => return Err(unexpected(type_id, name, "output")),
+        };
+        Ok(out)
+    }
+
+    pub fn type_name(&self) -> &str {
+        match self {
+            Output::SecpMint(_) => "secp256k1fx.MintOutput",
+            Output::SecpTransfer(_) => "secp256k1fx.TransferOutput",
+            Output::NftMint { .. } => "nftfx.MintOutput",
+            Output::NftTransfer { .. } => "nftfx.TransferOutput",
+            Output::PropertyMint(_) => "propertyfx.MintOutput",
+            Output::PropertyOwned(_) => "propertyfx.OwnedOutput",
+        }
+    }
+}
+
+/// Operation of the operation tx ("fxs.FxOperation" interface)
+/// on the UTXOs of the asset (e.g., minting more of the asset).
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/secp256k1fx#MintOperation
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/nftfx#MintOperation
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/propertyfx#MintOperation
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    SecpMint {
+        mint_input: secp256k1fx::Input,
+        mint_output: secp256k1fx::OutputOwners,
+        transfer_output: secp256k1fx::TransferOutput,
+    },
+    NftMint {
+        mint_input: secp256k1fx::Input,
+        group_id: u32,
+        payload: Vec<u8>,
+        outputs: Vec<secp256k1fx::OutputOwners>,
+    },
+    NftTransfer {
+        input: secp256k1fx::Input,
+        /// Must be "Output::NftTransfer".
+        output: Output,
+    },
+    PropertyMint {
+        mint_input: secp256k1fx::Input,
+        mint_output: secp256k1fx::OutputOwners,
+        owned_output: secp256k1fx::OutputOwners,
+    },
+    PropertyBurn {
+        input: secp256k1fx::Input,
+    },
+}
+
+impl Operation {
+    /// Packs the operation with its type ID.
+    pub fn pack(&self, packer: &packer::Packer) -> io::Result<()> {
+        packer.pack_u32(type_id(self.type_name())?);
+        match self {
+            Operation::SecpMint {
+                mint_input,
+                mint_output,
+                transfer_output,
+            } => {
+                pack_input(packer, mint_input);
+                mint_output.pack(packer);
+                transfer_output.pack(packer);
+            }
+            Operation::NftMint {
+                mint_input,
+                group_id,
+                payload,
+                outputs,
+            } => {
+                pack_input(packer, mint_input);
+                packer.pack_u32(*group_id);
+                packer.pack_bytes_with_header(payload);
+                packer.pack_u32(outputs.len() as u32);
+                for owners in outputs.iter() {
+                    owners.pack(packer);
+                }
+            }
+            Operation::NftTransfer { input, output } => {
+                pack_input(packer, input);
+                match output {
+                    Output::NftTransfer {
+                        group_id,
+                        payload,
+                        owners,
+                    } => {
+                        // embedded as the struct, so no type ID
+                        packer.pack_u32(*group_id);
+                        packer.pack_bytes_with_header(payload);
+                        owners.pack(packer);
+                    }
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("nft transfer operation with {}", output.type_name()),
+                        ))
+                    }
+                }
+            }
+            Operation::PropertyMint {
+                mint_input,
+                mint_output,
+                owned_output,
+            } => {
+                pack_input(packer, mint_input);
+                mint_output.pack(packer);
+                owned_output.pack(packer);
+            }
+            Operation::PropertyBurn { input } => pack_input(packer, input),
+        }
+        Ok(())
+    }
+
+    /// Unpacks the operation including its type ID.
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let type_id = packer.unpack_u32()?;
+        let op = match type_name(type_id) {
+            "secp256k1fx.MintOperation" => Operation::SecpMint {
+                mint_input: unpack_input(packer)?,
+                mint_output: secp256k1fx::OutputOwners::unpack(packer)?,
+                transfer_output: secp256k1fx::TransferOutput::unpack(packer)?,
+            },
+            "nftfx.MintOperation" => {
+                let mint_input = unpack_input(packer)?;
+                let group_id = packer.unpack_u32()?;
+                let payload = packer.unpack_bytes_with_header()?;
+                let n = packer.unpack_u32()?;
+                let mut outputs: Vec<secp256k1fx::OutputOwners> = Vec::new();
+                for _ in 0..n {
+                    outputs.push(secp256k1fx::OutputOwners::unpack(packer)?);
+                }
+                Operation::NftMint {
+                    mint_input,
+                    group_id,
+                    payload,
+                    outputs,
+                }
+            }
+            "nftfx.TransferOperation" => Operation::NftTransfer {
+                input: unpack_input(packer)?,
+                output: Output::NftTransfer {
+                    group_id: packer.unpack_u32()?,
+                    payload: packer.unpack_bytes_with_header()?,
+                    owners: secp256k1fx::OutputOwners::unpack(packer)?,
+                },
+            },
+            "propertyfx.MintOperation" => Operation::PropertyMint {
+                mint_input: unpack_input(packer)?,
+                mint_output: secp256k1fx::OutputOwners::unpack(packer)?,
+                owned_output: secp256k1fx::OutputOwners::unpack(packer)?,
+            },
+            "propertyfx.BurnOperation" => Operation::PropertyBurn {
+                input: unpack_input(packer)?,
+            },
+            name => return Err(unexpected(type_id, name, "operation")),
+        };
+        Ok(op)
+    }
+
+    pub fn type_name(&self) -> &str {
+        match self {
+            Operation::SecpMint { .. } => "secp256k1fx.MintOperation",
+            Operation::NftMint { .. } => "nftfx.MintOperation",
+            Operation::NftTransfer { .. } => "nftfx.TransferOperation",
+            Operation::PropertyMint { .. } => "propertyfx.MintOperation",
+            Operation::PropertyBurn { .. } => "propertyfx.BurnOperation",
+        }
+    }
+}
+
+/// Unpacks the credential of any fx, which share the same layout
+/// (the signatures), so the nftfx and propertyfx credentials are
+/// decoded as "secp256k1fx.Credential".
+pub fn unpack_credential(packer: &packer::Packer) -> io::Result<secp256k1fx::Credential> {
+    let type_id = packer.unpack_u32()?;
+    match type_name(type_id) {
+        "secp256k1fx.Credential" | "nftfx.Credential" | "propertyfx.Credential" => {}
+        name => return Err(unexpected(type_id, name, "credential")),
+    }
+
+    let n = packer.unpack_u32()?;
+    let mut signatures: Vec<Vec<u8>> = Vec::new();
+    for _ in 0..n {
+        signatures.push(packer.unpack_bytes(secp256k1fx::SIG_LEN)?);
+    }
+    Ok(secp256k1fx::Credential::new(signatures))
+}
+
+/// "secp256k1fx.Input" embedded as the struct in the operations, so no type ID.
+fn pack_input(packer: &packer::Packer, input: &secp256k1fx::Input) {
+    packer.pack_u32(input.sig_indices.len() as u32);
+    for idx in input.sig_indices.iter() {
+        packer.pack_u32(*idx);
+    }
+}
+
+fn unpack_input(packer: &packer::Packer) -> io::Result<secp256k1fx::Input> {
+    let n = packer.unpack_u32()?;
+    let mut sig_indices: Vec<u32> = Vec::new();
+    for _ in 0..n {
+        sig_indices.push(packer.unpack_u32()?);
+    }
+    Ok(secp256k1fx::Input::new(sig_indices))
+}
+
+fn type_id(name: &str) -> io::Result<u32> {
+    codec::X_CODEC.type_id(codec::VERSION, name)
+}
+
+fn type_name(type_id: u32) -> &'static str {
+    codec::X_CODEC
+        .type_name(codec::VERSION, type_id)
+        .unwrap_or("unknown")
+}
+
+fn unexpected(type_id: u32, name: &str, kind: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("type ID {} ({}) is not an X-chain {}", type_id, name, kind),
+    )
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- avm::txs::fxs::test_pack_unpack --exact --show-output
+#[test]
+fn test_pack_unpack() {
+    use crate::ids;
+
+    let owners = secp256k1fx::OutputOwners::new(0, 1, &[ids::ShortId::from_slice(&[0x11; 20])]);
+    let outs = vec![
+        Output::SecpMint(owners.clone()),
+        Output::SecpTransfer(secp256k1fx::TransferOutput::new(1_000, owners.clone())),
+        Output::NftMint {
+            group_id: 1,
+            owners: owners.clone(),
+        },
+        Output::NftTransfer {
+            group_id: 1,
+            payload: b"hello".to_vec(),
+            owners: owners.clone(),
+        },
+        Output::PropertyMint(owners.clone()),
+        Output::PropertyOwned(owners.clone()),
+    ];
+    let ops = vec![
+        Operation::SecpMint {
+            mint_input: secp256k1fx::Input::new(vec![0]),
+            mint_output: owners.clone(),
+            transfer_output: secp256k1fx::TransferOutput::new(1_000, owners.clone()),
+        },
+        Operation::NftMint {
+            mint_input: secp256k1fx::Input::new(vec![0]),
+            group_id: 1,
+            payload: b"hello".to_vec(),
+            outputs: vec![owners.clone(), owners.clone()],
+        },
+        Operation::NftTransfer {
+            input: secp256k1fx::Input::new(vec![0]),
+            output: outs[3].clone(),
+        },
+        Operation::PropertyMint {
+            mint_input: secp256k1fx::Input::new(vec![0]),
+            mint_output: owners.clone(),
+            owned_output: owners,
+        },
+        Operation::PropertyBurn {
+            input: secp256k1fx::Input::new(vec![0, 1]),
+        },
+    ];
+
+    for out in outs.iter() {
+        let b = codec::X_CODEC
+            .marshal(codec::VERSION, |packer| out.pack(packer))
+            .unwrap();
+        let decoded = codec::X_CODEC
+            .unmarshal(&b, |_, packer| Output::unpack(packer))
+            .unwrap();
+        assert_eq!(&decoded, out);
+    }
+    for op in ops.iter() {
+        let b = codec::X_CODEC
+            .marshal(codec::VERSION, |packer| op.pack(packer))
+            .unwrap();
+        let decoded = codec::X_CODEC
+            .unmarshal(&b, |_, packer| Operation::unpack(packer))
+            .unwrap();
+        assert_eq!(&decoded, op);
+    }
+
+    // "propertyfx.BurnOperation" type ID followed by the signature indices
+    let b = codec::X_CODEC
+        .marshal(codec::VERSION, |packer| ops[4].pack(packer))
+        .unwrap();
+    assert_eq!(
+        b,
+        vec![
+            0x00, 0x00, // codec version
+            0x00, 0x00, 0x00, 0x12, // "propertyfx.BurnOperation" type ID
+            0x00, 0x00, 0x00, 0x02, // number of signature indices
+            0x00, 0x00, 0x00, 0x00, //
+            0x00, 0x00, 0x00, 0x01, //
+        ]
+    );
+
+    // the operation is not an output
+    let err = codec::X_CODEC
+        .unmarshal(&b, |_, packer| Output::unpack(packer))
+        .unwrap_err();
+    assert!(err.to_string().contains("is not an X-chain output"));
+}
@@ -0,0 +1,97 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer, secp256k1fx};
+
+/// Imports the UTXOs exported from the source chain (e.g., the P-chain)
+/// to the X-chain.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/avm/txs#ImportTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+    pub source_chain_id: ids::Id,
+    pub imported_inputs: Vec<avax::TransferableInput>,
+
+    /// One per input of the base tx and then per imported input.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            source_chain_id: ids::Id::empty(),
+            imported_inputs: Vec::new(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "avm.ImportTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::X_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// Encodes the unsigned "avm.ImportTx": the base tx, the source chain
+    /// ID and the inputs consumed from its shared memory.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::X_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            self.base_tx.pack(packer)?;
+            packer.pack_bytes(self.source_chain_id.as_bytes());
+            packer.pack_u32(self.imported_inputs.len() as u32);
+            for input in self.imported_inputs.iter() {
+                input.pack(packer)?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        Ok(Self {
+            base_tx: avax::BaseTx::unpack(packer)?,
+            source_chain_id: ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?),
+            imported_inputs: super::unpack_inputs(packer)?,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- avm::txs::import::test_unsigned_bytes --exact --show-output
+#[test]
+fn test_unsigned_bytes() {
+    let base_tx = super::test_base_tx();
+    let imported_inputs = base_tx.transferable_inputs.clone().unwrap();
+    let tx = Tx {
+        base_tx,
+        source_chain_id: ids::Id::from_slice(&[0x33; 32]),
+        imported_inputs,
+        creds: vec![secp256k1fx::Credential::new(vec![vec![0x44; 65]])],
+    };
+
+    let unsigned_bytes = tx.unsigned_bytes().unwrap();
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x03, // "avm.ImportTx" type ID
+    ];
+    expected.extend(super::test_base_tx_bytes());
+    expected.extend_from_slice(&[0x33; 32]); // source chain ID
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // number of imported inputs
+    expected.extend(super::test_transfer_input_bytes());
+    assert_eq!(unsigned_bytes, expected);
+
+    let (signed_bytes, tx_id) = avax::pack_signed_tx(&unsigned_bytes, &tx.creds).unwrap();
+    assert_eq!(
+        super::Tx::from_bytes(&signed_bytes).unwrap(),
+        (super::Tx::Import(tx), tx_id)
+    );
+}
@@ -0,0 +1,198 @@
+pub mod base;
+pub mod create_asset;
+pub mod export;
+pub mod fxs;
+pub mod import;
+pub mod operation;
+
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, formatting, ids, packer, secp256k1fx};
+use utils::hash;
+
+/// X-chain tx of any type, decoded with its credentials
+/// (e.g., embedded in the vertex or the block).
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/avm/txs#Tx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Tx {
+    Base(base::Tx),
+    CreateAsset(create_asset::Tx),
+    Operation(operation::Tx),
+    Import(import::Tx),
+    Export(export::Tx),
+}
+
+impl Tx {
+    /// Unpacks the type ID, the fields and the credentials,
+    /// without the codec version (i.e., "*txs.Tx" embedded in the block).
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let type_id = packer.unpack_u32()?;
+        let name = codec::X_CODEC
+            .type_name(codec::VERSION, type_id)
+            .unwrap_or("unknown");
+        let tx = match name {
+            "avm.BaseTx" => Tx::Base(base::Tx::unpack(packer)?),
+            "avm.CreateAssetTx" => Tx::CreateAsset(create_asset::Tx::unpack(packer)?),
+            "avm.OperationTx" => Tx::Operation(operation::Tx::unpack(packer)?),
+            "avm.ImportTx" => Tx::Import(import::Tx::unpack(packer)?),
+            "avm.ExportTx" => Tx::Export(export::Tx::unpack(packer)?),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("type ID {} ({}) is not an X-chain tx", type_id, name),
+                ))
+            }
+        };
+        Ok(tx)
+    }
+
+    /// Decodes the signed tx bytes (e.g., in the vertex or "/ext/index/X/tx"),
+    /// returning the tx with its ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        let tx = codec::X_CODEC.unmarshal(b, |_, packer| Self::unpack(packer))?;
+        Ok((tx, ids::Id::from_slice(&hash::compute_sha256(b))))
+    }
+
+    /// Returns the codec type name of the tx (e.g., "avm.BaseTx").
+    pub fn type_name(&self) -> String {
+        match self {
+            Tx::Base(_) => base::Tx::type_name(),
+            Tx::CreateAsset(_) => create_asset::Tx::type_name(),
+            Tx::Operation(_) => operation::Tx::type_name(),
+            Tx::Import(_) => import::Tx::type_name(),
+            Tx::Export(_) => export::Tx::type_name(),
+        }
+    }
+}
+
+/// Decodes the untrusted "0x"-prefixed hex or CB58 signed tx with the checksum
+/// (e.g., returned by "avm.getTx"), with the size limits of "formatting"
+/// and the codec, returning the tx with its ID.
+pub fn decode_tx_untrusted(encoded: &str) -> io::Result<(Tx, ids::Id)> {
+    Tx::from_bytes(&formatting::decode_untrusted(encoded)?)
+}
+
+/// Unpacks the outputs prefixed with their number (e.g., the exported outputs).
+fn unpack_outputs(packer: &packer::Packer) -> io::Result<Vec<avax::TransferableOutput>> {
+    let n = packer.unpack_u32()?;
+    let mut outs: Vec<avax::TransferableOutput> = Vec::new();
+    for _ in 0..n {
+        outs.push(avax::TransferableOutput::unpack(packer)?);
+    }
+    Ok(outs)
+}
+
+/// Unpacks the inputs prefixed with their number (e.g., the imported inputs).
+fn unpack_inputs(packer: &packer::Packer) -> io::Result<Vec<avax::TransferableInput>> {
+    let n = packer.unpack_u32()?;
+    let mut ins: Vec<avax::TransferableInput> = Vec::new();
+    for _ in 0..n {
+        ins.push(avax::TransferableInput::unpack(packer)?);
+    }
+    Ok(ins)
+}
+
+/// Unpacks the credentials appended by "avax::pack_signed_tx".
+fn unpack_creds(packer: &packer::Packer) -> io::Result<Vec<secp256k1fx::Credential>> {
+    let n = packer.unpack_u32()?;
+    let mut creds: Vec<secp256k1fx::Credential> = Vec::new();
+    for _ in 0..n {
+        creds.push(fxs::unpack_credential(packer)?);
+    }
+    Ok(creds)
+}
+
+#[cfg(test)]
+pub(crate) fn test_base_tx() -> avax::BaseTx {
+    let owner = secp256k1fx::OutputOwners::new(0, 1, &[ids::ShortId::from_slice(&[0x22; 20])]);
+    avax::BaseTx {
+        network_id: 12345,
+        blockchain_id: ids::Id::from_slice(&[0xcc; 32]),
+        transferable_outputs: Some(vec![avax::TransferableOutput {
+            asset_id: ids::Id::from_slice(&[0xbb; 32]),
+            transfer_output: Some(secp256k1fx::TransferOutput::new(1_000, owner)),
+            ..avax::TransferableOutput::default()
+        }]),
+        transferable_inputs: Some(vec![avax::TransferableInput {
+            utxo_id: avax::UtxoId::new(&[0x01; 32], 0, false),
+            asset_id: ids::Id::from_slice(&[0xbb; 32]),
+            transfer_input: Some(secp256k1fx::TransferInput::new(2_000, vec![0])),
+            ..avax::TransferableInput::default()
+        }]),
+        memo: Some(b"memo".to_vec()),
+    }
+}
+
+/// "test_base_tx", written out field by field per "avax.BaseTx" of avalanchego,
+/// so the expected bytes of the tx tests do not come from the packer under test.
+#[cfg(test)]
+pub(crate) fn test_base_tx_bytes() -> Vec<u8> {
+    let mut b: Vec<u8> = vec![0x00, 0x00, 0x30, 0x39]; // network ID
+    b.extend_from_slice(&[0xcc; 32]); // blockchain ID
+    b.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // number of outputs
+    b.extend(test_transfer_output_bytes());
+    b.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // number of inputs
+    b.extend(test_transfer_input_bytes());
+    b.extend_from_slice(&[0x00, 0x00, 0x00, 0x04]); // memo length
+    b.extend_from_slice(b"memo");
+    b
+}
+
+/// Output of "test_base_tx".
+#[cfg(test)]
+pub(crate) fn test_transfer_output_bytes() -> Vec<u8> {
+    let mut b: Vec<u8> = vec![0xbb; 32]; // asset ID
+    b.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x07, // "secp256k1fx.TransferOutput" type ID
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xe8, // amount
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // locktime
+        0x00, 0x00, 0x00, 0x01, // threshold
+        0x00, 0x00, 0x00, 0x01, // number of addresses
+    ]);
+    b.extend_from_slice(&[0x22; 20]);
+    b
+}
+
+/// Input of "test_base_tx".
+#[cfg(test)]
+pub(crate) fn test_transfer_input_bytes() -> Vec<u8> {
+    let mut b: Vec<u8> = vec![0x01; 32]; // UTXO tx ID
+    b.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]); // UTXO output index
+    b.extend_from_slice(&[0xbb; 32]); // asset ID
+    b.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x05, // "secp256k1fx.TransferInput" type ID
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07, 0xd0, // amount
+        0x00, 0x00, 0x00, 0x01, // number of signature indices
+        0x00, 0x00, 0x00, 0x00, // signature index
+    ]);
+    b
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- avm::txs::test_tx_from_bytes --exact --show-output
+#[test]
+fn test_tx_from_bytes() {
+    // the nftfx credential has the same layout as the secp256k1fx credential
+    let tx = base::Tx {
+        base_tx: test_base_tx(),
+        creds: vec![secp256k1fx::Credential::new(vec![vec![0x44; 65]])],
+    };
+    let (mut signed_bytes, tx_id) =
+        avax::pack_signed_tx(&tx.unsigned_bytes().unwrap(), &tx.creds).unwrap();
+    let cred_type_id = signed_bytes.len() - 65 - 4 - 4;
+    assert_eq!(signed_bytes[cred_type_id + 3], 0x09); // "secp256k1fx.Credential" type ID
+    signed_bytes[cred_type_id + 3] = 0x0e; // "nftfx.Credential" type ID
+    let (decoded, nft_tx_id) = Tx::from_bytes(&signed_bytes).unwrap();
+    assert_eq!(decoded, Tx::Base(tx));
+    assert_eq!(decoded.type_name(), "avm.BaseTx");
+    assert_ne!(nft_tx_id, tx_id);
+
+    // an output is not a credential
+    signed_bytes[cred_type_id + 3] = 0x07;
+    assert!(Tx::from_bytes(&signed_bytes).is_err());
+
+    let err = Tx::from_bytes(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x05]).unwrap_err();
+    assert!(err.to_string().contains("is not an X-chain tx"));
+}
@@ -0,0 +1,140 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer, secp256k1fx};
+
+use super::fxs;
+
+/// Operation on the UTXOs of the asset.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/avm/txs#Operation
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Operation {
+    pub asset_id: ids::Id,
+    pub utxo_ids: Vec<avax::UtxoId>,
+    pub fx_operation: fxs::Operation,
+}
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/avm/txs#OperationTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+    pub operations: Vec<Operation>,
+
+    /// One per input and then per operation.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            operations: Vec::new(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "avm.OperationTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::X_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// Encodes the unsigned "avm.OperationTx": the base tx, then each
+    /// operation's asset ID, consumed UTXO IDs and fx operation.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::X_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            self.base_tx.pack(packer)?;
+            packer.pack_u32(self.operations.len() as u32);
+            for op in self.operations.iter() {
+                packer.pack_bytes(op.asset_id.as_bytes());
+                packer.pack_u32(op.utxo_ids.len() as u32);
+                for utxo_id in op.utxo_ids.iter() {
+                    packer.pack_bytes(utxo_id.tx_id.as_bytes());
+                    packer.pack_u32(utxo_id.output_index);
+                }
+                op.fx_operation.pack(packer)?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let base_tx = avax::BaseTx::unpack(packer)?;
+
+        let n = packer.unpack_u32()?;
+        let mut operations: Vec<Operation> = Vec::new();
+        for _ in 0..n {
+            let asset_id = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+            let m = packer.unpack_u32()?;
+            let mut utxo_ids: Vec<avax::UtxoId> = Vec::new();
+            for _ in 0..m {
+                let tx_id = packer.unpack_bytes(ids::ID_LEN)?;
+                let output_index = packer.unpack_u32()?;
+                utxo_ids.push(avax::UtxoId::new(&tx_id, output_index, false));
+            }
+            operations.push(Operation {
+                asset_id,
+                utxo_ids,
+                fx_operation: fxs::Operation::unpack(packer)?,
+            });
+        }
+        Ok(Self {
+            base_tx,
+            operations,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- avm::txs::operation::test_unsigned_bytes --exact --show-output
+#[test]
+fn test_unsigned_bytes() {
+    let tx = Tx {
+        base_tx: super::test_base_tx(),
+        operations: vec![Operation {
+            asset_id: ids::Id::from_slice(&[0x66; 32]),
+            utxo_ids: vec![avax::UtxoId::new(&[0x77; 32], 1, false)],
+            fx_operation: fxs::Operation::PropertyBurn {
+                input: secp256k1fx::Input::new(vec![0]),
+            },
+        }],
+        creds: vec![
+            secp256k1fx::Credential::new(vec![vec![0x44; 65]]),
+            secp256k1fx::Credential::new(vec![vec![0x55; 65]]),
+        ],
+    };
+
+    let unsigned_bytes = tx.unsigned_bytes().unwrap();
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x02, // "avm.OperationTx" type ID
+    ];
+    expected.extend(super::test_base_tx_bytes());
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // number of operations
+    expected.extend_from_slice(&[0x66; 32]); // asset ID
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // number of UTXO IDs
+    expected.extend_from_slice(&[0x77; 32]);
+    expected.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x01, // output index
+        0x00, 0x00, 0x00, 0x12, // "propertyfx.BurnOperation" type ID
+        0x00, 0x00, 0x00, 0x01, // number of signature indices
+        0x00, 0x00, 0x00, 0x00, // signature index
+    ]);
+    assert_eq!(unsigned_bytes, expected);
+
+    let (signed_bytes, tx_id) = avax::pack_signed_tx(&unsigned_bytes, &tx.creds).unwrap();
+    assert_eq!(
+        super::Tx::from_bytes(&signed_bytes).unwrap(),
+        (super::Tx::Operation(tx), tx_id)
+    );
+}
@@ -0,0 +1,188 @@
+use std::collections::{btree_map, BTreeMap, BTreeSet};
+
+/// Multiset that counts how many times each element was added
+/// (e.g., the votes for each block ID weighted by the stake of the voter),
+/// for the consensus simulations.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/bag#Bag
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Bag<T: Ord + Copy> {
+    counts: BTreeMap<T, u64>,
+    size: u64,
+    threshold: u64,
+    met_threshold: BTreeSet<T>,
+}
+
+impl<T: Ord + Copy> Default for Bag<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Copy> Bag<T> {
+    pub fn new() -> Self {
+        Self {
+            counts: BTreeMap::new(),
+            size: 0,
+            threshold: 0,
+            met_threshold: BTreeSet::new(),
+        }
+    }
+
+    /// Sets the count at which the element is returned by "met_threshold",
+    /// re-evaluating the elements already in the bag.
+    /// ref. "avalanchego/utils/bag.Bag.SetThreshold"
+    pub fn set_threshold(&mut self, threshold: u64) {
+        self.threshold = threshold;
+        self.met_threshold = self
+            .counts
+            .iter()
+            .filter(|(_, c)| **c >= threshold)
+            .map(|(elem, _)| *elem)
+            .collect();
+    }
+
+    pub fn add(&mut self, elem: T) {
+        self.add_count(elem, 1);
+    }
+
+    /// Adds the element "count" times (e.g., the stake weight of the vote).
+    pub fn add_count(&mut self, elem: T, count: u64) {
+        if count == 0 {
+            return;
+        }
+        let c = self.counts.entry(elem).or_insert(0);
+        *c += count;
+        self.size += count;
+        if *c >= self.threshold {
+            self.met_threshold.insert(elem);
+        }
+    }
+
+    /// Returns the number of times the element was added.
+    pub fn count(&self, elem: &T) -> u64 {
+        self.counts.get(elem).copied().unwrap_or(0)
+    }
+
+    /// Returns the total count of all elements.
+    pub fn len(&self) -> u64 {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the distinct elements in the sorted order.
+    pub fn list(&self) -> Vec<T> {
+        self.counts.keys().copied().collect()
+    }
+
+    /// Returns the element with the highest count,
+    /// preferring the smallest element on a tie so that it is deterministic.
+    /// ref. "avalanchego/utils/bag.Bag.Mode"
+    pub fn mode(&self) -> Option<(T, u64)> {
+        let mut mode: Option<(T, u64)> = None;
+        for (elem, c) in self.counts.iter() {
+            if mode.map_or(true, |(_, max)| *c > max) {
+                mode = Some((*elem, *c));
+            }
+        }
+        mode
+    }
+
+    /// Returns the elements whose counts reached the threshold.
+    pub fn met_threshold(&self) -> &BTreeSet<T> {
+        &self.met_threshold
+    }
+
+    /// Returns the bag with only the elements that satisfy the predicate,
+    /// keeping the threshold.
+    /// ref. "avalanchego/utils/bag.Bag.Filter"
+    pub fn filter<F: Fn(&T) -> bool>(&self, f: F) -> Self {
+        let mut b = Self::new();
+        b.set_threshold(self.threshold);
+        for (elem, c) in self.counts.iter() {
+            if f(elem) {
+                b.add_count(*elem, *c);
+            }
+        }
+        b
+    }
+
+    /// Iterates the distinct elements with their counts in the sorted order.
+    pub fn iter(&self) -> btree_map::Iter<'_, T, u64> {
+        self.counts.iter()
+    }
+}
+
+impl<T: Ord + Copy> FromIterator<T> for Bag<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut b = Self::new();
+        for elem in iter {
+            b.add(elem);
+        }
+        b
+    }
+}
+
+impl<T: Ord + Copy> Extend<T> for Bag<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.add(elem);
+        }
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- bag::test_bag --exact --show-output
+#[test]
+fn test_bag() {
+    use crate::ids;
+
+    let id1 = ids::Id::from_slice(&[0x01]);
+    let id2 = ids::Id::from_slice(&[0x02]);
+    let id3 = ids::Id::from_slice(&[0x03]);
+
+    let mut bag: Bag<ids::Id> = Bag::new();
+    assert!(bag.is_empty());
+    assert_eq!(bag.mode(), None);
+
+    bag.set_threshold(3);
+    bag.add(id1);
+    bag.add_count(id2, 2);
+    bag.add_count(id3, 0);
+    assert_eq!(bag.len(), 3);
+    assert_eq!(bag.count(&id1), 1);
+    assert_eq!(bag.count(&id3), 0);
+    assert_eq!(bag.list(), vec![id1, id2]);
+    assert_eq!(bag.mode(), Some((id2, 2)));
+    assert!(bag.met_threshold().is_empty());
+
+    bag.add(id2);
+    assert_eq!(
+        bag.met_threshold().iter().copied().collect::<Vec<_>>(),
+        vec![id2]
+    );
+    bag.set_threshold(1);
+    assert_eq!(bag.met_threshold().len(), 2);
+
+    // ties go to the smallest element
+    bag.add_count(id1, 2);
+    assert_eq!(bag.mode(), Some((id1, 3)));
+
+    let filtered = bag.filter(|id| *id != id1);
+    assert_eq!(filtered.len(), 3);
+    assert_eq!(filtered.list(), vec![id2]);
+
+    let node_id = |b: u8| {
+        let mut d = [0_u8; ids::NODE_ID_LEN];
+        d[ids::NODE_ID_LEN - 1] = b;
+        ids::NodeId::from_slice(&d)
+    };
+    let mut votes: Bag<ids::NodeId> = vec![node_id(0x01); 4].into_iter().collect();
+    votes.extend(vec![node_id(0x02)]);
+    assert_eq!(votes.len(), 5);
+    assert_eq!(
+        votes.iter().map(|(_, c)| *c).collect::<Vec<_>>(),
+        vec![4, 1]
+    );
+}
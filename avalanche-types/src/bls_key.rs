@@ -0,0 +1,383 @@
+use std::{
+    fmt, fs,
+    io::{self, Error, ErrorKind},
+};
+
+use blst::{
+    min_pk::{AggregatePublicKey, AggregateSignature, PublicKey, SecretKey, Signature},
+    BLST_ERROR,
+};
+use log::info;
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "full")]
+use crate::cert;
+use crate::{codec, formatting, packer};
+
+/// ref. "avalanchego/utils/crypto/bls.SecretKeyLen"
+pub const SECRET_KEY_LEN: usize = 32;
+/// Compressed G1 point.
+/// ref. "avalanchego/utils/crypto/bls.PublicKeyLen"
+pub const PUBLIC_KEY_LEN: usize = 48;
+/// Compressed G2 point.
+/// ref. "avalanchego/utils/crypto/bls.SignatureLen"
+pub const SIGNATURE_LEN: usize = 96;
+
+/// Domain separation tag of the message signatures.
+/// ref. "avalanchego/utils/crypto/bls.ciphersuiteSignature"
+const CIPHERSUITE_SIGNATURE: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+/// Domain separation tag of the proof of possession,
+/// so that the proof is never valid as a message signature.
+/// ref. "avalanchego/utils/crypto/bls.ciphersuiteProofOfPossession"
+const CIPHERSUITE_PROOF_OF_POSSESSION: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// BLS12-381 key of the node, which signs the warp messages and
+/// registers with the validator in "AddPermissionlessValidatorTx".
+/// Same as the "--staking-signer-key-file" of avalanchego.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/crypto/bls
+#[derive(Clone)]
+pub struct Key {
+    secret_key: SecretKey,
+    public_key: PublicKey,
+}
+
+impl fmt::Debug for Key {
+    /// Never prints the secret key.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Key")
+            .field("public_key", &hex::encode(self.public_key_bytes()))
+            .finish()
+    }
+}
+
+impl Key {
+    /// Generates a new BLS key from the random input key material.
+    /// ref. "avalanchego/utils/crypto/bls.NewSecretKey"
+    pub fn generate() -> io::Result<Self> {
+        info!("generating BLS key");
+
+        let mut ikm = [0u8; SECRET_KEY_LEN];
+        OsRng.fill_bytes(&mut ikm);
+        let secret_key = SecretKey::key_gen(&ikm, &[]).map_err(|e| to_io_error("key_gen", e))?;
+        Ok(Self::from_secret_key(secret_key))
+    }
+
+    /// Loads the key from the 32-byte big-endian secret key
+    /// (e.g., the contents of "staking/signer.key").
+    /// ref. "avalanchego/utils/crypto/bls.SecretKeyFromBytes"
+    pub fn from_bytes(b: &[u8]) -> io::Result<Self> {
+        if b.len() != SECRET_KEY_LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "invalid BLS secret key length {} (expected {})",
+                    b.len(),
+                    SECRET_KEY_LEN
+                ),
+            ));
+        }
+        let secret_key = SecretKey::from_bytes(b).map_err(|e| to_io_error("secret key", e))?;
+        Ok(Self::from_secret_key(secret_key))
+    }
+
+    /// Loads the key from the file of "--staking-signer-key-file".
+    pub fn from_file(key_path: &str) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(key_path)?)
+    }
+
+    /// Saves the secret key as the file of "--staking-signer-key-file",
+    /// only readable by the owner (0600 on unix).
+    #[cfg(feature = "full")]
+    pub fn save(&self, key_path: &str) -> io::Result<()> {
+        cert::write_atomic(key_path, &self.to_bytes(), 0o600)?;
+        info!("saved BLS key {}", key_path);
+        Ok(())
+    }
+
+    fn from_secret_key(secret_key: SecretKey) -> Self {
+        let public_key = secret_key.sk_to_pk();
+        Self {
+            secret_key,
+            public_key,
+        }
+    }
+
+    /// NEVER log or save the returned bytes unencrypted.
+    pub fn to_bytes(&self) -> [u8; SECRET_KEY_LEN] {
+        self.secret_key.to_bytes()
+    }
+
+    /// Returns the compressed public key.
+    pub fn public_key_bytes(&self) -> [u8; PUBLIC_KEY_LEN] {
+        self.public_key.compress()
+    }
+
+    /// Returns the compressed signature of the message.
+    /// ref. "avalanchego/utils/crypto/bls.Sign"
+    pub fn sign(&self, msg: &[u8]) -> [u8; SIGNATURE_LEN] {
+        self.secret_key
+            .sign(msg, CIPHERSUITE_SIGNATURE, &[])
+            .compress()
+    }
+
+    /// Returns the proof that this node holds the secret key of the public key,
+    /// which prevents the rogue public key attacks on the aggregate signatures.
+    /// ref. "avalanchego/vms/platformvm/signer.NewProofOfPossession"
+    pub fn proof_of_possession(&self) -> ProofOfPossession {
+        let public_key = self.public_key_bytes();
+        let sig = self
+            .secret_key
+            .sign(&public_key, CIPHERSUITE_PROOF_OF_POSSESSION, &[]);
+        ProofOfPossession {
+            public_key: public_key.to_vec(),
+            proof_of_possession: sig.compress().to_vec(),
+        }
+    }
+}
+
+/// Verifies the signature of the message against the compressed public key.
+/// ref. "avalanchego/utils/crypto/bls.Verify"
+pub fn verify(public_key: &[u8], msg: &[u8], sig: &[u8]) -> io::Result<()> {
+    let public_key = parse_public_key(public_key)?;
+    let sig = parse_signature(sig)?;
+    match sig.verify(true, msg, CIPHERSUITE_SIGNATURE, &[], &public_key, true) {
+        BLST_ERROR::BLST_SUCCESS => Ok(()),
+        e => Err(to_io_error("signature verification", e)),
+    }
+}
+
+/// Aggregates the compressed signatures of the same message
+/// (e.g., the warp message signed by the validators).
+/// ref. "avalanchego/utils/crypto/bls.AggregateSignatures"
+pub fn aggregate_signatures(sigs: &[&[u8]]) -> io::Result<[u8; SIGNATURE_LEN]> {
+    if sigs.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "no signature to aggregate",
+        ));
+    }
+    let mut parsed = Vec::with_capacity(sigs.len());
+    for sig in sigs.iter() {
+        parsed.push(parse_signature(sig)?);
+    }
+    let refs: Vec<&Signature> = parsed.iter().collect();
+    let agg = AggregateSignature::aggregate(&refs, false)
+        .map_err(|e| to_io_error("signature aggregation", e))?;
+    Ok(agg.to_signature().compress())
+}
+
+/// Aggregates the compressed public keys, whose proofs of possession
+/// must have been verified (e.g., the registered validators).
+/// ref. "avalanchego/utils/crypto/bls.AggregatePublicKeys"
+pub fn aggregate_public_keys(public_keys: &[&[u8]]) -> io::Result<[u8; PUBLIC_KEY_LEN]> {
+    if public_keys.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "no public key to aggregate",
+        ));
+    }
+    let mut parsed = Vec::with_capacity(public_keys.len());
+    for pk in public_keys.iter() {
+        parsed.push(parse_public_key(pk)?);
+    }
+    let refs: Vec<&PublicKey> = parsed.iter().collect();
+    let agg = AggregatePublicKey::aggregate(&refs, false)
+        .map_err(|e| to_io_error("public key aggregation", e))?;
+    Ok(agg.to_public_key().compress())
+}
+
+/// Public key of the validator with its proof of possession,
+/// which the permissionless validator tx registers.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/signer#ProofOfPossession
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofOfPossession {
+    /// Compressed public key, "PUBLIC_KEY_LEN" bytes.
+    #[serde(with = "formatting::serde_hex_format")]
+    pub public_key: Vec<u8>,
+    /// Compressed signature of the public key, "SIGNATURE_LEN" bytes.
+    #[serde(with = "formatting::serde_hex_format")]
+    pub proof_of_possession: Vec<u8>,
+}
+
+impl ProofOfPossession {
+    pub fn type_name() -> String {
+        "signer.ProofOfPossession".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// Type name of the signer for the subnet validators, with no BLS key.
+    pub fn empty_type_name() -> String {
+        "signer.Empty".to_string()
+    }
+
+    pub fn empty_type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::empty_type_name()).unwrap()) as u32
+    }
+
+    /// Verifies the proof is the signature of the public key by its own secret key.
+    /// ref. "avalanchego/vms/platformvm/signer.ProofOfPossession.Verify"
+    pub fn verify(&self) -> io::Result<()> {
+        let public_key = parse_public_key(&self.public_key)?;
+        let sig = parse_signature(&self.proof_of_possession)?;
+        match sig.verify(
+            true,
+            &self.public_key,
+            CIPHERSUITE_PROOF_OF_POSSESSION,
+            &[],
+            &public_key,
+            true,
+        ) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            // ref. "avalanchego/vms/platformvm/signer.errInvalidProofOfPossession"
+            e => Err(to_io_error("proof of possession", e)),
+        }
+    }
+
+    /// Packs the "signer.Signer" interface, so the type ID comes first.
+    pub fn pack(&self, packer: &packer::Packer) -> io::Result<()> {
+        if self.public_key.len() != PUBLIC_KEY_LEN
+            || self.proof_of_possession.len() != SIGNATURE_LEN
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "invalid proof of possession lengths {}/{} (expected {}/{})",
+                    self.public_key.len(),
+                    self.proof_of_possession.len(),
+                    PUBLIC_KEY_LEN,
+                    SIGNATURE_LEN
+                ),
+            ));
+        }
+        packer.pack_u32(Self::type_id());
+        packer.pack_bytes(&self.public_key);
+        packer.pack_bytes(&self.proof_of_possession);
+        Ok(())
+    }
+
+    /// Unpacks the "signer.Signer" interface, returning "None" for "signer.Empty".
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Option<Self>> {
+        let type_id = packer.unpack_u32()?;
+        if type_id == Self::empty_type_id() {
+            return Ok(None);
+        }
+        if type_id != Self::type_id() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unexpected signer type ID {}", type_id),
+            ));
+        }
+        Ok(Some(Self {
+            public_key: packer.unpack_bytes(PUBLIC_KEY_LEN)?,
+            proof_of_possession: packer.unpack_bytes(SIGNATURE_LEN)?,
+        }))
+    }
+}
+
+fn parse_public_key(b: &[u8]) -> io::Result<PublicKey> {
+    if b.len() != PUBLIC_KEY_LEN {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "invalid BLS public key length {} (expected {})",
+                b.len(),
+                PUBLIC_KEY_LEN
+            ),
+        ));
+    }
+    let public_key = PublicKey::uncompress(b).map_err(|e| to_io_error("public key", e))?;
+    public_key
+        .validate()
+        .map_err(|e| to_io_error("public key", e))?;
+    Ok(public_key)
+}
+
+fn parse_signature(b: &[u8]) -> io::Result<Signature> {
+    if b.len() != SIGNATURE_LEN {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "invalid BLS signature length {} (expected {})",
+                b.len(),
+                SIGNATURE_LEN
+            ),
+        ));
+    }
+    Signature::uncompress(b).map_err(|e| to_io_error("signature", e))
+}
+
+fn to_io_error(what: &str, e: BLST_ERROR) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("invalid BLS {} ({:?})", what, e),
+    )
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- bls_key::test_bls_key --exact --show-output
+#[test]
+fn test_bls_key() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let key = Key::generate().unwrap();
+    let loaded = Key::from_bytes(&key.to_bytes()).unwrap();
+    assert_eq!(loaded.public_key_bytes(), key.public_key_bytes());
+    assert!(Key::from_bytes(&[1u8; 31]).is_err());
+
+    #[cfg(feature = "full")]
+    {
+        let key_path = utils::random::tmp_path(10, Some(".key")).unwrap();
+        key.save(&key_path).unwrap();
+        assert_eq!(fs::read(&key_path).unwrap(), key.to_bytes());
+        assert_eq!(
+            Key::from_file(&key_path).unwrap().public_key_bytes(),
+            key.public_key_bytes()
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&key_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        fs::remove_file(&key_path).unwrap();
+    }
+    assert!(!format!("{:?}", key).contains(&hex::encode(key.to_bytes())));
+
+    // BLS signatures are deterministic
+    let msg = b"hello";
+    let sig = key.sign(msg);
+    assert_eq!(sig, loaded.sign(msg));
+    verify(&key.public_key_bytes(), msg, &sig).unwrap();
+    assert!(verify(&key.public_key_bytes(), b"world", &sig).is_err());
+    assert!(verify(&key.public_key_bytes(), msg, &sig[1..]).is_err());
+
+    let pop = key.proof_of_possession();
+    pop.verify().unwrap();
+    assert_eq!(pop.public_key, key.public_key_bytes().to_vec());
+    // the proof uses its own domain separation tag
+    assert!(verify(&pop.public_key, &pop.public_key, &pop.proof_of_possession).is_err());
+    let other = Key::generate().unwrap();
+    let forged = ProofOfPossession {
+        public_key: other.public_key_bytes().to_vec(),
+        proof_of_possession: pop.proof_of_possession.clone(),
+    };
+    assert!(forged.verify().is_err());
+
+    let s = serde_json::to_string(&pop).unwrap();
+    assert!(s.contains("\"publicKey\":\"0x"));
+    assert!(s.contains("\"proofOfPossession\":\"0x"));
+    let decoded: ProofOfPossession = serde_json::from_str(&s).unwrap();
+    assert_eq!(decoded, pop);
+
+    let sig2 = other.sign(msg);
+    let agg_sig = aggregate_signatures(&[&sig, &sig2]).unwrap();
+    let agg_pk =
+        aggregate_public_keys(&[&key.public_key_bytes(), &other.public_key_bytes()]).unwrap();
+    verify(&agg_pk, msg, &agg_sig).unwrap();
+    assert!(verify(&key.public_key_bytes(), msg, &agg_sig).is_err());
+    assert!(aggregate_signatures(&[]).is_err());
+}
@@ -1,9 +1,25 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Error, ErrorKind},
+};
 
 use lazy_static::lazy_static;
 
+use crate::packer;
+
+/// Codec version that this crate packs with.
+/// ref. "avalanchego/vms/avm.codecVersion"
 pub const VERSION: u16 = 0;
 
+/// ref. "avalanchego/codec.defaultMaxSize"
+pub const DEFAULT_MAX_SIZE: usize = 256 * 1024;
+
+/// ref. "avalanchego/vms/platformvm/warp/payload.MaxMessageSize"
+pub const WARP_PAYLOAD_MAX_SIZE: usize = 24 * 1024;
+
+/// ref. "avalanchego/snow/engine/avalanche/vertex.maxSize"
+pub const VERTEX_MAX_SIZE: usize = 1024 * 1024;
+
 lazy_static! {
     /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/codec#Registry
     /// ref. https://github.com/ava-labs/avalanchego/blob/v1.7.9/wallet/chain/x/codec.go#L31
@@ -32,6 +48,8 @@ lazy_static! {
         m.insert("propertyfx.MintOperation".to_string(), 17);
         m.insert("propertyfx.BurnOperation".to_string(), 18);
         m.insert("propertyfx.Credential".to_string(), 19);
+        // ref. https://github.com/ava-labs/avalanchego/blob/v1.10.0/vms/avm/blocks/parser.go
+        m.insert("blocks.StandardBlock".to_string(), 20);
         m
     };
 
@@ -63,6 +81,289 @@ lazy_static! {
         m.insert("platformvm.UnsignedRewardValidatorTx".to_string(), 20);
         m.insert("platformvm.StakeableLockIn".to_string(), 21);
         m.insert("platformvm.StakeableLockOut".to_string(), 22);
+        // ref. https://github.com/ava-labs/avalanchego/blob/v1.9.0/vms/platformvm/txs/codec.go
+        m.insert("txs.RemoveSubnetValidatorTx".to_string(), 23);
+        m.insert("txs.TransformSubnetTx".to_string(), 24);
+        m.insert("txs.AddPermissionlessValidatorTx".to_string(), 25);
+        m.insert("txs.AddPermissionlessDelegatorTx".to_string(), 26);
+        m.insert("signer.Empty".to_string(), 27);
+        m.insert("signer.ProofOfPossession".to_string(), 28);
+        // ref. https://github.com/ava-labs/avalanchego/blob/v1.9.0/vms/platformvm/blocks/codec.go
+        m.insert("blocks.BanffProposalBlock".to_string(), 29);
+        m.insert("blocks.BanffAbortBlock".to_string(), 30);
+        m.insert("blocks.BanffCommitBlock".to_string(), 31);
+        m.insert("blocks.BanffStandardBlock".to_string(), 32);
+        m
+    };
+
+    /// ref. https://github.com/ava-labs/coreth/blob/v0.8.7/plugin/evm/codec.go
+    /// ref. https://github.com/ava-labs/avalanchego/blob/v1.7.9/codec/reflectcodec/type_codec.go#L128-L131
+    ///     (used for encoding Go interface type into a "struct")
+    pub static ref C_TYPES: HashMap<String, usize> = {
+        let mut m = HashMap::new();
+        m.insert("evm.UnsignedImportTx".to_string(), 0);
+        m.insert("evm.UnsignedExportTx".to_string(), 1);
+        // skips 3 registrations
+        m.insert("secp256k1fx.TransferInput".to_string(), 5);
+        m.insert("secp256k1fx.MintOutput".to_string(), 6);
+        m.insert("secp256k1fx.TransferOutput".to_string(), 7);
+        m.insert("secp256k1fx.MintOperation".to_string(), 8);
+        m.insert("secp256k1fx.Credential".to_string(), 9);
+        m.insert("secp256k1fx.Input".to_string(), 10);
+        m.insert("secp256k1fx.OutputOwners".to_string(), 11);
         m
     };
+
+    /// ref. https://github.com/ava-labs/avalanchego/blob/v1.10.0/vms/platformvm/warp/codec.go
+    pub static ref WARP_TYPES: HashMap<String, usize> = {
+        let mut m = HashMap::new();
+        m.insert("warp.BitSetSignature".to_string(), 0);
+        m
+    };
+
+    /// ref. https://github.com/ava-labs/avalanchego/blob/v1.10.0/vms/platformvm/warp/payload/codec.go
+    pub static ref WARP_PAYLOAD_TYPES: HashMap<String, usize> = {
+        let mut m = HashMap::new();
+        m.insert("payload.Hash".to_string(), 0);
+        m.insert("payload.AddressedCall".to_string(), 1);
+        m
+    };
+
+    /// The vertex has no interface field, so no type is registered.
+    pub static ref VERTEX_TYPES: HashMap<String, usize> = HashMap::new();
+}
+
+lazy_static! {
+    pub static ref X_CODEC: Manager = Manager::new("avm", DEFAULT_MAX_SIZE)
+        .register(VERSION, &X_TYPES)
+        .expect("failed to register X-chain codec");
+
+    /// UTXOs of both the X-chain and the P-chain are unpacked with this,
+    /// since "StakeableLockOut" is only registered in the P-chain codec.
+    pub static ref P_CODEC: Manager = Manager::new("platformvm", DEFAULT_MAX_SIZE)
+        .register(VERSION, &P_TYPES)
+        .expect("failed to register P-chain codec");
+
+    pub static ref C_CODEC: Manager = Manager::new("evm", DEFAULT_MAX_SIZE)
+        .register(VERSION, &C_TYPES)
+        .expect("failed to register C-chain codec");
+
+    /// ref. "avalanchego/vms/platformvm/warp.c"
+    pub static ref WARP_CODEC: Manager = Manager::new("warp", DEFAULT_MAX_SIZE)
+        .register(VERSION, &WARP_TYPES)
+        .expect("failed to register warp codec");
+
+    /// ref. "avalanchego/vms/platformvm/warp/payload.c"
+    pub static ref WARP_PAYLOAD_CODEC: Manager =
+        Manager::new("warp payload", WARP_PAYLOAD_MAX_SIZE)
+            .register(VERSION, &WARP_PAYLOAD_TYPES)
+            .expect("failed to register warp payload codec");
+
+    /// Version 1 is the stop vertex without the epoch and txs.
+    /// ref. "avalanchego/snow/engine/avalanche/vertex.c"
+    pub static ref VERTEX_CODEC: Manager = Manager::new("vertex", VERTEX_MAX_SIZE)
+        .register(0, &VERTEX_TYPES)
+        .and_then(|m| m.register(1, &VERTEX_TYPES))
+        .expect("failed to register vertex codec");
+}
+
+/// Registers the type IDs of each codec version of the VM,
+/// so that the bytes of the future avalanchego releases with the new
+/// version are rejected with a clear error rather than mis-parsed.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/codec#Manager
+#[derive(Debug, Clone)]
+pub struct Manager {
+    /// Name of the VM for the errors (e.g., "avm").
+    pub name: &'static str,
+    pub max_size: usize,
+    versions: BTreeMap<u16, &'static HashMap<String, usize>>,
+}
+
+impl Manager {
+    pub fn new(name: &'static str, max_size: usize) -> Self {
+        Self {
+            name,
+            max_size,
+            versions: BTreeMap::new(),
+        }
+    }
+
+    /// Registers the type IDs of the version.
+    /// ref. "avalanchego/codec.Manager.RegisterCodec"
+    pub fn register(
+        mut self,
+        version: u16,
+        types: &'static HashMap<String, usize>,
+    ) -> io::Result<Self> {
+        if self.versions.contains_key(&version) {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} codec version {} already registered", self.name, version),
+            ));
+        }
+        self.versions.insert(version, types);
+        Ok(self)
+    }
+
+    pub fn versions(&self) -> Vec<u16> {
+        self.versions.keys().copied().collect()
+    }
+
+    /// ref. "avalanchego/codec.errUnknownVersion"
+    pub fn check_version(&self, version: u16) -> io::Result<()> {
+        if !self.versions.contains_key(&version) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "unknown {} codec version {} (supported {:?}), the bytes may be from the newer avalanchego",
+                    self.name,
+                    version,
+                    self.versions()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the type ID of the type registered in the version.
+    pub fn type_id(&self, version: u16, type_name: &str) -> io::Result<u32> {
+        self.check_version(version)?;
+        match self.versions[&version].get(type_name) {
+            Some(id) => Ok(*id as u32),
+            None => Err(Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "type '{}' not registered in {} codec version {}",
+                    type_name, self.name, version
+                ),
+            )),
+        }
+    }
+
+    /// Returns the name of the type ID registered in the version.
+    pub fn type_name(&self, version: u16, type_id: u32) -> Option<&str> {
+        self.versions.get(&version).and_then(|types| {
+            types
+                .iter()
+                .find(|(_, id)| **id as u32 == type_id)
+                .map(|(name, _)| name.as_str())
+        })
+    }
+
+    /// Packs the version and then the value with "f".
+    /// ref. "avalanchego/codec.Manager.Marshal"
+    pub fn marshal<F>(&self, version: u16, f: F) -> io::Result<Vec<u8>>
+    where
+        F: FnOnce(&packer::Packer) -> io::Result<()>,
+    {
+        self.check_version(version)?;
+        let packer = packer::Packer::new(self.max_size, 512);
+        packer.pack_u16(version);
+        f(&packer)?;
+        if let Some(e) = packer.take_error() {
+            return Err(e);
+        }
+        Ok(packer.take_bytes().to_vec())
+    }
+
+    /// Unpacks the version and then the value with "f" for that version,
+    /// rejecting the bytes larger than the max size, the unknown versions,
+    /// and any trailing bytes.
+    /// ref. "avalanchego/codec.Manager.Unmarshal"
+    pub fn unmarshal<T, F>(&self, b: &[u8], f: F) -> io::Result<T>
+    where
+        F: FnOnce(u16, &packer::Packer) -> io::Result<T>,
+    {
+        if b.len() > self.max_size {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "{} bytes exceed {} codec max size {}", // ref. "errUnmarshalTooBig"
+                    b.len(),
+                    self.name,
+                    self.max_size
+                ),
+            ));
+        }
+        let packer = packer::Packer::load_bytes_for_unpack(self.max_size, b);
+        let version = packer.unpack_u16()?;
+        self.check_version(version)?;
+
+        let v = f(version, &packer)?;
+        if packer.remaining() > 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "{} extra bytes after {} codec version {} value",
+                    packer.remaining(),
+                    self.name,
+                    version
+                ),
+            ));
+        }
+        Ok(v)
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- codec::test_manager --exact --show-output
+#[test]
+fn test_manager() {
+    assert_eq!(X_CODEC.versions(), vec![VERSION]);
+    assert_eq!(X_CODEC.type_id(VERSION, "avm.BaseTx").unwrap(), 0);
+    assert_eq!(
+        P_CODEC
+            .type_id(VERSION, "platformvm.UnsignedCreateSubnetTx")
+            .unwrap(),
+        16
+    );
+    assert_eq!(C_CODEC.type_name(VERSION, 1), Some("evm.UnsignedExportTx"));
+    assert_eq!(VERTEX_CODEC.versions(), vec![0, 1]);
+    assert_eq!(C_CODEC.type_name(VERSION, 2), None);
+    assert_eq!(
+        WARP_PAYLOAD_CODEC
+            .type_id(VERSION, "payload.AddressedCall")
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        X_CODEC
+            .type_id(VERSION, "platformvm.StakeableLockOut")
+            .unwrap_err()
+            .kind(),
+        ErrorKind::NotFound
+    );
+
+    let b = X_CODEC
+        .marshal(VERSION, |packer| {
+            packer.pack_u32(7);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(b, vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x07]);
+    assert_eq!(
+        X_CODEC
+            .unmarshal(&b, |_, packer| packer.unpack_u32())
+            .unwrap(),
+        7
+    );
+
+    // trailing bytes
+    assert!(X_CODEC
+        .unmarshal(&b, |_, packer| packer.unpack_u16())
+        .is_err());
+
+    // bytes from the future version
+    let err = X_CODEC
+        .unmarshal(&[0x00, 0x01, 0x00, 0x00, 0x00, 0x07], |_, packer| {
+            packer.unpack_u32()
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("unknown avm codec version 1"));
+    assert!(X_CODEC.marshal(1, |_| Ok(())).is_err());
+
+    let dup = Manager::new("avm", DEFAULT_MAX_SIZE)
+        .register(VERSION, &X_TYPES)
+        .unwrap()
+        .register(VERSION, &X_TYPES);
+    assert!(dup.is_err());
 }
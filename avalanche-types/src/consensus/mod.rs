@@ -0,0 +1,114 @@
+pub mod sim;
+
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+/// Snowball parameters of the avalanchego node.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/snow/consensus/snowball#Parameters
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Parameters {
+    /// Sample size, "--snow-sample-size".
+    pub k: u32,
+    /// Quorum size, "--snow-quorum-size".
+    pub alpha: u32,
+    /// Consecutive successful polls to finalize the item without conflicts,
+    /// "--snow-virtuous-commit-threshold".
+    pub beta_virtuous: u32,
+    /// Consecutive successful polls to finalize the conflicting item,
+    /// "--snow-rogue-commit-threshold".
+    pub beta_rogue: u32,
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Parameters {
+    /// ref. "avalanchego/config/flags.go"
+    pub fn default() -> Self {
+        Self {
+            k: 20,
+            alpha: 15,
+            beta_virtuous: 15,
+            beta_rogue: 20,
+        }
+    }
+
+    /// ref. "avalanchego/snow/consensus/snowball.Parameters.Verify"
+    pub fn verify(&self) -> io::Result<()> {
+        if self.alpha <= self.k / 2 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "k = {}, alpha = {}: fails the condition that: k/2 < alpha",
+                    self.k, self.alpha
+                ),
+            ));
+        }
+        if self.k < self.alpha {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "k = {}, alpha = {}: fails the condition that: alpha <= k",
+                    self.k, self.alpha
+                ),
+            ));
+        }
+        if self.beta_virtuous == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "betaVirtuous = 0: fails the condition that: 0 < betaVirtuous",
+            ));
+        }
+        if self.beta_rogue < self.beta_virtuous {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "betaVirtuous = {}, betaRogue = {}: fails the condition that: betaVirtuous <= betaRogue",
+                    self.beta_virtuous, self.beta_rogue
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- consensus::test_parameters --exact --show-output
+#[test]
+fn test_parameters() {
+    Parameters::default().verify().unwrap();
+
+    // custom network defaults of 5 anchor nodes
+    let params = Parameters {
+        k: 5,
+        alpha: 3,
+        ..Parameters::default()
+    };
+    params.verify().unwrap();
+
+    let no_majority = Parameters {
+        alpha: 10,
+        ..Parameters::default()
+    };
+    assert!(no_majority
+        .verify()
+        .unwrap_err()
+        .to_string()
+        .contains("k/2 < alpha"));
+
+    let no_quorum = Parameters {
+        alpha: 21,
+        ..Parameters::default()
+    };
+    assert!(no_quorum.verify().is_err());
+
+    let rogue_too_low = Parameters {
+        beta_rogue: 14,
+        ..Parameters::default()
+    };
+    assert!(rogue_too_low.verify().is_err());
+}
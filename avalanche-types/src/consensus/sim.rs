@@ -0,0 +1,415 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use super::Parameters;
+
+/// Network and adversary of the simulation, where the equally weighted
+/// validators decide between two conflicting items (e.g., the blocks
+/// at the same height) with the Snowball polls of Snowman.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Config {
+    pub parameters: Parameters,
+    /// Number of the validators, including the byzantine ones.
+    pub nodes: u32,
+    /// Fraction of the byzantine validators in [0, 1), which always vote
+    /// for the item preferred by fewer honest validators to keep them split.
+    pub byzantine_fraction: f64,
+    /// Fraction of the honest validators initially preferring the second item.
+    /// Zero is the virtuous item without the conflict, finalized with
+    /// "beta_virtuous". Otherwise, finalized with "beta_rogue".
+    pub initial_split: f64,
+    /// Round-trip query latency, sampled uniformly in the range for each query.
+    /// The poll completes with its slowest query.
+    pub min_latency_ms: u64,
+    pub max_latency_ms: u64,
+    /// Polls per validator after which the undecided validators are not finalized.
+    pub max_rounds: u32,
+    pub runs: u32,
+    /// Same seed reproduces the same report.
+    pub seed: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Config {
+    pub fn default() -> Self {
+        Self {
+            parameters: Parameters::default(),
+            nodes: 100,
+            byzantine_fraction: 0.0,
+            initial_split: 0.5,
+            min_latency_ms: 50,
+            max_latency_ms: 200,
+            max_rounds: 1000,
+            runs: 100,
+            seed: 0,
+        }
+    }
+
+    pub fn verify(&self) -> io::Result<()> {
+        self.parameters.verify()?;
+        if self.parameters.k > self.nodes {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "sample size {} exceeds {} nodes",
+                    self.parameters.k, self.nodes
+                ),
+            ));
+        }
+        if !(0.0..1.0).contains(&self.byzantine_fraction) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "byzantine fraction {} must be in [0, 1)",
+                    self.byzantine_fraction
+                ),
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.initial_split) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("initial split {} must be in [0, 1]", self.initial_split),
+            ));
+        }
+        if self.min_latency_ms > self.max_latency_ms {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "min latency {}ms exceeds max latency {}ms",
+                    self.min_latency_ms, self.max_latency_ms
+                ),
+            ));
+        }
+        if self.max_rounds == 0 || self.runs == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "max rounds and runs must be non-zero",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the number of the honest and byzantine validators.
+    pub fn honest_byzantine(&self) -> (u32, u32) {
+        let byzantine = (self.nodes as f64 * self.byzantine_fraction).floor() as u32;
+        (self.nodes - byzantine, byzantine)
+    }
+
+    /// Returns the beta to finalize the item.
+    pub fn beta(&self) -> u32 {
+        if self.initial_split == 0.0 {
+            self.parameters.beta_virtuous
+        } else {
+            self.parameters.beta_rogue
+        }
+    }
+}
+
+/// Summary of the samples, with the nearest-rank percentiles.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Distribution {
+    pub count: usize,
+    pub min: u64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+    pub mean: f64,
+}
+
+impl Distribution {
+    /// Returns "None" if there is no sample.
+    pub fn new(samples: &[u64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let percentile = |p: usize| sorted[((sorted.len() * p + 99) / 100).max(1) - 1];
+        Some(Self {
+            count: sorted.len(),
+            min: sorted[0],
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: sorted[sorted.len() - 1],
+            mean: sorted.iter().map(|v| *v as f64).sum::<f64>() / sorted.len() as f64,
+        })
+    }
+}
+
+/// Outcome of all runs.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Report {
+    pub runs: u32,
+    pub honest_nodes: u32,
+    pub byzantine_nodes: u32,
+    pub beta: u32,
+    /// Fraction of the honest validators that finalized within "max_rounds",
+    /// over all runs.
+    pub finalized: f64,
+    /// Runs where the honest validators finalized the conflicting items.
+    pub safety_violations: u32,
+    /// Polls until each honest validator finalized, "None" if none did.
+    pub finality_rounds: Option<Distribution>,
+    /// Time until each honest validator finalized.
+    pub finality_time_ms: Option<Distribution>,
+    /// Confidence of each honest validator at the end of the run,
+    /// at least "beta" for the finalized ones.
+    pub confidence: Distribution,
+}
+
+/// Simulates the runs of the config.
+pub fn simulate(cfg: &Config) -> io::Result<Report> {
+    cfg.verify()?;
+    let (honest, byzantine) = cfg.honest_byzantine();
+    let beta = cfg.beta();
+
+    let mut decided: u64 = 0;
+    let mut safety_violations: u32 = 0;
+    let mut rounds: Vec<u64> = Vec::new();
+    let mut times: Vec<u64> = Vec::new();
+    let mut confidences: Vec<u64> = Vec::new();
+    for run in 0..cfg.runs {
+        let mut rng = Rng::new(cfg.seed.wrapping_add(run as u64));
+        let nodes = run_once(cfg, honest, beta, &mut rng);
+
+        let mut finalized = [false; 2];
+        for node in nodes.iter() {
+            confidences.push(node.confidence as u64);
+            if let Some((round, time_ms)) = node.decided {
+                decided += 1;
+                finalized[node.preference] = true;
+                rounds.push(round as u64);
+                times.push(time_ms);
+            }
+        }
+        if finalized[0] && finalized[1] {
+            safety_violations += 1;
+        }
+    }
+
+    Ok(Report {
+        runs: cfg.runs,
+        honest_nodes: honest,
+        byzantine_nodes: byzantine,
+        beta,
+        finalized: decided as f64 / (honest as u64 * cfg.runs as u64) as f64,
+        safety_violations,
+        finality_rounds: Distribution::new(&rounds),
+        finality_time_ms: Distribution::new(&times),
+        confidence: Distribution::new(&confidences).unwrap(),
+    })
+}
+
+/// Binary Snowball instance of the honest validator.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/snow/consensus/snowball
+struct Node {
+    preference: usize,
+    /// Successful polls of each item.
+    counts: [u64; 2],
+    last: usize,
+    confidence: u32,
+    elapsed_ms: u64,
+    /// Round and time when finalized.
+    decided: Option<(u32, u64)>,
+}
+
+impl Node {
+    fn record_poll(&mut self, votes: [u32; 2], alpha: u32) {
+        let choice = match votes {
+            [v, _] if v >= alpha => 0,
+            [_, v] if v >= alpha => 1,
+            _ => {
+                self.confidence = 0;
+                return;
+            }
+        };
+        self.counts[choice] += 1;
+        if self.counts[choice] > self.counts[self.preference] {
+            self.preference = choice;
+        }
+        if self.last == choice {
+            self.confidence += 1;
+        } else {
+            self.last = choice;
+            self.confidence = 1;
+        }
+    }
+}
+
+/// Runs the polls until all honest validators finalize or "max_rounds".
+/// The validators poll in the lock step, each seeing the preferences
+/// at the start of the round.
+fn run_once(cfg: &Config, honest: u32, beta: u32, rng: &mut Rng) -> Vec<Node> {
+    let second = (honest as f64 * cfg.initial_split).round() as u32;
+    let mut nodes: Vec<Node> = (0..honest)
+        .map(|i| {
+            let preference = if i < second { 1 } else { 0 };
+            Node {
+                preference,
+                counts: [0, 0],
+                last: preference,
+                confidence: 0,
+                elapsed_ms: 0,
+                decided: None,
+            }
+        })
+        .collect();
+
+    // validators at and after "honest" are byzantine
+    let mut peers: Vec<u32> = (0..cfg.nodes).collect();
+    for round in 1..=cfg.max_rounds {
+        let prefs: Vec<usize> = nodes.iter().map(|n| n.preference).collect();
+        let seconds = prefs.iter().filter(|p| **p == 1).count() as u32;
+        let byzantine_vote = if seconds * 2 < honest { 1 } else { 0 };
+
+        for node in nodes.iter_mut().filter(|n| n.decided.is_none()) {
+            let mut votes = [0_u32; 2];
+            let mut poll_ms = 0;
+            // partial Fisher-Yates over the validators, including itself
+            for i in 0..cfg.parameters.k as usize {
+                let j = i + rng.below((cfg.nodes as usize - i) as u64) as usize;
+                peers.swap(i, j);
+                let peer = peers[i];
+                let vote = if peer < honest {
+                    prefs[peer as usize]
+                } else {
+                    byzantine_vote
+                };
+                votes[vote] += 1;
+
+                let latency =
+                    cfg.min_latency_ms + rng.below(cfg.max_latency_ms - cfg.min_latency_ms + 1);
+                poll_ms = poll_ms.max(latency);
+            }
+            node.elapsed_ms += poll_ms;
+            node.record_poll(votes, cfg.parameters.alpha);
+            if node.confidence >= beta {
+                // finalized on the item of the consecutive polls,
+                // which is preferred from then on
+                node.preference = node.last;
+                node.decided = Some((round, node.elapsed_ms));
+            }
+        }
+        if nodes.iter().all(|n| n.decided.is_some()) {
+            break;
+        }
+    }
+    nodes
+}
+
+/// SplitMix64, so the simulation is reproducible without the external crate.
+/// ref. https://prng.di.unimi.it/splitmix64.c
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns the number in [0, n).
+    fn below(&mut self, n: u64) -> u64 {
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- consensus::sim::test_distribution --exact --show-output
+#[test]
+fn test_distribution() {
+    assert!(Distribution::new(&[]).is_none());
+
+    let samples: Vec<u64> = (1..=100).rev().collect();
+    let d = Distribution::new(&samples).unwrap();
+    assert_eq!(d.count, 100);
+    assert_eq!(d.min, 1);
+    assert_eq!(d.p50, 50);
+    assert_eq!(d.p90, 90);
+    assert_eq!(d.p99, 99);
+    assert_eq!(d.max, 100);
+    assert_eq!(d.mean, 50.5);
+
+    let d = Distribution::new(&[7]).unwrap();
+    assert_eq!((d.min, d.p50, d.p99, d.max), (7, 7, 7, 7));
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- consensus::sim::test_simulate --exact --show-output
+#[test]
+fn test_simulate() {
+    // every poll succeeds without the conflict and the adversary,
+    // so all validators finalize after exactly "beta_virtuous" polls
+    let virtuous = Config {
+        initial_split: 0.0,
+        runs: 5,
+        ..Config::default()
+    };
+    let report = simulate(&virtuous).unwrap();
+    assert_eq!(report.beta, 15);
+    assert_eq!(report.finalized, 1.0);
+    assert_eq!(report.safety_violations, 0);
+    let rounds = report.finality_rounds.unwrap();
+    assert_eq!((rounds.min, rounds.max), (15, 15));
+    let time_ms = report.finality_time_ms.unwrap();
+    assert!(time_ms.min >= 15 * 50);
+    assert!(time_ms.max <= 15 * 200);
+    assert_eq!(report.confidence.min, 15);
+
+    // reproducible with the same seed
+    let rogue = Config {
+        runs: 5,
+        ..Config::default()
+    };
+    let report = simulate(&rogue).unwrap();
+    assert_eq!(report, simulate(&rogue).unwrap());
+    assert_eq!(report.beta, 20);
+    assert_eq!(report.safety_violations, 0);
+
+    // 40% byzantine validators voting against the honest majority
+    // leave the honest validators short of the quorum
+    let attacked = Config {
+        byzantine_fraction: 0.4,
+        initial_split: 0.0,
+        max_rounds: 200,
+        runs: 5,
+        ..Config::default()
+    };
+    let report = simulate(&attacked).unwrap();
+    assert_eq!((report.honest_nodes, report.byzantine_nodes), (60, 40));
+    assert!(report.finalized < 0.5);
+
+    let too_few_nodes = Config {
+        nodes: 10,
+        ..Config::default()
+    };
+    assert!(simulate(&too_few_nodes)
+        .unwrap_err()
+        .to_string()
+        .contains("exceeds 10 nodes"));
+    let all_byzantine = Config {
+        byzantine_fraction: 1.0,
+        ..Config::default()
+    };
+    assert!(simulate(&all_byzantine).is_err());
+    let bad_latency = Config {
+        min_latency_ms: 300,
+        ..Config::default()
+    };
+    assert!(simulate(&bad_latency).is_err());
+}
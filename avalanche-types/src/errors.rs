@@ -0,0 +1,131 @@
+use std::io::{self, ErrorKind};
+
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Backing errors of the remote calls (the node APIs and the AWS APIs) and
+/// of the spec validation, so that the callers can branch on the failure class
+/// and the retryability rather than matching the error strings.
+/// The local failures (e.g., invalid arguments, file I/O) stay "io::Error"
+/// with the matching kind. The functions return "io::Result" either way,
+/// so this converts into "io::Error", and "Error::from_io" recovers it.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// Failed to decode the bytes or the response body.
+    #[error("failed to decode {message}")]
+    Decode { message: String },
+    /// JSON-RPC error object from the node.
+    /// ref. https://www.jsonrpc.org/specification#error_object
+    #[error("{message} (code {code})")]
+    API {
+        code: i32,
+        message: String,
+        is_retryable: bool,
+    },
+    /// Failed AWS API call.
+    #[error("failed AWS API {message}")]
+    AWS { message: String, is_retryable: bool },
+    /// Invalid spec that must be fixed by the user, never retryable.
+    #[error("invalid spec {message}")]
+    SpecValidation { message: String },
+    #[error("{message}")]
+    Other { message: String, is_retryable: bool },
+}
+
+impl Error {
+    /// Returns the error message in "String".
+    #[inline]
+    #[must_use]
+    pub fn message(&self) -> String {
+        match self {
+            Error::Decode { message }
+            | Error::API { message, .. }
+            | Error::AWS { message, .. }
+            | Error::SpecValidation { message }
+            | Error::Other { message, .. } => message.clone(),
+        }
+    }
+
+    /// Returns if the error is retryable.
+    #[inline]
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Decode { .. } | Error::SpecValidation { .. } => false,
+            Error::API { is_retryable, .. }
+            | Error::AWS { is_retryable, .. }
+            | Error::Other { is_retryable, .. } => *is_retryable,
+        }
+    }
+
+    /// Returns the kind of the converted "io::Error".
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Decode { .. } => ErrorKind::InvalidData,
+            Error::SpecValidation { .. } => ErrorKind::InvalidInput,
+            Error::API { .. } | Error::AWS { .. } | Error::Other { .. } => ErrorKind::Other,
+        }
+    }
+
+    /// Returns the error that the "io::Error" was converted from, if any.
+    pub fn from_io(e: &io::Error) -> Option<&Error> {
+        e.get_ref().and_then(|inner| inner.downcast_ref::<Error>())
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        io::Error::new(e.kind(), e)
+    }
+}
+
+/// Returns if the error is worth retrying, falling back to the error kind
+/// for the errors not converted from "Error".
+pub fn is_retryable(e: &io::Error) -> bool {
+    match Error::from_io(e) {
+        Some(inner) => inner.is_retryable(),
+        None => matches!(
+            e.kind(),
+            ErrorKind::TimedOut
+                | ErrorKind::ConnectionRefused
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::Interrupted
+        ),
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- errors::test_errors --exact --show-output
+#[test]
+fn test_errors() {
+    let e: io::Error = Error::API {
+        code: -32000,
+        message: String::from("eth_getBalance failed (header not found)"),
+        is_retryable: true,
+    }
+    .into();
+    assert_eq!(e.kind(), ErrorKind::Other);
+    assert_eq!(
+        e.to_string(),
+        "eth_getBalance failed (header not found) (code -32000)"
+    );
+    assert!(is_retryable(&e));
+    match Error::from_io(&e) {
+        Some(Error::API { code, .. }) => assert_eq!(*code, -32000),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let e: io::Error = Error::Decode {
+        message: String::from("invalid JSON"),
+    }
+    .into();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "failed to decode invalid JSON");
+    assert!(!is_retryable(&e));
+
+    let e = io::Error::new(ErrorKind::TimedOut, "timed out");
+    assert!(Error::from_io(&e).is_none());
+    assert!(is_retryable(&e));
+    assert!(!is_retryable(&io::Error::new(ErrorKind::Other, "failed")));
+}
@@ -0,0 +1,2 @@
+pub mod tx;
+pub mod txs;
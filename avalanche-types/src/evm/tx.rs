@@ -0,0 +1,284 @@
+use std::io::{self, Error, ErrorKind};
+
+use ethereum_types::{Address, H256, U256};
+use rlp::RlpStream;
+use sha3::{Digest, Keccak256};
+
+use crate::signer;
+
+/// EIP-2718 transaction type of "DynamicFeeTx".
+/// ref. https://eips.ethereum.org/EIPS/eip-1559
+pub const DYNAMIC_FEE_TX_TYPE: u8 = 0x02;
+
+/// Transaction to an EVM chain (e.g., C-chain, subnet-evm),
+/// signed for "eth_sendRawTransaction".
+/// ref. https://pkg.go.dev/github.com/ava-labs/coreth/core/types#Transaction
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Tx {
+    /// Pre-EIP-1559 transaction with the EIP-155 replay protection.
+    /// ref. https://eips.ethereum.org/EIPS/eip-155
+    Legacy(LegacyTx),
+    /// EIP-1559 transaction with the empty access list.
+    /// ref. https://eips.ethereum.org/EIPS/eip-1559
+    DynamicFee(DynamicFeeTx),
+}
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/coreth/core/types#LegacyTx
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LegacyTx {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub gas_price: U256,
+    pub gas_limit: u64,
+    /// "None" to create a contract.
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Vec<u8>,
+}
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/coreth/core/types#DynamicFeeTx
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DynamicFeeTx {
+    pub chain_id: u64,
+    pub nonce: u64,
+    /// Tip to the block producer ("gasTipCap").
+    pub max_priority_fee_per_gas: U256,
+    /// Maximum total fee per gas including the base fee ("gasFeeCap").
+    pub max_fee_per_gas: U256,
+    pub gas_limit: u64,
+    /// "None" to create a contract.
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Vec<u8>,
+}
+
+/// Signed transaction ready to issue.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SignedTx {
+    /// Encoded bytes for "eth_sendRawTransaction".
+    pub raw: Vec<u8>,
+    /// Transaction hash, the keccak256 of "raw".
+    pub hash: H256,
+}
+
+impl SignedTx {
+    /// Returns the "0x"-prefixed hex-encoded raw transaction.
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", hex::encode(&self.raw))
+    }
+}
+
+impl Tx {
+    pub fn chain_id(&self) -> u64 {
+        match self {
+            Tx::Legacy(tx) => tx.chain_id,
+            Tx::DynamicFee(tx) => tx.chain_id,
+        }
+    }
+
+    /// Returns the digest to sign, which commits to the chain ID
+    /// so the signed transaction cannot be replayed on another chain.
+    pub fn signing_hash(&self) -> H256 {
+        match self {
+            Tx::Legacy(tx) => {
+                // ref. "go-ethereum/core/types.EIP155Signer.Hash"
+                let mut s = RlpStream::new_list(9);
+                tx.append_fields(&mut s);
+                s.append(&tx.chain_id);
+                s.append(&0u8);
+                s.append(&0u8);
+                keccak256(&s.out())
+            }
+            Tx::DynamicFee(tx) => {
+                // ref. "go-ethereum/core/types.LondonSigner.Hash"
+                let mut s = RlpStream::new_list(9);
+                tx.append_fields(&mut s);
+                let mut b = vec![DYNAMIC_FEE_TX_TYPE];
+                b.extend_from_slice(&s.out());
+                keccak256(&b)
+            }
+        }
+    }
+
+    /// Encodes the transaction with the 65-byte recoverable signature
+    /// (as returned by "signer::Signer.sign_digest") of "signing_hash".
+    pub fn encode_signed(&self, sig: &[u8]) -> io::Result<SignedTx> {
+        if sig.len() != 65 || sig[64] > 1 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "expected 65-byte recoverable signature, got {} bytes",
+                    sig.len()
+                ),
+            ));
+        }
+        let r = U256::from_big_endian(&sig[..32]);
+        let s = U256::from_big_endian(&sig[32..64]);
+        let recovery_id = sig[64] as u64;
+
+        let raw = match self {
+            Tx::Legacy(tx) => {
+                let mut stream = RlpStream::new_list(9);
+                tx.append_fields(&mut stream);
+                stream.append(&(recovery_id + tx.chain_id * 2 + 35));
+                stream.append(&r);
+                stream.append(&s);
+                stream.out().to_vec()
+            }
+            Tx::DynamicFee(tx) => {
+                let mut stream = RlpStream::new_list(12);
+                tx.append_fields(&mut stream);
+                stream.append(&recovery_id);
+                stream.append(&r);
+                stream.append(&s);
+                let mut b = vec![DYNAMIC_FEE_TX_TYPE];
+                b.extend_from_slice(&stream.out());
+                b
+            }
+        };
+        let hash = keccak256(&raw);
+        Ok(SignedTx { raw, hash })
+    }
+
+    /// Signs the transaction with the soft key, KMS, or any other signer.
+    pub async fn sign(&self, signer: &dyn signer::Signer) -> io::Result<SignedTx> {
+        let digest = self.signing_hash();
+        let sig = signer.sign_digest(digest.as_bytes()).await?;
+        self.encode_signed(&sig)
+    }
+}
+
+impl LegacyTx {
+    fn append_fields(&self, s: &mut RlpStream) {
+        s.append(&self.nonce);
+        s.append(&self.gas_price);
+        s.append(&self.gas_limit);
+        append_to(s, &self.to);
+        s.append(&self.value);
+        s.append(&self.data);
+    }
+}
+
+impl DynamicFeeTx {
+    fn append_fields(&self, s: &mut RlpStream) {
+        s.append(&self.chain_id);
+        s.append(&self.nonce);
+        s.append(&self.max_priority_fee_per_gas);
+        s.append(&self.max_fee_per_gas);
+        s.append(&self.gas_limit);
+        append_to(s, &self.to);
+        s.append(&self.value);
+        s.append(&self.data);
+        // empty access list
+        s.begin_list(0);
+    }
+}
+
+/// The contract creation has the empty recipient.
+fn append_to(s: &mut RlpStream, to: &Option<Address>) {
+    match to {
+        Some(addr) => s.append(addr),
+        None => s.append_empty_data(),
+    };
+}
+
+fn keccak256(data: &[u8]) -> H256 {
+    H256::from_slice(&Keccak256::digest(data))
+}
+
+#[cfg(test)]
+fn recover_address(digest: &H256, sig: &[u8]) -> Address {
+    use secp256k1::{
+        ecdsa::{RecoverableSignature, RecoveryId},
+        Message, Secp256k1,
+    };
+    let rec_id = RecoveryId::from_i32(sig[64] as i32).unwrap();
+    let sig = RecoverableSignature::from_compact(&sig[..64], rec_id).unwrap();
+    let m = Message::from_slice(digest.as_bytes()).unwrap();
+    let public_key = Secp256k1::verification_only()
+        .recover_ecdsa(&m, &sig)
+        .unwrap();
+    let d = keccak256(&public_key.serialize_uncompressed()[1..]);
+    Address::from_slice(&d[12..])
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- evm::tx::test_legacy_tx --exact --show-output
+#[test]
+fn test_legacy_tx() {
+    use crate::soft_key;
+    use std::str::FromStr;
+
+    // ref. https://eips.ethereum.org/EIPS/eip-155#example
+    let key = soft_key::Key::from_private_key_eth(
+        "4646464646464646464646464646464646464646464646464646464646464646",
+    )
+    .unwrap();
+    let tx = Tx::Legacy(LegacyTx {
+        chain_id: 1,
+        nonce: 9,
+        gas_price: U256::from(20_000_000_000_u64),
+        gas_limit: 21_000,
+        to: Some(Address::from_str("3535353535353535353535353535353535353535").unwrap()),
+        value: U256::from(1_000_000_000_000_000_000_u64),
+        data: Vec::new(),
+    });
+    assert_eq!(
+        hex::encode(tx.signing_hash()),
+        "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+    );
+
+    let signed = tokio_test::block_on(tx.sign(&key)).unwrap();
+    assert_eq!(
+        signed.to_hex(),
+        "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+    );
+    assert_eq!(
+        hex::encode(signed.hash),
+        "33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788"
+    );
+
+    assert!(tx.encode_signed(&[0u8; 64]).is_err());
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- evm::tx::test_dynamic_fee_tx --exact --show-output
+#[test]
+fn test_dynamic_fee_tx() {
+    use crate::soft_key;
+    use std::str::FromStr;
+
+    let key = soft_key::TEST_KEYS[0].clone();
+    let tx = Tx::DynamicFee(DynamicFeeTx {
+        chain_id: 43112,
+        nonce: 0,
+        max_priority_fee_per_gas: U256::from(1_000_000_000_u64),
+        max_fee_per_gas: U256::from(50_000_000_000_u64),
+        gas_limit: 21_000,
+        to: Some(Address::from_str("613040a239BDfCF110969fecB41c6f92EA3515C0").unwrap()),
+        value: U256::from(1_000_000_000_000_000_000_u64),
+        data: Vec::new(),
+    });
+    assert_eq!(tx.chain_id(), 43112);
+
+    let digest = tx.signing_hash();
+    let sig = key.sign_digest(digest.as_bytes()).unwrap();
+    let signed = tokio_test::block_on(tx.sign(&key)).unwrap();
+    assert_eq!(signed, tx.encode_signed(&sig).unwrap());
+    assert_eq!(signed.raw[0], DYNAMIC_FEE_TX_TYPE);
+
+    // typed envelope of the 12-item list
+    let rlp = rlp::Rlp::new(&signed.raw[1..]);
+    assert_eq!(rlp.item_count().unwrap(), 12);
+    assert_eq!(rlp.val_at::<u64>(0).unwrap(), 43112);
+    assert_eq!(rlp.val_at::<u64>(4).unwrap(), 21_000);
+    assert_eq!(rlp.at(8).unwrap().item_count().unwrap(), 0);
+    assert_eq!(rlp.val_at::<u64>(9).unwrap(), sig[64] as u64);
+
+    // signed by the key, and bound to the chain ID
+    let recovered = recover_address(&digest, &sig);
+    assert_eq!(format!("{:?}", recovered), key.eth_address.to_lowercase());
+    let mut other_chain = tx.clone();
+    if let Tx::DynamicFee(t) = &mut other_chain {
+        t.chain_id = 43114;
+    }
+    assert_ne!(other_chain.signing_hash(), digest);
+}
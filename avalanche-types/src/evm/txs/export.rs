@@ -0,0 +1,183 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, evm::txs::EvmInput, ids, secp256k1fx, soft_key};
+
+/// Exports the funds of EVM accounts to the destination chain (e.g., X-chain).
+/// ref. https://pkg.go.dev/github.com/ava-labs/coreth/plugin/evm#UnsignedExportTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub network_id: u32,
+    pub blockchain_id: ids::Id,
+    pub destination_chain: ids::Id,
+    pub ins: Vec<EvmInput>,
+    pub exported_outputs: Vec<avax::TransferableOutput>,
+
+    /// Populated by "sign", one per EVM input.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            network_id: 0,
+            blockchain_id: ids::Id::empty(),
+            destination_chain: ids::Id::empty(),
+            ins: Vec::new(),
+            exported_outputs: Vec::new(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "evm.UnsignedExportTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::C_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// ref. "coreth/plugin/evm.UnsignedExportTx.Verify"
+    pub fn verify(&self) -> io::Result<()> {
+        if self.exported_outputs.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "tx has no exported outputs", // ref. "errNoExportOutputs"
+            ));
+        }
+        if self
+            .exported_outputs
+            .iter()
+            .any(|out| out.stakeable_lock_out.is_some())
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "stakeable lock outputs are not supported on the C-chain",
+            ));
+        }
+        if !super::is_sorted_and_unique_inputs(&self.ins) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "inputs not sorted and unique", // ref. "errInputsNotSortedUnique"
+            ));
+        }
+
+        let mut sorted = self.exported_outputs.clone();
+        avax::sort_transferable_outputs(&mut sorted)?;
+        if sorted != self.exported_outputs {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "outputs not sorted", // ref. "errOutputsNotSorted"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Encodes the unsigned "evm.UnsignedExportTx" with the C-chain codec;
+    /// the EVM inputs carry nonces, so there is no Avalanche base tx.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::C_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            packer.pack_u32(self.network_id);
+            packer.pack_bytes(&self.blockchain_id.d);
+            packer.pack_bytes(&self.destination_chain.d);
+
+            packer.pack_u32(self.ins.len() as u32);
+            for input in self.ins.iter() {
+                input.pack(packer);
+            }
+
+            packer.pack_u32(self.exported_outputs.len() as u32);
+            for out in self.exported_outputs.iter() {
+                out.pack(packer)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Signs the tx with the key of each EVM input address (in the same order),
+    /// and returns the signed tx bytes to issue with its tx ID.
+    pub fn sign(&mut self, signers: &[Vec<soft_key::Key>]) -> io::Result<(Vec<u8>, ids::Id)> {
+        self.verify()?;
+        if self.ins.len() != signers.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} inputs but {} signers", self.ins.len(), signers.len()),
+            ));
+        }
+
+        let unsigned_bytes = self.unsigned_bytes()?;
+        self.creds = avax::sign_credentials(&unsigned_bytes, signers)?;
+        avax::pack_signed_tx(&unsigned_bytes, &self.creds)
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- evm::txs::export::test_unsigned_bytes --exact --show-output
+#[test]
+fn test_unsigned_bytes() {
+    use std::str::FromStr;
+
+    use ethereum_types::Address;
+
+    let key = soft_key::TEST_KEYS[0].clone();
+    let avax_asset_id = ids::Id::from_slice(&[0xbb; 32]);
+
+    let mut tx = Tx {
+        network_id: 12345,
+        blockchain_id: ids::Id::from_slice(&[0xcc; 32]),
+        destination_chain: ids::Id::from_slice(&[0xdd; 32]),
+        ins: vec![EvmInput {
+            address: Address::from_str(key.eth_address.trim_start_matches("0x")).unwrap(),
+            amount: 1_001_000_000,
+            asset_id: avax_asset_id,
+            nonce: 7,
+        }],
+        exported_outputs: vec![avax::TransferableOutput {
+            asset_id: avax_asset_id,
+            transfer_output: Some(secp256k1fx::TransferOutput::new(
+                1_000_000_000,
+                secp256k1fx::OutputOwners::new(0, 1, &[key.short_address]),
+            )),
+            ..avax::TransferableOutput::default()
+        }],
+        ..Tx::default()
+    };
+
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x01, // "evm.UnsignedExportTx" type ID
+        0x00, 0x00, 0x30, 0x39, // network ID
+    ];
+    expected.extend_from_slice(&[0xcc; 32]); // blockchain ID
+    expected.extend_from_slice(&[0xdd; 32]); // destination chain
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // number of inputs
+    expected.extend_from_slice(tx.ins[0].address.as_bytes());
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x00, 0x3b, 0xaa, 0x0c, 0x40]); // amount
+    expected.extend_from_slice(&[0xbb; 32]); // asset ID
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07]); // nonce
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // number of outputs
+    expected.extend_from_slice(&[0xbb; 32]); // asset ID
+    expected.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x07, // "secp256k1fx.TransferOutput" type ID
+        0x00, 0x00, 0x00, 0x00, 0x3b, 0x9a, 0xca, 0x00, // amount
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // locktime
+        0x00, 0x00, 0x00, 0x01, // threshold
+        0x00, 0x00, 0x00, 0x01, // number of addresses
+    ]);
+    expected.extend_from_slice(&key.short_address.d);
+    assert_eq!(tx.unsigned_bytes().unwrap(), expected);
+
+    let (signed_bytes, tx_id) = tx.sign(&[vec![key.clone()]]).unwrap();
+    assert_eq!(signed_bytes.len(), expected.len() + 12 + 65);
+    assert_eq!(tx.sign(&[vec![key]]).unwrap().1, tx_id);
+
+    tx.exported_outputs.clear();
+    assert!(tx.verify().is_err());
+}
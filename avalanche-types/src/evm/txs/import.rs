@@ -0,0 +1,190 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, evm::txs::EvmOutput, ids, secp256k1fx, soft_key};
+use utils::cmp;
+
+/// Imports the UTXOs exported from the source chain (e.g., X-chain) into EVM accounts.
+/// ref. https://pkg.go.dev/github.com/ava-labs/coreth/plugin/evm#UnsignedImportTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub network_id: u32,
+    pub blockchain_id: ids::Id,
+    pub source_chain: ids::Id,
+    pub imported_inputs: Vec<avax::TransferableInput>,
+    pub outs: Vec<EvmOutput>,
+
+    /// Populated by "sign", one per imported input.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            network_id: 0,
+            blockchain_id: ids::Id::empty(),
+            source_chain: ids::Id::empty(),
+            imported_inputs: Vec::new(),
+            outs: Vec::new(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "evm.UnsignedImportTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::C_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// ref. "coreth/plugin/evm.UnsignedImportTx.Verify"
+    pub fn verify(&self) -> io::Result<()> {
+        if self.imported_inputs.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "tx has no imported inputs", // ref. "errNoImportInputs"
+            ));
+        }
+        if self
+            .imported_inputs
+            .iter()
+            .any(|input| input.stakeable_lock_in.is_some())
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "stakeable lock inputs are not supported on the C-chain",
+            ));
+        }
+        if !cmp::is_sorted_and_unique(&self.imported_inputs) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "inputs not sorted and unique", // ref. "errInputsNotSortedUnique"
+            ));
+        }
+        if !super::is_sorted_and_unique_outputs(&self.outs) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "outputs not sorted and unique", // ref. "errOutputsNotSortedUnique"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Encodes the unsigned "evm.UnsignedImportTx" with the C-chain codec;
+    /// the imported UTXOs are credited to the EVM outputs.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::C_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            packer.pack_u32(self.network_id);
+            packer.pack_bytes(&self.blockchain_id.d);
+            packer.pack_bytes(&self.source_chain.d);
+
+            packer.pack_u32(self.imported_inputs.len() as u32);
+            for input in self.imported_inputs.iter() {
+                input.pack(packer)?;
+            }
+
+            packer.pack_u32(self.outs.len() as u32);
+            for out in self.outs.iter() {
+                out.pack(packer);
+            }
+            Ok(())
+        })
+    }
+
+    /// Signs the tx with the keys for each imported input (in the same order),
+    /// and returns the signed tx bytes to issue with its tx ID.
+    pub fn sign(&mut self, signers: &[Vec<soft_key::Key>]) -> io::Result<(Vec<u8>, ids::Id)> {
+        self.verify()?;
+        if self.imported_inputs.len() != signers.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{} inputs but {} signers",
+                    self.imported_inputs.len(),
+                    signers.len()
+                ),
+            ));
+        }
+
+        let unsigned_bytes = self.unsigned_bytes()?;
+        self.creds = avax::sign_credentials(&unsigned_bytes, signers)?;
+        avax::pack_signed_tx(&unsigned_bytes, &self.creds)
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- evm::txs::import::test_sign --exact --show-output
+#[test]
+fn test_sign() {
+    use ethereum_types::Address;
+
+    let key = soft_key::TEST_KEYS[0].clone();
+    let avax_asset_id = ids::Id::from_slice(&[0xbb; 32]);
+
+    let mut tx = Tx {
+        network_id: 12345,
+        blockchain_id: ids::Id::from_slice(&[0xcc; 32]),
+        source_chain: ids::Id::from_slice(&[0xdd; 32]),
+        imported_inputs: vec![avax::TransferableInput {
+            utxo_id: avax::UtxoId {
+                tx_id: ids::Id::from_slice(&[0xaa; 32]),
+                output_index: 0,
+                ..avax::UtxoId::default()
+            },
+            asset_id: avax_asset_id,
+            transfer_input: Some(secp256k1fx::TransferInput::new(1_000_000_000, vec![0])),
+            ..avax::TransferableInput::default()
+        }],
+        outs: vec![EvmOutput {
+            address: Address::from_slice(&[0x33; 20]),
+            amount: 999_000_000,
+            asset_id: avax_asset_id,
+        }],
+        ..Tx::default()
+    };
+
+    let unsigned_bytes = tx.unsigned_bytes().unwrap();
+    assert_eq!(
+        unsigned_bytes[..10],
+        [
+            0x00, 0x00, // codec version
+            0x00, 0x00, 0x00, 0x00, // "evm.UnsignedImportTx" type ID
+            0x00, 0x00, 0x30, 0x39, // network ID
+        ]
+    );
+
+    let mut expected_outs: Vec<u8> = vec![0x00, 0x00, 0x00, 0x01]; // number of outputs
+    expected_outs.extend_from_slice(&[0x33; 20]); // address
+    expected_outs.extend_from_slice(&[0x00, 0x00, 0x00, 0x00, 0x3b, 0x8b, 0x87, 0xc0]); // amount
+    expected_outs.extend_from_slice(&[0xbb; 32]); // asset ID
+    assert_eq!(
+        unsigned_bytes[unsigned_bytes.len() - expected_outs.len()..],
+        expected_outs[..]
+    );
+
+    assert!(tx.sign(&[]).is_err());
+    let (signed_bytes, _) = tx.sign(&[vec![key]]).unwrap();
+    assert_eq!(tx.creds.len(), 1);
+    assert_eq!(signed_bytes.len(), unsigned_bytes.len() + 12 + 65);
+
+    // outputs of the same address and asset ID are not unique,
+    // even though they differ in the amount
+    let mut dup = tx.clone();
+    dup.outs.push(EvmOutput {
+        amount: 1,
+        ..tx.outs[0].clone()
+    });
+    assert_ne!(dup.outs[0], dup.outs[1]);
+    assert!(dup.verify().is_err());
+
+    tx.imported_inputs.push(tx.imported_inputs[0].clone());
+    assert!(tx.verify().is_err());
+}
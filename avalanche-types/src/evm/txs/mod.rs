@@ -0,0 +1,112 @@
+pub mod export;
+pub mod import;
+
+use std::cmp::Ordering;
+
+use ethereum_types::Address;
+use serde::{Deserialize, Serialize};
+
+use crate::{ids, packer};
+
+/// Output of an import tx that credits an EVM account.
+/// ref. https://pkg.go.dev/github.com/ava-labs/coreth/plugin/evm#EVMOutput
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct EvmOutput {
+    pub address: Address,
+    pub amount: u64,
+    pub asset_id: ids::Id,
+}
+
+impl Default for EvmOutput {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl EvmOutput {
+    pub fn default() -> Self {
+        Self {
+            address: Address::zero(),
+            amount: 0,
+            asset_id: ids::Id::empty(),
+        }
+    }
+
+    pub fn pack(&self, packer: &packer::Packer) {
+        packer.pack_bytes(self.address.as_bytes());
+        packer.pack_u64(self.amount);
+        packer.pack_bytes(&self.asset_id.d);
+    }
+
+    /// Orders the outputs by address and then asset ID, for sorting only:
+    /// the outputs of different amounts compare equal, but are not "==".
+    /// ref. "coreth/plugin/evm.EVMOutput.Less"
+    pub fn sort_cmp(&self, other: &EvmOutput) -> Ordering {
+        self.address
+            .cmp(&other.address) // returns when "address"es are not Equal
+            .then_with(
+                || self.asset_id.cmp(&other.asset_id), // if "address"es are Equal, compare "asset_id"
+            )
+    }
+}
+
+/// Returns true if the outputs are sorted by "EvmOutput::sort_cmp",
+/// with no two outputs of the same address and asset ID.
+/// ref. "coreth/plugin/evm.IsSortedAndUniqueEVMOutputs"
+pub fn is_sorted_and_unique_outputs(outs: &[EvmOutput]) -> bool {
+    outs.windows(2)
+        .all(|w| w[0].sort_cmp(&w[1]) == Ordering::Less)
+}
+
+/// Input of an export tx that debits an EVM account.
+/// ref. https://pkg.go.dev/github.com/ava-labs/coreth/plugin/evm#EVMInput
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct EvmInput {
+    pub address: Address,
+    pub amount: u64,
+    pub asset_id: ids::Id,
+    pub nonce: u64,
+}
+
+impl Default for EvmInput {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl EvmInput {
+    pub fn default() -> Self {
+        Self {
+            address: Address::zero(),
+            amount: 0,
+            asset_id: ids::Id::empty(),
+            nonce: 0,
+        }
+    }
+
+    pub fn pack(&self, packer: &packer::Packer) {
+        packer.pack_bytes(self.address.as_bytes());
+        packer.pack_u64(self.amount);
+        packer.pack_bytes(&self.asset_id.d);
+        packer.pack_u64(self.nonce);
+    }
+
+    /// Orders the inputs by address and then asset ID, for sorting only:
+    /// the inputs of different amounts or nonces compare equal, but are not "==".
+    /// ref. "coreth/plugin/evm.EVMInput.Less"
+    pub fn sort_cmp(&self, other: &EvmInput) -> Ordering {
+        self.address
+            .cmp(&other.address) // returns when "address"es are not Equal
+            .then_with(
+                || self.asset_id.cmp(&other.asset_id), // if "address"es are Equal, compare "asset_id"
+            )
+    }
+}
+
+/// Returns true if the inputs are sorted by "EvmInput::sort_cmp",
+/// with no two inputs of the same address and asset ID.
+/// ref. "coreth/plugin/evm.IsSortedAndUniqueEVMInputs"
+pub fn is_sorted_and_unique_inputs(ins: &[EvmInput]) -> bool {
+    ins.windows(2)
+        .all(|w| w[0].sort_cmp(&w[1]) == Ordering::Less)
+}
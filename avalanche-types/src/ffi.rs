@@ -0,0 +1,333 @@
+// C-ABI functions of the ID and address utilities ("ffi" feature), so that
+// the Go and Python tooling links the same encoders instead of re-implementing
+// them. The header "include/avalanche_types.h" is generated by cbindgen
+// (see "scripts/build.ffi.sh").
+//
+// Every returned string or byte buffer is owned by the caller and must be
+// released with "avax_string_free" or "avax_bytes_free". On failure, the
+// functions return NULL (or -1) and "avax_last_error" describes the error.
+
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString},
+    io::{self, Error, ErrorKind},
+    os::raw::{c_char, c_int},
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+    str::FromStr,
+};
+
+use secp256k1::PublicKey;
+
+use crate::{formatting, ids, soft_key};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+fn set_last_error(e: &Error) {
+    let msg =
+        CString::new(e.to_string().replace('\0', "")).expect("unexpected NUL in the error message");
+    LAST_ERROR.with(|v| *v.borrow_mut() = Some(msg));
+}
+
+/// Runs the function, recording its error (or panic) for "avax_last_error"
+/// and returning the fallback value, since neither can cross the C boundary.
+fn call<T, F>(fallback: T, f: F) -> T
+where
+    F: FnOnce() -> io::Result<T>,
+{
+    let res = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(v) => v,
+        Err(_) => Err(Error::new(ErrorKind::Other, "panicked in avalanche-types")),
+    };
+    match res {
+        Ok(v) => v,
+        Err(e) => {
+            set_last_error(&e);
+            fallback
+        }
+    }
+}
+
+unsafe fn to_str<'a>(s: *const c_char, name: &str) -> io::Result<&'a str> {
+    if s.is_null() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} is NULL", name),
+        ));
+    }
+    CStr::from_ptr(s).to_str().map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} is not UTF-8 ({})", name, e),
+        )
+    })
+}
+
+unsafe fn to_slice<'a>(d: *const u8, len: usize, name: &str) -> io::Result<&'a [u8]> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if d.is_null() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} is NULL with length {}", name, len),
+        ));
+    }
+    Ok(slice::from_raw_parts(d, len))
+}
+
+fn into_c_string(s: String) -> io::Result<*mut c_char> {
+    let s = CString::new(s).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("failed to convert to C string ({})", e),
+        )
+    })?;
+    Ok(s.into_raw())
+}
+
+fn public_key_from_slice(d: &[u8]) -> io::Result<PublicKey> {
+    PublicKey::from_slice(d).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid secp256k1 public key ({})", e),
+        )
+    })
+}
+
+/// Returns the error message of the last failed call on this thread,
+/// or NULL if none failed. The string is owned by the library and stays
+/// valid until the next failed call on the same thread.
+#[no_mangle]
+pub extern "C" fn avax_last_error() -> *const c_char {
+    LAST_ERROR.with(|v| match v.borrow().as_ref() {
+        Some(msg) => msg.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+/// Releases the string returned by this library.
+///
+/// # Safety
+///
+/// "s" must be NULL or a string returned by this library that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn avax_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Releases the byte buffer returned by this library.
+///
+/// # Safety
+///
+/// "d" must be NULL or a buffer returned by this library that has not been freed,
+/// and "len" must be the length returned with it.
+#[no_mangle]
+pub unsafe extern "C" fn avax_bytes_free(d: *mut u8, len: usize) {
+    if !d.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(d, len)));
+    }
+}
+
+/// Encodes the bytes in CB58 (base58 with the 4-byte checksum).
+///
+/// # Safety
+///
+/// "d" must point to "len" readable bytes (or be NULL if "len" is zero).
+#[no_mangle]
+pub unsafe extern "C" fn avax_cb58_encode(d: *const u8, len: usize) -> *mut c_char {
+    call(ptr::null_mut(), || {
+        let d = to_slice(d, len, "d")?;
+        into_c_string(formatting::encode_cb58_with_checksum(d))
+    })
+}
+
+/// Decodes the CB58 string and verifies its checksum, writing the length of
+/// the returned buffer to "out_len". The input is treated as untrusted,
+/// so the string that decodes beyond the CB58 size limit is rejected.
+///
+/// # Safety
+///
+/// "s" must be a NUL-terminated string and "out_len" must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn avax_cb58_decode(s: *const c_char, out_len: *mut usize) -> *mut u8 {
+    call(ptr::null_mut(), || {
+        if out_len.is_null() {
+            return Err(Error::new(ErrorKind::InvalidInput, "out_len is NULL"));
+        }
+        let s = to_str(s, "s")?;
+        let decoded = formatting::decode_cb58_untrusted(s)?.into_boxed_slice();
+        *out_len = decoded.len();
+        Ok(Box::into_raw(decoded) as *mut u8)
+    })
+}
+
+/// Derives the node ID (e.g., "NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg")
+/// from the PEM-encoded staking certificate.
+///
+/// # Safety
+///
+/// "pem" must point to "len" readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn avax_node_id_from_cert_pem(pem: *const u8, len: usize) -> *mut c_char {
+    call(ptr::null_mut(), || {
+        let pem = to_slice(pem, len, "pem")?;
+        let node_id = ids::NodeId::from_cert_pem(pem)?;
+        into_c_string(node_id.to_string())
+    })
+}
+
+/// Derives the node ID from the DER-encoded staking certificate
+/// (i.e., "Certificate.Leaf.Raw" in avalanchego).
+///
+/// # Safety
+///
+/// "der" must point to "len" readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn avax_node_id_from_cert_der(der: *const u8, len: usize) -> *mut c_char {
+    call(ptr::null_mut(), || {
+        let der = to_slice(der, len, "der")?;
+        let node_id = ids::NodeId::from_cert_raw(der)?;
+        into_c_string(node_id.to_string())
+    })
+}
+
+/// Derives the chain-prefixed bech32 address (e.g., "X-avax1...") of the
+/// secp256k1 public key (33-byte compressed or 65-byte uncompressed),
+/// with the hrp of the network ID.
+///
+/// # Safety
+///
+/// "public_key" must point to "len" readable bytes and "chain_alias"
+/// ("X", "P", or "C") must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn avax_address_from_public_key(
+    public_key: *const u8,
+    len: usize,
+    chain_alias: *const c_char,
+    network_id: u32,
+) -> *mut c_char {
+    call(ptr::null_mut(), || {
+        let public_key = public_key_from_slice(to_slice(public_key, len, "public_key")?)?;
+        let chain = formatting::address::Chain::from_str(to_str(chain_alias, "chain_alias")?)?;
+        let short_id = soft_key::public_key_to_short_address(&public_key)?;
+        into_c_string(formatting::address::encode(chain, network_id, &short_id)?)
+    })
+}
+
+/// Derives the checksummed ETH address (e.g., "0x8db9...") of the
+/// secp256k1 public key (33-byte compressed or 65-byte uncompressed).
+///
+/// # Safety
+///
+/// "public_key" must point to "len" readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn avax_eth_address_from_public_key(
+    public_key: *const u8,
+    len: usize,
+) -> *mut c_char {
+    call(ptr::null_mut(), || {
+        let public_key = public_key_from_slice(to_slice(public_key, len, "public_key")?)?;
+        into_c_string(soft_key::public_key_to_eth_address(&public_key)?)
+    })
+}
+
+/// Decodes the chain-prefixed bech32 address into its 20-byte short ID.
+/// Returns 0 on success and -1 on the invalid address.
+///
+/// # Safety
+///
+/// "addr" must be a NUL-terminated string and "out" must point to
+/// 20 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn avax_address_to_short_id(addr: *const c_char, out: *mut u8) -> c_int {
+    call(-1, || {
+        if out.is_null() {
+            return Err(Error::new(ErrorKind::InvalidInput, "out is NULL"));
+        }
+        let decoded = formatting::address::decode(to_str(addr, "addr")?)?;
+        ptr::copy_nonoverlapping(decoded.short_id.as_bytes().as_ptr(), out, ids::SHORT_ID_LEN);
+        Ok(0)
+    })
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib --features ffi -- ffi::test_ffi --exact --show-output
+#[test]
+fn test_ffi() {
+    unsafe fn take_string(s: *mut c_char) -> String {
+        assert!(!s.is_null());
+        let v = CStr::from_ptr(s).to_str().unwrap().to_string();
+        avax_string_free(s);
+        v
+    }
+
+    unsafe {
+        let d = [1_u8, 2, 3];
+        let encoded = take_string(avax_cb58_encode(d.as_ptr(), d.len()));
+        assert_eq!(encoded, formatting::encode_cb58_with_checksum(&d));
+
+        let c_encoded = CString::new(encoded).unwrap();
+        let mut len = 0_usize;
+        let decoded = avax_cb58_decode(c_encoded.as_ptr(), &mut len);
+        assert_eq!(slice::from_raw_parts(decoded, len), &d);
+        avax_bytes_free(decoded, len);
+
+        // bad checksum
+        let c_bad = CString::new("1111111").unwrap();
+        assert!(avax_cb58_decode(c_bad.as_ptr(), &mut len).is_null());
+        assert!(!avax_last_error().is_null());
+        assert!(avax_cb58_decode(ptr::null(), &mut len).is_null());
+        assert_eq!(
+            CStr::from_ptr(avax_last_error()).to_str().unwrap(),
+            "s is NULL"
+        );
+
+        let pem = std::fs::read("./artifacts/staker1.insecure.crt").unwrap();
+        assert_eq!(
+            take_string(avax_node_id_from_cert_pem(pem.as_ptr(), pem.len())),
+            "NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg"
+        );
+        assert!(avax_node_id_from_cert_pem(d.as_ptr(), d.len()).is_null());
+
+        let key = soft_key::Key::from_private_key_eth(
+            "56289e99c94b6912bfc12adc093c9b51124f0dc54ac7a766b2bc5ccf558d8027",
+        )
+        .unwrap();
+        let public_key = key.public_key.unwrap().serialize();
+        let chain = CString::new("X").unwrap();
+        let addr = take_string(avax_address_from_public_key(
+            public_key.as_ptr(),
+            public_key.len(),
+            chain.as_ptr(),
+            crate::constants::DEFAULT_CUSTOM_NETWORK_ID,
+        ));
+        assert_eq!(addr, "X-custom18jma8ppw3nhx5r4ap8clazz0dps7rv5u9xde7p");
+        assert_eq!(
+            take_string(avax_eth_address_from_public_key(
+                public_key.as_ptr(),
+                public_key.len()
+            )),
+            "0x8db97C7cEcE249c2b98bDC0226Cc4C2A57BF52FC"
+        );
+
+        let c_addr = CString::new(addr).unwrap();
+        let mut short_id = [0_u8; ids::SHORT_ID_LEN];
+        assert_eq!(
+            avax_address_to_short_id(c_addr.as_ptr(), short_id.as_mut_ptr()),
+            0
+        );
+        assert_eq!(
+            ids::ShortId::from_slice(&short_id).to_string(),
+            "6Y3kysjF9jnHnYkdS9yGAuoHyae2eNmeV"
+        );
+        let c_bad = CString::new("Z-custom18jma8ppw3nhx5r4ap8clazz0dps7rv5u9xde7p").unwrap();
+        assert_eq!(
+            avax_address_to_short_id(c_bad.as_ptr(), short_id.as_mut_ptr()),
+            -1
+        );
+    }
+}
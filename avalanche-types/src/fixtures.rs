@@ -0,0 +1,355 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Error, ErrorKind},
+};
+
+use crate::{
+    avax, avm, bls_key, cert, constants, genesis, genesis::coreth as coreth_genesis, ids,
+    platformvm, secp256k1fx, soft_key, units, utxo,
+};
+use utils::{hash, prefix};
+
+/// Genesis start time and the "now" of the generated txs (2022-01-01T00:00:00Z),
+/// so that the fixtures do not depend on the clock.
+pub const START_TIME: u64 = 1_640_995_200;
+
+/// Amount of each fixture UTXO.
+pub const UTXO_AMOUNT: u64 = 10 * units::KILO_AVAX;
+
+/// Stake of each fixture validator.
+pub const VALIDATOR_WEIGHT: u64 = 2 * units::KILO_AVAX;
+
+/// Staking period of each fixture validator, starting at "START_TIME".
+pub const VALIDATOR_DURATION: u64 = 30 * 24 * 60 * 60;
+
+/// Test data of a custom network generated from the seed, so that
+/// the same seed always regenerates the same keys, genesis, and txs.
+#[derive(Debug, Clone)]
+pub struct Fixtures {
+    pub seed: u64,
+    pub network_id: u32,
+    pub avax_asset_id: ids::Id,
+    pub x_chain_id: ids::Id,
+
+    pub keys: Vec<soft_key::Key>,
+    pub nodes: Vec<Node>,
+    /// Allocates to every key, with all the nodes as the initial stakers
+    /// rewarded to the first key.
+    pub genesis: genesis::Genesis,
+
+    /// One X-chain UTXO per key.
+    pub x_utxos: Vec<utxo::Utxo>,
+    /// One P-chain UTXO per node, owned by the key that stakes the node.
+    pub p_utxos: Vec<utxo::Utxo>,
+    /// X-chain base txs from each key to the next key,
+    /// then the P-chain add validator txs of each node.
+    pub txs: Vec<SignedTx>,
+}
+
+/// Staking identity of a fixture node.
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub node_id: ids::NodeId,
+    pub cert: cert::StakingCert,
+    pub bls_key: bls_key::Key,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SignedTx {
+    /// "X" or "P".
+    pub chain_alias: String,
+    pub tx_id: ids::Id,
+    pub bytes: Vec<u8>,
+}
+
+impl Fixtures {
+    /// Generates the fixtures of "keys" keys and "nodes" nodes
+    /// on the default custom network ID.
+    pub fn new(seed: u64, keys: usize, nodes: usize) -> io::Result<Self> {
+        if keys == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "fixtures require at least one key",
+            ));
+        }
+
+        let network_id = constants::DEFAULT_CUSTOM_NETWORK_ID;
+        let avax_asset_id = id(seed, "avax-asset-id", 0);
+        let x_chain_id = id(seed, "x-chain-id", 0);
+        let tx_fee = units::Avax::from_navax(units::MILLI_AVAX);
+
+        let mut ks: Vec<soft_key::Key> = Vec::new();
+        for i in 0..keys {
+            ks.push(key(seed, i as u32)?);
+        }
+        let mut ns: Vec<Node> = Vec::new();
+        for i in 0..nodes {
+            let cert = staking_cert(seed, i as u32)?;
+            ns.push(Node {
+                node_id: cert.node_id,
+                cert,
+                bls_key: bls_key(seed, i as u32)?,
+            });
+        }
+        let genesis = new_genesis(network_id, &ks, &ns)?;
+
+        let utxo = |label: &str, i: usize, owner: &soft_key::Key| utxo::Utxo {
+            utxo_id: avax::UtxoId::new(id(seed, label, i as u32).as_bytes(), 0, false),
+            asset_id: avax_asset_id,
+            transfer_output: Some(secp256k1fx::TransferOutput::new(
+                UTXO_AMOUNT,
+                secp256k1fx::OutputOwners::new(0, 1, &[owner.short_address]),
+            )),
+            ..utxo::Utxo::default()
+        };
+        let x_utxos: Vec<utxo::Utxo> = ks
+            .iter()
+            .enumerate()
+            .map(|(i, k)| utxo("x-utxo", i, k))
+            .collect();
+        let p_utxos: Vec<utxo::Utxo> = (0..nodes)
+            .map(|i| utxo("p-utxo", i, &ks[i % keys]))
+            .collect();
+
+        let mut txs: Vec<SignedTx> = Vec::new();
+        for (i, k) in ks.iter().enumerate() {
+            let to = &ks[(i + 1) % keys];
+            let builder = avm::txs::base::Builder {
+                network_id,
+                blockchain_id: x_chain_id,
+                avax_asset_id,
+                tx_fee,
+                keychain: soft_key::Keychain::new(vec![k.clone()]),
+            };
+            let (mut tx, signers) = builder.new_base_tx(
+                &x_utxos[i..=i],
+                vec![avax::TransferableOutput {
+                    asset_id: avax_asset_id,
+                    transfer_output: Some(secp256k1fx::TransferOutput::new(
+                        units::AVAX,
+                        secp256k1fx::OutputOwners::new(0, 1, &[to.short_address]),
+                    )),
+                    ..avax::TransferableOutput::default()
+                }],
+                &k.short_address,
+                START_TIME,
+            )?;
+            let (bytes, tx_id) = tx.sign(&signers)?;
+            txs.push(SignedTx {
+                chain_alias: String::from("X"),
+                tx_id,
+                bytes,
+            });
+        }
+        for (i, n) in ns.iter().enumerate() {
+            let k = &ks[i % keys];
+            let builder = platformvm::txs::add_validator::Builder {
+                network_id,
+                avax_asset_id,
+                tx_fee,
+                keychain: soft_key::Keychain::new(vec![k.clone()]),
+            };
+            let (mut tx, signers) = builder.new_add_validator_tx(
+                &p_utxos[i..=i],
+                platformvm::Validator {
+                    node_id: n.node_id.short_id(),
+                    start: START_TIME,
+                    end: START_TIME + VALIDATOR_DURATION,
+                    weight: VALIDATOR_WEIGHT,
+                },
+                secp256k1fx::OutputOwners::new(0, 1, &[k.short_address]),
+                genesis::DEFAULT_DELEGATION_FEE,
+                &k.short_address,
+                START_TIME,
+            )?;
+            let (bytes, tx_id) = tx.sign(&signers)?;
+            txs.push(SignedTx {
+                chain_alias: String::from("P"),
+                tx_id,
+                bytes,
+            });
+        }
+
+        Ok(Self {
+            seed,
+            network_id,
+            avax_asset_id,
+            x_chain_id,
+            keys: ks,
+            nodes: ns,
+            genesis,
+            x_utxos,
+            p_utxos,
+            txs,
+        })
+    }
+}
+
+/// Derives the 32 bytes of the label and the index from the seed.
+/// Bump "attempt" to derive another value of the same label and index
+/// (e.g., when the bytes are not a valid secret key).
+pub fn derive_bytes(seed: u64, label: &str, index: u32, attempt: u32) -> [u8; 32] {
+    let mut b: Vec<u8> = Vec::new();
+    b.extend_from_slice(b"avalanche-ops/fixtures/");
+    b.extend_from_slice(label.as_bytes());
+    b.extend_from_slice(&seed.to_be_bytes());
+    b.extend_from_slice(&index.to_be_bytes());
+    b.extend_from_slice(&attempt.to_be_bytes());
+
+    let mut d = [0u8; 32];
+    d.copy_from_slice(&hash::compute_sha256(&b));
+    d
+}
+
+/// Derives the ID of the label and the index from the seed.
+pub fn id(seed: u64, label: &str, index: u32) -> ids::Id {
+    ids::Id::from_slice(&derive_bytes(seed, label, index, 0))
+}
+
+/// Derives the Secp256k1 key of the index from the seed.
+pub fn key(seed: u64, index: u32) -> io::Result<soft_key::Key> {
+    derive_secret(
+        seed,
+        "secp256k1",
+        index,
+        soft_key::Key::from_private_key_raw,
+    )
+}
+
+/// Derives the BLS key of the index from the seed.
+pub fn bls_key(seed: u64, index: u32) -> io::Result<bls_key::Key> {
+    derive_secret(seed, "bls", index, bls_key::Key::from_bytes)
+}
+
+/// Derives the staking certificate of the index from the seed,
+/// so the node ID is the same across the runs.
+pub fn staking_cert(seed: u64, index: u32) -> io::Result<cert::StakingCert> {
+    derive_secret(seed, "staking", index, cert::generate_pem_from_secret)
+}
+
+/// Retries with the next attempt until the derived bytes are a valid secret key,
+/// which fails with the negligible probability (e.g., zero or not less than the curve order).
+fn derive_secret<T, F>(seed: u64, label: &str, index: u32, f: F) -> io::Result<T>
+where
+    F: Fn(&[u8]) -> io::Result<T>,
+{
+    let mut attempt = 0;
+    loop {
+        match f(&derive_bytes(seed, label, index, attempt)) {
+            Ok(v) => return Ok(v),
+            Err(e) if attempt >= 16 => return Err(e),
+            Err(_) => attempt += 1,
+        }
+    }
+}
+
+/// Same as "genesis::Genesis::new" but with the given keys and stakers,
+/// and the fixed start time.
+fn new_genesis(
+    network_id: u32,
+    keys: &[soft_key::Key],
+    nodes: &[Node],
+) -> io::Result<genesis::Genesis> {
+    let mut allocations: Vec<genesis::Allocation> = Vec::new();
+    let mut c_chain_allocs = BTreeMap::new();
+    let mut x_addresses: Vec<String> = Vec::new();
+    for k in keys.iter() {
+        let info = k.info(network_id)?;
+
+        let mut alloc = genesis::Allocation::default();
+        alloc.eth_addr = Some(info.eth_address.clone());
+        alloc.avax_addr = Some(info.x_address.clone());
+        allocations.push(alloc);
+
+        c_chain_allocs.insert(
+            String::from(prefix::strip_0x(&info.eth_address)),
+            coreth_genesis::AllocAccount::default(),
+        );
+        x_addresses.push(info.x_address);
+    }
+
+    let initial_stakers: Vec<genesis::Staker> = nodes
+        .iter()
+        .map(|n| genesis::Staker {
+            node_id: Some(n.node_id.to_string()),
+            reward_address: Some(x_addresses[0].clone()),
+            ..genesis::Staker::default()
+        })
+        .collect();
+
+    let mut c_chain_genesis = coreth_genesis::Genesis::default();
+    c_chain_genesis.alloc = Some(c_chain_allocs);
+    Ok(genesis::Genesis {
+        network_id,
+        allocations: Some(allocations),
+        start_time: Some(START_TIME),
+        initial_staked_funds: Some(vec![x_addresses[0].clone()]),
+        initial_stakers: if initial_stakers.is_empty() {
+            None
+        } else {
+            Some(initial_stakers)
+        },
+        c_chain_genesis,
+        ..Default::default()
+    })
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- fixtures::test_fixtures --exact --show-output
+#[test]
+fn test_fixtures() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let fixtures = Fixtures::new(1, 3, 2).unwrap();
+    assert_eq!(fixtures.keys.len(), 3);
+    assert_eq!(fixtures.nodes.len(), 2);
+    assert_ne!(fixtures.nodes[0].node_id, fixtures.nodes[1].node_id);
+    assert_eq!(fixtures.genesis.initial_stakers.as_ref().unwrap().len(), 2);
+    assert_eq!(fixtures.genesis.start_time, Some(START_TIME));
+
+    // same seed regenerates the same data
+    let regenerated = Fixtures::new(1, 3, 2).unwrap();
+    assert_eq!(fixtures.keys, regenerated.keys);
+    assert_eq!(fixtures.genesis, regenerated.genesis);
+    assert_eq!(fixtures.txs, regenerated.txs);
+    assert_eq!(
+        fixtures.nodes[1].bls_key.public_key_bytes(),
+        regenerated.nodes[1].bls_key.public_key_bytes()
+    );
+    for (n, r) in fixtures.nodes.iter().zip(regenerated.nodes.iter()) {
+        assert_eq!(n.node_id, r.node_id);
+        assert_eq!(n.cert.cert_pem, r.cert.cert_pem);
+        assert_eq!(n.cert.key_pem, r.cert.key_pem);
+    }
+
+    let other = Fixtures::new(2, 3, 2).unwrap();
+    assert_ne!(fixtures.keys, other.keys);
+    assert_ne!(fixtures.txs, other.txs);
+    assert_ne!(fixtures.nodes[0].node_id, other.nodes[0].node_id);
+
+    // signed txs decode with their IDs
+    assert_eq!(fixtures.txs.len(), 5);
+    for tx in fixtures.txs.iter() {
+        let tx_id = match tx.chain_alias.as_str() {
+            "X" => avm::txs::Tx::from_bytes(&tx.bytes).unwrap().1,
+            _ => platformvm::txs::Tx::from_bytes(&tx.bytes).unwrap().1,
+        };
+        assert_eq!(tx_id, tx.tx_id);
+    }
+    match platformvm::txs::Tx::from_bytes(&fixtures.txs[3].bytes)
+        .unwrap()
+        .0
+    {
+        platformvm::txs::Tx::AddValidator(tx) => {
+            assert_eq!(tx.validator.node_id, fixtures.nodes[0].node_id.short_id());
+            assert_eq!(tx.validator.weight, VALIDATOR_WEIGHT);
+        }
+        tx => panic!("unexpected tx {}", tx.type_name()),
+    }
+
+    assert!(Fixtures::new(1, 0, 1).is_err());
+
+    // no limit on the number of nodes
+    let many = Fixtures::new(1, 1, 8).unwrap();
+    assert_eq!(many.genesis.initial_stakers.as_ref().unwrap().len(), 8);
+    assert_eq!(many.nodes[0].node_id, fixtures.nodes[0].node_id);
+}
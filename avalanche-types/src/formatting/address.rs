@@ -0,0 +1,266 @@
+use std::{
+    fmt,
+    io::{self, Error, ErrorKind},
+    str::FromStr,
+};
+
+use crate::{constants, formatting, ids};
+
+/// Chain whose alias prefixes the bech32 address (e.g., "X-avax1...").
+/// ref. https://docs.avax.network/quickstart/multisig-utxos-with-avalanchejs
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Chain {
+    X,
+    P,
+    C,
+}
+
+impl Chain {
+    pub fn alias(&self) -> &'static str {
+        match self {
+            Chain::X => "X",
+            Chain::P => "P",
+            Chain::C => "C",
+        }
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-chain", self.alias())
+    }
+}
+
+impl FromStr for Chain {
+    type Err = Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "X" => Ok(Chain::X),
+            "P" => Ok(Chain::P),
+            "C" => Ok(Chain::C),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("unknown chain alias '{}' (expected 'X', 'P', or 'C')", s),
+            )),
+        }
+    }
+}
+
+/// Implements "constants.GetHRP".
+/// Returns "custom" for the network IDs without the well-known hrp.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/constants#GetHRP
+pub fn hrp(network_id: u32) -> &'static str {
+    match constants::NETWORK_ID_TO_HRP.get(&network_id) {
+        Some(v) => v,
+        None => constants::FALLBACK_HRP,
+    }
+}
+
+/// Bech32 address with its chain alias and "human readable part" (hrp).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Address {
+    pub chain: Chain,
+    pub hrp: String,
+    pub short_id: ids::ShortId,
+}
+
+impl Address {
+    pub fn new(chain: Chain, network_id: u32, short_id: ids::ShortId) -> Self {
+        Self {
+            chain,
+            hrp: hrp(network_id).to_string(),
+            short_id,
+        }
+    }
+
+    /// Returns the network ID of the hrp, or "None" for the "custom" hrp
+    /// that is shared by all custom networks.
+    pub fn network_id(&self) -> Option<u32> {
+        constants::HRP_TO_NETWORK_ID.get(self.hrp.as_str()).copied()
+    }
+
+    /// Errors if the address is not for the chain and the network,
+    /// so that funds are not sent to an address on the wrong chain.
+    pub fn validate(&self, chain: Chain, network_id: u32) -> io::Result<()> {
+        if self.chain != chain {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "address '{}' is for the {}, but the {} was expected (use '{}-{}' instead)",
+                    self,
+                    self.chain,
+                    chain,
+                    chain.alias(),
+                    self.bech32()?,
+                ),
+            ));
+        }
+        let expected_hrp = hrp(network_id);
+        if self.hrp != expected_hrp {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "address '{}' has hrp '{}', but network ID {} expects '{}'",
+                    self, self.hrp, network_id, expected_hrp
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the bech32 part of the address without the chain alias.
+    fn bech32(&self) -> io::Result<String> {
+        formatting::encode_bech32(&self.hrp, self.short_id.as_bytes())
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.bech32() {
+            Ok(s) => write!(f, "{}-{}", self.chain.alias(), s),
+            Err(_) => write!(f, "{}-{}?{}", self.chain.alias(), self.hrp, self.short_id),
+        }
+    }
+}
+
+impl FromStr for Address {
+    type Err = Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        decode(s)
+    }
+}
+
+/// Encodes the short ID as the chain-prefixed bech32 address
+/// with the hrp of the network (e.g., "P-fuji1...").
+pub fn encode(chain: Chain, network_id: u32, short_id: &ids::ShortId) -> io::Result<String> {
+    formatting::address(chain.alias(), hrp(network_id), short_id.as_bytes())
+}
+
+/// Decodes the chain-prefixed bech32 address.
+/// Unlike "formatting::parse_address", errors on the unknown chain alias,
+/// the unknown hrp, the mixed-case address, and the payload that is not a short ID.
+pub fn decode(addr: &str) -> io::Result<Address> {
+    let (alias, raw) = match addr.split_once('-') {
+        Some(v) => v,
+        None => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "address '{}' has no chain alias (expected e.g., 'X-{}')",
+                    addr, addr
+                ),
+            ));
+        }
+    };
+    let chain = Chain::from_str(alias)?;
+
+    if raw.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("address '{}' must be lowercase", addr),
+        ));
+    }
+
+    let (hrp, d) = formatting::decode_bech32(raw)?;
+    if hrp != constants::FALLBACK_HRP && !constants::HRP_TO_NETWORK_ID.contains_key(hrp.as_str()) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("address '{}' has unknown hrp '{}'", addr, hrp),
+        ));
+    }
+    if d.len() != ids::SHORT_ID_LEN {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "address '{}' has unexpected length {} (expected {})",
+                addr,
+                d.len(),
+                ids::SHORT_ID_LEN
+            ),
+        ));
+    }
+
+    Ok(Address {
+        chain,
+        hrp,
+        short_id: ids::ShortId::from_slice(&d),
+    })
+}
+
+/// Decodes the address and errors if it is not for the chain and the network.
+pub fn decode_for(addr: &str, chain: Chain, network_id: u32) -> io::Result<ids::ShortId> {
+    let decoded = decode(addr)?;
+    decoded.validate(chain, network_id)?;
+    Ok(decoded.short_id)
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- formatting::address::test_address --exact --show-output
+#[test]
+fn test_address() {
+    assert_eq!(hrp(1), "avax");
+    assert_eq!(hrp(5), "fuji");
+    assert_eq!(hrp(12345), "local");
+    assert_eq!(hrp(constants::DEFAULT_CUSTOM_NETWORK_ID), "custom");
+
+    let addr = "X-custom18jma8ppw3nhx5r4ap8clazz0dps7rv5u9xde7p";
+    let decoded = decode(addr).unwrap();
+    assert_eq!(decoded.chain, Chain::X);
+    assert_eq!(decoded.hrp, "custom");
+    assert_eq!(decoded.network_id(), None);
+    assert_eq!(decoded.to_string(), addr);
+    assert_eq!(Address::from_str(addr).unwrap(), decoded);
+
+    let short_id = decoded.short_id;
+    assert_eq!(
+        encode(Chain::X, constants::DEFAULT_CUSTOM_NETWORK_ID, &short_id).unwrap(),
+        addr
+    );
+    assert_eq!(
+        encode(Chain::P, 12345, &short_id).unwrap(),
+        "P-local18jma8ppw3nhx5r4ap8clazz0dps7rv5u00z96u"
+    );
+    let local = Address::new(Chain::P, 12345, short_id);
+    assert_eq!(local.network_id(), Some(12345));
+    assert_eq!(
+        local.to_string(),
+        "P-local18jma8ppw3nhx5r4ap8clazz0dps7rv5u00z96u"
+    );
+
+    assert_eq!(
+        decode_for(addr, Chain::X, constants::DEFAULT_CUSTOM_NETWORK_ID).unwrap(),
+        short_id
+    );
+    let err = decode_for(addr, Chain::P, constants::DEFAULT_CUSTOM_NETWORK_ID).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("is for the X-chain, but the P-chain"));
+    assert!(err
+        .to_string()
+        .contains("P-custom18jma8ppw3nhx5r4ap8clazz0dps7rv5u9xde7p"));
+    let err = decode_for(addr, Chain::X, 1).unwrap_err();
+    assert!(err.to_string().contains("network ID 1 expects 'avax'"));
+
+    // no chain alias, unknown chain alias, mixed case, bad checksum
+    assert!(decode("custom18jma8ppw3nhx5r4ap8clazz0dps7rv5u9xde7p").is_err());
+    assert!(decode("Z-custom18jma8ppw3nhx5r4ap8clazz0dps7rv5u9xde7p").is_err());
+    assert!(decode("x-custom18jma8ppw3nhx5r4ap8clazz0dps7rv5u9xde7p").is_err());
+    assert!(decode("X-Custom18jma8ppw3nhx5r4ap8clazz0dps7rv5u9xde7p").is_err());
+    assert!(decode("X-custom18jma8ppw3nhx5r4ap8clazz0dps7rv5u9xde7q").is_err());
+
+    // unknown hrp, wrong length
+    let unknown_hrp = formatting::address("X", "foo", short_id.as_bytes()).unwrap();
+    assert!(decode(&unknown_hrp)
+        .unwrap_err()
+        .to_string()
+        .contains("unknown hrp 'foo'"));
+    let short = format!(
+        "X-{}",
+        formatting::encode_bech32("avax", &[0x01; 19]).unwrap()
+    );
+    assert!(decode(&short)
+        .unwrap_err()
+        .to_string()
+        .contains("unexpected length 19"));
+}
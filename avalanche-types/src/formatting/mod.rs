@@ -0,0 +1,455 @@
+use std::io::{self, Error, ErrorKind};
+
+use bech32::{FromBase32, ToBase32, Variant};
+
+use utils::hash;
+
+pub mod address;
+
+pub const CHECKSUM_LENGTH: usize = 4;
+
+/// Bitcoin base58 alphabet, as used by "formatting.CB58".
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+const BASE58_INVALID: u8 = 0xff;
+
+/// Maps each ASCII character to its base58 digit.
+const BASE58_DECODE_TABLE: [u8; 128] = {
+    let mut table = [BASE58_INVALID; 128];
+    let mut i = 0;
+    while i < BASE58_ALPHABET.len() {
+        table[BASE58_ALPHABET[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+};
+
+/// Encodes the bytes in base58, converting in place on a single digit
+/// buffer rather than dividing a big integer per digit.
+/// Each leading zero byte is encoded as '1'.
+pub fn encode_base58(d: &[u8]) -> String {
+    let zeros = d.iter().take_while(|b| **b == 0).count();
+
+    // log(256) / log(58) ~= 1.37 digits per byte, little-endian
+    let mut digits = vec![0u8; (d.len() - zeros) * 138 / 100 + 1];
+    let mut length = 0;
+    for b in d[zeros..].iter() {
+        let mut carry = *b as u32;
+        for digit in digits[..length].iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits[length] = (carry % 58) as u8;
+            length += 1;
+            carry /= 58;
+        }
+    }
+
+    let mut encoded = String::with_capacity(zeros + length);
+    encoded.extend(std::iter::repeat('1').take(zeros));
+    encoded.extend(
+        digits[..length]
+            .iter()
+            .rev()
+            .map(|v| BASE58_ALPHABET[*v as usize] as char),
+    );
+    encoded
+}
+
+/// Decodes the base58 string, the reverse of "encode_base58".
+pub fn decode_base58(s: &str) -> io::Result<Vec<u8>> {
+    let s = s.as_bytes();
+    let zeros = s.iter().take_while(|c| **c == b'1').count();
+
+    // log(58) / log(256) ~= 0.733 bytes per digit, little-endian
+    let mut bytes = vec![0u8; (s.len() - zeros) * 733 / 1000 + 1];
+    let mut length = 0;
+    for (i, c) in s.iter().enumerate().skip(zeros) {
+        let v = BASE58_DECODE_TABLE
+            .get(*c as usize)
+            .copied()
+            .unwrap_or(BASE58_INVALID);
+        if v == BASE58_INVALID {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid base58 character {:?} at {}", *c as char, i),
+            ));
+        }
+
+        let mut carry = v as u32;
+        for byte in bytes[..length].iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes[length] = carry as u8;
+            length += 1;
+            carry >>= 8;
+        }
+    }
+
+    let mut decoded = Vec::with_capacity(zeros + length);
+    decoded.resize(zeros, 0);
+    decoded.extend(bytes[..length].iter().rev());
+    Ok(decoded)
+}
+
+/// Returns the bytes with the checksum appended.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/hashing#Checksum
+fn append_checksum(d: &[u8]) -> Vec<u8> {
+    // "hashing.Checksum" of "sha256.Sum256"
+    let checksum = hash::compute_sha256(d);
+    let mut checked = Vec::with_capacity(d.len() + CHECKSUM_LENGTH);
+    checked.extend_from_slice(d);
+    checked.extend_from_slice(&checksum[checksum.len() - CHECKSUM_LENGTH..]);
+    checked
+}
+
+/// Verifies and strips the checksum at the end of the decoded bytes.
+fn strip_checksum(mut decoded: Vec<u8>) -> io::Result<Vec<u8>> {
+    if decoded.len() < CHECKSUM_LENGTH {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "decoded length {} is shorter than the checksum",
+                decoded.len()
+            ),
+        ));
+    }
+    let orig_length = decoded.len() - CHECKSUM_LENGTH;
+
+    // "hashing.Checksum" of "sha256.Sum256"
+    let orig_checksum = hash::compute_sha256(&decoded[..orig_length]);
+    let orig_checksum = &orig_checksum[orig_checksum.len() - CHECKSUM_LENGTH..];
+    let checksum = &decoded[orig_length..];
+    if checksum != orig_checksum {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid checksum {:?} != {:?}", checksum, orig_checksum),
+        ));
+    }
+
+    decoded.truncate(orig_length);
+    Ok(decoded)
+}
+
+/// Implements "formatting.EncodeWithChecksum" with "formatting.CB58".
+/// "ids.ShortID.String" appends checksum to the digest bytes.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/formatting#EncodeWithChecksum
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/hashing#Checksum
+pub fn encode_cb58_with_checksum(d: &[u8]) -> String {
+    // ref. "utils/formatting encode.CB58"
+    encode_base58(&append_checksum(d))
+}
+
+/// Implements "formatting.Decode" with "formatting.CB58".
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/formatting#Decode
+pub fn decode_cb58_with_checksum(d: &str) -> io::Result<Vec<u8>> {
+    strip_checksum(decode_base58(d)?)
+}
+
+/// Implements "formatting.Encode" with "formatting.Hex",
+/// the "0x"-prefixed hex with the checksum.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/formatting#Encode
+pub fn encode_hex_with_checksum(d: &[u8]) -> String {
+    let checked = append_checksum(d);
+    let mut encoded = vec![0u8; 2 + checked.len() * 2];
+    encoded[..2].copy_from_slice(b"0x");
+    hex::encode_to_slice(&checked, &mut encoded[2..]).expect("unexpected hex buffer length");
+    String::from_utf8(encoded).expect("unexpected non-UTF-8 hex")
+}
+
+/// Implements "formatting.Decode" with "formatting.Hex".
+/// The "0x" prefix is required ("errMissingHexPrefix").
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/formatting#Decode
+pub fn decode_hex_with_checksum(d: &[u8]) -> io::Result<Vec<u8>> {
+    let d = d
+        .strip_prefix(b"0x")
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "missing 0x prefix to hex encoding"))?;
+    let decoded = match hex::decode(d) {
+        Ok(v) => v,
+        Err(e) => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("failed to decode hex ({})", e),
+            ));
+        }
+    };
+    strip_checksum(decoded)
+}
+
+/// Serializes the bytes as "0x"-prefixed hex without the checksum
+/// (e.g., the BLS keys of "info.getNodeID"), for "#[serde(with = ...)]".
+/// Deserializes the hex with or without the "0x" prefix.
+pub mod serde_hex_format {
+    use serde::{self, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(b: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format!("0x{}", hex::encode(b)))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(s.trim_start_matches("0x")).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Maximum bytes that avalanchego encodes in CB58 ("errEncodingOverFlow"),
+/// so the node never returns the longer CB58 strings.
+/// ref. "avalanchego/utils/formatting.maxCB58EncodeSize"
+pub const MAX_CB58_DECODED_LEN: usize = 16 * 1024;
+
+/// Maximum bytes decoded from the untrusted hex input (e.g., the node API responses),
+/// since no response is larger than the max message size.
+pub const MAX_HEX_DECODED_LEN: usize = crate::message::DEFAULT_MAX_MESSAGE_SIZE;
+
+/// Same as "decode_cb58_with_checksum" but for the untrusted input,
+/// rejecting the input longer than any encoding of "MAX_CB58_DECODED_LEN" bytes
+/// before decoding, since the base58 decoding is quadratic in the input length.
+pub fn decode_cb58_untrusted(d: &str) -> io::Result<Vec<u8>> {
+    // at most ~1.37 digits per byte, and one digit per leading zero byte
+    let max_len = (MAX_CB58_DECODED_LEN + CHECKSUM_LENGTH) * 138 / 100 + 1;
+    if d.len() > max_len {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("cb58 input length {} exceeds {}", d.len(), max_len),
+        ));
+    }
+    let decoded = decode_cb58_with_checksum(d)?;
+    if decoded.len() > MAX_CB58_DECODED_LEN {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "cb58 decoded length {} exceeds {}",
+                decoded.len(),
+                MAX_CB58_DECODED_LEN
+            ),
+        ));
+    }
+    Ok(decoded)
+}
+
+/// Same as "decode_hex_with_checksum" but for the untrusted input,
+/// rejecting the input that decodes to more than "MAX_HEX_DECODED_LEN" bytes
+/// before decoding.
+pub fn decode_hex_untrusted(d: &[u8]) -> io::Result<Vec<u8>> {
+    let max_len = 2 + (MAX_HEX_DECODED_LEN + CHECKSUM_LENGTH) * 2;
+    if d.len() > max_len {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("hex input length {} exceeds {}", d.len(), max_len),
+        ));
+    }
+    decode_hex_with_checksum(d)
+}
+
+/// Decodes the untrusted "0x"-prefixed hex or CB58 with the checksum,
+/// as returned by the node APIs in either encoding.
+pub fn decode_untrusted(d: &str) -> io::Result<Vec<u8>> {
+    if d.starts_with("0x") {
+        decode_hex_untrusted(d.as_bytes())
+    } else {
+        decode_cb58_untrusted(d)
+    }
+}
+
+/// Implements "formatting.FormatAddress/FormatBech32".
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/formatting#FormatAddress
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/formatting#FormatBech32
+pub fn address(chain_id_alias: &str, hrp: &str, d: &[u8]) -> io::Result<String> {
+    assert_eq!(d.len(), 20);
+    let encoded = encode_bech32(hrp, d)?;
+    Ok(format!("{}-{}", chain_id_alias, encoded))
+}
+
+/// Implements "formatting.ParseAddress".
+/// Returns the chain ID alias, the "human readable part" (hrp), and the decoded bytes.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/formatting#ParseAddress
+pub fn parse_address(addr: &str) -> io::Result<(String, String, Vec<u8>)> {
+    let (chain_id_alias, raw) = match addr.split_once('-') {
+        Some(v) => v,
+        None => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("no separator found in address '{}'", addr),
+            ));
+        }
+    };
+    let (hrp, d) = decode_bech32(raw)?;
+    Ok((chain_id_alias.to_string(), hrp, d))
+}
+
+/// Implements "formatting.FormatBech32".
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/formatting#FormatBech32
+pub fn encode_bech32(hrp: &str, d: &[u8]) -> io::Result<String> {
+    // No need to call "bech32.ConvertBits(payload, 8, 5, true)"
+    // ".to_base32()" already does "bech32::convert_bits(d, 8, 5, true)"
+    match bech32::encode(hrp, d.to_base32(), Variant::Bech32) {
+        Ok(enc) => Ok(enc),
+        Err(e) => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("failed bech32::encode {}", e),
+        )),
+    }
+}
+
+/// Implements "formatting.ParseBech32".
+/// Returns the "human readable part" (hrp) and the decoded bytes.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/formatting#ParseBech32
+pub fn decode_bech32(s: &str) -> io::Result<(String, Vec<u8>)> {
+    let (hrp, d, _variant) = match bech32::decode(s) {
+        Ok(v) => v,
+        Err(e) => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("failed bech32::decode '{}' ({})", s, e),
+            ));
+        }
+    };
+
+    // ref. "bech32.ConvertBits(decoded, 5, 8, false)"
+    let d = match Vec::<u8>::from_base32(&d) {
+        Ok(v) => v,
+        Err(e) => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("failed to convert base32 to bytes ({})", e),
+            ));
+        }
+    };
+    Ok((hrp, d))
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- formatting::test_parse_address --exact --show-output
+#[test]
+fn test_parse_address() {
+    let d: Vec<u8> = vec![
+        0x3d, 0x0a, 0xd1, 0x2b, 0x8e, 0xe8, 0x92, 0x8e, 0xdf, 0x24, //
+        0x8c, 0xa9, 0x1c, 0xa5, 0x56, 0x00, 0xfb, 0x38, 0x3f, 0x07, //
+    ];
+    let addr = address("X", "avax", &d).unwrap();
+    assert!(addr.starts_with("X-avax1"));
+
+    let (chain_id_alias, hrp, decoded) = parse_address(&addr).unwrap();
+    assert_eq!(chain_id_alias, "X");
+    assert_eq!(hrp, "avax");
+    assert_eq!(decoded, d);
+
+    assert!(parse_address("avax1invalid").is_err());
+    assert!(parse_address("X-avax1invalid").is_err());
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- formatting::test_encode_c58_with_checksum --exact --show-output
+#[test]
+fn test_encode_c58_with_checksum() {
+    // ref. https://github.com/ava-labs/avalanchego/blob/v1.7.5/utils/formatting/encoding_test.go#L71
+    let d: Vec<u8> = Vec::new();
+    let hashed = encode_cb58_with_checksum(&d);
+    assert_eq!(hashed, "45PJLL");
+    let decoded = decode_cb58_with_checksum(&hashed).unwrap();
+    assert_eq!(d, decoded);
+
+    let d: Vec<u8> = vec![0];
+    let hashed = encode_cb58_with_checksum(&d);
+    assert_eq!(hashed, "1c7hwa");
+    let decoded = decode_cb58_with_checksum(&hashed).unwrap();
+    assert_eq!(d, decoded);
+
+    let d: Vec<u8> = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 255];
+    let hashed = encode_cb58_with_checksum(&d);
+    assert_eq!(hashed, "1NVSVezva3bAtJesnUj");
+    let decoded = decode_cb58_with_checksum(&hashed).unwrap();
+    assert_eq!(d, decoded);
+
+    let d: Vec<u8> = vec![
+        1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+        26, 27, 28, 29, 30, 31, 32,
+    ];
+    let hashed = encode_cb58_with_checksum(&d);
+    assert_eq!(hashed, "SkB92YpWm4Q2ijQHH34cqbKkCZWszsiQgHVjtNeFF2HdvDQU");
+    let decoded = decode_cb58_with_checksum(&hashed).unwrap();
+    assert_eq!(d, decoded);
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- formatting::test_base58 --exact --show-output
+#[test]
+fn test_base58() {
+    use bitcoin::util::base58;
+
+    // compare against the reference implementation
+    let mut d: Vec<u8> = Vec::new();
+    for i in 0..300_u32 {
+        let encoded = encode_base58(&d);
+        assert_eq!(encoded, base58::encode_slice(&d));
+        assert_eq!(decode_base58(&encoded).unwrap(), d);
+        // leading zeros in every 7th input
+        d.push(if i % 7 == 0 { 0 } else { (i * 31 % 256) as u8 });
+    }
+
+    assert_eq!(encode_base58(&[]), "");
+    assert_eq!(encode_base58(&[0, 0]), "11");
+    assert_eq!(decode_base58("11").unwrap(), vec![0, 0]);
+    assert!(decode_base58("0OIl").is_err());
+    assert!(decode_base58("abc\u{e9}").is_err());
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- formatting::test_hex_with_checksum --exact --show-output
+#[test]
+fn test_hex_with_checksum() {
+    // ref. https://github.com/ava-labs/avalanchego/blob/v1.7.5/utils/formatting/encoding_test.go
+    let d: Vec<u8> = Vec::new();
+    let encoded = encode_hex_with_checksum(&d);
+    assert_eq!(encoded, "0x7852b855");
+    assert_eq!(decode_hex_with_checksum(encoded.as_bytes()).unwrap(), d);
+
+    let d: Vec<u8> = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 255];
+    let encoded = encode_hex_with_checksum(&d);
+    assert_eq!(encoded, "0x00010203040506070809ff4482539c");
+    assert_eq!(decode_hex_with_checksum(encoded.as_bytes()).unwrap(), d);
+    // without the prefix
+    assert!(decode_hex_with_checksum(&encoded.as_bytes()[2..]).is_err());
+    assert!(decode_hex_untrusted(&encoded.as_bytes()[2..]).is_err());
+
+    assert!(decode_hex_with_checksum(b"0x00010203").is_err());
+    assert!(decode_hex_with_checksum(b"0x00").is_err());
+    assert!(decode_cb58_with_checksum("1").is_err());
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- formatting::test_decode_untrusted --exact --show-output
+#[test]
+fn test_decode_untrusted() {
+    let d: Vec<u8> = (0..=255).collect();
+    let cb58 = encode_cb58_with_checksum(&d);
+    let hex = encode_hex_with_checksum(&d);
+    assert_eq!(decode_cb58_untrusted(&cb58).unwrap(), d);
+    assert_eq!(decode_hex_untrusted(hex.as_bytes()).unwrap(), d);
+    assert_eq!(decode_untrusted(&cb58).unwrap(), d);
+    assert_eq!(decode_untrusted(&hex).unwrap(), d);
+
+    // longest CB58 that avalanchego encodes, with the leading zeros and the largest digits
+    let mut d = vec![0xff; MAX_CB58_DECODED_LEN];
+    d[..8].copy_from_slice(&[0; 8]);
+    assert_eq!(
+        decode_cb58_untrusted(&encode_cb58_with_checksum(&d)).unwrap(),
+        d
+    );
+    d.push(0xff);
+    assert!(decode_cb58_untrusted(&encode_cb58_with_checksum(&d)).is_err());
+    assert!(decode_cb58_untrusted(&"z".repeat(1024 * 1024)).is_err());
+
+    let too_long = format!(
+        "0x{}",
+        "00".repeat(MAX_HEX_DECODED_LEN + CHECKSUM_LENGTH + 1)
+    );
+    assert!(decode_hex_untrusted(too_long.as_bytes()).is_err());
+    assert!(decode_untrusted("").is_err());
+}
@@ -12,7 +12,7 @@ use std::{
 use log::info;
 use serde::{Deserialize, Serialize};
 
-use crate::{constants, genesis::coreth as coreth_genesis, soft_key};
+use crate::{constants, genesis::coreth as coreth_genesis, soft_key, units};
 use utils::prefix;
 
 /// Represents Avalanche network genesis configuration.
@@ -270,26 +270,17 @@ pub struct Allocation {
     #[serde(rename = "ethAddr", skip_serializing_if = "Option::is_none")]
     pub eth_addr: Option<String>,
     /// Initially allocated amount for X-chain.
-    /// On the X-Chain, one AVAX is 10^9  units.
-    /// On the P-Chain, one AVAX is 10^9  units.
-    /// On the C-Chain, one AVAX is 10^18 units.
     #[serde(rename = "initialAmount", skip_serializing_if = "Option::is_none")]
-    pub initial_amount: Option<u64>,
+    pub initial_amount: Option<units::Avax>,
     #[serde(rename = "unlockSchedule", skip_serializing_if = "Option::is_none")]
     pub unlock_schedule: Option<Vec<LockedAmount>>,
 }
 
-/// On the X-Chain, one AVAX is 10^9  units.
-/// On the P-Chain, one AVAX is 10^9  units.
-/// On the C-Chain, one AVAX is 10^18 units.
 /// 300,000,000 AVAX.
-pub const DEFAULT_INITIAL_AMOUNT_X_CHAIN: u64 = 300000000000000000;
+pub const DEFAULT_INITIAL_AMOUNT_X_CHAIN: units::Avax = units::Avax::from_navax(300000000000000000);
 
-/// On the X-Chain, one AVAX is 10^9  units.
-/// On the P-Chain, one AVAX is 10^9  units.
-/// On the C-Chain, one AVAX is 10^18 units.
 /// 200,000,000 AVAX.
-pub const DEFAULT_LOCKED_AMOUNT_P_CHAIN: u64 = 200000000000000000;
+pub const DEFAULT_LOCKED_AMOUNT_P_CHAIN: units::Avax = units::Avax::from_navax(200000000000000000);
 
 impl Default for Allocation {
     fn default() -> Self {
@@ -314,11 +305,8 @@ impl Allocation {
 pub struct LockedAmount {
     /// P-chain amount to lock for the duration of "locktime"
     /// in addition to the initial amount.
-    /// On the X-Chain, one AVAX is 10^9  units.
-    /// On the P-Chain, one AVAX is 10^9  units.
-    /// On the C-Chain, one AVAX is 10^18 units.
     #[serde(rename = "amount", skip_serializing_if = "Option::is_none")]
-    pub amount: Option<u64>,
+    pub amount: Option<units::Avax>,
     /// Unix timestamp to unlock the "amount".
     #[serde(rename = "locktime", skip_serializing_if = "Option::is_none")]
     pub locktime: Option<u64>,
@@ -414,9 +402,9 @@ fn test_genesis() {
                 avax_addr: Some(String::from(
                     "X-custom1g65uqn6t77p656w64023nh8nd9updzmxwd59gh",
                 )),
-                initial_amount: Some(0),
+                initial_amount: Some(units::Avax::from_navax(0)),
                 unlock_schedule: Some(vec![LockedAmount {
-                    amount: Some(10000000000000000),
+                    amount: Some(units::Avax::from_navax(10000000000000000)),
                     locktime: Some(1633824000),
                 }]),
             },
@@ -425,14 +413,14 @@ fn test_genesis() {
                 avax_addr: Some(String::from(
                     "X-custom18jma8ppw3nhx5r4ap8clazz0dps7rv5u9xde7p",
                 )),
-                initial_amount: Some(300000000000000000),
+                initial_amount: Some(units::Avax::from_navax(300000000000000000)),
                 unlock_schedule: Some(vec![
                     LockedAmount {
-                        amount: Some(20000000000000000),
+                        amount: Some(units::Avax::from_navax(20000000000000000)),
                         locktime: None,
                     },
                     LockedAmount {
-                        amount: Some(10000000000000000),
+                        amount: Some(units::Avax::from_navax(10000000000000000)),
                         locktime: Some(1633824000),
                     },
                 ]),
@@ -442,9 +430,9 @@ fn test_genesis() {
                 avax_addr: Some(String::from(
                     "X-custom16045mxr3s2cjycqe2xfluk304xv3ezhkhsvkpr",
                 )),
-                initial_amount: Some(10000000000000000),
+                initial_amount: Some(units::Avax::from_navax(10000000000000000)),
                 unlock_schedule: Some(vec![LockedAmount {
-                    amount: Some(10000000000000000),
+                    amount: Some(units::Avax::from_navax(10000000000000000)),
                     locktime: Some(1633824000),
                 }]),
             },
@@ -1,16 +1,15 @@
 use std::{
     cmp::Ordering,
-    fmt,
-    fs::File,
-    hash::{Hash, Hasher},
+    collections::{BTreeSet, HashMap},
+    fmt, fs,
     io::{self, BufReader, Error, ErrorKind},
     path::Path,
     str::FromStr,
     string::String,
 };
 
-use lazy_static::lazy_static;
 use log::{info, warn};
+use rand_core::{CryptoRng, OsRng, RngCore};
 use rustls_pemfile::{read_one, Item};
 use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
 
@@ -23,16 +22,11 @@ pub const SHORT_ID_LEN: usize = 20;
 pub const NODE_ID_LEN: usize = 20;
 pub const NODE_ID_ENCODE_PREFIX: &str = "NodeID-";
 
-lazy_static! {
-    static ref EMPTY: Vec<u8> = vec![0; ID_LEN];
-    static ref SHORT_EMPTY: Vec<u8> = vec![0; SHORT_ID_LEN];
-    static ref NODE_ID_EMPTY: Vec<u8> = vec![0; NODE_ID_LEN];
-}
-
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/ids#ID
-#[derive(Debug, Deserialize, Clone, Eq)]
+/// Backed by a fixed-size array, so it is "Copy" and never heap-allocates.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Id {
-    pub d: Vec<u8>,
+    pub d: [u8; ID_LEN],
 }
 
 impl Default for Id {
@@ -42,27 +36,96 @@ impl Default for Id {
 }
 
 impl Id {
-    pub fn default() -> Self {
-        Self { d: EMPTY.to_vec() }
+    pub const fn default() -> Self {
+        Self::empty()
     }
 
-    pub fn empty() -> Self {
-        Self { d: EMPTY.to_vec() }
+    pub const fn empty() -> Self {
+        Self { d: [0; ID_LEN] }
+    }
+
+    pub const fn from_array(d: [u8; ID_LEN]) -> Self {
+        Self { d }
     }
 
     pub fn is_empty(&self) -> bool {
         (*self) == Self::empty()
     }
 
+    /// Generates a random ID by filling all ID_LEN bytes from the
+    /// cryptographically secure RNG, without any data-dependent branches.
+    pub fn from_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut id = Self::empty();
+        rng.fill_bytes(&mut id.d);
+        id
+    }
+
     /// If the passed array is shorter than the ID_LEN,
     /// it fills in with zero.
     pub fn from_slice(d: &[u8]) -> Self {
         assert!(d.len() <= ID_LEN);
-        let mut d: Vec<u8> = Vec::from(d);
-        if d.len() < ID_LEN {
-            d.resize(ID_LEN, 0);
+        let mut id = Self::empty();
+        id.d[..d.len()].copy_from_slice(d);
+        id
+    }
+
+    /// Returns the VM ID of the VM name, which is the name bytes
+    /// padded with zeros (e.g., "subnetevm" is "srEXiWaHuhNyGwPUi444Tu47ZEDwxTWrbQiuD7FmgSAQ6X7Dy").
+    /// ref. https://github.com/ava-labs/subnet-cli/blob/main/cmd/vm_id.go
+    pub fn from_vm_name(name: &str) -> io::Result<Self> {
+        if name.is_empty() || name.len() > ID_LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("VM name '{}' must be 1-{} bytes", name, ID_LEN),
+            ));
         }
-        Self { d }
+        Ok(Self::from_slice(name.as_bytes()))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.d
+    }
+
+    /// Returns the hex-encoded ID without the "0x" prefix.
+    /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/ids#ID.Hex
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.d)
+    }
+
+    /// Parses the ID with its encoding auto-detected:
+    /// "0x"-prefixed hex (as used by C-chain tooling, with or without
+    /// the 4-byte checksum of "formatting.Hex"), plain hex, or CB58.
+    /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/formatting#Decode
+    pub fn from_any_str(s: &str) -> io::Result<Self> {
+        let s = s.trim();
+        if let Some(h) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            return Self::from_hex(h);
+        }
+        if s.len() == ID_LEN * 2 && s.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Self::from_hex(s);
+        }
+        Self::from_str(s)
+    }
+
+    /// Decodes the hex-encoded ID (without the "0x" prefix).
+    /// The 4-byte checksum is verified if present.
+    pub fn from_hex(h: &str) -> io::Result<Self> {
+        let decoded = if h.len() == ID_LEN * 2 {
+            hex::decode(h).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("failed to decode hex '{}' ({})", h, e),
+                )
+            })?
+        } else if h.len() == (ID_LEN + formatting::CHECKSUM_LENGTH) * 2 {
+            formatting::decode_hex_with_checksum(format!("0x{}", h).as_bytes())?
+        } else {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("unexpected hex-encoded ID length {}", h.len()),
+            ));
+        };
+        Ok(Self::from_slice(&decoded))
     }
 
     /// ref. "ids.ID.Prefix(output_index)"
@@ -78,6 +141,24 @@ impl Id {
         let d = hash::compute_sha256(&b);
         Self::from_slice(&d)
     }
+
+    /// Returns the bitwise XOR of the two IDs, which is their distance
+    /// in the ID space (e.g., for the peer sampling).
+    /// ref. "avalanchego/ids.ID.XOR"
+    pub fn xor(&self, other: &Id) -> Self {
+        let mut d = self.d;
+        for (b, o) in d.iter_mut().zip(other.d.iter()) {
+            *b ^= o;
+        }
+        Self { d }
+    }
+
+    /// Returns the "i"-th bit of the ID, where the bits of each byte
+    /// are indexed from the least significant one.
+    /// ref. "avalanchego/ids.ID.Bit"
+    pub fn bit(&self, i: usize) -> u8 {
+        (self.d[i / 8] >> (i % 8)) & 1
+    }
 }
 
 /// ref. https://doc.rust-lang.org/std/string/trait.ToString.html
@@ -100,6 +181,17 @@ impl FromStr for Id {
                 format!("failed decode_cb58_with_checksum '{}'", e),
             )
         })?;
+        if decoded.len() > ID_LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'{}' decodes to {} bytes (expected at most {})",
+                    s,
+                    decoded.len(),
+                    ID_LEN
+                ),
+            ));
+        }
         Ok(Self::from_slice(&decoded))
     }
 }
@@ -114,12 +206,23 @@ impl Serialize for Id {
     }
 }
 
+/// Accepts any encoding of "Id::from_any_str", so the IDs copied from
+/// the EVM explorers (e.g., the "0x"-prefixed C-chain hashes) parse as well.
+impl<'de> Deserialize<'de> for Id {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        fmt_id(deserializer)
+    }
+}
+
 fn fmt_id<'de, D>(deserializer: D) -> Result<Id, D::Error>
 where
     D: Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    Id::from_str(&s).map_err(serde::de::Error::custom)
+    Id::from_any_str(&s).map_err(serde::de::Error::custom)
 }
 
 pub fn deserialize_id<'de, D>(deserializer: D) -> Result<Option<Id>, D::Error>
@@ -145,6 +248,29 @@ where
     }
 }
 
+/// Serializes the ID as "0x"-prefixed hex without the checksum
+/// (e.g., the C-chain block and tx hashes), for "#[serde(with = ...)]".
+/// Deserializes any encoding of "Id::from_any_str".
+pub mod serde_hex_format {
+    use serde::{self, Deserializer, Serializer};
+
+    use super::Id;
+
+    pub fn serialize<S>(id: &Id, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format!("0x{}", id.to_hex()))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Id, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::fmt_id(deserializer)
+    }
+}
+
 /// RUST_LOG=debug cargo test --package avalanche-types --lib -- ids::test_id --exact --show-output
 /// ref. "avalanchego/ids.TestIDMarshalJSON"
 #[test]
@@ -173,40 +299,302 @@ fn test_id() {
     assert_eq!(id, id_from_str);
 }
 
-impl Ord for Id {
-    fn cmp(&self, other: &Id) -> Ordering {
-        self.d.cmp(&(other.d))
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- ids::test_id_from_vm_name --exact --show-output
+#[test]
+fn test_id_from_vm_name() {
+    assert_eq!(
+        Id::from_vm_name("subnetevm").unwrap().to_string(),
+        "srEXiWaHuhNyGwPUi444Tu47ZEDwxTWrbQiuD7FmgSAQ6X7Dy"
+    );
+    assert_eq!(
+        Id::from_vm_name("timestampvm").unwrap().to_string(),
+        "tGas3T58KzdjcJ2iKSyiYsWiqYctRXaPTqBCA11BqEkNg8kPc"
+    );
+    assert!(Id::from_vm_name("").is_err());
+    assert!(Id::from_vm_name(&"a".repeat(ID_LEN + 1)).is_err());
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- ids::test_id_from_any_str --exact --show-output
+#[test]
+fn test_id_from_any_str() {
+    let id = Id::from_str("TtF4d2QWbk5vzQGTEPrN48x6vwgAoAmKQ9cbp79inpQmcRKES").unwrap();
+    let h = "3d0ad12b8ee8928edf248ca91ca55600fb383f07c32bff1d6dec472b25cf59a7";
+    assert_eq!(id.to_hex(), h);
+
+    assert_eq!(Id::from_any_str(h).unwrap(), id);
+    assert_eq!(Id::from_any_str(&format!("0x{}", h)).unwrap(), id);
+    assert_eq!(
+        Id::from_any_str(&format!("0x{}", h.to_uppercase())).unwrap(),
+        id
+    );
+    assert_eq!(
+        Id::from_any_str("TtF4d2QWbk5vzQGTEPrN48x6vwgAoAmKQ9cbp79inpQmcRKES").unwrap(),
+        id
+    );
+
+    // "formatting.Hex" appends the last 4 bytes of sha256 as checksum
+    let checksum = hash::compute_sha256(&id.d);
+    let with_checksum = format!("0x{}{}", h, hex::encode(&checksum[28..]));
+    assert_eq!(Id::from_any_str(&with_checksum).unwrap(), id);
+    let bad_checksum = format!("0x{}00000000", h);
+    assert!(Id::from_any_str(&bad_checksum).is_err());
+
+    assert!(Id::from_any_str("0x1234").is_err());
+    assert!(Id::from_any_str("not-an-id").is_err());
+
+    // CB58 that decodes to more bytes than the ID is an error, not a panic
+    let too_long = formatting::encode_cb58_with_checksum(&[0x01; ID_LEN + 1]);
+    assert_eq!(
+        Id::from_any_str(&too_long).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+    assert!(serde_json::from_str::<Id>(&format!("\"{}\"", too_long)).is_err());
+    assert_eq!(
+        ShortId::from_str(&too_long).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        NodeId::from_str(&format!("{}{}", NODE_ID_ENCODE_PREFIX, too_long))
+            .unwrap_err()
+            .kind(),
+        ErrorKind::InvalidInput
+    );
+
+    // serializes in CB58, deserializes any encoding
+    let json = serde_json::to_string(&id).unwrap();
+    assert_eq!(
+        json,
+        "\"TtF4d2QWbk5vzQGTEPrN48x6vwgAoAmKQ9cbp79inpQmcRKES\""
+    );
+    assert_eq!(serde_json::from_str::<Id>(&json).unwrap(), id);
+    assert_eq!(
+        serde_json::from_str::<Id>(&format!("\"0x{}\"", h)).unwrap(),
+        id
+    );
+    assert!(serde_json::from_str::<Id>("\"0x1234\"").is_err());
+
+    #[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+    struct Hashes {
+        #[serde(with = "serde_hex_format")]
+        hash: Id,
     }
+    let hashes = Hashes { hash: id };
+    let json = serde_json::to_string(&hashes).unwrap();
+    assert_eq!(json, format!("{{\"hash\":\"0x{}\"}}", h));
+    assert_eq!(serde_json::from_str::<Hashes>(&json).unwrap(), hashes);
+    assert_eq!(
+        serde_json::from_str::<Hashes>(
+            r#"{"hash":"TtF4d2QWbk5vzQGTEPrN48x6vwgAoAmKQ9cbp79inpQmcRKES"}"#
+        )
+        .unwrap(),
+        hashes
+    );
 }
 
-impl PartialOrd for Id {
-    fn partial_cmp(&self, other: &Id) -> Option<Ordering> {
-        Some(self.cmp(other))
+#[derive(Debug, Clone, Default, Eq)]
+pub struct Ids(Vec<Id>);
+
+impl Ids {
+    pub fn new(ids: &[Id]) -> Self {
+        Ids(Vec::from(ids))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, id: &Id) -> bool {
+        self.0.contains(id)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Id> {
+        self.0.iter()
+    }
+
+    pub fn as_slice(&self) -> &[Id] {
+        &self.0
+    }
+
+    /// Returns the sorted IDs in either set, without duplicates.
+    /// ref. "avalanchego/utils/set.Set.Union"
+    pub fn union(&self, other: &Ids) -> Self {
+        Ids(union(&self.0, &other.0))
+    }
+
+    /// Returns the sorted IDs that are not in the other set, without duplicates.
+    /// ref. "avalanchego/utils/set.Set.Difference"
+    pub fn difference(&self, other: &Ids) -> Self {
+        Ids(difference(&self.0, &other.0))
+    }
+
+    /// Returns the shortest CB58 prefix of each ID that is unique within the set
+    /// (in the same order), for printing the large sets.
+    pub fn short_strings(&self, min_len: usize) -> Vec<String> {
+        let strs: Vec<String> = self.0.iter().map(|id| id.to_string()).collect();
+        shortest_unique_prefixes(&strs, min_len)
+    }
+
+    /// Sorts the IDs by the XOR distance to the target, closest first.
+    pub fn sort_by_hash_distance(&mut self, target: &Id) {
+        self.0.sort_by_cached_key(|id| id.xor(target));
     }
 }
 
-impl PartialEq for Id {
-    fn eq(&self, other: &Id) -> bool {
-        self.cmp(other) == Ordering::Equal
+impl FromIterator<Id> for Ids {
+    fn from_iter<I: IntoIterator<Item = Id>>(iter: I) -> Self {
+        Ids(iter.into_iter().collect())
     }
 }
 
-/// ref. https://rust-lang.github.io/rust-clippy/master/index.html#derive_hash_xor_eq
-impl Hash for Id {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.d.hash(state);
+impl IntoIterator for Ids {
+    type Item = Id;
+    type IntoIter = std::vec::IntoIter<Id>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
     }
 }
 
-#[derive(Eq)]
-pub struct Ids(Vec<Id>);
+impl<'a> IntoIterator for &'a Ids {
+    type Item = &'a Id;
+    type IntoIter = std::slice::Iter<'a, Id>;
 
-impl Ids {
-    pub fn new(ids: &[Id]) -> Self {
-        Ids(Vec::from(ids))
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
     }
 }
 
+fn union<T: Ord + Copy>(a: &[T], b: &[T]) -> Vec<T> {
+    let set: BTreeSet<T> = a.iter().chain(b.iter()).copied().collect();
+    set.into_iter().collect()
+}
+
+fn difference<T: Ord + Copy>(a: &[T], b: &[T]) -> Vec<T> {
+    let excluded: BTreeSet<&T> = b.iter().collect();
+    let set: BTreeSet<T> = a
+        .iter()
+        .filter(|v| !excluded.contains(v))
+        .copied()
+        .collect();
+    set.into_iter().collect()
+}
+
+/// Returns the shortest prefix of each string that no other distinct string
+/// in the list starts with, but not shorter than "min_len" characters.
+/// The duplicate strings get the same prefix.
+pub fn shortest_unique_prefixes(strs: &[String], min_len: usize) -> Vec<String> {
+    let mut sorted: Vec<&str> = strs.iter().map(|s| s.as_str()).collect();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    // the longest common prefix is always with one of the sorted neighbors
+    let common_len =
+        |a: &str, b: &str| a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count();
+    strs.iter()
+        .map(|s| {
+            let idx = sorted
+                .binary_search(&s.as_str())
+                .expect("unexpected missing string");
+            let mut n = 0;
+            if idx > 0 {
+                n = n.max(common_len(sorted[idx - 1], s));
+            }
+            if idx + 1 < sorted.len() {
+                n = n.max(common_len(sorted[idx + 1], s));
+            }
+            s.chars().take((n + 1).max(min_len)).collect()
+        })
+        .collect()
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- ids::test_shortest_unique_prefixes --exact --show-output
+#[test]
+fn test_shortest_unique_prefixes() {
+    let strs: Vec<String> = vec!["abcdef", "abcxyz", "b", "abcdeg", "b"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    assert_eq!(
+        shortest_unique_prefixes(&strs, 1),
+        vec!["abcdef", "abcx", "b", "abcdeg", "b"]
+    );
+    assert_eq!(
+        shortest_unique_prefixes(&strs, 4),
+        vec!["abcdef", "abcx", "b", "abcdeg", "b"]
+    );
+    assert!(shortest_unique_prefixes(&[], 4).is_empty());
+
+    let id1 = Id::from_slice(&[0x01]);
+    let id2 = Id::from_slice(&[0x02]);
+    let id3 = Id::from_slice(&[0x03]);
+    let ids1 = Ids::new(&[id3, id1, id1]);
+    let ids2: Ids = vec![id2, id3].into_iter().collect();
+    assert_eq!(ids1.len(), 3);
+    assert!(ids1.contains(&id1));
+    assert!(!ids1.contains(&id2));
+    assert_eq!(ids1.union(&ids2), Ids::new(&[id1, id2, id3]));
+    assert_eq!(ids1.difference(&ids2), Ids::new(&[id1]));
+    assert!(ids2.difference(&ids1.union(&ids2)).is_empty());
+    assert_eq!((&ids2).into_iter().count(), 2);
+
+    let short = ids2.short_strings(8);
+    assert_eq!(short.len(), 2);
+    for (s, id) in short.iter().zip(ids2.iter()) {
+        assert!(s.len() >= 8);
+        assert!(id.to_string().starts_with(s.as_str()));
+    }
+    assert_ne!(short[0], short[1]);
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- ids::test_id_fixed_size --exact --show-output
+#[test]
+fn test_id_fixed_size() {
+    const ZERO: Id = Id::empty();
+    assert!(ZERO.is_empty());
+    assert_eq!(ZERO, Id::default());
+    assert_eq!(std::mem::size_of::<Id>(), ID_LEN);
+    assert_eq!(std::mem::size_of::<ShortId>(), SHORT_ID_LEN);
+    assert_eq!(std::mem::size_of::<NodeId>(), NODE_ID_LEN);
+
+    let mut d = [0u8; ID_LEN];
+    d[0] = 0x01;
+    let id = Id::from_array(d);
+    assert_eq!(id, Id::from_slice(&[0x01]));
+
+    // "Copy" leaves the original usable
+    let copied = id;
+    assert_eq!(copied.as_bytes(), id.as_bytes());
+
+    const SHORT_ZERO: ShortId = ShortId::empty();
+    assert!(SHORT_ZERO.is_empty());
+    let short_id = ShortId::from_array([0x02; SHORT_ID_LEN]);
+    assert_eq!(short_id, ShortId::from_slice(&[0x02; SHORT_ID_LEN]));
+
+    const NODE_ZERO: NodeId = NodeId::empty();
+    assert!(NODE_ZERO.is_empty());
+    let node_id = NodeId::from_array(short_id.d);
+    assert_eq!(node_id.short_id(), short_id);
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- ids::test_random --exact --show-output
+#[test]
+fn test_random() {
+    let id1 = Id::from_rng(&mut OsRng);
+    let id2 = Id::from_rng(&mut OsRng);
+    assert!(!id1.is_empty());
+    assert_ne!(id1, id2);
+
+    let node_id1 = NodeId::random();
+    let node_id2 = NodeId::random();
+    assert!(!node_id1.is_empty());
+    assert_ne!(node_id1, node_id2);
+    assert_eq!(NodeId::from_str(&node_id1.to_string()).unwrap(), node_id1);
+}
+
 impl Ord for Ids {
     fn cmp(&self, other: &Ids) -> Ordering {
         // packer encodes the array length first
@@ -319,9 +707,10 @@ fn test_sort_ids() {
 }
 
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/ids#ShortID
-#[derive(Debug, Deserialize, Clone, Eq)]
+/// Backed by a fixed-size array, so it is "Copy" and never heap-allocates.
+#[derive(Debug, Deserialize, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct ShortId {
-    pub d: Vec<u8>,
+    pub d: [u8; SHORT_ID_LEN],
 }
 
 impl Default for ShortId {
@@ -331,29 +720,66 @@ impl Default for ShortId {
 }
 
 impl ShortId {
-    pub fn default() -> Self {
-        Self {
-            d: SHORT_EMPTY.to_vec(),
-        }
+    pub const fn default() -> Self {
+        Self::empty()
     }
 
-    pub fn empty() -> Self {
+    pub const fn empty() -> Self {
         Self {
-            d: SHORT_EMPTY.to_vec(),
+            d: [0; SHORT_ID_LEN],
         }
     }
 
+    pub const fn from_array(d: [u8; SHORT_ID_LEN]) -> Self {
+        Self { d }
+    }
+
     pub fn is_empty(&self) -> bool {
         (*self) == Self::empty()
     }
 
+    /// If the passed array is shorter than the SHORT_ID_LEN,
+    /// it fills in with zero.
     pub fn from_slice(d: &[u8]) -> Self {
         assert!(d.len() <= SHORT_ID_LEN);
-        let mut d: Vec<u8> = Vec::from(d);
-        if d.len() < SHORT_ID_LEN {
-            d.resize(SHORT_ID_LEN, 0);
+        let mut id = Self::empty();
+        id.d[..d.len()].copy_from_slice(d);
+        id
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.d
+    }
+
+    /// Returns the hex-encoded short ID without the "0x" prefix.
+    /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/ids#ShortID.Hex
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.d)
+    }
+
+    /// Encodes the short ID as a chain-prefixed bech32 address (e.g., "X-avax1...").
+    /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/formatting#FormatAddress
+    pub fn to_bech32_address(&self, chain_id_alias: &str, hrp: &str) -> io::Result<String> {
+        formatting::address(chain_id_alias, hrp, &self.d)
+    }
+
+    /// Decodes the chain-prefixed bech32 address (e.g., "P-fuji1...") into a short ID.
+    /// The chain ID alias and the "human readable part" (hrp) are discarded.
+    /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/formatting#ParseAddress
+    pub fn from_bech32_address(addr: &str) -> io::Result<Self> {
+        let (_, _, d) = formatting::parse_address(addr)?;
+        if d.len() != SHORT_ID_LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "address '{}' has unexpected length {} (expected {})",
+                    addr,
+                    d.len(),
+                    SHORT_ID_LEN
+                ),
+            ));
         }
-        Self { d }
+        Ok(Self::from_slice(&d))
     }
 }
 
@@ -377,6 +803,17 @@ impl FromStr for ShortId {
                 format!("failed decode_cb58_with_checksum '{}'", e),
             )
         })?;
+        if decoded.len() > SHORT_ID_LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'{}' decodes to {} bytes (expected at most {})",
+                    s,
+                    decoded.len(),
+                    SHORT_ID_LEN
+                ),
+            ));
+        }
         Ok(Self::from_slice(&decoded))
     }
 }
@@ -434,30 +871,22 @@ fn test_short_id() {
     assert_eq!(id.to_string(), "6ZmBHXTqjknJoZtXbnJ6x7af863rXDTwx");
     let id_from_str = ShortId::from_str("6ZmBHXTqjknJoZtXbnJ6x7af863rXDTwx").unwrap();
     assert_eq!(id, id_from_str);
-}
-
-impl Ord for ShortId {
-    fn cmp(&self, other: &ShortId) -> Ordering {
-        self.d.cmp(&(other.d))
-    }
-}
-
-impl PartialOrd for ShortId {
-    fn partial_cmp(&self, other: &ShortId) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
 
-impl PartialEq for ShortId {
-    fn eq(&self, other: &ShortId) -> bool {
-        self.cmp(other) == Ordering::Equal
+    for (chain_id_alias, hrp) in [("X", "avax"), ("P", "fuji"), ("C", "local")] {
+        let addr = id.to_bech32_address(chain_id_alias, hrp).unwrap();
+        assert!(addr.starts_with(&format!("{}-{}1", chain_id_alias, hrp)));
+        assert_eq!(ShortId::from_bech32_address(&addr).unwrap(), id);
     }
-}
 
-/// ref. https://rust-lang.github.io/rust-clippy/master/index.html#derive_hash_xor_eq
-impl Hash for ShortId {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.d.hash(state);
+    // valid bech32 but not a 20-byte short ID
+    for len in [SHORT_ID_LEN - 1, ID_LEN] {
+        let addr = format!(
+            "X-{}",
+            formatting::encode_bech32("avax", &vec![0x01; len]).unwrap()
+        );
+        let err = ShortId::from_bech32_address(&addr).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("unexpected length"));
     }
 }
 
@@ -582,9 +1011,10 @@ fn test_sort_short_ids() {
 }
 
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/ids#ShortID
-#[derive(Debug, Deserialize, Clone, Eq)]
+/// Backed by a fixed-size array, so it is "Copy" and never heap-allocates.
+#[derive(Debug, Deserialize, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct NodeId {
-    pub d: Vec<u8>,
+    pub d: [u8; NODE_ID_LEN],
 }
 
 impl Default for NodeId {
@@ -594,26 +1024,42 @@ impl Default for NodeId {
 }
 
 impl NodeId {
-    pub fn default() -> Self {
-        Self {
-            d: NODE_ID_EMPTY.to_vec(),
-        }
+    pub const fn default() -> Self {
+        Self::empty()
     }
 
-    pub fn empty() -> Self {
+    pub const fn empty() -> Self {
         Self {
-            d: NODE_ID_EMPTY.to_vec(),
+            d: [0; NODE_ID_LEN],
         }
     }
 
+    pub const fn from_array(d: [u8; NODE_ID_LEN]) -> Self {
+        Self { d }
+    }
+
     pub fn is_empty(&self) -> bool {
         (*self) == Self::empty()
     }
 
+    /// Generates a random node ID from the OS RNG.
+    /// The node ID is not backed by any staking certificate,
+    /// so use "cert::generate_pem" for the node that actually stakes.
+    pub fn random() -> Self {
+        let mut id = Self::empty();
+        OsRng.fill_bytes(&mut id.d);
+        id
+    }
+
     pub fn from_slice(d: &[u8]) -> Self {
-        assert_eq!(d.len(), SHORT_ID_LEN);
-        let d = Vec::from(d);
-        Self { d }
+        assert_eq!(d.len(), NODE_ID_LEN);
+        let mut id = Self::empty();
+        id.d.copy_from_slice(d);
+        id
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.d
     }
 
     /// Loads a node ID from the PEM-encoded X509 certificate.
@@ -642,24 +1088,31 @@ impl NodeId {
         // let pub_key = pem::parse(&pub_key_contents.to_vec()).unwrap();
         // let pub_key_der = pub_key.contents;
 
-        let pub_key_file = File::open(cert_file_path)?;
-        let mut reader = BufReader::new(pub_key_file);
+        let pub_key_contents = fs::read(cert_file_path)?;
+        Self::from_cert_pem(&pub_key_contents).map_err(|e| {
+            Error::new(
+                e.kind(),
+                format!("cert path {} has no valid cert ({})", cert_file_path, e),
+            )
+        })
+    }
+
+    /// Loads a node ID from the PEM-encoded X509 certificate bytes.
+    pub fn from_cert_pem(pem: &[u8]) -> io::Result<Self> {
+        let mut reader = BufReader::new(pem);
         let pem_read = read_one(&mut reader)?;
         let cert = {
-            match pem_read.unwrap() {
-                Item::X509Certificate(cert) => Some(cert),
-                Item::RSAKey(_) | Item::PKCS8Key(_) | Item::ECKey(_) => {
-                    warn!("cert path {} has unexpected private key", cert_file_path);
+            match pem_read {
+                Some(Item::X509Certificate(cert)) => Some(cert),
+                Some(Item::RSAKey(_)) | Some(Item::PKCS8Key(_)) | Some(Item::ECKey(_)) => {
+                    warn!("unexpected private key in cert PEM");
                     None
                 }
                 _ => None,
             }
         };
         if cert.is_none() {
-            return Err(Error::new(
-                ErrorKind::NotFound,
-                format!("cert path {} found no cert", cert_file_path),
-            ));
+            return Err(Error::new(ErrorKind::NotFound, "found no cert in PEM"));
         }
 
         // "ids.ToShortID(hashing.PubkeyBytesToAddress(StakingTLSCert.Leaf.Raw))"
@@ -681,6 +1134,21 @@ impl NodeId {
     pub fn short_id(&self) -> ShortId {
         ShortId::from_slice(&self.d)
     }
+
+    /// Returns the bitwise XOR of the two node IDs,
+    /// which is their distance for the bucketed gossip.
+    pub fn xor(&self, other: &NodeId) -> Self {
+        let mut d = self.d;
+        for (b, o) in d.iter_mut().zip(other.d.iter()) {
+            *b ^= o;
+        }
+        Self { d }
+    }
+
+    /// Encodes the node ID bytes as a chain-prefixed bech32 address.
+    pub fn to_bech32_address(&self, chain_id_alias: &str, hrp: &str) -> io::Result<String> {
+        self.short_id().to_bech32_address(chain_id_alias, hrp)
+    }
 }
 
 /// ref. https://doc.rust-lang.org/std/string/trait.ToString.html
@@ -706,6 +1174,17 @@ impl FromStr for NodeId {
                 format!("failed decode_cb58_with_checksum '{}'", e),
             )
         })?;
+        if decoded.len() > NODE_ID_LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'{}' decodes to {} bytes (expected at most {})",
+                    s,
+                    decoded.len(),
+                    NODE_ID_LEN
+                ),
+            ));
+        }
         Ok(Self::from_slice(&decoded))
     }
 }
@@ -894,40 +1373,164 @@ fn test_from_cert_file() {
     );
 }
 
-impl Ord for NodeId {
-    fn cmp(&self, other: &NodeId) -> Ordering {
-        self.d.cmp(&(other.d))
+#[derive(Debug, Clone, Default, Eq)]
+pub struct NodeIds(Vec<NodeId>);
+
+impl NodeIds {
+    pub fn new(ids: &[NodeId]) -> Self {
+        NodeIds(Vec::from(ids))
     }
-}
 
-impl PartialOrd for NodeId {
-    fn partial_cmp(&self, other: &NodeId) -> Option<Ordering> {
-        Some(self.cmp(other))
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, id: &NodeId) -> bool {
+        self.0.contains(id)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, NodeId> {
+        self.0.iter()
+    }
+
+    pub fn as_slice(&self) -> &[NodeId] {
+        &self.0
+    }
+
+    /// Returns the sorted node IDs in either set, without duplicates.
+    pub fn union(&self, other: &NodeIds) -> Self {
+        NodeIds(union(&self.0, &other.0))
+    }
+
+    /// Returns the sorted node IDs that are not in the other set, without duplicates.
+    pub fn difference(&self, other: &NodeIds) -> Self {
+        NodeIds(difference(&self.0, &other.0))
+    }
+
+    /// Returns the shortest "NodeID-"-prefixed string of each node ID
+    /// that is unique within the set (in the same order), so that the large
+    /// validator sets fit in the CLI output (e.g., "NodeID-7Xhw2").
+    pub fn short_strings(&self, min_len: usize) -> Vec<String> {
+        let strs: Vec<String> = self
+            .0
+            .iter()
+            .map(|id| id.to_string()[NODE_ID_ENCODE_PREFIX.len()..].to_string())
+            .collect();
+        shortest_unique_prefixes(&strs, min_len)
+            .into_iter()
+            .map(|s| format!("{}{}", NODE_ID_ENCODE_PREFIX, s))
+            .collect()
+    }
+
+    /// Sorts the node IDs by the XOR distance to the target, closest first.
+    pub fn sort_by_hash_distance(&mut self, target: &NodeId) {
+        self.0.sort_by_cached_key(|id| id.xor(target));
     }
 }
 
-impl PartialEq for NodeId {
-    fn eq(&self, other: &NodeId) -> bool {
-        self.cmp(other) == Ordering::Equal
+impl FromIterator<NodeId> for NodeIds {
+    fn from_iter<I: IntoIterator<Item = NodeId>>(iter: I) -> Self {
+        NodeIds(iter.into_iter().collect())
     }
 }
 
-/// ref. https://rust-lang.github.io/rust-clippy/master/index.html#derive_hash_xor_eq
-impl Hash for NodeId {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.d.hash(state);
+impl IntoIterator for NodeIds {
+    type Item = NodeId;
+    type IntoIter = std::vec::IntoIter<NodeId>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
     }
 }
 
-#[derive(Eq)]
-pub struct NodeIds(Vec<NodeId>);
+impl<'a> IntoIterator for &'a NodeIds {
+    type Item = &'a NodeId;
+    type IntoIter = std::slice::Iter<'a, NodeId>;
 
-impl NodeIds {
-    pub fn new(ids: &[NodeId]) -> Self {
-        NodeIds(Vec::from(ids))
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
     }
 }
 
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- ids::test_node_ids_set --exact --show-output
+#[test]
+fn test_node_ids_set() {
+    let node_id1 = NodeId::from_str("NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg").unwrap();
+    let node_id2 = NodeId::from_str("NodeID-MFrZFVCXPv5iCn6M9K6XduxGTYp891xXZ").unwrap();
+    let node_id3 = NodeId::from_str("NodeID-NFBbbJ4qCmNaCzeW7sxErhvWqvEQMnYcN").unwrap();
+
+    let validators = NodeIds::new(&[node_id1, node_id2]);
+    let bootstrapped = NodeIds::new(&[node_id2, node_id3]);
+    assert_eq!(
+        validators.union(&bootstrapped),
+        NodeIds::new(&[node_id1, node_id2, node_id3])
+    );
+    assert_eq!(
+        validators.difference(&bootstrapped),
+        NodeIds::new(&[node_id1])
+    );
+    assert!(validators.contains(&node_id2));
+    assert!(!validators.contains(&node_id3));
+
+    let short = validators.union(&bootstrapped).short_strings(1);
+    assert_eq!(short, vec!["NodeID-7", "NodeID-M", "NodeID-N"]);
+    let short = validators.short_strings(5);
+    assert_eq!(short, vec!["NodeID-7Xhw2", "NodeID-MFrZF"]);
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- ids::test_hash_distance --exact --show-output
+#[test]
+fn test_hash_distance() {
+    let id1 = Id::from_slice(&[0b0000_1111, 0xff]);
+    let id2 = Id::from_slice(&[0b0101_0101, 0x0f]);
+    assert_eq!(id1.xor(&id2), Id::from_slice(&[0b0101_1010, 0xf0]));
+    assert_eq!(id1.xor(&id2), id2.xor(&id1));
+    assert!(id1.xor(&id1).is_empty());
+
+    assert_eq!(id2.bit(0), 1);
+    assert_eq!(id2.bit(1), 0);
+    assert_eq!(id2.bit(6), 1);
+    assert_eq!(id2.bit(7), 0);
+    assert_eq!(id2.bit(8), 1);
+    assert_eq!(id2.bit(12), 0);
+
+    let target = Id::from_slice(&[0x10]);
+    let mut ids = Ids::new(&[
+        Id::from_slice(&[0xf0]),
+        Id::from_slice(&[0x11]),
+        Id::from_slice(&[0x00, 0x01]),
+        target,
+    ]);
+    ids.sort_by_hash_distance(&target);
+    assert_eq!(
+        ids,
+        Ids::new(&[
+            target,
+            Id::from_slice(&[0x11]),
+            Id::from_slice(&[0x00, 0x01]),
+            Id::from_slice(&[0xf0]),
+        ])
+    );
+
+    let node_id = |b: u8| {
+        let mut d = [0_u8; NODE_ID_LEN];
+        d[0] = b;
+        d[NODE_ID_LEN - 1] = 0xff;
+        NodeId::from_slice(&d)
+    };
+    let target = node_id(0x80);
+    let mut node_ids = NodeIds::new(&[node_id(0x01), node_id(0x81), target]);
+    node_ids.sort_by_hash_distance(&target);
+    assert_eq!(
+        node_ids,
+        NodeIds::new(&[target, node_id(0x81), node_id(0x01)])
+    );
+}
+
 impl Ord for NodeIds {
     fn cmp(&self, other: &NodeIds) -> Ordering {
         // packer encodes the array length first
@@ -1161,3 +1764,133 @@ pub fn strip_node_id_prefix(addr: &str) -> &str {
         addr
     }
 }
+
+/// Bidirectional registry of the aliases of the IDs (e.g., "X" for the X-chain ID),
+/// where the first alias registered for an ID is its primary alias.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/ids#Aliaser
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Aliaser {
+    dealias: HashMap<String, Id>,
+    aliases: HashMap<Id, Vec<String>>,
+}
+
+impl Aliaser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the alias of the ID, failing if the alias is already taken.
+    /// ref. "avalanchego/ids.Aliaser.Alias"
+    pub fn alias(&mut self, id: Id, alias: &str) -> io::Result<()> {
+        if let Some(existing) = self.dealias.get(alias) {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("alias '{}' is already used by {}", alias, existing),
+            ));
+        }
+        self.dealias.insert(alias.to_string(), id);
+        self.aliases.entry(id).or_default().push(alias.to_string());
+        Ok(())
+    }
+
+    /// ref. "avalanchego/ids.Aliaser.Lookup"
+    pub fn lookup(&self, alias: &str) -> io::Result<Id> {
+        self.dealias.get(alias).copied().ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("no ID found for alias '{}'", alias),
+            )
+        })
+    }
+
+    /// Returns all aliases of the ID in the registered order.
+    pub fn aliases(&self, id: &Id) -> &[String] {
+        self.aliases.get(id).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// ref. "avalanchego/ids.Aliaser.PrimaryAlias"
+    pub fn primary_alias(&self, id: &Id) -> io::Result<&str> {
+        self.aliases(id).first().map(|s| s.as_str()).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("there is no alias for ID {}", id),
+            )
+        })
+    }
+
+    /// Returns the primary alias, or the ID string if the ID has no alias.
+    /// ref. "avalanchego/ids.Aliaser.PrimaryAliasOrDefault"
+    pub fn primary_alias_or_default(&self, id: &Id) -> String {
+        match self.primary_alias(id) {
+            Ok(alias) => alias.to_string(),
+            Err(_) => id.to_string(),
+        }
+    }
+
+    /// Removes all aliases of the ID.
+    /// ref. "avalanchego/ids.Aliaser.RemoveAliases"
+    pub fn remove_aliases(&mut self, id: &Id) {
+        if let Some(aliases) = self.aliases.remove(id) {
+            for alias in aliases.iter() {
+                self.dealias.remove(alias);
+            }
+        }
+    }
+
+    /// Parses the alias or the ID in any encoding supported by "Id::from_any_str",
+    /// so that the CLI accepts "X", "P", "C" or the custom chain aliases
+    /// wherever the chain ID is expected.
+    pub fn resolve(&self, s: &str) -> io::Result<Id> {
+        let s = s.trim();
+        if let Ok(id) = self.lookup(s) {
+            return Ok(id);
+        }
+        Id::from_any_str(s).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("'{}' is neither a known alias nor an ID ({})", s, e),
+            )
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- ids::test_aliaser --exact --show-output
+#[test]
+fn test_aliaser() {
+    let x_chain_id = Id::from_str("2oYMBNV4eNHyqk2fjjV5nVQLDbtmNJzq5s3qs3Lo6ftnC6FByM").unwrap();
+    let c_chain_id = Id::from_str("2q9e4r6Mu3U68nU1fYjgbR6JvwrRx36CohpAX5UQxse55x1Q5").unwrap();
+
+    let mut aliaser = Aliaser::new();
+    aliaser.alias(x_chain_id, "X").unwrap();
+    aliaser.alias(x_chain_id, "avm").unwrap();
+    aliaser.alias(c_chain_id, "C").unwrap();
+    assert!(aliaser.alias(c_chain_id, "X").is_err());
+
+    assert_eq!(aliaser.lookup("X").unwrap(), x_chain_id);
+    assert_eq!(aliaser.lookup("avm").unwrap(), x_chain_id);
+    assert_eq!(aliaser.lookup("P").unwrap_err().kind(), ErrorKind::NotFound);
+    assert_eq!(aliaser.aliases(&x_chain_id), &["X", "avm"]);
+    assert_eq!(aliaser.primary_alias(&x_chain_id).unwrap(), "X");
+    assert_eq!(aliaser.primary_alias_or_default(&c_chain_id), "C");
+
+    assert_eq!(aliaser.resolve(" C ").unwrap(), c_chain_id);
+    assert_eq!(
+        aliaser
+            .resolve("2oYMBNV4eNHyqk2fjjV5nVQLDbtmNJzq5s3qs3Lo6ftnC6FByM")
+            .unwrap(),
+        x_chain_id
+    );
+    assert!(aliaser.resolve("P").is_err());
+
+    aliaser.remove_aliases(&x_chain_id);
+    assert!(aliaser.lookup("avm").is_err());
+    assert!(aliaser.aliases(&x_chain_id).is_empty());
+    assert_eq!(
+        aliaser.primary_alias_or_default(&x_chain_id),
+        x_chain_id.to_string()
+    );
+
+    // the alias is free again after the removal
+    aliaser.alias(c_chain_id, "X").unwrap();
+    assert_eq!(aliaser.lookup("X").unwrap(), c_chain_id);
+}
@@ -0,0 +1,440 @@
+// Argon2id (version 0x13) with its BLAKE2b, as used by the avalanchego
+// keystore password hash.
+// ref. https://www.rfc-editor.org/rfc/rfc9106
+// ref. https://www.rfc-editor.org/rfc/rfc7693
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const BLAKE2B_SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+const BLAKE2B_BLOCK_LEN: usize = 128;
+
+/// Unkeyed BLAKE2b with the "out_len" (1-64) bytes digest.
+struct Blake2b {
+    h: [u64; 8],
+    t: u128,
+    buf: [u8; BLAKE2B_BLOCK_LEN],
+    buf_len: usize,
+    out_len: usize,
+}
+
+impl Blake2b {
+    fn new(out_len: usize) -> Self {
+        assert!(out_len > 0 && out_len <= 64);
+        let mut h = BLAKE2B_IV;
+        h[0] ^= 0x01010000 ^ out_len as u64;
+        Self {
+            h,
+            t: 0,
+            buf: [0; BLAKE2B_BLOCK_LEN],
+            buf_len: 0,
+            out_len,
+        }
+    }
+
+    fn update(&mut self, mut d: &[u8]) {
+        while !d.is_empty() {
+            // keeps the last block for "finalize"
+            if self.buf_len == BLAKE2B_BLOCK_LEN {
+                self.t += BLAKE2B_BLOCK_LEN as u128;
+                let block = self.buf;
+                self.compress(&block, false);
+                self.buf_len = 0;
+            }
+            let n = (BLAKE2B_BLOCK_LEN - self.buf_len).min(d.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&d[..n]);
+            self.buf_len += n;
+            d = &d[n..];
+        }
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        self.t += self.buf_len as u128;
+        let mut block = [0u8; BLAKE2B_BLOCK_LEN];
+        block[..self.buf_len].copy_from_slice(&self.buf[..self.buf_len]);
+        self.compress(&block, true);
+
+        let mut out = Vec::with_capacity(64);
+        for w in self.h.iter() {
+            out.extend_from_slice(&w.to_le_bytes());
+        }
+        out.truncate(self.out_len);
+        out
+    }
+
+    fn compress(&mut self, block: &[u8; BLAKE2B_BLOCK_LEN], last: bool) {
+        let mut m = [0u64; 16];
+        for (i, w) in m.iter_mut().enumerate() {
+            *w = u64::from_le_bytes(block[i * 8..i * 8 + 8].try_into().unwrap());
+        }
+
+        let mut v = [0u64; 16];
+        v[..8].copy_from_slice(&self.h);
+        v[8..].copy_from_slice(&BLAKE2B_IV);
+        v[12] ^= self.t as u64;
+        v[13] ^= (self.t >> 64) as u64;
+        if last {
+            v[14] = !v[14];
+        }
+
+        for round in 0..12 {
+            let s = &BLAKE2B_SIGMA[round % 10];
+            blake2b_g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            blake2b_g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            blake2b_g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            blake2b_g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            blake2b_g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            blake2b_g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            blake2b_g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            blake2b_g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+        for i in 0..8 {
+            self.h[i] ^= v[i] ^ v[i + 8];
+        }
+    }
+}
+
+fn blake2b_g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+fn blake2b(out_len: usize, parts: &[&[u8]]) -> Vec<u8> {
+    let mut h = Blake2b::new(out_len);
+    for p in parts.iter() {
+        h.update(p);
+    }
+    h.finalize()
+}
+
+/// Variable-length hash function "H'".
+/// ref. https://www.rfc-editor.org/rfc/rfc9106#section-3.3
+fn blake2b_long(out_len: usize, d: &[u8]) -> Vec<u8> {
+    let len = (out_len as u32).to_le_bytes();
+    if out_len <= 64 {
+        return blake2b(out_len, &[&len, d]);
+    }
+
+    let mut out = Vec::with_capacity(out_len);
+    let mut v = blake2b(64, &[&len, d]);
+    out.extend_from_slice(&v[..32]);
+    while out_len - out.len() > 64 {
+        v = blake2b(64, &[&v]);
+        out.extend_from_slice(&v[..32]);
+    }
+    let rest = out_len - out.len();
+    out.extend_from_slice(&blake2b(rest, &[&v]));
+    out
+}
+
+const BLOCK_WORDS: usize = 128;
+const SYNC_POINTS: usize = 4;
+const VERSION: u32 = 0x13;
+const TYPE_ID: u32 = 2;
+
+type Block = [u64; BLOCK_WORDS];
+
+fn f_bla_mka(x: u64, y: u64) -> u64 {
+    let xy = (x & 0xffff_ffff).wrapping_mul(y & 0xffff_ffff);
+    x.wrapping_add(y).wrapping_add(xy.wrapping_mul(2))
+}
+
+fn argon2_g(v: &mut Block, a: usize, b: usize, c: usize, d: usize) {
+    v[a] = f_bla_mka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = f_bla_mka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = f_bla_mka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = f_bla_mka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// Permutation "P" on the 16 words at the indices.
+fn permute(v: &mut Block, i: [usize; 16]) {
+    argon2_g(v, i[0], i[4], i[8], i[12]);
+    argon2_g(v, i[1], i[5], i[9], i[13]);
+    argon2_g(v, i[2], i[6], i[10], i[14]);
+    argon2_g(v, i[3], i[7], i[11], i[15]);
+    argon2_g(v, i[0], i[5], i[10], i[15]);
+    argon2_g(v, i[1], i[6], i[11], i[12]);
+    argon2_g(v, i[2], i[7], i[8], i[13]);
+    argon2_g(v, i[3], i[4], i[9], i[14]);
+}
+
+/// Compression function "G", XOR-ing the result into "next" when "with_xor"
+/// (the passes after the first).
+/// ref. https://www.rfc-editor.org/rfc/rfc9106#section-3.5
+fn fill_block(prev: &Block, reference: &Block, next: &mut Block, with_xor: bool) {
+    let mut r = [0u64; BLOCK_WORDS];
+    for i in 0..BLOCK_WORDS {
+        r[i] = prev[i] ^ reference[i];
+    }
+    let mut tmp = r;
+    if with_xor {
+        for i in 0..BLOCK_WORDS {
+            tmp[i] ^= next[i];
+        }
+    }
+
+    // rows of 16 words
+    for row in 0..8 {
+        let mut idx = [0usize; 16];
+        for (j, v) in idx.iter_mut().enumerate() {
+            *v = row * 16 + j;
+        }
+        permute(&mut r, idx);
+    }
+    // columns of 2 words from each row
+    for col in 0..8 {
+        let mut idx = [0usize; 16];
+        for row in 0..8 {
+            idx[row * 2] = row * 16 + col * 2;
+            idx[row * 2 + 1] = row * 16 + col * 2 + 1;
+        }
+        permute(&mut r, idx);
+    }
+
+    for i in 0..BLOCK_WORDS {
+        next[i] = tmp[i] ^ r[i];
+    }
+}
+
+fn block_from_bytes(b: &[u8]) -> Block {
+    let mut block = [0u64; BLOCK_WORDS];
+    for (i, w) in block.iter_mut().enumerate() {
+        *w = u64::from_le_bytes(b[i * 8..i * 8 + 8].try_into().unwrap());
+    }
+    block
+}
+
+/// Returns the Argon2id tag of the password, with no secret nor associated data,
+/// as "golang.org/x/crypto/argon2.IDKey".
+pub fn id_key(
+    password: &[u8],
+    salt: &[u8],
+    time: u32,
+    memory_kib: u32,
+    threads: u32,
+    key_len: u32,
+) -> Vec<u8> {
+    hash(password, salt, &[], &[], time, memory_kib, threads, key_len)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn hash(
+    password: &[u8],
+    salt: &[u8],
+    secret: &[u8],
+    ad: &[u8],
+    time: u32,
+    memory_kib: u32,
+    threads: u32,
+    key_len: u32,
+) -> Vec<u8> {
+    assert!(time >= 1 && threads >= 1 && key_len >= 4);
+
+    let h0 = blake2b(
+        64,
+        &[
+            &threads.to_le_bytes(),
+            &key_len.to_le_bytes(),
+            &memory_kib.to_le_bytes(),
+            &time.to_le_bytes(),
+            &VERSION.to_le_bytes(),
+            &TYPE_ID.to_le_bytes(),
+            &(password.len() as u32).to_le_bytes(),
+            password,
+            &(salt.len() as u32).to_le_bytes(),
+            salt,
+            &(secret.len() as u32).to_le_bytes(),
+            secret,
+            &(ad.len() as u32).to_le_bytes(),
+            ad,
+        ],
+    );
+
+    let lanes = threads as usize;
+    let memory_blocks = (memory_kib as usize).max(2 * SYNC_POINTS * lanes);
+    let segment_len = memory_blocks / (SYNC_POINTS * lanes);
+    let lane_len = segment_len * SYNC_POINTS;
+    let memory_blocks = lane_len * lanes;
+
+    let mut memory: Vec<Block> = vec![[0u64; BLOCK_WORDS]; memory_blocks];
+    for lane in 0..lanes {
+        for i in 0..2u32 {
+            let mut input = h0.clone();
+            input.extend_from_slice(&i.to_le_bytes());
+            input.extend_from_slice(&(lane as u32).to_le_bytes());
+            memory[lane * lane_len + i as usize] = block_from_bytes(&blake2b_long(1024, &input));
+        }
+    }
+
+    let zero_block: Block = [0u64; BLOCK_WORDS];
+    for pass in 0..time as usize {
+        for slice in 0..SYNC_POINTS {
+            for lane in 0..lanes {
+                let data_independent = pass == 0 && slice < SYNC_POINTS / 2;
+
+                let mut input_block: Block = [0u64; BLOCK_WORDS];
+                let mut address_block: Block = [0u64; BLOCK_WORDS];
+                if data_independent {
+                    input_block[0] = pass as u64;
+                    input_block[1] = lane as u64;
+                    input_block[2] = slice as u64;
+                    input_block[3] = memory_blocks as u64;
+                    input_block[4] = time as u64;
+                    input_block[5] = TYPE_ID as u64;
+                }
+                let next_addresses = |input_block: &mut Block, address_block: &mut Block| {
+                    input_block[6] += 1;
+                    let mut tmp = [0u64; BLOCK_WORDS];
+                    fill_block(&zero_block, input_block, &mut tmp, false);
+                    fill_block(&zero_block, &tmp.clone(), &mut tmp, false);
+                    *address_block = tmp;
+                };
+
+                let mut start = 0;
+                if pass == 0 && slice == 0 {
+                    // the first two blocks are already filled
+                    start = 2;
+                    if data_independent {
+                        next_addresses(&mut input_block, &mut address_block);
+                    }
+                }
+
+                let segment_start = lane * lane_len + slice * segment_len;
+                for index in start..segment_len {
+                    let curr = segment_start + index;
+                    // the last block of the lane precedes its first block
+                    let prev = if curr % lane_len == 0 {
+                        curr + lane_len - 1
+                    } else {
+                        curr - 1
+                    };
+
+                    let pseudo_rand = if data_independent {
+                        if index % BLOCK_WORDS == 0 {
+                            next_addresses(&mut input_block, &mut address_block);
+                        }
+                        address_block[index % BLOCK_WORDS]
+                    } else {
+                        memory[prev][0]
+                    };
+
+                    let ref_lane = if pass == 0 && slice == 0 {
+                        lane
+                    } else {
+                        ((pseudo_rand >> 32) % lanes as u64) as usize
+                    };
+                    let same_lane = ref_lane == lane;
+
+                    // ref. "index_alpha" of the reference implementation
+                    let area = if pass == 0 {
+                        if slice == 0 {
+                            index - 1
+                        } else if same_lane {
+                            slice * segment_len + index - 1
+                        } else {
+                            slice * segment_len - usize::from(index == 0)
+                        }
+                    } else if same_lane {
+                        lane_len - segment_len + index - 1
+                    } else {
+                        lane_len - segment_len - usize::from(index == 0)
+                    } as u64;
+                    let mut rel = pseudo_rand & 0xffff_ffff;
+                    rel = (rel * rel) >> 32;
+                    let rel = area - 1 - ((area * rel) >> 32);
+                    let start_pos = if pass != 0 && slice != SYNC_POINTS - 1 {
+                        (slice + 1) * segment_len
+                    } else {
+                        0
+                    } as u64;
+                    let ref_index = ((start_pos + rel) % lane_len as u64) as usize;
+
+                    let reference = memory[ref_lane * lane_len + ref_index];
+                    let prev_block = memory[prev];
+                    fill_block(&prev_block, &reference, &mut memory[curr], pass != 0);
+                }
+            }
+        }
+    }
+
+    let mut last = memory[lane_len - 1];
+    for lane in 1..lanes {
+        let b = &memory[lane * lane_len + lane_len - 1];
+        for i in 0..BLOCK_WORDS {
+            last[i] ^= b[i];
+        }
+    }
+    let mut last_bytes = Vec::with_capacity(1024);
+    for w in last.iter() {
+        last_bytes.extend_from_slice(&w.to_le_bytes());
+    }
+    blake2b_long(key_len as usize, &last_bytes)
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- keystore::argon2::test_blake2b --exact --show-output
+#[test]
+fn test_blake2b() {
+    // ref. https://www.rfc-editor.org/rfc/rfc7693#appendix-A
+    assert_eq!(
+        hex::encode(blake2b(64, &[b"abc"])),
+        "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+         7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+    );
+
+    // multi-block inputs hash the same regardless of how they are split
+    let d = vec![0x5a; 300];
+    assert_eq!(
+        blake2b(32, &[&d]),
+        blake2b(32, &[&d[..1], &d[1..129], &d[129..]])
+    );
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- keystore::argon2::test_argon2id --exact --show-output
+#[test]
+fn test_argon2id() {
+    // ref. https://www.rfc-editor.org/rfc/rfc9106#section-5.3
+    let tag = hash(
+        &[0x01; 32],
+        &[0x02; 16],
+        &[0x03; 8],
+        &[0x04; 12],
+        3,
+        32,
+        4,
+        32,
+    );
+    assert_eq!(
+        hex::encode(tag),
+        "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659"
+    );
+}
@@ -0,0 +1,183 @@
+// Values of the avalanchego keystore user database, encrypted with the
+// XChaCha20-Poly1305 key of the password hash.
+// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/database/encdb
+
+use std::io::{self, Error, ErrorKind};
+
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305},
+    rand::{SecureRandom, SystemRandom},
+};
+
+use crate::packer;
+
+/// ref. "golang.org/x/crypto/chacha20poly1305.NonceSizeX"
+pub const NONCE_LEN: usize = 24;
+
+/// ref. "avalanchego/database/encdb.codecVersion"
+const CODEC_VERSION: u16 = 0;
+
+/// Encrypts the value as "encdb.Database.Put" with the key
+/// "hashing.ComputeHash256(password)", returning the codec-encoded
+/// "encryptedValue" ("Ciphertext" and then "Nonce").
+pub fn encrypt(key: &[u8; 32], value: &[u8]) -> io::Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new().fill(&mut nonce).map_err(|e| {
+        Error::new(
+            ErrorKind::Other,
+            format!("failed to generate ring.random for nonce ({:?})", e),
+        )
+    })?;
+
+    let (safe_key, nonce_96) = x_key(key, &nonce)?;
+    let mut ciphertext = value.to_vec();
+    safe_key
+        .seal_in_place_append_tag(nonce_96, Aad::empty(), &mut ciphertext)
+        .map_err(|e| Error::new(ErrorKind::Other, format!("failed to seal ({:?})", e)))?;
+
+    let packer = packer::Packer::new(usize::MAX, 2 + 4 + ciphertext.len() + 4 + NONCE_LEN);
+    packer.pack_u16(CODEC_VERSION);
+    packer.pack_bytes_with_header(&ciphertext);
+    packer.pack_bytes_with_header(&nonce);
+    if let Some(e) = packer.take_error() {
+        return Err(e);
+    }
+    Ok(packer.take_bytes().to_vec())
+}
+
+/// Decrypts the codec-encoded "encryptedValue" as "encdb.Database.Get".
+pub fn decrypt(key: &[u8; 32], encrypted: &[u8]) -> io::Result<Vec<u8>> {
+    let packer = packer::Packer::load_bytes_for_unpack(encrypted.len(), encrypted);
+    let version = packer.unpack_u16()?;
+    if version != CODEC_VERSION {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("unknown encdb codec version {}", version),
+        ));
+    }
+    let mut ciphertext = packer.unpack_bytes_with_header()?;
+    let nonce = packer.unpack_bytes_with_header()?;
+    if packer.remaining() > 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("{} extra bytes after encdb value", packer.remaining()),
+        ));
+    }
+    let nonce: [u8; NONCE_LEN] = nonce.try_into().map_err(|v: Vec<u8>| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("nonce length {} (expected {})", v.len(), NONCE_LEN),
+        )
+    })?;
+
+    let (safe_key, nonce_96) = x_key(key, &nonce)?;
+    let decrypted = safe_key
+        .open_in_place(nonce_96, Aad::empty(), &mut ciphertext)
+        .map_err(|_| {
+            Error::new(
+                ErrorKind::PermissionDenied,
+                "failed to open (wrong password or corrupted data)",
+            )
+        })?;
+    Ok(decrypted.to_vec())
+}
+
+/// Returns the ChaCha20-Poly1305 key and nonce of the XChaCha20-Poly1305 nonce:
+/// the HChaCha20 subkey of the first 16 nonce bytes, and the last 8 nonce bytes
+/// prefixed with 4 zero bytes.
+/// ref. https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-xchacha-03#section-2.3
+fn x_key(key: &[u8; 32], nonce: &[u8; NONCE_LEN]) -> io::Result<(LessSafeKey, Nonce)> {
+    let subkey = hchacha20(key, nonce[..16].try_into().unwrap());
+    let unbound_key = UnboundKey::new(&CHACHA20_POLY1305, &subkey).map_err(|e| {
+        Error::new(
+            ErrorKind::Other,
+            format!("failed to create UnboundKey ({:?})", e),
+        )
+    })?;
+
+    let mut nonce_96 = [0u8; 12];
+    nonce_96[4..].copy_from_slice(&nonce[16..]);
+    Ok((
+        LessSafeKey::new(unbound_key),
+        Nonce::assume_unique_for_key(nonce_96),
+    ))
+}
+
+/// ref. https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-xchacha-03#section-2.2
+fn hchacha20(key: &[u8; 32], nonce: &[u8; 16]) -> [u8; 32] {
+    let mut s = [0u32; 16];
+    s[..4].copy_from_slice(&[0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]);
+    for i in 0..8 {
+        s[4 + i] = u32::from_le_bytes(key[i * 4..i * 4 + 4].try_into().unwrap());
+    }
+    for i in 0..4 {
+        s[12 + i] = u32::from_le_bytes(nonce[i * 4..i * 4 + 4].try_into().unwrap());
+    }
+
+    for _ in 0..10 {
+        quarter_round(&mut s, 0, 4, 8, 12);
+        quarter_round(&mut s, 1, 5, 9, 13);
+        quarter_round(&mut s, 2, 6, 10, 14);
+        quarter_round(&mut s, 3, 7, 11, 15);
+        quarter_round(&mut s, 0, 5, 10, 15);
+        quarter_round(&mut s, 1, 6, 11, 12);
+        quarter_round(&mut s, 2, 7, 8, 13);
+        quarter_round(&mut s, 3, 4, 9, 14);
+    }
+
+    let mut subkey = [0u8; 32];
+    for (i, w) in s[..4].iter().chain(s[12..].iter()).enumerate() {
+        subkey[i * 4..i * 4 + 4].copy_from_slice(&w.to_le_bytes());
+    }
+    subkey
+}
+
+fn quarter_round(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(16);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(12);
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(8);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(7);
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- keystore::encdb::test_hchacha20 --exact --show-output
+#[test]
+fn test_hchacha20() {
+    // ref. https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-xchacha-03#section-2.2.1
+    let key: [u8; 32] = (0x00..=0x1f).collect::<Vec<u8>>().try_into().unwrap();
+    let nonce: [u8; 16] = hex::decode("000000090000004a0000000031415927")
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert_eq!(
+        hex::encode(hchacha20(&key, &nonce)),
+        "82413b4227b27bfed30e42508a877d73a0f9e4d58a74a853c12ec41326d3ecdc"
+    );
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- keystore::encdb::test_encrypt --exact --show-output
+#[test]
+fn test_encrypt() {
+    let key = [0x07; 32];
+    let encrypted = encrypt(&key, b"hello").unwrap();
+
+    // version, ciphertext with the 16-byte tag, and nonce
+    assert_eq!(encrypted.len(), 2 + 4 + 5 + 16 + 4 + NONCE_LEN);
+    assert_eq!(&encrypted[..6], &[0x00, 0x00, 0x00, 0x00, 0x00, 0x15]);
+    assert_eq!(&encrypted[27..31], &[0x00, 0x00, 0x00, NONCE_LEN as u8]);
+    assert_eq!(decrypt(&key, &encrypted).unwrap(), b"hello");
+
+    // each value uses a new nonce
+    assert_ne!(encrypt(&key, b"hello").unwrap(), encrypted);
+
+    assert!(decrypt(&[0x08; 32], &encrypted).is_err());
+    let mut tampered = encrypted.clone();
+    tampered[6] ^= 1;
+    assert!(decrypt(&key, &tampered).is_err());
+    let mut extra = encrypted;
+    extra.push(0);
+    assert!(decrypt(&key, &extra).is_err());
+}
@@ -0,0 +1,608 @@
+mod argon2;
+mod encdb;
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, Error, ErrorKind},
+    num::NonZeroU32,
+    path::Path,
+    str,
+};
+
+use log::info;
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{api, cert, formatting, ids, packer, soft_key};
+
+pub const CIPHER_AES_256_GCM: &str = "aes-256-gcm";
+pub const KDF_PBKDF2_HMAC_SHA256: &str = "pbkdf2-hmac-sha256";
+
+/// Number of PBKDF2 iterations for newly sealed keys.
+/// Opening uses the iterations recorded in the file.
+pub const DEFAULT_PBKDF2_ITERATIONS: u32 = 100_000;
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+/// Argon2id parameters of the avalanchego keystore password hash.
+/// ref. "avalanchego/utils/password.Hash.Set"
+const PASSWORD_HASH_TIME: u32 = 1;
+const PASSWORD_HASH_MEMORY_KIB: u32 = 64 * 1024;
+const PASSWORD_HASH_THREADS: u32 = 4;
+
+/// Key of the codec-encoded addresses in each chain of the user database.
+/// ref. "avalanchego/vms/components/keystore.addressesKey"
+const ADDRESSES_KEY: [u8; ids::ID_LEN] = [0; ids::ID_LEN];
+
+/// Password-encrypted data at rest.
+/// The AES-256-GCM key is derived from the password with PBKDF2-HMAC-SHA256.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Crypto {
+    pub cipher: String,
+    pub kdf: String,
+    pub iterations: u32,
+    /// Hex-encoded.
+    pub salt: String,
+    /// Hex-encoded.
+    pub nonce: String,
+    /// Hex-encoded, with the 16-byte authentication tag appended.
+    pub ciphertext: String,
+}
+
+impl Crypto {
+    /// Encrypts the data with the password, authenticating the "aad" as well.
+    pub fn seal(d: &[u8], password: &str, aad: &[u8]) -> io::Result<Self> {
+        Self::seal_with_iterations(d, password, aad, DEFAULT_PBKDF2_ITERATIONS)
+    }
+
+    pub fn seal_with_iterations(
+        d: &[u8],
+        password: &str,
+        aad: &[u8],
+        iterations: u32,
+    ) -> io::Result<Self> {
+        if password.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "empty password"));
+        }
+
+        let random = SystemRandom::new();
+        let mut salt = [0u8; SALT_LEN];
+        random.fill(&mut salt).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to generate ring.random for salt ({:?})", e),
+            )
+        })?;
+        let mut nonce = [0u8; NONCE_LEN];
+        random.fill(&mut nonce).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to generate ring.random for nonce ({:?})", e),
+            )
+        })?;
+
+        let safe_key = derive_key(password, &salt, iterations)?;
+
+        // overwrites the original array
+        let mut cipher = d.to_vec();
+        safe_key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(aad),
+                &mut cipher,
+            )
+            .map_err(|e| Error::new(ErrorKind::Other, format!("failed to seal ({:?})", e)))?;
+
+        Ok(Self {
+            cipher: String::from(CIPHER_AES_256_GCM),
+            kdf: String::from(KDF_PBKDF2_HMAC_SHA256),
+            iterations,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(cipher),
+        })
+    }
+
+    /// Decrypts the data with the password and the same "aad" used for sealing.
+    pub fn open(&self, password: &str, aad: &[u8]) -> io::Result<Vec<u8>> {
+        if self.cipher != CIPHER_AES_256_GCM || self.kdf != KDF_PBKDF2_HMAC_SHA256 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unsupported cipher '{}' or kdf '{}'", self.cipher, self.kdf),
+            ));
+        }
+
+        let salt = decode_hex("salt", &self.salt)?;
+        let nonce = decode_hex("nonce", &self.nonce)?;
+        let mut cipher = decode_hex("ciphertext", &self.ciphertext)?;
+        let nonce = Nonce::try_assume_unique_for_key(&nonce)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("invalid nonce ({:?})", e)))?;
+
+        let safe_key = derive_key(password, &salt, self.iterations)?;
+        let decrypted = safe_key
+            .open_in_place(nonce, Aad::from(aad), &mut cipher)
+            .map_err(|_| {
+                Error::new(
+                    ErrorKind::PermissionDenied,
+                    "failed to open (wrong password or corrupted data)",
+                )
+            })?;
+        Ok(decrypted.to_vec())
+    }
+}
+
+/// Represents the "soft_key::Key" encrypted at rest.
+/// The addresses are kept in plaintext to find the key without the password.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct EncryptedKey {
+    #[serde(deserialize_with = "ids::must_deserialize_short_id")]
+    pub short_address: ids::ShortId,
+    pub eth_address: String,
+    /// Encrypts the CB58-encoded private key with the prefix "PrivateKey-"
+    /// (the "privateKey" parameter of "avm.importKey" and "platform.importKey").
+    /// The short address is authenticated as the additional data.
+    pub crypto: Crypto,
+}
+
+impl EncryptedKey {
+    pub fn seal(key: &soft_key::Key, password: &str) -> io::Result<Self> {
+        let crypto = Crypto::seal(
+            key.private_key.as_bytes(),
+            password,
+            key.short_address.as_bytes(),
+        )?;
+        Ok(Self {
+            short_address: key.short_address,
+            eth_address: key.eth_address.clone(),
+            crypto,
+        })
+    }
+
+    pub fn open(&self, password: &str) -> io::Result<soft_key::Key> {
+        let d = self.crypto.open(password, self.short_address.as_bytes())?;
+        let private_key = str::from_utf8(&d).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid private key encoding ({})", e),
+            )
+        })?;
+
+        let key = soft_key::Key::from_private_key(private_key)?;
+        if key.short_address != self.short_address {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "decrypted key address {} != {}",
+                    key.short_address, self.short_address
+                ),
+            ));
+        }
+        Ok(key)
+    }
+}
+
+/// Represents the keys of a keystore user, each encrypted with the user password.
+/// "export_user" and "import_user" convert the keys from and to the
+/// avalanchego "keystore.exportUser" format, which "keystore.importUser" takes.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct UserKeys {
+    pub username: String,
+    pub keys: Vec<EncryptedKey>,
+}
+
+impl UserKeys {
+    pub fn new(username: &str) -> Self {
+        Self {
+            username: String::from(username),
+            keys: Vec::new(),
+        }
+    }
+
+    /// Encrypts and adds the keys, replacing the ones with the same address.
+    pub fn seal_keys(&mut self, keys: &[soft_key::Key], password: &str) -> io::Result<()> {
+        for k in keys.iter() {
+            let encrypted = EncryptedKey::seal(k, password)?;
+            self.keys.retain(|v| v.short_address != k.short_address);
+            self.keys.push(encrypted);
+        }
+        Ok(())
+    }
+
+    /// Decrypts all keys, failing if any key cannot be decrypted.
+    pub fn open_keys(&self, password: &str) -> io::Result<Vec<soft_key::Key>> {
+        let mut keys: Vec<soft_key::Key> = Vec::with_capacity(self.keys.len());
+        for k in self.keys.iter() {
+            keys.push(k.open(password)?);
+        }
+        Ok(keys)
+    }
+
+    pub fn load(file_path: &str) -> io::Result<Self> {
+        info!("loading UserKeys from {}", file_path);
+
+        if !Path::new(file_path).exists() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("file {} does not exists", file_path),
+            ));
+        }
+
+        let f = File::open(file_path).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to open {} ({})", file_path, e),
+            )
+        })?;
+        serde_json::from_reader(f)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid JSON: {}", e)))
+    }
+
+    /// Writes the keys with the "0600" permissions, replacing the file
+    /// atomically so that a crash never leaves a partial file.
+    pub fn sync(&self, file_path: &str) -> io::Result<()> {
+        info!("syncing UserKeys to '{}'", file_path);
+        let path = Path::new(file_path);
+        let parent_dir = path.parent().expect("unexpected None parent");
+        fs::create_dir_all(parent_dir)?;
+
+        let d = serde_json::to_vec(&self).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to serialize UserKeys to JSON {}", e),
+            )
+        })?;
+        cert::write_atomic(file_path, &d, 0o600)
+    }
+
+    /// Encodes the keys as "keystore.exportUser" with the "hex" encoding,
+    /// storing each key in the user database of each chain.
+    /// The "chain_ids" are the blockchain IDs of the chains
+    /// (e.g., the X-chain, the P-chain "ids::Id::empty()", and the C-chain).
+    /// ref. https://docs.avax.network/build/avalanchego-apis/keystore#keystoreexportuser
+    pub fn export_user(
+        &self,
+        password: &str,
+        chain_ids: &[ids::Id],
+    ) -> io::Result<api::keystore::ExportUserResult> {
+        let d = self.encode_user_db(password, chain_ids)?;
+        Ok(api::keystore::ExportUserResult {
+            user: formatting::encode_hex_with_checksum(&d),
+            encoding: String::from("hex"),
+        })
+    }
+
+    /// Decodes the "keystore.exportUser" result, and encrypts the keys
+    /// of all chains at rest with the same password.
+    pub fn import_user(
+        username: &str,
+        export: &api::keystore::ExportUserResult,
+        password: &str,
+    ) -> io::Result<Self> {
+        let d = match export.encoding.as_str() {
+            "hex" => formatting::decode_hex_untrusted(export.user.as_bytes())?,
+            "cb58" => formatting::decode_cb58_untrusted(&export.user)?,
+            enc => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("unknown encoding '{}'", enc),
+                ));
+            }
+        };
+        Self::decode_user_db(username, &d, password)
+    }
+
+    /// Returns the codec-encoded "keystore.UserDB": the password hash
+    /// and the key-value pairs of the user database, whose keys are
+    /// prefixed with the SHA256 of the blockchain ID ("prefixdb.NewNested")
+    /// and whose values are encrypted with the password ("encdb").
+    /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/api/keystore#UserDB
+    pub fn encode_user_db(&self, password: &str, chain_ids: &[ids::Id]) -> io::Result<Vec<u8>> {
+        if password.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "empty password"));
+        }
+        let keys = self.open_keys(password)?;
+
+        let mut salt = [0u8; SALT_LEN];
+        SystemRandom::new().fill(&mut salt).map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to generate ring.random for salt ({:?})", e),
+            )
+        })?;
+        let hash = password_hash(password, &salt);
+        let enc_key = encdb_key(password);
+
+        // same order as the user database iterator
+        let mut pairs: BTreeMap<Vec<u8>, Vec<u8>> = BTreeMap::new();
+        for chain_id in chain_ids.iter() {
+            let prefix = utils::hash::compute_sha256(chain_id.as_bytes());
+
+            let addresses = packer::Packer::new(usize::MAX, 6 + keys.len() * 20);
+            addresses.pack_u16(0);
+            addresses.pack_u32(keys.len() as u32);
+            for k in keys.iter() {
+                addresses.pack_bytes(k.short_address.as_bytes());
+
+                let mut db_key = prefix.clone();
+                db_key.extend_from_slice(k.short_address.as_bytes());
+                let private_key = hex::decode(&k.private_key_hex).map_err(|e| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("failed to decode private key hex ({})", e),
+                    )
+                })?;
+                pairs.insert(db_key, encdb::encrypt(&enc_key, &private_key)?);
+            }
+            let addresses = take_packed(&addresses)?;
+
+            let mut db_key = prefix;
+            db_key.extend_from_slice(&ADDRESSES_KEY);
+            pairs.insert(db_key, encdb::encrypt(&enc_key, &addresses)?);
+        }
+
+        let packer = packer::Packer::new(usize::MAX, 1024);
+        packer.pack_u16(0);
+        packer.pack_bytes(&hash);
+        packer.pack_bytes(&salt);
+        packer.pack_u32(pairs.len() as u32);
+        for (k, v) in pairs.iter() {
+            packer.pack_bytes_with_header(k);
+            packer.pack_bytes_with_header(v);
+        }
+        take_packed(&packer)
+    }
+
+    /// Decodes the codec-encoded "keystore.UserDB", failing if the password
+    /// does not match the password hash. The keys of all chains are
+    /// deduplicated by the address, and the other values are ignored.
+    pub fn decode_user_db(username: &str, b: &[u8], password: &str) -> io::Result<Self> {
+        let packer = packer::Packer::load_bytes_for_unpack(b.len(), b);
+        let version = packer.unpack_u16()?;
+        if version != 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unknown keystore codec version {}", version),
+            ));
+        }
+        let hash = packer.unpack_bytes(KEY_LEN)?;
+        let salt = packer.unpack_bytes(SALT_LEN)?;
+        let n = packer.unpack_u32()?;
+        let mut pairs: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+        for _ in 0..n {
+            let k = packer.unpack_bytes_with_header()?;
+            let v = packer.unpack_bytes_with_header()?;
+            pairs.push((k, v));
+        }
+        if packer.remaining() > 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{} extra bytes after keystore user", packer.remaining()),
+            ));
+        }
+
+        // ref. "avalanchego/utils/password.Hash.Check"
+        if ring::constant_time::verify_slices_are_equal(&password_hash(password, &salt), &hash)
+            .is_err()
+        {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "incorrect password",
+            ));
+        }
+
+        let enc_key = encdb_key(password);
+        let mut keys: BTreeMap<ids::ShortId, soft_key::Key> = BTreeMap::new();
+        for (k, v) in pairs.iter() {
+            // chain prefix and the address
+            if k.len() != ids::ID_LEN + ids::SHORT_ID_LEN {
+                continue;
+            }
+            let address = ids::ShortId::from_slice(&k[ids::ID_LEN..]);
+            let private_key = encdb::decrypt(&enc_key, v)?;
+            let key = soft_key::Key::from_private_key_eth(hex::encode(private_key))?;
+            if key.short_address != address {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "private key address {} != database key {}",
+                        key.short_address, address
+                    ),
+                ));
+            }
+            keys.insert(address, key);
+        }
+
+        let mut user_keys = Self::new(username);
+        user_keys.seal_keys(&keys.into_values().collect::<Vec<_>>(), password)?;
+        Ok(user_keys)
+    }
+}
+
+/// ref. "avalanchego/utils/password.Hash.Set"
+fn password_hash(password: &str, salt: &[u8]) -> Vec<u8> {
+    argon2::id_key(
+        password.as_bytes(),
+        salt,
+        PASSWORD_HASH_TIME,
+        PASSWORD_HASH_MEMORY_KIB,
+        PASSWORD_HASH_THREADS,
+        KEY_LEN as u32,
+    )
+}
+
+/// ref. "avalanchego/database/encdb.New"
+fn encdb_key(password: &str) -> [u8; KEY_LEN] {
+    utils::hash::compute_sha256(password.as_bytes())
+        .try_into()
+        .expect("unexpected SHA256 length")
+}
+
+fn take_packed(packer: &packer::Packer) -> io::Result<Vec<u8>> {
+    if let Some(e) = packer.take_error() {
+        return Err(e);
+    }
+    Ok(packer.take_bytes().to_vec())
+}
+
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> io::Result<LessSafeKey> {
+    let iterations = NonZeroU32::new(iterations)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "zero PBKDF2 iterations"))?;
+
+    let mut key = [0u8; KEY_LEN];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        password.as_bytes(),
+        &mut key,
+    );
+
+    let unbound_key = UnboundKey::new(&AES_256_GCM, &key).map_err(|e| {
+        Error::new(
+            ErrorKind::Other,
+            format!("failed to create UnboundKey ({:?})", e),
+        )
+    })?;
+    Ok(LessSafeKey::new(unbound_key))
+}
+
+fn decode_hex(field: &str, s: &str) -> io::Result<Vec<u8>> {
+    hex::decode(s).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("failed to decode {} hex ({})", field, e),
+        )
+    })
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- keystore::test_crypto --exact --show-output
+#[test]
+fn test_crypto() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let sealed = Crypto::seal_with_iterations(b"hello", "secret", b"aad", 10).unwrap();
+    assert_ne!(sealed.ciphertext, hex::encode(b"hello"));
+    assert_eq!(sealed.open("secret", b"aad").unwrap(), b"hello");
+    assert!(sealed.open("wrong", b"aad").is_err());
+    assert!(sealed.open("secret", b"other").is_err());
+
+    // each seal uses a new salt and nonce
+    let sealed2 = Crypto::seal_with_iterations(b"hello", "secret", b"aad", 10).unwrap();
+    assert_ne!(sealed.ciphertext, sealed2.ciphertext);
+
+    assert!(Crypto::seal(b"hello", "", b"aad").is_err());
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- keystore::test_user_keys --exact --show-output
+#[test]
+fn test_user_keys() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let keys: Vec<soft_key::Key> = soft_key::TEST_KEYS[..2].to_vec();
+    let mut user_keys = UserKeys::new("test-user");
+    user_keys.seal_keys(&keys, "insecure-password").unwrap();
+    user_keys
+        .seal_keys(&keys[..1], "insecure-password")
+        .unwrap();
+    assert_eq!(user_keys.keys.len(), 2);
+
+    let f = tempfile::NamedTempFile::new().unwrap();
+    let p = f.path().to_str().unwrap();
+    user_keys.sync(p).unwrap();
+
+    // private keys are never written in plaintext
+    let written = fs::read_to_string(p).unwrap();
+    for k in keys.iter() {
+        assert!(!written.contains(&k.private_key));
+        assert!(!written.contains(&k.private_key_hex));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(p).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let loaded = UserKeys::load(p).unwrap();
+    assert_eq!(loaded, user_keys);
+    let mut opened = loaded.open_keys("insecure-password").unwrap();
+    opened.sort_by_key(|k| k.short_address);
+    let mut expected: Vec<String> = keys.iter().map(|k| k.private_key.clone()).collect();
+    expected.sort_by_key(|pk| soft_key::Key::from_private_key(pk).unwrap().short_address);
+    assert_eq!(
+        opened
+            .iter()
+            .map(|k| k.private_key.clone())
+            .collect::<Vec<String>>(),
+        expected
+    );
+    assert!(loaded.open_keys("wrong-password").is_err());
+
+    // the ciphertext is bound to the address
+    let mut swapped = loaded.clone();
+    swapped.keys[0].crypto = loaded.keys[1].crypto.clone();
+    assert!(swapped.open_keys("insecure-password").is_err());
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- keystore::test_export_user --exact --show-output
+#[test]
+fn test_export_user() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let keys: Vec<soft_key::Key> = soft_key::TEST_KEYS[..2].to_vec();
+    let mut user_keys = UserKeys::new("test-user");
+    user_keys.seal_keys(&keys, "insecure-password").unwrap();
+
+    let chain_ids = [ids::Id::empty(), ids::Id::from_slice(&[0x01; ids::ID_LEN])];
+    let export = user_keys
+        .export_user("insecure-password", &chain_ids)
+        .unwrap();
+    assert_eq!(export.encoding, "hex");
+    let d = formatting::decode_hex_with_checksum(export.user.as_bytes()).unwrap();
+
+    // version, the password hash and salt, and the key-value pairs
+    // (each key and the addresses of each chain)
+    assert_eq!(&d[..2], &[0x00, 0x00]);
+    assert_eq!(
+        &d[2 + KEY_LEN + SALT_LEN..2 + KEY_LEN + SALT_LEN + 4],
+        &[0x00, 0x00, 0x00, 0x06]
+    );
+    let mut addresses_key = utils::hash::compute_sha256(ids::Id::empty().as_bytes());
+    addresses_key.extend_from_slice(&ADDRESSES_KEY);
+    let mut entry = vec![0x00, 0x00, 0x00, 0x40];
+    entry.extend_from_slice(&addresses_key);
+    assert!(d.windows(entry.len()).any(|w| w == entry.as_slice()));
+    for k in keys.iter() {
+        let pk = hex::decode(&k.private_key_hex).unwrap();
+        assert!(!d.windows(pk.len()).any(|w| w == pk.as_slice()));
+    }
+
+    let imported = UserKeys::import_user("test-user", &export, "insecure-password").unwrap();
+    let mut expected: Vec<ids::ShortId> = keys.iter().map(|k| k.short_address).collect();
+    expected.sort();
+    assert_eq!(
+        imported
+            .open_keys("insecure-password")
+            .unwrap()
+            .iter()
+            .map(|k| k.short_address)
+            .collect::<Vec<ids::ShortId>>(),
+        expected
+    );
+
+    let err = UserKeys::import_user("test-user", &export, "wrong-password").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    let mut extra = d;
+    extra.push(0);
+    assert!(UserKeys::decode_user_db("test-user", &extra, "insecure-password").is_err());
+}
@@ -0,0 +1,447 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Error, ErrorKind, Read, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+use async_trait::async_trait;
+use log::info;
+use secp256k1::PublicKey;
+
+use crate::{ids, signer, soft_key};
+
+/// ref. https://github.com/ava-labs/ledger-app-avalanche/blob/master/src/apdu.h
+pub const CLA: u8 = 0x80;
+pub const INS_VERSION: u8 = 0x00;
+pub const INS_PROMPT_PUBLIC_KEY: u8 = 0x02;
+pub const INS_PROMPT_EXT_PUBLIC_KEY: u8 = 0x03;
+pub const INS_SIGN_HASH: u8 = 0x04;
+
+/// "P1" of the last sign hash APDU with the path suffix,
+/// where "0x01" would be for each of the other signers.
+const P1_SIGN_HASH_LAST: u8 = 0x81;
+
+/// Status word appended to every successful response.
+const SW_OK: u16 = 0x9000;
+
+const HARDENED: u32 = 0x8000_0000;
+
+/// BIP44 account path of the Avalanche app "m/44'/9000'/0'",
+/// shared by the P and X-chain addresses.
+pub const AVAX_ACCOUNT_PATH: [u32; 3] = [44 | HARDENED, 9000 | HARDENED, HARDENED];
+
+pub const LEDGER_VENDOR_ID: u16 = 0x2c97;
+
+/// ref. https://github.com/LedgerHQ/ledgerjs/blob/master/packages/devices/src/hid-framing.ts
+const HID_PACKET_SIZE: usize = 64;
+const HID_CHANNEL: u16 = 0x0101;
+const HID_TAG_APDU: u8 = 0x05;
+
+/// Exchanges the raw APDUs with the Ledger device.
+pub trait Transport: Send + Sync {
+    /// Sends the APDU command and returns the response with its status word.
+    fn exchange(&self, apdu: &[u8]) -> io::Result<Vec<u8>>;
+}
+
+/// Transport over the Linux "hidraw" device, with no extra dependency.
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct HidTransport {
+    file: Mutex<File>,
+}
+
+#[cfg(target_os = "linux")]
+impl HidTransport {
+    pub fn open(device_path: &Path) -> io::Result<Self> {
+        info!("opening Ledger device {}", device_path.display());
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(device_path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Finds the first connected Ledger device, with the app opened.
+    pub fn find() -> io::Result<Self> {
+        for entry in fs::read_dir("/sys/class/hidraw")? {
+            let entry = entry?;
+            let device = entry.path().join("device");
+
+            // e.g., "HID_ID=0003:00002C97:00004015"
+            let uevent = fs::read_to_string(device.join("uevent")).unwrap_or_default();
+            let vendor = format!(":{:08X}:", LEDGER_VENDOR_ID);
+            if !uevent
+                .lines()
+                .any(|l| l.starts_with("HID_ID=") && l.to_uppercase().contains(&vendor))
+            {
+                continue;
+            }
+
+            // APDUs are only served by the first USB interface
+            // e.g., ".../1-1:1.0/0003:2C97:4015.0001"
+            let resolved = fs::canonicalize(&device)?;
+            let is_first_interface = resolved
+                .parent()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().ends_with(".0"))
+                .unwrap_or(false);
+            if is_first_interface {
+                return Self::open(&Path::new("/dev").join(entry.file_name()));
+            }
+        }
+        Err(Error::new(ErrorKind::NotFound, "no Ledger device found"))
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Transport for HidTransport {
+    fn exchange(&self, apdu: &[u8]) -> io::Result<Vec<u8>> {
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|e| Error::new(ErrorKind::Other, format!("failed to lock device ({})", e)))?;
+
+        for packet in hid_packets(apdu)? {
+            // report ID must come first, even if the device does not use numbered reports
+            let mut report = Vec::with_capacity(1 + HID_PACKET_SIZE);
+            report.push(0x00);
+            report.extend_from_slice(&packet);
+            file.write_all(&report)?;
+        }
+        read_hid_response(|| {
+            let mut packet = [0u8; HID_PACKET_SIZE];
+            file.read_exact(&mut packet)?;
+            Ok(packet)
+        })
+    }
+}
+
+/// Splits the APDU into the HID packets, prefixed with its length.
+fn hid_packets(apdu: &[u8]) -> io::Result<Vec<[u8; HID_PACKET_SIZE]>> {
+    if apdu.len() > u16::MAX as usize {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("APDU too large {}", apdu.len()),
+        ));
+    }
+    let mut payload = Vec::with_capacity(2 + apdu.len());
+    payload.extend_from_slice(&(apdu.len() as u16).to_be_bytes());
+    payload.extend_from_slice(apdu);
+
+    let mut packets = Vec::new();
+    for (seq, chunk) in payload.chunks(HID_PACKET_SIZE - 5).enumerate() {
+        let mut packet = [0u8; HID_PACKET_SIZE];
+        packet[..2].copy_from_slice(&HID_CHANNEL.to_be_bytes());
+        packet[2] = HID_TAG_APDU;
+        packet[3..5].copy_from_slice(&(seq as u16).to_be_bytes());
+        packet[5..5 + chunk.len()].copy_from_slice(chunk);
+        packets.push(packet);
+    }
+    Ok(packets)
+}
+
+/// Reassembles the response from the HID packets returned by "read_packet".
+fn read_hid_response<F>(mut read_packet: F) -> io::Result<Vec<u8>>
+where
+    F: FnMut() -> io::Result<[u8; HID_PACKET_SIZE]>,
+{
+    let mut resp: Vec<u8> = Vec::new();
+    let mut expected_len = 0;
+    let mut seq: u16 = 0;
+    loop {
+        let packet = read_packet()?;
+        if u16::from_be_bytes([packet[0], packet[1]]) != HID_CHANNEL || packet[2] != HID_TAG_APDU {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "unexpected HID channel or tag",
+            ));
+        }
+        if u16::from_be_bytes([packet[3], packet[4]]) != seq {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unexpected HID packet sequence (expected {})", seq),
+            ));
+        }
+
+        let data = if seq == 0 {
+            expected_len = u16::from_be_bytes([packet[5], packet[6]]) as usize;
+            &packet[7..]
+        } else {
+            &packet[5..]
+        };
+        let n = data.len().min(expected_len - resp.len());
+        resp.extend_from_slice(&data[..n]);
+        if resp.len() == expected_len {
+            return Ok(resp);
+        }
+        seq += 1;
+    }
+}
+
+/// Encodes the APDU command with the short length.
+pub fn encode_apdu(ins: u8, p1: u8, p2: u8, data: &[u8]) -> io::Result<Vec<u8>> {
+    if data.len() > u8::MAX as usize {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("APDU data too large {}", data.len()),
+        ));
+    }
+    let mut apdu = Vec::with_capacity(5 + data.len());
+    apdu.extend_from_slice(&[CLA, ins, p1, p2, data.len() as u8]);
+    apdu.extend_from_slice(data);
+    Ok(apdu)
+}
+
+/// Encodes the BIP32 path as the number of components,
+/// followed by each big-endian component.
+pub fn encode_bip32_path(path: &[u32]) -> Vec<u8> {
+    let mut b = Vec::with_capacity(1 + path.len() * 4);
+    b.push(path.len() as u8);
+    for c in path.iter() {
+        b.extend_from_slice(&c.to_be_bytes());
+    }
+    b
+}
+
+/// Sends the APDU, and returns the response data without the status word.
+fn send(transport: &dyn Transport, ins: u8, p1: u8, data: &[u8]) -> io::Result<Vec<u8>> {
+    let apdu = encode_apdu(ins, p1, 0x00, data)?;
+    let mut resp = transport.exchange(&apdu)?;
+    if resp.len() < 2 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("response too short {}", resp.len()),
+        ));
+    }
+    let sw_bytes = resp.split_off(resp.len() - 2);
+    let sw = u16::from_be_bytes([sw_bytes[0], sw_bytes[1]]);
+    if sw != SW_OK {
+        // e.g., 0x6985 when the user rejects on the device
+        return Err(Error::new(
+            ErrorKind::Other,
+            format!("Ledger returned status 0x{:04x} for INS 0x{:02x}", sw, ins),
+        ));
+    }
+    Ok(resp)
+}
+
+/// Returns the major, minor, and patch version of the Avalanche app.
+pub fn get_version(transport: &dyn Transport) -> io::Result<(u8, u8, u8)> {
+    let resp = send(transport, INS_VERSION, 0x00, &[])?;
+    if resp.len() < 3 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("version response too short {}", resp.len()),
+        ));
+    }
+    Ok((resp[0], resp[1], resp[2]))
+}
+
+/// Implements "signer::Signer" with the Avalanche app on the Ledger device,
+/// for the address "m/44'/9000'/0'/0/{address_index}".
+/// The transport is blocking, while the user confirms on the device.
+pub struct LedgerSigner {
+    transport: Box<dyn Transport>,
+    pub address_index: u32,
+    public_key: PublicKey,
+    short_address: ids::ShortId,
+    eth_address: String,
+}
+
+impl LedgerSigner {
+    pub fn new(transport: Box<dyn Transport>, address_index: u32) -> io::Result<Self> {
+        let mut path = AVAX_ACCOUNT_PATH.to_vec();
+        path.extend_from_slice(&[0, address_index]);
+
+        // [public key length][public key][chain code length][chain code]
+        let resp = send(
+            transport.as_ref(),
+            INS_PROMPT_EXT_PUBLIC_KEY,
+            0x00,
+            &encode_bip32_path(&path),
+        )?;
+        let pk_len = *resp
+            .first()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "empty public key response"))?
+            as usize;
+        if resp.len() < 1 + pk_len {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("public key response too short {}", resp.len()),
+            ));
+        }
+        let public_key = PublicKey::from_slice(&resp[1..1 + pk_len]).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("failed to parse public key ({})", e),
+            )
+        })?;
+
+        let short_address = soft_key::public_key_to_short_address(&public_key)?;
+        let eth_address = soft_key::public_key_to_eth_address(&public_key)?;
+        info!(
+            "loaded Ledger signer at index {} with address {}",
+            address_index, short_address
+        );
+
+        Ok(Self {
+            transport,
+            address_index,
+            public_key,
+            short_address,
+            eth_address,
+        })
+    }
+
+    /// Displays the address on the device for the user to verify.
+    pub fn prompt_address(&self) -> io::Result<()> {
+        let mut path = AVAX_ACCOUNT_PATH.to_vec();
+        path.extend_from_slice(&[0, self.address_index]);
+
+        let resp = send(
+            self.transport.as_ref(),
+            INS_PROMPT_PUBLIC_KEY,
+            0x00,
+            &encode_bip32_path(&path),
+        )?;
+        if resp != self.short_address.as_bytes() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Ledger reported an address that does not match the public key",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl signer::Signer for LedgerSigner {
+    /// ref. "ledger-app-avalanche/src/sign_hash.c"
+    async fn sign_digest(&self, digest: &[u8]) -> io::Result<Vec<u8>> {
+        if digest.len() != 32 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("digest must be 32-byte, got {}", digest.len()),
+            ));
+        }
+
+        // [number of signers][hash][account path]
+        let mut first = vec![1u8];
+        first.extend_from_slice(digest);
+        first.extend_from_slice(&encode_bip32_path(&AVAX_ACCOUNT_PATH));
+        let resp = send(self.transport.as_ref(), INS_SIGN_HASH, 0x00, &first)?;
+        if resp.len() < 32 || resp[..32] != digest[..] {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Ledger reported a hash that does not match the digest",
+            ));
+        }
+
+        // only one signer, so the first suffix is the last
+        let sig = send(
+            self.transport.as_ref(),
+            INS_SIGN_HASH,
+            P1_SIGN_HASH_LAST,
+            &encode_bip32_path(&[0, self.address_index]),
+        )?;
+        if sig.len() != crate::secp256k1fx::SIG_LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unexpected signature length {}", sig.len()),
+            ));
+        }
+        Ok(sig)
+    }
+
+    fn public_key(&self) -> io::Result<PublicKey> {
+        Ok(self.public_key)
+    }
+
+    fn short_address(&self) -> ids::ShortId {
+        self.short_address
+    }
+
+    fn eth_address(&self) -> String {
+        self.eth_address.clone()
+    }
+}
+
+/// Emulates the Avalanche app with the in-memory key.
+#[cfg(test)]
+struct MockApp {
+    key: soft_key::Key,
+    hash: Mutex<Vec<u8>>,
+}
+
+#[cfg(test)]
+impl Transport for MockApp {
+    fn exchange(&self, apdu: &[u8]) -> io::Result<Vec<u8>> {
+        assert_eq!(apdu[0], CLA);
+        assert_eq!(apdu[4] as usize, apdu.len() - 5);
+        let data = &apdu[5..];
+
+        let mut resp = match (apdu[1], apdu[2]) {
+            (INS_PROMPT_EXT_PUBLIC_KEY, 0x00) => {
+                assert_eq!(data[0], 5);
+                let pk = self.key.public_key.unwrap().serialize_uncompressed();
+                let mut b = vec![pk.len() as u8];
+                b.extend_from_slice(&pk);
+                b.push(32);
+                b.extend_from_slice(&[0u8; 32]);
+                b
+            }
+            (INS_PROMPT_PUBLIC_KEY, 0x00) => self.key.short_address.as_bytes().to_vec(),
+            (INS_SIGN_HASH, 0x00) => {
+                assert_eq!(data[0], 1);
+                assert_eq!(&data[33..], &encode_bip32_path(&AVAX_ACCOUNT_PATH)[..]);
+                *self.hash.lock().unwrap() = data[1..33].to_vec();
+                data[1..33].to_vec()
+            }
+            (INS_SIGN_HASH, P1_SIGN_HASH_LAST) => {
+                let hash = self.hash.lock().unwrap();
+                self.key.sign_digest(&hash)?
+            }
+            _ => return Ok(vec![0x6d, 0x00]),
+        };
+        resp.extend_from_slice(&SW_OK.to_be_bytes());
+        Ok(resp)
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib --features ledger -- ledger::test_hid_packets --exact --show-output
+#[test]
+fn test_hid_packets() {
+    let apdu: Vec<u8> = (0..150).map(|i| i as u8).collect();
+    let packets = hid_packets(&apdu).unwrap();
+    assert_eq!(packets.len(), 3);
+    assert_eq!(&packets[0][..7], &[0x01, 0x01, 0x05, 0x00, 0x00, 0x00, 150]);
+    assert_eq!(&packets[2][..5], &[0x01, 0x01, 0x05, 0x00, 0x02]);
+
+    let mut it = packets.into_iter();
+    let resp = read_hid_response(|| Ok(it.next().unwrap())).unwrap();
+    assert_eq!(resp, apdu);
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib --features ledger -- ledger::test_ledger_signer --exact --show-output
+#[test]
+fn test_ledger_signer() {
+    use crate::signer::Signer;
+
+    let key = soft_key::TEST_KEYS[0].clone();
+    let app = MockApp {
+        key: key.clone(),
+        hash: Mutex::new(Vec::new()),
+    };
+    let ledger = LedgerSigner::new(Box::new(app), 0).unwrap();
+    assert_eq!(ledger.short_address(), key.short_address);
+    assert_eq!(ledger.eth_address(), key.eth_address);
+    ledger.prompt_address().unwrap();
+
+    let digest = [7u8; 32];
+    let sig = tokio_test::block_on(ledger.sign_digest(&digest)).unwrap();
+    assert_eq!(sig, key.sign_digest(&digest).unwrap());
+    assert!(tokio_test::block_on(ledger.sign_digest(&[0u8; 20])).is_err());
+}
@@ -1,15 +1,38 @@
 pub mod api;
 pub mod avax;
+pub mod avm;
+pub mod bag;
+pub mod bls_key;
+#[cfg(feature = "full")]
 pub mod cert;
 pub mod codec;
+pub mod consensus;
 pub mod constants;
+pub mod errors;
+pub mod evm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "full")]
+pub mod fixtures;
 pub mod formatting;
 pub mod genesis;
 pub mod ids;
+#[cfg(feature = "full")]
+pub mod keystore;
+#[cfg(feature = "ledger")]
+pub mod ledger;
+pub mod message;
 pub mod metrics;
 pub mod node;
 pub mod packer;
 pub mod platformvm;
+#[cfg(feature = "python")]
+pub mod python;
 pub mod secp256k1fx;
+pub mod signer;
 pub mod soft_key;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+pub mod strategies;
 pub mod units;
+pub mod utxo;
+pub mod warp;
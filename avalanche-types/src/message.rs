@@ -0,0 +1,377 @@
+use std::{
+    io::{self, Error, ErrorKind},
+    net::SocketAddr,
+};
+
+use crate::{ids, packer};
+#[cfg(feature = "full")]
+use utils::compress;
+
+/// Default maximum size of the message, excluding its length prefix.
+/// ref. "avalanchego/utils/constants.DefaultMaxMessageSize"
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 2 * 1024 * 1024;
+
+/// ref. "avalanchego/message.Op"
+pub const OP_PING: u8 = 4;
+pub const OP_PONG: u8 = 5;
+pub const OP_PEER_LIST: u8 = 18;
+pub const OP_VERSION: u8 = 19;
+
+/// Returns true if the message of the op carries the compression flag.
+/// ref. "avalanchego/message.Op.Compressible"
+pub fn is_compressible(op: u8) -> bool {
+    matches!(op, OP_PEER_LIST)
+}
+
+/// First message sent by each side after the TLS handshake.
+/// ref. "avalanchego/message.outMsgBuilder.Version"
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Version {
+    pub network_id: u32,
+    /// Deprecated nonce, not the node ID from the staking certificate.
+    pub node_id: u32,
+    /// Unix timestamp in seconds, must be within the peer's clock skew.
+    pub my_time: u64,
+    pub ip: SocketAddr,
+    /// e.g., "avalanche/1.7.10"
+    pub version_str: String,
+    pub version_time: u64,
+    /// Signature of "signed_ip_bytes(ip, version_time)" by the staking key.
+    pub sig: Vec<u8>,
+    pub tracked_subnets: Vec<ids::Id>,
+}
+
+impl Version {
+    fn pack(&self, packer: &packer::Packer) {
+        packer.pack_u32(self.network_id);
+        packer.pack_u32(self.node_id);
+        packer.pack_u64(self.my_time);
+        packer.pack_ip(&self.ip);
+        packer.pack_str(&self.version_str);
+        packer.pack_u64(self.version_time);
+        packer.pack_bytes_with_header(&self.sig);
+        let subnets: Vec<Vec<u8>> = self
+            .tracked_subnets
+            .iter()
+            .map(|id| id.d.to_vec())
+            .collect();
+        packer.pack_2d_bytes(&subnets);
+    }
+
+    fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let network_id = packer.unpack_u32()?;
+        let node_id = packer.unpack_u32()?;
+        let my_time = packer.unpack_u64()?;
+        let ip = packer.unpack_ip()?;
+        let version_str = packer.unpack_str()?;
+        let version_time = packer.unpack_u64()?;
+        let sig = packer.unpack_bytes_with_header()?;
+
+        let mut tracked_subnets: Vec<ids::Id> = Vec::new();
+        for b in packer.unpack_2d_bytes()? {
+            if b.len() != ids::ID_LEN {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("unexpected subnet ID length {}", b.len()),
+                ));
+            }
+            tracked_subnets.push(ids::Id::from_slice(&b));
+        }
+
+        Ok(Self {
+            network_id,
+            node_id,
+            my_time,
+            ip,
+            version_str,
+            version_time,
+            sig,
+            tracked_subnets,
+        })
+    }
+}
+
+/// IP claimed by the peer, signed by the staking key of its certificate.
+/// ref. "avalanchego/utils/ips.ClaimedIPPort"
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ClaimedIpPort {
+    /// DER-encoded staking certificate.
+    pub cert: Vec<u8>,
+    pub ip: SocketAddr,
+    pub timestamp: u64,
+    /// Signature of "signed_ip_bytes(ip, timestamp)".
+    pub signature: Vec<u8>,
+}
+
+impl ClaimedIpPort {
+    /// Returns the node ID derived from the certificate.
+    pub fn node_id(&self) -> io::Result<ids::NodeId> {
+        ids::NodeId::from_cert_raw(&self.cert)
+    }
+}
+
+/// Gossips the known peers.
+/// ref. "avalanchego/message.outMsgBuilder.PeerList"
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PeerList {
+    pub peers: Vec<ClaimedIpPort>,
+}
+
+impl PeerList {
+    /// ref. "avalanchego/utils/wrappers.TryPackClaimedIPPortList"
+    fn pack(&self, packer: &packer::Packer) {
+        packer.pack_u32(self.peers.len() as u32);
+        for peer in self.peers.iter() {
+            packer.pack_bytes_with_header(&peer.cert);
+            packer.pack_ip(&peer.ip);
+            packer.pack_u64(peer.timestamp);
+            packer.pack_bytes_with_header(&peer.signature);
+        }
+    }
+
+    /// ref. "avalanchego/utils/wrappers.TryUnpackClaimedIPPortList"
+    fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let n = packer.unpack_u32()?;
+        let mut peers: Vec<ClaimedIpPort> = Vec::new();
+        for _ in 0..n {
+            peers.push(ClaimedIpPort {
+                cert: packer.unpack_bytes_with_header()?,
+                ip: packer.unpack_ip()?,
+                timestamp: packer.unpack_u64()?,
+                signature: packer.unpack_bytes_with_header()?,
+            });
+        }
+        Ok(Self { peers })
+    }
+}
+
+/// Reply to "Ping" with the uptime of the sender, as seen by the replying peer.
+/// ref. "avalanchego/message.outMsgBuilder.Pong"
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Pong {
+    /// Percentage in [0, 100].
+    pub uptime: u8,
+}
+
+/// Network message exchanged over the staking port.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Message {
+    Version(Version),
+    PeerList(PeerList),
+    Ping,
+    Pong(Pong),
+}
+
+impl Message {
+    pub fn op(&self) -> u8 {
+        match self {
+            Message::Version(_) => OP_VERSION,
+            Message::PeerList(_) => OP_PEER_LIST,
+            Message::Ping => OP_PING,
+            Message::Pong(_) => OP_PONG,
+        }
+    }
+
+    /// Packs the op and its uncompressed fields, without the length prefix.
+    /// ref. "avalanchego/message.codec.Pack"
+    pub fn pack(&self) -> io::Result<Vec<u8>> {
+        self.pack_with_compression(false)
+    }
+
+    /// Packs the op and its fields, without the length prefix.
+    /// For the compressible ops, the compression flag follows the op,
+    /// and the fields are gzip-compressed if the flag is set.
+    /// The flag is ignored for the other ops.
+    /// ref. "avalanchego/message.codec.Pack"
+    pub fn pack_with_compression(&self, compress: bool) -> io::Result<Vec<u8>> {
+        let fields = packer::Packer::new(DEFAULT_MAX_MESSAGE_SIZE, 64);
+        match self {
+            Message::Version(v) => v.pack(&fields),
+            Message::PeerList(v) => v.pack(&fields),
+            Message::Ping => {}
+            Message::Pong(v) => fields.pack_byte(v.uptime),
+        }
+        if let Some(e) = fields.take_error() {
+            return Err(e);
+        }
+        let fields = fields.take_bytes();
+
+        let op = self.op();
+        let packer = packer::Packer::new(DEFAULT_MAX_MESSAGE_SIZE, 2 + fields.len());
+        packer.pack_byte(op);
+        if is_compressible(op) {
+            packer.pack_bool(compress);
+        }
+        if compress && is_compressible(op) {
+            packer.pack_bytes(&gzip_compress(&fields)?);
+        } else {
+            packer.pack_bytes(&fields);
+        }
+        if let Some(e) = packer.take_error() {
+            return Err(e);
+        }
+        Ok(packer.take_bytes().to_vec())
+    }
+
+    /// Unpacks the message written by "pack_with_compression",
+    /// rejecting any trailing bytes.
+    /// ref. "avalanchego/message.codec.Parse"
+    pub fn unpack(b: &[u8]) -> io::Result<Self> {
+        let packer = packer::Packer::load_bytes_for_unpack(DEFAULT_MAX_MESSAGE_SIZE, b);
+        let op = packer.unpack_byte()?;
+        if !matches!(op, OP_VERSION | OP_PEER_LIST | OP_PING | OP_PONG) {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                format!("unsupported op {}", op),
+            ));
+        }
+        let compressed = is_compressible(op) && packer.unpack_bool()?;
+
+        let fields = packer.unpack_bytes(packer.remaining())?;
+        let fields = if compressed {
+            gzip_decompress(&fields, DEFAULT_MAX_MESSAGE_SIZE)?
+        } else {
+            fields
+        };
+        let packer = packer::Packer::load_bytes_for_unpack(DEFAULT_MAX_MESSAGE_SIZE, &fields);
+
+        let msg = match op {
+            OP_VERSION => Message::Version(Version::unpack(&packer)?),
+            OP_PEER_LIST => Message::PeerList(PeerList::unpack(&packer)?),
+            OP_PING => Message::Ping,
+            _ => Message::Pong(Pong {
+                uptime: packer.unpack_byte()?,
+            }),
+        };
+        if packer.remaining() > 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{} extra bytes after op {}", packer.remaining(), op),
+            ));
+        }
+        Ok(msg)
+    }
+}
+
+/// ref. "avalanchego/utils/compression.gzipCompressor.Compress"
+#[cfg(feature = "full")]
+fn gzip_compress(d: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoded = Vec::new();
+    compress::pack_stream(
+        d,
+        &mut encoded,
+        compress::Encoder::Gzip(compress::DEFAULT_GZIP_LEVEL),
+        &compress::StreamOptions::default(),
+    )?;
+    Ok(encoded)
+}
+
+/// Decompresses the gzip bytes, failing if the output exceeds the limit
+/// so that a small message cannot expand into a large allocation.
+/// ref. "avalanchego/utils/compression.gzipCompressor.Decompress"
+#[cfg(feature = "full")]
+fn gzip_decompress(d: &[u8], limit: usize) -> io::Result<Vec<u8>> {
+    let mut decoded = Vec::new();
+    let opts = compress::StreamOptions {
+        max_unpacked_bytes: Some(limit as u64),
+        ..Default::default()
+    };
+    compress::unpack_stream(d, &mut decoded, compress::Decoder::Gzip, &opts)
+        .map_err(|e| Error::new(e.kind(), format!("failed to decompress message ({})", e)))?;
+    Ok(decoded)
+}
+
+/// The gzip codec lives in "utils::compress" with the file archives,
+/// so the builds without the "full" feature only take the uncompressed messages.
+#[cfg(not(feature = "full"))]
+fn gzip_compress(_d: &[u8]) -> io::Result<Vec<u8>> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "message compression requires the 'full' feature",
+    ))
+}
+
+#[cfg(not(feature = "full"))]
+fn gzip_decompress(_d: &[u8], _limit: usize) -> io::Result<Vec<u8>> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "message decompression requires the 'full' feature",
+    ))
+}
+
+/// Returns the bytes of the IP and its timestamp, whose SHA256 digest
+/// is signed by the staking key to prove the ownership of the IP.
+/// ref. "avalanchego/network/peer.UnsignedIP.Sign"
+pub fn signed_ip_bytes(ip: &SocketAddr, timestamp: u64) -> Vec<u8> {
+    let packer = packer::Packer::new(packer::IP_LEN + packer::U64_LEN, 0);
+    packer.pack_ip(ip);
+    packer.pack_u64(timestamp);
+    packer.take_bytes().to_vec()
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- message::test_message --exact --show-output
+#[test]
+fn test_message() {
+    let version = Message::Version(Version {
+        network_id: 1,
+        node_id: 0,
+        my_time: 1652000000,
+        ip: "1.2.3.4:9651".parse().unwrap(),
+        version_str: String::from("avalanche/1.7.10"),
+        version_time: 1652000000,
+        sig: vec![0xaa, 0xbb],
+        tracked_subnets: vec![ids::Id::from_slice(&[1; 32])],
+    });
+    let b = version.pack().unwrap();
+    assert_eq!(&b[..9], &[OP_VERSION, 0, 0, 0, 1, 0, 0, 0, 0]);
+    assert_eq!(Message::unpack(&b).unwrap(), version);
+
+    let pong = Message::Pong(Pong { uptime: 99 });
+    assert_eq!(pong.pack().unwrap(), vec![OP_PONG, 99]);
+    assert_eq!(Message::unpack(&[OP_PONG, 99]).unwrap(), pong);
+    assert_eq!(Message::unpack(&[OP_PING]).unwrap(), Message::Ping);
+
+    assert!(Message::unpack(&[OP_PING, 0]).is_err());
+
+    let peer_list = Message::PeerList(PeerList {
+        peers: vec![ClaimedIpPort {
+            cert: vec![0x30; 100],
+            ip: "10.0.0.1:9651".parse().unwrap(),
+            timestamp: 1652000000,
+            signature: vec![0xcc; 72],
+        }],
+    });
+    let b = peer_list.pack().unwrap();
+    assert_eq!(&b[..6], &[OP_PEER_LIST, 0, 0, 0, 0, 1]);
+    assert_eq!(Message::unpack(&b).unwrap(), peer_list);
+
+    #[cfg(feature = "full")]
+    {
+        let compressed = peer_list.pack_with_compression(true).unwrap();
+        assert_eq!(&compressed[..2], &[OP_PEER_LIST, 1]);
+        assert!(compressed.len() < b.len());
+        assert_eq!(Message::unpack(&compressed).unwrap(), peer_list);
+        assert!(gzip_decompress(&gzip_compress(&[0; 1024]).unwrap(), 1023).is_err());
+    }
+    #[cfg(not(feature = "full"))]
+    assert_eq!(
+        peer_list.pack_with_compression(true).unwrap_err().kind(),
+        ErrorKind::Unsupported
+    );
+
+    // only the compressible ops carry the flag
+    assert_eq!(
+        Message::Ping.pack_with_compression(true).unwrap(),
+        vec![OP_PING]
+    );
+    assert_eq!(
+        Message::unpack(&[0xff]).unwrap_err().kind(),
+        ErrorKind::Unsupported
+    );
+
+    let b = signed_ip_bytes(&"1.2.3.4:5".parse().unwrap(), 6);
+    assert_eq!(
+        b,
+        vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 1, 2, 3, 4, 0, 5, 0, 0, 0, 0, 0, 0, 0, 6]
+    );
+}
@@ -1,11 +1,13 @@
 use std::io::{self, Error, ErrorKind};
 
 /// Defines the node type.
-/// MUST BE either "anchor" or "non-anchor"
+/// MUST BE either "anchor", "non-anchor", or "api"
 #[derive(Eq, PartialEq, Clone)]
 pub enum Kind {
     Anchor,
     NonAnchor,
+    /// Serves the RPC without staking (never registers as a validator).
+    Api,
 }
 
 impl Kind {
@@ -13,6 +15,7 @@ impl Kind {
         match self {
             Kind::Anchor => "anchor",
             Kind::NonAnchor => "non-anchor",
+            Kind::Api => "api",
         }
     }
     pub fn from_str(&self, s: &str) -> io::Result<Self> {
@@ -20,6 +23,7 @@ impl Kind {
             "anchor" => Ok(Kind::Anchor),
             "non-anchor" => Ok(Kind::NonAnchor),
             "non_anchor" => Ok(Kind::NonAnchor),
+            "api" => Ok(Kind::Api),
             _ => Err(Error::new(
                 ErrorKind::Other,
                 format!("unknown node type '{}'", s),
@@ -1,7 +1,8 @@
 use std::{
     cell::Cell,
-    io::{Error, ErrorKind},
-    u16,
+    io::{self, Error, ErrorKind},
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+    str, u16,
 };
 
 use bytes::{Buf, BufMut, Bytes, BytesMut};
@@ -109,6 +110,11 @@ impl Packer {
         errored
     }
 
+    /// Returns the last error if any, and clears it.
+    pub fn take_error(&self) -> Option<Error> {
+        self.error.take()
+    }
+
     fn set_error(&self, err: Error) {
         self.error.set(Some(err));
     }
@@ -213,6 +219,25 @@ impl Packer {
         };
     }
 
+    /// Returns the current offset if at least "n" bytes are left to unpack.
+    /// Unlike "check_space", it does not set the error, so the caller can
+    /// return on the first short read.
+    /// ref. "avalanchego/utils/wrappers.Packer.CheckSpace"
+    fn check_unpack(&self, n: usize) -> io::Result<usize> {
+        let offset = self.get_offset();
+        let len = self.bytes_len();
+        match offset.checked_add(n) {
+            Some(needed) if needed <= len => Ok(offset),
+            _ => Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                format!(
+                    "packer has insufficient length for input (offset {}, needed {}, length {})", // ref. "errBadLength"
+                    offset, n, len
+                ),
+            )),
+        }
+    }
+
     /// Writes the "u8" value at the offset and increments the offset afterwards.
     /// ref. "avalanchego/utils/wrappers.Packer.PackByte"
     pub fn pack_byte(&self, v: u8) {
@@ -243,13 +268,8 @@ impl Packer {
     /// Unpacks the byte in the "offset" position,
     /// and advances the cursor and offset.
     /// ref. "avalanchego/utils/wrappers.Packer.UnpackByte"
-    pub fn unpack_byte(&self) -> u8 {
-        self.check_space(BYTE_LEN);
-        if self.errored() {
-            return BYTE_SENTINEL;
-        }
-
-        let offset = self.get_offset();
+    pub fn unpack_byte(&self) -> io::Result<u8> {
+        let offset = self.check_unpack(BYTE_LEN)?;
         let b = self.bytes.take();
 
         let p = &b[offset];
@@ -259,7 +279,7 @@ impl Packer {
         self.bytes.set(b);
 
         self.set_offset(offset + BYTE_LEN);
-        v
+        Ok(v)
     }
 
     /// Writes the "u16" value at the offset and increments the offset afterwards.
@@ -293,13 +313,8 @@ impl Packer {
     /// Unpacks the u16 from the "offset" position,
     /// and advances the cursor and offset.
     /// ref. "avalanchego/utils/wrappers.Packer.UnpackShort"
-    pub fn unpack_u16(&self) -> u16 {
-        self.check_space(U16_LEN);
-        if self.errored() {
-            return U16_SENTINEL;
-        }
-
-        let offset = self.get_offset();
+    pub fn unpack_u16(&self) -> io::Result<u16> {
+        let offset = self.check_unpack(U16_LEN)?;
         let b = self.bytes.take();
 
         let pos = &b[offset..offset + U16_LEN];
@@ -312,7 +327,7 @@ impl Packer {
         self.bytes.set(b);
 
         self.set_offset(offset + U16_LEN);
-        v
+        Ok(v)
     }
 
     /// Writes the "u32" value at the offset and increments the offset afterwards.
@@ -347,13 +362,8 @@ impl Packer {
     /// Unpacks the u32 from the "offset" position,
     /// and advances the cursor and offset.
     /// ref. "avalanchego/utils/wrappers.Packer.UnpackInt"
-    pub fn unpack_u32(&self) -> u32 {
-        self.check_space(U32_LEN);
-        if self.errored() {
-            return U32_SENTINEL;
-        }
-
-        let offset = self.get_offset();
+    pub fn unpack_u32(&self) -> io::Result<u32> {
+        let offset = self.check_unpack(U32_LEN)?;
         let b = self.bytes.take();
 
         let pos = &b[offset..offset + U32_LEN];
@@ -366,7 +376,7 @@ impl Packer {
         self.bytes.set(b);
 
         self.set_offset(offset + U32_LEN);
-        v
+        Ok(v)
     }
 
     /// Writes the "u64" value at the offset and increments the offset afterwards.
@@ -397,6 +407,28 @@ impl Packer {
         self.set_offset(offset + U64_LEN);
     }
 
+    /// Unpacks the u64 from the "offset" position,
+    /// and advances the cursor and offset.
+    /// ref. "avalanchego/utils/wrappers.Packer.UnpackLong"
+    pub fn unpack_u64(&self) -> io::Result<u64> {
+        let offset = self.check_unpack(U64_LEN)?;
+        let b = self.bytes.take();
+
+        let pos = &b[offset..offset + U64_LEN];
+
+        // ref. "binary.BigEndian.Uint64"
+        // ref. https://doc.rust-lang.org/std/primitive.u64.html#method.from_be_bytes
+        let v = u64::from_be_bytes([
+            pos[0], pos[1], pos[2], pos[3], pos[4], pos[5], pos[6], pos[7],
+        ]);
+
+        // remember to put it back -- "take" leaves the field as "Default::default()"
+        self.bytes.set(b);
+
+        self.set_offset(offset + U64_LEN);
+        Ok(v)
+    }
+
     /// Writes the "u8" slice from the offset and increments the offset as much.
     /// ref. "avalanchego/utils/wrappers.Packer.PackFixedBytes"
     pub fn pack_bytes(&self, v: &[u8]) {
@@ -425,4 +457,284 @@ impl Packer {
         // ref. https://docs.rs/bytes/latest/bytes/buf/trait.BufMut.html#method.put_u64
         self.set_offset(offset + n);
     }
+
+    /// Unpacks the "n" bytes from the "offset" position,
+    /// and advances the cursor and offset.
+    /// ref. "avalanchego/utils/wrappers.Packer.UnpackFixedBytes"
+    pub fn unpack_bytes(&self, n: usize) -> io::Result<Vec<u8>> {
+        let offset = self.check_unpack(n)?;
+        let b = self.bytes.take();
+
+        let v = b[offset..offset + n].to_vec();
+
+        // remember to put it back -- "take" leaves the field as "Default::default()"
+        self.bytes.set(b);
+
+        self.set_offset(offset + n);
+        Ok(v)
+    }
+
+    /// Writes the "u8" slice prefixed with its "u32" length.
+    /// ref. "avalanchego/utils/wrappers.Packer.PackBytes"
+    pub fn pack_bytes_with_header(&self, v: &[u8]) {
+        self.pack_u32(v.len() as u32);
+        self.pack_bytes(v);
+    }
+
+    /// Unpacks the "u8" slice prefixed with its "u32" length.
+    /// ref. "avalanchego/utils/wrappers.Packer.UnpackBytes"
+    pub fn unpack_bytes_with_header(&self) -> io::Result<Vec<u8>> {
+        self.unpack_or_restore(|| {
+            let n = self.unpack_u32()?;
+            self.unpack_bytes(n as usize)
+        })
+    }
+
+    /// Writes the string prefixed with its "u16" length.
+    /// ref. "avalanchego/utils/wrappers.Packer.PackStr"
+    pub fn pack_str(&self, v: &str) {
+        let n = v.len();
+        if n > MAX_STR_LEN as usize {
+            self.set_error(Error::new(
+                ErrorKind::InvalidInput,
+                format!("string length {} > max {}", n, MAX_STR_LEN), // ref. "errInvalidInput"
+            ));
+            return;
+        }
+        self.pack_u16(n as u16);
+        self.pack_bytes(v.as_bytes());
+    }
+
+    /// Unpacks the string prefixed with its "u16" length.
+    /// ref. "avalanchego/utils/wrappers.Packer.UnpackStr"
+    pub fn unpack_str(&self) -> io::Result<String> {
+        self.unpack_or_restore(|| {
+            let n = self.unpack_u16()?;
+            let b = self.unpack_bytes(n as usize)?;
+            String::from_utf8(b).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("failed to decode string ({})", e),
+                )
+            })
+        })
+    }
+
+    /// Writes the "bool" value as a single byte.
+    /// ref. "avalanchego/utils/wrappers.Packer.PackBool"
+    pub fn pack_bool(&self, v: bool) {
+        self.pack_byte(if v { 1 } else { 0 });
+    }
+
+    /// Unpacks the "bool" value, rejecting any byte other than 0 or 1.
+    /// ref. "avalanchego/utils/wrappers.Packer.UnpackBool"
+    pub fn unpack_bool(&self) -> io::Result<bool> {
+        let offset = self.get_offset();
+        match self.unpack_byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => {
+                self.set_offset(offset);
+                Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("unexpected value {} when unpacking bool", b), // ref. "errBadBool"
+                ))
+            }
+        }
+    }
+
+    /// Writes the number of byte slices, followed by each slice with its length.
+    /// ref. "avalanchego/utils/wrappers.Packer.Pack2DByteSlice"
+    pub fn pack_2d_bytes(&self, v: &[Vec<u8>]) {
+        self.pack_u32(v.len() as u32);
+        for b in v.iter() {
+            self.pack_bytes_with_header(b);
+        }
+    }
+
+    /// Unpacks the byte slices written by "pack_2d_bytes".
+    /// ref. "avalanchego/utils/wrappers.Packer.Unpack2DByteSlice"
+    pub fn unpack_2d_bytes(&self) -> io::Result<Vec<Vec<u8>>> {
+        self.unpack_or_restore(|| {
+            let n = self.unpack_u32()?;
+            let mut v: Vec<Vec<u8>> = Vec::new();
+            for _ in 0..n {
+                v.push(self.unpack_bytes_with_header()?);
+            }
+            Ok(v)
+        })
+    }
+
+    /// Writes the 16-byte IP address followed by the "u16" port,
+    /// where the IPv4 address is written in its IPv4-mapped IPv6 form.
+    /// ref. "avalanchego/utils/wrappers.Packer.PackIP"
+    pub fn pack_ip(&self, v: &SocketAddr) {
+        let ip = match v.ip() {
+            IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+            IpAddr::V6(ip) => ip,
+        };
+        self.pack_bytes(&ip.octets());
+        self.pack_u16(v.port());
+    }
+
+    /// Unpacks the IP address and port written by "pack_ip",
+    /// returning the IPv4 address for the IPv4-mapped IPv6 form.
+    /// ref. "avalanchego/utils/wrappers.Packer.UnpackIP"
+    pub fn unpack_ip(&self) -> io::Result<SocketAddr> {
+        self.check_unpack(IP_LEN)?;
+        let b = self.unpack_bytes(IP_LEN - U16_LEN)?;
+        let port = self.unpack_u16()?;
+
+        let mut octets = [0u8; 16];
+        octets.copy_from_slice(&b);
+        let ip = Ipv6Addr::from(octets);
+        let ip = match ip.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => IpAddr::V6(ip),
+        };
+        Ok(SocketAddr::new(ip, port))
+    }
+
+    /// Runs the unpacking of the multiple fields, and moves the offset back
+    /// to where it started if any field fails, so that a failed read never
+    /// leaves the offset in the middle of a value (same as the single reads).
+    fn unpack_or_restore<T, F>(&self, f: F) -> io::Result<T>
+    where
+        F: FnOnce() -> io::Result<T>,
+    {
+        let offset = self.get_offset();
+        let v = f();
+        if v.is_err() {
+            self.set_offset(offset);
+        }
+        v
+    }
+
+    /// Returns the number of bytes left to unpack.
+    pub fn remaining(&self) -> usize {
+        self.bytes_len().saturating_sub(self.get_offset())
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- packer::test_unpack --exact --show-output
+#[test]
+fn test_unpack() {
+    let packer = Packer::new(1024, 0);
+    packer.pack_byte(0x01);
+    packer.pack_u16(0x0203);
+    packer.pack_u32(0x04050607);
+    packer.pack_u64(0x08090a0b0c0d0e0f);
+    packer.pack_bytes(&[0x10, 0x11]);
+    packer.pack_bytes_with_header(&[0x12]);
+    packer.pack_str("avax");
+    assert!(packer.take_error().is_none());
+    let b = packer.take_bytes();
+    assert_eq!(
+        &b[..],
+        &[
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+            0x0f, 0x10, 0x11, 0x00, 0x00, 0x00, 0x01, 0x12, 0x00, 0x04, b'a', b'v', b'a', b'x',
+        ]
+    );
+
+    let packer = Packer::load_bytes_for_unpack(1024, &b);
+    assert_eq!(packer.unpack_byte().unwrap(), 0x01);
+    assert_eq!(packer.unpack_u16().unwrap(), 0x0203);
+    assert_eq!(packer.unpack_u32().unwrap(), 0x04050607);
+    assert_eq!(packer.unpack_u64().unwrap(), 0x08090a0b0c0d0e0f);
+    assert_eq!(packer.unpack_bytes(2).unwrap(), vec![0x10, 0x11]);
+    assert_eq!(packer.unpack_bytes_with_header().unwrap(), vec![0x12]);
+    assert_eq!(packer.unpack_str().unwrap(), "avax");
+    assert_eq!(packer.remaining(), 0);
+
+    // short reads fail without moving the offset
+    let offset = packer.get_offset();
+    let err = packer.unpack_u32().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(packer.get_offset(), offset);
+
+    // length header larger than the remaining bytes
+    let packer = Packer::load_bytes_for_unpack(1024, &[0x00, 0x00, 0x00, 0xff, 0x01]);
+    assert!(packer.unpack_bytes_with_header().is_err());
+    assert_eq!(packer.get_offset(), 0);
+    assert!(packer.unpack_bytes(usize::MAX).is_err());
+
+    let packer = Packer::load_bytes_for_unpack(1024, &[0x00, 0x01, 0xff]);
+    assert_eq!(
+        packer.unpack_str().unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+    assert_eq!(packer.get_offset(), 0);
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- packer::test_wire_format --exact --show-output
+#[test]
+fn test_wire_format() {
+    use std::net::Ipv4Addr;
+
+    // written out field by field per "avalanchego/utils/wrappers.Packer"
+    let packer = Packer::new(1024, 0);
+    packer.pack_bool(false);
+    packer.pack_bool(true);
+    packer.pack_2d_bytes(&[]);
+    packer.pack_2d_bytes(&[vec![1, 2, 3], vec![4, 5, 6]]);
+    packer.pack_ip(&SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)), 5));
+    packer.pack_ip(&SocketAddr::new(IpAddr::V6(Ipv6Addr::LOCALHOST), 9651));
+    assert!(packer.take_error().is_none());
+    let b = packer.take_bytes();
+
+    let expected: Vec<u8> = vec![
+        0x00, // false
+        0x01, // true
+        0x00, 0x00, 0x00, 0x00, // number of byte slices
+        0x00, 0x00, 0x00, 0x02, // number of byte slices
+        0x00, 0x00, 0x00, 0x03, // length
+        0x01, 0x02, 0x03, //
+        0x00, 0x00, 0x00, 0x03, // length
+        0x04, 0x05, 0x06, //
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // IPv4-mapped IPv6 prefix
+        0x00, 0x00, 0xff, 0xff, // IPv4-mapped IPv6 prefix
+        0x01, 0x02, 0x03, 0x04, // 1.2.3.4
+        0x00, 0x05, // port
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // ::1
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, // ::1
+        0x25, 0xb3, // port 9651
+    ];
+    assert_eq!(&b[..], &expected[..]);
+
+    let packer = Packer::load_bytes_for_unpack(1024, &b);
+    assert!(!packer.unpack_bool().unwrap());
+    assert!(packer.unpack_bool().unwrap());
+    assert!(packer.unpack_2d_bytes().unwrap().is_empty());
+    assert_eq!(
+        packer.unpack_2d_bytes().unwrap(),
+        vec![vec![1, 2, 3], vec![4, 5, 6]]
+    );
+    assert_eq!(packer.unpack_ip().unwrap().to_string(), "1.2.3.4:5");
+    assert_eq!(packer.unpack_ip().unwrap().to_string(), "[::1]:9651");
+    assert_eq!(packer.remaining(), 0);
+
+    let packer = Packer::load_bytes_for_unpack(1024, &[0x02]);
+    assert_eq!(
+        packer.unpack_bool().unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+    assert_eq!(packer.get_offset(), 0);
+
+    // two byte slices announced, only one written
+    let packer = Packer::load_bytes_for_unpack(
+        1024,
+        &[0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x01],
+    );
+    assert_eq!(
+        packer.unpack_2d_bytes().unwrap_err().kind(),
+        ErrorKind::UnexpectedEof
+    );
+    // not left after the first slice, so the same bytes can be read again
+    assert_eq!(packer.get_offset(), 0);
+    assert_eq!(packer.unpack_u32().unwrap(), 2);
+
+    let packer = Packer::load_bytes_for_unpack(1024, &[0x00; IP_LEN - 1]);
+    assert!(packer.unpack_ip().is_err());
+    assert_eq!(packer.get_offset(), 0);
 }
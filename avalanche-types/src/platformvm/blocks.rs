@@ -0,0 +1,365 @@
+use std::{
+    collections::HashMap,
+    io::{self, Error, ErrorKind},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{codec, ids, packer, platformvm::txs};
+use utils::hash;
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/blocks#Block
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Proposal,
+    Abort,
+    Commit,
+    Standard,
+    Atomic,
+}
+
+impl Kind {
+    /// Returns the kind of the block type ID, and whether it is the Banff block.
+    fn from_type_id(type_id: u32) -> io::Result<(Self, bool)> {
+        let name = codec::P_CODEC
+            .type_name(codec::VERSION, type_id)
+            .unwrap_or("unknown");
+        match name {
+            "platformvm.ProposalBlock" => Ok((Kind::Proposal, false)),
+            "platformvm.AbortBlock" => Ok((Kind::Abort, false)),
+            "platformvm.CommitBlock" => Ok((Kind::Commit, false)),
+            "platformvm.StandardBlock" => Ok((Kind::Standard, false)),
+            "platformvm.AtomicBlock" => Ok((Kind::Atomic, false)),
+            "blocks.BanffProposalBlock" => Ok((Kind::Proposal, true)),
+            "blocks.BanffAbortBlock" => Ok((Kind::Abort, true)),
+            "blocks.BanffCommitBlock" => Ok((Kind::Commit, true)),
+            "blocks.BanffStandardBlock" => Ok((Kind::Standard, true)),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("type ID {} ({}) is not a block", type_id, name),
+            )),
+        }
+    }
+}
+
+/// Tx embedded in the block, with its ID.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct BlockTx {
+    pub id: ids::Id,
+    pub tx: txs::Tx,
+}
+
+/// Accepted P-chain block, as returned by the "/ext/index/P/block" index.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/blocks
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Block {
+    pub id: ids::Id,
+    pub kind: Kind,
+    /// Unix time in seconds of the Banff block, "None" before Banff
+    /// where the time is only moved by the advance time tx.
+    pub time: Option<u64>,
+    pub parent_id: ids::Id,
+    pub height: u64,
+    /// Decision txs of the standard block, or the one tx
+    /// of the proposal and atomic blocks. Empty for the option blocks.
+    pub txs: Vec<BlockTx>,
+}
+
+impl Block {
+    /// Decodes the codec-encoded block bytes (e.g., "index::Container.decode_bytes")
+    /// with all its txs.
+    pub fn from_bytes(b: &[u8]) -> io::Result<Self> {
+        codec::P_CODEC.unmarshal(b, |_, packer| {
+            let (kind, banff) = Kind::from_type_id(packer.unpack_u32()?)?;
+
+            // Banff blocks prepend the time to the Apricot block fields
+            let time = if banff {
+                Some(packer.unpack_u64()?)
+            } else {
+                None
+            };
+            if banff && kind == Kind::Proposal {
+                // reserved for the future, so must be empty
+                let n = packer.unpack_u32()?;
+                if n > 0 {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("unexpected {} txs in Banff proposal block", n),
+                    ));
+                }
+            }
+
+            let parent_id = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+            let height = packer.unpack_u64()?;
+
+            let mut txs: Vec<BlockTx> = Vec::new();
+            match kind {
+                Kind::Proposal | Kind::Atomic => txs.push(unpack_block_tx(b, packer)?),
+                Kind::Standard => {
+                    let n = packer.unpack_u32()?;
+                    for _ in 0..n {
+                        txs.push(unpack_block_tx(b, packer)?);
+                    }
+                }
+                Kind::Abort | Kind::Commit => {}
+            }
+
+            Ok(Self {
+                id: ids::Id::from_slice(&hash::compute_sha256(b)),
+                kind,
+                time,
+                parent_id,
+                height,
+                txs,
+            })
+        })
+    }
+
+    /// Returns the tx of the proposal block, whose outcome is decided
+    /// by the following commit or abort block.
+    pub fn proposal_tx(&self) -> Option<&txs::Tx> {
+        match self.kind {
+            Kind::Proposal => self.txs.first().map(|blk_tx| &blk_tx.tx),
+            _ => None,
+        }
+    }
+}
+
+/// Unpacks the "*txs.Tx" embedded in the block "b", which has no codec version.
+/// The tx ID is computed over the bytes of the tx as if issued alone,
+/// with the codec version of the block.
+fn unpack_block_tx(b: &[u8], packer: &packer::Packer) -> io::Result<BlockTx> {
+    let start = packer.get_offset();
+    let tx = txs::Tx::unpack(packer)?;
+    let end = packer.get_offset();
+
+    let mut tx_bytes = b[..2].to_vec();
+    tx_bytes.extend_from_slice(&b[start..end]);
+    Ok(BlockTx {
+        id: ids::Id::from_slice(&hash::compute_sha256(&tx_bytes)),
+        tx,
+    })
+}
+
+/// Outcome of the reward validator tx.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct RewardDecision {
+    /// ID of the add validator or delegator tx of the staker.
+    pub staker_tx_id: ids::Id,
+    /// Height of the commit or abort block.
+    pub height: u64,
+    /// "true" if committed and the reward was paid, "false" if aborted
+    /// (e.g., the staker uptime was below the requirement).
+    pub rewarded: bool,
+}
+
+/// Pairs each reward validator tx with the commit or abort block
+/// accepted after its proposal block, in the order of "blocks"
+/// (e.g., the range of the P-chain block index).
+/// The proposal blocks without the option block in "blocks" are ignored.
+pub fn reward_decisions(blocks: &[Block]) -> Vec<RewardDecision> {
+    let mut pending: HashMap<ids::Id, ids::Id> = HashMap::new();
+    let mut decisions: Vec<RewardDecision> = Vec::new();
+    for blk in blocks.iter() {
+        match (&blk.kind, blk.proposal_tx()) {
+            (Kind::Proposal, Some(txs::Tx::RewardValidator(tx))) => {
+                pending.insert(blk.id, tx.tx_id);
+            }
+            (Kind::Commit, _) | (Kind::Abort, _) => {
+                if let Some(staker_tx_id) = pending.remove(&blk.parent_id) {
+                    decisions.push(RewardDecision {
+                        staker_tx_id,
+                        height: blk.height,
+                        rewarded: blk.kind == Kind::Commit,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    decisions
+}
+
+#[cfg(test)]
+fn test_block_bytes(
+    type_name: &str,
+    time: Option<u64>,
+    parent_id: &ids::Id,
+    height: u64,
+    body: &[u8],
+) -> Vec<u8> {
+    codec::P_CODEC
+        .marshal(codec::VERSION, |packer| {
+            packer.pack_u32(codec::P_CODEC.type_id(codec::VERSION, type_name)?);
+            if let Some(time) = time {
+                packer.pack_u64(time);
+            }
+            if type_name == "blocks.BanffProposalBlock" {
+                packer.pack_u32(0);
+            }
+            packer.pack_bytes(parent_id.as_bytes());
+            packer.pack_u64(height);
+            packer.pack_bytes(body);
+            Ok(())
+        })
+        .unwrap()
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::blocks::test_reward_decisions --exact --show-output
+#[test]
+fn test_reward_decisions() {
+    use crate::platformvm::txs::{advance_time, reward_validator};
+
+    // strips the codec version to embed the tx in the block
+    let embed = |b: Vec<u8>| b[2..].to_vec();
+    let staker_a = ids::Id::from_slice(&[0xaa; 32]);
+    let staker_b = ids::Id::from_slice(&[0xbb; 32]);
+
+    let genesis = ids::Id::from_slice(&[0x01; 32]);
+    let b1 = test_block_bytes(
+        "platformvm.ProposalBlock",
+        None,
+        &genesis,
+        1,
+        &embed(advance_time::Tx::new(1_700_000_000).to_bytes().unwrap().0),
+    );
+    let blk1 = Block::from_bytes(&b1).unwrap();
+    assert_eq!(blk1.kind, Kind::Proposal);
+    assert_eq!(blk1.time, None);
+    assert_eq!(blk1.parent_id, genesis);
+    assert_eq!(
+        blk1.proposal_tx(),
+        Some(&txs::Tx::AdvanceTime(advance_time::Tx::new(1_700_000_000)))
+    );
+    // same ID as the tx issued alone
+    assert_eq!(
+        blk1.txs[0].id,
+        advance_time::Tx::new(1_700_000_000).to_bytes().unwrap().1
+    );
+    assert_eq!(blk1.id, ids::Id::from_slice(&hash::compute_sha256(&b1)));
+
+    let blk2 = Block::from_bytes(&test_block_bytes(
+        "platformvm.CommitBlock",
+        None,
+        &blk1.id,
+        2,
+        &[],
+    ))
+    .unwrap();
+
+    let blk3 = Block::from_bytes(&test_block_bytes(
+        "platformvm.ProposalBlock",
+        None,
+        &blk2.id,
+        3,
+        &embed(reward_validator::Tx::new(staker_a).to_bytes().unwrap().0),
+    ))
+    .unwrap();
+    let blk4 = Block::from_bytes(&test_block_bytes(
+        "platformvm.CommitBlock",
+        None,
+        &blk3.id,
+        4,
+        &[],
+    ))
+    .unwrap();
+
+    let mut validator_tx = txs::add_validator::test_tx();
+    let (signed_bytes, validator_tx_id) = validator_tx
+        .sign(&[vec![crate::soft_key::TEST_KEYS[0].clone()]])
+        .unwrap();
+    let mut body = vec![0x00, 0x00, 0x00, 0x01]; // number of txs
+    body.extend(embed(signed_bytes));
+    let blk5 = Block::from_bytes(&test_block_bytes(
+        "blocks.BanffStandardBlock",
+        Some(1_700_000_100),
+        &blk4.id,
+        5,
+        &body,
+    ))
+    .unwrap();
+    assert_eq!(blk5.kind, Kind::Standard);
+    assert_eq!(blk5.time, Some(1_700_000_100));
+    assert_eq!(blk5.proposal_tx(), None);
+    assert_eq!(
+        blk5.txs,
+        vec![BlockTx {
+            id: validator_tx_id,
+            tx: txs::Tx::AddValidator(validator_tx),
+        }]
+    );
+
+    let blk6 = Block::from_bytes(&test_block_bytes(
+        "blocks.BanffProposalBlock",
+        Some(1_700_000_200),
+        &blk5.id,
+        6,
+        &embed(reward_validator::Tx::new(staker_b).to_bytes().unwrap().0),
+    ))
+    .unwrap();
+    assert_eq!(blk6.height, 6);
+    assert_eq!(
+        blk6.proposal_tx(),
+        Some(&txs::Tx::RewardValidator(reward_validator::Tx::new(
+            staker_b
+        )))
+    );
+    let blk7 = Block::from_bytes(&test_block_bytes(
+        "blocks.BanffAbortBlock",
+        Some(1_700_000_200),
+        &blk6.id,
+        7,
+        &[],
+    ))
+    .unwrap();
+    assert_eq!(blk7.kind, Kind::Abort);
+
+    let blocks = vec![blk1, blk2, blk3, blk4, blk5, blk6, blk7];
+    assert_eq!(
+        reward_decisions(&blocks),
+        vec![
+            RewardDecision {
+                staker_tx_id: staker_a,
+                height: 4,
+                rewarded: true,
+            },
+            RewardDecision {
+                staker_tx_id: staker_b,
+                height: 7,
+                rewarded: false,
+            },
+        ]
+    );
+    // the last proposal block is still waiting for its option block
+    assert_eq!(reward_decisions(&blocks[..6]).len(), 1);
+
+    // the truncated tx in the standard block
+    assert!(Block::from_bytes(&test_block_bytes(
+        "blocks.BanffStandardBlock",
+        Some(1_700_000_100),
+        &genesis,
+        1,
+        &[0x00, 0x00, 0x00, 0x01, 0xff, 0xff],
+    ))
+    .is_err());
+
+    // option blocks have no body
+    assert!(Block::from_bytes(&test_block_bytes(
+        "platformvm.AbortBlock",
+        None,
+        &genesis,
+        1,
+        &[0x00],
+    ))
+    .is_err());
+    let err = Block::from_bytes(&test_block_bytes(
+        "platformvm.UnsignedAddValidatorTx",
+        None,
+        &genesis,
+        1,
+        &[],
+    ))
+    .unwrap_err();
+    assert!(err.to_string().contains("is not a block"));
+}
@@ -1,8 +1,11 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, io};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{codec, ids, secp256k1fx};
+pub mod blocks;
+pub mod txs;
+
+use crate::{codec, ids, packer, secp256k1fx};
 
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/constants#pkg-variables
 pub fn chain_id() -> ids::Id {
@@ -33,6 +36,22 @@ impl Validator {
             weight: 0,
         }
     }
+
+    pub fn pack(&self, packer: &packer::Packer) {
+        packer.pack_bytes(&self.node_id.d);
+        packer.pack_u64(self.start);
+        packer.pack_u64(self.end);
+        packer.pack_u64(self.weight);
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        Ok(Self {
+            node_id: ids::ShortId::from_slice(&packer.unpack_bytes(ids::SHORT_ID_LEN)?),
+            start: packer.unpack_u64()?,
+            end: packer.unpack_u64()?,
+            weight: packer.unpack_u64()?,
+        })
+    }
 }
 
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm#StakeableLockIn
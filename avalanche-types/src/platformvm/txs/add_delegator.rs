@@ -0,0 +1,171 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer, platformvm, secp256k1fx, soft_key};
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm#UnsignedAddDelegatorTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+    pub validator: platformvm::Validator,
+    pub stake_transferable_outputs: Option<Vec<avax::TransferableOutput>>,
+    pub rewards_owner: secp256k1fx::OutputOwners,
+
+    /// Populated by "sign", one per input.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            validator: platformvm::Validator::default(),
+            stake_transferable_outputs: None,
+            rewards_owner: secp256k1fx::OutputOwners::default(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "platformvm.UnsignedAddDelegatorTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// ref. "avalanchego/vms/platformvm.UnsignedAddDelegatorTx.SyntacticVerify"
+    pub fn verify(&self) -> io::Result<()> {
+        super::verify_staker(
+            &self.validator,
+            &self.stake_transferable_outputs.clone().unwrap_or_default(),
+        )
+    }
+
+    /// Encodes the unsigned "platformvm.UnsignedAddDelegatorTx", the staker
+    /// fields alone since a delegator sets no fee shares.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::P_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            super::pack_staker(
+                packer,
+                &self.base_tx,
+                &self.validator,
+                &self.stake_transferable_outputs.clone().unwrap_or_default(),
+                &self.rewards_owner,
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Decodes the signed tx bytes (e.g., returned by "platform.getTx"),
+    /// returning the tx with its credentials and ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        super::decode_signed(b, Self::type_id(), Self::unpack)
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let (base_tx, validator, stake, rewards_owner) = super::unpack_staker(packer)?;
+        Ok(Self {
+            base_tx,
+            validator,
+            stake_transferable_outputs: if stake.is_empty() { None } else { Some(stake) },
+            rewards_owner,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+
+    /// Signs the tx with the keys for each input (in the same order as the inputs),
+    /// and returns the signed tx bytes to issue with its tx ID.
+    pub fn sign(&mut self, signers: &[Vec<soft_key::Key>]) -> io::Result<(Vec<u8>, ids::Id)> {
+        self.verify()?;
+
+        let ins = self
+            .base_tx
+            .transferable_inputs
+            .as_ref()
+            .map_or(0, |ins| ins.len());
+        if ins != signers.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} inputs but {} signers", ins, signers.len()),
+            ));
+        }
+
+        let unsigned_bytes = self.unsigned_bytes()?;
+        self.creds = avax::sign_credentials(&unsigned_bytes, signers)?;
+        avax::pack_signed_tx(&unsigned_bytes, &self.creds)
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::add_delegator::test_unsigned_bytes --exact --show-output
+#[test]
+fn test_unsigned_bytes() {
+    let validator_tx = super::add_validator::test_tx();
+    let tx = Tx {
+        base_tx: validator_tx.base_tx.clone(),
+        validator: validator_tx.validator.clone(),
+        stake_transferable_outputs: validator_tx.stake_transferable_outputs.clone(),
+        rewards_owner: validator_tx.rewards_owner.clone(),
+        creds: Vec::new(),
+    };
+    tx.verify().unwrap();
+
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x0e, // "platformvm.UnsignedAddDelegatorTx" type ID
+    ];
+    expected.extend(super::test_base_tx_bytes());
+    expected.extend(super::test_validator_bytes());
+    expected.extend(super::test_stake_outputs_bytes());
+    expected.extend(super::test_owner_bytes());
+    assert_eq!(tx.unsigned_bytes().unwrap(), expected);
+
+    let mut mismatch = tx.clone();
+    mismatch.validator.weight += 1;
+    assert!(mismatch.verify().is_err());
+
+    let mut signed = tx.clone();
+    let (signed_bytes, tx_id) = signed
+        .sign(&[vec![soft_key::TEST_KEYS[0].clone()]])
+        .unwrap();
+    let mut expected_signed = expected.clone();
+    expected_signed.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x01, // number of credentials
+        0x00, 0x00, 0x00, 0x09, // "secp256k1fx.Credential" type ID
+        0x00, 0x00, 0x00, 0x01, // number of signatures
+    ]);
+    expected_signed.extend_from_slice(&signed.creds[0].signatures[0]);
+    assert_eq!(signed_bytes, expected_signed);
+    assert_eq!(Tx::from_bytes(&signed_bytes).unwrap(), (signed, tx_id));
+    // the same tx signed by avalanchego is checked against this one by
+    // "compatibility/add-delegator-tx" in "scripts/tests.compatibility.sh"
+
+    // decodes the tx written out field by field, not only what the packer wrote
+    let mut b = expected;
+    b.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x01, // number of credentials
+        0x00, 0x00, 0x00, 0x09, // "secp256k1fx.Credential" type ID
+        0x00, 0x00, 0x00, 0x01, // number of signatures
+    ]);
+    b.extend_from_slice(&[0x44; 65]);
+    let (decoded, decoded_id) = Tx::from_bytes(&b).unwrap();
+    assert_eq!(
+        decoded,
+        Tx {
+            creds: vec![secp256k1fx::Credential::new(vec![vec![0x44; 65]])],
+            ..tx
+        }
+    );
+    assert_eq!(
+        decoded_id,
+        ids::Id::from_slice(&utils::hash::compute_sha256(&b))
+    );
+}
@@ -0,0 +1,119 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer, platformvm, secp256k1fx};
+
+/// Delegates to the primary network validator or to the elastic subnet validator.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/txs#AddPermissionlessDelegatorTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+    pub validator: platformvm::Validator,
+    /// Empty for the primary network.
+    pub subnet_id: ids::Id,
+    pub stake_transferable_outputs: Option<Vec<avax::TransferableOutput>>,
+    pub rewards_owner: secp256k1fx::OutputOwners,
+
+    /// One per input.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            validator: platformvm::Validator::default(),
+            subnet_id: ids::Id::empty(),
+            stake_transferable_outputs: None,
+            rewards_owner: secp256k1fx::OutputOwners::default(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "txs.AddPermissionlessDelegatorTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// Encodes the unsigned "txs.AddPermissionlessDelegatorTx": the base tx,
+    /// the validator, the subnet ID, the stake and the rewards owner.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::P_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            self.base_tx.pack(packer)?;
+            self.validator.pack(packer);
+            packer.pack_bytes(self.subnet_id.as_bytes());
+
+            let stake = self.stake_transferable_outputs.clone().unwrap_or_default();
+            packer.pack_u32(stake.len() as u32);
+            for out in stake.iter() {
+                out.pack(packer)?;
+            }
+
+            // "fx.Owner" interface, so the type ID comes first
+            packer.pack_u32(secp256k1fx::OutputOwners::type_id());
+            self.rewards_owner.pack(packer);
+            Ok(())
+        })
+    }
+
+    /// Decodes the signed tx bytes (e.g., returned by "platform.getTx"),
+    /// returning the tx with its credentials and ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        super::decode_signed(b, Self::type_id(), Self::unpack)
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let base_tx = avax::BaseTx::unpack(packer)?;
+        let validator = platformvm::Validator::unpack(packer)?;
+        let subnet_id = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+        let stake = super::unpack_outputs(packer)?;
+        Ok(Self {
+            base_tx,
+            validator,
+            subnet_id,
+            stake_transferable_outputs: if stake.is_empty() { None } else { Some(stake) },
+            rewards_owner: super::unpack_owner(packer)?,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::add_permissionless_delegator::test_from_bytes --exact --show-output
+#[test]
+fn test_from_bytes() {
+    let validator_tx = super::add_validator::test_tx();
+    let tx = Tx {
+        base_tx: validator_tx.base_tx.clone(),
+        validator: validator_tx.validator.clone(),
+        subnet_id: ids::Id::empty(),
+        stake_transferable_outputs: validator_tx.stake_transferable_outputs.clone(),
+        rewards_owner: validator_tx.rewards_owner.clone(),
+        creds: vec![secp256k1fx::Credential::new(vec![vec![0x44; 65]])],
+    };
+
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x1a, // "txs.AddPermissionlessDelegatorTx" type ID
+    ];
+    expected.extend(super::test_base_tx_bytes());
+    expected.extend(super::test_validator_bytes());
+    expected.extend_from_slice(&[0x00; 32]); // primary network subnet ID
+    expected.extend(super::test_stake_outputs_bytes());
+    expected.extend(super::test_owner_bytes());
+    let unsigned_bytes = tx.unsigned_bytes().unwrap();
+    assert_eq!(unsigned_bytes, expected);
+
+    let (signed_bytes, tx_id) = avax::pack_signed_tx(&unsigned_bytes, &tx.creds).unwrap();
+    assert_eq!(Tx::from_bytes(&signed_bytes).unwrap(), (tx, tx_id));
+}
@@ -0,0 +1,241 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, bls_key, codec, ids, packer, platformvm, secp256k1fx, soft_key};
+
+/// Adds the validator to the primary network with its BLS key,
+/// or to the elastic subnet without one.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/txs#AddPermissionlessValidatorTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+    pub validator: platformvm::Validator,
+    /// Empty for the primary network.
+    pub subnet_id: ids::Id,
+    /// Required for the primary network, "None" (i.e., "signer.Empty") otherwise.
+    pub signer: Option<bls_key::ProofOfPossession>,
+    pub stake_transferable_outputs: Option<Vec<avax::TransferableOutput>>,
+    pub validator_rewards_owner: secp256k1fx::OutputOwners,
+    pub delegator_rewards_owner: secp256k1fx::OutputOwners,
+    /// Delegation fee in the units of "txs::PERCENT_DENOMINATOR".
+    pub delegation_shares: u32,
+
+    /// Populated by "sign", one per input.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            validator: platformvm::Validator::default(),
+            subnet_id: ids::Id::empty(),
+            signer: None,
+            stake_transferable_outputs: None,
+            validator_rewards_owner: secp256k1fx::OutputOwners::default(),
+            delegator_rewards_owner: secp256k1fx::OutputOwners::default(),
+            delegation_shares: 0,
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "txs.AddPermissionlessValidatorTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// ref. "avalanchego/vms/platformvm/txs.AddPermissionlessValidatorTx.SyntacticVerify"
+    pub fn verify(&self) -> io::Result<()> {
+        if self.delegation_shares > super::PERCENT_DENOMINATOR {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "delegation shares {} exceeds {}", // ref. "errTooManyShares"
+                    self.delegation_shares,
+                    super::PERCENT_DENOMINATOR
+                ),
+            ));
+        }
+
+        let primary_network = self.subnet_id == ids::Id::empty();
+        match &self.signer {
+            Some(pop) if primary_network => pop.verify()?,
+            Some(_) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "subnet {} validator must not have the BLS key",
+                        self.subnet_id
+                    ),
+                ))
+            }
+            None if primary_network => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "primary network validator requires the BLS key", // ref. "errEmptySigner"
+                ));
+            }
+            None => {}
+        }
+
+        super::verify_staker(
+            &self.validator,
+            &self.stake_transferable_outputs.clone().unwrap_or_default(),
+        )
+    }
+
+    /// Encodes the unsigned "txs.AddPermissionlessValidatorTx", where the BLS
+    /// proof of possession (or the empty signer) precedes the stake.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::P_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            self.base_tx.pack(packer)?;
+            self.validator.pack(packer);
+            packer.pack_bytes(self.subnet_id.as_bytes());
+
+            // "signer.Signer" interface, so the type ID comes first
+            match &self.signer {
+                Some(pop) => pop.pack(packer)?,
+                None => packer.pack_u32(bls_key::ProofOfPossession::empty_type_id()),
+            }
+
+            let stake = self.stake_transferable_outputs.clone().unwrap_or_default();
+            packer.pack_u32(stake.len() as u32);
+            for out in stake.iter() {
+                out.pack(packer)?;
+            }
+
+            // "fx.Owner" interfaces
+            packer.pack_u32(secp256k1fx::OutputOwners::type_id());
+            self.validator_rewards_owner.pack(packer);
+            packer.pack_u32(secp256k1fx::OutputOwners::type_id());
+            self.delegator_rewards_owner.pack(packer);
+
+            packer.pack_u32(self.delegation_shares);
+            Ok(())
+        })
+    }
+
+    /// Decodes the signed tx bytes (e.g., returned by "platform.getTx"),
+    /// returning the tx with its credentials and ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        super::decode_signed(b, Self::type_id(), Self::unpack)
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let base_tx = avax::BaseTx::unpack(packer)?;
+        let validator = platformvm::Validator::unpack(packer)?;
+        let subnet_id = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+        let signer = bls_key::ProofOfPossession::unpack(packer)?;
+        let stake = super::unpack_outputs(packer)?;
+        Ok(Self {
+            base_tx,
+            validator,
+            subnet_id,
+            signer,
+            stake_transferable_outputs: if stake.is_empty() { None } else { Some(stake) },
+            validator_rewards_owner: super::unpack_owner(packer)?,
+            delegator_rewards_owner: super::unpack_owner(packer)?,
+            delegation_shares: packer.unpack_u32()?,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+
+    /// Signs the tx with the keys for each input (in the same order as the inputs),
+    /// and returns the signed tx bytes to issue with its tx ID.
+    pub fn sign(&mut self, signers: &[Vec<soft_key::Key>]) -> io::Result<(Vec<u8>, ids::Id)> {
+        self.verify()?;
+
+        let ins = self
+            .base_tx
+            .transferable_inputs
+            .as_ref()
+            .map_or(0, |ins| ins.len());
+        if ins != signers.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} inputs but {} signers", ins, signers.len()),
+            ));
+        }
+
+        let unsigned_bytes = self.unsigned_bytes()?;
+        self.creds = avax::sign_credentials(&unsigned_bytes, signers)?;
+        avax::pack_signed_tx(&unsigned_bytes, &self.creds)
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::add_permissionless_validator::test_unsigned_bytes --exact --show-output
+#[test]
+fn test_unsigned_bytes() {
+    let validator_tx = super::add_validator::test_tx();
+    let signer_key = bls_key::Key::generate().unwrap();
+    let pop = signer_key.proof_of_possession();
+    let tx = Tx {
+        base_tx: validator_tx.base_tx.clone(),
+        validator: validator_tx.validator.clone(),
+        subnet_id: ids::Id::empty(),
+        signer: Some(pop.clone()),
+        stake_transferable_outputs: validator_tx.stake_transferable_outputs.clone(),
+        validator_rewards_owner: validator_tx.rewards_owner.clone(),
+        delegator_rewards_owner: validator_tx.rewards_owner.clone(),
+        delegation_shares: validator_tx.shares,
+        creds: Vec::new(),
+    };
+    tx.verify().unwrap();
+
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x19, // "txs.AddPermissionlessValidatorTx" type ID
+    ];
+    expected.extend(super::test_base_tx_bytes());
+    expected.extend(super::test_validator_bytes());
+    let validator_end = expected.len();
+    expected.extend_from_slice(&[0x00; 32]); // primary network subnet ID
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x1c]); // "signer.ProofOfPossession" type ID
+    expected.extend_from_slice(&signer_key.public_key_bytes());
+    expected.extend_from_slice(&pop.proof_of_possession);
+    expected.extend(super::test_stake_outputs_bytes());
+    expected.extend(super::test_owner_bytes()); // validator rewards owner
+    expected.extend(super::test_owner_bytes()); // delegator rewards owner
+    expected.extend_from_slice(&[0x00, 0x00, 0x4e, 0x20]); // delegation shares
+    assert_eq!(tx.unsigned_bytes().unwrap(), expected);
+
+    let mut no_signer = tx.clone();
+    no_signer.signer = None;
+    assert!(no_signer.verify().is_err());
+    no_signer.subnet_id = ids::Id::from_slice(&[0x33; 32]);
+    no_signer.verify().unwrap();
+    let b = no_signer.unsigned_bytes().unwrap();
+    assert_eq!(
+        b[validator_end + 32..validator_end + 36],
+        [0x00, 0x00, 0x00, 0x1b] // "signer.Empty" type ID
+    );
+
+    let mut subnet_signer = tx.clone();
+    subnet_signer.subnet_id = ids::Id::from_slice(&[0x33; 32]);
+    assert!(subnet_signer.verify().is_err());
+
+    let mut forged = tx.clone();
+    forged.signer = Some(bls_key::ProofOfPossession {
+        public_key: bls_key::Key::generate()
+            .unwrap()
+            .public_key_bytes()
+            .to_vec(),
+        proof_of_possession: pop.proof_of_possession,
+    });
+    assert!(forged.verify().is_err());
+
+    let mut too_many_shares = tx;
+    too_many_shares.delegation_shares = super::PERCENT_DENOMINATOR + 1;
+    assert!(too_many_shares.verify().is_err());
+}
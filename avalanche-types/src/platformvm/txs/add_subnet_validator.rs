@@ -0,0 +1,157 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer, platformvm, secp256k1fx, soft_key};
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm#UnsignedAddSubnetValidatorTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+    /// Must be a primary network validator for the whole period.
+    pub validator: platformvm::Validator,
+    pub subnet_id: ids::Id,
+    /// Signature indices of the subnet owner keys.
+    pub subnet_auth: secp256k1fx::Input,
+
+    /// Populated by "sign", one per input and the last one for the subnet auth.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            validator: platformvm::Validator::default(),
+            subnet_id: ids::Id::empty(),
+            subnet_auth: secp256k1fx::Input::default(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "platformvm.UnsignedAddSubnetValidatorTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// ref. "avalanchego/vms/platformvm.UnsignedAddSubnetValidatorTx.SyntacticVerify"
+    pub fn verify(&self) -> io::Result<()> {
+        if self.subnet_id == platformvm::chain_id() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "cannot add subnet validator to the primary network", // ref. "errDSValidatorSubset"
+            ));
+        }
+        if self.validator.weight == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "subnet validator weight must be non-zero", // ref. "errWeightTooSmall"
+            ));
+        }
+        if self.validator.end <= self.validator.start {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "validator end time {} must be after start time {}",
+                    self.validator.end, self.validator.start
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Encodes the unsigned "platformvm.UnsignedAddSubnetValidatorTx"; the
+    /// subnet auth indices select the subnet owner's keys that also sign it.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::P_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            self.base_tx.pack(packer)?;
+            self.validator.pack(packer);
+            packer.pack_bytes(self.subnet_id.as_bytes());
+            super::pack_subnet_auth(packer, &self.subnet_auth);
+            Ok(())
+        })
+    }
+
+    /// Decodes the signed tx bytes (e.g., returned by "platform.getTx"),
+    /// returning the tx with its credentials and ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        super::decode_signed(b, Self::type_id(), Self::unpack)
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        Ok(Self {
+            base_tx: avax::BaseTx::unpack(packer)?,
+            validator: platformvm::Validator::unpack(packer)?,
+            subnet_id: ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?),
+            subnet_auth: super::unpack_subnet_auth(packer)?,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+
+    /// Signs the tx with the keys for each input (in the same order as the inputs)
+    /// followed by the subnet owner keys, and returns the signed tx bytes to issue with its tx ID.
+    pub fn sign(&mut self, signers: &[Vec<soft_key::Key>]) -> io::Result<(Vec<u8>, ids::Id)> {
+        self.verify()?;
+        super::verify_subnet_signers(&self.base_tx, &self.subnet_auth, signers)?;
+
+        let unsigned_bytes = self.unsigned_bytes()?;
+        self.creds = avax::sign_credentials(&unsigned_bytes, signers)?;
+        avax::pack_signed_tx(&unsigned_bytes, &self.creds)
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::add_subnet_validator::test_unsigned_bytes --exact --show-output
+#[test]
+fn test_unsigned_bytes() {
+    let validator_tx = super::add_validator::test_tx();
+    let mut tx = Tx {
+        base_tx: validator_tx.base_tx.clone(),
+        validator: platformvm::Validator {
+            weight: 1_000,
+            ..validator_tx.validator.clone()
+        },
+        subnet_id: ids::Id::from_slice(&[0xcc; 32]),
+        subnet_auth: secp256k1fx::Input::new(vec![0]),
+        creds: Vec::new(),
+    };
+    tx.verify().unwrap();
+
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x0d, // "platformvm.UnsignedAddSubnetValidatorTx" type ID
+    ];
+    expected.extend(super::test_base_tx_bytes());
+    expected.extend_from_slice(&[0x11; 20]); // validator node ID
+    expected.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x00, 0x5f, 0x5e, 0x10, 0x00, // start
+        0x00, 0x00, 0x00, 0x00, 0x65, 0x53, 0xf1, 0x00, // end
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xe8, // weight
+    ]);
+    expected.extend_from_slice(&[0xcc; 32]); // subnet ID
+    expected.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x0a, // "secp256k1fx.Input" type ID
+        0x00, 0x00, 0x00, 0x01, // number of signature indices
+        0x00, 0x00, 0x00, 0x00, // signature index
+    ]);
+    assert_eq!(tx.unsigned_bytes().unwrap(), expected);
+
+    // one signer for the input and one for the subnet auth
+    let key = soft_key::TEST_KEYS[0].clone();
+    assert!(tx.sign(&[vec![key.clone()]]).is_err());
+    tx.sign(&[vec![key.clone()], vec![key]]).unwrap();
+    assert_eq!(tx.creds.len(), 2);
+
+    let mut primary = tx.clone();
+    primary.subnet_id = platformvm::chain_id();
+    assert!(primary.verify().is_err());
+}
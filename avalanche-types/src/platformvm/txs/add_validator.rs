@@ -0,0 +1,402 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer, platformvm, secp256k1fx, soft_key, units, utxo};
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm#UnsignedAddValidatorTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+    pub validator: platformvm::Validator,
+    pub stake_transferable_outputs: Option<Vec<avax::TransferableOutput>>,
+    pub rewards_owner: secp256k1fx::OutputOwners,
+    /// Delegation fee in the units of "txs::PERCENT_DENOMINATOR".
+    pub shares: u32,
+
+    /// Populated by "sign", one per input.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            validator: platformvm::Validator::default(),
+            stake_transferable_outputs: None,
+            rewards_owner: secp256k1fx::OutputOwners::default(),
+            shares: 0,
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "platformvm.UnsignedAddValidatorTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// ref. "avalanchego/vms/platformvm.UnsignedAddValidatorTx.SyntacticVerify"
+    pub fn verify(&self) -> io::Result<()> {
+        if self.shares > super::PERCENT_DENOMINATOR {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "shares {} exceeds {}", // ref. "errTooManyShares"
+                    self.shares,
+                    super::PERCENT_DENOMINATOR
+                ),
+            ));
+        }
+        super::verify_staker(
+            &self.validator,
+            &self.stake_transferable_outputs.clone().unwrap_or_default(),
+        )
+    }
+
+    /// Encodes the unsigned "platformvm.UnsignedAddValidatorTx": the staker
+    /// fields shared with the delegator tx, followed by the delegation fee shares.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::P_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            super::pack_staker(
+                packer,
+                &self.base_tx,
+                &self.validator,
+                &self.stake_transferable_outputs.clone().unwrap_or_default(),
+                &self.rewards_owner,
+            )?;
+            packer.pack_u32(self.shares);
+            Ok(())
+        })
+    }
+
+    /// Decodes the signed tx bytes (e.g., returned by "platform.getTx"),
+    /// returning the tx with its credentials and ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        super::decode_signed(b, Self::type_id(), Self::unpack)
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let (base_tx, validator, stake, rewards_owner) = super::unpack_staker(packer)?;
+        let shares = packer.unpack_u32()?;
+        Ok(Self {
+            base_tx,
+            validator,
+            stake_transferable_outputs: if stake.is_empty() { None } else { Some(stake) },
+            rewards_owner,
+            shares,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+
+    /// Signs the tx with the keys for each input (in the same order as the inputs),
+    /// and returns the signed tx bytes to issue with its tx ID.
+    pub fn sign(&mut self, signers: &[Vec<soft_key::Key>]) -> io::Result<(Vec<u8>, ids::Id)> {
+        self.verify()?;
+
+        let ins = self
+            .base_tx
+            .transferable_inputs
+            .as_ref()
+            .map_or(0, |ins| ins.len());
+        if ins != signers.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} inputs but {} signers", ins, signers.len()),
+            ));
+        }
+
+        let unsigned_bytes = self.unsigned_bytes()?;
+        self.creds = avax::sign_credentials(&unsigned_bytes, signers)?;
+        avax::pack_signed_tx(&unsigned_bytes, &self.creds)
+    }
+}
+
+/// Builds P-chain add validator txs by spending the AVAX UTXOs owned by the keychain.
+/// ref. https://github.com/ava-labs/avalanchego/blob/v1.7.9/wallet/chain/p/builder.go
+pub struct Builder {
+    pub network_id: u32,
+    pub avax_asset_id: ids::Id,
+    /// Burned in AVAX on every add validator tx.
+    pub tx_fee: units::Avax,
+    pub keychain: soft_key::Keychain,
+}
+
+impl Builder {
+    /// Selects enough unlocked AVAX UTXOs to cover the validator weight and the tx fee,
+    /// locks the weight as the stake owned by "change_to",
+    /// and sends any remainder back to "change_to".
+    /// Returns the unsigned tx and the signers of each input to pass to "Tx::sign".
+    /// ref. "avalanchego/wallet/chain/p.builder.NewAddValidatorTx"
+    pub fn new_add_validator_tx(
+        &self,
+        utxos: &[utxo::Utxo],
+        validator: platformvm::Validator,
+        rewards_owner: secp256k1fx::OutputOwners,
+        shares: u32,
+        change_to: &ids::ShortId,
+        now: u64,
+    ) -> io::Result<(Tx, Vec<Vec<soft_key::Key>>)> {
+        let needed = validator
+            .weight
+            .checked_add(self.tx_fee.as_navax())
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "amount overflows u64"))?;
+
+        let (mut ins, consumed) =
+            super::spend_avax(&self.keychain, &self.avax_asset_id, utxos, needed, now)?;
+        if consumed < needed {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "insufficient funds to stake {} nAVAX (needed {}, available {})",
+                    validator.weight, needed, consumed
+                ),
+            ));
+        }
+
+        let owners = secp256k1fx::OutputOwners::new(0, 1, &[*change_to]);
+        let mut outs: Vec<avax::TransferableOutput> = Vec::new();
+        if consumed > needed {
+            outs.push(avax::TransferableOutput {
+                asset_id: self.avax_asset_id,
+                transfer_output: Some(secp256k1fx::TransferOutput::new(
+                    consumed - needed,
+                    owners.clone(),
+                )),
+                ..avax::TransferableOutput::default()
+            });
+        }
+        let stake_outs = vec![avax::TransferableOutput {
+            asset_id: self.avax_asset_id,
+            transfer_output: Some(secp256k1fx::TransferOutput::new(validator.weight, owners)),
+            ..avax::TransferableOutput::default()
+        }];
+
+        ins.sort_by(|a, b| a.0.cmp(&b.0));
+        let (ins, signers): (Vec<avax::TransferableInput>, Vec<Vec<soft_key::Key>>) =
+            ins.into_iter().unzip();
+
+        let tx = Tx {
+            base_tx: avax::BaseTx {
+                network_id: self.network_id,
+                blockchain_id: platformvm::chain_id(),
+                transferable_outputs: Some(outs),
+                transferable_inputs: Some(ins),
+                memo: None,
+            },
+            validator,
+            stake_transferable_outputs: Some(stake_outs),
+            rewards_owner,
+            shares,
+            creds: Vec::new(),
+        };
+        tx.verify()?;
+        Ok((tx, signers))
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn test_tx() -> Tx {
+    let avax_asset_id = ids::Id::from_slice(&[0xbb; 32]);
+    let owners = secp256k1fx::OutputOwners::new(0, 1, &[ids::ShortId::from_slice(&[0x22; 20])]);
+    Tx {
+        base_tx: avax::BaseTx {
+            network_id: 12345,
+            blockchain_id: platformvm::chain_id(),
+            transferable_outputs: Some(vec![avax::TransferableOutput {
+                asset_id: avax_asset_id,
+                transfer_output: Some(secp256k1fx::TransferOutput::new(
+                    1_000_000_000,
+                    owners.clone(),
+                )),
+                ..avax::TransferableOutput::default()
+            }]),
+            transferable_inputs: Some(vec![avax::TransferableInput {
+                utxo_id: avax::UtxoId {
+                    tx_id: ids::Id::from_slice(&[0xaa; 32]),
+                    output_index: 1,
+                    ..avax::UtxoId::default()
+                },
+                asset_id: avax_asset_id,
+                transfer_input: Some(secp256k1fx::TransferInput::new(2_001_000_000_000, vec![0])),
+                ..avax::TransferableInput::default()
+            }]),
+            memo: None,
+        },
+        validator: platformvm::Validator {
+            node_id: ids::ShortId::from_slice(&[0x11; 20]),
+            start: 1_600_000_000,
+            end: 1_700_000_000,
+            weight: 2_000_000_000_000,
+        },
+        stake_transferable_outputs: Some(vec![avax::TransferableOutput {
+            asset_id: avax_asset_id,
+            transfer_output: Some(secp256k1fx::TransferOutput::new(
+                2_000_000_000_000,
+                owners.clone(),
+            )),
+            ..avax::TransferableOutput::default()
+        }]),
+        rewards_owner: owners,
+        shares: 20_000,
+        creds: Vec::new(),
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::add_validator::test_unsigned_bytes --exact --show-output
+#[test]
+fn test_unsigned_bytes() {
+    let tx = test_tx();
+    tx.verify().unwrap();
+
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x0c, // "platformvm.UnsignedAddValidatorTx" type ID
+    ];
+    expected.extend(super::test_base_tx_bytes());
+    expected.extend(super::test_validator_bytes());
+    expected.extend(super::test_stake_outputs_bytes());
+    expected.extend(super::test_owner_bytes());
+    expected.extend_from_slice(&[0x00, 0x00, 0x4e, 0x20]); // shares
+
+    assert_eq!(tx.unsigned_bytes().unwrap(), expected);
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::add_validator::test_sign --exact --show-output
+#[test]
+fn test_sign() {
+    use secp256k1::{
+        ecdsa::{RecoverableSignature, RecoveryId},
+        Message, Secp256k1,
+    };
+    use utils::hash;
+
+    let key = soft_key::TEST_KEYS[0].clone();
+
+    let mut tx = test_tx();
+    assert!(tx.sign(&[]).is_err());
+
+    let (signed_bytes, tx_id) = tx.sign(&[vec![key.clone()]]).unwrap();
+    assert_eq!(tx.creds.len(), 1);
+    assert_eq!(tx.creds[0].signatures.len(), 1);
+
+    // RFC6979 signatures are deterministic
+    let mut tx2 = test_tx();
+    assert_eq!(
+        tx2.sign(&[vec![key.clone()]]).unwrap(),
+        (signed_bytes.clone(), tx_id)
+    );
+    assert_eq!(
+        tx_id,
+        ids::Id::from_slice(&hash::compute_sha256(&signed_bytes))
+    );
+
+    let unsigned_bytes = tx.unsigned_bytes().unwrap();
+    let sig = &tx.creds[0].signatures[0];
+    let mut expected_suffix: Vec<u8> = vec![
+        0x00, 0x00, 0x00, 0x01, // number of credentials
+        0x00, 0x00, 0x00, 0x09, // "secp256k1fx.Credential" type ID
+        0x00, 0x00, 0x00, 0x01, // number of signatures
+    ];
+    expected_suffix.extend_from_slice(sig);
+    assert_eq!(signed_bytes[..unsigned_bytes.len()], unsigned_bytes[..]);
+    assert_eq!(signed_bytes[unsigned_bytes.len()..], expected_suffix[..]);
+
+    // the signature must recover the signing key
+    let digest = hash::compute_sha256(&unsigned_bytes);
+    let rec_id = RecoveryId::from_i32(sig[64] as i32).unwrap();
+    let rec_sig = RecoverableSignature::from_compact(&sig[..64], rec_id).unwrap();
+    let pubkey = Secp256k1::new()
+        .recover_ecdsa(&Message::from_slice(&digest).unwrap(), &rec_sig)
+        .unwrap();
+    assert_eq!(
+        soft_key::public_key_to_short_address_bytes(&pubkey).unwrap(),
+        key.short_address.d.to_vec()
+    );
+
+    // round trip through the signed bytes, as returned by "platform.getTx"
+    assert_eq!(Tx::from_bytes(&signed_bytes).unwrap(), (tx, tx_id));
+    let err = super::add_delegator::Tx::from_bytes(&signed_bytes).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("platformvm.UnsignedAddValidatorTx"));
+    assert!(Tx::from_bytes(&signed_bytes[..signed_bytes.len() - 1]).is_err());
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::add_validator::test_new_add_validator_tx --exact --show-output
+#[test]
+fn test_new_add_validator_tx() {
+    let key = soft_key::TEST_KEYS[0].clone();
+    let avax_asset_id = ids::Id::from_slice(&[0xbb; 32]);
+    let owner = secp256k1fx::OutputOwners::new(0, 1, &[key.short_address]);
+
+    let mut utxos: Vec<utxo::Utxo> = Vec::new();
+    for (i, amount) in [1_500_000_000_000_u64, 1_000_000_000_000, 500_000_000_000]
+        .iter()
+        .enumerate()
+    {
+        utxos.push(utxo::Utxo {
+            utxo_id: avax::UtxoId {
+                tx_id: ids::Id::from_slice(&[(3 - i) as u8; 32]),
+                output_index: 0,
+                ..avax::UtxoId::default()
+            },
+            asset_id: avax_asset_id,
+            transfer_output: Some(secp256k1fx::TransferOutput::new(*amount, owner.clone())),
+            ..utxo::Utxo::default()
+        });
+    }
+
+    let builder = Builder {
+        network_id: 12345,
+        avax_asset_id,
+        tx_fee: units::Avax::from_navax(1_000_000),
+        keychain: soft_key::Keychain::new(vec![key.clone()]),
+    };
+    let validator = platformvm::Validator {
+        node_id: ids::ShortId::from_slice(&[0x11; 20]),
+        start: 1_600_000_000,
+        end: 1_700_000_000,
+        weight: 2_000_000_000_000,
+    };
+
+    // only the first two UTXOs are needed for 2,000 AVAX + fee
+    let (mut tx, signers) = builder
+        .new_add_validator_tx(
+            &utxos,
+            validator.clone(),
+            owner.clone(),
+            20_000,
+            &key.short_address,
+            0,
+        )
+        .unwrap();
+    let ins = tx.base_tx.transferable_inputs.clone().unwrap();
+    assert_eq!(ins.len(), 2);
+    assert_eq!(signers.len(), 2);
+    assert!(ins[0] < ins[1]);
+    assert_eq!(tx.base_tx.blockchain_id, platformvm::chain_id());
+
+    let outs = tx.base_tx.transferable_outputs.clone().unwrap();
+    let amounts: Vec<u64> = outs.iter().map(|o| o.amount()).collect();
+    assert_eq!(amounts, vec![499_999_000_000]);
+    let stake = tx.stake_transferable_outputs.clone().unwrap();
+    assert_eq!(stake.len(), 1);
+    assert_eq!(stake[0].amount(), 2_000_000_000_000);
+
+    tx.sign(&signers).unwrap();
+    assert_eq!(tx.creds.len(), 2);
+
+    let res =
+        builder.new_add_validator_tx(&utxos[1..], validator, owner, 20_000, &key.short_address, 0);
+    assert!(res.unwrap_err().to_string().contains("insufficient funds"));
+}
@@ -0,0 +1,96 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer};
+
+/// Moves the chain time forward (e.g., to the next staker end time),
+/// in the proposal blocks before Banff.
+/// Issued by the block builder, so it has no inputs or credentials.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/txs#AdvanceTimeTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    /// Unix time in seconds.
+    pub time: u64,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self { time: 0 }
+    }
+
+    pub fn new(time: u64) -> Self {
+        Self { time }
+    }
+
+    pub fn type_name() -> String {
+        "platformvm.UnsignedAdvanceTimeTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::P_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            packer.pack_u64(self.time);
+            Ok(())
+        })
+    }
+
+    /// Returns the tx bytes with no credentials and its ID,
+    /// as embedded in the proposal block.
+    pub fn to_bytes(&self) -> io::Result<(Vec<u8>, ids::Id)> {
+        avax::pack_signed_tx(&self.unsigned_bytes()?, &[])
+    }
+
+    /// Decodes the tx bytes (e.g., returned by "platform.getTx"),
+    /// returning the tx with its ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        super::decode_signed(b, Self::type_id(), Self::unpack)
+    }
+
+    /// Unpacks the fields and the empty credentials after the type ID.
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let time = packer.unpack_u64()?;
+        let creds = super::unpack_creds(packer)?;
+        if !creds.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("advance time tx has {} credentials", creds.len()),
+            ));
+        }
+        Ok(Self { time })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::advance_time::test_bytes --exact --show-output
+#[test]
+fn test_bytes() {
+    use utils::hash;
+
+    let tx = Tx::new(1_700_000_000);
+
+    let expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x13, // "platformvm.UnsignedAdvanceTimeTx" type ID
+        0x00, 0x00, 0x00, 0x00, 0x65, 0x53, 0xf1, 0x00, // time
+        0x00, 0x00, 0x00, 0x00, // number of credentials
+    ];
+    let (b, tx_id) = tx.to_bytes().unwrap();
+    assert_eq!(b, expected);
+    assert_eq!(tx_id, ids::Id::from_slice(&hash::compute_sha256(&expected)));
+    assert_eq!(Tx::from_bytes(&b).unwrap(), (tx, tx_id));
+
+    // trailing bytes after the credentials
+    let mut trailing = b;
+    trailing.push(0x00);
+    assert!(Tx::from_bytes(&trailing).is_err());
+}
@@ -0,0 +1,263 @@
+use std::{
+    io::{self, Error, ErrorKind},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer, platformvm, secp256k1fx, soft_key};
+
+/// Maximum length of the blockchain name.
+/// ref. "avalanchego/vms/platformvm.maxNameLen"
+pub const MAX_NAME_LEN: usize = 128;
+/// Maximum size of the blockchain genesis data.
+/// ref. "avalanchego/vms/platformvm.maxGenesisLen"
+pub const MAX_GENESIS_LEN: usize = 1 << 20;
+
+/// Fxs that avalanchego registers, whose IDs are the name bytes.
+/// ref. "avalanchego/vms/secp256k1fx.ID"
+/// ref. "avalanchego/vms/nftfx.ID"
+/// ref. "avalanchego/vms/propertyfx.ID"
+pub const FX_NAMES: [&str; 3] = ["secp256k1fx", "nftfx", "propertyfx"];
+
+/// Returns the ID of the registered fx name (e.g., "secp256k1fx"),
+/// or parses the CB58-encoded fx ID.
+pub fn parse_fx_id(s: &str) -> io::Result<ids::Id> {
+    if FX_NAMES.contains(&s) {
+        let mut b = [0u8; ids::ID_LEN];
+        b[..s.len()].copy_from_slice(s.as_bytes());
+        return Ok(ids::Id::from_slice(&b));
+    }
+    ids::Id::from_str(s).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("unknown fx '{}' ({})", s, e),
+        )
+    })
+}
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm#UnsignedCreateChainTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+    pub subnet_id: ids::Id,
+    pub chain_name: String,
+    pub vm_id: ids::Id,
+    /// Must be sorted and unique.
+    pub fx_ids: Vec<ids::Id>,
+    pub genesis_data: Vec<u8>,
+    /// Signature indices of the subnet owner keys.
+    pub subnet_auth: secp256k1fx::Input,
+
+    /// Populated by "sign", one per input and the last one for the subnet auth.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            subnet_id: ids::Id::empty(),
+            chain_name: String::new(),
+            vm_id: ids::Id::empty(),
+            fx_ids: Vec::new(),
+            genesis_data: Vec::new(),
+            subnet_auth: secp256k1fx::Input::default(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "platformvm.UnsignedCreateChainTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// ref. "avalanchego/vms/platformvm.UnsignedCreateChainTx.SyntacticVerify"
+    pub fn verify(&self) -> io::Result<()> {
+        if self.subnet_id == platformvm::chain_id() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "cannot create blockchain on the primary network", // ref. "errDSCantValidate"
+            ));
+        }
+        if self.chain_name.len() > MAX_NAME_LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "chain name length {} exceeds {}", // ref. "errNameTooLong"
+                    self.chain_name.len(),
+                    MAX_NAME_LEN
+                ),
+            ));
+        }
+        if !self
+            .chain_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == ' ')
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "chain name '{}' has illegal characters", // ref. "errIllegalNameCharacter"
+                    self.chain_name
+                ),
+            ));
+        }
+        if self.vm_id.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "empty VM ID"));
+        }
+        if !self.fx_ids.windows(2).all(|w| w[0] < w[1]) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "fx IDs not sorted and unique", // ref. "errFxIDsNotSortedAndUnique"
+            ));
+        }
+        if self.genesis_data.len() > MAX_GENESIS_LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "genesis data size {} exceeds {}", // ref. "errGenesisTooLong"
+                    self.genesis_data.len(),
+                    MAX_GENESIS_LEN
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Encodes the unsigned "platformvm.UnsignedCreateChainTx": the base tx,
+    /// the subnet, chain name, VM and fx IDs, the genesis bytes and the subnet
+    /// auth.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::P_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            self.base_tx.pack(packer)?;
+            packer.pack_bytes(self.subnet_id.as_bytes());
+            packer.pack_str(&self.chain_name);
+            packer.pack_bytes(self.vm_id.as_bytes());
+            packer.pack_u32(self.fx_ids.len() as u32);
+            for fx_id in self.fx_ids.iter() {
+                packer.pack_bytes(fx_id.as_bytes());
+            }
+            packer.pack_bytes_with_header(&self.genesis_data);
+            super::pack_subnet_auth(packer, &self.subnet_auth);
+            Ok(())
+        })
+    }
+
+    /// Decodes the signed tx bytes (e.g., returned by "platform.getTx"),
+    /// returning the tx with its credentials and ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        super::decode_signed(b, Self::type_id(), Self::unpack)
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let base_tx = avax::BaseTx::unpack(packer)?;
+        let subnet_id = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+        let chain_name = packer.unpack_str()?;
+        let vm_id = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+        let n = packer.unpack_u32()?;
+        let mut fx_ids: Vec<ids::Id> = Vec::new();
+        for _ in 0..n {
+            fx_ids.push(ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?));
+        }
+        Ok(Self {
+            base_tx,
+            subnet_id,
+            chain_name,
+            vm_id,
+            fx_ids,
+            genesis_data: packer.unpack_bytes_with_header()?,
+            subnet_auth: super::unpack_subnet_auth(packer)?,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+
+    /// Signs the tx with the keys for each input (in the same order as the inputs)
+    /// followed by the subnet owner keys, and returns the signed tx bytes to issue with its tx ID.
+    /// The tx ID is the ID of the new blockchain.
+    pub fn sign(&mut self, signers: &[Vec<soft_key::Key>]) -> io::Result<(Vec<u8>, ids::Id)> {
+        self.verify()?;
+        super::verify_subnet_signers(&self.base_tx, &self.subnet_auth, signers)?;
+
+        let unsigned_bytes = self.unsigned_bytes()?;
+        self.creds = avax::sign_credentials(&unsigned_bytes, signers)?;
+        avax::pack_signed_tx(&unsigned_bytes, &self.creds)
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::create_chain::test_unsigned_bytes --exact --show-output
+#[test]
+fn test_unsigned_bytes() {
+    let validator_tx = super::add_validator::test_tx();
+    let tx = Tx {
+        base_tx: validator_tx.base_tx.clone(),
+        subnet_id: ids::Id::from_slice(&[0xcc; 32]),
+        chain_name: String::from("subnet evm"),
+        vm_id: ids::Id::from_slice(&[0xdd; 32]),
+        fx_ids: Vec::new(),
+        genesis_data: vec![0x7b, 0x7d],
+        subnet_auth: secp256k1fx::Input::new(vec![0]),
+        creds: Vec::new(),
+    };
+    tx.verify().unwrap();
+
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x0f, // "platformvm.UnsignedCreateChainTx" type ID
+    ];
+    expected.extend(super::test_base_tx_bytes());
+    expected.extend_from_slice(&[0xcc; 32]); // subnet ID
+    expected.extend_from_slice(&[0x00, 0x0a]); // chain name length
+    expected.extend_from_slice(b"subnet evm");
+    expected.extend_from_slice(&[0xdd; 32]); // VM ID
+    expected.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x00, // number of fx IDs
+        0x00, 0x00, 0x00, 0x02, // genesis data length
+        0x7b, 0x7d, // genesis data
+        0x00, 0x00, 0x00, 0x0a, // "secp256k1fx.Input" type ID
+        0x00, 0x00, 0x00, 0x01, // number of signature indices
+        0x00, 0x00, 0x00, 0x00, // signature index
+    ]);
+    assert_eq!(tx.unsigned_bytes().unwrap(), expected);
+
+    let mut illegal = tx.clone();
+    illegal.chain_name = String::from("subnet-evm");
+    assert!(illegal.verify().is_err());
+
+    let mut unsorted = tx.clone();
+    unsorted.fx_ids = vec![
+        ids::Id::from_slice(&[0x02; 32]),
+        ids::Id::from_slice(&[0x01; 32]),
+    ];
+    assert!(unsorted.verify().is_err());
+
+    let secp256k1fx_id = parse_fx_id("secp256k1fx").unwrap();
+    assert_eq!(
+        secp256k1fx_id.to_string(),
+        "spdxUxVJQbX85MGxMHbKw1sHxMnSqJ3QBzDyDYEP3h6TLuxqQ"
+    );
+    assert_eq!(
+        parse_fx_id("qd2U4HDWUvMrVUeTcCHp6xH3Qpnn1XbU5MDdnBoiifFqvgXwT").unwrap(),
+        parse_fx_id("nftfx").unwrap()
+    );
+    assert!(parse_fx_id("unknownfx").is_err());
+
+    let mut with_fx = tx;
+    with_fx.fx_ids = vec![secp256k1fx_id];
+    with_fx.verify().unwrap();
+    let b = with_fx.unsigned_bytes().unwrap();
+    assert_eq!(b.len(), expected.len() + ids::ID_LEN);
+    let fx_offset = expected.len() - 18; // after the number of fx IDs
+    assert_eq!(b[fx_offset - 4..fx_offset], [0x00, 0x00, 0x00, 0x01]);
+    assert_eq!(b[fx_offset..fx_offset + 11], *b"secp256k1fx");
+}
@@ -0,0 +1,126 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer, secp256k1fx, soft_key};
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm#UnsignedCreateSubnetTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+    /// Keys that authorize the subnet validators and blockchains.
+    pub owner: secp256k1fx::OutputOwners,
+
+    /// Populated by "sign", one per input.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            owner: secp256k1fx::OutputOwners::default(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "platformvm.UnsignedCreateSubnetTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// ref. "avalanchego/vms/platformvm.UnsignedCreateSubnetTx.SyntacticVerify"
+    pub fn verify(&self) -> io::Result<()> {
+        self.owner.verify()
+    }
+
+    /// Encodes the unsigned "platformvm.UnsignedCreateSubnetTx": the base tx
+    /// and the owner, whose keys later sign the subnet auth of this subnet.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::P_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            self.base_tx.pack(packer)?;
+
+            // "fx.Owner" interface, so the type ID comes first
+            packer.pack_u32(secp256k1fx::OutputOwners::type_id());
+            self.owner.pack(packer);
+            Ok(())
+        })
+    }
+
+    /// Decodes the signed tx bytes (e.g., returned by "platform.getTx"),
+    /// returning the tx with its credentials and ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        super::decode_signed(b, Self::type_id(), Self::unpack)
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        Ok(Self {
+            base_tx: avax::BaseTx::unpack(packer)?,
+            owner: super::unpack_owner(packer)?,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+
+    /// Signs the tx with the keys for each input (in the same order as the inputs),
+    /// and returns the signed tx bytes to issue with its tx ID.
+    /// The tx ID is the ID of the new subnet.
+    pub fn sign(&mut self, signers: &[Vec<soft_key::Key>]) -> io::Result<(Vec<u8>, ids::Id)> {
+        self.verify()?;
+
+        let ins = self
+            .base_tx
+            .transferable_inputs
+            .as_ref()
+            .map_or(0, |ins| ins.len());
+        if ins != signers.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} inputs but {} signers", ins, signers.len()),
+            ));
+        }
+
+        let unsigned_bytes = self.unsigned_bytes()?;
+        self.creds = avax::sign_credentials(&unsigned_bytes, signers)?;
+        avax::pack_signed_tx(&unsigned_bytes, &self.creds)
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::create_subnet::test_unsigned_bytes --exact --show-output
+#[test]
+fn test_unsigned_bytes() {
+    let validator_tx = super::add_validator::test_tx();
+    let tx = Tx {
+        base_tx: validator_tx.base_tx.clone(),
+        owner: validator_tx.rewards_owner.clone(),
+        creds: Vec::new(),
+    };
+    tx.verify().unwrap();
+
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x10, // "platformvm.UnsignedCreateSubnetTx" type ID
+    ];
+    expected.extend(super::test_base_tx_bytes());
+    expected.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x0b, // "secp256k1fx.OutputOwners" type ID
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // locktime
+        0x00, 0x00, 0x00, 0x01, // threshold
+        0x00, 0x00, 0x00, 0x01, // number of addresses
+    ]);
+    expected.extend_from_slice(&[0x22; 20]);
+    assert_eq!(tx.unsigned_bytes().unwrap(), expected);
+
+    let mut unspendable = tx.clone();
+    unspendable.owner.threshold = 2;
+    assert!(unspendable.verify().is_err());
+}
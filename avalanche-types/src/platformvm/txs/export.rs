@@ -0,0 +1,136 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer, secp256k1fx, soft_key};
+
+/// Exports the outputs from the P-chain to the destination chain
+/// (e.g., the X-chain), which then imports them.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/txs#ExportTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+    pub destination_chain_id: ids::Id,
+    /// Put in the shared memory with the destination chain.
+    pub exported_outputs: Vec<avax::TransferableOutput>,
+
+    /// One per input.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            destination_chain_id: ids::Id::empty(),
+            exported_outputs: Vec::new(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "platformvm.UnsignedExportTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// ref. "avalanchego/vms/platformvm.UnsignedExportTx.SyntacticVerify"
+    pub fn verify(&self) -> io::Result<()> {
+        if self.exported_outputs.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "no exported outputs", // ref. "errNoExportOutputs"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Encodes the unsigned "platformvm.UnsignedExportTx": the base tx, the
+    /// destination chain ID and the outputs exported to it.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::P_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            self.base_tx.pack(packer)?;
+            packer.pack_bytes(self.destination_chain_id.as_bytes());
+            packer.pack_u32(self.exported_outputs.len() as u32);
+            for out in self.exported_outputs.iter() {
+                out.pack(packer)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Decodes the signed tx bytes (e.g., returned by "platform.getTx"),
+    /// returning the tx with its credentials and ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        super::decode_signed(b, Self::type_id(), Self::unpack)
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        Ok(Self {
+            base_tx: avax::BaseTx::unpack(packer)?,
+            destination_chain_id: ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?),
+            exported_outputs: super::unpack_outputs(packer)?,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+
+    /// Signs the tx with the keys for each input (in the same order as the inputs),
+    /// and returns the signed tx bytes to issue with its tx ID.
+    pub fn sign(&mut self, signers: &[Vec<soft_key::Key>]) -> io::Result<(Vec<u8>, ids::Id)> {
+        self.verify()?;
+
+        let ins = self
+            .base_tx
+            .transferable_inputs
+            .as_ref()
+            .map_or(0, |ins| ins.len());
+        if ins != signers.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} inputs but {} signers", ins, signers.len()),
+            ));
+        }
+
+        let unsigned_bytes = self.unsigned_bytes()?;
+        self.creds = avax::sign_credentials(&unsigned_bytes, signers)?;
+        avax::pack_signed_tx(&unsigned_bytes, &self.creds)
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::export::test_from_bytes --exact --show-output
+#[test]
+fn test_from_bytes() {
+    let validator_tx = super::add_validator::test_tx();
+    let tx = Tx {
+        base_tx: validator_tx.base_tx.clone(),
+        destination_chain_id: ids::Id::from_slice(&[0x33; 32]),
+        exported_outputs: validator_tx.stake_transferable_outputs.clone().unwrap(),
+        creds: vec![secp256k1fx::Credential::new(vec![vec![0x44; 65]])],
+    };
+
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x12, // "platformvm.UnsignedExportTx" type ID
+    ];
+    expected.extend(super::test_base_tx_bytes());
+    expected.extend_from_slice(&[0x33; 32]); // destination chain ID
+    expected.extend(super::test_stake_outputs_bytes()); // exported outputs
+    let unsigned_bytes = tx.unsigned_bytes().unwrap();
+    assert_eq!(unsigned_bytes, expected);
+
+    let (signed_bytes, tx_id) = avax::pack_signed_tx(&unsigned_bytes, &tx.creds).unwrap();
+    assert_eq!(Tx::from_bytes(&signed_bytes).unwrap(), (tx.clone(), tx_id));
+
+    let mut empty = tx;
+    empty.exported_outputs.clear();
+    assert!(empty.verify().is_err());
+}
@@ -0,0 +1,111 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer, secp256k1fx};
+
+/// Imports the UTXOs exported from the source chain (e.g., the X-chain)
+/// to the P-chain.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/txs#ImportTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+    pub source_chain_id: ids::Id,
+    /// Spends the UTXOs in the shared memory with the source chain.
+    pub imported_inputs: Vec<avax::TransferableInput>,
+
+    /// One per input of the base tx and then per imported input.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            source_chain_id: ids::Id::empty(),
+            imported_inputs: Vec::new(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "platformvm.UnsignedImportTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// Encodes the unsigned "platformvm.UnsignedImportTx": the base tx, the
+    /// source chain ID and the inputs consumed from its shared memory.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::P_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            self.base_tx.pack(packer)?;
+            packer.pack_bytes(self.source_chain_id.as_bytes());
+            packer.pack_u32(self.imported_inputs.len() as u32);
+            for input in self.imported_inputs.iter() {
+                input.pack(packer)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Decodes the signed tx bytes (e.g., returned by "platform.getTx"),
+    /// returning the tx with its credentials and ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        super::decode_signed(b, Self::type_id(), Self::unpack)
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        Ok(Self {
+            base_tx: avax::BaseTx::unpack(packer)?,
+            source_chain_id: ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?),
+            imported_inputs: super::unpack_inputs(packer)?,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::import::test_from_bytes --exact --show-output
+#[test]
+fn test_from_bytes() {
+    let validator_tx = super::add_validator::test_tx();
+    let mut base_tx = validator_tx.base_tx.clone();
+    let imported_inputs = base_tx.transferable_inputs.take().unwrap();
+    let tx = Tx {
+        base_tx,
+        source_chain_id: ids::Id::from_slice(&[0x33; 32]),
+        imported_inputs,
+        creds: vec![secp256k1fx::Credential::new(vec![vec![0x44; 65]])],
+    };
+
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x11, // "platformvm.UnsignedImportTx" type ID
+        0x00, 0x00, 0x30, 0x39, // network ID
+    ];
+    expected.extend_from_slice(&[0x00; 32]); // P-chain blockchain ID
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // number of outputs
+    expected.extend(super::test_transfer_output_bytes([
+        0x00, 0x00, 0x00, 0x00, 0x3b, 0x9a, 0xca, 0x00, //
+    ]));
+    expected.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x00, // number of inputs
+        0x00, 0x00, 0x00, 0x00, // memo length
+    ]);
+    expected.extend_from_slice(&[0x33; 32]); // source chain ID
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // number of imported inputs
+    expected.extend(super::test_transfer_input_bytes());
+    let unsigned_bytes = tx.unsigned_bytes().unwrap();
+    assert_eq!(unsigned_bytes, expected);
+
+    let (signed_bytes, tx_id) = avax::pack_signed_tx(&unsigned_bytes, &tx.creds).unwrap();
+    assert_eq!(Tx::from_bytes(&signed_bytes).unwrap(), (tx, tx_id));
+}
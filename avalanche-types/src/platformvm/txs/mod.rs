@@ -0,0 +1,1134 @@
+pub mod add_delegator;
+pub mod add_permissionless_delegator;
+pub mod add_permissionless_validator;
+pub mod add_subnet_validator;
+pub mod add_validator;
+pub mod advance_time;
+pub mod create_chain;
+pub mod create_subnet;
+pub mod export;
+pub mod import;
+pub mod remove_subnet_validator;
+pub mod reward_validator;
+pub mod transform_subnet;
+
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, formatting, ids, packer, platformvm, secp256k1fx, soft_key, units, utxo};
+use utils::hash;
+
+/// Denominator of the delegation fee "shares" (e.g., 20,000 is 2%).
+/// ref. "avalanchego/vms/platformvm.PercentDenominator"
+pub const PERCENT_DENOMINATOR: u32 = 1_000_000;
+
+/// Checks the fields shared by the add validator and delegator txs.
+/// ref. "avalanchego/vms/platformvm.UnsignedAddValidatorTx.SyntacticVerify"
+fn verify_staker(
+    validator: &platformvm::Validator,
+    stake: &[avax::TransferableOutput],
+) -> io::Result<()> {
+    if validator.end <= validator.start {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "validator end time {} must be after start time {}",
+                validator.end, validator.start
+            ),
+        ));
+    }
+
+    let mut total_stake: u64 = 0;
+    for out in stake.iter() {
+        total_stake = total_stake
+            .checked_add(out.amount())
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "stake amount overflows u64"))?;
+    }
+    if total_stake != validator.weight {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "validator weight {} does not match total stake {}", // ref. "errValidatorWeightMismatch"
+                validator.weight, total_stake
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Packs the fields shared by the add validator and delegator txs,
+/// after the codec version and type ID.
+fn pack_staker(
+    packer: &packer::Packer,
+    base_tx: &avax::BaseTx,
+    validator: &platformvm::Validator,
+    stake: &[avax::TransferableOutput],
+    rewards_owner: &secp256k1fx::OutputOwners,
+) -> io::Result<()> {
+    base_tx.pack(packer)?;
+    validator.pack(packer);
+
+    packer.pack_u32(stake.len() as u32);
+    for out in stake.iter() {
+        out.pack(packer)?;
+    }
+
+    // "fx.Owner" interface, so the type ID comes first
+    packer.pack_u32(secp256k1fx::OutputOwners::type_id());
+    rewards_owner.pack(packer);
+    Ok(())
+}
+
+/// Unpacks the fields written by "pack_staker".
+fn unpack_staker(
+    packer: &packer::Packer,
+) -> io::Result<(
+    avax::BaseTx,
+    platformvm::Validator,
+    Vec<avax::TransferableOutput>,
+    secp256k1fx::OutputOwners,
+)> {
+    let base_tx = avax::BaseTx::unpack(packer)?;
+    let validator = platformvm::Validator::unpack(packer)?;
+    let stake = unpack_outputs(packer)?;
+    let rewards_owner = unpack_owner(packer)?;
+    Ok((base_tx, validator, stake, rewards_owner))
+}
+
+/// Unpacks the outputs prefixed with their number (e.g., the stake outputs).
+fn unpack_outputs(packer: &packer::Packer) -> io::Result<Vec<avax::TransferableOutput>> {
+    let n = packer.unpack_u32()?;
+    let mut outs: Vec<avax::TransferableOutput> = Vec::new();
+    for _ in 0..n {
+        outs.push(avax::TransferableOutput::unpack(packer)?);
+    }
+    Ok(outs)
+}
+
+/// Unpacks the inputs prefixed with their number (e.g., the imported inputs).
+fn unpack_inputs(packer: &packer::Packer) -> io::Result<Vec<avax::TransferableInput>> {
+    let n = packer.unpack_u32()?;
+    let mut ins: Vec<avax::TransferableInput> = Vec::new();
+    for _ in 0..n {
+        ins.push(avax::TransferableInput::unpack(packer)?);
+    }
+    Ok(ins)
+}
+
+/// Unpacks the "fx.Owner" interface (e.g., the rewards owner).
+fn unpack_owner(packer: &packer::Packer) -> io::Result<secp256k1fx::OutputOwners> {
+    let type_id = packer.unpack_u32()?;
+    if type_id != secp256k1fx::OutputOwners::type_id() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("unexpected owner type ID {}", type_id),
+        ));
+    }
+    secp256k1fx::OutputOwners::unpack(packer)
+}
+
+/// Unpacks the subnet authorization written by "pack_subnet_auth".
+fn unpack_subnet_auth(packer: &packer::Packer) -> io::Result<secp256k1fx::Input> {
+    let type_id = packer.unpack_u32()?;
+    if type_id != secp256k1fx::Input::type_id() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("unexpected subnet auth type ID {}", type_id),
+        ));
+    }
+    let n = packer.unpack_u32()?;
+    let mut sig_indices: Vec<u32> = Vec::new();
+    for _ in 0..n {
+        sig_indices.push(packer.unpack_u32()?);
+    }
+    Ok(secp256k1fx::Input::new(sig_indices))
+}
+
+/// Unpacks the credentials appended by "avax::pack_signed_tx".
+fn unpack_creds(packer: &packer::Packer) -> io::Result<Vec<secp256k1fx::Credential>> {
+    let n = packer.unpack_u32()?;
+    let mut creds: Vec<secp256k1fx::Credential> = Vec::new();
+    for _ in 0..n {
+        creds.push(secp256k1fx::Credential::unpack(packer)?);
+    }
+    Ok(creds)
+}
+
+/// Decodes the signed tx bytes (e.g., returned by "platform.getTx") of the type,
+/// with "unpack" for the fields and credentials after the type ID.
+/// Returns the tx with its ID.
+fn decode_signed<T>(
+    b: &[u8],
+    type_id: u32,
+    unpack: fn(&packer::Packer) -> io::Result<T>,
+) -> io::Result<(T, ids::Id)> {
+    let tx = codec::P_CODEC.unmarshal(b, |_, packer| {
+        let actual = packer.unpack_u32()?;
+        if actual != type_id {
+            // e.g., the add delegator tx passed to the validator decoder
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "tx type ID {} ({}) is not {}",
+                    actual,
+                    codec::P_CODEC
+                        .type_name(codec::VERSION, actual)
+                        .unwrap_or("unknown"),
+                    codec::P_CODEC
+                        .type_name(codec::VERSION, type_id)
+                        .unwrap_or("unknown")
+                ),
+            ));
+        }
+        unpack(packer)
+    })?;
+    Ok((tx, ids::Id::from_slice(&hash::compute_sha256(b))))
+}
+
+/// P-chain tx of any type, decoded with its credentials
+/// (e.g., embedded in the block or returned by "platform.getTx").
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/txs#Tx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Tx {
+    AddValidator(add_validator::Tx),
+    AddSubnetValidator(add_subnet_validator::Tx),
+    AddDelegator(add_delegator::Tx),
+    CreateChain(create_chain::Tx),
+    CreateSubnet(create_subnet::Tx),
+    Import(import::Tx),
+    Export(export::Tx),
+    AdvanceTime(advance_time::Tx),
+    RewardValidator(reward_validator::Tx),
+    RemoveSubnetValidator(remove_subnet_validator::Tx),
+    TransformSubnet(transform_subnet::Tx),
+    AddPermissionlessValidator(add_permissionless_validator::Tx),
+    AddPermissionlessDelegator(add_permissionless_delegator::Tx),
+}
+
+impl Tx {
+    /// Unpacks the type ID, the fields and the credentials,
+    /// without the codec version (i.e., "*txs.Tx" embedded in the block).
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let type_id = packer.unpack_u32()?;
+        let name = codec::P_CODEC
+            .type_name(codec::VERSION, type_id)
+            .unwrap_or("unknown");
+        let tx = match name {
+            "platformvm.UnsignedAddValidatorTx" => {
+                Tx::AddValidator(add_validator::Tx::unpack(packer)?)
+            }
+            "platformvm.UnsignedAddSubnetValidatorTx" => {
+                Tx::AddSubnetValidator(add_subnet_validator::Tx::unpack(packer)?)
+            }
+            "platformvm.UnsignedAddDelegatorTx" => {
+                Tx::AddDelegator(add_delegator::Tx::unpack(packer)?)
+            }
+            "platformvm.UnsignedCreateChainTx" => {
+                Tx::CreateChain(create_chain::Tx::unpack(packer)?)
+            }
+            "platformvm.UnsignedCreateSubnetTx" => {
+                Tx::CreateSubnet(create_subnet::Tx::unpack(packer)?)
+            }
+            "platformvm.UnsignedImportTx" => Tx::Import(import::Tx::unpack(packer)?),
+            "platformvm.UnsignedExportTx" => Tx::Export(export::Tx::unpack(packer)?),
+            "platformvm.UnsignedAdvanceTimeTx" => {
+                Tx::AdvanceTime(advance_time::Tx::unpack(packer)?)
+            }
+            "platformvm.UnsignedRewardValidatorTx" => {
+                Tx::RewardValidator(reward_validator::Tx::unpack(packer)?)
+            }
+            "txs.RemoveSubnetValidatorTx" => {
+                Tx::RemoveSubnetValidator(remove_subnet_validator::Tx::unpack(packer)?)
+            }
+            "txs.TransformSubnetTx" => Tx::TransformSubnet(transform_subnet::Tx::unpack(packer)?),
+            "txs.AddPermissionlessValidatorTx" => {
+                Tx::AddPermissionlessValidator(add_permissionless_validator::Tx::unpack(packer)?)
+            }
+            "txs.AddPermissionlessDelegatorTx" => {
+                Tx::AddPermissionlessDelegator(add_permissionless_delegator::Tx::unpack(packer)?)
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("type ID {} ({}) is not a tx", type_id, name),
+                ))
+            }
+        };
+        Ok(tx)
+    }
+
+    /// Decodes the signed tx bytes of any type, returning the tx with its ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        let tx = codec::P_CODEC.unmarshal(b, |_, packer| Self::unpack(packer))?;
+        Ok((tx, ids::Id::from_slice(&hash::compute_sha256(b))))
+    }
+
+    /// Returns the codec type name of the tx (e.g., "txs.AddPermissionlessValidatorTx").
+    pub fn type_name(&self) -> String {
+        match self {
+            Tx::AddValidator(_) => add_validator::Tx::type_name(),
+            Tx::AddSubnetValidator(_) => add_subnet_validator::Tx::type_name(),
+            Tx::AddDelegator(_) => add_delegator::Tx::type_name(),
+            Tx::CreateChain(_) => create_chain::Tx::type_name(),
+            Tx::CreateSubnet(_) => create_subnet::Tx::type_name(),
+            Tx::Import(_) => import::Tx::type_name(),
+            Tx::Export(_) => export::Tx::type_name(),
+            Tx::AdvanceTime(_) => advance_time::Tx::type_name(),
+            Tx::RewardValidator(_) => reward_validator::Tx::type_name(),
+            Tx::RemoveSubnetValidator(_) => remove_subnet_validator::Tx::type_name(),
+            Tx::TransformSubnet(_) => transform_subnet::Tx::type_name(),
+            Tx::AddPermissionlessValidator(_) => add_permissionless_validator::Tx::type_name(),
+            Tx::AddPermissionlessDelegator(_) => add_permissionless_delegator::Tx::type_name(),
+        }
+    }
+}
+
+/// Decodes the untrusted "0x"-prefixed hex or CB58 signed tx with the checksum
+/// (e.g., returned by "platform.getTx"), with the size limits of "formatting"
+/// and the codec, returning the tx with its ID.
+pub fn decode_tx_untrusted(encoded: &str) -> io::Result<(Tx, ids::Id)> {
+    Tx::from_bytes(&formatting::decode_untrusted(encoded)?)
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::test_tx_from_bytes --exact --show-output
+#[test]
+fn test_tx_from_bytes() {
+    let (b, tx_id) = advance_time::Tx::new(1_700_000_000).to_bytes().unwrap();
+    let (tx, id) = Tx::from_bytes(&b).unwrap();
+    assert_eq!(tx, Tx::AdvanceTime(advance_time::Tx::new(1_700_000_000)));
+    assert_eq!(tx.type_name(), "platformvm.UnsignedAdvanceTimeTx");
+    assert_eq!(id, tx_id);
+
+    let mut validator_tx = add_validator::test_tx();
+    let (b, tx_id) = validator_tx
+        .sign(&[vec![soft_key::TEST_KEYS[0].clone()]])
+        .unwrap();
+    assert_eq!(
+        Tx::from_bytes(&b).unwrap(),
+        (Tx::AddValidator(validator_tx.clone()), tx_id)
+    );
+    assert_eq!(
+        decode_tx_untrusted(&formatting::encode_cb58_with_checksum(&b)).unwrap(),
+        (Tx::AddValidator(validator_tx), tx_id)
+    );
+
+    // not a tx type, e.g., the block
+    let b = codec::P_CODEC
+        .marshal(codec::VERSION, |packer| {
+            packer.pack_u32(secp256k1fx::OutputOwners::type_id());
+            Ok(())
+        })
+        .unwrap();
+    let err = Tx::from_bytes(&b).unwrap_err();
+    assert!(err.to_string().contains("is not a tx"));
+}
+
+/// Inputs with the keys to sign each of them.
+type SignedInputs = Vec<(avax::TransferableInput, Vec<soft_key::Key>)>;
+
+/// Selects the unlocked AVAX UTXOs owned by the keychain until "needed" is covered.
+/// Returns the inputs with their signers and the total consumed amount,
+/// which is less than "needed" if the keychain does not have enough funds.
+fn spend_avax(
+    keychain: &soft_key::Keychain,
+    avax_asset_id: &ids::Id,
+    utxos: &[utxo::Utxo],
+    needed: u64,
+    now: u64,
+) -> io::Result<(SignedInputs, u64)> {
+    let mut consumed: u64 = 0;
+    let mut ins: SignedInputs = Vec::new();
+    for utxo in utxos.iter() {
+        if consumed >= needed {
+            break;
+        }
+        if utxo.asset_id != *avax_asset_id {
+            continue;
+        }
+
+        // stakeable locked outputs are not spent for now
+        let out = match &utxo.transfer_output {
+            Some(out) => out,
+            None => continue,
+        };
+        let (transfer_input, keys) = match keychain.spend(out, now) {
+            Some(v) => v,
+            None => continue,
+        };
+
+        consumed = consumed
+            .checked_add(transfer_input.amount)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "amount overflows u64"))?;
+        ins.push((
+            avax::TransferableInput {
+                utxo_id: utxo.utxo_id.clone(),
+                asset_id: utxo.asset_id,
+                transfer_input: Some(transfer_input),
+                ..avax::TransferableInput::default()
+            },
+            keys,
+        ));
+    }
+    Ok((ins, consumed))
+}
+
+/// Packs the "verify.Verifiable" subnet authorization of the subnet txs.
+fn pack_subnet_auth(packer: &packer::Packer, subnet_auth: &secp256k1fx::Input) {
+    // interface, so the type ID comes first
+    packer.pack_u32(secp256k1fx::Input::type_id());
+    packer.pack_u32(subnet_auth.sig_indices.len() as u32);
+    for idx in subnet_auth.sig_indices.iter() {
+        packer.pack_u32(*idx);
+    }
+}
+
+/// Checks the number of signers of the subnet txs:
+/// one per input, and the last one for the subnet authorization.
+/// The multisig subnet owner with the co-signers is signed with
+/// "signer::PartialCredentials" instead.
+fn verify_subnet_signers(
+    base_tx: &avax::BaseTx,
+    subnet_auth: &secp256k1fx::Input,
+    signers: &[Vec<soft_key::Key>],
+) -> io::Result<()> {
+    let ins = base_tx
+        .transferable_inputs
+        .as_ref()
+        .map_or(0, |ins| ins.len());
+    if ins + 1 != signers.len() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "{} inputs and subnet auth but {} signers",
+                ins,
+                signers.len()
+            ),
+        ));
+    }
+    let auth_signers = signers.last().map_or(0, |keys| keys.len());
+    if auth_signers != subnet_auth.sig_indices.len() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "subnet auth requires {} signatures but {} keys (co-signers sign with PartialCredentials)",
+                subnet_auth.sig_indices.len(),
+                auth_signers
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Base tx of "add_validator::test_tx", written out field by field
+/// per "avax.BaseTx" of avalanchego, so the expected bytes of the tx tests
+/// do not come from the packer under test.
+#[cfg(test)]
+pub(crate) fn test_base_tx_bytes() -> Vec<u8> {
+    let mut b: Vec<u8> = vec![0x00, 0x00, 0x30, 0x39]; // network ID
+    b.extend_from_slice(&[0x00; 32]); // P-chain blockchain ID
+    b.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // number of outputs
+    b.extend(test_transfer_output_bytes([
+        0x00, 0x00, 0x00, 0x00, 0x3b, 0x9a, 0xca, 0x00, // 1 AVAX change
+    ]));
+    b.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // number of inputs
+    b.extend(test_transfer_input_bytes());
+    b.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]); // memo length
+    b
+}
+
+/// Transfer output of the amount to 0x22...22, as in "add_validator::test_tx".
+#[cfg(test)]
+pub(crate) fn test_transfer_output_bytes(amount: [u8; 8]) -> Vec<u8> {
+    let mut b: Vec<u8> = vec![0xbb; 32]; // asset ID
+    b.extend_from_slice(&[0x00, 0x00, 0x00, 0x07]); // "secp256k1fx.TransferOutput" type ID
+    b.extend_from_slice(&amount);
+    b.extend_from_slice(&[0x00; 8]); // locktime
+    b.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // threshold
+    b.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // number of addresses
+    b.extend_from_slice(&[0x22; 20]);
+    b
+}
+
+/// Transfer input of 2,001 AVAX, as in "add_validator::test_tx".
+#[cfg(test)]
+pub(crate) fn test_transfer_input_bytes() -> Vec<u8> {
+    let mut b: Vec<u8> = vec![0xaa; 32]; // UTXO tx ID
+    b.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]); // UTXO output index
+    b.extend_from_slice(&[0xbb; 32]); // asset ID
+    b.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x05, // "secp256k1fx.TransferInput" type ID
+        0x00, 0x00, 0x01, 0xd1, 0xe4, 0xe4, 0xea, 0x00, // amount
+        0x00, 0x00, 0x00, 0x01, // number of signature indices
+        0x00, 0x00, 0x00, 0x00, // signature index
+    ]);
+    b
+}
+
+/// Validator of "add_validator::test_tx", staking 2,000 AVAX.
+#[cfg(test)]
+pub(crate) fn test_validator_bytes() -> Vec<u8> {
+    let mut b: Vec<u8> = vec![0x11; 20]; // node ID
+    b.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x00, 0x5f, 0x5e, 0x10, 0x00, // start
+        0x00, 0x00, 0x00, 0x00, 0x65, 0x53, 0xf1, 0x00, // end
+        0x00, 0x00, 0x01, 0xd1, 0xa9, 0x4a, 0x20, 0x00, // weight
+    ]);
+    b
+}
+
+/// Stake outputs of "add_validator::test_tx", locking 2,000 AVAX.
+#[cfg(test)]
+pub(crate) fn test_stake_outputs_bytes() -> Vec<u8> {
+    let mut b: Vec<u8> = vec![0x00, 0x00, 0x00, 0x01]; // number of stake outputs
+    b.extend(test_transfer_output_bytes([
+        0x00, 0x00, 0x01, 0xd1, 0xa9, 0x4a, 0x20, 0x00, //
+    ]));
+    b
+}
+
+/// Rewards owner of "add_validator::test_tx".
+#[cfg(test)]
+pub(crate) fn test_owner_bytes() -> Vec<u8> {
+    let mut b: Vec<u8> = vec![
+        0x00, 0x00, 0x00, 0x0b, // "secp256k1fx.OutputOwners" type ID
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // locktime
+        0x00, 0x00, 0x00, 0x01, // threshold
+        0x00, 0x00, 0x00, 0x01, // number of addresses
+    ];
+    b.extend_from_slice(&[0x22; 20]);
+    b
+}
+
+/// Builds the P-chain subnet (and export) txs, paying the fees with the AVAX UTXOs owned by the keychain.
+/// The subnet owner keys must be in the same keychain to authorize
+/// the subnet validators and blockchains.
+/// ref. https://github.com/ava-labs/avalanchego/blob/v1.7.9/wallet/chain/p/builder.go
+pub struct SubnetBuilder {
+    pub network_id: u32,
+    pub avax_asset_id: ids::Id,
+    /// Burned in AVAX on every add subnet validator tx.
+    pub tx_fee: units::Avax,
+    /// Burned in AVAX on every create subnet and create blockchain tx.
+    pub creation_tx_fee: units::Avax,
+    pub keychain: soft_key::Keychain,
+    /// Subnet owner addresses whose keys are held outside the keychain
+    /// (e.g., KMS or Ledger of the other operators of the 2-of-3 subnet owner).
+    /// The txs authorized by them are signed with "signer::PartialCredentials".
+    pub co_signers: Vec<ids::ShortId>,
+}
+
+impl SubnetBuilder {
+    /// Returns the base tx burning "fee", with any remainder sent back to "change_to".
+    fn new_base_tx(
+        &self,
+        utxos: &[utxo::Utxo],
+        fee: &units::Avax,
+        change_to: &ids::ShortId,
+        now: u64,
+    ) -> io::Result<(avax::BaseTx, Vec<Vec<soft_key::Key>>)> {
+        let needed = fee.as_navax();
+        let (mut ins, consumed) =
+            spend_avax(&self.keychain, &self.avax_asset_id, utxos, needed, now)?;
+        if consumed < needed {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "insufficient funds to pay the fee (needed {}, available {})",
+                    needed, consumed
+                ),
+            ));
+        }
+
+        let mut outs: Vec<avax::TransferableOutput> = Vec::new();
+        if consumed > needed {
+            outs.push(avax::TransferableOutput {
+                asset_id: self.avax_asset_id,
+                transfer_output: Some(secp256k1fx::TransferOutput::new(
+                    consumed - needed,
+                    secp256k1fx::OutputOwners::new(0, 1, &[*change_to]),
+                )),
+                ..avax::TransferableOutput::default()
+            });
+        }
+
+        ins.sort_by(|a, b| a.0.cmp(&b.0));
+        let (ins, signers): (Vec<avax::TransferableInput>, Vec<Vec<soft_key::Key>>) =
+            ins.into_iter().unzip();
+        Ok((
+            avax::BaseTx {
+                network_id: self.network_id,
+                blockchain_id: platformvm::chain_id(),
+                transferable_outputs: Some(outs),
+                transferable_inputs: Some(ins),
+                memo: None,
+            },
+            signers,
+        ))
+    }
+
+    /// Returns the signature indices of the subnet owner keys in the keychain,
+    /// falling back to the co-signers only if the keychain alone
+    /// does not meet the threshold.
+    /// ref. "avalanchego/wallet/chain/p.builder.authorizeSubnet"
+    fn authorize_subnet(
+        &self,
+        subnet_owner: &secp256k1fx::OutputOwners,
+        now: u64,
+    ) -> io::Result<(secp256k1fx::Input, Vec<soft_key::Key>)> {
+        if let Some((sig_indices, keys)) = self.keychain.match_threshold(subnet_owner, now) {
+            return Ok((secp256k1fx::Input::new(sig_indices), keys));
+        }
+
+        let mut signers: Vec<ids::ShortId> =
+            self.keychain.keys.iter().map(|k| k.short_address).collect();
+        signers.extend_from_slice(&self.co_signers);
+        match subnet_owner.match_addrs(&signers, now) {
+            Some((sig_indices, addrs)) => {
+                let keys = addrs.iter().filter_map(|a| self.keychain.get(a)).collect();
+                Ok((secp256k1fx::Input::new(sig_indices), keys))
+            }
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "keychain and co-signers do not meet the subnet owner threshold",
+            )),
+        }
+    }
+
+    /// Returns the addresses to sign each credential of the subnet tx
+    /// to create "signer::PartialCredentials", where the last one
+    /// includes the co-signers of the subnet authorization.
+    pub fn signing_plan(
+        signers: &[Vec<soft_key::Key>],
+        subnet_owner: &secp256k1fx::OutputOwners,
+        subnet_auth: &secp256k1fx::Input,
+    ) -> io::Result<Vec<Vec<ids::ShortId>>> {
+        let mut plan: Vec<Vec<ids::ShortId>> = signers
+            .iter()
+            .map(|keys| keys.iter().map(|k| k.short_address).collect())
+            .collect();
+        match plan.last_mut() {
+            Some(last) => *last = subnet_owner.addrs_at(&subnet_auth.sig_indices)?,
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "no signer for the subnet auth",
+                ))
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Returns the unsigned create subnet tx owned by "subnet_owner",
+    /// with the signers of each input to pass to "create_subnet::Tx::sign".
+    /// ref. "avalanchego/wallet/chain/p.builder.NewCreateSubnetTx"
+    pub fn new_create_subnet_tx(
+        &self,
+        utxos: &[utxo::Utxo],
+        subnet_owner: secp256k1fx::OutputOwners,
+        change_to: &ids::ShortId,
+        now: u64,
+    ) -> io::Result<(create_subnet::Tx, Vec<Vec<soft_key::Key>>)> {
+        let (base_tx, signers) = self.new_base_tx(utxos, &self.creation_tx_fee, change_to, now)?;
+        let tx = create_subnet::Tx {
+            base_tx,
+            owner: subnet_owner,
+            creds: Vec::new(),
+        };
+        tx.verify()?;
+        Ok((tx, signers))
+    }
+
+    /// Returns the unsigned export tx of the AVAX "exported_outputs"
+    /// (in the canonical sorted order) to the destination chain
+    /// (e.g., the X-chain), which then imports them,
+    /// with the signers of each input to pass to "export::Tx::sign".
+    /// ref. "avalanchego/wallet/chain/p.builder.NewExportTx"
+    pub fn new_export_tx(
+        &self,
+        utxos: &[utxo::Utxo],
+        destination_chain_id: ids::Id,
+        exported_outputs: Vec<avax::TransferableOutput>,
+        change_to: &ids::ShortId,
+        now: u64,
+    ) -> io::Result<(export::Tx, Vec<Vec<soft_key::Key>>)> {
+        let mut needed = self.tx_fee;
+        for out in exported_outputs.iter() {
+            if out.asset_id != self.avax_asset_id {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("exported output asset {} is not AVAX", out.asset_id),
+                ));
+            }
+            let amount = out.transfer_output.as_ref().map_or(0, |o| o.amount);
+            needed = needed
+                .checked_add(units::Avax::from_navax(amount))
+                .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "exported amount overflows"))?;
+        }
+        let (base_tx, signers) = self.new_base_tx(utxos, &needed, change_to, now)?;
+
+        let tx = export::Tx {
+            base_tx,
+            destination_chain_id,
+            exported_outputs,
+            creds: Vec::new(),
+        };
+        tx.verify()?;
+        Ok((tx, signers))
+    }
+
+    /// Returns the unsigned add subnet validator tx,
+    /// with the signers of each input and the subnet authorization
+    /// to pass to "add_subnet_validator::Tx::sign".
+    /// ref. "avalanchego/wallet/chain/p.builder.NewAddSubnetValidatorTx"
+    pub fn new_add_subnet_validator_tx(
+        &self,
+        utxos: &[utxo::Utxo],
+        validator: platformvm::Validator,
+        subnet_id: ids::Id,
+        subnet_owner: &secp256k1fx::OutputOwners,
+        change_to: &ids::ShortId,
+        now: u64,
+    ) -> io::Result<(add_subnet_validator::Tx, Vec<Vec<soft_key::Key>>)> {
+        let (base_tx, mut signers) = self.new_base_tx(utxos, &self.tx_fee, change_to, now)?;
+        let (subnet_auth, keys) = self.authorize_subnet(subnet_owner, now)?;
+        signers.push(keys);
+
+        let tx = add_subnet_validator::Tx {
+            base_tx,
+            validator,
+            subnet_id,
+            subnet_auth,
+            creds: Vec::new(),
+        };
+        tx.verify()?;
+        Ok((tx, signers))
+    }
+
+    /// Returns the unsigned create blockchain tx of the VM with the fxs
+    /// (e.g., "create_chain::parse_fx_id") and the genesis bytes the VM parses,
+    /// with the signers of each input and the subnet authorization
+    /// to pass to "create_chain::Tx::sign".
+    /// ref. "avalanchego/wallet/chain/p.builder.NewCreateChainTx"
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_create_chain_tx(
+        &self,
+        utxos: &[utxo::Utxo],
+        subnet_id: ids::Id,
+        subnet_owner: &secp256k1fx::OutputOwners,
+        chain_name: &str,
+        vm_id: ids::Id,
+        fx_ids: &[ids::Id],
+        genesis_data: Vec<u8>,
+        change_to: &ids::ShortId,
+        now: u64,
+    ) -> io::Result<(create_chain::Tx, Vec<Vec<soft_key::Key>>)> {
+        let mut fx_ids = fx_ids.to_vec();
+        fx_ids.sort();
+        fx_ids.dedup();
+
+        let (base_tx, mut signers) =
+            self.new_base_tx(utxos, &self.creation_tx_fee, change_to, now)?;
+        let (subnet_auth, keys) = self.authorize_subnet(subnet_owner, now)?;
+        signers.push(keys);
+
+        let tx = create_chain::Tx {
+            base_tx,
+            subnet_id,
+            chain_name: chain_name.to_string(),
+            vm_id,
+            fx_ids,
+            genesis_data,
+            subnet_auth,
+            creds: Vec::new(),
+        };
+        tx.verify()?;
+        Ok((tx, signers))
+    }
+
+    /// Returns the unsigned transform subnet tx, which locks the reward pool
+    /// of the subnet asset held by the keychain on the P-chain,
+    /// with the signers of each input and the subnet authorization
+    /// to pass to "transform_subnet::Tx::sign".
+    /// ref. "avalanchego/wallet/chain/p.builder.NewTransformSubnetTx"
+    pub fn new_transform_subnet_tx(
+        &self,
+        utxos: &[utxo::Utxo],
+        subnet_id: ids::Id,
+        subnet_owner: &secp256k1fx::OutputOwners,
+        parameters: transform_subnet::Parameters,
+        change_to: &ids::ShortId,
+        now: u64,
+    ) -> io::Result<(transform_subnet::Tx, Vec<Vec<soft_key::Key>>)> {
+        if parameters.asset_id == self.avax_asset_id {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "elastic subnet asset cannot be AVAX", // ref. "errAssetIDCantBeAVAX"
+            ));
+        }
+        parameters.verify()?;
+
+        // same as the create subnet fee
+        // ref. "avalanchego/vms/platformvm/config.Config.TransformSubnetTxFee"
+        let (mut base_tx, mut signers) =
+            self.new_base_tx(utxos, &self.creation_tx_fee, change_to, now)?;
+
+        let needed = parameters.reward_pool();
+        if needed > 0 {
+            let (mut ins, consumed) =
+                spend_avax(&self.keychain, &parameters.asset_id, utxos, needed, now)?;
+            if consumed < needed {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "insufficient subnet asset {} for the reward pool (needed {}, available {})",
+                        parameters.asset_id, needed, consumed
+                    ),
+                ));
+            }
+
+            let mut outs = base_tx.transferable_outputs.take().unwrap_or_default();
+            if consumed > needed {
+                outs.push(avax::TransferableOutput {
+                    asset_id: parameters.asset_id,
+                    transfer_output: Some(secp256k1fx::TransferOutput::new(
+                        consumed - needed,
+                        secp256k1fx::OutputOwners::new(0, 1, &[*change_to]),
+                    )),
+                    ..avax::TransferableOutput::default()
+                });
+            }
+            outs.sort_by(|a, b| a.asset_id.cmp(&b.asset_id));
+            base_tx.transferable_outputs = Some(outs);
+
+            // re-sort the inputs of both assets with their signers
+            let prev_ins = base_tx.transferable_inputs.take().unwrap_or_default();
+            ins.extend(prev_ins.into_iter().zip(signers));
+            ins.sort_by(|a, b| a.0.cmp(&b.0));
+            let (ins, sorted_signers): (Vec<avax::TransferableInput>, Vec<Vec<soft_key::Key>>) =
+                ins.into_iter().unzip();
+            base_tx.transferable_inputs = Some(ins);
+            signers = sorted_signers;
+        }
+
+        let (subnet_auth, keys) = self.authorize_subnet(subnet_owner, now)?;
+        signers.push(keys);
+
+        let tx = transform_subnet::Tx {
+            base_tx,
+            subnet_id,
+            parameters,
+            subnet_auth,
+            creds: Vec::new(),
+        };
+        tx.verify()?;
+        Ok((tx, signers))
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::test_subnet_builder --exact --show-output
+#[test]
+fn test_subnet_builder() {
+    let key = soft_key::TEST_KEYS[0].clone();
+    let avax_asset_id = ids::Id::from_slice(&[0xbb; 32]);
+    let owner = secp256k1fx::OutputOwners::new(0, 1, &[key.short_address]);
+    let utxos = vec![utxo::Utxo {
+        utxo_id: avax::UtxoId {
+            tx_id: ids::Id::from_slice(&[0x01; 32]),
+            output_index: 0,
+            ..avax::UtxoId::default()
+        },
+        asset_id: avax_asset_id,
+        transfer_output: Some(secp256k1fx::TransferOutput::new(
+            2_000_000_000,
+            owner.clone(),
+        )),
+        ..utxo::Utxo::default()
+    }];
+
+    let builder = SubnetBuilder {
+        network_id: 12345,
+        avax_asset_id,
+        tx_fee: units::Avax::from_navax(1_000_000),
+        creation_tx_fee: units::Avax::from_navax(1_000_000_000),
+        keychain: soft_key::Keychain::new(vec![key.clone()]),
+        co_signers: Vec::new(),
+    };
+
+    let (mut create_subnet_tx, signers) = builder
+        .new_create_subnet_tx(&utxos, owner.clone(), &key.short_address, 0)
+        .unwrap();
+    assert_eq!(signers.len(), 1);
+    let outs = create_subnet_tx
+        .base_tx
+        .transferable_outputs
+        .clone()
+        .unwrap();
+    let amounts: Vec<u64> = outs.iter().map(|o| o.amount()).collect();
+    assert_eq!(amounts, vec![1_000_000_000]);
+    let (_, subnet_id) = create_subnet_tx.sign(&signers).unwrap();
+
+    let validator = platformvm::Validator {
+        node_id: ids::ShortId::from_slice(&[0x11; 20]),
+        start: 1_600_000_000,
+        end: 1_700_000_000,
+        weight: 1_000,
+    };
+    let (mut add_subnet_validator_tx, signers) = builder
+        .new_add_subnet_validator_tx(&utxos, validator, subnet_id, &owner, &key.short_address, 0)
+        .unwrap();
+    assert_eq!(signers.len(), 2);
+    assert_eq!(add_subnet_validator_tx.subnet_auth.sig_indices, vec![0]);
+    add_subnet_validator_tx.sign(&signers).unwrap();
+
+    let secp256k1fx_id = create_chain::parse_fx_id("secp256k1fx").unwrap();
+    let nftfx_id = create_chain::parse_fx_id("nftfx").unwrap();
+    let (mut create_chain_tx, signers) = builder
+        .new_create_chain_tx(
+            &utxos,
+            subnet_id,
+            &owner,
+            "subnetevm",
+            ids::Id::from_slice(&[0xdd; 32]),
+            &[nftfx_id, secp256k1fx_id, nftfx_id],
+            b"{}".to_vec(),
+            &key.short_address,
+            0,
+        )
+        .unwrap();
+    assert_eq!(signers.len(), 2);
+    let mut expected_fx_ids = vec![secp256k1fx_id, nftfx_id];
+    expected_fx_ids.sort();
+    assert_eq!(create_chain_tx.fx_ids, expected_fx_ids);
+    create_chain_tx.sign(&signers).unwrap();
+    assert_eq!(create_chain_tx.creds.len(), 2);
+
+    // only the subnet owner can authorize
+    let other = secp256k1fx::OutputOwners::new(0, 1, &[ids::ShortId::from_slice(&[0x22; 20])]);
+    assert!(builder
+        .new_create_chain_tx(
+            &utxos,
+            subnet_id,
+            &other,
+            "subnetevm",
+            ids::Id::from_slice(&[0xdd; 32]),
+            &[],
+            b"{}".to_vec(),
+            &key.short_address,
+            0,
+        )
+        .is_err());
+
+    // no UTXO to pay the creation fee
+    assert!(builder
+        .new_create_subnet_tx(&utxos[..0], owner, &key.short_address, 0)
+        .unwrap_err()
+        .to_string()
+        .contains("insufficient funds"));
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::test_new_transform_subnet_tx --exact --show-output
+#[test]
+fn test_new_transform_subnet_tx() {
+    let key = soft_key::TEST_KEYS[0].clone();
+    let avax_asset_id = ids::Id::from_slice(&[0xbb; 32]);
+    let owner = secp256k1fx::OutputOwners::new(0, 1, &[key.short_address]);
+    let parameters = transform_subnet::test_parameters();
+
+    // the subnet asset UTXO sorts before the AVAX UTXO
+    let mut utxos = Vec::new();
+    for (tx_id, asset_id, amount) in [
+        (0x02, avax_asset_id, 2_000_000_000),
+        (0x01, parameters.asset_id, 5_000_000),
+    ] {
+        utxos.push(utxo::Utxo {
+            utxo_id: avax::UtxoId {
+                tx_id: ids::Id::from_slice(&[tx_id; 32]),
+                output_index: 0,
+                ..avax::UtxoId::default()
+            },
+            asset_id,
+            transfer_output: Some(secp256k1fx::TransferOutput::new(amount, owner.clone())),
+            ..utxo::Utxo::default()
+        });
+    }
+
+    let builder = SubnetBuilder {
+        network_id: 12345,
+        avax_asset_id,
+        tx_fee: units::Avax::from_navax(1_000_000),
+        creation_tx_fee: units::Avax::from_navax(1_000_000_000),
+        keychain: soft_key::Keychain::new(vec![key.clone()]),
+        co_signers: Vec::new(),
+    };
+    let subnet_id = ids::Id::from_slice(&[0xdd; 32]);
+    let (mut tx, signers) = builder
+        .new_transform_subnet_tx(
+            &utxos,
+            subnet_id,
+            &owner,
+            parameters.clone(),
+            &key.short_address,
+            0,
+        )
+        .unwrap();
+
+    let ins = tx.base_tx.transferable_inputs.clone().unwrap();
+    assert_eq!(ins.len(), 2);
+    assert!(ins[0] < ins[1]);
+    assert_eq!(ins[0].asset_id, parameters.asset_id);
+    assert_eq!(signers.len(), 3);
+
+    // AVAX change after the fee, and the subnet asset change after the reward pool
+    let outs = tx.base_tx.transferable_outputs.clone().unwrap();
+    let amounts: Vec<(ids::Id, u64)> = outs.iter().map(|o| (o.asset_id, o.amount())).collect();
+    assert_eq!(
+        amounts,
+        vec![
+            (avax_asset_id, 1_000_000_000),
+            (parameters.asset_id, 3_000_000)
+        ]
+    );
+    tx.sign(&signers).unwrap();
+    assert_eq!(tx.creds.len(), 3);
+
+    let mut avax_params = parameters.clone();
+    avax_params.asset_id = avax_asset_id;
+    assert!(builder
+        .new_transform_subnet_tx(
+            &utxos,
+            subnet_id,
+            &owner,
+            avax_params,
+            &key.short_address,
+            0
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("AVAX"));
+
+    let mut large_pool = parameters;
+    large_pool.maximum_supply = 7_000_000;
+    large_pool.max_validator_stake = 7_000_000;
+    assert!(builder
+        .new_transform_subnet_tx(&utxos, subnet_id, &owner, large_pool, &key.short_address, 0)
+        .unwrap_err()
+        .to_string()
+        .contains("reward pool"));
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::test_multisig_subnet_owner --exact --show-output
+#[test]
+fn test_multisig_subnet_owner() {
+    use crate::signer::{PartialCredentials, Signer};
+
+    let keys = &soft_key::TEST_KEYS[..3];
+    let avax_asset_id = ids::Id::from_slice(&[0xbb; 32]);
+    let payer = secp256k1fx::OutputOwners::new(0, 1, &[keys[0].short_address]);
+    let utxos = vec![utxo::Utxo {
+        utxo_id: avax::UtxoId {
+            tx_id: ids::Id::from_slice(&[0x01; 32]),
+            output_index: 0,
+            ..avax::UtxoId::default()
+        },
+        asset_id: avax_asset_id,
+        transfer_output: Some(secp256k1fx::TransferOutput::new(2_000_000_000, payer)),
+        ..utxo::Utxo::default()
+    }];
+
+    // 2-of-3 subnet owner, where the third key is held by the other operator
+    let subnet_owner = secp256k1fx::OutputOwners::new_sorted(
+        0,
+        2,
+        &[
+            keys[0].short_address,
+            keys[1].short_address,
+            keys[2].short_address,
+        ],
+    );
+    subnet_owner.verify().unwrap();
+    let builder = SubnetBuilder {
+        network_id: 12345,
+        avax_asset_id,
+        tx_fee: units::Avax::from_navax(1_000_000),
+        creation_tx_fee: units::Avax::from_navax(1_000_000_000),
+        keychain: soft_key::Keychain::new(vec![keys[0].clone()]),
+        co_signers: vec![keys[2].short_address],
+    };
+
+    let validator = platformvm::Validator {
+        node_id: ids::ShortId::from_slice(&[0x11; 20]),
+        start: 1_600_000_000,
+        end: 1_700_000_000,
+        weight: 1_000,
+    };
+    let (mut tx, signers) = builder
+        .new_add_subnet_validator_tx(
+            &utxos,
+            validator,
+            ids::Id::from_slice(&[0xcc; 32]),
+            &subnet_owner,
+            &keys[0].short_address,
+            0,
+        )
+        .unwrap();
+    assert_eq!(tx.subnet_auth.sig_indices.len(), 2);
+    assert_eq!(signers[1].len(), 1);
+    // the keychain alone cannot sign the subnet auth
+    assert!(tx.clone().sign(&signers).is_err());
+
+    let plan = SubnetBuilder::signing_plan(&signers, &subnet_owner, &tx.subnet_auth).unwrap();
+    let mut auth_addrs = plan[1].clone();
+    auth_addrs.sort();
+    let mut expected = vec![keys[0].short_address, keys[2].short_address];
+    expected.sort();
+    assert_eq!(auth_addrs, expected);
+
+    let unsigned_bytes = tx.unsigned_bytes().unwrap();
+    let mut partial = PartialCredentials::new(&unsigned_bytes, plan.clone());
+    let local: &dyn Signer = &keys[0];
+    assert_eq!(tokio_test::block_on(partial.sign(local)).unwrap(), 2);
+    assert!(!partial.is_complete());
+    assert_eq!(partial.missing(), vec![(1, keys[2].short_address)]);
+    assert!(partial.finalize().is_err());
+
+    // the other operator signs the same plan and sends back the signature
+    let mut remote = PartialCredentials::new(&unsigned_bytes, plan);
+    let co_signer: &dyn Signer = &keys[2];
+    assert_eq!(tokio_test::block_on(remote.sign(co_signer)).unwrap(), 1);
+    let sig =
+        tokio_test::block_on(co_signer.sign_digest(&utils::hash::compute_sha256(&unsigned_bytes)))
+            .unwrap();
+    assert!(partial.add_signature(0, sig.clone()).is_err());
+    partial.add_signature(1, sig).unwrap();
+    assert!(partial.is_complete());
+
+    tx.creds = partial.finalize().unwrap();
+    assert_eq!(tx.creds.len(), 2);
+    assert_eq!(tx.creds[1].signatures.len(), 2);
+    let (_, tx_id) = avax::pack_signed_tx(&unsigned_bytes, &tx.creds).unwrap();
+    assert_ne!(tx_id, ids::Id::empty());
+
+    // the keychain with two owner keys needs no co-signer
+    let local_builder = SubnetBuilder {
+        keychain: soft_key::Keychain::new(vec![keys[0].clone(), keys[1].clone()]),
+        co_signers: Vec::new(),
+        ..builder
+    };
+    let (mut tx, signers) = local_builder
+        .new_create_chain_tx(
+            &utxos,
+            ids::Id::from_slice(&[0xcc; 32]),
+            &subnet_owner,
+            "subnetevm",
+            ids::Id::from_slice(&[0xdd; 32]),
+            &[],
+            b"{}".to_vec(),
+            &keys[0].short_address,
+            0,
+        )
+        .unwrap();
+    assert_eq!(signers[1].len(), 2);
+    tx.sign(&signers).unwrap();
+}
@@ -0,0 +1,115 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer, secp256k1fx};
+
+/// Removes the validator from the subnet before its end time.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/txs#RemoveSubnetValidatorTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    pub base_tx: avax::BaseTx,
+    pub node_id: ids::ShortId,
+    pub subnet_id: ids::Id,
+    /// Signature indices of the subnet owner keys.
+    pub subnet_auth: secp256k1fx::Input,
+
+    /// One per input and the last one for the subnet auth.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            node_id: ids::ShortId::empty(),
+            subnet_id: ids::Id::empty(),
+            subnet_auth: secp256k1fx::Input::default(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "txs.RemoveSubnetValidatorTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// Encodes the unsigned "txs.RemoveSubnetValidatorTx": the base tx,
+    /// the node and subnet IDs, then the subnet auth.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::P_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            self.base_tx.pack(packer)?;
+            packer.pack_bytes(self.node_id.as_bytes());
+            packer.pack_bytes(self.subnet_id.as_bytes());
+            super::pack_subnet_auth(packer, &self.subnet_auth);
+            Ok(())
+        })
+    }
+
+    /// Decodes the signed tx bytes (e.g., returned by "platform.getTx"),
+    /// returning the tx with its credentials and ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        super::decode_signed(b, Self::type_id(), Self::unpack)
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        Ok(Self {
+            base_tx: avax::BaseTx::unpack(packer)?,
+            node_id: ids::ShortId::from_slice(&packer.unpack_bytes(ids::SHORT_ID_LEN)?),
+            subnet_id: ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?),
+            subnet_auth: super::unpack_subnet_auth(packer)?,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::remove_subnet_validator::test_from_bytes --exact --show-output
+#[test]
+fn test_from_bytes() {
+    let validator_tx = super::add_validator::test_tx();
+    let tx = Tx {
+        base_tx: validator_tx.base_tx.clone(),
+        node_id: validator_tx.validator.node_id,
+        subnet_id: ids::Id::from_slice(&[0xcc; 32]),
+        subnet_auth: secp256k1fx::Input::new(vec![0]),
+        creds: vec![
+            secp256k1fx::Credential::new(vec![vec![0x44; 65]]),
+            secp256k1fx::Credential::new(vec![vec![0x55; 65]]),
+        ],
+    };
+
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x17, // "txs.RemoveSubnetValidatorTx" type ID
+    ];
+    expected.extend(super::test_base_tx_bytes());
+    expected.extend_from_slice(&[0x11; 20]); // node ID
+    expected.extend_from_slice(&[0xcc; 32]); // subnet ID
+    expected.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x0a, // "secp256k1fx.Input" type ID
+        0x00, 0x00, 0x00, 0x01, // number of signature indices
+        0x00, 0x00, 0x00, 0x00, // signature index
+    ]);
+    let unsigned_bytes = tx.unsigned_bytes().unwrap();
+    assert_eq!(unsigned_bytes, expected);
+
+    let (signed_bytes, tx_id) = avax::pack_signed_tx(&unsigned_bytes, &tx.creds).unwrap();
+    assert_eq!(Tx::from_bytes(&signed_bytes).unwrap(), (tx, tx_id));
+
+    // the tx of the other type is rejected
+    let (b, _) = super::advance_time::Tx::new(1).to_bytes().unwrap();
+    let err = Tx::from_bytes(&b).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("is not txs.RemoveSubnetValidatorTx"));
+}
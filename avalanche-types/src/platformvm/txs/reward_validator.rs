@@ -0,0 +1,107 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer};
+
+/// Removes the staker whose end time is reached from the current set.
+/// Issued by the block builder rather than the users, so it has no inputs
+/// or credentials. The reward is paid only if the proposal block is followed
+/// by the commit block, and the abort block returns just the stake.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/txs#RewardValidatorTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    /// ID of the add validator or delegator tx of the staker.
+    pub tx_id: ids::Id,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            tx_id: ids::Id::empty(),
+        }
+    }
+
+    pub fn new(tx_id: ids::Id) -> Self {
+        Self { tx_id }
+    }
+
+    pub fn type_name() -> String {
+        "platformvm.UnsignedRewardValidatorTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::P_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            packer.pack_bytes(self.tx_id.as_bytes());
+            Ok(())
+        })
+    }
+
+    /// Returns the tx bytes with no credentials and its ID,
+    /// as embedded in the proposal block.
+    pub fn to_bytes(&self) -> io::Result<(Vec<u8>, ids::Id)> {
+        avax::pack_signed_tx(&self.unsigned_bytes()?, &[])
+    }
+
+    /// Decodes the tx bytes (e.g., returned by "platform.getTx"),
+    /// returning the tx with its ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        super::decode_signed(b, Self::type_id(), Self::unpack)
+    }
+
+    /// Unpacks the fields and the empty credentials after the type ID.
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let tx_id = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+        let creds = super::unpack_creds(packer)?;
+        if !creds.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("reward validator tx has {} credentials", creds.len()),
+            ));
+        }
+        Ok(Self { tx_id })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::reward_validator::test_bytes --exact --show-output
+#[test]
+fn test_bytes() {
+    let tx = Tx::new(ids::Id::from_slice(&[0x11; 32]));
+
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x14, // "platformvm.UnsignedRewardValidatorTx" type ID
+    ];
+    expected.extend_from_slice(&[0x11; 32]); // staker tx ID
+    assert_eq!(tx.unsigned_bytes().unwrap(), expected);
+
+    let (b, tx_id) = tx.to_bytes().unwrap();
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]); // number of credentials
+    assert_eq!(b, expected);
+    assert_eq!(Tx::from_bytes(&b).unwrap(), (tx, tx_id));
+
+    let mut with_cred = b[..b.len() - 4].to_vec();
+    with_cred.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x01, // number of credentials
+        0x00, 0x00, 0x00, 0x09, // "secp256k1fx.Credential" type ID
+        0x00, 0x00, 0x00, 0x00, // number of signatures
+    ]);
+    assert!(Tx::from_bytes(&with_cred).is_err());
+
+    let (advance_time, _) = super::advance_time::Tx::new(1_700_000_000)
+        .to_bytes()
+        .unwrap();
+    let err = Tx::from_bytes(&advance_time).unwrap_err();
+    assert!(err.to_string().contains("platformvm.UnsignedAdvanceTimeTx"));
+}
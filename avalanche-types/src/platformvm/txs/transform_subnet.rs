@@ -0,0 +1,391 @@
+use std::io::{self, Error, ErrorKind};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, ids, packer, platformvm, secp256k1fx, soft_key};
+
+/// Staking and reward parameters that make the permissioned subnet elastic,
+/// staked with its own asset instead of the subnet owner adding the validators.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/txs#TransformSubnetTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct Parameters {
+    /// Asset to stake, which must not be AVAX.
+    #[serde(deserialize_with = "ids::must_deserialize_id")]
+    pub asset_id: ids::Id,
+    /// Supply of the asset in circulation,
+    /// the rest up to "maximum_supply" is the reward pool.
+    pub initial_supply: u64,
+    pub maximum_supply: u64,
+    /// Reward rates in the units of "txs::PERCENT_DENOMINATOR",
+    /// for the minimum and maximum stake durations respectively.
+    pub min_consumption_rate: u64,
+    pub max_consumption_rate: u64,
+    pub min_validator_stake: u64,
+    pub max_validator_stake: u64,
+    /// In seconds.
+    pub min_stake_duration: u32,
+    pub max_stake_duration: u32,
+    /// In the units of "txs::PERCENT_DENOMINATOR".
+    pub min_delegation_fee: u32,
+    pub min_delegator_stake: u64,
+    /// Maximum delegated weight as the multiple of the validator stake.
+    pub max_validator_weight_factor: u8,
+    /// Minimum uptime for the rewards, in the units of "txs::PERCENT_DENOMINATOR".
+    pub uptime_requirement: u32,
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Parameters {
+    pub fn default() -> Self {
+        Self {
+            asset_id: ids::Id::empty(),
+            initial_supply: 0,
+            maximum_supply: 0,
+            min_consumption_rate: 0,
+            max_consumption_rate: 0,
+            min_validator_stake: 0,
+            max_validator_stake: 0,
+            min_stake_duration: 0,
+            max_stake_duration: 0,
+            min_delegation_fee: 0,
+            min_delegator_stake: 0,
+            max_validator_weight_factor: 0,
+            uptime_requirement: 0,
+        }
+    }
+
+    /// Amount of the asset that the transformation locks as the reward pool.
+    pub fn reward_pool(&self) -> u64 {
+        self.maximum_supply.saturating_sub(self.initial_supply)
+    }
+
+    /// ref. "avalanchego/vms/platformvm/txs.TransformSubnetTx.SyntacticVerify"
+    pub fn verify(&self) -> io::Result<()> {
+        let percent_denominator = super::PERCENT_DENOMINATOR as u64;
+        if self.asset_id == ids::Id::empty() {
+            return invalid("asset ID must be non-empty".to_string()); // ref. "errEmptyAssetID"
+        }
+        if self.initial_supply == 0 {
+            return invalid("initial supply must be non-zero".to_string()); // ref. "errInitialSupplyZero"
+        }
+        if self.initial_supply > self.maximum_supply {
+            return invalid(format!(
+                "initial supply {} exceeds maximum supply {}", // ref. "errInitialSupplyGreaterThanMaxSupply"
+                self.initial_supply, self.maximum_supply
+            ));
+        }
+        if self.min_consumption_rate > self.max_consumption_rate {
+            return invalid(format!(
+                "min consumption rate {} exceeds max consumption rate {}", // ref. "errMinConsumptionRateTooLarge"
+                self.min_consumption_rate, self.max_consumption_rate
+            ));
+        }
+        if self.max_consumption_rate > percent_denominator {
+            return invalid(format!(
+                "max consumption rate {} exceeds {}", // ref. "errMaxConsumptionRateTooLarge"
+                self.max_consumption_rate, percent_denominator
+            ));
+        }
+        if self.min_validator_stake == 0 {
+            return invalid("min validator stake must be non-zero".to_string()); // ref. "errMinValidatorStakeZero"
+        }
+        if self.min_validator_stake > self.initial_supply {
+            return invalid(format!(
+                "min validator stake {} exceeds initial supply {}", // ref. "errMinValidatorStakeAboveSupply"
+                self.min_validator_stake, self.initial_supply
+            ));
+        }
+        if self.min_validator_stake > self.max_validator_stake {
+            return invalid(format!(
+                "min validator stake {} exceeds max validator stake {}", // ref. "errMinValidatorStakeAboveMax"
+                self.min_validator_stake, self.max_validator_stake
+            ));
+        }
+        if self.max_validator_stake > self.maximum_supply {
+            return invalid(format!(
+                "max validator stake {} exceeds maximum supply {}", // ref. "errMaxValidatorStakeTooLarge"
+                self.max_validator_stake, self.maximum_supply
+            ));
+        }
+        if self.min_stake_duration == 0 {
+            return invalid("min stake duration must be non-zero".to_string()); // ref. "errMinStakeDurationZero"
+        }
+        if self.min_stake_duration > self.max_stake_duration {
+            return invalid(format!(
+                "min stake duration {} exceeds max stake duration {}", // ref. "errMinStakeDurationTooLarge"
+                self.min_stake_duration, self.max_stake_duration
+            ));
+        }
+        if self.min_delegation_fee > super::PERCENT_DENOMINATOR {
+            return invalid(format!(
+                "min delegation fee {} exceeds {}", // ref. "errMinDelegationFeeTooLarge"
+                self.min_delegation_fee,
+                super::PERCENT_DENOMINATOR
+            ));
+        }
+        if self.min_delegator_stake == 0 {
+            return invalid("min delegator stake must be non-zero".to_string()); // ref. "errMinDelegatorStakeZero"
+        }
+        if self.max_validator_weight_factor == 0 {
+            return invalid("max validator weight factor must be non-zero".to_string());
+            // ref. "errMaxValidatorWeightFactorZero"
+        }
+        if self.uptime_requirement > super::PERCENT_DENOMINATOR {
+            return invalid(format!(
+                "uptime requirement {} exceeds {}", // ref. "errUptimeRequirementTooLarge"
+                self.uptime_requirement,
+                super::PERCENT_DENOMINATOR
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn invalid(msg: String) -> io::Result<()> {
+    Err(Error::new(ErrorKind::InvalidInput, msg))
+}
+
+/// Transforms the permissioned subnet into the elastic subnet.
+/// The subnet owner can no longer add the validators or create the blockchains,
+/// so this must be the last tx authorized by the subnet owner.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/txs#TransformSubnetTx
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Tx {
+    /// Consumes the reward pool of the asset as well as the fee.
+    pub base_tx: avax::BaseTx,
+    pub subnet_id: ids::Id,
+    pub parameters: Parameters,
+    /// Signature indices of the subnet owner keys.
+    pub subnet_auth: secp256k1fx::Input,
+
+    /// Populated by "sign", one per input and the last one for the subnet auth.
+    pub creds: Vec<secp256k1fx::Credential>,
+}
+
+impl Default for Tx {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Tx {
+    pub fn default() -> Self {
+        Self {
+            base_tx: avax::BaseTx::default(),
+            subnet_id: ids::Id::empty(),
+            parameters: Parameters::default(),
+            subnet_auth: secp256k1fx::Input::default(),
+            creds: Vec::new(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "txs.TransformSubnetTx".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// ref. "avalanchego/vms/platformvm/txs.TransformSubnetTx.SyntacticVerify"
+    pub fn verify(&self) -> io::Result<()> {
+        if self.subnet_id == platformvm::chain_id() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "cannot transform the primary network", // ref. "errCantTransformPrimaryNetwork"
+            ));
+        }
+        self.parameters.verify()
+    }
+
+    /// Encodes the unsigned "txs.TransformSubnetTx": the base tx, the
+    /// subnet ID, its staking parameters and the subnet auth.
+    pub fn unsigned_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::P_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            self.base_tx.pack(packer)?;
+            packer.pack_bytes(self.subnet_id.as_bytes());
+
+            let p = &self.parameters;
+            packer.pack_bytes(p.asset_id.as_bytes());
+            packer.pack_u64(p.initial_supply);
+            packer.pack_u64(p.maximum_supply);
+            packer.pack_u64(p.min_consumption_rate);
+            packer.pack_u64(p.max_consumption_rate);
+            packer.pack_u64(p.min_validator_stake);
+            packer.pack_u64(p.max_validator_stake);
+            packer.pack_u32(p.min_stake_duration);
+            packer.pack_u32(p.max_stake_duration);
+            packer.pack_u32(p.min_delegation_fee);
+            packer.pack_u64(p.min_delegator_stake);
+            packer.pack_byte(p.max_validator_weight_factor);
+            packer.pack_u32(p.uptime_requirement);
+
+            super::pack_subnet_auth(packer, &self.subnet_auth);
+            Ok(())
+        })
+    }
+
+    /// Decodes the signed tx bytes (e.g., returned by "platform.getTx"),
+    /// returning the tx with its credentials and ID.
+    pub fn from_bytes(b: &[u8]) -> io::Result<(Self, ids::Id)> {
+        super::decode_signed(b, Self::type_id(), Self::unpack)
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let base_tx = avax::BaseTx::unpack(packer)?;
+        let subnet_id = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+        let parameters = Parameters {
+            asset_id: ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?),
+            initial_supply: packer.unpack_u64()?,
+            maximum_supply: packer.unpack_u64()?,
+            min_consumption_rate: packer.unpack_u64()?,
+            max_consumption_rate: packer.unpack_u64()?,
+            min_validator_stake: packer.unpack_u64()?,
+            max_validator_stake: packer.unpack_u64()?,
+            min_stake_duration: packer.unpack_u32()?,
+            max_stake_duration: packer.unpack_u32()?,
+            min_delegation_fee: packer.unpack_u32()?,
+            min_delegator_stake: packer.unpack_u64()?,
+            max_validator_weight_factor: packer.unpack_byte()?,
+            uptime_requirement: packer.unpack_u32()?,
+        };
+        Ok(Self {
+            base_tx,
+            subnet_id,
+            parameters,
+            subnet_auth: super::unpack_subnet_auth(packer)?,
+            creds: super::unpack_creds(packer)?,
+        })
+    }
+
+    /// Signs the tx with the keys for each input (in the same order as the inputs)
+    /// followed by the subnet owner keys, and returns the signed tx bytes to issue with its tx ID.
+    pub fn sign(&mut self, signers: &[Vec<soft_key::Key>]) -> io::Result<(Vec<u8>, ids::Id)> {
+        self.verify()?;
+        super::verify_subnet_signers(&self.base_tx, &self.subnet_auth, signers)?;
+
+        let unsigned_bytes = self.unsigned_bytes()?;
+        self.creds = avax::sign_credentials(&unsigned_bytes, signers)?;
+        avax::pack_signed_tx(&unsigned_bytes, &self.creds)
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn test_parameters() -> Parameters {
+    Parameters {
+        asset_id: ids::Id::from_slice(&[0xcc; 32]),
+        initial_supply: 1_000_000,
+        maximum_supply: 3_000_000,
+        min_consumption_rate: 100_000,
+        max_consumption_rate: 120_000,
+        min_validator_stake: 2_000,
+        max_validator_stake: 3_000_000,
+        min_stake_duration: 24 * 60 * 60,
+        max_stake_duration: 365 * 24 * 60 * 60,
+        min_delegation_fee: 20_000,
+        min_delegator_stake: 25,
+        max_validator_weight_factor: 5,
+        uptime_requirement: 800_000,
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::transform_subnet::test_unsigned_bytes --exact --show-output
+#[test]
+fn test_unsigned_bytes() {
+    let validator_tx = super::add_validator::test_tx();
+    let tx = Tx {
+        base_tx: validator_tx.base_tx.clone(),
+        subnet_id: ids::Id::from_slice(&[0xdd; 32]),
+        parameters: test_parameters(),
+        subnet_auth: secp256k1fx::Input::new(vec![0]),
+        creds: Vec::new(),
+    };
+    tx.verify().unwrap();
+    assert_eq!(tx.parameters.reward_pool(), 2_000_000);
+
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0x00, 0x00, 0x00, 0x18, // "txs.TransformSubnetTx" type ID
+    ];
+    expected.extend(super::test_base_tx_bytes());
+    expected.extend_from_slice(&[0xdd; 32]); // subnet ID
+    expected.extend_from_slice(&[0xcc; 32]); // asset ID
+    expected.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x0f, 0x42, 0x40, // initial supply
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x2d, 0xc6, 0xc0, // maximum supply
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x86, 0xa0, // min consumption rate
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0xd4, 0xc0, // max consumption rate
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07, 0xd0, // min validator stake
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x2d, 0xc6, 0xc0, // max validator stake
+        0x00, 0x01, 0x51, 0x80, // min stake duration
+        0x01, 0xe1, 0x33, 0x80, // max stake duration
+        0x00, 0x00, 0x4e, 0x20, // min delegation fee
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x19, // min delegator stake
+        0x05, // max validator weight factor
+        0x00, 0x0c, 0x35, 0x00, // uptime requirement
+        0x00, 0x00, 0x00, 0x0a, // "secp256k1fx.Input" type ID
+        0x00, 0x00, 0x00, 0x01, // number of signature indices
+        0x00, 0x00, 0x00, 0x00, // signature index
+    ]);
+    assert_eq!(tx.unsigned_bytes().unwrap(), expected);
+
+    let mut primary = tx.clone();
+    primary.subnet_id = platformvm::chain_id();
+    assert!(primary.verify().is_err());
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- platformvm::txs::transform_subnet::test_parameters_verify --exact --show-output
+#[test]
+fn test_parameters_verify() {
+    let p = test_parameters();
+    p.verify().unwrap();
+
+    // initial supply may be the maximum supply, with no reward pool
+    let mut no_rewards = p.clone();
+    no_rewards.initial_supply = no_rewards.maximum_supply;
+    no_rewards.verify().unwrap();
+    assert_eq!(no_rewards.reward_pool(), 0);
+
+    let invalids: &[(&str, fn(&mut Parameters))] = &[
+        ("asset ID", |p| p.asset_id = ids::Id::empty()),
+        ("initial supply", |p| p.initial_supply = 0),
+        ("maximum supply", |p| p.maximum_supply = 999_999),
+        ("min consumption rate", |p| p.min_consumption_rate = 130_000),
+        ("max consumption rate", |p| {
+            p.max_consumption_rate = 1_000_001
+        }),
+        ("min validator stake", |p| p.min_validator_stake = 0),
+        ("initial supply", |p| p.min_validator_stake = 1_000_001),
+        ("max validator stake", |p| p.max_validator_stake = 1_000),
+        ("maximum supply", |p| p.max_validator_stake = 3_000_001),
+        ("min stake duration", |p| p.min_stake_duration = 0),
+        ("max stake duration", |p| p.max_stake_duration = 60),
+        ("min delegation fee", |p| p.min_delegation_fee = 1_000_001),
+        ("min delegator stake", |p| p.min_delegator_stake = 0),
+        ("weight factor", |p| p.max_validator_weight_factor = 0),
+        ("uptime requirement", |p| p.uptime_requirement = 1_000_001),
+    ];
+    for (expected, f) in invalids.iter() {
+        let mut params = p.clone();
+        f(&mut params);
+        let err = params.verify().unwrap_err();
+        assert!(
+            err.to_string().contains(expected),
+            "'{}' does not contain '{}'",
+            err,
+            expected
+        );
+    }
+
+    let yaml = serde_yaml::to_string(&p).unwrap();
+    assert!(yaml.contains("max_validator_weight_factor: 5"));
+    let decoded: Parameters = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(decoded, p);
+}
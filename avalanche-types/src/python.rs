@@ -0,0 +1,417 @@
+// Python bindings of the keys and the P/X-chain tx builders ("python" feature),
+// for scripting the test network scenarios without writing Rust
+// (e.g., "maturin develop" with "pyproject.toml").
+//
+// Amounts are in nAVAX, addresses are the chain-prefixed bech32 strings
+// (e.g., "X-local1..."), UTXOs are the hex or CB58 strings returned by "getUTXOs",
+// and the signed txs carry the checksummed hex for "issueTx" with "encoding=hex".
+
+use std::{
+    io::{self, Error, ErrorKind},
+    str::FromStr,
+    time::SystemTime,
+};
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    avax, avm,
+    formatting::{
+        self,
+        address::{self, Chain},
+    },
+    ids, platformvm, secp256k1fx, soft_key, units, utxo,
+};
+
+fn to_py_err(e: Error) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// Secp256k1 key of the X/P-chain and C-chain addresses.
+#[pyclass(name = "Key")]
+#[derive(Clone)]
+pub struct Key {
+    inner: soft_key::Key,
+}
+
+#[pymethods]
+impl Key {
+    #[staticmethod]
+    fn generate() -> PyResult<Self> {
+        let inner = soft_key::Key::generate().map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Loads the CB58-encoded private key (e.g., "PrivateKey-ewoq...").
+    #[staticmethod]
+    fn from_private_key(encoded: &str) -> PyResult<Self> {
+        let inner = soft_key::Key::from_private_key(encoded).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Loads the hex-encoded private key (e.g., exported from MetaMask).
+    #[staticmethod]
+    fn from_private_key_hex(encoded: &str) -> PyResult<Self> {
+        let inner = soft_key::Key::from_private_key_eth(encoded).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    #[staticmethod]
+    fn from_mnemonic_phrase(phrase: &str) -> PyResult<Self> {
+        let inner = soft_key::Key::from_mnemonic_phrase(phrase).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    #[getter]
+    fn private_key(&self) -> String {
+        self.inner.private_key.clone()
+    }
+
+    #[getter]
+    fn private_key_hex(&self) -> String {
+        self.inner.private_key_hex.clone()
+    }
+
+    #[getter]
+    fn short_address(&self) -> String {
+        self.inner.short_address.to_string()
+    }
+
+    #[getter]
+    fn eth_address(&self) -> String {
+        self.inner.eth_address.clone()
+    }
+
+    /// Returns the chain-prefixed bech32 address (e.g., "X-local1...").
+    fn address(&self, chain_alias: &str, network_id: u32) -> PyResult<String> {
+        let chain = Chain::from_str(chain_alias).map_err(to_py_err)?;
+        address::encode(chain, network_id, &self.inner.short_address).map_err(to_py_err)
+    }
+
+    /// Never prints the private key.
+    fn __repr__(&self) -> String {
+        format!(
+            "Key(short_address='{}', eth_address='{}')",
+            self.inner.short_address, self.inner.eth_address
+        )
+    }
+}
+
+/// Signed tx with its ID, ready for "issueTx".
+#[pyclass(name = "SignedTx")]
+pub struct SignedTx {
+    #[pyo3(get)]
+    chain_alias: String,
+    #[pyo3(get)]
+    tx_id: String,
+    /// "0x"-prefixed hex with the checksum.
+    #[pyo3(get)]
+    tx_hex: String,
+}
+
+#[pymethods]
+impl SignedTx {
+    fn __repr__(&self) -> String {
+        format!(
+            "SignedTx(chain_alias='{}', tx_id='{}')",
+            self.chain_alias, self.tx_id
+        )
+    }
+}
+
+impl SignedTx {
+    fn new(chain_alias: &str, (bytes, tx_id): (Vec<u8>, ids::Id)) -> Self {
+        Self {
+            chain_alias: String::from(chain_alias),
+            tx_id: tx_id.to_string(),
+            tx_hex: formatting::encode_hex_with_checksum(&bytes),
+        }
+    }
+}
+
+fn keychain(keys: &[Key]) -> soft_key::Keychain {
+    soft_key::Keychain::new(keys.iter().map(|k| k.inner.clone()).collect())
+}
+
+fn parse_utxos(utxos: &[String]) -> io::Result<Vec<utxo::Utxo>> {
+    utxos
+        .iter()
+        .map(|u| utxo::decode_utxo_untrusted(u))
+        .collect()
+}
+
+fn parse_id(name: &str, s: &str) -> io::Result<ids::Id> {
+    ids::Id::from_str(s).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid {} '{}' ({})", name, s, e),
+        )
+    })
+}
+
+fn parse_owners(
+    addrs: &[String],
+    threshold: u32,
+    chain: Chain,
+    network_id: u32,
+) -> io::Result<secp256k1fx::OutputOwners> {
+    let mut owners: Vec<ids::ShortId> = Vec::new();
+    for addr in addrs.iter() {
+        owners.push(address::decode_for(addr, chain, network_id)?);
+    }
+    Ok(secp256k1fx::OutputOwners::new_sorted(0, threshold, &owners))
+}
+
+fn parse_validator(
+    node_id: &str,
+    start: u64,
+    end: u64,
+    weight: u64,
+) -> io::Result<platformvm::Validator> {
+    Ok(platformvm::Validator {
+        node_id: ids::NodeId::from_str(node_id)?.short_id(),
+        start,
+        end,
+        weight,
+    })
+}
+
+fn now_unix(now: Option<u64>) -> u64 {
+    now.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("unexpected None duration_since")
+            .as_secs()
+    })
+}
+
+/// Builds and signs the X-chain base tx sending "outputs" (the pairs of
+/// the address and the amount in nAVAX) from the UTXOs of the keys,
+/// with the change sent back to "change_address".
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn x_base_tx(
+    keys: Vec<Key>,
+    network_id: u32,
+    blockchain_id: &str,
+    avax_asset_id: &str,
+    tx_fee: u64,
+    utxos: Vec<String>,
+    outputs: Vec<(String, u64)>,
+    change_address: &str,
+    now: Option<u64>,
+) -> PyResult<SignedTx> {
+    let avax_asset_id = parse_id("avax_asset_id", avax_asset_id).map_err(to_py_err)?;
+    let builder = avm::txs::base::Builder {
+        network_id,
+        blockchain_id: parse_id("blockchain_id", blockchain_id).map_err(to_py_err)?,
+        avax_asset_id,
+        tx_fee: units::Avax::from_navax(tx_fee),
+        keychain: keychain(&keys),
+    };
+    let mut outs: Vec<avax::TransferableOutput> = Vec::new();
+    for (addr, amount) in outputs.iter() {
+        outs.push(avax::TransferableOutput {
+            asset_id: avax_asset_id,
+            transfer_output: Some(secp256k1fx::TransferOutput::new(
+                *amount,
+                parse_owners(&[addr.clone()], 1, Chain::X, network_id).map_err(to_py_err)?,
+            )),
+            ..avax::TransferableOutput::default()
+        });
+    }
+    let (mut tx, signers) = builder
+        .new_base_tx(
+            &parse_utxos(&utxos).map_err(to_py_err)?,
+            outs,
+            &address::decode_for(change_address, Chain::X, network_id).map_err(to_py_err)?,
+            now_unix(now),
+        )
+        .map_err(to_py_err)?;
+    Ok(SignedTx::new("X", tx.sign(&signers).map_err(to_py_err)?))
+}
+
+/// Builds and signs the P-chain add validator tx staking "weight" nAVAX
+/// from the UTXOs of the keys, with the rewards sent to "reward_address".
+/// "delegation_fee" is in the units of 10,000 (e.g., 20000 for 2%).
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn p_add_validator_tx(
+    keys: Vec<Key>,
+    network_id: u32,
+    avax_asset_id: &str,
+    tx_fee: u64,
+    utxos: Vec<String>,
+    node_id: &str,
+    start: u64,
+    end: u64,
+    weight: u64,
+    reward_address: &str,
+    delegation_fee: u32,
+    change_address: &str,
+    now: Option<u64>,
+) -> PyResult<SignedTx> {
+    let builder = platformvm::txs::add_validator::Builder {
+        network_id,
+        avax_asset_id: parse_id("avax_asset_id", avax_asset_id).map_err(to_py_err)?,
+        tx_fee: units::Avax::from_navax(tx_fee),
+        keychain: keychain(&keys),
+    };
+    let (mut tx, signers) = builder
+        .new_add_validator_tx(
+            &parse_utxos(&utxos).map_err(to_py_err)?,
+            parse_validator(node_id, start, end, weight).map_err(to_py_err)?,
+            parse_owners(&[String::from(reward_address)], 1, Chain::P, network_id)
+                .map_err(to_py_err)?,
+            delegation_fee,
+            &address::decode_for(change_address, Chain::P, network_id).map_err(to_py_err)?,
+            now_unix(now),
+        )
+        .map_err(to_py_err)?;
+    Ok(SignedTx::new("P", tx.sign(&signers).map_err(to_py_err)?))
+}
+
+/// Builds and signs the P-chain create subnet tx owned by the
+/// "threshold"-of-"subnet_owners" addresses.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn p_create_subnet_tx(
+    keys: Vec<Key>,
+    network_id: u32,
+    avax_asset_id: &str,
+    creation_tx_fee: u64,
+    utxos: Vec<String>,
+    subnet_owners: Vec<String>,
+    threshold: u32,
+    change_address: &str,
+    now: Option<u64>,
+) -> PyResult<SignedTx> {
+    let builder = platformvm::txs::SubnetBuilder {
+        network_id,
+        avax_asset_id: parse_id("avax_asset_id", avax_asset_id).map_err(to_py_err)?,
+        tx_fee: units::Avax::ZERO,
+        creation_tx_fee: units::Avax::from_navax(creation_tx_fee),
+        keychain: keychain(&keys),
+        co_signers: Vec::new(),
+    };
+    let (mut tx, signers) = builder
+        .new_create_subnet_tx(
+            &parse_utxos(&utxos).map_err(to_py_err)?,
+            parse_owners(&subnet_owners, threshold, Chain::P, network_id).map_err(to_py_err)?,
+            &address::decode_for(change_address, Chain::P, network_id).map_err(to_py_err)?,
+            now_unix(now),
+        )
+        .map_err(to_py_err)?;
+    Ok(SignedTx::new("P", tx.sign(&signers).map_err(to_py_err)?))
+}
+
+/// Builds and signs the P-chain add subnet validator tx, authorized by
+/// the subnet owner keys which must be among "keys".
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn p_add_subnet_validator_tx(
+    keys: Vec<Key>,
+    network_id: u32,
+    avax_asset_id: &str,
+    tx_fee: u64,
+    utxos: Vec<String>,
+    subnet_id: &str,
+    subnet_owners: Vec<String>,
+    threshold: u32,
+    node_id: &str,
+    start: u64,
+    end: u64,
+    weight: u64,
+    change_address: &str,
+    now: Option<u64>,
+) -> PyResult<SignedTx> {
+    let builder = platformvm::txs::SubnetBuilder {
+        network_id,
+        avax_asset_id: parse_id("avax_asset_id", avax_asset_id).map_err(to_py_err)?,
+        tx_fee: units::Avax::from_navax(tx_fee),
+        creation_tx_fee: units::Avax::ZERO,
+        keychain: keychain(&keys),
+        co_signers: Vec::new(),
+    };
+    let (mut tx, signers) = builder
+        .new_add_subnet_validator_tx(
+            &parse_utxos(&utxos).map_err(to_py_err)?,
+            parse_validator(node_id, start, end, weight).map_err(to_py_err)?,
+            parse_id("subnet_id", subnet_id).map_err(to_py_err)?,
+            &parse_owners(&subnet_owners, threshold, Chain::P, network_id).map_err(to_py_err)?,
+            &address::decode_for(change_address, Chain::P, network_id).map_err(to_py_err)?,
+            now_unix(now),
+        )
+        .map_err(to_py_err)?;
+    Ok(SignedTx::new("P", tx.sign(&signers).map_err(to_py_err)?))
+}
+
+/// "import avalanche_types" after "maturin develop".
+#[pymodule]
+fn avalanche_types(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<Key>()?;
+    m.add_class::<SignedTx>()?;
+    m.add_function(wrap_pyfunction!(x_base_tx, m)?)?;
+    m.add_function(wrap_pyfunction!(p_add_validator_tx, m)?)?;
+    m.add_function(wrap_pyfunction!(p_create_subnet_tx, m)?)?;
+    m.add_function(wrap_pyfunction!(p_add_subnet_validator_tx, m)?)?;
+    Ok(())
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib --features python -- python::test_python --exact --show-output
+#[test]
+fn test_python() {
+    let network_id = 12345;
+    let key = Key::from_private_key("PrivateKey-ewoqjP7PxY4yr3iLTpLisriqt94hdyDFNgchSxGGztUrTXtNN")
+        .unwrap();
+    assert_eq!(
+        key.address("X", network_id).unwrap(),
+        "X-local18jma8ppw3nhx5r4ap8clazz0dps7rv5u00z96u"
+    );
+    assert!(key.address("Z", network_id).is_err());
+    assert!(!key.__repr__().contains(&key.private_key()));
+    let to = Key::generate().unwrap();
+
+    let avax_asset_id = ids::Id::from_slice(&[1; 32]);
+    let utxo = utxo::Utxo {
+        utxo_id: avax::UtxoId::new(&[2; 32], 0, false),
+        asset_id: avax_asset_id,
+        transfer_output: Some(secp256k1fx::TransferOutput::new(
+            10 * units::AVAX,
+            secp256k1fx::OutputOwners::new(0, 1, &[key.inner.short_address]),
+        )),
+        ..utxo::Utxo::default()
+    };
+    let utxos = vec![formatting::encode_hex_with_checksum(
+        &utxo.to_bytes().unwrap(),
+    )];
+
+    let signed = x_base_tx(
+        vec![key.clone()],
+        network_id,
+        &ids::Id::from_slice(&[3; 32]).to_string(),
+        &avax_asset_id.to_string(),
+        units::MILLI_AVAX,
+        utxos.clone(),
+        vec![(to.address("X", network_id).unwrap(), units::AVAX)],
+        &key.address("X", network_id).unwrap(),
+        Some(0),
+    )
+    .unwrap();
+    let (_, tx_id) = avm::txs::decode_tx_untrusted(&signed.tx_hex).unwrap();
+    assert_eq!(tx_id.to_string(), signed.tx_id);
+
+    // wrong chain of the output address
+    assert!(x_base_tx(
+        vec![key.clone()],
+        network_id,
+        &ids::Id::from_slice(&[3; 32]).to_string(),
+        &avax_asset_id.to_string(),
+        units::MILLI_AVAX,
+        utxos,
+        vec![(to.address("P", network_id).unwrap(), units::AVAX)],
+        &key.address("X", network_id).unwrap(),
+        Some(0),
+    )
+    .is_err());
+}
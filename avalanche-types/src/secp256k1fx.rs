@@ -5,9 +5,12 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
-use crate::{codec, ids};
+use crate::{codec, ids, packer};
 use utils::cmp;
 
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/crypto#SECP256K1RSigLen
+pub const SIG_LEN: usize = 65;
+
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/avm#FxCredential
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/components/verify#Verifiable
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/secp256k1fx#Credential
@@ -42,6 +45,33 @@ impl Credential {
     pub fn type_id() -> u32 {
         *(codec::X_TYPES.get(&Self::type_name()).unwrap()) as u32
     }
+
+    /// Packs the credential with its type ID, as an element of "[]verify.Verifiable".
+    pub fn pack(&self, packer: &packer::Packer) {
+        packer.pack_u32(Self::type_id());
+        packer.pack_u32(self.signatures.len() as u32);
+        for sig in self.signatures.iter() {
+            packer.pack_bytes(sig);
+        }
+    }
+
+    /// Unpacks the credential including its type ID.
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let type_id = packer.unpack_u32()?;
+        if type_id != Self::type_id() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unexpected credential type ID {}", type_id),
+            ));
+        }
+
+        let n = packer.unpack_u32()?;
+        let mut signatures: Vec<Vec<u8>> = Vec::new();
+        for _ in 0..n {
+            signatures.push(packer.unpack_bytes(SIG_LEN)?);
+        }
+        Ok(Self { signatures })
+    }
 }
 
 impl Ord for Credential {
@@ -196,6 +226,99 @@ impl OutputOwners {
         }
     }
 
+    /// Sorts and dedups the addresses as required by "verify"
+    /// (e.g., the 2-of-3 multisig owners from the unordered control keys).
+    pub fn new_sorted(locktime: u64, threshold: u32, addrs: &[ids::ShortId]) -> Self {
+        let mut addrs = Vec::from(addrs);
+        addrs.sort();
+        addrs.dedup();
+        Self {
+            locktime,
+            threshold,
+            addrs,
+        }
+    }
+
+    /// ref. "avalanchego/vms/secp256k1fx.OutputOwners.Verify"
+    pub fn verify(&self) -> io::Result<()> {
+        if self.threshold as usize > self.addrs.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "owner threshold {} exceeds {} addresses", // ref. "errOutputUnspendable"
+                    self.threshold,
+                    self.addrs.len()
+                ),
+            ));
+        }
+        if self.threshold == 0 && !self.addrs.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "owner threshold 0 with addresses", // ref. "errOutputUnoptimized"
+            ));
+        }
+        if !self.addrs.windows(2).all(|w| w[0] < w[1]) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "owner addresses not sorted and unique", // ref. "errAddrsNotSortedUnique"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the signature indices and the addresses of the first "threshold"
+    /// owners in "signers", or "None" if still locked or the threshold is NOT met.
+    /// Unlike "Keychain::match_threshold", the signers may hold the keys elsewhere
+    /// (e.g., the co-signers of the multisig subnet owner).
+    /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/secp256k1fx#Keychain.Match
+    pub fn match_addrs(
+        &self,
+        signers: &[ids::ShortId],
+        time: u64,
+    ) -> Option<(Vec<u32>, Vec<ids::ShortId>)> {
+        if self.locktime > time {
+            return None;
+        }
+
+        let mut sig_indices: Vec<u32> = Vec::new();
+        let mut addrs: Vec<ids::ShortId> = Vec::new();
+        for (pos, addr) in self.addrs.iter().enumerate() {
+            if (addrs.len() as u32) == self.threshold {
+                break;
+            }
+            if !signers.contains(addr) {
+                continue;
+            }
+            sig_indices.push(pos as u32);
+            addrs.push(*addr);
+        }
+        if (addrs.len() as u32) == self.threshold {
+            Some((sig_indices, addrs))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the addresses at the signature indices,
+    /// in the order that the credential must be signed.
+    pub fn addrs_at(&self, sig_indices: &[u32]) -> io::Result<Vec<ids::ShortId>> {
+        sig_indices
+            .iter()
+            .map(|idx| {
+                self.addrs.get(*idx as usize).copied().ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "signature index {} out of {} addresses",
+                            idx,
+                            self.addrs.len()
+                        ),
+                    )
+                })
+            })
+            .collect()
+    }
+
     pub fn type_name() -> String {
         "secp256k1fx.OutputOwners".to_string()
     }
@@ -203,6 +326,35 @@ impl OutputOwners {
     pub fn type_id() -> u32 {
         *(codec::P_TYPES.get(&Self::type_name()).unwrap()) as u32
     }
+
+    /// Packs the fields without the type ID (e.g., embedded in "TransferOutput").
+    pub fn pack(&self, packer: &packer::Packer) {
+        packer.pack_u64(self.locktime);
+        packer.pack_u32(self.threshold);
+        packer.pack_u32(self.addrs.len() as u32);
+        for addr in self.addrs.iter() {
+            packer.pack_bytes(&addr.d);
+        }
+    }
+
+    /// Unpacks the fields without the type ID.
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let locktime = packer.unpack_u64()?;
+        let threshold = packer.unpack_u32()?;
+
+        let n = packer.unpack_u32()?;
+        let mut addrs: Vec<ids::ShortId> = Vec::new();
+        for _ in 0..n {
+            addrs.push(ids::ShortId::from_slice(
+                &packer.unpack_bytes(ids::SHORT_ID_LEN)?,
+            ));
+        }
+        Ok(Self {
+            locktime,
+            threshold,
+            addrs,
+        })
+    }
 }
 
 impl Ord for OutputOwners {
@@ -300,6 +452,39 @@ fn test_sort_output_owners() {
     assert_eq!(owners, sorted_owners);
 }
 
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- secp256k1fx::test_multisig_output_owners --exact --show-output
+#[test]
+fn test_multisig_output_owners() {
+    let a = ids::ShortId::from_slice(&[0x01; 20]);
+    let b = ids::ShortId::from_slice(&[0x02; 20]);
+    let c = ids::ShortId::from_slice(&[0x03; 20]);
+
+    // 2-of-3
+    let owners = OutputOwners::new_sorted(0, 2, &[c, a, b, a]);
+    assert_eq!(owners.addrs, vec![a, b, c]);
+    owners.verify().unwrap();
+    assert!(OutputOwners::new(0, 2, &[c, a, b]).verify().is_err());
+    assert!(OutputOwners::new_sorted(0, 4, &[a, b, c]).verify().is_err());
+    assert!(OutputOwners::new_sorted(0, 0, &[a]).verify().is_err());
+
+    assert_eq!(
+        owners.match_addrs(&[c, a], 0),
+        Some((vec![0, 2], vec![a, c]))
+    );
+    assert_eq!(
+        owners.match_addrs(&[a, b, c], 0),
+        Some((vec![0, 1], vec![a, b]))
+    );
+    assert_eq!(owners.match_addrs(&[b], 0), None);
+
+    let locked = OutputOwners::new_sorted(100, 2, &[a, b, c]);
+    assert_eq!(locked.match_addrs(&[a, b], 99), None);
+    assert!(locked.match_addrs(&[a, b], 100).is_some());
+
+    assert_eq!(owners.addrs_at(&[0, 2]).unwrap(), vec![a, c]);
+    assert!(owners.addrs_at(&[3]).is_err());
+}
+
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/components/avax#TransferableOutput
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/components/avax#TransferableOut
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/secp256k1fx#TransferOutput
@@ -337,6 +522,22 @@ impl TransferOutput {
     pub fn type_id() -> u32 {
         *(codec::X_TYPES.get(&Self::type_name()).unwrap()) as u32
     }
+
+    /// Packs the fields without the type ID.
+    pub fn pack(&self, packer: &packer::Packer) {
+        packer.pack_u64(self.amount);
+        self.output_owners.pack(packer);
+    }
+
+    /// Unpacks the fields without the type ID.
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let amount = packer.unpack_u64()?;
+        let output_owners = OutputOwners::unpack(packer)?;
+        Ok(Self {
+            amount,
+            output_owners,
+        })
+    }
 }
 
 impl Ord for TransferOutput {
@@ -541,6 +742,30 @@ impl TransferInput {
         let sigs = self.sig_indices.len();
         (sigs as u64) * 1000
     }
+
+    /// Packs the fields without the type ID.
+    pub fn pack(&self, packer: &packer::Packer) {
+        packer.pack_u64(self.amount);
+        packer.pack_u32(self.sig_indices.len() as u32);
+        for idx in self.sig_indices.iter() {
+            packer.pack_u32(*idx);
+        }
+    }
+
+    /// Unpacks the fields without the type ID.
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let amount = packer.unpack_u64()?;
+
+        let n = packer.unpack_u32()?;
+        let mut sig_indices: Vec<u32> = Vec::new();
+        for _ in 0..n {
+            sig_indices.push(packer.unpack_u32()?);
+        }
+        Ok(Self {
+            amount,
+            sig_indices,
+        })
+    }
 }
 
 impl Ord for TransferInput {
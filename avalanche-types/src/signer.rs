@@ -0,0 +1,212 @@
+use std::io::{self, Error, ErrorKind};
+
+use async_trait::async_trait;
+use secp256k1::PublicKey;
+
+use crate::{formatting, ids, secp256k1fx, soft_key};
+use utils::{hash, secp256k1r};
+
+/// Signs the transaction digests with a secp256k1 key,
+/// whether the key is held in memory or in a remote key manager.
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// Signs the 32-byte digest and returns the 65-byte recoverable signature.
+    /// ref. "avalanchego/utils/crypto.PrivateKeySECP256K1R.SignHash"
+    async fn sign_digest(&self, digest: &[u8]) -> io::Result<Vec<u8>>;
+
+    fn public_key(&self) -> io::Result<PublicKey>;
+
+    /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/hashing#PubkeyBytesToAddress
+    fn short_address(&self) -> ids::ShortId;
+
+    /// ref. https://pkg.go.dev/github.com/ethereum/go-ethereum/common#Address
+    fn eth_address(&self) -> String;
+
+    /// Returns the bech32 address for the chain (e.g., "X", "P").
+    fn address(&self, chain_id_alias: &str, network_id: u32) -> io::Result<String> {
+        let hrp = formatting::address::hrp(network_id);
+        self.short_address().to_bech32_address(chain_id_alias, hrp)
+    }
+}
+
+#[async_trait]
+impl Signer for soft_key::Key {
+    async fn sign_digest(&self, digest: &[u8]) -> io::Result<Vec<u8>> {
+        soft_key::Key::sign_digest(self, digest)
+    }
+
+    fn public_key(&self) -> io::Result<PublicKey> {
+        // not persisted, thus empty for the deserialized key
+        match &self.public_key {
+            Some(v) => Ok(*v),
+            None => Ok(soft_key::Key::from_private_key(&self.private_key)?
+                .public_key
+                .expect("unexpected None public_key")),
+        }
+    }
+
+    fn short_address(&self) -> ids::ShortId {
+        self.short_address
+    }
+
+    fn eth_address(&self) -> String {
+        self.eth_address.clone()
+    }
+}
+
+/// Signs the hash of the unsigned tx bytes with the signers of each input,
+/// returning one credential per input in the same order.
+/// Same as "avax::sign_credentials" but for any signer (e.g., KMS, Ledger),
+/// so the signed tx is packed with "avax::pack_signed_tx".
+pub async fn sign_credentials(
+    unsigned_bytes: &[u8],
+    signers: &[Vec<&dyn Signer>],
+) -> io::Result<Vec<secp256k1fx::Credential>> {
+    let digest = hash::compute_sha256(unsigned_bytes);
+
+    let mut creds: Vec<secp256k1fx::Credential> = Vec::with_capacity(signers.len());
+    for input_signers in signers.iter() {
+        let mut sigs: Vec<Vec<u8>> = Vec::with_capacity(input_signers.len());
+        for s in input_signers.iter() {
+            sigs.push(s.sign_digest(&digest).await?);
+        }
+        creds.push(secp256k1fx::Credential::new(sigs));
+    }
+    Ok(creds)
+}
+
+/// Collects the signatures of the multisig credentials from multiple signers
+/// (e.g., the local key, KMS, and Ledger of the 2-of-3 subnet owner),
+/// each of which only signs for its own address.
+/// ref. "avalanchego/wallet/chain/p.signer.Sign"
+#[derive(Debug, Clone)]
+pub struct PartialCredentials {
+    digest: Vec<u8>,
+    /// Addresses to sign each credential, in the order of the signatures.
+    addrs: Vec<Vec<ids::ShortId>>,
+    sigs: Vec<Vec<Option<Vec<u8>>>>,
+}
+
+impl PartialCredentials {
+    /// Takes the addresses to sign each credential in the order of the inputs
+    /// (e.g., "platformvm::txs::SubnetBuilder::signing_plan").
+    pub fn new(unsigned_bytes: &[u8], addrs: Vec<Vec<ids::ShortId>>) -> Self {
+        let sigs = addrs.iter().map(|a| vec![None; a.len()]).collect();
+        Self {
+            digest: hash::compute_sha256(unsigned_bytes),
+            addrs,
+            sigs,
+        }
+    }
+
+    /// Signs all missing signatures of the signer's address,
+    /// and returns the number of the signatures added.
+    /// The digest is signed at most once, since it is the same for every credential.
+    pub async fn sign(&mut self, signer: &dyn Signer) -> io::Result<usize> {
+        let short_address = signer.short_address();
+        let mut sig: Option<Vec<u8>> = None;
+        let mut added = 0;
+        for (addrs, sigs) in self.addrs.iter().zip(self.sigs.iter_mut()) {
+            for (addr, slot) in addrs.iter().zip(sigs.iter_mut()) {
+                if *addr != short_address || slot.is_some() {
+                    continue;
+                }
+                if sig.is_none() {
+                    sig = Some(signer.sign_digest(&self.digest).await?);
+                }
+                *slot = sig.clone();
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Adds the signature made elsewhere (e.g., sent by the other operator)
+    /// to the credential at "index", after recovering its signer address.
+    pub fn add_signature(&mut self, index: usize, sig: Vec<u8>) -> io::Result<()> {
+        let public_key = secp256k1r::recover_public_key(&self.digest, &sig)?;
+        let short_address = soft_key::public_key_to_short_address(&public_key)?;
+
+        let (addrs, sigs) = match (self.addrs.get(index), self.sigs.get_mut(index)) {
+            (Some(addrs), Some(sigs)) => (addrs, sigs),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("credential index {} out of {}", index, self.addrs.len()),
+                ))
+            }
+        };
+        let pos = addrs
+            .iter()
+            .position(|a| *a == short_address)
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "signer {} is not required by credential {}",
+                        short_address, index
+                    ),
+                )
+            })?;
+        sigs[pos] = Some(sig);
+        Ok(())
+    }
+
+    /// Returns the credential index and the address of each missing signature.
+    pub fn missing(&self) -> Vec<(usize, ids::ShortId)> {
+        let mut missing = Vec::new();
+        for (i, (addrs, sigs)) in self.addrs.iter().zip(self.sigs.iter()).enumerate() {
+            for (addr, sig) in addrs.iter().zip(sigs.iter()) {
+                if sig.is_none() {
+                    missing.push((i, *addr));
+                }
+            }
+        }
+        missing
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.sigs.iter().flatten().all(|s| s.is_some())
+    }
+
+    /// Returns the credentials to pack with "avax::pack_signed_tx",
+    /// failing if any signature is still missing.
+    pub fn finalize(&self) -> io::Result<Vec<secp256k1fx::Credential>> {
+        let missing = self.missing();
+        if !missing.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} signature(s) missing", missing.len()),
+            ));
+        }
+        Ok(self
+            .sigs
+            .iter()
+            .map(|sigs| secp256k1fx::Credential::new(sigs.iter().flatten().cloned().collect()))
+            .collect())
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- signer::test_soft_key_signer --exact --show-output
+#[test]
+fn test_soft_key_signer() {
+    let key = soft_key::TEST_KEYS[0].clone();
+    let signer: &dyn Signer = &key;
+
+    let digest = [1u8; 32];
+    let sig = tokio_test::block_on(signer.sign_digest(&digest)).unwrap();
+    assert_eq!(sig, key.sign_digest(&digest).unwrap());
+    assert_eq!(signer.public_key().unwrap(), key.public_key.unwrap());
+    assert_eq!(signer.short_address(), key.short_address);
+    assert_eq!(
+        signer.address("X", 1).unwrap(),
+        key.address("X", 1).unwrap()
+    );
+
+    let unsigned_bytes = [1u8, 2, 3];
+    let creds = tokio_test::block_on(sign_credentials(&unsigned_bytes, &[vec![signer]])).unwrap();
+    assert_eq!(
+        creds,
+        crate::avax::sign_credentials(&unsigned_bytes, &[vec![key.clone()]]).unwrap()
+    );
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt,
     fs::{self, File},
     io::{self, Error, ErrorKind, Write},
@@ -8,7 +8,7 @@ use std::{
     string::String,
 };
 
-use bip32::{DerivationPath, Language, Mnemonic, XPrv};
+use bip32::{ChildNumber, DerivationPath, Language, Mnemonic, XPrv};
 use bitcoin::hashes::hex::ToHex;
 use ethereum_types::{Address, H256};
 use lazy_static::lazy_static;
@@ -20,7 +20,7 @@ use serde::{Deserialize, Serialize};
 use sha3::Keccak256;
 
 use crate::{constants, formatting, ids, secp256k1fx};
-use utils::{cmp, hash, prefix};
+use utils::{cmp, hash, prefix, secp256k1r};
 
 pub const PRIVATE_KEY_ENCODE_PREFIX: &str = "PrivateKey-";
 
@@ -48,8 +48,24 @@ lazy_static! {
     pub static ref AVAX_ACCOUNT_DERIVIATION_PATH: DerivationPath = {
         "m/44'/9000'/0'".parse().unwrap()
     };
+
+    /// External (receiving) addresses of the AVAX wallet are "m/44'/9000'/0'/0/n".
+    /// ref. https://github.com/ava-labs/avalanche-wallet/blob/v0.3.8/src/js/wallets/HdHelper.ts
+    pub static ref AVAX_EXTERNAL_CHAIN_DERIVIATION_PATH: DerivationPath = {
+        "m/44'/9000'/0'/0".parse().unwrap()
+    };
 }
 
+/// Number of consecutive unused addresses after which
+/// the AVAX wallet stops scanning.
+/// ref. https://github.com/ava-labs/avalanche-wallet/blob/v0.3.8/src/js/wallets/HdHelper.ts
+pub const AVAX_WALLET_SCAN_GAP_SIZE: u32 = 20;
+
+/// Networks whose addresses every key precomputes
+/// (mainnet, fuji, local, and the "custom" hrp shared by all custom networks).
+pub const DEFAULT_ADDRESS_NETWORK_IDS: [u32; 4] =
+    [1, 5, 12345, constants::DEFAULT_CUSTOM_NETWORK_ID];
+
 /// Loads keys from texts, assuming each key is line-separated.
 pub fn load_encoded_keys(d: &[u8]) -> io::Result<Vec<Key>> {
     let text = match str::from_utf8(d) {
@@ -123,6 +139,11 @@ pub struct Key {
 
     /// ref. https://pkg.go.dev/github.com/ethereum/go-ethereum/common#Address
     pub eth_address: String,
+
+    /// Bech32 addresses keyed by the hrp, so that "address" does not
+    /// re-encode for every call (e.g., matching UTXOs of thousands of keys).
+    #[serde(skip_serializing, skip_deserializing)]
+    pub addresses: BTreeMap<String, NetworkAddresses>,
 }
 
 impl Key {
@@ -144,7 +165,7 @@ impl Key {
         private_key.push_str(&enc);
         let private_key_hex = hex::encode(&priv_bytes);
 
-        Ok(Self {
+        let mut key = Self {
             mnemonic_phrase: None,
             secret_key: Some(secret_key),
             public_key: Some(public_key),
@@ -152,7 +173,10 @@ impl Key {
             private_key_hex,
             short_address,
             eth_address,
-        })
+            addresses: BTreeMap::new(),
+        };
+        key.cache_addresses(&DEFAULT_ADDRESS_NETWORK_IDS)?;
+        Ok(key)
     }
 
     /// Loads the specified Secp256k1 key with CB58 encoding.
@@ -200,7 +224,7 @@ impl Key {
         private_key.push_str(&enc);
         let private_key_hex = hex::encode(&priv_bytes);
 
-        Ok(Self {
+        let mut key = Self {
             mnemonic_phrase: None,
             secret_key: Some(secret_key),
             public_key: Some(public_key),
@@ -208,7 +232,10 @@ impl Key {
             private_key_hex,
             short_address,
             eth_address,
-        })
+            addresses: BTreeMap::new(),
+        };
+        key.cache_addresses(&DEFAULT_ADDRESS_NETWORK_IDS)?;
+        Ok(key)
     }
 
     /// Loads the specified Secp256k1 key with CB58 encoding.
@@ -256,7 +283,7 @@ impl Key {
         private_key.push_str(&enc);
         let private_key_hex = hex::encode(&priv_bytes);
 
-        Ok(Self {
+        let mut key = Self {
             mnemonic_phrase: None,
             secret_key: Some(secret_key),
             public_key: Some(public_key),
@@ -264,7 +291,10 @@ impl Key {
             private_key_hex,
             short_address,
             eth_address,
-        })
+            addresses: BTreeMap::new(),
+        };
+        key.cache_addresses(&DEFAULT_ADDRESS_NETWORK_IDS)?;
+        Ok(key)
     }
 
     /// Loads the specified Secp256k1 key with hex encoding.
@@ -300,25 +330,37 @@ impl Key {
     where
         S: AsRef<str>,
     {
-        let mnemonic = Mnemonic::new(phrase, Language::English).map_err(|e| {
-            return Error::new(
-                ErrorKind::Other,
-                format!("failed to read mnemonic phrase ({})", e),
-            );
-        })?;
-        let seed = mnemonic.to_seed("password");
-
         // ref. https://github.com/ava-labs/avalanche-wallet/blob/v0.3.8/src/js/wallets/MnemonicWallet.ts
-        let child_xprv =
-            XPrv::derive_from_path(&seed, &AVAX_ACCOUNT_DERIVIATION_PATH).map_err(|e| {
-                return Error::new(
-                    ErrorKind::Other,
-                    format!("failed to derive AVAX account path ({})", e),
-                );
-            })?;
+        let mnemonic = parse_mnemonic(phrase)?;
+        let child_xprv = derive_xprv(&mnemonic, "password", &AVAX_ACCOUNT_DERIVIATION_PATH)?;
+        Self::from_xprv(&child_xprv, &mnemonic)
+    }
 
-        let pk = child_xprv.private_key().to_bytes();
+    /// Derives the key from the BIP39 mnemonic phrase with the BIP39 passphrase
+    /// (empty for the AVAX wallet) and the BIP44 derivation path
+    /// (e.g., "m/44'/9000'/0'/0/0").
+    /// Only 24-word English phrases are supported, as generated by the AVAX wallet.
+    pub fn from_mnemonic_phrase_with_path<S>(
+        phrase: S,
+        passphrase: &str,
+        path: &str,
+    ) -> io::Result<Self>
+    where
+        S: AsRef<str>,
+    {
+        let path = DerivationPath::from_str(path).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid derivation path '{}' ({})", path, e),
+            )
+        })?;
+        let mnemonic = parse_mnemonic(phrase)?;
+        let xprv = derive_xprv(&mnemonic, passphrase, &path)?;
+        Self::from_xprv(&xprv, &mnemonic)
+    }
 
+    fn from_xprv(xprv: &XPrv, mnemonic: &Mnemonic) -> io::Result<Self> {
+        let pk = xprv.private_key().to_bytes();
         let mut key = Self::from_private_key_raw(&pk)?;
         key.mnemonic_phrase = Some(String::from(mnemonic.phrase()));
         Ok(key)
@@ -328,17 +370,61 @@ impl Key {
     /// "human readable part" (hrp) must be valid output from "constants.GetHRP(networkID)".
     /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/constants
     pub fn address(&self, chain_id_alias: &str, network_id: u32) -> io::Result<String> {
-        let hrp = match constants::NETWORK_ID_TO_HRP.get(&network_id) {
-            Some(v) => v,
-            None => constants::FALLBACK_HRP,
-        };
-        // ref. "pk.PublicKey().Address().Bytes()"
-        let short_address_bytes = public_key_to_short_address_bytes(
-            &self.public_key.expect("unexpected empty public_key"),
-        )?;
+        let hrp = formatting::address::hrp(network_id);
+        if let Some(addrs) = self.addresses.get(hrp) {
+            if let Some(addr) = addrs.get(chain_id_alias) {
+                return Ok(addr.to_string());
+            }
+        }
+        // ref. "formatting.FormatAddress(chainIDAlias, hrp, pk.PublicKey().Address().Bytes())"
+        self.short_address.to_bech32_address(chain_id_alias, hrp)
+    }
+
+    /// Precomputes the X/P/C-chain addresses of the networks
+    /// in addition to "DEFAULT_ADDRESS_NETWORK_IDS".
+    pub fn cache_addresses(&mut self, network_ids: &[u32]) -> io::Result<()> {
+        for network_id in network_ids.iter() {
+            let hrp = formatting::address::hrp(*network_id);
+            if self.addresses.contains_key(hrp) {
+                continue;
+            }
+            let addrs = NetworkAddresses::new(&self.short_address, hrp)?;
+            self.addresses.insert(hrp.to_string(), addrs);
+        }
+        Ok(())
+    }
+
+    /// Returns the address book of all cached networks.
+    pub fn key_info(&self) -> KeyInfo {
+        KeyInfo {
+            mnemonic_phrase: self.mnemonic_phrase.clone(),
+            private_key: self.private_key.clone(),
+            private_key_hex: self.private_key_hex.clone(),
+            short_address: self.short_address,
+            eth_address: self.eth_address.clone(),
+            addresses: self.addresses.clone(),
+        }
+    }
 
-        // ref. "formatting.FormatAddress(chainIDAlias, hrp, pubBytes)"
-        formatting::address(chain_id_alias, hrp, &short_address_bytes)
+    /// Signs the 32-byte digest and returns the 65-byte recoverable signature.
+    /// ref. "avalanchego/utils/crypto.PrivateKeySECP256K1R.SignHash"
+    pub fn sign_digest(&self, digest: &[u8]) -> io::Result<Vec<u8>> {
+        let secret_key = match &self.secret_key {
+            Some(sk) => sk,
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "key has no secret key to sign with",
+                ));
+            }
+        };
+        if digest.len() != 32 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("digest must be 32-byte, got {}", digest.len()),
+            ));
+        }
+        Ok(secp256k1r::sign_ecdsa_recoverable(secret_key, digest))
     }
 
     pub fn short_address_bytes(&self) -> io::Result<Vec<u8>> {
@@ -356,7 +442,7 @@ impl Key {
             x_address,
             p_address,
             c_address,
-            short_address: self.short_address.clone(),
+            short_address: self.short_address,
             eth_address: self.eth_address.clone(),
         })
     }
@@ -423,6 +509,148 @@ impl FromStr for Key {
     }
 }
 
+/// Derives "count" keys of the AVAX wallet external addresses
+/// "m/44'/9000'/0'/0/n" starting from the index "start".
+pub fn derive_wallet_keys<S>(
+    phrase: S,
+    passphrase: &str,
+    start: u32,
+    count: u32,
+) -> io::Result<Vec<Key>>
+where
+    S: AsRef<str>,
+{
+    let mnemonic = parse_mnemonic(phrase)?;
+    let chain_xprv = derive_xprv(&mnemonic, passphrase, &AVAX_EXTERNAL_CHAIN_DERIVIATION_PATH)?;
+
+    let mut keys: Vec<Key> = Vec::with_capacity(count as usize);
+    for idx in start..start.saturating_add(count) {
+        let xprv = derive_child_xprv(&chain_xprv, idx)?;
+        keys.push(Key::from_xprv(&xprv, &mnemonic)?);
+    }
+    Ok(keys)
+}
+
+/// Scans the AVAX wallet external addresses in order, the same way
+/// the wallet discovers them, until "gap_size" consecutive keys
+/// are reported unused by "is_used" (e.g., no UTXO on any chain).
+/// Returns the keys up to and including the last used one.
+pub fn scan_wallet_keys<S, F>(
+    phrase: S,
+    passphrase: &str,
+    gap_size: u32,
+    mut is_used: F,
+) -> io::Result<Vec<Key>>
+where
+    S: AsRef<str>,
+    F: FnMut(&Key) -> io::Result<bool>,
+{
+    let mnemonic = parse_mnemonic(phrase)?;
+    let chain_xprv = derive_xprv(&mnemonic, passphrase, &AVAX_EXTERNAL_CHAIN_DERIVIATION_PATH)?;
+
+    let mut keys: Vec<Key> = Vec::new();
+    let mut used = 0;
+    let mut idx = 0;
+    while keys.len() - used < gap_size as usize {
+        let xprv = derive_child_xprv(&chain_xprv, idx)?;
+        let key = Key::from_xprv(&xprv, &mnemonic)?;
+        let key_used = is_used(&key)?;
+        keys.push(key);
+        if key_used {
+            used = keys.len();
+        }
+        idx += 1;
+    }
+    keys.truncate(used);
+    info!("found {} used wallet key(s)", keys.len());
+    Ok(keys)
+}
+
+fn parse_mnemonic<S>(phrase: S) -> io::Result<Mnemonic>
+where
+    S: AsRef<str>,
+{
+    Mnemonic::new(phrase, Language::English).map_err(|e| {
+        Error::new(
+            ErrorKind::Other,
+            format!("failed to read mnemonic phrase ({})", e),
+        )
+    })
+}
+
+fn derive_xprv(mnemonic: &Mnemonic, passphrase: &str, path: &DerivationPath) -> io::Result<XPrv> {
+    let seed = mnemonic.to_seed(passphrase);
+    XPrv::derive_from_path(&seed, path).map_err(|e| {
+        Error::new(
+            ErrorKind::Other,
+            format!("failed to derive path {} ({})", path, e),
+        )
+    })
+}
+
+fn derive_child_xprv(xprv: &XPrv, idx: u32) -> io::Result<XPrv> {
+    let child_number = ChildNumber::new(idx, false).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid child index {} ({})", idx, e),
+        )
+    })?;
+    xprv.derive_child(child_number).map_err(|e| {
+        Error::new(
+            ErrorKind::Other,
+            format!("failed to derive child {} ({})", idx, e),
+        )
+    })
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- soft_key::test_derive_wallet_keys --exact --show-output
+#[test]
+fn test_derive_wallet_keys() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    // expected keys are derived independently with BIP32/BIP39
+    // for "m/44'/9000'/0'/0/n" and an empty passphrase
+    let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+    let keys = derive_wallet_keys(phrase, "", 0, 3).unwrap();
+    assert_eq!(
+        keys.iter()
+            .map(|k| k.private_key_hex.clone())
+            .collect::<Vec<String>>(),
+        vec![
+            "8f7bc8f5afef0237ce1ac23152ab785f36d60d875cd479a22ede211057a5bd6a",
+            "469d86a4098ef992a86f693831ffb9a75ecd402d987467799e5f142915054ca0",
+            "3076c21966010ea8b640f40013d48a10dc59246b71de16d77c363679d21db469",
+        ]
+    );
+    assert_eq!(keys[0].mnemonic_phrase, Some(String::from(phrase)));
+
+    let k = Key::from_mnemonic_phrase_with_path(phrase, "", "m/44'/9000'/0'/0/2").unwrap();
+    assert_eq!(k, keys[2]);
+    assert_eq!(derive_wallet_keys(phrase, "", 2, 1).unwrap(), vec![k]);
+    assert!(Key::from_mnemonic_phrase_with_path(phrase, "", "m/44'/x").is_err());
+
+    // the same path as "from_mnemonic_phrase"
+    let test_key = TEST_KEYS[0].clone();
+    let k = Key::from_mnemonic_phrase_with_path(
+        test_key.mnemonic_phrase.clone().unwrap(),
+        "password",
+        "m/44'/9000'/0'",
+    )
+    .unwrap();
+    assert_eq!(k, test_key);
+
+    // only index 1 is used
+    let used = keys[1].short_address;
+    let mut scanned = 0;
+    let found = scan_wallet_keys(phrase, "", 3, |k| {
+        scanned += 1;
+        Ok(k.short_address == used)
+    })
+    .unwrap();
+    assert_eq!(found, keys[..2].to_vec());
+    assert_eq!(scanned, 5);
+}
+
 /// Only supports "English" for now.
 /// ref. https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki
 /// ref. https://github.com/rust-bitcoin/rust-bitcoin/blob/master/src/util/bip32.rs
@@ -439,17 +667,13 @@ pub fn generate_mnemonic_phrase_24_word() -> String {
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/hashing#PubkeyBytesToAddress
 pub fn bytes_to_short_address(d: &[u8]) -> io::Result<ids::ShortId> {
     let short_address_bytes = bytes_to_short_address_bytes(d)?;
-
-    // "ids.ShortID.String"
-    // ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/ids#ShortID.String
-    let encoded = formatting::encode_cb58_with_checksum(&short_address_bytes);
-    ids::ShortId::from_str(&encoded)
+    Ok(ids::ShortId::from_slice(&short_address_bytes))
 }
 
 /// "hashing.PubkeyBytesToAddress"
 /// ref. "pk.PublicKey().Address().Bytes()"
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/utils/hashing#PubkeyBytesToAddress
-fn public_key_to_short_address(public_key: &PublicKey) -> io::Result<ids::ShortId> {
+pub fn public_key_to_short_address(public_key: &PublicKey) -> io::Result<ids::ShortId> {
     let public_key_bytes_compressed = public_key.serialize();
     bytes_to_short_address(&public_key_bytes_compressed)
 }
@@ -641,6 +865,17 @@ fn test_soft_key() {
 
         assert_eq!(k.short_address, ki.short_address);
         assert_eq!(k.eth_address, ki.eth_address);
+
+        // bech32 addresses round-trip back to the short address
+        for addr in [
+            &ki.addresses.get("1").unwrap().x_address,
+            &ki.addresses.get("9999").unwrap().p_address,
+        ] {
+            assert_eq!(
+                ids::ShortId::from_bech32_address(addr).unwrap(),
+                k.short_address
+            );
+        }
     }
 }
 
@@ -746,6 +981,136 @@ pub struct PrivateKeyInfoEntryAddress {
     pub c_address: String,
 }
 
+/// X/P/C-chain addresses of the key in one network.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct NetworkAddresses {
+    pub x_address: String,
+    pub p_address: String,
+    pub c_address: String,
+}
+
+impl NetworkAddresses {
+    /// Derives the bech32 addresses of the short address with the hrp.
+    pub fn new(short_address: &ids::ShortId, hrp: &str) -> io::Result<Self> {
+        Ok(Self {
+            x_address: short_address.to_bech32_address("X", hrp)?,
+            p_address: short_address.to_bech32_address("P", hrp)?,
+            c_address: short_address.to_bech32_address("C", hrp)?,
+        })
+    }
+
+    /// Returns the address of the chain alias ("X", "P", or "C").
+    pub fn get(&self, chain_id_alias: &str) -> Option<&str> {
+        match chain_id_alias {
+            "X" => Some(&self.x_address),
+            "P" => Some(&self.p_address),
+            "C" => Some(&self.c_address),
+            _ => None,
+        }
+    }
+}
+
+/// Address book of the key across the networks
+/// (e.g., the funded test keys shared by the mainnet-like and custom networks).
+/// NEVER save mainnet-funded wallet keys here.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct KeyInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mnemonic_phrase: Option<String>,
+
+    /// CB58-encoded private key with the prefix "PrivateKey-".
+    pub private_key: String,
+    pub private_key_hex: String,
+
+    #[serde(deserialize_with = "ids::must_deserialize_short_id")]
+    pub short_address: ids::ShortId,
+    /// C-chain hex address.
+    pub eth_address: String,
+
+    /// Bech32 addresses keyed by the hrp (e.g., "avax", "fuji", "custom").
+    #[serde(default)]
+    pub addresses: BTreeMap<String, NetworkAddresses>,
+}
+
+impl KeyInfo {
+    /// Returns "None" if the network was not cached when the record was created.
+    pub fn address(&self, chain_id_alias: &str, network_id: u32) -> Option<&str> {
+        self.addresses
+            .get(formatting::address::hrp(network_id))
+            .and_then(|addrs| addrs.get(chain_id_alias))
+    }
+
+    /// Loads the key with the networks of the record cached,
+    /// whose addresses are derived from the key rather than copied from the record.
+    pub fn to_key(&self) -> io::Result<Key> {
+        let mut key = Key::from_private_key(&self.private_key)?;
+        if key.short_address != self.short_address {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "private key does not match the short address {}",
+                    self.short_address
+                ),
+            ));
+        }
+        key.mnemonic_phrase = self.mnemonic_phrase.clone();
+        for hrp in self.addresses.keys() {
+            if !key.addresses.contains_key(hrp) {
+                let addrs = NetworkAddresses::new(&key.short_address, hrp)?;
+                key.addresses.insert(hrp.clone(), addrs);
+            }
+        }
+        Ok(key)
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- soft_key::test_key_info --exact --show-output
+#[test]
+fn test_key_info() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut k = TEST_KEYS[0].clone();
+    assert_eq!(k.addresses.len(), DEFAULT_ADDRESS_NETWORK_IDS.len());
+    assert_eq!(
+        k.address("X", 1).unwrap(),
+        k.short_address.to_bech32_address("X", "avax").unwrap()
+    );
+    // all custom networks share the "custom" hrp
+    assert_eq!(
+        k.address("P", 9999).unwrap(),
+        k.addresses.get("custom").unwrap().p_address
+    );
+
+    k.cache_addresses(&[2, 5]).unwrap();
+    assert_eq!(k.addresses.len(), DEFAULT_ADDRESS_NETWORK_IDS.len() + 1);
+
+    let info = k.key_info();
+    assert_eq!(
+        info.address("C", 2),
+        Some(k.address("C", 2).unwrap().as_str())
+    );
+    assert_eq!(info.address("Y", 2), None);
+    assert_eq!(info.eth_address, k.eth_address);
+
+    let yaml = serde_yaml::to_string(&info).unwrap();
+    info!("{}", yaml);
+    let decoded: KeyInfo = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(decoded, info);
+    assert_eq!(decoded.to_key().unwrap(), k);
+
+    let mut mismatch = info.clone();
+    mismatch.private_key = TEST_KEYS[1].private_key.clone();
+    assert!(mismatch.to_key().is_err());
+
+    // the tampered addresses of the record are never loaded
+    let hrp = formatting::address::hrp(2);
+    let mut tampered = info.clone();
+    tampered.addresses.get_mut(hrp).unwrap().p_address = TEST_KEYS[1].address("P", 2).unwrap();
+    assert_eq!(tampered.to_key().unwrap(), k);
+}
+
 /// Support multiple keys as a chain.
 /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/secp256k1fx#Keychain
 /// ref. https://github.com/ava-labs/avalanchego/blob/v1.7.9/wallet/chain/p/builder.go
@@ -0,0 +1,540 @@
+// Arbitrary values of the core types for the property tests ("proptest" feature)
+// and the fuzz targets ("arbitrary" feature).
+//
+// Only the canonical values are generated (e.g., "None" rather than the empty
+// outputs, and the UTXO ID derived from its tx ID and output index),
+// so that every value survives the pack and unpack round trip unchanged.
+// X-chain txs never spend or create the P-chain stakeable lock outputs.
+
+use crate::{avax, ids, platformvm, secp256k1fx};
+
+/// Upper bound of the generated collections (e.g., outputs, addresses),
+/// to keep each value small enough to shrink.
+pub const MAX_LEN: usize = 4;
+
+fn canonical<T>(v: Vec<T>) -> Option<Vec<T>> {
+    if v.is_empty() {
+        None
+    } else {
+        Some(v)
+    }
+}
+
+fn to_output(
+    asset_id: ids::Id,
+    locktime: Option<u64>,
+    transfer_output: secp256k1fx::TransferOutput,
+) -> avax::TransferableOutput {
+    match locktime {
+        Some(locktime) => avax::TransferableOutput {
+            asset_id,
+            stakeable_lock_out: Some(platformvm::StakeableLockOut {
+                locktime,
+                transfer_output,
+            }),
+            ..avax::TransferableOutput::default()
+        },
+        None => avax::TransferableOutput {
+            asset_id,
+            transfer_output: Some(transfer_output),
+            ..avax::TransferableOutput::default()
+        },
+    }
+}
+
+fn to_input(
+    utxo_id: avax::UtxoId,
+    asset_id: ids::Id,
+    locktime: Option<u64>,
+    transfer_input: secp256k1fx::TransferInput,
+) -> avax::TransferableInput {
+    match locktime {
+        Some(locktime) => avax::TransferableInput {
+            utxo_id,
+            asset_id,
+            stakeable_lock_in: Some(platformvm::StakeableLockIn {
+                locktime,
+                transfer_input,
+            }),
+            ..avax::TransferableInput::default()
+        },
+        None => avax::TransferableInput {
+            utxo_id,
+            asset_id,
+            transfer_input: Some(transfer_input),
+            ..avax::TransferableInput::default()
+        },
+    }
+}
+
+#[cfg(feature = "proptest")]
+pub use self::proptest_impls::{base_tx, transferable_input, transferable_output};
+
+#[cfg(feature = "proptest")]
+mod proptest_impls {
+    use proptest::{collection, option, prelude::*};
+
+    use super::{canonical, MAX_LEN};
+    use crate::{avax, avm, ids, platformvm, secp256k1fx, utxo};
+
+    impl Arbitrary for ids::Id {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            any::<[u8; ids::ID_LEN]>()
+                .prop_map(ids::Id::from_array)
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for ids::ShortId {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            any::<[u8; ids::SHORT_ID_LEN]>()
+                .prop_map(ids::ShortId::from_array)
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for ids::NodeId {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            any::<[u8; ids::NODE_ID_LEN]>()
+                .prop_map(ids::NodeId::from_array)
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for avax::UtxoId {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            (any::<ids::Id>(), any::<u32>())
+                .prop_map(|(tx_id, output_index)| {
+                    avax::UtxoId::new(tx_id.as_bytes(), output_index, false)
+                })
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for secp256k1fx::OutputOwners {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            (
+                any::<u64>(),
+                any::<u32>(),
+                collection::vec(any::<ids::ShortId>(), 0..MAX_LEN),
+            )
+                .prop_map(|(locktime, threshold, addrs)| {
+                    secp256k1fx::OutputOwners::new(locktime, threshold, &addrs)
+                })
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for secp256k1fx::TransferOutput {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            (any::<u64>(), any::<secp256k1fx::OutputOwners>())
+                .prop_map(|(amount, owners)| secp256k1fx::TransferOutput::new(amount, owners))
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for secp256k1fx::TransferInput {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            (any::<u64>(), collection::vec(any::<u32>(), 0..MAX_LEN))
+                .prop_map(|(amount, sig_indices)| {
+                    secp256k1fx::TransferInput::new(amount, sig_indices)
+                })
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for secp256k1fx::Credential {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            collection::vec(
+                collection::vec(any::<u8>(), secp256k1fx::SIG_LEN),
+                0..MAX_LEN,
+            )
+            .prop_map(secp256k1fx::Credential::new)
+            .boxed()
+        }
+    }
+
+    impl Arbitrary for utxo::Utxo {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            (any::<avax::UtxoId>(), transferable_output(true))
+                .prop_map(|(utxo_id, out)| utxo::Utxo {
+                    utxo_id,
+                    asset_id: out.asset_id,
+                    transfer_output: out.transfer_output,
+                    stakeable_lock_out: out.stakeable_lock_out,
+                })
+                .boxed()
+        }
+    }
+
+    /// P-chain base tx, whose outputs and inputs may be stakeable locked.
+    impl Arbitrary for avax::BaseTx {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            base_tx(true)
+        }
+    }
+
+    impl Arbitrary for avm::txs::base::Tx {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            (base_tx(false), creds())
+                .prop_map(|(base_tx, creds)| avm::txs::base::Tx { base_tx, creds })
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for platformvm::Validator {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            (
+                any::<ids::ShortId>(),
+                any::<u64>(),
+                any::<u64>(),
+                any::<u64>(),
+            )
+                .prop_map(|(node_id, start, end, weight)| platformvm::Validator {
+                    node_id,
+                    start,
+                    end,
+                    weight,
+                })
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for platformvm::txs::add_validator::Tx {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            (
+                base_tx(true),
+                any::<platformvm::Validator>(),
+                collection::vec(transferable_output(true), 0..MAX_LEN),
+                any::<secp256k1fx::OutputOwners>(),
+                any::<u32>(),
+                creds(),
+            )
+                .prop_map(
+                    |(base_tx, validator, stake, rewards_owner, shares, creds)| {
+                        platformvm::txs::add_validator::Tx {
+                            base_tx,
+                            validator,
+                            stake_transferable_outputs: canonical(stake),
+                            rewards_owner,
+                            shares,
+                            creds,
+                        }
+                    },
+                )
+                .boxed()
+        }
+    }
+
+    /// Generates the outputs, including the P-chain stakeable lock outputs if "stakeable".
+    pub fn transferable_output(stakeable: bool) -> BoxedStrategy<avax::TransferableOutput> {
+        let locktime = if stakeable {
+            option::of(any::<u64>()).boxed()
+        } else {
+            Just(None).boxed()
+        };
+        (
+            any::<ids::Id>(),
+            locktime,
+            any::<secp256k1fx::TransferOutput>(),
+        )
+            .prop_map(|(asset_id, locktime, out)| super::to_output(asset_id, locktime, out))
+            .boxed()
+    }
+
+    /// Generates the inputs, including the P-chain stakeable lock inputs if "stakeable".
+    pub fn transferable_input(stakeable: bool) -> BoxedStrategy<avax::TransferableInput> {
+        let locktime = if stakeable {
+            option::of(any::<u64>()).boxed()
+        } else {
+            Just(None).boxed()
+        };
+        (
+            any::<avax::UtxoId>(),
+            any::<ids::Id>(),
+            locktime,
+            any::<secp256k1fx::TransferInput>(),
+        )
+            .prop_map(|(utxo_id, asset_id, locktime, input)| {
+                super::to_input(utxo_id, asset_id, locktime, input)
+            })
+            .boxed()
+    }
+
+    pub fn base_tx(stakeable: bool) -> BoxedStrategy<avax::BaseTx> {
+        (
+            any::<u32>(),
+            any::<ids::Id>(),
+            collection::vec(transferable_output(stakeable), 0..MAX_LEN),
+            collection::vec(transferable_input(stakeable), 0..MAX_LEN),
+            collection::vec(any::<u8>(), 0..32),
+        )
+            .prop_map(
+                |(network_id, blockchain_id, outs, ins, memo)| avax::BaseTx {
+                    network_id,
+                    blockchain_id,
+                    transferable_outputs: canonical(outs),
+                    transferable_inputs: canonical(ins),
+                    memo: canonical(memo),
+                },
+            )
+            .boxed()
+    }
+
+    fn creds() -> BoxedStrategy<Vec<secp256k1fx::Credential>> {
+        collection::vec(any::<secp256k1fx::Credential>(), 0..MAX_LEN).boxed()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls {
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    use super::{canonical, MAX_LEN};
+    use crate::{avax, avm, ids, platformvm, secp256k1fx, utxo};
+
+    fn vec_of<'a, T, F>(u: &mut Unstructured<'a>, f: F) -> Result<Vec<T>>
+    where
+        F: Fn(&mut Unstructured<'a>) -> Result<T>,
+    {
+        let n = u.int_in_range(0..=MAX_LEN)?;
+        (0..n).map(|_| f(u)).collect()
+    }
+
+    fn locktime(u: &mut Unstructured<'_>, stakeable: bool) -> Result<Option<u64>> {
+        if stakeable && u.arbitrary()? {
+            Ok(Some(u.arbitrary()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn transferable_output(
+        u: &mut Unstructured<'_>,
+        stakeable: bool,
+    ) -> Result<avax::TransferableOutput> {
+        let asset_id = u.arbitrary()?;
+        let locktime = locktime(u, stakeable)?;
+        Ok(super::to_output(asset_id, locktime, u.arbitrary()?))
+    }
+
+    fn transferable_input(
+        u: &mut Unstructured<'_>,
+        stakeable: bool,
+    ) -> Result<avax::TransferableInput> {
+        let utxo_id = u.arbitrary()?;
+        let asset_id = u.arbitrary()?;
+        let locktime = locktime(u, stakeable)?;
+        Ok(super::to_input(utxo_id, asset_id, locktime, u.arbitrary()?))
+    }
+
+    fn base_tx(u: &mut Unstructured<'_>, stakeable: bool) -> Result<avax::BaseTx> {
+        Ok(avax::BaseTx {
+            network_id: u.arbitrary()?,
+            blockchain_id: u.arbitrary()?,
+            transferable_outputs: canonical(vec_of(u, |u| transferable_output(u, stakeable))?),
+            transferable_inputs: canonical(vec_of(u, |u| transferable_input(u, stakeable))?),
+            memo: canonical(vec_of(u, |u| u.arbitrary::<u8>())?),
+        })
+    }
+
+    impl<'a> Arbitrary<'a> for ids::Id {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(Self::from_array(u.arbitrary()?))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for ids::ShortId {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(Self::from_array(u.arbitrary()?))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for ids::NodeId {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(Self::from_array(u.arbitrary()?))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for avax::UtxoId {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let tx_id: ids::Id = u.arbitrary()?;
+            Ok(Self::new(tx_id.as_bytes(), u.arbitrary()?, false))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for secp256k1fx::OutputOwners {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let locktime = u.arbitrary()?;
+            let threshold = u.arbitrary()?;
+            let addrs: Vec<ids::ShortId> = vec_of(u, |u| u.arbitrary())?;
+            Ok(Self::new(locktime, threshold, &addrs))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for secp256k1fx::TransferOutput {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(Self::new(u.arbitrary()?, u.arbitrary()?))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for secp256k1fx::TransferInput {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let amount = u.arbitrary()?;
+            Ok(Self::new(amount, vec_of(u, |u| u.arbitrary())?))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for secp256k1fx::Credential {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(Self::new(vec_of(u, |u| {
+                Ok(u.bytes(secp256k1fx::SIG_LEN)?.to_vec())
+            })?))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for utxo::Utxo {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let utxo_id = u.arbitrary()?;
+            let out = transferable_output(u, true)?;
+            Ok(Self {
+                utxo_id,
+                asset_id: out.asset_id,
+                transfer_output: out.transfer_output,
+                stakeable_lock_out: out.stakeable_lock_out,
+            })
+        }
+    }
+
+    /// P-chain base tx, whose outputs and inputs may be stakeable locked.
+    impl<'a> Arbitrary<'a> for avax::BaseTx {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            base_tx(u, true)
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for avm::txs::base::Tx {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(Self {
+                base_tx: base_tx(u, false)?,
+                creds: vec_of(u, |u| u.arbitrary())?,
+            })
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for platformvm::Validator {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(Self {
+                node_id: u.arbitrary()?,
+                start: u.arbitrary()?,
+                end: u.arbitrary()?,
+                weight: u.arbitrary()?,
+            })
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for platformvm::txs::add_validator::Tx {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(Self {
+                base_tx: base_tx(u, true)?,
+                validator: u.arbitrary()?,
+                stake_transferable_outputs: canonical(vec_of(u, |u| transferable_output(u, true))?),
+                rewards_owner: u.arbitrary()?,
+                shares: u.arbitrary()?,
+                creds: vec_of(u, |u| u.arbitrary())?,
+            })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "proptest"))]
+mod tests {
+    use std::str::FromStr;
+
+    use proptest::prelude::*;
+
+    use crate::{avax, avm, codec, formatting, ids, packer, platformvm, utxo};
+
+    proptest! {
+        /// RUST_LOG=debug cargo test --package avalanche-types --lib --features proptest -- strategies::tests::test_ids_round_trip --exact --show-output
+        #[test]
+        fn test_ids_round_trip(id in any::<ids::Id>(), short_id in any::<ids::ShortId>(), node_id in any::<ids::NodeId>()) {
+            prop_assert_eq!(ids::Id::from_str(&id.to_string()).unwrap(), id);
+            prop_assert_eq!(ids::ShortId::from_str(&short_id.to_string()).unwrap(), short_id);
+            prop_assert_eq!(ids::NodeId::from_str(&node_id.to_string()).unwrap(), node_id);
+
+            let json = serde_json::to_string(&id).unwrap();
+            prop_assert_eq!(serde_json::from_str::<ids::Id>(&json).unwrap(), id);
+        }
+
+        /// RUST_LOG=debug cargo test --package avalanche-types --lib --features proptest -- strategies::tests::test_utxo_round_trip --exact --show-output
+        #[test]
+        fn test_utxo_round_trip(utxo in any::<utxo::Utxo>()) {
+            let b = utxo.to_bytes().unwrap();
+            prop_assert_eq!(&utxo::Utxo::from_bytes(&b).unwrap(), &utxo);
+
+            // "getUTXOs" payloads in either encoding
+            let hex_encoded = formatting::encode_hex_with_checksum(&b);
+            prop_assert_eq!(&utxo::decode_utxo_untrusted(&hex_encoded).unwrap(), &utxo);
+            let cb58_encoded = formatting::encode_cb58_with_checksum(&b);
+            prop_assert_eq!(utxo::decode_utxo_untrusted(&cb58_encoded).unwrap(), utxo);
+        }
+
+        /// RUST_LOG=debug cargo test --package avalanche-types --lib --features proptest -- strategies::tests::test_base_tx_round_trip --exact --show-output
+        #[test]
+        fn test_base_tx_round_trip(tx in any::<avax::BaseTx>()) {
+            let packer = packer::Packer::new(codec::DEFAULT_MAX_SIZE, 0);
+            tx.pack(&packer).unwrap();
+            prop_assert!(packer.take_error().is_none());
+            let b = packer.take_bytes();
+
+            let packer = packer::Packer::load_bytes_for_unpack(codec::DEFAULT_MAX_SIZE, &b);
+            prop_assert_eq!(avax::BaseTx::unpack(&packer).unwrap(), tx);
+            prop_assert_eq!(packer.remaining(), 0);
+        }
+
+        /// RUST_LOG=debug cargo test --package avalanche-types --lib --features proptest -- strategies::tests::test_x_tx_round_trip --exact --show-output
+        #[test]
+        fn test_x_tx_round_trip(tx in any::<avm::txs::base::Tx>()) {
+            let (b, tx_id) = avax::pack_signed_tx(&tx.unsigned_bytes().unwrap(), &tx.creds).unwrap();
+            let (decoded, decoded_id) = avm::txs::Tx::from_bytes(&b).unwrap();
+            prop_assert_eq!(decoded, avm::txs::Tx::Base(tx));
+            prop_assert_eq!(decoded_id, tx_id);
+        }
+
+        /// RUST_LOG=debug cargo test --package avalanche-types --lib --features proptest -- strategies::tests::test_p_tx_round_trip --exact --show-output
+        #[test]
+        fn test_p_tx_round_trip(tx in any::<platformvm::txs::add_validator::Tx>()) {
+            let (b, tx_id) = avax::pack_signed_tx(&tx.unsigned_bytes().unwrap(), &tx.creds).unwrap();
+            let (decoded, decoded_id) = platformvm::txs::Tx::from_bytes(&b).unwrap();
+            prop_assert_eq!(decoded, platformvm::txs::Tx::AddValidator(tx));
+            prop_assert_eq!(decoded_id, tx_id);
+        }
+    }
+}
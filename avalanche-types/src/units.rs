@@ -1,3 +1,14 @@
+use std::{
+    fmt,
+    io::{self, Error, ErrorKind},
+    str::FromStr,
+};
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
 pub const NANO_AVAX: u64 = 1;
 pub const MICRO_AVAX: u64 = 1000 * NANO_AVAX;
 pub const MILLI_AVAX: u64 = 1000 * MICRO_AVAX;
@@ -11,3 +22,224 @@ pub const MEGA_AVAX: u64 = 1000 * KILO_AVAX;
 
 /// On the C-Chain, one AVAX is 10^18 units.
 pub const AVAX_C_CHAIN: u64 = 1000 * MEGA_AVAX;
+
+/// Number of decimal places of one AVAX in nAVAX.
+const AVAX_DECIMALS: usize = 9;
+
+/// Amount of AVAX in nAVAX, the smallest unit on the X and P-chain,
+/// so that the amounts in nAVAX and AVAX are never mixed up.
+/// Serialized as the nAVAX number, as in the genesis and the APIs,
+/// while also deserialized from the string (e.g., "1.5 AVAX").
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Avax(u64);
+
+impl Avax {
+    pub const ZERO: Self = Self(0);
+
+    pub const fn from_navax(navax: u64) -> Self {
+        Self(navax)
+    }
+
+    /// Returns "None" if the amount overflows u64 in nAVAX.
+    pub fn from_avax(avax: u64) -> Option<Self> {
+        avax.checked_mul(AVAX).map(Self)
+    }
+
+    pub const fn as_navax(&self) -> u64 {
+        self.0
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    pub fn checked_mul(self, n: u64) -> Option<Self> {
+        self.0.checked_mul(n).map(Self)
+    }
+}
+
+impl fmt::Display for Avax {
+    /// e.g., "1.5 AVAX", "0.000000001 AVAX".
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let whole = self.0 / AVAX;
+        let frac = self.0 % AVAX;
+        if frac == 0 {
+            return write!(f, "{} AVAX", whole);
+        }
+        let frac = format!("{:0width$}", frac, width = AVAX_DECIMALS);
+        write!(f, "{}.{} AVAX", whole, frac.trim_end_matches('0'))
+    }
+}
+
+impl FromStr for Avax {
+    type Err = Error;
+
+    /// Parses the amount in AVAX with the "AVAX" suffix (e.g., "1.5 AVAX"),
+    /// otherwise in nAVAX with or without the "nAVAX" suffix (e.g., "2500000000").
+    fn from_str(s: &str) -> io::Result<Self> {
+        let s = s.trim();
+        let lower = s.to_lowercase();
+        if let Some(n) = lower.strip_suffix("navax") {
+            return parse_navax(n.trim_end());
+        }
+        if let Some(n) = lower.strip_suffix("avax") {
+            return parse_avax(n.trim_end());
+        }
+        parse_navax(s)
+    }
+}
+
+fn parse_navax(s: &str) -> io::Result<Avax> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid nAVAX amount '{}'", s),
+        ));
+    }
+    s.parse::<u64>().map(Avax).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid nAVAX amount '{}' ({})", s, e),
+        )
+    })
+}
+
+fn parse_avax(s: &str) -> io::Result<Avax> {
+    let invalid = || {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid AVAX amount '{}'", s),
+        )
+    };
+
+    let (whole, frac) = match s.split_once('.') {
+        Some((w, f)) => (w, f),
+        None => (s, ""),
+    };
+    if (whole.is_empty() && frac.is_empty())
+        || frac.len() > AVAX_DECIMALS
+        || !whole
+            .bytes()
+            .chain(frac.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let whole = if whole.is_empty() {
+        0
+    } else {
+        whole.parse::<u64>().map_err(|_| invalid())?
+    };
+    let frac = if frac.is_empty() {
+        0
+    } else {
+        format!("{:0<width$}", frac, width = AVAX_DECIMALS)
+            .parse::<u64>()
+            .map_err(|_| invalid())?
+    };
+    whole
+        .checked_mul(AVAX)
+        .and_then(|n| n.checked_add(frac))
+        .map(Avax)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("AVAX amount '{}' overflows u64 nAVAX", s),
+            )
+        })
+}
+
+impl Serialize for Avax {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Avax {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct AvaxVisitor;
+
+        impl<'de> Visitor<'de> for AvaxVisitor {
+            type Value = Avax;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("nAVAX number or amount string (e.g., \"1.5 AVAX\")")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Avax, E> {
+                Ok(Avax(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Avax, E> {
+                u64::try_from(v)
+                    .map(Avax)
+                    .map_err(|_| E::custom(format!("negative amount {}", v)))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Avax, E> {
+                Avax::from_str(v).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(AvaxVisitor)
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- units::test_avax --exact --show-output
+#[test]
+fn test_avax() {
+    assert_eq!(
+        Avax::from_str("1.5 AVAX").unwrap().as_navax(),
+        1_500_000_000
+    );
+    assert_eq!(Avax::from_str("1.5avax").unwrap().as_navax(), 1_500_000_000);
+    assert_eq!(Avax::from_str(".5 AVAX").unwrap().as_navax(), 500_000_000);
+    assert_eq!(
+        Avax::from_str("2500000000").unwrap().as_navax(),
+        2_500_000_000
+    );
+    assert_eq!(Avax::from_str("25 nAVAX").unwrap().as_navax(), 25);
+    assert_eq!(
+        Avax::from_str("0.000000001 AVAX").unwrap(),
+        Avax::from_navax(1)
+    );
+    for s in [
+        "",
+        "AVAX",
+        "1.5",
+        "-1 AVAX",
+        "1.0000000001 AVAX",
+        "1.5 nAVAX",
+        "18446744074 AVAX",
+    ] {
+        assert!(Avax::from_str(s).is_err(), "'{}' should fail", s);
+    }
+
+    assert_eq!(Avax::from_navax(1_500_000_000).to_string(), "1.5 AVAX");
+    assert_eq!(Avax::from_avax(300).unwrap().to_string(), "300 AVAX");
+    assert_eq!(Avax::from_navax(1).to_string(), "0.000000001 AVAX");
+    assert_eq!(Avax::ZERO.to_string(), "0 AVAX");
+
+    let one = Avax::from_avax(1).unwrap();
+    assert_eq!(one.checked_add(one), Avax::from_avax(2));
+    assert_eq!(Avax::ZERO.checked_sub(one), None);
+    assert_eq!(one.checked_mul(3), Avax::from_avax(3));
+    assert_eq!(Avax::from_navax(u64::MAX).checked_add(one), None);
+    assert_eq!(Avax::from_avax(u64::MAX), None);
+
+    assert_eq!(serde_json::to_string(&one).unwrap(), "1000000000");
+    let parsed: Vec<Avax> = serde_json::from_str(r#"[1000000000, "1 AVAX"]"#).unwrap();
+    assert_eq!(parsed, vec![one, one]);
+    assert!(serde_json::from_str::<Avax>("-1").is_err());
+}
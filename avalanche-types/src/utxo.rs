@@ -0,0 +1,224 @@
+// UTXOs and the transferable outputs and inputs that create and spend them,
+// as returned by "avm.getUTXOs" and "platform.getUTXOs".
+// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/components/avax
+
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{avax, codec, formatting, ids, packer, platformvm, secp256k1fx};
+
+pub use crate::avax::{TransferableInput, TransferableOutput, UtxoId};
+pub use crate::secp256k1fx::{
+    TransferInput as SECP256K1TransferInput, TransferOutput as SECP256K1TransferOutput,
+};
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/components/avax#UTXO
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct Utxo {
+    pub utxo_id: avax::UtxoId,
+    pub asset_id: ids::Id,
+    /// Only one of "transfer_output" and "stakeable_lock_out" must be set.
+    pub transfer_output: Option<secp256k1fx::TransferOutput>,
+    /// Only valid on the P-chain.
+    pub stakeable_lock_out: Option<platformvm::StakeableLockOut>,
+}
+
+impl Default for Utxo {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Utxo {
+    pub fn default() -> Self {
+        Self {
+            utxo_id: avax::UtxoId::default(),
+            asset_id: ids::Id::empty(),
+            transfer_output: None,
+            stakeable_lock_out: None,
+        }
+    }
+
+    /// Returns the amount of the underlying transfer output.
+    pub fn amount(&self) -> u64 {
+        if let Some(out) = &self.transfer_output {
+            return out.amount;
+        }
+        if let Some(lock_out) = &self.stakeable_lock_out {
+            return lock_out.transfer_output.amount;
+        }
+        0
+    }
+
+    /// Packs the UTXO with the type ID of its "Out" interface.
+    pub fn pack(&self, packer: &packer::Packer) -> io::Result<()> {
+        packer.pack_bytes(&self.utxo_id.tx_id.d);
+        packer.pack_u32(self.utxo_id.output_index);
+        packer.pack_bytes(&self.asset_id.d);
+        avax::pack_out(packer, &self.transfer_output, &self.stakeable_lock_out)
+    }
+
+    /// Unpacks the UTXO written by "pack".
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let tx_id = packer.unpack_bytes(ids::ID_LEN)?;
+        let output_index = packer.unpack_u32()?;
+        let out = avax::TransferableOutput::unpack(packer)?;
+        Ok(Self {
+            utxo_id: avax::UtxoId::new(&tx_id, output_index, false),
+            asset_id: out.asset_id,
+            transfer_output: out.transfer_output,
+            stakeable_lock_out: out.stakeable_lock_out,
+        })
+    }
+
+    /// Returns the codec-encoded bytes of the UTXO, as returned by "getUTXOs".
+    /// The X-chain and P-chain UTXOs share the secp256k1fx type IDs,
+    /// so both are packed with the P-chain codec.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::P_CODEC.marshal(codec::VERSION, |packer| self.pack(packer))
+    }
+
+    /// Decodes the codec-encoded UTXO bytes, rejecting the unknown codec versions
+    /// and any trailing bytes.
+    /// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/codec#Manager.Unmarshal
+    pub fn from_bytes(b: &[u8]) -> io::Result<Self> {
+        codec::P_CODEC.unmarshal(b, |_, packer| Self::unpack(packer))
+    }
+
+    /// Decodes the "0x"-prefixed hex-encoded UTXO with its checksum,
+    /// as returned by "getUTXOs" with "hex" encoding.
+    pub fn from_hex(h: &str) -> io::Result<Self> {
+        let decoded = formatting::decode_hex_untrusted(h.as_bytes())?;
+        Self::from_bytes(&decoded)
+    }
+
+    /// Decodes the CB58-encoded UTXO with its checksum,
+    /// as returned by "getUTXOs" with "cb58" encoding.
+    pub fn from_cb58(s: &str) -> io::Result<Self> {
+        let decoded = formatting::decode_cb58_untrusted(s)?;
+        Self::from_bytes(&decoded)
+    }
+}
+
+/// Decodes the untrusted "0x"-prefixed hex or CB58 UTXO with the checksum,
+/// as returned by "getUTXOs" in either encoding.
+pub fn decode_utxo_untrusted(encoded: &str) -> io::Result<Utxo> {
+    Utxo::from_bytes(&formatting::decode_untrusted(encoded)?)
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- utxo::test_utxo_unpack --exact --show-output
+#[test]
+fn test_utxo_unpack() {
+    // ref. "platform.getUTXOs" response from avalanchego with "hex" encoding
+    let encoded = "0x000000000000000000000000000000000000000000000000000000000000000000000000000088eec2e099c6a528e689618e8721e04ae85ea574c7a15a7968644d14d54780140000000702c68af0bb1400000000000000000000000000010000000165844a05405f3662c1928142c6c2a783ef871de939b564db";
+    let utxo = Utxo::from_hex(encoded).unwrap();
+
+    let expected = Utxo {
+        utxo_id: avax::UtxoId::new(&[0; 32], 0, false),
+        asset_id: ids::Id::from_hex(
+            "88eec2e099c6a528e689618e8721e04ae85ea574c7a15a7968644d14d5478014",
+        )
+        .unwrap(),
+        transfer_output: Some(secp256k1fx::TransferOutput::new(
+            0x02c68af0bb140000,
+            secp256k1fx::OutputOwners::new(
+                0,
+                1,
+                &[ids::ShortId::from_slice(
+                    &hex::decode("65844a05405f3662c1928142c6c2a783ef871de9").unwrap(),
+                )],
+            ),
+        )),
+        ..Utxo::default()
+    };
+    assert_eq!(utxo, expected);
+
+    // re-encoding must produce the same bytes (without the 4-byte checksum)
+    let b = utxo.to_bytes().unwrap();
+    assert_eq!(hex::encode(&b), encoded[2..encoded.len() - 8]);
+    assert_eq!(
+        Utxo::from_cb58(&formatting::encode_cb58_with_checksum(&b)).unwrap(),
+        expected
+    );
+
+    // truncated, trailing bytes, and bad checksum
+    assert!(Utxo::from_bytes(&b[..b.len() - 1]).is_err());
+    let mut extra = b.clone();
+    extra.push(0);
+    assert!(Utxo::from_bytes(&extra).is_err());
+    assert!(Utxo::from_hex(&encoded.replace("39b564db", "39b564dc")).is_err());
+
+    assert_eq!(decode_utxo_untrusted(&encoded).unwrap(), expected);
+    let oversized = vec![0; codec::DEFAULT_MAX_SIZE + 1];
+    assert!(decode_utxo_untrusted(&formatting::encode_hex_with_checksum(&oversized)).is_err());
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- utxo::test_utxo_serialize --exact --show-output
+#[test]
+fn test_utxo_serialize() {
+    // the UTXO of "avalanchego/vms/components/avax.TestUTXOSerialize",
+    // whose bytes avalanchego writes in "compatibility/utxo-serialize"
+    let utxo = Utxo {
+        utxo_id: avax::UtxoId::new(
+            &[
+                0xf9, 0x66, 0x75, 0x0f, 0x43, 0x88, 0x67, 0xc3, //
+                0xc9, 0x82, 0x8d, 0xdc, 0xdb, 0xe6, 0x60, 0xe2, //
+                0x1c, 0xcd, 0xbb, 0x36, 0xa9, 0x27, 0x69, 0x58, //
+                0xf0, 0x11, 0xba, 0x47, 0x2f, 0x75, 0xd4, 0xe7, //
+            ],
+            13,
+            false,
+        ),
+        asset_id: ids::Id::from_slice(&(0x00..=0x1f).collect::<Vec<u8>>()),
+        transfer_output: Some(secp256k1fx::TransferOutput::new(
+            12345,
+            secp256k1fx::OutputOwners::new(
+                54321,
+                1,
+                &[
+                    ids::ShortId::from_slice(&[
+                        0x51, 0x02, 0x5c, 0x61, 0xfb, 0xcf, 0xc0, 0x78, //
+                        0xf6, 0x93, 0x34, 0xf8, 0x34, 0xbe, 0x6d, 0xd2, //
+                        0x6d, 0x55, 0xa9, 0x55, //
+                    ]),
+                    ids::ShortId::from_slice(&[
+                        0xc3, 0x34, 0x41, 0x28, 0xe0, 0x60, 0x12, 0x8e, //
+                        0xde, 0x35, 0x23, 0xa2, 0x4a, 0x46, 0x1c, 0x89, //
+                        0x43, 0xab, 0x08, 0x59, //
+                    ]),
+                ],
+            ),
+        )),
+        ..Utxo::default()
+    };
+
+    let mut expected: Vec<u8> = vec![
+        0x00, 0x00, // codec version
+        0xf9, 0x66, 0x75, 0x0f, 0x43, 0x88, 0x67, 0xc3, // tx ID
+        0xc9, 0x82, 0x8d, 0xdc, 0xdb, 0xe6, 0x60, 0xe2, //
+        0x1c, 0xcd, 0xbb, 0x36, 0xa9, 0x27, 0x69, 0x58, //
+        0xf0, 0x11, 0xba, 0x47, 0x2f, 0x75, 0xd4, 0xe7, //
+        0x00, 0x00, 0x00, 0x0d, // output index
+    ];
+    expected.extend(0x00..=0x1f); // asset ID
+    expected.extend_from_slice(&[
+        0x00, 0x00, 0x00, 0x07, // "secp256k1fx.TransferOutput" type ID
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x30, 0x39, // amount
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xd4, 0x31, // locktime
+        0x00, 0x00, 0x00, 0x01, // threshold
+        0x00, 0x00, 0x00, 0x02, // number of addresses
+        0x51, 0x02, 0x5c, 0x61, 0xfb, 0xcf, 0xc0, 0x78, //
+        0xf6, 0x93, 0x34, 0xf8, 0x34, 0xbe, 0x6d, 0xd2, //
+        0x6d, 0x55, 0xa9, 0x55, //
+        0xc3, 0x34, 0x41, 0x28, 0xe0, 0x60, 0x12, 0x8e, //
+        0xde, 0x35, 0x23, 0xa2, 0x4a, 0x46, 0x1c, 0x89, //
+        0x43, 0xab, 0x08, 0x59, //
+    ]);
+    assert_eq!(utxo.to_bytes().unwrap(), expected);
+    assert_eq!(Utxo::from_bytes(&expected).unwrap(), utxo);
+    assert_eq!(
+        decode_utxo_untrusted(&formatting::encode_hex_with_checksum(&expected)).unwrap(),
+        utxo
+    );
+}
@@ -0,0 +1,360 @@
+pub mod payload;
+
+use std::io::{self, Error, ErrorKind};
+
+use utils::hash;
+
+use crate::{bls_key, codec, ids, packer};
+
+/// Length of the BLS signature.
+/// ref. "avalanchego/utils/crypto/bls.SignatureLen"
+pub const BLS_SIGNATURE_LEN: usize = bls_key::SIGNATURE_LEN;
+
+/// Default quorum of the stake weight that signs the message.
+/// ref. "avalanchego/vms/platformvm/warp.WarpDefaultQuorumNumerator"
+pub const DEFAULT_QUORUM_NUMERATOR: u64 = 67;
+/// ref. "avalanchego/vms/platformvm/warp.WarpQuorumDenominator"
+pub const QUORUM_DENOMINATOR: u64 = 100;
+
+/// Message from the source chain, which the validators of its subnet sign.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/warp#UnsignedMessage
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct UnsignedMessage {
+    pub network_id: u32,
+    pub source_chain_id: ids::Id,
+    /// e.g., "payload::AddressedPayload" bytes.
+    pub payload: Vec<u8>,
+}
+
+impl UnsignedMessage {
+    pub fn new(network_id: u32, source_chain_id: ids::Id, payload: Vec<u8>) -> Self {
+        Self {
+            network_id,
+            source_chain_id,
+            payload,
+        }
+    }
+
+    pub fn pack(&self, packer: &packer::Packer) -> io::Result<()> {
+        packer.pack_u32(self.network_id);
+        packer.pack_bytes(self.source_chain_id.as_bytes());
+        packer.pack_bytes_with_header(&self.payload);
+        Ok(())
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let network_id = packer.unpack_u32()?;
+        let source_chain_id = ids::Id::from_slice(&packer.unpack_bytes(ids::ID_LEN)?);
+        let payload = packer.unpack_bytes_with_header()?;
+        Ok(Self {
+            network_id,
+            source_chain_id,
+            payload,
+        })
+    }
+
+    /// Returns the codec-encoded bytes, which is what the validators sign.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::WARP_CODEC.marshal(codec::VERSION, |packer| self.pack(packer))
+    }
+
+    pub fn from_bytes(b: &[u8]) -> io::Result<Self> {
+        codec::WARP_CODEC.unmarshal(b, |_, packer| Self::unpack(packer))
+    }
+
+    /// ref. "avalanchego/vms/platformvm/warp.UnsignedMessage.ID"
+    pub fn id(&self) -> io::Result<ids::Id> {
+        Ok(ids::Id::from_slice(&hash::compute_sha256(
+            &self.to_bytes()?,
+        )))
+    }
+}
+
+/// Aggregate signature of the validators in the bit set, indexed by the
+/// canonical (sorted by the BLS public key) validator set of the source subnet.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/warp#BitSetSignature
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct BitSetSignature {
+    /// Big-endian bytes of the big integer whose bit "i" is set
+    /// if the validator "i" signed, without the leading zeros.
+    pub signers: Vec<u8>,
+    pub signature: [u8; BLS_SIGNATURE_LEN],
+}
+
+impl BitSetSignature {
+    pub fn type_name() -> String {
+        "warp.BitSetSignature".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::WARP_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// Returns the signature with the signer bit set of the validator indices.
+    pub fn new(signer_indices: &[usize], signature: [u8; BLS_SIGNATURE_LEN]) -> Self {
+        let len = signer_indices.iter().max().map_or(0, |i| i / 8 + 1);
+        let mut signers = vec![0u8; len];
+        for i in signer_indices.iter() {
+            signers[len - 1 - i / 8] |= 1 << (i % 8);
+        }
+        Self { signers, signature }
+    }
+
+    /// Returns the validator indices of the signers in the ascending order.
+    /// ref. "avalanchego/utils/set.BitsFromBytes"
+    pub fn signer_indices(&self) -> io::Result<Vec<usize>> {
+        if self.signers.first() == Some(&0) {
+            // ref. "avalanchego/vms/platformvm/warp.ErrInvalidBitSet"
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "signers bit set has the leading zeros",
+            ));
+        }
+        let len = self.signers.len();
+        let mut indices = Vec::new();
+        for i in 0..len * 8 {
+            if self.signers[len - 1 - i / 8] & (1 << (i % 8)) != 0 {
+                indices.push(i);
+            }
+        }
+        Ok(indices)
+    }
+
+    pub fn pack(&self, packer: &packer::Packer) -> io::Result<()> {
+        packer.pack_u32(Self::type_id());
+        packer.pack_bytes_with_header(&self.signers);
+        packer.pack_bytes(&self.signature);
+        Ok(())
+    }
+
+    pub fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let type_id = packer.unpack_u32()?;
+        if type_id != Self::type_id() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unknown warp signature type ID {}", type_id),
+            ));
+        }
+        let signers = packer.unpack_bytes_with_header()?;
+        let mut signature = [0u8; BLS_SIGNATURE_LEN];
+        signature.copy_from_slice(&packer.unpack_bytes(BLS_SIGNATURE_LEN)?);
+        Ok(Self { signers, signature })
+    }
+}
+
+/// Aggregates the BLS signatures of the signers
+/// (e.g., "bls_key::Key::sign" of the unsigned message bytes).
+pub fn aggregate_signatures(
+    signatures: &[[u8; BLS_SIGNATURE_LEN]],
+) -> io::Result<[u8; BLS_SIGNATURE_LEN]> {
+    let sigs: Vec<&[u8]> = signatures.iter().map(|s| &s[..]).collect();
+    bls_key::aggregate_signatures(&sigs)
+}
+
+/// Unsigned message with the signature of the source subnet validators.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/warp#Message
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Message {
+    pub unsigned_message: UnsignedMessage,
+    pub signature: BitSetSignature,
+}
+
+impl Message {
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::WARP_CODEC.marshal(codec::VERSION, |packer| {
+            self.unsigned_message.pack(packer)?;
+            self.signature.pack(packer)
+        })
+    }
+
+    pub fn from_bytes(b: &[u8]) -> io::Result<Self> {
+        codec::WARP_CODEC.unmarshal(b, |_, packer| {
+            Ok(Self {
+                unsigned_message: UnsignedMessage::unpack(packer)?,
+                signature: BitSetSignature::unpack(packer)?,
+            })
+        })
+    }
+
+    /// Verifies the network, the source chain, and that the signers
+    /// hold the quorum of the validator weights (in the canonical order).
+    /// Returns the signed weight. The aggregate BLS signature is verified
+    /// separately by "verify_signature".
+    /// ref. "avalanchego/vms/platformvm/warp.BitSetSignature.Verify"
+    pub fn verify(
+        &self,
+        network_id: u32,
+        source_chain_id: &ids::Id,
+        validator_weights: &[u64],
+        quorum_numerator: u64,
+    ) -> io::Result<u64> {
+        if self.unsigned_message.network_id != network_id {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "message network ID {} != expected {}",
+                    self.unsigned_message.network_id, network_id
+                ),
+            ));
+        }
+        if self.unsigned_message.source_chain_id != *source_chain_id {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "message source chain {} != expected {}",
+                    self.unsigned_message.source_chain_id, source_chain_id
+                ),
+            ));
+        }
+        verify_weight(
+            &self.signature.signer_indices()?,
+            validator_weights,
+            quorum_numerator,
+            QUORUM_DENOMINATOR,
+        )
+    }
+
+    /// Verifies the aggregate signature against the aggregate public key of the signers,
+    /// given the BLS public keys of the validator set in the same order as the weights.
+    /// ref. "avalanchego/vms/platformvm/warp.BitSetSignature.Verify"
+    pub fn verify_signature(&self, validator_public_keys: &[Vec<u8>]) -> io::Result<()> {
+        let mut signer_keys: Vec<&[u8]> = Vec::new();
+        for i in self.signature.signer_indices()? {
+            let pk = validator_public_keys.get(i).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "signer index {} out of {} validators",
+                        i,
+                        validator_public_keys.len()
+                    ),
+                )
+            })?;
+            signer_keys.push(pk);
+        }
+        let agg_pk = bls_key::aggregate_public_keys(&signer_keys)?;
+        bls_key::verify(
+            &agg_pk,
+            &self.unsigned_message.to_bytes()?,
+            &self.signature.signature,
+        )
+    }
+}
+
+/// Returns the weight of the signers if it reaches the quorum
+/// "quorum_numerator/quorum_denominator" of the total weight.
+/// ref. "avalanchego/vms/platformvm/warp.VerifyWeight"
+pub fn verify_weight(
+    signer_indices: &[usize],
+    validator_weights: &[u64],
+    quorum_numerator: u64,
+    quorum_denominator: u64,
+) -> io::Result<u64> {
+    let mut total: u128 = 0;
+    for w in validator_weights.iter() {
+        total += *w as u128;
+    }
+    let mut signed: u128 = 0;
+    for i in signer_indices.iter() {
+        let w = validator_weights.get(*i).ok_or_else(|| {
+            // ref. "avalanchego/vms/platformvm/warp.ErrUnknownValidator"
+            Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "signer index {} out of {} validators",
+                    i,
+                    validator_weights.len()
+                ),
+            )
+        })?;
+        signed += *w as u128;
+    }
+    if signed * (quorum_denominator as u128) < total * (quorum_numerator as u128) {
+        // ref. "avalanchego/vms/platformvm/warp.ErrInsufficientWeight"
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "signed weight {} < quorum {}/{} of total {}",
+                signed, quorum_numerator, quorum_denominator, total
+            ),
+        ));
+    }
+    Ok(signed as u64)
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- warp::test_message --exact --show-output
+#[test]
+fn test_message() {
+    let source_chain_id = ids::Id::from_slice(&[1u8; 32]);
+    let unsigned = UnsignedMessage::new(12345, source_chain_id, vec![0xab, 0xcd]);
+    let b = unsigned.to_bytes().unwrap();
+    let mut expected = vec![0x00, 0x00, 0x00, 0x00, 0x30, 0x39];
+    expected.extend_from_slice(&[1u8; 32]);
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x02, 0xab, 0xcd]);
+    assert_eq!(b, expected);
+    assert_eq!(UnsignedMessage::from_bytes(&b).unwrap(), unsigned);
+    assert_eq!(
+        unsigned.id().unwrap(),
+        ids::Id::from_slice(&hash::compute_sha256(&expected))
+    );
+
+    let signature = BitSetSignature::new(&[0, 2, 9], [7u8; BLS_SIGNATURE_LEN]);
+    assert_eq!(signature.signers, vec![0b0000_0010, 0b0000_0101]);
+    assert_eq!(signature.signer_indices().unwrap(), vec![0, 2, 9]);
+    let invalid = BitSetSignature {
+        signers: vec![0, 1],
+        signature: [0u8; BLS_SIGNATURE_LEN],
+    };
+    assert!(invalid.signer_indices().is_err());
+
+    let msg = Message {
+        unsigned_message: unsigned,
+        signature,
+    };
+    let b = msg.to_bytes().unwrap();
+    assert_eq!(b.len(), expected.len() + 4 + 4 + 2 + BLS_SIGNATURE_LEN);
+    assert_eq!(Message::from_bytes(&b).unwrap(), msg);
+
+    // 3 of 10 validators with the equal weights
+    let weights = vec![10u64; 10];
+    assert!(msg
+        .verify(12345, &source_chain_id, &weights, DEFAULT_QUORUM_NUMERATOR)
+        .is_err());
+    let weights = vec![30, 1, 30, 1, 1, 1, 1, 1, 1, 30];
+    assert_eq!(
+        msg.verify(12345, &source_chain_id, &weights, DEFAULT_QUORUM_NUMERATOR)
+            .unwrap(),
+        90
+    );
+    assert!(msg
+        .verify(1, &source_chain_id, &weights, DEFAULT_QUORUM_NUMERATOR)
+        .is_err());
+    assert!(msg
+        .verify(
+            12345,
+            &source_chain_id,
+            &weights[..5],
+            DEFAULT_QUORUM_NUMERATOR
+        )
+        .is_err());
+
+    // validators 0 and 2 of 3 sign the unsigned message
+    let keys: Vec<bls_key::Key> = (0..3).map(|_| bls_key::Key::generate().unwrap()).collect();
+    let public_keys: Vec<Vec<u8>> = keys.iter().map(|k| k.public_key_bytes().to_vec()).collect();
+    let unsigned_bytes = msg.unsigned_message.to_bytes().unwrap();
+    let signature =
+        aggregate_signatures(&[keys[0].sign(&unsigned_bytes), keys[2].sign(&unsigned_bytes)])
+            .unwrap();
+    let signed = Message {
+        unsigned_message: msg.unsigned_message.clone(),
+        signature: BitSetSignature::new(&[0, 2], signature),
+    };
+    signed.verify_signature(&public_keys).unwrap();
+    assert!(signed.verify_signature(&public_keys[..2]).is_err());
+    let wrong_signers = Message {
+        signature: BitSetSignature::new(&[0, 1], signature),
+        ..signed
+    };
+    assert!(wrong_signers.verify_signature(&public_keys).is_err());
+    assert!(aggregate_signatures(&[]).is_err());
+}
@@ -0,0 +1,119 @@
+use std::io::{self, Error, ErrorKind};
+
+use crate::{codec, ids, packer};
+
+/// Payload of the message that carries only the hash of the data
+/// (e.g., the accepted block ID).
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/warp/payload#Hash
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Hash {
+    pub hash: ids::Id,
+}
+
+impl Hash {
+    pub fn type_name() -> String {
+        "payload.Hash".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::WARP_PAYLOAD_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::WARP_PAYLOAD_CODEC.marshal(codec::VERSION, |packer| {
+            packer.pack_u32(Self::type_id());
+            packer.pack_bytes(self.hash.as_bytes());
+            Ok(())
+        })
+    }
+}
+
+/// Payload of the message sent by the address on the source chain
+/// (e.g., the teleporter contract), which the destination VM dispatches.
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/vms/platformvm/warp/payload#AddressedCall
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct AddressedPayload {
+    /// e.g., the 20-byte EVM address.
+    pub source_address: Vec<u8>,
+    pub payload: Vec<u8>,
+}
+
+impl AddressedPayload {
+    pub fn new(source_address: &[u8], payload: &[u8]) -> Self {
+        Self {
+            source_address: source_address.to_vec(),
+            payload: payload.to_vec(),
+        }
+    }
+
+    pub fn type_name() -> String {
+        "payload.AddressedCall".to_string()
+    }
+
+    pub fn type_id() -> u32 {
+        *(codec::WARP_PAYLOAD_TYPES.get(&Self::type_name()).unwrap()) as u32
+    }
+
+    /// Returns the codec-encoded bytes for "UnsignedMessage.payload".
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        codec::WARP_PAYLOAD_CODEC.marshal(codec::VERSION, |packer| self.pack(packer))
+    }
+
+    pub fn from_bytes(b: &[u8]) -> io::Result<Self> {
+        codec::WARP_PAYLOAD_CODEC.unmarshal(b, |_, packer| Self::unpack(packer))
+    }
+
+    fn pack(&self, packer: &packer::Packer) -> io::Result<()> {
+        packer.pack_u32(Self::type_id());
+        packer.pack_bytes_with_header(&self.source_address);
+        packer.pack_bytes_with_header(&self.payload);
+        Ok(())
+    }
+
+    fn unpack(packer: &packer::Packer) -> io::Result<Self> {
+        let type_id = packer.unpack_u32()?;
+        if type_id != Self::type_id() {
+            let name = codec::WARP_PAYLOAD_CODEC
+                .type_name(codec::VERSION, type_id)
+                .unwrap_or("unknown");
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "payload type ID {} ({}) is not an addressed call",
+                    type_id, name
+                ),
+            ));
+        }
+        Ok(Self {
+            source_address: packer.unpack_bytes_with_header()?,
+            payload: packer.unpack_bytes_with_header()?,
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanche-types --lib -- warp::payload::test_payload --exact --show-output
+#[test]
+fn test_payload() {
+    let addressed = AddressedPayload::new(&[0x11; 20], b"hello");
+    let b = addressed.to_bytes().unwrap();
+    let mut expected = vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x14];
+    expected.extend_from_slice(&[0x11; 20]);
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x05]);
+    expected.extend_from_slice(b"hello");
+    assert_eq!(b, expected);
+    assert_eq!(AddressedPayload::from_bytes(&b).unwrap(), addressed);
+
+    // round trip through the unsigned message
+    let unsigned = super::UnsignedMessage::new(1, ids::Id::empty(), b.clone());
+    let decoded = super::UnsignedMessage::from_bytes(&unsigned.to_bytes().unwrap()).unwrap();
+    assert_eq!(
+        AddressedPayload::from_bytes(&decoded.payload).unwrap(),
+        addressed
+    );
+
+    let hash = Hash {
+        hash: ids::Id::from_slice(&[2u8; 32]),
+    };
+    let err = AddressedPayload::from_bytes(&hash.to_bytes().unwrap()).unwrap_err();
+    assert!(err.to_string().contains("payload.Hash"));
+}
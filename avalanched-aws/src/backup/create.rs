@@ -0,0 +1,283 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Error, ErrorKind},
+    path::Path,
+    sync::Arc,
+    time::SystemTime,
+};
+
+use clap::{Arg, Command};
+use log::info;
+use tokio::runtime::Runtime;
+
+use avalanche_api::{eth as api_eth, info as api_info, p as api_p};
+use avalanche_ops_aws::BackupManifest;
+use aws::{self, s3};
+use utils::{bash, compress, random};
+
+use crate::run::supervisor;
+
+pub const NAME: &str = "create";
+
+pub fn subcommand() -> Command<'static> {
+    Command::new(NAME)
+        .about("Backs up the node database to S3 with the manifest to restore from")
+        .arg(
+            Arg::new("REGION")
+                .long("region")
+                .short('r')
+                .help("Sets the AWS region for API calls/endpoints")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("us-west-2"),
+        )
+        .arg(
+            Arg::new("LOG_LEVEL")
+                .long("log-level")
+                .short('l')
+                .help("Sets the log level")
+                .required(false)
+                .takes_value(true)
+                .possible_value("debug")
+                .possible_value("info")
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("ARCHIVE_COMPRESSION_METHOD")
+                .long("archive-compression-method")
+                .short('c')
+                .help("Sets the archive and compression method (tar-zstd streams without the intermediate archive)")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .possible_value(compress::DirEncoder::TarZstd(1).id())
+                .possible_value(compress::DirEncoder::TarZstd(2).id())
+                .possible_value(compress::DirEncoder::TarZstd(3).id())
+                .default_value(compress::DirEncoder::TarZstd(3).id()),
+        )
+        .arg(
+            Arg::new("DB_DIR")
+                .long("db-dir")
+                .short('d')
+                .help("Sets the database directory of the node to back up")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("WORK_DIR")
+                .long("work-dir")
+                .short('w')
+                .help("Sets the directory for the archive and the hot copy, which must not be inside the database directory (default to the parent of the database directory)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("HTTP_PORT")
+                .long("http-port")
+                .help("Sets the HTTP port of the node to fetch the manifest from")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("9650"),
+        )
+        .arg(
+            Arg::new("HOT_COPY")
+                .long("hot-copy")
+                .help("Stops the node only while copying the database to the work directory, and archives the copy after the restart (shorter downtime for the disk space of the copy)")
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("S3_BUCKET")
+                .long("s3-bucket")
+                .short('b')
+                .help("Sets the S3 bucket name to upload to")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("S3_KEY")
+                .long("s3-key")
+                .short('k')
+                .help("Sets the S3 key name of the archive (the manifest is uploaded with the '.manifest.yaml' suffix)")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute(
+    reg: &str,
+    log_level: &str,
+    archive_compression_method: &str,
+    db_dir: &str,
+    work_dir: Option<&str>,
+    http_port: &str,
+    hot_copy: bool,
+    s3_bucket: &str,
+    s3_key: &str,
+) -> io::Result<()> {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    );
+
+    let rt = Runtime::new().unwrap();
+
+    info!("STEP: loading AWS config");
+    let shared_config = rt
+        .block_on(aws::load_config(Some(reg.to_string())))
+        .unwrap();
+    let s3_manager = s3::Manager::new(&shared_config);
+
+    let enc = compress::DirEncoder::new(archive_compression_method)?;
+
+    // fetch before stopping the node, so the heights are lower bounds
+    // of what the archive contains
+    let http_ep = format!("http://127.0.0.1:{}", http_port);
+    info!("STEP: fetching backup manifest from {}", http_ep);
+    let mut manifest = rt.block_on(fetch_manifest(&http_ep))?;
+    manifest.archive_s3_key = s3_key.to_string();
+    manifest.compression = enc.id().to_string();
+    info!("fetched backup manifest {:?}", manifest);
+
+    let work_dir = match work_dir {
+        Some(v) => Path::new(v).to_path_buf(),
+        None => Path::new(db_dir)
+            .parent()
+            .expect("unexpected None parent dir")
+            .to_path_buf(),
+    };
+    let archive_path = work_dir.join(format!("backup-{}{}", random::string(10), enc.ext()));
+    let archive_path = archive_path.as_path().as_os_str().to_str().unwrap();
+
+    if hot_copy {
+        // the copy of the live LevelDB files is not a consistent snapshot,
+        // so the node is stopped for the local copy (much shorter than the pack)
+        let copy_dir = work_dir.join(format!("hot-copy-{}", random::string(10)));
+        let copy_dir = copy_dir.as_path().as_os_str().to_str().unwrap();
+        info!("STEP: stopping avalanchego to copy the consistent database");
+        supervisor::stop()?;
+
+        info!("STEP: copying {} to {}", db_dir, copy_dir);
+        let copied = bash::run(format!("cp -a {} {}", db_dir, copy_dir).as_str());
+
+        info!("STEP: starting avalanchego after the copy");
+        supervisor::start()?;
+        if let Err(e) = copied {
+            fs::remove_dir_all(copy_dir).ok();
+            return Err(e);
+        }
+
+        info!("STEP: backup {} with {}", copy_dir, enc.to_string());
+        let packed = compress::pack_directory(copy_dir, archive_path, enc);
+        fs::remove_dir_all(copy_dir)?;
+        packed?;
+    } else {
+        info!("STEP: stopping avalanchego for the consistent database");
+        supervisor::stop()?;
+
+        info!("STEP: backup {} with {}", db_dir, enc.to_string());
+        let packed = compress::pack_directory(db_dir, archive_path, enc);
+
+        // restart before the upload, which takes much longer than the pack
+        info!("STEP: starting avalanchego after the backup");
+        supervisor::start()?;
+        packed?;
+    }
+    manifest.size_bytes = fs::metadata(archive_path)?.len();
+    manifest.sha256 = avalanche_ops_aws::sha256_file_hex(archive_path)?;
+
+    info!("STEP: upload backup {} to S3", archive_path);
+    rt.block_on(s3_manager.put_artifact(
+        archive_path,
+        s3_bucket,
+        s3_key,
+        &super::transfer_options("uploaded"),
+    ))?;
+    fs::remove_file(archive_path)?;
+
+    // upload the manifest last, so the restore never finds the manifest
+    // of the partially uploaded archive
+    let manifest_path = random::tmp_path(10, Some(".yaml"))?;
+    fs::write(&manifest_path, manifest.encode_yaml()?)?;
+    let manifest_s3_key = BackupManifest::s3_key(s3_key);
+    info!("STEP: upload backup manifest to S3 {}", manifest_s3_key);
+    rt.block_on(s3_manager.put_object(
+        Arc::new(manifest_path.clone()),
+        Arc::new(s3_bucket.to_string()),
+        Arc::new(manifest_s3_key),
+    ))?;
+    fs::remove_file(manifest_path)?;
+
+    info!("'avalanched backup create' all success!");
+    Ok(())
+}
+
+/// Returns the manifest without the archive fields.
+async fn fetch_manifest(http_ep: &str) -> io::Result<BackupManifest> {
+    let missing = |field: &str| {
+        Error::new(
+            ErrorKind::Other,
+            format!("no {} in the response from {}", field, http_ep),
+        )
+    };
+
+    let network_id = api_info::get_network_id(http_ep)
+        .await?
+        .result
+        .map(|r| r.network_id)
+        .ok_or_else(|| missing("network ID"))?;
+    let node_id = api_info::get_node_id(http_ep)
+        .await?
+        .result
+        .map(|r| r.node_id.to_string())
+        .ok_or_else(|| missing("node ID"))?;
+    let avalanchego_version = api_info::get_node_version(http_ep)
+        .await?
+        .result
+        .and_then(|r| r.version)
+        .ok_or_else(|| missing("node version"))?;
+
+    let p_height = api_p::get_height(http_ep)
+        .await?
+        .result
+        .and_then(|r| r.height)
+        .ok_or_else(|| missing("P-chain height"))?;
+    let c_height = api_eth::block_number(http_ep, api_eth::C_CHAIN_RPC_PATH)
+        .await?
+        .result
+        .to_string();
+    let c_height = c_height.parse::<u64>().map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid C-chain height '{}' ({})", c_height, e),
+        )
+    })?;
+    let mut chain_heights = BTreeMap::new();
+    chain_heights.insert(String::from("P"), p_height);
+    chain_heights.insert(String::from("C"), c_height);
+
+    let created_at = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("unexpected None duration_since")
+        .as_secs();
+    Ok(BackupManifest {
+        network_id,
+        node_id,
+        avalanchego_version,
+        chain_heights,
+        archive_s3_key: String::new(),
+        compression: String::new(),
+        size_bytes: 0,
+        sha256: String::new(),
+        created_at,
+    })
+}
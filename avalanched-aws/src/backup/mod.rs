@@ -1,12 +1,42 @@
+pub mod create;
 pub mod download;
+pub mod restore;
 pub mod upload;
 
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
 use clap::Command;
+use log::info;
+
+use aws::s3;
 
 pub const NAME: &str = "backup";
 
 pub fn command() -> Command<'static> {
     Command::new(NAME)
         .about("Events to trigger to the network")
-        .subcommands(vec![download::subcommand(), upload::subcommand()])
+        .subcommands(vec![
+            create::subcommand(),
+            download::subcommand(),
+            restore::subcommand(),
+            upload::subcommand(),
+        ])
+}
+
+/// Returns the transfer options that log every 5%
+/// rather than every part of the tens of GB backup.
+pub fn transfer_options(action: &'static str) -> s3::artifact::TransferOptions {
+    let logged_percent = Arc::new(AtomicU64::new(0));
+    s3::artifact::TransferOptions {
+        progress: Some(Arc::new(move |p: s3::artifact::Progress| {
+            let percent = (p.percent() as u64) / 5 * 5;
+            if logged_percent.fetch_max(percent, Ordering::Relaxed) < percent {
+                info!("{} {}% of database backup", action, percent);
+            }
+        })),
+        ..Default::default()
+    }
 }
@@ -0,0 +1,225 @@
+use std::{
+    fs,
+    io::{self, Error, ErrorKind},
+    path::Path,
+    sync::Arc,
+};
+
+use clap::{Arg, Command};
+use log::{info, warn};
+use tokio::runtime::Runtime;
+
+use avalanche_ops_aws::BackupManifest;
+use aws::{self, s3};
+use utils::{compress, random};
+
+use crate::run::supervisor;
+
+pub const NAME: &str = "restore";
+
+pub fn subcommand() -> Command<'static> {
+    Command::new(NAME)
+        .about("Restores the node database from the backup created by 'backup create'")
+        .arg(
+            Arg::new("REGION")
+                .long("region")
+                .short('r')
+                .help("Sets the AWS region for API calls/endpoints")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("us-west-2"),
+        )
+        .arg(
+            Arg::new("LOG_LEVEL")
+                .long("log-level")
+                .short('l')
+                .help("Sets the log level")
+                .required(false)
+                .takes_value(true)
+                .possible_value("debug")
+                .possible_value("info")
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("S3_BUCKET")
+                .long("s3-bucket")
+                .short('b')
+                .help("Sets the S3 bucket name to download from")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("S3_KEY")
+                .long("s3-key")
+                .short('k')
+                .help("Sets the S3 key name of the archive (the manifest is downloaded with the '.manifest.yaml' suffix)")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("DB_DIR")
+                .long("db-dir")
+                .short('d')
+                .help("Sets the database directory of the node to restore into")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("WORK_DIR")
+                .long("work-dir")
+                .short('w')
+                .help("Sets the directory to download the archive to, which must not be inside the database directory (default to the parent of the database directory)")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("NETWORK_ID")
+                .long("network-id")
+                .short('n')
+                .help("Sets the network ID that the backup must match")
+                .required(false)
+                .takes_value(true)
+                .allow_invalid_utf8(false),
+        )
+        .arg(
+            Arg::new("SKIP_START")
+                .long("skip-start")
+                .help("Does not start avalanchego after the restore (e.g., to restore before the first start)")
+                .required(false)
+                .takes_value(false)
+                .allow_invalid_utf8(false),
+        )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute(
+    reg: &str,
+    log_level: &str,
+    s3_bucket: &str,
+    s3_key: &str,
+    db_dir: &str,
+    work_dir: Option<&str>,
+    network_id: Option<&str>,
+    skip_start: bool,
+) -> io::Result<()> {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    );
+
+    let rt = Runtime::new().unwrap();
+
+    info!("STEP: loading AWS config");
+    let shared_config = rt
+        .block_on(aws::load_config(Some(reg.to_string())))
+        .unwrap();
+    let s3_manager = s3::Manager::new(&shared_config);
+
+    let manifest_s3_key = BackupManifest::s3_key(s3_key);
+    info!(
+        "STEP: downloading backup manifest from S3 {}",
+        manifest_s3_key
+    );
+    let manifest_path = random::tmp_path(10, Some(".yaml"))?;
+    rt.block_on(s3_manager.get_object(
+        Arc::new(s3_bucket.to_string()),
+        Arc::new(manifest_s3_key),
+        Arc::new(manifest_path.clone()),
+    ))?;
+    let manifest = BackupManifest::decode_yaml(&fs::read(&manifest_path)?)?;
+    fs::remove_file(manifest_path)?;
+    info!("downloaded backup manifest {:?}", manifest);
+
+    if let Some(v) = network_id {
+        let network_id = v.parse::<u32>().map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid --network-id '{}' ({})", v, e),
+            )
+        })?;
+        manifest.verify_network_id(network_id)?;
+    }
+    let dec = compress::DirDecoder::new_from_file_name(s3_key)?;
+
+    // fresh instances may not have the service installed yet
+    info!("STEP: stopping avalanchego before the restore");
+    if let Err(e) = supervisor::stop() {
+        warn!("failed to stop avalanchego ({}), restoring anyway", e);
+    }
+
+    // do not store in "tmp", will run out of space
+    // nor in the database directory, which is cleared before the unpack
+    // fixed file name so that the download resumes after the interrupted restore
+    let work_dir = match work_dir {
+        Some(v) => Path::new(v).to_path_buf(),
+        None => Path::new(db_dir)
+            .parent()
+            .expect("unexpected None parent dir")
+            .to_path_buf(),
+    };
+    if work_dir.starts_with(db_dir) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "work directory {} must not be inside the database directory {}",
+                work_dir.display(),
+                db_dir
+            ),
+        ));
+    }
+    fs::create_dir_all(&work_dir)?;
+    let download_path = work_dir.join(format!("db-restore{}", dec.ext()));
+    let download_path = download_path.as_path().as_os_str().to_str().unwrap();
+    info!(
+        "STEP: downloading database backup 's3://{}/{}' to {}",
+        s3_bucket, s3_key, download_path
+    );
+    rt.block_on(s3_manager.get_artifact(
+        s3_bucket,
+        s3_key,
+        download_path,
+        &super::transfer_options("downloaded"),
+    ))?;
+    manifest.verify_archive(download_path)?;
+
+    // the leftover files (e.g., newer LevelDB tables) would corrupt the restored database,
+    // and only the contents are removed since the directory may be the volume mount point
+    if Path::new(db_dir).exists() {
+        info!("STEP: removing the existing database in {}", db_dir);
+        for entry in fs::read_dir(db_dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                fs::remove_dir_all(&path)?;
+            } else {
+                fs::remove_file(&path)?;
+            }
+        }
+    }
+
+    info!(
+        "STEP: unpack backup {} to {} with {}",
+        download_path,
+        db_dir,
+        dec.to_string()
+    );
+    compress::unpack_directory(download_path, db_dir, dec)?;
+    fs::remove_file(download_path)?;
+    info!(
+        "restored database of network ID {} at heights {:?}",
+        manifest.network_id, manifest.chain_heights
+    );
+
+    if !skip_start {
+        info!("STEP: starting avalanchego after the restore");
+        supervisor::start()?;
+    }
+
+    info!("'avalanched backup restore' all success!");
+    Ok(())
+}
@@ -2,6 +2,7 @@ use clap::Command;
 
 mod backup;
 mod run;
+mod volume;
 
 const NAME: &str = "avalanched-aws";
 
@@ -10,7 +11,7 @@ async fn main() {
     let matches = Command::new(NAME)
         .about("avalanched on AWS")
         .long_about("Avalanche agent (daemon) on AWS")
-        .subcommands(vec![run::command(), backup::command()])
+        .subcommands(vec![run::command(), backup::command(), volume::command()])
         .get_matches();
 
     match matches.subcommand() {
@@ -20,6 +21,24 @@ async fn main() {
         }
 
         Some((backup::NAME, sub_matches)) => match sub_matches.subcommand() {
+            Some((backup::create::NAME, sub_sub_matches)) => {
+                let log_lvl = sub_sub_matches.value_of("LOG_LEVEL").unwrap_or("info");
+                backup::create::execute(
+                    sub_sub_matches.value_of("REGION").unwrap_or("us-west-2"),
+                    log_lvl,
+                    sub_sub_matches
+                        .value_of("ARCHIVE_COMPRESSION_METHOD")
+                        .unwrap(),
+                    sub_sub_matches.value_of("DB_DIR").unwrap(),
+                    sub_sub_matches.value_of("WORK_DIR"),
+                    sub_sub_matches.value_of("HTTP_PORT").unwrap_or("9650"),
+                    sub_sub_matches.is_present("HOT_COPY"),
+                    sub_sub_matches.value_of("S3_BUCKET").unwrap(),
+                    sub_sub_matches.value_of("S3_KEY").unwrap(),
+                )
+                .unwrap();
+            }
+
             Some((backup::download::NAME, sub_sub_matches)) => {
                 let log_lvl = sub_sub_matches.value_of("LOG_LEVEL").unwrap_or("info");
                 backup::download::execute(
@@ -35,6 +54,21 @@ async fn main() {
                 .unwrap();
             }
 
+            Some((backup::restore::NAME, sub_sub_matches)) => {
+                let log_lvl = sub_sub_matches.value_of("LOG_LEVEL").unwrap_or("info");
+                backup::restore::execute(
+                    sub_sub_matches.value_of("REGION").unwrap_or("us-west-2"),
+                    log_lvl,
+                    sub_sub_matches.value_of("S3_BUCKET").unwrap(),
+                    sub_sub_matches.value_of("S3_KEY").unwrap(),
+                    sub_sub_matches.value_of("DB_DIR").unwrap(),
+                    sub_sub_matches.value_of("WORK_DIR"),
+                    sub_sub_matches.value_of("NETWORK_ID"),
+                    sub_sub_matches.is_present("SKIP_START"),
+                )
+                .unwrap();
+            }
+
             Some((backup::upload::NAME, sub_sub_matches)) => {
                 let log_lvl = sub_sub_matches.value_of("LOG_LEVEL").unwrap_or("info");
                 backup::upload::execute(
@@ -53,6 +87,19 @@ async fn main() {
             _ => unreachable!("unknown sub-subcommand"),
         },
 
+        Some((volume::NAME, sub_matches)) => match sub_matches.subcommand() {
+            Some((volume::provision::NAME, sub_sub_matches)) => {
+                let log_lvl = sub_sub_matches.value_of("LOG_LEVEL").unwrap_or("info");
+                volume::provision::execute(
+                    sub_sub_matches.value_of("REGION").unwrap_or("us-west-2"),
+                    log_lvl,
+                )
+                .unwrap();
+            }
+
+            _ => unreachable!("unknown sub-subcommand"),
+        },
+
         _ => unreachable!("unknown subcommand"),
     }
 }
@@ -0,0 +1,233 @@
+use std::{sync::Arc, time::Duration};
+
+use log::{info, warn};
+use tokio::time::sleep;
+
+use avalanche_api::{eth as api_eth, info as api_info, metrics as api_metrics, p as api_p};
+use aws::{cloudwatch, ec2, s3};
+
+use super::status;
+
+/// Chains whose progress is tracked, only the snowman chains ("P" and "C")
+/// are compared against the peer heights. The DAG "X"-chain has no height,
+/// so it is either 0 or 100.
+const CHAINS: [&str; 3] = ["X", "P", "C"];
+
+/// Limits the number of ready peers queried for the heights per poll.
+const MAX_REFERENCE_ENDPOINTS: usize = 3;
+
+pub struct BootstrapProgressPoller {
+    pub ec2_manager: ec2::Manager,
+    pub cw_manager: cloudwatch::Manager,
+    pub cw_namespace: String,
+    pub s3_manager: s3::Manager,
+    pub s3_bucket: String,
+    pub id: String,
+    pub local_node: avalanche_ops_aws::Node,
+    pub network_id: u32,
+    pub reporter: Arc<status::Reporter>,
+    pub interval_seconds: u64,
+}
+
+/// Polls the bootstrap progress of the local node, and publishes it to
+/// the "BOOTSTRAP_PROGRESS" instance tag and the CloudWatch metrics,
+/// so that the operators can watch the fleet converge without the logs.
+/// Keeps polling after bootstrapped, since the node may re-bootstrap
+/// after restarts.
+pub async fn bootstrap_progress_loop(poller: Arc<BootstrapProgressPoller>) {
+    info!(
+        "STEP: starting 'bootstrap_progress_loop' with {}-second interval",
+        poller.interval_seconds
+    );
+
+    let network_id = poller.network_id.to_string();
+    let mut tagged: Option<u32> = None;
+    loop {
+        sleep(Duration::from_secs(poller.interval_seconds)).await;
+
+        let chains = match poller.collect().await {
+            Some(v) => v,
+            None => {
+                warn!("local node metrics not available yet, retrying...");
+                continue;
+            }
+        };
+        let percent = avalanche_ops_aws::bootstrap_progress_percent(&chains);
+        info!("bootstrap progress {:.1}% ({:?})", percent, chains);
+        poller.reporter.set_bootstrap_progress(percent);
+
+        let dimensions = [
+            ("network-id", network_id.as_str()),
+            ("node-id", poller.local_node.node_id.as_str()),
+        ];
+        let mut data = cloudwatch::with_dimensions(
+            cloudwatch::Telemetry {
+                bootstrap_progress_percent: Some(percent),
+                ..Default::default()
+            }
+            .to_metric_data(),
+            &dimensions,
+        );
+        for c in chains.iter() {
+            let chain_percent = match c.percent() {
+                Some(v) => v,
+                None => continue,
+            };
+            data.extend(cloudwatch::with_dimensions(
+                cloudwatch::Telemetry {
+                    bootstrap_progress_percent: Some(chain_percent),
+                    ..Default::default()
+                }
+                .to_metric_data(),
+                &[dimensions[0], dimensions[1], ("chain", c.chain.as_str())],
+            ));
+        }
+        if let Err(e) = cloudwatch::spawn_put_metric_data(
+            poller.cw_manager.clone(),
+            poller.cw_namespace.as_str(),
+            data,
+        )
+        .await
+        {
+            warn!("failed to put bootstrap progress {}, retrying...", e);
+        }
+
+        // only re-tag on changes, to not throttle the EC2 API in a large fleet
+        let rounded = percent.floor() as u32;
+        if tagged == Some(rounded) {
+            continue;
+        }
+        match poller
+            .ec2_manager
+            .create_tags(
+                &poller.local_node.machine_id,
+                &[(
+                    avalanche_ops_aws::BOOTSTRAP_PROGRESS_TAG,
+                    rounded.to_string().as_str(),
+                )],
+            )
+            .await
+        {
+            Ok(_) => tagged = Some(rounded),
+            Err(e) => warn!("failed to tag bootstrap progress {}", e.message()),
+        }
+    }
+}
+
+impl BootstrapProgressPoller {
+    /// Returns the progress of each chain, or "None" if the local node
+    /// is not serving the metrics yet (e.g., downloading the database).
+    async fn collect(&self) -> Option<Vec<avalanche_ops_aws::ChainBootstrapProgress>> {
+        let http_ep = self.local_node.http_endpoint.as_str();
+        let scrape = match api_metrics::scrape(http_ep).await {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("failed to scrape metrics ({})", e);
+                return None;
+            }
+        };
+
+        let mut chains = Vec::new();
+        let mut reference_endpoints: Option<Vec<String>> = None;
+        for chain in CHAINS.iter() {
+            let bootstrapped = match api_info::is_bootstrapped(http_ep, chain).await {
+                Ok(resp) => resp.result.map(|r| r.bootstrapped).unwrap_or(false),
+                Err(e) => {
+                    warn!("failed is_bootstrapped for {} ({})", chain, e);
+                    false
+                }
+            };
+
+            // only look up the peers while bootstrapping
+            let expected = if bootstrapped || *chain == "X" {
+                None
+            } else {
+                if reference_endpoints.is_none() {
+                    reference_endpoints = Some(self.list_reference_endpoints().await);
+                }
+                fetch_expected_height(reference_endpoints.as_deref().unwrap_or_default(), chain)
+                    .await
+            };
+
+            chains.push(avalanche_ops_aws::ChainBootstrapProgress {
+                chain: chain.to_string(),
+                bootstrapped,
+                fetched: api_metrics::bootstrap_fetched(&scrape, chain),
+                expected,
+            });
+        }
+        Some(chains)
+    }
+
+    /// Returns the HTTP endpoints of the other ready nodes in the fleet,
+    /// falling back to the public API of the network.
+    async fn list_reference_endpoints(&self) -> Vec<String> {
+        let mut endpoints = Vec::new();
+        for dir in [
+            avalanche_ops_aws::StorageNamespace::DiscoverReadyAnchorNodesDir(self.id.clone()),
+            avalanche_ops_aws::StorageNamespace::DiscoverReadyNonAnchorNodesDir(self.id.clone()),
+            avalanche_ops_aws::StorageNamespace::DiscoverReadyApiNodesDir(self.id.clone()),
+        ] {
+            let objects = match s3::spawn_list_objects(
+                self.s3_manager.clone(),
+                &self.s3_bucket,
+                Some(s3::append_slash(&dir.encode())),
+            )
+            .await
+            {
+                Ok(v) => v,
+                Err(e) => {
+                    warn!("failed to list ready nodes {}", e.message());
+                    continue;
+                }
+            };
+            for obj in objects.iter() {
+                let node = match obj
+                    .key()
+                    .map(avalanche_ops_aws::StorageNamespace::parse_node_from_path)
+                {
+                    Some(Ok(v)) => v,
+                    _ => continue,
+                };
+                if node.node_id != self.local_node.node_id {
+                    endpoints.push(node.http_endpoint);
+                }
+            }
+        }
+        endpoints.truncate(MAX_REFERENCE_ENDPOINTS);
+
+        if let Some(ep) = avalanche_ops_aws::public_api_endpoint(self.network_id) {
+            endpoints.push(ep.to_string());
+        }
+        endpoints
+    }
+}
+
+/// Returns the highest height of the chain among the endpoints,
+/// or "None" if none is reachable.
+async fn fetch_expected_height(endpoints: &[String], chain: &str) -> Option<u64> {
+    let mut expected: Option<u64> = None;
+    for ep in endpoints.iter() {
+        let height = match chain {
+            "P" => match api_p::get_height(ep).await {
+                Ok(resp) => resp.result.and_then(|r| r.height),
+                Err(e) => {
+                    warn!("failed get_height from {} ({})", ep, e);
+                    None
+                }
+            },
+            "C" => match api_eth::block_number(ep, api_eth::C_CHAIN_RPC_PATH).await {
+                Ok(resp) => u64::try_from(&resp.result).ok(),
+                Err(e) => {
+                    warn!("failed eth_blockNumber from {} ({})", ep, e);
+                    None
+                }
+            },
+            _ => None,
+        };
+        if let Some(h) = height {
+            expected = Some(expected.map_or(h, |v| v.max(h)));
+        }
+    }
+    expected
+}
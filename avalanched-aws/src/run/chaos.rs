@@ -0,0 +1,102 @@
+use std::{
+    io,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use log::{info, warn};
+use tokio::time::sleep;
+
+use aws::s3;
+
+use super::{supervisor, upgrade};
+
+/// Interval between the chaos event polls.
+const POLL_INTERVAL_SECONDS: u64 = 30;
+
+pub struct ChaosAgent {
+    pub s3_manager: s3::Manager,
+    pub s3_bucket: String,
+    pub id: String,
+    pub machine_id: String,
+    /// Throttles the block device of the database directory.
+    pub db_dir: String,
+}
+
+/// Polls the disk throttle from "avalanche-ops-aws chaos", and throttles
+/// the avalanchego I/O while the local machine is listed and the throttle
+/// has not expired. The throttle is removed once the event is deleted,
+/// so a failed "chaos" run never leaves the node throttled past "expires_at".
+pub async fn chaos_loop(agent: Arc<ChaosAgent>) {
+    info!("STEP: starting 'chaos_loop'");
+
+    // version of the applied throttle
+    let mut throttled: Option<u64> = None;
+    loop {
+        sleep(Duration::from_secs(POLL_INTERVAL_SECONDS)).await;
+
+        let throttle = match agent.fetch_throttle().await {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("failed to fetch chaos disk throttle ({}), retrying...", e);
+                continue;
+            }
+        };
+        let desired = throttle
+            .as_ref()
+            .filter(|t| t.applies_to(&agent.machine_id, unix_now()));
+
+        match (desired, throttled) {
+            (Some(t), Some(version)) if t.version == version => {}
+            (Some(t), _) => {
+                info!(
+                    "STEP: throttling '{}' (read {} B/s, write {} B/s) until {}",
+                    agent.db_dir, t.read_bytes_per_second, t.write_bytes_per_second, t.expires_at
+                );
+                // a new version replaces the previous caps
+                if throttled.is_some() {
+                    if let Err(e) = supervisor::reset_io_bandwidth() {
+                        warn!("failed to reset I/O bandwidth ({}), retrying...", e);
+                        continue;
+                    }
+                }
+                match supervisor::set_io_bandwidth(
+                    &agent.db_dir,
+                    t.read_bytes_per_second,
+                    t.write_bytes_per_second,
+                ) {
+                    Ok(_) => throttled = Some(t.version),
+                    Err(e) => warn!("failed to set I/O bandwidth ({}), retrying...", e),
+                }
+            }
+            (None, Some(version)) => {
+                info!("STEP: removing disk throttle version {}", version);
+                match supervisor::reset_io_bandwidth() {
+                    Ok(_) => throttled = None,
+                    Err(e) => warn!("failed to reset I/O bandwidth ({}), retrying...", e),
+                }
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+impl ChaosAgent {
+    async fn fetch_throttle(&self) -> io::Result<Option<avalanche_ops_aws::ChaosDiskThrottle>> {
+        let s3_key =
+            avalanche_ops_aws::StorageNamespace::EventsChaosDiskThrottle(self.id.clone()).encode();
+        let d = match upgrade::get_object(self.s3_manager.clone(), &self.s3_bucket, &s3_key).await?
+        {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+        avalanche_ops_aws::ChaosDiskThrottle::decode_yaml(&d).map(Some)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("unexpected None duration_since")
+        .as_secs()
+}
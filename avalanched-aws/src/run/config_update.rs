@@ -0,0 +1,279 @@
+use std::{
+    fs, io,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use log::{error, info, warn};
+use tokio::time::sleep;
+
+use avalanchego::config as avalanchego_config;
+use aws::s3;
+use utils::random;
+
+use super::{supervisor, upgrade};
+
+/// Interval between the config update event polls.
+const POLL_INTERVAL_SECONDS: u64 = 30;
+
+/// Time to wait after recording the restart claim before listing the claims,
+/// so that the claims recorded at the same time are visible to each other.
+const CLAIM_SETTLE_SECONDS: u64 = 10;
+
+/// Suffix of the previous config file kept during the update.
+const PREVIOUS_SUFFIX: &str = ".config-update";
+
+pub struct ConfigUpdater {
+    pub s3_manager: s3::Manager,
+    pub s3_bucket: String,
+    pub id: String,
+    pub local_node: avalanche_ops_aws::Node,
+    pub avalanchego_config_file: String,
+}
+
+/// Polls the config update event from "avalanche-ops-aws events update-config",
+/// and applies each new event version once: writes the delta to the avalanchego
+/// config file and restarts the node, once the node holds one of the
+/// "max_unavailable" restart slots. Restores the previous config if the node
+/// does not become healthy.
+pub async fn config_update_loop(updater: Arc<ConfigUpdater>) {
+    info!("STEP: starting 'config_update_loop'");
+
+    loop {
+        sleep(Duration::from_secs(POLL_INTERVAL_SECONDS)).await;
+
+        let event = match updater.fetch_event().await {
+            Ok(Some(v)) => v,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!("failed to fetch config-update event ({}), retrying...", e);
+                continue;
+            }
+        };
+        match updater.fetch_status().await {
+            Ok(Some(status)) if event.is_applied(&status) => continue,
+            Ok(_) => {}
+            Err(e) => {
+                warn!("failed to fetch config-update status ({}), retrying...", e);
+                continue;
+            }
+        }
+
+        // new nodes already boot with the updated spec
+        let mut config = match avalanchego_config::Config::load(&updater.avalanchego_config_file) {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("failed to load avalanchego config ({}), retrying...", e);
+                continue;
+            }
+        };
+        if !event.delta.apply(&mut config) {
+            info!("config already up-to-date, skipping restart");
+            updater
+                .report(&event, avalanche_ops_aws::ConfigUpdateState::Updated, None)
+                .await;
+            continue;
+        }
+
+        updater
+            .report(
+                &event,
+                avalanche_ops_aws::ConfigUpdateState::Restarting,
+                None,
+            )
+            .await;
+        sleep(Duration::from_secs(CLAIM_SETTLE_SECONDS)).await;
+        match updater.holds_slot(&event).await {
+            Ok(true) => {}
+            Ok(false) => {
+                info!(
+                    "all {} restart slot(s) taken, waiting for the next slot",
+                    event.policy.max_unavailable
+                );
+                updater
+                    .report(&event, avalanche_ops_aws::ConfigUpdateState::Waiting, None)
+                    .await;
+                continue;
+            }
+            Err(e) => {
+                warn!("failed to list config-update statuses ({}), retrying...", e);
+                updater
+                    .report(&event, avalanche_ops_aws::ConfigUpdateState::Waiting, None)
+                    .await;
+                continue;
+            }
+        }
+        info!("STEP: applying config update version {}", event.version);
+
+        match updater.apply(&event, config).await {
+            Ok(_) => {
+                info!("applied config update version {}", event.version);
+                updater
+                    .report(&event, avalanche_ops_aws::ConfigUpdateState::Updated, None)
+                    .await;
+            }
+            Err(e) => {
+                error!("failed to apply config update ({})", e);
+                updater
+                    .report(
+                        &event,
+                        avalanche_ops_aws::ConfigUpdateState::Failed,
+                        Some(e.to_string()),
+                    )
+                    .await;
+            }
+        }
+    }
+}
+
+impl ConfigUpdater {
+    async fn fetch_event(&self) -> io::Result<Option<avalanche_ops_aws::ConfigUpdateEvent>> {
+        let s3_key =
+            avalanche_ops_aws::StorageNamespace::EventsConfigUpdate(self.id.clone()).encode();
+        let d = match upgrade::get_object(self.s3_manager.clone(), &self.s3_bucket, &s3_key).await?
+        {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+        avalanche_ops_aws::ConfigUpdateEvent::decode_yaml(&d).map(Some)
+    }
+
+    async fn fetch_status(&self) -> io::Result<Option<avalanche_ops_aws::ConfigUpdateStatus>> {
+        let d = match upgrade::get_object(
+            self.s3_manager.clone(),
+            &self.s3_bucket,
+            &self.status_s3_key(),
+        )
+        .await?
+        {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+        avalanche_ops_aws::ConfigUpdateStatus::decode_yaml(&d).map(Some)
+    }
+
+    fn status_s3_key(&self) -> String {
+        avalanche_ops_aws::StorageNamespace::EventsConfigUpdateStatus(
+            self.id.clone(),
+            self.local_node.machine_id.clone(),
+        )
+        .encode()
+    }
+
+    /// Returns true if the recorded claim of the local node
+    /// is within the "max_unavailable" earliest claims.
+    async fn holds_slot(&self, event: &avalanche_ops_aws::ConfigUpdateEvent) -> io::Result<bool> {
+        let dir = avalanche_ops_aws::StorageNamespace::EventsConfigUpdateStatusDir(self.id.clone())
+            .encode();
+        let objects = s3::spawn_list_objects(
+            self.s3_manager.clone(),
+            &self.s3_bucket,
+            Some(s3::append_slash(&dir)),
+        )
+        .await?;
+
+        let mut statuses = Vec::new();
+        for obj in objects.iter() {
+            let s3_key = match obj.key() {
+                Some(v) => v,
+                None => continue,
+            };
+            if let Some(d) =
+                upgrade::get_object(self.s3_manager.clone(), &self.s3_bucket, s3_key).await?
+            {
+                match avalanche_ops_aws::ConfigUpdateStatus::decode_yaml(&d) {
+                    Ok(v) => statuses.push(v),
+                    Err(e) => warn!("skipping invalid status '{}' ({})", s3_key, e),
+                }
+            }
+        }
+
+        // the node that crashed while restarting never releases its slot
+        let stale_seconds = event.policy.health_check_timeout_seconds + 5 * 60;
+        Ok(avalanche_ops_aws::config_update_slot(
+            &statuses,
+            event.version,
+            &self.local_node.machine_id,
+            event.policy.max_unavailable,
+            unix_now(),
+            stale_seconds,
+        ))
+    }
+
+    async fn apply(
+        &self,
+        event: &avalanche_ops_aws::ConfigUpdateEvent,
+        config: avalanchego_config::Config,
+    ) -> io::Result<()> {
+        config.validate()?;
+        let previous_path = format!("{}{}", self.avalanchego_config_file, PREVIOUS_SUFFIX);
+        fs::copy(&self.avalanchego_config_file, &previous_path)?;
+        config.sync(None)?;
+        info!(
+            "updated '{}' with {:?}",
+            self.avalanchego_config_file, event.delta
+        );
+
+        // avalanchego only reads the config file on start
+        supervisor::restart()?;
+        match upgrade::wait_for_health(
+            &self.local_node.http_endpoint,
+            event.policy.health_check_timeout_seconds,
+        )
+        .await
+        {
+            Ok(_) => {
+                fs::remove_file(&previous_path)?;
+                Ok(())
+            }
+            Err(e) => {
+                warn!("restoring the previous config ({})", e);
+                fs::rename(&previous_path, &self.avalanchego_config_file)?;
+                supervisor::restart()?;
+                Err(e)
+            }
+        }
+    }
+
+    async fn report(
+        &self,
+        event: &avalanche_ops_aws::ConfigUpdateEvent,
+        state: avalanche_ops_aws::ConfigUpdateState,
+        message: Option<String>,
+    ) {
+        let status = avalanche_ops_aws::ConfigUpdateStatus {
+            machine_id: self.local_node.machine_id.clone(),
+            node_id: self.local_node.node_id.clone(),
+            version: event.version,
+            state,
+            message,
+            updated_at: unix_now(),
+        };
+        match status.encode_yaml() {
+            Ok(contents) => {
+                let tmp_path =
+                    random::tmp_path(10, Some(".yaml")).expect("unexpected tmp_path failure");
+                fs::write(&tmp_path, contents).expect("failed fs::write");
+                if let Err(e) = s3::spawn_put_object(
+                    self.s3_manager.clone(),
+                    &tmp_path,
+                    &self.s3_bucket,
+                    &self.status_s3_key(),
+                )
+                .await
+                {
+                    warn!("failed to record config-update status ({})", e.message());
+                }
+                fs::remove_file(&tmp_path).expect("failed fs::remove_file");
+            }
+            Err(e) => warn!("failed to encode config-update status ({})", e),
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("unexpected None duration_since")
+        .as_secs()
+}
@@ -0,0 +1,163 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Error, ErrorKind},
+    sync::Arc,
+    time::Duration,
+};
+
+use log::{info, warn};
+use tokio::time::sleep;
+
+use avalanche_api::index as api_index;
+use avalanche_types::api::index;
+use aws::s3;
+use utils::{compress, random};
+
+pub struct IndexArchiver {
+    pub s3_manager: s3::Manager,
+    pub s3_bucket: String,
+    pub id: String,
+    pub local_node: avalanche_ops_aws::Node,
+    pub archiver: avalanche_ops_aws::IndexArchiver,
+}
+
+/// Archives the newly accepted containers of each index on the interval.
+/// Resumes from the last archived file, so the restarts and the
+/// replaced instances of the same machine ID do not re-upload.
+pub async fn index_archive_loop(archiver: Arc<IndexArchiver>) {
+    info!(
+        "STEP: starting 'index_archive_loop' with {}-second interval",
+        archiver.archiver.interval_seconds
+    );
+
+    // next index to archive, once resumed from the archived files
+    let mut next: BTreeMap<index::Index, u64> = BTreeMap::new();
+    loop {
+        sleep(Duration::from_secs(archiver.archiver.interval_seconds)).await;
+
+        for idx in archiver.archiver.indexes.iter() {
+            let start = match next.get(idx) {
+                Some(v) => *v,
+                None => match archiver.resume(idx).await {
+                    Ok(v) => v,
+                    Err(e) => {
+                        warn!("failed to resume archiving '{}' ({}), retrying...", idx, e);
+                        continue;
+                    }
+                },
+            };
+
+            let mut archived = start;
+            loop {
+                match archiver.archive(idx, archived).await {
+                    Ok(0) => break,
+                    Ok(n) => archived += n,
+                    Err(e) => {
+                        warn!("failed to archive '{}' from {} ({})", idx, archived, e);
+                        break;
+                    }
+                }
+            }
+            next.insert(*idx, archived);
+        }
+    }
+}
+
+impl IndexArchiver {
+    fn archive_dir(&self, idx: &index::Index) -> String {
+        avalanche_ops_aws::StorageNamespace::IndexArchiveDir(
+            self.id.clone(),
+            self.local_node.machine_id.clone(),
+            idx.as_str().to_string(),
+        )
+        .encode()
+    }
+
+    /// Returns the index after the last archived container.
+    async fn resume(&self, idx: &index::Index) -> io::Result<u64> {
+        let objects = s3::spawn_list_objects(
+            self.s3_manager.clone(),
+            &self.s3_bucket,
+            Some(format!("{}/", self.archive_dir(idx))),
+        )
+        .await?;
+        let last = objects
+            .iter()
+            .filter_map(|o| o.key())
+            .filter_map(avalanche_ops_aws::parse_index_archive_last)
+            .max();
+        Ok(last.map_or(0, |v| v + 1))
+    }
+
+    /// Archives the containers from "start" to one file,
+    /// and returns the number of the archived containers
+    /// (zero if none accepted since "start").
+    async fn archive(&self, idx: &index::Index, start: u64) -> io::Result<u64> {
+        let http_ep = self.local_node.http_endpoint.as_str();
+        let last = match api_index::get_last_accepted(http_ep, idx).await {
+            Ok(resp) => match resp.result {
+                Some(c) => c.index,
+                None => return Ok(0),
+            },
+            // nothing accepted yet in the index
+            Err(e) if e.to_string().contains("no containers") => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        if last < start {
+            return Ok(0);
+        }
+
+        let end = last.min(start + self.archiver.containers_per_file - 1);
+        let mut containers: Vec<index::Container> = Vec::new();
+        let mut cur = start;
+        while cur <= end {
+            let num_to_fetch = (end - cur + 1).min(index::MAX_FETCHED_BY_RANGE);
+            let resp = api_index::get_container_range(http_ep, idx, cur, num_to_fetch).await?;
+            let fetched = resp.result.unwrap_or_default();
+            if fetched.is_empty() {
+                break;
+            }
+            cur += fetched.len() as u64;
+            containers.extend(fetched);
+        }
+        if containers.is_empty() {
+            return Ok(0);
+        }
+        if containers.first().map(|c| c.index) != Some(start) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("'{}' range from {} starts at a different index", idx, start),
+            ));
+        }
+
+        let first = start;
+        let last = start + containers.len() as u64 - 1;
+        let d = compress::pack(
+            &avalanche_ops_aws::encode_ndjson(&containers)?,
+            compress::Encoder::Zstd(3),
+        )?;
+        let tmp_path = random::tmp_path(15, Some(compress::Encoder::Zstd(3).ext()))?;
+        fs::write(&tmp_path, d)?;
+        let s3_key = format!(
+            "{}/{}",
+            self.archive_dir(idx),
+            avalanche_ops_aws::index_archive_file_name(first, last)
+        );
+        let ret =
+            s3::spawn_put_object(self.s3_manager.clone(), &tmp_path, &self.s3_bucket, &s3_key)
+                .await;
+        fs::remove_file(&tmp_path)?;
+        ret?;
+
+        info!(
+            "archived {} '{}' container(s) [{}, {}] to {}",
+            containers.len(),
+            idx,
+            first,
+            last,
+            s3_key
+        );
+        Ok(containers.len() as u64)
+    }
+}
@@ -0,0 +1,132 @@
+use std::{fs, io, sync::Arc, time::Duration};
+
+use log::{info, warn};
+use tokio::time::sleep;
+
+use avalanche_api::keystore as api_keystore;
+use avalanche_types::{api::keystore, soft_key};
+use aws::{envelope, s3};
+use utils::random;
+
+const RETRY_INTERVAL_SECONDS: u64 = 60;
+
+pub struct KeystoreProvisioner {
+    pub s3_manager: s3::Manager,
+    pub s3_bucket: String,
+    pub envelope: envelope::Envelope,
+    pub id: String,
+    pub local_node: avalanche_ops_aws::Node,
+    pub users: Vec<avalanche_ops_aws::KeystoreUser>,
+    pub seed_private_keys: Vec<soft_key::PrivateKeyInfo>,
+}
+
+/// Creates the keystore users on the local API node, retrying until
+/// the node serves the keystore and the chains are bootstrapped.
+/// The users that already exist (e.g., restarted "avalanched") are
+/// only exported again.
+pub async fn provision_keystore_loop(provisioner: Arc<KeystoreProvisioner>) {
+    info!(
+        "STEP: starting 'provision_keystore_loop' for {} user(s)",
+        provisioner.users.len()
+    );
+
+    for user in provisioner.users.iter() {
+        loop {
+            match provisioner.provision(user).await {
+                Ok(_) => break,
+                Err(e) => warn!(
+                    "failed to provision keystore user '{}' ({}), retrying...",
+                    user.username, e
+                ),
+            }
+            sleep(Duration::from_secs(RETRY_INTERVAL_SECONDS)).await;
+        }
+    }
+    info!("provisioned {} keystore user(s)", provisioner.users.len());
+}
+
+impl KeystoreProvisioner {
+    async fn provision(&self, user: &avalanche_ops_aws::KeystoreUser) -> io::Result<()> {
+        let http_ep = self.local_node.http_endpoint.as_str();
+        let existing = api_keystore::list_users(http_ep)
+            .await?
+            .result
+            .map(|v| v.users)
+            .unwrap_or_default();
+        if existing.contains(&user.username) {
+            info!("keystore user '{}' already exists", user.username);
+        } else {
+            api_keystore::create_user(http_ep, &user.username, &user.password).await?;
+        }
+
+        if user.import_seed_private_keys {
+            for key in self.seed_private_keys.iter() {
+                for chain in [keystore::Chain::X, keystore::Chain::P, keystore::Chain::C] {
+                    api_keystore::import_key(
+                        http_ep,
+                        &chain,
+                        &user.username,
+                        &user.password,
+                        &key.private_key,
+                    )
+                    .await?;
+                }
+            }
+            info!(
+                "imported {} seed private key(s) to keystore user '{}'",
+                self.seed_private_keys.len(),
+                user.username
+            );
+        }
+
+        let export = api_keystore::export_user(http_ep, &user.username, &user.password)
+            .await?
+            .result
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("no export for keystore user '{}'", user.username),
+                )
+            })?;
+        self.upload_export(&user.username, &export).await
+    }
+
+    /// Uploads the export envelope-encrypted, on top of the user password.
+    async fn upload_export(
+        &self,
+        username: &str,
+        export: &keystore::ExportUserResult,
+    ) -> io::Result<()> {
+        let d = export.encode_json()?;
+        let tmp_path = random::tmp_path(15, Some(".json"))?;
+        let tmp_encrypted_path = random::tmp_path(15, Some(".json.encrypted"))?;
+        fs::write(&tmp_path, d)?;
+        let sealed = envelope::spawn_seal_aes_256_file(
+            self.envelope.clone(),
+            &tmp_path,
+            &tmp_encrypted_path,
+        )
+        .await;
+        fs::remove_file(&tmp_path)?;
+        sealed?;
+
+        let s3_key = avalanche_ops_aws::StorageNamespace::KeystoreExportEncrypted(
+            self.id.clone(),
+            self.local_node.machine_id.clone(),
+            username.to_string(),
+        )
+        .encode();
+        let ret = s3::spawn_put_object(
+            self.s3_manager.clone(),
+            &tmp_encrypted_path,
+            &self.s3_bucket,
+            &s3_key,
+        )
+        .await;
+        fs::remove_file(&tmp_encrypted_path)?;
+        ret?;
+
+        info!("uploaded keystore user '{}' export to {}", username, s3_key);
+        Ok(())
+    }
+}
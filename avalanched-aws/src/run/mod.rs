@@ -1,27 +1,61 @@
+pub mod bootstrap;
+pub mod chaos;
+pub mod config_update;
+pub mod index_archive;
+pub mod keystore;
+pub mod plugins;
+pub mod public_ip;
+pub mod rotate_cert;
+pub mod status;
+pub mod subnet;
+pub mod supervisor;
+pub mod termination;
+pub mod upgrade;
+
 use std::{
     fs::{self, File},
-    io::Write,
+    io::{self, Error, ErrorKind},
+    net::Ipv4Addr,
     os::unix::fs::PermissionsExt,
     path::Path,
-    sync::Arc,
-    time::{Duration, SystemTime},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime},
 };
 
 use aws_sdk_s3::model::Object;
+use chrono::Utc;
 use clap::{Arg, Command};
 use log::{info, warn};
 use tokio::time::sleep;
 
-use avalanche_api::{health as api_health, metrics as api_metrics};
+use avalanche_api::{
+    alert, health as api_health, info as api_info, metrics as api_metrics, p as api_p, x as api_x,
+};
+use avalanche_ops_aws::notify;
 use avalanche_types::{
-    api::health as api_health_types, cert, constants, genesis as avalanchego_genesis, ids,
-    metrics::avalanchego as avalanchego_metrics, node,
+    api::{health as api_health_types, platformvm as platformvm_api},
+    bls_key, cert, constants, formatting, genesis as avalanchego_genesis, ids,
+    metrics::avalanchego as avalanchego_metrics,
+    node, platformvm, secp256k1fx, soft_key, units,
 };
-use aws::{self, cloudwatch, ec2, envelope, kms, s3};
+use avalanchego::{chain_config::ChainConfigManager, config as avalanchego_config, vms};
+use aws::{self, autoscaling, cloudwatch, dynamodb, ec2, ec2::ebs, envelope, kms, route53, s3};
 use utils::{bash, compress, random};
 
 pub const NAME: &str = "run";
 
+/// Rejects the compressed avalanchego binary that unpacks to more than 1 GiB,
+/// far above the actual size (~100 MiB), to not fill the disk.
+const MAX_AVALANCHE_BIN_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Attempts to claim the Elastic IP, when the anchor nodes
+/// launched together race for the same unassociated one.
+const MAX_ANCHOR_EIP_CLAIM_ATTEMPTS: u64 = 5;
+
 /// Should be able to run with idempotency
 /// (e.g., multiple restarts should not change node ID)
 /// TODO: support download mainnet database from s3
@@ -43,10 +77,7 @@ pub fn command() -> Command<'static> {
 }
 
 pub async fn execute(log_level: &str) {
-    // ref. https://github.com/env-logger-rs/env_logger/issues/47
-    env_logger::init_from_env(
-        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
-    );
+    let recent_errors = status::init_logger(log_level);
 
     info!("STEP: fetching intance metadata using IMDSv2");
 
@@ -68,12 +99,6 @@ pub async fn execute(log_level: &str) {
         .expect("failed ec2::fetch_instance_id");
     info!("fetched instance ID {}", instance_id);
 
-    let public_ipv4 = tokio::spawn(ec2::fetch_public_ipv4())
-        .await
-        .expect("failed spawn await")
-        .expect("failed ec2::fetch_public_ipv4");
-    info!("fetched public ipv4 {}", public_ipv4);
-
     info!("STEP: loading AWS config");
     let shared_config = tokio::spawn(aws::load_config(Some(reg.clone())))
         .await
@@ -81,14 +106,12 @@ pub async fn execute(log_level: &str) {
         .expect("failed aws::load_config");
 
     let ec2_manager = ec2::Manager::new(&shared_config);
-    let kms_manager = kms::Manager::new(&shared_config);
-    let s3_manager = s3::Manager::new(&shared_config);
-    let cw_manager = cloudwatch::Manager::new(&shared_config);
 
     info!("STEP: fetching tags from the local instance");
     let instance_id_arc = Arc::new(instance_id.clone());
+    let ec2_manager_cloned = ec2_manager.clone();
     let tags = tokio::spawn(async move {
-        let ec2_manager_arc = Arc::new(ec2_manager);
+        let ec2_manager_arc = Arc::new(ec2_manager_cloned);
         ec2_manager_arc.fetch_tags(instance_id_arc).await
     })
     .await
@@ -99,10 +122,13 @@ pub async fn execute(log_level: &str) {
     let mut _node_kind: String = String::new();
     let mut kms_cmk_arn: String = String::new();
     let mut s3_bucket: String = String::new();
+    let mut install_s3_bucket: String = String::new();
+    let mut home_region: String = String::new();
     let mut cloudwatch_config_file_path: String = String::new();
     let mut avalanched_bin_path: String = String::new();
     let mut avalanche_bin_path: String = String::new();
     let mut avalanche_data_volume_path: String = String::new();
+    let mut asg_name: Option<String> = None;
     for c in tags {
         let k = c.key().unwrap();
         let v = c.value().unwrap();
@@ -120,6 +146,12 @@ pub async fn execute(log_level: &str) {
             "S3_BUCKET_NAME" => {
                 s3_bucket = v.to_string();
             }
+            "INSTALL_S3_BUCKET_NAME" => {
+                install_s3_bucket = v.to_string();
+            }
+            "HOME_REGION" => {
+                home_region = v.to_string();
+            }
             "CLOUDWATCH_CONFIG_FILE_PATH" => {
                 cloudwatch_config_file_path = v.to_string();
             }
@@ -132,6 +164,10 @@ pub async fn execute(log_level: &str) {
             "AVALANCHE_DATA_VOLUME_PATH" => {
                 avalanche_data_volume_path = v.to_string();
             }
+            // propagated by the ASG
+            "aws:autoscaling:groupName" => {
+                asg_name = Some(v.to_string());
+            }
             _ => {}
         }
     }
@@ -141,12 +177,10 @@ pub async fn execute(log_level: &str) {
     if _node_kind.is_empty() {
         panic!("'NODE_KIND' tag not found")
     }
-    let node_kind = {
-        if _node_kind.eq("anchor") {
-            node::Kind::Anchor
-        } else {
-            node::Kind::NonAnchor
-        }
+    let node_kind = match _node_kind.as_str() {
+        "anchor" => node::Kind::Anchor,
+        "api" => node::Kind::Api,
+        _ => node::Kind::NonAnchor,
     };
     if kms_cmk_arn.is_empty() {
         panic!("'KMS_CMK_ARN' tag not found")
@@ -166,6 +200,31 @@ pub async fn execute(log_level: &str) {
     if avalanche_data_volume_path.is_empty() {
         panic!("'AVALANCHE_DATA_VOLUME_PATH' tag not found")
     }
+    if install_s3_bucket.is_empty() {
+        install_s3_bucket = s3_bucket.clone();
+    }
+    if home_region.is_empty() {
+        home_region = reg.clone();
+    }
+
+    // the shared resources (S3 bucket, KMS key, CloudWatch) are in the home region,
+    // while the install artifacts are downloaded within the local region
+    let home_config = if home_region == reg {
+        shared_config.clone()
+    } else {
+        info!(
+            "STEP: loading AWS config for home region {} (local region {})",
+            home_region, reg
+        );
+        tokio::spawn(aws::load_config(Some(home_region.clone())))
+            .await
+            .expect("failed spawn aws::load_config")
+            .expect("failed aws::load_config")
+    };
+    let kms_manager = kms::Manager::new(&home_config);
+    let s3_manager = s3::Manager::new(&home_config);
+    let cw_manager = cloudwatch::Manager::new(&home_config);
+    let install_s3_manager = s3::Manager::new(&shared_config);
 
     let envelope = envelope::Envelope::new(Some(kms_manager), Some(kms_cmk_arn));
 
@@ -173,62 +232,39 @@ pub async fn execute(log_level: &str) {
         info!("STEP: downloading avalanche binary from S3");
         let s3_key =
             avalanche_ops_aws::StorageNamespace::AvalancheBinCompressed(id.clone()).encode();
-        let tmp_avalanche_bin_compressed_path = random::tmp_path(15, Some(".zstd")).unwrap();
-        s3::spawn_get_object(
-            s3_manager.clone(),
-            &s3_bucket,
-            &s3_key,
-            &tmp_avalanche_bin_compressed_path,
-        )
-        .await
-        .expect("failed s3::spawn_get_object");
-
-        compress::unpack_file(
-            &tmp_avalanche_bin_compressed_path,
-            &avalanche_bin_path,
-            compress::Decoder::Zstd,
-        )
-        .expect("failed unpack_file avalanche_bin_compressed_path");
+        install_s3_manager
+            .get_object_unpacked(
+                &install_s3_bucket,
+                &s3_key,
+                &avalanche_bin_path,
+                compress::Decoder::Zstd,
+                &compress::StreamOptions {
+                    max_unpacked_bytes: Some(MAX_AVALANCHE_BIN_SIZE),
+                    ..Default::default()
+                },
+            )
+            .await
+            .expect("failed s3::Manager::get_object_unpacked");
 
         let f = File::open(&avalanche_bin_path).expect("failed to open avalanche_bin");
         f.set_permissions(PermissionsExt::from_mode(0o777))
             .expect("failed to set file permission for avalanche_bin");
-        fs::remove_file(&tmp_avalanche_bin_compressed_path).expect("failed fs::remove_file");
     }
 
+    // new plugins are added on restarts, while the installed ones are kept
+    // as "upgrade_loop" may have replaced them
     let plugins_dir = get_plugins_dir(&avalanche_bin_path);
-    if !Path::new(&plugins_dir).exists() {
-        info!("STEP: creating '{}' for plugins", plugins_dir);
-        fs::create_dir_all(plugins_dir.clone()).unwrap();
-
-        info!("STEP: downloading plugins from S3 (if any)");
-        let objects = s3::spawn_list_objects(
-            s3_manager.clone(),
-            &s3_bucket,
-            Some(s3::append_slash(
-                &avalanche_ops_aws::StorageNamespace::PluginsDir(id.clone()).encode(),
-            )),
-        )
-        .await
-        .expect("failed s3::spawn_list_objects");
-        info!("listed {} plugins from S3", objects.len());
-        for obj in objects.iter() {
-            let s3_key = obj.key().expect("unexpected None s3 object");
-            let tmp_path = random::tmp_path(15, None).unwrap();
-            s3::spawn_get_object(s3_manager.clone(), &s3_bucket, s3_key, &tmp_path)
-                .await
-                .expect("failed s3::spawn_get_object");
-
-            let file_name = extract_filename(s3_key);
-            let file_path = format!("{}/{}", plugins_dir, file_name);
-            compress::unpack_file(&tmp_path, &file_path, compress::Decoder::Zstd).unwrap();
-
-            let f = File::open(file_path).expect("failed to open plugin file");
-            f.set_permissions(PermissionsExt::from_mode(0o777))
-                .expect("failed to set file permission");
-            fs::remove_file(&tmp_path).expect("failed fs::remove_file");
-        }
-    }
+    info!("STEP: installing plugins from S3 (if any)");
+    let mut installed_plugins = plugins::install_from_s3(
+        install_s3_manager.clone(),
+        &install_s3_bucket,
+        &avalanche_ops_aws::StorageNamespace::PluginsDir(id.clone()).encode(),
+        &avalanche_bin_path,
+        &plugins_dir,
+        vms::DEFAULT_VM_ALIASES_FILE,
+    )
+    .await
+    .expect("failed plugins::install_from_s3");
 
     info!("STEP: downloading avalanche-ops::Spec from S3");
     let tmp_spec_file_path = random::tmp_path(15, Some(".yaml")).unwrap();
@@ -242,7 +278,108 @@ pub async fn execute(log_level: &str) {
     .expect("failed s3::spawn_get_object");
 
     let mut spec = avalanche_ops_aws::Spec::load(&tmp_spec_file_path).unwrap();
-    spec.avalanchego_config.public_ip = Some(public_ipv4.clone());
+    if spec.is_redacted() {
+        info!("STEP: downloading envelope-encrypted seed private keys from S3");
+        let tmp_encrypted_path = random::tmp_path(15, Some(".encrypted")).unwrap();
+        let tmp_decrypted_path = random::tmp_path(15, Some(".yaml")).unwrap();
+        s3::spawn_get_object(
+            s3_manager.clone(),
+            &s3_bucket,
+            &avalanche_ops_aws::StorageNamespace::SeedPrivateKeysEncrypted(id.clone()).encode(),
+            &tmp_encrypted_path,
+        )
+        .await
+        .expect("failed s3::spawn_get_object");
+        envelope::spawn_unseal_aes_256_file(
+            envelope.clone(),
+            &tmp_encrypted_path,
+            &tmp_decrypted_path,
+        )
+        .await
+        .expect("failed envelope::spawn_unseal_aes_256_file");
+
+        let d = fs::read(&tmp_decrypted_path).expect("failed fs::read");
+        let keys = avalanche_ops_aws::SeedPrivateKeys::decode(&d).unwrap();
+        spec.restore_seed_private_keys(keys);
+        fs::remove_file(&tmp_encrypted_path).expect("failed fs::remove_file");
+        fs::remove_file(&tmp_decrypted_path).expect("failed fs::remove_file");
+    }
+
+    let notifier = Arc::new(notify::Notifier::new(
+        &id,
+        spec.notifications.clone().unwrap_or_default(),
+        &home_config,
+    ));
+
+    // the subnet VM is installed by "subnet_loop" with its VM ID
+    let mut keep_plugins = installed_plugins.file_names.clone();
+    if let Some(subnet) = &spec.subnet {
+        keep_plugins.insert(subnet.vm_id.clone());
+    }
+    let removed =
+        plugins::garbage_collect(&plugins_dir, &keep_plugins, &mut installed_plugins.aliases)
+            .expect("failed plugins::garbage_collect");
+    info!("removed {} stale plugin(s)", removed.len());
+    if !installed_plugins.aliases.0.is_empty() {
+        info!(
+            "STEP: writing VM aliases to '{}'",
+            vms::DEFAULT_VM_ALIASES_FILE
+        );
+        installed_plugins
+            .aliases
+            .sync(vms::DEFAULT_VM_ALIASES_FILE)
+            .expect("failed to sync VM aliases");
+        spec.avalanchego_config.vm_aliases_file = Some(String::from(vms::DEFAULT_VM_ALIASES_FILE));
+    }
+
+    // the node advertises the Elastic IP that the other nodes
+    // bootstrap from, rather than the address of this instance,
+    // or its IPv6 address with "advertise_ipv6",
+    // otherwise the address resolved per "public_ip_policy"
+    let public_ip_policy = spec
+        .public_ip_policy
+        .clone()
+        .unwrap_or_else(avalanche_ops_aws::PublicIpPolicy::imds_only);
+    let mut redetect_public_ip = false;
+    let public_ip = match &spec.machine.anchor_endpoints {
+        Some(anchor_endpoints)
+            if anchor_endpoints.elastic_ips && matches!(node_kind, node::Kind::Anchor) =>
+        {
+            info!("STEP: claiming Elastic IP of the anchor node");
+            claim_anchor_endpoint(
+                &ec2_manager,
+                &route53::Manager::new(&home_config),
+                &id,
+                &instance_id,
+                anchor_endpoints,
+            )
+            .await
+            .expect("failed claim_anchor_endpoint")
+        }
+        _ if spec
+            .aws_resources
+            .as_ref()
+            .and_then(|v| v.advertise_ipv6)
+            .unwrap_or(false) =>
+        {
+            info!("STEP: fetching IPv6 address to advertise");
+            ec2::fetch_ipv6()
+                .await
+                .expect("failed ec2::fetch_ipv6")
+                .expect("no IPv6 address on the instance (requires 'dual_stack')")
+        }
+        _ => {
+            info!("STEP: resolving public IP to advertise");
+            redetect_public_ip = public_ip_policy.redetect_interval_seconds > 0;
+            public_ip::resolve(&public_ip_policy, &instance_id)
+                .await
+                .expect("failed public_ip::resolve")
+        }
+    };
+    spec.avalanchego_config.public_ip = Some(public_ip.clone());
+    allow_staking_ingress(&spec, &public_ip)
+        .await
+        .expect("failed allow_staking_ingress");
     spec.avalanchego_config
         .sync(None)
         .expect("failed to sync avalanchego config_file");
@@ -253,28 +390,23 @@ pub async fn execute(log_level: &str) {
     // ref. https://docs.aws.amazon.com/AmazonCloudWatch/latest/monitoring/CloudWatch-Agent-Configuration-File-Details.html
     info!("STEP: writing CloudWatch configuration JSON file");
     let aws_resources = spec.aws_resources.clone().unwrap();
-    let mut log_collect_list = vec![
-        cloudwatch::Collect {
-            log_group_name: id.clone(),
-            log_stream_name: format!("{{instance_id}}-{}-avalanched", node_kind.as_str()),
-            file_path: String::from("/var/log/avalanched/avalanched.log"),
-            auto_removal: Some(true),
-            retention_in_days: Some(7),
-            ..cloudwatch::Collect::default()
-        },
-        // collect all .log files in the /var/log/avalanche tree
-        cloudwatch::Collect {
-            log_group_name: id.clone(),
-            log_stream_name: format!("{{instance_id}}-{}-all-logs", node_kind.as_str()),
-            file_path: format!("{}/**.log", spec.avalanchego_config.log_dir),
-
-            // TODO: replace this with log rotation
-            auto_removal: Some(false),
-
-            retention_in_days: Some(7),
-            ..cloudwatch::Collect::default()
-        },
-    ];
+    let logs_retention_in_days = aws_resources
+        .instance_logs_retention_in_days
+        .unwrap_or(cloudwatch::logs::DEFAULT_RETENTION_IN_DAYS);
+    let mut log_collect_list = vec![cloudwatch::Collect {
+        log_group_name: id.clone(),
+        log_stream_name: format!("{{instance_id}}-{}-avalanched", node_kind.as_str()),
+        file_path: String::from("/var/log/avalanched/avalanched.log"),
+        auto_removal: Some(true),
+        retention_in_days: Some(logs_retention_in_days),
+        ..cloudwatch::Collect::default()
+    }];
+    log_collect_list.extend(avalanchego_log_collect_list(
+        &id,
+        node_kind.as_str(),
+        &spec.avalanchego_config.log_dir,
+        logs_retention_in_days,
+    ));
     if aws_resources.instance_system_logs.is_some() && aws_resources.instance_system_logs.unwrap() {
         // to check OOMs via "oom-kill" or "Out of memory: Killed process 8266 (srEXiWaHuhNyGwP)"
         log_collect_list.push(cloudwatch::Collect {
@@ -282,7 +414,7 @@ pub async fn execute(log_level: &str) {
             log_stream_name: format!("{{instance_id}}-{}-syslog", node_kind.as_str()),
             file_path: String::from("/var/log/syslog"),
             auto_removal: Some(true),
-            retention_in_days: Some(7),
+            retention_in_days: Some(logs_retention_in_days),
             ..cloudwatch::Collect::default()
         });
         // to check device layer logs
@@ -291,16 +423,22 @@ pub async fn execute(log_level: &str) {
             log_stream_name: format!("{{instance_id}}-{}-dmesg", node_kind.as_str()),
             file_path: String::from("/var/log/dmesg"),
             auto_removal: Some(true),
-            retention_in_days: Some(7),
+            retention_in_days: Some(logs_retention_in_days),
             ..cloudwatch::Collect::default()
         });
     }
     let mut cloudwatch_config = cloudwatch::Config::default();
+    if home_region != reg {
+        // ship to the log group in the home region
+        if let Some(agent) = cloudwatch_config.agent.as_mut() {
+            agent.region = Some(home_region.clone());
+        }
+    }
     cloudwatch_config.logs = Some(cloudwatch::Logs {
         force_flush_interval: Some(60),
         logs_collected: Some(cloudwatch::LogsCollected {
             files: Some(cloudwatch::Files {
-                collect_list: Some(log_collect_list),
+                collect_list: Some(log_collect_list.clone()),
             }),
         }),
     });
@@ -319,6 +457,9 @@ pub async fn execute(log_level: &str) {
         .sync(&cloudwatch_config_file_path)
         .unwrap();
 
+    // the DynamoDB table of the validator registration and certificate rotation locks
+    let lock_table = spec.node_lock_table();
+
     // TODO: reuse TLS certs for static node IDs
     info!("checking TLS certs for node ID");
     let tls_key_path = spec
@@ -326,70 +467,94 @@ pub async fn execute(log_level: &str) {
         .clone()
         .staking_tls_key_file
         .unwrap();
-    let tls_key_exists = Path::new(&tls_key_path).exists();
     let tls_cert_path = spec
         .avalanchego_config
         .clone()
         .staking_tls_cert_file
         .unwrap();
+    rotate_cert::recover(&tls_key_path, &tls_cert_path)
+        .expect("failed to recover the interrupted certificate rotation");
+    let tls_key_exists = Path::new(&tls_key_path).exists();
     let tls_cert_exists = Path::new(&tls_cert_path).exists();
     if !tls_key_exists || !tls_cert_exists {
-        info!(
-            "STEP: generating TLS certs (key exists {}, cert exists {})",
-            tls_key_exists, tls_cert_exists
-        );
-        cert::generate(&tls_key_path, &tls_cert_path).unwrap();
-
-        info!("uploading generated TLS certs to S3");
-        let s3_key = format!(
-            "{}/{}.crt",
-            avalanche_ops_aws::StorageNamespace::PkiKeyDir(id.clone()).encode(),
-            instance_id
-        );
-        s3::spawn_put_object(s3_manager.clone(), &tls_cert_path, &s3_bucket, &s3_key)
+        // the cloned custom network keeps the validator set of the genesis,
+        // so the node restores the certs of its source node
+        let restored = if spec.clone_source.is_some() && spec.avalanchego_config.is_custom_network()
+        {
+            info!("STEP: downloading TLS certs of the cloned source node");
+            download_clone_staking_certs(
+                s3_manager.clone(),
+                envelope.clone(),
+                &s3_bucket,
+                &id,
+                &instance_id,
+                &tls_key_path,
+                &tls_cert_path,
+            )
             .await
-            .expect("failed s3::spawn_put_object");
-
-        let tmp_compressed_path = random::tmp_path(15, Some(".zstd")).unwrap();
-        let tmp_encrypted_path = random::tmp_path(15, Some(".zstd.encrypted")).unwrap();
-
-        compress::pack_file(
-            &tls_key_path,
-            &tmp_compressed_path,
-            compress::Encoder::Zstd(3),
-        )
-        .expect("failed pack_file tls_key_path");
-
-        envelope::spawn_seal_aes_256_file(
-            envelope.clone(),
-            &tmp_compressed_path,
-            &tmp_encrypted_path,
-        )
-        .await
-        .expect("failed envelope::spawn_seal_aes_256_file");
+            .expect("failed download_clone_staking_certs")
+        } else {
+            false
+        };
+        if !restored {
+            info!(
+                "STEP: generating TLS certs (key exists {}, cert exists {})",
+                tls_key_exists, tls_cert_exists
+            );
+            cert::generate(&tls_key_path, &tls_cert_path).unwrap();
+        }
 
-        s3::spawn_put_object(
+        info!("uploading TLS certs to S3");
+        upload_staking_certs(
             s3_manager.clone(),
-            &tmp_encrypted_path,
+            envelope.clone(),
             &s3_bucket,
-            format!(
-                "{}/{}.key.zstd.seal_aes_256.encrypted",
-                avalanche_ops_aws::StorageNamespace::PkiKeyDir(id.clone()).encode(),
-                instance_id
-            )
-            .as_str(),
+            &id,
+            &instance_id,
+            &tls_key_path,
+            &tls_cert_path,
         )
         .await
-        .expect("failed s3::spawn_put_object");
-
-        fs::remove_file(tmp_compressed_path).expect("failed fs::remove_file");
-        fs::remove_file(tmp_encrypted_path).expect("failed fs::remove_file");
+        .expect("failed upload_staking_certs");
     }
 
     // loads the node ID from generated/existing certs
     let node_id = ids::NodeId::from_cert_file(&tls_cert_path).expect("failed to load node ID");
     info!("loaded node ID {}", node_id);
 
+    // the BLS key stays with the node across the certificate rotations
+    let signer_key_path = spec
+        .avalanchego_config
+        .staking_signer_key_file
+        .get_or_insert_with(|| String::from(avalanchego_config::DEFAULT_STAKING_SIGNER_KEY_FILE))
+        .clone();
+    if !Path::new(&signer_key_path).exists() {
+        info!("STEP: generating BLS signer key");
+        let signer_key = bls_key::Key::generate().expect("failed bls_key::Key::generate");
+
+        // backs up before saving in place, so the node never runs
+        // with the key missing from S3 (regenerated after the crash)
+        let staged_path = format!("{}.new", signer_key_path);
+        signer_key
+            .save(&staged_path)
+            .expect("failed to save BLS signer key");
+        info!("uploading BLS signer key to S3");
+        upload_signer_key(
+            s3_manager.clone(),
+            envelope.clone(),
+            &s3_bucket,
+            &id,
+            &instance_id,
+            &staged_path,
+        )
+        .await
+        .expect("failed upload_signer_key");
+        fs::rename(&staged_path, &signer_key_path).expect("failed to rename BLS signer key");
+    }
+    let signer_key =
+        bls_key::Key::from_file(&signer_key_path).expect("failed to load BLS signer key");
+    info!("loaded BLS signer key {:?}", signer_key);
+
     let http_scheme = {
         if spec.avalanchego_config.http_tls_enabled.is_some()
             && spec
@@ -406,7 +571,7 @@ pub async fn execute(log_level: &str) {
         node_kind.clone(),
         &instance_id,
         &node_id.to_string(),
-        &public_ipv4,
+        &public_ip,
         http_scheme,
         spec.avalanchego_config.http_port,
     );
@@ -417,6 +582,84 @@ pub async fn execute(log_level: &str) {
             .expect("failed to encode node Info")
     );
 
+    let reporter = Arc::new(status::Reporter::new(&id, &local_node, recent_errors));
+    let status_api_policy = spec.status_api_policy.clone().unwrap_or_default();
+    let status_handle = if status_api_policy.port > 0 {
+        Some(tokio::spawn(status::serve(
+            reporter.clone(),
+            status_api_policy.port,
+        )))
+    } else {
+        None
+    };
+
+    info!("STEP: registering the local node for discovery");
+    let staking_cert_fingerprint =
+        cert::fingerprint_sha256(&fs::read(&tls_cert_path).expect("failed fs::read"))
+            .expect("failed cert::fingerprint_sha256");
+    let terminating = Arc::new(AtomicBool::new(false));
+    let register_handle = tokio::spawn(register_node_loop(
+        s3_manager.clone(),
+        Arc::new(s3_bucket.clone()),
+        Arc::new(
+            avalanche_ops_aws::StorageNamespace::DiscoverRegisteredNode(
+                id.clone(),
+                local_node.clone(),
+            )
+            .encode(),
+        ),
+        Arc::new(avalanche_ops_aws::Registration {
+            node: local_node.clone(),
+            network_id: spec.avalanchego_config.network_id,
+            staking_cert_fingerprint,
+        }),
+        terminating.clone(),
+    ));
+
+    // the instance may go away while downloading the db backup or bootstrapping
+    let termination_handle = spec.termination_policy.clone().map(|policy| {
+        info!("STEP: watching the spot interruption and ASG termination notices");
+        tokio::spawn(termination::termination_loop(Arc::new(
+            termination::TerminationHandler {
+                ec2_manager: ec2_manager.clone(),
+                autoscaling_manager: autoscaling::Manager::new(&shared_config),
+                s3_manager: s3_manager.clone(),
+                s3_bucket: s3_bucket.clone(),
+                id: id.clone(),
+                local_node: local_node.clone(),
+                asg_name: asg_name.clone(),
+                policy,
+                terminating: terminating.clone(),
+                notifier: notifier.clone(),
+            },
+        )))
+    });
+
+    // spawned before the database download and the health checks,
+    // so that the progress is reported as soon as the node serves the metrics
+    let bootstrap_progress_handle = tokio::spawn(bootstrap::bootstrap_progress_loop(Arc::new(
+        bootstrap::BootstrapProgressPoller {
+            ec2_manager: ec2_manager.clone(),
+            cw_manager: cw_manager.clone(),
+            cw_namespace: spec
+                .aws_resources
+                .as_ref()
+                .and_then(|r| r.cloudwatch_avalanche_metrics_namespace.clone())
+                .unwrap_or_else(|| format!("{}-avalanche", id)),
+            s3_manager: s3_manager.clone(),
+            s3_bucket: s3_bucket.clone(),
+            id: id.clone(),
+            local_node: local_node.clone(),
+            network_id: spec.avalanchego_config.network_id,
+            reporter: reporter.clone(),
+            interval_seconds: spec
+                .aws_resources
+                .as_ref()
+                .and_then(|r| r.cloudwatch_telemetry_interval_seconds)
+                .unwrap_or(cloudwatch::DEFAULT_TELEMETRY_INTERVAL_SECONDS),
+        },
+    )));
+
     // "63.65 GB" .tar.gz download  takes about 45-min
     // "63.65 GB" .tar.gz unpack    takes about 7-min
     // "75.47 GB" .tar    unarchive takes about 5-min
@@ -427,18 +670,25 @@ pub async fn execute(log_level: &str) {
             && aws_resources.db_backup_s3_key.is_some()
         {
             info!("STEP: publishing node information before db backup downloads");
-            let s3_key = {
-                if matches!(node_kind, node::Kind::Anchor) {
+            let s3_key = match node_kind {
+                node::Kind::Anchor => {
                     avalanche_ops_aws::StorageNamespace::DiscoverProvisioningAnchorNode(
                         id.clone(),
                         local_node.clone(),
                     )
-                } else {
+                }
+                node::Kind::NonAnchor => {
                     avalanche_ops_aws::StorageNamespace::DiscoverProvisioningNonAnchorNode(
                         id.clone(),
                         local_node.clone(),
                     )
                 }
+                node::Kind::Api => {
+                    avalanche_ops_aws::StorageNamespace::DiscoverProvisioningApiNode(
+                        id.clone(),
+                        local_node.clone(),
+                    )
+                }
             }
             .encode();
             let node_info = avalanche_ops_aws::NodeInfo::new(
@@ -474,21 +724,30 @@ pub async fn execute(log_level: &str) {
             let db_backup_s3_manager = s3::Manager::new(&db_backup_s3_config);
 
             // do not store in "tmp", will run out of space
-            let download_path = format!(
-                "{}/{}{}",
-                spec.avalanchego_config.db_dir,
-                random::string(10),
-                dec.ext()
-            );
-
-            s3::spawn_get_object(
-                db_backup_s3_manager.clone(),
-                &db_backup_s3_bucket,
-                &db_backup_s3_key,
-                &download_path,
-            )
-            .await
-            .expect("failed s3::spawn_get_object");
+            // fixed file name so that the download resumes after the agent restarts
+            let download_path =
+                format!("{}/db-backup{}", spec.avalanchego_config.db_dir, dec.ext());
+
+            // log every 5% rather than every part of the tens of GB backup
+            let logged_percent = Arc::new(AtomicU64::new(0));
+            let opts = s3::artifact::TransferOptions {
+                progress: Some(Arc::new(move |p: s3::artifact::Progress| {
+                    let percent = (p.percent() as u64) / 5 * 5;
+                    if logged_percent.fetch_max(percent, Ordering::Relaxed) < percent {
+                        info!("downloaded {}% of database backup", percent);
+                    }
+                })),
+                ..Default::default()
+            };
+            db_backup_s3_manager
+                .get_artifact(
+                    &db_backup_s3_bucket,
+                    &db_backup_s3_key,
+                    &download_path,
+                    &opts,
+                )
+                .await
+                .expect("failed s3::Manager::get_artifact");
 
             compress::unpack_directory(&download_path, &spec.avalanchego_config.db_dir, dec)
                 .unwrap();
@@ -502,8 +761,10 @@ pub async fn execute(log_level: &str) {
         }
     }
 
+    // the cloned network reuses the genesis of the source network
     if spec.avalanchego_config.is_custom_network()
         && matches!(node_kind, node::Kind::Anchor)
+        && spec.clone_source.is_none()
         && spec.avalanchego_config.genesis.is_some()
         && !Path::new(&spec.avalanchego_config.clone().genesis.unwrap()).exists()
     {
@@ -559,7 +820,7 @@ pub async fn execute(log_level: &str) {
 
         // "initial_staked_funds" is reserved for locked P-chain balance
         // with "spec.generated_seed_private_key_with_locked_p_chain_balance"
-        let seed_priv_keys = spec.generated_seed_private_keys.unwrap();
+        let seed_priv_keys = spec.generated_seed_private_keys.clone().unwrap();
         let seed_priv_key = seed_priv_keys[0].clone();
         for obj in objects.iter() {
             let s3_key = obj.key().expect("unexpected None s3 object");
@@ -604,7 +865,7 @@ pub async fn execute(log_level: &str) {
     }
 
     if spec.avalanchego_config.is_custom_network()
-        && matches!(node_kind, node::Kind::NonAnchor)
+        && (!matches!(node_kind, node::Kind::Anchor) || spec.clone_source.is_some())
         && spec.avalanchego_config.genesis.is_some()
         && !Path::new(&spec.avalanchego_config.clone().genesis.unwrap()).exists()
     {
@@ -633,7 +894,7 @@ pub async fn execute(log_level: &str) {
 
     // mainnet/other pre-defined test nets have hard-coded anchor nodes
     // thus no need for anchor nodes
-    if spec.avalanchego_config.is_custom_network() && matches!(node_kind, node::Kind::NonAnchor) {
+    if spec.avalanchego_config.is_custom_network() && !matches!(node_kind, node::Kind::Anchor) {
         sleep(Duration::from_secs(1)).await;
         info!(
             "STEP: downloading anchor node information for network '{}'",
@@ -642,86 +903,67 @@ pub async fn execute(log_level: &str) {
 
         // "avalanche-ops" should always set up anchor nodes first
         // so here we assume anchor nodes are already set up
-        // and their information is already available via shared,
+        // and have registered themselves via shared,
         // remote storage for service discovery
-        // so that we block non-anchor nodes until anchor nodes are ready
+        // so that we block non-anchor nodes until anchor nodes are registered
         //
         // always send a new "list_objects" on remote storage
         // rather than relying on potentially stale (not via "spec")
         // in case the member lists for "anchor" nodes becomes stale
         // (e.g., machine replacement in "anchor" nodes ASG)
-        //
-        // TODO: handle stale anchor nodes by heartbeats timestamps
+        // the registrations without recent heartbeats are skipped
         let target_nodes = spec
             .machine
             .anchor_nodes
             .expect("unexpected None machine.anchor_nodes for custom network");
-        let s3_key = s3::append_slash(
-            &avalanche_ops_aws::StorageNamespace::DiscoverReadyAnchorNodesDir(id.clone()).encode(),
-        );
-        let mut objects: Vec<Object>;
+        let mut anchor_nodes: Vec<avalanche_ops_aws::Node>;
         loop {
             sleep(Duration::from_secs(20)).await;
 
-            objects = s3::spawn_list_objects(s3_manager.clone(), &s3_bucket, Some(s3_key.clone()))
-                .await
-                .expect("failed s3::spawn_list_objects");
+            anchor_nodes = match list_live_anchor_nodes(s3_manager.clone(), &s3_bucket, &id).await {
+                Ok(v) => v,
+                Err(e) => {
+                    warn!("failed to list anchor nodes {}, retrying...", e);
+                    continue;
+                }
+            };
             info!(
-                "{} anchor nodes are ready (expecting {} nodes)",
-                objects.len(),
+                "{} anchor nodes are registered (expecting {} nodes)",
+                anchor_nodes.len(),
                 target_nodes
             );
-            if objects.len() as u32 >= target_nodes {
+            if anchor_nodes.len() as u32 >= target_nodes {
                 break;
             }
         }
 
         info!("STEP: updating bootstrap IPs/IDs with all anchor nodes");
-        let mut bootstrap_ips: Vec<String> = vec![];
-        let mut bootstrap_ids: Vec<String> = vec![];
-        for obj in objects.iter() {
-            let s3_key = obj.key().expect("unexpected None s3 object");
+        let (bootstrap_ips, bootstrap_ids) =
+            avalanche_ops_aws::bootstrap_flags(&anchor_nodes, spec.avalanchego_config.staking_port);
+        info!("found {} bootstrap nodes", anchor_nodes.len());
 
-            // just parse the s3 key name
-            // to reduce "s3_manager.get_object" call volume
-            let anchor_node = avalanche_ops_aws::StorageNamespace::parse_node_from_path(s3_key)
-                .expect("failed to parse node from storage path");
-
-            // assume all nodes in the network use the same ports
-            // ref. "avalanchego/config.StakingPortKey" default value is "9651"
-            let staking_port = spec.avalanchego_config.staking_port;
-            bootstrap_ips.push(format!("{}:{}", anchor_node.public_ip, staking_port));
-            bootstrap_ids.push(anchor_node.node_id);
-        }
-        info!("found {} bootstrap nodes", bootstrap_ids.len());
+        spec.avalanchego_config.bootstrap_ips = Some(bootstrap_ips);
+        spec.avalanchego_config.bootstrap_ids = Some(bootstrap_ids);
+    }
 
-        spec.avalanchego_config.bootstrap_ips = Some(bootstrap_ips.join(","));
-        spec.avalanchego_config.bootstrap_ids = Some(bootstrap_ids.join(","));
+    if matches!(node_kind, node::Kind::Api) {
+        if let Some(api_nodes) = &spec.machine.api_nodes {
+            info!("STEP: applying RPC config for API node");
+            api_nodes.apply_rpc_config(&mut spec.avalanchego_config, &mut spec.coreth_config);
+        }
     }
 
     let log_dir = spec.avalanchego_config.clone().log_dir;
     fs::create_dir_all(&log_dir).expect("failed to create log_dir");
 
-    let chain_config_dir = spec.avalanchego_config.clone().chain_config_dir;
-    fs::create_dir_all(Path::new(&chain_config_dir).join("C"))
-        .expect("failed to create dir for chain config");
-
+    let chain_config_manager = ChainConfigManager::from_config(&spec.avalanchego_config);
     info!(
         "STEP: saving coreth evm config file to chain config dir {}",
-        chain_config_dir
-    );
-    let tmp_coreth_config_path = random::tmp_path(15, Some(".json")).unwrap();
-    let chain_config_dir = spec.avalanchego_config.clone().chain_config_dir;
-    let chain_config_c_path = Path::new(&chain_config_dir).join("C").join("config.json");
-    info!(
-        "saving coreth config file to {:?}",
-        chain_config_c_path.as_os_str()
+        chain_config_manager.chain_config_dir
     );
-    spec.coreth_config
-        .sync(&tmp_coreth_config_path)
-        .expect("failed to sync coreth_config");
-    fs::copy(&tmp_coreth_config_path, chain_config_c_path).expect("failed fs::copy");
-    fs::remove_file(&tmp_coreth_config_path).expect("failed fs::remove_file");
+    chain_config_manager
+        .write_chain_config("C", &spec.coreth_config, None)
+        .expect("failed to write coreth chain config");
 
     if spec.avalanchego_config.subnet_config_dir.is_some() {
         let subnet_config_dir = spec
@@ -763,48 +1005,17 @@ pub async fn execute(log_level: &str) {
         spec.avalanchego_config.clone().config_file.unwrap()
     );
 
-    // don't use "Type=notify"
-    // as "avalanchego" currently does not do anything specific to systemd
-    // ref. "expected that the service sends a notification message via sd_notify"
-    // ref. https://www.freedesktop.org/software/systemd/man/systemd.service.html
-    //
-    // NOTE: remove "StandardOutput" and "StandardError" since we already
-    // wildcard all log files in "/var/log/avalanche" (a lot of duplicates)
-    let avalanche_service_file_contents = format!(
-        "[Unit]
-Description=avalanche node
-
-[Service]
-Type=exec
-TimeoutStartSec=300
-Restart=always
-RestartSec=5s
-LimitNOFILE=40000
-ExecStart={} --config-file={}
-StandardOutput=append:/var/log/avalanche/avalanche.log
-StandardError=append:/var/log/avalanche/avalanche.log
-
-[Install]
-WantedBy=multi-user.target",
-        avalanche_bin_path,
-        spec.avalanchego_config.clone().config_file.unwrap(),
+    let restart_policy = spec.restart_policy.clone().unwrap_or_default();
+    let avalanche_service_file_contents = supervisor::unit_file_contents(
+        &avalanche_bin_path,
+        &spec.avalanchego_config.clone().config_file.unwrap(),
+        &restart_policy,
     );
-    let mut avalanche_service_file = tempfile::NamedTempFile::new().unwrap();
-    avalanche_service_file
-        .write_all(avalanche_service_file_contents.as_bytes())
-        .expect("failed write_all avalanche_service_file");
-    let avalanche_service_file_path = avalanche_service_file.path().to_str().unwrap();
-    fs::copy(
-        avalanche_service_file_path,
-        "/etc/systemd/system/avalanche.service",
-    )
-    .expect("failed to copy /etc/systemd/system/avalanche.service");
-    bash::run("sudo systemctl daemon-reload").expect("failed systemctl daemon-reload command");
-    bash::run("sudo systemctl disable avalanche.service")
-        .expect("failed systemctl disable command");
-    bash::run("sudo systemctl enable avalanche.service").expect("failed systemctl enable command");
-    bash::run("sudo systemctl restart --no-block avalanche.service")
-        .expect("failed systemctl restart command");
+    supervisor::write_unit(&avalanche_service_file_contents)
+        .expect("failed to write avalanche.service unit");
+    supervisor::enable().expect("failed systemctl enable command");
+    supervisor::restart().expect("failed systemctl restart command");
+    reporter.set_state(avalanche_ops_aws::BootstrapState::Bootstrapping);
 
     // this can take awhile if loaded from backups or syncing from peers
     info!("'avalanched run' all success -- now waiting for local node liveness check");
@@ -822,6 +1033,18 @@ WantedBy=multi-user.target",
         };
         if res.healthy.is_some() && res.healthy.unwrap() {
             info!("health/liveness check success for {}", instance_id);
+            notifier
+                .notify(
+                    notify::Event::NodeBootstrapped,
+                    Some(&local_node.node_id),
+                    &format!(
+                        "{} node {} is healthy at {}",
+                        node_kind.as_str(),
+                        instance_id,
+                        local_node.http_endpoint
+                    ),
+                )
+                .await;
             break;
         }
         warn!(
@@ -847,21 +1070,27 @@ WantedBy=multi-user.target",
     }
 
     info!("spawning async routines...");
-    let node_info_ready_s3_key = {
-        if matches!(node_kind, node::Kind::Anchor) {
-            avalanche_ops_aws::StorageNamespace::DiscoverReadyAnchorNode(
-                id.to_string(),
-                local_node.clone(),
-            )
-            .encode()
-        } else {
-            avalanche_ops_aws::StorageNamespace::DiscoverReadyNonAnchorNode(
-                id.to_string(),
-                local_node.clone(),
-            )
-            .encode()
-        }
+    let node_info_ready_s3_key = match node_kind {
+        node::Kind::Anchor => avalanche_ops_aws::StorageNamespace::DiscoverReadyAnchorNode(
+            id.to_string(),
+            local_node.clone(),
+        )
+        .encode(),
+        node::Kind::NonAnchor => avalanche_ops_aws::StorageNamespace::DiscoverReadyNonAnchorNode(
+            id.to_string(),
+            local_node.clone(),
+        )
+        .encode(),
+        node::Kind::Api => avalanche_ops_aws::StorageNamespace::DiscoverReadyApiNode(
+            id.to_string(),
+            local_node.clone(),
+        )
+        .encode(),
     };
+    let cw_namespace = aws_resources
+        .cloudwatch_avalanche_metrics_namespace
+        .clone()
+        .unwrap_or_else(|| format!("{}-avalanche", id));
     let mut handles = vec![
         tokio::spawn(publish_node_info_ready_loop(
             s3_manager.clone(),
@@ -875,21 +1104,123 @@ WantedBy=multi-user.target",
         )),
         tokio::spawn(fetch_metrics_loop(
             cw_manager.clone(),
-            Arc::new(
-                aws_resources
-                    .clone()
-                    .cloudwatch_avalanche_metrics_namespace
-                    .unwrap(),
-            ),
+            Arc::new(cw_namespace.clone()),
             Arc::new(local_node.http_endpoint.clone()),
+            Arc::new(spec.alert_rules.clone().unwrap_or_default()),
         )),
-        tokio::spawn(check_node_update_loop(
+        tokio::spawn(publish_telemetry_loop(
+            cw_manager.clone(),
+            Arc::new(cw_namespace.clone()),
+            Arc::new(local_node.http_endpoint.clone()),
+            Arc::new(local_node.node_id.clone()),
+            spec.avalanchego_config.network_id,
+            Arc::new(spec.avalanchego_config.db_dir.clone()),
+            aws_resources
+                .cloudwatch_telemetry_interval_seconds
+                .unwrap_or(cloudwatch::DEFAULT_TELEMETRY_INTERVAL_SECONDS),
+        )),
+        tokio::spawn(upgrade::upgrade_loop(Arc::new(upgrade::Upgrader {
+            s3_manager: s3_manager.clone(),
+            s3_bucket: s3_bucket.clone(),
+            id: id.clone(),
+            local_node: local_node.clone(),
+            network_id: spec.avalanchego_config.network_id,
+            avalanche_bin_path: avalanche_bin_path.clone(),
+            policy: spec.upgrade_policy.clone().unwrap_or_default(),
+            cw_manager: cw_manager.clone(),
+            cw_namespace,
+            notifier: notifier.clone(),
+        }))),
+        tokio::spawn(subnet::subnet_loop(Arc::new(subnet::SubnetInstaller {
+            s3_manager: s3_manager.clone(),
+            s3_bucket: s3_bucket.clone(),
+            install_s3_manager: install_s3_manager.clone(),
+            install_s3_bucket: install_s3_bucket.clone(),
+            id: id.clone(),
+            local_node: local_node.clone(),
+            avalanche_bin_path,
+            avalanchego_config_file: spec
+                .avalanchego_config
+                .config_file
+                .clone()
+                .expect("unexpected None avalanchego_config.config_file"),
+            health_check_timeout_seconds: spec
+                .upgrade_policy
+                .clone()
+                .unwrap_or_default()
+                .health_check_timeout_seconds,
+        }))),
+        tokio::spawn(config_update::config_update_loop(Arc::new(
+            config_update::ConfigUpdater {
+                s3_manager: s3_manager.clone(),
+                s3_bucket: s3_bucket.clone(),
+                id: id.clone(),
+                local_node: local_node.clone(),
+                avalanchego_config_file: spec
+                    .avalanchego_config
+                    .config_file
+                    .clone()
+                    .expect("unexpected None avalanchego_config.config_file"),
+            },
+        ))),
+        tokio::spawn(chaos::chaos_loop(Arc::new(chaos::ChaosAgent {
+            s3_manager: s3_manager.clone(),
+            s3_bucket: s3_bucket.clone(),
+            id: id.clone(),
+            machine_id: local_node.machine_id.clone(),
+            db_dir: spec.avalanchego_config.db_dir.clone(),
+        }))),
+        tokio::spawn(rotate_cert::rotate_cert_loop(Arc::new(
+            rotate_cert::CertRotator {
+                s3_manager: s3_manager.clone(),
+                s3_bucket: s3_bucket.clone(),
+                envelope: envelope.clone(),
+                dynamodb_manager: dynamodb::Manager::new(&home_config),
+                lock_table: lock_table.clone(),
+                id: id.clone(),
+                local_node: local_node.clone(),
+                tls_key_path: tls_key_path.clone(),
+                tls_cert_path: tls_cert_path.clone(),
+                health_check_timeout_seconds: spec
+                    .upgrade_policy
+                    .clone()
+                    .unwrap_or_default()
+                    .health_check_timeout_seconds,
+            },
+        ))),
+    ];
+    handles.push(register_handle);
+    handles.push(bootstrap_progress_handle);
+    handles.push(tokio::spawn(status::status_loop(reporter.clone())));
+    if let Some(handle) = status_handle {
+        handles.push(handle);
+    }
+    if let Some(handle) = termination_handle {
+        handles.push(handle);
+    }
+    handles.push(tokio::spawn(supervisor::supervise_loop(Arc::new(
+        restart_policy,
+    ))));
+    if redetect_public_ip {
+        handles.push(tokio::spawn(public_ip::redetect_loop(Arc::new(
+            public_ip::Redetector {
+                s3_manager: s3_manager.clone(),
+                s3_bucket: s3_bucket.clone(),
+                id: id.clone(),
+                local_node: local_node.clone(),
+                policy: public_ip_policy,
+            },
+        ))));
+    }
+    if spec.avalanchego_config.is_custom_network() && !matches!(node_kind, node::Kind::Anchor) {
+        handles.push(tokio::spawn(refresh_bootstrap_nodes_loop(
             s3_manager.clone(),
             Arc::new(s3_bucket.clone()),
             Arc::new(id.clone()),
-            Arc::new(avalanche_bin_path),
-        )),
-    ];
+            spec.avalanchego_config.clone(),
+        )));
+    }
+
     if aws_resources.db_backup_s3_bucket.is_some() {
         handles.push(tokio::spawn(print_backup_commands(
             Arc::new(aws_resources.db_backup_s3_region.clone().unwrap()),
@@ -900,36 +1231,221 @@ WantedBy=multi-user.target",
         )));
     }
 
+    // the agent install in the user data may have failed
+    // (e.g., the package repository was not reachable)
+    let agent_installed = cloudwatch::logs::is_agent_installed();
+    if aws_resources.install_cloudwatch_agent.unwrap_or(true) && !agent_installed {
+        warn!(
+            "CloudWatch agent not found at '{}', shipping the logs from avalanched",
+            cloudwatch::logs::DEFAULT_AGENT_CTL_PATH
+        );
+    }
+    if !aws_resources.install_cloudwatch_agent.unwrap_or(true) || !agent_installed {
+        handles.push(tokio::spawn(ship_logs_loop(
+            cw_manager.clone(),
+            Arc::new(log_collect_list),
+            Arc::new(instance_id.clone()),
+        )));
+    }
+
+    if matches!(node_kind, node::Kind::Api) {
+        info!("API node never stakes, skipping validator registration");
+    } else if let Some(registration) = spec.validator_registration.clone() {
+        if matches!(node_kind, node::Kind::NonAnchor) {
+            if rotate_cert::skips_validator_registration(
+                s3_manager.clone(),
+                &s3_bucket,
+                &id,
+                &local_node,
+            )
+            .await
+            {
+                info!(
+                    "rotated to {} without re-registration, skipping validator registration",
+                    local_node.node_id
+                );
+            } else {
+                let funding_key = spec
+                    .generated_seed_private_keys
+                    .clone()
+                    .and_then(|keys| keys.first().cloned())
+                    .expect("'validator_registration' without generated seed keys");
+                handles.push(tokio::spawn(register_validator_loop(
+                    s3_manager.clone(),
+                    Arc::new(s3_bucket.clone()),
+                    dynamodb::Manager::new(&home_config),
+                    Arc::new(lock_table.clone()),
+                    Arc::new(id.clone()),
+                    Arc::new(local_node.clone()),
+                    spec.avalanchego_config.network_id,
+                    Arc::new(funding_key),
+                    Arc::new(registration),
+                    notifier.clone(),
+                )));
+            }
+        }
+    }
+
+    let keystore_users = spec
+        .machine
+        .api_nodes
+        .as_ref()
+        .map(|v| v.keystore_users.clone())
+        .unwrap_or_default();
+    if matches!(node_kind, node::Kind::Api) && !keystore_users.is_empty() {
+        handles.push(tokio::spawn(keystore::provision_keystore_loop(Arc::new(
+            keystore::KeystoreProvisioner {
+                s3_manager: s3_manager.clone(),
+                s3_bucket: s3_bucket.clone(),
+                envelope: envelope.clone(),
+                id: id.clone(),
+                local_node: local_node.clone(),
+                users: keystore_users,
+                seed_private_keys: spec.generated_seed_private_keys.clone().unwrap_or_default(),
+            },
+        ))));
+    }
+
+    if let Some(archiver) = spec.index_archiver.clone() {
+        handles.push(tokio::spawn(index_archive::index_archive_loop(Arc::new(
+            index_archive::IndexArchiver {
+                s3_manager: s3_manager.clone(),
+                s3_bucket: s3_bucket.clone(),
+                id: id.clone(),
+                local_node: local_node.clone(),
+                archiver,
+            },
+        ))));
+    }
+
+    if let Some(data_volume) = spec.machine.data_volume.clone() {
+        if data_volume.snapshot_interval_seconds > 0 {
+            handles.push(tokio::spawn(snapshot_data_volume_loop(
+                ec2_manager.clone(),
+                Arc::new(id.clone()),
+                Arc::new(node_kind.as_str().to_string()),
+                Arc::new(instance_id.clone()),
+                Arc::new(data_volume),
+                reporter.clone(),
+            )));
+        }
+    }
+
     info!("STEP: blocking on handles via JoinHandle");
     for handle in handles {
         handle.await.expect("failed handle");
     }
 }
 
-/// if run in anchor nodes, the uploaded file will be downloaded
-/// in bootstrapping non-anchor nodes for custom networks
-async fn publish_node_info_ready_loop(
-    s3_manager: s3::Manager,
-    s3_bucket: Arc<String>,
-    s3_key: Arc<String>,
-    node_info: Arc<avalanche_ops_aws::NodeInfo>,
-) {
-    info!("STEP: starting 'publish_node_info_ready_loop'");
+/// Returns the CloudWatch agent log collect list for the avalanchego
+/// main and per-chain logs, one log stream per file.
+fn avalanchego_log_collect_list(
+    id: &str,
+    node_kind: &str,
+    log_dir: &str,
+    retention_in_days: u16,
+) -> Vec<cloudwatch::Collect> {
+    ["main", "C", "X", "P"]
+        .iter()
+        .map(|name| cloudwatch::Collect {
+            log_group_name: id.to_string(),
+            log_stream_name: format!("{{instance_id}}-{}-avalanchego-{}", node_kind, name),
+            file_path: format!("{}/{}.log", log_dir, name),
 
-    loop {
-        info!(
-            "STEP: posting node info ready for {}",
-            node_info.local_node.kind
-        );
+            // TODO: replace this with log rotation
+            auto_removal: Some(false),
 
-        let tmp_path = random::tmp_path(10, Some(".yaml")).expect("unexpected tmp_path failure");
-        node_info.sync(tmp_path.clone()).unwrap();
+            retention_in_days: Some(retention_in_days),
+            ..cloudwatch::Collect::default()
+        })
+        .collect()
+}
 
-        s3::spawn_put_object(
-            s3_manager.clone(),
-            &tmp_path,
-            s3_bucket.as_str(),
-            s3_key.as_str(),
+/// Tails the log files and ships them to CloudWatch Logs,
+/// for the instances without the CloudWatch agent.
+async fn ship_logs_loop(
+    cw_manager: cloudwatch::Manager,
+    collect_list: Arc<Vec<cloudwatch::Collect>>,
+    instance_id: Arc<String>,
+) {
+    info!(
+        "STEP: starting 'ship_logs_loop' for {} log file(s)",
+        collect_list.len()
+    );
+    let mut streams = Vec::new();
+    for c in collect_list.iter() {
+        // the CloudWatch agent replaces the placeholder
+        let log_stream_name = c
+            .log_stream_name
+            .replace("{instance_id}", instance_id.as_str());
+        if let Err(e) = cw_manager
+            .create_log_stream(&c.log_group_name, &log_stream_name)
+            .await
+        {
+            warn!("failed to create log stream {}, skipping...", e.message());
+            continue;
+        }
+        streams.push((
+            c.log_group_name.clone(),
+            log_stream_name,
+            cloudwatch::logs::FileTailer::new(&c.file_path),
+        ));
+    }
+
+    loop {
+        sleep(Duration::from_secs(10)).await;
+
+        for (log_group_name, log_stream_name, tailer) in streams.iter_mut() {
+            let lines = match tailer.read_lines() {
+                Ok(v) => v,
+                Err(e) => {
+                    warn!("failed to read '{}' {}", tailer.file_path, e);
+                    continue;
+                }
+            };
+            if lines.is_empty() {
+                continue;
+            }
+            // lines are dropped on failures, not to block the newer ones
+            if let Err(e) = cw_manager
+                .put_log_events(log_group_name, log_stream_name, &lines)
+                .await
+            {
+                warn!(
+                    "failed to ship {} line(s) from '{}' {}",
+                    lines.len(),
+                    tailer.file_path,
+                    e.message()
+                );
+            }
+        }
+    }
+}
+
+/// if run in anchor nodes, the uploaded file will be downloaded
+/// in bootstrapping non-anchor nodes for custom networks
+async fn publish_node_info_ready_loop(
+    s3_manager: s3::Manager,
+    s3_bucket: Arc<String>,
+    s3_key: Arc<String>,
+    node_info: Arc<avalanche_ops_aws::NodeInfo>,
+) {
+    info!("STEP: starting 'publish_node_info_ready_loop'");
+
+    loop {
+        info!(
+            "STEP: posting node info ready for {}",
+            node_info.local_node.kind
+        );
+
+        let tmp_path = random::tmp_path(10, Some(".yaml")).expect("unexpected tmp_path failure");
+        node_info.sync(tmp_path.clone()).unwrap();
+
+        s3::spawn_put_object(
+            s3_manager.clone(),
+            &tmp_path,
+            s3_bucket.as_str(),
+            s3_key.as_str(),
         )
         .await
         .expect("failed s3::spawn_put_object");
@@ -941,184 +1457,845 @@ async fn publish_node_info_ready_loop(
     }
 }
 
-async fn fetch_metrics_loop(
-    cw_manager: cloudwatch::Manager,
-    cw_namespace: Arc<String>,
-    metrics_ep: Arc<String>,
-) {
-    info!("STEP: starting 'fetch_metrics_loop' with initial 2-minute wait");
-    sleep(Duration::from_secs(120)).await;
+/// Uploads the staking certificate, and the key envelope-encrypted,
+/// to "PkiKeyDir" under the instance ID, overwriting the previous ones.
+pub(super) async fn upload_staking_certs(
+    s3_manager: s3::Manager,
+    envelope: envelope::Envelope,
+    s3_bucket: &str,
+    id: &str,
+    instance_id: &str,
+    tls_key_path: &str,
+    tls_cert_path: &str,
+) -> io::Result<()> {
+    let pki_key_dir = avalanche_ops_aws::StorageNamespace::PkiKeyDir(id.to_string()).encode();
+    s3::spawn_put_object(
+        s3_manager.clone(),
+        tls_cert_path,
+        s3_bucket,
+        &format!("{}/{}.crt", pki_key_dir, instance_id),
+    )
+    .await?;
+    upload_sealed(
+        s3_manager,
+        envelope,
+        s3_bucket,
+        tls_key_path,
+        &format!(
+            "{}/{}.key.zstd.seal_aes_256.encrypted",
+            pki_key_dir, instance_id
+        ),
+    )
+    .await
+}
 
-    let mut prev_raw_metrics: Option<avalanchego_metrics::RawMetrics> = None;
-    loop {
-        info!("STEP: fetching metrics in 1-min");
-        sleep(Duration::from_secs(60)).await;
+/// Uploads the BLS signer key envelope-encrypted to "PkiKeyDir"
+/// under the instance ID, next to the staking certificate.
+async fn upload_signer_key(
+    s3_manager: s3::Manager,
+    envelope: envelope::Envelope,
+    s3_bucket: &str,
+    id: &str,
+    instance_id: &str,
+    signer_key_path: &str,
+) -> io::Result<()> {
+    let pki_key_dir = avalanche_ops_aws::StorageNamespace::PkiKeyDir(id.to_string()).encode();
+    upload_sealed(
+        s3_manager,
+        envelope,
+        s3_bucket,
+        signer_key_path,
+        &format!(
+            "{}/{}.signer.key.zstd.seal_aes_256.encrypted",
+            pki_key_dir, instance_id
+        ),
+    )
+    .await
+}
 
-        let cur_metrics = match api_metrics::spawn_get(metrics_ep.as_str()).await {
-            Ok(v) => v,
-            Err(e) => {
-                warn!("failed to fetch metrics {}, retrying...", e);
-                continue;
-            }
-        };
+/// Compresses and envelope-encrypts the file, and uploads it to the S3 key.
+async fn upload_sealed(
+    s3_manager: s3::Manager,
+    envelope: envelope::Envelope,
+    s3_bucket: &str,
+    file_path: &str,
+    s3_key: &str,
+) -> io::Result<()> {
+    let tmp_compressed_path = random::tmp_path(15, Some(".zstd"))?;
+    let tmp_encrypted_path = random::tmp_path(15, Some(".zstd.encrypted"))?;
+    compress::pack_file(file_path, &tmp_compressed_path, compress::Encoder::Zstd(3))?;
+    envelope::spawn_seal_aes_256_file(envelope, &tmp_compressed_path, &tmp_encrypted_path).await?;
+    s3::spawn_put_object(s3_manager, &tmp_encrypted_path, s3_bucket, s3_key).await?;
+
+    fs::remove_file(tmp_compressed_path)?;
+    fs::remove_file(tmp_encrypted_path)?;
+    Ok(())
+}
 
-        match cloudwatch::spawn_put_metric_data(
-            cw_manager.clone(),
-            cw_namespace.as_str(),
-            api_metrics::to_cw_metric_data(&cur_metrics, prev_raw_metrics.clone()),
-        )
-        .await
-        {
-            Ok(_) => {}
-            Err(e) => {
-                warn!("failed to put metric data {}, retrying...", e);
-                prev_raw_metrics = Some(cur_metrics.clone());
-                continue;
-            }
+/// Downloads the staking certificate and key of the source node, that
+/// "avalanched volume provision" assigned to the instance with the data volume.
+/// Returns false if no source node is assigned (e.g., all were claimed).
+async fn download_clone_staking_certs(
+    s3_manager: s3::Manager,
+    envelope: envelope::Envelope,
+    s3_bucket: &str,
+    id: &str,
+    instance_id: &str,
+    tls_key_path: &str,
+    tls_cert_path: &str,
+) -> io::Result<bool> {
+    let assignment_key = avalanche_ops_aws::StorageNamespace::CloneAssignment(
+        id.to_string(),
+        instance_id.to_string(),
+    )
+    .encode();
+    let claim = match upgrade::get_object(s3_manager.clone(), s3_bucket, &assignment_key).await? {
+        Some(d) => avalanche_ops_aws::CloneClaim::decode_yaml(&d)?,
+        None => {
+            warn!(
+                "no clone assignment for '{}', generating new certs",
+                instance_id
+            );
+            return Ok(false);
         }
+    };
+    info!(
+        "restoring node ID {} of source node '{}'",
+        claim.source.node_id, claim.source.machine_id
+    );
 
-        prev_raw_metrics = Some(cur_metrics.clone());
+    let pki_key_dir = avalanche_ops_aws::StorageNamespace::ClonePkiKeyDir(id.to_string()).encode();
+    let tmp_cert_path = random::tmp_path(15, Some(".crt"))?;
+    s3::spawn_get_object(
+        s3_manager.clone(),
+        s3_bucket,
+        &format!("{}/{}.crt", pki_key_dir, claim.source.machine_id),
+        &tmp_cert_path,
+    )
+    .await?;
+
+    let tmp_encrypted_path = random::tmp_path(15, Some(".zstd.encrypted"))?;
+    let tmp_compressed_path = random::tmp_path(15, Some(".zstd"))?;
+    let tmp_key_path = random::tmp_path(15, Some(".key"))?;
+    s3::spawn_get_object(
+        s3_manager,
+        s3_bucket,
+        &format!(
+            "{}/{}.key.zstd.seal_aes_256.encrypted",
+            pki_key_dir, claim.source.machine_id
+        ),
+        &tmp_encrypted_path,
+    )
+    .await?;
+    envelope::spawn_unseal_aes_256_file(envelope, &tmp_encrypted_path, &tmp_compressed_path)
+        .await?;
+    compress::unpack_file(&tmp_compressed_path, &tmp_key_path, compress::Decoder::Zstd)?;
+
+    for p in [tls_key_path, tls_cert_path] {
+        if let Some(parent_dir) = Path::new(p).parent() {
+            fs::create_dir_all(parent_dir)?;
+        }
+    }
+    fs::copy(&tmp_key_path, tls_key_path)?;
+    fs::copy(&tmp_cert_path, tls_cert_path)?;
+
+    for p in [
+        tmp_cert_path,
+        tmp_encrypted_path,
+        tmp_compressed_path,
+        tmp_key_path,
+    ] {
+        fs::remove_file(p)?;
     }
+    Ok(true)
 }
 
-async fn check_node_update_loop(
+/// Uploads the node registration, and re-uploads on the interval
+/// as heartbeats (the S3 object last modified time).
+async fn register_node_loop(
     s3_manager: s3::Manager,
     s3_bucket: Arc<String>,
-    id: Arc<String>,
-    avalanche_bin_path: Arc<String>,
+    s3_key: Arc<String>,
+    registration: Arc<avalanche_ops_aws::Registration>,
+    terminating: Arc<AtomicBool>,
 ) {
-    info!("STEP: starting 'check_node_update_loop'");
+    info!("STEP: starting 'register_node_loop'");
 
+    let contents = registration
+        .encode_yaml()
+        .expect("failed to encode Registration");
     loop {
-        info!("sleeping 3-min for 'check_node_update_loop'");
-        sleep(Duration::from_secs(180)).await;
+        if terminating.load(Ordering::SeqCst) {
+            info!("node terminating, stopping registration heartbeats");
+            return;
+        }
+        let tmp_path = random::tmp_path(10, Some(".yaml")).expect("unexpected tmp_path failure");
+        fs::write(&tmp_path, &contents).expect("failed fs::write");
 
-        info!("STEP: checking update artifacts event key");
-        let objects = match s3::spawn_list_objects(
+        match s3::spawn_put_object(
             s3_manager.clone(),
+            &tmp_path,
             s3_bucket.as_str(),
-            Some(
-                avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsEvent(id.to_string())
-                    .encode(),
-            ),
+            s3_key.as_str(),
         )
         .await
         {
-            Ok(v) => v,
-            Err(e) => {
-                warn!("failed s3::spawn_list_objects {}, retrying...", e);
-                continue;
-            }
-        };
+            Ok(_) => info!("registered node {}", registration.node.node_id),
+            Err(e) => warn!("failed to register node {}, retrying...", e.message()),
+        }
+        fs::remove_file(&tmp_path).expect("failed fs::remove_file");
+
+        sleep(Duration::from_secs(
+            avalanche_ops_aws::REGISTRATION_INTERVAL_SECONDS,
+        ))
+        .await;
+    }
+}
 
-        if objects.is_empty() {
-            warn!("no event key found");
+/// Allows the public IP of the node to the staking port of the security groups
+/// in all regions, if "ingress_policy" restricts the staking port,
+/// so that the replaced node (i.e., new public IP) reaches its peers without
+/// re-running "apply", which keeps the rules of the running nodes.
+async fn allow_staking_ingress(spec: &avalanche_ops_aws::Spec, public_ip: &str) -> io::Result<()> {
+    let restricted = spec
+        .ingress_policy
+        .as_ref()
+        .map(|p| p.staking_ipv4_ranges.is_some())
+        .unwrap_or(false);
+    // the rules are IPv4 only
+    if !restricted || public_ip.parse::<Ipv4Addr>().is_err() {
+        return Ok(());
+    }
+
+    let cidr = avalanche_ops_aws::host_cidr(public_ip);
+    let aws_resources = spec.aws_resources.clone().unwrap_or_default();
+    for (region, security_group_id) in aws_resources.security_groups() {
+        info!(
+            "allowing {} to the staking port of '{}' in {}",
+            cidr, security_group_id, region
+        );
+        let shared_config = aws::load_config(Some(region)).await?;
+        ec2::Manager::new(&shared_config)
+            .authorize_ingress_tcp(
+                &security_group_id,
+                spec.avalanchego_config.staking_port,
+                &[cidr.clone()],
+            )
+            .await
+            .map_err(|e| Error::new(ErrorKind::Other, e.message()))?;
+    }
+    Ok(())
+}
+
+/// Issues the "AddValidatorTx" for the local node once bootstrapped,
+/// and records the tx ID in "StorageNamespace::ValidatorRegistrationStatus".
+/// Retries until the tx is issued, or the node is found to be
+/// a current or pending validator already (e.g., "avalanched" restarts).
+/// The nodes take turns on the DynamoDB lock, and each holds it until
+/// its tx is committed, so no two nodes spend the same seed key UTXOs.
+#[allow(clippy::too_many_arguments)]
+async fn register_validator_loop(
+    s3_manager: s3::Manager,
+    s3_bucket: Arc<String>,
+    dynamodb_manager: dynamodb::Manager,
+    lock_table: Arc<String>,
+    id: Arc<String>,
+    local_node: Arc<avalanche_ops_aws::Node>,
+    network_id: u32,
+    funding_key: Arc<soft_key::PrivateKeyInfo>,
+    registration: Arc<avalanche_ops_aws::ValidatorRegistration>,
+    notifier: Arc<notify::Notifier>,
+) {
+    info!("STEP: starting 'register_validator_loop'");
+
+    let lock_id = avalanche_ops_aws::registration_lock_id(&id);
+    let status = loop {
+        // renews the lease for each attempt
+        let lock = dynamodb::Lock::new(
+            &lock_id,
+            &local_node.node_id,
+            "validator-registration",
+            Duration::from_secs(avalanche_ops_aws::REGISTRATION_LOCK_LEASE_SECONDS),
+        );
+        if let Err(e) = dynamodb_manager.acquire_lock(&lock_table, &lock).await {
+            info!("waiting for the registration lock ({})", e.message());
+            sleep(Duration::from_secs(30 + random::u64() % 30)).await;
             continue;
         }
 
-        let obj = objects[0].clone();
-        let last_modified = obj.last_modified.unwrap();
-        let last_modified_unix = last_modified.as_secs_f64();
+        let ret =
+            match register_validator(&local_node, network_id, &funding_key, &registration).await {
+                // the next node must not fetch the UTXOs until this tx spent them
+                Ok(Some(status)) => wait_for_commit(&local_node.http_endpoint, &status.tx_id)
+                    .await
+                    .map(|_| Some(status)),
+                v => v,
+            };
+        if let Err(e) = dynamodb_manager.release_lock(&lock_table, &lock).await {
+            // expires after the lease
+            warn!("failed to release the registration lock ({})", e.message());
+        }
+        match ret {
+            Ok(Some(status)) => break status,
+            Ok(None) => {
+                info!(
+                    "{} is already a validator, skipping registration",
+                    local_node.node_id
+                );
+                return;
+            }
+            Err(e) => warn!("failed to register validator ({}), retrying...", e),
+        }
+        sleep(Duration::from_secs(60)).await;
+    };
+
+    let contents = status
+        .encode_yaml()
+        .expect("failed to encode ValidatorRegistrationStatus");
+    let s3_key = avalanche_ops_aws::StorageNamespace::ValidatorRegistrationStatus(
+        id.to_string(),
+        local_node.node_id.clone(),
+    )
+    .encode();
+    loop {
+        let tmp_path = random::tmp_path(10, Some(".yaml")).expect("unexpected tmp_path failure");
+        fs::write(&tmp_path, &contents).expect("failed fs::write");
+        let ret =
+            s3::spawn_put_object(s3_manager.clone(), &tmp_path, s3_bucket.as_str(), &s3_key).await;
+        fs::remove_file(&tmp_path).expect("failed fs::remove_file");
+        match ret {
+            Ok(_) => {
+                info!(
+                    "recorded validator registration tx {} for {}",
+                    status.tx_id, status.node_id
+                );
+                notifier
+                    .notify(
+                        notify::Event::ValidatorAdded,
+                        Some(&status.node_id),
+                        &format!(
+                            "issued AddValidatorTx {} staking {} until {}",
+                            status.tx_id, status.stake_amount, status.end
+                        ),
+                    )
+                    .await;
+                return;
+            }
+            Err(e) => warn!(
+                "failed to record validator registration ({}), retrying...",
+                e.message()
+            ),
+        }
+        sleep(Duration::from_secs(30)).await;
+    }
+}
+
+/// Waits until the P-chain tx is committed, or fails if dropped.
+async fn wait_for_commit(http_ep: &str, tx_id: &str) -> io::Result<()> {
+    let started = Instant::now();
+    loop {
+        sleep(Duration::from_secs(5)).await;
+        let status = api_p::get_tx_status(http_ep, tx_id)
+            .await?
+            .result
+            .unwrap_or_default();
+        if status.is_committed() {
+            info!("tx {} committed", tx_id);
+            return Ok(());
+        }
+        if status.is_dropped() {
+            return Err(Error::new(
+                ErrorKind::Other,
+                format!(
+                    "tx {} dropped ({})",
+                    tx_id,
+                    status.reason.unwrap_or_default()
+                ),
+            ));
+        }
+        // within the lock lease
+        if started.elapsed() >= Duration::from_secs(300) {
+            return Err(Error::new(
+                ErrorKind::TimedOut,
+                format!(
+                    "tx {} not committed within 300 seconds (status '{}')",
+                    tx_id, status.status
+                ),
+            ));
+        }
+        info!("tx {} status '{}', waiting...", tx_id, status.status);
+    }
+}
+
+/// Returns "None" if the node is already a current or pending validator.
+async fn register_validator(
+    local_node: &avalanche_ops_aws::Node,
+    network_id: u32,
+    funding_key: &soft_key::PrivateKeyInfo,
+    registration: &avalanche_ops_aws::ValidatorRegistration,
+) -> io::Result<Option<avalanche_ops_aws::ValidatorRegistrationStatus>> {
+    let http_ep = local_node.http_endpoint.as_str();
+
+    let bootstrapped = api_info::is_bootstrapped(http_ep, "P")
+        .await?
+        .result
+        .map(|r| r.bootstrapped)
+        .unwrap_or(false);
+    if !bootstrapped {
+        return Err(Error::new(
+            ErrorKind::Other,
+            "P-chain is not bootstrapped yet",
+        ));
+    }
 
-        let now = SystemTime::now();
-        let now_unix = now
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .expect("unexpected None duration_since")
-            .as_secs() as f64;
+    let node_id = ids::NodeId::from_str(&local_node.node_id)?;
+    let is_validator = |validators: Option<Vec<platformvm_api::ApiPrimaryValidator>>| {
+        validators
+            .unwrap_or_default()
+            .iter()
+            .any(|v| v.node_id == Some(node_id))
+    };
+    let current = api_p::get_current_validators(http_ep).await?;
+    if is_validator(current.result.and_then(|r| r.validators)) {
+        return Ok(None);
+    }
+    let pending = api_p::get_pending_validators(http_ep).await?;
+    if is_validator(pending.result.and_then(|r| r.validators)) {
+        return Ok(None);
+    }
 
-        // requested for the last 6-min
-        // TODO: can this be smarter...?
-        let needs_update = (now_unix - last_modified_unix) < 360_f64;
+    let key = soft_key::Key::from_private_key(&funding_key.private_key)?;
+    let p_address = key.address("P", network_id)?;
+    let tx_fee = api_info::get_tx_fee(http_ep)
+        .await?
+        .result
+        .map(|r| r.tx_fee)
+        .unwrap_or_default();
+    let needed = registration
+        .stake_amount
+        .checked_add(units::Avax::from_navax(tx_fee))
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "stake amount overflows u64"))?;
+    let unlocked = api_p::get_balance(http_ep, &p_address)
+        .await?
+        .result
+        .and_then(|r| r.unlocked)
+        .unwrap_or_default();
+    if unlocked < needed.as_navax() {
+        return Err(Error::new(
+            ErrorKind::Other,
+            format!(
+                "insufficient unlocked balance {} on {} (needed {})",
+                units::Avax::from_navax(unlocked),
+                p_address,
+                needed
+            ),
+        ));
+    }
+
+    let avax_asset_id = api_x::get_asset_description(http_ep, "AVAX")
+        .await?
+        .result
+        .map(|r| r.asset_id)
+        .ok_or_else(|| Error::new(ErrorKind::Other, "AVAX asset description not found"))?;
+    let utxos = api_p::get_utxos(http_ep, &p_address).await?;
+    let utxos = match utxos.result {
+        Some(r) => r.decode_utxos()?,
+        None => Vec::new(),
+    };
+
+    let rewards_to = match &registration.reward_address {
+        Some(addr) => {
+            formatting::address::decode_for(addr, formatting::address::Chain::P, network_id)?
+        }
+        None => key.short_address.clone(),
+    };
+
+    // the start time must be in the future when the tx is accepted
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("unexpected None duration_since")
+        .as_secs();
+    let start = now + 120;
+    let end = start + registration.staking_period_seconds;
+
+    let builder = platformvm::txs::add_validator::Builder {
+        network_id,
+        avax_asset_id,
+        tx_fee: units::Avax::from_navax(tx_fee),
+        keychain: soft_key::Keychain::new(vec![key.clone()]),
+    };
+    let (mut tx, signers) = builder.new_add_validator_tx(
+        &utxos,
+        platformvm::Validator {
+            node_id: node_id.short_id(),
+            start,
+            end,
+            weight: registration.stake_amount.as_navax(),
+        },
+        secp256k1fx::OutputOwners::new(0, 1, &[rewards_to]),
+        registration.delegation_fee_shares,
+        &key.short_address,
+        now,
+    )?;
+    let (signed_bytes, tx_id) = tx.sign(&signers)?;
+
+    let resp = api_p::issue_tx(
+        http_ep,
+        &formatting::encode_cb58_with_checksum(&signed_bytes),
+        "cb58",
+    )
+    .await?;
+    let issued = resp
+        .result
+        .map(|r| r.tx_id)
+        .ok_or_else(|| Error::new(ErrorKind::Other, "platform.issueTx returned no tx ID"))?;
+    if issued != tx_id {
+        warn!(
+            "issued tx ID {} differs from the signed tx ID {}",
+            issued, tx_id
+        );
+    }
+    info!(
+        "issued AddValidatorTx {} for {} staking {} from {}",
+        issued, local_node.node_id, registration.stake_amount, p_address
+    );
+
+    Ok(Some(avalanche_ops_aws::ValidatorRegistrationStatus {
+        node_id: local_node.node_id.clone(),
+        tx_id: issued.to_string(),
+        stake_amount: registration.stake_amount,
+        start,
+        end,
+    }))
+}
+
+/// Associates the Elastic IP of the anchor node with the local instance,
+/// and publishes the DNS record of the anchor node (if any).
+/// Returns the Elastic IP address.
+async fn claim_anchor_endpoint(
+    ec2_manager: &ec2::Manager,
+    route53_manager: &route53::Manager,
+    id: &str,
+    instance_id: &str,
+    anchor_endpoints: &avalanche_ops_aws::AnchorEndpoints,
+) -> io::Result<String> {
+    let tags = avalanche_ops_aws::AnchorEndpoints::tags(id);
+    for attempt in 1..=MAX_ANCHOR_EIP_CLAIM_ATTEMPTS {
+        let eips = ec2_manager.list_eips(&tags).await?;
+        let eip = match ec2::eip::select_for_instance(&eips, instance_id) {
+            Some(v) => v.clone(),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    format!("no unclaimed Elastic IP among {} for {}", eips.len(), id),
+                ));
+            }
+        };
+
+        if eip.instance_id.as_deref() != Some(instance_id) {
+            // fails if the other anchor node claimed it first
+            if let Err(e) = ec2_manager
+                .associate_eip(&eip.allocation_id, instance_id)
+                .await
+            {
+                warn!(
+                    "failed to claim Elastic IP {} ({}), retrying...",
+                    eip.public_ip,
+                    e.message()
+                );
+                sleep(Duration::from_secs(attempt * 5 + random::u64() % 10)).await;
+                continue;
+            }
+        }
         info!(
-            "last_modified_unix {}, now_unix {} [needs update: {}]",
-            last_modified_unix, now_unix, needs_update
+            "claimed Elastic IP {} of anchor node {:?}",
+            eip.public_ip, eip.anchor_index
         );
 
-        if !needs_update {
-            warn!(
-                "update artifacts event not found (seeing {} objects)",
-                objects.len()
-            );
+        if let (Some(hosted_zone_id), Some(name)) = (
+            &anchor_endpoints.route53_hosted_zone_id,
+            anchor_endpoints.dns_name(eip.anchor_index.unwrap_or_default()),
+        ) {
+            route53_manager
+                .upsert_a_record(
+                    hosted_zone_id,
+                    &name,
+                    &eip.public_ip,
+                    anchor_endpoints.dns_ttl_seconds,
+                )
+                .await?;
+        }
+        return Ok(eip.public_ip);
+    }
+    Err(Error::new(
+        ErrorKind::Other,
+        format!(
+            "failed to claim Elastic IP after {} attempts",
+            MAX_ANCHOR_EIP_CLAIM_ATTEMPTS
+        ),
+    ))
+}
+
+/// Lists the anchor nodes with the recent registration heartbeats.
+/// Only parses the S3 key names to reduce the "get_object" call volume.
+async fn list_live_anchor_nodes(
+    s3_manager: s3::Manager,
+    s3_bucket: &str,
+    id: &str,
+) -> io::Result<Vec<avalanche_ops_aws::Node>> {
+    let s3_key = s3::append_slash(
+        &avalanche_ops_aws::StorageNamespace::DiscoverRegisteredNodesDir(id.to_string()).encode(),
+    );
+    let objects = s3::spawn_list_objects(s3_manager, s3_bucket, Some(s3_key)).await?;
+
+    let registered: Vec<(String, i64)> = objects
+        .iter()
+        .filter_map(|obj| match (obj.key(), obj.last_modified()) {
+            (Some(k), Some(ts)) => Some((k.to_string(), ts.secs())),
+            _ => None,
+        })
+        .collect();
+    let now_unix = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("unexpected SystemTime")
+        .as_secs() as i64;
+    avalanche_ops_aws::live_anchor_nodes(
+        &registered,
+        now_unix,
+        avalanche_ops_aws::REGISTRATION_TTL_SECONDS,
+    )
+}
+
+/// Polls the anchor node set, and updates the avalanchego config file
+/// when the anchor nodes are replaced, so that the next restart
+/// bootstraps from the live anchor nodes.
+async fn refresh_bootstrap_nodes_loop(
+    s3_manager: s3::Manager,
+    s3_bucket: Arc<String>,
+    id: Arc<String>,
+    mut avalanchego_config: avalanchego_config::Config,
+) {
+    info!("STEP: starting 'refresh_bootstrap_nodes_loop'");
+
+    loop {
+        sleep(Duration::from_secs(
+            avalanche_ops_aws::REGISTRATION_INTERVAL_SECONDS,
+        ))
+        .await;
+
+        let anchor_nodes =
+            match list_live_anchor_nodes(s3_manager.clone(), s3_bucket.as_str(), id.as_str()).await
+            {
+                Ok(v) => v,
+                Err(e) => {
+                    warn!("failed to list anchor nodes {}, retrying...", e);
+                    continue;
+                }
+            };
+        if anchor_nodes.is_empty() {
+            warn!("no live anchor node found, keeping the current bootstrap nodes");
             continue;
         }
 
-        info!("STEP: downloading avalanched binary from S3");
+        let (bootstrap_ips, bootstrap_ids) =
+            avalanche_ops_aws::bootstrap_flags(&anchor_nodes, avalanchego_config.staking_port);
+        // reload, since "subnet_loop" may have updated the tracked subnets
+        if let Some(config_file) = avalanchego_config.config_file.clone() {
+            match avalanchego_config::Config::load(&config_file) {
+                Ok(v) => avalanchego_config = v,
+                Err(e) => warn!("failed to reload avalanchego config file {}", e),
+            }
+        }
+        if avalanchego_config.bootstrap_ids.as_deref() == Some(bootstrap_ids.as_str())
+            && avalanchego_config.bootstrap_ips.as_deref() == Some(bootstrap_ips.as_str())
+        {
+            continue;
+        }
 
-        // TODO: replace "avalanched" itself?
-        // TODO: fs::copy fails with 'Os { code: 26, kind: ExecutableFileBusy, message: "Text file busy" }'
-        // can't replace the process itself...
+        info!(
+            "anchor nodes changed from {:?} to {}, updating the config file",
+            avalanchego_config.bootstrap_ids, bootstrap_ids
+        );
+        avalanchego_config.bootstrap_ips = Some(bootstrap_ips);
+        avalanchego_config.bootstrap_ids = Some(bootstrap_ids);
+        if let Err(e) = avalanchego_config.sync(None) {
+            warn!("failed to sync avalanchego config file {}", e);
+        }
+    }
+}
 
-        info!("STEP: downloading avalanche binary from S3");
-        let tmp_avalanche_bin_compressed_path = random::tmp_path(15, Some(".zstd")).unwrap();
-        s3::spawn_get_object(
-                    s3_manager.clone(),
-                    &s3_bucket,
-                    &avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsInstallDirAvalancheBinCompressed(id.to_string()).encode(),
-                    &tmp_avalanche_bin_compressed_path,
-                )
-                .await
-                .expect("failed s3::spawn_get_object");
+/// Snapshots the data volume attached to the local instance,
+/// and deletes the expired snapshots of the same node kind.
+/// The snapshot is crash-consistent, the same as a power loss of the instance.
+async fn snapshot_data_volume_loop(
+    ec2_manager: ec2::Manager,
+    id: Arc<String>,
+    node_kind: Arc<String>,
+    instance_id: Arc<String>,
+    data_volume: Arc<avalanche_ops_aws::DataVolume>,
+    reporter: Arc<status::Reporter>,
+) {
+    info!("STEP: starting 'snapshot_data_volume_loop'");
+    let tags = avalanche_ops_aws::DataVolume::tags(id.as_str(), node_kind.as_str());
 
-        warn!("stopping avalanche.service before unpack...");
-        bash::run("sudo systemctl stop avalanche.service").expect("failed systemctl stop command");
-        warn!("stopped avalanche.service before unpack...");
-        sleep(Duration::from_secs(10)).await;
+    loop {
+        info!(
+            "sleeping {}-sec for 'snapshot_data_volume_loop'",
+            data_volume.snapshot_interval_seconds
+        );
+        sleep(Duration::from_secs(data_volume.snapshot_interval_seconds)).await;
 
-        compress::unpack_file(
-            &tmp_avalanche_bin_compressed_path,
-            avalanche_bin_path.as_str(),
-            compress::Decoder::Zstd,
-        )
-        .expect("failed unpack_file avalanche_bin_compressed_path");
+        let volume = match ec2_manager
+            .find_attached_volume(instance_id.as_str(), ebs::DEFAULT_DATA_DEVICE_NAME)
+            .await
+        {
+            Ok(Some(v)) => v,
+            Ok(None) => {
+                warn!("no data volume attached to {}, retrying...", instance_id);
+                continue;
+            }
+            Err(e) => {
+                warn!("failed to find data volume {}, retrying...", e.message());
+                continue;
+            }
+        };
 
-        let f = File::open(avalanche_bin_path.as_str()).expect("failed to open avalanche_bin");
-        f.set_permissions(PermissionsExt::from_mode(0o777))
-            .expect("failed to set file permission for avalanche_bin");
-        fs::remove_file(&tmp_avalanche_bin_compressed_path).expect("failed fs::remove_file");
+        let description = format!("{} {} data volume", id, node_kind);
+        if let Err(e) = ec2_manager
+            .create_snapshot(&volume.volume_id, &description, &tags)
+            .await
+        {
+            warn!("failed to create snapshot {}, retrying...", e.message());
+            continue;
+        }
+        reporter.set_last_backup();
 
-        let plugins_dir = get_plugins_dir(avalanche_bin_path.as_str());
-        if !Path::new(&plugins_dir).exists() {
-            info!("STEP: creating '{}' for plugins", plugins_dir);
-            fs::create_dir_all(plugins_dir.clone()).unwrap();
+        match ec2_manager
+            .prune_snapshots(&tags, data_volume.snapshot_retention)
+            .await
+        {
+            Ok(deleted) => info!("deleted {} expired snapshot(s)", deleted),
+            Err(e) => warn!("failed to prune snapshots {}", e.message()),
         }
+    }
+}
 
-        info!("STEP: downloading plugins from S3 (if any) to overwrite");
-        let objects = s3::spawn_list_objects(
-            s3_manager.clone(),
-            &s3_bucket,
-            Some(s3::append_slash(
-                &avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsInstallDirPluginsDir(
-                    id.to_string(),
-                )
-                .encode(),
-            )),
-        )
-        .await
-        .expect("failed s3::spawn_list_objects");
+async fn fetch_metrics_loop(
+    cw_manager: cloudwatch::Manager,
+    cw_namespace: Arc<String>,
+    metrics_ep: Arc<String>,
+    alert_rules: Arc<Vec<alert::Rule>>,
+) {
+    info!("STEP: starting 'fetch_metrics_loop' with initial 2-minute wait");
+    sleep(Duration::from_secs(120)).await;
 
-        info!("listed {} plugins from S3", objects.len());
-        for obj in objects.iter() {
-            let s3_key = obj.key().expect("unexpected None s3 object");
-            let tmp_path = random::tmp_path(15, None).unwrap();
-            s3::spawn_get_object(s3_manager.clone(), &s3_bucket, s3_key, &tmp_path)
-                .await
-                .expect("failed s3::spawn_get_object");
+    let mut prev_raw_metrics: Option<avalanchego_metrics::RawMetrics> = None;
+    loop {
+        info!("STEP: fetching metrics in 1-min");
+        sleep(Duration::from_secs(60)).await;
+
+        let ts = Utc::now();
+        let scrape = match api_metrics::scrape(metrics_ep.as_str()).await {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("failed to fetch metrics {}, retrying...", e);
+                continue;
+            }
+        };
+        let cur_metrics = api_metrics::to_raw_metrics(ts, &scrape);
+
+        let mut data = api_metrics::to_cw_metric_data(&cur_metrics, prev_raw_metrics.clone());
+        if !alert_rules.is_empty() {
+            match alert::evaluate(&alert_rules, &scrape) {
+                Ok(alerts) => {
+                    for a in alerts.iter() {
+                        warn!("alert firing: {}", a);
+                    }
+                    data.extend(alert::to_cw_metric_data(&alert_rules, &alerts, ts));
+                }
+                Err(e) => warn!("failed to evaluate alert rules {}", e),
+            }
+        }
+
+        match cloudwatch::spawn_put_metric_data(cw_manager.clone(), cw_namespace.as_str(), data)
+            .await
+        {
+            Ok(_) => {}
+            Err(e) => {
+                warn!("failed to put metric data {}, retrying...", e);
+                prev_raw_metrics = Some(cur_metrics.clone());
+                continue;
+            }
+        }
 
-            let file_name = extract_filename(s3_key);
-            let file_path = format!("{}/{}", plugins_dir, file_name);
-            compress::unpack_file(&tmp_path, &file_path, compress::Decoder::Zstd).unwrap();
+        prev_raw_metrics = Some(cur_metrics.clone());
+    }
+}
 
-            let f = File::open(file_path).expect("failed to open plugin file");
-            f.set_permissions(PermissionsExt::from_mode(0o777))
-                .expect("failed to set file permission");
-            fs::remove_file(&tmp_path).expect("failed fs::remove_file");
+/// Publishes the node telemetry as custom metrics,
+/// with the "network-id" and "node-id" dimensions to filter per node.
+async fn publish_telemetry_loop(
+    cw_manager: cloudwatch::Manager,
+    cw_namespace: Arc<String>,
+    http_ep: Arc<String>,
+    node_id: Arc<String>,
+    network_id: u32,
+    db_dir: Arc<String>,
+    interval_seconds: u64,
+) {
+    info!(
+        "STEP: starting 'publish_telemetry_loop' with {}-second interval",
+        interval_seconds
+    );
+    let network_id = network_id.to_string();
+    loop {
+        sleep(Duration::from_secs(interval_seconds)).await;
+
+        let telemetry = collect_telemetry(http_ep.as_str(), db_dir.as_str()).await;
+        info!("collected telemetry {:?}", telemetry);
+        let data = cloudwatch::with_dimensions(
+            telemetry.to_metric_data(),
+            &[
+                ("network-id", network_id.as_str()),
+                ("node-id", node_id.as_str()),
+            ],
+        );
+        if data.is_empty() {
+            warn!("no telemetry collected, retrying...");
+            continue;
+        }
+        if let Err(e) =
+            cloudwatch::spawn_put_metric_data(cw_manager.clone(), cw_namespace.as_str(), data).await
+        {
+            warn!("failed to put telemetry {}, retrying...", e);
         }
+    }
+}
+
+/// Collects the node telemetry, leaving the fields "None" on failures
+/// (e.g., the P-chain API is not available until bootstrapped).
+async fn collect_telemetry(http_ep: &str, db_dir: &str) -> cloudwatch::Telemetry {
+    let mut telemetry = cloudwatch::Telemetry::default();
+
+    // "bootstrap_progress_percent" is published by "bootstrap_progress_loop"
+    match api_info::peers(http_ep).await {
+        Ok(resp) => telemetry.peers = resp.result.map(|r| r.num_peers),
+        Err(e) => warn!("failed peers ({})", e),
+    }
+    match api_p::get_height(http_ep).await {
+        Ok(resp) => telemetry.last_accepted_height = resp.result.and_then(|r| r.height),
+        Err(e) => warn!("failed get_height ({})", e),
+    }
 
-        // updated the avalanched itself, so sleep for cloudwatch logs and restart
-        warn!("artifacts have been updated... will trigger avalanched restart by panic here...");
-        sleep(Duration::from_secs(240)).await; // sleep to prevent duplicate updates
-        panic!("panic avalanched to trigger restarts via systemd service!!!")
+    match bash::run(format!("df --output=pcent {} | tail -1", db_dir).as_str()) {
+        Ok(out) => match out.0.trim().trim_end_matches('%').parse::<f64>() {
+            Ok(v) => telemetry.disk_used_percent = Some(v),
+            Err(e) => warn!("failed to parse 'df' output '{}' ({})", out.0, e),
+        },
+        Err(e) => warn!("failed 'df' for {} ({})", db_dir, e),
     }
+
+    telemetry
 }
 
 async fn print_backup_commands(
@@ -1156,6 +2333,24 @@ async fn print_backup_commands(
             db_dir,
         );
 
+        println!("[TO CREATE DATABASE BACKUP] /usr/local/bin/avalanched backup create --region {} --archive-compression-method {} --db-dir {} --s3-bucket {} --s3-key {}/db-backup{}",
+            s3_region,
+            compress::DirEncoder::TarZstd(3).id(),
+            db_dir,
+            s3_bucket,
+            avalanche_ops_aws::StorageNamespace::BackupsDir(id.to_string()).encode(),
+            compress::DirEncoder::TarZstd(3).ext(),
+        );
+
+        println!("[TO RESTORE DATABASE BACKUP] /usr/local/bin/avalanched backup restore --region {} --s3-bucket {} --s3-key {}/db-backup{} --db-dir {} --network-id {}",
+            s3_region,
+            s3_bucket,
+            avalanche_ops_aws::StorageNamespace::BackupsDir(id.to_string()).encode(),
+            compress::DirDecoder::TarZstd.ext(),
+            db_dir,
+            network_id,
+        );
+
         info!("sleeping 5-hour 'print_backup_commands'");
         sleep(Duration::from_secs(5 * 3600)).await;
     }
@@ -0,0 +1,155 @@
+use std::{
+    collections::BTreeSet,
+    fs::{self, File},
+    io,
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    str::FromStr,
+};
+
+use log::{info, warn};
+
+use avalanche_types::ids;
+use avalanchego::vms;
+use aws::s3;
+use utils::{bash, compress, random};
+
+/// Plugins installed from "StorageNamespace::PluginsDir".
+pub struct Installed {
+    /// File names in the plugins directory.
+    pub file_names: BTreeSet<String>,
+    pub aliases: vms::VmAliases,
+}
+
+/// Returns the plugin file name, which is the VM ID for the custom VMs
+/// (e.g., "subnetevm" is installed as "srEXiWaHuhNyGwPUi444Tu47ZEDwxTWrbQiuD7FmgSAQ6X7Dy"),
+/// and the alias to record if the name is not the VM ID.
+/// The built-in aliases (e.g., "evm") are installed as is.
+pub fn file_name(name: &str) -> io::Result<(String, Option<ids::Id>)> {
+    if vms::BUILTIN_VM_ALIASES.contains(&name) {
+        return Ok((String::from(name), None));
+    }
+    if let Ok(vm_id) = ids::Id::from_str(name) {
+        return Ok((vm_id.to_string(), None));
+    }
+    let vm_id = ids::Id::from_vm_name(name)?;
+    Ok((vm_id.to_string(), Some(vm_id)))
+}
+
+/// Downloads each plugin from S3 that is not yet installed, and
+/// installs it by its VM ID. Plugins already installed are not overwritten,
+/// as they may have been upgraded since.
+pub async fn install_from_s3(
+    s3_manager: s3::Manager,
+    s3_bucket: &str,
+    s3_dir: &str,
+    avalanche_bin_path: &str,
+    plugins_dir: &str,
+    aliases_file: &str,
+) -> io::Result<Installed> {
+    if !Path::new(plugins_dir).exists() {
+        info!("creating '{}' for plugins", plugins_dir);
+        fs::create_dir_all(plugins_dir)?;
+    }
+    let mut aliases = if Path::new(aliases_file).exists() {
+        vms::VmAliases::load(aliases_file)?
+    } else {
+        vms::VmAliases::default()
+    };
+
+    let objects = s3::spawn_list_objects(
+        s3_manager.clone(),
+        s3_bucket,
+        Some(s3::append_slash(s3_dir)),
+    )
+    .await?;
+    info!("listed {} plugins from S3", objects.len());
+
+    let mut file_names = BTreeSet::new();
+    for obj in objects.iter() {
+        let s3_key = obj.key().expect("unexpected None s3 object");
+        let name = super::extract_filename(s3_key);
+        let (file_name, alias_vm_id) = file_name(&name)?;
+        if let Some(vm_id) = alias_vm_id {
+            aliases.insert(&vm_id, &name)?;
+        }
+        file_names.insert(file_name.clone());
+
+        let plugin_path = format!("{}/{}", plugins_dir, file_name);
+        if Path::new(&plugin_path).exists() {
+            info!("plugin '{}' already installed as '{}'", name, plugin_path);
+            continue;
+        }
+
+        let tmp_path = random::tmp_path(15, None)?;
+        s3::spawn_get_object(s3_manager.clone(), s3_bucket, s3_key, &tmp_path).await?;
+        let staged_path = format!("{}.install", plugin_path);
+        compress::unpack_file(&tmp_path, &staged_path, compress::Decoder::Zstd)?;
+        fs::remove_file(&tmp_path)?;
+        File::open(&staged_path)?.set_permissions(PermissionsExt::from_mode(0o777))?;
+
+        if let Err(e) = check_compatibility(avalanche_bin_path, &staged_path) {
+            fs::remove_file(&staged_path)?;
+            return Err(e);
+        }
+        fs::rename(&staged_path, &plugin_path)?;
+        info!("installed plugin '{}' as '{}'", name, plugin_path);
+    }
+    Ok(Installed {
+        file_names,
+        aliases,
+    })
+}
+
+/// Returns an error if the plugin speaks a different "rpcchainvm" protocol
+/// than the avalanchego binary, which would fail the node on start.
+/// Binaries that do not report the version are not checked.
+pub fn check_compatibility(avalanche_bin_path: &str, plugin_path: &str) -> io::Result<()> {
+    let avalanchego_version = match bash::run(&format!("{} --version", avalanche_bin_path)) {
+        Ok((stdout, _)) => stdout,
+        Err(e) => {
+            warn!("failed to get avalanchego version ({}), skipping check", e);
+            return Ok(());
+        }
+    };
+    // the plugins that do not support "--version" exit without the plugin handshake
+    let plugin_version = match bash::run(&format!("{} --version", plugin_path)) {
+        Ok((stdout, _)) => stdout,
+        Err(e) => {
+            warn!(
+                "failed to get '{}' version ({}), skipping check",
+                plugin_path, e
+            );
+            return Ok(());
+        }
+    };
+    vms::check_rpcchainvm_compatibility(&avalanchego_version, &plugin_version)
+}
+
+/// Removes the plugins named by VM IDs that are not in "keep",
+/// and their aliases. Other files (e.g., "evm") are left as is.
+/// Returns the removed file names.
+pub fn garbage_collect(
+    plugins_dir: &str,
+    keep: &BTreeSet<String>,
+    aliases: &mut vms::VmAliases,
+) -> io::Result<Vec<String>> {
+    let mut removed = Vec::new();
+    for entry in fs::read_dir(plugins_dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let vm_id = match ids::Id::from_str(&file_name) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        if keep.contains(&file_name) || !entry.file_type()?.is_file() {
+            continue;
+        }
+
+        info!("removing stale plugin '{}'", file_name);
+        fs::remove_file(entry.path())?;
+        aliases.remove(&vm_id);
+        removed.push(file_name);
+    }
+    Ok(removed)
+}
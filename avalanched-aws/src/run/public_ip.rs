@@ -0,0 +1,224 @@
+use std::{
+    io::{self, Error, ErrorKind},
+    net::IpAddr,
+    process,
+    sync::Arc,
+    time::Duration,
+};
+
+use log::{info, warn};
+use tokio::time::{sleep, timeout};
+
+use avalanche_ops_aws::{PublicIpPolicy, PublicIpSource};
+use aws::{ec2, s3};
+use utils::public_ip;
+
+/// Time limit of each source, so that the unreachable one
+/// (e.g., the instance metadata outside EC2) does not block the others.
+const SOURCE_TIMEOUT_SECONDS: u64 = 10;
+
+pub struct Redetector {
+    pub s3_manager: s3::Manager,
+    pub s3_bucket: String,
+    pub id: String,
+    pub local_node: avalanche_ops_aws::Node,
+    pub policy: PublicIpPolicy,
+}
+
+/// Resolves the address that the node advertises: the override of
+/// "machine_id" if any, otherwise the first public address from
+/// "policy.sources" in order. With "cross_check", the next source
+/// that resolves must return the same address.
+pub async fn resolve(policy: &PublicIpPolicy, machine_id: &str) -> io::Result<String> {
+    if let Some(ip) = policy.overrides.get(machine_id) {
+        info!("using the override public IP {} for '{}'", ip, machine_id);
+        return Ok(ip.clone());
+    }
+
+    let mut resolved: Option<(PublicIpSource, IpAddr)> = None;
+    for source in policy.sources.iter() {
+        let ip = match fetch(policy, source).await {
+            Ok(Some(ip)) => ip,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!(
+                    "failed to resolve public IP from '{}' ({})",
+                    source.as_str(),
+                    e
+                );
+                continue;
+            }
+        };
+        match resolved {
+            None => {
+                info!("resolved public IP {} from '{}'", ip, source.as_str());
+                if !policy.cross_check {
+                    return Ok(ip.to_string());
+                }
+                resolved = Some((*source, ip));
+            }
+            Some((first, first_ip)) => {
+                if first_ip != ip {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "public IP {} from '{}' != {} from '{}'",
+                            first_ip,
+                            first.as_str(),
+                            ip,
+                            source.as_str()
+                        ),
+                    ));
+                }
+                info!("cross-checked public IP {} with '{}'", ip, source.as_str());
+                return Ok(ip.to_string());
+            }
+        }
+    }
+
+    match resolved {
+        Some((source, ip)) => {
+            warn!(
+                "no other source to cross-check public IP {} from '{}'",
+                ip,
+                source.as_str()
+            );
+            Ok(ip.to_string())
+        }
+        None => Err(Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "no public IP from {:?}",
+                policy
+                    .sources
+                    .iter()
+                    .map(|s| s.as_str())
+                    .collect::<Vec<&str>>()
+            ),
+        )),
+    }
+}
+
+/// Returns "None" if the source has no public address for this host.
+async fn fetch(policy: &PublicIpPolicy, source: &PublicIpSource) -> io::Result<Option<IpAddr>> {
+    let timeout_dur = Duration::from_secs(SOURCE_TIMEOUT_SECONDS);
+    let ip = match source {
+        PublicIpSource::Imds => {
+            let s = match timeout(timeout_dur, ec2::fetch_optional_public_ipv4()).await?? {
+                Some(s) => s,
+                None => return Ok(None),
+            };
+            s.trim().parse::<IpAddr>().map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid public-ipv4 '{}' ({})", s, e),
+                )
+            })?
+        }
+        PublicIpSource::Stun => {
+            let mut found = None;
+            for server in policy.stun_servers.iter() {
+                match public_ip::fetch_stun(server, timeout_dur).await {
+                    Ok(ip) => {
+                        found = Some(ip);
+                        break;
+                    }
+                    Err(e) => warn!("failed STUN server '{}' ({})", server, e),
+                }
+            }
+            match found {
+                Some(ip) => ip,
+                None => return Ok(None),
+            }
+        }
+        PublicIpSource::Echo => {
+            let mut found = None;
+            for url in policy.echo_urls.iter() {
+                match public_ip::fetch_echo(url, timeout_dur).await {
+                    Ok(ip) => {
+                        found = Some(ip);
+                        break;
+                    }
+                    Err(e) => warn!("failed echo service '{}' ({})", url, e),
+                }
+            }
+            match found {
+                Some(ip) => ip,
+                None => return Ok(None),
+            }
+        }
+        PublicIpSource::Interface => match public_ip::fetch_interface()? {
+            Some(ip) => ip,
+            None => return Ok(None),
+        },
+    };
+
+    if !public_ip::is_public(&ip) {
+        warn!(
+            "skipping non-public address {} from '{}'",
+            ip,
+            source.as_str()
+        );
+        return Ok(None);
+    }
+    Ok(Some(ip))
+}
+
+/// Resolves the public IP on the interval. Once the new address is seen
+/// twice in a row (not a flaky source), deregisters the node and exits,
+/// so that "avalanched run" restarts with the new address: rewrites the
+/// avalanchego config, restarts the node, and registers it again.
+pub async fn redetect_loop(redetector: Arc<Redetector>) {
+    info!("STEP: starting 'redetect_loop'");
+
+    let interval = Duration::from_secs(redetector.policy.redetect_interval_seconds);
+    let current = redetector.local_node.public_ip.as_str();
+    let mut changed_to: Option<String> = None;
+    loop {
+        sleep(interval).await;
+
+        let ip = match resolve(&redetector.policy, &redetector.local_node.machine_id).await {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("failed to re-detect public IP ({}), keeping {}", e, current);
+                continue;
+            }
+        };
+        if ip == current {
+            changed_to = None;
+            continue;
+        }
+        if changed_to.as_deref() != Some(ip.as_str()) {
+            info!(
+                "public IP changed from {} to {}, confirming on the next check",
+                current, ip
+            );
+            changed_to = Some(ip);
+            continue;
+        }
+
+        let registered_key = avalanche_ops_aws::StorageNamespace::DiscoverRegisteredNode(
+            redetector.id.clone(),
+            redetector.local_node.clone(),
+        )
+        .encode();
+        if let Err(e) = s3::spawn_delete_objects(
+            redetector.s3_manager.clone(),
+            &redetector.s3_bucket,
+            Some(registered_key),
+        )
+        .await
+        {
+            // expires after "REGISTRATION_TTL_SECONDS" anyways
+            warn!("failed to delete registration {}", e.message());
+        }
+
+        // every other routine holds the old address,
+        // "avalanched.service" restarts on failure
+        warn!(
+            "exiting to restart 'avalanched' with the new public IP {}",
+            ip
+        );
+        process::exit(1);
+    }
+}
@@ -0,0 +1,512 @@
+use std::{
+    fs,
+    future::Future,
+    io::{self, Error, ErrorKind},
+    path::Path,
+    process,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use log::{error, info, warn};
+use tokio::time::sleep;
+
+use avalanche_api::p as api_p;
+use avalanche_types::{cert, ids};
+use aws::{dynamodb, envelope, s3};
+use utils::random;
+
+use super::{supervisor, upgrade};
+
+/// Interval between the rotation event polls.
+const POLL_INTERVAL_SECONDS: u64 = 300;
+
+/// Suffix of the generated certificate and key before the swap.
+const STAGED_SUFFIX: &str = ".rotate";
+/// Suffix of the previous certificate and key kept on the disk,
+/// in case the old node ID must be restored by hand.
+const PREVIOUS_SUFFIX: &str = ".rotated";
+/// Suffix of the journal of the rotation, written before the swap and removed
+/// once the rotation is reported, so that the rotation interrupted by a crash
+/// is reported on restart rather than applied twice.
+const JOURNAL_SUFFIX: &str = ".rotate.yaml";
+
+/// Attempts to upload the new certificate or record the status,
+/// before giving up on the rotation.
+const ATTEMPTS: u64 = 5;
+
+pub struct CertRotator {
+    pub s3_manager: s3::Manager,
+    pub s3_bucket: String,
+    pub envelope: envelope::Envelope,
+    pub dynamodb_manager: dynamodb::Manager,
+    pub lock_table: String,
+    pub id: String,
+    pub local_node: avalanche_ops_aws::Node,
+    pub tls_key_path: String,
+    pub tls_cert_path: String,
+    pub health_check_timeout_seconds: u64,
+}
+
+/// Polls the rotation event from "avalanche-ops-aws events rotate-cert",
+/// and applies each new event version once the node is neither a current
+/// nor a pending validator, so that the staking rewards are not forfeited.
+/// The nodes take turns on the DynamoDB lock, and each keeps it for
+/// "ROTATE_CERT_SPACING_SECONDS" after the rotation, so the fleet rotates
+/// one node at a time.
+/// On success, exits "avalanched" for systemd to restart it with the new
+/// node ID, which re-registers the node and (optionally) the validator.
+pub async fn rotate_cert_loop(rotator: Arc<CertRotator>) {
+    info!("STEP: starting 'rotate_cert_loop'");
+
+    loop {
+        sleep(Duration::from_secs(POLL_INTERVAL_SECONDS)).await;
+
+        // the node already runs with the journaled certificate,
+        // only its backup and status are missing
+        match rotator.journaled() {
+            Ok(Some(status)) => {
+                info!(
+                    "completing the rotation from {} to {:?}",
+                    status.old_node_id, status.new_node_id
+                );
+                let completed = match rotator.upload_certs().await {
+                    Ok(_) => rotator.finish(&status).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = completed {
+                    warn!("failed to complete the rotation ({}), retrying...", e);
+                }
+                continue;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!("failed to read the rotation journal ({}), retrying...", e);
+                continue;
+            }
+        }
+
+        let event = match rotator.fetch_event().await {
+            Ok(Some(v)) => v,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!("failed to fetch rotate-cert event ({}), retrying...", e);
+                continue;
+            }
+        };
+        let status = match fetch_status(
+            rotator.s3_manager.clone(),
+            &rotator.s3_bucket,
+            &rotator.id,
+            &rotator.local_node.machine_id,
+        )
+        .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("failed to fetch rotate-cert status ({}), retrying...", e);
+                continue;
+            }
+        };
+        if let Some(status) = &status {
+            if event.is_applied(status) {
+                continue;
+            }
+        }
+
+        let validation_end = match rotator.validation_end().await {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("failed to fetch validators ({}), retrying...", e);
+                continue;
+            }
+        };
+        if let Some(end) = validation_end {
+            let already_scheduled = status.as_ref().map_or(false, |st| {
+                st.version == event.version && st.scheduled_after == Some(end)
+            });
+            if !already_scheduled {
+                info!(
+                    "{} validates until {}, scheduling the rotation",
+                    rotator.local_node.node_id, end
+                );
+                rotator
+                    .report(
+                        &event,
+                        avalanche_ops_aws::RotateCertState::Scheduled,
+                        None,
+                        Some(end),
+                        None,
+                    )
+                    .await;
+            }
+            continue;
+        }
+
+        // renews the lease for each attempt, the holder keeps the lock
+        // after the rotation until the lease expires
+        let lock = dynamodb::Lock::new(
+            &avalanche_ops_aws::rotate_cert_lock_id(&rotator.id),
+            &rotator.local_node.machine_id,
+            "rotate-cert",
+            Duration::from_secs(
+                rotator.health_check_timeout_seconds
+                    + avalanche_ops_aws::ROTATE_CERT_SPACING_SECONDS,
+            ),
+        );
+        if let Err(e) = rotator
+            .dynamodb_manager
+            .acquire_lock(&rotator.lock_table, &lock)
+            .await
+        {
+            info!("waiting for the rotation lock ({})", e.message());
+            continue;
+        }
+        info!(
+            "STEP: rotating the staking certificate of {}",
+            rotator.local_node.node_id
+        );
+
+        match rotator.apply(&event).await {
+            Ok(status) => {
+                info!(
+                    "rotated node ID {} to {:?}",
+                    status.old_node_id, status.new_node_id
+                );
+                if let Err(e) = rotator.finish(&status).await {
+                    // reported on restart from the journal
+                    warn!("failed to record rotate-cert status ({})", e);
+                }
+
+                // every other routine holds the old node ID,
+                // "avalanched.service" restarts on failure
+                warn!("exiting to restart 'avalanched' with the new node ID");
+                process::exit(1);
+            }
+            Err(e) => {
+                error!("failed to rotate the staking certificate ({})", e);
+                if let Err(e) = rotator
+                    .dynamodb_manager
+                    .release_lock(&rotator.lock_table, &lock)
+                    .await
+                {
+                    // expires after the lease
+                    warn!("failed to release the rotation lock ({})", e.message());
+                }
+                rotator
+                    .report(
+                        &event,
+                        avalanche_ops_aws::RotateCertState::Failed,
+                        None,
+                        None,
+                        Some(e.to_string()),
+                    )
+                    .await;
+            }
+        }
+    }
+}
+
+/// Returns "None" if the node has not reported any rotation.
+pub(super) async fn fetch_status(
+    s3_manager: s3::Manager,
+    s3_bucket: &str,
+    id: &str,
+    machine_id: &str,
+) -> io::Result<Option<avalanche_ops_aws::RotateCertStatus>> {
+    let s3_key = avalanche_ops_aws::StorageNamespace::EventsRotateCertStatus(
+        id.to_string(),
+        machine_id.to_string(),
+    )
+    .encode();
+    let d = match upgrade::get_object(s3_manager, s3_bucket, &s3_key).await? {
+        Some(d) => d,
+        None => return Ok(None),
+    };
+    avalanche_ops_aws::RotateCertStatus::decode_yaml(&d).map(Some)
+}
+
+/// Restores the previous certificate and key, if the rotation was interrupted
+/// (e.g., crashed) before both were swapped. The journal of the completed swap
+/// is kept for "rotate_cert_loop" to report. Must run before the node starts.
+pub(super) fn recover(tls_key_path: &str, tls_cert_path: &str) -> io::Result<()> {
+    let journal_path = format!("{}{}", tls_cert_path, JOURNAL_SUFFIX);
+    if !Path::new(&journal_path).exists() {
+        return Ok(());
+    }
+    let status = avalanche_ops_aws::RotateCertStatus::decode_yaml(&fs::read(&journal_path)?)?;
+
+    // the certificate is swapped last, so the key is already swapped
+    let node_id = ids::NodeId::from_cert_file(tls_cert_path)
+        .ok()
+        .map(|v| v.to_string());
+    if node_id.is_some() && node_id == status.new_node_id {
+        info!(
+            "found the rotation to {:?} not yet reported",
+            status.new_node_id
+        );
+        return Ok(());
+    }
+
+    warn!(
+        "restoring {} of the interrupted rotation",
+        status.old_node_id
+    );
+    restore_previous(tls_key_path, tls_cert_path)?;
+    fs::remove_file(&journal_path)
+}
+
+/// Moves back the previous certificate and key that the rotation copied
+/// before the swap, the certificate first so that "recover" never takes
+/// the half-restored pair for the swapped one.
+fn restore_previous(tls_key_path: &str, tls_cert_path: &str) -> io::Result<()> {
+    for p in [tls_cert_path, tls_key_path] {
+        let previous_path = format!("{}{}", p, PREVIOUS_SUFFIX);
+        if Path::new(&previous_path).exists() {
+            fs::rename(&previous_path, p)?;
+        }
+    }
+    Ok(())
+}
+
+/// Retries the operation with the linear backoff, up to "ATTEMPTS" times.
+async fn retry<F, Fut>(what: &str, f: F) -> io::Result<()>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = io::Result<()>>,
+{
+    let mut attempt = 1;
+    loop {
+        match f().await {
+            Ok(_) => return Ok(()),
+            Err(e) if attempt < ATTEMPTS => {
+                warn!("failed to {} ({}), retrying...", what, e);
+                sleep(Duration::from_secs(10 * attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+impl CertRotator {
+    async fn fetch_event(&self) -> io::Result<Option<avalanche_ops_aws::RotateCertEvent>> {
+        let s3_key =
+            avalanche_ops_aws::StorageNamespace::EventsRotateCert(self.id.clone()).encode();
+        let d = match upgrade::get_object(self.s3_manager.clone(), &self.s3_bucket, &s3_key).await?
+        {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+        avalanche_ops_aws::RotateCertEvent::decode_yaml(&d).map(Some)
+    }
+
+    /// Returns the latest end time of the current or pending validation
+    /// of the local node, "None" if the validation has ended.
+    async fn validation_end(&self) -> io::Result<Option<u64>> {
+        let http_ep = self.local_node.http_endpoint.as_str();
+        let node_id = ids::NodeId::from_str(&self.local_node.node_id)?;
+
+        let mut validators = api_p::get_current_validators(http_ep)
+            .await?
+            .result
+            .and_then(|r| r.validators)
+            .unwrap_or_default();
+        validators.extend(
+            api_p::get_pending_validators(http_ep)
+                .await?
+                .result
+                .and_then(|r| r.validators)
+                .unwrap_or_default(),
+        );
+
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("unexpected None duration_since")
+            .as_secs();
+        Ok(validators
+            .iter()
+            .filter(|v| v.node_id == Some(node_id))
+            .filter_map(|v| v.end_time)
+            .filter(|end| *end > now)
+            .max())
+    }
+
+    fn journal_path(&self) -> String {
+        format!("{}{}", self.tls_cert_path, JOURNAL_SUFFIX)
+    }
+
+    /// Returns the status of the swapped certificate not yet reported.
+    fn journaled(&self) -> io::Result<Option<avalanche_ops_aws::RotateCertStatus>> {
+        let journal_path = self.journal_path();
+        if !Path::new(&journal_path).exists() {
+            return Ok(None);
+        }
+        avalanche_ops_aws::RotateCertStatus::decode_yaml(&fs::read(&journal_path)?).map(Some)
+    }
+
+    /// Generates the new certificate and key, swaps them in place,
+    /// restarts the node, and backs them up to S3. Restores the previous
+    /// ones if the node does not become healthy or the backup fails,
+    /// so that no node runs with the certificate missing from S3.
+    /// Returns the journaled status to report.
+    async fn apply(
+        &self,
+        event: &avalanche_ops_aws::RotateCertEvent,
+    ) -> io::Result<avalanche_ops_aws::RotateCertStatus> {
+        let staged_key_path = format!("{}{}", self.tls_key_path, STAGED_SUFFIX);
+        let staged_cert_path = format!("{}{}", self.tls_cert_path, STAGED_SUFFIX);
+        let new_node_id = cert::generate(&staged_key_path, &staged_cert_path)?;
+
+        let previous_key_path = format!("{}{}", self.tls_key_path, PREVIOUS_SUFFIX);
+        let previous_cert_path = format!("{}{}", self.tls_cert_path, PREVIOUS_SUFFIX);
+        fs::copy(&self.tls_key_path, &previous_key_path)?;
+        fs::copy(&self.tls_cert_path, &previous_cert_path)?;
+
+        let status = self.status(
+            event,
+            avalanche_ops_aws::RotateCertState::Rotated,
+            Some(new_node_id.to_string()),
+            None,
+            None,
+        );
+        fs::write(self.journal_path(), status.encode_yaml()?)?;
+        fs::rename(&staged_key_path, &self.tls_key_path)?;
+        fs::rename(&staged_cert_path, &self.tls_cert_path)?;
+
+        info!("STEP: restarting the node with the new staking certificate");
+        let restarted = match supervisor::restart() {
+            Ok(_) => {
+                upgrade::wait_for_health(
+                    &self.local_node.http_endpoint,
+                    self.health_check_timeout_seconds,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = restarted {
+            warn!("restoring the previous staking certificate ({})", e);
+            self.rollback()?;
+            return Err(e);
+        }
+
+        if let Err(e) = self.upload_certs().await {
+            warn!("restoring the previous staking certificate ({})", e);
+            self.rollback()?;
+            // the partial upload may have replaced the backup of the previous one
+            if let Err(e) = self.upload_certs().await {
+                warn!("failed to upload the previous staking certificate ({})", e);
+            }
+            return Err(Error::new(
+                e.kind(),
+                format!("failed to upload the new staking certificate ({})", e),
+            ));
+        }
+        Ok(status)
+    }
+
+    fn rollback(&self) -> io::Result<()> {
+        restore_previous(&self.tls_key_path, &self.tls_cert_path)?;
+        fs::remove_file(self.journal_path())?;
+        supervisor::restart()
+    }
+
+    async fn upload_certs(&self) -> io::Result<()> {
+        retry("upload the staking certificate", || {
+            super::upload_staking_certs(
+                self.s3_manager.clone(),
+                self.envelope.clone(),
+                &self.s3_bucket,
+                &self.id,
+                &self.local_node.machine_id,
+                &self.tls_key_path,
+                &self.tls_cert_path,
+            )
+        })
+        .await
+    }
+
+    /// Records the journaled status, after which the journal is removed.
+    async fn finish(&self, status: &avalanche_ops_aws::RotateCertStatus) -> io::Result<()> {
+        retry("record rotate-cert status", || self.put_status(status)).await?;
+        fs::remove_file(self.journal_path())
+    }
+
+    fn status(
+        &self,
+        event: &avalanche_ops_aws::RotateCertEvent,
+        state: avalanche_ops_aws::RotateCertState,
+        new_node_id: Option<String>,
+        scheduled_after: Option<u64>,
+        message: Option<String>,
+    ) -> avalanche_ops_aws::RotateCertStatus {
+        avalanche_ops_aws::RotateCertStatus {
+            machine_id: self.local_node.machine_id.clone(),
+            old_node_id: self.local_node.node_id.clone(),
+            new_node_id,
+            version: event.version,
+            state,
+            reregister_validator: event.reregister_validator,
+            scheduled_after,
+            message,
+            updated_at: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("unexpected None duration_since")
+                .as_secs(),
+        }
+    }
+
+    async fn report(
+        &self,
+        event: &avalanche_ops_aws::RotateCertEvent,
+        state: avalanche_ops_aws::RotateCertState,
+        new_node_id: Option<String>,
+        scheduled_after: Option<u64>,
+        message: Option<String>,
+    ) {
+        let status = self.status(event, state, new_node_id, scheduled_after, message);
+        if let Err(e) = self.put_status(&status).await {
+            warn!("failed to record rotate-cert status ({})", e);
+        }
+    }
+
+    async fn put_status(&self, status: &avalanche_ops_aws::RotateCertStatus) -> io::Result<()> {
+        let s3_key = avalanche_ops_aws::StorageNamespace::EventsRotateCertStatus(
+            self.id.clone(),
+            self.local_node.machine_id.clone(),
+        )
+        .encode();
+        let tmp_path = random::tmp_path(10, Some(".yaml"))?;
+        fs::write(&tmp_path, status.encode_yaml()?)?;
+        let ret =
+            s3::spawn_put_object(self.s3_manager.clone(), &tmp_path, &self.s3_bucket, &s3_key)
+                .await
+                .map_err(|e| Error::new(ErrorKind::Other, e.message()));
+        fs::remove_file(&tmp_path)?;
+        ret
+    }
+}
+
+/// Returns true if the last rotation to the local node ID
+/// opted out of registering the new node ID as a validator.
+pub(super) async fn skips_validator_registration(
+    s3_manager: s3::Manager,
+    s3_bucket: &str,
+    id: &str,
+    local_node: &avalanche_ops_aws::Node,
+) -> bool {
+    match fetch_status(s3_manager, s3_bucket, id, &local_node.machine_id).await {
+        Ok(Some(status)) => {
+            status.state == avalanche_ops_aws::RotateCertState::Rotated
+                && status.new_node_id.as_deref() == Some(local_node.node_id.as_str())
+                && !status.reregister_validator
+        }
+        Ok(None) => false,
+        Err(e) => {
+            warn!("failed to fetch rotate-cert status ({}), registering", e);
+            false
+        }
+    }
+}
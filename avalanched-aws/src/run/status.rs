@@ -0,0 +1,260 @@
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use log::{info, warn, Level, Log, Metadata, Record};
+use tokio::time::sleep;
+
+use avalanche_api::{health as api_health, info as api_info};
+use avalanche_ops_aws::{BootstrapState, NodeStatus, StatusError};
+
+/// Number of the recent warnings and errors kept for the status.
+const MAX_RECENT_ERRORS: usize = 20;
+
+const POLL_INTERVAL_SECONDS: u64 = 30;
+
+/// Recent warnings and errors (most recent first),
+/// shared by the logger and the reporter.
+pub type RecentErrors = Arc<Mutex<VecDeque<StatusError>>>;
+
+/// Wraps "env_logger" to keep the recent warnings and errors for the status.
+struct Logger {
+    inner: env_logger::Logger,
+    recent_errors: RecentErrors,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.level() <= Level::Warn && self.inner.matches(record) {
+            let mut recent_errors = self.recent_errors.lock().expect("failed to lock");
+            if recent_errors.len() >= MAX_RECENT_ERRORS {
+                recent_errors.pop_back();
+            }
+            recent_errors.push_front(StatusError {
+                at: unix_now(),
+                message: record.args().to_string(),
+            });
+        }
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Same as "env_logger::init_from_env", but also keeps
+/// the recent warnings and errors for the status.
+pub fn init_logger(log_level: &str) -> RecentErrors {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    let inner = env_logger::Builder::from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    )
+    .build();
+    let max_level = inner.filter();
+
+    let recent_errors = Arc::new(Mutex::new(VecDeque::with_capacity(MAX_RECENT_ERRORS)));
+    log::set_boxed_logger(Box::new(Logger {
+        inner,
+        recent_errors: recent_errors.clone(),
+    }))
+    .expect("failed to set logger");
+    log::set_max_level(max_level);
+    recent_errors
+}
+
+/// Tracks the status of the local node, updated by the other routines.
+pub struct Reporter {
+    http_endpoint: String,
+    status: Mutex<NodeStatus>,
+    recent_errors: RecentErrors,
+}
+
+impl Reporter {
+    pub fn new(
+        id: &str,
+        local_node: &avalanche_ops_aws::Node,
+        recent_errors: RecentErrors,
+    ) -> Self {
+        let mut status = NodeStatus::new(id, local_node);
+        status.versions.insert(
+            String::from("avalanched"),
+            env!("CARGO_PKG_VERSION").to_string(),
+        );
+        Self {
+            http_endpoint: local_node.http_endpoint.clone(),
+            status: Mutex::new(status),
+            recent_errors,
+        }
+    }
+
+    pub fn set_state(&self, state: BootstrapState) {
+        self.status.lock().expect("failed to lock").bootstrap_state = state;
+    }
+
+    pub fn set_bootstrap_progress(&self, percent: f64) {
+        self.status
+            .lock()
+            .expect("failed to lock")
+            .bootstrap_progress_percent = Some(percent);
+    }
+
+    pub fn set_last_backup(&self) {
+        self.status.lock().expect("failed to lock").last_backup_at = Some(unix_now());
+    }
+
+    pub fn status(&self) -> NodeStatus {
+        let mut status = self.status.lock().expect("failed to lock").clone();
+        status.recent_errors = self
+            .recent_errors
+            .lock()
+            .expect("failed to lock")
+            .iter()
+            .cloned()
+            .collect();
+        status.updated_at = unix_now();
+        status
+    }
+
+    /// Records the avalanchego and VM versions of the running node.
+    async fn fetch_versions(&self) {
+        let result = match api_info::get_node_version(&self.http_endpoint).await {
+            Ok(resp) => match resp.result {
+                Some(v) => v,
+                None => return,
+            },
+            Err(e) => {
+                warn!("failed get_node_version {}", e);
+                return;
+            }
+        };
+
+        let mut versions = vec![
+            ("avalanchego", result.version),
+            ("database", result.database_version),
+        ];
+        if let Some(vm_versions) = result.vm_versions {
+            versions.push(("avm", vm_versions.avm));
+            versions.push(("evm", vm_versions.evm));
+            versions.push(("platform", vm_versions.platform));
+        }
+        let mut status = self.status.lock().expect("failed to lock");
+        for (k, v) in versions {
+            if let Some(v) = v {
+                status.versions.insert(k.to_string(), v);
+            }
+        }
+    }
+}
+
+/// Polls the health of the local node once "avalanched" starts the service.
+/// Re-reads the installed versions every time the node becomes healthy,
+/// since the upgrades restart the node.
+pub async fn status_loop(reporter: Arc<Reporter>) {
+    info!("STEP: starting 'status_loop'");
+
+    loop {
+        sleep(Duration::from_secs(POLL_INTERVAL_SECONDS)).await;
+
+        let prev = reporter
+            .status
+            .lock()
+            .expect("failed to lock")
+            .bootstrap_state;
+        if prev == BootstrapState::Starting {
+            continue;
+        }
+
+        let healthy = match api_health::spawn_check(&reporter.http_endpoint, true).await {
+            Ok(res) => res.healthy.unwrap_or(false),
+            Err(_) => false,
+        };
+        let state = match (healthy, prev) {
+            (true, _) => BootstrapState::Healthy,
+            (false, BootstrapState::Healthy) | (false, BootstrapState::Unhealthy) => {
+                BootstrapState::Unhealthy
+            }
+            (false, _) => BootstrapState::Bootstrapping,
+        };
+        if state != prev {
+            info!(
+                "local node state changed from '{}' to '{}'",
+                prev.as_str(),
+                state.as_str()
+            );
+            reporter.set_state(state);
+            if state == BootstrapState::Healthy {
+                reporter.fetch_versions().await;
+            }
+        }
+    }
+}
+
+/// Serves "NodeStatus" at "http://127.0.0.1:[PORT]/status".
+/// Only bound to the loopback interface, so the status is read
+/// on the node (e.g., "run-command --command status" or the sidecars).
+pub async fn serve(reporter: Arc<Reporter>, port: u16) {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let make_svc = make_service_fn(move |_| {
+        let reporter = reporter.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let reporter = reporter.clone();
+                async move { Ok::<_, Infallible>(handle(&reporter, &req)) }
+            }))
+        }
+    });
+
+    let builder = match Server::try_bind(&addr) {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("failed to bind the status API to {} ({})", addr, e);
+            return;
+        }
+    };
+    info!("STEP: serving the status at http://{}/status", addr);
+    if let Err(e) = builder.serve(make_svc).await {
+        warn!("status API failed {}", e);
+    }
+}
+
+fn handle(reporter: &Reporter, req: &Request<Body>) -> Response<Body> {
+    if req.method() != Method::GET || req.uri().path() != "/status" {
+        return respond(StatusCode::NOT_FOUND, Body::empty());
+    }
+    match reporter.status().encode_json() {
+        Ok(d) => respond(StatusCode::OK, Body::from(d)),
+        Err(e) => respond(StatusCode::INTERNAL_SERVER_ERROR, Body::from(e.to_string())),
+    }
+}
+
+fn respond(status: StatusCode, body: Body) -> Response<Body> {
+    let mut resp = Response::new(body);
+    *resp.status_mut() = status;
+    if status == StatusCode::OK {
+        resp.headers_mut().insert(
+            hyper::header::CONTENT_TYPE,
+            hyper::header::HeaderValue::from_static("application/json"),
+        );
+    }
+    resp
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("unexpected None duration_since")
+        .as_secs()
+}
@@ -0,0 +1,211 @@
+use std::{
+    fs::{self, File},
+    io,
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use log::{error, info, warn};
+use tokio::time::sleep;
+
+use avalanchego::config as avalanchego_config;
+use aws::s3;
+use utils::{compress, random};
+
+use super::{supervisor, upgrade};
+
+/// Interval between the subnet event polls.
+const POLL_INTERVAL_SECONDS: u64 = 60;
+
+pub struct SubnetInstaller {
+    pub s3_manager: s3::Manager,
+    pub s3_bucket: String,
+    /// The VM plugin is downloaded within the local region.
+    pub install_s3_manager: s3::Manager,
+    pub install_s3_bucket: String,
+    pub id: String,
+    pub local_node: avalanche_ops_aws::Node,
+    pub avalanche_bin_path: String,
+    pub avalanchego_config_file: String,
+    pub health_check_timeout_seconds: u64,
+}
+
+/// Polls the subnet event from "avalanche-ops-aws subnet", and applies each
+/// new event version once: installs the plugin, tracks the subnet
+/// in the avalanchego config file, and restarts the node.
+/// The result is recorded in S3 for the command to proceed.
+pub async fn subnet_loop(installer: Arc<SubnetInstaller>) {
+    info!("STEP: starting 'subnet_loop'");
+
+    loop {
+        sleep(Duration::from_secs(POLL_INTERVAL_SECONDS)).await;
+
+        let event = match installer.fetch_event().await {
+            Ok(Some(v)) => v,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!("failed to fetch subnet event ({}), retrying...", e);
+                continue;
+            }
+        };
+        match installer.fetch_status().await {
+            Ok(Some(status)) if event.is_applied(&status) => continue,
+            Ok(_) => {}
+            Err(e) => {
+                warn!("failed to fetch subnet status ({}), retrying...", e);
+                continue;
+            }
+        }
+        info!(
+            "STEP: installing VM {} for subnet {}",
+            event.vm_id, event.subnet_id
+        );
+
+        let message = match installer.apply(&event).await {
+            Ok(_) => {
+                info!("now tracking subnet {}", event.subnet_id);
+                None
+            }
+            Err(e) => {
+                error!("failed to track subnet {} ({})", event.subnet_id, e);
+                Some(e.to_string())
+            }
+        };
+        installer.report(&event, message).await;
+    }
+}
+
+impl SubnetInstaller {
+    async fn fetch_event(&self) -> io::Result<Option<avalanche_ops_aws::SubnetEvent>> {
+        let s3_key = avalanche_ops_aws::StorageNamespace::EventsSubnet(self.id.clone()).encode();
+        let d = match upgrade::get_object(self.s3_manager.clone(), &self.s3_bucket, &s3_key).await?
+        {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+        avalanche_ops_aws::SubnetEvent::decode_yaml(&d).map(Some)
+    }
+
+    async fn fetch_status(&self) -> io::Result<Option<avalanche_ops_aws::SubnetNodeStatus>> {
+        let d = match upgrade::get_object(
+            self.s3_manager.clone(),
+            &self.s3_bucket,
+            &self.status_s3_key(),
+        )
+        .await?
+        {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+        avalanche_ops_aws::SubnetNodeStatus::decode_yaml(&d).map(Some)
+    }
+
+    fn status_s3_key(&self) -> String {
+        avalanche_ops_aws::StorageNamespace::EventsSubnetStatus(
+            self.id.clone(),
+            self.local_node.node_id.clone(),
+        )
+        .encode()
+    }
+
+    async fn apply(&self, event: &avalanche_ops_aws::SubnetEvent) -> io::Result<()> {
+        let plugins_dir = super::get_plugins_dir(&self.avalanche_bin_path);
+        if !Path::new(&plugins_dir).exists() {
+            fs::create_dir_all(&plugins_dir)?;
+        }
+        let plugin_path = format!("{}/{}", plugins_dir, event.vm_id);
+
+        // new nodes already install the plugin and track the subnet on boot
+        let mut changed = false;
+        if avalanche_ops_aws::verify_sha256_file(&plugin_path, &event.vm_plugin_sha256).is_err() {
+            let s3_key = format!(
+                "{}/{}{}",
+                avalanche_ops_aws::StorageNamespace::PluginsDir(self.id.clone()).encode(),
+                event.vm_id,
+                compress::Encoder::Zstd(3).ext()
+            );
+            let tmp_path = random::tmp_path(15, Some(compress::Encoder::Zstd(3).ext()))?;
+            s3::spawn_get_object(
+                self.install_s3_manager.clone(),
+                &self.install_s3_bucket,
+                &s3_key,
+                &tmp_path,
+            )
+            .await?;
+
+            // verify before replacing, so a bad download does not break the running plugin
+            let staged_path = format!("{}.subnet", plugin_path);
+            compress::unpack_file(&tmp_path, &staged_path, compress::Decoder::Zstd)?;
+            fs::remove_file(&tmp_path)?;
+            if let Err(e) =
+                avalanche_ops_aws::verify_sha256_file(&staged_path, &event.vm_plugin_sha256)
+            {
+                fs::remove_file(&staged_path)?;
+                return Err(e);
+            }
+            File::open(&staged_path)?.set_permissions(PermissionsExt::from_mode(0o777))?;
+            fs::rename(&staged_path, &plugin_path)?;
+            info!("installed '{}'", plugin_path);
+            changed = true;
+        }
+
+        let mut config = avalanchego_config::Config::load(&self.avalanchego_config_file)?;
+        if config.track_subnet(&event.subnet_id) {
+            config.validate()?;
+            config.sync(None)?;
+            info!(
+                "added subnet {} to '{}'",
+                event.subnet_id, self.avalanchego_config_file
+            );
+            changed = true;
+        }
+        if !changed {
+            info!("VM and subnet already loaded, skipping restart");
+            return Ok(());
+        }
+
+        // avalanchego loads the plugins and the tracked subnets only on start
+        info!("STEP: restarting the node to load the VM and the subnet");
+        supervisor::restart()?;
+        upgrade::wait_for_health(
+            &self.local_node.http_endpoint,
+            self.health_check_timeout_seconds,
+        )
+        .await
+    }
+
+    async fn report(&self, event: &avalanche_ops_aws::SubnetEvent, message: Option<String>) {
+        let status = avalanche_ops_aws::SubnetNodeStatus {
+            node_id: self.local_node.node_id.clone(),
+            version: event.version,
+            subnet_id: event.subnet_id.clone(),
+            tracking: message.is_none(),
+            message,
+            updated_at: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("unexpected None duration_since")
+                .as_secs(),
+        };
+        match status.encode_yaml() {
+            Ok(contents) => {
+                let tmp_path =
+                    random::tmp_path(10, Some(".yaml")).expect("unexpected tmp_path failure");
+                fs::write(&tmp_path, contents).expect("failed fs::write");
+                if let Err(e) = s3::spawn_put_object(
+                    self.s3_manager.clone(),
+                    &tmp_path,
+                    &self.s3_bucket,
+                    &self.status_s3_key(),
+                )
+                .await
+                {
+                    warn!("failed to record subnet status ({})", e.message());
+                }
+                fs::remove_file(&tmp_path).expect("failed fs::remove_file");
+            }
+            Err(e) => warn!("failed to encode subnet status ({})", e),
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use std::{
+    fs,
+    io::{self, Error, ErrorKind},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use log::{error, info, warn};
+use tokio::time::sleep;
+
+use utils::{bash, random};
+
+pub const SERVICE_NAME: &str = "avalanche.service";
+pub const UNIT_FILE_PATH: &str = "/etc/systemd/system/avalanche.service";
+
+/// Interval between the service status polls.
+const POLL_INTERVAL_SECONDS: u64 = 10;
+/// Number of the last log lines to capture on each failure.
+const DIAGNOSTICS_LINES: u32 = 30;
+
+/// Returns the avalanchego systemd unit with the restart policy.
+/// systemd marks the unit failed once the start limit is hit,
+/// which "supervise_loop" detects as a crash loop.
+pub fn unit_file_contents(
+    avalanche_bin: &str,
+    config_file: &str,
+    policy: &avalanche_ops_aws::RestartPolicy,
+) -> String {
+    // don't use "Type=notify"
+    // as "avalanchego" currently does not do anything specific to systemd
+    // ref. "expected that the service sends a notification message via sd_notify"
+    // ref. https://www.freedesktop.org/software/systemd/man/systemd.service.html
+    //
+    // NOTE: remove "StandardOutput" and "StandardError" since we already
+    // ship the main and chain log files in "/var/log/avalanche" (duplicates)
+    format!(
+        "[Unit]
+Description=avalanche node
+StartLimitIntervalSec={}
+StartLimitBurst={}
+
+[Service]
+Type=exec
+TimeoutStartSec=300
+Restart=always
+RestartSec={}s
+LimitNOFILE=40000
+ExecStart={} --config-file={}
+StandardOutput=append:/var/log/avalanche/avalanche.log
+StandardError=append:/var/log/avalanche/avalanche.log
+
+[Install]
+WantedBy=multi-user.target",
+        policy.restart_interval_seconds,
+        policy.max_restarts,
+        policy.restart_delay_seconds,
+        avalanche_bin,
+        config_file,
+    )
+}
+
+/// Writes the unit file and reloads systemd, only if the contents changed.
+/// Returns true if the unit file has been updated.
+pub fn write_unit(contents: &str) -> io::Result<bool> {
+    if let Ok(existing) = fs::read_to_string(UNIT_FILE_PATH) {
+        if existing == contents {
+            info!("'{}' unchanged", UNIT_FILE_PATH);
+            return Ok(false);
+        }
+    }
+
+    let tmp_path = random::tmp_path(15, Some(".service"))?;
+    fs::write(&tmp_path, contents)?;
+    fs::copy(&tmp_path, UNIT_FILE_PATH)?;
+    fs::remove_file(&tmp_path)?;
+    bash::run("sudo systemctl daemon-reload")?;
+    Ok(true)
+}
+
+pub fn enable() -> io::Result<()> {
+    bash::run(format!("sudo systemctl enable {}", SERVICE_NAME).as_str())?;
+    Ok(())
+}
+
+pub fn start() -> io::Result<()> {
+    bash::run(format!("sudo systemctl start --no-block {}", SERVICE_NAME).as_str())?;
+    Ok(())
+}
+
+pub fn stop() -> io::Result<()> {
+    bash::run(format!("sudo systemctl stop {}", SERVICE_NAME).as_str())?;
+    Ok(())
+}
+
+pub fn restart() -> io::Result<()> {
+    bash::run(format!("sudo systemctl restart --no-block {}", SERVICE_NAME).as_str())?;
+    Ok(())
+}
+
+/// Clears the failed state and the restart counter,
+/// so that the service can be started after hitting the start limit.
+pub fn reset_failed() -> io::Result<()> {
+    bash::run(format!("sudo systemctl reset-failed {}", SERVICE_NAME).as_str())?;
+    Ok(())
+}
+
+/// Caps the I/O bandwidth of the service on the block device of "path"
+/// via the cgroup, until "reset_io_bandwidth" or the next reboot.
+/// Zero leaves the direction unlimited.
+/// ref. https://www.freedesktop.org/software/systemd/man/systemd.resource-control.html
+pub fn set_io_bandwidth(
+    path: &str,
+    read_bytes_per_second: u64,
+    write_bytes_per_second: u64,
+) -> io::Result<()> {
+    let mut properties = Vec::new();
+    if read_bytes_per_second > 0 {
+        properties.push(format!(
+            "'IOReadBandwidthMax={} {}'",
+            path, read_bytes_per_second
+        ));
+    }
+    if write_bytes_per_second > 0 {
+        properties.push(format!(
+            "'IOWriteBandwidthMax={} {}'",
+            path, write_bytes_per_second
+        ));
+    }
+    bash::run(
+        format!(
+            "sudo systemctl set-property --runtime {} {}",
+            SERVICE_NAME,
+            properties.join(" ")
+        )
+        .as_str(),
+    )?;
+    Ok(())
+}
+
+/// Removes the I/O bandwidth caps of "set_io_bandwidth".
+pub fn reset_io_bandwidth() -> io::Result<()> {
+    bash::run(
+        format!(
+            "sudo systemctl set-property --runtime {} IOReadBandwidthMax= IOWriteBandwidthMax=",
+            SERVICE_NAME
+        )
+        .as_str(),
+    )?;
+    Ok(())
+}
+
+/// Subset of the "systemctl show" properties of the service.
+/// ref. https://www.freedesktop.org/software/systemd/man/org.freedesktop.systemd1.html
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Status {
+    /// e.g., "active", "activating", "failed".
+    pub active_state: String,
+    /// e.g., "running", "auto-restart", "failed".
+    pub sub_state: String,
+    /// e.g., "success", "exit-code", "signal", "start-limit-hit".
+    pub result: String,
+    /// Number of the automatic restarts since the last manual start.
+    pub n_restarts: u32,
+    /// Exit code or signal number of the last main process.
+    pub exec_main_status: i32,
+}
+
+impl Status {
+    fn parse(out: &str) -> Self {
+        let mut status = Self::default();
+        for line in out.lines() {
+            let (k, v) = match line.split_once('=') {
+                Some(kv) => kv,
+                None => continue,
+            };
+            match k {
+                "ActiveState" => status.active_state = v.to_string(),
+                "SubState" => status.sub_state = v.to_string(),
+                "Result" => status.result = v.to_string(),
+                "NRestarts" => status.n_restarts = v.parse().unwrap_or_default(),
+                "ExecMainStatus" => status.exec_main_status = v.parse().unwrap_or_default(),
+                _ => {}
+            }
+        }
+        status
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.active_state == "active" && self.sub_state == "running"
+    }
+
+    /// Returns true if systemd gave up restarting the service.
+    pub fn is_failed(&self) -> bool {
+        self.active_state == "failed"
+    }
+}
+
+pub fn status() -> io::Result<Status> {
+    let (out, _) = bash::run(
+        format!(
+            "systemctl show {} --property=ActiveState,SubState,Result,NRestarts,ExecMainStatus",
+            SERVICE_NAME
+        )
+        .as_str(),
+    )?;
+    let status = Status::parse(&out);
+    if status.active_state.is_empty() {
+        return Err(Error::new(
+            ErrorKind::Other,
+            format!("unexpected 'systemctl show' output '{}'", out),
+        ));
+    }
+    Ok(status)
+}
+
+/// Returns the last exit status with the tails of the journal
+/// and the avalanchego log, to find out why the process exited.
+pub fn capture_diagnostics(status: &Status) -> String {
+    let mut diagnostics = format!(
+        "{} exited (result '{}', status {}, {} restarts)\n",
+        SERVICE_NAME, status.result, status.exec_main_status, status.n_restarts
+    );
+    for cmd in [
+        format!(
+            "sudo journalctl -u {} --lines={} --no-pager",
+            SERVICE_NAME, DIAGNOSTICS_LINES
+        ),
+        format!(
+            "sudo tail -{} /var/log/avalanche/avalanche.log",
+            DIAGNOSTICS_LINES
+        ),
+    ] {
+        match bash::run(cmd.as_str()) {
+            Ok((stdout, _)) => diagnostics.push_str(&format!("\n'{}':\n{}\n", cmd, stdout)),
+            Err(e) => diagnostics.push_str(&format!("\n'{}' failed: {}\n", cmd, e)),
+        }
+    }
+    diagnostics
+}
+
+/// Polls the service status, and logs the diagnostics on every restart.
+/// Once systemd gives up restarting (crash loop), restarts the service
+/// with the exponential backoff of the policy, until the crash loops
+/// exceed the limit so the node is left failed for the health checks.
+/// Stopped services (e.g., artifact updates) are left as is.
+pub async fn supervise_loop(policy: Arc<avalanche_ops_aws::RestartPolicy>) {
+    info!("STEP: starting 'supervise_loop'");
+
+    let mut last_restarts: Option<u32> = None;
+    let mut crash_loops: u32 = 0;
+    let mut running_since: Option<Instant> = None;
+    loop {
+        sleep(Duration::from_secs(POLL_INTERVAL_SECONDS)).await;
+
+        let status = match status() {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("failed to get {} status ({}), retrying...", SERVICE_NAME, e);
+                continue;
+            }
+        };
+
+        if let Some(prev) = last_restarts {
+            if status.n_restarts > prev {
+                warn!("{}", capture_diagnostics(&status));
+            }
+        }
+        last_restarts = Some(status.n_restarts);
+
+        if status.is_running() {
+            let since = *running_since.get_or_insert_with(Instant::now);
+            if crash_loops > 0
+                && since.elapsed() >= Duration::from_secs(policy.restart_interval_seconds)
+            {
+                info!(
+                    "{} stable for {} seconds, resetting {} crash loop(s)",
+                    SERVICE_NAME, policy.restart_interval_seconds, crash_loops
+                );
+                crash_loops = 0;
+            }
+        } else {
+            running_since = None;
+        }
+
+        if !status.is_failed() {
+            continue;
+        }
+        crash_loops += 1;
+        error!(
+            "{} crash loop {} detected\n{}",
+            SERVICE_NAME,
+            crash_loops,
+            capture_diagnostics(&status)
+        );
+
+        let backoff = match policy.backoff_seconds(crash_loops) {
+            Some(v) => v,
+            None => {
+                error!(
+                    "{} failed {} crash loop(s), exceeding the limit {} -- giving up",
+                    SERVICE_NAME, crash_loops, policy.max_crash_loops
+                );
+                return;
+            }
+        };
+        warn!("restarting {} in {} seconds", SERVICE_NAME, backoff);
+        sleep(Duration::from_secs(backoff)).await;
+
+        if let Err(e) = reset_failed().and_then(|_| start()) {
+            warn!("failed to restart {} ({})", SERVICE_NAME, e);
+        }
+        last_restarts = None;
+    }
+}
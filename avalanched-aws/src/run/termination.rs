@@ -0,0 +1,209 @@
+use std::{
+    fs,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+
+use log::{info, warn};
+use tokio::time::sleep;
+
+use avalanche_ops_aws::notify;
+use aws::{autoscaling, ec2, ec2::ebs, s3};
+use utils::random;
+
+use super::supervisor;
+
+pub struct TerminationHandler {
+    pub ec2_manager: ec2::Manager,
+    pub autoscaling_manager: autoscaling::Manager,
+    pub s3_manager: s3::Manager,
+    pub s3_bucket: String,
+    pub id: String,
+    pub local_node: avalanche_ops_aws::Node,
+    /// "None" if the ASG name tag is not found (e.g., launched out of the ASG).
+    pub asg_name: Option<String>,
+    pub policy: avalanche_ops_aws::TerminationPolicy,
+    /// Set once the termination starts, so that "register_node_loop"
+    /// stops the heartbeats.
+    pub terminating: Arc<AtomicBool>,
+    pub notifier: Arc<notify::Notifier>,
+}
+
+/// Polls the spot interruption notice and the ASG target lifecycle state,
+/// and drains the node once either shows the instance is going away.
+/// Returns once the termination proceeds, without exiting "avalanched",
+/// so that systemd does not restart the node on the terminating instance.
+pub async fn termination_loop(handler: Arc<TerminationHandler>) {
+    info!("STEP: starting 'termination_loop'");
+
+    let reason = loop {
+        sleep(Duration::from_secs(handler.policy.poll_interval_seconds)).await;
+
+        match ec2::fetch_spot_instance_action().await {
+            Ok(Some(action)) => {
+                warn!(
+                    "spot instance '{}' scheduled at {}",
+                    action.action, action.time
+                );
+                break avalanche_ops_aws::TerminationReason::SpotInterruption;
+            }
+            Ok(None) => {}
+            Err(e) => warn!("failed to fetch spot instance action {}", e.message()),
+        }
+
+        // only set with the lifecycle hook
+        if handler.asg_name.is_some() {
+            match ec2::fetch_target_lifecycle_state().await {
+                Ok(state) if state == ec2::TARGET_LIFECYCLE_STATE_TERMINATED => {
+                    warn!("ASG target lifecycle state '{}'", state);
+                    break avalanche_ops_aws::TerminationReason::AsgTermination;
+                }
+                Ok(_) => {}
+                Err(e) => warn!("failed to fetch target lifecycle state {}", e.message()),
+            }
+        }
+    };
+
+    handler.terminating.store(true, Ordering::SeqCst);
+    handler
+        .notifier
+        .notify(
+            notify::Event::NodeTerminating,
+            Some(&handler.local_node.node_id),
+            &format!(
+                "{} node {} is terminating ({})",
+                handler.local_node.kind,
+                handler.local_node.machine_id,
+                reason.as_str()
+            ),
+        )
+        .await;
+
+    handler.drain(reason).await;
+
+    if reason == avalanche_ops_aws::TerminationReason::AsgTermination {
+        let asg_name = handler.asg_name.clone().unwrap();
+        if let Err(e) = handler
+            .autoscaling_manager
+            .complete_lifecycle_action(
+                &asg_name,
+                avalanche_ops_aws::TERMINATION_LIFECYCLE_HOOK_NAME,
+                &handler.local_node.machine_id,
+                autoscaling::LIFECYCLE_ACTION_RESULT_CONTINUE,
+            )
+            .await
+        {
+            // the hook times out with "CONTINUE" anyways
+            warn!("failed to complete lifecycle action {}", e.message());
+        }
+    }
+    info!("node drained, waiting for the instance termination");
+}
+
+impl TerminationHandler {
+    /// Best-effort, since the instance is going away regardless:
+    /// each step logs the failure and moves on.
+    async fn drain(&self, reason: avalanche_ops_aws::TerminationReason) {
+        info!("STEP: deregistering the node from the discovery");
+        let registered_key = avalanche_ops_aws::StorageNamespace::DiscoverRegisteredNode(
+            self.id.clone(),
+            self.local_node.clone(),
+        )
+        .encode();
+        if let Err(e) = s3::spawn_delete_objects(
+            self.s3_manager.clone(),
+            &self.s3_bucket,
+            Some(registered_key),
+        )
+        .await
+        {
+            warn!("failed to delete registration {}", e.message());
+        }
+
+        info!("STEP: stopping avalanchego to flush the database");
+        if let Err(e) = supervisor::stop() {
+            warn!("failed to stop avalanchego ({})", e);
+        }
+
+        let snapshot_id = if self.policy.final_snapshot {
+            self.snapshot_data_volume().await
+        } else {
+            None
+        };
+
+        info!("STEP: publishing the termination notice");
+        let notice = avalanche_ops_aws::TerminationNotice {
+            machine_id: self.local_node.machine_id.clone(),
+            node_id: self.local_node.node_id.clone(),
+            node_kind: self.local_node.kind.clone(),
+            reason,
+            snapshot_id,
+            terminated_at: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("unexpected None duration_since")
+                .as_secs(),
+        };
+        let s3_key = avalanche_ops_aws::StorageNamespace::EventsTermination(
+            self.id.clone(),
+            self.local_node.machine_id.clone(),
+        )
+        .encode();
+        match notice.encode_yaml() {
+            Ok(contents) => {
+                let tmp_path =
+                    random::tmp_path(10, Some(".yaml")).expect("unexpected tmp_path failure");
+                fs::write(&tmp_path, contents).expect("failed fs::write");
+                if let Err(e) = s3::spawn_put_object(
+                    self.s3_manager.clone(),
+                    &tmp_path,
+                    &self.s3_bucket,
+                    &s3_key,
+                )
+                .await
+                {
+                    warn!("failed to put termination notice {}", e.message());
+                }
+                fs::remove_file(&tmp_path).expect("failed fs::remove_file");
+            }
+            Err(e) => warn!("failed to encode termination notice ({})", e),
+        }
+    }
+
+    /// Starts the snapshot of the data volume, which is point-in-time,
+    /// so the instance can be terminated while the snapshot is pending.
+    async fn snapshot_data_volume(&self) -> Option<String> {
+        info!("STEP: taking the final data volume snapshot");
+        let volume = match self
+            .ec2_manager
+            .find_attached_volume(&self.local_node.machine_id, ebs::DEFAULT_DATA_DEVICE_NAME)
+            .await
+        {
+            Ok(Some(v)) => v,
+            Ok(None) => {
+                warn!("no data volume attached, skipping final snapshot");
+                return None;
+            }
+            Err(e) => {
+                warn!("failed to find data volume {}", e.message());
+                return None;
+            }
+        };
+
+        let tags = avalanche_ops_aws::DataVolume::tags(&self.id, &self.local_node.kind);
+        let description = format!("{} {} final data volume", self.id, self.local_node.kind);
+        match self
+            .ec2_manager
+            .create_snapshot(&volume.volume_id, &description, &tags)
+            .await
+        {
+            Ok(snapshot) => Some(snapshot.snapshot_id),
+            Err(e) => {
+                warn!("failed to create final snapshot {}", e.message());
+                None
+            }
+        }
+    }
+}
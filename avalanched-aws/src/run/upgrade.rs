@@ -0,0 +1,567 @@
+use std::{
+    fs::{self, File},
+    io::{self, Error, ErrorKind},
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+
+use log::{error, info, warn};
+use tokio::time::sleep;
+
+use avalanche_api::health as api_health;
+use avalanche_ops_aws::notify;
+use aws::{cloudwatch, s3};
+use utils::{compress, random};
+
+use super::supervisor;
+
+/// Interval between the manifest polls.
+const POLL_INTERVAL_SECONDS: u64 = 180;
+
+/// Directory of the staged and the rollback binaries, next to the plugins
+/// directory, since avalanchego loads every file in the plugins directory as a VM.
+/// Within the same file system as the installed binaries for the atomic rename.
+const STAGING_DIR_NAME: &str = "upgrade-staging";
+/// Suffix of the verified binaries before the swap.
+const STAGED_SUFFIX: &str = ".upgrade";
+/// Suffix of the previous binaries kept for the rollback.
+const ROLLBACK_SUFFIX: &str = ".rollback";
+
+/// Represents the staged binary to swap with the installed one.
+struct Swap {
+    staged_path: String,
+    installed_path: String,
+    rollback_path: String,
+    /// Set once swapped, "false" if the binary is newly added (e.g., new plugin).
+    swapped: Option<bool>,
+}
+
+impl Swap {
+    /// Stages the binary at the same path relative to the avalanche binary
+    /// directory in "STAGING_DIR_NAME" (e.g., "plugins/[VM ID].upgrade").
+    fn new(avalanche_bin_path: &str, installed_path: &str) -> io::Result<Self> {
+        let bin_dir = Path::new(avalanche_bin_path)
+            .parent()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "no avalanche binary directory"))?;
+        let relative = Path::new(installed_path)
+            .strip_prefix(bin_dir)
+            .map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "'{}' not in '{}' ({})",
+                        installed_path,
+                        bin_dir.display(),
+                        e
+                    ),
+                )
+            })?;
+        let staging = bin_dir
+            .join(STAGING_DIR_NAME)
+            .join(relative)
+            .display()
+            .to_string();
+        Ok(Self {
+            staged_path: format!("{}{}", staging, STAGED_SUFFIX),
+            installed_path: installed_path.to_string(),
+            rollback_path: format!("{}{}", staging, ROLLBACK_SUFFIX),
+            swapped: None,
+        })
+    }
+}
+
+pub struct Upgrader {
+    pub s3_manager: s3::Manager,
+    pub s3_bucket: String,
+    pub id: String,
+    pub local_node: avalanche_ops_aws::Node,
+    pub network_id: u32,
+    pub avalanche_bin_path: String,
+    pub policy: avalanche_ops_aws::UpgradePolicy,
+
+    pub cw_manager: cloudwatch::Manager,
+    pub cw_namespace: String,
+
+    pub notifier: Arc<notify::Notifier>,
+}
+
+/// Polls the upgrade manifest, and applies each new version once:
+/// downloads and verifies the binaries, stops the node, swaps the binaries,
+/// and rolls back if the node does not become healthy.
+/// The result is recorded in S3 and CloudWatch, and the last recorded
+/// version is not applied again after "avalanched" restarts.
+pub async fn upgrade_loop(upgrader: Arc<Upgrader>) {
+    info!("STEP: starting 'upgrade_loop'");
+
+    let mut applied_version = match upgrader.fetch_status().await {
+        Ok(Some(status)) => {
+            info!(
+                "last upgrade version {} {}",
+                status.version,
+                status.state.as_str()
+            );
+            status.version
+        }
+        Ok(None) => 0,
+        Err(e) => {
+            warn!("failed to fetch upgrade status ({}), assuming none", e);
+            0
+        }
+    };
+
+    loop {
+        sleep(Duration::from_secs(POLL_INTERVAL_SECONDS)).await;
+
+        let manifest = match upgrader.fetch_manifest().await {
+            Ok(Some(v)) => v,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!("failed to fetch upgrade manifest ({}), retrying...", e);
+                continue;
+            }
+        };
+        let rollout = match upgrader.fetch_rollout(manifest.version).await {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("failed to fetch upgrade rollout ({}), retrying...", e);
+                continue;
+            }
+        };
+        if manifest.version <= applied_version {
+            if let Some(rollout) = &rollout {
+                if manifest.version == applied_version {
+                    upgrader.finish_rollout(&manifest, rollout).await;
+                }
+            }
+            continue;
+        }
+        if let Some(rollout) = &rollout {
+            if !rollout.allows(&upgrader.local_node.machine_id) {
+                continue;
+            }
+        }
+        info!(
+            "STEP: applying upgrade manifest version {}",
+            manifest.version
+        );
+
+        // keeps the previous binaries until the rolling upgrade completes
+        let keep_rollback = rollout.map_or(false, |r| {
+            r.state == avalanche_ops_aws::UpgradeRolloutState::InProgress
+        });
+        let (state, message) = match upgrader.apply(&manifest, keep_rollback).await {
+            Ok(state) => (state, None),
+            Err((state, e)) => {
+                error!(
+                    "upgrade version {} {} ({})",
+                    manifest.version,
+                    state.as_str(),
+                    e
+                );
+                upgrader
+                    .notifier
+                    .notify(
+                        notify::Event::UpgradeFailed,
+                        Some(&upgrader.local_node.node_id),
+                        &format!(
+                            "upgrade version {} {} ({})",
+                            manifest.version,
+                            state.as_str(),
+                            e
+                        ),
+                    )
+                    .await;
+                (state, Some(e.to_string()))
+            }
+        };
+        applied_version = manifest.version;
+        upgrader.report(manifest.version, state, message).await;
+    }
+}
+
+impl Upgrader {
+    async fn fetch_manifest(&self) -> io::Result<Option<avalanche_ops_aws::UpgradeManifest>> {
+        let s3_key =
+            avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsManifest(self.id.clone())
+                .encode();
+        let d = match self.get_object(&s3_key).await? {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+        let manifest = avalanche_ops_aws::UpgradeManifest::decode_yaml(&d)?;
+        manifest.validate()?;
+        Ok(Some(manifest))
+    }
+
+    async fn fetch_status(&self) -> io::Result<Option<avalanche_ops_aws::UpgradeStatus>> {
+        let s3_key = self.status_s3_key();
+        let d = match self.get_object(&s3_key).await? {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+        avalanche_ops_aws::UpgradeStatus::decode_yaml(&d).map(Some)
+    }
+
+    /// Returns "None" if the manifest version is not rolled out in waves.
+    async fn fetch_rollout(
+        &self,
+        version: u64,
+    ) -> io::Result<Option<avalanche_ops_aws::UpgradeRollout>> {
+        let s3_key =
+            avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsRollout(self.id.clone())
+                .encode();
+        let d = match self.get_object(&s3_key).await? {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+        let rollout = avalanche_ops_aws::UpgradeRollout::decode_yaml(&d)?;
+        if rollout.version != version {
+            return Ok(None);
+        }
+        Ok(Some(rollout))
+    }
+
+    /// Removes the kept previous binaries once the rolling upgrade completes,
+    /// or restores them if aborted.
+    async fn finish_rollout(
+        &self,
+        manifest: &avalanche_ops_aws::UpgradeManifest,
+        rollout: &avalanche_ops_aws::UpgradeRollout,
+    ) {
+        let kept = Swap::new(&self.avalanche_bin_path, &self.avalanche_bin_path)
+            .map_or(false, |sw| Path::new(&sw.rollback_path).exists());
+        if !kept {
+            return;
+        }
+        let swaps = self.kept_swaps(manifest);
+        match rollout.state {
+            avalanche_ops_aws::UpgradeRolloutState::Completed => {
+                info!("rollout version {} completed", manifest.version);
+                cleanup(&swaps);
+            }
+            avalanche_ops_aws::UpgradeRolloutState::Aborted
+                if rollout.rolls_back(&self.local_node.machine_id) =>
+            {
+                warn!("rollout version {} aborted, rolling back", manifest.version);
+                let state = self.rollback(&swaps);
+                if let Err(e) = self.wait_for_health().await {
+                    error!("node is not healthy after rollback ({})", e);
+                }
+                self.report(
+                    manifest.version,
+                    state,
+                    Some(String::from("rolling upgrade aborted")),
+                )
+                .await;
+            }
+            _ => {}
+        }
+    }
+
+    fn status_s3_key(&self) -> String {
+        avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsStatus(
+            self.id.clone(),
+            self.local_node.node_id.clone(),
+        )
+        .encode()
+    }
+
+    async fn get_object(&self, s3_key: &str) -> io::Result<Option<Vec<u8>>> {
+        get_object(self.s3_manager.clone(), &self.s3_bucket, s3_key).await
+    }
+
+    /// Returns the upgrade state with the error if the upgrade did not succeed.
+    /// If "keep_rollback" is true, the previous binaries are kept on success.
+    async fn apply(
+        &self,
+        manifest: &avalanche_ops_aws::UpgradeManifest,
+        keep_rollback: bool,
+    ) -> Result<avalanche_ops_aws::UpgradeState, (avalanche_ops_aws::UpgradeState, Error)> {
+        let rejected = |e: Error| (avalanche_ops_aws::UpgradeState::Rejected, e);
+
+        if let Some(public_key) = &self.policy.signing_public_key {
+            manifest.verify_signature(public_key).map_err(rejected)?;
+            info!("verified manifest version {} signature", manifest.version);
+        }
+        let mut swaps = self.download_and_verify(manifest).await.map_err(rejected)?;
+
+        // avalanchego has no drain API, so stop gracefully (SIGTERM) before the swap,
+        // the supervisor leaves the stopped service as is
+        info!("STEP: draining the node before swapping binaries");
+        supervisor::stop().map_err(rejected)?;
+
+        if let Err(e) = swap(&mut swaps) {
+            warn!("failed to swap binaries ({}), rolling back", e);
+            return Err((self.rollback(&swaps), e));
+        }
+        info!("swapped {} binaries", swaps.len());
+        if let Err(e) = supervisor::start() {
+            warn!("failed to start upgraded node ({}), rolling back", e);
+            return Err((self.rollback(&swaps), e));
+        }
+
+        match self.wait_for_health().await {
+            Ok(_) => {
+                info!("upgrade version {} succeeded", manifest.version);
+                if !keep_rollback {
+                    cleanup(&swaps);
+                }
+                Ok(avalanche_ops_aws::UpgradeState::Succeeded)
+            }
+            Err(e) => {
+                warn!("upgraded node is not healthy ({}), rolling back", e);
+                let state = self.rollback(&swaps);
+                if matches!(state, avalanche_ops_aws::UpgradeState::RolledBack) {
+                    if let Err(e) = self.wait_for_health().await {
+                        error!("node is not healthy after rollback ({})", e);
+                    }
+                }
+                Err((state, e))
+            }
+        }
+    }
+
+    /// Downloads each binary to "STAGING_DIR_NAME" with "STAGED_SUFFIX",
+    /// and verifies its checksum.
+    async fn download_and_verify(
+        &self,
+        manifest: &avalanche_ops_aws::UpgradeManifest,
+    ) -> io::Result<Vec<Swap>> {
+        let plugins_dir = super::get_plugins_dir(&self.avalanche_bin_path);
+        if !manifest.plugins_sha256.is_empty() && !Path::new(&plugins_dir).exists() {
+            fs::create_dir_all(&plugins_dir)?;
+        }
+
+        let mut swaps = Vec::new();
+        for (s3_key, installed_path, digest) in self.artifacts(manifest).iter() {
+            let sw = Swap::new(&self.avalanche_bin_path, installed_path)?;
+            let staged_path = sw.staged_path.clone();
+            if let Some(parent) = Path::new(&staged_path).parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let tmp_path = random::tmp_path(15, Some(compress::Encoder::Zstd(3).ext()))?;
+            s3::spawn_get_object(self.s3_manager.clone(), &self.s3_bucket, s3_key, &tmp_path)
+                .await?;
+            compress::unpack_file(&tmp_path, &staged_path, compress::Decoder::Zstd)?;
+            fs::remove_file(&tmp_path)?;
+
+            avalanche_ops_aws::verify_sha256_file(&staged_path, digest)?;
+            File::open(&staged_path)?.set_permissions(PermissionsExt::from_mode(0o777))?;
+            info!("verified '{}' for '{}'", staged_path, installed_path);
+            swaps.push(sw);
+        }
+        Ok(swaps)
+    }
+
+    /// Returns the S3 key, the installed path, and the digest of each binary.
+    fn artifacts(
+        &self,
+        manifest: &avalanche_ops_aws::UpgradeManifest,
+    ) -> Vec<(String, String, String)> {
+        let mut artifacts = vec![(
+            avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsInstallDirAvalancheBinCompressed(
+                self.id.clone(),
+            )
+            .encode(),
+            self.avalanche_bin_path.clone(),
+            manifest.avalanche_bin_sha256.clone(),
+        )];
+        let plugins_dir = super::get_plugins_dir(&self.avalanche_bin_path);
+        let plugins_s3_dir =
+            avalanche_ops_aws::StorageNamespace::EventsUpdateArtifactsInstallDirPluginsDir(
+                self.id.clone(),
+            )
+            .encode();
+        for (name, digest) in manifest.plugins_sha256.iter() {
+            artifacts.push((
+                format!(
+                    "{}/{}{}",
+                    plugins_s3_dir,
+                    name,
+                    compress::Encoder::Zstd(3).ext()
+                ),
+                format!("{}/{}", plugins_dir, name),
+                digest.clone(),
+            ));
+        }
+        artifacts
+    }
+
+    /// Rebuilds the swaps of the applied manifest from the kept previous
+    /// binaries, since "avalanched" may have restarted since the upgrade.
+    /// The binaries without the previous one were newly added.
+    fn kept_swaps(&self, manifest: &avalanche_ops_aws::UpgradeManifest) -> Vec<Swap> {
+        self.artifacts(manifest)
+            .into_iter()
+            .filter_map(|(_, installed_path, _)| {
+                match Swap::new(&self.avalanche_bin_path, &installed_path) {
+                    Ok(mut sw) => {
+                        sw.swapped = Some(Path::new(&sw.rollback_path).exists());
+                        Some(sw)
+                    }
+                    Err(e) => {
+                        warn!("failed to find kept '{}' ({})", installed_path, e);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    async fn wait_for_health(&self) -> io::Result<()> {
+        wait_for_health(
+            &self.local_node.http_endpoint,
+            self.policy.health_check_timeout_seconds,
+        )
+        .await
+    }
+
+    /// Restores the previous binaries and restarts the node.
+    /// Returns "Rejected" if nothing has been swapped, otherwise "RolledBack".
+    fn rollback(&self, swaps: &[Swap]) -> avalanche_ops_aws::UpgradeState {
+        let mut restored = 0;
+        for sw in swaps.iter() {
+            let ret = match sw.swapped {
+                Some(true) => fs::rename(&sw.rollback_path, &sw.installed_path),
+                Some(false) => fs::remove_file(&sw.installed_path),
+                None => continue,
+            };
+            match ret {
+                Ok(_) => restored += 1,
+                Err(e) => error!("failed to restore '{}' ({})", sw.installed_path, e),
+            }
+        }
+        cleanup(swaps);
+        if let Err(e) = supervisor::restart() {
+            error!("failed to restart after rollback ({})", e);
+        }
+        if restored == 0 {
+            return avalanche_ops_aws::UpgradeState::Rejected;
+        }
+        info!("rolled back {} binaries", restored);
+        avalanche_ops_aws::UpgradeState::RolledBack
+    }
+
+    /// Records the upgrade status in S3 and the count metric in CloudWatch.
+    async fn report(
+        &self,
+        version: u64,
+        state: avalanche_ops_aws::UpgradeState,
+        message: Option<String>,
+    ) {
+        let metric_name = format!("upgrade_{}", state.as_str().replace('-', "_"));
+        let status = avalanche_ops_aws::UpgradeStatus {
+            node_id: self.local_node.node_id.clone(),
+            version,
+            state,
+            message,
+            updated_at: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("unexpected None duration_since")
+                .as_secs(),
+        };
+        match status.encode_yaml() {
+            Ok(contents) => {
+                let tmp_path =
+                    random::tmp_path(10, Some(".yaml")).expect("unexpected tmp_path failure");
+                fs::write(&tmp_path, contents).expect("failed fs::write");
+                if let Err(e) = s3::spawn_put_object(
+                    self.s3_manager.clone(),
+                    &tmp_path,
+                    &self.s3_bucket,
+                    &self.status_s3_key(),
+                )
+                .await
+                {
+                    warn!("failed to record upgrade status ({})", e.message());
+                }
+                fs::remove_file(&tmp_path).expect("failed fs::remove_file");
+            }
+            Err(e) => warn!("failed to encode upgrade status ({})", e),
+        }
+
+        let network_id = self.network_id.to_string();
+        let data = cloudwatch::with_dimensions(
+            vec![cloudwatch::new_count_datum(&metric_name, 1.0)],
+            &[
+                ("network-id", network_id.as_str()),
+                ("node-id", self.local_node.node_id.as_str()),
+            ],
+        );
+        if let Err(e) =
+            cloudwatch::spawn_put_metric_data(self.cw_manager.clone(), &self.cw_namespace, data)
+                .await
+        {
+            warn!("failed to put upgrade metric ({})", e);
+        }
+    }
+}
+
+/// Returns "None" if the object does not exist.
+pub(super) async fn get_object(
+    s3_manager: s3::Manager,
+    s3_bucket: &str,
+    s3_key: &str,
+) -> io::Result<Option<Vec<u8>>> {
+    let objects =
+        s3::spawn_list_objects(s3_manager.clone(), s3_bucket, Some(s3_key.to_string())).await?;
+    if !objects.iter().any(|o| o.key() == Some(s3_key)) {
+        return Ok(None);
+    }
+
+    let tmp_path = random::tmp_path(15, Some(".yaml"))?;
+    s3::spawn_get_object(s3_manager, s3_bucket, s3_key, &tmp_path).await?;
+    let d = fs::read(&tmp_path)?;
+    fs::remove_file(&tmp_path)?;
+    Ok(Some(d))
+}
+
+/// Polls the local node health until healthy or "timeout_seconds" elapse.
+pub(super) async fn wait_for_health(http_endpoint: &str, timeout_seconds: u64) -> io::Result<()> {
+    let timeout = Duration::from_secs(timeout_seconds);
+    let started = Instant::now();
+    loop {
+        sleep(Duration::from_secs(20)).await;
+        match api_health::spawn_check(http_endpoint, true).await {
+            Ok(res) if res.healthy == Some(true) => return Ok(()),
+            Ok(res) => info!("node not healthy yet {:?}", res.checks),
+            Err(e) => info!("health check failed ({})", e),
+        }
+        if started.elapsed() >= timeout {
+            return Err(Error::new(
+                ErrorKind::TimedOut,
+                format!("node not healthy within {} seconds", timeout_seconds),
+            ));
+        }
+    }
+}
+
+/// Copies each installed binary to "ROLLBACK_SUFFIX" and moves the staged one in place.
+/// "rename" is atomic within the same file system, so each path always has
+/// either the previous or the new binary.
+fn swap(swaps: &mut [Swap]) -> io::Result<()> {
+    for sw in swaps.iter_mut() {
+        let existed = Path::new(&sw.installed_path).exists();
+        if existed {
+            fs::copy(&sw.installed_path, &sw.rollback_path)?;
+        }
+        fs::rename(&sw.staged_path, &sw.installed_path)?;
+        sw.swapped = Some(existed);
+    }
+    Ok(())
+}
+
+/// Removes the staged and the rollback binaries.
+fn cleanup(swaps: &[Swap]) {
+    for sw in swaps.iter() {
+        for p in [sw.staged_path.clone(), sw.rollback_path.clone()] {
+            if Path::new(&p).exists() {
+                if let Err(e) = fs::remove_file(&p) {
+                    warn!("failed to remove '{}' ({})", p, e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,11 @@
+pub mod provision;
+
+use clap::Command;
+
+pub const NAME: &str = "volume";
+
+pub fn command() -> Command<'static> {
+    Command::new(NAME)
+        .about("Manages the data volume of the local instance")
+        .subcommands(vec![provision::subcommand()])
+}
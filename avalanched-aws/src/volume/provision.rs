@@ -0,0 +1,370 @@
+use std::{
+    fs,
+    io::{self, Error, ErrorKind},
+    sync::Arc,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use clap::{Arg, Command};
+use log::{info, warn};
+use tokio::{runtime::Runtime, time::sleep};
+
+use aws::{self, ec2, ec2::ebs, s3};
+use utils::random;
+
+pub const NAME: &str = "provision";
+
+/// Up to 30 minutes for the final snapshot of the terminated instance.
+const FINAL_SNAPSHOT_WAIT_RETRIES: usize = 60;
+
+/// Time for the concurrent claims on the same source node to land,
+/// before picking the winner.
+const CLONE_CLAIM_SETTLE_SECONDS: u64 = 10;
+
+pub fn subcommand() -> Command<'static> {
+    Command::new(NAME)
+        .about("Attaches the data volume left by the terminated instance, or creates one (run before mount)")
+        .arg(
+            Arg::new("REGION")
+                .long("region")
+                .short('r')
+                .help("Sets the AWS region for API calls/endpoints")
+                .required(true)
+                .takes_value(true)
+                .allow_invalid_utf8(false)
+                .default_value("us-west-2"),
+        )
+        .arg(
+            Arg::new("LOG_LEVEL")
+                .long("log-level")
+                .short('l')
+                .help("Sets the log level")
+                .required(false)
+                .takes_value(true)
+                .possible_value("debug")
+                .possible_value("info")
+                .allow_invalid_utf8(false),
+        )
+}
+
+pub fn execute(reg: &str, log_level: &str) -> io::Result<()> {
+    // ref. https://github.com/env-logger-rs/env_logger/issues/47
+    env_logger::init_from_env(
+        env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, log_level),
+    );
+
+    let rt = Runtime::new().unwrap();
+
+    let az = rt.block_on(ec2::fetch_availability_zone()).unwrap();
+    info!("fetched availability zone {}", az);
+    let instance_id = rt.block_on(ec2::fetch_instance_id()).unwrap();
+    info!("fetched instance ID {}", instance_id);
+
+    info!("STEP: loading AWS config");
+    let shared_config = rt
+        .block_on(aws::load_config(Some(reg.to_string())))
+        .unwrap();
+    let ec2_manager = ec2::Manager::new(&shared_config);
+    let s3_manager = s3::Manager::new(&shared_config);
+
+    let device = ebs::DEFAULT_DATA_DEVICE_NAME;
+    if let Some(v) = rt.block_on(ec2_manager.find_attached_volume(&instance_id, device))? {
+        info!("volume '{}' already attached at '{}'", v.volume_id, device);
+        return Ok(());
+    }
+
+    info!("STEP: fetching tags from the local instance");
+    let tags = rt.block_on(ec2_manager.fetch_tags(Arc::new(instance_id.clone())))?;
+    let tag_value = |k: &str| -> io::Result<String> {
+        match tags
+            .iter()
+            .find(|t| t.key() == Some(k))
+            .and_then(|t| t.value())
+        {
+            Some(v) => Ok(v.to_string()),
+            None => Err(Error::new(
+                ErrorKind::NotFound,
+                format!("'{}' tag not found", k),
+            )),
+        }
+    };
+    let id = tag_value("ID")?;
+    let node_kind = tag_value("NODE_KIND")?;
+    let s3_bucket = tag_value("S3_BUCKET_NAME")?;
+
+    info!("STEP: downloading avalanche-ops::Spec from S3");
+    let tmp_spec_file_path = random::tmp_path(15, Some(".yaml")).unwrap();
+    rt.block_on(s3_manager.get_object(
+        Arc::new(s3_bucket.clone()),
+        Arc::new(avalanche_ops_aws::StorageNamespace::ConfigFile(id.clone()).encode()),
+        Arc::new(tmp_spec_file_path.clone()),
+    ))?;
+    let spec = avalanche_ops_aws::Spec::load(&tmp_spec_file_path)?;
+    fs::remove_file(&tmp_spec_file_path)?;
+    let data_volume = match spec.machine.data_volume {
+        Some(v) => v,
+        None => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'machine.data_volume' not found in spec",
+            ));
+        }
+    };
+
+    let volume_tags = avalanche_ops_aws::DataVolume::tags(&id, &node_kind);
+
+    info!("STEP: attaching the volume left by the terminated instance (if any)");
+    let available = rt.block_on(ec2_manager.list_available_volumes(Some(&az), &volume_tags))?;
+    for v in available.iter() {
+        // other instances may race for the same volume
+        match rt.block_on(ec2_manager.attach_volume(&v.volume_id, &instance_id, device)) {
+            Ok(_) => {
+                info!("'avalanched volume provision' reattached '{}'", v.volume_id);
+                return Ok(());
+            }
+            Err(e) => warn!("failed to attach '{}' ({})", v.volume_id, e.message()),
+        }
+    }
+
+    let snapshot_id = if spec.clone_source.is_some() {
+        info!("STEP: claiming the snapshot of the cloned source node");
+        let claimed = rt.block_on(claim_clone_source(
+            &s3_manager,
+            &s3_bucket,
+            &id,
+            &node_kind,
+            &instance_id,
+        ))?;
+        if claimed.is_none() {
+            warn!(
+                "no unclaimed source node of kind '{}', creating an empty volume",
+                node_kind
+            );
+        }
+        claimed.map(|n| n.snapshot_id)
+    } else if data_volume.restore_from_snapshot {
+        info!("STEP: finding the latest snapshot to restore from");
+        let final_snapshot_id = rt.block_on(fetch_final_snapshot_id(
+            &s3_manager,
+            &s3_bucket,
+            &id,
+            &node_kind,
+        ))?;
+        let mut snapshots = rt.block_on(ec2_manager.list_snapshots(&volume_tags))?;
+        if let Some(final_snapshot_id) = &final_snapshot_id {
+            // the final snapshot is started right before the termination
+            // so it may be still pending
+            for _ in 0..FINAL_SNAPSHOT_WAIT_RETRIES {
+                let completed = snapshots
+                    .iter()
+                    .any(|s| &s.snapshot_id == final_snapshot_id && s.is_completed());
+                if completed {
+                    break;
+                }
+                info!("waiting for the final snapshot '{}'", final_snapshot_id);
+                thread::sleep(Duration::from_secs(30));
+                snapshots = rt.block_on(ec2_manager.list_snapshots(&volume_tags))?;
+            }
+        }
+        let latest = ebs::latest_completed(&snapshots).map(|s| s.snapshot_id.clone());
+        if latest.is_none() {
+            warn!("no completed snapshot found, creating an empty volume");
+        }
+        latest
+    } else {
+        None
+    };
+
+    info!("STEP: creating a new data volume");
+    let name = format!("{}-{}-{}", id, node_kind, spec.machine.arch);
+    let mut create_tags = volume_tags.to_vec();
+    create_tags.push(("Name", name.as_str()));
+    let volume = rt.block_on(ec2_manager.create_volume(
+        &data_volume.volume,
+        &az,
+        snapshot_id.as_deref(),
+        &create_tags,
+    ))?;
+    rt.block_on(ec2_manager.attach_volume(&volume.volume_id, &instance_id, device))?;
+
+    info!(
+        "'avalanched volume provision' attached '{}' (restored from {:?})",
+        volume.volume_id, volume.snapshot_id
+    );
+    Ok(())
+}
+
+/// Returns the final snapshot of the most recently terminated node of the same kind,
+/// if published by the termination handler.
+async fn fetch_final_snapshot_id(
+    s3_manager: &s3::Manager,
+    s3_bucket: &str,
+    id: &str,
+    node_kind: &str,
+) -> io::Result<Option<String>> {
+    let s3_key = avalanche_ops_aws::StorageNamespace::EventsTerminationDir(id.to_string()).encode();
+    let objects = s3::spawn_list_objects(s3_manager.clone(), s3_bucket, Some(s3_key)).await?;
+
+    let mut notices = Vec::new();
+    for obj in objects.iter() {
+        let s3_key = match obj.key() {
+            Some(k) => k.to_string(),
+            None => continue,
+        };
+        let tmp_path = random::tmp_path(10, Some(".yaml"))?;
+        s3::spawn_get_object(s3_manager.clone(), s3_bucket, &s3_key, &tmp_path).await?;
+        match avalanche_ops_aws::TerminationNotice::decode_yaml(&fs::read(&tmp_path)?) {
+            Ok(notice) => notices.push(notice),
+            Err(e) => warn!("skipping invalid termination notice '{}' ({})", s3_key, e),
+        }
+        fs::remove_file(&tmp_path)?;
+    }
+
+    let snapshot_id = avalanche_ops_aws::latest_final_snapshot(&notices, node_kind);
+    if let Some(v) = &snapshot_id {
+        info!(
+            "found final snapshot '{}' from {} notice(s)",
+            v,
+            notices.len()
+        );
+    }
+    Ok(snapshot_id)
+}
+
+/// Claims one source node of the same kind in the snapshot of the cloned network,
+/// so that each new node restores a distinct data volume. Concurrent claims on
+/// the same source node are resolved by "clone_claim_winner", and the losers
+/// move on to the next source node. Returns the claimed source node, if any.
+async fn claim_clone_source(
+    s3_manager: &s3::Manager,
+    s3_bucket: &str,
+    id: &str,
+    node_kind: &str,
+    instance_id: &str,
+) -> io::Result<Option<avalanche_ops_aws::SnapshotNode>> {
+    let assignment_key = avalanche_ops_aws::StorageNamespace::CloneAssignment(
+        id.to_string(),
+        instance_id.to_string(),
+    )
+    .encode();
+    if let Some(d) = get_object(s3_manager, s3_bucket, &assignment_key).await? {
+        // provisioned before the reboot, but the volume was not attached
+        let claim = avalanche_ops_aws::CloneClaim::decode_yaml(&d)?;
+        return Ok(Some(claim.source));
+    }
+
+    let snapshot_key = avalanche_ops_aws::StorageNamespace::CloneSnapshot(id.to_string()).encode();
+    let snapshot = match get_object(s3_manager, s3_bucket, &snapshot_key).await? {
+        Some(d) => avalanche_ops_aws::NetworkSnapshot::decode_yaml(&d)?,
+        None => {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("'{}' not found (run 'apply' first)", snapshot_key),
+            ))
+        }
+    };
+
+    for source in snapshot.nodes.iter().filter(|n| n.kind == node_kind) {
+        let claims_dir = s3::append_slash(
+            &avalanche_ops_aws::StorageNamespace::CloneClaimsDir(
+                id.to_string(),
+                source.machine_id.clone(),
+            )
+            .encode(),
+        );
+        let objects =
+            s3::spawn_list_objects(s3_manager.clone(), s3_bucket, Some(claims_dir.clone())).await?;
+        if !objects.is_empty() {
+            // every claimed source node already has a winner
+            continue;
+        }
+
+        let claim = avalanche_ops_aws::CloneClaim {
+            machine_id: instance_id.to_string(),
+            source: source.clone(),
+            claimed_at: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("unexpected None duration_since")
+                .as_secs(),
+        };
+        put_object(
+            s3_manager,
+            s3_bucket,
+            &avalanche_ops_aws::StorageNamespace::CloneClaim(
+                id.to_string(),
+                source.machine_id.clone(),
+                instance_id.to_string(),
+            )
+            .encode(),
+            claim.encode_yaml()?.as_bytes(),
+        )
+        .await?;
+        sleep(Duration::from_secs(CLONE_CLAIM_SETTLE_SECONDS)).await;
+
+        let objects =
+            s3::spawn_list_objects(s3_manager.clone(), s3_bucket, Some(claims_dir)).await?;
+        let mut claims = Vec::new();
+        for obj in objects.iter() {
+            let s3_key = match obj.key() {
+                Some(k) => k,
+                None => continue,
+            };
+            if let Some(d) = get_object(s3_manager, s3_bucket, s3_key).await? {
+                match avalanche_ops_aws::CloneClaim::decode_yaml(&d) {
+                    Ok(c) => claims.push(c),
+                    Err(e) => warn!("skipping invalid clone claim '{}' ({})", s3_key, e),
+                }
+            }
+        }
+        if avalanche_ops_aws::clone_claim_winner(&claims) != Some(instance_id) {
+            info!("lost the claim on '{}', trying the next", source.machine_id);
+            continue;
+        }
+
+        info!(
+            "claimed source node '{}' ({}, snapshot '{}')",
+            source.machine_id, source.node_id, source.snapshot_id
+        );
+        put_object(
+            s3_manager,
+            s3_bucket,
+            &assignment_key,
+            claim.encode_yaml()?.as_bytes(),
+        )
+        .await?;
+        return Ok(Some(claim.source));
+    }
+    Ok(None)
+}
+
+/// Returns "None" if the object does not exist.
+async fn get_object(
+    s3_manager: &s3::Manager,
+    s3_bucket: &str,
+    s3_key: &str,
+) -> io::Result<Option<Vec<u8>>> {
+    let objects =
+        s3::spawn_list_objects(s3_manager.clone(), s3_bucket, Some(s3_key.to_string())).await?;
+    if !objects.iter().any(|o| o.key() == Some(s3_key)) {
+        return Ok(None);
+    }
+
+    let tmp_path = random::tmp_path(15, Some(".yaml"))?;
+    s3::spawn_get_object(s3_manager.clone(), s3_bucket, s3_key, &tmp_path).await?;
+    let d = fs::read(&tmp_path)?;
+    fs::remove_file(&tmp_path)?;
+    Ok(Some(d))
+}
+
+async fn put_object(
+    s3_manager: &s3::Manager,
+    s3_bucket: &str,
+    s3_key: &str,
+    d: &[u8],
+) -> io::Result<()> {
+    let tmp_path = random::tmp_path(15, Some(".yaml"))?;
+    fs::write(&tmp_path, d)?;
+    s3::spawn_put_object(s3_manager.clone(), &tmp_path, s3_bucket, s3_key).await?;
+    fs::remove_file(&tmp_path)
+}
@@ -0,0 +1,267 @@
+use std::{
+    fs::{self, File},
+    io::{self, Error, ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config;
+
+/// Lays down the per-chain and per-subnet configuration files
+/// under the directories that avalanchego reads on start-up.
+/// ref. https://docs.avax.network/nodes/maintain/chain-config-flags
+/// ref. https://github.com/ava-labs/avalanchego/blob/v1.7.6/config/config.go
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct ChainConfigManager {
+    /// "chain-config-dir" where each chain reads "[CHAIN_ID_OR_ALIAS]/config.json".
+    pub chain_config_dir: String,
+    /// "subnet-config-dir" where each subnet reads "[SUBNET_ID].json".
+    pub subnet_config_dir: String,
+}
+
+impl ChainConfigManager {
+    pub fn new(chain_config_dir: &str, subnet_config_dir: &str) -> Self {
+        Self {
+            chain_config_dir: String::from(chain_config_dir),
+            subnet_config_dir: String::from(subnet_config_dir),
+        }
+    }
+
+    /// Creates the manager with the directories of the avalanchego configuration.
+    pub fn from_config(config: &config::Config) -> Self {
+        Self::new(
+            &config.chain_config_dir,
+            config
+                .subnet_config_dir
+                .as_deref()
+                .unwrap_or(config::DEFAULT_SUBNET_CONFIG_DIR),
+        )
+    }
+
+    /// Returns the config file path for the chain ID or alias (e.g., "C").
+    pub fn chain_config_path(&self, chain: &str) -> io::Result<PathBuf> {
+        check_file_name(chain)?;
+        Ok(Path::new(&self.chain_config_dir)
+            .join(chain)
+            .join("config.json"))
+    }
+
+    /// Returns the config file path for the subnet ID.
+    pub fn subnet_config_path(&self, subnet_id: &str) -> io::Result<PathBuf> {
+        check_file_name(subnet_id)?;
+        Ok(Path::new(&self.subnet_config_dir).join(format!("{}.json", subnet_id)))
+    }
+
+    /// Writes the chain config (e.g., coreth or subnet-evm "Config"),
+    /// with the "overrides" merged on top of it, and returns the file path.
+    pub fn write_chain_config<T: Serialize>(
+        &self,
+        chain: &str,
+        chain_config: &T,
+        overrides: Option<&Value>,
+    ) -> io::Result<PathBuf> {
+        let p = self.chain_config_path(chain)?;
+        info!("writing chain config for '{}' to {:?}", chain, p);
+        write_merged(&p, chain_config, overrides)?;
+        Ok(p)
+    }
+
+    /// Writes the subnet config, with the "overrides" merged
+    /// on top of it, and returns the file path.
+    pub fn write_subnet_config(
+        &self,
+        subnet_id: &str,
+        subnet_config: &SubnetConfig,
+        overrides: Option<&Value>,
+    ) -> io::Result<PathBuf> {
+        let p = self.subnet_config_path(subnet_id)?;
+        info!("writing subnet config for '{}' to {:?}", subnet_id, p);
+        write_merged(&p, subnet_config, overrides)?;
+        Ok(p)
+    }
+}
+
+/// Represents the subnet configuration loaded from "subnet-config-dir".
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/chains#SubnetConfig
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubnetConfig {
+    /// Only allows the subnet validators to connect to this node.
+    pub validator_only: bool,
+    /// Overrides the consensus parameters of the primary network.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consensus_parameters: Option<ConsensusParameters>,
+}
+
+impl Default for SubnetConfig {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl SubnetConfig {
+    pub fn default() -> Self {
+        Self {
+            validator_only: false,
+            consensus_parameters: None,
+        }
+    }
+}
+
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/snow/consensus/avalanche#Parameters
+/// ref. https://pkg.go.dev/github.com/ava-labs/avalanchego/snow/consensus/snowball#Parameters
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusParameters {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub k: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alpha: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub beta_virtuous: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub beta_rogue: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub concurrent_repolls: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optimal_processing: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_outstanding_items: Option<u32>,
+    /// In nanoseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_item_processing_time: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parents: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<u32>,
+}
+
+impl Default for ConsensusParameters {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl ConsensusParameters {
+    pub fn default() -> Self {
+        Self {
+            k: None,
+            alpha: None,
+            beta_virtuous: None,
+            beta_rogue: None,
+            concurrent_repolls: None,
+            optimal_processing: None,
+            max_outstanding_items: None,
+            max_item_processing_time: None,
+            parents: None,
+            batch_size: None,
+        }
+    }
+}
+
+/// Merges "overrides" into "base" recursively.
+/// Objects are merged key by key, and any other value
+/// (including arrays and nulls) replaces the one in "base".
+pub fn merge_json(base: &mut Value, overrides: &Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (k, v) in overrides.iter() {
+                merge_json(base.entry(k.clone()).or_insert(Value::Null), v);
+            }
+        }
+        (base, overrides) => {
+            *base = overrides.clone();
+        }
+    }
+}
+
+/// Chain aliases and subnet IDs become the file names,
+/// so they must not escape the config directory.
+fn check_file_name(name: &str) -> io::Result<()> {
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') || name.contains('\\') {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid chain or subnet name '{}'", name),
+        ));
+    }
+    Ok(())
+}
+
+fn write_merged<T: Serialize>(p: &Path, v: &T, overrides: Option<&Value>) -> io::Result<()> {
+    let mut merged = serde_json::to_value(v).map_err(|e| {
+        Error::new(
+            ErrorKind::Other,
+            format!("failed to serialize to JSON {}", e),
+        )
+    })?;
+    if let Some(overrides) = overrides {
+        merge_json(&mut merged, overrides);
+    }
+    let d = serde_json::to_vec(&merged).map_err(|e| {
+        Error::new(
+            ErrorKind::Other,
+            format!("failed to serialize to JSON {}", e),
+        )
+    })?;
+
+    let parent_dir = p.parent().expect("unexpected None parent");
+    fs::create_dir_all(parent_dir)?;
+    let mut f = File::create(p)?;
+    f.write_all(&d)?;
+    Ok(())
+}
+
+#[test]
+fn test_chain_config_manager() {
+    use serde_json::json;
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dir = tempfile::tempdir().unwrap();
+    let chain_config_dir = dir.path().join("chains");
+    let subnet_config_dir = dir.path().join("subnets");
+    let manager = ChainConfigManager::new(
+        chain_config_dir.to_str().unwrap(),
+        subnet_config_dir.to_str().unwrap(),
+    );
+
+    let p = manager
+        .write_chain_config(
+            "C",
+            &json!({"log-level": "info", "eth-apis": ["eth"], "pruning-enabled": true}),
+            Some(&json!({"log-level": "debug", "eth-apis": ["eth", "admin"]})),
+        )
+        .unwrap();
+    assert_eq!(p, chain_config_dir.join("C").join("config.json"));
+    let written: Value = serde_json::from_slice(&fs::read(&p).unwrap()).unwrap();
+    assert_eq!(
+        written,
+        json!({"log-level": "debug", "eth-apis": ["eth", "admin"], "pruning-enabled": true})
+    );
+
+    let subnet_config = SubnetConfig {
+        validator_only: true,
+        consensus_parameters: Some(ConsensusParameters {
+            k: Some(5),
+            alpha: Some(4),
+            ..ConsensusParameters::default()
+        }),
+    };
+    let p = manager
+        .write_subnet_config(
+            "p433wpuXyJiDhyazPYyZMJeaoPSW76CBZ2x7wrVPLgvokotXz",
+            &subnet_config,
+            Some(&json!({"consensusParameters": {"betaVirtuous": 10}})),
+        )
+        .unwrap();
+    let written: Value = serde_json::from_slice(&fs::read(&p).unwrap()).unwrap();
+    assert_eq!(
+        written,
+        json!({"validatorOnly": true, "consensusParameters": {"k": 5, "alpha": 4, "betaVirtuous": 10}})
+    );
+
+    assert!(manager.chain_config_path("../C").is_err());
+    assert!(manager.subnet_config_path("").is_err());
+}
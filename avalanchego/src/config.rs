@@ -80,6 +80,11 @@ pub struct Config {
     /// MUST BE a valid path in remote host machine.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub staking_tls_cert_file: Option<String>,
+    /// BLS key of the node that signs the warp messages and registers
+    /// with the permissionless validator, kept across the certificate rotations.
+    /// MUST BE a valid path in remote host machine.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub staking_signer_key_file: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bootstrap_ips: Option<String>,
@@ -129,12 +134,22 @@ pub struct Config {
     /// A list of whitelisted subnet IDs (comma-separated).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub whitelisted_subnets: Option<String>,
+    /// A list of subnet IDs to track (comma-separated).
+    /// Replaces "whitelisted-subnets" in newer avalanchego releases,
+    /// so only one of them may be set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track_subnets: Option<String>,
 
     /// Chain configuration directory for all chains.
     /// ref. https://github.com/ava-labs/avalanchego/blob/v1.7.6/config/flags.go#L25-L44
     pub chain_config_dir: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub subnet_config_dir: Option<String>,
+    /// JSON file that maps each VM ID to its aliases.
+    /// Only set once written, as avalanchego fails on start
+    /// if the explicitly set file does not exist.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vm_aliases_file: Option<String>,
 
     /// A comma seperated string of explicit nodeID and IPs
     /// to contact for starting state sync. Useful for testing.
@@ -205,6 +220,8 @@ pub const DEFAULT_STAKING_PORT: u32 = 9651;
 pub const DEFAULT_STAKING_TLS_KEY_FILE: &str = "/etc/pki/tls/certs/avalanched.pki.key";
 /// MUST BE a valid path in remote host machine.
 pub const DEFAULT_STAKING_TLS_CERT_FILE: &str = "/etc/pki/tls/certs/avalanched.pki.crt";
+/// MUST BE a valid path in remote host machine.
+pub const DEFAULT_STAKING_SIGNER_KEY_FILE: &str = "/etc/pki/tls/certs/avalanched.signer.key";
 
 /// Default snow sample size.
 /// NOTE: keep this in sync with "avalanchego/config/flags.go".
@@ -261,6 +278,7 @@ impl Config {
             staking_port: DEFAULT_STAKING_PORT,
             staking_tls_key_file: Some(String::from(DEFAULT_STAKING_TLS_KEY_FILE)),
             staking_tls_cert_file: Some(String::from(DEFAULT_STAKING_TLS_CERT_FILE)),
+            staking_signer_key_file: Some(String::from(DEFAULT_STAKING_SIGNER_KEY_FILE)),
 
             bootstrap_ips: None,
             bootstrap_ids: None,
@@ -286,9 +304,11 @@ impl Config {
             api_ipcs_enabled: Some(DEFAULT_API_IPCS_ENABLED),
 
             whitelisted_subnets: None,
+            track_subnets: None,
 
             chain_config_dir: String::from(DEFAULT_CHAIN_CONFIG_DIR),
             subnet_config_dir: Some(String::from(DEFAULT_SUBNET_CONFIG_DIR)),
+            vm_aliases_file: None,
 
             state_sync_ids: None,
             state_sync_ips: None,
@@ -306,6 +326,18 @@ impl Config {
         }
     }
 
+    /// Returns the default configuration for the network ID.
+    /// Known networks (e.g., mainnet, fuji) use the genesis
+    /// embedded in avalanchego, thus no genesis file.
+    pub fn default_for_network(network_id: u32) -> Self {
+        let mut config = Self::default();
+        config.network_id = network_id;
+        if !config.is_custom_network() {
+            config.genesis = None;
+        }
+        config
+    }
+
     /// Returns true if the configuration is mainnet.
     pub fn is_mainnet(&self) -> bool {
         self.network_id == 1
@@ -430,8 +462,7 @@ impl Config {
         // network ID must match with the one in genesis file
         if self.genesis.is_some() {
             let genesis_file_path = self.genesis.clone().expect("unexpected None genesis");
-            let genesis_config =
-                genesis::Genesis::load(&genesis_file_path).expect("unexpected None genesis config");
+            let genesis_config = genesis::Genesis::load(&genesis_file_path)?;
             if genesis_config.network_id.ne(&self.network_id) {
                 return Err(Error::new(
                     ErrorKind::InvalidInput,
@@ -443,6 +474,27 @@ impl Config {
             }
         }
 
+        // ports
+        if self.http_port == self.staking_port {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'http-port' and 'staking-port' must be different (both {})",
+                    self.http_port
+                ),
+            ));
+        }
+
+        // HTTP TLS
+        if self.http_tls_enabled.unwrap_or(false)
+            && (self.http_tls_key_file.is_none() || self.http_tls_cert_file.is_none())
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'http-tls-enabled' but empty 'http-tls-key-file' or 'http-tls-cert-file'",
+            ));
+        }
+
         // staking
         if self.staking_enabled.is_some() && !self.staking_enabled.unwrap() {
             return Err(Error::new(
@@ -463,6 +515,42 @@ impl Config {
             ));
         }
 
+        // bootstrap beacons must be paired
+        let bootstrap_ips = split_list(&self.bootstrap_ips);
+        let bootstrap_ids = split_list(&self.bootstrap_ids);
+        if bootstrap_ips.len() != bootstrap_ids.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'bootstrap-ips' has {} entries but 'bootstrap-ids' has {}",
+                    bootstrap_ips.len(),
+                    bootstrap_ids.len()
+                ),
+            ));
+        }
+
+        // snowball parameters
+        // ref. "avalanchego/snow/consensus/snowball.Parameters.Verify"
+        let sample_size = self.snow_sample_size.unwrap_or(DEFAULT_SNOW_SAMPLE_SIZE);
+        let quorum_size = self.snow_quorum_size.unwrap_or(DEFAULT_SNOW_QUORUM_SIZE);
+        if quorum_size <= sample_size / 2 || quorum_size > sample_size {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'snow-quorum-size' {} must be in ('snow-sample-size'/2, 'snow-sample-size'] for 'snow-sample-size' {}",
+                    quorum_size, sample_size
+                ),
+            ));
+        }
+
+        // subnets
+        if self.whitelisted_subnets.is_some() && self.track_subnets.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "only one of 'whitelisted-subnets' and 'track-subnets' can be set",
+            ));
+        }
+
         // state sync
         if self.state_sync_ids.is_some() && self.state_sync_ips.is_none() {
             return Err(Error::new(
@@ -500,6 +588,37 @@ impl Config {
 
         Ok(())
     }
+
+    /// Adds the subnet ID to the tracked subnets, to "whitelisted-subnets"
+    /// if already set (older avalanchego releases), otherwise to "track-subnets".
+    /// Returns false if the subnet is already tracked.
+    pub fn track_subnet(&mut self, subnet_id: &str) -> bool {
+        let tracked = if self.whitelisted_subnets.is_some() {
+            &mut self.whitelisted_subnets
+        } else {
+            &mut self.track_subnets
+        };
+        let mut subnets = split_list(tracked);
+        if subnets.iter().any(|s| s == subnet_id) {
+            return false;
+        }
+        subnets.push(subnet_id.to_string());
+        *tracked = Some(subnets.join(","));
+        true
+    }
+}
+
+/// Splits the comma-separated flag value, ignoring empty entries.
+fn split_list(v: &Option<String>) -> Vec<String> {
+    match v {
+        Some(s) => s
+            .split(',')
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+            .map(String::from)
+            .collect(),
+        None => Vec::new(),
+    }
 }
 
 #[test]
@@ -525,3 +644,68 @@ fn test_config() {
 
     fs::remove_file(p).unwrap();
 }
+
+#[test]
+fn test_config_validate() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let config = Config::default_for_network(1);
+    assert!(config.genesis.is_none());
+    assert!(config.validate().is_ok());
+
+    // mainnet with custom genesis
+    let mut config = Config::default_for_network(1);
+    config.genesis = Some(String::from(DEFAULT_GENESIS_PATH));
+    assert!(config.validate().is_err());
+
+    // custom network without genesis
+    let mut config = Config::default_for_network(constants::DEFAULT_CUSTOM_NETWORK_ID);
+    assert!(config.genesis.is_some());
+    config.genesis = None;
+    assert!(config.validate().is_err());
+
+    let mut config = Config::default_for_network(5);
+    config.staking_port = config.http_port;
+    assert!(config.validate().is_err());
+
+    let mut config = Config::default_for_network(5);
+    config.http_tls_enabled = Some(true);
+    assert!(config.validate().is_err());
+    config.http_tls_key_file = config.staking_tls_key_file.clone();
+    config.http_tls_cert_file = config.staking_tls_cert_file.clone();
+    assert!(config.validate().is_ok());
+
+    let mut config = Config::default_for_network(5);
+    config.bootstrap_ips = Some(String::from("1.2.3.4:9651,5.6.7.8:9651"));
+    config.bootstrap_ids = Some(String::from("NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg"));
+    assert!(config.validate().is_err());
+
+    let mut config = Config::default_for_network(5);
+    config.snow_sample_size = Some(20);
+    config.snow_quorum_size = Some(10);
+    assert!(config.validate().is_err());
+    config.snow_quorum_size = Some(21);
+    assert!(config.validate().is_err());
+
+    let mut config = Config::default_for_network(5);
+    config.whitelisted_subnets = Some(String::from("a"));
+    config.track_subnets = Some(String::from("a"));
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn test_config_track_subnet() {
+    let mut config = Config::default_for_network(5);
+    assert!(config.track_subnet("a"));
+    assert!(config.track_subnet("b"));
+    assert!(!config.track_subnet("a"));
+    assert_eq!(config.track_subnets, Some(String::from("a,b")));
+    assert!(config.whitelisted_subnets.is_none());
+
+    let mut config = Config::default_for_network(5);
+    config.whitelisted_subnets = Some(String::from("a, "));
+    assert!(config.track_subnet("b"));
+    assert_eq!(config.whitelisted_subnets, Some(String::from("a,b")));
+    assert!(config.track_subnets.is_none());
+    config.validate().unwrap();
+}
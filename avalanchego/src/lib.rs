@@ -1 +1,4 @@
+pub mod chain_config;
 pub mod config;
+pub mod network_runner;
+pub mod vms;
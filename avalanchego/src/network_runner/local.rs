@@ -0,0 +1,626 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Error, ErrorKind, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    path::Path,
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use log::{info, warn};
+
+use crate::config;
+use avalanche_types::{cert, constants, genesis, ids, soft_key};
+
+/// Host of all local nodes.
+const LOCALHOST: &str = "127.0.0.1";
+
+/// Pre-funded keys of the generated genesis, saved next to the genesis
+/// so the restarted network can still spend the allocations.
+const SEED_KEYS_FILE: &str = "seed_keys.json";
+
+/// Interval to poll the HTTP ports of the starting nodes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Options of the local network.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Path to the "avalanchego" binary.
+    pub avalanchego_bin: String,
+    /// Directory to write the node configs, certs, databases, and logs.
+    pub root_dir: String,
+    /// Number of the nodes, all of which are the initial stakers.
+    pub nodes: usize,
+    /// Must be a custom network ID, since the known networks
+    /// use the genesis embedded in avalanchego.
+    pub network_id: u32,
+    /// Number of the pre-funded keys in the generated genesis.
+    pub keys: usize,
+    /// Custom genesis to override the generated one, whose initial stakers
+    /// are replaced with the generated nodes.
+    pub genesis: Option<genesis::Genesis>,
+    pub log_level: String,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl Options {
+    pub fn default() -> Self {
+        Self {
+            avalanchego_bin: String::from("avalanchego"),
+            root_dir: String::new(),
+            nodes: 5,
+            network_id: constants::DEFAULT_CUSTOM_NETWORK_ID,
+            keys: 5,
+            genesis: None,
+            log_level: String::from(config::DEFAULT_LOG_LEVEL),
+        }
+    }
+}
+
+/// A node of the local network, running as a local process once started.
+#[derive(Debug)]
+pub struct Node {
+    pub name: String,
+    pub node_id: ids::NodeId,
+    pub http_port: u32,
+    pub staking_port: u32,
+    pub config_file: String,
+    pub log_file: String,
+    /// Listeners holding the ports while the node is not running.
+    reserved: Vec<TcpListener>,
+    process: Option<Child>,
+}
+
+impl Node {
+    /// Returns the HTTP API endpoint (e.g., "http://127.0.0.1:[PORT]").
+    pub fn http_endpoint(&self) -> String {
+        format!("http://{}:{}", LOCALHOST, self.http_port)
+    }
+
+    pub fn staking_endpoint(&self) -> String {
+        format!("{}:{}", LOCALHOST, self.staking_port)
+    }
+
+    pub fn is_running(&mut self) -> bool {
+        match self.process.as_mut() {
+            Some(child) => matches!(child.try_wait(), Ok(None)),
+            None => false,
+        }
+    }
+
+    fn start(&mut self, avalanchego_bin: &str) -> io::Result<()> {
+        if self.is_running() {
+            return Ok(());
+        }
+        info!(
+            "starting {} ({}) with config {}",
+            self.name, self.node_id, self.config_file
+        );
+        let log = File::create(&self.log_file)?;
+
+        // release the ports right before the node binds them
+        self.reserved.clear();
+        let spawned = Command::new(avalanchego_bin)
+            .arg(format!("--config-file={}", self.config_file))
+            .stdout(Stdio::from(log.try_clone()?))
+            .stderr(Stdio::from(log))
+            .spawn();
+        match spawned {
+            Ok(child) => {
+                self.process = Some(child);
+                Ok(())
+            }
+            Err(e) => {
+                self.reserved =
+                    reserve_ports(&[self.http_port, self.staking_port]).unwrap_or_default();
+                Err(Error::new(
+                    e.kind(),
+                    format!(
+                        "failed to spawn '{}' for {} ({})",
+                        avalanchego_bin, self.name, e
+                    ),
+                ))
+            }
+        }
+    }
+
+    fn stop(&mut self) -> io::Result<()> {
+        if let Some(mut child) = self.process.take() {
+            info!("stopping {} ({})", self.name, self.node_id);
+            if let Err(e) = child.kill() {
+                // already exited
+                warn!("failed to kill {} ({})", self.name, e);
+            }
+            child.wait()?;
+
+            match reserve_ports(&[self.http_port, self.staking_port]) {
+                Ok(reserved) => self.reserved = reserved,
+                Err(e) => warn!("failed to reserve the ports of {} ({})", self.name, e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Local network of avalanchego processes with the generated staking certs,
+/// ephemeral ports, and a custom genesis, for integration tests without AWS.
+#[derive(Debug)]
+pub struct Network {
+    pub options: Options,
+    pub genesis: genesis::Genesis,
+    /// Pre-funded keys in the generated genesis, empty for the custom genesis.
+    pub seed_keys: Vec<soft_key::PrivateKeyInfo>,
+    pub nodes: Vec<Node>,
+}
+
+impl Network {
+    /// Writes the genesis, staking certs, and node configs under the root directory,
+    /// without starting the nodes. The first node is the bootstrap beacon of the others.
+    /// The genesis, seed keys, and staking certs of the previous run in the same
+    /// root directory are re-used, so the existing databases stay valid.
+    pub fn new(options: Options) -> io::Result<Self> {
+        if options.nodes == 0 {
+            return Err(Error::new(ErrorKind::InvalidInput, "no node to run"));
+        }
+        if options.root_dir.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "empty root directory"));
+        }
+        let root_dir = Path::new(&options.root_dir);
+        fs::create_dir_all(root_dir)?;
+
+        let mut reserved = free_ports(options.nodes * 2)?;
+        let mut nodes: Vec<Node> = Vec::new();
+        for i in 0..options.nodes {
+            let name = format!("node{}", i + 1);
+            let ports: Vec<TcpListener> = reserved.drain(..2).collect();
+            nodes.push(prepare_node(root_dir, &name, ports)?);
+        }
+
+        let genesis_path = path_str(&root_dir.join("genesis.json"))?;
+        let seed_keys_path = path_str(&root_dir.join(SEED_KEYS_FILE))?;
+        let (genesis, seed_keys) = if Path::new(&genesis_path).exists() {
+            // the databases of the previous run are only valid with the same genesis
+            // (e.g., the start time and the allocations of the generated keys)
+            info!("re-using the genesis of the previous run {}", genesis_path);
+            let genesis = genesis::Genesis::load(&genesis_path)?;
+            let seed_keys = if Path::new(&seed_keys_path).exists() {
+                let f = File::open(&seed_keys_path)?;
+                serde_json::from_reader(f).map_err(|e| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("invalid seed keys {} ({})", seed_keys_path, e),
+                    )
+                })?
+            } else {
+                Vec::new()
+            };
+            (genesis, seed_keys)
+        } else {
+            let (genesis, seed_keys) = new_genesis(&options, &nodes)?;
+            write_seed_keys(&seed_keys_path, &seed_keys)?;
+            // written last, so an interrupted run is regenerated from scratch
+            genesis.sync(&genesis_path)?;
+            (genesis, seed_keys)
+        };
+        if genesis.network_id != options.network_id {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "genesis network ID {} != network ID {}",
+                    genesis.network_id, options.network_id
+                ),
+            ));
+        }
+        let stakers: Vec<Option<String>> = genesis
+            .initial_stakers
+            .iter()
+            .flatten()
+            .map(|s| s.node_id.clone())
+            .collect();
+        let node_ids: Vec<Option<String>> =
+            nodes.iter().map(|n| Some(n.node_id.to_string())).collect();
+        if stakers != node_ids {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "initial stakers of {} do not match the {} nodes (remove {} to start over)",
+                    genesis_path,
+                    nodes.len(),
+                    options.root_dir
+                ),
+            ));
+        }
+
+        let validators = nodes.len();
+        for (i, node) in nodes.iter().enumerate() {
+            let beacon = if i > 0 { Some(&nodes[0]) } else { None };
+            write_config(&options, node, beacon, validators)?;
+        }
+
+        info!(
+            "prepared {} nodes of network ID {} in {}",
+            nodes.len(),
+            options.network_id,
+            options.root_dir
+        );
+        Ok(Self {
+            options,
+            genesis,
+            seed_keys,
+            nodes,
+        })
+    }
+
+    /// Starts all the nodes, beacon first.
+    pub fn start(&mut self) -> io::Result<()> {
+        for i in 0..self.nodes.len() {
+            if let Err(e) = self.nodes[i].start(&self.options.avalanchego_bin) {
+                self.stop()?;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits until every node accepts the connections on its HTTP port,
+    /// failing early if any node process exits.
+    pub fn wait_ready(&mut self, timeout: Duration) -> io::Result<()> {
+        let started = Instant::now();
+        for node in self.nodes.iter_mut() {
+            let addr: SocketAddr = format!("{}:{}", LOCALHOST, node.http_port)
+                .parse()
+                .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("{}", e)))?;
+            loop {
+                if !node.is_running() {
+                    return Err(Error::new(
+                        ErrorKind::Other,
+                        format!("{} exited (see {})", node.name, node.log_file),
+                    ));
+                }
+                if TcpStream::connect_timeout(&addr, POLL_INTERVAL).is_ok() {
+                    info!("{} is listening on {}", node.name, node.http_endpoint());
+                    break;
+                }
+                if started.elapsed() >= timeout {
+                    return Err(Error::new(
+                        ErrorKind::TimedOut,
+                        format!("{} not listening after {:?}", node.name, timeout),
+                    ));
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a non-validator node bootstrapping from the first node,
+    /// and starts it if the network is running.
+    pub fn add_node(&mut self, name: &str) -> io::Result<()> {
+        if self.nodes.iter().any(|n| n.name == name) {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("node {} already exists", name),
+            ));
+        }
+        let running = self.nodes.iter_mut().any(|n| n.is_running());
+
+        let root_dir = Path::new(&self.options.root_dir);
+        let mut node = prepare_node(root_dir, name, free_ports(2)?)?;
+        let validators = self
+            .genesis
+            .initial_stakers
+            .as_ref()
+            .map(|s| s.len())
+            .unwrap_or(self.nodes.len());
+        write_config(&self.options, &node, self.nodes.first(), validators)?;
+
+        if running {
+            node.start(&self.options.avalanchego_bin)?;
+        }
+        self.nodes.push(node);
+        Ok(())
+    }
+
+    /// Stops the node and removes it from the network, keeping its directory.
+    pub fn remove_node(&mut self, name: &str) -> io::Result<()> {
+        let idx = self
+            .nodes
+            .iter()
+            .position(|n| n.name == name)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("node {} not found", name)))?;
+        if idx == 0 && self.nodes.len() > 1 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} is the bootstrap beacon of the other nodes", name),
+            ));
+        }
+        let mut node = self.nodes.remove(idx);
+        node.stop()
+    }
+
+    /// Returns true if every node is running and accepts the connections on its HTTP port.
+    pub fn is_healthy(&mut self) -> bool {
+        self.nodes.iter_mut().all(|n| {
+            n.is_running()
+                && format!("{}:{}", LOCALHOST, n.http_port)
+                    .parse::<SocketAddr>()
+                    .map(|addr| TcpStream::connect_timeout(&addr, POLL_INTERVAL).is_ok())
+                    .unwrap_or(false)
+        })
+    }
+
+    /// Returns the HTTP API endpoints of all nodes.
+    pub fn http_endpoints(&self) -> Vec<String> {
+        self.nodes.iter().map(|n| n.http_endpoint()).collect()
+    }
+
+    /// Stops all the nodes, keeping the root directory for the next start.
+    pub fn stop(&mut self) -> io::Result<()> {
+        for node in self.nodes.iter_mut().rev() {
+            node.stop()?;
+        }
+        Ok(())
+    }
+
+    /// Stops all the nodes and removes the root directory.
+    pub fn teardown(mut self) -> io::Result<()> {
+        self.stop()?;
+        info!("removing {}", self.options.root_dir);
+        fs::remove_dir_all(&self.options.root_dir)
+    }
+}
+
+impl Drop for Network {
+    fn drop(&mut self) {
+        if let Err(e) = self.stop() {
+            warn!("failed to stop the local network ({})", e);
+        }
+    }
+}
+
+/// Returns the genesis of the generated (or custom) allocations,
+/// with every node as the initial staker rewarded to the first seed key.
+fn new_genesis(
+    options: &Options,
+    nodes: &[Node],
+) -> io::Result<(genesis::Genesis, Vec<soft_key::PrivateKeyInfo>)> {
+    // the keys of the custom genesis allocations are unknown
+    let (mut genesis, seed_keys) = match &options.genesis {
+        Some(custom) => (custom.clone(), Vec::new()),
+        None => genesis::Genesis::new(options.network_id, options.keys)?,
+    };
+
+    let reward_address = seed_keys
+        .first()
+        .map(|k| k.x_address.clone())
+        .or_else(|| {
+            genesis
+                .initial_staked_funds
+                .clone()
+                .and_then(|f| f.first().cloned())
+        })
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "no seed key nor initial staked funds to reward the initial stakers",
+            )
+        })?;
+    genesis.initial_stakers = Some(
+        nodes
+            .iter()
+            .map(|n| genesis::Staker {
+                node_id: Some(n.node_id.to_string()),
+                reward_address: Some(reward_address.clone()),
+                ..genesis::Staker::default()
+            })
+            .collect(),
+    );
+    Ok((genesis, seed_keys))
+}
+
+/// Saves the pre-funded keys of the generated genesis, readable only by the owner.
+fn write_seed_keys(file_path: &str, seed_keys: &[soft_key::PrivateKeyInfo]) -> io::Result<()> {
+    let d = serde_json::to_vec(seed_keys).map_err(|e| {
+        Error::new(
+            ErrorKind::Other,
+            format!("failed to serialize seed keys ({})", e),
+        )
+    })?;
+    let mut opts = OpenOptions::new();
+    opts.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+    let mut f = opts.open(file_path)?;
+    f.write_all(&d)?;
+    f.sync_all()
+}
+
+/// Creates the node directory with the staking certs, re-using the existing ones,
+/// on the reserved HTTP and staking ports.
+fn prepare_node(root_dir: &Path, name: &str, reserved: Vec<TcpListener>) -> io::Result<Node> {
+    let http_port = local_port(&reserved[0])?;
+    let staking_port = local_port(&reserved[1])?;
+    let node_dir = root_dir.join(name);
+    fs::create_dir_all(&node_dir)?;
+
+    let key_path = path_str(&node_dir.join("staking.key"))?;
+    let cert_path = path_str(&node_dir.join("staking.crt"))?;
+    let node_id = if Path::new(&cert_path).exists() {
+        // re-use the identity of the previous run
+        ids::NodeId::from_cert_file(&cert_path)?
+    } else {
+        cert::generate(&key_path, &cert_path)?
+    };
+
+    Ok(Node {
+        name: name.to_string(),
+        node_id,
+        http_port,
+        staking_port,
+        config_file: path_str(&node_dir.join("config.json"))?,
+        log_file: path_str(&node_dir.join("avalanchego.log"))?,
+        reserved,
+        process: None,
+    })
+}
+
+/// Writes the node config, bootstrapping from the beacon if any.
+fn write_config(
+    options: &Options,
+    node: &Node,
+    beacon: Option<&Node>,
+    validators: usize,
+) -> io::Result<()> {
+    let root_dir = Path::new(&options.root_dir);
+    let node_dir = root_dir.join(&node.name);
+
+    // snowball parameters must be sampled from the local validators
+    let sample_size = validators as u32;
+    let quorum_size = sample_size / 2 + 1;
+
+    let mut cfg = config::Config::default_for_network(options.network_id);
+    cfg.config_file = Some(node.config_file.clone());
+    cfg.genesis = Some(path_str(&root_dir.join("genesis.json"))?);
+    cfg.db_dir = path_str(&node_dir.join("db"))?;
+    cfg.log_dir = path_str(&node_dir.join("logs"))?;
+    cfg.log_level = Some(options.log_level.clone());
+    cfg.http_host = Some(String::from(LOCALHOST));
+    cfg.http_port = node.http_port;
+    cfg.public_ip = Some(String::from(LOCALHOST));
+    cfg.staking_port = node.staking_port;
+    cfg.staking_tls_key_file = Some(path_str(&node_dir.join("staking.key"))?);
+    cfg.staking_tls_cert_file = Some(path_str(&node_dir.join("staking.crt"))?);
+    cfg.staking_signer_key_file = Some(path_str(&node_dir.join("signer.key"))?);
+    cfg.chain_config_dir = path_str(&node_dir.join("configs").join("chains"))?;
+    cfg.subnet_config_dir = Some(path_str(&node_dir.join("configs").join("subnets"))?);
+    cfg.profile_dir = Some(path_str(&node_dir.join("profiles"))?);
+    cfg.snow_sample_size = Some(sample_size);
+    cfg.snow_quorum_size = Some(quorum_size);
+    if let Some(beacon) = beacon {
+        cfg.bootstrap_ips = Some(beacon.staking_endpoint());
+        cfg.bootstrap_ids = Some(beacon.node_id.to_string());
+    }
+    cfg.validate()?;
+    cfg.sync(None)
+}
+
+/// Binds "n" distinct ephemeral ports, which stay reserved until the listeners
+/// are dropped right before the node binds them, so no other process
+/// (or another local network) is handed the same ports in between.
+fn free_ports(n: usize) -> io::Result<Vec<TcpListener>> {
+    (0..n).map(|_| TcpListener::bind((LOCALHOST, 0))).collect()
+}
+
+/// Binds the ports again to hold them while the node is stopped.
+/// The listeners set "SO_REUSEADDR" on unix, so the connections
+/// of the stopped node in TIME_WAIT do not block the bind.
+fn reserve_ports(ports: &[u32]) -> io::Result<Vec<TcpListener>> {
+    ports
+        .iter()
+        .map(|p| TcpListener::bind((LOCALHOST, *p as u16)))
+        .collect()
+}
+
+fn local_port(l: &TcpListener) -> io::Result<u32> {
+    l.local_addr().map(|a| u32::from(a.port()))
+}
+
+fn path_str(p: &Path) -> io::Result<String> {
+    p.to_str()
+        .map(String::from)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("non-UTF-8 path {:?}", p)))
+}
+
+/// RUST_LOG=debug cargo test --package avalanchego --lib -- network_runner::local::test_network --exact --show-output
+#[test]
+fn test_network() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let root_dir = tempfile::tempdir().unwrap();
+    let root = root_dir.path().to_str().unwrap().to_string();
+    let net = Network::new(Options {
+        avalanchego_bin: String::from("/nonexistent/avalanchego"),
+        root_dir: root.clone(),
+        nodes: 3,
+        keys: 2,
+        ..Options::default()
+    })
+    .unwrap();
+    assert_eq!(net.seed_keys.len(), 2);
+    assert_eq!(net.http_endpoints().len(), 3);
+
+    let mut ports: Vec<u32> = net
+        .nodes
+        .iter()
+        .flat_map(|n| vec![n.http_port, n.staking_port])
+        .collect();
+    ports.sort_unstable();
+    ports.dedup();
+    assert_eq!(ports.len(), 6);
+
+    let stakers = net.genesis.initial_stakers.clone().unwrap();
+    assert_eq!(stakers.len(), 3);
+    assert_eq!(stakers[2].node_id, Some(net.nodes[2].node_id.to_string()));
+
+    let beacon = config::Config::load(&net.nodes[0].config_file).unwrap();
+    assert!(beacon.bootstrap_ips.is_none());
+    let cfg = config::Config::load(&net.nodes[1].config_file).unwrap();
+    assert_eq!(cfg.bootstrap_ips, Some(net.nodes[0].staking_endpoint()));
+    assert_eq!(cfg.snow_sample_size, Some(3));
+    assert_eq!(cfg.snow_quorum_size, Some(2));
+    cfg.validate().unwrap();
+    let node_ids: Vec<ids::NodeId> = net.nodes.iter().map(|n| n.node_id).collect();
+    let (genesis, seed_keys) = (net.genesis.clone(), net.seed_keys.clone());
+    drop(net);
+
+    // the genesis of the previous run only matches the same nodes
+    assert!(Network::new(Options {
+        avalanchego_bin: String::from("/nonexistent/avalanchego"),
+        root_dir: root.clone(),
+        nodes: 2,
+        keys: 2,
+        ..Options::default()
+    })
+    .is_err());
+
+    // re-uses the genesis, seed keys, and staking certs
+    let mut net = Network::new(Options {
+        avalanchego_bin: String::from("/nonexistent/avalanchego"),
+        root_dir: root,
+        nodes: 3,
+        keys: 2,
+        ..Options::default()
+    })
+    .unwrap();
+    let reused: Vec<ids::NodeId> = net.nodes.iter().map(|n| n.node_id).collect();
+    assert_eq!(node_ids, reused);
+    assert_eq!(net.genesis, genesis);
+    assert_eq!(net.seed_keys, seed_keys);
+
+    // the ports stay reserved until the node starts
+    assert!(TcpListener::bind((LOCALHOST, net.nodes[0].http_port as u16)).is_err());
+
+    net.add_node("node4").unwrap();
+    assert!(net.add_node("node4").is_err());
+    let added = config::Config::load(&net.nodes[3].config_file).unwrap();
+    assert_eq!(added.bootstrap_ips, Some(net.nodes[0].staking_endpoint()));
+    assert_eq!(added.snow_sample_size, Some(3));
+    assert!(net.remove_node("node1").is_err());
+    net.remove_node("node4").unwrap();
+    assert_eq!(net.nodes.len(), 3);
+
+    assert!(net.start().is_err());
+    assert!(!net.nodes[0].is_running());
+    assert!(TcpListener::bind((LOCALHOST, net.nodes[0].http_port as u16)).is_err());
+    net.teardown().unwrap();
+    assert!(!root_dir.path().exists());
+}
@@ -0,0 +1,72 @@
+pub mod local;
+pub mod rpc;
+
+use std::io::{self, Error, ErrorKind};
+
+use async_trait::async_trait;
+
+/// Manages the lifecycle of a test network, either as the local processes
+/// or through the network-runner gRPC server, so the test flows can target both.
+#[async_trait]
+pub trait Runner: Send {
+    /// Starts all the nodes.
+    async fn start(&mut self) -> io::Result<()>;
+
+    /// Adds a node with the name, and starts it if the network is running.
+    async fn add_node(&mut self, name: &str) -> io::Result<()>;
+
+    /// Stops the node and removes it from the network.
+    async fn remove_node(&mut self, name: &str) -> io::Result<()>;
+
+    /// Returns true if all the nodes are up and serving.
+    async fn health(&mut self) -> io::Result<bool>;
+
+    /// Returns the HTTP API endpoints of all nodes.
+    async fn uris(&mut self) -> io::Result<Vec<String>>;
+
+    /// Saves the databases of the network under the name, returning the snapshot path.
+    async fn save_snapshot(&mut self, name: &str) -> io::Result<String> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            format!("snapshot {} not supported by the runner", name),
+        ))
+    }
+
+    /// Restarts the network from the saved snapshot.
+    async fn load_snapshot(&mut self, name: &str) -> io::Result<()> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            format!("snapshot {} not supported by the runner", name),
+        ))
+    }
+
+    /// Stops all the nodes.
+    async fn stop(&mut self) -> io::Result<()>;
+}
+
+#[async_trait]
+impl Runner for local::Network {
+    async fn start(&mut self) -> io::Result<()> {
+        local::Network::start(self)
+    }
+
+    async fn add_node(&mut self, name: &str) -> io::Result<()> {
+        local::Network::add_node(self, name)
+    }
+
+    async fn remove_node(&mut self, name: &str) -> io::Result<()> {
+        local::Network::remove_node(self, name)
+    }
+
+    async fn health(&mut self) -> io::Result<bool> {
+        Ok(self.is_healthy())
+    }
+
+    async fn uris(&mut self) -> io::Result<Vec<String>> {
+        Ok(self.http_endpoints())
+    }
+
+    async fn stop(&mut self) -> io::Result<()> {
+        local::Network::stop(self)
+    }
+}
@@ -0,0 +1,391 @@
+use std::{
+    collections::HashMap,
+    io::{self, Error, ErrorKind},
+};
+
+use async_trait::async_trait;
+use log::info;
+use tonic::{
+    client::Grpc,
+    codec::ProstCodec,
+    codegen::http::uri::PathAndQuery,
+    transport::{Channel, Endpoint},
+};
+
+use super::Runner;
+
+/// Messages of the network-runner control service, kept in sync by hand
+/// to avoid the protoc build step.
+/// ref. https://github.com/ava-labs/avalanche-network-runner/blob/main/rpcpb/rpc.proto
+pub mod rpcpb {
+    use std::collections::HashMap;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ClusterInfo {
+        #[prost(string, repeated, tag = "1")]
+        pub node_names: Vec<String>,
+        #[prost(map = "string, message", tag = "2")]
+        pub node_infos: HashMap<String, NodeInfo>,
+        #[prost(int32, tag = "3")]
+        pub pid: i32,
+        #[prost(string, tag = "4")]
+        pub root_data_dir: String,
+        #[prost(bool, tag = "5")]
+        pub healthy: bool,
+        #[prost(bool, tag = "7")]
+        pub custom_vms_healthy: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct NodeInfo {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(string, tag = "2")]
+        pub exec_path: String,
+        #[prost(string, tag = "3")]
+        pub uri: String,
+        #[prost(string, tag = "4")]
+        pub id: String,
+        #[prost(string, tag = "5")]
+        pub log_dir: String,
+        #[prost(string, tag = "6")]
+        pub db_dir: String,
+        #[prost(string, tag = "7")]
+        pub plugin_dir: String,
+        #[prost(string, tag = "8")]
+        pub whitelisted_subnets: String,
+        #[prost(bytes = "vec", tag = "9")]
+        pub config: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StartRequest {
+        #[prost(string, tag = "1")]
+        pub exec_path: String,
+        #[prost(uint32, optional, tag = "2")]
+        pub num_nodes: Option<u32>,
+        #[prost(string, optional, tag = "3")]
+        pub whitelisted_subnets: Option<String>,
+        #[prost(string, optional, tag = "4")]
+        pub global_node_config: Option<String>,
+        #[prost(string, optional, tag = "5")]
+        pub root_data_dir: Option<String>,
+        #[prost(string, optional, tag = "6")]
+        pub plugin_dir: Option<String>,
+        /// Maps the VM name to its genesis file path.
+        #[prost(map = "string, string", tag = "7")]
+        pub custom_vms: HashMap<String, String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StartResponse {
+        #[prost(message, optional, tag = "1")]
+        pub cluster_info: Option<ClusterInfo>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct HealthRequest {}
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct HealthResponse {
+        #[prost(message, optional, tag = "1")]
+        pub cluster_info: Option<ClusterInfo>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct UrIsRequest {}
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct UrIsResponse {
+        #[prost(string, repeated, tag = "1")]
+        pub uris: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct AddNodeRequest {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(string, tag = "2")]
+        pub exec_path: String,
+        #[prost(string, optional, tag = "3")]
+        pub node_config: Option<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct AddNodeResponse {
+        #[prost(message, optional, tag = "1")]
+        pub cluster_info: Option<ClusterInfo>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct RemoveNodeRequest {
+        #[prost(string, tag = "1")]
+        pub name: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct RemoveNodeResponse {
+        #[prost(message, optional, tag = "1")]
+        pub cluster_info: Option<ClusterInfo>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StopRequest {}
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StopResponse {
+        #[prost(message, optional, tag = "1")]
+        pub cluster_info: Option<ClusterInfo>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SaveSnapshotRequest {
+        #[prost(string, tag = "1")]
+        pub snapshot_name: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SaveSnapshotResponse {
+        #[prost(string, tag = "1")]
+        pub snapshot_path: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct LoadSnapshotRequest {
+        #[prost(string, tag = "1")]
+        pub snapshot_name: String,
+        #[prost(string, tag = "2")]
+        pub exec_path: String,
+        #[prost(string, optional, tag = "3")]
+        pub plugin_dir: Option<String>,
+        #[prost(string, optional, tag = "4")]
+        pub root_data_dir: Option<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct LoadSnapshotResponse {
+        #[prost(message, optional, tag = "1")]
+        pub cluster_info: Option<ClusterInfo>,
+    }
+}
+
+const START: &str = "/rpcpb.ControlService/Start";
+const HEALTH: &str = "/rpcpb.ControlService/Health";
+const URIS: &str = "/rpcpb.ControlService/URIs";
+const ADD_NODE: &str = "/rpcpb.ControlService/AddNode";
+const REMOVE_NODE: &str = "/rpcpb.ControlService/RemoveNode";
+const STOP: &str = "/rpcpb.ControlService/Stop";
+const SAVE_SNAPSHOT: &str = "/rpcpb.ControlService/SaveSnapshot";
+const LOAD_SNAPSHOT: &str = "/rpcpb.ControlService/LoadSnapshot";
+
+/// Options of the network started by the network-runner server.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Path to the "avalanchego" binary on the server host.
+    pub exec_path: String,
+    /// Number of the nodes, or the server default if None.
+    pub num_nodes: Option<u32>,
+    pub whitelisted_subnets: Option<String>,
+    /// JSON-encoded avalanchego flags applied to all nodes.
+    pub global_node_config: Option<String>,
+    pub root_data_dir: Option<String>,
+    pub plugin_dir: Option<String>,
+    /// Maps the VM name to its genesis file path on the server host.
+    pub custom_vms: HashMap<String, String>,
+}
+
+/// Client of the ava-labs network-runner gRPC server,
+/// which manages the avalanchego processes on its own host.
+#[derive(Debug, Clone)]
+pub struct Client {
+    pub endpoint: String,
+    pub options: Options,
+    grpc: Grpc<Channel>,
+}
+
+impl Client {
+    /// Connects to the server gRPC endpoint (e.g., "http://127.0.0.1:8080").
+    pub async fn connect(endpoint: &str, options: Options) -> io::Result<Self> {
+        let channel = Endpoint::from_shared(endpoint.to_string())
+            .map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid endpoint {} ({})", endpoint, e),
+                )
+            })?
+            .connect()
+            .await
+            .map_err(|e| {
+                Error::new(
+                    ErrorKind::ConnectionRefused,
+                    format!("failed to connect {} ({})", endpoint, e),
+                )
+            })?;
+        info!("connected to the network-runner server {}", endpoint);
+        Ok(Self {
+            endpoint: endpoint.to_string(),
+            options,
+            grpc: Grpc::new(channel),
+        })
+    }
+
+    async fn unary<Req, Resp>(&mut self, path: &'static str, req: Req) -> io::Result<Resp>
+    where
+        Req: prost::Message + 'static,
+        Resp: prost::Message + Default + 'static,
+    {
+        self.grpc.ready().await.map_err(|e| {
+            Error::new(
+                ErrorKind::NotConnected,
+                format!("{} not ready ({})", self.endpoint, e),
+            )
+        })?;
+        let resp = self
+            .grpc
+            .unary(
+                tonic::Request::new(req),
+                PathAndQuery::from_static(path),
+                ProstCodec::default(),
+            )
+            .await
+            .map_err(|s| {
+                Error::new(
+                    status_kind(s.code()),
+                    format!("{} failed ({})", path, s.message()),
+                )
+            })?;
+        Ok(resp.into_inner())
+    }
+
+    pub async fn cluster_health(&mut self) -> io::Result<rpcpb::ClusterInfo> {
+        let resp: rpcpb::HealthResponse = self.unary(HEALTH, rpcpb::HealthRequest {}).await?;
+        resp.cluster_info
+            .ok_or_else(|| Error::new(ErrorKind::Other, "no cluster info in health response"))
+    }
+}
+
+#[async_trait]
+impl Runner for Client {
+    async fn start(&mut self) -> io::Result<()> {
+        let req = rpcpb::StartRequest {
+            exec_path: self.options.exec_path.clone(),
+            num_nodes: self.options.num_nodes,
+            whitelisted_subnets: self.options.whitelisted_subnets.clone(),
+            global_node_config: self.options.global_node_config.clone(),
+            root_data_dir: self.options.root_data_dir.clone(),
+            plugin_dir: self.options.plugin_dir.clone(),
+            custom_vms: self.options.custom_vms.clone(),
+        };
+        let resp: rpcpb::StartResponse = self.unary(START, req).await?;
+        if let Some(info) = resp.cluster_info {
+            info!(
+                "started {} nodes in {}",
+                info.node_names.len(),
+                info.root_data_dir
+            );
+        }
+        Ok(())
+    }
+
+    async fn add_node(&mut self, name: &str) -> io::Result<()> {
+        let req = rpcpb::AddNodeRequest {
+            name: name.to_string(),
+            exec_path: self.options.exec_path.clone(),
+            node_config: self.options.global_node_config.clone(),
+        };
+        let _: rpcpb::AddNodeResponse = self.unary(ADD_NODE, req).await?;
+        Ok(())
+    }
+
+    async fn remove_node(&mut self, name: &str) -> io::Result<()> {
+        let req = rpcpb::RemoveNodeRequest {
+            name: name.to_string(),
+        };
+        let _: rpcpb::RemoveNodeResponse = self.unary(REMOVE_NODE, req).await?;
+        Ok(())
+    }
+
+    async fn health(&mut self) -> io::Result<bool> {
+        Ok(self.cluster_health().await?.healthy)
+    }
+
+    async fn uris(&mut self) -> io::Result<Vec<String>> {
+        let resp: rpcpb::UrIsResponse = self.unary(URIS, rpcpb::UrIsRequest {}).await?;
+        Ok(resp.uris)
+    }
+
+    async fn save_snapshot(&mut self, name: &str) -> io::Result<String> {
+        let req = rpcpb::SaveSnapshotRequest {
+            snapshot_name: name.to_string(),
+        };
+        let resp: rpcpb::SaveSnapshotResponse = self.unary(SAVE_SNAPSHOT, req).await?;
+        Ok(resp.snapshot_path)
+    }
+
+    async fn load_snapshot(&mut self, name: &str) -> io::Result<()> {
+        let req = rpcpb::LoadSnapshotRequest {
+            snapshot_name: name.to_string(),
+            exec_path: self.options.exec_path.clone(),
+            plugin_dir: self.options.plugin_dir.clone(),
+            root_data_dir: self.options.root_data_dir.clone(),
+        };
+        let _: rpcpb::LoadSnapshotResponse = self.unary(LOAD_SNAPSHOT, req).await?;
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> io::Result<()> {
+        let _: rpcpb::StopResponse = self.unary(STOP, rpcpb::StopRequest {}).await?;
+        Ok(())
+    }
+}
+
+fn status_kind(code: tonic::Code) -> ErrorKind {
+    match code {
+        tonic::Code::InvalidArgument => ErrorKind::InvalidInput,
+        tonic::Code::NotFound => ErrorKind::NotFound,
+        tonic::Code::AlreadyExists => ErrorKind::AlreadyExists,
+        tonic::Code::DeadlineExceeded => ErrorKind::TimedOut,
+        tonic::Code::PermissionDenied | tonic::Code::Unauthenticated => ErrorKind::PermissionDenied,
+        tonic::Code::Unimplemented => ErrorKind::Unsupported,
+        tonic::Code::Unavailable => ErrorKind::NotConnected,
+        _ => ErrorKind::Other,
+    }
+}
+
+/// RUST_LOG=debug cargo test --package avalanchego --lib -- network_runner::rpc::test_messages --exact --show-output
+#[test]
+fn test_messages() {
+    use prost::Message;
+
+    let req = rpcpb::StartRequest {
+        exec_path: String::from("/tmp/avalanchego"),
+        num_nodes: Some(5),
+        custom_vms: HashMap::from([(String::from("subnetevm"), String::from("/tmp/genesis.json"))]),
+        ..rpcpb::StartRequest::default()
+    };
+    let b = req.encode_to_vec();
+    // field 1, length-delimited
+    assert_eq!(b[0], 0x0a);
+    assert_eq!(rpcpb::StartRequest::decode(b.as_slice()).unwrap(), req);
+
+    let info = rpcpb::ClusterInfo {
+        node_names: vec![String::from("node1")],
+        node_infos: HashMap::from([(
+            String::from("node1"),
+            rpcpb::NodeInfo {
+                name: String::from("node1"),
+                uri: String::from("http://127.0.0.1:9650"),
+                ..rpcpb::NodeInfo::default()
+            },
+        )]),
+        healthy: true,
+        ..rpcpb::ClusterInfo::default()
+    };
+    let resp = rpcpb::HealthResponse {
+        cluster_info: Some(info),
+    };
+    let decoded = rpcpb::HealthResponse::decode(resp.encode_to_vec().as_slice()).unwrap();
+    assert!(decoded.cluster_info.unwrap().healthy);
+
+    assert_eq!(status_kind(tonic::Code::NotFound), ErrorKind::NotFound);
+}
@@ -0,0 +1,188 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, Error, ErrorKind, Write},
+    path::Path,
+};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use avalanche_types::ids;
+
+/// Default "vm-aliases-file" path on the remote linux machines.
+pub const DEFAULT_VM_ALIASES_FILE: &str = "/etc/avalanche/configs/vms/aliases.json";
+
+/// VM aliases built into avalanchego, which a plugin cannot be aliased to.
+/// ref. https://github.com/ava-labs/avalanchego/blob/v1.7.14/genesis/aliases.go
+pub const BUILTIN_VM_ALIASES: [&str; 7] = [
+    "platform",
+    "avm",
+    "evm",
+    "timestamp",
+    "secp256k1fx",
+    "nftfx",
+    "propertyfx",
+];
+
+/// Maps each VM ID to its aliases, as loaded from "vm-aliases-file",
+/// so the plugins installed by their VM IDs can be referred by names.
+/// ref. https://docs.avax.network/nodes/maintain/avalanchego-config-flags#--vm-aliases-file-string
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Default)]
+#[serde(transparent)]
+pub struct VmAliases(pub BTreeMap<String, Vec<String>>);
+
+impl VmAliases {
+    pub fn load(file_path: &str) -> io::Result<Self> {
+        info!("loading VM aliases from {}", file_path);
+
+        if !Path::new(file_path).exists() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("file {} does not exists", file_path),
+            ));
+        }
+        let f = File::open(file_path)?;
+        serde_json::from_reader(f)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid JSON: {}", e)))
+    }
+
+    /// Adds the alias to the VM, and returns false if already added.
+    /// The alias must be unique across the VMs, and not a built-in alias.
+    pub fn insert(&mut self, vm_id: &ids::Id, alias: &str) -> io::Result<bool> {
+        if alias.is_empty() || BUILTIN_VM_ALIASES.contains(&alias) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("VM alias '{}' is empty or reserved", alias),
+            ));
+        }
+        let vm_id = vm_id.to_string();
+        if let Some((other, _)) = self
+            .0
+            .iter()
+            .find(|(id, aliases)| **id != vm_id && aliases.iter().any(|a| a == alias))
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("VM alias '{}' is already used by VM {}", alias, other),
+            ));
+        }
+
+        let aliases = self.0.entry(vm_id).or_default();
+        if aliases.iter().any(|a| a == alias) {
+            return Ok(false);
+        }
+        aliases.push(String::from(alias));
+        Ok(true)
+    }
+
+    /// Removes the VM and its aliases, and returns true if it existed.
+    pub fn remove(&mut self, vm_id: &ids::Id) -> bool {
+        self.0.remove(&vm_id.to_string()).is_some()
+    }
+
+    /// Saves the aliases to disk and overwrites the file.
+    pub fn sync(&self, file_path: &str) -> io::Result<()> {
+        info!("syncing VM aliases to '{}'", file_path);
+        let path = Path::new(file_path);
+        let parent_dir = path.parent().expect("unexpected None parent");
+        fs::create_dir_all(parent_dir)?;
+
+        let d = serde_json::to_vec(self).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("failed to serialize VM aliases to JSON {}", e),
+            )
+        })?;
+        let mut f = File::create(file_path)?;
+        f.write_all(&d)?;
+        Ok(())
+    }
+}
+
+/// Parses the "rpcchainvm" protocol version from the "--version" output
+/// of avalanchego (e.g., "avalanche/1.7.14 [database=v1.4.5, rpcchainvm=16, ...]")
+/// or the VM plugin (e.g., "Subnet-EVM/v0.2.7 [AvalancheGo=v1.7.14, rpcchainvm=16]").
+/// Returns "None" for the older releases that do not report it.
+pub fn parse_rpcchainvm_version(out: &str) -> Option<u32> {
+    let (_, rest) = out.split_once("rpcchainvm=")?;
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// Returns an error if avalanchego and the VM plugin both report
+/// the "rpcchainvm" protocol versions but they differ, in which case
+/// avalanchego fails to load the plugin on start.
+pub fn check_rpcchainvm_compatibility(
+    avalanchego_version: &str,
+    plugin_version: &str,
+) -> io::Result<()> {
+    match (
+        parse_rpcchainvm_version(avalanchego_version),
+        parse_rpcchainvm_version(plugin_version),
+    ) {
+        (Some(expected), Some(got)) if expected != got => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "VM plugin rpcchainvm protocol {} incompatible with avalanchego {} ('{}')",
+                got,
+                expected,
+                plugin_version.trim()
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[test]
+fn test_vm_aliases() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let subnet_evm = ids::Id::from_vm_name("subnetevm").unwrap();
+    let timestamp_vm = ids::Id::from_vm_name("timestampvm").unwrap();
+
+    let mut aliases = VmAliases::default();
+    assert!(aliases.insert(&subnet_evm, "subnetevm").unwrap());
+    assert!(!aliases.insert(&subnet_evm, "subnetevm").unwrap());
+    assert!(aliases.insert(&subnet_evm, "subnet-evm").unwrap());
+    assert!(aliases.insert(&timestamp_vm, "subnetevm").is_err());
+    assert!(aliases.insert(&timestamp_vm, "evm").is_err());
+    assert!(aliases.insert(&timestamp_vm, "timestampvm").unwrap());
+
+    let f = tempfile::NamedTempFile::new().unwrap();
+    let p = f.path().to_str().unwrap();
+    aliases.sync(p).unwrap();
+    assert_eq!(
+        fs::read_to_string(p).unwrap(),
+        r#"{"srEXiWaHuhNyGwPUi444Tu47ZEDwxTWrbQiuD7FmgSAQ6X7Dy":["subnetevm","subnet-evm"],"tGas3T58KzdjcJ2iKSyiYsWiqYctRXaPTqBCA11BqEkNg8kPc":["timestampvm"]}"#
+    );
+    let mut loaded = VmAliases::load(p).unwrap();
+    assert_eq!(loaded, aliases);
+
+    assert!(loaded.remove(&timestamp_vm));
+    assert!(!loaded.remove(&timestamp_vm));
+    assert!(!loaded.0.contains_key(&timestamp_vm.to_string()));
+}
+
+#[test]
+fn test_check_rpcchainvm_compatibility() {
+    let avalanchego = "avalanche/1.7.14 [database=v1.4.5, rpcchainvm=16, commit=0a06b7f]\n";
+    assert_eq!(parse_rpcchainvm_version(avalanchego), Some(16));
+    assert_eq!(
+        parse_rpcchainvm_version("avalanche/1.7.10 [database=v1.4.5]"),
+        None
+    );
+
+    check_rpcchainvm_compatibility(
+        avalanchego,
+        "Subnet-EVM/v0.2.7 [AvalancheGo=v1.7.14, rpcchainvm=16]",
+    )
+    .unwrap();
+    assert!(check_rpcchainvm_compatibility(
+        avalanchego,
+        "Subnet-EVM/v0.2.5 [AvalancheGo=v1.7.13, rpcchainvm=15]"
+    )
+    .is_err());
+    // older plugins without the protocol version are loaded as is
+    check_rpcchainvm_compatibility(avalanchego, "timestampvm v1.2.1").unwrap();
+}
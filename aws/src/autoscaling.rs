@@ -0,0 +1,120 @@
+use aws_sdk_autoscaling::{types::SdkError, Client};
+use aws_types::SdkConfig as AwsSdkConfig;
+use log::info;
+
+use crate::errors::{Error::API, Result};
+
+/// Lets the instance proceed to the termination.
+pub const LIFECYCLE_ACTION_RESULT_CONTINUE: &str = "CONTINUE";
+
+/// Implements AWS EC2 Auto Scaling manager.
+#[derive(Debug, Clone)]
+pub struct Manager {
+    #[allow(dead_code)]
+    shared_config: AwsSdkConfig,
+    cli: Client,
+}
+
+impl Manager {
+    pub fn new(shared_config: &AwsSdkConfig) -> Self {
+        let cloned = shared_config.clone();
+        let cli = Client::new(shared_config);
+        Self {
+            shared_config: cloned,
+            cli,
+        }
+    }
+
+    /// Extends the lifecycle hook timeout of the instance
+    /// in the "Terminating:Wait" state.
+    pub async fn record_lifecycle_action_heartbeat(
+        &self,
+        asg_name: &str,
+        hook_name: &str,
+        instance_id: &str,
+    ) -> Result<()> {
+        info!(
+            "recording lifecycle action heartbeat for '{}' in '{}'",
+            instance_id, asg_name
+        );
+        let ret = self
+            .cli
+            .record_lifecycle_action_heartbeat()
+            .auto_scaling_group_name(asg_name)
+            .lifecycle_hook_name(hook_name)
+            .instance_id(instance_id)
+            .send()
+            .await;
+        match ret {
+            Ok(_) => Ok(()),
+            Err(e) => Err(API {
+                message: format!("failed record_lifecycle_action_heartbeat {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            }),
+        }
+    }
+
+    /// Completes the lifecycle action of the instance
+    /// (e.g., "CONTINUE" to proceed to the termination).
+    pub async fn complete_lifecycle_action(
+        &self,
+        asg_name: &str,
+        hook_name: &str,
+        instance_id: &str,
+        action_result: &str,
+    ) -> Result<()> {
+        info!(
+            "completing lifecycle action for '{}' in '{}' with {}",
+            instance_id, asg_name, action_result
+        );
+        let ret = self
+            .cli
+            .complete_lifecycle_action()
+            .auto_scaling_group_name(asg_name)
+            .lifecycle_hook_name(hook_name)
+            .instance_id(instance_id)
+            .lifecycle_action_result(action_result)
+            .send()
+            .await;
+        match ret {
+            Ok(_) => Ok(()),
+            Err(e) => Err(API {
+                message: format!("failed complete_lifecycle_action {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            }),
+        }
+    }
+
+    /// Sets the desired capacity of the Auto Scaling Group, which launches
+    /// or terminates the instances within its min and max sizes.
+    pub async fn set_desired_capacity(&self, asg_name: &str, desired_capacity: u32) -> Result<()> {
+        info!(
+            "setting desired capacity of '{}' to {}",
+            asg_name, desired_capacity
+        );
+        let ret = self
+            .cli
+            .set_desired_capacity()
+            .auto_scaling_group_name(asg_name)
+            .desired_capacity(desired_capacity as i32)
+            .honor_cooldown(false)
+            .send()
+            .await;
+        match ret {
+            Ok(_) => Ok(()),
+            Err(e) => Err(API {
+                message: format!("failed set_desired_capacity {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            }),
+        }
+    }
+}
+
+#[inline]
+pub fn is_error_retryable<E>(e: &SdkError<E>) -> bool {
+    match e {
+        SdkError::TimeoutError(_) | SdkError::ResponseError { .. } => true,
+        SdkError::DispatchFailure(e) => e.is_timeout() || e.is_io(),
+        _ => false,
+    }
+}
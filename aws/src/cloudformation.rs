@@ -1,16 +1,21 @@
 use std::{
-    thread,
+    fmt,
     time::{Duration, Instant},
 };
 
 use aws_sdk_cloudformation::{
-    error::{DeleteStackError, DescribeStacksError},
-    model::{Capability, OnFailure, Output, Parameter, StackStatus, Tag},
+    error::{DeleteStackError, DescribeStacksError, UpdateStackError},
+    model::{
+        Capability, ChangeSetStatus, ChangeSetType, OnFailure, Output, Parameter, ResourceStatus,
+        StackDriftDetectionStatus, StackDriftStatus, StackEvent, StackResourceDriftStatus,
+        StackStatus, Tag,
+    },
     types::SdkError,
     Client,
 };
 use aws_types::SdkConfig as AwsSdkConfig;
 use log::{info, warn};
+use tokio::time::sleep;
 
 use crate::errors::{
     Error::{Other, API},
@@ -110,7 +115,151 @@ impl Manager {
         ))
     }
 
+    /// Updates a CloudFormation stack with the new template and parameters.
+    /// Returns the stack in "UpdateComplete" if there is nothing to update.
+    /// The separate caller is expected to poll the status asynchronously.
+    pub async fn update_stack(
+        &self,
+        stack_name: &str,
+        capabilities: Option<Vec<Capability>>,
+        template_body: &str,
+        parameters: Option<Vec<Parameter>>,
+    ) -> Result<Stack> {
+        info!("updating stack '{}'", stack_name);
+        let ret = self
+            .cli
+            .update_stack()
+            .stack_name(stack_name)
+            .set_capabilities(capabilities)
+            .template_body(template_body)
+            .set_parameters(parameters)
+            .send()
+            .await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                if is_error_no_updates(&e) {
+                    info!("stack '{}' is already up-to-date", stack_name);
+                    return Ok(Stack::new(
+                        stack_name,
+                        "",
+                        StackStatus::UpdateComplete,
+                        None,
+                    ));
+                }
+                return Err(API {
+                    message: format!("failed update_stack {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+
+        let stack_id = resp.stack_id().unwrap_or("");
+        info!("updating stack '{}' with '{}'", stack_name, stack_id);
+        Ok(Stack::new(
+            stack_name,
+            stack_id,
+            StackStatus::UpdateInProgress,
+            None,
+        ))
+    }
+
+    /// Creates the stack from the template if it does not exist yet,
+    /// otherwise updates the existing stack to the template.
+    /// Poll the returned stack for "Stack::desired_status" to wait for completion.
+    pub async fn create_or_update_stack(
+        &self,
+        stack_name: &str,
+        capabilities: Option<Vec<Capability>>,
+        template_body: &str,
+        tags: Option<Vec<Tag>>,
+        parameters: Option<Vec<Parameter>>,
+    ) -> Result<Stack> {
+        match self.describe_stack(stack_name).await? {
+            Some(_) => {
+                self.update_stack(stack_name, capabilities, template_body, parameters)
+                    .await
+            }
+            None => {
+                self.create_stack(
+                    stack_name,
+                    capabilities,
+                    OnFailure::Delete,
+                    template_body,
+                    tags,
+                    parameters,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Returns the current stack, or "None" if the stack does not exist.
+    pub async fn describe_stack(&self, stack_name: &str) -> Result<Option<Stack>> {
+        let ret = self
+            .cli
+            .describe_stacks()
+            .stack_name(stack_name)
+            .send()
+            .await;
+        let stacks = match ret {
+            Ok(v) => v.stacks.unwrap_or_default(),
+            Err(e) => {
+                if is_error_describe_stacks_does_not_exist(&e) {
+                    return Ok(None);
+                }
+                return Err(API {
+                    message: format!("failed describe_stacks {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+        let stack = match stacks.first() {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let status = stack.stack_status().unwrap();
+        if status.eq(&StackStatus::DeleteComplete) {
+            return Ok(None);
+        }
+        Ok(Some(Stack::new(
+            stack_name,
+            stack.stack_id().unwrap_or(""),
+            status.clone(),
+            stack.outputs().map(Vec::from),
+        )))
+    }
+
+    /// Returns the failed resource events of the stack, the latest first.
+    pub async fn failure_events(&self, stack_name: &str) -> Result<Vec<StackEvent>> {
+        let ret = self
+            .cli
+            .describe_stack_events()
+            .stack_name(stack_name)
+            .send()
+            .await;
+        let events = match ret {
+            Ok(v) => v.stack_events.unwrap_or_default(),
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed describe_stack_events {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+        Ok(events
+            .into_iter()
+            .filter(|ev| match &ev.resource_status {
+                Some(st) => is_resource_status_failed(st),
+                None => false,
+            })
+            .collect())
+    }
+
     /// Polls CloudFormation stack status.
+    /// The poll interval starts from 1 second and doubles on every poll,
+    /// up to "interval".
+    /// If the stack fails, the error includes its failed resource events.
     pub async fn poll_stack(
         &self,
         stack_name: &str,
@@ -124,111 +273,339 @@ impl Manager {
         );
 
         let start = Instant::now();
-        let mut cnt: u128 = 0;
+        let mut itv = Duration::from_secs(1);
         loop {
             let elapsed = start.elapsed();
             if elapsed.gt(&timeout) {
                 break;
             }
+            sleep(itv).await;
+            itv = next_poll_interval(itv, interval);
 
-            let itv = {
-                if cnt == 0 {
-                    // first poll with no wait
-                    Duration::from_secs(1)
-                } else {
-                    interval
-                }
-            };
-            thread::sleep(itv);
-
-            let ret = self
-                .cli
-                .describe_stacks()
-                .stack_name(stack_name)
-                .send()
-                .await;
-            let stacks = match ret {
-                Ok(v) => v.stacks,
-                Err(e) => {
+            let stack = match self.describe_stack(stack_name).await? {
+                Some(v) => v,
+                None => {
                     // CFN should fail for non-existing stack, instead of returning 0 stack
-                    if is_error_describe_stacks_does_not_exist(&e)
-                        && desired_status.eq(&StackStatus::DeleteComplete)
-                    {
+                    if desired_status.eq(&StackStatus::DeleteComplete) {
                         info!("stack already deleted as desired");
                         return Ok(Stack::new(stack_name, "", desired_status, None));
                     }
-                    return Err(API {
-                        message: format!("failed describe_stacks {:?}", e),
-                        is_retryable: is_error_retryable(&e),
+                    return Err(Other {
+                        message: format!(
+                            "stack '{}' not found (create/update failed thus deleted?)",
+                            stack_name
+                        ),
+                        is_retryable: false,
                     });
                 }
             };
-            let stacks = stacks.unwrap();
-            if stacks.len() != 1 {
-                // CFN should fail for non-existing stack, instead of returning 0 stack
+            info!("poll (current {:?}, elapsed {:?})", stack.status, elapsed);
+
+            if stack.status.eq(&desired_status) {
+                return Ok(stack);
+            }
+            if is_stack_status_failed(&desired_status, &stack.status) {
+                let events = self.failure_events(stack_name).await.unwrap_or_else(|e| {
+                    warn!("failed to fetch failure events ({})", e.message());
+                    Vec::new()
+                });
                 return Err(Other {
-                    message: String::from("failed to find stack"),
+                    message: format_failure(stack_name, &stack.status, &events),
                     is_retryable: false,
                 });
             }
+        }
 
-            let stack = stacks.get(0).unwrap();
-            let current_id = stack.stack_id().unwrap();
-            let current_status = stack.stack_status().unwrap();
-            info!("poll (current {:?}, elapsed {:?})", current_status, elapsed);
+        return Err(Other {
+            message: format!("failed to poll stack {} in time", stack_name),
+            is_retryable: true,
+        });
+    }
 
-            if desired_status.ne(&StackStatus::DeleteComplete)
-                && current_status.eq(&StackStatus::DeleteComplete)
-            {
+    /// Detects the drift of the stack resources from the template,
+    /// such as the resources that were manually modified or deleted.
+    /// Run before the updates so that the out-of-band changes are not silently reverted.
+    pub async fn detect_drift(&self, stack_name: &str, timeout: Duration) -> Result<StackDrift> {
+        info!("detecting drift of stack '{}'", stack_name);
+        let detection_id = self
+            .cli
+            .detect_stack_drift()
+            .stack_name(stack_name)
+            .send()
+            .await
+            .map_err(|e| API {
+                message: format!("failed detect_stack_drift {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            })?
+            .stack_drift_detection_id
+            .unwrap_or_default();
+
+        let start = Instant::now();
+        let mut itv = Duration::from_secs(1);
+        let status = loop {
+            if start.elapsed().gt(&timeout) {
                 return Err(Other {
-                    message: String::from("stack create/update failed thus deleted"),
-                    is_retryable: false,
+                    message: format!("failed to detect drift of stack {} in time", stack_name),
+                    is_retryable: true,
                 });
             }
+            sleep(itv).await;
+            itv = next_poll_interval(itv, Duration::from_secs(10));
 
-            if desired_status.eq(&StackStatus::CreateComplete)
-                && current_status.eq(&StackStatus::CreateFailed)
-            {
-                return Err(Other {
-                    message: String::from("stack create failed"),
-                    is_retryable: false,
+            let out = self
+                .cli
+                .describe_stack_drift_detection_status()
+                .stack_drift_detection_id(&detection_id)
+                .send()
+                .await
+                .map_err(|e| API {
+                    message: format!("failed describe_stack_drift_detection_status {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                })?;
+            match out.detection_status {
+                Some(StackDriftDetectionStatus::DetectionComplete) => {
+                    break out
+                        .stack_drift_status
+                        .unwrap_or(StackDriftStatus::UnknownValue);
+                }
+                Some(StackDriftDetectionStatus::DetectionFailed) => {
+                    return Err(Other {
+                        message: format!(
+                            "drift detection of stack '{}' failed ({})",
+                            stack_name,
+                            out.detection_status_reason.unwrap_or_default()
+                        ),
+                        is_retryable: false,
+                    });
+                }
+                _ => {}
+            }
+        };
+
+        let mut drifted_resources = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let out = self
+                .cli
+                .describe_stack_resource_drifts()
+                .stack_name(stack_name)
+                .stack_resource_drift_status_filters(StackResourceDriftStatus::Modified)
+                .stack_resource_drift_status_filters(StackResourceDriftStatus::Deleted)
+                .set_next_token(token.clone())
+                .send()
+                .await
+                .map_err(|e| API {
+                    message: format!("failed describe_stack_resource_drifts {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                })?;
+            for drift in out.stack_resource_drifts.unwrap_or_default() {
+                let differences = drift
+                    .property_differences
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|d| {
+                        format!(
+                            "{}: expected '{}', actual '{}'",
+                            d.property_path.unwrap_or_default(),
+                            d.expected_value.unwrap_or_default(),
+                            d.actual_value.unwrap_or_default()
+                        )
+                    })
+                    .collect();
+                drifted_resources.push(ResourceDrift {
+                    logical_id: drift.logical_resource_id.unwrap_or_default(),
+                    resource_type: drift.resource_type.unwrap_or_default(),
+                    status: drift
+                        .stack_resource_drift_status
+                        .unwrap_or(StackResourceDriftStatus::NotChecked),
+                    differences,
                 });
             }
+            token = out.next_token;
+            if token.is_none() {
+                break;
+            }
+        }
+
+        let drift = StackDrift {
+            stack_name: stack_name.to_string(),
+            status,
+            drifted_resources,
+        };
+        info!("detected drift: {}", drift);
+        Ok(drift)
+    }
+
+    /// Previews the update of the existing stack with a change set,
+    /// without executing it. The parameters not in "overrides" keep
+    /// their previous values. The change set is deleted once described.
+    pub async fn preview_stack_update(
+        &self,
+        stack_name: &str,
+        capabilities: Option<Vec<Capability>>,
+        template_body: &str,
+        overrides: Option<Vec<Parameter>>,
+        timeout: Duration,
+    ) -> Result<StackChangeSet> {
+        info!("previewing update of stack '{}'", stack_name);
+        let out = self
+            .cli
+            .describe_stacks()
+            .stack_name(stack_name)
+            .send()
+            .await
+            .map_err(|e| API {
+                message: format!("failed describe_stacks {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            })?;
+        let deployed_params = out
+            .stacks
+            .unwrap_or_default()
+            .first()
+            .and_then(|s| s.parameters.clone())
+            .unwrap_or_default();
+
+        let overrides = overrides.unwrap_or_default();
+        let mut params: Vec<Parameter> = deployed_params
+            .iter()
+            .filter_map(|p| p.parameter_key())
+            .filter(|k| !overrides.iter().any(|o| o.parameter_key() == Some(*k)))
+            .map(|k| {
+                Parameter::builder()
+                    .parameter_key(k)
+                    .use_previous_value(true)
+                    .build()
+            })
+            .collect();
+        params.extend(overrides);
+
+        let change_set_name = format!("{}-preview", stack_name);
+        let change_set_id = self
+            .cli
+            .create_change_set()
+            .stack_name(stack_name)
+            .change_set_name(&change_set_name)
+            .change_set_type(ChangeSetType::Update)
+            .set_capabilities(capabilities)
+            .template_body(template_body)
+            .set_parameters(Some(params))
+            .send()
+            .await
+            .map_err(|e| API {
+                message: format!("failed create_change_set {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            })?
+            .id
+            .unwrap_or_default();
+
+        let ret = self
+            .describe_change_set(stack_name, &change_set_id, timeout)
+            .await;
+
+        // delete regardless of the result, so the preview leaves nothing behind
+        if let Err(e) = self
+            .cli
+            .delete_change_set()
+            .stack_name(stack_name)
+            .change_set_name(&change_set_id)
+            .send()
+            .await
+        {
+            warn!("failed delete_change_set {:?}", e);
+        }
+        ret
+    }
 
-            if desired_status.eq(&StackStatus::DeleteComplete)
-                && current_status.eq(&StackStatus::DeleteFailed)
-            {
+    async fn describe_change_set(
+        &self,
+        stack_name: &str,
+        change_set_id: &str,
+        timeout: Duration,
+    ) -> Result<StackChangeSet> {
+        let start = Instant::now();
+        let mut itv = Duration::from_secs(1);
+        let mut changes = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            if start.elapsed().gt(&timeout) {
                 return Err(Other {
-                    message: String::from("stack delete failed"),
-                    is_retryable: false,
+                    message: format!(
+                        "failed to create change set of stack {} in time",
+                        stack_name
+                    ),
+                    is_retryable: true,
                 });
             }
 
-            if current_status.eq(&desired_status) {
-                let outputs = stack.outputs();
-                let outputs = outputs.unwrap();
-                let outputs = Vec::from(outputs);
-                let current_stack = Stack::new(
-                    stack_name,
-                    current_id,
-                    current_status.clone(),
-                    Some(outputs),
-                );
-                return Ok(current_stack);
+            let out = self
+                .cli
+                .describe_change_set()
+                .stack_name(stack_name)
+                .change_set_name(change_set_id)
+                .set_next_token(token.clone())
+                .send()
+                .await
+                .map_err(|e| API {
+                    message: format!("failed describe_change_set {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                })?;
+            match out.status {
+                Some(ChangeSetStatus::CreateComplete) => {}
+                Some(ChangeSetStatus::Failed) => {
+                    let reason = out.status_reason.unwrap_or_default();
+                    // e.g., "The submitted information didn't contain changes..."
+                    if reason.contains("didn't contain changes")
+                        || reason.contains("No updates are to be performed")
+                    {
+                        break;
+                    }
+                    return Err(Other {
+                        message: format!(
+                            "change set of stack '{}' failed ({})",
+                            stack_name, reason
+                        ),
+                        is_retryable: false,
+                    });
+                }
+                _ => {
+                    sleep(itv).await;
+                    itv = next_poll_interval(itv, Duration::from_secs(10));
+                    continue;
+                }
             }
 
-            cnt += 1;
+            for c in out.changes.unwrap_or_default() {
+                let rc = match c.resource_change {
+                    Some(v) => v,
+                    None => continue,
+                };
+                changes.push(ResourceChange {
+                    logical_id: rc.logical_resource_id.unwrap_or_default(),
+                    resource_type: rc.resource_type.unwrap_or_default(),
+                    action: rc
+                        .action
+                        .map(|a| a.as_str().to_string())
+                        .unwrap_or_default(),
+                    replacement: rc.replacement.map(|r| r.as_str().to_string()),
+                });
+            }
+            token = out.next_token;
+            if token.is_none() {
+                break;
+            }
         }
 
-        return Err(Other {
-            message: format!("failed to poll stack {} in time", stack_name),
-            is_retryable: true,
-        });
+        let change_set = StackChangeSet {
+            stack_name: stack_name.to_string(),
+            changes,
+        };
+        info!("previewed change set: {}", change_set);
+        Ok(change_set)
     }
 }
 
 /// Represents the CloudFormation stack.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Stack {
     pub name: String,
     pub id: String,
@@ -248,6 +625,170 @@ impl Stack {
     }
 }
 
+impl Stack {
+    /// Returns the status to poll for after the in-progress operation.
+    pub fn desired_status(&self) -> StackStatus {
+        match self.status {
+            StackStatus::CreateInProgress => StackStatus::CreateComplete,
+            StackStatus::UpdateInProgress => StackStatus::UpdateComplete,
+            StackStatus::DeleteInProgress => StackStatus::DeleteComplete,
+            _ => self.status.clone(),
+        }
+    }
+}
+
+/// Drift of the stack resources from the stack template.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackDrift {
+    pub stack_name: String,
+    pub status: StackDriftStatus,
+    /// Resources that were modified or deleted out of band.
+    pub drifted_resources: Vec<ResourceDrift>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceDrift {
+    pub logical_id: String,
+    pub resource_type: String,
+    pub status: StackResourceDriftStatus,
+    /// Human-readable property differences (e.g., "Path: expected 'a', actual 'b'").
+    pub differences: Vec<String>,
+}
+
+impl StackDrift {
+    pub fn is_drifted(&self) -> bool {
+        self.status.eq(&StackDriftStatus::Drifted) || !self.drifted_resources.is_empty()
+    }
+}
+
+impl fmt::Display for StackDrift {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.is_drifted() {
+            return write!(f, "stack '{}' is {:?}", self.stack_name, self.status);
+        }
+        write!(
+            f,
+            "stack '{}' has {} drifted resource(s)",
+            self.stack_name,
+            self.drifted_resources.len()
+        )?;
+        for r in self.drifted_resources.iter() {
+            write!(
+                f,
+                "\n  {} ({}) {:?}",
+                r.logical_id, r.resource_type, r.status
+            )?;
+            for d in r.differences.iter() {
+                write!(f, "\n    {}", d)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Resource changes of the stack update, as previewed by the change set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackChangeSet {
+    pub stack_name: String,
+    pub changes: Vec<ResourceChange>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceChange {
+    pub logical_id: String,
+    pub resource_type: String,
+    /// e.g., "Add", "Modify", "Remove".
+    pub action: String,
+    /// e.g., "True", "False", "Conditional" (only for "Modify").
+    pub replacement: Option<String>,
+}
+
+impl fmt::Display for ResourceChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} ({})",
+            self.action, self.logical_id, self.resource_type
+        )?;
+        if let Some(replacement) = &self.replacement {
+            write!(f, " replacement '{}'", replacement)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for StackChangeSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.changes.is_empty() {
+            return write!(f, "stack '{}' has no changes", self.stack_name);
+        }
+        write!(
+            f,
+            "stack '{}' has {} change(s)",
+            self.stack_name,
+            self.changes.len()
+        )?;
+        for c in self.changes.iter() {
+            write!(f, "\n  {}", c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Doubles the poll interval up to the max.
+fn next_poll_interval(cur: Duration, max: Duration) -> Duration {
+    std::cmp::min(cur * 2, max)
+}
+
+/// Returns true if the stack can no longer reach the desired status.
+fn is_stack_status_failed(desired: &StackStatus, current: &StackStatus) -> bool {
+    if current.eq(desired) {
+        return false;
+    }
+    matches!(
+        current,
+        StackStatus::CreateFailed
+            | StackStatus::DeleteFailed
+            | StackStatus::RollbackComplete
+            | StackStatus::RollbackFailed
+            | StackStatus::UpdateFailed
+            | StackStatus::UpdateRollbackComplete
+            | StackStatus::UpdateRollbackFailed
+            | StackStatus::ImportRollbackComplete
+            | StackStatus::ImportRollbackFailed
+    ) || (desired.ne(&StackStatus::DeleteComplete) && current.eq(&StackStatus::DeleteComplete))
+}
+
+fn is_resource_status_failed(status: &ResourceStatus) -> bool {
+    matches!(
+        status,
+        ResourceStatus::CreateFailed
+            | ResourceStatus::DeleteFailed
+            | ResourceStatus::UpdateFailed
+            | ResourceStatus::ImportFailed
+            | ResourceStatus::RollbackFailed
+            | ResourceStatus::UpdateRollbackFailed
+            | ResourceStatus::ImportRollbackFailed
+    )
+}
+
+/// Formats the stack failure with the failed resource events,
+/// so that the root cause shows up without visiting the console.
+fn format_failure(stack_name: &str, status: &StackStatus, events: &[StackEvent]) -> String {
+    let mut msg = format!("stack '{}' failed with {:?}", stack_name, status);
+    for ev in events.iter() {
+        msg.push_str(&format!(
+            "\n  {} ({}) {:?}: {}",
+            ev.logical_resource_id().unwrap_or(""),
+            ev.resource_type().unwrap_or(""),
+            ev.resource_status()
+                .unwrap_or(&ResourceStatus::Unknown(String::new())),
+            ev.resource_status_reason().unwrap_or("")
+        ));
+    }
+    msg
+}
+
 #[inline]
 pub fn is_error_retryable<E>(e: &SdkError<E>) -> bool {
     match e {
@@ -278,3 +819,142 @@ fn is_error_describe_stacks_does_not_exist(e: &SdkError<DescribeStacksError>) ->
         _ => false,
     }
 }
+
+#[inline]
+fn is_error_no_updates(e: &SdkError<UpdateStackError>) -> bool {
+    match e {
+        SdkError::ServiceError { err, .. } => {
+            let msg = format!("{:?}", err);
+            msg.contains("No updates are to be performed")
+        }
+        _ => false,
+    }
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- cloudformation::test_stack_status --exact --show-output
+#[test]
+fn test_stack_status() {
+    assert_eq!(
+        next_poll_interval(Duration::from_secs(1), Duration::from_secs(30)),
+        Duration::from_secs(2)
+    );
+    assert_eq!(
+        next_poll_interval(Duration::from_secs(16), Duration::from_secs(30)),
+        Duration::from_secs(30)
+    );
+
+    let create = StackStatus::CreateComplete;
+    assert!(!is_stack_status_failed(
+        &create,
+        &StackStatus::CreateInProgress
+    ));
+    assert!(!is_stack_status_failed(
+        &create,
+        &StackStatus::CreateComplete
+    ));
+    assert!(is_stack_status_failed(&create, &StackStatus::CreateFailed));
+    assert!(is_stack_status_failed(
+        &create,
+        &StackStatus::RollbackComplete
+    ));
+    assert!(is_stack_status_failed(
+        &create,
+        &StackStatus::DeleteComplete
+    ));
+    let update = StackStatus::UpdateComplete;
+    assert!(!is_stack_status_failed(
+        &update,
+        &StackStatus::UpdateCompleteCleanupInProgress
+    ));
+    assert!(is_stack_status_failed(
+        &update,
+        &StackStatus::UpdateRollbackComplete
+    ));
+    let delete = StackStatus::DeleteComplete;
+    assert!(!is_stack_status_failed(
+        &delete,
+        &StackStatus::DeleteComplete
+    ));
+    assert!(is_stack_status_failed(&delete, &StackStatus::DeleteFailed));
+
+    let stack = Stack::new("a", "b", StackStatus::UpdateInProgress, None);
+    assert_eq!(stack.desired_status(), StackStatus::UpdateComplete);
+
+    let events = vec![StackEvent::builder()
+        .logical_resource_id("VPC")
+        .resource_type("AWS::EC2::VPC")
+        .resource_status(ResourceStatus::CreateFailed)
+        .resource_status_reason("limit exceeded")
+        .build()];
+    let msg = format_failure("my-stack", &StackStatus::RollbackComplete, &events);
+    assert!(msg.starts_with("stack 'my-stack' failed with RollbackComplete"));
+    assert!(msg.contains("VPC (AWS::EC2::VPC) CreateFailed: limit exceeded"));
+    assert!(is_resource_status_failed(&ResourceStatus::CreateFailed));
+    assert!(!is_resource_status_failed(&ResourceStatus::CreateComplete));
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- cloudformation::test_stack_drift --exact --show-output
+#[test]
+fn test_stack_drift() {
+    let in_sync = StackDrift {
+        stack_name: String::from("my-stack"),
+        status: StackDriftStatus::InSync,
+        drifted_resources: Vec::new(),
+    };
+    assert!(!in_sync.is_drifted());
+    assert_eq!(in_sync.to_string(), "stack 'my-stack' is InSync");
+
+    let drifted = StackDrift {
+        stack_name: String::from("my-stack"),
+        status: StackDriftStatus::Drifted,
+        drifted_resources: vec![ResourceDrift {
+            logical_id: String::from("SecurityGroup"),
+            resource_type: String::from("AWS::EC2::SecurityGroup"),
+            status: StackResourceDriftStatus::Modified,
+            differences: vec![String::from(
+                "/SecurityGroupIngress/0/CidrIp: expected '0.0.0.0/0', actual '1.2.3.4/32'",
+            )],
+        }],
+    };
+    assert!(drifted.is_drifted());
+    assert_eq!(
+        drifted.to_string(),
+        "stack 'my-stack' has 1 drifted resource(s)
+  SecurityGroup (AWS::EC2::SecurityGroup) Modified
+    /SecurityGroupIngress/0/CidrIp: expected '0.0.0.0/0', actual '1.2.3.4/32'"
+    );
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- cloudformation::test_stack_change_set --exact --show-output
+#[test]
+fn test_stack_change_set() {
+    let unchanged = StackChangeSet {
+        stack_name: String::from("my-stack"),
+        changes: Vec::new(),
+    };
+    assert_eq!(unchanged.to_string(), "stack 'my-stack' has no changes");
+
+    let changed = StackChangeSet {
+        stack_name: String::from("my-stack"),
+        changes: vec![
+            ResourceChange {
+                logical_id: String::from("ASG"),
+                resource_type: String::from("AWS::AutoScaling::AutoScalingGroup"),
+                action: String::from("Modify"),
+                replacement: Some(String::from("False")),
+            },
+            ResourceChange {
+                logical_id: String::from("NLB"),
+                resource_type: String::from("AWS::ElasticLoadBalancingV2::LoadBalancer"),
+                action: String::from("Add"),
+                replacement: None,
+            },
+        ],
+    };
+    assert_eq!(
+        changed.to_string(),
+        "stack 'my-stack' has 2 change(s)
+  Modify ASG (AWS::AutoScaling::AutoScalingGroup) replacement 'False'
+  Add NLB (AWS::ElasticLoadBalancingV2::LoadBalancer)"
+    );
+}
@@ -0,0 +1,301 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Error, ErrorKind, Seek, SeekFrom},
+    path::Path,
+};
+
+use aws_sdk_cloudwatchlogs::{
+    error::{CreateLogStreamError, CreateLogStreamErrorKind},
+    model::InputLogEvent,
+    types::SdkError as LogsSdkError,
+};
+use chrono::Utc;
+use log::{info, warn};
+
+use crate::{
+    cloudwatch::{is_logs_error_retryable, Manager},
+    errors::{Error::API, Result},
+};
+
+/// Retention periods that CloudWatch Logs accepts.
+/// ref. https://docs.aws.amazon.com/AmazonCloudWatchLogs/latest/APIReference/API_PutRetentionPolicy.html
+pub const VALID_RETENTION_IN_DAYS: [u16; 19] = [
+    1, 3, 5, 7, 14, 30, 60, 90, 120, 150, 180, 365, 400, 545, 731, 1096, 1827, 2192, 3653,
+];
+pub const DEFAULT_RETENTION_IN_DAYS: u16 = 7;
+
+/// ref. https://docs.aws.amazon.com/AmazonCloudWatch/latest/monitoring/install-CloudWatch-Agent-commandline-fleet.html
+pub const DEFAULT_AGENT_CTL_PATH: &str =
+    "/opt/aws/amazon-cloudwatch-agent/bin/amazon-cloudwatch-agent-ctl";
+
+/// "PutLogEvents" limits: 10,000 events and 1,048,576 bytes per batch,
+/// where each event counts its message length plus 26 bytes.
+/// ref. https://docs.aws.amazon.com/AmazonCloudWatchLogs/latest/APIReference/API_PutLogEvents.html
+pub const MAX_LOG_EVENTS_PER_PUT: usize = 10_000;
+pub const MAX_LOG_EVENTS_BYTES_PER_PUT: usize = 1_048_576;
+const LOG_EVENT_OVERHEAD_BYTES: usize = 26;
+/// Maximum size of a single log event, longer lines are truncated.
+pub const MAX_LOG_EVENT_BYTES: usize = 262_144 - LOG_EVENT_OVERHEAD_BYTES;
+
+pub fn validate_retention_in_days(days: u16) -> io::Result<()> {
+    if !VALID_RETENTION_IN_DAYS.contains(&days) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "invalid log retention {} days (expected one of {:?})",
+                days, VALID_RETENTION_IN_DAYS
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns true if the CloudWatch agent is installed on the local machine.
+pub fn is_agent_installed() -> bool {
+    Path::new(DEFAULT_AGENT_CTL_PATH).exists()
+}
+
+/// Splits the lines into batches within the "PutLogEvents" limits.
+pub fn batch_lines(lines: &[String]) -> Vec<&[String]> {
+    let mut batches = Vec::new();
+    let (mut start, mut size) = (0, 0);
+    for (i, line) in lines.iter().enumerate() {
+        let n = line.len().min(MAX_LOG_EVENT_BYTES) + LOG_EVENT_OVERHEAD_BYTES;
+        if i > start
+            && (i - start == MAX_LOG_EVENTS_PER_PUT || size + n > MAX_LOG_EVENTS_BYTES_PER_PUT)
+        {
+            batches.push(&lines[start..i]);
+            start = i;
+            size = 0;
+        }
+        size += n;
+    }
+    if start < lines.len() {
+        batches.push(&lines[start..]);
+    }
+    batches
+}
+
+/// Tails a log file from the last read offset, for shipping the logs
+/// without the CloudWatch agent. Starts over if the file is truncated
+/// or rotated (e.g., the size shrinks below the offset).
+#[derive(Debug, Clone)]
+pub struct FileTailer {
+    pub file_path: String,
+    pub offset: u64,
+}
+
+impl FileTailer {
+    pub fn new(file_path: &str) -> Self {
+        Self {
+            file_path: file_path.to_string(),
+            offset: 0,
+        }
+    }
+
+    /// Reads the complete lines appended since the last read.
+    /// A trailing partial line is left for the next read.
+    pub fn read_lines(&mut self) -> io::Result<Vec<String>> {
+        if !Path::new(&self.file_path).exists() {
+            return Ok(Vec::new());
+        }
+        let mut f = File::open(&self.file_path)?;
+        let size = f.metadata()?.len();
+        if size < self.offset {
+            warn!(
+                "'{}' shrank from {} to {} bytes, reading from the start",
+                self.file_path, self.offset, size
+            );
+            self.offset = 0;
+        }
+        f.seek(SeekFrom::Start(self.offset))?;
+
+        let mut reader = BufReader::new(f);
+        let mut lines = Vec::new();
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            let n = reader.read_until(b'\n', &mut buf)?;
+            if n == 0 || buf.last() != Some(&b'\n') {
+                break;
+            }
+            self.offset += n as u64;
+
+            let line = String::from_utf8_lossy(&buf[..n - 1]);
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() {
+                continue;
+            }
+            let mut end = line.len().min(MAX_LOG_EVENT_BYTES);
+            while !line.is_char_boundary(end) {
+                end -= 1;
+            }
+            lines.push(line[..end].to_string());
+        }
+        Ok(lines)
+    }
+}
+
+impl Manager {
+    /// Sets the retention of a CloudWatch log group.
+    /// ref. https://docs.aws.amazon.com/AmazonCloudWatchLogs/latest/APIReference/API_PutRetentionPolicy.html
+    pub async fn put_retention_policy(&self, log_group_name: &str, days: u16) -> Result<()> {
+        info!(
+            "setting CloudWatch log group '{}' retention to {} days",
+            log_group_name, days
+        );
+        let ret = self
+            .logs_cli
+            .put_retention_policy()
+            .log_group_name(log_group_name)
+            .retention_in_days(days as i32)
+            .send()
+            .await;
+        match ret {
+            Ok(_) => {
+                info!("set CloudWatch log group retention");
+                Ok(())
+            }
+            Err(e) => Err(API {
+                message: format!("failed put_retention_policy {:?}", e),
+                is_retryable: is_logs_error_retryable(&e),
+            }),
+        }
+    }
+
+    /// Creates a CloudWatch log stream, ignoring the one that already exists.
+    /// ref. https://docs.aws.amazon.com/AmazonCloudWatchLogs/latest/APIReference/API_CreateLogStream.html
+    pub async fn create_log_stream(
+        &self,
+        log_group_name: &str,
+        log_stream_name: &str,
+    ) -> Result<()> {
+        info!(
+            "creating CloudWatch log stream '{}' in '{}'",
+            log_stream_name, log_group_name
+        );
+        let ret = self
+            .logs_cli
+            .create_log_stream()
+            .log_group_name(log_group_name)
+            .log_stream_name(log_stream_name)
+            .send()
+            .await;
+        match ret {
+            Ok(_) => info!("created CloudWatch log stream"),
+            Err(e) => {
+                if !is_logs_error_create_log_stream_already_exists(&e) {
+                    return Err(API {
+                        message: format!("failed create_log_stream {:?}", e),
+                        is_retryable: is_logs_error_retryable(&e),
+                    });
+                }
+                warn!("log_stream already exists ({})", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Uploads the lines to the log stream, in batches within the request limits.
+    /// All events are stamped with the current time.
+    /// ref. https://docs.aws.amazon.com/AmazonCloudWatchLogs/latest/APIReference/API_PutLogEvents.html
+    pub async fn put_log_events(
+        &self,
+        log_group_name: &str,
+        log_stream_name: &str,
+        lines: &[String],
+    ) -> Result<()> {
+        let ts = Utc::now().timestamp_millis();
+        for batch in batch_lines(lines) {
+            let events: Vec<InputLogEvent> = batch
+                .iter()
+                .map(|line| InputLogEvent::builder().message(line).timestamp(ts).build())
+                .collect();
+            let ret = self
+                .logs_cli
+                .put_log_events()
+                .log_group_name(log_group_name)
+                .log_stream_name(log_stream_name)
+                .set_log_events(Some(events))
+                .send()
+                .await;
+            if let Err(e) = ret {
+                return Err(API {
+                    message: format!("failed put_log_events {:?}", e),
+                    is_retryable: is_logs_error_retryable(&e),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[inline]
+fn is_logs_error_create_log_stream_already_exists(e: &LogsSdkError<CreateLogStreamError>) -> bool {
+    match e {
+        LogsSdkError::ServiceError { err, .. } => {
+            matches!(
+                err.kind,
+                CreateLogStreamErrorKind::ResourceAlreadyExistsException(_)
+            )
+        }
+        _ => false,
+    }
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- cloudwatch::logs::test_retention_in_days --exact --show-output
+#[test]
+fn test_retention_in_days() {
+    validate_retention_in_days(DEFAULT_RETENTION_IN_DAYS).unwrap();
+    validate_retention_in_days(365).unwrap();
+    assert!(validate_retention_in_days(0).is_err());
+    assert!(validate_retention_in_days(10).is_err());
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- cloudwatch::logs::test_batch_lines --exact --show-output
+#[test]
+fn test_batch_lines() {
+    assert!(batch_lines(&[]).is_empty());
+
+    let lines: Vec<String> = (0..MAX_LOG_EVENTS_PER_PUT + 1)
+        .map(|i| i.to_string())
+        .collect();
+    let batches = batch_lines(&lines);
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].len(), MAX_LOG_EVENTS_PER_PUT);
+    assert_eq!(batches[1], &[MAX_LOG_EVENTS_PER_PUT.to_string()]);
+
+    // 5 x 256 KiB events do not fit in 1 MiB
+    let lines = vec!["a".repeat(MAX_LOG_EVENT_BYTES); 5];
+    let batches = batch_lines(&lines);
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].len(), 4);
+    assert_eq!(batches[1].len(), 1);
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- cloudwatch::logs::test_file_tailer --exact --show-output
+#[test]
+fn test_file_tailer() {
+    use std::{fs, io::Write};
+    use utils::random;
+
+    let p = random::tmp_path(10, Some(".log")).unwrap();
+    let mut tailer = FileTailer::new(&p);
+    assert!(tailer.read_lines().unwrap().is_empty());
+
+    let mut f = File::create(&p).unwrap();
+    f.write_all(b"first\nsecond\n\nthi").unwrap();
+    assert_eq!(tailer.read_lines().unwrap(), vec!["first", "second"]);
+    assert_eq!(tailer.offset, 14);
+
+    // partial line is read once completed
+    f.write_all(b"rd\r\n").unwrap();
+    assert_eq!(tailer.read_lines().unwrap(), vec!["third"]);
+    assert!(tailer.read_lines().unwrap().is_empty());
+
+    // truncated (e.g., rotated)
+    fs::write(&p, b"new\n").unwrap();
+    assert_eq!(tailer.read_lines().unwrap(), vec!["new"]);
+
+    fs::remove_file(&p).unwrap();
+}
@@ -1,3 +1,5 @@
+pub mod logs;
+
 use std::{
     collections::HashMap,
     fs::{self, File},
@@ -9,7 +11,9 @@ use std::{
 };
 
 use aws_sdk_cloudwatch::{
-    model::MetricDatum, types::SdkError as MetricsSdkError, Client as MetricsClient,
+    model::{Dimension, MetricDatum, StandardUnit},
+    types::SdkError as MetricsSdkError,
+    Client as MetricsClient,
 };
 use aws_sdk_cloudwatchlogs::{
     error::{
@@ -18,12 +22,18 @@ use aws_sdk_cloudwatchlogs::{
     types::SdkError as LogsSdkError,
     Client as LogsClient,
 };
+use aws_smithy_types::DateTime as SmithyDateTime;
 use aws_types::SdkConfig as AwsSdkConfig;
+use chrono::Utc;
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
 
 use crate::errors::{Error::API, Result};
 
+/// Maximum number of metric data in a single "PutMetricData" request.
+/// ref. https://docs.aws.amazon.com/AmazonCloudWatch/latest/APIReference/API_PutMetricData.html
+pub const MAX_METRIC_DATA_PER_PUT: usize = 20;
+
 /// Implements AWS CloudWatch manager.
 #[derive(Debug, Clone)]
 pub struct Manager {
@@ -60,7 +70,7 @@ impl Manager {
     ) -> Result<()> {
         let n = data.len();
         info!("posting CloudWatch {} metrics in '{}'", n, namespace);
-        if n <= 20 {
+        if n <= MAX_METRIC_DATA_PER_PUT {
             let ret = self
                 .metrics_cli
                 .put_metric_data()
@@ -80,8 +90,11 @@ impl Manager {
                 }
             };
         } else {
-            warn!("put_metric_data limit is 20, got {}; batching by 20...", n);
-            for batch in data.chunks(20) {
+            warn!(
+                "put_metric_data limit is {}, got {}; batching...",
+                MAX_METRIC_DATA_PER_PUT, n
+            );
+            for batch in data.chunks(MAX_METRIC_DATA_PER_PUT) {
                 let batch_n = batch.len();
                 let ret = self
                     .metrics_cli
@@ -234,6 +247,160 @@ pub async fn spawn_put_metric_data(
     .expect("failed spawn await")
 }
 
+pub const DEFAULT_TELEMETRY_INTERVAL_SECONDS: u64 = 60;
+
+/// The telemetry is published with the standard resolution (1-minute),
+/// so a shorter interval would only add "PutMetricData" calls, and
+/// 0 would publish in a busy loop.
+pub fn validate_telemetry_interval_seconds(interval_seconds: u64) -> io::Result<()> {
+    if interval_seconds < 60 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "telemetry interval {} seconds too short (at least 60)",
+                interval_seconds
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Represents the node telemetry published as custom metrics.
+/// "None" fields are not published (e.g., the API was not reachable).
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct Telemetry {
+    /// Bootstrap progress of the node or the chain (0 ~ 100), derived from
+    /// the fetched blocks against the heights of the peers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bootstrap_progress_percent: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peers: Option<u32>,
+    /// Last accepted P-chain height.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_accepted_height: Option<u64>,
+    /// Disk usage of the volume for the database directory (0 ~ 100).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk_used_percent: Option<f64>,
+}
+
+impl Telemetry {
+    /// Converts the telemetry to the CloudWatch metric data at the current time,
+    /// skipping the fields that are not collected.
+    pub fn to_metric_data(&self) -> Vec<MetricDatum> {
+        let ts = SmithyDateTime::from_secs(Utc::now().timestamp());
+        let datum = |name: &str, value: f64, unit: StandardUnit| {
+            MetricDatum::builder()
+                .metric_name(name)
+                .value(value)
+                .unit(unit)
+                .timestamp(ts)
+                .build()
+        };
+
+        let mut data = Vec::new();
+        if let Some(v) = self.bootstrap_progress_percent {
+            data.push(datum(
+                "bootstrap_progress_percent",
+                v,
+                StandardUnit::Percent,
+            ));
+        }
+        if let Some(v) = self.peers {
+            data.push(datum("peers", v as f64, StandardUnit::Count));
+        }
+        if let Some(v) = self.last_accepted_height {
+            data.push(datum("last_accepted_height", v as f64, StandardUnit::Count));
+        }
+        if let Some(v) = self.disk_used_percent {
+            data.push(datum("disk_used_percent", v, StandardUnit::Percent));
+        }
+        data
+    }
+}
+
+/// Appends the dimensions to every metric datum
+/// (e.g., "network-id" and "node-id" to filter metrics per node).
+/// ref. https://docs.aws.amazon.com/AmazonCloudWatch/latest/monitoring/cloudwatch_concepts.html#Dimension
+pub fn with_dimensions(data: Vec<MetricDatum>, dimensions: &[(&str, &str)]) -> Vec<MetricDatum> {
+    data.into_iter()
+        .map(|mut d| {
+            let mut dims = d.dimensions.take().unwrap_or_default();
+            for (k, v) in dimensions.iter() {
+                dims.push(Dimension::builder().name(*k).value(*v).build());
+            }
+            d.dimensions = Some(dims);
+            d
+        })
+        .collect()
+}
+
+/// Returns the count metric datum at the current time
+/// (e.g., to record an event such as the node upgrade).
+pub fn new_count_datum(name: &str, value: f64) -> MetricDatum {
+    MetricDatum::builder()
+        .metric_name(name)
+        .value(value)
+        .unit(StandardUnit::Count)
+        .timestamp(SmithyDateTime::from_secs(Utc::now().timestamp()))
+        .build()
+}
+
+/// Returns the metric datum with no unit at the current time
+/// (e.g., a gauge such as the number of upgraded nodes).
+pub fn new_datum(name: &str, value: f64) -> MetricDatum {
+    MetricDatum::builder()
+        .metric_name(name)
+        .value(value)
+        .unit(StandardUnit::None)
+        .timestamp(SmithyDateTime::from_secs(Utc::now().timestamp()))
+        .build()
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- cloudwatch::test_telemetry --exact --show-output
+#[test]
+fn test_telemetry() {
+    assert!(validate_telemetry_interval_seconds(0).is_err());
+    assert!(validate_telemetry_interval_seconds(59).is_err());
+    validate_telemetry_interval_seconds(DEFAULT_TELEMETRY_INTERVAL_SECONDS).unwrap();
+
+    assert!(Telemetry::default().to_metric_data().is_empty());
+
+    let telemetry = Telemetry {
+        bootstrap_progress_percent: Some(66.0),
+        peers: Some(5),
+        last_accepted_height: None,
+        disk_used_percent: Some(41.0),
+    };
+    let data = with_dimensions(
+        telemetry.to_metric_data(),
+        &[("network-id", "1337"), ("node-id", "NodeID-abc")],
+    );
+    let names: Vec<&str> = data
+        .iter()
+        .map(|d| d.metric_name.as_deref().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        vec!["bootstrap_progress_percent", "peers", "disk_used_percent"]
+    );
+    for d in data.iter() {
+        let dims: Vec<(&str, &str)> = d
+            .dimensions
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|x| (x.name.as_deref().unwrap(), x.value.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            dims,
+            vec![("network-id", "1337"), ("node-id", "NodeID-abc")]
+        );
+        assert!(d.timestamp.is_some());
+    }
+    assert_eq!(data[1].value, Some(5.0));
+}
+
 /// ref. https://docs.aws.amazon.com/AmazonCloudWatch/latest/monitoring/CloudWatch-Agent-Configuration-File-Details.html
 pub const DEFAULT_CONFIG_FILE_PATH: &str = "/opt/aws/amazon-cloudwatch-agent/bin/config.json";
 
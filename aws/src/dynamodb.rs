@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant, SystemTime},
+};
+
+use aws_sdk_dynamodb::{
+    model::{
+        AttributeDefinition, AttributeValue, BillingMode, KeySchemaElement, KeyType,
+        ScalarAttributeType, TableStatus,
+    },
+    types::SdkError,
+    Client,
+};
+use aws_types::SdkConfig as AwsSdkConfig;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+
+use crate::errors::{
+    Error::{Other, API},
+    Result,
+};
+
+/// Partition key of the lock table.
+pub const LOCK_TABLE_KEY: &str = "LockID";
+
+/// Represents the lock item.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Lock {
+    pub lock_id: String,
+    /// Unique per lock holder (e.g., caller ARN with a random suffix).
+    pub owner: String,
+    /// Operation that holds the lock (e.g., "apply").
+    pub operation: String,
+    /// Unix timestamps.
+    pub acquired_at: u64,
+    pub expires_at: u64,
+}
+
+impl Lock {
+    pub fn new(lock_id: &str, owner: &str, operation: &str, lease: Duration) -> Self {
+        let now = unix_now();
+        Self {
+            lock_id: lock_id.to_string(),
+            owner: owner.to_string(),
+            operation: operation.to_string(),
+            acquired_at: now,
+            expires_at: now + lease.as_secs(),
+        }
+    }
+
+    fn to_item(&self) -> HashMap<String, AttributeValue> {
+        HashMap::from([
+            (
+                String::from(LOCK_TABLE_KEY),
+                AttributeValue::S(self.lock_id.clone()),
+            ),
+            (String::from("Owner"), AttributeValue::S(self.owner.clone())),
+            (
+                String::from("Operation"),
+                AttributeValue::S(self.operation.clone()),
+            ),
+            (
+                String::from("AcquiredAt"),
+                AttributeValue::N(self.acquired_at.to_string()),
+            ),
+            (
+                String::from("ExpiresAt"),
+                AttributeValue::N(self.expires_at.to_string()),
+            ),
+        ])
+    }
+
+    fn from_item(item: &HashMap<String, AttributeValue>) -> Option<Self> {
+        let s = |k: &str| item.get(k).and_then(|v| v.as_s().ok()).cloned();
+        let n = |k: &str| {
+            item.get(k)
+                .and_then(|v| v.as_n().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+        };
+        Some(Self {
+            lock_id: s(LOCK_TABLE_KEY)?,
+            owner: s("Owner")?,
+            operation: s("Operation").unwrap_or_default(),
+            acquired_at: n("AcquiredAt").unwrap_or_default(),
+            expires_at: n("ExpiresAt")?,
+        })
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at < now
+    }
+}
+
+/// Implements AWS DynamoDB manager.
+#[derive(Debug, Clone)]
+pub struct Manager {
+    #[allow(dead_code)]
+    shared_config: AwsSdkConfig,
+    cli: Client,
+}
+
+impl Manager {
+    pub fn new(shared_config: &AwsSdkConfig) -> Self {
+        let cloned = shared_config.clone();
+        let cli = Client::new(shared_config);
+        Self {
+            shared_config: cloned,
+            cli,
+        }
+    }
+
+    /// Creates the on-demand lock table, if not exists,
+    /// and waits until the table becomes active.
+    pub async fn create_lock_table(&self, table_name: &str) -> Result<()> {
+        info!("creating lock table '{}'", table_name);
+        let ret = self
+            .cli
+            .create_table()
+            .table_name(table_name)
+            .attribute_definitions(
+                AttributeDefinition::builder()
+                    .attribute_name(LOCK_TABLE_KEY)
+                    .attribute_type(ScalarAttributeType::S)
+                    .build(),
+            )
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name(LOCK_TABLE_KEY)
+                    .key_type(KeyType::Hash)
+                    .build(),
+            )
+            .billing_mode(BillingMode::PayPerRequest)
+            .send()
+            .await;
+        if let Err(e) = ret {
+            if !format!("{:?}", e).contains("ResourceInUseException") {
+                return Err(API {
+                    message: format!("failed create_table {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+            info!("lock table '{}' already exists", table_name);
+        }
+
+        let start = Instant::now();
+        loop {
+            let ret = self
+                .cli
+                .describe_table()
+                .table_name(table_name)
+                .send()
+                .await;
+            let status = match ret {
+                Ok(v) => v.table().and_then(|t| t.table_status().cloned()),
+                Err(e) => {
+                    return Err(API {
+                        message: format!("failed describe_table {:?}", e),
+                        is_retryable: is_error_retryable(&e),
+                    });
+                }
+            };
+            if status == Some(TableStatus::Active) {
+                return Ok(());
+            }
+            if start.elapsed() > Duration::from_secs(300) {
+                return Err(Other {
+                    message: format!("lock table '{}' not active ({:?})", table_name, status),
+                    is_retryable: true,
+                });
+            }
+            info!("waiting for lock table '{}' ({:?})", table_name, status);
+            sleep(Duration::from_secs(5)).await;
+        }
+    }
+
+    /// Acquires the lock, unless held by other owner and not yet expired.
+    /// Re-acquiring by the same owner renews the lease.
+    /// Returns the retryable error with the current holder if held by other.
+    pub async fn acquire_lock(&self, table_name: &str, lock: &Lock) -> Result<()> {
+        info!(
+            "acquiring lock '{}' in '{}' for '{}'",
+            lock.lock_id, table_name, lock.owner
+        );
+        let ret = self
+            .cli
+            .put_item()
+            .table_name(table_name)
+            .set_item(Some(lock.to_item()))
+            .condition_expression(
+                "attribute_not_exists(LockID) OR ExpiresAt < :now OR #owner = :owner",
+            )
+            .expression_attribute_names("#owner", "Owner")
+            .expression_attribute_values(":now", AttributeValue::N(unix_now().to_string()))
+            .expression_attribute_values(":owner", AttributeValue::S(lock.owner.clone()))
+            .send()
+            .await;
+        match ret {
+            Ok(_) => {
+                info!("acquired lock '{}'", lock.lock_id);
+                Ok(())
+            }
+            Err(e) => {
+                if !format!("{:?}", e).contains("ConditionalCheckFailedException") {
+                    return Err(API {
+                        message: format!("failed put_item {:?}", e),
+                        is_retryable: is_error_retryable(&e),
+                    });
+                }
+                let holder = self.get_lock(table_name, &lock.lock_id).await?;
+                Err(Other {
+                    message: format!("lock '{}' is held by {:?}", lock.lock_id, holder),
+                    is_retryable: true,
+                })
+            }
+        }
+    }
+
+    /// Fetches the current lock, or "None" if not held.
+    pub async fn get_lock(&self, table_name: &str, lock_id: &str) -> Result<Option<Lock>> {
+        let ret = self
+            .cli
+            .get_item()
+            .table_name(table_name)
+            .key(LOCK_TABLE_KEY, AttributeValue::S(lock_id.to_string()))
+            .consistent_read(true)
+            .send()
+            .await;
+        match ret {
+            Ok(v) => Ok(v.item().and_then(Lock::from_item)),
+            Err(e) => Err(API {
+                message: format!("failed get_item {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            }),
+        }
+    }
+
+    /// Releases the lock only if held by the owner, so that the expired lock
+    /// taken over by other owner is never released.
+    pub async fn release_lock(&self, table_name: &str, lock: &Lock) -> Result<()> {
+        info!("releasing lock '{}' in '{}'", lock.lock_id, table_name);
+        let ret = self
+            .cli
+            .delete_item()
+            .table_name(table_name)
+            .key(LOCK_TABLE_KEY, AttributeValue::S(lock.lock_id.clone()))
+            .condition_expression("#owner = :owner")
+            .expression_attribute_names("#owner", "Owner")
+            .expression_attribute_values(":owner", AttributeValue::S(lock.owner.clone()))
+            .send()
+            .await;
+        match ret {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                if format!("{:?}", e).contains("ConditionalCheckFailedException") {
+                    warn!("lock '{}' no longer held by '{}'", lock.lock_id, lock.owner);
+                    return Ok(());
+                }
+                Err(API {
+                    message: format!("failed delete_item {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                })
+            }
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("unexpected None duration_since")
+        .as_secs()
+}
+
+#[inline]
+pub fn is_error_retryable<E>(e: &SdkError<E>) -> bool {
+    match e {
+        SdkError::TimeoutError(_) | SdkError::ResponseError { .. } => true,
+        SdkError::DispatchFailure(e) => e.is_timeout() || e.is_io(),
+        _ => false,
+    }
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- dynamodb::test_lock --exact --show-output
+#[test]
+fn test_lock() {
+    let lock = Lock::new(
+        "my-id",
+        "arn:aws:iam::123:user/a-abc",
+        "apply",
+        Duration::from_secs(600),
+    );
+    assert_eq!(lock.expires_at - lock.acquired_at, 600);
+    assert!(!lock.is_expired(lock.acquired_at));
+    assert!(lock.is_expired(lock.expires_at + 1));
+
+    let decoded = Lock::from_item(&lock.to_item()).unwrap();
+    assert_eq!(decoded, lock);
+
+    let mut item = lock.to_item();
+    item.remove("ExpiresAt");
+    assert!(Lock::from_item(&item).is_none());
+}
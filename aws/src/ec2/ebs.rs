@@ -0,0 +1,684 @@
+use std::{
+    cmp::Reverse,
+    io::{self, Error, ErrorKind},
+    time::Duration,
+};
+
+use aws_sdk_cloudformation::model::Parameter;
+use aws_sdk_ec2::model::{
+    Filter, ResourceType, Snapshot as Ec2Snapshot, SnapshotState, Tag, TagSpecification,
+    Volume as Ec2Volume, VolumeType,
+};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::time::{sleep, Instant};
+
+use crate::{
+    ec2::{is_error_retryable, Manager},
+    errors::{
+        Error::{Other, API},
+        Result,
+    },
+};
+use utils::rfc3339;
+
+/// Device name of the data volume, shared with the ASG launch template.
+/// Nitro instances expose it as "/dev/nvme1n1".
+pub const DEFAULT_DATA_DEVICE_NAME: &str = "/dev/xvdb";
+
+pub const VOLUME_STATE_AVAILABLE: &str = "available";
+pub const VOLUME_STATE_IN_USE: &str = "in-use";
+pub const ATTACHMENT_STATE_ATTACHED: &str = "attached";
+
+/// ref. https://aws.amazon.com/ebs/volume-types/
+pub const VOLUME_TYPES: [&str; 4] = ["gp2", "gp3", "io1", "io2"];
+
+/// Defines the EBS volume type, size, and performance.
+/// ref. https://docs.aws.amazon.com/AWSEC2/latest/APIReference/API_CreateVolume.html
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct VolumeSpec {
+    #[serde(default = "default_volume_type")]
+    pub volume_type: String,
+    #[serde(default = "default_size_in_gb")]
+    pub size_in_gb: u32,
+    /// Only for "gp3", "io1", and "io2".
+    #[serde(default = "default_iops")]
+    pub iops: u32,
+    /// Only for "gp3", in MiB/s.
+    #[serde(default = "default_throughput")]
+    pub throughput: u32,
+}
+
+fn default_volume_type() -> String {
+    String::from("gp3")
+}
+
+fn default_size_in_gb() -> u32 {
+    400
+}
+
+fn default_iops() -> u32 {
+    3000
+}
+
+fn default_throughput() -> u32 {
+    500
+}
+
+impl Default for VolumeSpec {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl VolumeSpec {
+    pub fn default() -> Self {
+        Self {
+            volume_type: default_volume_type(),
+            size_in_gb: default_size_in_gb(),
+            iops: default_iops(),
+            throughput: default_throughput(),
+        }
+    }
+
+    fn has_iops(&self) -> bool {
+        self.volume_type != "gp2"
+    }
+
+    fn has_throughput(&self) -> bool {
+        self.volume_type == "gp3"
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        if !VOLUME_TYPES.contains(&self.volume_type.as_str()) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "unknown 'volume_type' '{}' (expected one of {:?})",
+                    self.volume_type, VOLUME_TYPES
+                ),
+            ));
+        }
+        // same range as the ASG template "VolumeSize" parameter
+        if !(40..=1024).contains(&self.size_in_gb) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("'size_in_gb' {} not in [40, 1024]", self.size_in_gb),
+            ));
+        }
+        if self.has_throughput() {
+            if !(125..=1000).contains(&self.throughput) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("'throughput' {} not in [125, 1000]", self.throughput),
+                ));
+            }
+            // "InvalidParameterValue - Throughput (MiBps) to iops ratio of 0.333333 is too high; maximum is 0.250000 MiBps per iops."
+            if self.throughput * 4 > self.iops {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "'throughput' {} too high for 'iops' {} (at most 0.25 MiB/s per iops)",
+                        self.throughput, self.iops
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the ASG CloudFormation template parameters.
+    pub fn to_parameters(&self) -> Vec<Parameter> {
+        let param = |k: &str, v: String| {
+            Parameter::builder()
+                .parameter_key(k)
+                .parameter_value(v)
+                .build()
+        };
+        vec![
+            param("VolumeType", self.volume_type.clone()),
+            param("VolumeSize", self.size_in_gb.to_string()),
+            param("VolumeIops", self.iops.to_string()),
+            param("VolumeThroughput", self.throughput.to_string()),
+        ]
+    }
+}
+
+/// Represents the EBS volume.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Volume {
+    pub volume_id: String,
+    pub state: String,
+    pub availability_zone: String,
+    pub volume_type: String,
+    pub size_in_gb: u32,
+    /// Snapshot that the volume was restored from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attached_instance_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment_state: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+}
+
+impl Volume {
+    pub fn new(v: &Ec2Volume) -> Self {
+        let attachment = v.attachments().unwrap_or_default().first();
+        Self {
+            volume_id: v.volume_id().unwrap_or_default().to_string(),
+            state: v
+                .state()
+                .map(|s| s.as_str().to_string())
+                .unwrap_or_default(),
+            availability_zone: v.availability_zone().unwrap_or_default().to_string(),
+            volume_type: v
+                .volume_type()
+                .map(|t| t.as_str().to_string())
+                .unwrap_or_default(),
+            size_in_gb: v.size().unwrap_or(0) as u32,
+            snapshot_id: v.snapshot_id().filter(|s| !s.is_empty()).map(String::from),
+            attached_instance_id: attachment.and_then(|a| a.instance_id().map(String::from)),
+            attachment_state: attachment.and_then(|a| a.state().map(|s| s.as_str().to_string())),
+            device: attachment.and_then(|a| a.device().map(String::from)),
+        }
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.state == VOLUME_STATE_IN_USE
+            && self.attachment_state.as_deref() == Some(ATTACHMENT_STATE_ATTACHED)
+    }
+}
+
+/// Represents the EBS snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Snapshot {
+    pub snapshot_id: String,
+    pub volume_id: String,
+    pub state: String,
+    pub size_in_gb: u32,
+    /// Represents the data format in RFC3339.
+    #[serde(with = "rfc3339::serde_format")]
+    pub started_at_utc: DateTime<Utc>,
+}
+
+impl Snapshot {
+    pub fn new(s: &Ec2Snapshot) -> Self {
+        let secs = s.start_time().map(|t| t.secs()).unwrap_or(0);
+        Self {
+            snapshot_id: s.snapshot_id().unwrap_or_default().to_string(),
+            volume_id: s.volume_id().unwrap_or_default().to_string(),
+            state: s
+                .state()
+                .map(|v| v.as_str().to_string())
+                .unwrap_or_default(),
+            size_in_gb: s.volume_size().unwrap_or(0) as u32,
+            started_at_utc: DateTime::<Utc>::from_timestamp(secs, 0).unwrap_or_default(),
+        }
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.state == SnapshotState::Completed.as_str()
+    }
+}
+
+/// Returns the most recent completed snapshot.
+pub fn latest_completed(snapshots: &[Snapshot]) -> Option<&Snapshot> {
+    snapshots
+        .iter()
+        .filter(|s| s.is_completed())
+        .max_by_key(|s| s.started_at_utc)
+}
+
+/// Returns the completed snapshots beyond the "retention" most recent ones.
+/// Pending snapshots are never returned, so that the in-flight snapshot
+/// does not count towards the retention.
+pub fn expired(snapshots: &[Snapshot], retention: usize) -> Vec<&Snapshot> {
+    let mut completed: Vec<&Snapshot> = snapshots.iter().filter(|s| s.is_completed()).collect();
+    completed.sort_by_key(|s| Reverse(s.started_at_utc));
+    completed.into_iter().skip(retention).collect()
+}
+
+/// Returns the filters that match all the tags.
+pub fn tag_filters(tags: &[(&str, &str)]) -> Vec<Filter> {
+    tags.iter()
+        .map(|(k, v)| {
+            Filter::builder()
+                .name(format!("tag:{}", k))
+                .values(v.to_string())
+                .build()
+        })
+        .collect()
+}
+
+pub(crate) fn tag_specification(
+    resource_type: ResourceType,
+    tags: &[(&str, &str)],
+) -> TagSpecification {
+    let mut spec = TagSpecification::builder().resource_type(resource_type);
+    for (k, v) in tags {
+        spec = spec.tags(Tag::builder().key(*k).value(*v).build());
+    }
+    spec.build()
+}
+
+impl Manager {
+    /// Creates an encrypted EBS volume, optionally restored from the snapshot,
+    /// and waits until it is available.
+    pub async fn create_volume(
+        &self,
+        spec: &VolumeSpec,
+        availability_zone: &str,
+        snapshot_id: Option<&str>,
+        tags: &[(&str, &str)],
+    ) -> Result<Volume> {
+        info!(
+            "creating {} volume ({} GiB) in '{}' (snapshot {:?})",
+            spec.volume_type, spec.size_in_gb, availability_zone, snapshot_id
+        );
+        let mut req = self
+            .cli
+            .create_volume()
+            .availability_zone(availability_zone)
+            .volume_type(VolumeType::from(spec.volume_type.as_str()))
+            .size(spec.size_in_gb as i32)
+            .encrypted(true)
+            .set_snapshot_id(snapshot_id.map(String::from))
+            .tag_specifications(tag_specification(ResourceType::Volume, tags));
+        if spec.has_iops() {
+            req = req.iops(spec.iops as i32);
+        }
+        if spec.has_throughput() {
+            req = req.throughput(spec.throughput as i32);
+        }
+        let resp = match req.send().await {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed create_volume {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+        let volume_id = resp.volume_id().unwrap_or_default().to_string();
+        info!("created volume '{}'", volume_id);
+
+        self.poll_volume(
+            &volume_id,
+            VOLUME_STATE_AVAILABLE,
+            Duration::from_secs(300),
+            Duration::from_secs(10),
+        )
+        .await
+    }
+
+    /// Describes the volumes that match all the filters.
+    pub async fn describe_volumes(&self, filters: Vec<Filter>) -> Result<Vec<Volume>> {
+        let ret = self
+            .cli
+            .describe_volumes()
+            .set_filters(Some(filters))
+            .send()
+            .await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed describe_volumes {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+        Ok(resp
+            .volumes()
+            .unwrap_or_default()
+            .iter()
+            .map(Volume::new)
+            .collect())
+    }
+
+    /// Returns the volume attached to the instance at the device, if any.
+    pub async fn find_attached_volume(
+        &self,
+        instance_id: &str,
+        device: &str,
+    ) -> Result<Option<Volume>> {
+        let filters = vec![
+            Filter::builder()
+                .name("attachment.instance-id")
+                .values(instance_id)
+                .build(),
+            Filter::builder()
+                .name("attachment.device")
+                .values(device)
+                .build(),
+        ];
+        let volumes = self.describe_volumes(filters).await?;
+        Ok(volumes.into_iter().next())
+    }
+
+    /// Lists the unattached volumes with all the tags (in the availability zone, if any),
+    /// left behind by the terminated instances.
+    pub async fn list_available_volumes(
+        &self,
+        availability_zone: Option<&str>,
+        tags: &[(&str, &str)],
+    ) -> Result<Vec<Volume>> {
+        let mut filters = tag_filters(tags);
+        filters.push(
+            Filter::builder()
+                .name("status")
+                .values(VOLUME_STATE_AVAILABLE)
+                .build(),
+        );
+        if let Some(az) = availability_zone {
+            filters.push(
+                Filter::builder()
+                    .name("availability-zone")
+                    .values(az)
+                    .build(),
+            );
+        }
+        self.describe_volumes(filters).await
+    }
+
+    /// Attaches the volume to the instance and waits until it is attached.
+    pub async fn attach_volume(
+        &self,
+        volume_id: &str,
+        instance_id: &str,
+        device: &str,
+    ) -> Result<Volume> {
+        info!(
+            "attaching volume '{}' to '{}' at '{}'",
+            volume_id, instance_id, device
+        );
+        let ret = self
+            .cli
+            .attach_volume()
+            .volume_id(volume_id)
+            .instance_id(instance_id)
+            .device(device)
+            .send()
+            .await;
+        if let Err(e) = ret {
+            return Err(API {
+                message: format!("failed attach_volume {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            });
+        }
+
+        self.poll_volume(
+            volume_id,
+            VOLUME_STATE_IN_USE,
+            Duration::from_secs(300),
+            Duration::from_secs(5),
+        )
+        .await
+    }
+
+    /// Detaches the volume and waits until it is available.
+    pub async fn detach_volume(&self, volume_id: &str) -> Result<Volume> {
+        info!("detaching volume '{}'", volume_id);
+        let ret = self.cli.detach_volume().volume_id(volume_id).send().await;
+        if let Err(e) = ret {
+            return Err(API {
+                message: format!("failed detach_volume {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            });
+        }
+
+        self.poll_volume(
+            volume_id,
+            VOLUME_STATE_AVAILABLE,
+            Duration::from_secs(300),
+            Duration::from_secs(5),
+        )
+        .await
+    }
+
+    /// Deletes the volume.
+    pub async fn delete_volume(&self, volume_id: &str) -> Result<()> {
+        info!("deleting volume '{}'", volume_id);
+        let ret = self.cli.delete_volume().volume_id(volume_id).send().await;
+        match ret {
+            Ok(_) => Ok(()),
+            Err(e) => Err(API {
+                message: format!("failed delete_volume {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            }),
+        }
+    }
+
+    /// Polls the volume until it reaches the desired state.
+    /// For "in-use", also waits for the attachment to complete.
+    pub async fn poll_volume(
+        &self,
+        volume_id: &str,
+        desired_state: &str,
+        timeout: Duration,
+        interval: Duration,
+    ) -> Result<Volume> {
+        info!(
+            "polling volume '{}' with desired state '{}' for timeout {:?} and interval {:?}",
+            volume_id, desired_state, timeout, interval,
+        );
+
+        let start = Instant::now();
+        loop {
+            let elapsed = start.elapsed();
+            if elapsed.gt(&timeout) {
+                break;
+            }
+
+            let filters = vec![Filter::builder()
+                .name("volume-id")
+                .values(volume_id)
+                .build()];
+            let volume = match self.describe_volumes(filters).await?.into_iter().next() {
+                Some(v) => v,
+                None => {
+                    return Err(Other {
+                        message: format!("volume '{}' not found", volume_id),
+                        is_retryable: false,
+                    });
+                }
+            };
+            info!(
+                "poll (current '{}', attachment {:?}, elapsed {:?})",
+                volume.state, volume.attachment_state, elapsed
+            );
+            if volume.state == "error" {
+                return Err(Other {
+                    message: format!("volume '{}' in error state", volume_id),
+                    is_retryable: false,
+                });
+            }
+            let done = if desired_state == VOLUME_STATE_IN_USE {
+                volume.is_attached()
+            } else {
+                volume.state == desired_state
+            };
+            if done {
+                return Ok(volume);
+            }
+
+            sleep(interval).await;
+        }
+
+        Err(Other {
+            message: format!("failed to poll volume '{}' in time", volume_id),
+            is_retryable: true,
+        })
+    }
+
+    /// Starts the snapshot of the volume.
+    /// The snapshot is point-in-time, so the volume can be used right away
+    /// while the snapshot is pending.
+    pub async fn create_snapshot(
+        &self,
+        volume_id: &str,
+        description: &str,
+        tags: &[(&str, &str)],
+    ) -> Result<Snapshot> {
+        info!("creating snapshot of volume '{}'", volume_id);
+        let ret = self
+            .cli
+            .create_snapshot()
+            .volume_id(volume_id)
+            .description(description)
+            .tag_specifications(tag_specification(ResourceType::Snapshot, tags))
+            .send()
+            .await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed create_snapshot {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+
+        let snapshot = Ec2Snapshot::builder()
+            .set_snapshot_id(resp.snapshot_id().map(String::from))
+            .set_volume_id(resp.volume_id().map(String::from))
+            .set_state(resp.state().cloned())
+            .set_volume_size(resp.volume_size())
+            .set_start_time(resp.start_time().cloned())
+            .build();
+        let snapshot = Snapshot::new(&snapshot);
+        info!("created snapshot '{}'", snapshot.snapshot_id);
+        Ok(snapshot)
+    }
+
+    /// Lists the snapshots owned by the account with all the tags.
+    pub async fn list_snapshots(&self, tags: &[(&str, &str)]) -> Result<Vec<Snapshot>> {
+        let ret = self
+            .cli
+            .describe_snapshots()
+            .owner_ids("self")
+            .set_filters(Some(tag_filters(tags)))
+            .send()
+            .await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed describe_snapshots {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+        Ok(resp
+            .snapshots()
+            .unwrap_or_default()
+            .iter()
+            .map(Snapshot::new)
+            .collect())
+    }
+
+    /// Deletes the snapshot.
+    pub async fn delete_snapshot(&self, snapshot_id: &str) -> Result<()> {
+        info!("deleting snapshot '{}'", snapshot_id);
+        let ret = self
+            .cli
+            .delete_snapshot()
+            .snapshot_id(snapshot_id)
+            .send()
+            .await;
+        match ret {
+            Ok(_) => Ok(()),
+            Err(e) => Err(API {
+                message: format!("failed delete_snapshot {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            }),
+        }
+    }
+
+    /// Deletes the completed snapshots with all the tags,
+    /// beyond the "retention" most recent ones.
+    /// Returns the number of deleted snapshots.
+    pub async fn prune_snapshots(&self, tags: &[(&str, &str)], retention: usize) -> Result<usize> {
+        let snapshots = self.list_snapshots(tags).await?;
+        let mut deleted = 0;
+        for s in expired(&snapshots, retention) {
+            match self.delete_snapshot(&s.snapshot_id).await {
+                Ok(_) => deleted += 1,
+                Err(e) => warn!(
+                    "failed to delete snapshot '{}' ({})",
+                    s.snapshot_id,
+                    e.message()
+                ),
+            }
+        }
+        Ok(deleted)
+    }
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- ec2::ebs::test_volume_spec --exact --show-output
+#[test]
+fn test_volume_spec() {
+    let spec = VolumeSpec::default();
+    spec.validate().unwrap();
+
+    let spec: VolumeSpec = serde_json::from_str(r#"{"volume_type":"io2","iops":16000}"#).unwrap();
+    assert_eq!(spec.size_in_gb, 400);
+    spec.validate().unwrap();
+    let params: Vec<(String, String)> = spec
+        .to_parameters()
+        .into_iter()
+        .map(|p| (p.parameter_key.unwrap(), p.parameter_value.unwrap()))
+        .collect();
+    assert_eq!(
+        params[..2],
+        [
+            (String::from("VolumeType"), String::from("io2")),
+            (String::from("VolumeSize"), String::from("400")),
+        ]
+    );
+
+    let mut invalid = VolumeSpec::default();
+    invalid.volume_type = String::from("st1");
+    assert!(invalid.validate().is_err());
+    let mut invalid = VolumeSpec::default();
+    invalid.size_in_gb = 2048;
+    assert!(invalid.validate().is_err());
+    let mut invalid = VolumeSpec::default();
+    invalid.throughput = 1000;
+    assert!(invalid.validate().is_err());
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- ec2::ebs::test_snapshot_retention --exact --show-output
+#[test]
+fn test_snapshot_retention() {
+    let snapshot = |id: &str, state: &str, secs: i64| Snapshot {
+        snapshot_id: id.to_string(),
+        volume_id: String::from("vol-1"),
+        state: state.to_string(),
+        size_in_gb: 400,
+        started_at_utc: DateTime::<Utc>::from_timestamp(secs, 0).unwrap(),
+    };
+    let snapshots = vec![
+        snapshot("snap-1", "completed", 100),
+        snapshot("snap-4", "pending", 400),
+        snapshot("snap-3", "completed", 300),
+        snapshot("snap-2", "completed", 200),
+        snapshot("snap-0", "error", 50),
+    ];
+
+    assert_eq!(latest_completed(&snapshots).unwrap().snapshot_id, "snap-3");
+    assert!(latest_completed(&snapshots[1..2]).is_none());
+
+    let ids = |v: Vec<&Snapshot>| -> Vec<String> {
+        v.into_iter().map(|s| s.snapshot_id.clone()).collect()
+    };
+    assert_eq!(ids(expired(&snapshots, 1)), vec!["snap-2", "snap-1"]);
+    assert_eq!(ids(expired(&snapshots, 2)), vec!["snap-1"]);
+    assert!(expired(&snapshots, 3).is_empty());
+}
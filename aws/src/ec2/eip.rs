@@ -0,0 +1,207 @@
+use aws_sdk_ec2::model::{Address, DomainType, ResourceType};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ec2::{
+        ebs::{tag_filters, tag_specification},
+        is_error_retryable, Manager,
+    },
+    errors::{Error::API, Result},
+};
+
+/// Represents the Elastic IP address.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Eip {
+    pub allocation_id: String,
+    pub public_ip: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub association_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance_id: Option<String>,
+    /// Value of the "ANCHOR_INDEX" tag.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor_index: Option<u32>,
+}
+
+impl Eip {
+    pub fn new(a: &Address) -> Self {
+        let anchor_index = a
+            .tags()
+            .unwrap_or_default()
+            .iter()
+            .find(|t| t.key() == Some(ANCHOR_INDEX_TAG))
+            .and_then(|t| t.value())
+            .and_then(|v| v.parse::<u32>().ok());
+        Self {
+            allocation_id: a.allocation_id().unwrap_or_default().to_string(),
+            public_ip: a.public_ip().unwrap_or_default().to_string(),
+            association_id: a.association_id().map(String::from),
+            instance_id: a.instance_id().filter(|v| !v.is_empty()).map(String::from),
+            anchor_index,
+        }
+    }
+
+    pub fn is_associated(&self) -> bool {
+        self.association_id.is_some()
+    }
+}
+
+/// Tag of the anchor node slot that the Elastic IP is reserved for,
+/// so that the replacement instance takes over the same address and DNS name.
+pub const ANCHOR_INDEX_TAG: &str = "ANCHOR_INDEX";
+
+impl Manager {
+    /// Allocates the VPC Elastic IP with the tags.
+    pub async fn allocate_eip(&self, tags: &[(&str, &str)]) -> Result<Eip> {
+        info!("allocating Elastic IP with tags {:?}", tags);
+        let ret = self
+            .cli
+            .allocate_address()
+            .domain(DomainType::Vpc)
+            .tag_specifications(tag_specification(ResourceType::ElasticIp, tags))
+            .send()
+            .await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed allocate_address {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+        let eip = Eip {
+            allocation_id: resp.allocation_id().unwrap_or_default().to_string(),
+            public_ip: resp.public_ip().unwrap_or_default().to_string(),
+            association_id: None,
+            instance_id: None,
+            anchor_index: tags
+                .iter()
+                .find(|(k, _)| *k == ANCHOR_INDEX_TAG)
+                .and_then(|(_, v)| v.parse::<u32>().ok()),
+        };
+        info!(
+            "allocated Elastic IP {} ('{}')",
+            eip.public_ip, eip.allocation_id
+        );
+        Ok(eip)
+    }
+
+    /// Lists the Elastic IPs with all the tags.
+    pub async fn list_eips(&self, tags: &[(&str, &str)]) -> Result<Vec<Eip>> {
+        let ret = self
+            .cli
+            .describe_addresses()
+            .set_filters(Some(tag_filters(tags)))
+            .send()
+            .await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed describe_addresses {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+        Ok(resp
+            .addresses()
+            .unwrap_or_default()
+            .iter()
+            .map(Eip::new)
+            .collect())
+    }
+
+    /// Associates the Elastic IP with the instance, which replaces
+    /// the instance public IP. Fails if the Elastic IP is already associated
+    /// with another instance, so that two instances never claim the same one.
+    pub async fn associate_eip(&self, allocation_id: &str, instance_id: &str) -> Result<String> {
+        info!(
+            "associating Elastic IP '{}' with '{}'",
+            allocation_id, instance_id
+        );
+        let ret = self
+            .cli
+            .associate_address()
+            .allocation_id(allocation_id)
+            .instance_id(instance_id)
+            .allow_reassociation(false)
+            .send()
+            .await;
+        match ret {
+            Ok(resp) => Ok(resp.association_id().unwrap_or_default().to_string()),
+            Err(e) => Err(API {
+                message: format!("failed associate_address {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            }),
+        }
+    }
+
+    /// Releases the Elastic IP, which must be disassociated
+    /// (e.g., the instance is terminated).
+    pub async fn release_eip(&self, allocation_id: &str) -> Result<()> {
+        info!("releasing Elastic IP '{}'", allocation_id);
+        let ret = self
+            .cli
+            .release_address()
+            .allocation_id(allocation_id)
+            .send()
+            .await;
+        match ret {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                if format!("{:?}", e).contains("InvalidAllocationID.NotFound") {
+                    warn!("Elastic IP '{}' already released", allocation_id);
+                    return Ok(());
+                }
+                Err(API {
+                    message: format!("failed release_address {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                })
+            }
+        }
+    }
+}
+
+/// Returns the Elastic IP for the instance: the one already associated with
+/// the instance (e.g., "avalanched" restart), otherwise the unassociated one
+/// with the lowest anchor index (e.g., left by the terminated instance).
+pub fn select_for_instance<'a>(eips: &'a [Eip], instance_id: &str) -> Option<&'a Eip> {
+    if let Some(eip) = eips
+        .iter()
+        .find(|e| e.instance_id.as_deref() == Some(instance_id))
+    {
+        return Some(eip);
+    }
+    eips.iter()
+        .filter(|e| !e.is_associated())
+        .min_by_key(|e| e.anchor_index.unwrap_or(u32::MAX))
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- ec2::eip::test_select_for_instance --exact --show-output
+#[test]
+fn test_select_for_instance() {
+    let eip = |allocation_id: &str, instance_id: Option<&str>, anchor_index: u32| Eip {
+        allocation_id: allocation_id.to_string(),
+        public_ip: String::from("1.2.3.4"),
+        association_id: instance_id.map(|_| String::from("eipassoc-1")),
+        instance_id: instance_id.map(String::from),
+        anchor_index: Some(anchor_index),
+    };
+    let eips = vec![
+        eip("eipalloc-1", Some("i-1"), 1),
+        eip("eipalloc-3", None, 3),
+        eip("eipalloc-2", None, 2),
+    ];
+    assert_eq!(
+        select_for_instance(&eips, "i-1").unwrap().allocation_id,
+        "eipalloc-1"
+    );
+    assert_eq!(
+        select_for_instance(&eips, "i-2").unwrap().allocation_id,
+        "eipalloc-2"
+    );
+    assert!(select_for_instance(&eips[..1], "i-2").is_none());
+}
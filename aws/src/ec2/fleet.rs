@@ -0,0 +1,207 @@
+use std::io::{self, Error, ErrorKind};
+
+use aws_sdk_cloudformation::model::Parameter;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ec2::{Droplet, Manager},
+    errors::Result,
+};
+
+pub const LIFECYCLE_SPOT: &str = "spot";
+pub const LIFECYCLE_ON_DEMAND: &str = "on-demand";
+
+/// ref. https://docs.aws.amazon.com/autoscaling/ec2/APIReference/API_InstancesDistribution.html
+pub const SPOT_ALLOCATION_STRATEGIES: [&str; 4] = [
+    "lowest-price",
+    "capacity-optimized",
+    "capacity-optimized-prioritized",
+    "price-capacity-optimized",
+];
+
+/// Defines the spot and on-demand mix of the Auto Scaling Group,
+/// passed as the "MixedInstancesPolicy.InstancesDistribution" parameters
+/// of the ASG CloudFormation template.
+/// The default is all on-demand.
+/// ref. https://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/aws-properties-autoscaling-autoscalinggroup-instancesdistribution.html
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct PurchaseOptions {
+    /// Number of instances that are always on-demand.
+    #[serde(default)]
+    pub on_demand_base_capacity: u32,
+    /// Percentage of on-demand instances beyond the base capacity (0 ~ 100),
+    /// the rest is spot.
+    #[serde(default = "default_on_demand_percentage")]
+    pub on_demand_percentage_above_base_capacity: u32,
+    #[serde(default = "default_spot_allocation_strategy")]
+    pub spot_allocation_strategy: String,
+    /// Launches a replacement before the spot instance is interrupted,
+    /// on the EC2 rebalance recommendation.
+    /// ref. https://docs.aws.amazon.com/autoscaling/ec2/userguide/ec2-auto-scaling-capacity-rebalancing.html
+    #[serde(default)]
+    pub capacity_rebalance: bool,
+}
+
+fn default_on_demand_percentage() -> u32 {
+    100
+}
+
+fn default_spot_allocation_strategy() -> String {
+    String::from("capacity-optimized")
+}
+
+impl Default for PurchaseOptions {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl PurchaseOptions {
+    pub fn default() -> Self {
+        Self {
+            on_demand_base_capacity: 0,
+            on_demand_percentage_above_base_capacity: default_on_demand_percentage(),
+            spot_allocation_strategy: default_spot_allocation_strategy(),
+            capacity_rebalance: false,
+        }
+    }
+
+    /// Returns true if no instance can be spot.
+    pub fn is_all_on_demand(&self) -> bool {
+        self.on_demand_percentage_above_base_capacity == 100
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        if self.on_demand_percentage_above_base_capacity > 100 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'on_demand_percentage_above_base_capacity' {} >100",
+                    self.on_demand_percentage_above_base_capacity
+                ),
+            ));
+        }
+        if !SPOT_ALLOCATION_STRATEGIES.contains(&self.spot_allocation_strategy.as_str()) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "unknown 'spot_allocation_strategy' '{}' (expected one of {:?})",
+                    self.spot_allocation_strategy, SPOT_ALLOCATION_STRATEGIES
+                ),
+            ));
+        }
+        if self.capacity_rebalance && self.is_all_on_demand() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'capacity_rebalance' requires spot instances ('on_demand_percentage_above_base_capacity' <100)",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the ASG CloudFormation template parameters.
+    pub fn to_parameters(&self) -> Vec<Parameter> {
+        let param = |k: &str, v: String| {
+            Parameter::builder()
+                .parameter_key(k)
+                .parameter_value(v)
+                .build()
+        };
+        vec![
+            param(
+                "OnDemandBaseCapacity",
+                self.on_demand_base_capacity.to_string(),
+            ),
+            param(
+                "OnDemandPercentageAboveBaseCapacity",
+                self.on_demand_percentage_above_base_capacity.to_string(),
+            ),
+            param(
+                "SpotAllocationStrategy",
+                self.spot_allocation_strategy.clone(),
+            ),
+            param("CapacityRebalance", self.capacity_rebalance.to_string()),
+        ]
+    }
+}
+
+/// Represents the instances of an Auto Scaling Group.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Fleet {
+    pub asg_name: String,
+    pub instances: Vec<Droplet>,
+}
+
+impl Fleet {
+    pub fn spot_instances(&self) -> usize {
+        self.instances
+            .iter()
+            .filter(|d| d.instance_lifecycle == LIFECYCLE_SPOT)
+            .count()
+    }
+
+    pub fn on_demand_instances(&self) -> usize {
+        self.instances.len() - self.spot_instances()
+    }
+}
+
+impl Manager {
+    /// Describes the instances of the Auto Scaling Group
+    /// with their lifecycle (spot or on-demand) and node role tags.
+    pub async fn describe_fleet(&self, asg_name: &str) -> Result<Fleet> {
+        let instances = self.list_asg(asg_name).await?;
+        Ok(Fleet {
+            asg_name: asg_name.to_string(),
+            instances,
+        })
+    }
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- ec2::fleet::test_purchase_options --exact --show-output
+#[test]
+fn test_purchase_options() {
+    let opts = PurchaseOptions::default();
+    assert!(opts.is_all_on_demand());
+    opts.validate().unwrap();
+
+    let opts: PurchaseOptions = serde_json::from_str(
+        r#"{"on_demand_base_capacity":1,"on_demand_percentage_above_base_capacity":25,"capacity_rebalance":true}"#,
+    )
+    .unwrap();
+    assert!(!opts.is_all_on_demand());
+    assert_eq!(opts.spot_allocation_strategy, "capacity-optimized");
+    opts.validate().unwrap();
+
+    let params: Vec<(String, String)> = opts
+        .to_parameters()
+        .into_iter()
+        .map(|p| (p.parameter_key.unwrap(), p.parameter_value.unwrap()))
+        .collect();
+    assert_eq!(
+        params,
+        vec![
+            (String::from("OnDemandBaseCapacity"), String::from("1")),
+            (
+                String::from("OnDemandPercentageAboveBaseCapacity"),
+                String::from("25")
+            ),
+            (
+                String::from("SpotAllocationStrategy"),
+                String::from("capacity-optimized")
+            ),
+            (String::from("CapacityRebalance"), String::from("true")),
+        ]
+    );
+
+    let mut invalid = opts.clone();
+    invalid.on_demand_percentage_above_base_capacity = 101;
+    assert!(invalid.validate().is_err());
+    let mut invalid = opts.clone();
+    invalid.spot_allocation_strategy = String::from("cheapest");
+    assert!(invalid.validate().is_err());
+    let mut invalid = PurchaseOptions::default();
+    invalid.capacity_rebalance = true;
+    assert!(invalid.validate().is_err());
+}
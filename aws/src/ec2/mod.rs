@@ -1,7 +1,18 @@
-use std::{fs::File, io::prelude::*, path::Path, sync::Arc, time::Duration};
+pub mod ebs;
+pub mod eip;
+pub mod fleet;
+pub mod sg;
+
+use std::{
+    fs::File,
+    io::{prelude::*, ErrorKind},
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
 
 use aws_sdk_ec2::{
-    error::DeleteKeyPairError,
+    error::{AuthorizeSecurityGroupIngressError, DeleteKeyPairError},
     model::{Filter, Instance, InstanceState, InstanceStateName, Tag},
     types::SdkError,
     Client,
@@ -105,6 +116,43 @@ impl Manager {
         Ok(())
     }
 
+    /// Opens the TCP port of the security group to the IP ranges.
+    /// The ranges that are already allowed are skipped.
+    pub async fn authorize_ingress_tcp(
+        &self,
+        security_group_id: &str,
+        port: u32,
+        cidr_ips: &[String],
+    ) -> Result<()> {
+        info!(
+            "authorizing TCP port {} ingress of '{}' from {} range(s)",
+            port,
+            security_group_id,
+            cidr_ips.len()
+        );
+        for cidr_ip in cidr_ips.iter() {
+            let permission = sg::tcp_permission(port, cidr_ip);
+            let ret = self
+                .cli
+                .authorize_security_group_ingress()
+                .group_id(security_group_id)
+                .ip_permissions(permission)
+                .send()
+                .await;
+            if let Err(e) = ret {
+                if !is_error_duplicate_permission(&e) {
+                    return Err(API {
+                        message: format!("failed authorize_security_group_ingress {:?}", e),
+                        is_retryable: is_error_retryable(&e),
+                    });
+                }
+                info!("'{}' already allowed in '{}'", cidr_ip, security_group_id);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Fetches all tags for the specified instance.
     ///
     /// "If a single piece of data must be accessible from more than one task
@@ -174,16 +222,54 @@ impl Manager {
         Ok(tags)
     }
 
+    /// Creates or overwrites the tags of the resource (e.g., instance ID).
+    pub async fn create_tags(&self, resource_id: &str, tags: &[(&str, &str)]) -> Result<()> {
+        info!("creating {} tag(s) for '{}'", tags.len(), resource_id);
+        let tags: Vec<Tag> = tags
+            .iter()
+            .map(|(k, v)| Tag::builder().key(*k).value(*v).build())
+            .collect();
+        let ret = self
+            .cli
+            .create_tags()
+            .resources(resource_id)
+            .set_tags(Some(tags))
+            .send()
+            .await;
+        match ret {
+            Ok(_) => Ok(()),
+            Err(e) => Err(API {
+                message: format!("failed create_tags {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            }),
+        }
+    }
+
     /// Lists instances by the Auto Scaling Groups name.
     pub async fn list_asg(&self, asg_name: &str) -> Result<Vec<Droplet>> {
         let filter = Filter::builder()
             .set_name(Some(String::from("tag:aws:autoscaling:groupName")))
             .set_values(Some(vec![String::from(asg_name)]))
             .build();
+        self.describe_instances(Some(vec![filter]), None).await
+    }
+
+    /// Lists instances by the instance IDs.
+    pub async fn list_instances(&self, instance_ids: &[String]) -> Result<Vec<Droplet>> {
+        self.describe_instances(None, Some(instance_ids.to_vec()))
+            .await
+    }
+
+    async fn describe_instances(
+        &self,
+        filters: Option<Vec<Filter>>,
+        instance_ids: Option<Vec<String>>,
+    ) -> Result<Vec<Droplet>> {
         let resp = match self
             .cli
             .describe_instances()
-            .set_filters(Some(vec![filter]))
+            .set_filters(filters)
+            .set_instance_ids(instance_ids)
             .send()
             .await
         {
@@ -232,6 +318,18 @@ pub struct Droplet {
     pub availability_zone: String,
     pub public_hostname: String,
     pub public_ipv4: String,
+    /// "spot" or "on-demand".
+    #[serde(default)]
+    pub instance_lifecycle: String,
+    /// "NODE_KIND" tag set by the ASG (e.g., "anchor", "non-anchor").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_kind: Option<String>,
+    /// "NETWORK_ID" tag set by the ASG.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_id: Option<u32>,
+    /// Security groups of the instance (e.g., swapped by the chaos partitions).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub security_group_ids: Vec<String>,
 }
 
 impl Droplet {
@@ -271,6 +369,29 @@ impl Droplet {
             .to_owned()
             .unwrap_or_else(|| String::from(""));
 
+        // "DescribeInstances" omits the lifecycle for on-demand instances
+        let instance_lifecycle = match inst.instance_lifecycle() {
+            Some(v) => v.as_str().to_string(),
+            None => String::from(fleet::LIFECYCLE_ON_DEMAND),
+        };
+
+        let tag_value = |k: &str| -> Option<String> {
+            inst.tags()
+                .unwrap_or_default()
+                .iter()
+                .find(|t| t.key() == Some(k))
+                .and_then(|t| t.value().map(String::from))
+        };
+        let node_kind = tag_value("NODE_KIND");
+        let network_id = tag_value("NETWORK_ID").and_then(|v| v.parse::<u32>().ok());
+
+        let security_group_ids = inst
+            .security_groups()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|g| g.group_id().map(String::from))
+            .collect();
+
         Self {
             instance_id,
             launched_at_utc,
@@ -279,6 +400,10 @@ impl Droplet {
             availability_zone,
             public_hostname,
             public_ipv4,
+            instance_lifecycle,
+            node_kind,
+            network_id,
+            security_group_ids,
         }
     }
 }
@@ -304,6 +429,17 @@ fn is_error_delete_key_pair_does_not_exist(e: &SdkError<DeleteKeyPairError>) ->
     }
 }
 
+#[inline]
+fn is_error_duplicate_permission(e: &SdkError<AuthorizeSecurityGroupIngressError>) -> bool {
+    match e {
+        SdkError::ServiceError { err, .. } => {
+            let msg = format!("{:?}", err);
+            msg.contains("InvalidPermission.Duplicate")
+        }
+        _ => false,
+    }
+}
+
 /// Fetches the instance ID on the host EC2 machine.
 pub async fn fetch_instance_id() -> Result<String> {
     fetch_metadata("instance-id").await
@@ -319,6 +455,30 @@ pub async fn fetch_public_ipv4() -> Result<String> {
     fetch_metadata("public-ipv4").await
 }
 
+/// Fetches the public IPv4 address of the host EC2 machine,
+/// "None" if the instance has no public IPv4 (e.g., private subnet).
+pub async fn fetch_optional_public_ipv4() -> Result<Option<String>> {
+    fetch_optional_metadata("public-ipv4").await
+}
+
+/// Fetches the IPv6 address of the primary network interface of the host EC2 machine,
+/// "None" if the interface has no IPv6 address (e.g., IPv4-only subnet).
+/// The IPv6 addresses on EC2 are globally unique, so there is no separate public one.
+/// ref. https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/instancedata-data-categories.html
+pub async fn fetch_ipv6() -> Result<Option<String>> {
+    let mac = fetch_metadata("mac").await?;
+    let s = match fetch_optional_metadata(&format!("network/interfaces/macs/{}/ipv6s", mac.trim()))
+        .await?
+    {
+        Some(s) => s,
+        None => return Ok(None),
+    };
+    Ok(s.lines()
+        .map(|l| l.trim())
+        .find(|l| !l.is_empty())
+        .map(String::from))
+}
+
 /// Fetches the availability of the host EC2 machine.
 pub async fn fetch_availability_zone() -> Result<String> {
     fetch_metadata("placement/availability-zone").await
@@ -332,11 +492,60 @@ pub async fn fetch_region() -> Result<String> {
     Ok(az)
 }
 
+/// Represents the spot interruption notice, issued two minutes before
+/// the instance is stopped or terminated.
+/// ref. https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/spot-instance-termination-notices.html
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct SpotInstanceAction {
+    /// "terminate", "stop", or "hibernate".
+    pub action: String,
+    /// Represents the interruption time in RFC3339.
+    pub time: String,
+}
+
+/// Fetches the spot interruption notice of the host EC2 machine,
+/// "None" if no interruption is scheduled (or if not a spot instance).
+pub async fn fetch_spot_instance_action() -> Result<Option<SpotInstanceAction>> {
+    let s = match fetch_optional_metadata("spot/instance-action").await? {
+        Some(s) => s,
+        None => return Ok(None),
+    };
+    match serde_json::from_str(&s) {
+        Ok(v) => Ok(Some(v)),
+        Err(e) => Err(Other {
+            message: format!("failed to parse spot/instance-action '{}' ({})", s, e),
+            is_retryable: false,
+        }),
+    }
+}
+
+/// Target lifecycle state of the ASG instance being terminated,
+/// once the termination lifecycle hook puts it in "Terminating:Wait".
+pub const TARGET_LIFECYCLE_STATE_TERMINATED: &str = "Terminated";
+
+/// Fetches the target lifecycle state of the ASG instance
+/// (e.g., "InService", "Terminated").
+/// ref. https://docs.aws.amazon.com/autoscaling/ec2/userguide/retrieving-target-lifecycle-state-through-imds.html
+pub async fn fetch_target_lifecycle_state() -> Result<String> {
+    fetch_metadata("autoscaling/target-lifecycle-state").await
+}
+
 /// Fetches instance metadata service v2 with the "path".
 /// ref. https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/instancedata-data-retrieval.html
 /// ref. https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/configuring-instance-metadata-service.html
 /// e.g., curl -H "X-aws-ec2-metadata-token: $TOKEN" -v http://169.254.169.254/latest/meta-data/public-ipv4
 async fn fetch_metadata(path: &str) -> Result<String> {
+    match fetch_optional_metadata(path).await? {
+        Some(s) => Ok(s),
+        None => Err(API {
+            message: format!("meta-data/{} not found", path),
+            is_retryable: false,
+        }),
+    }
+}
+
+/// Returns "None" if the meta-data path is not found (HTTP 404).
+async fn fetch_optional_metadata(path: &str) -> Result<Option<String>> {
     info!("fetching meta-data/{}", path);
 
     let uri = format!("http://169.254.169.254/latest/meta-data/{}", path);
@@ -373,6 +582,7 @@ async fn fetch_metadata(path: &str) -> Result<String> {
             };
             s
         }
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
         Err(e) => {
             return Err(API {
                 message: format!("failed GET meta-data/{} {:?}", path, e),
@@ -380,7 +590,7 @@ async fn fetch_metadata(path: &str) -> Result<String> {
             })
         }
     };
-    Ok(rs)
+    Ok(Some(rs))
 }
 
 /// Serves session token for instance metadata service v2.
@@ -0,0 +1,433 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use aws_sdk_ec2::model::{Filter, IpPermission, IpRange, Ipv6Range};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ec2::{is_error_retryable, Manager},
+    errors::{Error::API, Result},
+};
+
+/// Represents the TCP ingress rule of the security group.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct IngressRule {
+    pub port: u32,
+    pub cidr_ip: String,
+}
+
+impl IngressRule {
+    pub fn new(port: u32, cidr_ip: &str) -> Self {
+        Self {
+            port,
+            cidr_ip: cidr_ip.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for IngressRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "tcp/{} from {}", self.port, self.cidr_ip)
+    }
+}
+
+/// Returns the TCP permission on the port from the IPv4 or IPv6 range.
+pub(crate) fn tcp_permission(port: u32, cidr_ip: &str) -> IpPermission {
+    let builder = IpPermission::builder()
+        .ip_protocol("tcp")
+        .from_port(port as i32)
+        .to_port(port as i32);
+    if cidr_ip.contains(':') {
+        builder
+            .ipv6_ranges(Ipv6Range::builder().cidr_ipv6(cidr_ip).build())
+            .build()
+    } else {
+        builder
+            .ip_ranges(IpRange::builder().cidr_ip(cidr_ip).build())
+            .build()
+    }
+}
+
+/// Rules to add and remove to converge the security group to the desired rules.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct IngressDiff {
+    pub authorize: Vec<IngressRule>,
+    pub revoke: Vec<IngressRule>,
+}
+
+impl IngressDiff {
+    pub fn is_empty(&self) -> bool {
+        self.authorize.is_empty() && self.revoke.is_empty()
+    }
+}
+
+/// Diffs the current rules against the desired ones.
+/// Only revokes the rules on the managed ports, so the rules
+/// added out of band for other ports are left alone.
+pub fn diff_ingress(
+    current: &[IngressRule],
+    desired: &[IngressRule],
+    managed_ports: &[u32],
+) -> IngressDiff {
+    let current: BTreeSet<&IngressRule> = current.iter().collect();
+    let desired: BTreeSet<&IngressRule> = desired.iter().collect();
+    IngressDiff {
+        authorize: desired.difference(&current).map(|r| (*r).clone()).collect(),
+        revoke: current
+            .difference(&desired)
+            .filter(|r| managed_ports.contains(&r.port))
+            .map(|r| (*r).clone())
+            .collect(),
+    }
+}
+
+impl Manager {
+    /// Lists the TCP ingress rules of the security group
+    /// on the single port with the IPv4 ranges.
+    pub async fn list_ingress_tcp(&self, security_group_id: &str) -> Result<Vec<IngressRule>> {
+        let ret = self
+            .cli
+            .describe_security_groups()
+            .group_ids(security_group_id)
+            .send()
+            .await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed describe_security_groups {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+
+        let mut rules = Vec::new();
+        for sg in resp.security_groups().unwrap_or_default() {
+            for p in sg.ip_permissions().unwrap_or_default() {
+                if p.ip_protocol() != Some("tcp") || p.from_port() != p.to_port() {
+                    continue;
+                }
+                let port = match p.from_port() {
+                    Some(v) if v > 0 => v as u32,
+                    _ => continue,
+                };
+                for r in p.ip_ranges().unwrap_or_default() {
+                    if let Some(cidr_ip) = r.cidr_ip() {
+                        rules.push(IngressRule::new(port, cidr_ip));
+                    }
+                }
+            }
+        }
+        rules.sort();
+        Ok(rules)
+    }
+
+    /// Revokes the TCP ingress of the security group from the IP ranges.
+    /// The ranges that are not allowed are skipped.
+    pub async fn revoke_ingress_tcp(
+        &self,
+        security_group_id: &str,
+        port: u32,
+        cidr_ips: &[String],
+    ) -> Result<()> {
+        info!(
+            "revoking TCP port {} ingress of '{}' from {} range(s)",
+            port,
+            security_group_id,
+            cidr_ips.len()
+        );
+        for cidr_ip in cidr_ips.iter() {
+            let permission = tcp_permission(port, cidr_ip);
+            let ret = self
+                .cli
+                .revoke_security_group_ingress()
+                .group_id(security_group_id)
+                .ip_permissions(permission)
+                .send()
+                .await;
+            if let Err(e) = ret {
+                if !format!("{:?}", e).contains("InvalidPermission.NotFound") {
+                    return Err(API {
+                        message: format!("failed revoke_security_group_ingress {:?}", e),
+                        is_retryable: is_error_retryable(&e),
+                    });
+                }
+                info!("'{}' already revoked in '{}'", cidr_ip, security_group_id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies the diff, authorizing first so that the replaced ranges
+    /// on the same port never leave the port closed.
+    pub async fn apply_ingress_diff(
+        &self,
+        security_group_id: &str,
+        diff: &IngressDiff,
+    ) -> Result<()> {
+        for (port, cidr_ips) in group_by_port(&diff.authorize) {
+            self.authorize_ingress_tcp(security_group_id, port, &cidr_ips)
+                .await?;
+        }
+        for (port, cidr_ips) in group_by_port(&diff.revoke) {
+            self.revoke_ingress_tcp(security_group_id, port, &cidr_ips)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Creates the security group in the VPC, and returns its ID.
+    pub async fn create_security_group(
+        &self,
+        vpc_id: &str,
+        group_name: &str,
+        description: &str,
+    ) -> Result<String> {
+        info!("creating security group '{}' in '{}'", group_name, vpc_id);
+        let ret = self
+            .cli
+            .create_security_group()
+            .vpc_id(vpc_id)
+            .group_name(group_name)
+            .description(description)
+            .send()
+            .await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed create_security_group {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+        match resp.group_id() {
+            Some(v) => Ok(v.to_string()),
+            None => Err(API {
+                message: String::from("empty group ID from create_security_group"),
+                is_retryable: false,
+            }),
+        }
+    }
+
+    /// Returns the ID of the security group of the name in the VPC, if any.
+    pub async fn find_security_group(
+        &self,
+        vpc_id: &str,
+        group_name: &str,
+    ) -> Result<Option<String>> {
+        let ret = self
+            .cli
+            .describe_security_groups()
+            .filters(Filter::builder().name("vpc-id").values(vpc_id).build())
+            .filters(
+                Filter::builder()
+                    .name("group-name")
+                    .values(group_name)
+                    .build(),
+            )
+            .send()
+            .await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed describe_security_groups {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+        Ok(resp
+            .security_groups()
+            .unwrap_or_default()
+            .iter()
+            .find_map(|sg| sg.group_id().map(String::from)))
+    }
+
+    /// Deletes the security group.
+    /// Fails with "DependencyViolation" while any instance still uses it.
+    pub async fn delete_security_group(&self, security_group_id: &str) -> Result<()> {
+        info!("deleting security group '{}'", security_group_id);
+        let ret = self
+            .cli
+            .delete_security_group()
+            .group_id(security_group_id)
+            .send()
+            .await;
+        if let Err(e) = ret {
+            if !format!("{:?}", e).contains("InvalidGroup.NotFound") {
+                return Err(API {
+                    message: format!("failed delete_security_group {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+            info!("'{}' already deleted", security_group_id);
+        }
+        Ok(())
+    }
+
+    /// Authorizes the TCP egress of the security group to the IP ranges.
+    pub async fn authorize_egress_tcp(
+        &self,
+        security_group_id: &str,
+        port: u32,
+        cidr_ips: &[String],
+    ) -> Result<()> {
+        info!(
+            "authorizing TCP port {} egress of '{}' to {} range(s)",
+            port,
+            security_group_id,
+            cidr_ips.len()
+        );
+        for cidr_ip in cidr_ips.iter() {
+            let permission = tcp_permission(port, cidr_ip);
+            let ret = self
+                .cli
+                .authorize_security_group_egress()
+                .group_id(security_group_id)
+                .ip_permissions(permission)
+                .send()
+                .await;
+            if let Err(e) = ret {
+                if !format!("{:?}", e).contains("InvalidPermission.Duplicate") {
+                    return Err(API {
+                        message: format!("failed authorize_security_group_egress {:?}", e),
+                        is_retryable: is_error_retryable(&e),
+                    });
+                }
+                info!("'{}' already allowed in '{}'", cidr_ip, security_group_id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Revokes the allow-all egress rules that every new security group has
+    /// (IPv6 as well in the dual-stack VPC), so that only the explicitly
+    /// authorized egress is allowed.
+    pub async fn revoke_default_egress(&self, security_group_id: &str) -> Result<()> {
+        info!("revoking default egress of '{}'", security_group_id);
+        let permissions = [
+            IpPermission::builder()
+                .ip_protocol("-1")
+                .ip_ranges(IpRange::builder().cidr_ip("0.0.0.0/0").build())
+                .build(),
+            IpPermission::builder()
+                .ip_protocol("-1")
+                .ipv6_ranges(Ipv6Range::builder().cidr_ipv6("::/0").build())
+                .build(),
+        ];
+        for permission in permissions {
+            let ret = self
+                .cli
+                .revoke_security_group_egress()
+                .group_id(security_group_id)
+                .ip_permissions(permission)
+                .send()
+                .await;
+            if let Err(e) = ret {
+                if !format!("{:?}", e).contains("InvalidPermission.NotFound") {
+                    return Err(API {
+                        message: format!("failed revoke_security_group_egress {:?}", e),
+                        is_retryable: is_error_retryable(&e),
+                    });
+                }
+                info!("default egress already revoked in '{}'", security_group_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the security groups of the instance (primary network interface).
+    /// The tracked connections that the new groups do not allow
+    /// are NOT interrupted until they are closed.
+    /// ref. https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/security-group-connection-tracking.html
+    pub async fn modify_instance_security_groups(
+        &self,
+        instance_id: &str,
+        security_group_ids: &[String],
+    ) -> Result<()> {
+        info!(
+            "setting security groups of '{}' to {:?}",
+            instance_id, security_group_ids
+        );
+        let ret = self
+            .cli
+            .modify_instance_attribute()
+            .instance_id(instance_id)
+            .set_groups(Some(security_group_ids.to_vec()))
+            .send()
+            .await;
+        if let Err(e) = ret {
+            return Err(API {
+                message: format!("failed modify_instance_attribute {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn group_by_port(rules: &[IngressRule]) -> BTreeMap<u32, Vec<String>> {
+    let mut ports: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+    for r in rules.iter() {
+        ports.entry(r.port).or_default().push(r.cidr_ip.clone());
+    }
+    ports
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- ec2::sg::test_diff_ingress --exact --show-output
+#[test]
+fn test_diff_ingress() {
+    let current = vec![
+        IngressRule::new(22, "0.0.0.0/0"),
+        IngressRule::new(9650, "0.0.0.0/0"),
+        IngressRule::new(9651, "0.0.0.0/0"),
+        IngressRule::new(8080, "10.0.0.0/16"),
+    ];
+    let desired = vec![
+        IngressRule::new(9650, "10.0.0.0/16"),
+        IngressRule::new(9650, "203.0.113.0/24"),
+        IngressRule::new(9651, "0.0.0.0/0"),
+    ];
+    let diff = diff_ingress(&current, &desired, &[22, 9650, 9651]);
+    assert_eq!(
+        diff.authorize,
+        vec![
+            IngressRule::new(9650, "10.0.0.0/16"),
+            IngressRule::new(9650, "203.0.113.0/24"),
+        ]
+    );
+    // unmanaged port 8080 is kept
+    assert_eq!(
+        diff.revoke,
+        vec![
+            IngressRule::new(22, "0.0.0.0/0"),
+            IngressRule::new(9650, "0.0.0.0/0"),
+        ]
+    );
+    assert_eq!(
+        IngressRule::new(9651, "0.0.0.0/0").to_string(),
+        "tcp/9651 from 0.0.0.0/0"
+    );
+
+    assert!(diff_ingress(&desired, &desired, &[9650, 9651]).is_empty());
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- ec2::sg::test_tcp_permission --exact --show-output
+#[test]
+fn test_tcp_permission() {
+    let p = tcp_permission(9651, "1.2.3.4/32");
+    assert_eq!(p.from_port(), Some(9651));
+    assert_eq!(p.ip_ranges().unwrap()[0].cidr_ip(), Some("1.2.3.4/32"));
+    assert!(p.ipv6_ranges().is_none());
+
+    let p = tcp_permission(9651, "2600:1f14::1/128");
+    assert_eq!(
+        p.ipv6_ranges().unwrap()[0].cidr_ipv6(),
+        Some("2600:1f14::1/128")
+    );
+    assert!(p.ip_ranges().is_none());
+}
@@ -22,6 +22,209 @@ const DEK_AES_256_LENGTH: usize = 32;
 
 const AAD_TAG: &str = "avalanche-ops-envelope-encryption";
 
+/// Prefix of the sealed header, never the first bytes of the legacy format
+/// that starts with the nonce length.
+const HEADER_MAGIC: &[u8; 4] = b"AOEE";
+
+/// Legacy format without the magic, the version, and the authenticated header.
+pub const HEADER_VERSION_LEGACY: u8 = 0;
+pub const HEADER_VERSION_1: u8 = 1;
+
+/// Represents the header of the envelope-encrypted data.
+/// The version 1 header is aligned as below:
+/// [ magic "AOEE" ][ version ][ Nonce bytes "length" ][ DEK.ciphertext "length" ][ Nonce bytes ][ DEK.ciphertext ]
+/// and authenticated as the AES-GCM additional data, so that
+/// the nonce and the DEK cannot be swapped without failing the decryption.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Header {
+    pub version: u8,
+    pub nonce: [u8; NONCE_LEN],
+    pub dek_ciphertext: Vec<u8>,
+}
+
+impl Header {
+    pub fn new(nonce: [u8; NONCE_LEN], dek_ciphertext: Vec<u8>) -> Self {
+        Self {
+            version: HEADER_VERSION_1,
+            nonce,
+            dek_ciphertext,
+        }
+    }
+
+    /// Encodes the header, followed by the data ciphertext.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut b = Vec::new();
+        if self.version != HEADER_VERSION_LEGACY {
+            b.extend_from_slice(HEADER_MAGIC);
+            b.push(self.version);
+        }
+        b.write_u16::<LittleEndian>(NONCE_LEN as u16)
+            .and_then(|_| b.write_u16::<LittleEndian>(self.dek_ciphertext.len() as u16))
+            .and_then(|_| b.write_all(&self.nonce))
+            .and_then(|_| b.write_all(&self.dek_ciphertext))
+            .map_err(|e| Other {
+                message: format!("failed to write header ({:?})", e),
+                is_retryable: false,
+            })?;
+        Ok(b)
+    }
+
+    /// Decodes the header of the sealed data.
+    /// Returns the header and the offset of the data ciphertext.
+    pub fn decode(d: &[u8]) -> Result<(Self, usize)> {
+        let (version, offset) = if d.starts_with(HEADER_MAGIC) {
+            match d.get(HEADER_MAGIC.len()) {
+                Some(&HEADER_VERSION_1) => (HEADER_VERSION_1, HEADER_MAGIC.len() + 1),
+                v => {
+                    return Err(Other {
+                        message: format!("unknown envelope header version {:?}", v),
+                        is_retryable: false,
+                    });
+                }
+            }
+        } else {
+            (HEADER_VERSION_LEGACY, 0)
+        };
+        let mut buf = Cursor::new(&d[offset..]);
+
+        let nonce_len = match buf.read_u16::<LittleEndian>() {
+            Ok(v) => v as usize,
+            Err(e) => {
+                return Err(Other {
+                    message: format!("failed to read_u16 for nonce_len ({:?})", e),
+                    is_retryable: false,
+                });
+            }
+        };
+        if nonce_len != NONCE_LEN {
+            return Err(Other {
+                message: format!("nonce_len {} != NONCE_LEN {}", nonce_len, NONCE_LEN),
+                is_retryable: false,
+            });
+        }
+
+        let dek_ciphertext_len = match buf.read_u16::<LittleEndian>() {
+            Ok(v) => v as usize,
+            Err(e) => {
+                return Err(Other {
+                    message: format!("failed to read_u16 for dek_ciphertext_len ({:?})", e),
+                    is_retryable: false,
+                });
+            }
+        };
+        if dek_ciphertext_len > d.len() {
+            return Err(Other {
+                message: format!(
+                    "invalid DEK ciphertext len {} > cipher.len {}",
+                    dek_ciphertext_len,
+                    d.len()
+                ),
+                is_retryable: false,
+            });
+        }
+
+        let mut nonce = [0u8; NONCE_LEN];
+        match buf.read_exact(&mut nonce) {
+            Ok(_) => {}
+            Err(e) => {
+                return Err(Other {
+                    message: format!("failed to read_exact for nonce_bytes ({:?})", e),
+                    is_retryable: false,
+                });
+            }
+        };
+
+        let mut dek_ciphertext = zero_vec(dek_ciphertext_len);
+        match buf.read_exact(&mut dek_ciphertext) {
+            Ok(_) => {}
+            Err(e) => {
+                return Err(Other {
+                    message: format!("failed to read_exact for DEK.ciphertext ({:?})", e),
+                    is_retryable: false,
+                });
+            }
+        };
+
+        let header = Self {
+            version,
+            nonce,
+            dek_ciphertext,
+        };
+        Ok((header, offset + buf.position() as usize))
+    }
+
+    /// Returns the additional data to authenticate.
+    fn aad(&self) -> Result<Vec<u8>> {
+        let mut aad = AAD_TAG.as_bytes().to_vec();
+        if self.version != HEADER_VERSION_LEGACY {
+            aad.extend(self.encode()?);
+        }
+        Ok(aad)
+    }
+}
+
+/// Encrypts the data with the plaintext DEK and "AES_256_GCM",
+/// and returns the header followed by the data ciphertext.
+pub fn seal_with_dek(dek_plaintext: &[u8], header: &Header, d: &[u8]) -> Result<Vec<u8>> {
+    let unbound_key = match UnboundKey::new(&AES_256_GCM, dek_plaintext) {
+        Ok(v) => v,
+        Err(e) => {
+            return Err(Other {
+                message: format!("failed to create UnboundKey ({:?})", e),
+                is_retryable: false,
+            });
+        }
+    };
+    let safe_key = LessSafeKey::new(unbound_key);
+
+    // overwrites the original array
+    let mut cipher = d.to_vec();
+    match safe_key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(header.nonce),
+        Aad::from(header.aad()?),
+        &mut cipher,
+    ) {
+        Ok(_) => {}
+        Err(e) => {
+            return Err(Other {
+                message: format!("failed to seal ({:?})", e),
+                is_retryable: false,
+            });
+        }
+    }
+
+    let mut encrypted = header.encode()?;
+    encrypted.extend(cipher);
+    Ok(encrypted)
+}
+
+/// Decrypts the data ciphertext with the plaintext DEK of the header.
+pub fn open_with_dek(dek_plaintext: &[u8], header: &Header, cipher: &[u8]) -> Result<Vec<u8>> {
+    let unbound_key = match UnboundKey::new(&AES_256_GCM, dek_plaintext) {
+        Ok(v) => v,
+        Err(e) => {
+            return Err(Other {
+                message: format!("failed to create UnboundKey ({:?})", e),
+                is_retryable: false,
+            });
+        }
+    };
+    let safe_key = LessSafeKey::new(unbound_key);
+
+    let mut cipher = cipher.to_vec();
+    match safe_key.open_in_place(
+        Nonce::assume_unique_for_key(header.nonce),
+        Aad::from(header.aad()?),
+        &mut cipher,
+    ) {
+        Ok(plaintext) => Ok(plaintext.to_vec()),
+        Err(e) => Err(Other {
+            message: format!("failed to open_in_place ciphertext ({:?})", e),
+            is_retryable: false,
+        }),
+    }
+}
+
 /// Implements envelope encryption manager.
 #[derive(std::clone::Clone)]
 pub struct Envelope {
@@ -40,7 +243,8 @@ impl Envelope {
     /// Envelope-encrypts the data using AWS KMS data-encryption key (DEK)
     /// and "AES_256_GCM", since kms:Encrypt can only encrypt 4 KiB).
     /// The encrypted data are aligned as below:
-    /// [ Nonce bytes "length" ][ DEK.ciphertext "length" ][ Nonce bytes ][ DEK.ciphertext ][ data ciphertext ]
+    /// [ version 1 header ][ data ciphertext ]
+    /// See "Header" for the header format.
     pub async fn seal_aes_256(&self, d: &[u8]) -> Result<Vec<u8>> {
         info!(
             "AES_256 envelope-encrypting data (size before encryption {})",
@@ -81,95 +285,9 @@ impl Envelope {
                 });
             }
         }
-        let unbound_key = match UnboundKey::new(&AES_256_GCM, &dek.plaintext) {
-            Ok(v) => v,
-            Err(e) => {
-                return Err(Other {
-                    message: format!("failed to create UnboundKey ({:?})", e),
-                    is_retryable: false,
-                });
-            }
-        };
-        let safe_key = LessSafeKey::new(unbound_key);
-
-        // overwrites the original array
-        let mut cipher = d.to_vec();
-        match safe_key.seal_in_place_append_tag(
-            Nonce::assume_unique_for_key(nonce_bytes),
-            Aad::from(AAD_TAG),
-            &mut cipher,
-        ) {
-            Ok(_) => {}
-            Err(e) => {
-                return Err(Other {
-                    message: format!("failed to seal ({:?})", e),
-                    is_retryable: false,
-                });
-            }
-        }
-
-        // align bytes in the order of
-        // - Nonce bytes "length"
-        // - DEK.ciphertext "length"
-        // - Nonce bytes
-        // - DEK.ciphertext
-        // - data ciphertext
-        let mut encrypted = Vec::new();
-
-        // Nonce bytes "length"
-        match encrypted.write_u16::<LittleEndian>(NONCE_LEN as u16) {
-            Ok(_) => {}
-            Err(e) => {
-                return Err(Other {
-                    message: format!("failed to write ({:?})", e),
-                    is_retryable: false,
-                });
-            }
-        }
 
-        // DEK.ciphertext "length"
-        match encrypted.write_u16::<LittleEndian>(dek.ciphertext.len() as u16) {
-            Ok(_) => {}
-            Err(e) => {
-                return Err(Other {
-                    message: format!("failed to write ({:?})", e),
-                    is_retryable: false,
-                });
-            }
-        }
-
-        // Nonce bytes
-        match encrypted.write_all(&nonce_bytes) {
-            Ok(_) => {}
-            Err(e) => {
-                return Err(Other {
-                    message: format!("failed to write ({:?})", e),
-                    is_retryable: false,
-                });
-            }
-        }
-
-        // DEK.ciphertext
-        match encrypted.write_all(&dek.ciphertext) {
-            Ok(_) => {}
-            Err(e) => {
-                return Err(Other {
-                    message: format!("failed to write ({:?})", e),
-                    is_retryable: false,
-                });
-            }
-        }
-
-        // data ciphertext
-        match encrypted.write_all(&cipher) {
-            Ok(_) => {}
-            Err(e) => {
-                return Err(Other {
-                    message: format!("failed to write ({:?})", e),
-                    is_retryable: false,
-                });
-            }
-        }
+        let header = Header::new(nonce_bytes, dek.ciphertext.clone());
+        let encrypted = seal_with_dek(&dek.plaintext, &header, d)?;
 
         info!(
             "AES_256 envelope-encrypted data (encrypted size {})",
@@ -179,7 +297,7 @@ impl Envelope {
     }
 
     /// Envelope-decrypts using KMS DEK and "AES_256_GCM".
-    /// Assume the input (ciphertext) data are packed in the order of:
+    /// Supports both the version 1 header and the legacy format of:
     /// [ Nonce bytes "length" ][ DEK.ciphertext "length" ][ Nonce bytes ][ DEK.ciphertext ][ data ciphertext ]
     pub async fn unseal_aes_256(&self, d: &[u8]) -> Result<Vec<u8>> {
         info!(
@@ -196,111 +314,17 @@ impl Envelope {
         let kms_manager = self.aws_kms_manager.clone().unwrap();
         let key_id = self.aws_kms_key_id.clone().unwrap();
 
-        // bytes are packed in the order of
-        // - Nonce bytes "length"
-        // - DEK.ciphertext "length"
-        // - Nonce bytes
-        // - DEK.ciphertext
-        // - data ciphertext
-        let mut buf = Cursor::new(d);
-
-        let nonce_len = match buf.read_u16::<LittleEndian>() {
-            Ok(v) => v as usize,
-            Err(e) => {
-                return Err(Other {
-                    message: format!("failed to read_u16 for nonce_len ({:?})", e),
-                    is_retryable: false,
-                });
-            }
-        };
-        if nonce_len != NONCE_LEN {
-            return Err(Other {
-                message: format!("nonce_len {} != NONCE_LEN {}", nonce_len, NONCE_LEN),
-                is_retryable: false,
-            });
-        }
-
-        let dek_ciphertext_len = match buf.read_u16::<LittleEndian>() {
-            Ok(v) => v as usize,
-            Err(e) => {
-                return Err(Other {
-                    message: format!("failed to read_u16 for dek_ciphertext_len ({:?})", e),
-                    is_retryable: false,
-                });
-            }
-        };
-        if dek_ciphertext_len > d.len() {
-            return Err(Other {
-                message: format!(
-                    "invalid DEK ciphertext len {} > cipher.len {}",
-                    dek_ciphertext_len,
-                    d.len()
-                ),
-                is_retryable: false,
-            });
-        }
-
-        let mut nonce_bytes = [0u8; NONCE_LEN];
-        match buf.read_exact(&mut nonce_bytes) {
-            Ok(_) => {}
-            Err(e) => {
-                return Err(Other {
-                    message: format!("failed to read_exact for nonce_bytes ({:?})", e),
-                    is_retryable: false,
-                });
-            }
-        };
-        let nonce = Nonce::assume_unique_for_key(nonce_bytes);
+        let (header, offset) = Header::decode(d)?;
 
-        let mut dek_ciphertext = zero_vec(dek_ciphertext_len);
-        match buf.read_exact(&mut dek_ciphertext) {
-            Ok(_) => {}
-            Err(e) => {
-                return Err(Other {
-                    message: format!("failed to read_exact for DEK.ciphertext ({:?})", e),
-                    is_retryable: false,
-                });
-            }
-        };
         // use the default "SYMMETRIC_DEFAULT"
         let dek_plain = kms_manager
             .decrypt(
                 &key_id,
                 Some(EncryptionAlgorithmSpec::SymmetricDefault),
-                dek_ciphertext,
+                header.dek_ciphertext.clone(),
             )
             .await?;
-        let unbound_key = match UnboundKey::new(&AES_256_GCM, &dek_plain) {
-            Ok(v) => v,
-            Err(e) => {
-                return Err(Other {
-                    message: format!("failed to create UnboundKey ({:?})", e),
-                    is_retryable: false,
-                });
-            }
-        };
-        let safe_key = LessSafeKey::new(unbound_key);
-
-        let mut cipher = Vec::new();
-        match buf.read_to_end(&mut cipher) {
-            Ok(_) => {}
-            Err(e) => {
-                return Err(Other {
-                    message: format!("failed to read_to_end for ciphertext ({:?})", e),
-                    is_retryable: false,
-                });
-            }
-        };
-
-        let decrypted = match safe_key.open_in_place(nonce, Aad::from(AAD_TAG), &mut cipher) {
-            Ok(plaintext) => plaintext.to_vec(),
-            Err(e) => {
-                return Err(Other {
-                    message: format!("failed to open_in_place ciphertext ({:?})", e),
-                    is_retryable: false,
-                });
-            }
-        };
+        let decrypted = open_with_dek(&dek_plain, &header, &d[offset..])?;
 
         info!(
             "AES_256 envelope-decrypted data (decrypted size {})",
@@ -443,3 +467,46 @@ pub async fn spawn_unseal_aes_256_file(
     .await
     .expect("failed spawn await")
 }
+
+/// RUST_LOG=debug cargo test --package aws --lib -- envelope::test_seal_with_dek --exact --show-output
+#[test]
+fn test_seal_with_dek() {
+    let dek = [7u8; DEK_AES_256_LENGTH];
+    let header = Header::new([1u8; NONCE_LEN], vec![9u8; 184]);
+    let msg = b"staking key";
+
+    let sealed = seal_with_dek(&dek, &header, msg).unwrap();
+    assert!(sealed.starts_with(HEADER_MAGIC));
+    let (decoded, offset) = Header::decode(&sealed).unwrap();
+    assert_eq!(decoded, header);
+    assert_eq!(offset, 4 + 1 + 2 + 2 + NONCE_LEN + 184);
+    assert_eq!(
+        open_with_dek(&dek, &decoded, &sealed[offset..]).unwrap(),
+        msg
+    );
+
+    // tampered header fails the authentication
+    let mut tampered = sealed.clone();
+    tampered[4 + 1 + 2 + 2 + NONCE_LEN] ^= 1;
+    let (decoded, offset) = Header::decode(&tampered).unwrap();
+    assert!(open_with_dek(&dek, &decoded, &tampered[offset..]).is_err());
+
+    // unknown version
+    let mut unknown = sealed.clone();
+    unknown[4] = 2;
+    assert!(Header::decode(&unknown).is_err());
+
+    // legacy format without the magic and the version
+    let legacy_header = Header {
+        version: HEADER_VERSION_LEGACY,
+        ..header
+    };
+    let legacy = seal_with_dek(&dek, &legacy_header, msg).unwrap();
+    assert_eq!(&legacy[..2], &(NONCE_LEN as u16).to_le_bytes());
+    let (decoded, offset) = Header::decode(&legacy).unwrap();
+    assert_eq!(decoded.version, HEADER_VERSION_LEGACY);
+    assert_eq!(
+        open_with_dek(&dek, &decoded, &legacy[offset..]).unwrap(),
+        msg
+    );
+}
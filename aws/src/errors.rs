@@ -30,3 +30,20 @@ impl Error {
         }
     }
 }
+
+impl From<Error> for avalanche_types::errors::Error {
+    fn from(e: Error) -> Self {
+        avalanche_types::errors::Error::AWS {
+            message: e.message(),
+            is_retryable: e.is_retryable(),
+        }
+    }
+}
+
+/// So that the AWS calls propagate with "?" in the functions that return "io::Result",
+/// preserving the retryability.
+impl From<Error> for std::io::Error {
+    fn from(e: Error) -> Self {
+        avalanche_types::errors::Error::from(e).into()
+    }
+}
@@ -1,27 +1,35 @@
 use std::{
     fs::{self, File},
-    io::Write,
+    io::{self, Write},
     string::String,
 };
 
+use async_trait::async_trait;
+
 use aws_sdk_kms::{
     error::{
         CreateKeyError, CreateKeyErrorKind, DecryptError, DecryptErrorKind, EncryptError,
-        EncryptErrorKind, GenerateDataKeyError, GenerateDataKeyErrorKind, ScheduleKeyDeletionError,
-        ScheduleKeyDeletionErrorKind,
+        EncryptErrorKind, GenerateDataKeyError, GenerateDataKeyErrorKind, GetPublicKeyError,
+        GetPublicKeyErrorKind, ScheduleKeyDeletionError, ScheduleKeyDeletionErrorKind, SignError,
+        SignErrorKind,
+    },
+    model::{
+        DataKeySpec, EncryptionAlgorithmSpec, KeySpec, KeyUsageType, MessageType,
+        SigningAlgorithmSpec, Tag,
     },
-    model::{DataKeySpec, EncryptionAlgorithmSpec, Tag},
     types::{Blob, SdkError},
     Client,
 };
 use aws_types::SdkConfig as AwsSdkConfig;
 use log::{info, warn};
+use secp256k1::PublicKey;
 
 use crate::errors::{
     Error::{Other, API},
     Result,
 };
-use utils::humanize;
+use avalanche_types::{ids, signer, soft_key};
+use utils::{humanize, secp256k1r};
 
 /// Represents the data encryption key.
 #[derive(Debug)]
@@ -100,6 +108,139 @@ impl Manager {
         Ok(Key::new(key_id, key_arn))
     }
 
+    /// Creates an AWS KMS asymmetric CMK for secp256k1 signing,
+    /// whose private key never leaves KMS.
+    /// ref. https://docs.aws.amazon.com/kms/latest/developerguide/asymmetric-key-specs.html
+    pub async fn create_secp256k1_key(&self, key_desc: &str) -> Result<Key> {
+        info!("creating KMS CMK '{}' for secp256k1 signing", key_desc);
+        let ret = self
+            .cli
+            .create_key()
+            .description(key_desc)
+            .key_spec(KeySpec::EccSecgP256K1)
+            .key_usage(KeyUsageType::SignVerify)
+            .tags(Tag::builder().tag_key("Name").tag_value(key_desc).build())
+            .tags(
+                Tag::builder()
+                    .tag_key("KIND")
+                    .tag_value("avalanche-ops")
+                    .build(),
+            )
+            .send()
+            .await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed create_key {:?}", e),
+                    is_retryable: is_error_retryable_create_key(&e),
+                });
+            }
+        };
+
+        let meta = match resp.key_metadata() {
+            Some(v) => v,
+            None => {
+                return Err(Other {
+                    message: String::from("unexpected empty key metadata"),
+                    is_retryable: false,
+                });
+            }
+        };
+        let key_id = meta.key_id().unwrap_or("");
+        let key_arn = meta.arn().unwrap_or("");
+
+        info!("created KMS CMK id '{}' and arn '{}'", key_id, key_arn);
+        Ok(Key::new(key_id, key_arn))
+    }
+
+    /// Returns the DER-encoded "SubjectPublicKeyInfo" of the asymmetric CMK.
+    /// ref. https://docs.aws.amazon.com/kms/latest/APIReference/API_GetPublicKey.html
+    pub async fn get_public_key(&self, key_id: &str) -> Result<Vec<u8>> {
+        info!("getting public key for KMS CMK '{}'", key_id);
+        let ret = self.cli.get_public_key().key_id(key_id).send().await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed get_public_key {:?}", e),
+                    is_retryable: is_error_retryable_get_public_key(&e),
+                });
+            }
+        };
+
+        match resp.public_key() {
+            Some(v) => Ok(v.clone().into_inner()),
+            None => Err(API {
+                message: String::from("GetPublicKeyOutput.public_key not found"),
+                is_retryable: false,
+            }),
+        }
+    }
+
+    /// Signs the 32-byte digest with "ECDSA_SHA_256",
+    /// and returns the DER-encoded signature.
+    /// ref. https://docs.aws.amazon.com/kms/latest/APIReference/API_Sign.html
+    pub async fn sign_digest(&self, key_id: &str, digest: &[u8]) -> Result<Vec<u8>> {
+        let ret = self
+            .cli
+            .sign()
+            .key_id(key_id)
+            .message(Blob::new(digest))
+            .message_type(MessageType::Digest)
+            .signing_algorithm(SigningAlgorithmSpec::EcdsaSha256)
+            .send()
+            .await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed sign {:?}", e),
+                    is_retryable: is_error_retryable_sign(&e),
+                });
+            }
+        };
+
+        match resp.signature() {
+            Some(v) => Ok(v.clone().into_inner()),
+            None => Err(API {
+                message: String::from("SignOutput.signature not found"),
+                is_retryable: false,
+            }),
+        }
+    }
+
+    /// Replaces the key policy of the KMS CMK.
+    /// ref. https://docs.aws.amazon.com/kms/latest/APIReference/API_PutKeyPolicy.html
+    /// Replaces the key policy. "bypass_lockout_check" is required for
+    /// the policy that does not let the caller put the policy again
+    /// (e.g., "restricted_key_policy").
+    /// ref. https://docs.aws.amazon.com/kms/latest/APIReference/API_PutKeyPolicy.html
+    pub async fn put_key_policy(
+        &self,
+        key_id: &str,
+        policy: &str,
+        bypass_lockout_check: bool,
+    ) -> Result<()> {
+        info!("putting key policy for KMS CMK '{}'", key_id);
+        let ret = self
+            .cli
+            .put_key_policy()
+            .key_id(key_id)
+            .policy_name("default")
+            .policy(policy)
+            .bypass_policy_lockout_safety_check(bypass_lockout_check)
+            .send()
+            .await;
+        match ret {
+            Ok(_) => Ok(()),
+            Err(e) => Err(API {
+                message: format!("failed put_key_policy {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            }),
+        }
+    }
+
     /// Schedules to delete a KMS CMK.
     pub async fn schedule_to_delete(&self, key_id: &str) -> Result<()> {
         info!("deleting KMS CMK '{}'", key_id);
@@ -376,6 +517,81 @@ impl Manager {
     }
 }
 
+/// Actions for the account to administer the key, listed one by one
+/// without the data key access, "kms:CreateGrant" (a grant can allow
+/// the decryption to any principal), and "kms:PutKeyPolicy" (the new policy
+/// can), so that none of them is added by a wildcard.
+/// ref. https://docs.aws.amazon.com/kms/latest/developerguide/key-policy-default.html#key-policy-default-allow-administrators
+const KEY_ADMIN_ACTIONS: [&str; 21] = [
+    "kms:CreateAlias",
+    "kms:UpdateAlias",
+    "kms:DeleteAlias",
+    "kms:DescribeKey",
+    "kms:EnableKey",
+    "kms:DisableKey",
+    "kms:EnableKeyRotation",
+    "kms:DisableKeyRotation",
+    "kms:GetKeyRotationStatus",
+    "kms:GetKeyPolicy",
+    "kms:ListKeyPolicies",
+    "kms:ListAliases",
+    "kms:ListGrants",
+    "kms:ListResourceTags",
+    "kms:RevokeGrant",
+    "kms:RetireGrant",
+    "kms:UpdateKeyDescription",
+    "kms:TagResource",
+    "kms:UntagResource",
+    "kms:ScheduleKeyDeletion",
+    "kms:CancelKeyDeletion",
+];
+
+/// Actions for the envelope encryption ("aws::envelope").
+const KEY_ENVELOPE_ACTIONS: [&str; 4] = [
+    "kms:Encrypt",
+    "kms:Decrypt",
+    "kms:GenerateDataKey*",
+    "kms:DescribeKey",
+];
+
+/// Returns the key policy that lets the account administer the key,
+/// but restricts the envelope encryption and decryption to the principals
+/// (e.g., the node instance roles and the operator).
+/// The IAM policies in the account can only allow the admin actions,
+/// which neither decrypt, create grants, nor replace the policy, so
+/// the policy must be put with "put_key_policy" bypassing the lockout check,
+/// and cannot be changed afterwards (only the key deletion is left).
+/// ref. https://docs.aws.amazon.com/kms/latest/developerguide/key-policies.html
+pub fn restricted_key_policy(account_root_arn: &str, principal_arns: &[&str]) -> String {
+    let policy = serde_json::json!({
+        "Version": "2012-10-17",
+        "Statement": [
+            {
+                "Sid": "AllowKeyAdministration",
+                "Effect": "Allow",
+                "Principal": { "AWS": account_root_arn },
+                "Action": KEY_ADMIN_ACTIONS,
+                "Resource": "*",
+            },
+            {
+                "Sid": "AllowEnvelopeEncryption",
+                "Effect": "Allow",
+                "Principal": { "AWS": principal_arns },
+                "Action": KEY_ENVELOPE_ACTIONS,
+                "Resource": "*",
+            },
+        ],
+    });
+    policy.to_string()
+}
+
+/// Returns the root ARN of the account (e.g., "arn:aws:iam::123:root"),
+/// in the same partition as the ARN.
+pub fn account_root_arn(arn: &str, account_id: &str) -> String {
+    let partition = arn.split(':').nth(1).unwrap_or("aws");
+    format!("arn:{}:iam::{}:root", partition, account_id)
+}
+
 /// Represents the KMS CMK.
 #[derive(Debug)]
 pub struct Key {
@@ -393,6 +609,70 @@ impl Key {
     }
 }
 
+/// Implements "avalanche_types::signer::Signer" with the secp256k1 KMS CMK.
+/// KMS returns DER-encoded signatures without the recovery ID, so each
+/// signature is normalized to low "s" and the recovery ID is computed
+/// against the public key, which is fetched once on creation.
+#[derive(Debug, Clone)]
+pub struct KmsSigner {
+    manager: Manager,
+    pub key_id: String,
+    public_key: PublicKey,
+    short_address: ids::ShortId,
+    eth_address: String,
+}
+
+impl KmsSigner {
+    pub async fn new(manager: Manager, key_id: &str) -> Result<Self> {
+        let der = manager.get_public_key(key_id).await?;
+        let public_key = secp256k1r::public_key_from_spki_der(&der).map_err(|e| Other {
+            message: format!("KMS CMK '{}' is not a secp256k1 key ({})", key_id, e),
+            is_retryable: false,
+        })?;
+        let short_address =
+            soft_key::public_key_to_short_address(&public_key).map_err(|e| Other {
+                message: format!("failed to compute short address ({})", e),
+                is_retryable: false,
+            })?;
+        let eth_address = soft_key::public_key_to_eth_address(&public_key).map_err(|e| Other {
+            message: format!("failed to compute eth address ({})", e),
+            is_retryable: false,
+        })?;
+        info!(
+            "loaded KMS signer '{}' with address {}",
+            key_id, short_address
+        );
+
+        Ok(Self {
+            manager,
+            key_id: String::from(key_id),
+            public_key,
+            short_address,
+            eth_address,
+        })
+    }
+}
+
+#[async_trait]
+impl signer::Signer for KmsSigner {
+    async fn sign_digest(&self, digest: &[u8]) -> io::Result<Vec<u8>> {
+        let der_sig = self.manager.sign_digest(&self.key_id, digest).await?;
+        secp256k1r::recoverable_from_der(&der_sig, digest, &self.public_key)
+    }
+
+    fn public_key(&self) -> io::Result<PublicKey> {
+        Ok(self.public_key)
+    }
+
+    fn short_address(&self) -> ids::ShortId {
+        self.short_address
+    }
+
+    fn eth_address(&self) -> String {
+        self.eth_address.clone()
+    }
+}
+
 #[inline]
 pub fn is_error_retryable<E>(e: &SdkError<E>) -> bool {
     match e {
@@ -461,6 +741,36 @@ pub fn is_error_retryable_decrypt(e: &SdkError<DecryptError>) -> bool {
     }
 }
 
+#[inline]
+pub fn is_error_retryable_get_public_key(e: &SdkError<GetPublicKeyError>) -> bool {
+    match e {
+        SdkError::ServiceError { err, .. } => {
+            matches!(
+                err.kind,
+                GetPublicKeyErrorKind::DependencyTimeoutException(_)
+                    | GetPublicKeyErrorKind::KmsInternalException(_)
+                    | GetPublicKeyErrorKind::KeyUnavailableException(_)
+            )
+        }
+        _ => false,
+    }
+}
+
+#[inline]
+pub fn is_error_retryable_sign(e: &SdkError<SignError>) -> bool {
+    match e {
+        SdkError::ServiceError { err, .. } => {
+            matches!(
+                err.kind,
+                SignErrorKind::DependencyTimeoutException(_)
+                    | SignErrorKind::KmsInternalException(_)
+                    | SignErrorKind::KeyUnavailableException(_)
+            )
+        }
+        _ => false,
+    }
+}
+
 #[inline]
 fn is_error_schedule_key_deletion_does_not_exist(e: &SdkError<ScheduleKeyDeletionError>) -> bool {
     match e {
@@ -483,3 +793,49 @@ fn is_error_schedule_key_deletion_already_scheduled(
         _ => false,
     }
 }
+
+/// RUST_LOG=debug cargo test --package aws --lib -- kms::test_restricted_key_policy --exact --show-output
+#[test]
+fn test_restricted_key_policy() {
+    let root = account_root_arn("arn:aws-cn:sts::123:assumed-role/a/b", "123");
+    assert_eq!(root, "arn:aws-cn:iam::123:root");
+
+    let policy = restricted_key_policy(
+        &root,
+        &[
+            "arn:aws-cn:iam::123:role/node",
+            "arn:aws-cn:iam::123:user/op",
+        ],
+    );
+    let policy: serde_json::Value = serde_json::from_str(&policy).unwrap();
+    let statements = policy["Statement"].as_array().unwrap();
+    assert_eq!(statements.len(), 2);
+
+    let admin = &statements[0];
+    assert_eq!(admin["Principal"]["AWS"], "arn:aws-cn:iam::123:root");
+    let admin_actions: Vec<&str> = admin["Action"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    for action in admin_actions.iter() {
+        assert!(!action.contains('*'), "wildcard action {}", action);
+    }
+    assert!(!admin_actions.contains(&"kms:Decrypt"));
+    assert!(!admin_actions.contains(&"kms:CreateGrant"));
+    assert!(!admin_actions.contains(&"kms:PutKeyPolicy"));
+
+    let envelope = &statements[1];
+    assert_eq!(
+        envelope["Principal"]["AWS"],
+        serde_json::json!([
+            "arn:aws-cn:iam::123:role/node",
+            "arn:aws-cn:iam::123:user/op"
+        ])
+    );
+    assert!(envelope["Action"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("kms:Decrypt")));
+}
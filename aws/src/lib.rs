@@ -1,10 +1,16 @@
+pub mod autoscaling;
 pub mod cloudformation;
 pub mod cloudwatch;
+pub mod dynamodb;
 pub mod ec2;
 pub mod envelope;
 pub mod errors;
 pub mod kms;
+pub mod pricing;
+pub mod route53;
 pub mod s3;
+pub mod sns;
+pub mod ssm;
 pub mod sts;
 
 use std::io;
@@ -63,6 +69,50 @@ pub struct Resources {
     pub instance_system_logs: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instance_system_metrics: Option<bool>,
+    /// Retention of the CloudWatch log group for the instance logs.
+    /// Defaults to 7 days if None.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance_logs_retention_in_days: Option<u16>,
+    /// Set to false to not install the CloudWatch agent,
+    /// in which case "avalanched" ships the logs itself
+    /// and no instance system metrics are collected.
+    /// "avalanched" also ships the logs if the agent install failed.
+    /// Defaults to true if None.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub install_cloudwatch_agent: Option<bool>,
+
+    /// IP range for the SSH, HTTP, and staking port inbound traffic.
+    /// If restricted (not "0.0.0.0/0"), the staking port is opened
+    /// to each node public IP so that nodes can still reach each other
+    /// across the regions.
+    /// Defaults to "0.0.0.0/0" if None.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ingress_ipv4_range: Option<String>,
+
+    /// Set to true to assign the Amazon-provided IPv6 range to the VPC,
+    /// so that the nodes get both the IPv4 and IPv6 addresses (dual-stack).
+    /// The NLB and the anchor Elastic IPs remain IPv4.
+    /// Defaults to false if None.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dual_stack: Option<bool>,
+    /// IPv6 range for the SSH, HTTP, and staking port inbound traffic
+    /// with "dual_stack". Unlike the IPv4 ranges, always managed by the VPC stack.
+    /// Defaults to "::/0" if None.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ingress_ipv6_range: Option<String>,
+    /// Set to true for the nodes to advertise their IPv6 addresses
+    /// to the peers (avalanchego "--public-ip"), requires "dual_stack".
+    /// The peers without IPv6 cannot connect to the nodes.
+    /// Defaults to false if None.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub advertise_ipv6: Option<bool>,
+
+    /// Regions other than "region" to spread the non-anchor nodes across,
+    /// each with its own VPC and install artifacts bucket.
+    /// The anchor nodes, the NLB, and the shared resources
+    /// (e.g., "s3_bucket", KMS key) remain in "region".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regional_resources: Option<Vec<RegionalResources>>,
 
     /// AWS region to create resources.
     /// NON-EMPTY TO ENABLE HTTPS over NLB.
@@ -139,6 +189,25 @@ pub struct Resources {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cloudformation_asg_non_anchor_nodes_logical_id: Option<String>,
 
+    /// CloudFormation stack name of Auto Scaling Group (ASG)
+    /// for API nodes.
+    /// None if no API node is requested.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloudformation_asg_api_nodes: Option<String>,
+    /// Only updated after creation.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloudformation_asg_api_nodes_logical_id: Option<String>,
+
+    /// CloudFormation stack name of the load balancer
+    /// from "load_balancer" in the spec.
+    /// None if the NLB is created with the nodes.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloudformation_load_balancer: Option<String>,
+
+    /// From the load balancer stack, or the NLB created with the nodes.
     /// Only updated after creation.
     /// READ ONLY -- DO NOT SET.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -152,8 +221,18 @@ pub struct Resources {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cloudformation_asg_nlb_dns_name: Option<String>,
 
+    /// Elastic IPs of the anchor nodes, one per anchor node.
+    /// Only updated after creation.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_eips: Option<Vec<ec2::eip::Eip>>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cloudwatch_avalanche_metrics_namespace: Option<String>,
+    /// Interval to publish the node telemetry (e.g., bootstrap progress, peers).
+    /// Defaults to 60 seconds if None.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloudwatch_telemetry_interval_seconds: Option<u64>,
 }
 
 impl Default for Resources {
@@ -176,6 +255,8 @@ impl Resources {
 
             instance_system_logs: Some(true),
             instance_system_metrics: Some(true),
+            instance_logs_retention_in_days: None,
+            install_cloudwatch_agent: None,
 
             nlb_acm_certificate_arn: None,
 
@@ -199,11 +280,153 @@ impl Resources {
             cloudformation_asg_non_anchor_nodes: None,
             cloudformation_asg_non_anchor_nodes_logical_id: None,
 
+            cloudformation_asg_api_nodes: None,
+            cloudformation_asg_api_nodes_logical_id: None,
+
+            cloudformation_load_balancer: None,
+
             cloudformation_asg_nlb_arn: None,
             cloudformation_asg_nlb_target_group_arn: None,
             cloudformation_asg_nlb_dns_name: None,
 
+            anchor_eips: None,
+
             cloudwatch_avalanche_metrics_namespace: None,
+            cloudwatch_telemetry_interval_seconds: None,
+
+            ingress_ipv4_range: None,
+            dual_stack: None,
+            ingress_ipv6_range: None,
+            advertise_ipv6: None,
+            regional_resources: None,
+        }
+    }
+
+    /// Returns the regions of all nodes, starting with "region".
+    pub fn regions(&self) -> Vec<String> {
+        let mut regions = vec![self.region.clone()];
+        if let Some(regional_resources) = &self.regional_resources {
+            for r in regional_resources.iter() {
+                regions.push(r.region.clone());
+            }
+        }
+        regions
+    }
+
+    /// Returns the region and the ID of the security group of each VPC,
+    /// starting with "region".
+    pub fn security_groups(&self) -> Vec<(String, String)> {
+        let mut security_groups = Vec::new();
+        if let Some(id) = &self.cloudformation_vpc_security_group_id {
+            security_groups.push((self.region.clone(), id.clone()));
+        }
+        for r in self.regional_resources.iter().flatten() {
+            if let Some(id) = &r.cloudformation_vpc_security_group_id {
+                security_groups.push((r.region.clone(), id.clone()));
+            }
+        }
+        security_groups
+    }
+
+    /// Returns the region and the name of each node ASG created so far,
+    /// starting with "region".
+    pub fn node_asg_names(&self) -> Vec<(String, String)> {
+        let mut asg_names: Vec<(String, String)> = [
+            &self.cloudformation_asg_anchor_nodes_logical_id,
+            &self.cloudformation_asg_non_anchor_nodes_logical_id,
+            &self.cloudformation_asg_api_nodes_logical_id,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|name| (self.region.clone(), name.clone()))
+        .collect();
+        for r in self.regional_resources.iter().flatten() {
+            if let Some(name) = &r.cloudformation_asg_non_anchor_nodes_logical_id {
+                asg_names.push((r.region.clone(), name.clone()));
+            }
+        }
+        asg_names
+    }
+}
+
+/// Default IP range for the inbound traffic.
+pub const DEFAULT_INGRESS_IPV4_RANGE: &str = "0.0.0.0/0";
+
+/// Default IPv6 range for the inbound traffic with "Resources.dual_stack".
+pub const DEFAULT_INGRESS_IPV6_RANGE: &str = "::/0";
+
+/// Represents the resources of the non-anchor nodes in a region
+/// other than the primary "Resources.region".
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct RegionalResources {
+    /// AWS region to create resources.
+    /// MUST BE NON-EMPTY.
+    #[serde(default)]
+    pub region: String,
+    /// Number of the non-anchor nodes in this region.
+    #[serde(default)]
+    pub non_anchor_nodes: u32,
+
+    /// Name of the bucket in this region to install the artifacts from,
+    /// so that the nodes download the binaries within the region.
+    /// The shared state (e.g., node discovery) is still in the primary bucket.
+    /// Set to "[primary bucket]-[region]" to match the instance role policy.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub s3_bucket: Option<String>,
+
+    /// EC2 key pair name for SSH access to EC2 instances in this region.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ec2_key_name: Option<String>,
+    /// Only updated after creation.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ec2_key_path: Option<String>,
+
+    /// CloudFormation stack name for VPC.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloudformation_vpc: Option<String>,
+    /// Only updated after creation.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloudformation_vpc_id: Option<String>,
+    /// Only updated after creation.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloudformation_vpc_security_group_id: Option<String>,
+    /// Only updated after creation.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloudformation_vpc_public_subnet_ids: Option<Vec<String>>,
+
+    /// CloudFormation stack name of Auto Scaling Group (ASG)
+    /// for non-anchor nodes in this region.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloudformation_asg_non_anchor_nodes: Option<String>,
+    /// Only updated after creation.
+    /// READ ONLY -- DO NOT SET.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloudformation_asg_non_anchor_nodes_logical_id: Option<String>,
+}
+
+impl RegionalResources {
+    pub fn new(region: &str, non_anchor_nodes: u32) -> Self {
+        Self {
+            region: region.to_string(),
+            non_anchor_nodes,
+            s3_bucket: None,
+            ec2_key_name: None,
+            ec2_key_path: None,
+            cloudformation_vpc: None,
+            cloudformation_vpc_id: None,
+            cloudformation_vpc_security_group_id: None,
+            cloudformation_vpc_public_subnet_ids: None,
+            cloudformation_asg_non_anchor_nodes: None,
+            cloudformation_asg_non_anchor_nodes_logical_id: None,
         }
     }
 }
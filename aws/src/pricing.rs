@@ -0,0 +1,152 @@
+use aws_sdk_pricing::{
+    model::{Filter, FilterType},
+    types::SdkError,
+    Client, Region,
+};
+use aws_types::SdkConfig as AwsSdkConfig;
+use log::info;
+
+use crate::errors::{Error::API, Result};
+
+/// The price list API is only served in "us-east-1" and "ap-south-1",
+/// regardless of the region of the priced resources.
+/// ref. https://docs.aws.amazon.com/awsaccountbilling/latest/aboutv2/using-pelong.html
+pub const PRICING_API_REGION: &str = "us-east-1";
+
+/// Implements AWS Price List manager.
+#[derive(Debug, Clone)]
+pub struct Manager {
+    #[allow(dead_code)]
+    shared_config: AwsSdkConfig,
+    cli: Client,
+}
+
+impl Manager {
+    pub fn new(shared_config: &AwsSdkConfig) -> Self {
+        let cloned = shared_config.clone();
+        let conf = aws_sdk_pricing::config::Builder::from(shared_config)
+            .region(Region::new(PRICING_API_REGION))
+            .build();
+        let cli = Client::from_conf(conf);
+        Self {
+            shared_config: cloned,
+            cli,
+        }
+    }
+
+    /// Fetches the on-demand hourly price of the Linux instance type
+    /// with the shared tenancy. Returns "None" if not offered in the region.
+    pub async fn fetch_instance_hourly_usd(
+        &self,
+        region: &str,
+        instance_type: &str,
+    ) -> Result<Option<f64>> {
+        info!("fetching price of '{}' in '{}'", instance_type, region);
+        let price_list = self
+            .get_products(&[
+                ("regionCode", region),
+                ("instanceType", instance_type),
+                ("operatingSystem", "Linux"),
+                ("tenancy", "Shared"),
+                ("preInstalledSw", "NA"),
+                ("capacitystatus", "Used"),
+            ])
+            .await?;
+        Ok(price_list.iter().find_map(|p| parse_on_demand_usd(p)))
+    }
+
+    /// Fetches the monthly price per GB of the EBS volume type (e.g., "gp3").
+    pub async fn fetch_ebs_gb_month_usd(
+        &self,
+        region: &str,
+        volume_type: &str,
+    ) -> Result<Option<f64>> {
+        info!("fetching price of '{}' volume in '{}'", volume_type, region);
+        let price_list = self
+            .get_products(&[
+                ("regionCode", region),
+                ("productFamily", "Storage"),
+                ("volumeApiName", volume_type),
+            ])
+            .await?;
+        Ok(price_list.iter().find_map(|p| parse_on_demand_usd(p)))
+    }
+
+    /// Returns the JSON-encoded price list items of "AmazonEC2" that match all the fields.
+    async fn get_products(&self, fields: &[(&str, &str)]) -> Result<Vec<String>> {
+        let filters: Vec<Filter> = fields
+            .iter()
+            .map(|(k, v)| {
+                Filter::builder()
+                    .r#type(FilterType::TermMatch)
+                    .field(*k)
+                    .value(*v)
+                    .build()
+            })
+            .collect();
+        let ret = self
+            .cli
+            .get_products()
+            .service_code("AmazonEC2")
+            .set_filters(Some(filters))
+            .max_results(10)
+            .send()
+            .await;
+        match ret {
+            Ok(v) => Ok(v.price_list().unwrap_or_default().to_vec()),
+            Err(e) => Err(API {
+                message: format!("failed get_products {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            }),
+        }
+    }
+}
+
+/// Parses the first on-demand USD price of the price list item.
+/// Returns "None" for the zero prices (e.g., the reserved capacity placeholders).
+pub fn parse_on_demand_usd(price_list_item: &str) -> Option<f64> {
+    let v: serde_json::Value = serde_json::from_str(price_list_item).ok()?;
+    let on_demand = v.get("terms")?.get("OnDemand")?.as_object()?;
+    on_demand
+        .values()
+        .filter_map(|term| term.get("priceDimensions")?.as_object())
+        .flat_map(|dims| dims.values())
+        .filter_map(|dim| dim.get("pricePerUnit")?.get("USD")?.as_str())
+        .filter_map(|usd| usd.parse::<f64>().ok())
+        .find(|usd| *usd > 0.0)
+}
+
+#[inline]
+pub fn is_error_retryable<E>(e: &SdkError<E>) -> bool {
+    match e {
+        SdkError::TimeoutError(_) | SdkError::ResponseError { .. } => true,
+        SdkError::DispatchFailure(e) => e.is_timeout() || e.is_io(),
+        _ => false,
+    }
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- pricing::test_parse_on_demand_usd --exact --show-output
+#[test]
+fn test_parse_on_demand_usd() {
+    let item = r#"{
+  "product": {"attributes": {"instanceType": "c6a.2xlarge", "regionCode": "us-west-2"}},
+  "terms": {
+    "OnDemand": {
+      "ABC.JRTCKXETXF": {
+        "priceDimensions": {
+          "ABC.JRTCKXETXF.6YS6EN2CT7": {
+            "unit": "Hrs",
+            "pricePerUnit": {"USD": "0.3060000000"}
+          }
+        }
+      }
+    }
+  }
+}"#;
+    assert_eq!(parse_on_demand_usd(item), Some(0.306));
+
+    let zero = item.replace("0.3060000000", "0.0000000000");
+    assert_eq!(parse_on_demand_usd(&zero), None);
+    assert_eq!(parse_on_demand_usd(r#"{"terms": {}}"#), None);
+    assert_eq!(parse_on_demand_usd("not json"), None);
+}
@@ -0,0 +1,130 @@
+use aws_sdk_route53::{
+    error::ChangeResourceRecordSetsError,
+    model::{Change, ChangeAction, ChangeBatch, ResourceRecord, ResourceRecordSet, RrType},
+    types::SdkError,
+    Client,
+};
+use aws_types::SdkConfig as AwsSdkConfig;
+use log::{info, warn};
+
+use crate::errors::{Error::API, Result};
+
+/// Implements AWS Route53 manager.
+#[derive(Debug, Clone)]
+pub struct Manager {
+    #[allow(dead_code)]
+    shared_config: AwsSdkConfig,
+    cli: Client,
+}
+
+impl Manager {
+    pub fn new(shared_config: &AwsSdkConfig) -> Self {
+        let cloned = shared_config.clone();
+        let cli = Client::new(shared_config);
+        Self {
+            shared_config: cloned,
+            cli,
+        }
+    }
+
+    /// Creates or updates the A record of the name to the IPv4 address.
+    pub async fn upsert_a_record(
+        &self,
+        hosted_zone_id: &str,
+        name: &str,
+        ipv4: &str,
+        ttl_seconds: i64,
+    ) -> Result<()> {
+        info!(
+            "upserting A record '{}' to {} in '{}'",
+            name, ipv4, hosted_zone_id
+        );
+        self.change_a_record(
+            hosted_zone_id,
+            ChangeAction::Upsert,
+            name,
+            ipv4,
+            ttl_seconds,
+        )
+        .await
+    }
+
+    /// Deletes the A record, which must match the IPv4 address and the TTL.
+    /// The record that does not exist is ignored.
+    pub async fn delete_a_record(
+        &self,
+        hosted_zone_id: &str,
+        name: &str,
+        ipv4: &str,
+        ttl_seconds: i64,
+    ) -> Result<()> {
+        info!("deleting A record '{}' in '{}'", name, hosted_zone_id);
+        match self
+            .change_a_record(
+                hosted_zone_id,
+                ChangeAction::Delete,
+                name,
+                ipv4,
+                ttl_seconds,
+            )
+            .await
+        {
+            Err(API { message, .. }) if message.contains("not found") => {
+                warn!("A record '{}' already deleted", name);
+                Ok(())
+            }
+            ret => ret,
+        }
+    }
+
+    async fn change_a_record(
+        &self,
+        hosted_zone_id: &str,
+        action: ChangeAction,
+        name: &str,
+        ipv4: &str,
+        ttl_seconds: i64,
+    ) -> Result<()> {
+        let record_set = ResourceRecordSet::builder()
+            .name(name)
+            .r#type(RrType::A)
+            .ttl(ttl_seconds)
+            .resource_records(ResourceRecord::builder().value(ipv4).build())
+            .build();
+        let batch = ChangeBatch::builder()
+            .changes(
+                Change::builder()
+                    .action(action)
+                    .resource_record_set(record_set)
+                    .build(),
+            )
+            .build();
+        let ret = self
+            .cli
+            .change_resource_record_sets()
+            .hosted_zone_id(hosted_zone_id)
+            .change_batch(batch)
+            .send()
+            .await;
+        match ret {
+            Ok(_) => Ok(()),
+            Err(e) => Err(API {
+                message: format!("failed change_resource_record_sets {:?}", e),
+                is_retryable: is_error_retryable(&e),
+            }),
+        }
+    }
+}
+
+#[inline]
+pub fn is_error_retryable(e: &SdkError<ChangeResourceRecordSetsError>) -> bool {
+    match e {
+        SdkError::TimeoutError(_) | SdkError::ResponseError { .. } => true,
+        SdkError::DispatchFailure(e) => e.is_timeout() || e.is_io(),
+        // concurrent changes to the same hosted zone (e.g., all anchor nodes on boot)
+        SdkError::ServiceError { err, .. } => {
+            format!("{:?}", err).contains("PriorRequestNotComplete")
+        }
+        _ => false,
+    }
+}
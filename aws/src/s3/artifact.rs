@@ -0,0 +1,671 @@
+use std::{collections::HashMap, fmt, io::SeekFrom, path::Path, sync::Arc};
+
+use aws_sdk_s3::{
+    model::{CompletedMultipartUpload, CompletedPart, ObjectCannedAcl, Part, ServerSideEncryption},
+    types::ByteStream,
+};
+use log::{info, warn};
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+};
+use tokio_stream::StreamExt;
+
+use crate::{
+    errors::{
+        Error::{Other, API},
+        Result,
+    },
+    s3::{is_error_retryable, Manager},
+};
+use utils::humanize;
+
+/// S3 rejects the multipart upload parts smaller than 5 MiB (except the last).
+pub const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+/// S3 allows at most 10,000 parts per multipart upload.
+pub const MAX_PARTS: u64 = 10_000;
+pub const DEFAULT_PART_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Typed S3 object key layout shared by avalanche-ops and avalanched,
+/// where every key is prefixed with the spec ID.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ObjectKey {
+    /// The avalanche-ops spec file.
+    Spec(String),
+    /// Install artifact (e.g., avalanchego binary, plugin VM) by its name.
+    Artifact(String, String),
+    /// Database backup by its file name.
+    Backup(String, String),
+}
+
+impl ObjectKey {
+    pub fn encode(&self) -> String {
+        match self {
+            ObjectKey::Spec(id) => format!("{}/avalanche-ops.config.yaml", id),
+            ObjectKey::Artifact(id, name) => format!("{}/{}", artifacts_prefix(id), name),
+            ObjectKey::Backup(id, name) => format!("{}/{}", backups_prefix(id), name),
+        }
+    }
+}
+
+impl fmt::Display for ObjectKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.encode())
+    }
+}
+
+/// Returns the prefix of all install artifacts (without the trailing slash).
+pub fn artifacts_prefix(id: &str) -> String {
+    format!("{}/install", id)
+}
+
+/// Returns the prefix of all database backups (without the trailing slash).
+pub fn backups_prefix(id: &str) -> String {
+    format!("{}/backups", id)
+}
+
+/// Bytes transferred so far, passed to the progress callback.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Progress {
+    pub transferred_bytes: u64,
+    pub total_bytes: u64,
+}
+
+impl Progress {
+    pub fn percent(&self) -> f64 {
+        if self.total_bytes == 0 {
+            return 100.0;
+        }
+        self.transferred_bytes as f64 * 100.0 / self.total_bytes as f64
+    }
+}
+
+pub type ProgressFn = Arc<dyn Fn(Progress) + Send + Sync>;
+
+/// Options for the multipart transfers.
+#[derive(Clone)]
+pub struct TransferOptions {
+    /// Size of each multipart upload part and each ranged download request.
+    /// Raised as needed to stay within "MAX_PARTS".
+    pub part_size: u64,
+    /// KMS key to encrypt the uploaded object with (SSE-KMS).
+    /// If "None", the bucket default encryption applies.
+    pub kms_key_id: Option<String>,
+    /// Called after every transferred part.
+    pub progress: Option<ProgressFn>,
+}
+
+impl Default for TransferOptions {
+    fn default() -> Self {
+        Self {
+            part_size: DEFAULT_PART_SIZE,
+            kms_key_id: None,
+            progress: None,
+        }
+    }
+}
+
+impl fmt::Debug for TransferOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TransferOptions")
+            .field("part_size", &self.part_size)
+            .field("kms_key_id", &self.kms_key_id)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+impl TransferOptions {
+    fn report(&self, transferred_bytes: u64, total_bytes: u64) {
+        if let Some(f) = &self.progress {
+            f(Progress {
+                transferred_bytes,
+                total_bytes,
+            });
+        }
+    }
+}
+
+/// Returns the part size for the object size, which is at least "MIN_PART_SIZE"
+/// and large enough to fit the object in "MAX_PARTS".
+pub fn part_size(size: u64, requested: u64) -> u64 {
+    let min_for_size = size.div_ceil(MAX_PARTS);
+    requested.max(MIN_PART_SIZE).max(min_for_size)
+}
+
+/// Splits the object into the "(part number, offset, length)" of each part,
+/// where the part number starts from 1.
+pub fn plan_parts(size: u64, part_size: u64) -> Vec<(i32, u64, u64)> {
+    let mut parts = Vec::new();
+    let mut offset = 0;
+    while offset < size {
+        let len = part_size.min(size - offset);
+        parts.push((parts.len() as i32 + 1, offset, len));
+        offset += len;
+    }
+    parts
+}
+
+/// Returns the parts of the previous upload that can be reused,
+/// which must have the same size as planned and the ETag of the same content
+/// (i.e., the hex-encoded MD5 digest of the local part in "local_md5s").
+/// Otherwise, the local file must have changed, so nothing is reused.
+/// With SSE-KMS, the ETag is not the MD5 digest, so every part is uploaded again.
+pub fn reusable_parts(
+    planned: &[(i32, u64, u64)],
+    uploaded: &[Part],
+    local_md5s: &HashMap<i32, String>,
+) -> Vec<CompletedPart> {
+    let mut reused = Vec::new();
+    for part in uploaded {
+        let matched = planned
+            .iter()
+            .any(|(n, _, len)| *n == part.part_number && *len as i64 == part.size);
+        let same_content = match (&part.e_tag, local_md5s.get(&part.part_number)) {
+            (Some(e_tag), Some(md5)) => e_tag.trim_matches('"') == md5,
+            _ => false,
+        };
+        if !matched || !same_content {
+            return Vec::new();
+        }
+        reused.push(
+            CompletedPart::builder()
+                .part_number(part.part_number)
+                .e_tag(part.e_tag.clone().unwrap())
+                .build(),
+        );
+    }
+    reused
+}
+
+async fn read_part(file: &mut File, offset: u64, len: u64) -> Result<Vec<u8>> {
+    let mut buf = vec![0u8; len as usize];
+    file.seek(SeekFrom::Start(offset))
+        .await
+        .map_err(|e| Other {
+            message: format!("failed File.seek {}", e),
+            is_retryable: false,
+        })?;
+    file.read_exact(&mut buf).await.map_err(|e| Other {
+        message: format!("failed File.read_exact {}", e),
+        is_retryable: false,
+    })?;
+    Ok(buf)
+}
+
+impl Manager {
+    /// Uploads a large artifact (e.g., avalanchego binary, database backup)
+    /// with the multipart upload, one part at a time so the memory usage
+    /// is bounded by the part size.
+    /// If a previous upload of the same key was interrupted,
+    /// it resumes by reusing the parts that were already uploaded.
+    pub async fn put_artifact(
+        &self,
+        file_path: &str,
+        s3_bucket: &str,
+        s3_key: &str,
+        opts: &TransferOptions,
+    ) -> Result<()> {
+        let size = fs::metadata(file_path)
+            .await
+            .map_err(|e| Other {
+                message: format!("failed metadata {} ({})", file_path, e),
+                is_retryable: false,
+            })?
+            .len();
+        let part_size = part_size(size, opts.part_size);
+        info!(
+            "starting put_artifact '{}' (size {}, part size {}) to 's3://{}/{}'",
+            file_path,
+            humanize::bytes(size as f64),
+            humanize::bytes(part_size as f64),
+            s3_bucket,
+            s3_key
+        );
+        if size <= part_size {
+            return self
+                .put_artifact_single(file_path, size, s3_bucket, s3_key, opts)
+                .await;
+        }
+
+        let mut file = File::open(file_path).await.map_err(|e| Other {
+            message: format!("failed File::open {} ({})", file_path, e),
+            is_retryable: false,
+        })?;
+        let planned = plan_parts(size, part_size);
+        let (upload_id, mut completed) = match self.find_multipart_upload(s3_bucket, s3_key).await?
+        {
+            Some(upload_id) => {
+                let uploaded = self.list_parts(s3_bucket, s3_key, &upload_id).await?;
+                let mut local_md5s = HashMap::new();
+                for (part_number, offset, len) in planned.iter() {
+                    if uploaded.iter().any(|p| p.part_number == *part_number) {
+                        let buf = read_part(&mut file, *offset, *len).await?;
+                        local_md5s.insert(*part_number, format!("{:x}", md5::compute(&buf)));
+                    }
+                }
+                let reused = reusable_parts(&planned, &uploaded, &local_md5s);
+                info!(
+                    "resuming multipart upload '{}' with {} of {} parts",
+                    upload_id,
+                    reused.len(),
+                    planned.len()
+                );
+                (upload_id, reused)
+            }
+            None => (
+                self.create_multipart_upload(s3_bucket, s3_key, opts)
+                    .await?,
+                Vec::new(),
+            ),
+        };
+
+        let mut transferred: u64 = 0;
+        for (part_number, offset, len) in planned.iter() {
+            if completed.iter().any(|p| p.part_number == *part_number) {
+                transferred += len;
+                opts.report(transferred, size);
+                continue;
+            }
+
+            let buf = read_part(&mut file, *offset, *len).await?;
+
+            let out = self
+                .cli
+                .upload_part()
+                .bucket(s3_bucket)
+                .key(s3_key)
+                .upload_id(&upload_id)
+                .part_number(*part_number)
+                .body(ByteStream::from(buf))
+                .send()
+                .await
+                .map_err(|e| API {
+                    message: format!("failed upload_part {} ({})", part_number, e),
+                    is_retryable: is_error_retryable(&e),
+                })?;
+            let e_tag = match out.e_tag {
+                Some(v) => v,
+                None => {
+                    return Err(API {
+                        message: format!("upload_part {} returned no ETag", part_number),
+                        is_retryable: true,
+                    });
+                }
+            };
+            completed.push(
+                CompletedPart::builder()
+                    .part_number(*part_number)
+                    .e_tag(e_tag)
+                    .build(),
+            );
+
+            transferred += len;
+            opts.report(transferred, size);
+        }
+
+        completed.sort_by_key(|p| p.part_number);
+        self.cli
+            .complete_multipart_upload()
+            .bucket(s3_bucket)
+            .key(s3_key)
+            .upload_id(&upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(completed))
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(|e| API {
+                message: format!("failed complete_multipart_upload {}", e),
+                is_retryable: is_error_retryable(&e),
+            })?;
+        info!("uploaded 's3://{}/{}'", s3_bucket, s3_key);
+
+        Ok(())
+    }
+
+    async fn put_artifact_single(
+        &self,
+        file_path: &str,
+        size: u64,
+        s3_bucket: &str,
+        s3_key: &str,
+        opts: &TransferOptions,
+    ) -> Result<()> {
+        let byte_stream = ByteStream::from_path(Path::new(file_path))
+            .await
+            .map_err(|e| Other {
+                message: format!("failed ByteStream::from_path {}", e),
+                is_retryable: false,
+            })?;
+        let mut req = self
+            .cli
+            .put_object()
+            .bucket(s3_bucket)
+            .key(s3_key)
+            .body(byte_stream)
+            .acl(ObjectCannedAcl::Private);
+        if let Some(kms_key_id) = &opts.kms_key_id {
+            req = req
+                .server_side_encryption(ServerSideEncryption::AwsKms)
+                .ssekms_key_id(kms_key_id);
+        }
+        req.send().await.map_err(|e| API {
+            message: format!("failed put_object {}", e),
+            is_retryable: is_error_retryable(&e),
+        })?;
+        opts.report(size, size);
+
+        Ok(())
+    }
+
+    async fn create_multipart_upload(
+        &self,
+        s3_bucket: &str,
+        s3_key: &str,
+        opts: &TransferOptions,
+    ) -> Result<String> {
+        let mut req = self
+            .cli
+            .create_multipart_upload()
+            .bucket(s3_bucket)
+            .key(s3_key)
+            .acl(ObjectCannedAcl::Private);
+        if let Some(kms_key_id) = &opts.kms_key_id {
+            req = req
+                .server_side_encryption(ServerSideEncryption::AwsKms)
+                .ssekms_key_id(kms_key_id);
+        }
+        let out = req.send().await.map_err(|e| API {
+            message: format!("failed create_multipart_upload {}", e),
+            is_retryable: is_error_retryable(&e),
+        })?;
+        match out.upload_id {
+            Some(v) => Ok(v),
+            None => Err(API {
+                message: String::from("create_multipart_upload returned no upload ID"),
+                is_retryable: false,
+            }),
+        }
+    }
+
+    /// Returns the latest in-progress multipart upload of the exact key, if any.
+    async fn find_multipart_upload(&self, s3_bucket: &str, s3_key: &str) -> Result<Option<String>> {
+        let out = self
+            .cli
+            .list_multipart_uploads()
+            .bucket(s3_bucket)
+            .prefix(s3_key)
+            .send()
+            .await
+            .map_err(|e| API {
+                message: format!("failed list_multipart_uploads {}", e),
+                is_retryable: is_error_retryable(&e),
+            })?;
+        let mut uploads: Vec<_> = out
+            .uploads
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|u| u.key.as_deref() == Some(s3_key) && u.upload_id.is_some())
+            .collect();
+        uploads.sort_by_key(|u| u.initiated.map(|t| t.as_nanos()));
+        Ok(uploads.pop().and_then(|u| u.upload_id))
+    }
+
+    async fn list_parts(
+        &self,
+        s3_bucket: &str,
+        s3_key: &str,
+        upload_id: &str,
+    ) -> Result<Vec<Part>> {
+        let mut parts = Vec::new();
+        let mut marker: Option<String> = None;
+        loop {
+            let out = self
+                .cli
+                .list_parts()
+                .bucket(s3_bucket)
+                .key(s3_key)
+                .upload_id(upload_id)
+                .set_part_number_marker(marker.clone())
+                .send()
+                .await
+                .map_err(|e| API {
+                    message: format!("failed list_parts {}", e),
+                    is_retryable: is_error_retryable(&e),
+                })?;
+            parts.extend(out.parts.unwrap_or_default());
+            if !out.is_truncated {
+                break;
+            }
+            marker = out.next_part_number_marker;
+            if marker.is_none() {
+                break;
+            }
+        }
+        Ok(parts)
+    }
+
+    /// Downloads a large artifact with the ranged requests, one part at a time.
+    /// The data is written to "[file_path].part" and renamed on completion,
+    /// so an interrupted download resumes from where it left off,
+    /// as long as the object has not changed since (same ETag).
+    pub async fn get_artifact(
+        &self,
+        s3_bucket: &str,
+        s3_key: &str,
+        file_path: &str,
+        opts: &TransferOptions,
+    ) -> Result<()> {
+        if Path::new(file_path).exists() {
+            return Err(Other {
+                message: format!("file path {} already exists", file_path),
+                is_retryable: false,
+            });
+        }
+
+        let head_output = self
+            .cli
+            .head_object()
+            .bucket(s3_bucket)
+            .key(s3_key)
+            .send()
+            .await
+            .map_err(|e| API {
+                message: format!("failed head_object {}", e),
+                is_retryable: is_error_retryable(&e),
+            })?;
+        let size = head_output.content_length().max(0) as u64;
+        let e_tag = head_output.e_tag().unwrap_or("").to_string();
+
+        let part_path = format!("{}.part", file_path);
+        let e_tag_path = format!("{}.part.etag", file_path);
+        let mut offset = match fs::read_to_string(&e_tag_path).await {
+            Ok(prev) if !e_tag.is_empty() && prev == e_tag => {
+                match fs::metadata(&part_path).await {
+                    Ok(meta) if meta.len() <= size => meta.len(),
+                    _ => 0,
+                }
+            }
+            _ => 0,
+        };
+        if offset == 0 {
+            fs::write(&e_tag_path, &e_tag).await.map_err(|e| Other {
+                message: format!("failed fs::write {} ({})", e_tag_path, e),
+                is_retryable: false,
+            })?;
+        } else {
+            info!(
+                "resuming get_artifact 's3://{}/{}' from {}",
+                s3_bucket,
+                s3_key,
+                humanize::bytes(offset as f64)
+            );
+        }
+        info!(
+            "starting get_artifact 's3://{}/{}' (size {}) to '{}'",
+            s3_bucket,
+            s3_key,
+            humanize::bytes(size as f64),
+            file_path
+        );
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(offset == 0)
+            .open(&part_path)
+            .await
+            .map_err(|e| Other {
+                message: format!("failed to open {} ({})", part_path, e),
+                is_retryable: false,
+            })?;
+        file.seek(SeekFrom::Start(offset))
+            .await
+            .map_err(|e| Other {
+                message: format!("failed File.seek {}", e),
+                is_retryable: false,
+            })?;
+        opts.report(offset, size);
+
+        let part_size = part_size(size, opts.part_size);
+        while offset < size {
+            let end = (offset + part_size).min(size) - 1;
+            let mut req = self
+                .cli
+                .get_object()
+                .bucket(s3_bucket)
+                .key(s3_key)
+                .range(format!("bytes={}-{}", offset, end));
+            if !e_tag.is_empty() {
+                // fails if the object is overwritten in the middle of the download
+                req = req.if_match(&e_tag);
+            }
+            let mut output = req.send().await.map_err(|e| API {
+                message: format!("failed get_object {}", e),
+                is_retryable: is_error_retryable(&e),
+            })?;
+            while let Some(d) = output.body.try_next().await.map_err(|e| Other {
+                message: format!("failed ByteStream::try_next {}", e),
+                is_retryable: true,
+            })? {
+                file.write_all(&d).await.map_err(|e| Other {
+                    message: format!("failed File.write_all {}", e),
+                    is_retryable: false,
+                })?;
+                offset += d.len() as u64;
+            }
+            file.flush().await.map_err(|e| Other {
+                message: format!("failed File.flush {}", e),
+                is_retryable: false,
+            })?;
+            opts.report(offset, size);
+        }
+        file.sync_all().await.map_err(|e| Other {
+            message: format!("failed File.sync_all {}", e),
+            is_retryable: false,
+        })?;
+
+        fs::rename(&part_path, file_path).await.map_err(|e| Other {
+            message: format!("failed fs::rename {} ({})", part_path, e),
+            is_retryable: false,
+        })?;
+        if let Err(e) = fs::remove_file(&e_tag_path).await {
+            warn!("failed to remove {} ({})", e_tag_path, e);
+        }
+        info!(
+            "downloaded 's3://{}/{}' to '{}'",
+            s3_bucket, s3_key, file_path
+        );
+
+        Ok(())
+    }
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- s3::artifact::test_object_key --exact --show-output
+#[test]
+fn test_object_key() {
+    assert_eq!(
+        ObjectKey::Spec(String::from("my-id")).encode(),
+        "my-id/avalanche-ops.config.yaml"
+    );
+    assert_eq!(
+        ObjectKey::Artifact(String::from("my-id"), String::from("avalanche.zstd")).to_string(),
+        "my-id/install/avalanche.zstd"
+    );
+    assert_eq!(
+        ObjectKey::Backup(String::from("my-id"), String::from("db.tar.gz")).encode(),
+        "my-id/backups/db.tar.gz"
+    );
+    assert_eq!(artifacts_prefix("my-id"), "my-id/install");
+    assert_eq!(backups_prefix("my-id"), "my-id/backups");
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- s3::artifact::test_plan_parts --exact --show-output
+#[test]
+fn test_plan_parts() {
+    assert_eq!(part_size(100, 1), MIN_PART_SIZE);
+    assert_eq!(part_size(100, DEFAULT_PART_SIZE), DEFAULT_PART_SIZE);
+    // 1 TiB does not fit in 10,000 parts of 16 MiB
+    let tib = 1024 * 1024 * 1024 * 1024;
+    assert_eq!(part_size(tib, DEFAULT_PART_SIZE), tib.div_ceil(MAX_PARTS));
+    assert!(plan_parts(tib, part_size(tib, DEFAULT_PART_SIZE)).len() as u64 <= MAX_PARTS);
+
+    assert!(plan_parts(0, 10).is_empty());
+    assert_eq!(plan_parts(10, 10), vec![(1, 0, 10)]);
+    assert_eq!(
+        plan_parts(25, 10),
+        vec![(1, 0, 10), (2, 10, 10), (3, 20, 5)]
+    );
+
+    let planned = plan_parts(25, 10);
+    let md5 = |d: &[u8]| format!("{:x}", md5::compute(d));
+    let part = |n: i32, size: i64, d: &[u8]| {
+        Part::builder()
+            .part_number(n)
+            .size(size)
+            // S3 quotes the ETag
+            .e_tag(format!("\"{}\"", md5(d)))
+            .build()
+    };
+    let local_md5s: HashMap<i32, String> = [(1, md5(b"part-1")), (3, md5(b"part-3"))]
+        .into_iter()
+        .collect();
+    let reused = reusable_parts(
+        &planned,
+        &[part(1, 10, b"part-1"), part(3, 5, b"part-3")],
+        &local_md5s,
+    );
+    assert_eq!(
+        reused.iter().map(|p| p.part_number).collect::<Vec<i32>>(),
+        vec![1, 3]
+    );
+    assert_eq!(
+        reused[1].e_tag(),
+        Some(format!("\"{}\"", md5(b"part-3")).as_str())
+    );
+
+    // the local file has changed since the interrupted upload
+    assert!(reusable_parts(
+        &planned,
+        &[part(1, 10, b"part-1"), part(3, 7, b"part-3")],
+        &local_md5s
+    )
+    .is_empty());
+    assert!(reusable_parts(&planned, &[part(4, 10, b"part-4")], &local_md5s).is_empty());
+    // same size, but different content
+    assert!(reusable_parts(
+        &planned,
+        &[part(1, 10, b"part-1"), part(3, 5, b"changed")],
+        &local_md5s
+    )
+    .is_empty());
+
+    let progress = Progress {
+        transferred_bytes: 5,
+        total_bytes: 20,
+    };
+    assert_eq!(progress.percent(), 25.0);
+}
@@ -1,7 +1,12 @@
-use std::{fs, path::Path, sync::Arc};
+pub mod artifact;
+
+use std::{fs, io::Write, path::Path, sync::Arc};
 
 use aws_sdk_s3::{
-    error::{CreateBucketError, CreateBucketErrorKind, DeleteBucketError},
+    error::{
+        CreateBucketError, CreateBucketErrorKind, DeleteBucketError, HeadBucketError,
+        HeadBucketErrorKind,
+    },
     model::{
         BucketCannedAcl, BucketLocationConstraint, CreateBucketConfiguration, Delete, Object,
         ObjectCannedAcl, ObjectIdentifier, PublicAccessBlockConfiguration, ServerSideEncryption,
@@ -19,7 +24,7 @@ use crate::errors::{
     Error::{Other, API},
     Result,
 };
-use utils::humanize;
+use utils::{compress, humanize};
 
 /// Implements AWS S3 manager.
 #[derive(Debug, Clone)]
@@ -146,6 +151,23 @@ impl Manager {
         Ok(())
     }
 
+    /// Returns true if the S3 bucket exists and is accessible.
+    pub async fn bucket_exists(&self, s3_bucket: &str) -> Result<bool> {
+        let ret = self.cli.head_bucket().bucket(s3_bucket).send().await;
+        match ret {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                if is_error_head_bucket_not_found(&e) {
+                    return Ok(false);
+                }
+                Err(API {
+                    message: format!("failed head_bucket {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                })
+            }
+        }
+    }
+
     /// Deletes objects by "prefix".
     /// If "prefix" is "None", empties a S3 bucket, deleting all files.
     /// ref. https://github.com/awslabs/aws-sdk-rust/blob/main/examples/s3/src/bin/delete-objects.rs
@@ -432,6 +454,85 @@ impl Manager {
 
         Ok(())
     }
+
+    /// Downloads the compressed object and decompresses the byte stream
+    /// into the file as it arrives, without the temporary compressed file.
+    /// Fails if the decompressed size exceeds "opts.max_unpacked_bytes".
+    /// Returns the decompressed size.
+    pub async fn get_object_unpacked(
+        &self,
+        s3_bucket: &str,
+        s3_key: &str,
+        file_path: &str,
+        dec: compress::Decoder,
+        opts: &compress::StreamOptions,
+    ) -> Result<u64> {
+        if Path::new(file_path).exists() {
+            return Err(Other {
+                message: format!("file path {} already exists", file_path),
+                is_retryable: false,
+            });
+        }
+
+        info!(
+            "starting get_object_unpacked 's3://{}/{}' ({}) to '{}'",
+            s3_bucket, s3_key, dec, file_path
+        );
+        let mut output = self
+            .cli
+            .get_object()
+            .bucket(s3_bucket)
+            .key(s3_key)
+            .send()
+            .await
+            .map_err(|e| API {
+                message: format!("failed get_object {}", e),
+                is_retryable: is_error_retryable(&e),
+            })?;
+
+        let file = fs::File::create(file_path).map_err(|e| Other {
+            message: format!("failed File::create {}", e),
+            is_retryable: false,
+        })?;
+        let mut w = compress::UnpackWriter::new(file, dec, opts).map_err(|e| Other {
+            message: format!("failed UnpackWriter::new {}", e),
+            is_retryable: false,
+        })?;
+        let unpacked = async {
+            while let Some(d) = output.body.try_next().await.map_err(|e| Other {
+                message: format!("failed ByteStream::try_next {}", e),
+                is_retryable: true,
+            })? {
+                w.write_all(&d).map_err(|e| Other {
+                    message: format!("failed UnpackWriter.write_all {}", e),
+                    is_retryable: false,
+                })?;
+            }
+            w.finish().map_err(|e| Other {
+                message: format!("failed UnpackWriter.finish {}", e),
+                is_retryable: false,
+            })
+        }
+        .await;
+
+        // do not leave the partially unpacked file (e.g., exceeded limit)
+        match unpacked {
+            Ok(size) => {
+                info!(
+                    "unpacked 's3://{}/{}' to '{}' (size {})",
+                    s3_bucket,
+                    s3_key,
+                    file_path,
+                    humanize::bytes(size as f64)
+                );
+                Ok(size)
+            }
+            Err(e) => {
+                let _ = fs::remove_file(file_path);
+                Err(e)
+            }
+        }
+    }
 }
 
 #[inline]
@@ -468,6 +569,16 @@ fn is_error_bucket_does_not_exist(e: &SdkError<DeleteBucketError>) -> bool {
     }
 }
 
+#[inline]
+fn is_error_head_bucket_not_found(e: &SdkError<HeadBucketError>) -> bool {
+    match e {
+        SdkError::ServiceError { err, .. } => {
+            matches!(err.kind, HeadBucketErrorKind::NotFound(_))
+        }
+        _ => false,
+    }
+}
+
 #[test]
 fn test_append_slash() {
     let s = "hello";
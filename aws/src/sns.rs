@@ -0,0 +1,63 @@
+use aws_sdk_sns::{types::SdkError, Client};
+use aws_types::SdkConfig as AwsSdkConfig;
+use log::info;
+
+use crate::errors::{Error::API, Result};
+
+/// Maximum length of the SNS message subject.
+/// ref. https://docs.aws.amazon.com/sns/latest/api/API_Publish.html
+pub const MAX_SUBJECT_LEN: usize = 100;
+
+/// Implements AWS SNS manager.
+#[derive(Debug, Clone)]
+pub struct Manager {
+    #[allow(dead_code)]
+    shared_config: AwsSdkConfig,
+    cli: Client,
+}
+
+impl Manager {
+    pub fn new(shared_config: &AwsSdkConfig) -> Self {
+        let cloned = shared_config.clone();
+        let cli = Client::new(shared_config);
+        Self {
+            shared_config: cloned,
+            cli,
+        }
+    }
+
+    /// Publishes the message to the topic, returning the message ID.
+    /// The subject is truncated to the SNS limit.
+    pub async fn publish(&self, topic_arn: &str, subject: &str, message: &str) -> Result<String> {
+        info!("publishing {}-byte message to {}", message.len(), topic_arn);
+        let subject: String = subject.chars().take(MAX_SUBJECT_LEN).collect();
+        let ret = self
+            .cli
+            .publish()
+            .topic_arn(topic_arn)
+            .subject(subject)
+            .message(message)
+            .send()
+            .await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed publish {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+
+        Ok(resp.message_id().unwrap_or("").to_string())
+    }
+}
+
+#[inline]
+pub fn is_error_retryable<E>(e: &SdkError<E>) -> bool {
+    match e {
+        SdkError::TimeoutError(_) | SdkError::ResponseError { .. } => true,
+        SdkError::DispatchFailure(e) => e.is_timeout() || e.is_io(),
+        _ => false,
+    }
+}
@@ -0,0 +1,252 @@
+use std::time::{Duration, Instant};
+
+use aws_sdk_ssm::{model::CommandInvocationStatus, types::SdkError, Client};
+use aws_types::SdkConfig as AwsSdkConfig;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+
+use crate::errors::{
+    Error::{Other, API},
+    Result,
+};
+
+/// Runs the shell commands on the Linux instances.
+/// ref. https://docs.aws.amazon.com/systems-manager/latest/userguide/documents-command-ssm-plugin-reference.html
+pub const DOCUMENT_RUN_SHELL_SCRIPT: &str = "AWS-RunShellScript";
+
+/// SendCommand accepts up to 50 instance IDs per request.
+pub const MAX_INSTANCE_IDS_PER_COMMAND: usize = 50;
+
+/// Represents the result of the command on each instance.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct CommandInvocation {
+    pub instance_id: String,
+    /// e.g., "Pending", "InProgress", "Success", "Failed", "TimedOut".
+    pub status: String,
+    /// Exit code of the shell script, -1 if not yet finished.
+    pub response_code: i32,
+    /// Truncated to the first 24,000 characters by SSM.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub output: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+impl CommandInvocation {
+    /// Returns "true" once the status is terminal.
+    pub fn is_finished(&self) -> bool {
+        !matches!(
+            self.status.as_str(),
+            "Pending" | "InProgress" | "Delayed" | "Cancelling"
+        )
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.status == CommandInvocationStatus::Success.as_str()
+    }
+}
+
+/// Implements AWS SSM manager.
+#[derive(Debug, Clone)]
+pub struct Manager {
+    #[allow(dead_code)]
+    shared_config: AwsSdkConfig,
+    cli: Client,
+}
+
+impl Manager {
+    pub fn new(shared_config: &AwsSdkConfig) -> Self {
+        let cloned = shared_config.clone();
+        let cli = Client::new(shared_config);
+        Self {
+            shared_config: cloned,
+            cli,
+        }
+    }
+
+    /// Sends the shell commands to the instances, and returns the command ID.
+    /// The commands run in order, and stop at the first failure.
+    pub async fn send_shell_commands(
+        &self,
+        instance_ids: &[String],
+        commands: &[String],
+        comment: &str,
+        timeout_seconds: i32,
+    ) -> Result<String> {
+        if instance_ids.is_empty() || instance_ids.len() > MAX_INSTANCE_IDS_PER_COMMAND {
+            return Err(Other {
+                message: format!(
+                    "expected 1 to {} instance IDs, got {}",
+                    MAX_INSTANCE_IDS_PER_COMMAND,
+                    instance_ids.len()
+                ),
+                is_retryable: false,
+            });
+        }
+        info!(
+            "sending {} command(s) to {} instance(s) ({})",
+            commands.len(),
+            instance_ids.len(),
+            comment
+        );
+
+        let mut script = vec![String::from("set -eu")];
+        script.extend(commands.iter().cloned());
+        let ret = self
+            .cli
+            .send_command()
+            .document_name(DOCUMENT_RUN_SHELL_SCRIPT)
+            .set_instance_ids(Some(instance_ids.to_vec()))
+            .parameters("commands", script)
+            .parameters("executionTimeout", vec![timeout_seconds.to_string()])
+            .comment(comment)
+            .send()
+            .await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(API {
+                    message: format!("failed send_command {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+
+        match resp.command().and_then(|c| c.command_id()) {
+            Some(v) => {
+                info!("sent command '{}'", v);
+                Ok(v.to_string())
+            }
+            None => Err(API {
+                message: String::from("empty command ID from send_command"),
+                is_retryable: false,
+            }),
+        }
+    }
+
+    /// Fetches the result of the command on the instance.
+    /// Returns "None" if the invocation is not yet created.
+    pub async fn get_command_invocation(
+        &self,
+        command_id: &str,
+        instance_id: &str,
+    ) -> Result<Option<CommandInvocation>> {
+        let ret = self
+            .cli
+            .get_command_invocation()
+            .command_id(command_id)
+            .instance_id(instance_id)
+            .send()
+            .await;
+        let resp = match ret {
+            Ok(v) => v,
+            Err(e) => {
+                // eventually consistent right after "send_command"
+                if format!("{:?}", e).contains("InvocationDoesNotExist") {
+                    return Ok(None);
+                }
+                return Err(API {
+                    message: format!("failed get_command_invocation {:?}", e),
+                    is_retryable: is_error_retryable(&e),
+                });
+            }
+        };
+
+        Ok(Some(CommandInvocation {
+            instance_id: instance_id.to_string(),
+            status: resp.status().map_or("Pending", |s| s.as_str()).to_string(),
+            response_code: resp.response_code(),
+            output: resp.standard_output_content().unwrap_or("").to_string(),
+            error: resp.standard_error_content().unwrap_or("").to_string(),
+        }))
+    }
+
+    /// Polls the command until all instances finish, or the timeout elapses.
+    /// The instances that have not finished are returned with their last status.
+    pub async fn poll_command(
+        &self,
+        command_id: &str,
+        instance_ids: &[String],
+        timeout: Duration,
+        interval: Duration,
+    ) -> Result<Vec<CommandInvocation>> {
+        let start = Instant::now();
+        let mut invocations: Vec<CommandInvocation> = instance_ids
+            .iter()
+            .map(|id| CommandInvocation {
+                instance_id: id.clone(),
+                status: String::from("Pending"),
+                response_code: -1,
+                output: String::new(),
+                error: String::new(),
+            })
+            .collect();
+
+        loop {
+            for inv in invocations.iter_mut().filter(|v| !v.is_finished()) {
+                match self
+                    .get_command_invocation(command_id, &inv.instance_id)
+                    .await
+                {
+                    Ok(Some(v)) => *inv = v,
+                    Ok(None) => {}
+                    Err(e) => {
+                        if !e.is_retryable() {
+                            return Err(e);
+                        }
+                        warn!("retriable get_command_invocation error {}", e.message());
+                    }
+                }
+            }
+
+            let finished = invocations.iter().filter(|v| v.is_finished()).count();
+            info!(
+                "command '{}' finished on {}/{} instance(s) (took {:?})",
+                command_id,
+                finished,
+                invocations.len(),
+                start.elapsed()
+            );
+            if finished == invocations.len() || start.elapsed() > timeout {
+                break;
+            }
+            sleep(interval).await;
+        }
+
+        Ok(invocations)
+    }
+}
+
+#[inline]
+pub fn is_error_retryable<E>(e: &SdkError<E>) -> bool {
+    match e {
+        SdkError::TimeoutError(_) | SdkError::ResponseError { .. } => true,
+        SdkError::DispatchFailure(e) => e.is_timeout() || e.is_io(),
+        _ => false,
+    }
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- ssm::test_command_invocation --exact --show-output
+#[test]
+fn test_command_invocation() {
+    let mut inv = CommandInvocation {
+        instance_id: String::from("i-123123"),
+        status: String::from("InProgress"),
+        response_code: -1,
+        output: String::new(),
+        error: String::new(),
+    };
+    assert!(!inv.is_finished());
+    assert!(!inv.is_success());
+
+    inv.status = String::from("Success");
+    inv.response_code = 0;
+    assert!(inv.is_finished());
+    assert!(inv.is_success());
+
+    inv.status = String::from("TimedOut");
+    assert!(inv.is_finished());
+    assert!(!inv.is_success());
+}
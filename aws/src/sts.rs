@@ -62,6 +62,42 @@ impl Identity {
             user_id: String::from(user_id),
         }
     }
+
+    /// Returns the IAM ARN of the caller to name in the resource policies,
+    /// the role ARN for the assumed-role session ARN
+    /// (e.g., "arn:aws:sts::123:assumed-role/a/session" to "arn:aws:iam::123:role/a"),
+    /// since the session ARN only matches that one session.
+    /// The session ARN does not carry the role path, so the policy naming
+    /// the role with a path is rejected as the unknown principal.
+    /// ref. https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_policies_elements_principal.html#principal-role-session
+    pub fn principal_arn(&self) -> String {
+        let fields: Vec<&str> = self.role_arn.splitn(6, ':').collect();
+        if fields.len() == 6 && fields[2] == "sts" {
+            if let Some(rest) = fields[5].strip_prefix("assumed-role/") {
+                if let Some((role_name, _session)) = rest.split_once('/') {
+                    return format!("arn:{}:iam::{}:role/{}", fields[1], fields[4], role_name);
+                }
+            }
+        }
+        self.role_arn.clone()
+    }
+}
+
+/// RUST_LOG=debug cargo test --package aws --lib -- sts::test_principal_arn --exact --show-output
+#[test]
+fn test_principal_arn() {
+    let identity = Identity::new(
+        "123",
+        "arn:aws-cn:sts::123:assumed-role/operator/session-1",
+        "AROA:session-1",
+    );
+    assert_eq!(
+        identity.principal_arn(),
+        "arn:aws-cn:iam::123:role/operator"
+    );
+
+    let user = Identity::new("123", "arn:aws:iam::123:user/op", "AIDA");
+    assert_eq!(user.principal_arn(), "arn:aws:iam::123:user/op");
 }
 
 #[inline]
@@ -10,7 +10,7 @@ use log::info;
 use num_bigint::BigInt;
 use serde::{Deserialize, Serialize};
 
-use utils::big_int;
+use utils::{big_int, prefix};
 
 /// ref. https://pkg.go.dev/github.com/ava-labs/subnet-evm/core#Genesis
 /// ref. https://pkg.go.dev/github.com/ava-labs/subnet-evm/params#ChainConfig
@@ -69,6 +69,50 @@ pub struct Genesis {
 /// ref. https://www.rapidtables.com/convert/number/hex-to-decimal.html
 pub const DEFAULT_INITIAL_AMOUNT: &str = "0x52B7D2DCC80CD2E4000000";
 
+/// One AVAX in the EVM denomination (10^18 wei).
+pub const WEI_PER_AVAX: u64 = 1_000_000_000_000_000_000;
+
+/// Chain IDs of the C-chain, which the subnet must not reuse.
+/// ref. https://github.com/ava-labs/coreth/blob/v0.8.6/params/config.go
+pub const RESERVED_CHAIN_IDS: [u64; 3] = [43112, 43113, 43114];
+
+/// Converts the AVAX amount to the EVM denomination (e.g., "balance" of "AllocAccount").
+pub fn avax_to_wei(avax: u64) -> BigInt {
+    BigInt::from(avax) * BigInt::from(WEI_PER_AVAX)
+}
+
+/// Validates the hex-encoded EVM address, with or without the "0x" prefix,
+/// and returns the address without the prefix (e.g., the key of "alloc").
+pub fn parse_address(addr: &str) -> io::Result<String> {
+    let hex = if addr.len() >= 2 {
+        prefix::strip_0x(addr)
+    } else {
+        addr
+    };
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid EVM address '{}' (expected 20-byte hex)", addr),
+        ));
+    }
+    Ok(String::from(hex))
+}
+
+/// Returns true if the string is "0x"-prefixed hex of "bytes" length,
+/// or any even length if "bytes" is zero.
+fn is_hex(s: &str, bytes: usize) -> bool {
+    let hex = match s.strip_prefix("0x") {
+        Some(v) => v,
+        None => return false,
+    };
+    let len_ok = if bytes == 0 {
+        hex.len() % 2 == 0
+    } else {
+        hex.len() == bytes * 2
+    };
+    len_ok && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
 impl Default for Genesis {
     fn default() -> Self {
         Self::default()
@@ -76,6 +120,18 @@ impl Default for Genesis {
 }
 
 impl Genesis {
+    /// Returns the genesis of the chain ID without any allocation,
+    /// to be funded with "add_alloc" or "set_airdrop".
+    pub fn new(chain_id: u64) -> Self {
+        let mut config = ChainConfig::default();
+        config.chain_id = Some(chain_id);
+        Self {
+            config: Some(config),
+            alloc: Some(BTreeMap::new()),
+            ..Self::default()
+        }
+    }
+
     pub fn default() -> Self {
         let mut alloc = BTreeMap::new();
         alloc.insert(
@@ -112,6 +168,189 @@ impl Genesis {
         }
     }
 
+    /// Sets the block gas limit both in the header and the fee config,
+    /// which subnet-evm requires to be equal.
+    pub fn set_gas_limit(&mut self, gas_limit: u64) {
+        self.gas_limit = BigInt::from(gas_limit);
+        let config = self.config.get_or_insert_with(ChainConfig::default);
+        let fee_config = config.fee_config.get_or_insert_with(FeeConfig::default);
+        fee_config.gas_limit = Some(gas_limit);
+    }
+
+    /// Funds the address in the genesis, overwriting the existing balance.
+    pub fn add_alloc(&mut self, address: &str, balance: BigInt) -> io::Result<()> {
+        let address = parse_address(address)?;
+        let account = AllocAccount {
+            balance,
+            ..AllocAccount::default()
+        };
+        self.alloc
+            .get_or_insert_with(BTreeMap::new)
+            .insert(address, account);
+        Ok(())
+    }
+
+    /// Sets the airdrop of "amount" to each address in the airdrop file of "hash".
+    pub fn set_airdrop(&mut self, hash: &str, amount: &BigInt) -> io::Result<()> {
+        if !is_hex(hash, 32) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid airdrop hash '{}' (expected 32-byte hex)", hash),
+            ));
+        }
+        self.airdrop_hash = Some(String::from(hash));
+        self.airdrop_amount = Some(big_int::to_upper_hex(amount));
+        Ok(())
+    }
+
+    /// Enables the contract deployer allow list precompile from the genesis.
+    pub fn enable_contract_deployer_allow_list(&mut self, admins: &[String]) -> io::Result<()> {
+        let allow_list = AllowListConfig::new(admins)?;
+        self.chain_config_mut().contract_deployer_allow_list_config = Some(allow_list);
+        Ok(())
+    }
+
+    /// Enables the transaction allow list precompile from the genesis.
+    pub fn enable_tx_allow_list(&mut self, admins: &[String]) -> io::Result<()> {
+        let allow_list = AllowListConfig::new(admins)?;
+        self.chain_config_mut().tx_allow_list_config = Some(allow_list);
+        Ok(())
+    }
+
+    /// Enables the fee manager precompile from the genesis,
+    /// so the admins can update the fee config without a network upgrade.
+    pub fn enable_fee_manager(&mut self, admins: &[String]) -> io::Result<()> {
+        let allow_list = AllowListConfig::new(admins)?;
+        self.chain_config_mut().fee_manager_config = Some(allow_list);
+        Ok(())
+    }
+
+    /// Enables the native minter precompile from the genesis.
+    pub fn enable_native_minter(&mut self, admins: &[String]) -> io::Result<()> {
+        let allow_list = AllowListConfig::new(admins)?;
+        self.chain_config_mut().contract_native_minter_config = Some(allow_list);
+        Ok(())
+    }
+
+    fn chain_config_mut(&mut self) -> &mut ChainConfig {
+        self.config.get_or_insert_with(ChainConfig::default)
+    }
+
+    /// Checks the fields that subnet-evm would otherwise reject on chain creation,
+    /// or that would leave the chain without any funds.
+    pub fn validate(&self) -> io::Result<()> {
+        let config = match &self.config {
+            Some(v) => v,
+            None => {
+                return Err(Error::new(ErrorKind::InvalidInput, "'config' must be set"));
+            }
+        };
+        match config.chain_id {
+            Some(chain_id) if chain_id > 0 && !RESERVED_CHAIN_IDS.contains(&chain_id) => {}
+            Some(chain_id) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "'config.chainId' {} must be >0 and not one of {:?}",
+                        chain_id, RESERVED_CHAIN_IDS
+                    ),
+                ));
+            }
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'config.chainId' must be set",
+                ));
+            }
+        }
+
+        let fee_config = match &config.fee_config {
+            Some(v) => v,
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'config.feeConfig' must be set",
+                ));
+            }
+        };
+        fee_config.validate()?;
+        if fee_config.gas_limit.map(BigInt::from) != Some(self.gas_limit.clone()) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'gasLimit' {} must equal 'config.feeConfig.gasLimit' {:?}",
+                    self.gas_limit, fee_config.gas_limit
+                ),
+            ));
+        }
+
+        for (name, allow_list) in [
+            (
+                "contractDeployerAllowListConfig",
+                &config.contract_deployer_allow_list_config,
+            ),
+            ("txAllowListConfig", &config.tx_allow_list_config),
+            ("feeManagerConfig", &config.fee_manager_config),
+            (
+                "contractNativeMinterConfig",
+                &config.contract_native_minter_config,
+            ),
+        ] {
+            if let Some(allow_list) = allow_list {
+                allow_list.validate().map_err(|e| {
+                    Error::new(ErrorKind::InvalidInput, format!("'config.{}' {}", name, e))
+                })?;
+            }
+        }
+        // without any admin, nobody can be added to the list to issue transactions
+        if let Some(tx_allow_list) = &config.tx_allow_list_config {
+            if tx_allow_list
+                .allow_list_admins
+                .as_deref()
+                .unwrap_or_default()
+                .is_empty()
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'config.txAllowListConfig.adminAddresses' must be non-empty",
+                ));
+            }
+        }
+
+        if let Some(extra_data) = &self.extra_data {
+            if !is_hex(extra_data, 0) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("'extraData' '{}' must be hex", extra_data),
+                ));
+            }
+        }
+
+        let alloc_len = self.alloc.as_ref().map_or(0, |alloc| alloc.len());
+        if let Some(alloc) = &self.alloc {
+            for address in alloc.keys() {
+                parse_address(address)?;
+            }
+        }
+        match (&self.airdrop_hash, &self.airdrop_amount) {
+            (Some(hash), Some(_)) if is_hex(hash, 32) => {}
+            (None, None) if alloc_len > 0 => {}
+            (None, None) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "either 'alloc' or 'airdropHash' must be set to fund the chain",
+                ));
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "'airdropHash' must be 32-byte hex and set with 'airdropAmount'",
+                ));
+            }
+        }
+        Ok(())
+    }
+
     pub fn encode_json(&self) -> io::Result<String> {
         match serde_json::to_string(&self) {
             Ok(s) => Ok(s),
@@ -190,6 +429,12 @@ pub struct ChainConfig {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub contract_deployer_allow_list_config: Option<ContractDeployerAllowListConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_allow_list_config: Option<TxAllowListConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_manager_config: Option<FeeManagerConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_native_minter_config: Option<ContractNativeMinterConfig>,
 }
 
 impl Default for ChainConfig {
@@ -227,6 +472,9 @@ impl ChainConfig {
             allow_fee_recipients: None,
 
             contract_deployer_allow_list_config: Some(ContractDeployerAllowListConfig::default()),
+            tx_allow_list_config: None,
+            fee_manager_config: None,
+            contract_native_minter_config: None,
         }
     }
 }
@@ -278,31 +526,89 @@ impl FeeConfig {
             block_gas_cost_step: Some(500000),
         }
     }
+
+    /// ref. https://github.com/ava-labs/subnet-evm/blob/master/commontype/fee_config.go
+    pub fn validate(&self) -> io::Result<()> {
+        for (name, v) in [
+            ("gasLimit", self.gas_limit),
+            ("targetBlockRate", self.target_block_rate),
+            ("minBaseFee", self.min_base_fee),
+            ("targetGas", self.target_gas),
+            ("baseFeeChangeDenominator", self.base_fee_change_denominator),
+        ] {
+            if v.unwrap_or(0) == 0 {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("'config.feeConfig.{}' must be >0", name),
+                ));
+            }
+        }
+        if self.min_block_gas_cost.unwrap_or(0) > self.max_block_gas_cost.unwrap_or(0) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "'config.feeConfig.minBlockGasCost' {:?} must be <= 'maxBlockGasCost' {:?}",
+                    self.min_block_gas_cost, self.max_block_gas_cost
+                ),
+            ));
+        }
+        Ok(())
+    }
 }
 
+/// The allow list precompiles share the same config,
+/// and each admin can add or remove the addresses in the list.
+/// ref. https://github.com/ava-labs/subnet-evm/blob/master/precompile/allow_list.go
+pub type ContractDeployerAllowListConfig = AllowListConfig;
+/// ref. https://github.com/ava-labs/subnet-evm/blob/master/precompile/tx_allow_list.go
+pub type TxAllowListConfig = AllowListConfig;
+/// ref. https://github.com/ava-labs/subnet-evm/blob/master/precompile/fee_config_manager.go
+pub type FeeManagerConfig = AllowListConfig;
+/// ref. https://github.com/ava-labs/subnet-evm/blob/master/precompile/contract_native_minter.go
+pub type ContractNativeMinterConfig = AllowListConfig;
+
 /// ref. https://github.com/ava-labs/subnet-evm/blob/master/precompile/contract_deployer_allow_list.go
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
-pub struct ContractDeployerAllowListConfig {
+pub struct AllowListConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub block_timestamp: Option<u64>,
     #[serde(rename = "adminAddresses", skip_serializing_if = "Option::is_none")]
     pub allow_list_admins: Option<Vec<String>>,
 }
 
-impl Default for ContractDeployerAllowListConfig {
+impl Default for AllowListConfig {
     fn default() -> Self {
         Self::default()
     }
 }
 
-impl ContractDeployerAllowListConfig {
+impl AllowListConfig {
     pub fn default() -> Self {
         Self {
             block_timestamp: Some(0),
             allow_list_admins: None,
         }
     }
+
+    /// Returns the config enabled from the genesis with the "0x"-prefixed admins.
+    pub fn new(admins: &[String]) -> io::Result<Self> {
+        let mut allow_list_admins = Vec::new();
+        for admin in admins.iter() {
+            allow_list_admins.push(prefix::prepend_0x(&parse_address(admin)?));
+        }
+        Ok(Self {
+            allow_list_admins: Some(allow_list_admins),
+            ..Self::default()
+        })
+    }
+
+    pub fn validate(&self) -> io::Result<()> {
+        for admin in self.allow_list_admins.as_deref().unwrap_or_default().iter() {
+            parse_address(admin)?;
+        }
+        Ok(())
+    }
 }
 
 /// ref. https://pkg.go.dev/github.com/ava-labs/subnet-evm/core#GenesisAlloc
@@ -404,3 +710,72 @@ fn test_parse() {
     let d = d.encode_json().unwrap();
     info!("{}", d);
 }
+
+#[test]
+fn test_genesis_builder() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    assert_eq!(
+        big_int::to_upper_hex(&avax_to_wei(100_000_000)),
+        DEFAULT_INITIAL_AMOUNT
+    );
+    assert_eq!(
+        parse_address("0x6f0f6DA1852857d7789f68a28bba866671f3880D").unwrap(),
+        "6f0f6DA1852857d7789f68a28bba866671f3880D"
+    );
+    assert!(parse_address("0x6f0f").is_err());
+    assert!(parse_address("0xZZ0f6DA1852857d7789f68a28bba866671f3880D").is_err());
+
+    let admin = String::from("0x8db97C7cEcE249c2b98bDC0226Cc4C2A57BF52FC");
+    let mut genesis = Genesis::new(99999);
+    assert!(genesis.validate().is_err()); // no funds
+
+    genesis.add_alloc(&admin, avax_to_wei(1_000)).unwrap();
+    genesis.set_gas_limit(15_000_000);
+    let admins = vec![admin.clone()];
+    genesis.enable_tx_allow_list(&admins).unwrap();
+    genesis.enable_fee_manager(&admins).unwrap();
+    genesis.enable_native_minter(&admins).unwrap();
+    genesis.validate().unwrap();
+
+    let config = genesis.config.clone().unwrap();
+    assert_eq!(config.fee_config.unwrap().gas_limit, Some(15_000_000));
+    assert_eq!(
+        config.tx_allow_list_config.unwrap().allow_list_admins,
+        Some(admins)
+    );
+    let d = genesis.encode_json().unwrap();
+    info!("{}", d);
+    assert!(d.contains("\"txAllowListConfig\""));
+    assert!(d.contains("\"feeManagerConfig\""));
+    assert!(d.contains("\"contractNativeMinterConfig\""));
+    assert!(d.contains("\"8db97C7cEcE249c2b98bDC0226Cc4C2A57BF52FC\""));
+    let decoded: Genesis = serde_json::from_str(&d).unwrap();
+    assert_eq!(decoded, genesis);
+
+    let mut invalid = genesis.clone();
+    invalid.gas_limit = BigInt::from(8_000_000);
+    assert!(invalid.validate().is_err());
+    let mut invalid = genesis.clone();
+    invalid.config.as_mut().unwrap().chain_id = Some(43114);
+    assert!(invalid.validate().is_err());
+    let mut invalid = genesis.clone();
+    invalid.airdrop_amount = Some(String::from("0x1"));
+    assert!(invalid.validate().is_err());
+    let mut invalid = genesis.clone();
+    invalid.config.as_mut().unwrap().tx_allow_list_config = Some(AllowListConfig::default());
+    assert!(invalid.validate().is_err());
+    assert!(genesis.enable_fee_manager(&[String::from("0x00")]).is_err());
+
+    let mut airdrop = Genesis::new(99999);
+    assert!(airdrop.set_airdrop("0x00", &avax_to_wei(1)).is_err());
+    airdrop
+        .set_airdrop(
+            "0xccbf8e430b30d08b5b3342208781c40b373d1b5885c1903828f367230a2568da",
+            &avax_to_wei(1),
+        )
+        .unwrap();
+    airdrop.validate().unwrap();
+
+    Genesis::default().validate().unwrap();
+}
@@ -4,6 +4,7 @@ use std::{
     io::{self, BufReader, Cursor, Error, ErrorKind, Read, Write},
     os::unix::fs::PermissionsExt,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use flate2::{
@@ -20,11 +21,14 @@ use zstd;
 
 use crate::{humanize, random};
 
+/// Same as "flate2::Compression::default()".
+pub const DEFAULT_GZIP_LEVEL: u32 = 6;
+
 #[derive(Eq, PartialEq, Clone)]
 /// Represents the compression encoding algorithm.
 pub enum Encoder {
     /// Encodes with "Gzip" compression.
-    Gzip,
+    Gzip(u32),
     /// Encodes with "Zstandard" compression.
     Zstd(i32),
     /// Encodes with "Zstandard" compression and apply base58.
@@ -35,7 +39,7 @@ impl fmt::Display for Encoder {
     /// The last integer is the zstd compression level.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Encoder::Gzip => write!(f, "gzip"),
+            Encoder::Gzip(level) => write!(f, "gzip{}", level),
             Encoder::Zstd(level) => write!(f, "zstd{}", level),
             Encoder::ZstdBase58(level) => {
                 write!(f, "zstd-base58{}", level)
@@ -47,7 +51,9 @@ impl fmt::Display for Encoder {
 impl Encoder {
     pub fn id(&self) -> &str {
         match self {
-            Encoder::Gzip => "gzip",
+            Encoder::Gzip(1) => "gzip1",
+            Encoder::Gzip(DEFAULT_GZIP_LEVEL) => "gzip",
+            Encoder::Gzip(9) => "gzip9",
             Encoder::Zstd(1) => "zstd1",
             Encoder::Zstd(2) => "zstd2",
             Encoder::Zstd(3) => "zstd3",
@@ -59,7 +65,9 @@ impl Encoder {
     }
     pub fn new(id: &str) -> io::Result<Self> {
         match id {
-            "gzip" => Ok(Encoder::Gzip),
+            "gzip1" => Ok(Encoder::Gzip(1)),
+            "gzip" => Ok(Encoder::Gzip(DEFAULT_GZIP_LEVEL)),
+            "gzip9" => Ok(Encoder::Gzip(9)),
             "zstd1" => Ok(Encoder::Zstd(1)),
             "zstd2" => Ok(Encoder::Zstd(2)),
             "zstd3" => Ok(Encoder::Zstd(3)),
@@ -76,14 +84,14 @@ impl Encoder {
     }
     pub fn suffix(&self) -> &str {
         match self {
-            Encoder::Gzip => "gz",
+            Encoder::Gzip(_) => "gz",
             Encoder::Zstd(_) => "zstd",
             Encoder::ZstdBase58(_) => "zstd.base58",
         }
     }
     pub fn ext(&self) -> &str {
         match self {
-            Encoder::Gzip => ".gz",
+            Encoder::Gzip(_) => ".gz",
             Encoder::Zstd(_) => ".zstd",
             Encoder::ZstdBase58(_) => ".zstd.base58",
         }
@@ -140,8 +148,8 @@ pub fn pack(d: &[u8], enc: Encoder) -> io::Result<Vec<u8>> {
     );
 
     let packed = match enc {
-        Encoder::Gzip => {
-            let mut gz = GzEncoder::new(Cursor::new(d), Compression::default());
+        Encoder::Gzip(lvl) => {
+            let mut gz = GzEncoder::new(Cursor::new(d), Compression::new(lvl));
             let mut encoded = Vec::new();
             gz.read_to_end(&mut encoded)?;
             encoded
@@ -221,6 +229,16 @@ pub fn unpack(d: &[u8], dec: Decoder) -> io::Result<Vec<u8>> {
 ///  f2.write_all(&decoded[..])?;
 ///
 pub fn pack_file(src_path: &str, dst_path: &str, enc: Encoder) -> io::Result<()> {
+    pack_file_with_options(src_path, dst_path, enc, &StreamOptions::default())
+}
+
+/// Same as "pack_file" but reports the progress with the options.
+pub fn pack_file_with_options(
+    src_path: &str,
+    dst_path: &str,
+    enc: Encoder,
+    opts: &StreamOptions,
+) -> io::Result<()> {
     let meta = fs::metadata(src_path)?;
     let size_before = meta.len() as f64;
     info!(
@@ -232,24 +250,10 @@ pub fn pack_file(src_path: &str, dst_path: &str, enc: Encoder) -> io::Result<()>
     );
 
     match enc {
-        Encoder::Gzip => {
+        Encoder::Gzip(_) | Encoder::Zstd(_) => {
             let f1 = File::open(src_path)?;
-            let mut f2 = File::create(dst_path)?;
-
-            let mut enc = GzEncoder::new(BufReader::new(f1), Compression::default());
-
-            // reads from reader (enc) and writes to writer "f2"
-            io::copy(&mut enc, &mut f2)?;
-        }
-        Encoder::Zstd(lvl) => {
-            let mut f1 = File::open(src_path)?;
             let f2 = File::create(dst_path)?;
-
-            let mut enc = zstd::Encoder::new(f2, lvl)?;
-
-            // reads from reader (f1) and writes to writer "enc"
-            io::copy(&mut f1, &mut enc)?;
-            enc.finish()?;
+            pack_stream(f1, f2, enc.clone(), opts)?;
         }
         Encoder::ZstdBase58(lvl) => {
             // reading the entire file at once may cause OOM...
@@ -291,6 +295,17 @@ pub fn pack_file(src_path: &str, dst_path: &str, enc: Encoder) -> io::Result<()>
 ///  f2.write_all(&decoded[..])?;
 ///
 pub fn unpack_file(src_path: &str, dst_path: &str, dec: Decoder) -> io::Result<()> {
+    unpack_file_with_options(src_path, dst_path, dec, &StreamOptions::default())
+}
+
+/// Same as "unpack_file" but limits the unpacked size
+/// and reports the progress with the options.
+pub fn unpack_file_with_options(
+    src_path: &str,
+    dst_path: &str,
+    dec: Decoder,
+    opts: &StreamOptions,
+) -> io::Result<()> {
     let meta = fs::metadata(src_path)?;
     let size_before = meta.len() as f64;
     info!(
@@ -302,23 +317,10 @@ pub fn unpack_file(src_path: &str, dst_path: &str, dec: Decoder) -> io::Result<(
     );
 
     match dec {
-        Decoder::Gzip => {
+        Decoder::Gzip | Decoder::Zstd => {
             let f1 = File::open(src_path)?;
-            let mut f2 = File::create(dst_path)?;
-
-            let mut dec = GzDecoder::new(BufReader::new(f1));
-
-            // reads from reader (dec) and writes to writer "f2"
-            io::copy(&mut dec, &mut f2)?;
-        }
-        Decoder::Zstd => {
-            let f1 = File::open(src_path)?;
-            let mut f2 = File::create(dst_path)?;
-
-            let mut dec = zstd::Decoder::new(BufReader::new(f1))?;
-
-            // reads from reader (dec) and writes to writer "f2"
-            io::copy(&mut dec, &mut f2)?;
+            let f2 = File::create(dst_path)?;
+            unpack_stream(BufReader::new(f1), f2, dec.clone(), opts)?;
         }
         Decoder::ZstdBase58 => {
             // reading the entire file at once may cause OOM...
@@ -342,6 +344,194 @@ pub fn unpack_file(src_path: &str, dst_path: &str, dec: Decoder) -> io::Result<(
     Ok(())
 }
 
+/// Called with the number of the compressed or uncompressed input bytes
+/// consumed so far (e.g., to report against the downloaded object size).
+pub type ProgressFn = Arc<dyn Fn(u64) + Send + Sync>;
+
+/// Options for the streaming compression and decompression.
+#[derive(Clone, Default)]
+pub struct StreamOptions {
+    /// Fails the decompression once the output exceeds the limit,
+    /// so that a small input cannot expand to fill the disk or memory
+    /// (i.e., decompression bomb). Ignored for the compression.
+    pub max_unpacked_bytes: Option<u64>,
+    /// Called after every chunk of the input.
+    pub progress: Option<ProgressFn>,
+}
+
+impl fmt::Debug for StreamOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StreamOptions")
+            .field("max_unpacked_bytes", &self.max_unpacked_bytes)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+/// Compresses the "src" into the "dst" as it reads, so the memory usage
+/// is bounded regardless of the input size.
+/// Returns the number of the uncompressed bytes read.
+pub fn pack_stream<R: Read, W: Write>(
+    src: R,
+    dst: W,
+    enc: Encoder,
+    opts: &StreamOptions,
+) -> io::Result<u64> {
+    let mut src = ProgressReader {
+        inner: src,
+        read: 0,
+        progress: opts.progress.as_ref(),
+    };
+    match enc {
+        Encoder::Gzip(lvl) => {
+            let mut enc = flate2::write::GzEncoder::new(dst, Compression::new(lvl));
+            io::copy(&mut src, &mut enc)?;
+            enc.finish()?;
+        }
+        Encoder::Zstd(lvl) => {
+            let mut enc = zstd::Encoder::new(dst, lvl)?;
+            io::copy(&mut src, &mut enc)?;
+            enc.finish()?;
+        }
+        Encoder::ZstdBase58(_) => {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                format!("{} cannot be streamed", enc),
+            ));
+        }
+    }
+    Ok(src.read)
+}
+
+/// Decompresses the "src" into the "dst" as it reads.
+/// Returns the number of the uncompressed bytes written.
+pub fn unpack_stream<R: Read, W: Write>(
+    mut src: R,
+    dst: W,
+    dec: Decoder,
+    opts: &StreamOptions,
+) -> io::Result<u64> {
+    let mut w = UnpackWriter::new(dst, dec, opts)?;
+    io::copy(&mut src, &mut w)?;
+    w.finish()
+}
+
+/// Decompresses the bytes as they are written, so that the chunks
+/// of a network body (e.g., S3 object) are unpacked without the temporary file.
+pub struct UnpackWriter<W: Write> {
+    dec: UnpackWriterDecoder<W>,
+    written: u64,
+    progress: Option<ProgressFn>,
+}
+
+enum UnpackWriterDecoder<W: Write> {
+    Gzip(flate2::write::GzDecoder<LimitWriter<W>>),
+    Zstd(zstd::stream::write::Decoder<'static, LimitWriter<W>>),
+}
+
+impl<W: Write> UnpackWriter<W> {
+    pub fn new(dst: W, dec: Decoder, opts: &StreamOptions) -> io::Result<Self> {
+        let limited = LimitWriter {
+            inner: dst,
+            written: 0,
+            limit: opts.max_unpacked_bytes,
+        };
+        let dec = match dec {
+            Decoder::Gzip => UnpackWriterDecoder::Gzip(flate2::write::GzDecoder::new(limited)),
+            Decoder::Zstd => UnpackWriterDecoder::Zstd(zstd::stream::write::Decoder::new(limited)?),
+            Decoder::ZstdBase58 => {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    format!("{} cannot be streamed", dec),
+                ));
+            }
+        };
+        Ok(Self {
+            dec,
+            written: 0,
+            progress: opts.progress.clone(),
+        })
+    }
+
+    /// Flushes the remaining output, and returns the number of the uncompressed bytes.
+    pub fn finish(self) -> io::Result<u64> {
+        let mut limited = match self.dec {
+            UnpackWriterDecoder::Gzip(d) => d.finish()?,
+            UnpackWriterDecoder::Zstd(mut d) => {
+                d.flush()?;
+                d.into_inner()
+            }
+        };
+        limited.flush()?;
+        Ok(limited.written)
+    }
+}
+
+impl<W: Write> Write for UnpackWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = match &mut self.dec {
+            UnpackWriterDecoder::Gzip(d) => d.write(buf)?,
+            UnpackWriterDecoder::Zstd(d) => d.write(buf)?,
+        };
+        self.written += n as u64;
+        if let Some(f) = &self.progress {
+            f(self.written);
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.dec {
+            UnpackWriterDecoder::Gzip(d) => d.flush(),
+            UnpackWriterDecoder::Zstd(d) => d.flush(),
+        }
+    }
+}
+
+struct ProgressReader<'a, R: Read> {
+    inner: R,
+    read: u64,
+    progress: Option<&'a ProgressFn>,
+}
+
+impl<'a, R: Read> Read for ProgressReader<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        if let Some(f) = self.progress {
+            f(self.read);
+        }
+        Ok(n)
+    }
+}
+
+/// Fails the writes past the limit, before they reach the inner writer.
+struct LimitWriter<W: Write> {
+    inner: W,
+    written: u64,
+    limit: Option<u64>,
+}
+
+impl<W: Write> Write for LimitWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(limit) = self.limit {
+            if self.written + buf.len() as u64 > limit {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("unpacked size exceeds the limit {} bytes", limit),
+                ));
+            }
+        }
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 #[derive(Clone)]
 /// Represents the compression encoding algorithm for directory.
 pub enum DirEncoder {
@@ -538,9 +728,9 @@ pub fn pack_directory(src_dir_path: &str, dst_path: &str, enc: DirEncoder) -> io
         .as_path()
         .to_str()
         .expect("unexpected None path");
-    let archive_file = File::create(&archive_path)?;
     match enc {
         DirEncoder::TarGzip => {
+            let archive_file = File::create(&archive_path)?;
             // e.g.,
             // tar -czvf db.tar.gz mainnet/
             // -c to create a new archive
@@ -586,10 +776,11 @@ pub fn pack_directory(src_dir_path: &str, dst_path: &str, enc: DirEncoder) -> io
                 let mut f = File::open(&full_path)?;
                 tar.append_file(&file_name, &mut f)?;
             }
-            pack_file(archive_path, dst_path, Encoder::Gzip)?;
+            pack_file(archive_path, dst_path, Encoder::Gzip(DEFAULT_GZIP_LEVEL))?;
         }
 
         DirEncoder::ZipGzip => {
+            let archive_file = File::create(&archive_path)?;
             let mut zip = ZipWriter::new(archive_file);
 
             let mut buffer = Vec::new();
@@ -649,11 +840,14 @@ pub fn pack_directory(src_dir_path: &str, dst_path: &str, enc: DirEncoder) -> io
                 buffer.clear();
             }
             zip.finish()?;
-            pack_file(archive_path, dst_path, Encoder::Gzip)?;
+            pack_file(archive_path, dst_path, Encoder::Gzip(DEFAULT_GZIP_LEVEL))?;
         }
 
         DirEncoder::TarZstd(lvl) => {
-            let mut tar = Builder::new(archive_file);
+            // streams the tar entries into the compressor, since the database
+            // backups are too large to archive to disk first
+            let zstd_enc = zstd::Encoder::new(File::create(dst_path)?, lvl)?;
+            let mut tar = Builder::new(zstd_enc);
             let src_dir = Path::new(src_dir_path);
             let src_dir_full_path = absolute_path(src_dir)?;
             for entry in WalkDir::new(src_dir_path).into_iter() {
@@ -692,10 +886,11 @@ pub fn pack_directory(src_dir_path: &str, dst_path: &str, enc: DirEncoder) -> io
                 let mut f = File::open(&full_path)?;
                 tar.append_file(&file_name, &mut f)?;
             }
-            pack_file(archive_path, dst_path, Encoder::Zstd(lvl))?;
+            tar.into_inner()?.finish()?;
         }
 
         DirEncoder::ZipZstd(lvl) => {
+            let archive_file = File::create(&archive_path)?;
             let mut zip = ZipWriter::new(archive_file);
 
             let mut buffer = Vec::new();
@@ -887,34 +1082,23 @@ pub fn unpack_directory(
         }
 
         DirDecoder::TarZstd => {
-            unpack_file(src_archive_path, &unpacked_path, Decoder::Zstd)?;
-
-            info!("unarchiving unpacked file {}", unpacked_path);
-            let tar_file = File::open(&unpacked_path)?;
-            let mut tar = Archive::new(tar_file);
+            // streams the decompressed tar into the entries, since the database
+            // backups are too large to decompress to disk first
+            info!("unarchiving {} while decompressing", src_archive_path);
+            let zstd_dec = zstd::Decoder::new(File::open(src_archive_path)?)?;
+            let mut tar = Archive::new(zstd_dec);
             let entries = tar.entries()?;
             for file in entries {
                 let mut f = file?;
-                let output_path = f.path()?;
-                let output_path = target_dir.join(output_path);
-                if let Some(p) = output_path.parent() {
-                    if !p.exists() {
-                        fs::create_dir_all(&p)?;
-                    }
-                }
-                if output_path.is_dir()
-                    || output_path
-                        .to_str()
-                        .expect("unexpected None str")
-                        .ends_with('/')
-                {
-                    info!("extracting directory {}", output_path.display());
-                    fs::create_dir_all(output_path)?;
-                } else {
-                    info!("extracting file {}", output_path.display());
-                    let mut f2 = File::create(&output_path)?;
-                    io::copy(&mut f, &mut f2)?;
-                    fs::set_permissions(&output_path, PermissionsExt::from_mode(0o444))?;
+                info!("extracting {}", f.path()?.display());
+                // keeps the mode of the archived file, since the database
+                // must be writable by avalanchego after the restore,
+                // and rejects the paths outside the target directory
+                if !f.unpack_in(target_dir)? {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("archived path {:?} escapes {}", f.path()?, dst_dir_path),
+                    ));
                 }
             }
         }
@@ -969,8 +1153,10 @@ pub fn unpack_directory(
         }
     }
 
-    info!("removing unpacked file {} after unarchive", unpacked_path);
-    fs::remove_file(unpacked_path)?;
+    if Path::new(&unpacked_path).exists() {
+        info!("removing unpacked file {} after unarchive", unpacked_path);
+        fs::remove_file(unpacked_path)?;
+    }
 
     let size = fs_extra::dir::get_size(target_dir).map_err(|e| {
         return Error::new(
@@ -1019,6 +1205,8 @@ fn test_pack_unpack() {
 
     let encs = vec![
         "gzip",
+        "gzip1",
+        "gzip9",
         "zstd1",
         "zstd2",
         "zstd3",
@@ -1027,6 +1215,8 @@ fn test_pack_unpack() {
         "zstd3-base58",
     ];
     let decs = vec![
+        "gzip",
+        "gzip",
         "gzip",
         "zstd",
         "zstd",
@@ -1125,8 +1315,84 @@ fn test_pack_unpack() {
         )
         .unwrap();
 
+        // the streamed database backups stay writable
+        if decs[i] == "tar-zstd" {
+            for entry in fs::read_dir(&dst_dir_path).unwrap() {
+                let entry = entry.unwrap();
+                let mode = entry.metadata().unwrap().permissions().mode();
+                let orig = fs::metadata(src_dir_path.join(entry.file_name())).unwrap();
+                assert_eq!(mode & 0o777, orig.permissions().mode() & 0o777);
+                assert_eq!(fs::read(entry.path()).unwrap().len(), contents.len());
+            }
+        }
+
         fs::remove_file(packed_path).unwrap();
         fs::remove_dir_all(dst_dir_path).unwrap();
     }
     fs::remove_dir_all(_src_dir_path).unwrap();
 }
+
+/// RUST_LOG=debug cargo test --package utils --lib -- compress::test_pack_unpack_stream --exact --show-output
+#[test]
+fn test_pack_unpack_stream() {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let contents = vec![7; 1 * 1024 * 1024];
+    for (enc, dec) in [
+        (Encoder::Gzip(1), Decoder::Gzip),
+        (Encoder::Gzip(9), Decoder::Gzip),
+        (Encoder::Zstd(3), Decoder::Zstd),
+    ] {
+        let read = Arc::new(AtomicU64::new(0));
+        let read_cloned = read.clone();
+        let opts = StreamOptions {
+            progress: Some(Arc::new(move |n| read_cloned.store(n, Ordering::Relaxed))),
+            ..Default::default()
+        };
+        let mut packed = Vec::new();
+        let n = pack_stream(Cursor::new(&contents), &mut packed, enc.clone(), &opts).unwrap();
+        assert_eq!(n, contents.len() as u64);
+        assert_eq!(read.load(Ordering::Relaxed), contents.len() as u64);
+        assert!(contents.len() > packed.len());
+        assert_eq!(unpack(&packed, dec.clone()).unwrap(), contents);
+
+        // chunks as they arrive from the network body
+        let mut unpacked = Vec::new();
+        let mut w = UnpackWriter::new(&mut unpacked, dec.clone(), &opts).unwrap();
+        for chunk in packed.chunks(100) {
+            w.write_all(chunk).unwrap();
+        }
+        assert_eq!(w.finish().unwrap(), contents.len() as u64);
+        assert_eq!(read.load(Ordering::Relaxed), packed.len() as u64);
+        assert_eq!(unpacked, contents);
+
+        // decompression bomb
+        let opts = StreamOptions {
+            max_unpacked_bytes: Some(contents.len() as u64 - 1),
+            ..Default::default()
+        };
+        let mut unpacked = Vec::new();
+        let err = unpack_stream(Cursor::new(&packed), &mut unpacked, dec.clone(), &opts)
+            .expect_err("expected limit error");
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(unpacked.len() < contents.len());
+
+        let opts = StreamOptions {
+            max_unpacked_bytes: Some(contents.len() as u64),
+            ..Default::default()
+        };
+        let mut unpacked = Vec::new();
+        let n = unpack_stream(Cursor::new(&packed), &mut unpacked, dec, &opts).unwrap();
+        assert_eq!(n, contents.len() as u64);
+    }
+
+    assert!(pack_stream(
+        Cursor::new(&contents),
+        Vec::new(),
+        Encoder::ZstdBase58(3),
+        &StreamOptions::default()
+    )
+    .is_err());
+}
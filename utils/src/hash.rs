@@ -1,5 +1,38 @@
-use ring::digest::{digest, SHA256};
+use std::{
+    fs::File,
+    io::{self, Read},
+};
+
+use ring::digest::{digest, Context, SHA256};
 
 pub fn compute_sha256(input: &[u8]) -> Vec<u8> {
     digest(&SHA256, input).as_ref().into()
 }
+
+/// Computes the SHA256 digest of the file without loading it all into memory.
+pub fn compute_sha256_file(file_path: &str) -> io::Result<Vec<u8>> {
+    let mut f = File::open(file_path)?;
+    let mut ctx = Context::new(&SHA256);
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = f.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        ctx.update(&buf[..n]);
+    }
+    Ok(ctx.finish().as_ref().into())
+}
+
+/// RUST_LOG=debug cargo test --package utils --lib -- hash::test_compute_sha256_file --exact --show-output
+#[test]
+fn test_compute_sha256_file() {
+    use std::io::Write;
+
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    let d = vec![0xab_u8; 200 * 1024];
+    f.write_all(&d).unwrap();
+    let p = f.path().to_str().unwrap();
+    assert_eq!(compute_sha256_file(p).unwrap(), compute_sha256(&d));
+    assert!(compute_sha256_file("/not/found").is_err());
+}
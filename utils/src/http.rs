@@ -1,16 +1,24 @@
 use std::{
-    io,
+    fs, io,
     io::{Error, ErrorKind},
-    process::Command,
+    sync::RwLock,
     time::Duration,
 };
 
-use hyper::{body::Bytes, client::HttpConnector, Body, Client, Method, Request, Response};
+use hyper::{
+    body::Bytes, client::HttpConnector, header, Body, Client as HyperClient, Method, Request,
+    Response, StatusCode,
+};
 use hyper_tls::HttpsConnector;
+use lazy_static::lazy_static;
 use log::{info, warn};
-use tokio::time::timeout;
+use native_tls::{Certificate, Identity, TlsConnector};
+use serde::{Deserialize, Serialize};
+use tokio::time::{sleep, timeout};
 use url::Url;
 
+use crate::random;
+
 /// Creates a simple HTTP GET request with no header and no body.
 pub fn create_get(url: &str, path: &str) -> io::Result<Request<Body>> {
     let uri = match join_uri(url, path) {
@@ -77,8 +85,14 @@ pub async fn read_bytes(
             resp.status().is_server_error()
         );
         if check_status_code {
+            // so that the callers tell the missing resource from the failed request
+            let kind = if resp.status() == StatusCode::NOT_FOUND {
+                ErrorKind::NotFound
+            } else {
+                ErrorKind::Other
+            };
             return Err(Error::new(
-                ErrorKind::Other,
+                kind,
                 format!(
                     "unexpected HTTP response code {} (server error {})",
                     resp.status(),
@@ -130,26 +144,28 @@ async fn send_req(
 
     let task = {
         if !enable_https {
-            let cli = Client::builder().build(connector);
+            let cli = HyperClient::builder().build(connector);
             cli.request(req)
         } else {
-            // TODO: implement "curl --insecure"
-            let https_connector = HttpsConnector::new_with_connector(connector);
-            let cli = Client::builder().build(https_connector);
+            // verifies the server unless "insecure" is explicitly set
+            let https_connector = default_tls().unwrap_or_default().https_connector()?;
+            let cli = HyperClient::builder().build(https_connector);
             cli.request(req)
         }
     };
 
     let res = timeout(timeout_dur, task).await?;
-    match res {
-        Ok(resp) => Ok(resp),
-        Err(e) => {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("failed to fetch response {}", e),
-            ))
-        }
-    }
+    res.map_err(fetch_error)
+}
+
+fn fetch_error(e: hyper::Error) -> Error {
+    // the request never reached the server, so it is always safe to retry
+    let kind = if e.is_connect() {
+        ErrorKind::ConnectionRefused
+    } else {
+        ErrorKind::Other
+    };
+    Error::new(kind, format!("failed to fetch response {}", e))
 }
 
 #[test]
@@ -221,59 +237,512 @@ fn test_join_uri() {
     assert_eq!(t, expected);
 }
 
-/// TODO: implement this with native Rust
+/// Sends the HTTP GET request without the retries, returning the body
+/// regardless of the response code (e.g., 503 from the unhealthy node).
+/// The "https" URLs are verified with the TLS config from "set_default_tls".
 pub async fn get_non_tls(url: &str, url_path: &str) -> io::Result<Vec<u8>> {
     let joined = join_uri(url, url_path)?;
     info!("non-TLS HTTP get for {:?}", joined);
 
-    let output = {
-        if url.starts_with("https") {
-            info!("sending via curl --insecure");
-            let mut cmd = Command::new("curl");
-            cmd.arg("--insecure");
-            cmd.arg(joined.as_str());
-            let output = cmd.output()?;
-            output.stdout
-        } else {
-            let req = create_get(url, url_path)?;
-            let buf = match read_bytes(req, Duration::from_secs(5), url.starts_with("https"), false)
-                .await
-            {
-                Ok(b) => b,
-                Err(e) => return Err(e),
-            };
-            buf.to_vec()
-        }
-    };
-    Ok(output)
+    let req = create_get(url, url_path)?;
+    let buf = read_bytes(req, Duration::from_secs(5), url.starts_with("https"), false).await?;
+    Ok(buf.to_vec())
 }
 
-/// TODO: implement this with native Rust
+/// Sends the HTTP POST request without the retries, returning the body
+/// regardless of the response code.
+/// The "https" URLs are verified with the TLS config from "set_default_tls".
 pub async fn post_non_tls(url: &str, url_path: &str, data: &str) -> io::Result<Vec<u8>> {
     let joined = join_uri(url, url_path)?;
     info!("non-TLS HTTP post {}-byte data to {:?}", data.len(), joined);
 
-    let output = {
-        if url.starts_with("https") {
-            info!("sending via curl --insecure");
-            let mut cmd = Command::new("curl");
-            cmd.arg("--insecure");
-            cmd.arg("-X POST");
-            cmd.arg("--header 'content-type:application/json;'");
-            cmd.arg(format!("--data '{}'", data));
-            cmd.arg(joined.as_str());
-            let output = cmd.output()?;
-            output.stdout
+    let req = create_json_post(url, url_path, data)?;
+    let buf = read_bytes(req, Duration::from_secs(5), url.starts_with("https"), false).await?;
+    Ok(buf.to_vec())
+}
+
+/// Whether the request can be sent again after it may have reached the server.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Idempotency {
+    /// Retried on any transient failure (e.g., reads).
+    Idempotent,
+    /// Only retried if the server could not have processed the request
+    /// (i.e., connection refused, 429, 503), so that the timed out
+    /// transaction is not issued twice.
+    NonIdempotent,
+}
+
+/// Retries the transient failures with the jittered exponential backoff.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// Includes the first attempt, so 1 disables the retries.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Timeout of each attempt, for both the response and its body.
+    pub request_timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+impl RetryPolicy {
+    pub fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Returns the wait before the retry after the "attempt" (starting at 1).
+    /// The "jitter" in [0.0, 1.0] picks the wait between the half and the full
+    /// backoff, so that the clients that failed together do not retry together.
+    pub fn backoff(&self, attempt: u32, jitter: f64) -> Duration {
+        let exp = attempt.saturating_sub(1).min(16);
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << exp)
+            .min(self.max_backoff);
+        let half = backoff / 2;
+        half + half.mul_f64(jitter.clamp(0.0, 1.0))
+    }
+}
+
+/// Returns true if the response code is worth retrying.
+/// 429 and 503 are rejected before processing, so they are retried regardless.
+pub fn is_retryable_status(status: u16, idempotency: Idempotency) -> bool {
+    match status {
+        429 | 503 => true,
+        500 | 502 | 504 => idempotency == Idempotency::Idempotent,
+        _ => false,
+    }
+}
+
+/// Returns true if the request error is worth retrying.
+pub fn is_retryable_error(kind: ErrorKind, idempotency: Idempotency) -> bool {
+    match kind {
+        ErrorKind::ConnectionRefused => true,
+        ErrorKind::TimedOut
+        | ErrorKind::ConnectionReset
+        | ErrorKind::ConnectionAborted
+        | ErrorKind::BrokenPipe
+        | ErrorKind::UnexpectedEof
+        | ErrorKind::Other => idempotency == Idempotency::Idempotent,
+        _ => false,
+    }
+}
+
+/// Verifies the "https" API endpoints with the custom CA and the client
+/// certificates (e.g., nodes fronted by the proxy that requires them).
+/// The default verifies the servers with the system roots.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct TlsConfig {
+    /// PEM-encoded CA certificates to trust in addition to the system roots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle_path: Option<String>,
+    /// PEM-encoded client certificate, which requires "client_key_path".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert_path: Option<String>,
+    /// PEM-encoded PKCS #8 private key of the client certificate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key_path: Option<String>,
+    /// Skips the server certificate verification, but still sends
+    /// the client certificate (e.g., proxies with the self-signed certificates).
+    /// Only set explicitly, since the API calls carry the keystore passwords
+    /// and the signed transactions.
+    #[serde(default)]
+    pub insecure: bool,
+}
+
+impl TlsConfig {
+    /// Only checks the fields, since the files may not exist
+    /// on the host that validates the spec.
+    pub fn validate(&self) -> io::Result<()> {
+        if self.client_cert_path.is_some() != self.client_key_path.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "'client_cert_path' and 'client_key_path' must be set together",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Loads the certificates and returns the connector that verifies the servers with them.
+    pub fn https_connector(&self) -> io::Result<HttpsConnector<HttpConnector>> {
+        let mut builder = TlsConnector::builder();
+        if let Some(p) = &self.ca_bundle_path {
+            let pem = read_pem(p)?;
+            let certs = split_pem_certificates(&pem);
+            if certs.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("no certificate in CA bundle '{}'", p),
+                ));
+            }
+            for cert in certs.iter() {
+                let cert = Certificate::from_pem(cert.as_bytes()).map_err(|e| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("invalid certificate in CA bundle '{}' ({})", p, e),
+                    )
+                })?;
+                builder.add_root_certificate(cert);
+            }
+        }
+        if let (Some(cert_path), Some(key_path)) = (&self.client_cert_path, &self.client_key_path) {
+            let cert = read_pem(cert_path)?;
+            let key = read_pem(key_path)?;
+            let identity = Identity::from_pkcs8(cert.as_bytes(), key.as_bytes()).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "invalid client certificate '{}' or key '{}' ({})",
+                        cert_path, key_path, e
+                    ),
+                )
+            })?;
+            builder.identity(identity);
+        }
+        builder.danger_accept_invalid_certs(self.insecure);
+        let tls = builder.build().map_err(|e| {
+            Error::new(
+                ErrorKind::Other,
+                format!("failed to build TLS connector {}", e),
+            )
+        })?;
+
+        let mut connector = HttpConnector::new();
+        // ref. https://github.com/hyperium/hyper/issues/1097
+        connector.set_connect_timeout(Some(Duration::from_secs(5)));
+        connector.enforce_http(false);
+        Ok(HttpsConnector::from((
+            connector,
+            tokio_native_tls::TlsConnector::from(tls),
+        )))
+    }
+}
+
+fn read_pem(p: &str) -> io::Result<String> {
+    fs::read_to_string(p)
+        .map_err(|e| Error::new(e.kind(), format!("failed to read PEM file '{}' ({})", p, e)))
+}
+
+/// Returns each certificate in the PEM bundle,
+/// since "Certificate::from_pem" only reads the first one.
+fn split_pem_certificates(pem: &str) -> Vec<String> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+
+    let mut certs = Vec::new();
+    let mut rest = pem;
+    while let Some(begin) = rest.find(BEGIN) {
+        let end = match rest[begin..].find(END) {
+            Some(end) => begin + end + END.len(),
+            None => break,
+        };
+        certs.push(format!("{}\n", &rest[begin..end]));
+        rest = &rest[end..];
+    }
+    certs
+}
+
+/// RUST_LOG=debug cargo test --package utils --lib -- http::test_tls_config --exact --show-output
+#[test]
+fn test_tls_config() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let pem = "# root
+-----BEGIN CERTIFICATE-----
+MIIB
+-----END CERTIFICATE-----
+# intermediate
+-----BEGIN CERTIFICATE-----
+MIIC
+-----END CERTIFICATE-----
+-----BEGIN CERTIFICATE-----
+truncated";
+    let certs = split_pem_certificates(pem);
+    assert_eq!(certs.len(), 2);
+    assert!(certs[0].contains("MIIB") && !certs[0].contains("MIIC"));
+    assert!(certs[1].starts_with("-----BEGIN CERTIFICATE-----\nMIIC"));
+    assert!(split_pem_certificates("").is_empty());
+
+    let tls = TlsConfig {
+        ca_bundle_path: Some(String::from("/data/ca.pem")),
+        ..TlsConfig::default()
+    };
+    tls.validate().unwrap();
+
+    let tls = TlsConfig {
+        client_cert_path: Some(String::from("/data/client.pem")),
+        ..TlsConfig::default()
+    };
+    assert!(tls.validate().is_err());
+    assert!(set_default_tls(Some(tls)).is_err());
+    assert!(Client::default().tls.is_none());
+
+    // verifies with the system roots by default
+    let tls = TlsConfig::default();
+    assert!(!tls.insecure);
+    tls.https_connector().unwrap();
+
+    let tls = TlsConfig {
+        ca_bundle_path: Some(String::from("/tmp/not-found-ca.pem")),
+        ..TlsConfig::default()
+    };
+    assert!(tls.https_connector().is_err());
+}
+
+lazy_static! {
+    static ref DEFAULT_TLS: RwLock<Option<TlsConfig>> = RwLock::new(None);
+}
+
+/// Sets the TLS config of the clients created afterwards
+/// (e.g., "api_tls" in the spec), so that every API call verifies the endpoints.
+pub fn set_default_tls(tls: Option<TlsConfig>) -> io::Result<()> {
+    if let Some(t) = &tls {
+        t.validate()?;
+    }
+    *DEFAULT_TLS
+        .write()
+        .expect("failed to lock default TLS config") = tls;
+    Ok(())
+}
+
+fn default_tls() -> Option<TlsConfig> {
+    DEFAULT_TLS
+        .read()
+        .expect("failed to lock default TLS config")
+        .clone()
+}
+
+/// HTTP client shared by the API clients, which retries the transient failures
+/// (e.g., freshly bootstrapped nodes refusing the connections or returning 503).
+/// The "https" URLs are verified with "tls" if set, and otherwise
+/// with the system roots.
+#[derive(Debug, Clone)]
+pub struct Client {
+    pub policy: RetryPolicy,
+    pub tls: Option<TlsConfig>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::default()
+    }
+}
+
+/// Response of each attempt.
+struct Reply {
+    status: u16,
+    retry_after: Option<Duration>,
+    body: Vec<u8>,
+}
+
+impl Client {
+    /// Uses the TLS config from "set_default_tls".
+    pub fn default() -> Self {
+        Self::new(RetryPolicy::default())
+    }
+
+    /// Uses the TLS config from "set_default_tls".
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            tls: default_tls(),
+        }
+    }
+
+    /// Sends the HTTP GET request, which is always idempotent.
+    pub async fn get(&self, url: &str, path: &str) -> io::Result<Vec<u8>> {
+        self.send(url, path, None, Idempotency::Idempotent).await
+    }
+
+    /// Sends the HTTP POST request with the JSON body.
+    pub async fn post_json(
+        &self,
+        url: &str,
+        path: &str,
+        data: &str,
+        idempotency: Idempotency,
+    ) -> io::Result<Vec<u8>> {
+        self.send(url, path, Some(data), idempotency).await
+    }
+
+    /// Returns the body of the first non-retryable response,
+    /// regardless of its response code.
+    async fn send(
+        &self,
+        url: &str,
+        path: &str,
+        data: Option<&str>,
+        idempotency: Idempotency,
+    ) -> io::Result<Vec<u8>> {
+        // fail fast on the invalid URL, which is never worth retrying
+        let joined =
+            join_uri(url, path).map_err(|e| Error::new(ErrorKind::InvalidInput, e.to_string()))?;
+
+        // load the certificates once, rather than on every attempt
+        let https_connector = if url.starts_with("https") {
+            Some(self.tls.clone().unwrap_or_default().https_connector()?)
         } else {
-            let req = create_json_post(url, url_path, data)?;
-            let buf = match read_bytes(req, Duration::from_secs(5), url.starts_with("https"), false)
+            None
+        };
+
+        let mut attempt = 1;
+        loop {
+            let (retry_after, err) = match self
+                .attempt(url, path, data, https_connector.as_ref())
                 .await
             {
-                Ok(b) => b,
-                Err(e) => return Err(e),
+                Ok(reply) => {
+                    if !is_retryable_status(reply.status, idempotency) {
+                        return Ok(reply.body);
+                    }
+                    (
+                        reply.retry_after,
+                        Error::new(
+                            ErrorKind::Other,
+                            format!("retryable HTTP response code {}", reply.status),
+                        ),
+                    )
+                }
+                Err(e) => {
+                    if !is_retryable_error(e.kind(), idempotency) {
+                        return Err(e);
+                    }
+                    (None, e)
+                }
             };
-            buf.to_vec()
+            if attempt >= self.policy.max_attempts {
+                return Err(Error::new(
+                    err.kind(),
+                    format!("{:?} failed after {} attempt(s) ({})", joined, attempt, err),
+                ));
+            }
+
+            let jitter = random::u64() as f64 / u64::MAX as f64;
+            let wait = match retry_after {
+                Some(d) => d.min(self.policy.max_backoff),
+                None => self.policy.backoff(attempt, jitter),
+            };
+            warn!(
+                "retrying {:?} in {:?} (attempt {}/{}, {})",
+                joined, wait, attempt, self.policy.max_attempts, err
+            );
+            sleep(wait).await;
+            attempt += 1;
         }
-    };
-    Ok(output)
+    }
+
+    async fn attempt(
+        &self,
+        url: &str,
+        path: &str,
+        data: Option<&str>,
+        https_connector: Option<&HttpsConnector<HttpConnector>>,
+    ) -> io::Result<Reply> {
+        let req = match data {
+            Some(d) => create_json_post(url, path, d)?,
+            None => create_get(url, path)?,
+        };
+        let resp = match https_connector {
+            Some(connector) => {
+                let cli = HyperClient::builder().build::<_, Body>(connector.clone());
+                timeout(self.policy.request_timeout, cli.request(req))
+                    .await?
+                    .map_err(fetch_error)?
+            }
+            None => send_req(req, self.policy.request_timeout, false).await?,
+        };
+        let status = resp.status().as_u16();
+        let retry_after = resp
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+
+        // set timeouts for reads
+        // https://github.com/hyperium/hyper/issues/1097
+        let body = timeout(self.policy.request_timeout, hyper::body::to_bytes(resp))
+            .await?
+            .map_err(|e| Error::new(ErrorKind::Other, format!("failed to read response {}", e)))?;
+        Ok(Reply {
+            status,
+            retry_after,
+            body: body.to_vec(),
+        })
+    }
+}
+
+/// Sends the JSON POST request with the default "RetryPolicy".
+pub async fn post_json(
+    url: &str,
+    path: &str,
+    data: &str,
+    idempotency: Idempotency,
+) -> io::Result<Vec<u8>> {
+    Client::default()
+        .post_json(url, path, data, idempotency)
+        .await
+}
+
+/// RUST_LOG=debug cargo test --package utils --lib -- http::test_retry_policy --exact --show-output
+#[test]
+fn test_retry_policy() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let policy = RetryPolicy::default();
+    assert_eq!(policy.backoff(1, 1.0), Duration::from_millis(500));
+    assert_eq!(policy.backoff(1, 0.0), Duration::from_millis(250));
+    assert_eq!(policy.backoff(3, 1.0), Duration::from_secs(2));
+    assert_eq!(policy.backoff(10, 1.0), Duration::from_secs(10));
+    assert_eq!(policy.backoff(u32::MAX, 0.5), Duration::from_millis(7500));
+
+    assert!(is_retryable_status(503, Idempotency::NonIdempotent));
+    assert!(is_retryable_status(429, Idempotency::NonIdempotent));
+    assert!(is_retryable_status(502, Idempotency::Idempotent));
+    assert!(!is_retryable_status(502, Idempotency::NonIdempotent));
+    assert!(!is_retryable_status(200, Idempotency::Idempotent));
+    assert!(!is_retryable_status(404, Idempotency::Idempotent));
+
+    assert!(is_retryable_error(
+        ErrorKind::ConnectionRefused,
+        Idempotency::NonIdempotent
+    ));
+    assert!(is_retryable_error(
+        ErrorKind::TimedOut,
+        Idempotency::Idempotent
+    ));
+    assert!(!is_retryable_error(
+        ErrorKind::TimedOut,
+        Idempotency::NonIdempotent
+    ));
+    assert!(!is_retryable_error(
+        ErrorKind::InvalidInput,
+        Idempotency::Idempotent
+    ));
+
+    // nothing listens on the port, so every attempt is refused
+    let cli = Client::new(RetryPolicy {
+        max_attempts: 2,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(10),
+        request_timeout: Duration::from_secs(1),
+    });
+    let err = tokio_test::block_on(cli.post_json(
+        "http://localhost:12",
+        "ext/info",
+        "{}",
+        Idempotency::NonIdempotent,
+    ))
+    .expect_err("expected connection refused");
+    assert!(err.to_string().contains("2 attempt(s)"));
 }
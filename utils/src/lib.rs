@@ -1,14 +1,20 @@
+#[cfg(feature = "full")]
 pub mod bash;
 pub mod big_int;
 pub mod cmp;
+#[cfg(feature = "full")]
 pub mod compress;
 pub mod hash;
+#[cfg(feature = "full")]
 pub mod home_dir;
+#[cfg(feature = "full")]
 pub mod http;
 pub mod humanize;
 pub mod id;
 pub mod prefix;
 pub mod prometheus;
+#[cfg(feature = "full")]
+pub mod public_ip;
 pub mod random;
 pub mod rfc3339;
 pub mod secp256k1r;
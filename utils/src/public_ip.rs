@@ -0,0 +1,357 @@
+use std::{
+    io::{self, Error, ErrorKind},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    str::FromStr,
+    time::Duration,
+};
+
+use log::info;
+use tokio::{
+    net::{lookup_host, UdpSocket},
+    time::timeout,
+};
+
+use crate::{http, random};
+
+/// ref. https://datatracker.ietf.org/doc/html/rfc5389#section-6
+const STUN_MAGIC_COOKIE: u32 = 0x2112_A442;
+const STUN_BINDING_REQUEST: u16 = 0x0001;
+const STUN_BINDING_RESPONSE: u16 = 0x0101;
+const STUN_ATTR_MAPPED_ADDRESS: u16 = 0x0001;
+const STUN_ATTR_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+const STUN_HEADER_LEN: usize = 20;
+
+/// Returns true if the address is routable on the public internet,
+/// so that the other nodes can dial it (e.g., not "10.0.0.0/8" behind the NAT).
+pub fn is_public(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let o = v4.octets();
+            !(v4.is_unspecified()
+                || v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || v4.is_multicast()
+                // shared address space of the carrier-grade NAT
+                // ref. https://datatracker.ietf.org/doc/html/rfc6598
+                || (o[0] == 100 && (o[1] & 0xc0) == 64)
+                || o[0] == 0)
+        }
+        IpAddr::V6(v6) => {
+            let s = v6.segments();
+            !(v6.is_unspecified()
+                || v6.is_loopback()
+                || v6.is_multicast()
+                // unique local "fc00::/7"
+                || (s[0] & 0xfe00) == 0xfc00
+                // link local "fe80::/10"
+                || (s[0] & 0xffc0) == 0xfe80
+                // documentation "2001:db8::/32"
+                || (s[0] == 0x2001 && s[1] == 0x0db8))
+        }
+    }
+}
+
+/// Parses the IP address in the response body of the HTTPS echo service
+/// (e.g., "https://checkip.amazonaws.com" returns "1.2.3.4\n").
+pub fn parse_echo(body: &[u8]) -> io::Result<IpAddr> {
+    let s = String::from_utf8_lossy(body);
+    let s = s.trim();
+    IpAddr::from_str(s).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid echo response '{}' ({})", s, e),
+        )
+    })
+}
+
+/// Fetches the address of this host as seen by the HTTPS echo service.
+pub async fn fetch_echo(url: &str, timeout_dur: Duration) -> io::Result<IpAddr> {
+    info!("fetching public IP from '{}'", url);
+    let req = http::create_get(url, "")?;
+    let body = http::read_bytes(req, timeout_dur, url.starts_with("https"), true).await?;
+    parse_echo(&body)
+}
+
+/// Encodes the STUN binding request with no attribute.
+pub fn encode_stun_binding_request(transaction_id: &[u8; 12]) -> Vec<u8> {
+    let mut b = Vec::with_capacity(STUN_HEADER_LEN);
+    b.extend_from_slice(&STUN_BINDING_REQUEST.to_be_bytes());
+    b.extend_from_slice(&0_u16.to_be_bytes());
+    b.extend_from_slice(&STUN_MAGIC_COOKIE.to_be_bytes());
+    b.extend_from_slice(transaction_id);
+    b
+}
+
+/// Decodes the reflexive address from the STUN binding success response,
+/// preferring "XOR-MAPPED-ADDRESS" over the legacy "MAPPED-ADDRESS".
+/// ref. https://datatracker.ietf.org/doc/html/rfc5389#section-15.2
+pub fn decode_stun_binding_response(b: &[u8], transaction_id: &[u8; 12]) -> io::Result<IpAddr> {
+    if b.len() < STUN_HEADER_LEN {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("STUN response too short ({} bytes)", b.len()),
+        ));
+    }
+    let msg_type = u16::from_be_bytes([b[0], b[1]]);
+    if msg_type != STUN_BINDING_RESPONSE {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("unexpected STUN message type 0x{:04x}", msg_type),
+        ));
+    }
+    if b[4..8] != STUN_MAGIC_COOKIE.to_be_bytes() || &b[8..20] != transaction_id {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "STUN response does not match the request",
+        ));
+    }
+    let msg_len = u16::from_be_bytes([b[2], b[3]]) as usize;
+    let attrs = b
+        .get(STUN_HEADER_LEN..STUN_HEADER_LEN + msg_len)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("truncated STUN response (length {})", msg_len),
+            )
+        })?;
+
+    let mut mapped = None;
+    let mut pos = 0;
+    while pos + 4 <= attrs.len() {
+        let attr_type = u16::from_be_bytes([attrs[pos], attrs[pos + 1]]);
+        let attr_len = u16::from_be_bytes([attrs[pos + 2], attrs[pos + 3]]) as usize;
+        let value = attrs.get(pos + 4..pos + 4 + attr_len).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("truncated STUN attribute 0x{:04x}", attr_type),
+            )
+        })?;
+        match attr_type {
+            STUN_ATTR_XOR_MAPPED_ADDRESS => {
+                return decode_stun_address(value, Some(transaction_id));
+            }
+            STUN_ATTR_MAPPED_ADDRESS => mapped = Some(decode_stun_address(value, None)?),
+            _ => {}
+        }
+        // attributes are padded to the 4-byte boundary
+        pos += 4 + (attr_len + 3) / 4 * 4;
+    }
+    mapped.ok_or_else(|| Error::new(ErrorKind::NotFound, "no mapped address in STUN response"))
+}
+
+/// "transaction_id" is "Some" for "XOR-MAPPED-ADDRESS".
+fn decode_stun_address(value: &[u8], transaction_id: Option<&[u8; 12]>) -> io::Result<IpAddr> {
+    let family = value.get(1).copied().unwrap_or(0);
+    let addr = value.get(4..).unwrap_or(&[]);
+    let mut mask = STUN_MAGIC_COOKIE.to_be_bytes().to_vec();
+    if let Some(id) = transaction_id {
+        mask.extend_from_slice(id);
+    }
+    let unmask = |i: usize, v: u8| {
+        if transaction_id.is_some() {
+            v ^ mask[i]
+        } else {
+            v
+        }
+    };
+    match (family, addr.len()) {
+        (0x01, 4) => {
+            let mut o = [0_u8; 4];
+            for (i, v) in addr.iter().enumerate() {
+                o[i] = unmask(i, *v);
+            }
+            Ok(IpAddr::V4(Ipv4Addr::from(o)))
+        }
+        (0x02, 16) => {
+            let mut o = [0_u8; 16];
+            for (i, v) in addr.iter().enumerate() {
+                o[i] = unmask(i, *v);
+            }
+            Ok(IpAddr::V6(Ipv6Addr::from(o)))
+        }
+        _ => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "invalid STUN address (family 0x{:02x}, {} bytes)",
+                family,
+                addr.len()
+            ),
+        )),
+    }
+}
+
+/// Fetches the address of this host as seen by the STUN server ("host:port"),
+/// which is the address of the NAT gateway if the host is behind one.
+pub async fn fetch_stun(server: &str, timeout_dur: Duration) -> io::Result<IpAddr> {
+    info!("fetching public IP from STUN server '{}'", server);
+    let server_addr = lookup_host(server)
+        .await?
+        .find(|a| a.is_ipv4())
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("no IPv4 address for STUN server '{}'", server),
+            )
+        })?;
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(server_addr).await?;
+
+    let mut transaction_id = [0_u8; 12];
+    transaction_id[..8].copy_from_slice(&random::u64().to_be_bytes());
+    transaction_id[8..].copy_from_slice(&(random::u64() as u32).to_be_bytes());
+    socket
+        .send(&encode_stun_binding_request(&transaction_id))
+        .await?;
+
+    let mut buf = [0_u8; 512];
+    let n = timeout(timeout_dur, socket.recv(&mut buf)).await??;
+    decode_stun_binding_response(&buf[..n], &transaction_id)
+}
+
+/// Returns the source address of the default route (IPv4 first, then IPv6)
+/// if it is public, which is the case for the hosts with the public address
+/// on the network interface (e.g., bare-metal servers).
+/// No packet is sent, since connecting the UDP socket only selects the route.
+pub fn fetch_interface() -> io::Result<Option<IpAddr>> {
+    let probes = [
+        (
+            "0.0.0.0:0",
+            SocketAddr::from((Ipv4Addr::new(192, 0, 2, 1), 53)),
+        ),
+        (
+            "[::]:0",
+            SocketAddr::from((Ipv6Addr::new(0x2001, 0x0db8, 0, 0, 0, 0, 0, 1), 53)),
+        ),
+    ];
+    for (bind, target) in probes.iter() {
+        let socket = match std::net::UdpSocket::bind(bind) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        // no route for the address family
+        if socket.connect(target).is_err() {
+            continue;
+        }
+        let ip = socket.local_addr()?.ip();
+        if is_public(&ip) {
+            return Ok(Some(ip));
+        }
+        info!("skipping non-public interface address {}", ip);
+    }
+    Ok(None)
+}
+
+/// RUST_LOG=debug cargo test --package utils --lib -- public_ip::test_is_public --exact --show-output
+#[test]
+fn test_is_public() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    for s in ["1.2.3.4", "54.1.2.3", "2600:1f14::1"] {
+        assert!(is_public(&IpAddr::from_str(s).unwrap()), "{}", s);
+    }
+    for s in [
+        "10.0.1.2",
+        "172.16.0.1",
+        "192.168.1.1",
+        "127.0.0.1",
+        "169.254.169.254",
+        "100.64.0.1",
+        "100.127.255.255",
+        "0.0.0.0",
+        "192.0.2.1",
+        "::1",
+        "fd00::1",
+        "fe80::1",
+        "2001:db8::1",
+    ] {
+        assert!(!is_public(&IpAddr::from_str(s).unwrap()), "{}", s);
+    }
+    assert!(is_public(&IpAddr::from_str("100.128.0.1").unwrap()));
+}
+
+/// RUST_LOG=debug cargo test --package utils --lib -- public_ip::test_parse_echo --exact --show-output
+#[test]
+fn test_parse_echo() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    assert_eq!(
+        parse_echo(b"54.1.2.3\n").unwrap(),
+        IpAddr::from_str("54.1.2.3").unwrap()
+    );
+    assert_eq!(
+        parse_echo(b" 2600:1f14::1 ").unwrap(),
+        IpAddr::from_str("2600:1f14::1").unwrap()
+    );
+    assert!(parse_echo(b"<html>").is_err());
+    assert!(parse_echo(b"").is_err());
+}
+
+/// RUST_LOG=debug cargo test --package utils --lib -- public_ip::test_stun --exact --show-output
+#[test]
+fn test_stun() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let id = [1_u8, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+    let req = encode_stun_binding_request(&id);
+    assert_eq!(req.len(), STUN_HEADER_LEN);
+    assert_eq!(&req[..8], &[0x00, 0x01, 0x00, 0x00, 0x21, 0x12, 0xa4, 0x42]);
+    assert_eq!(&req[8..], &id);
+
+    let header = |len: u16| {
+        let mut b = vec![0x01, 0x01];
+        b.extend_from_slice(&len.to_be_bytes());
+        b.extend_from_slice(&STUN_MAGIC_COOKIE.to_be_bytes());
+        b.extend_from_slice(&id);
+        b
+    };
+
+    // "54.1.2.3:19302" in "XOR-MAPPED-ADDRESS", after an unknown padded attribute
+    let mut resp = header(20);
+    resp.extend_from_slice(&[0x80, 0x22, 0x00, 0x03, b'a', b'b', b'c', 0x00]);
+    resp.extend_from_slice(&[0x00, 0x20, 0x00, 0x08, 0x00, 0x01]);
+    resp.extend_from_slice(&(19302_u16 ^ 0x2112).to_be_bytes());
+    resp.extend_from_slice(&[54 ^ 0x21, 1 ^ 0x12, 2 ^ 0xa4, 3 ^ 0x42]);
+    assert_eq!(
+        decode_stun_binding_response(&resp, &id).unwrap(),
+        IpAddr::from_str("54.1.2.3").unwrap()
+    );
+
+    // IPv6 is masked with the magic cookie and the transaction ID
+    let ip = Ipv6Addr::from_str("2600:1f14::1").unwrap();
+    let mut mask = STUN_MAGIC_COOKIE.to_be_bytes().to_vec();
+    mask.extend_from_slice(&id);
+    let mut resp = header(24);
+    resp.extend_from_slice(&[0x00, 0x20, 0x00, 0x14, 0x00, 0x02, 0x00, 0x00]);
+    for (i, v) in ip.octets().iter().enumerate() {
+        resp.push(v ^ mask[i]);
+    }
+    assert_eq!(
+        decode_stun_binding_response(&resp, &id).unwrap(),
+        IpAddr::V6(ip)
+    );
+
+    // legacy "MAPPED-ADDRESS" is not masked
+    let mut resp = header(12);
+    resp.extend_from_slice(&[0x00, 0x01, 0x00, 0x08, 0x00, 0x01, 0x4b, 0x66, 54, 1, 2, 3]);
+    assert_eq!(
+        decode_stun_binding_response(&resp, &id).unwrap(),
+        IpAddr::from_str("54.1.2.3").unwrap()
+    );
+
+    // other transaction
+    let mut other = resp.clone();
+    other[19] ^= 0xff;
+    assert!(decode_stun_binding_response(&other, &id).is_err());
+
+    // truncated attribute
+    let mut truncated = header(8);
+    truncated.extend_from_slice(&[0x00, 0x20, 0x00, 0x08, 0x00, 0x01, 0x00, 0x00]);
+    assert!(decode_stun_binding_response(&truncated, &id).is_err());
+
+    // no address
+    assert!(decode_stun_binding_response(&header(0), &id).is_err());
+    assert!(decode_stun_binding_response(&resp[..10], &id).is_err());
+}
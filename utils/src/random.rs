@@ -13,6 +13,14 @@ pub fn string(n: usize) -> String {
     d
 }
 
+/// Generates a random u64.
+pub fn u64() -> u64 {
+    let bytes = rand_bytes(8).unwrap();
+    let mut d = [0u8; 8];
+    d.copy_from_slice(&bytes);
+    u64::from_le_bytes(d)
+}
+
 /// Generates a random string of length "n".
 fn rand_bytes(n: usize) -> Result<Vec<u8>, String> {
     let mut d: Vec<u8> = vec![0u8; n];
@@ -1,4 +1,10 @@
-use secp256k1::{self, Message, Secp256k1, SecretKey};
+use std::io::{self, Error, ErrorKind};
+
+use secp256k1::{
+    self,
+    ecdsa::{RecoverableSignature, RecoveryId, Signature},
+    Message, PublicKey, Secp256k1, SecretKey,
+};
 
 /// "github.com/decred/dcrd/dcrec/secp256k1/v3/ecdsa.SignCompact" outputs
 /// 65-byte signature
@@ -30,3 +36,160 @@ pub fn sign_ecdsa_recoverable(secret_key: &SecretKey, msg: &[u8]) -> Vec<u8> {
     assert_eq!(sig.len(), SIG_LEN);
     sig
 }
+
+/// Recovers the public key from the 65-byte signature of "sign_ecdsa_recoverable",
+/// to check which key signed the message without its public key.
+pub fn recover_public_key(msg: &[u8], sig: &[u8]) -> io::Result<PublicKey> {
+    if sig.len() != SIG_LEN {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("signature must be {}-byte, got {}", SIG_LEN, sig.len()),
+        ));
+    }
+    let m = Message::from_slice(msg)
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid message ({})", e)))?;
+    let rec_id = RecoveryId::from_i32(sig[64] as i32).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid recovery code ({})", e),
+        )
+    })?;
+    let sig = RecoverableSignature::from_compact(&sig[..64], rec_id).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid signature ({})", e),
+        )
+    })?;
+    Secp256k1::verification_only()
+        .recover_ecdsa(&m, &sig)
+        .map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("failed to recover public key ({})", e),
+            )
+        })
+}
+
+/// DER "SubjectPublicKeyInfo" header for the uncompressed secp256k1 public key
+/// (e.g., AWS KMS "GetPublicKey" with "ECC_SECG_P256K1").
+/// ref. https://datatracker.ietf.org/doc/html/rfc5480#section-2
+const SPKI_SECP256K1_HEADER: [u8; 23] = [
+    0x30, 0x56, // SEQUENCE (86 bytes)
+    0x30, 0x10, // SEQUENCE (16 bytes)
+    0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, // OID "ecPublicKey"
+    0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x0a, // OID "secp256k1"
+    0x03, 0x42, 0x00, // BIT STRING (66 bytes, no unused bits)
+];
+
+/// Parses the DER-encoded "SubjectPublicKeyInfo" of the secp256k1 public key.
+pub fn public_key_from_spki_der(der: &[u8]) -> io::Result<PublicKey> {
+    if der.len() != SPKI_SECP256K1_HEADER.len() + 65 || der[..23] != SPKI_SECP256K1_HEADER {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "not a DER-encoded secp256k1 SubjectPublicKeyInfo",
+        ));
+    }
+    PublicKey::from_slice(&der[23..]).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid secp256k1 public key ({})", e),
+        )
+    })
+}
+
+/// Converts the DER-encoded ECDSA signature of the 32-byte digest
+/// (e.g., from AWS KMS "Sign") to the 65-byte recoverable signature
+/// in the same format as "sign_ecdsa_recoverable".
+/// The "s" value is normalized to the lower half of the curve order,
+/// and the recovery ID is found by recovering the expected public key.
+pub fn recoverable_from_der(
+    der_sig: &[u8],
+    digest: &[u8],
+    public_key: &PublicKey,
+) -> io::Result<Vec<u8>> {
+    let mut sig = Signature::from_der(der_sig).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid DER signature ({})", e),
+        )
+    })?;
+    sig.normalize_s();
+    let compact = sig.serialize_compact();
+
+    let m = Message::from_slice(digest)
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid digest ({})", e)))?;
+    let secp = Secp256k1::verification_only();
+    for rec_id in 0..4 {
+        let rec_id = RecoveryId::from_i32(rec_id).expect("unexpected invalid recovery ID");
+        let recoverable = match RecoverableSignature::from_compact(&compact, rec_id) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        if let Ok(recovered) = secp.recover_ecdsa(&m, &recoverable) {
+            if recovered == *public_key {
+                let mut b = Vec::from(compact);
+                b.push(rec_id.to_i32() as u8);
+                return Ok(b);
+            }
+        }
+    }
+    Err(Error::new(
+        ErrorKind::InvalidData,
+        "signature does not recover the public key",
+    ))
+}
+
+/// RUST_LOG=debug cargo test --package utils --lib -- secp256k1r::test_recoverable_from_der --exact --show-output
+#[test]
+fn test_recoverable_from_der() {
+    use secp256k1::rand::rngs::OsRng;
+
+    let secp = Secp256k1::new();
+    let mut rng = OsRng::new().unwrap();
+    let (secret_key, public_key) = secp.generate_keypair(&mut rng);
+
+    let mut spki = SPKI_SECP256K1_HEADER.to_vec();
+    spki.extend_from_slice(&public_key.serialize_uncompressed());
+    assert_eq!(public_key_from_spki_der(&spki).unwrap(), public_key);
+    assert!(public_key_from_spki_der(&spki[1..]).is_err());
+
+    let digest = [7u8; 32];
+    let expected = sign_ecdsa_recoverable(&secret_key, &digest);
+    assert_eq!(recover_public_key(&digest, &expected).unwrap(), public_key);
+    assert!(recover_public_key(&digest, &expected[..64]).is_err());
+
+    // both use RFC6979 nonces, so must match the recoverable signature
+    let m = Message::from_slice(&digest).unwrap();
+    let sig = secp.sign_ecdsa(&m, &secret_key);
+    let der = sig.serialize_der();
+    assert_eq!(
+        recoverable_from_der(&der, &digest, &public_key).unwrap(),
+        expected
+    );
+
+    // high "s" (n - s) must be normalized
+    // ref. https://en.bitcoin.it/wiki/BIP_0062#Low_S_values_in_signatures
+    let n: [u8; 32] = [
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xfe, 0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36,
+        0x41, 0x41,
+    ];
+    let mut compact = sig.serialize_compact();
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let v = n[i] as i16 - compact[32 + i] as i16 - borrow;
+        borrow = if v < 0 { 1 } else { 0 };
+        compact[32 + i] = (v + 256 * borrow) as u8;
+    }
+    let high_s = Signature::from_compact(&compact).unwrap();
+    assert_ne!(high_s, sig);
+    let der = high_s.serialize_der();
+    assert_eq!(
+        recoverable_from_der(&der, &digest, &public_key).unwrap(),
+        expected
+    );
+
+    // wrong public key
+    let (_, other) = secp.generate_keypair(&mut rng);
+    assert!(recoverable_from_der(&der, &digest, &other).is_err());
+}